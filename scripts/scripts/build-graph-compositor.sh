@@ -8,6 +8,7 @@ PROJECT_ROOT="$(cd "$SCRIPT_DIR/../.." && pwd)"
 DESKTOP_DIR="$PROJECT_ROOT/src/desktop"
 BUILD_DIR="$PROJECT_ROOT/build/graph-compositor"
 TARGET_DIR="$PROJECT_ROOT/scripts/archiso/airootfs/usr/share/horizonos/desktop/graph"
+ASSET_PACK="$PROJECT_ROOT/scripts/archiso/airootfs/usr/share/horizonos/assets.hzp"
 
 echo "=== Building HorizonOS Graph Desktop Compositor ==="
 
@@ -43,6 +44,11 @@ if [ "$SKIP_RUST_BUILD" = "false" ] && [ -f "Cargo.toml" ]; then
         cp "target/release/horizonos-compositor" "$TARGET_DIR/"
         chmod +x "$TARGET_DIR/horizonos-compositor"
         echo "Graph compositor built and copied successfully!"
+        
+        # Built-in shaders and themes are read from one memory-mapped pack
+        echo "Building asset pack..."
+        cargo run --release --package horizonos-graph-engine --bin horizonos-asset-pack -- \
+            "$ASSET_PACK" --themes "$DESKTOP_DIR/config/themes"
     else
         echo "Warning: Compositor binary not found. Creating placeholder..."
         # Create a placeholder script that shows a message
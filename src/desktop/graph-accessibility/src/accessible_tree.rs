@@ -0,0 +1,488 @@
+//! Hierarchical accessible tree of the desktop
+//!
+//! Screen readers navigate the desktop structurally through this tree:
+//! the application at the root holds one panel per workspace, each
+//! workspace holds its clusters and the nodes outside any cluster, each
+//! cluster its nodes, and each node its actions. A node appears once, under
+//! the first workspace and cluster (by name) that contain it; nodes in no
+//! workspace sit directly under the root. The root also holds a live region
+//! that recent notifications are posted to, so screen readers announce them
+//! as they arrive.
+
+use crate::at_spi::{
+    convert_to_at_spi_role, convert_to_at_spi_state_set, determine_interfaces, AnnouncementPoliteness,
+    AtSpiAnnouncement, AtSpiInterfaceType, AtSpiRole, AtSpiState, AtSpiStateSet,
+};
+use crate::{AccessibleAction, NodeAccessibilityInfo};
+use horizonos_graph_clustering::{Cluster, ClusterId};
+use horizonos_graph_engine::SceneId;
+use horizonos_graph_workspaces::Workspace;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Object path all accessibles of the desktop live under
+const PATH_PREFIX: &str = "/org/a11y/horizonos/accessible";
+
+/// Notifications kept in the live region
+pub const LIVE_REGION_CAPACITY: usize = 5;
+
+/// What an accessible in the tree stands for
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AccessibleKey {
+    /// The desktop application
+    Root,
+    /// A workspace, by ID
+    Workspace(String),
+    /// A cluster within a workspace
+    Cluster { workspace: String, cluster: ClusterId },
+    /// A graph node
+    Node(SceneId),
+    /// An action of a node, by its index in the node's actions
+    Action(SceneId, usize),
+    /// The live region notifications are posted to
+    Notifications,
+    /// A notification in the live region, by posting order
+    Notification(u64),
+}
+
+/// An accessible in the tree
+#[derive(Debug, Clone)]
+pub struct AccessibleTreeItem {
+    /// D-Bus object path
+    pub path: String,
+    /// Accessible name
+    pub name: String,
+    /// Accessible description
+    pub description: Option<String>,
+    /// AT-SPI role
+    pub role: AtSpiRole,
+    /// AT-SPI states
+    pub states: AtSpiStateSet,
+    /// Interfaces the object implements
+    pub interfaces: Vec<AtSpiInterfaceType>,
+    /// Object attributes, e.g. `container-live` for live regions
+    pub attributes: HashMap<String, String>,
+    /// Parent, `None` for the root
+    pub parent: Option<AccessibleKey>,
+    /// Children in navigation order
+    pub children: Vec<AccessibleKey>,
+}
+
+impl AccessibleTreeItem {
+    fn new(path: String, name: String, role: AtSpiRole, parent: Option<AccessibleKey>) -> Self {
+        Self {
+            path,
+            name,
+            description: None,
+            role,
+            states: AtSpiStateSet::default(),
+            interfaces: vec![AtSpiInterfaceType::Accessible],
+            attributes: HashMap::new(),
+            parent,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// Accessible tree of workspaces, clusters, nodes and their actions
+#[derive(Debug, Clone)]
+pub struct AccessibleTree {
+    items: HashMap<AccessibleKey, AccessibleTreeItem>,
+    /// Notifications in the live region, oldest first
+    notifications: VecDeque<(u64, AtSpiAnnouncement)>,
+    /// Posting order of the next notification
+    next_notification: u64,
+}
+
+impl Default for AccessibleTree {
+    fn default() -> Self {
+        Self::build("", &[], None, &[], &HashMap::new())
+    }
+}
+
+impl AccessibleTree {
+    /// Build the tree for the given workspaces and clusters
+    ///
+    /// Only nodes with accessibility information are included.
+    pub fn build(
+        app_name: &str,
+        workspaces: &[Workspace],
+        active_workspace: Option<&str>,
+        clusters: &[Cluster],
+        nodes: &HashMap<SceneId, NodeAccessibilityInfo>,
+    ) -> Self {
+        let mut tree = Self {
+            items: HashMap::new(),
+            notifications: VecDeque::new(),
+            next_notification: 0,
+        };
+        let mut root = AccessibleTreeItem::new(format!("{}/root", PATH_PREFIX), app_name.to_string(), AtSpiRole::Application, None);
+        root.states.add(AtSpiState::Enabled);
+        root.states.add(AtSpiState::Showing);
+        tree.items.insert(AccessibleKey::Root, root);
+
+        let mut clusters: Vec<&Cluster> = clusters.iter().collect();
+        clusters.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+        let mut placed: HashSet<SceneId> = HashSet::new();
+
+        for workspace in workspaces {
+            let key = AccessibleKey::Workspace(workspace.id.clone());
+            let mut item = AccessibleTreeItem::new(
+                format!("{}/workspace/{}", PATH_PREFIX, path_element(&workspace.id)),
+                workspace.name.clone(),
+                AtSpiRole::Panel,
+                Some(AccessibleKey::Root),
+            );
+            item.description = Some(workspace.description.clone()).filter(|description| !description.is_empty());
+            item.states.add(AtSpiState::Enabled);
+            item.states.add(AtSpiState::Selectable);
+            if active_workspace == Some(workspace.id.as_str()) {
+                item.states.add(AtSpiState::Active);
+                item.states.add(AtSpiState::Selected);
+                item.states.add(AtSpiState::Showing);
+            }
+            tree.add_child(&AccessibleKey::Root, key.clone(), item);
+
+            let members: Vec<SceneId> = workspace
+                .nodes
+                .iter()
+                .copied()
+                .filter(|id| nodes.contains_key(id) && !placed.contains(id))
+                .collect();
+            for cluster in &clusters {
+                let cluster_members: Vec<SceneId> = members
+                    .iter()
+                    .copied()
+                    .filter(|id| cluster.nodes.contains(id) && !placed.contains(id))
+                    .collect();
+                if cluster_members.is_empty() {
+                    continue;
+                }
+                let cluster_key = AccessibleKey::Cluster { workspace: workspace.id.clone(), cluster: cluster.id };
+                let mut item = AccessibleTreeItem::new(
+                    format!("{}/workspace/{}/cluster/{}", PATH_PREFIX, path_element(&workspace.id), cluster.id.simple()),
+                    cluster.name.clone(),
+                    AtSpiRole::List,
+                    Some(key.clone()),
+                );
+                item.description = cluster.description.clone();
+                item.interfaces.push(AtSpiInterfaceType::Selection);
+                item.states.add(AtSpiState::Enabled);
+                item.states.add(AtSpiState::Expandable);
+                item.states.add(if cluster.expanded { AtSpiState::Expanded } else { AtSpiState::Collapsed });
+                tree.add_child(&key, cluster_key.clone(), item);
+                for node_id in cluster_members {
+                    tree.add_node(&cluster_key, &nodes[&node_id]);
+                    placed.insert(node_id);
+                }
+            }
+            for node_id in members {
+                if placed.insert(node_id) {
+                    tree.add_node(&key, &nodes[&node_id]);
+                }
+            }
+        }
+
+        let mut unplaced: Vec<SceneId> = nodes.keys().copied().filter(|id| !placed.contains(id)).collect();
+        unplaced.sort_unstable();
+        for node_id in unplaced {
+            tree.add_node(&AccessibleKey::Root, &nodes[&node_id]);
+        }
+
+        let mut live_region = AccessibleTreeItem::new(
+            format!("{}/notifications", PATH_PREFIX),
+            "Notifications".to_string(),
+            AtSpiRole::Panel,
+            Some(AccessibleKey::Root),
+        );
+        live_region.attributes.insert("container-live".to_string(), "polite".to_string());
+        live_region.attributes.insert("container-relevant".to_string(), "additions".to_string());
+        live_region.states.add(AtSpiState::Enabled);
+        tree.add_child(&AccessibleKey::Root, AccessibleKey::Notifications, live_region);
+        tree
+    }
+
+    /// Carry the live region of an older tree over, e.g. after rebuilding it
+    pub fn keep_notifications(&mut self, previous: &AccessibleTree) {
+        self.next_notification = previous.next_notification;
+        for (id, announcement) in &previous.notifications {
+            self.insert_notification(*id, announcement.clone());
+        }
+    }
+
+    /// Post a notification to the live region, dropping the oldest beyond its capacity
+    ///
+    /// Returns the key of the new notification.
+    pub fn post_notification(&mut self, announcement: AtSpiAnnouncement) -> AccessibleKey {
+        let id = self.next_notification;
+        self.next_notification += 1;
+        while self.notifications.len() >= LIVE_REGION_CAPACITY {
+            let Some((oldest, _)) = self.notifications.pop_front() else {
+                break;
+            };
+            self.remove(&AccessibleKey::Notification(oldest));
+        }
+        self.insert_notification(id, announcement);
+        AccessibleKey::Notification(id)
+    }
+
+    fn insert_notification(&mut self, id: u64, announcement: AtSpiAnnouncement) {
+        let key = AccessibleKey::Notification(id);
+        let mut item = AccessibleTreeItem::new(
+            format!("{}/notifications/{}", PATH_PREFIX, id),
+            announcement.text.clone(),
+            AtSpiRole::Label,
+            Some(AccessibleKey::Notifications),
+        );
+        item.interfaces.push(AtSpiInterfaceType::Text);
+        item.states.add(AtSpiState::Enabled);
+        item.states.add(AtSpiState::Showing);
+        if announcement.politeness == AnnouncementPoliteness::Assertive {
+            item.attributes.insert("live".to_string(), "assertive".to_string());
+        }
+        self.add_child(&AccessibleKey::Notifications, key, item);
+        self.notifications.push_back((id, announcement));
+    }
+
+    /// Add a node and its actions below a parent
+    fn add_node(&mut self, parent: &AccessibleKey, info: &NodeAccessibilityInfo) {
+        let key = AccessibleKey::Node(info.node_id);
+        let path = format!("{}/{}", PATH_PREFIX, info.node_id);
+        let mut item = AccessibleTreeItem::new(path.clone(), info.name.clone(), convert_to_at_spi_role(&info.role), Some(parent.clone()));
+        item.description = info.description.clone();
+        item.states = convert_to_at_spi_state_set(&info.state);
+        item.interfaces = determine_interfaces(&info.role);
+        if !info.actions.is_empty() && !item.interfaces.iter().any(|i| matches!(i, AtSpiInterfaceType::Action)) {
+            item.interfaces.push(AtSpiInterfaceType::Action);
+        }
+        self.add_child(parent, key.clone(), item);
+
+        for (index, action) in info.actions.iter().enumerate() {
+            let mut item = AccessibleTreeItem::new(
+                format!("{}/action/{}", path, index),
+                action_name(action),
+                AtSpiRole::PushButton,
+                Some(key.clone()),
+            );
+            item.interfaces.push(AtSpiInterfaceType::Action);
+            item.states.add(AtSpiState::Enabled);
+            item.states.add(AtSpiState::Focusable);
+            self.add_child(&key, AccessibleKey::Action(info.node_id, index), item);
+        }
+    }
+
+    fn add_child(&mut self, parent: &AccessibleKey, key: AccessibleKey, item: AccessibleTreeItem) {
+        if let Some(parent) = self.items.get_mut(parent) {
+            parent.children.push(key.clone());
+        }
+        self.items.insert(key, item);
+    }
+
+    /// Remove an item and its descendants
+    fn remove(&mut self, key: &AccessibleKey) {
+        let Some(item) = self.items.remove(key) else {
+            return;
+        };
+        if let Some(parent) = item.parent.as_ref().and_then(|parent| self.items.get_mut(parent)) {
+            parent.children.retain(|child| child != key);
+        }
+        for child in &item.children {
+            self.remove(child);
+        }
+    }
+
+    /// Item of a key
+    pub fn get(&self, key: &AccessibleKey) -> Option<&AccessibleTreeItem> {
+        self.items.get(key)
+    }
+
+    /// Root item
+    pub fn root(&self) -> &AccessibleTreeItem {
+        &self.items[&AccessibleKey::Root]
+    }
+
+    /// Children of an item in navigation order
+    pub fn children(&self, key: &AccessibleKey) -> &[AccessibleKey] {
+        self.items.get(key).map(|item| item.children.as_slice()).unwrap_or_default()
+    }
+
+    /// Index of an item among its parent's children
+    pub fn index_in_parent(&self, key: &AccessibleKey) -> Option<usize> {
+        let parent = self.items.get(key)?.parent.as_ref()?;
+        self.children(parent).iter().position(|child| child == key)
+    }
+
+    /// Item with a D-Bus object path
+    pub fn find_by_path(&self, path: &str) -> Option<(&AccessibleKey, &AccessibleTreeItem)> {
+        self.items.iter().find(|(_, item)| item.path == path)
+    }
+
+    /// Number of accessibles in the tree
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether the tree holds only the root
+    pub fn is_empty(&self) -> bool {
+        self.items.len() <= 1
+    }
+
+    /// Items whose children differ from those in another tree, including items it lacks
+    pub fn changed_parents<'a>(&'a self, previous: &AccessibleTree) -> Vec<&'a AccessibleKey> {
+        self.items
+            .iter()
+            .filter(|(key, item)| previous.items.get(*key).is_none_or(|old| old.children != item.children))
+            .map(|(key, _)| key)
+            .collect()
+    }
+}
+
+/// Spoken name of a node action
+pub fn action_name(action: &AccessibleAction) -> String {
+    match action {
+        AccessibleAction::Activate => "Activate".to_string(),
+        AccessibleAction::Focus => "Focus".to_string(),
+        AccessibleAction::Select => "Select".to_string(),
+        AccessibleAction::Toggle => "Expand or collapse".to_string(),
+        AccessibleAction::ShowContextMenu => "Show context menu".to_string(),
+        AccessibleAction::NavigateTo => "Navigate to".to_string(),
+        AccessibleAction::Open => "Open".to_string(),
+        AccessibleAction::Close => "Close".to_string(),
+        AccessibleAction::Custom(name) => name.clone(),
+    }
+}
+
+/// D-Bus object path element for an arbitrary ID
+fn path_element(id: &str) -> String {
+    id.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AccessibleBounds, AccessibleRole, AccessibleState};
+
+    fn node(node_id: SceneId, name: &str, role: AccessibleRole) -> NodeAccessibilityInfo {
+        NodeAccessibilityInfo {
+            node_id,
+            name: name.to_string(),
+            description: None,
+            role,
+            state: AccessibleState { enabled: true, visible: true, ..Default::default() },
+            actions: vec![AccessibleAction::Activate, AccessibleAction::ShowContextMenu],
+            relationships: Vec::new(),
+            bounds: AccessibleBounds { x: 0.0, y: 0.0, width: 10.0, height: 10.0 },
+            text_content: None,
+            value: None,
+        }
+    }
+
+    fn nodes() -> HashMap<SceneId, NodeAccessibilityInfo> {
+        [
+            node(1, "Firefox", AccessibleRole::Application),
+            node(2, "Notes.md", AccessibleRole::Document),
+            node(3, "Docs", AccessibleRole::Link),
+            node(4, "Loose", AccessibleRole::GenericObject),
+        ]
+        .into_iter()
+        .map(|info| (info.node_id, info))
+        .collect()
+    }
+
+    fn workspace(id: &str, name: &str, nodes: &[SceneId]) -> Workspace {
+        let mut workspace = Workspace::new(name, "");
+        workspace.id = id.to_string();
+        for node_id in nodes {
+            workspace.add_node(*node_id);
+        }
+        workspace
+    }
+
+    #[test]
+    fn test_tree_shape() {
+        let workspaces = [workspace("work", "Work", &[3, 1, 2]), workspace("home", "Home", &[2])];
+        let cluster = Cluster::new_manual("Code".to_string(), vec![2, 1]);
+        let tree = AccessibleTree::build("Desktop", &workspaces, Some("work"), std::slice::from_ref(&cluster), &nodes());
+
+        let work = AccessibleKey::Workspace("work".to_string());
+        let home = AccessibleKey::Workspace("home".to_string());
+        let code = AccessibleKey::Cluster { workspace: "work".to_string(), cluster: cluster.id };
+        assert_eq!(
+            tree.children(&AccessibleKey::Root),
+            [work.clone(), home.clone(), AccessibleKey::Node(4), AccessibleKey::Notifications]
+        );
+        // Clustered nodes come first, in workspace order; a node appears only once
+        assert_eq!(tree.children(&work), [code.clone(), AccessibleKey::Node(3)]);
+        assert_eq!(tree.children(&code), [AccessibleKey::Node(1), AccessibleKey::Node(2)]);
+        assert!(tree.children(&home).is_empty());
+        assert_eq!(tree.children(&AccessibleKey::Node(1)), [AccessibleKey::Action(1, 0), AccessibleKey::Action(1, 1)]);
+        assert_eq!(tree.len(), 17);
+
+        // Parents, indices and paths agree with the children
+        assert_eq!(tree.get(&AccessibleKey::Node(2)).unwrap().parent, Some(code.clone()));
+        assert_eq!(tree.index_in_parent(&AccessibleKey::Node(3)), Some(1));
+        let path = &tree.get(&code).unwrap().path;
+        assert_eq!(path, &format!("{}/workspace/work/cluster/{}", PATH_PREFIX, cluster.id.simple()));
+        assert_eq!(tree.find_by_path(path).map(|(key, _)| key), Some(&code));
+        assert_eq!(tree.root().name, "Desktop");
+    }
+
+    #[test]
+    fn test_roles_and_states() {
+        let workspaces = [workspace("work", "Work", &[1, 2, 3]), workspace("home", "Home", &[])];
+        let mut cluster = Cluster::new_manual("Code".to_string(), vec![1]);
+        cluster.expanded = false;
+        let tree = AccessibleTree::build("Desktop", &workspaces, Some("work"), &[cluster.clone()], &nodes());
+        let item = |key: AccessibleKey| tree.get(&key).unwrap().clone();
+
+        assert!(matches!(tree.root().role, AtSpiRole::Application));
+
+        let work = item(AccessibleKey::Workspace("work".to_string()));
+        assert!(matches!(work.role, AtSpiRole::Panel));
+        assert!(work.states.contains(&AtSpiState::Active) && work.states.contains(&AtSpiState::Showing));
+        let home = item(AccessibleKey::Workspace("home".to_string()));
+        assert!(home.states.contains(&AtSpiState::Selectable) && !home.states.contains(&AtSpiState::Active));
+
+        let code = item(AccessibleKey::Cluster { workspace: "work".to_string(), cluster: cluster.id });
+        assert!(matches!(code.role, AtSpiRole::List));
+        assert!(code.states.contains(&AtSpiState::Collapsed) && !code.states.contains(&AtSpiState::Expanded));
+        assert!(code.interfaces.iter().any(|i| matches!(i, AtSpiInterfaceType::Selection)));
+
+        assert!(matches!(item(AccessibleKey::Node(1)).role, AtSpiRole::Application));
+        assert!(matches!(item(AccessibleKey::Node(2)).role, AtSpiRole::DocumentFrame));
+        assert!(matches!(item(AccessibleKey::Node(3)).role, AtSpiRole::Link));
+        assert!(matches!(item(AccessibleKey::Node(4)).role, AtSpiRole::Unknown));
+
+        let action = item(AccessibleKey::Action(1, 1));
+        assert!(matches!(action.role, AtSpiRole::PushButton));
+        assert_eq!(action.name, "Show context menu");
+        assert!(item(AccessibleKey::Node(1)).interfaces.iter().any(|i| matches!(i, AtSpiInterfaceType::Action)));
+
+        let live_region = item(AccessibleKey::Notifications);
+        assert!(matches!(live_region.role, AtSpiRole::Panel));
+        assert_eq!(live_region.attributes.get("container-live").map(String::as_str), Some("polite"));
+    }
+
+    #[test]
+    fn test_live_region_survives_rebuilds() {
+        let mut tree = AccessibleTree::build("Desktop", &[], None, &[], &nodes());
+        for n in 0..LIVE_REGION_CAPACITY + 2 {
+            tree.post_notification(AtSpiAnnouncement {
+                text: format!("Notification {}", n),
+                braille: Vec::new(),
+                politeness: AnnouncementPoliteness::Polite,
+            });
+        }
+        let posted = tree.children(&AccessibleKey::Notifications).to_vec();
+        assert_eq!(posted.len(), LIVE_REGION_CAPACITY);
+        assert_eq!(posted[0], AccessibleKey::Notification(2));
+        assert!(tree.get(&AccessibleKey::Notification(0)).is_none());
+
+        let mut rebuilt = AccessibleTree::build("Desktop", &[workspace("work", "Work", &[1])], None, &[], &nodes());
+        rebuilt.keep_notifications(&tree);
+        assert_eq!(rebuilt.children(&AccessibleKey::Notifications), posted.as_slice());
+        let changed = rebuilt.changed_parents(&tree);
+        assert!(changed.contains(&&AccessibleKey::Root));
+        assert!(changed.contains(&&AccessibleKey::Workspace("work".to_string())));
+        assert!(!changed.contains(&&AccessibleKey::Notifications));
+    }
+}
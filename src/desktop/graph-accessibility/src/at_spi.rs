@@ -1,9 +1,20 @@
 //! AT-SPI (Assistive Technology Service Provider Interface) integration
 
+use crate::accessible_tree::{AccessibleKey, AccessibleTree};
 use crate::{NodeAccessibilityInfo, AccessibilityEvent, AccessibleRole, AccessibleState};
 use anyhow::Result;
 use horizonos_graph_engine::SceneId;
 use std::collections::HashMap;
+use zbus::names::BusName;
+use zbus::zvariant::{ObjectPath, Structure, Value};
+
+/// D-Bus interfaces AT-SPI events are signalled on
+const OBJECT_EVENTS: &str = "org.a11y.atspi.Event.Object";
+const WINDOW_EVENTS: &str = "org.a11y.atspi.Event.Window";
+const FOCUS_EVENTS: &str = "org.a11y.atspi.Event.Focus";
+const DOCUMENT_EVENTS: &str = "org.a11y.atspi.Event.Document";
+const MOUSE_EVENTS: &str = "org.a11y.atspi.Event.Mouse";
+const KEYBOARD_EVENTS: &str = "org.a11y.atspi.Event.Keyboard";
 
 /// AT-SPI interface for system accessibility integration
 #[derive(Debug)]
@@ -16,6 +27,12 @@ pub struct AtSpiInterface {
     app_context: AtSpiApplicationContext,
     /// Event listeners
     event_listeners: Vec<AtSpiEventListener>,
+    /// Last announcement made, for braille displays to show
+    last_announcement: Option<AtSpiAnnouncement>,
+    /// Exported tree of workspaces, clusters, nodes and actions
+    tree: AccessibleTree,
+    /// Events not yet sent on the accessibility bus
+    outbox: Vec<AtSpiSignal>,
     /// Enabled state
     enabled: bool,
 }
@@ -396,6 +413,63 @@ pub enum AtSpiState {
     LastDefined,
 }
 
+/// An AT-SPI event, ready to be sent on the accessibility bus
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtSpiSignal {
+    /// Object path of the accessible the event is about
+    pub path: String,
+    /// D-Bus interface, e.g. `org.a11y.atspi.Event.Object`
+    pub interface: &'static str,
+    /// Signal name, e.g. `StateChanged`
+    pub member: String,
+    /// Event detail, e.g. the state that changed or `add`
+    pub detail: String,
+    pub detail1: i32,
+    pub detail2: i32,
+    /// Data the event carries
+    pub data: AtSpiSignalData,
+}
+
+/// Data carried by an AT-SPI event
+#[derive(Debug, Clone, PartialEq)]
+pub enum AtSpiSignalData {
+    None,
+    /// Announced, inserted or deleted text
+    Text(String),
+    /// Object path of an added or removed child
+    Object(String),
+}
+
+/// Text announced without an accessible object, e.g. a notification read in review mode
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtSpiAnnouncement {
+    /// Spoken text
+    pub text: String,
+    /// Lines for braille displays, already fitted to the display width
+    pub braille: Vec<String>,
+    /// How urgently screen readers present it
+    pub politeness: AnnouncementPoliteness,
+}
+
+/// Live region politeness of an announcement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnouncementPoliteness {
+    /// Read after current speech finishes
+    Polite,
+    /// Interrupts current speech
+    Assertive,
+}
+
+impl AnnouncementPoliteness {
+    /// `ATSPI_LIVE_POLITE` or `ATSPI_LIVE_ASSERTIVE`
+    pub fn at_spi_level(self) -> i32 {
+        match self {
+            AnnouncementPoliteness::Polite => 1,
+            AnnouncementPoliteness::Assertive => 2,
+        }
+    }
+}
+
 /// AT-SPI application context
 #[derive(Debug)]
 pub struct AtSpiApplicationContext {
@@ -486,6 +560,8 @@ pub enum ObjectEventType {
     TextCaretMoved,
     /// Attributes changed
     AttributesChanged,
+    /// Announcement
+    Announcement,
 }
 
 /// Window event types
@@ -581,6 +657,21 @@ pub enum KeyboardEventType {
     Release,
 }
 
+impl AtSpiEventType {
+    /// D-Bus interface and signal name of the event
+    pub fn signal(&self) -> (&'static str, String) {
+        match self {
+            AtSpiEventType::Object(event) => (OBJECT_EVENTS, format!("{:?}", event)),
+            AtSpiEventType::Window(WindowEventType::Unshade) => (WINDOW_EVENTS, "uUshade".to_string()),
+            AtSpiEventType::Window(event) => (WINDOW_EVENTS, format!("{:?}", event)),
+            AtSpiEventType::Focus(_) => (FOCUS_EVENTS, "Focus".to_string()),
+            AtSpiEventType::Document(event) => (DOCUMENT_EVENTS, format!("{:?}", event)),
+            AtSpiEventType::Mouse(_) => (MOUSE_EVENTS, "Button".to_string()),
+            AtSpiEventType::Keyboard(_) => (KEYBOARD_EVENTS, "Modifiers".to_string()),
+        }
+    }
+}
+
 impl AtSpiInterface {
     /// Create a new AT-SPI interface
     pub fn new() -> Result<Self> {
@@ -592,6 +683,9 @@ impl AtSpiInterface {
             objects: HashMap::new(),
             app_context,
             event_listeners: Vec::new(),
+            last_announcement: None,
+            tree: AccessibleTree::default(),
+            outbox: Vec::new(),
             enabled: false,
         })
     }
@@ -608,6 +702,7 @@ impl AtSpiInterface {
     /// Disable AT-SPI interface
     pub fn disable(&mut self) -> Result<()> {
         self.enabled = false;
+        self.outbox.clear();
         self.connection.disconnect()?;
         log::info!("AT-SPI interface disabled");
         Ok(())
@@ -620,10 +715,12 @@ impl AtSpiInterface {
         }
 
         let at_spi_object = self.create_at_spi_object(info)?;
-        self.objects.insert(info.node_id, at_spi_object);
+        let previous = self.objects.insert(info.node_id, at_spi_object);
 
         // Notify AT-SPI of object change
-        self.notify_object_changed(info.node_id)?;
+        if let Some(previous) = previous {
+            self.notify_object_changed(&previous, info)?;
+        }
 
         Ok(())
     }
@@ -634,7 +731,7 @@ impl AtSpiInterface {
             return Ok(());
         }
 
-        if let Some(object) = self.objects.remove(&node_id) {
+        if self.objects.remove(&node_id).is_some() {
             // Notify AT-SPI of object removal
             self.notify_object_removed(node_id)?;
         }
@@ -648,12 +745,153 @@ impl AtSpiInterface {
             return Ok(());
         }
 
-        let at_spi_event = self.convert_to_at_spi_event(event)?;
-        self.emit_at_spi_event(&at_spi_event)?;
+        let object = AtSpiEventType::Object;
+        match event {
+            AccessibilityEvent::FocusChanged { old_focus, new_focus } => {
+                if let Some(old_focus) = old_focus {
+                    let path = self.node_path(*old_focus);
+                    self.emit(path, object(ObjectEventType::StateChanged), "focused", 0, 0, AtSpiSignalData::None);
+                }
+                if let Some(new_focus) = new_focus {
+                    let path = self.node_path(*new_focus);
+                    self.emit(path.clone(), object(ObjectEventType::StateChanged), "focused", 1, 0, AtSpiSignalData::None);
+                    self.emit(path, AtSpiEventType::Focus(FocusEventType::In), "", 0, 0, AtSpiSignalData::None);
+                }
+            }
+            AccessibilityEvent::SelectionChanged { .. } => {
+                let path = self.tree.root().path.clone();
+                self.emit(path, object(ObjectEventType::SelectionChanged), "", 0, 0, AtSpiSignalData::None);
+            }
+            AccessibilityEvent::StateChanged { node_id, old_state, new_state } => {
+                let path = self.node_path(*node_id);
+                for (state, value) in changed_states(old_state, new_state) {
+                    self.emit(path.clone(), object(ObjectEventType::StateChanged), state, value as i32, 0, AtSpiSignalData::None);
+                }
+            }
+            AccessibilityEvent::StructureChanged { parent, added, removed } => {
+                let parent_path = match parent {
+                    Some(parent) => self.node_path(*parent),
+                    None => self.tree.root().path.clone(),
+                };
+                for (change, children) in [("remove", removed), ("add", added)] {
+                    for child in children {
+                        let index = self.tree.index_in_parent(&AccessibleKey::Node(*child)).map_or(-1, |index| index as i32);
+                        let child_path = self.node_path(*child);
+                        self.emit(parent_path.clone(), object(ObjectEventType::ChildrenChanged), change, index, 0, AtSpiSignalData::Object(child_path));
+                    }
+                }
+            }
+            AccessibilityEvent::TextChanged { node_id, old_text, new_text } => {
+                let path = self.node_path(*node_id);
+                let length = |text: &str| text.chars().count() as i32;
+                self.emit(path.clone(), object(ObjectEventType::TextChanged), "delete", 0, length(old_text), AtSpiSignalData::Text(old_text.clone()));
+                self.emit(path, object(ObjectEventType::TextChanged), "insert", 0, length(new_text), AtSpiSignalData::Text(new_text.clone()));
+            }
+            AccessibilityEvent::ValueChanged { node_id, .. } => {
+                let path = self.node_path(*node_id);
+                self.emit(path, object(ObjectEventType::PropertyChange), "accessible-value", 0, 0, AtSpiSignalData::None);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Announce text not tied to an object as an `object:announcement` event
+    pub fn announce(&mut self, announcement: AtSpiAnnouncement) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        log::debug!("AT-SPI announcement ({:?}): {}", announcement.politeness, announcement.text);
+        let path = self.tree.root().path.clone();
+        let politeness = announcement.politeness.at_spi_level();
+        let text = AtSpiSignalData::Text(announcement.text.clone());
+        self.last_announcement = Some(announcement);
+        self.emit(path, AtSpiEventType::Object(ObjectEventType::Announcement), "", politeness, 0, text);
+        Ok(())
+    }
+
+    /// Last announcement made
+    pub fn last_announcement(&self) -> Option<&AtSpiAnnouncement> {
+        self.last_announcement.as_ref()
+    }
+
+    /// Replace the exported tree, keeping its live region
+    ///
+    /// Emits `object:children-changed:remove` and `:add` for every child
+    /// that left or joined an object.
+    pub fn export_tree(&mut self, mut tree: AccessibleTree) -> Result<()> {
+        tree.keep_notifications(&self.tree);
+        let previous = std::mem::replace(&mut self.tree, tree);
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let mut signals = Vec::new();
+        for key in self.tree.changed_parents(&previous) {
+            let Some(item) = self.tree.get(key) else {
+                continue;
+            };
+            log::debug!("AT-SPI children changed: {}", item.path);
+            let old_children = previous.children(key);
+            for (index, child) in old_children.iter().enumerate() {
+                if !item.children.contains(child) {
+                    if let Some(child) = previous.get(child) {
+                        signals.push((item.path.clone(), "remove", index, child.path.clone()));
+                    }
+                }
+            }
+            for (index, child) in item.children.iter().enumerate() {
+                if !old_children.contains(child) {
+                    if let Some(child) = self.tree.get(child) {
+                        signals.push((item.path.clone(), "add", index, child.path.clone()));
+                    }
+                }
+            }
+        }
+        for (path, change, index, child) in signals {
+            self.emit(path, AtSpiEventType::Object(ObjectEventType::ChildrenChanged), change, index as i32, 0, AtSpiSignalData::Object(child));
+        }
+        Ok(())
+    }
 
+    /// Exported tree
+    pub fn tree(&self) -> &AccessibleTree {
+        &self.tree
+    }
+
+    /// Name of the application at the root of the tree
+    pub fn application_name(&self) -> &str {
+        &self.app_context.name
+    }
+
+    /// Post a notification to the live region of the tree, so screen readers announce it
+    pub fn post_notification(&mut self, announcement: AtSpiAnnouncement) -> Result<()> {
+        let key = self.tree.post_notification(announcement.clone());
+        if !self.enabled {
+            return Ok(());
+        }
+
+        if let (Some(item), Some(index)) = (self.tree.get(&key), self.tree.index_in_parent(&key)) {
+            log::debug!("AT-SPI live region addition ({:?}): {}", announcement.politeness, item.path);
+            let region = self.tree.get(&AccessibleKey::Notifications).map(|region| region.path.clone()).unwrap_or_default();
+            let child = AtSpiSignalData::Object(item.path.clone());
+            self.emit(region, AtSpiEventType::Object(ObjectEventType::ChildrenChanged), "add", index as i32, 0, child);
+        }
+        self.last_announcement = Some(announcement);
         Ok(())
     }
 
+    /// Take the events not yet sent on the accessibility bus
+    pub fn take_signals(&mut self) -> Vec<AtSpiSignal> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    /// Parent of a node in the exported tree, e.g. its cluster
+    pub fn node_parent(&self, node_id: SceneId) -> Option<&AccessibleKey> {
+        self.tree.get(&AccessibleKey::Node(node_id))?.parent.as_ref()
+    }
+
     /// Register application with AT-SPI
     fn register_application(&mut self) -> Result<()> {
         log::debug!("Registering application with AT-SPI");
@@ -663,9 +901,9 @@ impl AtSpiInterface {
 
     /// Create AT-SPI object from accessibility info
     fn create_at_spi_object(&self, info: &NodeAccessibilityInfo) -> Result<AtSpiObject> {
-        let role = self.convert_to_at_spi_role(&info.role);
-        let state_set = self.convert_to_at_spi_state_set(&info.state);
-        let interfaces = self.determine_interfaces(&info.role);
+        let role = convert_to_at_spi_role(&info.role);
+        let state_set = convert_to_at_spi_state_set(&info.state);
+        let interfaces = determine_interfaces(&info.role);
 
         let mut properties = HashMap::new();
         properties.insert("name".to_string(), AtSpiProperty::String(info.name.clone()));
@@ -692,155 +930,50 @@ impl AtSpiInterface {
         })
     }
 
-    /// Convert accessibility role to AT-SPI role
-    fn convert_to_at_spi_role(&self, role: &AccessibleRole) -> AtSpiRole {
-        match role {
-            AccessibleRole::Application => AtSpiRole::Application,
-            AccessibleRole::Dialog => AtSpiRole::Dialog,
-            AccessibleRole::Button => AtSpiRole::PushButton,
-            AccessibleRole::Link => AtSpiRole::Link,
-            AccessibleRole::TextInput => AtSpiRole::Text,
-            AccessibleRole::Label => AtSpiRole::Label,
-            AccessibleRole::Menu => AtSpiRole::Menu,
-            AccessibleRole::MenuItem => AtSpiRole::MenuItem,
-            AccessibleRole::List => AtSpiRole::List,
-            AccessibleRole::ListItem => AtSpiRole::ListItem,
-            AccessibleRole::Tab => AtSpiRole::PageTab,
-            AccessibleRole::TabPanel => AtSpiRole::PageTabList,
-            AccessibleRole::Tree => AtSpiRole::Tree,
-            AccessibleRole::TreeItem => AtSpiRole::TreeTable,
-            AccessibleRole::Graph => AtSpiRole::Canvas,
-            AccessibleRole::GraphNode => AtSpiRole::Icon,
-            AccessibleRole::GraphEdge => AtSpiRole::Separator,
-            AccessibleRole::Custom(_) => AtSpiRole::Unknown,
-            AccessibleRole::GenericObject => AtSpiRole::Unknown,
-            AccessibleRole::CheckBox => AtSpiRole::CheckBox,
-            AccessibleRole::Document => AtSpiRole::DocumentFrame,
+    /// Object path of a node, whether or not the tree holds it yet
+    fn node_path(&self, node_id: SceneId) -> String {
+        match self.tree.get(&AccessibleKey::Node(node_id)) {
+            Some(item) => item.path.clone(),
+            None => format!("/org/a11y/horizonos/accessible/{}", node_id),
         }
     }
 
-    /// Convert accessibility state to AT-SPI state set
-    fn convert_to_at_spi_state_set(&self, state: &AccessibleState) -> AtSpiStateSet {
-        let mut states = Vec::new();
-
-        if state.enabled {
-            states.push(AtSpiState::Enabled);
-            states.push(AtSpiState::Sensitive);
-        }
-
-        if state.visible {
-            states.push(AtSpiState::Visible);
-            states.push(AtSpiState::Showing);
-        }
-
-        if state.focused {
-            states.push(AtSpiState::Focused);
-            states.push(AtSpiState::Focusable);
-        }
-
-        if state.selected {
-            states.push(AtSpiState::Selected);
-            states.push(AtSpiState::Selectable);
-        }
-
-        if let Some(expanded) = state.expanded {
-            states.push(AtSpiState::Expandable);
-            if expanded {
-                states.push(AtSpiState::Expanded);
-            } else {
-                states.push(AtSpiState::Collapsed);
-            }
-        }
-
-        if let Some(checked) = state.checked {
-            states.push(AtSpiState::Checkable);
-            if checked {
-                states.push(AtSpiState::Checked);
-            }
-        }
-
-        if state.pressed {
-            states.push(AtSpiState::Pressed);
-        }
-
-        if state.busy {
-            states.push(AtSpiState::Busy);
-        }
-
-        AtSpiStateSet { states }
-    }
-
-    /// Determine required interfaces for a role
-    fn determine_interfaces(&self, role: &AccessibleRole) -> Vec<AtSpiInterfaceType> {
-        let mut interfaces = vec![AtSpiInterfaceType::Accessible, AtSpiInterfaceType::Component];
-
-        match role {
-            AccessibleRole::Button => {
-                interfaces.push(AtSpiInterfaceType::Action);
-            }
-            AccessibleRole::Link => {
-                interfaces.push(AtSpiInterfaceType::Action);
-                interfaces.push(AtSpiInterfaceType::Hyperlink);
-            }
-            AccessibleRole::TextInput => {
-                interfaces.push(AtSpiInterfaceType::Text);
-            }
-            AccessibleRole::List => {
-                interfaces.push(AtSpiInterfaceType::Selection);
-            }
-            AccessibleRole::Graph => {
-                interfaces.push(AtSpiInterfaceType::Selection);
-            }
-            _ => {}
-        }
-
-        interfaces
+    /// Queue an AT-SPI event for the accessibility bus
+    fn emit(&mut self, path: String, event: AtSpiEventType, detail: &str, detail1: i32, detail2: i32, data: AtSpiSignalData) {
+        log::debug!("Emitting AT-SPI event {:?}:{} on {}", event, detail, path);
+        let (interface, member) = event.signal();
+        self.outbox.push(AtSpiSignal {
+            path,
+            interface,
+            member,
+            detail: detail.to_string(),
+            detail1,
+            detail2,
+            data,
+        });
     }
 
-    /// Convert accessibility event to AT-SPI event
-    fn convert_to_at_spi_event(&self, event: &AccessibilityEvent) -> Result<AtSpiEventType> {
-        let at_spi_event = match event {
-            AccessibilityEvent::FocusChanged { .. } => {
-                AtSpiEventType::Focus(FocusEventType::In)
-            }
-            AccessibilityEvent::SelectionChanged { .. } => {
-                AtSpiEventType::Object(ObjectEventType::SelectionChanged)
-            }
-            AccessibilityEvent::StateChanged { .. } => {
-                AtSpiEventType::Object(ObjectEventType::StateChanged)
-            }
-            AccessibilityEvent::StructureChanged { .. } => {
-                AtSpiEventType::Object(ObjectEventType::ChildrenChanged)
-            }
-            AccessibilityEvent::TextChanged { .. } => {
-                AtSpiEventType::Object(ObjectEventType::TextChanged)
-            }
-            AccessibilityEvent::ValueChanged { .. } => {
-                AtSpiEventType::Object(ObjectEventType::PropertyChange)
-            }
+    /// Notify AT-SPI of a changed name or description
+    fn notify_object_changed(&mut self, previous: &AtSpiObject, info: &NodeAccessibilityInfo) -> Result<()> {
+        let text = |object: &AtSpiObject, key: &str| match object.properties.get(key) {
+            Some(AtSpiProperty::String(text)) => Some(text.clone()),
+            _ => None,
         };
-
-        Ok(at_spi_event)
-    }
-
-    /// Emit AT-SPI event
-    fn emit_at_spi_event(&mut self, event: &AtSpiEventType) -> Result<()> {
-        log::debug!("Emitting AT-SPI event: {:?}", event);
-        // TODO: Implement actual AT-SPI event emission
-        Ok(())
-    }
-
-    /// Notify AT-SPI of object change
-    fn notify_object_changed(&mut self, node_id: SceneId) -> Result<()> {
-        log::debug!("Notifying AT-SPI of object change: {:?}", node_id);
-        // TODO: Implement actual AT-SPI notification
+        let path = self.node_path(info.node_id);
+        if text(previous, "name").as_deref() != Some(info.name.as_str()) {
+            self.emit(path.clone(), AtSpiEventType::Object(ObjectEventType::PropertyChange), "accessible-name", 0, 0, AtSpiSignalData::Text(info.name.clone()));
+        }
+        if text(previous, "description") != info.description {
+            let description = info.description.clone().unwrap_or_default();
+            self.emit(path, AtSpiEventType::Object(ObjectEventType::PropertyChange), "accessible-description", 0, 0, AtSpiSignalData::Text(description));
+        }
         Ok(())
     }
 
-    /// Notify AT-SPI of object removal
+    /// Notify AT-SPI that an object is gone
     fn notify_object_removed(&mut self, node_id: SceneId) -> Result<()> {
-        log::debug!("Notifying AT-SPI of object removal: {:?}", node_id);
-        // TODO: Implement actual AT-SPI notification
+        let path = self.node_path(node_id);
+        self.emit(path, AtSpiEventType::Object(ObjectEventType::StateChanged), "defunct", 1, 0, AtSpiSignalData::None);
         Ok(())
     }
 
@@ -881,6 +1014,133 @@ impl AtSpiInterface {
     }
 }
 
+/// AT-SPI names and new values of the states that differ
+fn changed_states(old: &AccessibleState, new: &AccessibleState) -> Vec<(&'static str, bool)> {
+    let mut changed = Vec::new();
+    let mut compare = |name: &'static str, old: bool, new: bool| {
+        if old != new {
+            changed.push((name, new));
+        }
+    };
+    compare("focused", old.focused, new.focused);
+    compare("selected", old.selected, new.selected);
+    compare("expanded", old.expanded == Some(true), new.expanded == Some(true));
+    compare("collapsed", old.expanded == Some(false), new.expanded == Some(false));
+    compare("enabled", old.enabled, new.enabled);
+    compare("sensitive", old.enabled, new.enabled);
+    compare("visible", old.visible, new.visible);
+    compare("showing", old.visible, new.visible);
+    compare("checked", old.checked == Some(true), new.checked == Some(true));
+    compare("pressed", old.pressed, new.pressed);
+    compare("busy", old.busy, new.busy);
+    changed
+}
+
+/// Convert accessibility role to AT-SPI role
+pub(crate) fn convert_to_at_spi_role(role: &AccessibleRole) -> AtSpiRole {
+    match role {
+        AccessibleRole::Application => AtSpiRole::Application,
+        AccessibleRole::Dialog => AtSpiRole::Dialog,
+        AccessibleRole::Button => AtSpiRole::PushButton,
+        AccessibleRole::Link => AtSpiRole::Link,
+        AccessibleRole::TextInput => AtSpiRole::Text,
+        AccessibleRole::Label => AtSpiRole::Label,
+        AccessibleRole::Menu => AtSpiRole::Menu,
+        AccessibleRole::MenuItem => AtSpiRole::MenuItem,
+        AccessibleRole::List => AtSpiRole::List,
+        AccessibleRole::ListItem => AtSpiRole::ListItem,
+        AccessibleRole::Tab => AtSpiRole::PageTab,
+        AccessibleRole::TabPanel => AtSpiRole::PageTabList,
+        AccessibleRole::Tree => AtSpiRole::Tree,
+        AccessibleRole::TreeItem => AtSpiRole::TreeTable,
+        AccessibleRole::Graph => AtSpiRole::Canvas,
+        AccessibleRole::GraphNode => AtSpiRole::Icon,
+        AccessibleRole::GraphEdge => AtSpiRole::Separator,
+        AccessibleRole::Custom(_) => AtSpiRole::Unknown,
+        AccessibleRole::GenericObject => AtSpiRole::Unknown,
+        AccessibleRole::CheckBox => AtSpiRole::CheckBox,
+        AccessibleRole::Document => AtSpiRole::DocumentFrame,
+    }
+}
+
+/// Convert accessibility state to AT-SPI state set
+pub(crate) fn convert_to_at_spi_state_set(state: &AccessibleState) -> AtSpiStateSet {
+    let mut states = Vec::new();
+
+    if state.enabled {
+        states.push(AtSpiState::Enabled);
+        states.push(AtSpiState::Sensitive);
+    }
+
+    if state.visible {
+        states.push(AtSpiState::Visible);
+        states.push(AtSpiState::Showing);
+    }
+
+    if state.focused {
+        states.push(AtSpiState::Focused);
+        states.push(AtSpiState::Focusable);
+    }
+
+    if state.selected {
+        states.push(AtSpiState::Selected);
+        states.push(AtSpiState::Selectable);
+    }
+
+    if let Some(expanded) = state.expanded {
+        states.push(AtSpiState::Expandable);
+        if expanded {
+            states.push(AtSpiState::Expanded);
+        } else {
+            states.push(AtSpiState::Collapsed);
+        }
+    }
+
+    if let Some(checked) = state.checked {
+        states.push(AtSpiState::Checkable);
+        if checked {
+            states.push(AtSpiState::Checked);
+        }
+    }
+
+    if state.pressed {
+        states.push(AtSpiState::Pressed);
+    }
+
+    if state.busy {
+        states.push(AtSpiState::Busy);
+    }
+
+    AtSpiStateSet { states }
+}
+
+/// Determine required interfaces for a role
+pub(crate) fn determine_interfaces(role: &AccessibleRole) -> Vec<AtSpiInterfaceType> {
+    let mut interfaces = vec![AtSpiInterfaceType::Accessible, AtSpiInterfaceType::Component];
+
+    match role {
+        AccessibleRole::Button => {
+            interfaces.push(AtSpiInterfaceType::Action);
+        }
+        AccessibleRole::Link => {
+            interfaces.push(AtSpiInterfaceType::Action);
+            interfaces.push(AtSpiInterfaceType::Hyperlink);
+        }
+        AccessibleRole::TextInput => {
+            interfaces.push(AtSpiInterfaceType::Text);
+        }
+        AccessibleRole::List => {
+            interfaces.push(AtSpiInterfaceType::Selection);
+        }
+        AccessibleRole::Graph => {
+            interfaces.push(AtSpiInterfaceType::Selection);
+        }
+        _ => {}
+    }
+
+    interfaces
+}
+
 impl AtSpiConnection {
     /// Create a new AT-SPI connection
     pub fn new() -> Result<Self> {
@@ -908,6 +1168,53 @@ impl AtSpiConnection {
     }
 }
 
+/// Connection to the accessibility bus AT-SPI events are sent on
+pub struct AtSpiBus {
+    connection: zbus::Connection,
+}
+
+impl AtSpiBus {
+    /// Connect to the accessibility bus, found through `AT_SPI_BUS_ADDRESS` or the session bus
+    pub async fn connect() -> Result<Self> {
+        let address = match std::env::var("AT_SPI_BUS_ADDRESS") {
+            Ok(address) => address,
+            Err(_) => {
+                let session = zbus::Connection::session().await?;
+                let reply = session
+                    .call_method(Some("org.a11y.Bus"), "/org/a11y/bus", Some("org.a11y.Bus"), "GetAddress", &())
+                    .await?;
+                reply.body::<String>()?
+            }
+        };
+        let connection = zbus::ConnectionBuilder::address(address.as_str())?.build().await?;
+        log::info!("Connected to the accessibility bus");
+        Ok(Self { connection })
+    }
+
+    /// Send an event as an AT-SPI signal
+    pub async fn emit(&self, signal: &AtSpiSignal) -> Result<()> {
+        let data = match &signal.data {
+            AtSpiSignalData::None => Value::from(0i32),
+            AtSpiSignalData::Text(text) => Value::from(text.as_str()),
+            AtSpiSignalData::Object(path) => {
+                let bus_name = self.connection.unique_name().map(|name| name.to_string()).unwrap_or_default();
+                Value::from(Structure::from((bus_name, ObjectPath::try_from(path.clone())?)))
+            }
+        };
+        let properties: HashMap<&str, Value> = HashMap::new();
+        self.connection
+            .emit_signal(
+                None::<BusName>,
+                signal.path.as_str(),
+                signal.interface,
+                signal.member.as_str(),
+                &(signal.detail.as_str(), signal.detail1, signal.detail2, data, properties),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
 impl AtSpiApplicationContext {
     /// Create a new application context
     pub fn new() -> Self {
@@ -949,4 +1256,89 @@ impl AtSpiStateSet {
     pub fn get_states(&self) -> &Vec<AtSpiState> {
         &self.states
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AccessibleAction, AccessibleBounds};
+    use horizonos_graph_workspaces::Workspace;
+
+    fn node(node_id: SceneId, name: &str) -> NodeAccessibilityInfo {
+        NodeAccessibilityInfo {
+            node_id,
+            name: name.to_string(),
+            description: None,
+            role: AccessibleRole::Document,
+            state: AccessibleState { enabled: true, visible: true, ..Default::default() },
+            actions: vec![AccessibleAction::Activate],
+            relationships: Vec::new(),
+            bounds: AccessibleBounds { x: 0.0, y: 0.0, width: 10.0, height: 10.0 },
+            text_content: None,
+            value: None,
+        }
+    }
+
+    fn tree(workspace_nodes: &[SceneId]) -> AccessibleTree {
+        let mut workspace = Workspace::new("Work", "");
+        workspace.id = "work".to_string();
+        for node_id in workspace_nodes {
+            workspace.add_node(*node_id);
+        }
+        let nodes = [node(1, "Notes.md"), node(2, "Todo.md")].into_iter().map(|info| (info.node_id, info)).collect();
+        AccessibleTree::build("Desktop", &[workspace], Some("work"), &[], &nodes)
+    }
+
+    #[test]
+    fn test_events_are_queued_as_signals() {
+        let mut at_spi = AtSpiInterface::new().unwrap();
+        at_spi.fire_event(&AccessibilityEvent::SelectionChanged { selected_nodes: vec![1] }).unwrap();
+        assert!(at_spi.take_signals().is_empty(), "nothing is sent while disabled");
+
+        at_spi.enable().unwrap();
+        at_spi.export_tree(tree(&[])).unwrap();
+        at_spi.take_signals();
+
+        // Moving a node into a workspace removes it from the root and adds it to the workspace
+        at_spi.export_tree(tree(&[1])).unwrap();
+        let signals = at_spi.take_signals();
+        let root = at_spi.tree().root().path.clone();
+        let workspace = at_spi.tree().get(&AccessibleKey::Workspace("work".to_string())).unwrap().path.clone();
+        let node_path = at_spi.tree().get(&AccessibleKey::Node(1)).unwrap().path.clone();
+        assert!(signals.iter().all(|s| s.interface == "org.a11y.atspi.Event.Object" && s.member == "ChildrenChanged"));
+        assert!(signals.iter().any(|s| s.path == root && s.detail == "remove" && s.data == AtSpiSignalData::Object(node_path.clone())));
+        assert!(signals.contains(&AtSpiSignal {
+            path: workspace,
+            interface: "org.a11y.atspi.Event.Object",
+            member: "ChildrenChanged".to_string(),
+            detail: "add".to_string(),
+            detail1: 0,
+            detail2: 0,
+            data: AtSpiSignalData::Object(node_path.clone()),
+        }));
+
+        at_spi.fire_event(&AccessibilityEvent::FocusChanged { old_focus: Some(2), new_focus: Some(1) }).unwrap();
+        let signals = at_spi.take_signals();
+        let summary: Vec<_> = signals.iter().map(|s| (s.member.as_str(), s.detail.as_str(), s.detail1)).collect();
+        assert_eq!(summary, [("StateChanged", "focused", 0), ("StateChanged", "focused", 1), ("Focus", "", 0)]);
+        assert_eq!(signals[1].path, node_path);
+
+        let mut selected = AccessibleState { enabled: true, visible: true, ..Default::default() };
+        let old_state = selected.clone();
+        selected.selected = true;
+        at_spi.fire_event(&AccessibilityEvent::StateChanged { node_id: 1, old_state, new_state: selected }).unwrap();
+        assert_eq!(at_spi.take_signals().iter().map(|s| (s.detail.as_str(), s.detail1)).collect::<Vec<_>>(), [("selected", 1)]);
+
+        at_spi.announce(AtSpiAnnouncement {
+            text: "Battery low".to_string(),
+            braille: Vec::new(),
+            politeness: AnnouncementPoliteness::Assertive,
+        }).unwrap();
+        let signals = at_spi.take_signals();
+        assert_eq!((signals[0].member.as_str(), signals[0].detail1), ("Announcement", 2));
+        assert_eq!(signals[0].data, AtSpiSignalData::Text("Battery low".to_string()));
+
+        at_spi.disable().unwrap();
+        at_spi.fire_event(&AccessibilityEvent::SelectionChanged { selected_nodes: vec![1] }).unwrap();
+        assert!(at_spi.take_signals().is_empty());
+    }
+}
@@ -0,0 +1,517 @@
+//! Keyboard-driven edge creation for graph desktop accessibility
+//!
+//! Lets keyboard-only users connect two nodes without a pointer: press `C` with a
+//! node focused to enter connect mode, move to the target with the spatial
+//! navigation keys, pick a relationship type from a list and confirm. The edge
+//! itself is held by the interaction layer's [`EdgeCreationController`], so it
+//! is checked against the same edge rules as one drawn with the pointer and
+//! added through the edge manager.
+
+use crate::keyboard_nav::{ArrowDirection, Key, KeyboardNavigator, Modifier};
+use crate::NodeAccessibilityInfo;
+use horizonos_graph_edges::EdgeManager;
+use horizonos_graph_engine::{EdgeType, Scene, SceneId};
+use horizonos_graph_interaction::{CreatedEdge, EdgeCreationController, GhostEdgeState};
+use std::collections::HashMap;
+use anyhow::Result;
+
+/// Keyboard edge creation state machine
+#[derive(Debug)]
+pub struct KeyboardEdgeCreator {
+    /// Edge being connected
+    edge: EdgeCreationController,
+    /// Entry of the relationship list that is selected, while it is open
+    choosing: Option<usize>,
+    /// Relationship types offered to the user, in list order
+    edge_types: Vec<EdgeTypeOption>,
+}
+
+/// States of the connect mode flow
+#[derive(Debug, Clone, PartialEq)]
+pub enum EdgeCreationState {
+    /// Connect mode is not active
+    Idle,
+    /// Moving focus to pick the target node
+    SelectingTarget {
+        source: SceneId,
+        target: Option<SceneId>,
+    },
+    /// Choosing the relationship type from the list
+    ChoosingEdgeType {
+        source: SceneId,
+        target: SceneId,
+        selected: usize,
+    },
+}
+
+/// A relationship type entry in the edge type list
+#[derive(Debug, Clone)]
+pub struct EdgeTypeOption {
+    /// Spoken label
+    pub label: String,
+    /// Edge type created when this entry is confirmed
+    pub edge_type: EdgeType,
+}
+
+/// Result of feeding a key press to the edge creator
+#[derive(Debug, Clone, Default)]
+pub struct EdgeCreationStep {
+    /// Whether the key was consumed by connect mode
+    pub handled: bool,
+    /// Text to announce through the screen reader
+    pub announcement: Option<String>,
+    /// Edge added to the edge manager once the user confirms a type; the
+    /// caller shows its scene edge
+    pub confirmed: Option<CreatedEdge>,
+}
+
+/// Scene and edge rules connect mode works against
+pub struct EdgeContext<'a> {
+    /// Scene holding the nodes being connected
+    pub scene: &'a Scene,
+    /// Edge manager new edges are checked against and added to
+    pub edges: &'a mut EdgeManager,
+}
+
+impl KeyboardEdgeCreator {
+    /// Create a new edge creator with the default relationship list
+    pub fn new() -> Self {
+        Self {
+            edge: EdgeCreationController::new(),
+            choosing: None,
+            edge_types: Self::default_edge_types(),
+        }
+    }
+
+    /// Current connect mode state
+    pub fn state(&self) -> EdgeCreationState {
+        let Some(source) = self.edge.source() else {
+            return EdgeCreationState::Idle;
+        };
+        match (self.edge.target(), self.choosing) {
+            (Some(target), Some(selected)) => EdgeCreationState::ChoosingEdgeType { source, target, selected },
+            (target, _) => EdgeCreationState::SelectingTarget { source, target },
+        }
+    }
+
+    /// Whether connect mode is active
+    pub fn is_active(&self) -> bool {
+        self.edge.is_active()
+    }
+
+    /// Replace the relationship types offered in the list
+    pub fn set_edge_types(&mut self, edge_types: Vec<EdgeTypeOption>) {
+        self.edge_types = edge_types;
+    }
+
+    /// Get the relationship types offered in the list
+    pub fn edge_types(&self) -> &[EdgeTypeOption] {
+        &self.edge_types
+    }
+
+    /// Handle a key press, returning what should be announced or created
+    pub fn handle_key_press(
+        &mut self,
+        key: Key,
+        modifiers: Vec<Modifier>,
+        navigator: &mut KeyboardNavigator,
+        node_cache: &HashMap<SceneId, NodeAccessibilityInfo>,
+        context: EdgeContext<'_>,
+    ) -> Result<EdgeCreationStep> {
+        match self.state() {
+            EdgeCreationState::Idle => self.handle_idle_key(key, modifiers, navigator, node_cache, context),
+            EdgeCreationState::SelectingTarget { .. } => {
+                self.handle_target_key(key, modifiers, navigator, node_cache, context)
+            }
+            EdgeCreationState::ChoosingEdgeType { selected, .. } => {
+                self.handle_edge_type_key(selected, key, navigator, node_cache, context)
+            }
+        }
+    }
+
+    /// Leave connect mode without creating an edge
+    pub fn cancel(&mut self, navigator: &mut KeyboardNavigator) -> Result<EdgeCreationStep> {
+        let Some(source) = self.edge.source() else {
+            return Ok(EdgeCreationStep::default());
+        };
+
+        self.edge.cancel();
+        self.choosing = None;
+        navigator.set_focus(Some(source))?;
+
+        Ok(EdgeCreationStep {
+            handled: true,
+            announcement: Some("Connect mode cancelled".to_string()),
+            confirmed: None,
+        })
+    }
+
+    /// Enter connect mode from the focused node
+    fn handle_idle_key(
+        &mut self,
+        key: Key,
+        modifiers: Vec<Modifier>,
+        navigator: &mut KeyboardNavigator,
+        node_cache: &HashMap<SceneId, NodeAccessibilityInfo>,
+        context: EdgeContext<'_>,
+    ) -> Result<EdgeCreationStep> {
+        let is_connect_key = matches!(key, Key::Character('c') | Key::Character('C'))
+            && modifiers.iter().all(|m| *m == Modifier::Shift);
+
+        if !is_connect_key {
+            return Ok(EdgeCreationStep::default());
+        }
+
+        let Some(source) = navigator.get_focus() else {
+            return Ok(EdgeCreationStep {
+                handled: true,
+                announcement: Some("Focus a node before entering connect mode".to_string()),
+                confirmed: None,
+            });
+        };
+
+        let Some(first) = self.edge_types.first() else {
+            return Ok(EdgeCreationStep {
+                handled: true,
+                announcement: Some("No relationship types available".to_string()),
+                confirmed: None,
+            });
+        };
+
+        if !self.edge.begin(source, first.edge_type.clone(), context.scene) {
+            return Ok(EdgeCreationStep {
+                handled: true,
+                announcement: Some(format!("{} cannot be connected", Self::node_name(source, node_cache))),
+                confirmed: None,
+            });
+        }
+        log::debug!("Entered connect mode from node: {:?}", source);
+
+        Ok(EdgeCreationStep {
+            handled: true,
+            announcement: Some(format!(
+                "Connect mode. Source: {}. Use arrow keys to choose a target, Enter to confirm, Escape to cancel",
+                Self::node_name(source, node_cache)
+            )),
+            confirmed: None,
+        })
+    }
+
+    /// Move between candidate targets and confirm one
+    fn handle_target_key(
+        &mut self,
+        key: Key,
+        modifiers: Vec<Modifier>,
+        navigator: &mut KeyboardNavigator,
+        node_cache: &HashMap<SceneId, NodeAccessibilityInfo>,
+        context: EdgeContext<'_>,
+    ) -> Result<EdgeCreationStep> {
+        let (Some(source), target) = (self.edge.source(), self.edge.target()) else {
+            return Ok(EdgeCreationStep::default());
+        };
+        match key {
+            Key::Escape => self.cancel(navigator),
+            Key::Enter => {
+                if target.is_none() {
+                    return Ok(EdgeCreationStep {
+                        handled: true,
+                        announcement: Some("No target chosen".to_string()),
+                        confirmed: None,
+                    });
+                }
+
+                self.choose_edge_type(0, context);
+
+                Ok(EdgeCreationStep {
+                    handled: true,
+                    announcement: Some(format!(
+                        "Choose relationship type. {}. Use up and down arrows, Enter to confirm",
+                        self.describe_edge_type(0)
+                    )),
+                    confirmed: None,
+                })
+            }
+            key => {
+                if navigator.handle_key_press(key, modifiers, node_cache)?.is_none() {
+                    // Swallow unbound keys so they don't leak into normal navigation
+                    return Ok(EdgeCreationStep {
+                        handled: true,
+                        announcement: None,
+                        confirmed: None,
+                    });
+                }
+
+                let candidate = navigator.get_focus().filter(|&id| id != source);
+                if candidate == target {
+                    return Ok(EdgeCreationStep {
+                        handled: true,
+                        announcement: None,
+                        confirmed: None,
+                    });
+                }
+
+                self.edge.set_target(candidate, context.scene, context.edges);
+
+                let announcement = match candidate {
+                    Some(id) => format!("Target: {}", Self::node_name(id, node_cache)),
+                    None => "Source node, choose another target".to_string(),
+                };
+
+                Ok(EdgeCreationStep {
+                    handled: true,
+                    announcement: Some(announcement),
+                    confirmed: None,
+                })
+            }
+        }
+    }
+
+    /// Cycle through the relationship list and confirm the edge
+    fn handle_edge_type_key(
+        &mut self,
+        selected: usize,
+        key: Key,
+        navigator: &mut KeyboardNavigator,
+        node_cache: &HashMap<SceneId, NodeAccessibilityInfo>,
+        context: EdgeContext<'_>,
+    ) -> Result<EdgeCreationStep> {
+        let count = self.edge_types.len();
+
+        let next = match key {
+            Key::Escape => return self.cancel(navigator),
+            Key::Arrow(ArrowDirection::Down) | Key::Tab => (selected + 1) % count,
+            Key::Arrow(ArrowDirection::Up) => (selected + count - 1) % count,
+            Key::Home => 0,
+            Key::End => count - 1,
+            Key::Enter | Key::Space => {
+                let (Some(source), Some(target)) = (self.edge.source(), self.edge.target()) else {
+                    return Ok(EdgeCreationStep::default());
+                };
+                let label = self.edge_types[selected].label.clone();
+                if let Some(GhostEdgeState::Invalid { reason }) = self.edge.target_state() {
+                    // Stay in the list so another type can be picked
+                    return Ok(EdgeCreationStep {
+                        handled: true,
+                        announcement: Some(format!("Cannot connect as {}: {}", label, reason)),
+                        confirmed: None,
+                    });
+                }
+
+                self.choosing = None;
+                let announcement = match self.edge.commit(context.scene, context.edges) {
+                    Ok(Some(created)) => {
+                        log::debug!("Keyboard edge confirmed: {:?} -> {:?}", source, target);
+                        let announcement = format!(
+                            "Connected {} to {} as {}",
+                            Self::node_name(source, node_cache),
+                            Self::node_name(target, node_cache),
+                            label
+                        );
+                        return Ok(EdgeCreationStep {
+                            handled: true,
+                            announcement: Some(announcement),
+                            confirmed: Some(created),
+                        });
+                    }
+                    Ok(None) => "No target chosen".to_string(),
+                    Err(e) => format!("Could not connect: {}", e),
+                };
+
+                return Ok(EdgeCreationStep {
+                    handled: true,
+                    announcement: Some(announcement),
+                    confirmed: None,
+                });
+            }
+            _ => {
+                return Ok(EdgeCreationStep {
+                    handled: true,
+                    announcement: None,
+                    confirmed: None,
+                });
+            }
+        };
+
+        self.choose_edge_type(next, context);
+
+        Ok(EdgeCreationStep {
+            handled: true,
+            announcement: Some(self.describe_edge_type(next)),
+            confirmed: None,
+        })
+    }
+
+    /// Select a list entry, checking its type against the edge rules
+    fn choose_edge_type(&mut self, index: usize, context: EdgeContext<'_>) {
+        self.choosing = Some(index);
+        self.edge.set_edge_type(self.edge_types[index].edge_type.clone(), context.scene, context.edges);
+    }
+
+    /// Describe a list entry as "label, n of total", noting when the edge
+    /// rules do not allow it
+    fn describe_edge_type(&self, index: usize) -> String {
+        let allowed = if matches!(self.edge.target_state(), Some(GhostEdgeState::Invalid { .. })) {
+            ", not allowed"
+        } else {
+            ""
+        };
+        format!(
+            "{}, {} of {}{}",
+            self.edge_types[index].label,
+            index + 1,
+            self.edge_types.len(),
+            allowed
+        )
+    }
+
+    /// Accessible name for a node, falling back to its ID
+    fn node_name(node_id: SceneId, node_cache: &HashMap<SceneId, NodeAccessibilityInfo>) -> String {
+        node_cache
+            .get(&node_id)
+            .map(|info| info.name.clone())
+            .unwrap_or_else(|| format!("node {}", node_id))
+    }
+
+    /// Relationship types offered by default
+    fn default_edge_types() -> Vec<EdgeTypeOption> {
+        vec![
+            EdgeTypeOption { label: "Related to".to_string(), edge_type: EdgeType::RelatedTo { similarity: 1.0 } },
+            EdgeTypeOption { label: "Depends on".to_string(), edge_type: EdgeType::DependsOn },
+            EdgeTypeOption { label: "Contains".to_string(), edge_type: EdgeType::Contains },
+            EdgeTypeOption { label: "Communicates with".to_string(), edge_type: EdgeType::CommunicatesWith },
+            EdgeTypeOption { label: "Created by".to_string(), edge_type: EdgeType::CreatedBy },
+            EdgeTypeOption { label: "Works on".to_string(), edge_type: EdgeType::WorksOn },
+        ]
+    }
+}
+
+impl Default for KeyboardEdgeCreator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AccessibleAction, AccessibleBounds, AccessibleRole, AccessibleState};
+    use horizonos_graph_engine::{NodeMetadata, NodeType, Position, SceneNode, Vec3};
+
+    struct Fixture {
+        creator: KeyboardEdgeCreator,
+        navigator: KeyboardNavigator,
+        nodes: HashMap<SceneId, NodeAccessibilityInfo>,
+        scene: Scene,
+        edges: EdgeManager,
+        notes: SceneId,
+        report: SceneId,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let mut scene = Scene::new();
+            let mut nodes = HashMap::new();
+            let mut add = |name: &str, x: f32| {
+                let id = scene.add_node(SceneNode {
+                    id: 0,
+                    position: Position::new(x, 0.0, 0.0),
+                    velocity: Vec3::zeros(),
+                    radius: 1.0,
+                    color: [1.0; 4],
+                    node_type: NodeType::Concept { title: name.to_string(), content: String::new() },
+                    metadata: NodeMetadata::default(),
+                    visible: true,
+                    selected: false,
+                });
+                nodes.insert(id, NodeAccessibilityInfo {
+                    node_id: id,
+                    name: name.to_string(),
+                    description: None,
+                    role: AccessibleRole::Document,
+                    state: AccessibleState { enabled: true, visible: true, ..Default::default() },
+                    actions: vec![AccessibleAction::Activate],
+                    relationships: Vec::new(),
+                    bounds: AccessibleBounds { x, y: 0.0, width: 10.0, height: 10.0 },
+                    text_content: None,
+                    value: None,
+                });
+                id
+            };
+            let notes = add("Notes", 0.0);
+            let report = add("Report", 50.0);
+            let mut navigator = KeyboardNavigator::new();
+            navigator.set_focus(Some(notes)).unwrap();
+            Self { creator: KeyboardEdgeCreator::new(), navigator, nodes, scene, edges: EdgeManager::new(), notes, report }
+        }
+
+        fn press(&mut self, key: Key) -> EdgeCreationStep {
+            let context = EdgeContext { scene: &self.scene, edges: &mut self.edges };
+            self.creator.handle_key_press(key, Vec::new(), &mut self.navigator, &self.nodes, context).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_select_target_and_confirm() {
+        let mut f = Fixture::new();
+        let (notes, report) = (f.notes, f.report);
+
+        // Other keys are left to normal navigation
+        assert!(!f.press(Key::Character('x')).handled);
+        let step = f.press(Key::Character('c'));
+        assert!(step.announcement.unwrap().contains("Source: Notes"));
+        assert_eq!(f.creator.state(), EdgeCreationState::SelectingTarget { source: notes, target: None });
+
+        // Enter needs a target first
+        assert_eq!(f.press(Key::Enter).announcement.as_deref(), Some("No target chosen"));
+        assert_eq!(f.press(Key::Arrow(ArrowDirection::Right)).announcement.as_deref(), Some("Target: Report"));
+
+        f.press(Key::Enter);
+        assert_eq!(f.creator.state(), EdgeCreationState::ChoosingEdgeType { source: notes, target: report, selected: 0 });
+        assert_eq!(f.press(Key::Arrow(ArrowDirection::Down)).announcement.as_deref(), Some("Depends on, 2 of 6"));
+        assert_eq!(f.press(Key::Arrow(ArrowDirection::Up)).announcement.as_deref(), Some("Related to, 1 of 6"));
+
+        // The edge rules are the ones pointer edges follow: only people work on things
+        assert_eq!(f.press(Key::End).announcement.as_deref(), Some("Works on, 6 of 6, not allowed"));
+        let step = f.press(Key::Enter);
+        assert!(step.announcement.unwrap().starts_with("Cannot connect as Works on"));
+        assert!(step.confirmed.is_none());
+        assert!(f.creator.is_active());
+
+        f.press(Key::Home);
+        let step = f.press(Key::Enter);
+        let created = step.confirmed.unwrap();
+        assert_eq!((created.source, created.target), (notes, report));
+        assert!(matches!(created.scene_edge.edge_type, EdgeType::RelatedTo { .. }));
+        assert_eq!(step.announcement.as_deref(), Some("Connected Notes to Report as Related to"));
+        assert!(f.edges.get_edge(created.edge_id).is_some());
+        assert!(!f.creator.is_active());
+    }
+
+    #[test]
+    fn test_cancel_returns_focus_to_source() {
+        let mut f = Fixture::new();
+        f.press(Key::Character('C'));
+        f.press(Key::Arrow(ArrowDirection::Right));
+        assert_eq!(f.navigator.get_focus(), Some(f.report));
+
+        let step = f.press(Key::Escape);
+        assert_eq!(step.announcement.as_deref(), Some("Connect mode cancelled"));
+        assert!(step.confirmed.is_none());
+        assert!(!f.creator.is_active());
+        assert_eq!(f.navigator.get_focus(), Some(f.notes));
+
+        // Escape in the type list cancels too, without adding an edge
+        f.press(Key::Character('c'));
+        f.press(Key::Arrow(ArrowDirection::Right));
+        f.press(Key::Enter);
+        f.press(Key::Escape);
+        assert_eq!(f.creator.state(), EdgeCreationState::Idle);
+        assert!(f.edges.get_all_edges(f.notes).is_empty());
+
+        // Without a focused node connect mode does not start
+        f.navigator.set_focus(None).unwrap();
+        let step = f.press(Key::Character('c'));
+        assert!(step.handled);
+        assert!(!f.creator.is_active());
+    }
+}
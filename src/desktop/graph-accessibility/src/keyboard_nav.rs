@@ -2,7 +2,7 @@
 
 use crate::{NodeAccessibilityInfo, AccessibleRole, AccessibilityEvent};
 use horizonos_graph_engine::SceneId;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use anyhow::Result;
 
 /// Keyboard navigation system for accessible graph navigation
@@ -22,6 +22,8 @@ pub struct KeyboardNavigator {
     spatial_navigation: bool,
     /// Focus constraints
     focus_constraints: Vec<FocusConstraint>,
+    /// Relationship order Tab walks in graph mode, kept between presses
+    relationship_order: Vec<SceneId>,
 }
 
 /// Focus ring visual settings
@@ -61,7 +63,7 @@ pub enum NavigationMode {
     Spatial,
     /// Semantic navigation (by role)
     Semantic,
-    /// Graph-aware navigation
+    /// Graph-aware navigation: Tab follows relationships
     Graph,
 }
 
@@ -289,6 +291,7 @@ impl KeyboardNavigator {
             key_bindings,
             spatial_navigation: true,
             focus_constraints: Vec::new(),
+            relationship_order: Vec::new(),
         }
     }
 
@@ -430,7 +433,7 @@ impl KeyboardNavigator {
 
     /// Navigate to next element
     fn navigate_to_next_element(&mut self, node_cache: &HashMap<SceneId, NodeAccessibilityInfo>) -> Result<()> {
-        let navigable_nodes = self.get_navigable_nodes(node_cache);
+        let navigable_nodes = self.get_tab_order(node_cache);
         
         if let Some(current) = self.current_focus {
             if let Some(current_index) = navigable_nodes.iter().position(|&id| id == current) {
@@ -446,7 +449,7 @@ impl KeyboardNavigator {
 
     /// Navigate to previous element
     fn navigate_to_previous_element(&mut self, node_cache: &HashMap<SceneId, NodeAccessibilityInfo>) -> Result<()> {
-        let navigable_nodes = self.get_navigable_nodes(node_cache);
+        let navigable_nodes = self.get_tab_order(node_cache);
         
         if let Some(current) = self.current_focus {
             if let Some(current_index) = navigable_nodes.iter().position(|&id| id == current) {
@@ -478,6 +481,8 @@ impl KeyboardNavigator {
             if let Some(current_info) = node_cache.get(&current) {
                 let next_node = self.find_spatial_neighbor(current_info, direction, node_cache);
                 if let Some(next) = next_node {
+                    // Tab continues along relationships from where the arrows led
+                    self.relationship_order.clear();
                     self.set_focus(Some(next))?;
                 }
             }
@@ -613,6 +618,53 @@ impl KeyboardNavigator {
         nodes
     }
 
+    /// Order Tab moves through in the current navigation mode
+    fn get_tab_order(&mut self, node_cache: &HashMap<SceneId, NodeAccessibilityInfo>) -> Vec<SceneId> {
+        let navigable_nodes = self.get_navigable_nodes(node_cache);
+        let Some(start) = self.current_focus.filter(|_| matches!(self.navigation_mode, NavigationMode::Graph)) else {
+            return navigable_nodes;
+        };
+        
+        // Rebuilt when focus left the order or nodes came or went
+        let stale = !self.relationship_order.contains(&start)
+            || self.relationship_order.len() != navigable_nodes.len();
+        if stale {
+            self.relationship_order = Self::relationship_order(start, &navigable_nodes, node_cache);
+        }
+        self.relationship_order.clone()
+    }
+    
+    /// Breadth-first walk over relationships from `start`, then the other nodes in reading order
+    fn relationship_order(
+        start: SceneId,
+        navigable_nodes: &[SceneId],
+        node_cache: &HashMap<SceneId, NodeAccessibilityInfo>,
+    ) -> Vec<SceneId> {
+        let navigable: HashSet<SceneId> = navigable_nodes.iter().copied().collect();
+        let mut visited = HashSet::new();
+        let mut order = Vec::new();
+        
+        for root in std::iter::once(start).chain(navigable_nodes.iter().copied()) {
+            if !navigable.contains(&root) || !visited.insert(root) {
+                continue;
+            }
+            let mut queue = VecDeque::from([root]);
+            while let Some(node_id) = queue.pop_front() {
+                order.push(node_id);
+                let Some(info) = node_cache.get(&node_id) else {
+                    continue;
+                };
+                for relationship in &info.relationships {
+                    if navigable.contains(&relationship.target) && visited.insert(relationship.target) {
+                        queue.push_back(relationship.target);
+                    }
+                }
+            }
+        }
+        
+        order
+    }
+
     /// Check if node is navigable based on constraints
     fn is_node_navigable(&self, node_id: SceneId, info: &NodeAccessibilityInfo) -> bool {
         for constraint in &self.focus_constraints {
@@ -665,11 +717,14 @@ impl KeyboardNavigator {
                 continue;
             }
             
+            // Only nodes lying more in the pressed direction than sideways
+            let dx = info.bounds.x - current_info.bounds.x;
+            let dy = info.bounds.y - current_info.bounds.y;
             let is_candidate = match direction {
-                ArrowDirection::Right => info.bounds.x > current_info.bounds.x,
-                ArrowDirection::Left => info.bounds.x < current_info.bounds.x,
-                ArrowDirection::Down => info.bounds.y > current_info.bounds.y,
-                ArrowDirection::Up => info.bounds.y < current_info.bounds.y,
+                ArrowDirection::Right => dx > 0.0 && dy.abs() <= dx,
+                ArrowDirection::Left => dx < 0.0 && dy.abs() <= -dx,
+                ArrowDirection::Down => dy > 0.0 && dx.abs() <= dy,
+                ArrowDirection::Up => dy < 0.0 && dx.abs() <= -dy,
             };
             
             if is_candidate {
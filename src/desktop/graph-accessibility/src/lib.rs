@@ -7,13 +7,18 @@
 pub mod screen_reader;
 pub mod keyboard_nav;
 pub mod voice_commands;
+pub mod voice_grammar;
 pub mod magnification;
 pub mod contrast;
 pub mod spatial_audio;
 pub mod at_spi;
+pub mod accessible_tree;
+pub mod edge_creation;
 
-use horizonos_graph_engine::{GraphEngine, SceneId, Scene};
-use horizonos_graph_nodes::GraphNode;
+use horizonos_graph_engine::{GraphEngine, NodeType, SceneId, Scene, SceneNode};
+use horizonos_graph_nodes::{GraphNode, SearchDocument};
+use horizonos_graph_clustering::Cluster;
+use horizonos_graph_workspaces::Workspace;
 use std::collections::HashMap;
 use anyhow::Result;
 
@@ -34,6 +39,8 @@ pub struct AccessibilityManager {
     pub spatial_audio: spatial_audio::SpatialAudioManager,
     /// AT-SPI interface
     pub at_spi: at_spi::AtSpiInterface,
+    /// Keyboard-driven edge creation
+    pub edge_creator: edge_creation::KeyboardEdgeCreator,
     /// Accessibility settings
     pub settings: AccessibilitySettings,
     /// Node accessibility cache
@@ -71,6 +78,62 @@ pub struct AccessibilitySettings {
     pub speech_rate: u32,
     /// Speech volume (0.0 to 1.0)
     pub speech_volume: f32,
+    /// How much the screen reader says about each object
+    pub verbosity: screen_reader::VerbosityProfile,
+    /// Which kinds of events the screen reader announces
+    pub speech_events: SpeechEventToggles,
+}
+
+/// Per-event-type switches for screen reader announcements
+///
+/// Responses to screen reader commands are always spoken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeechEventToggles {
+    /// Focus moves (navigation echo)
+    pub focus: bool,
+    /// Selection changes
+    pub selection: bool,
+    /// State changes of the focused object
+    pub state_changes: bool,
+    /// Text changes of the focused object
+    pub text_changes: bool,
+    /// Value changes of the focused object
+    pub value_changes: bool,
+    /// Desktop notifications
+    pub notifications: bool,
+    /// Feedback on keyboard interactions such as connect mode
+    pub feedback: bool,
+}
+
+impl SpeechEventToggles {
+    /// Whether announcements of this kind should be spoken
+    pub fn allows(&self, kind: screen_reader::SpeechEventKind) -> bool {
+        use screen_reader::SpeechEventKind;
+        match kind {
+            SpeechEventKind::Focus => self.focus,
+            SpeechEventKind::Selection => self.selection,
+            SpeechEventKind::StateChange => self.state_changes,
+            SpeechEventKind::TextChange => self.text_changes,
+            SpeechEventKind::ValueChange => self.value_changes,
+            SpeechEventKind::Notification => self.notifications,
+            SpeechEventKind::Feedback => self.feedback,
+            SpeechEventKind::System => true,
+        }
+    }
+}
+
+impl Default for SpeechEventToggles {
+    fn default() -> Self {
+        Self {
+            focus: true,
+            selection: true,
+            state_changes: true,
+            text_changes: true,
+            value_changes: true,
+            notifications: true,
+            feedback: true,
+        }
+    }
 }
 
 /// Color blind accessibility modes
@@ -300,14 +363,18 @@ pub enum AccessibilityEvent {
 impl AccessibilityManager {
     /// Create a new accessibility manager
     pub fn new(settings: AccessibilitySettings) -> Result<Self> {
+        let mut screen_reader = screen_reader::ScreenReaderInterface::new()?;
+        screen_reader.update_settings(&settings)?;
+
         Ok(Self {
-            screen_reader: screen_reader::ScreenReaderInterface::new()?,
+            screen_reader,
             keyboard_nav: keyboard_nav::KeyboardNavigator::new(),
             voice_commands: voice_commands::VoiceCommandSystem::new()?,
             magnification: magnification::MagnificationManager::new(),
             contrast: contrast::ContrastManager::new(),
             spatial_audio: spatial_audio::SpatialAudioManager::new()?,
             at_spi: at_spi::AtSpiInterface::new()?,
+            edge_creator: edge_creation::KeyboardEdgeCreator::new(),
             settings,
             node_cache: HashMap::new(),
         })
@@ -343,9 +410,31 @@ impl AccessibilityManager {
         Ok(())
     }
 
+    /// Update the accessibility information of every node of the scene, dropping nodes it no longer holds
+    ///
+    /// Nodes are described from the scene alone; use
+    /// [`update_node_accessibility`](Self::update_node_accessibility) where
+    /// the node implementation is at hand. Relationships follow the edges.
+    pub fn sync_scene(&mut self, scene: &Scene) -> Result<()> {
+        let removed: Vec<SceneId> = self.node_cache.keys().copied().filter(|id| scene.get_node(*id).is_none()).collect();
+        for node_id in removed {
+            self.remove_node_accessibility(node_id)?;
+        }
+        for (node_id, node) in scene.nodes() {
+            let info = self.scene_node_info(node);
+            let changed = self.node_cache.get(node_id).is_none_or(|cached| !self.accessibility_info_equal(cached, &info));
+            if changed {
+                self.at_spi.update_object(&info)?;
+                self.node_cache.insert(*node_id, info);
+            }
+        }
+        self.sync_relationships(scene);
+        Ok(())
+    }
+
     /// Remove accessibility information for a node
     pub fn remove_node_accessibility(&mut self, node_id: SceneId) -> Result<()> {
-        if let Some(info) = self.node_cache.remove(&node_id) {
+        if self.node_cache.remove(&node_id).is_some() {
             // Remove from AT-SPI
             self.at_spi.remove_object(node_id)?;
             
@@ -362,9 +451,15 @@ impl AccessibilityManager {
         // Fire AT-SPI events
         self.at_spi.fire_event(&event)?;
         
-        // Update screen reader
+        // Update screen reader, describing focused nodes it knows
         if self.settings.screen_reader_enabled {
-            self.screen_reader.handle_event(&event)?;
+            match &event {
+                AccessibilityEvent::FocusChanged { old_focus, new_focus: Some(new_focus) } if self.node_cache.contains_key(new_focus) => {
+                    let from = old_focus.and_then(|id| self.node_cache.get(&id));
+                    self.screen_reader.focus_object(&self.node_cache[new_focus], from)?;
+                }
+                _ => self.screen_reader.handle_event(&event)?,
+            }
         }
         
         // Update spatial audio
@@ -380,6 +475,93 @@ impl AccessibilityManager {
         Ok(())
     }
 
+    /// Start the next queued announcement once the current one was spoken
+    ///
+    /// Call regularly, e.g. once per frame.
+    pub fn poll_speech(&mut self) -> Result<()> {
+        if self.settings.screen_reader_enabled {
+            self.screen_reader.poll_speech()?;
+        }
+        Ok(())
+    }
+
+    /// Move keyboard focus to a node, e.g. after pointer or graph-interaction navigation, and announce it
+    pub fn set_focus(&mut self, node_id: Option<SceneId>) -> Result<()> {
+        let old_focus = self.keyboard_nav.get_focus();
+        if old_focus == node_id {
+            return Ok(());
+        }
+        self.keyboard_nav.set_focus(node_id)?;
+        self.handle_event(AccessibilityEvent::FocusChanged { old_focus, new_focus: node_id })
+    }
+
+    /// Record the edges of the scene as relationships, for relationship navigation and announcements
+    pub fn sync_relationships(&mut self, scene: &Scene) {
+        for info in self.node_cache.values_mut() {
+            info.relationships.clear();
+        }
+        for edge in scene.edges() {
+            if let Some(source) = self.node_cache.get_mut(&edge.source) {
+                source.relationships.push(AccessibleRelationship { relation_type: RelationType::FlowsTo, target: edge.target });
+            }
+            if let Some(target) = self.node_cache.get_mut(&edge.target) {
+                target.relationships.push(AccessibleRelationship { relation_type: RelationType::FlowsFrom, target: edge.source });
+            }
+        }
+    }
+
+    /// Announce text not tied to a node through the screen reader and AT-SPI
+    ///
+    /// `braille` holds the lines braille displays show instead of the
+    /// spoken text; urgent announcements interrupt current speech.
+    pub fn announce(&mut self, text: &str, braille: Vec<String>, urgent: bool) -> Result<()> {
+        if self.settings.screen_reader_enabled {
+            let priority = if urgent { screen_reader::SpeechPriority::Critical } else { screen_reader::SpeechPriority::Normal };
+            self.screen_reader.announce(text, priority)?;
+        }
+        self.at_spi.announce(at_spi::AtSpiAnnouncement {
+            text: text.to_string(),
+            braille,
+            politeness: if urgent { at_spi::AnnouncementPoliteness::Assertive } else { at_spi::AnnouncementPoliteness::Polite },
+        })
+    }
+
+    /// Announce a notification and post it to the AT-SPI live region
+    ///
+    /// Unlike [`announce`](Self::announce), the notification stays in the
+    /// live region, so screen reader users can navigate back to it.
+    pub fn announce_notification(&mut self, text: &str, braille: Vec<String>, urgent: bool) -> Result<()> {
+        if self.settings.screen_reader_enabled {
+            let priority = if urgent { screen_reader::SpeechPriority::Critical } else { screen_reader::SpeechPriority::Normal };
+            self.screen_reader.announce_as(text, priority, screen_reader::SpeechEventKind::Notification)?;
+        }
+        self.at_spi.post_notification(at_spi::AtSpiAnnouncement {
+            text: text.to_string(),
+            braille,
+            politeness: if urgent { at_spi::AnnouncementPoliteness::Assertive } else { at_spi::AnnouncementPoliteness::Polite },
+        })
+    }
+
+    /// Export the desktop structure to AT-SPI as workspaces, clusters, nodes and actions
+    ///
+    /// Call after workspaces, clusters or nodes change; nodes need their
+    /// accessibility information updated first.
+    pub fn export_tree(&mut self, workspaces: &[Workspace], active_workspace: Option<&str>, clusters: &[Cluster]) -> Result<()> {
+        let app_name = self.at_spi.application_name().to_string();
+        let tree = accessible_tree::AccessibleTree::build(&app_name, workspaces, active_workspace, clusters, &self.node_cache);
+        self.at_spi.export_tree(tree)
+    }
+
+    /// Start sending AT-SPI events, once the accessibility bus is connected
+    pub fn enable_at_spi(&mut self) -> Result<()> {
+        self.at_spi.enable()
+    }
+
+    /// Take the AT-SPI events not yet sent on the accessibility bus
+    pub fn take_at_spi_signals(&mut self) -> Vec<at_spi::AtSpiSignal> {
+        self.at_spi.take_signals()
+    }
+
     /// Get accessible node information
     pub fn get_node_info(&self, node_id: SceneId) -> Option<&NodeAccessibilityInfo> {
         self.node_cache.get(&node_id)
@@ -394,11 +576,62 @@ impl AccessibilityManager {
             .collect()
     }
 
+    /// Nodes voice commands can name, with the keyboard focus
+    ///
+    /// Callers add the workspaces and selection before handing it to
+    /// [`voice_commands`](Self::voice_commands).
+    pub fn voice_vocabulary(&self) -> voice_grammar::GraphVocabulary {
+        let mut vocabulary = voice_grammar::GraphVocabulary::new();
+        for (node_id, info) in &self.node_cache {
+            vocabulary.add_node(*node_id, info.name.clone());
+        }
+        vocabulary.focus = self.keyboard_nav.get_focus();
+        vocabulary
+    }
+
     /// Get navigation suggestions for keyboard navigation
     pub fn get_navigation_suggestions(&self, current_node: SceneId) -> Vec<SceneId> {
         self.keyboard_nav.get_navigation_suggestions(current_node, &self.node_cache)
     }
 
+    /// Handle a key press, giving connect mode first claim on the key
+    ///
+    /// Returns the edge the user confirmed, already added to the edge
+    /// manager; the caller is responsible for showing it in the scene.
+    pub fn handle_key_press(
+        &mut self,
+        key: keyboard_nav::Key,
+        modifiers: Vec<keyboard_nav::Modifier>,
+        context: edge_creation::EdgeContext<'_>,
+    ) -> Result<Option<horizonos_graph_interaction::CreatedEdge>> {
+        let old_focus = self.keyboard_nav.get_focus();
+
+        let step = self.edge_creator.handle_key_press(
+            key.clone(),
+            modifiers.clone(),
+            &mut self.keyboard_nav,
+            &self.node_cache,
+            context,
+        )?;
+
+        if !step.handled {
+            self.keyboard_nav.handle_key_press(key, modifiers, &self.node_cache)?;
+        }
+
+        let new_focus = self.keyboard_nav.get_focus();
+        if new_focus != old_focus {
+            self.handle_event(AccessibilityEvent::FocusChanged { old_focus, new_focus })?;
+        }
+
+        if let Some(announcement) = &step.announcement {
+            if self.settings.screen_reader_enabled {
+                self.screen_reader.announce_as(announcement, screen_reader::SpeechPriority::High, screen_reader::SpeechEventKind::Feedback)?;
+            }
+        }
+
+        Ok(step.confirmed)
+    }
+
     /// Update accessibility settings
     pub fn update_settings(&mut self, settings: AccessibilitySettings) -> Result<()> {
         let old_settings = self.settings.clone();
@@ -422,6 +655,7 @@ impl AccessibilityManager {
         }
 
         // Update other subsystems as needed
+        self.screen_reader.update_settings(&self.settings)?;
         self.magnification.update_settings(&self.settings)?;
         self.contrast.update_settings(&self.settings)?;
         self.spatial_audio.update_settings(&self.settings)?;
@@ -429,6 +663,12 @@ impl AccessibilityManager {
         Ok(())
     }
 
+    /// Apply settings the renderer handles, e.g. the text scale for all
+    /// labels and badges
+    pub fn apply_to_engine(&self, engine: &mut GraphEngine) {
+        engine.set_text_scale(self.settings.text_scale);
+    }
+
     /// Create accessibility information from a graph node
     fn create_accessibility_info(
         &self,
@@ -436,29 +676,11 @@ impl AccessibilityManager {
         node: &dyn GraphNode,
         bounds: AccessibleBounds,
     ) -> Result<NodeAccessibilityInfo> {
-        let node_type = node.node_type();
         let metadata = node.metadata();
-        
-        // Determine accessible role based on node type
-        let role = match node_type {
-            horizonos_graph_nodes::NodeType::Application { .. } => AccessibleRole::Application,
-            horizonos_graph_nodes::NodeType::File { .. } => AccessibleRole::Document,
-            horizonos_graph_nodes::NodeType::Person { .. } => AccessibleRole::ListItem,
-            horizonos_graph_nodes::NodeType::Task { .. } => AccessibleRole::CheckBox,
-            horizonos_graph_nodes::NodeType::Device { .. } => AccessibleRole::ListItem,
-            horizonos_graph_nodes::NodeType::AIAgent { .. } => AccessibleRole::GenericObject,
-            horizonos_graph_nodes::NodeType::System { .. } => AccessibleRole::ListItem,
-            horizonos_graph_nodes::NodeType::URL { .. } => AccessibleRole::Link,
-            horizonos_graph_nodes::NodeType::Automation { .. } => AccessibleRole::ListItem,
-            horizonos_graph_nodes::NodeType::Setting { .. } => AccessibleRole::CheckBox,
-            horizonos_graph_nodes::NodeType::ConfigGroup { .. } => AccessibleRole::TreeItem,
-            horizonos_graph_nodes::NodeType::Concept { .. } => AccessibleRole::GenericObject,
-        };
+        let role = accessible_role(&node.node_type());
 
-        // Create name from node type and metadata
-        let name = metadata.description
-            .clone()
-            .unwrap_or_else(|| format!("{:?}", node_type));
+        // The display name is what users see and say
+        let name = node.display_name();
 
         // Create accessible actions based on node type
         let actions = vec![
@@ -486,6 +708,43 @@ impl AccessibilityManager {
         })
     }
 
+    /// Accessibility information of a scene node without a node implementation
+    fn scene_node_info(&self, node: &SceneNode) -> NodeAccessibilityInfo {
+        let radius = node.radius.max(0.0);
+        let mut info = NodeAccessibilityInfo {
+            node_id: node.id,
+            name: SearchDocument::from_scene_node(node).display_name,
+            description: node.metadata.description.clone(),
+            role: accessible_role(&node.node_type),
+            state: AccessibleState {
+                enabled: true,
+                visible: node.visible,
+                selected: node.selected,
+                ..Default::default()
+            },
+            actions: vec![
+                AccessibleAction::Activate,
+                AccessibleAction::Focus,
+                AccessibleAction::Select,
+                AccessibleAction::ShowContextMenu,
+            ],
+            relationships: Vec::new(),
+            bounds: AccessibleBounds {
+                x: node.position.x - radius,
+                y: node.position.y - radius,
+                width: radius * 2.0,
+                height: radius * 2.0,
+            },
+            text_content: None,
+            value: None,
+        };
+        if let Some(cached) = self.node_cache.get(&node.id) {
+            info.state.focused = cached.state.focused;
+            info.relationships = cached.relationships.clone();
+        }
+        info
+    }
+
     /// Check if accessibility information has changed
     fn accessibility_info_equal(&self, a: &NodeAccessibilityInfo, b: &NodeAccessibilityInfo) -> bool {
         a.name == b.name &&
@@ -535,10 +794,31 @@ impl Default for AccessibilitySettings {
             color_blind_mode: ColorBlindMode::None,
             speech_rate: 200,
             speech_volume: 0.8,
+            verbosity: screen_reader::VerbosityProfile::default(),
+            speech_events: SpeechEventToggles::default(),
         }
     }
 }
 
+/// Accessible role of a node type
+fn accessible_role(node_type: &NodeType) -> AccessibleRole {
+    match node_type {
+        NodeType::Application { .. } => AccessibleRole::Application,
+        NodeType::File { .. } => AccessibleRole::Document,
+        NodeType::Person { .. } => AccessibleRole::ListItem,
+        NodeType::Task { .. } => AccessibleRole::CheckBox,
+        NodeType::Device { .. } => AccessibleRole::ListItem,
+        NodeType::AIAgent { .. } => AccessibleRole::GenericObject,
+        NodeType::System { .. } => AccessibleRole::ListItem,
+        NodeType::URL { .. } => AccessibleRole::Link,
+        NodeType::Automation { .. } => AccessibleRole::ListItem,
+        NodeType::Setting { .. } => AccessibleRole::CheckBox,
+        NodeType::ConfigGroup { .. } => AccessibleRole::TreeItem,
+        NodeType::Group { .. } => AccessibleRole::TreeItem,
+        NodeType::Concept { .. } => AccessibleRole::GenericObject,
+    }
+}
+
 /// Error types for accessibility system
 #[derive(Debug, thiserror::Error)]
 pub enum AccessibilityError {
@@ -402,12 +402,8 @@ impl MagnificationManager {
             }
         }
 
-        // Update text scaling
-        if settings.text_scale != 1.0 {
-            // Apply text scaling to magnification
-            let text_magnification = settings.text_scale;
-            // TODO: Implement text-specific magnification
-        }
+        // Text scaling is applied by the renderer to text alone, see
+        // `AccessibilityManager::apply_to_engine`, so it does not magnify
 
         Ok(())
     }
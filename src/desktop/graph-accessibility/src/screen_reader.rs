@@ -1,9 +1,10 @@
 //! Screen reader interface for graph desktop accessibility
 
-use crate::{NodeAccessibilityInfo, AccessibilityEvent, AccessibilitySettings};
+use crate::{NodeAccessibilityInfo, AccessibilityEvent, AccessibilitySettings, SpeechEventToggles};
 use horizonos_graph_engine::SceneId;
 use anyhow::Result;
 use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
 /// Screen reader interface providing text-to-speech and navigation
@@ -11,14 +12,20 @@ use std::sync::{Arc, Mutex};
 pub struct ScreenReaderInterface {
     /// Speech synthesis engine
     speech_engine: SpeechEngine,
-    /// Reading queue
+    /// Reading queue, highest priority first
     reading_queue: Arc<Mutex<VecDeque<SpeechItem>>>,
+    /// Item the speech engine is speaking
+    current_item: Option<SpeechItem>,
     /// Current reading state
     state: ScreenReaderState,
     /// Navigation history
     navigation_history: VecDeque<SceneId>,
     /// Reading mode
     reading_mode: ReadingMode,
+    /// How much to say about each object
+    verbosity: VerbosityProfile,
+    /// Which kinds of events to announce
+    event_toggles: SpeechEventToggles,
     /// Enabled state
     enabled: bool,
 }
@@ -34,6 +41,8 @@ pub struct SpeechEngine {
     voice: String,
     /// Available voices
     available_voices: Vec<String>,
+    /// Set by the synthesizer when the current utterance finished
+    utterance_done: Arc<AtomicBool>,
 }
 
 /// Screen reader state
@@ -58,6 +67,8 @@ pub struct SpeechItem {
     priority: SpeechPriority,
     /// Associated node (if any)
     node_id: Option<SceneId>,
+    /// What caused the announcement
+    kind: SpeechEventKind,
     /// Speech properties
     properties: SpeechProperties,
 }
@@ -75,6 +86,46 @@ pub enum SpeechPriority {
     Critical,
 }
 
+/// What caused an announcement, for per-event toggles and interrupt rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpeechEventKind {
+    /// Focus moved to an object
+    Focus,
+    /// Selection changed
+    Selection,
+    /// State of the focused object changed
+    StateChange,
+    /// Text of the focused object changed
+    TextChange,
+    /// Value of the focused object changed
+    ValueChange,
+    /// Desktop notification
+    Notification,
+    /// Feedback on a keyboard interaction
+    Feedback,
+    /// Screen reader status and command responses
+    System,
+}
+
+impl SpeechEventKind {
+    /// Navigation echo goes stale as soon as the user moves on
+    pub fn is_navigation_echo(self) -> bool {
+        matches!(self, Self::Focus | Self::Selection)
+    }
+}
+
+/// How much the screen reader says about each object
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VerbosityProfile {
+    /// Names and values only; low priority speech is dropped
+    Brief,
+    /// Role, name, state and value
+    #[default]
+    Normal,
+    /// Everything, including descriptions and relationships
+    Verbose,
+}
+
 /// Speech properties for customization
 #[derive(Debug, Clone)]
 pub struct SpeechProperties {
@@ -184,6 +235,7 @@ impl ScreenReaderInterface {
         Ok(Self {
             speech_engine,
             reading_queue: Arc::new(Mutex::new(VecDeque::new())),
+            current_item: None,
             state: ScreenReaderState {
                 current_focus: None,
                 reading_position: 0,
@@ -192,6 +244,8 @@ impl ScreenReaderInterface {
             },
             navigation_history: VecDeque::new(),
             reading_mode: ReadingMode::OnFocus,
+            verbosity: VerbosityProfile::default(),
+            event_toggles: SpeechEventToggles::default(),
             enabled: false,
         })
     }
@@ -207,6 +261,7 @@ impl ScreenReaderInterface {
         self.speak_text(
             "HorizonOS Graph Desktop screen reader activated".to_string(),
             SpeechPriority::High,
+            SpeechEventKind::System,
             None,
         )?;
         
@@ -223,11 +278,45 @@ impl ScreenReaderInterface {
         
         // Clear reading queue
         self.reading_queue.lock().unwrap().clear();
+        self.current_item = None;
+        self.state.speaking = false;
         
         log::info!("Screen reader disabled");
         Ok(())
     }
 
+    /// Apply the verbosity, per-event toggles and voice settings
+    pub fn update_settings(&mut self, settings: &AccessibilitySettings) -> Result<()> {
+        self.verbosity = settings.verbosity;
+        self.event_toggles = settings.speech_events;
+        self.speech_engine.rate = settings.speech_rate.clamp(100, 400);
+        self.speech_engine.volume = settings.speech_volume.clamp(0.0, 1.0);
+
+        // Drop queued speech the new settings no longer allow
+        let toggles = self.event_toggles;
+        let brief = self.verbosity == VerbosityProfile::Brief;
+        self.reading_queue.lock().unwrap().retain(|item| {
+            toggles.allows(item.kind) && !(brief && item.priority == SpeechPriority::Low)
+        });
+
+        Ok(())
+    }
+
+    /// Current verbosity profile
+    pub fn verbosity(&self) -> VerbosityProfile {
+        self.verbosity
+    }
+
+    /// Text the speech engine is speaking, if any
+    pub fn current_utterance(&self) -> Option<&str> {
+        self.current_item.as_ref().map(|item| item.text.as_str())
+    }
+
+    /// Texts waiting to be spoken, in speaking order
+    pub fn queued_utterances(&self) -> Vec<String> {
+        self.reading_queue.lock().unwrap().iter().map(|item| item.text.clone()).collect()
+    }
+
     /// Update object accessibility information
     pub fn update_object(&mut self, info: &NodeAccessibilityInfo) -> Result<()> {
         if !self.enabled {
@@ -254,6 +343,7 @@ impl ScreenReaderInterface {
             self.speak_text(
                 "Object removed".to_string(),
                 SpeechPriority::High,
+                SpeechEventKind::Focus,
                 None,
             )?;
         }
@@ -276,14 +366,15 @@ impl ScreenReaderInterface {
                     self.speak_text(
                         "Selected".to_string(),
                         SpeechPriority::Normal,
+                        SpeechEventKind::Selection,
                         Some(selected_nodes[0]),
                     )?;
                 } else if selected_nodes.len() > 1 {
-                    self.speak_text(
-                        format!("{} items selected", selected_nodes.len()),
-                        SpeechPriority::Normal,
-                        None,
-                    )?;
+                    let text = match self.verbosity {
+                        VerbosityProfile::Brief => format!("{} selected", selected_nodes.len()),
+                        _ => format!("{} items selected", selected_nodes.len()),
+                    };
+                    self.speak_text(text, SpeechPriority::Normal, SpeechEventKind::Selection, None)?;
                 }
             }
             AccessibilityEvent::StateChanged { node_id, new_state, .. } => {
@@ -293,11 +384,11 @@ impl ScreenReaderInterface {
             }
             AccessibilityEvent::TextChanged { node_id, new_text, .. } => {
                 if self.state.current_focus == Some(*node_id) {
-                    self.speak_text(
-                        format!("Text changed to: {}", new_text),
-                        SpeechPriority::High,
-                        Some(*node_id),
-                    )?;
+                    let text = match self.verbosity {
+                        VerbosityProfile::Brief => new_text.clone(),
+                        _ => format!("Text changed to: {}", new_text),
+                    };
+                    self.speak_text(text, SpeechPriority::High, SpeechEventKind::TextChange, Some(*node_id))?;
                 }
             }
             AccessibilityEvent::ValueChanged { node_id, new_value, .. } => {
@@ -306,11 +397,11 @@ impl ScreenReaderInterface {
                         let text = value.text.as_ref()
                             .cloned()
                             .unwrap_or_else(|| value.current.to_string());
-                        self.speak_text(
-                            format!("Value: {}", text),
-                            SpeechPriority::High,
-                            Some(*node_id),
-                        )?;
+                        let text = match self.verbosity {
+                            VerbosityProfile::Brief => text,
+                            _ => format!("Value: {}", text),
+                        };
+                        self.speak_text(text, SpeechPriority::High, SpeechEventKind::ValueChange, Some(*node_id))?;
                     }
                 }
             }
@@ -332,6 +423,7 @@ impl ScreenReaderInterface {
                     self.speak_text(
                         "Reading current object".to_string(),
                         SpeechPriority::High,
+                        SpeechEventKind::System,
                         Some(focus),
                     )?;
                 }
@@ -339,6 +431,7 @@ impl ScreenReaderInterface {
             ScreenReaderCommand::Stop => {
                 self.speech_engine.stop()?;
                 self.reading_queue.lock().unwrap().clear();
+                self.current_item = None;
                 self.state.speaking = false;
                 self.state.paused = false;
             }
@@ -358,6 +451,7 @@ impl ScreenReaderInterface {
                 self.speak_text(
                     format!("Reading speed: {} words per minute", self.speech_engine.rate),
                     SpeechPriority::High,
+                    SpeechEventKind::System,
                     None,
                 )?;
             }
@@ -366,6 +460,7 @@ impl ScreenReaderInterface {
                 self.speak_text(
                     format!("Reading speed: {} words per minute", self.speech_engine.rate),
                     SpeechPriority::High,
+                    SpeechEventKind::System,
                     None,
                 )?;
             }
@@ -381,9 +476,82 @@ impl ScreenReaderInterface {
         Ok(())
     }
 
-    /// Handle focus change
-    fn handle_focus_change(&mut self, new_focus: Option<SceneId>) -> Result<()> {
-        // Update navigation history
+    /// Queue an announcement that is not tied to an accessibility event
+    pub fn announce(&mut self, text: &str, priority: SpeechPriority) -> Result<()> {
+        self.announce_as(text, priority, SpeechEventKind::System)
+    }
+
+    /// Queue an announcement of the given kind, honouring its event toggle
+    pub fn announce_as(&mut self, text: &str, priority: SpeechPriority, kind: SpeechEventKind) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        self.speak_text(text.to_string(), priority, kind, self.state.current_focus)
+    }
+
+    /// Tell the screen reader the speech engine finished the current utterance
+    ///
+    /// Starts the next queued item, if any.
+    pub fn speech_finished(&mut self) -> Result<()> {
+        self.current_item = None;
+        self.process_speech_queue()
+    }
+
+    /// Move on to the next queued item once the speech engine finished the current one
+    ///
+    /// Call regularly, e.g. once per frame.
+    pub fn poll_speech(&mut self) -> Result<()> {
+        if self.state.paused || self.current_item.is_none() {
+            return Ok(());
+        }
+        if self.speech_engine.take_finished() {
+            self.speech_finished()?;
+        }
+        Ok(())
+    }
+
+    /// Move focus to an object and describe it, including how it relates to the object focus came from
+    pub fn focus_object(&mut self, info: &NodeAccessibilityInfo, from: Option<&NodeAccessibilityInfo>) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        self.record_focus(Some(info.node_id));
+        if !matches!(self.reading_mode, ReadingMode::Automatic | ReadingMode::OnFocus) {
+            return Ok(());
+        }
+
+        let mut text_parts = vec![info.name.clone()];
+        if self.verbosity != VerbosityProfile::Brief {
+            text_parts.push(format!("{:?}", info.role));
+            match info.relationships.len() {
+                0 => {}
+                1 => text_parts.push("1 related".to_string()),
+                n => text_parts.push(format!("{} related", n)),
+            }
+            if let Some(from) = from {
+                let linked = info.relationships.iter().any(|r| r.target == from.node_id)
+                    || from.relationships.iter().any(|r| r.target == info.node_id);
+                if linked {
+                    text_parts.push(format!("linked with {}", from.name));
+                }
+            }
+        }
+        if info.state.selected {
+            text_parts.push("selected".to_string());
+        }
+        if self.verbosity == VerbosityProfile::Verbose {
+            if let Some(description) = &info.description {
+                text_parts.push(description.clone());
+            }
+        }
+
+        self.speak_text(text_parts.join(", "), SpeechPriority::High, SpeechEventKind::Focus, Some(info.node_id))
+    }
+
+    /// Remember the focused object, keeping the previous one in the history
+    fn record_focus(&mut self, new_focus: Option<SceneId>) {
         if let Some(old_focus) = self.state.current_focus {
             self.navigation_history.push_back(old_focus);
             if self.navigation_history.len() > 50 {
@@ -392,6 +560,11 @@ impl ScreenReaderInterface {
         }
 
         self.state.current_focus = new_focus;
+    }
+
+    /// Handle focus change
+    fn handle_focus_change(&mut self, new_focus: Option<SceneId>) -> Result<()> {
+        self.record_focus(new_focus);
 
         // Announce new focus
         if let Some(focus_id) = new_focus {
@@ -400,6 +573,7 @@ impl ScreenReaderInterface {
                     self.speak_text(
                         "Focused".to_string(),
                         SpeechPriority::High,
+                        SpeechEventKind::Focus,
                         Some(focus_id),
                     )?;
                 }
@@ -413,16 +587,21 @@ impl ScreenReaderInterface {
     /// Read an accessible object
     fn read_object(&mut self, info: &NodeAccessibilityInfo) -> Result<()> {
         let mut text_parts = Vec::new();
+        let brief = self.verbosity == VerbosityProfile::Brief;
 
-        // Start with role
-        text_parts.push(format!("{:?}", info.role));
+        // Start with role, which brief speech leaves out
+        if !brief {
+            text_parts.push(format!("{:?}", info.role));
+        }
 
         // Add name
         text_parts.push(info.name.clone());
 
-        // Add description if available
-        if let Some(description) = &info.description {
-            text_parts.push(description.clone());
+        // Add description if the profile asks for it
+        if self.verbosity == VerbosityProfile::Verbose {
+            if let Some(description) = &info.description {
+                text_parts.push(description.clone());
+            }
         }
 
         // Add state information
@@ -432,10 +611,12 @@ impl ScreenReaderInterface {
         if !info.state.enabled {
             text_parts.push("disabled".to_string());
         }
-        if info.state.expanded == Some(true) {
-            text_parts.push("expanded".to_string());
-        } else if info.state.expanded == Some(false) {
-            text_parts.push("collapsed".to_string());
+        if !brief {
+            if info.state.expanded == Some(true) {
+                text_parts.push("expanded".to_string());
+            } else if info.state.expanded == Some(false) {
+                text_parts.push("collapsed".to_string());
+            }
         }
 
         // Add value if available
@@ -447,7 +628,7 @@ impl ScreenReaderInterface {
         }
 
         let full_text = text_parts.join(", ");
-        self.speak_text(full_text, SpeechPriority::Normal, Some(info.node_id))?;
+        self.speak_text(full_text, SpeechPriority::Normal, SpeechEventKind::StateChange, Some(info.node_id))?;
 
         Ok(())
     }
@@ -472,6 +653,7 @@ impl ScreenReaderInterface {
             self.speak_text(
                 announcements.join(", "),
                 SpeechPriority::High,
+                SpeechEventKind::StateChange,
                 self.state.current_focus,
             )?;
         }
@@ -486,12 +668,14 @@ impl ScreenReaderInterface {
             self.speak_text(
                 "Current position in graph".to_string(),
                 SpeechPriority::High,
+                SpeechEventKind::System,
                 None,
             )?;
         } else {
             self.speak_text(
                 "No object focused".to_string(),
                 SpeechPriority::High,
+                SpeechEventKind::System,
                 None,
             )?;
         }
@@ -499,22 +683,40 @@ impl ScreenReaderInterface {
     }
 
     /// Add text to speech queue
+    ///
+    /// Speech the event toggles or verbosity rule out is dropped. A new
+    /// focus echo replaces stale navigation echo, and critical speech
+    /// interrupts anything less urgent; interrupted navigation echo is
+    /// dropped, other speech is spoken again after the interruption.
     fn speak_text(
         &mut self,
         text: String,
         priority: SpeechPriority,
+        kind: SpeechEventKind,
         node_id: Option<SceneId>,
     ) -> Result<()> {
+        if !self.event_toggles.allows(kind) {
+            return Ok(());
+        }
+        if self.verbosity == VerbosityProfile::Brief && priority == SpeechPriority::Low {
+            return Ok(());
+        }
+
         let speech_item = SpeechItem {
             text,
             priority,
             node_id,
+            kind,
             properties: SpeechProperties::default(),
         };
 
         {
             let mut queue = self.reading_queue.lock().unwrap();
-            
+
+            if kind == SpeechEventKind::Focus {
+                queue.retain(|item| !item.kind.is_navigation_echo());
+            }
+
             // Insert based on priority
             let insert_pos = queue.iter()
                 .position(|item| item.priority < priority)
@@ -523,6 +725,14 @@ impl ScreenReaderInterface {
             queue.insert(insert_pos, speech_item);
         }
 
+        let interrupts = self.current_item.as_ref().is_some_and(|current| {
+            (priority == SpeechPriority::Critical && current.priority < SpeechPriority::Critical)
+                || (kind == SpeechEventKind::Focus && current.kind.is_navigation_echo())
+        });
+        if interrupts {
+            self.interrupt()?;
+        }
+
         // Start speaking if not already
         if !self.state.speaking {
             self.process_speech_queue()?;
@@ -531,13 +741,33 @@ impl ScreenReaderInterface {
         Ok(())
     }
 
+    /// Stop the current utterance so the queue head is spoken next
+    fn interrupt(&mut self) -> Result<()> {
+        self.speech_engine.stop()?;
+        self.state.speaking = false;
+
+        if let Some(interrupted) = self.current_item.take() {
+            if !interrupted.kind.is_navigation_echo() {
+                // Ahead of the speech it was already ahead of
+                let mut queue = self.reading_queue.lock().unwrap();
+                let insert_pos = queue.iter()
+                    .position(|item| item.priority <= interrupted.priority)
+                    .unwrap_or(queue.len());
+                queue.insert(insert_pos, interrupted);
+            }
+        }
+
+        Ok(())
+    }
+
     /// Process the speech queue
     fn process_speech_queue(&mut self) -> Result<()> {
-        let mut queue = self.reading_queue.lock().unwrap();
+        let next = self.reading_queue.lock().unwrap().pop_front();
         
-        if let Some(item) = queue.pop_front() {
+        if let Some(item) = next {
             self.state.speaking = true;
             self.speech_engine.speak(&item.text, &item.properties)?;
+            self.current_item = Some(item);
         } else {
             self.state.speaking = false;
         }
@@ -554,9 +784,20 @@ impl SpeechEngine {
             volume: 0.8,
             voice: "default".to_string(),
             available_voices: vec!["default".to_string()],
+            utterance_done: Arc::new(AtomicBool::new(false)),
         })
     }
 
+    /// Flag for the synthesizer's completion callback to set when an utterance finished
+    pub fn completion_flag(&self) -> Arc<AtomicBool> {
+        self.utterance_done.clone()
+    }
+
+    /// Whether the last utterance finished since the previous call
+    pub fn take_finished(&self) -> bool {
+        self.utterance_done.swap(false, Ordering::AcqRel)
+    }
+
     /// Initialize speech engine
     pub fn initialize(&mut self) -> Result<()> {
         // TODO: Initialize actual TTS engine (espeak, festival, etc.)
@@ -568,6 +809,8 @@ impl SpeechEngine {
     pub fn speak(&mut self, text: &str, properties: &SpeechProperties) -> Result<()> {
         // TODO: Implement actual speech synthesis
         log::info!("Speaking: {}", text);
+        // Until then the utterance is done once it is logged
+        self.utterance_done.store(true, Ordering::Release);
         Ok(())
     }
 
@@ -575,6 +818,8 @@ impl SpeechEngine {
     pub fn stop(&mut self) -> Result<()> {
         // TODO: Stop actual TTS
         log::info!("Speech stopped");
+        // A stopped utterance does not complete
+        self.utterance_done.store(false, Ordering::Release);
         Ok(())
     }
 
@@ -613,4 +858,111 @@ impl Default for ScreenReaderState {
             paused: false,
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reader() -> ScreenReaderInterface {
+        let mut reader = ScreenReaderInterface::new().unwrap();
+        reader.enable().unwrap();
+        reader.poll_speech().unwrap();
+        assert_eq!(reader.current_utterance(), None);
+        reader
+    }
+
+    #[test]
+    fn test_critical_speech_interrupts_and_resumes() {
+        let mut reader = reader();
+        reader.announce("first", SpeechPriority::Normal).unwrap();
+        reader.announce("second", SpeechPriority::Normal).unwrap();
+        reader.announce("help", SpeechPriority::Low).unwrap();
+        assert_eq!(reader.current_utterance(), Some("first"));
+        assert_eq!(reader.queued_utterances(), vec!["second", "help"]);
+
+        // Critical speech cuts in; the interrupted item is spoken again afterwards
+        reader.announce("battery low", SpeechPriority::Critical).unwrap();
+        assert_eq!(reader.current_utterance(), Some("battery low"));
+        assert_eq!(reader.queued_utterances(), vec!["first", "second", "help"]);
+
+        // High priority speech waits its turn but goes ahead of normal speech
+        reader.announce("saved", SpeechPriority::High).unwrap();
+        assert_eq!(reader.current_utterance(), Some("battery low"));
+        assert_eq!(reader.queued_utterances(), vec!["saved", "first", "second", "help"]);
+    }
+
+    #[test]
+    fn test_focus_echo_replaces_stale_navigation_echo() {
+        let mut reader = reader();
+        reader.announce_as("Firefox", SpeechPriority::High, SpeechEventKind::Focus).unwrap();
+        reader.announce_as("Terminal", SpeechPriority::High, SpeechEventKind::Focus).unwrap();
+        reader.announce_as("Notes", SpeechPriority::High, SpeechEventKind::Focus).unwrap();
+        assert_eq!(reader.current_utterance(), Some("Notes"));
+        assert!(reader.queued_utterances().is_empty());
+
+        // Other speech is not navigation echo and is not dropped
+        reader.announce("download finished", SpeechPriority::Normal).unwrap();
+        reader.announce_as("Editor", SpeechPriority::High, SpeechEventKind::Focus).unwrap();
+        assert_eq!(reader.current_utterance(), Some("Editor"));
+        assert_eq!(reader.queued_utterances(), vec!["download finished"]);
+    }
+
+    #[test]
+    fn test_verbosity_and_event_toggles_filter_speech() {
+        let mut reader = reader();
+        reader.announce("busy", SpeechPriority::Normal).unwrap();
+        reader.announce("hint", SpeechPriority::Low).unwrap();
+        reader.announce_as("new mail", SpeechPriority::Normal, SpeechEventKind::Notification).unwrap();
+        assert_eq!(reader.queued_utterances(), vec!["new mail", "hint"]);
+
+        // Brief speech drops low priority items, queued ones included
+        let mut settings = AccessibilitySettings {
+            verbosity: VerbosityProfile::Brief,
+            ..Default::default()
+        };
+        settings.speech_events.notifications = false;
+        reader.update_settings(&settings).unwrap();
+        assert_eq!(reader.verbosity(), VerbosityProfile::Brief);
+        assert!(reader.queued_utterances().is_empty());
+
+        reader.announce("tip", SpeechPriority::Low).unwrap();
+        reader.announce_as("new mail", SpeechPriority::Critical, SpeechEventKind::Notification).unwrap();
+        assert!(reader.queued_utterances().is_empty());
+        assert_eq!(reader.current_utterance(), Some("busy"));
+
+        // Command responses are always spoken
+        reader.announce("Reading speed: 225 words per minute", SpeechPriority::High).unwrap();
+        assert_eq!(reader.queued_utterances(), vec!["Reading speed: 225 words per minute"]);
+    }
+
+    #[test]
+    fn test_queue_drains_as_utterances_finish() {
+        let mut reader = reader();
+        for text in ["one", "two", "three"] {
+            reader.announce(text, SpeechPriority::Normal).unwrap();
+        }
+        assert_eq!(reader.current_utterance(), Some("one"));
+
+        // Nothing advances while paused
+        reader.execute_command(ScreenReaderCommand::TogglePause).unwrap();
+        reader.poll_speech().unwrap();
+        assert_eq!(reader.current_utterance(), Some("one"));
+        reader.execute_command(ScreenReaderCommand::TogglePause).unwrap();
+
+        let mut spoken = Vec::new();
+        while let Some(text) = reader.current_utterance() {
+            spoken.push(text.to_string());
+            reader.poll_speech().unwrap();
+        }
+        assert_eq!(spoken, vec!["one", "two", "three"]);
+        assert!(reader.queued_utterances().is_empty());
+
+        // Speech starts again when something new is queued
+        reader.announce("four", SpeechPriority::Normal).unwrap();
+        assert_eq!(reader.current_utterance(), Some("four"));
+        reader.execute_command(ScreenReaderCommand::Stop).unwrap();
+        assert_eq!(reader.current_utterance(), None);
+        reader.poll_speech().unwrap();
+        assert_eq!(reader.current_utterance(), None);
+    }
+}
@@ -1,9 +1,20 @@
 //! Voice command system for hands-free graph navigation
 
+use crate::voice_grammar::{CommandGrammar, GrammarError, GrammarMatch, GraphVocabulary};
 use anyhow::Result;
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
+/// How long a destructive command waits for "yes" before it is dropped
+pub const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Replies confirming a pending command
+const CONFIRM_WORDS: &[&str] = &["yes", "yeah", "yep", "confirm", "do it", "ok", "okay", "sure"];
+
+/// Replies cancelling a pending command
+const CANCEL_WORDS: &[&str] = &["no", "nope", "cancel", "stop", "never mind", "don't"];
+
 /// Voice command recognition and processing system
 #[derive(Debug)]
 pub struct VoiceCommandSystem {
@@ -21,10 +32,36 @@ pub struct VoiceCommandSystem {
     command_sender: mpsc::UnboundedSender<VoiceCommandEvent>,
     /// Command receiver
     command_receiver: mpsc::UnboundedReceiver<VoiceCommandEvent>,
+    /// Grammar for graph commands
+    grammar: CommandGrammar,
+    /// Nodes and workspaces graph commands can name
+    vocabulary: GraphVocabulary,
+    /// Destructive command waiting for confirmation
+    pending: Option<PendingCommand>,
     /// Enabled state
     enabled: bool,
 }
 
+/// A destructive command waiting for a spoken "yes"
+#[derive(Debug, Clone)]
+pub struct PendingCommand {
+    pub command: GrammarMatch,
+    /// Question read to the user
+    pub prompt: String,
+    pub asked_at: Instant,
+}
+
+/// What an utterance did
+#[derive(Debug, Clone)]
+pub enum VoiceOutcome {
+    /// A graph command to run
+    Execute(GrammarMatch),
+    /// A destructive command waits for confirmation
+    ConfirmationRequested { prompt: String },
+    /// The pending command was cancelled
+    Cancelled,
+}
+
 /// Voice recognizer for speech-to-text
 #[derive(Debug)]
 pub struct VoiceRecognizer {
@@ -333,6 +370,14 @@ pub enum VoiceCommandEvent {
         command: String,
         success: bool,
     },
+    /// A graph command was understood and, if destructive, confirmed
+    GraphCommand(GrammarMatch),
+    /// A destructive command waits for the user to say yes
+    ConfirmationRequested { prompt: String },
+    /// The user cancelled a pending command
+    ConfirmationCancelled,
+    /// The utterance named something that could not be found
+    CommandNotUnderstood { text: String, reason: String },
 }
 
 /// Classification model stub
@@ -396,6 +441,9 @@ impl VoiceCommandSystem {
             audio_stream: None,
             command_sender,
             command_receiver,
+            grammar: CommandGrammar::default(),
+            vocabulary: GraphVocabulary::default(),
+            pending: None,
             enabled: false,
         };
         
@@ -437,23 +485,104 @@ impl VoiceCommandSystem {
         let recognition_result = self.recognizer.recognize(audio_data).await?;
         
         if let Some(text) = recognition_result.text {
-            // Process recognized text
-            if let Some(command) = self.processor.process_text(&text, &self.commands)? {
-                // Send command event
-                self.command_sender.send(VoiceCommandEvent::CommandRecognized {
-                    command: command.clone(),
-                    confidence: recognition_result.confidence,
-                    text,
+            self.process_text(&text, recognition_result.confidence).await?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Process recognized text
+    ///
+    /// Graph commands and confirmations are sent as events for the desktop
+    /// to run; other text falls back to the registered trigger phrases.
+    pub async fn process_text(&mut self, text: &str, confidence: f32) -> Result<()> {
+        self.recognition_state.last_text = Some(text.to_string());
+        
+        match self.interpret(text) {
+            Ok(VoiceOutcome::Execute(found)) => {
+                self.command_sender.send(VoiceCommandEvent::GraphCommand(found))?;
+                return Ok(());
+            }
+            Ok(VoiceOutcome::ConfirmationRequested { prompt }) => {
+                self.command_sender.send(VoiceCommandEvent::ConfirmationRequested { prompt })?;
+                return Ok(());
+            }
+            Ok(VoiceOutcome::Cancelled) => {
+                self.command_sender.send(VoiceCommandEvent::ConfirmationCancelled)?;
+                return Ok(());
+            }
+            Err(GrammarError::NoMatch(_)) => {}
+            Err(e) => {
+                self.command_sender.send(VoiceCommandEvent::CommandNotUnderstood {
+                    text: text.to_string(),
+                    reason: e.to_string(),
                 })?;
-                
-                // Execute command
-                self.execute_command(&command).await?;
+                return Ok(());
             }
         }
         
+        // Process recognized text
+        if let Some(command) = self.processor.process_text(text, &self.commands)? {
+            // Send command event
+            self.command_sender.send(VoiceCommandEvent::CommandRecognized {
+                command: command.clone(),
+                confidence,
+                text: text.to_string(),
+            })?;
+            
+            // Execute command
+            self.execute_command(&command).await?;
+        }
+        
         Ok(())
     }
     
+    /// Interpret an utterance with the graph command grammar
+    ///
+    /// Destructive commands are held until the next utterance confirms
+    /// them; anything other than a yes or no drops the pending command.
+    pub fn interpret(&mut self, text: &str) -> std::result::Result<VoiceOutcome, GrammarError> {
+        if let Some(pending) = self.pending.take() {
+            let reply = text.trim().trim_end_matches(['.', '!']).to_lowercase();
+            if pending.asked_at.elapsed() <= CONFIRMATION_TIMEOUT {
+                if CONFIRM_WORDS.contains(&reply.as_str()) {
+                    return Ok(VoiceOutcome::Execute(pending.command));
+                }
+                if CANCEL_WORDS.contains(&reply.as_str()) {
+                    return Ok(VoiceOutcome::Cancelled);
+                }
+            }
+        }
+        
+        let found = self.grammar.parse(text, &self.vocabulary)?;
+        if !found.command.is_destructive() {
+            return Ok(VoiceOutcome::Execute(found));
+        }
+        let prompt = format!("{}? Say yes to confirm.", found.summary);
+        self.pending = Some(PendingCommand { command: found, prompt: prompt.clone(), asked_at: Instant::now() });
+        Ok(VoiceOutcome::ConfirmationRequested { prompt })
+    }
+    
+    /// Command waiting for confirmation, if any
+    pub fn pending_command(&self) -> Option<&PendingCommand> {
+        self.pending.as_ref().filter(|pending| pending.asked_at.elapsed() <= CONFIRMATION_TIMEOUT)
+    }
+    
+    /// Replace the nodes and workspaces graph commands can name
+    pub fn set_vocabulary(&mut self, vocabulary: GraphVocabulary) {
+        self.vocabulary = vocabulary;
+    }
+    
+    /// Grammar for graph commands, e.g. to add phrases
+    pub fn grammar_mut(&mut self) -> &mut CommandGrammar {
+        &mut self.grammar
+    }
+    
+    /// Next event for the desktop, if any
+    pub fn try_next_event(&mut self) -> Option<VoiceCommandEvent> {
+        self.command_receiver.try_recv().ok()
+    }
+    
     /// Start voice recognition
     fn start_recognition(&mut self) -> Result<()> {
         self.recognition_state.listening = true;
@@ -0,0 +1,574 @@
+//! Command grammar connecting voice commands to the graph
+//!
+//! Utterances such as "open firefox node", "connect selection to project
+//! alpha" or "switch to workspace personal" are matched against phrase
+//! patterns, and the names they mention are resolved against a
+//! [`GraphVocabulary`] of the nodes and workspaces currently on the desktop.
+//! The result is a [`GraphCommand`]: node actions for the node manager or a
+//! [`WorkspaceCommand`] for the workspace manager.
+//!
+//! Patterns are written as words with slots: `{nodes}` is one node or the
+//! selection, `{node}` exactly one node, `{workspace}` a workspace and
+//! `{name}` free text. A trailing `?` makes a word optional, as in
+//! `create a? workspace called? {name}`.
+
+use horizonos_graph_engine::{EdgeType, SceneId};
+use horizonos_graph_nodes::NodeAction;
+use horizonos_graph_workspaces::{WorkspaceError, WorkspaceService};
+
+/// Words naming the selected nodes, or the focused node without a selection
+const SELECTION_WORDS: &[&str] = &["selection", "selected", "selected nodes", "this", "it", "these", "them"];
+
+/// Words dropped from the start of a slot, e.g. "the" in "open the terminal"
+const LEADING_FILLERS: &[&str] = &["the", "a", "an", "my"];
+
+/// Nodes and workspaces the grammar can refer to
+#[derive(Debug, Clone, Default)]
+pub struct GraphVocabulary {
+    /// Node IDs with their spoken names
+    pub nodes: Vec<(SceneId, String)>,
+    /// Workspace IDs with their names
+    pub workspaces: Vec<(String, String)>,
+    /// Currently selected nodes
+    pub selection: Vec<SceneId>,
+    /// Node with keyboard focus
+    pub focus: Option<SceneId>,
+}
+
+impl GraphVocabulary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_node(&mut self, node_id: SceneId, name: impl Into<String>) {
+        self.nodes.push((node_id, name.into()));
+    }
+
+    pub fn add_workspace(&mut self, workspace_id: impl Into<String>, name: impl Into<String>) {
+        self.workspaces.push((workspace_id.into(), name.into()));
+    }
+
+    /// Add the workspaces known to a workspace service
+    pub fn with_workspaces(mut self, service: &dyn WorkspaceService) -> Self {
+        for info in service.list_workspaces() {
+            self.add_workspace(info.id, info.name);
+        }
+        self
+    }
+
+    pub fn with_selection(mut self, selection: Vec<SceneId>) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Spoken name of a node
+    pub fn node_name(&self, node_id: SceneId) -> String {
+        self.nodes
+            .iter()
+            .find(|(id, _)| *id == node_id)
+            .map(|(_, name)| name.clone())
+            .unwrap_or_else(|| format!("node {}", node_id))
+    }
+
+    fn workspace_name(&self, workspace_id: &str) -> String {
+        self.workspaces
+            .iter()
+            .find(|(id, _)| id == workspace_id)
+            .map(|(_, name)| name.clone())
+            .unwrap_or_else(|| workspace_id.to_string())
+    }
+
+    /// Nodes named by a phrase: the selection words or a node name
+    fn resolve_nodes(&self, phrase: &str) -> Result<Vec<SceneId>, GrammarError> {
+        if SELECTION_WORDS.contains(&phrase) {
+            return match (self.selection.is_empty(), self.focus) {
+                (false, _) => Ok(self.selection.clone()),
+                (true, Some(focus)) => Ok(vec![focus]),
+                (true, None) => Err(GrammarError::EmptySelection),
+            };
+        }
+        let candidates = self.nodes.iter().map(|(id, name)| (*id, name.as_str()));
+        let id = resolve_name(phrase, candidates, GrammarError::UnknownNode)?;
+        Ok(vec![id])
+    }
+
+    fn resolve_node(&self, phrase: &str) -> Result<SceneId, GrammarError> {
+        match self.resolve_nodes(phrase)?.as_slice() {
+            [node_id] => Ok(*node_id),
+            nodes => Err(GrammarError::Ambiguous {
+                phrase: phrase.to_string(),
+                candidates: nodes.iter().map(|id| self.node_name(*id)).collect(),
+            }),
+        }
+    }
+
+    fn resolve_workspace(&self, phrase: &str) -> Result<String, GrammarError> {
+        let candidates = self.workspaces.iter().map(|(id, name)| (id.clone(), name.as_str()));
+        resolve_name(phrase, candidates, GrammarError::UnknownWorkspace)
+    }
+}
+
+/// What a phrase pattern asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoiceIntent {
+    OpenNodes,
+    EditNodes,
+    DeleteNodes,
+    CopyNodes,
+    /// `{nodes}` to `{node}`
+    Connect,
+    /// `{nodes}` from `{node}`
+    Disconnect,
+    SwitchWorkspace,
+    CreateWorkspace,
+    DeleteWorkspace,
+}
+
+/// Workspace manager call requested by voice
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceCommand {
+    Switch { workspace_id: String },
+    Create { name: String },
+    Delete { workspace_id: String },
+}
+
+impl WorkspaceCommand {
+    /// Run the command, returning the ID of a created workspace
+    pub fn apply(&self, workspaces: &dyn WorkspaceService) -> Result<Option<String>, WorkspaceError> {
+        match self {
+            WorkspaceCommand::Switch { workspace_id } => workspaces.switch_workspace(workspace_id).map(|_| None),
+            WorkspaceCommand::Create { name } => workspaces.create_workspace(name, "").map(Some),
+            WorkspaceCommand::Delete { workspace_id } => workspaces.delete_workspace(workspace_id).map(|_| None),
+        }
+    }
+}
+
+/// Graph operation requested by voice
+#[derive(Debug, Clone)]
+pub enum GraphCommand {
+    /// Actions for the node manager, in order
+    NodeActions(Vec<(SceneId, NodeAction)>),
+    Workspace(WorkspaceCommand),
+}
+
+impl GraphCommand {
+    /// Whether the command removes something and needs a spoken confirmation
+    pub fn is_destructive(&self) -> bool {
+        match self {
+            GraphCommand::NodeActions(actions) => actions
+                .iter()
+                .any(|(_, action)| matches!(action, NodeAction::Delete | NodeAction::DisconnectFrom { .. })),
+            GraphCommand::Workspace(command) => matches!(command, WorkspaceCommand::Delete { .. }),
+        }
+    }
+}
+
+/// An understood utterance
+#[derive(Debug, Clone)]
+pub struct GrammarMatch {
+    pub intent: VoiceIntent,
+    pub command: GraphCommand,
+    /// What will happen, e.g. "Delete Firefox", for feedback and confirmation prompts
+    pub summary: String,
+}
+
+/// Why an utterance could not be turned into a command
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum GrammarError {
+    #[error("No command matches \"{0}\"")]
+    NoMatch(String),
+
+    #[error("No node called {0}")]
+    UnknownNode(String),
+
+    #[error("\"{phrase}\" could mean {}", .candidates.join(", "))]
+    Ambiguous { phrase: String, candidates: Vec<String> },
+
+    #[error("No workspace called {0}")]
+    UnknownWorkspace(String),
+
+    #[error("Nothing is selected")]
+    EmptySelection,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    OptionalWord(String),
+    Slot(Slot),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Nodes,
+    Node,
+    Workspace,
+    Name,
+}
+
+#[derive(Debug, Clone)]
+struct Phrase {
+    tokens: Vec<Token>,
+    intent: VoiceIntent,
+}
+
+/// Phrase patterns mapped to intents, tried in order
+#[derive(Debug, Clone)]
+pub struct CommandGrammar {
+    phrases: Vec<Phrase>,
+    /// Relationship created by "connect"
+    connect_edge_type: EdgeType,
+}
+
+impl Default for CommandGrammar {
+    fn default() -> Self {
+        let mut grammar = Self::empty();
+        // Workspace phrases first, so "open workspace x" is not read as a node
+        for pattern in [
+            "switch to workspace {workspace}",
+            "switch workspace to {workspace}",
+            "go to workspace {workspace}",
+            "open workspace {workspace}",
+            "switch to {workspace}",
+        ] {
+            grammar.add_phrase(pattern, VoiceIntent::SwitchWorkspace);
+        }
+        for pattern in ["create a? new? workspace called? named? {name}", "new workspace called? named? {name}"] {
+            grammar.add_phrase(pattern, VoiceIntent::CreateWorkspace);
+        }
+        for pattern in ["delete workspace {workspace}", "remove workspace {workspace}"] {
+            grammar.add_phrase(pattern, VoiceIntent::DeleteWorkspace);
+        }
+        for pattern in ["connect {nodes} to {node}", "connect {nodes} with {node}", "link {nodes} to {node}"] {
+            grammar.add_phrase(pattern, VoiceIntent::Connect);
+        }
+        for pattern in ["disconnect {nodes} from {node}", "unlink {nodes} from {node}"] {
+            grammar.add_phrase(pattern, VoiceIntent::Disconnect);
+        }
+        for pattern in ["open {nodes}", "launch {nodes}", "start {nodes}"] {
+            grammar.add_phrase(pattern, VoiceIntent::OpenNodes);
+        }
+        grammar.add_phrase("edit {nodes}", VoiceIntent::EditNodes);
+        for pattern in ["delete {nodes}", "remove {nodes}"] {
+            grammar.add_phrase(pattern, VoiceIntent::DeleteNodes);
+        }
+        for pattern in ["copy {nodes}", "duplicate {nodes}"] {
+            grammar.add_phrase(pattern, VoiceIntent::CopyNodes);
+        }
+        grammar
+    }
+}
+
+impl CommandGrammar {
+    /// Grammar without phrases
+    pub fn empty() -> Self {
+        Self {
+            phrases: Vec::new(),
+            connect_edge_type: EdgeType::RelatedTo { similarity: 1.0 },
+        }
+    }
+
+    /// Add a phrase pattern, tried after the existing ones
+    pub fn add_phrase(&mut self, pattern: &str, intent: VoiceIntent) {
+        let tokens = pattern
+            .split_whitespace()
+            .map(|word| match word {
+                "{nodes}" => Token::Slot(Slot::Nodes),
+                "{node}" => Token::Slot(Slot::Node),
+                "{workspace}" => Token::Slot(Slot::Workspace),
+                "{name}" => Token::Slot(Slot::Name),
+                _ => match word.strip_suffix('?') {
+                    Some(optional) => Token::OptionalWord(optional.to_lowercase()),
+                    None => Token::Word(word.to_lowercase()),
+                },
+            })
+            .collect();
+        self.phrases.push(Phrase { tokens, intent });
+    }
+
+    /// Relationship created by "connect" phrases
+    pub fn set_connect_edge_type(&mut self, edge_type: EdgeType) {
+        self.connect_edge_type = edge_type;
+    }
+
+    /// Turn an utterance into a command
+    ///
+    /// The first phrase whose slots all resolve wins; if phrases match but
+    /// their names do not resolve, the first resolution error is returned.
+    pub fn parse(&self, text: &str, vocabulary: &GraphVocabulary) -> Result<GrammarMatch, GrammarError> {
+        let words = words(text);
+        let mut first_error = None;
+        for phrase in &self.phrases {
+            let mut slots = Vec::new();
+            if !match_tokens(&phrase.tokens, &words, &mut slots) {
+                continue;
+            }
+            match self.build(phrase.intent, &slots, vocabulary) {
+                Ok(found) => return Ok(found),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        Err(first_error.unwrap_or_else(|| GrammarError::NoMatch(text.trim().to_string())))
+    }
+
+    fn build(&self, intent: VoiceIntent, slots: &[(Slot, String)], vocabulary: &GraphVocabulary) -> Result<GrammarMatch, GrammarError> {
+        let slot = |index: usize| slots.get(index).map(|(_, value)| value.as_str()).unwrap_or_default();
+        let node_actions = |verb: &str, action: NodeAction| -> Result<GrammarMatch, GrammarError> {
+            let nodes = vocabulary.resolve_nodes(slot(0))?;
+            Ok(GrammarMatch {
+                intent,
+                summary: format!("{} {}", verb, describe_nodes(&nodes, vocabulary)),
+                command: GraphCommand::NodeActions(nodes.into_iter().map(|id| (id, action.clone())).collect()),
+            })
+        };
+
+        match intent {
+            VoiceIntent::OpenNodes => node_actions("Open", NodeAction::Open),
+            VoiceIntent::EditNodes => node_actions("Edit", NodeAction::Edit),
+            VoiceIntent::DeleteNodes => node_actions("Delete", NodeAction::Delete),
+            VoiceIntent::CopyNodes => node_actions("Copy", NodeAction::Copy),
+            VoiceIntent::Connect | VoiceIntent::Disconnect => {
+                let target_id = vocabulary.resolve_node(slot(1))?;
+                let sources: Vec<SceneId> = vocabulary.resolve_nodes(slot(0))?.into_iter().filter(|id| *id != target_id).collect();
+                if sources.is_empty() {
+                    return Err(GrammarError::EmptySelection);
+                }
+                let (verb, preposition, action) = if intent == VoiceIntent::Connect {
+                    ("Connect", "to", NodeAction::ConnectTo { target_id, edge_type: self.connect_edge_type.clone() })
+                } else {
+                    ("Disconnect", "from", NodeAction::DisconnectFrom { target_id })
+                };
+                Ok(GrammarMatch {
+                    intent,
+                    summary: format!("{} {} {} {}", verb, describe_nodes(&sources, vocabulary), preposition, vocabulary.node_name(target_id)),
+                    command: GraphCommand::NodeActions(sources.into_iter().map(|id| (id, action.clone())).collect()),
+                })
+            }
+            VoiceIntent::SwitchWorkspace | VoiceIntent::DeleteWorkspace => {
+                let workspace_id = vocabulary.resolve_workspace(slot(0))?;
+                let name = vocabulary.workspace_name(&workspace_id);
+                let (summary, command) = if intent == VoiceIntent::SwitchWorkspace {
+                    (format!("Switch to workspace {}", name), WorkspaceCommand::Switch { workspace_id })
+                } else {
+                    (format!("Delete workspace {}", name), WorkspaceCommand::Delete { workspace_id })
+                };
+                Ok(GrammarMatch { intent, summary, command: GraphCommand::Workspace(command) })
+            }
+            VoiceIntent::CreateWorkspace => {
+                let name = title_case(slot(0));
+                Ok(GrammarMatch {
+                    intent,
+                    summary: format!("Create workspace {}", name),
+                    command: GraphCommand::Workspace(WorkspaceCommand::Create { name }),
+                })
+            }
+        }
+    }
+}
+
+/// Lowercase words of an utterance, without punctuation
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Match pattern tokens against all words, collecting slot values
+fn match_tokens(tokens: &[Token], words: &[String], slots: &mut Vec<(Slot, String)>) -> bool {
+    let Some((token, rest)) = tokens.split_first() else {
+        return words.is_empty();
+    };
+    match token {
+        Token::Word(word) => words.first() == Some(word) && match_tokens(rest, &words[1..], slots),
+        Token::OptionalWord(word) => {
+            (words.first() == Some(word) && match_tokens(rest, &words[1..], slots)) || match_tokens(rest, words, slots)
+        }
+        Token::Slot(slot) => {
+            // Shortest capture first, so literal words after the slot bind early
+            for end in 1..=words.len() {
+                let Some(value) = slot_value(*slot, &words[..end]) else {
+                    continue;
+                };
+                slots.push((*slot, value));
+                if match_tokens(rest, &words[end..], slots) {
+                    return true;
+                }
+                slots.pop();
+            }
+            false
+        }
+    }
+}
+
+/// Slot text without fillers such as "the" or a trailing "node"
+fn slot_value(slot: Slot, words: &[String]) -> Option<String> {
+    let mut words = words;
+    while let Some((first, rest)) = words.split_first() {
+        if rest.is_empty() || !LEADING_FILLERS.contains(&first.as_str()) {
+            break;
+        }
+        words = rest;
+    }
+    let kind_words: &[&str] = match slot {
+        Slot::Nodes | Slot::Node => &["node", "nodes"],
+        Slot::Workspace => &["workspace"],
+        Slot::Name => &[],
+    };
+    if let Some((last, rest)) = words.split_last() {
+        if !rest.is_empty() && kind_words.contains(&last.as_str()) {
+            words = rest;
+        }
+    }
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+/// Resolve a spoken name: an exact match, else the only name containing it
+fn resolve_name<'a, I: Clone>(
+    phrase: &str,
+    candidates: impl Iterator<Item = (I, &'a str)>,
+    unknown: fn(String) -> GrammarError,
+) -> Result<I, GrammarError> {
+    let mut partial = Vec::new();
+    for (id, name) in candidates {
+        let name_words = words(name);
+        if name_words.join(" ") == phrase {
+            return Ok(id);
+        }
+        let phrase_words = words(phrase);
+        if name_words.windows(phrase_words.len()).any(|window| window == phrase_words.as_slice()) {
+            partial.push((id, name.to_string()));
+        }
+    }
+    match partial.len() {
+        0 => Err(unknown(phrase.to_string())),
+        1 => Ok(partial.remove(0).0),
+        _ => Err(GrammarError::Ambiguous {
+            phrase: phrase.to_string(),
+            candidates: partial.into_iter().map(|(_, name)| name).collect(),
+        }),
+    }
+}
+
+fn describe_nodes(nodes: &[SceneId], vocabulary: &GraphVocabulary) -> String {
+    match nodes {
+        [node_id] => vocabulary.node_name(*node_id),
+        nodes => format!("{} nodes", nodes.len()),
+    }
+}
+
+/// "project alpha" -> "Project Alpha", for names spoken in lowercase
+fn title_case(text: &str) -> String {
+    text.split(' ')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vocabulary() -> GraphVocabulary {
+        let mut vocabulary = GraphVocabulary::new().with_selection(vec![2, 3]);
+        vocabulary.add_node(1, "Firefox");
+        vocabulary.add_node(2, "Notes.md");
+        vocabulary.add_node(3, "Terminal");
+        vocabulary.add_node(4, "Project Alpha");
+        vocabulary.add_node(5, "Project Beta");
+        vocabulary.add_workspace("ws-work", "Work");
+        vocabulary.add_workspace("ws-personal", "Personal");
+        vocabulary
+    }
+
+    fn node_actions(found: &GrammarMatch) -> &[(SceneId, NodeAction)] {
+        match &found.command {
+            GraphCommand::NodeActions(actions) => actions,
+            GraphCommand::Workspace(command) => panic!("expected node actions, got {:?}", command),
+        }
+    }
+
+    #[test]
+    fn test_open_firefox_node() {
+        let grammar = CommandGrammar::default();
+        let found = grammar.parse("Open Firefox node", &vocabulary()).unwrap();
+        assert_eq!(found.intent, VoiceIntent::OpenNodes);
+        assert_eq!(found.summary, "Open Firefox");
+        assert!(matches!(node_actions(&found), [(1, NodeAction::Open)]));
+        assert!(!found.command.is_destructive());
+
+        // Fillers and punctuation do not matter
+        let found = grammar.parse("open the firefox.", &vocabulary()).unwrap();
+        assert!(matches!(node_actions(&found), [(1, NodeAction::Open)]));
+    }
+
+    #[test]
+    fn test_connect_selection_to_project_alpha() {
+        let grammar = CommandGrammar::default();
+        let found = grammar.parse("connect selection to project alpha", &vocabulary()).unwrap();
+        assert_eq!(found.intent, VoiceIntent::Connect);
+        assert_eq!(found.summary, "Connect 2 nodes to Project Alpha");
+        let actions = node_actions(&found);
+        assert_eq!(actions.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![2, 3]);
+        assert!(actions.iter().all(|(_, action)| matches!(action, NodeAction::ConnectTo { target_id: 4, .. })));
+
+        // The target is left out of the nodes connected to it
+        let vocabulary = vocabulary().with_selection(vec![4, 1]);
+        let found = grammar.parse("connect selection to project alpha", &vocabulary).unwrap();
+        assert_eq!(found.summary, "Connect Firefox to Project Alpha");
+
+        // Disconnecting needs a confirmation
+        let found = grammar.parse("disconnect it from project alpha", &vocabulary).unwrap();
+        assert!(found.command.is_destructive());
+    }
+
+    #[test]
+    fn test_switch_to_workspace_personal() {
+        let grammar = CommandGrammar::default();
+        let found = grammar.parse("switch to workspace personal", &vocabulary()).unwrap();
+        assert_eq!(found.intent, VoiceIntent::SwitchWorkspace);
+        assert_eq!(found.summary, "Switch to workspace Personal");
+        assert!(matches!(
+            &found.command,
+            GraphCommand::Workspace(WorkspaceCommand::Switch { workspace_id }) if workspace_id == "ws-personal"
+        ));
+
+        // "open workspace" switches rather than opening a node
+        let found = grammar.parse("open workspace work", &vocabulary()).unwrap();
+        assert_eq!(found.intent, VoiceIntent::SwitchWorkspace);
+
+        let found = grammar.parse("create a new workspace called side project", &vocabulary()).unwrap();
+        assert!(matches!(&found.command, GraphCommand::Workspace(WorkspaceCommand::Create { name }) if name == "Side Project"));
+    }
+
+    #[test]
+    fn test_unresolved_utterances_say_why() {
+        let grammar = CommandGrammar::default();
+        assert_eq!(grammar.parse("open chrome", &vocabulary()).unwrap_err(), GrammarError::UnknownNode("chrome".to_string()));
+        assert_eq!(
+            grammar.parse("open project", &vocabulary()).unwrap_err(),
+            GrammarError::Ambiguous {
+                phrase: "project".to_string(),
+                candidates: vec!["Project Alpha".to_string(), "Project Beta".to_string()],
+            }
+        );
+        assert_eq!(
+            grammar.parse("switch to workspace gaming", &vocabulary()).unwrap_err(),
+            GrammarError::UnknownWorkspace("gaming".to_string())
+        );
+        assert_eq!(
+            grammar.parse("delete selection", &vocabulary().with_selection(Vec::new())).unwrap_err(),
+            GrammarError::EmptySelection
+        );
+        assert_eq!(grammar.parse("make coffee", &vocabulary()).unwrap_err(), GrammarError::NoMatch("make coffee".to_string()));
+    }
+}
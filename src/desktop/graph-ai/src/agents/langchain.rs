@@ -9,7 +9,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use parking_lot::RwLock;
 use chrono::{DateTime, Utc};
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 use log::{info, error, debug};
 
 /// LangChain configuration
@@ -449,6 +449,19 @@ pub enum StepType {
     Response,
 }
 
+/// Change in an agent or its tasks, for the nodes showing agents
+#[derive(Debug, Clone)]
+pub enum AgentEvent {
+    /// Agent status changed
+    StatusChanged { agent_id: String, status: AgentStatus },
+    /// Agent started working on a task
+    TaskStarted { agent_id: String, task_id: String, description: String },
+    /// Agent reported how far along a task is, from 0 to 1
+    TaskProgress { agent_id: String, task_id: String, progress: f32 },
+    /// Task finished, successfully or not
+    TaskFinished(AgentExecutionResult),
+}
+
 /// LangChain integration manager
 pub struct LangChainManager {
     /// Configuration
@@ -461,6 +474,8 @@ pub struct LangChainManager {
     task_executor: Option<tokio::task::JoinHandle<()>>,
     /// Task result sender
     result_sender: mpsc::UnboundedSender<AgentExecutionResult>,
+    /// Agent and task events
+    events: broadcast::Sender<AgentEvent>,
     /// Manager statistics
     stats: Arc<RwLock<LangChainStats>>,
 }
@@ -490,6 +505,7 @@ impl LangChainManager {
     /// Create a new LangChain manager
     pub async fn new(config: LangChainConfig) -> Result<Self, AIError> {
         let (result_sender, mut result_receiver) = mpsc::unbounded_channel();
+        let (events, _) = broadcast::channel(256);
         
         let manager = Self {
             config: Arc::new(RwLock::new(config.clone())),
@@ -497,6 +513,7 @@ impl LangChainManager {
             task_queue: Arc::new(RwLock::new(Vec::new())),
             task_executor: None,
             result_sender,
+            events,
             stats: Arc::new(RwLock::new(LangChainStats::default())),
         };
         
@@ -540,9 +557,10 @@ impl LangChainManager {
         let agents = self.agents.clone();
         let task_queue = self.task_queue.clone();
         let result_sender = self.result_sender.clone();
+        let events = self.events.clone();
         
         self.task_executor = Some(tokio::spawn(async move {
-            Self::task_execution_loop(config, agents, task_queue, result_sender).await;
+            Self::task_execution_loop(config, agents, task_queue, result_sender, events).await;
         }));
         
         info!("LangChain manager started");
@@ -608,6 +626,20 @@ impl LangChainManager {
         Ok(task_id)
     }
     
+    /// Subscribe to agent status changes and task progress
+    pub fn subscribe(&self) -> broadcast::Receiver<AgentEvent> {
+        self.events.subscribe()
+    }
+    
+    /// Report how far along a running task is, from 0 to 1
+    pub fn report_progress(&self, agent_id: &str, task_id: &str, progress: f32) {
+        let _ = self.events.send(AgentEvent::TaskProgress {
+            agent_id: agent_id.to_string(),
+            task_id: task_id.to_string(),
+            progress: progress.clamp(0.0, 1.0),
+        });
+    }
+    
     /// Get agent information
     pub fn get_agent(&self, agent_id: &str) -> Option<Agent> {
         self.agents.read().get(agent_id).cloned()
@@ -667,6 +699,7 @@ impl LangChainManager {
         agents: Arc<RwLock<HashMap<String, Agent>>>,
         task_queue: Arc<RwLock<Vec<AgentTask>>>,
         result_sender: mpsc::UnboundedSender<AgentExecutionResult>,
+        events: broadcast::Sender<AgentEvent>,
     ) {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(1));
         
@@ -689,10 +722,20 @@ impl LangChainManager {
             if let Some(mut task) = task {
                 task.status = TaskStatus::InProgress;
                 task.started_at = Some(Utc::now());
+                Self::set_status(&agents, &events, &task.assigned_agent, AgentStatus::Executing);
+                let _ = events.send(AgentEvent::TaskStarted {
+                    agent_id: task.assigned_agent.clone(),
+                    task_id: task.id.clone(),
+                    description: task.description.clone(),
+                });
                 
                 // Execute task
                 let result = Self::execute_task(&task, &agents).await;
                 
+                let status = if result.success { AgentStatus::Idle } else { AgentStatus::Error };
+                Self::set_status(&agents, &events, &task.assigned_agent, status);
+                let _ = events.send(AgentEvent::TaskFinished(result.clone()));
+                
                 // Send result
                 if let Err(e) = result_sender.send(result) {
                     error!("Failed to send task result: {}", e);
@@ -701,6 +744,23 @@ impl LangChainManager {
         }
     }
     
+    /// Set an agent's status, announcing changes
+    fn set_status(
+        agents: &Arc<RwLock<HashMap<String, Agent>>>,
+        events: &broadcast::Sender<AgentEvent>,
+        agent_id: &str,
+        status: AgentStatus,
+    ) {
+        let mut agents = agents.write();
+        let Some(agent) = agents.get_mut(agent_id) else {
+            return;
+        };
+        if agent.status != status {
+            agent.status = status.clone();
+            let _ = events.send(AgentEvent::StatusChanged { agent_id: agent_id.to_string(), status });
+        }
+    }
+    
     /// Execute a task
     async fn execute_task(
         task: &AgentTask,
@@ -721,9 +781,8 @@ impl LangChainManager {
         // Simulate task execution
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         
-        // Update agent status
+        // Update agent activity
         if let Some(agent) = agents.write().get_mut(&task.assigned_agent) {
-            agent.status = AgentStatus::Executing;
             agent.last_activity = Utc::now();
         }
         
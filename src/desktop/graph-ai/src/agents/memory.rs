@@ -5,6 +5,7 @@
 
 use crate::AIError;
 use crate::agents::langchain::{MemoryEntry, MemoryType};
+use crate::privacy::NodeAccessPolicy;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, BinaryHeap};
 use std::sync::Arc;
@@ -250,6 +251,8 @@ pub struct MemoryManager {
     maintenance_task: Option<tokio::task::JoinHandle<()>>,
     /// Memory statistics
     stats: Arc<RwLock<MemoryStats>>,
+    /// Nodes memories must not refer to
+    node_access: NodeAccessPolicy,
 }
 
 /// Memory index
@@ -344,6 +347,7 @@ impl MemoryManager {
             consolidation_task: None,
             maintenance_task: None,
             stats: Arc::new(RwLock::new(MemoryStats::default())),
+            node_access: NodeAccessPolicy::new(),
         };
         
         // Initialize storage directory
@@ -410,8 +414,20 @@ impl MemoryManager {
         Ok(())
     }
     
+    /// Share the AI access policy memories are checked against
+    pub fn set_node_access(&mut self, node_access: NodeAccessPolicy) {
+        self.node_access = node_access;
+    }
+    
     /// Store a memory entry
+    ///
+    /// Entries about AI-invisible nodes are dropped rather than stored.
     pub async fn store_memory(&self, entry: MemoryEntry) -> Result<(), AIError> {
+        if !self.node_access.allows_memory(&entry) {
+            debug!("Memory {} refers to an AI-invisible node, not stored", entry.id);
+            return Ok(());
+        }
+        
         let mut memories = self.memories.write();
         let memory_type = entry.entry_type.clone();
         
@@ -470,6 +486,11 @@ impl MemoryManager {
                         }
                     }
                     
+                    // Nodes may have been hidden after the memory was stored
+                    if !self.node_access.allows_memory(entry) {
+                        continue;
+                    }
+                    
                     // Calculate similarity
                     let similarity = self.calculate_similarity(&query.query, &entry.content).await?;
                     
@@ -8,6 +8,7 @@ pub mod coordinator;
 pub mod decomposition;
 pub mod memory;
 pub mod communication;
+pub mod nodes;
 
 use crate::AIError;
 use serde::{Deserialize, Serialize};
@@ -19,7 +20,7 @@ use tokio::sync::mpsc;
 use log::{info, debug};
 
 pub use langchain::{
-    LangChainManager, LangChainConfig, Agent, AgentType, AgentTask, AgentExecutionResult,
+    LangChainManager, LangChainConfig, Agent, AgentEvent, AgentType, AgentTask, AgentExecutionResult,
     TaskType, TaskPriority, TaskStatus, AgentStatus, AgentConfig, MemoryConfig, MemoryType,
     MemoryEntry,
 };
@@ -40,6 +41,7 @@ pub use communication::{
     MessageContent, ContentType, MessagePriority, Conversation, ConversationType,
     AgentPresence, PresenceStatus, create_text_message, create_data_message,
 };
+pub use nodes::{AgentArtifact, AgentNodeBinding, AgentTaskProgress};
 
 /// AI Agent System configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -123,11 +125,13 @@ pub struct AIAgentSystem {
     /// Memory manager
     memory_manager: Arc<RwLock<Option<MemoryManager>>>,
     /// Communication manager
-    communication_manager: Arc<RwLock<Option<CommunicationManager>>>,
+    communication_manager: Arc<RwLock<Option<Arc<CommunicationManager>>>>,
     /// System statistics
     stats: Arc<RwLock<SystemStats>>,
     /// Lifecycle manager handle
     lifecycle_handle: Option<tokio::task::JoinHandle<()>>,
+    /// Nodes agents must not remember
+    node_access: crate::privacy::NodeAccessPolicy,
 }
 
 /// System statistics
@@ -161,6 +165,7 @@ impl AIAgentSystem {
             communication_manager: Arc::new(RwLock::new(None)),
             stats: Arc::new(RwLock::new(SystemStats::default())),
             lifecycle_handle: None,
+            node_access: crate::privacy::NodeAccessPolicy::new(),
         };
         
         // Initialize persistence directory
@@ -197,12 +202,13 @@ impl AIAgentSystem {
         
         // Initialize memory manager
         let mut memory = MemoryManager::new(config.memory).await?;
+        memory.set_node_access(self.node_access.clone());
         memory.start().await?;
         *self.memory_manager.write() = Some(memory);
         
         // Initialize communication manager
         let communication = CommunicationManager::new(config.communication).await?;
-        *self.communication_manager.write() = Some(communication);
+        *self.communication_manager.write() = Some(Arc::new(communication));
         
         // Start lifecycle management
         if config.system.enable_lifecycle_management {
@@ -237,17 +243,20 @@ impl AIAgentSystem {
         }
         
         // Stop LangChain manager
-        if let Some(mut langchain) = self.langchain_manager.write().take() {
+        let langchain = self.langchain_manager.write().take();
+        if let Some(mut langchain) = langchain {
             langchain.stop().await?;
         }
         
         // Stop coordinator
-        if let Some(mut coordinator) = self.coordinator.write().take() {
+        let coordinator = self.coordinator.write().take();
+        if let Some(mut coordinator) = coordinator {
             coordinator.stop().await?;
         }
         
         // Stop memory manager
-        if let Some(mut memory) = self.memory_manager.write().take() {
+        let memory = self.memory_manager.write().take();
+        if let Some(mut memory) = memory {
             memory.stop().await?;
         }
         
@@ -273,6 +282,19 @@ impl AIAgentSystem {
         }
     }
     
+    /// Get an agent
+    pub fn get_agent(&self, agent_id: &str) -> Option<Agent> {
+        self.langchain_manager.read().as_ref().and_then(|manager| manager.get_agent(agent_id))
+    }
+    
+    /// Subscribe to agent status changes and task progress
+    pub fn subscribe_agent_events(&self) -> Result<tokio::sync::broadcast::Receiver<AgentEvent>, AIError> {
+        self.langchain_manager.read()
+            .as_ref()
+            .map(|manager| manager.subscribe())
+            .ok_or_else(|| AIError::Configuration("LangChain manager not initialized".to_string()))
+    }
+    
     /// Submit a task to an agent
     pub async fn submit_task(&self, agent_id: &str, task: AgentTask) -> Result<String, AIError> {
         let langchain = self.langchain_manager.read();
@@ -323,6 +345,14 @@ impl AIAgentSystem {
         }
     }
     
+    /// Share the AI access policy agent memories are checked against
+    pub fn set_node_access(&mut self, node_access: crate::privacy::NodeAccessPolicy) {
+        if let Some(memory) = self.memory_manager.write().as_mut() {
+            memory.set_node_access(node_access.clone());
+        }
+        self.node_access = node_access;
+    }
+    
     /// Store memory for an agent
     pub async fn store_memory(&self, agent_id: &str, entry: MemoryEntry) -> Result<(), AIError> {
         let memory = self.memory_manager.read();
@@ -349,25 +379,39 @@ impl AIAgentSystem {
     
     /// Send a message between agents
     pub async fn send_message(&self, message: AgentMessage) -> Result<(), AIError> {
-        let communication = self.communication_manager.read();
-        if let Some(manager) = communication.as_ref() {
-            // Update statistics
-            self.stats.write().total_messages_sent += 1;
-            
-            manager.send_message(message).await
-        } else {
-            Err(AIError::Configuration("Communication manager not initialized".to_string()))
-        }
+        let manager = self.communication()?;
+        // Update statistics
+        self.stats.write().total_messages_sent += 1;
+        
+        manager.send_message(message).await
+    }
+    
+    /// Start a conversation between participants
+    pub async fn create_conversation(&self, participants: Vec<String>, conversation_type: ConversationType) -> Result<String, AIError> {
+        self.communication()?.create_conversation(participants, conversation_type).await
+    }
+    
+    /// Get a conversation
+    pub async fn get_conversation(&self, conversation_id: &str) -> Option<Conversation> {
+        let manager = self.communication_manager.read().clone()?;
+        manager.get_conversation(conversation_id).await
+    }
+    
+    /// Add a message to a conversation
+    pub async fn add_message_to_conversation(&self, conversation_id: &str, message: AgentMessage) -> Result<(), AIError> {
+        self.communication()?.add_message_to_conversation(conversation_id, message).await
     }
     
     /// Register an agent for communication
     pub async fn register_agent_communication(&self, agent_id: String) -> Result<mpsc::UnboundedReceiver<AgentMessage>, AIError> {
-        let communication = self.communication_manager.read();
-        if let Some(manager) = communication.as_ref() {
-            manager.register_agent(agent_id).await
-        } else {
-            Err(AIError::Configuration("Communication manager not initialized".to_string()))
-        }
+        self.communication()?.register_agent(agent_id).await
+    }
+    
+    /// Communication manager, shared so no lock is held while it works
+    fn communication(&self) -> Result<Arc<CommunicationManager>, AIError> {
+        self.communication_manager.read()
+            .clone()
+            .ok_or_else(|| AIError::Configuration("Communication manager not initialized".to_string()))
     }
     
     /// Update system configuration
@@ -391,7 +435,8 @@ impl AIAgentSystem {
             manager.update_config(new_config.memory).await?;
         }
         
-        if let Some(manager) = self.communication_manager.read().as_ref() {
+        let communication = self.communication_manager.read().clone();
+        if let Some(manager) = communication {
             manager.update_config(new_config.communication).await?;
         }
         
@@ -0,0 +1,472 @@
+//! Agents as graph nodes
+//!
+//! [`AgentNodeBinding`] ties AI agent nodes to live agents of the
+//! [`AIAgentSystem`]. Opening an agent node opens a conversation with the
+//! agent, and questions asked in it become tasks whose answers are added to
+//! the conversation. While an agent works on a task its progress shows on the
+//! node's badge. Artifacts a task lists in its result become nodes connected
+//! to the agent node with a `CreatedBy` edge:
+//!
+//! ```json
+//! {"artifacts": [
+//!     {"kind": "file", "path": "/home/user/report.md"},
+//!     {"kind": "url", "url": "https://example.org/paper", "title": "Paper"},
+//!     {"kind": "note", "title": "Summary", "content": "..."}
+//! ]}
+//! ```
+
+use super::{
+    create_text_message, AIAgentSystem, Agent, AgentEvent, AgentExecutionResult, AgentStatus, AgentTask,
+    Conversation, ConversationType, MessageType, TaskPriority, TaskStatus, TaskType,
+};
+use crate::AIError;
+use chrono::Utc;
+use horizonos_graph_engine::{EdgeType, NodeType, Position, Scene, SceneEdge, SceneId, Vec3};
+use horizonos_graph_nodes::status_badges::BadgeState;
+use horizonos_graph_nodes::{
+    AIAgentNode, ConceptNode, FileNode, GraphNode, NodeError, NodeManager, NodeVisualData, UrlNode, AGENT_ID_PROPERTY,
+};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// Participant ID of the user in agent conversations
+pub const USER_PARTICIPANT: &str = "user";
+
+/// Color of edges from artifacts to the agent that produced them
+const ARTIFACT_EDGE_COLOR: [f32; 4] = [0.6, 0.4, 0.9, 0.8];
+
+/// Task an agent node's badge shows
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentTaskProgress {
+    pub task_id: String,
+    pub description: String,
+    /// Fraction done, once the agent reported any
+    pub progress: Option<f32>,
+}
+
+/// Something an agent produced that gets its own node
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AgentArtifact {
+    File { path: PathBuf },
+    Url {
+        url: String,
+        #[serde(default)]
+        title: Option<String>,
+    },
+    Note {
+        title: String,
+        #[serde(default)]
+        content: String,
+    },
+}
+
+impl AgentArtifact {
+    /// Artifacts listed in a task result; malformed entries are skipped
+    pub fn from_result(result: &AgentExecutionResult) -> Vec<Self> {
+        let Some(artifacts) = result.result.as_ref().and_then(|value| value.get("artifacts")) else {
+            return Vec::new();
+        };
+        artifacts
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|artifact| match serde_json::from_value(artifact.clone()) {
+                Ok(artifact) => Some(artifact),
+                Err(e) => {
+                    log::warn!("Skipping malformed artifact of task {}: {}", result.task_id, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn to_node(&self, id: SceneId) -> Result<Box<dyn GraphNode + Send + Sync>, NodeError> {
+        Ok(match self {
+            AgentArtifact::File { path } => Box::new(FileNode::new(id, path.clone())?),
+            AgentArtifact::Url { url, title: Some(title) } => Box::new(UrlNode::with_title(id, url.clone(), title.clone())),
+            AgentArtifact::Url { url, title: None } => Box::new(UrlNode::new(id, url.clone())),
+            AgentArtifact::Note { title, content } => {
+                Box::new(ConceptNode::new(id).with_content(title.clone(), content.clone()))
+            }
+        })
+    }
+}
+
+/// State of a node's agent
+#[derive(Debug, Default)]
+struct BoundAgent {
+    agent_id: String,
+    status: Option<AgentStatus>,
+    task: Option<AgentTaskProgress>,
+    conversation: Option<String>,
+    /// Tasks asked from the conversation, whose answers go back into it
+    questions: HashSet<String>,
+}
+
+/// Agent nodes and the live agents they stand for
+#[derive(Default)]
+pub struct AgentNodeBinding {
+    bound: RwLock<HashMap<SceneId, BoundAgent>>,
+}
+
+impl AgentNodeBinding {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a node for an agent at a position and bind it
+    pub fn add_agent_node(
+        &self,
+        agent: &Agent,
+        position: Position,
+        nodes: &mut NodeManager,
+        scene: &mut Scene,
+    ) -> Result<SceneId, AIError> {
+        let node = |id| AIAgentNode::for_agent(id, &agent.id, agent.name.clone(), agent.config.model.clone());
+        let mut scene_node = node(0).to_scene_node();
+        scene_node.position = position;
+        let node_id = scene.add_node(scene_node);
+        nodes
+            .add_node(Box::new(node(node_id)))
+            .map_err(|e| AIError::Configuration(format!("Failed to add node for agent {}: {}", agent.id, e)))?;
+        self.bind(node_id, &agent.id);
+        Ok(node_id)
+    }
+
+    /// Bind a node to an agent
+    pub fn bind(&self, node_id: SceneId, agent_id: &str) {
+        self.bound.write().insert(node_id, BoundAgent { agent_id: agent_id.to_string(), ..Default::default() });
+    }
+
+    /// Bind the agent nodes of a scene, e.g. a restored one, returning how many were bound
+    pub fn bind_scene(&self, scene: &Scene) -> usize {
+        let mut count = 0;
+        for (node_id, node) in scene.nodes() {
+            if !matches!(node.node_type, NodeType::AIAgent { .. }) {
+                continue;
+            }
+            if let Some(agent_id) = node.metadata.properties.get(AGENT_ID_PROPERTY) {
+                self.bind(*node_id, agent_id);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Unbind a node, returning the agent it was bound to
+    pub fn unbind(&self, node_id: SceneId) -> Option<String> {
+        self.bound.write().remove(&node_id).map(|bound| bound.agent_id)
+    }
+
+    /// Agent a node is bound to
+    pub fn agent_id(&self, node_id: SceneId) -> Option<String> {
+        self.bound.read().get(&node_id).map(|bound| bound.agent_id.clone())
+    }
+
+    /// Node bound to an agent
+    pub fn node_id(&self, agent_id: &str) -> Option<SceneId> {
+        self.bound.read().iter().find(|(_, bound)| bound.agent_id == agent_id).map(|(node_id, _)| *node_id)
+    }
+
+    /// Task the agent of a node is working on
+    pub fn task(&self, node_id: SceneId) -> Option<AgentTaskProgress> {
+        self.bound.read().get(&node_id).and_then(|bound| bound.task.clone())
+    }
+
+    /// Conversation between the user and a node's agent, started on first open
+    pub async fn open_conversation(&self, system: &AIAgentSystem, node_id: SceneId) -> Result<Conversation, AIError> {
+        let (agent_id, existing) = {
+            let bound = self.bound.read();
+            let bound = bound.get(&node_id).ok_or_else(|| not_bound(node_id))?;
+            (bound.agent_id.clone(), bound.conversation.clone())
+        };
+        if let Some(conversation_id) = existing {
+            if let Some(conversation) = system.get_conversation(&conversation_id).await {
+                return Ok(conversation);
+            }
+        }
+
+        let conversation_id = system
+            .create_conversation(vec![USER_PARTICIPANT.to_string(), agent_id], ConversationType::Direct)
+            .await?;
+        if let Some(bound) = self.bound.write().get_mut(&node_id) {
+            bound.conversation = Some(conversation_id.clone());
+        }
+        system
+            .get_conversation(&conversation_id)
+            .await
+            .ok_or_else(|| AIError::Configuration(format!("Conversation not found: {}", conversation_id)))
+    }
+
+    /// Ask a node's agent a question in its conversation, returning the task answering it
+    pub async fn ask(&self, system: &AIAgentSystem, node_id: SceneId, question: &str) -> Result<String, AIError> {
+        let conversation = self.open_conversation(system, node_id).await?;
+        let agent_id = self.agent_id(node_id).ok_or_else(|| not_bound(node_id))?;
+
+        let mut message = create_text_message(
+            USER_PARTICIPANT.to_string(),
+            vec![agent_id.clone()],
+            question.to_string(),
+            MessageType::Query,
+        );
+        message.conversation_id = Some(conversation.id.clone());
+        system.add_message_to_conversation(&conversation.id, message).await?;
+
+        let task = AgentTask {
+            id: uuid::Uuid::new_v4().to_string(),
+            description: question.to_string(),
+            task_type: TaskType::Question,
+            priority: TaskPriority::Normal,
+            status: TaskStatus::Queued,
+            input: serde_json::json!({ "question": question, "conversation_id": conversation.id }),
+            output: None,
+            error: None,
+            assigned_agent: agent_id.clone(),
+            created_at: Utc::now(),
+            started_at: None,
+            completed_at: None,
+            metadata: HashMap::new(),
+        };
+        let task_id = system.submit_task(&agent_id, task).await?;
+        if let Some(bound) = self.bound.write().get_mut(&node_id) {
+            bound.questions.insert(task_id.clone());
+        }
+        Ok(task_id)
+    }
+
+    /// Add the answer to a question asked with [`ask`](Self::ask) to its conversation
+    ///
+    /// Returns whether the result answered such a question.
+    pub async fn deliver_reply(&self, system: &AIAgentSystem, result: &AgentExecutionResult) -> Result<bool, AIError> {
+        let conversation = {
+            let mut bound = self.bound.write();
+            let Some(bound) = bound.values_mut().find_map(|bound| bound.questions.remove(&result.task_id).then_some(bound)) else {
+                return Ok(false);
+            };
+            bound.conversation.clone()
+        };
+        let Some(conversation_id) = conversation else {
+            return Ok(false);
+        };
+
+        let answer = result
+            .result
+            .as_ref()
+            .and_then(|value| value.get("response").or_else(|| value.get("message")))
+            .and_then(|text| text.as_str())
+            .map(str::to_string)
+            .or_else(|| result.error.clone())
+            .unwrap_or_default();
+        let message_type = if result.success { MessageType::QueryResponse } else { MessageType::Error };
+        let mut message =
+            create_text_message(result.agent_id.clone(), vec![USER_PARTICIPANT.to_string()], answer, message_type);
+        message.conversation_id = Some(conversation_id.clone());
+        message.reply_to = Some(result.task_id.clone());
+        system.add_message_to_conversation(&conversation_id, message).await?;
+        Ok(true)
+    }
+
+    /// Follow an agent event, returning the nodes spawned for artifacts
+    pub fn handle_event(&self, event: &AgentEvent, nodes: &mut NodeManager, scene: &mut Scene) -> Vec<SceneId> {
+        let agent_id = match event {
+            AgentEvent::StatusChanged { agent_id, .. }
+            | AgentEvent::TaskStarted { agent_id, .. }
+            | AgentEvent::TaskProgress { agent_id, .. } => agent_id,
+            AgentEvent::TaskFinished(result) => &result.agent_id,
+        };
+        let Some(node_id) = self.node_id(agent_id) else {
+            return Vec::new();
+        };
+
+        {
+            let mut bound = self.bound.write();
+            let Some(bound) = bound.get_mut(&node_id) else {
+                return Vec::new();
+            };
+            match event {
+                AgentEvent::StatusChanged { status, .. } => bound.status = Some(status.clone()),
+                AgentEvent::TaskStarted { task_id, description, .. } => {
+                    bound.task = Some(AgentTaskProgress {
+                        task_id: task_id.clone(),
+                        description: description.clone(),
+                        progress: None,
+                    });
+                }
+                AgentEvent::TaskProgress { task_id, progress, .. } => {
+                    if let Some(task) = bound.task.as_mut().filter(|task| task.task_id == *task_id) {
+                        task.progress = Some(*progress);
+                    }
+                }
+                AgentEvent::TaskFinished(result) => {
+                    if bound.task.as_ref().is_some_and(|task| task.task_id == result.task_id) {
+                        bound.task = None;
+                    }
+                }
+            }
+        }
+
+        let AgentEvent::TaskFinished(result) = event else {
+            return Vec::new();
+        };
+        let mut spawned = Vec::new();
+        for artifact in AgentArtifact::from_result(result) {
+            match spawn_artifact(node_id, &artifact, spawned.len(), nodes, scene) {
+                Ok(artifact_node) => spawned.push(artifact_node),
+                Err(e) => log::warn!("Could not add artifact {:?} of task {}: {}", artifact, result.task_id, e),
+            }
+        }
+        spawned
+    }
+
+    /// Badge text and state for a node's agent, if it has anything to show
+    pub fn badge(&self, node_id: SceneId) -> Option<(String, BadgeState)> {
+        let bound = self.bound.read();
+        let bound = bound.get(&node_id)?;
+        match (&bound.task, &bound.status) {
+            (Some(task), _) => {
+                let label = task.progress.map_or_else(|| "…".to_string(), |progress| format!("{:.0}%", progress * 100.0));
+                Some((label, BadgeState::Ok))
+            }
+            (None, Some(AgentStatus::Error)) => Some(("!".to_string(), BadgeState::Critical)),
+            (None, Some(AgentStatus::Waiting)) => Some(("?".to_string(), BadgeState::Warning)),
+            _ => None,
+        }
+    }
+
+    /// Show a node's agent activity on its visual data
+    pub fn apply(&self, node_id: SceneId, visual: &mut NodeVisualData) {
+        if let Some((label, state)) = self.badge(node_id) {
+            visual.badge = Some(label);
+            visual.extensions.badge_state = Some(state);
+        }
+        visual.glow |= self.task(node_id).is_some();
+    }
+}
+
+fn not_bound(node_id: SceneId) -> AIError {
+    AIError::Configuration(format!("Node {} is not bound to an agent", node_id))
+}
+
+/// Add an artifact node next to the agent node, connected to it
+fn spawn_artifact(
+    agent_node: SceneId,
+    artifact: &AgentArtifact,
+    index: usize,
+    nodes: &mut NodeManager,
+    scene: &mut Scene,
+) -> Result<SceneId, NodeError> {
+    let mut scene_node = artifact.to_node(0)?.to_scene_node();
+    if let Some(anchor) = scene.get_node(agent_node) {
+        scene_node.position = anchor.position + Vec3::new(anchor.radius + 1.5, index as f32 * 1.5, 0.0);
+    }
+    let node_id = scene.add_node(scene_node);
+    nodes.add_node(artifact.to_node(node_id)?)?;
+    scene.add_edge(SceneEdge {
+        id: 0,
+        source: node_id,
+        target: agent_node,
+        edge_type: EdgeType::CreatedBy,
+        weight: 1.0,
+        color: ARTIFACT_EDGE_COLOR,
+        visible: true,
+        animated: false,
+    });
+    Ok(node_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::langchain::{AgentMemory, AgentMetrics};
+    use crate::agents::{AgentConfig, AgentType, MemoryConfig, MemoryType};
+
+    fn agent() -> Agent {
+        Agent {
+            id: "agent-1".to_string(),
+            name: "Researcher".to_string(),
+            description: None,
+            agent_type: AgentType::Research,
+            status: AgentStatus::Idle,
+            capabilities: Vec::new(),
+            config: AgentConfig {
+                model: "llama3.2:latest".to_string(),
+                system_prompt: String::new(),
+                temperature: 0.7,
+                max_tokens: 1024,
+                enable_tools: false,
+                tools: Vec::new(),
+                tool_timeout: 30,
+                max_steps: 10,
+                enable_memory: false,
+                memory_config: MemoryConfig {
+                    memory_type: MemoryType::Conversation,
+                    max_entries: 10,
+                    retention_hours: 1,
+                    enable_similarity: false,
+                    similarity_threshold: 0.8,
+                },
+            },
+            memory: AgentMemory::default(),
+            metrics: AgentMetrics::default(),
+            created_at: Utc::now(),
+            last_activity: Utc::now(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_agent_node_shows_progress_and_spawns_artifacts() {
+        let (mut nodes, mut scene) = (NodeManager::new(), Scene::new());
+        let binding = AgentNodeBinding::new();
+        let node_id = binding.add_agent_node(&agent(), Position::origin(), &mut nodes, &mut scene).unwrap();
+        assert_eq!(binding.node_id("agent-1"), Some(node_id));
+        assert!(binding.badge(node_id).is_none());
+
+        let started = AgentEvent::TaskStarted {
+            agent_id: "agent-1".to_string(),
+            task_id: "task-1".to_string(),
+            description: "Find sources".to_string(),
+        };
+        binding.handle_event(&started, &mut nodes, &mut scene);
+        let progress = AgentEvent::TaskProgress { agent_id: "agent-1".to_string(), task_id: "task-1".to_string(), progress: 0.4 };
+        binding.handle_event(&progress, &mut nodes, &mut scene);
+        let mut visual = NodeVisualData::default();
+        binding.apply(node_id, &mut visual);
+        assert_eq!(visual.badge.as_deref(), Some("40%"));
+        assert!(visual.glow);
+
+        let finished = AgentEvent::TaskFinished(AgentExecutionResult {
+            task_id: "task-1".to_string(),
+            agent_id: "agent-1".to_string(),
+            success: true,
+            result: Some(serde_json::json!({ "artifacts": [
+                { "kind": "note", "title": "Summary", "content": "Three sources found" },
+                { "kind": "url", "url": "https://example.org/paper" },
+                { "kind": "file", "path": "/nonexistent/report.md" },
+                { "kind": "unknown" },
+            ]})),
+            error: None,
+            steps: Vec::new(),
+            tokens_used: 0,
+            execution_time: std::time::Duration::ZERO,
+            tools_used: Vec::new(),
+            memory_updates: Vec::new(),
+        });
+        let spawned = binding.handle_event(&finished, &mut nodes, &mut scene);
+        assert_eq!(spawned.len(), 2);
+        for artifact in &spawned {
+            let edge = scene.edges().find(|edge| edge.source == *artifact).unwrap();
+            assert_eq!(edge.target, node_id);
+            assert!(matches!(edge.edge_type, EdgeType::CreatedBy));
+        }
+        assert!(binding.badge(node_id).is_none());
+
+        // Bindings are restored from the node's metadata
+        let restored = AgentNodeBinding::new();
+        assert_eq!(restored.bind_scene(&scene), 1);
+        assert_eq!(restored.agent_id(node_id).as_deref(), Some("agent-1"));
+    }
+}
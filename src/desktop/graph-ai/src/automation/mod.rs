@@ -11,8 +11,12 @@ pub mod browser;
 pub mod ui;
 pub mod workflows;
 pub mod scheduler;
+pub mod script;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
 
 use crate::AIError;
+use horizonos_graph_errors::{Category, Problem, ProblemsPanel, RecoveryAction, RecoveryKind, Severity};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -20,6 +24,7 @@ use parking_lot::RwLock;
 use chrono::{DateTime, Utc};
 use tokio::sync::mpsc;
 use uuid::Uuid;
+use async_trait::async_trait;
 use log::{info, warn, error, debug};
 
 /// Automation configuration
@@ -122,6 +127,33 @@ pub struct ExecutionResult {
     pub logs: Vec<ExecutionLog>,
 }
 
+impl ExecutionResult {
+    /// Problem describing a failed or timed-out execution
+    ///
+    /// The problem carries the workflow and execution ids in its context
+    /// and offers to run the workflow again.
+    pub fn problem(&self) -> Option<Problem> {
+        let message = match self.status {
+            ExecutionStatus::Failed => format!("Automation {} failed", self.workflow_id),
+            ExecutionStatus::Timeout => format!("Automation {} timed out", self.workflow_id),
+            _ => return None,
+        };
+        let mut problem = Problem::new("automation.execution_failed", Category::Automation, Severity::Error, message)
+            .in_subsystem("automation")
+            .with_context("workflow_id", &self.workflow_id)
+            .with_context("execution_id", &self.execution_id)
+            .with_recovery(RecoveryAction::new(
+                "rerun",
+                "Run again",
+                RecoveryKind::Custom { handler: "automation.rerun".to_string() },
+            ));
+        if let Some(error) = &self.error {
+            problem = problem.with_detail(error);
+        }
+        Some(problem)
+    }
+}
+
 /// Individual execution step
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionStep {
@@ -169,6 +201,40 @@ pub enum LogLevel {
     Error,
 }
 
+/// Public automation operations, implemented by `AutomationManager` and by
+/// the scripted double in `testing`
+#[async_trait]
+pub trait AutomationService: Send + Sync {
+    /// Execute a workflow, returning the execution ID
+    async fn execute_workflow(
+        &self,
+        workflow_id: &str,
+        input: Option<serde_json::Value>,
+        user_id: &str,
+    ) -> Result<String, AIError>;
+    
+    /// Get execution status
+    fn get_execution_status(&self, execution_id: &str) -> Option<ExecutionStatus>;
+    
+    /// Get execution result
+    fn get_execution_result(&self, execution_id: &str) -> Option<ExecutionResult>;
+    
+    /// List active executions
+    fn list_active_executions(&self) -> Vec<AutomationContext>;
+    
+    /// Cancel execution
+    async fn cancel_execution(&self, execution_id: &str) -> Result<(), AIError>;
+    
+    /// Get available workflows
+    async fn list_workflows(&self) -> Result<Vec<workflows::WorkflowDefinition>, AIError>;
+    
+    /// Create a new workflow
+    async fn create_workflow(&self, workflow: workflows::WorkflowDefinition) -> Result<String, AIError>;
+    
+    /// Delete a workflow
+    async fn delete_workflow(&self, workflow_id: &str) -> Result<(), AIError>;
+}
+
 /// Automation manager that coordinates all automation systems
 pub struct AutomationManager {
     /// Configuration
@@ -189,6 +255,8 @@ pub struct AutomationManager {
     execution_tx: mpsc::UnboundedSender<ExecutionResult>,
     /// Statistics
     stats: Arc<RwLock<AutomationStats>>,
+    /// Where failed executions are reported
+    problems: Arc<RwLock<Option<Arc<ProblemsPanel>>>>,
 }
 
 /// Automation statistics
@@ -213,13 +281,12 @@ pub struct AutomationStats {
 impl AutomationManager {
     /// Create a new automation manager
     pub async fn new(config: AutomationConfig) -> Result<Self, AIError> {
-        let config = Arc::new(RwLock::new(config.clone()));
-        
         // Initialize components
-        let n8n = Arc::new(n8n::N8nIntegration::new(config.read().n8n.clone()).await?);
-        let browser = Arc::new(browser::BrowserAutomation::new(config.read().browser.clone()).await?);
-        let ui = Arc::new(ui::UIAutomation::new(config.read().ui.clone()).await?);
-        let scheduler = Arc::new(scheduler::WorkflowScheduler::new(config.read().scheduler.clone()).await?);
+        let n8n = Arc::new(n8n::N8nIntegration::new(config.n8n.clone()).await?);
+        let browser = Arc::new(browser::BrowserAutomation::new(config.browser.clone()).await?);
+        let ui = Arc::new(ui::UIAutomation::new(config.ui.clone()).await?);
+        let scheduler = Arc::new(scheduler::WorkflowScheduler::new(config.scheduler.clone()).await?);
+        let config = Arc::new(RwLock::new(config));
         
         // Create execution channel
         let (execution_tx, mut execution_rx) = mpsc::unbounded_channel();
@@ -234,13 +301,20 @@ impl AutomationManager {
             execution_results: Arc::new(RwLock::new(HashMap::new())),
             execution_tx,
             stats: Arc::new(RwLock::new(AutomationStats::default())),
+            problems: Arc::new(RwLock::new(None)),
         };
         
         // Start execution result processor
         let execution_results = manager.execution_results.clone();
         let stats = manager.stats.clone();
+        let problems = manager.problems.clone();
         tokio::spawn(async move {
             while let Some(result) = execution_rx.recv().await {
+                // Tell the user about failed executions
+                if let (Some(panel), Some(problem)) = (problems.read().as_ref(), result.problem()) {
+                    panel.report(problem);
+                }
+                
                 // Store execution result
                 execution_results.write().insert(result.execution_id.clone(), result.clone());
                 
@@ -266,6 +340,11 @@ impl AutomationManager {
         Ok(manager)
     }
     
+    /// Report failed executions to a problems panel
+    pub fn set_problems_panel(&self, problems: Arc<ProblemsPanel>) {
+        *self.problems.write() = Some(problems);
+    }
+    
     /// Start automation services
     pub async fn start(&self) -> Result<(), AIError> {
         if !self.config.read().enabled {
@@ -377,7 +456,8 @@ impl AutomationManager {
     
     /// Cancel execution
     pub async fn cancel_execution(&self, execution_id: &str) -> Result<(), AIError> {
-        if let Some(context) = self.active_executions.write().remove(execution_id) {
+        let context = self.active_executions.write().remove(execution_id);
+        if let Some(context) = context {
             // Try to cancel in n8n
             self.n8n.cancel_execution(execution_id).await?;
             
@@ -480,6 +560,73 @@ impl AutomationManager {
     }
 }
 
+#[async_trait]
+impl AutomationService for AutomationManager {
+    async fn execute_workflow(
+        &self,
+        workflow_id: &str,
+        input: Option<serde_json::Value>,
+        user_id: &str,
+    ) -> Result<String, AIError> {
+        AutomationManager::execute_workflow(self, workflow_id, input, user_id).await
+    }
+    
+    fn get_execution_status(&self, execution_id: &str) -> Option<ExecutionStatus> {
+        AutomationManager::get_execution_status(self, execution_id)
+    }
+    
+    fn get_execution_result(&self, execution_id: &str) -> Option<ExecutionResult> {
+        AutomationManager::get_execution_result(self, execution_id)
+    }
+    
+    fn list_active_executions(&self) -> Vec<AutomationContext> {
+        AutomationManager::list_active_executions(self)
+    }
+    
+    async fn cancel_execution(&self, execution_id: &str) -> Result<(), AIError> {
+        AutomationManager::cancel_execution(self, execution_id).await
+    }
+    
+    async fn list_workflows(&self) -> Result<Vec<workflows::WorkflowDefinition>, AIError> {
+        AutomationManager::list_workflows(self).await
+    }
+    
+    async fn create_workflow(&self, workflow: workflows::WorkflowDefinition) -> Result<String, AIError> {
+        AutomationManager::create_workflow(self, workflow).await
+    }
+    
+    async fn delete_workflow(&self, workflow_id: &str) -> Result<(), AIError> {
+        AutomationManager::delete_workflow(self, workflow_id).await
+    }
+}
+
+/// Starts workflows requested by node lifecycle hooks
+pub struct HookWorkflowLauncher {
+    service: Arc<dyn AutomationService>,
+    runtime: tokio::runtime::Handle,
+}
+
+impl HookWorkflowLauncher {
+    /// Create a launcher running workflows on `runtime`
+    pub fn new(service: Arc<dyn AutomationService>, runtime: tokio::runtime::Handle) -> Self {
+        Self { service, runtime }
+    }
+}
+
+impl horizonos_graph_nodes::hooks::WorkflowLauncher for HookWorkflowLauncher {
+    fn launch(&self, workflow_id: &str, input: serde_json::Value) -> Result<(), String> {
+        let service = self.service.clone();
+        let workflow_id = workflow_id.to_string();
+        self.runtime.spawn(async move {
+            match service.execute_workflow(&workflow_id, Some(input), "lifecycle-hooks").await {
+                Ok(execution_id) => debug!("Hook started workflow {} ({})", workflow_id, execution_id),
+                Err(e) => warn!("Hook failed to start workflow {}: {}", workflow_id, e),
+            }
+        });
+        Ok(())
+    }
+}
+
 /// Automation health status
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutomationHealth {
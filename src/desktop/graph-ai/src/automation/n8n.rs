@@ -213,8 +213,7 @@ impl N8nIntegration {
     
     /// Test connection to n8n server
     async fn test_connection(&self) -> Result<(), AIError> {
-        let config = self.config.read();
-        let url = format!("{}/healthz", config.server_url);
+        let url = format!("{}/healthz", self.config.read().server_url);
         
         let response = self.client.get(&url).send().await
             .map_err(|e| AIError::Configuration(format!("Failed to connect to n8n: {}", e)))?;
@@ -2,8 +2,12 @@
 //! 
 //! This module provides scheduling capabilities for automation workflows,
 //! including cron-like scheduling, interval-based scheduling, and event-driven scheduling.
+//! Schedules of automation node scripts run the script when they are due,
+//! once a [`ScriptRuntime`] is attached.
 
+use super::script::{AutomationScript, ScriptGraph, ScriptOutcome, ScriptRuntime};
 use crate::AIError;
+use horizonos_graph_errors::{Category, Problem, ProblemsPanel, RecoveryAction, RecoveryKind, Severity};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -169,6 +173,34 @@ pub struct ScheduledExecutionResult {
     pub retry_attempts: u32,
 }
 
+impl ScheduledExecutionResult {
+    /// Problem to report for a failed or timed out run, `None` otherwise
+    ///
+    /// The problem carries the schedule, workflow and execution ids in its
+    /// context and offers to run the workflow again.
+    pub fn problem(&self) -> Option<Problem> {
+        let message = match self.status {
+            ExecutionStatus::Failed => format!("Scheduled automation {} failed", self.workflow_id),
+            ExecutionStatus::Timeout => format!("Scheduled automation {} timed out", self.workflow_id),
+            _ => return None,
+        };
+        let mut problem = Problem::new("automation.execution_failed", Category::Automation, Severity::Error, message)
+            .in_subsystem("automation")
+            .with_context("schedule_id", &self.schedule_id)
+            .with_context("workflow_id", &self.workflow_id)
+            .with_context("execution_id", &self.execution_id)
+            .with_recovery(RecoveryAction::new(
+                "rerun",
+                "Run again",
+                RecoveryKind::Custom { handler: "automation.rerun".to_string() },
+            ));
+        if let Some(error) = &self.error {
+            problem = problem.with_detail(error);
+        }
+        Some(problem)
+    }
+}
+
 /// Execution status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ExecutionStatus {
@@ -202,6 +234,19 @@ pub struct WorkflowScheduler {
     execution_sender: mpsc::UnboundedSender<ScheduledExecutionResult>,
     /// Statistics
     stats: Arc<RwLock<SchedulerStats>>,
+    /// Scripts of automation nodes, by workflow ID
+    scripts: Arc<RwLock<HashMap<String, AutomationScript>>>,
+    /// Runs scripts against the graph, once attached
+    script_host: Arc<RwLock<Option<ScriptHost>>>,
+    /// Where failed executions are reported
+    problems: Arc<RwLock<Option<Arc<ProblemsPanel>>>>,
+}
+
+/// Interpreter and graph scripts run with
+#[derive(Clone)]
+struct ScriptHost {
+    runtime: Arc<ScriptRuntime>,
+    graph: Arc<dyn ScriptGraph>,
 }
 
 /// Scheduler statistics
@@ -237,6 +282,9 @@ impl WorkflowScheduler {
             scheduler_handle: Arc::new(RwLock::new(None)),
             execution_sender,
             stats: Arc::new(RwLock::new(SchedulerStats::default())),
+            scripts: Arc::new(RwLock::new(HashMap::new())),
+            script_host: Arc::new(RwLock::new(None)),
+            problems: Arc::new(RwLock::new(None)),
         };
         
         // Load schedules from file if persistent
@@ -247,8 +295,14 @@ impl WorkflowScheduler {
         // Start execution result processor
         let execution_history = scheduler.execution_history.clone();
         let stats = scheduler.stats.clone();
+        let problems = scheduler.problems.clone();
         tokio::spawn(async move {
             while let Some(result) = execution_receiver.recv().await {
+                // Tell the user about failed executions
+                if let (Some(panel), Some(problem)) = (problems.read().as_ref(), result.problem()) {
+                    panel.report(problem);
+                }
+                
                 // Store execution result
                 execution_history.write().push(result.clone());
                 
@@ -274,11 +328,20 @@ impl WorkflowScheduler {
         Ok(scheduler)
     }
     
+    /// Report failed executions to a problems panel
+    pub fn set_problems_panel(&self, problems: Arc<ProblemsPanel>) {
+        *self.problems.write() = Some(problems);
+    }
+    
     /// Start the scheduler
     pub async fn start(&self) -> Result<(), AIError> {
         if !self.config.read().enabled {
             return Ok(());
         }
+        if !crate::session_allows(crate::AUTOMATION_ENV) {
+            info!("Workflow scheduler is disabled by the startup profile");
+            return Ok(());
+        }
         
         if self.scheduler_handle.read().is_some() {
             return Ok(());
@@ -287,9 +350,11 @@ impl WorkflowScheduler {
         let config = self.config.clone();
         let schedules = self.schedules.clone();
         let execution_sender = self.execution_sender.clone();
+        let scripts = self.scripts.clone();
+        let script_host = self.script_host.clone();
         
         *self.scheduler_handle.write() = Some(tokio::spawn(async move {
-            Self::scheduler_loop(config, schedules, execution_sender).await;
+            Self::scheduler_loop(config, schedules, execution_sender, scripts, script_host).await;
         }));
         
         info!("Workflow scheduler started");
@@ -398,6 +463,44 @@ impl WorkflowScheduler {
         }
     }
     
+    /// Run scripts of automation nodes with `runtime`, against `graph`
+    pub fn attach_scripts(&self, runtime: ScriptRuntime, graph: Arc<dyn ScriptGraph>) {
+        *self.script_host.write() = Some(ScriptHost { runtime: Arc::new(runtime), graph });
+    }
+    
+    /// Register or replace the script of an automation node, returning the workflow ID to schedule it under
+    ///
+    /// Scripts are not persisted with the schedules; they are registered
+    /// again from their nodes when the graph is loaded.
+    pub fn register_script(&self, script: AutomationScript) -> Result<String, AIError> {
+        if let Some(host) = self.script_host.read().as_ref() {
+            host.runtime.check(&script.source)?;
+        }
+        let workflow_id = script.workflow_id();
+        self.scripts.write().insert(workflow_id.clone(), script);
+        Ok(workflow_id)
+    }
+    
+    /// Forget the script registered under a workflow ID
+    pub fn unregister_script(&self, workflow_id: &str) -> Option<AutomationScript> {
+        self.scripts.write().remove(workflow_id)
+    }
+    
+    /// Run a registered script now, outside its schedule
+    pub async fn run_script(&self, workflow_id: &str) -> Result<ScriptOutcome, AIError> {
+        let script = self.scripts.read().get(workflow_id).cloned()
+            .ok_or_else(|| AIError::Configuration(format!("No script registered for {}", workflow_id)))?;
+        let host = self.script_host.read().clone()
+            .ok_or_else(|| AIError::Unavailable("No script runtime attached".to_string()))?;
+        Self::run_script_on(host, script).await
+    }
+    
+    async fn run_script_on(host: ScriptHost, script: AutomationScript) -> Result<ScriptOutcome, AIError> {
+        tokio::task::spawn_blocking(move || host.runtime.run(&script, host.graph))
+            .await
+            .map_err(|e| AIError::Cancelled(format!("Script run aborted: {}", e)))
+    }
+    
     /// Get a schedule
     pub fn get_schedule(&self, schedule_id: &str) -> Option<Schedule> {
         self.schedules.read().get(schedule_id).cloned()
@@ -444,6 +547,8 @@ impl WorkflowScheduler {
         config: Arc<RwLock<SchedulerConfig>>,
         schedules: Arc<RwLock<HashMap<String, Schedule>>>,
         execution_sender: mpsc::UnboundedSender<ScheduledExecutionResult>,
+        scripts: Arc<RwLock<HashMap<String, AutomationScript>>>,
+        script_host: Arc<RwLock<Option<ScriptHost>>>,
     ) {
         let mut interval = tokio::time::interval(Duration::from_secs(config.read().check_interval));
         
@@ -499,6 +604,28 @@ impl WorkflowScheduler {
             for schedule in schedules_to_execute {
                 debug!("Executing scheduled workflow: {} -> {}", schedule.id, schedule.workflow_id);
                 
+                // Scripts run in the background and report when they finish
+                let script = scripts.read().get(&schedule.workflow_id).cloned();
+                let host = script_host.read().clone();
+                if let (Some(script), Some(host)) = (script, host) {
+                    let execution_sender = execution_sender.clone();
+                    tokio::spawn(async move {
+                        let result = match Self::run_script_on(host, script).await {
+                            Ok(outcome) => Self::script_result(&schedule, now, outcome),
+                            Err(e) => ScheduledExecutionResult {
+                                status: ExecutionStatus::Failed,
+                                error: Some(e.to_string()),
+                                completed_at: Some(Utc::now()),
+                                ..Self::scheduled_result(&schedule, now)
+                            },
+                        };
+                        if let Err(e) = execution_sender.send(result) {
+                            error!("Failed to send execution result: {}", e);
+                        }
+                    });
+                    continue;
+                }
+                
                 // Create execution result
                 let execution_result = ScheduledExecutionResult {
                     schedule_id: schedule.id.clone(),
@@ -525,6 +652,38 @@ impl WorkflowScheduler {
         }
     }
     
+    /// Result of a schedule that has just started
+    fn scheduled_result(schedule: &Schedule, now: DateTime<Utc>) -> ScheduledExecutionResult {
+        ScheduledExecutionResult {
+            schedule_id: schedule.id.clone(),
+            execution_id: uuid::Uuid::new_v4().to_string(),
+            workflow_id: schedule.workflow_id.clone(),
+            status: ExecutionStatus::Running,
+            scheduled_at: schedule.next_execution.unwrap_or(now),
+            executed_at: now,
+            completed_at: None,
+            duration: None,
+            result: None,
+            error: None,
+            retry_attempts: 0,
+        }
+    }
+    
+    /// Result of a finished script run
+    fn script_result(schedule: &Schedule, now: DateTime<Utc>, outcome: ScriptOutcome) -> ScheduledExecutionResult {
+        ScheduledExecutionResult {
+            status: if outcome.success() { ExecutionStatus::Success } else { ExecutionStatus::Failed },
+            completed_at: Some(Utc::now()),
+            duration: Some(outcome.duration),
+            result: Some(serde_json::json!({
+                "value": outcome.value,
+                "output": outcome.output,
+            })),
+            error: outcome.error,
+            ..Self::scheduled_result(schedule, now)
+        }
+    }
+    
     /// Calculate next execution time for a schedule
     fn calculate_next_execution(&self, schedule: &Schedule) -> Result<Option<DateTime<Utc>>, AIError> {
         Self::calculate_next_execution_static(schedule)
@@ -620,9 +779,10 @@ impl WorkflowScheduler {
     
     /// Load schedules from file
     async fn load_schedules(&self) -> Result<(), AIError> {
-        let config = self.config.read();
+        // The config lock is not held across the file reads, so the scheduler can start on any runtime thread
+        let schedule_file = self.config.read().schedule_file.clone();
         
-        if let Some(schedule_file) = &config.schedule_file {
+        if let Some(schedule_file) = &schedule_file {
             if tokio::fs::metadata(schedule_file).await.is_ok() {
                 let content = tokio::fs::read_to_string(schedule_file).await
                     .map_err(|e| AIError::Configuration(format!("Failed to read schedule file: {}", e)))?;
@@ -644,9 +804,9 @@ impl WorkflowScheduler {
     
     /// Save schedules to file
     async fn save_schedules(&self) -> Result<(), AIError> {
-        let config = self.config.read();
+        let schedule_file = self.config.read().schedule_file.clone();
         
-        if let Some(schedule_file) = &config.schedule_file {
+        if let Some(schedule_file) = &schedule_file {
             let schedules: Vec<Schedule> = self.schedules.read().values().cloned().collect();
             
             let content = serde_json::to_string_pretty(&schedules)
@@ -820,4 +980,65 @@ mod tests {
         assert_eq!(config.timeout, Some(300));
         assert!(config.retry_config.is_some());
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_registered_script_runs() {
+        use crate::automation::script::SceneScriptGraph;
+        use horizonos_graph_engine::Scene;
+        
+        let config = SchedulerConfig { persistent: false, ..Default::default() };
+        let scheduler = WorkflowScheduler::new(config).await.unwrap();
+        let node = horizonos_graph_nodes::AutomationNode::new_script(7, "Greeter".to_string(), "notify(\"hello\"); 40 + 2".to_string());
+        let script = AutomationScript::from_node(&node).unwrap();
+        
+        // Nothing runs scripts until a runtime is attached
+        let workflow_id = scheduler.register_script(script).unwrap();
+        assert!(scheduler.run_script(&workflow_id).await.is_err());
+        
+        let (tx, mut notifications) = mpsc::unbounded_channel();
+        let scene = Arc::new(RwLock::new(Scene::new()));
+        scheduler.attach_scripts(ScriptRuntime::default(), Arc::new(SceneScriptGraph::new(scene, tx)));
+        let outcome = scheduler.run_script(&workflow_id).await.unwrap();
+        assert_eq!(outcome.value, serde_json::json!(42));
+        assert_eq!(notifications.recv().await.unwrap().summary, "hello");
+        
+        // Scripts that do not compile are refused
+        let broken = horizonos_graph_nodes::AutomationNode::new_script(8, "Broken".to_string(), "let =".to_string());
+        assert!(scheduler.register_script(AutomationScript::from_node(&broken).unwrap()).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_failed_executions_are_reported() {
+        let config = SchedulerConfig { persistent: false, ..Default::default() };
+        let scheduler = WorkflowScheduler::new(config).await.unwrap();
+        let panel = Arc::new(ProblemsPanel::new());
+        scheduler.set_problems_panel(panel.clone());
+        
+        let result = |status| ScheduledExecutionResult {
+            schedule_id: "nightly".to_string(),
+            execution_id: uuid::Uuid::new_v4().to_string(),
+            workflow_id: "backup".to_string(),
+            status,
+            scheduled_at: Utc::now(),
+            executed_at: Utc::now(),
+            completed_at: None,
+            duration: None,
+            result: None,
+            error: Some("disk full".to_string()),
+            retry_attempts: 0,
+        };
+        assert!(result(ExecutionStatus::Success).problem().is_none());
+        scheduler.execution_sender.send(result(ExecutionStatus::Success)).unwrap();
+        scheduler.execution_sender.send(result(ExecutionStatus::Failed)).unwrap();
+        
+        for _ in 0..50 {
+            if !panel.problems().is_empty() {
+                break;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        let problems = panel.problems();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].detail.as_deref(), Some("disk full"));
+    }
+}
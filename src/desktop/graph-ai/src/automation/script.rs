@@ -0,0 +1,594 @@
+//! Scripts carried by automation nodes
+//!
+//! Automation nodes in the [`SCRIPT_LANGUAGE`] carry a Rhai script, which the
+//! [`WorkflowScheduler`](super::scheduler::WorkflowScheduler) runs on the
+//! node's schedule. A script sees the graph only through a [`ScriptGraph`]:
+//!
+//! - `find_nodes(text)` and `nodes_of_type(kind)` return maps with `id`,
+//!   `name` and `kind` (e.g. `"file"`)
+//! - `query(text)` returns the same maps for a graph query such as
+//!   `"file[modified<7d] -- task"`
+//! - `link(source, target, relation)` adds an edge such as `"depends_on"`
+//!   and returns its ID
+//! - `notify(summary)` and `notify(summary, body)` post a notification
+//! - `focus_mode(name)` switches to a notification focus mode such as
+//!   `"Deep Work"`, and `clear_focus_mode()` switches focus modes off
+//!
+//! The automation's parameters are in the `params` map and its node ID in
+//! `automation_id`; `print` and `debug` go to the run's output.
+//!
+//! Scripts cannot import modules, evaluate code, or reach files, processes
+//! or the network. Operations, call depth, data sizes and the edges and
+//! notifications one run may create are capped, and a run is stopped once
+//! its timeout passes.
+
+use crate::AIError;
+use chrono::{DateTime, Utc};
+use horizonos_graph_engine::{EdgeType, Scene, SceneEdge, SceneId};
+use horizonos_graph_nodes::hooks::node_type_name;
+use horizonos_graph_nodes::{AutomationNode, GraphQuery, SCRIPT_LANGUAGE};
+use parking_lot::{Mutex, RwLock};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Scope};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Color of edges created by scripts
+const SCRIPT_EDGE_COLOR: [f32; 4] = [0.4, 0.8, 0.4, 0.8];
+
+/// Relations scripts may link nodes with
+const RELATIONS: &[&str] = &["contains", "depends_on", "communicates_with", "created_by", "related_to", "works_on"];
+
+/// Resource limits of a script run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptLimits {
+    /// Interpreter operations before the run is stopped
+    pub max_operations: u64,
+    /// Nested function calls
+    pub max_call_depth: usize,
+    /// Length of a string in bytes
+    pub max_string_size: usize,
+    /// Elements of an array
+    pub max_array_size: usize,
+    /// Entries of a map
+    pub max_map_size: usize,
+    /// Nodes returned by one query
+    pub max_query_results: usize,
+    /// Edges and notifications one run may create
+    pub max_side_effects: usize,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            max_operations: 1_000_000,
+            max_call_depth: 32,
+            max_string_size: 64 * 1024,
+            max_array_size: 10_000,
+            max_map_size: 10_000,
+            max_query_results: 500,
+            max_side_effects: 100,
+        }
+    }
+}
+
+/// A node as scripts see it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptNode {
+    pub id: SceneId,
+    pub name: String,
+    /// Node type name, e.g. `file`
+    pub kind: String,
+}
+
+/// A notification posted by a script
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptNotification {
+    /// Automation node whose script posted it
+    pub automation_id: SceneId,
+    /// Name of the automation, shown as the sender
+    pub automation: String,
+    pub summary: String,
+    pub body: String,
+}
+
+/// A focus mode switch requested by a script
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptFocusMode {
+    /// Automation node whose script requested it
+    pub automation_id: SceneId,
+    /// Focus mode to switch to, `None` to switch focus modes off
+    pub mode: Option<String>,
+}
+
+/// The part of the graph scripts can reach
+pub trait ScriptGraph: Send + Sync {
+    /// Nodes whose name contains `text`, ignoring case; every node for empty text
+    fn find_nodes(&self, text: &str, limit: usize) -> Vec<ScriptNode>;
+
+    /// Nodes of a type such as `file` or `application`
+    fn nodes_of_type(&self, kind: &str, limit: usize) -> Vec<ScriptNode>;
+
+    /// Nodes matching a graph query
+    fn query(&self, query: &GraphQuery, limit: usize) -> Vec<ScriptNode>;
+
+    /// Add an edge between two nodes, returning its ID
+    fn link(&self, source: SceneId, target: SceneId, edge_type: EdgeType) -> Result<SceneId, String>;
+
+    /// Post a notification
+    fn notify(&self, notification: ScriptNotification);
+
+    /// Switch notification focus modes
+    fn set_focus_mode(&self, request: ScriptFocusMode) -> Result<(), String>;
+}
+
+/// A scene shared with scripts, behind whichever lock its owner uses
+pub trait SharedScene: Send + Sync {
+    fn read_scene<R>(&self, read: impl FnOnce(&Scene) -> R) -> R;
+
+    fn write_scene<R>(&self, write: impl FnOnce(&mut Scene) -> R) -> R;
+}
+
+impl SharedScene for RwLock<Scene> {
+    fn read_scene<R>(&self, read: impl FnOnce(&Scene) -> R) -> R {
+        read(&self.read())
+    }
+
+    fn write_scene<R>(&self, write: impl FnOnce(&mut Scene) -> R) -> R {
+        write(&mut self.write())
+    }
+}
+
+/// The compositor keeps its scene behind a standard mutex
+impl SharedScene for std::sync::Mutex<Scene> {
+    fn read_scene<R>(&self, read: impl FnOnce(&Scene) -> R) -> R {
+        read(&self.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn write_scene<R>(&self, write: impl FnOnce(&mut Scene) -> R) -> R {
+        write(&mut self.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+/// [`ScriptGraph`] over a shared scene, posting notifications and focus
+/// mode switches to channels
+pub struct SceneScriptGraph<S: SharedScene = RwLock<Scene>> {
+    scene: Arc<S>,
+    notifications: mpsc::UnboundedSender<ScriptNotification>,
+    focus_modes: Option<mpsc::UnboundedSender<ScriptFocusMode>>,
+}
+
+impl<S: SharedScene> SceneScriptGraph<S> {
+    pub fn new(scene: Arc<S>, notifications: mpsc::UnboundedSender<ScriptNotification>) -> Self {
+        Self { scene, notifications, focus_modes: None }
+    }
+
+    /// Let scripts switch focus modes by sending requests to `focus_modes`
+    pub fn with_focus_modes(mut self, focus_modes: mpsc::UnboundedSender<ScriptFocusMode>) -> Self {
+        self.focus_modes = Some(focus_modes);
+        self
+    }
+
+    fn nodes_where(&self, limit: usize, mut keep: impl FnMut(&ScriptNode) -> bool) -> Vec<ScriptNode> {
+        let mut nodes: Vec<ScriptNode> = self.scene.read_scene(|scene| {
+            scene
+                .nodes()
+                .map(|(id, node)| ScriptNode {
+                    id: *id,
+                    name: crate::search::node_name(&node.node_type).0,
+                    kind: node_type_name(&node.node_type).to_string(),
+                })
+                .filter(|node| keep(node))
+                .collect()
+        });
+        nodes.sort_by_key(|node| node.id);
+        nodes.truncate(limit);
+        nodes
+    }
+}
+
+impl<S: SharedScene> ScriptGraph for SceneScriptGraph<S> {
+    fn find_nodes(&self, text: &str, limit: usize) -> Vec<ScriptNode> {
+        let needle = text.to_lowercase();
+        self.nodes_where(limit, |node| node.name.to_lowercase().contains(&needle))
+    }
+
+    fn nodes_of_type(&self, kind: &str, limit: usize) -> Vec<ScriptNode> {
+        self.nodes_where(limit, |node| node.kind == kind)
+    }
+
+    fn query(&self, query: &GraphQuery, limit: usize) -> Vec<ScriptNode> {
+        let matches = self.scene.read_scene(|scene| query.evaluate(scene, Utc::now()));
+        self.nodes_where(limit, |node| matches.contains(&node.id))
+    }
+
+    fn link(&self, source: SceneId, target: SceneId, edge_type: EdgeType) -> Result<SceneId, String> {
+        self.scene.write_scene(|scene| {
+            for node_id in [source, target] {
+                if scene.get_node(node_id).is_none() {
+                    return Err(format!("Unknown node {}", node_id));
+                }
+            }
+            if source == target {
+                return Err("Cannot link a node to itself".to_string());
+            }
+            Ok(scene.add_edge(SceneEdge {
+                id: 0,
+                source,
+                target,
+                edge_type,
+                weight: 1.0,
+                color: SCRIPT_EDGE_COLOR,
+                visible: true,
+                animated: false,
+            }))
+        })
+    }
+
+    fn notify(&self, notification: ScriptNotification) {
+        if self.notifications.send(notification).is_err() {
+            log::debug!("Dropping script notification, nobody is listening");
+        }
+    }
+
+    fn set_focus_mode(&self, request: ScriptFocusMode) -> Result<(), String> {
+        self.focus_modes
+            .as_ref()
+            .ok_or_else(|| "Focus modes are not available".to_string())?
+            .send(request)
+            .map_err(|_| "Focus modes are not available".to_string())
+    }
+}
+
+/// Edge type for a relation name used by scripts
+pub fn edge_type_for(relation: &str) -> Option<EdgeType> {
+    Some(match relation {
+        "contains" => EdgeType::Contains,
+        "depends_on" => EdgeType::DependsOn,
+        "communicates_with" => EdgeType::CommunicatesWith,
+        "created_by" => EdgeType::CreatedBy,
+        "related_to" => EdgeType::RelatedTo { similarity: 1.0 },
+        "works_on" => EdgeType::WorksOn,
+        _ => return None,
+    })
+}
+
+/// A script ready to run
+#[derive(Debug, Clone)]
+pub struct AutomationScript {
+    /// Automation node carrying the script
+    pub automation_id: SceneId,
+    /// Automation name
+    pub name: String,
+    /// Script source
+    pub source: String,
+    /// Available to the script as `params`
+    pub parameters: HashMap<String, String>,
+    /// Time the run may take
+    pub timeout: Duration,
+}
+
+impl AutomationScript {
+    /// Script of an automation node, if its content is a script
+    pub fn from_node(node: &AutomationNode) -> Option<Self> {
+        node.is_script().then(|| Self {
+            automation_id: node.id,
+            name: node.name.clone(),
+            source: node.content.clone(),
+            parameters: node.parameters.clone(),
+            timeout: Duration::from_secs(node.timeout),
+        })
+    }
+
+    /// Workflow ID the script is scheduled under
+    pub fn workflow_id(&self) -> String {
+        script_workflow_id(self.automation_id)
+    }
+}
+
+/// Workflow ID of an automation node's script
+pub fn script_workflow_id(automation_id: SceneId) -> String {
+    format!("{}:{}", SCRIPT_LANGUAGE, automation_id)
+}
+
+/// Result of a script run
+#[derive(Debug, Clone)]
+pub struct ScriptOutcome {
+    /// Value of the script's last expression
+    pub value: serde_json::Value,
+    /// Lines written with `print` and `debug`
+    pub output: Vec<String>,
+    /// Why the run failed
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub duration: Duration,
+}
+
+impl ScriptOutcome {
+    pub fn success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Result to record on the automation node
+    pub fn to_execution_result(&self) -> horizonos_graph_nodes::ExecutionResult {
+        let duration = chrono::Duration::from_std(self.duration).unwrap_or_default();
+        horizonos_graph_nodes::ExecutionResult {
+            success: self.success(),
+            exit_code: None,
+            execution_time: self.duration.as_millis() as u64,
+            stdout: self.output.join("\n"),
+            stderr: self.error.clone().unwrap_or_default(),
+            start_time: self.started_at,
+            end_time: self.started_at + duration,
+            error: self.error.clone(),
+        }
+    }
+}
+
+/// Embedded interpreter for automation scripts
+#[derive(Debug, Clone, Default)]
+pub struct ScriptRuntime {
+    limits: ScriptLimits,
+}
+
+impl ScriptRuntime {
+    pub fn new(limits: ScriptLimits) -> Self {
+        Self { limits }
+    }
+
+    pub fn limits(&self) -> &ScriptLimits {
+        &self.limits
+    }
+
+    /// Check that a script compiles, e.g. while it is edited
+    pub fn check(&self, source: &str) -> Result<(), AIError> {
+        self.sandbox()
+            .compile(source)
+            .map(|_| ())
+            .map_err(|e| AIError::Configuration(format!("Invalid script: {}", e)))
+    }
+
+    /// Run a script to completion, blocking until it finishes or is stopped
+    pub fn run(&self, script: &AutomationScript, graph: Arc<dyn ScriptGraph>) -> ScriptOutcome {
+        let started_at = Utc::now();
+        let start = Instant::now();
+        let output = Arc::new(Mutex::new(Vec::new()));
+
+        let mut engine = self.sandbox();
+        let deadline = start + script.timeout;
+        engine.on_progress(move |_| (Instant::now() >= deadline).then_some(Dynamic::UNIT));
+        let print_output = output.clone();
+        engine.on_print(move |text| print_output.lock().push(text.to_string()));
+        let debug_output = output.clone();
+        engine.on_debug(move |text, _, _| debug_output.lock().push(text.to_string()));
+        self.register_graph_api(&mut engine, script, graph);
+
+        let mut scope = Scope::new();
+        scope.push_constant("automation_id", script.automation_id as i64);
+        let params: Map = script
+            .parameters
+            .iter()
+            .map(|(key, value)| (key.as_str().into(), Dynamic::from(value.clone())))
+            .collect();
+        scope.push_constant("params", params);
+
+        let result = engine.eval_with_scope::<Dynamic>(&mut scope, &script.source);
+        let duration = start.elapsed();
+        let (value, error) = match result {
+            Ok(value) => (rhai::serde::from_dynamic(&value).unwrap_or(serde_json::Value::Null), None),
+            Err(e) => {
+                let error = match *e {
+                    EvalAltResult::ErrorTerminated(..) => format!("Timed out after {:?}", script.timeout),
+                    other => other.to_string(),
+                };
+                log::warn!("Script of automation {} failed: {}", script.automation_id, error);
+                (serde_json::Value::Null, Some(error))
+            }
+        };
+        drop(engine);
+
+        let output = std::mem::take(&mut *output.lock());
+        ScriptOutcome { value, output, error, started_at, duration }
+    }
+
+    /// Engine without modules, `eval`, or anything reaching outside the interpreter
+    fn sandbox(&self) -> Engine {
+        let mut engine = Engine::new();
+        engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+        engine.set_max_operations(self.limits.max_operations);
+        engine.set_max_call_levels(self.limits.max_call_depth);
+        engine.set_max_string_size(self.limits.max_string_size);
+        engine.set_max_array_size(self.limits.max_array_size);
+        engine.set_max_map_size(self.limits.max_map_size);
+        engine.set_max_expr_depths(64, 32);
+        engine
+    }
+
+    fn register_graph_api(&self, engine: &mut Engine, script: &AutomationScript, graph: Arc<dyn ScriptGraph>) {
+        let limit = self.limits.max_query_results;
+        let query_graph = graph.clone();
+        engine.register_fn("find_nodes", move |text: &str| to_array(query_graph.find_nodes(text, limit)));
+        let query_graph = graph.clone();
+        engine.register_fn("nodes_of_type", move |kind: &str| to_array(query_graph.nodes_of_type(kind, limit)));
+        let query_graph = graph.clone();
+        engine.register_fn("query", move |text: &str| -> Result<Array, Box<EvalAltResult>> {
+            let query: GraphQuery = text.parse().map_err(|e| format!("Invalid query '{}': {}", text, e))?;
+            Ok(to_array(query_graph.query(&query, limit)))
+        });
+
+        // Edges and notifications share one budget per run
+        let budget = Arc::new(AtomicUsize::new(self.limits.max_side_effects));
+        let spend = move || -> Result<(), Box<EvalAltResult>> {
+            budget
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| left.checked_sub(1))
+                .map(|_| ())
+                .map_err(|_| "Too many edges and notifications for one run".into())
+        };
+
+        let link_graph = graph.clone();
+        let link_spend = spend.clone();
+        engine.register_fn(
+            "link",
+            move |source: i64, target: i64, relation: &str| -> Result<i64, Box<EvalAltResult>> {
+                let edge_type = edge_type_for(relation).ok_or_else(|| {
+                    format!("Unknown relation '{}', expected one of {:?}", relation, RELATIONS)
+                })?;
+                let source = SceneId::try_from(source).map_err(|_| format!("Invalid node ID {}", source))?;
+                let target = SceneId::try_from(target).map_err(|_| format!("Invalid node ID {}", target))?;
+                link_spend()?;
+                let edge_id = link_graph.link(source, target, edge_type)?;
+                Ok(edge_id as i64)
+            },
+        );
+
+        let automation_id = script.automation_id;
+        let focus_graph = graph.clone();
+        let set_focus_mode = move |mode: Option<String>| -> Result<(), Box<EvalAltResult>> {
+            focus_graph
+                .set_focus_mode(ScriptFocusMode { automation_id, mode })
+                .map_err(Into::into)
+        };
+        let clear_focus_mode = set_focus_mode.clone();
+        engine.register_fn("focus_mode", move |name: &str| set_focus_mode(Some(name.to_string())));
+        engine.register_fn("clear_focus_mode", move || clear_focus_mode(None));
+
+        let automation = script.name.clone();
+        let notify = move |summary: &str, body: &str| -> Result<(), Box<EvalAltResult>> {
+            spend()?;
+            graph.notify(ScriptNotification {
+                automation_id,
+                automation: automation.clone(),
+                summary: summary.to_string(),
+                body: body.to_string(),
+            });
+            Ok(())
+        };
+        let notify_summary = notify.clone();
+        engine.register_fn("notify", move |summary: &str| notify_summary(summary, ""));
+        engine.register_fn("notify", notify);
+    }
+}
+
+fn to_array(nodes: Vec<ScriptNode>) -> Array {
+    nodes
+        .into_iter()
+        .map(|node| {
+            let mut map = Map::new();
+            map.insert("id".into(), Dynamic::from(node.id as i64));
+            map.insert("name".into(), Dynamic::from(node.name));
+            map.insert("kind".into(), Dynamic::from(node.kind));
+            Dynamic::from_map(map)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::{FileType, NodeMetadata, NodeType, Position, SceneNode, Vec3};
+
+    fn add_node(scene: &mut Scene, node_type: NodeType) -> SceneId {
+        scene.add_node(SceneNode {
+            id: 0,
+            position: Position::origin(),
+            velocity: Vec3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type,
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        })
+    }
+
+    fn graph() -> (Arc<RwLock<Scene>>, Arc<dyn ScriptGraph>, mpsc::UnboundedReceiver<ScriptNotification>) {
+        let mut scene = Scene::new();
+        add_node(&mut scene, NodeType::File { path: "/home/user/notes.md".to_string(), file_type: FileType::RegularFile });
+        add_node(&mut scene, NodeType::Application { pid: 1, name: "Editor".to_string() });
+        let scene = Arc::new(RwLock::new(scene));
+        let (tx, rx) = mpsc::unbounded_channel();
+        (scene.clone(), Arc::new(SceneScriptGraph::new(scene, tx)), rx)
+    }
+
+    fn script(source: &str) -> AutomationScript {
+        AutomationScript {
+            automation_id: 42,
+            name: "Linker".to_string(),
+            source: source.to_string(),
+            parameters: HashMap::from([("relation".to_string(), "related_to".to_string())]),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    #[test]
+    fn test_script_queries_links_and_notifies() {
+        let (scene, graph, mut notifications) = graph();
+        let outcome = ScriptRuntime::default().run(
+            &script(
+                r#"
+                let file = find_nodes("notes")[0];
+                let app = nodes_of_type("application")[0];
+                link(app.id, file.id, params.relation);
+                if query("file -[related_to]- application").len() != 1 { throw "query missed the link"; }
+                notify("Linked", `${app.name} -> ${file.name}`);
+                print("done");
+                file.kind
+                "#,
+            ),
+            graph,
+        );
+
+        assert!(outcome.success(), "{:?}", outcome.error);
+        assert_eq!(outcome.value, serde_json::json!("file"));
+        assert_eq!(outcome.output, vec!["done".to_string()]);
+        assert_eq!(scene.read().edges().count(), 1);
+        let notification = notifications.try_recv().unwrap();
+        assert_eq!(notification.automation_id, 42);
+        assert_eq!(notification.body, "Editor -> notes.md");
+    }
+
+    #[test]
+    fn test_script_switches_focus_modes() {
+        let (scene, _graph, _notifications) = graph();
+        let (notifications, _) = mpsc::unbounded_channel();
+        let (tx, mut focus_modes) = mpsc::unbounded_channel();
+        let graph: Arc<dyn ScriptGraph> = Arc::new(SceneScriptGraph::new(scene.clone(), notifications).with_focus_modes(tx));
+
+        let outcome = ScriptRuntime::default().run(&script(r#"focus_mode("Deep Work"); clear_focus_mode();"#), graph);
+        assert!(outcome.success(), "{:?}", outcome.error);
+        assert_eq!(focus_modes.try_recv().unwrap(), ScriptFocusMode { automation_id: 42, mode: Some("Deep Work".to_string()) });
+        assert_eq!(focus_modes.try_recv().unwrap().mode, None);
+
+        // Without a listener the script fails instead of silently doing nothing
+        let (notifications, _) = mpsc::unbounded_channel();
+        let graph: Arc<dyn ScriptGraph> = Arc::new(SceneScriptGraph::new(scene, notifications));
+        assert!(!ScriptRuntime::default().run(&script(r#"focus_mode("Deep Work")"#), graph).success());
+    }
+
+    #[test]
+    fn test_script_sandbox() {
+        let (scene, graph, _notifications) = graph();
+        let runtime = ScriptRuntime::new(ScriptLimits { max_side_effects: 2, ..Default::default() });
+
+        // Runaway loops are stopped
+        let outcome = runtime.run(&script("loop {}"), graph.clone());
+        assert!(outcome.error.is_some());
+
+        // No modules or eval
+        assert!(!runtime.run(&script(r#"import "std" as s;"#), graph.clone()).success());
+        assert!(runtime.check(r#"eval("1")"#).is_err());
+
+        // Unknown relations and nodes are refused
+        assert!(!runtime.run(&script(r#"link(1, 2, "owns")"#), graph.clone()).success());
+        assert!(!runtime.run(&script(r#"link(1, 99, "contains")"#), graph.clone()).success());
+
+        // Side effects are capped per run
+        let outcome = runtime.run(&script(r#"for i in 0..5 { notify("spam") }"#), graph);
+        assert!(outcome.error.unwrap().contains("Too many"));
+        assert_eq!(scene.read().edges().count(), 0);
+    }
+}
@@ -0,0 +1,231 @@
+//! Scripted automation service for unit tests
+//!
+//! Enabled with the `test-util` feature. Workflows are stored in memory and
+//! executions finish immediately with a scripted outcome, unless held so a
+//! test can observe them while running.
+
+use super::{
+    AutomationContext, AutomationService, ExecutionResult, ExecutionStatus, WorkflowDefinition,
+};
+use crate::AIError;
+use async_trait::async_trait;
+use chrono::Utc;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Outcome a scripted workflow execution ends with
+#[derive(Debug, Clone)]
+pub enum ScriptedOutcome {
+    /// Succeed with the given result data
+    Success(serde_json::Value),
+    /// Fail with the given error message
+    Failure(String),
+}
+
+/// A call to `execute_workflow` on the mock
+#[derive(Debug, Clone)]
+pub struct RecordedExecution {
+    pub execution_id: String,
+    pub workflow_id: String,
+    pub input: Option<serde_json::Value>,
+    pub user_id: String,
+}
+
+/// Scripted `AutomationService`
+pub struct MockAutomationService {
+    /// Stored workflows by ID
+    workflows: RwLock<HashMap<String, WorkflowDefinition>>,
+    /// Outcomes by workflow ID
+    outcomes: RwLock<HashMap<String, ScriptedOutcome>>,
+    /// Executions that have not finished
+    active: RwLock<HashMap<String, AutomationContext>>,
+    /// Finished executions
+    results: RwLock<HashMap<String, ExecutionResult>>,
+    /// Every execution requested, in order
+    executions: RwLock<Vec<RecordedExecution>>,
+    /// Keep new executions running until `complete` is called
+    hold: RwLock<bool>,
+}
+
+impl MockAutomationService {
+    /// Create an empty service where every workflow succeeds with `null`
+    pub fn new() -> Self {
+        Self {
+            workflows: RwLock::new(HashMap::new()),
+            outcomes: RwLock::new(HashMap::new()),
+            active: RwLock::new(HashMap::new()),
+            results: RwLock::new(HashMap::new()),
+            executions: RwLock::new(Vec::new()),
+            hold: RwLock::new(false),
+        }
+    }
+
+    /// Set the outcome of future executions of a workflow
+    pub fn script(&self, workflow_id: &str, outcome: ScriptedOutcome) {
+        self.outcomes.write().insert(workflow_id.to_string(), outcome);
+    }
+
+    /// Keep new executions running until `complete` is called
+    pub fn hold_executions(&self, hold: bool) {
+        *self.hold.write() = hold;
+    }
+
+    /// Finish a held execution with its scripted outcome
+    pub fn complete(&self, execution_id: &str) -> Result<ExecutionResult, AIError> {
+        let context = self.active.write().remove(execution_id)
+            .ok_or_else(|| AIError::SessionNotFound(execution_id.to_string()))?;
+        Ok(self.finish(context))
+    }
+
+    /// Executions requested so far
+    pub fn executions(&self) -> Vec<RecordedExecution> {
+        self.executions.read().clone()
+    }
+
+    /// Build and store the result for a finished execution
+    fn finish(&self, context: AutomationContext) -> ExecutionResult {
+        let outcome = self.outcomes.read()
+            .get(&context.workflow_id)
+            .cloned()
+            .unwrap_or(ScriptedOutcome::Success(serde_json::Value::Null));
+        let (status, result, error) = match outcome {
+            ScriptedOutcome::Success(value) => (ExecutionStatus::Success, Some(value), None),
+            ScriptedOutcome::Failure(message) => (ExecutionStatus::Failed, None, Some(message)),
+        };
+        self.store(context, status, result, error)
+    }
+
+    /// Store a result for an execution
+    fn store(
+        &self,
+        context: AutomationContext,
+        status: ExecutionStatus,
+        result: Option<serde_json::Value>,
+        error: Option<String>,
+    ) -> ExecutionResult {
+        let ended_at = Utc::now();
+        let result = ExecutionResult {
+            execution_id: context.execution_id.clone(),
+            workflow_id: context.workflow_id,
+            status,
+            started_at: context.started_at,
+            ended_at: Some(ended_at),
+            duration: Some(ended_at.signed_duration_since(context.started_at).to_std().unwrap_or_default()),
+            result,
+            error,
+            steps: Vec::new(),
+            logs: Vec::new(),
+        };
+        self.results.write().insert(context.execution_id, result.clone());
+        result
+    }
+}
+
+impl Default for MockAutomationService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl AutomationService for MockAutomationService {
+    async fn execute_workflow(
+        &self,
+        workflow_id: &str,
+        input: Option<serde_json::Value>,
+        user_id: &str,
+    ) -> Result<String, AIError> {
+        let execution_id = Uuid::new_v4().to_string();
+        self.executions.write().push(RecordedExecution {
+            execution_id: execution_id.clone(),
+            workflow_id: workflow_id.to_string(),
+            input,
+            user_id: user_id.to_string(),
+        });
+
+        let context = AutomationContext {
+            execution_id: execution_id.clone(),
+            workflow_id: workflow_id.to_string(),
+            user_id: user_id.to_string(),
+            started_at: Utc::now(),
+            current_step: 0,
+            variables: HashMap::new(),
+            metadata: HashMap::new(),
+        };
+
+        if *self.hold.read() {
+            self.active.write().insert(execution_id.clone(), context);
+        } else {
+            self.finish(context);
+        }
+        Ok(execution_id)
+    }
+
+    fn get_execution_status(&self, execution_id: &str) -> Option<ExecutionStatus> {
+        if self.active.read().contains_key(execution_id) {
+            return Some(ExecutionStatus::Running);
+        }
+        self.results.read().get(execution_id).map(|result| result.status.clone())
+    }
+
+    fn get_execution_result(&self, execution_id: &str) -> Option<ExecutionResult> {
+        self.results.read().get(execution_id).cloned()
+    }
+
+    fn list_active_executions(&self) -> Vec<AutomationContext> {
+        self.active.read().values().cloned().collect()
+    }
+
+    async fn cancel_execution(&self, execution_id: &str) -> Result<(), AIError> {
+        let context = self.active.write().remove(execution_id);
+        if let Some(context) = context {
+            self.store(
+                context,
+                ExecutionStatus::Cancelled,
+                None,
+                Some("Execution cancelled by user".to_string()),
+            );
+        }
+        Ok(())
+    }
+
+    async fn list_workflows(&self) -> Result<Vec<WorkflowDefinition>, AIError> {
+        Ok(self.workflows.read().values().cloned().collect())
+    }
+
+    async fn create_workflow(&self, mut workflow: WorkflowDefinition) -> Result<String, AIError> {
+        let id = workflow.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+        workflow.id = Some(id.clone());
+        self.workflows.write().insert(id.clone(), workflow);
+        Ok(id)
+    }
+
+    async fn delete_workflow(&self, workflow_id: &str) -> Result<(), AIError> {
+        self.workflows.write().remove(workflow_id)
+            .map(|_| ())
+            .ok_or_else(|| AIError::Configuration(format!("Workflow not found: {}", workflow_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scripted_executions() {
+        let service = MockAutomationService::new();
+        service.script("backup", ScriptedOutcome::Failure("disk full".to_string()));
+
+        let failed = service.execute_workflow("backup", None, "user").await.unwrap();
+        assert_eq!(service.get_execution_status(&failed), Some(ExecutionStatus::Failed));
+        assert_eq!(service.get_execution_result(&failed).unwrap().error.as_deref(), Some("disk full"));
+
+        service.hold_executions(true);
+        let held = service.execute_workflow("sync", None, "user").await.unwrap();
+        assert_eq!(service.get_execution_status(&held), Some(ExecutionStatus::Running));
+        service.cancel_execution(&held).await.unwrap();
+        assert_eq!(service.get_execution_status(&held), Some(ExecutionStatus::Cancelled));
+        assert_eq!(service.executions().len(), 2);
+    }
+}
@@ -0,0 +1,145 @@
+//! Failure injection for resilience tests
+//!
+//! Enabled with the `chaos` feature. A [`FailureInjector`] attached to a
+//! client makes selected calls fail or stall before they reach the real
+//! subsystem, so tests can simulate Ollama going down or the database being
+//! locked and check that retries, backoff and degraded-mode reporting behave.
+//! Renderer frames are intercepted the same way: a fault at
+//! [`FaultPoint::GpuDevice`] reports a device loss to the engine's
+//! [`DeviceLossMonitor`], which starts its recovery.
+
+use crate::AIError;
+use horizonos_graph_engine::DeviceLossMonitor;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Where a fault is injected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FaultPoint {
+    /// HTTP requests to the Ollama server
+    OllamaRequest,
+    /// Queries against the pattern database
+    StorageQuery,
+    /// Frames rendered on the GPU device
+    GpuDevice,
+}
+
+/// What happens at a fault point
+#[derive(Debug, Clone)]
+pub enum Fault {
+    /// Transient failure, e.g. connection refused or database locked
+    Unavailable(String),
+    /// Permanent failure that must not be retried
+    Error(String),
+    /// Delay the call, then let it through
+    Latency(Duration),
+}
+
+#[derive(Debug)]
+struct Rule {
+    fault: Fault,
+    /// Injections left; `None` means until cleared
+    remaining: Option<u32>,
+}
+
+/// Injects faults at fault points
+#[derive(Debug, Default)]
+pub struct FailureInjector {
+    rules: Mutex<HashMap<FaultPoint, Rule>>,
+    hits: Mutex<HashMap<FaultPoint, u64>>,
+}
+
+impl FailureInjector {
+    /// Create an injector without faults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inject `fault` at `point` until cleared
+    pub fn fail(&self, point: FaultPoint, fault: Fault) {
+        self.rules.lock().insert(point, Rule { fault, remaining: None });
+    }
+
+    /// Inject `fault` at `point` for the next `times` calls
+    pub fn fail_times(&self, point: FaultPoint, fault: Fault, times: u32) {
+        self.rules.lock().insert(point, Rule { fault, remaining: Some(times) });
+    }
+
+    /// Simulate the Ollama server being down
+    pub fn ollama_down(&self) {
+        self.fail(FaultPoint::OllamaRequest, Fault::Unavailable("Connection refused (injected)".to_string()));
+    }
+
+    /// Simulate the database being locked
+    pub fn database_locked(&self) {
+        self.fail(FaultPoint::StorageQuery, Fault::Unavailable("Database is locked (injected)".to_string()));
+    }
+
+    /// Simulate the GPU device being lost, e.g. by a driver reset
+    pub fn device_lost(&self) {
+        self.fail_times(FaultPoint::GpuDevice, Fault::Unavailable("Device lost (injected)".to_string()), 1);
+    }
+
+    /// Remove the fault at `point`
+    pub fn clear(&self, point: FaultPoint) {
+        self.rules.lock().remove(&point);
+    }
+
+    /// Remove all faults
+    pub fn clear_all(&self) {
+        self.rules.lock().clear();
+    }
+
+    /// Calls that reached `point`, whether or not a fault was injected
+    pub fn hits(&self, point: FaultPoint) -> u64 {
+        self.hits.lock().get(&point).copied().unwrap_or(0)
+    }
+
+    /// Called by clients before performing the real operation
+    pub async fn intercept(&self, point: FaultPoint) -> Result<(), AIError> {
+        match self.next_fault(point) {
+            None => Ok(()),
+            Some(Fault::Unavailable(reason)) => Err(AIError::Unavailable(reason)),
+            Some(Fault::Error(reason)) => Err(AIError::Configuration(reason)),
+            Some(Fault::Latency(delay)) => {
+                tokio::time::sleep(delay).await;
+                Ok(())
+            }
+        }
+    }
+
+    /// Called by the renderer before a frame; failures are reported to
+    /// `monitor` as a lost device
+    ///
+    /// Returns whether the device was lost, in which case the frame is skipped.
+    pub fn intercept_frame(&self, monitor: &DeviceLossMonitor) -> bool {
+        match self.next_fault(FaultPoint::GpuDevice) {
+            None => false,
+            Some(Fault::Unavailable(reason) | Fault::Error(reason)) => {
+                monitor.report(reason);
+                true
+            }
+            Some(Fault::Latency(delay)) => {
+                std::thread::sleep(delay);
+                false
+            }
+        }
+    }
+
+    /// Count a call at `point` and take the fault to inject, if any
+    fn next_fault(&self, point: FaultPoint) -> Option<Fault> {
+        *self.hits.lock().entry(point).or_insert(0) += 1;
+
+        let mut rules = self.rules.lock();
+        let rule = rules.get_mut(&point)?;
+        let fault = rule.fault.clone();
+        if let Some(remaining) = &mut rule.remaining {
+            *remaining = remaining.saturating_sub(1);
+            if *remaining == 0 {
+                rules.remove(&point);
+            }
+        }
+        Some(fault)
+    }
+}
@@ -0,0 +1,485 @@
+//! Inference queue sharing the GPU between AI features
+//!
+//! Chat, suggestions and background summarization all run on the same local
+//! model server, and too many concurrent requests starve the compositor of
+//! GPU time. Every request goes through an [`InferenceQueue`], which runs at
+//! most [`InferenceLimits::max_concurrent`] jobs at once, highest priority
+//! first. When an interactive request arrives and every slot is taken, the
+//! most recently started background job is preempted: its future is dropped,
+//! which aborts the request, and the job is queued again to restart once a
+//! slot frees up. Jobs are therefore closures that can build their request
+//! future more than once.
+
+use crate::hardware::{should_throttle_ai, HardwareMonitor, HardwareProfile};
+use crate::AIError;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, watch};
+
+/// How urgently a request should run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum InferencePriority {
+    /// Summaries, indexing and other work nobody is waiting for; can be preempted
+    Background,
+    /// Suggestions shown when ready
+    Suggestion,
+    /// Chat and other requests a user is waiting on
+    Interactive,
+}
+
+/// Concurrency limits of the queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceLimits {
+    /// Jobs running at once
+    pub max_concurrent: usize,
+    /// Background jobs running at once, leaving slots for interactive requests
+    pub max_background: usize,
+    /// Background jobs waiting before new ones are rejected
+    pub max_queued_background: usize,
+}
+
+impl Default for InferenceLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 1,
+            max_background: 1,
+            max_queued_background: 32,
+        }
+    }
+}
+
+impl InferenceLimits {
+    /// Limits for a machine: its recommended session count, with one
+    /// request at a time while it is under load or low on battery
+    pub fn from_profile(profile: &HardwareProfile) -> Self {
+        let max_concurrent = if should_throttle_ai(profile) {
+            1
+        } else {
+            (profile.capabilities.max_concurrent_sessions as usize).max(1)
+        };
+        Self {
+            max_concurrent,
+            // Background work gets half the slots, but always at least one
+            max_background: max_concurrent.div_ceil(2),
+            ..Self::default()
+        }
+    }
+}
+
+/// Counters of one priority
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PriorityMetrics {
+    pub submitted: u64,
+    pub completed: u64,
+    pub failed: u64,
+    pub cancelled: u64,
+    /// Times a running job was stopped to make room and queued again
+    pub preempted: u64,
+    /// Time spent queued by all admitted jobs
+    pub total_wait: Duration,
+    pub max_wait: Duration,
+    admitted: u64,
+}
+
+impl PriorityMetrics {
+    /// Mean time jobs waited before running
+    pub fn average_wait(&self) -> Duration {
+        if self.admitted == 0 {
+            Duration::ZERO
+        } else {
+            self.total_wait / self.admitted as u32
+        }
+    }
+}
+
+/// Snapshot of the queue for monitoring
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InferenceMetrics {
+    /// Jobs waiting for a slot
+    pub queued: usize,
+    /// Jobs holding a slot
+    pub running: usize,
+    pub by_priority: HashMap<InferencePriority, PriorityMetrics>,
+}
+
+impl InferenceMetrics {
+    /// Counters of a priority
+    pub fn priority(&self, priority: InferencePriority) -> PriorityMetrics {
+        self.by_priority.get(&priority).cloned().unwrap_or_default()
+    }
+}
+
+/// Identifier of a submitted job
+pub type InferenceJobId = u64;
+
+/// Where a job is in its life
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JobState {
+    Queued,
+    Running,
+    Preempted,
+    Cancelled,
+}
+
+struct JobEntry {
+    priority: InferencePriority,
+    /// Submission order; kept across preemption so a restarted job is not overtaken
+    seq: u64,
+    queued_at: Instant,
+    started_at: Option<Instant>,
+    state: watch::Sender<JobState>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    limits: InferenceLimits,
+    next_id: InferenceJobId,
+    jobs: HashMap<InferenceJobId, JobEntry>,
+    metrics: HashMap<InferencePriority, PriorityMetrics>,
+}
+
+impl QueueState {
+    fn count(&self, state: JobState, priority: Option<InferencePriority>) -> usize {
+        self.jobs
+            .values()
+            .filter(|job| *job.state.borrow() == state && priority.is_none_or(|p| job.priority == p))
+            .count()
+    }
+
+    /// Start queued jobs while slots are free, highest priority and oldest first
+    fn dispatch(&mut self) {
+        loop {
+            if self.count(JobState::Running, None) >= self.limits.max_concurrent {
+                return;
+            }
+            let background_full =
+                self.count(JobState::Running, Some(InferencePriority::Background)) >= self.limits.max_background;
+            let next = self
+                .jobs
+                .iter()
+                .filter(|(_, job)| *job.state.borrow() == JobState::Queued)
+                .filter(|(_, job)| !(background_full && job.priority == InferencePriority::Background))
+                .max_by(|a, b| a.1.priority.cmp(&b.1.priority).then(b.1.seq.cmp(&a.1.seq)))
+                .map(|(id, _)| *id);
+            let Some(id) = next else {
+                return;
+            };
+
+            let now = Instant::now();
+            let job = self.jobs.get_mut(&id).expect("job picked from the map");
+            job.started_at = Some(now);
+            job.state.send_replace(JobState::Running);
+            let wait = now - job.queued_at;
+            let metrics = self.metrics.entry(job.priority).or_default();
+            metrics.admitted += 1;
+            metrics.total_wait += wait;
+            metrics.max_wait = metrics.max_wait.max(wait);
+        }
+    }
+
+    /// Stop the most recently started background job to free a slot
+    fn preempt_for(&mut self, priority: InferencePriority) {
+        if priority != InferencePriority::Interactive || self.count(JobState::Running, None) < self.limits.max_concurrent {
+            return;
+        }
+        let victim = self
+            .jobs
+            .iter()
+            .filter(|(_, job)| job.priority == InferencePriority::Background && *job.state.borrow() == JobState::Running)
+            .max_by_key(|(_, job)| job.started_at)
+            .map(|(id, _)| *id);
+        if let Some(id) = victim {
+            log::debug!("Preempting background inference job {} for an interactive request", id);
+            self.jobs[&id].state.send_replace(JobState::Preempted);
+        }
+    }
+
+    /// Put a preempted job back in line and hand its slot on
+    fn requeue(&mut self, id: InferenceJobId) {
+        if let Some(job) = self.jobs.get_mut(&id) {
+            job.queued_at = Instant::now();
+            job.started_at = None;
+            job.state.send_replace(JobState::Queued);
+            self.metrics.entry(job.priority).or_default().preempted += 1;
+        }
+        self.dispatch();
+    }
+
+    /// Remove a finished job, count how it ended and hand its slot on
+    fn finish<T>(&mut self, id: InferenceJobId, result: &Result<T, AIError>) {
+        if let Some(job) = self.jobs.remove(&id) {
+            let metrics = self.metrics.entry(job.priority).or_default();
+            match result {
+                Ok(_) => metrics.completed += 1,
+                Err(AIError::Cancelled(_)) => metrics.cancelled += 1,
+                Err(_) => metrics.failed += 1,
+            }
+        }
+        self.dispatch();
+    }
+}
+
+/// Priority queue with concurrency limits for AI requests
+#[derive(Clone, Default)]
+pub struct InferenceQueue {
+    state: Arc<Mutex<QueueState>>,
+}
+
+impl InferenceQueue {
+    pub fn new(limits: InferenceLimits) -> Self {
+        let queue = Self::default();
+        queue.state.lock().limits = limits;
+        queue
+    }
+
+    /// Queue with limits derived from the current hardware profile
+    pub fn from_profile(profile: &HardwareProfile) -> Self {
+        Self::new(InferenceLimits::from_profile(profile))
+    }
+
+    pub fn limits(&self) -> InferenceLimits {
+        self.state.lock().limits.clone()
+    }
+
+    /// Change the limits; running jobs above a lowered limit finish normally
+    pub fn set_limits(&self, limits: InferenceLimits) {
+        let mut state = self.state.lock();
+        state.limits = limits;
+        state.dispatch();
+    }
+
+    /// Re-derive the limits when the hardware monitor has a fresh profile
+    pub fn refresh_limits(&self, monitor: &HardwareMonitor) -> Result<(), AIError> {
+        if monitor.update_if_needed()? {
+            self.set_limits(InferenceLimits::from_profile(&monitor.get_profile()));
+        }
+        Ok(())
+    }
+
+    /// Submit a job, returning a handle to await or cancel it
+    ///
+    /// `job` builds the request future; it is called again when a preempted
+    /// job restarts. Must be called within a Tokio runtime.
+    pub fn submit<T, F, Fut>(&self, priority: InferencePriority, mut job: F) -> InferenceHandle<T>
+    where
+        T: Send + 'static,
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, AIError>> + Send,
+    {
+        let (result_tx, result_rx) = oneshot::channel();
+        let (id, mut signal) = {
+            let mut state = self.state.lock();
+            state.metrics.entry(priority).or_default().submitted += 1;
+            if priority == InferencePriority::Background
+                && state.count(JobState::Queued, Some(InferencePriority::Background)) >= state.limits.max_queued_background
+            {
+                state.metrics.entry(priority).or_default().failed += 1;
+                let _ = result_tx.send(Err(AIError::Unavailable("Inference queue is full".to_string())));
+                return InferenceHandle { id: None, queue: self.clone(), result: result_rx };
+            }
+
+            let id = state.next_id;
+            state.next_id += 1;
+            let (signal_tx, signal_rx) = watch::channel(JobState::Queued);
+            state.jobs.insert(id, JobEntry {
+                priority,
+                seq: id,
+                queued_at: Instant::now(),
+                started_at: None,
+                state: signal_tx,
+            });
+            state.preempt_for(priority);
+            state.dispatch();
+            (id, signal_rx)
+        };
+
+        let queue = self.clone();
+        tokio::spawn(async move {
+            let result = loop {
+                let admitted = signal
+                    .wait_for(|state| matches!(state, JobState::Running | JobState::Cancelled))
+                    .await
+                    .map(|state| *state == JobState::Running)
+                    .unwrap_or(false);
+                if !admitted {
+                    break Err(AIError::Cancelled(format!("Inference job {} was cancelled", id)));
+                }
+
+                let interrupted = async {
+                    signal
+                        .wait_for(|state| matches!(state, JobState::Preempted | JobState::Cancelled))
+                        .await
+                        .map(|state| *state)
+                        .unwrap_or(JobState::Cancelled)
+                };
+                tokio::select! {
+                    result = job() => break result,
+                    stop = interrupted => match stop {
+                        JobState::Preempted => queue.state.lock().requeue(id),
+                        _ => break Err(AIError::Cancelled(format!("Inference job {} was cancelled", id))),
+                    },
+                }
+            };
+            queue.state.lock().finish(id, &result);
+            let _ = result_tx.send(result);
+        });
+
+        InferenceHandle { id: Some(id), queue: self.clone(), result: result_rx }
+    }
+
+    /// Submit a job and wait for its result
+    pub async fn run<T, F, Fut>(&self, priority: InferencePriority, job: F) -> Result<T, AIError>
+    where
+        T: Send + 'static,
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, AIError>> + Send,
+    {
+        self.submit(priority, job).result().await
+    }
+
+    /// Cancel a queued or running job; returns whether it was still pending
+    pub fn cancel(&self, id: InferenceJobId) -> bool {
+        let state = self.state.lock();
+        match state.jobs.get(&id) {
+            Some(job) => {
+                job.state.send_replace(JobState::Cancelled);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancel every background job, e.g. before a heavy interactive session
+    pub fn cancel_background(&self) -> usize {
+        let state = self.state.lock();
+        let background: Vec<_> = state
+            .jobs
+            .values()
+            .filter(|job| job.priority == InferencePriority::Background)
+            .collect();
+        for job in &background {
+            job.state.send_replace(JobState::Cancelled);
+        }
+        background.len()
+    }
+
+    /// Current queue depth and counters
+    pub fn metrics(&self) -> InferenceMetrics {
+        let state = self.state.lock();
+        InferenceMetrics {
+            queued: state.count(JobState::Queued, None),
+            running: state.count(JobState::Running, None),
+            by_priority: state.metrics.clone(),
+        }
+    }
+}
+
+/// Pending result of a submitted job
+pub struct InferenceHandle<T> {
+    /// `None` when the job was rejected without being queued
+    id: Option<InferenceJobId>,
+    queue: InferenceQueue,
+    result: oneshot::Receiver<Result<T, AIError>>,
+}
+
+impl<T> InferenceHandle<T> {
+    pub fn id(&self) -> Option<InferenceJobId> {
+        self.id
+    }
+
+    /// Cancel the job; its result becomes [`AIError::Cancelled`]
+    pub fn cancel(&self) -> bool {
+        self.id.is_some_and(|id| self.queue.cancel(id))
+    }
+
+    /// Wait for the job to finish
+    pub async fn result(self) -> Result<T, AIError> {
+        self.result
+            .await
+            .unwrap_or_else(|_| Err(AIError::Cancelled("Inference job was dropped".to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn limits(max_concurrent: usize, max_background: usize) -> InferenceLimits {
+        InferenceLimits { max_concurrent, max_background, ..InferenceLimits::default() }
+    }
+
+    #[tokio::test]
+    async fn test_interactive_preempts_background() {
+        let queue = InferenceQueue::new(limits(1, 1));
+        let starts = Arc::new(AtomicU32::new(0));
+        let (release_tx, release_rx) = watch::channel(false);
+
+        let background = {
+            let starts = starts.clone();
+            queue.submit(InferencePriority::Background, move || {
+                let starts = starts.clone();
+                let mut release = release_rx.clone();
+                async move {
+                    starts.fetch_add(1, Ordering::SeqCst);
+                    let _ = release.wait_for(|released| *released).await;
+                    Ok("summary")
+                }
+            })
+        };
+        while starts.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let reply = queue.run(InferencePriority::Interactive, || async { Ok("chat reply") }).await;
+        assert_eq!(reply.unwrap(), "chat reply");
+
+        release_tx.send_replace(true);
+        assert_eq!(background.result().await.unwrap(), "summary");
+        assert_eq!(starts.load(Ordering::SeqCst), 2);
+
+        let metrics = queue.metrics();
+        assert_eq!(metrics.priority(InferencePriority::Background).preempted, 1);
+        assert_eq!(metrics.priority(InferencePriority::Background).completed, 1);
+        assert_eq!(metrics.priority(InferencePriority::Interactive).completed, 1);
+        assert_eq!((metrics.queued, metrics.running), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_queued_job() {
+        let queue = InferenceQueue::new(limits(1, 1));
+        let (release_tx, release_rx) = watch::channel(false);
+        let running = queue.submit(InferencePriority::Suggestion, move || {
+            let mut release = release_rx.clone();
+            async move {
+                let _ = release.wait_for(|released| *released).await;
+                Ok(())
+            }
+        });
+        let waiting = queue.submit(InferencePriority::Background, || async { Ok(()) });
+        assert_eq!(queue.metrics().queued, 1);
+
+        assert!(waiting.cancel());
+        assert!(matches!(waiting.result().await, Err(AIError::Cancelled(_))));
+        release_tx.send_replace(true);
+        running.result().await.unwrap();
+        assert_eq!(queue.metrics().priority(InferencePriority::Background).cancelled, 1);
+    }
+
+    #[test]
+    fn test_limits_from_profile() {
+        let mut profile = HardwareProfile::default();
+        profile.memory.total = 16_000;
+        profile.memory.free = 8_000;
+        profile.capabilities.max_concurrent_sessions = 4;
+        assert_eq!(InferenceLimits::from_profile(&profile).max_concurrent, 4);
+        assert_eq!(InferenceLimits::from_profile(&profile).max_background, 2);
+
+        profile.cpu.usage = 95.0;
+        assert_eq!(InferenceLimits::from_profile(&profile).max_concurrent, 1);
+    }
+}
@@ -16,19 +16,34 @@ pub mod storage;
 pub mod monitoring;
 pub mod automation;
 pub mod privacy;
+pub mod resilience;
+pub mod search;
+pub mod inference;
+#[cfg(feature = "chaos")]
+pub mod chaos;
 
 use std::sync::Arc;
 use dashmap::DashMap;
-use once_cell::sync::Lazy;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use chrono::{DateTime, Utc};
+use horizonos_graph_engine::{NodeType, SceneId};
+use horizonos_graph_errors::{Category, Classify, Problem, ProblemsPanel, RecoveryAction, Retryability, Severity};
 
-/// Global AI service instance
-pub static AI_SERVICE: Lazy<Arc<AIService>> = Lazy::new(|| {
-    Arc::new(AIService::new())
-});
+/// Set to `0` by the compositor when the startup profile disables AI
+pub const AI_ENV: &str = "HORIZONOS_AI";
+
+/// Set to `0` by the compositor when the startup profile disables automation
+pub const AUTOMATION_ENV: &str = "HORIZONOS_AUTOMATION";
+
+/// Whether the session's startup profile allows a subsystem
+///
+/// Allowed unless `var` is set to `0`, so services started outside a
+/// compositor session run as configured.
+pub fn session_allows(var: &str) -> bool {
+    std::env::var(var).map_or(true, |value| value.trim() != "0")
+}
 
 /// AI service configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -221,21 +236,62 @@ pub struct AIService {
     storage: Arc<storage::StorageManager>,
     /// Hardware monitor
     hardware_monitor: Arc<hardware::HardwareMonitor>,
+    /// Queue all model requests go through
+    inference: inference::InferenceQueue,
+    /// Model server client, connected by `initialize`
+    ollama: RwLock<Option<Arc<ollama::OllamaClient>>>,
+    /// Nodes the AI must not read
+    node_access: privacy::NodeAccessPolicy,
+    /// Learned workspace switch patterns
+    workspace_switches: RwLock<patterns::workspace_switch::WorkspaceSwitchPredictor>,
+    /// Windows and files focused together, shared with monitoring
+    focus_tracker: Arc<RwLock<monitoring::focus_cooccurrence::FocusCoOccurrenceTracker>>,
+    /// Applications and files used together, shared with monitoring
+    co_usage: Arc<RwLock<patterns::co_usage::CoUsageDetector>>,
+    /// Co-usage patterns published as edge suggestions
+    edge_suggestions: RwLock<suggestions::edges::EdgeSuggestionPipeline>,
+    /// Where an unreachable model server is reported
+    problems: RwLock<Option<Arc<ProblemsPanel>>>,
+    /// Behavioral monitoring, running while learning is enabled
+    monitoring: RwLock<Option<Arc<monitoring::MonitoringSystem>>>,
+    /// Agents the AI agent nodes of the graph talk to
+    agents: RwLock<Option<Arc<agents::AIAgentSystem>>>,
+    /// Live agents bound to AI agent nodes
+    agent_nodes: Arc<agents::AgentNodeBinding>,
 }
 
 impl AIService {
     /// Create a new AI service
     pub fn new() -> Self {
+        let hardware_monitor = Arc::new(hardware::HardwareMonitor::new());
+        let inference = inference::InferenceQueue::from_profile(&hardware_monitor.get_profile());
+        let node_access = privacy::NodeAccessPolicy::new();
         Self {
             config: RwLock::new(AIConfig::default()),
             sessions: DashMap::new(),
-            patterns: Arc::new(patterns::PatternStorage::new()),
-            suggestions: Arc::new(suggestions::SuggestionEngine::new()),
+            patterns: Arc::new(patterns::PatternStorage::with_node_access(node_access.clone())),
+            suggestions: Arc::new(suggestions::SuggestionEngine::with_node_access(node_access.clone())),
             storage: Arc::new(storage::StorageManager::new_default()),
-            hardware_monitor: Arc::new(hardware::HardwareMonitor::new()),
+            hardware_monitor,
+            inference,
+            ollama: RwLock::new(None),
+            node_access,
+            workspace_switches: RwLock::new(patterns::workspace_switch::WorkspaceSwitchPredictor::default()),
+            focus_tracker: Arc::new(RwLock::new(monitoring::focus_cooccurrence::FocusCoOccurrenceTracker::default())),
+            co_usage: Arc::new(RwLock::new(patterns::co_usage::CoUsageDetector::default())),
+            edge_suggestions: RwLock::new(suggestions::edges::EdgeSuggestionPipeline::default()),
+            problems: RwLock::new(None),
+            monitoring: RwLock::new(None),
+            agents: RwLock::new(None),
+            agent_nodes: Arc::new(agents::AgentNodeBinding::new()),
         }
     }
 
+    /// Report model server outages to a problems panel
+    pub fn set_problems_panel(&self, problems: Arc<ProblemsPanel>) {
+        *self.problems.write() = Some(problems);
+    }
+
     /// Initialize the AI service
     pub async fn initialize(&self) -> Result<(), AIError> {
         let config = self.config.read().clone();
@@ -244,15 +300,39 @@ impl AIService {
             log::info!("AI service is disabled");
             return Ok(());
         }
+        if !session_allows(AI_ENV) {
+            log::info!("AI service is disabled by the startup profile");
+            return Ok(());
+        }
+
+        // Encrypt behavioral data at rest
+        if config.privacy.encrypt_storage {
+            match Self::encryption_manager().await {
+                Some(encryption) => {
+                    self.storage.set_encryption(storage::encryption::StorageEncryption::new(Arc::new(encryption)));
+                    match self.storage.migrate_plaintext().await {
+                        Ok(migrated) if migrated > 0 => log::info!("Encrypted {} stored records", migrated),
+                        Ok(_) => {}
+                        Err(e) => log::warn!("Failed to encrypt existing records, will retry next start: {}", e),
+                    }
+                }
+                None => log::warn!("Behavioral data is stored unencrypted, no encryption key is available"),
+            }
+        }
 
         // Initialize Ollama connection
-        ollama::OllamaClient::new(&config.ollama_endpoint)
-            .test_connection()
-            .await?;
+        let mut health = resilience::HealthTracker::new("Ollama");
+        if let Some(problems) = self.problems.read().clone() {
+            health = health.with_problems(problems);
+        }
+        let client = ollama::OllamaClient::new(&config.ollama_endpoint).with_health_tracker(health);
+        client.test_connection().await?;
+        self.set_ollama_client(client);
 
         // Detect hardware capabilities
         let hardware_profile = hardware::detect_hardware_profile()?;
         log::info!("Detected hardware profile: {:?}", hardware_profile);
+        self.inference.set_limits(inference::InferenceLimits::from_profile(&hardware_profile));
 
         // Select optimal model based on hardware
         let model = hardware::select_optimal_model(
@@ -267,10 +347,110 @@ impl AIService {
         // Initialize suggestion engine
         self.suggestions.initialize(config.suggestions).await?;
 
+        // Monitor activity for learning; suggestions work without it
+        if config.learning.enabled {
+            match self.start_monitoring().await {
+                Ok(monitoring) => *self.monitoring.write() = Some(monitoring),
+                Err(e) => log::warn!("Behavioral monitoring unavailable: {}", e),
+            }
+        }
+
+        // Agent nodes stay plain nodes if no agents can run
+        match self.start_agents().await {
+            Ok(agents) => *self.agents.write() = Some(agents),
+            Err(e) => log::warn!("AI agents unavailable: {}", e),
+        }
+
         log::info!("AI service initialized successfully");
         Ok(())
     }
 
+    /// Start monitoring, feeding the detectors [`Self::publish_edge_suggestions`] publishes from
+    async fn start_monitoring(&self) -> Result<Arc<monitoring::MonitoringSystem>, AIError> {
+        let monitoring = monitoring::MonitoringSystem::with_detectors(
+            monitoring::MonitoringConfig::default(),
+            self.storage.clone(),
+            self.focus_tracker.clone(),
+            self.co_usage.clone(),
+        ).await?;
+        monitoring.start().await?;
+        Ok(Arc::new(monitoring))
+    }
+
+    /// Start the agent system, bound by the same AI-invisible flags as the rest of the service
+    async fn start_agents(&self) -> Result<Arc<agents::AIAgentSystem>, AIError> {
+        let mut agents = agents::AIAgentSystem::new(agents::AgentSystemConfig::default()).await?;
+        agents.set_node_access(self.node_access.clone());
+        agents.start().await?;
+        Ok(Arc::new(agents))
+    }
+
+    /// Running agent system, `None` until the service is initialized with AI enabled
+    pub fn agent_system(&self) -> Option<Arc<agents::AIAgentSystem>> {
+        self.agents.read().clone()
+    }
+
+    /// Binding of AI agent nodes to live agents
+    ///
+    /// Bind the scene with [`agents::AgentNodeBinding::bind_scene`] once it
+    /// is loaded, then feed it the events of [`Self::subscribe_agent_events`].
+    pub fn agent_nodes(&self) -> &Arc<agents::AgentNodeBinding> {
+        &self.agent_nodes
+    }
+
+    /// Subscribe to agent status changes and task progress, `None` while no agents run
+    pub fn subscribe_agent_events(&self) -> Option<tokio::sync::broadcast::Receiver<agents::AgentEvent>> {
+        let agents = self.agents.read().clone()?;
+        match agents.subscribe_agent_events() {
+            Ok(events) => Some(events),
+            Err(e) => {
+                log::warn!("Agent events unavailable: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Configured sampling rate of behavioral monitoring, `None` while it is not running
+    pub fn monitoring_sampling_rate(&self) -> Option<u32> {
+        self.monitoring.read().as_ref().map(|monitoring| monitoring.get_config().sampling_rate)
+    }
+
+    /// Limit the sampling rate of behavioral monitoring, e.g. while the
+    /// desktop saves power, or lift the limit with `None`
+    pub async fn set_sampling_limit(&self, limit: Option<u32>) -> Result<(), AIError> {
+        let monitoring = self.monitoring.read().clone();
+        match monitoring {
+            Some(monitoring) => monitoring.set_sampling_limit(limit).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Encryption keyed from the system keyring, or from a key file where there is none
+    async fn encryption_manager() -> Option<privacy::encryption::EncryptionManager> {
+        use privacy::encryption::{default_key_file, EncryptionManager};
+        let keyring_error = match EncryptionManager::new(privacy::EncryptionConfig::default()).await {
+            Ok(encryption) => return Some(encryption),
+            Err(e) => e,
+        };
+        let key_file = default_key_file();
+        log::warn!(
+            "System keyring unavailable ({}), keeping AI encryption keys in {}; data sealed with keyring keys stays unreadable until the keyring is back",
+            keyring_error,
+            key_file.display()
+        );
+        let config = privacy::EncryptionConfig {
+            key_management: privacy::KeyManagement::FileStorage,
+            ..privacy::EncryptionConfig::default()
+        };
+        match EncryptionManager::with_key_file(config, key_file).await {
+            Ok(encryption) => Some(encryption),
+            Err(e) => {
+                log::warn!("Key file unavailable: {}", e);
+                None
+            }
+        }
+    }
+
     /// Create a new AI session
     pub async fn create_session(&self, purpose: &str) -> Result<String, AIError> {
         let session_id = uuid::Uuid::new_v4().to_string();
@@ -281,6 +461,164 @@ impl AIService {
         Ok(session_id)
     }
 
+    /// Nodes marked AI-invisible, shared with pattern detection and suggestions
+    ///
+    /// Hand a clone to the monitoring and agent systems so they enforce
+    /// the same flags.
+    pub fn node_access(&self) -> &privacy::NodeAccessPolicy {
+        &self.node_access
+    }
+
+    /// Learn from a workspace switch
+    pub fn record_workspace_switch(&self, from: &str, to: &str) {
+        self.workspace_switches.write().record_switch(from, to, Utc::now());
+    }
+
+    /// Likely next workspaces after `current`, most likely first
+    pub fn predict_next_workspaces(&self, current: &str) -> Vec<patterns::workspace_switch::WorkspacePrediction> {
+        self.workspace_switches.read().predict(current, Utc::now())
+    }
+
+    /// Stop predicting a deleted workspace
+    pub fn forget_workspace(&self, workspace_id: &str) {
+        self.workspace_switches.write().forget(workspace_id);
+    }
+
+    /// Learn which windows and files are used together from a monitored action
+    pub fn record_action(&self, action: &storage::UserAction) {
+        if self.node_access.allows_action(action) {
+            self.focus_tracker.write().record_action(action);
+            self.co_usage.write().record_action(action);
+        }
+    }
+
+    /// Learn from the compositor focusing a node's window
+    pub fn record_node_focus(&self, node_id: SceneId, node_type: &NodeType) {
+        let Some(target) = privacy::node_access::node_target(node_type) else {
+            return;
+        };
+        if self.node_access.is_node_visible(node_id) && self.node_access.is_target_visible(&target) {
+            self.focus_tracker.write().record_focus(&target, Utc::now());
+        }
+    }
+
+    /// Suggest edges between windows and files newly found to be used together, returning how many were published
+    pub fn publish_edge_suggestions(&self) -> usize {
+        if !self.config.read().suggestions.enabled {
+            return 0;
+        }
+        let detected = self.co_usage.write().take_patterns();
+        for pattern in detected {
+            log::debug!("Detected co-usage pattern {}", pattern.id);
+            self.patterns.add_pattern(pattern);
+        }
+        let co_used = self.edge_suggestions.write().publish(&self.patterns, &self.suggestions);
+
+        let (proposals, ttl) = {
+            let mut tracker = self.focus_tracker.write();
+            let ttl = chrono::Duration::hours(tracker.config().suggestion_ttl_hours as i64);
+            (tracker.take_proposals(), ttl)
+        };
+        let focused = proposals.iter().filter(|proposal| self.suggestions.generate_suggestion(proposal.to_suggestion(ttl))).count();
+        co_used + focused
+    }
+
+    /// Publish edge suggestions every `period` until the service is dropped
+    pub async fn run_edge_suggestions(self: Arc<Self>, period: std::time::Duration) {
+        let service = Arc::downgrade(&self);
+        drop(self);
+        let mut ticks = tokio::time::interval(period);
+        loop {
+            ticks.tick().await;
+            let Some(service) = service.upgrade() else {
+                return;
+            };
+            let published = service.publish_edge_suggestions();
+            if published > 0 {
+                log::debug!("Published {} edge suggestions", published);
+            }
+        }
+    }
+
+    /// Stop suggesting an edge between two targets after the user rejected it
+    pub fn dismiss_relationship(&self, source: &str, target: &str) {
+        self.focus_tracker.write().dismiss(source, target);
+        self.edge_suggestions.write().reject(source, target);
+        {
+            let mut co_usage = self.co_usage.write();
+            co_usage.forget(source, target);
+            co_usage.forget(target, source);
+        }
+        self.suggestions.dismiss_edge(source, target);
+    }
+
+    /// Suggestion engine shared with the badges and notifications that present suggestions
+    pub fn suggestions(&self) -> &suggestions::SuggestionEngine {
+        &self.suggestions
+    }
+
+    /// Inference queue for model requests
+    pub fn inference(&self) -> &inference::InferenceQueue {
+        &self.inference
+    }
+
+    /// Send model requests to this client, normally the one `initialize` connected
+    pub fn set_ollama_client(&self, client: ollama::OllamaClient) {
+        *self.ollama.write() = Some(Arc::new(client));
+    }
+
+    fn ollama_client(&self) -> Result<Arc<ollama::OllamaClient>, AIError> {
+        self.ollama.read().clone().ok_or_else(|| AIError::Unavailable("Ollama is not connected".to_string()))
+    }
+
+    /// Generate a completion, waiting for a slot in the inference queue
+    pub async fn generate(
+        &self,
+        priority: inference::InferencePriority,
+        model: &str,
+        prompt: &str,
+        options: Option<ollama::GenerateOptions>,
+    ) -> Result<String, AIError> {
+        let client = self.ollama_client()?;
+        let (model, prompt) = (model.to_string(), prompt.to_string());
+        self.inference
+            .run(priority, move || {
+                let (client, model, prompt, options) = (client.clone(), model.clone(), prompt.clone(), options.clone());
+                async move { client.generate(&model, &prompt, options).await }
+            })
+            .await
+    }
+
+    /// Chat with a model, waiting for a slot in the inference queue
+    pub async fn chat(
+        &self,
+        priority: inference::InferencePriority,
+        model: &str,
+        messages: &[Message],
+        options: Option<ollama::GenerateOptions>,
+    ) -> Result<String, AIError> {
+        let client = self.ollama_client()?;
+        let (model, messages) = (model.to_string(), messages.to_vec());
+        self.inference
+            .run(priority, move || {
+                let (client, model, messages, options) = (client.clone(), model.clone(), messages.clone(), options.clone());
+                async move { client.chat(&model, &messages, options).await }
+            })
+            .await
+    }
+
+    /// Embed text, waiting for a slot in the inference queue
+    pub async fn embeddings(&self, priority: inference::InferencePriority, model: &str, prompt: &str) -> Result<Vec<f32>, AIError> {
+        let client = self.ollama_client()?;
+        let (model, prompt) = (model.to_string(), prompt.to_string());
+        self.inference
+            .run(priority, move || {
+                let (client, model, prompt) = (client.clone(), model.clone(), prompt.clone());
+                async move { client.embeddings(&model, &prompt).await }
+            })
+            .await
+    }
+
     /// Get current configuration
     pub fn config(&self) -> AIConfig {
         self.config.read().clone()
@@ -294,6 +632,22 @@ impl AIService {
         let mut config = self.config.write();
         f(&mut config);
     }
+
+    /// End all sessions and write out buffered user actions
+    pub async fn shutdown(&self) -> Result<(), AIError> {
+        self.sessions.clear();
+        let monitoring = self.monitoring.write().take();
+        if let Some(monitoring) = monitoring {
+            monitoring.stop().await?;
+        }
+        let agents = self.agents.write().take();
+        if let Some(mut agents) = agents.and_then(Arc::into_inner) {
+            agents.stop().await?;
+        }
+        let flushed = self.storage.timescale.flush_pending().await?;
+        log::info!("AI service shut down, {} buffered actions written", flushed);
+        Ok(())
+    }
 }
 
 /// AI session for a specific task
@@ -385,6 +739,15 @@ pub enum AIError {
     #[error("Configuration error: {0}")]
     Configuration(String),
     
+    #[error("Temporarily unavailable: {0}")]
+    Unavailable(String),
+    
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+    
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+    
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
     
@@ -392,6 +755,42 @@ pub enum AIError {
     Serialization(#[from] serde_json::Error),
 }
 
+impl AIError {
+    /// Whether the failure is temporary and the operation may succeed if retried
+    pub fn is_transient(&self) -> bool {
+        matches!(self, AIError::Unavailable(_))
+    }
+}
+
+impl Classify for AIError {
+    fn classify(&self) -> Problem {
+        let problem = match self {
+            AIError::OllamaConnection(_) => Problem::new("ai.backend_unreachable", Category::Network, Severity::Warning, "Cannot reach the AI backend")
+                .transient()
+                .with_recovery(RecoveryAction::open_settings("ai")),
+            AIError::Unavailable(_) => Problem::new("ai.backend_degraded", Category::Ai, Severity::Warning, "AI features are temporarily unavailable")
+                .transient(),
+            AIError::ModelNotAvailable(model) => Problem::new("ai.model_missing", Category::Ai, Severity::Error, format!("AI model {} is not installed", model))
+                .with_retry(Retryability::NeedsUser)
+                .with_recovery(RecoveryAction::open_settings("ai")),
+            AIError::Configuration(_) => Problem::new("ai.configuration", Category::Configuration, Severity::Error, "AI settings are invalid")
+                .with_retry(Retryability::NeedsUser)
+                .with_recovery(RecoveryAction::open_settings("ai")),
+            AIError::HardwareDetection(_) => Problem::new("ai.hardware_detection", Category::Ai, Severity::Info, "Could not detect hardware for model selection"),
+            AIError::SessionNotFound(_) => Problem::new("ai.session_not_found", Category::Ai, Severity::Warning, "AI session has ended"),
+            AIError::PatternDetection(_) => Problem::new("ai.pattern_detection", Category::Ai, Severity::Info, "Pattern detection failed"),
+            AIError::SuggestionError(_) => Problem::new("ai.suggestions", Category::Ai, Severity::Info, "Could not produce suggestions"),
+            AIError::Cancelled(_) => Problem::new("ai.cancelled", Category::Ai, Severity::Info, "AI request was cancelled"),
+            AIError::UnsupportedOperation(_) => Problem::new("ai.unsupported", Category::Ai, Severity::Warning, "Operation is not supported"),
+            AIError::Encryption(_) => Problem::new("ai.encryption", Category::Storage, Severity::Error, "AI data could not be encrypted or decrypted")
+                .with_retry(Retryability::NeedsUser),
+            AIError::Io(_) => Problem::new("ai.io", Category::Io, Severity::Error, "AI data could not be read or written"),
+            AIError::Serialization(_) => Problem::new("ai.data_corrupt", Category::Storage, Severity::Error, "AI data is unreadable"),
+        };
+        problem.in_subsystem("ai").with_detail(self)
+    }
+}
+
 // Re-export uuid
 pub use uuid;
 
@@ -418,4 +817,119 @@ mod tests {
         assert_eq!(session.purpose, "test-purpose");
         assert!(session.context.messages.is_empty());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_session_allows() {
+        let var = "HORIZONOS_TEST_SESSION_ALLOWS";
+        assert!(session_allows(var));
+        std::env::set_var(var, "0");
+        assert!(!session_allows(var));
+        std::env::set_var(var, "1");
+        assert!(session_allows(var));
+        std::env::remove_var(var);
+    }
+
+    #[tokio::test]
+    async fn test_dismissed_pair_is_never_suggested_again() {
+        let action = |action_type, target: &str, at| storage::UserAction {
+            id: uuid::Uuid::new_v4(),
+            user_id: "user".to_string(),
+            action_type,
+            target: target.to_string(),
+            application: "pdf-viewer".to_string(),
+            timestamp: at,
+            context: serde_json::Value::Null,
+            duration_ms: None,
+            success: true,
+            error_message: None,
+        };
+        let use_together = |service: &AIService, start: DateTime<Utc>| {
+            for i in 0..5 {
+                let at = start + chrono::Duration::minutes(i * 5);
+                service.record_action(&action(storage::ActionType::AppLaunch, "pdf-viewer", at));
+                service.record_action(&action(storage::ActionType::WindowFocus, "pdf-viewer", at + chrono::Duration::seconds(1)));
+                service.record_action(&action(storage::ActionType::FileOpen, "/home/user/report.pdf", at + chrono::Duration::seconds(5)));
+            }
+        };
+
+        // Co-usage and focus co-occurrence both find the pair; it is suggested once
+        let service = AIService::new();
+        let start = Utc::now();
+        use_together(&service, start);
+        assert_eq!(service.publish_edge_suggestions(), 1);
+        assert_eq!(service.suggestions().get_suggestions().len(), 1);
+
+        service.dismiss_relationship("/home/user/report.pdf", "pdf-viewer");
+        assert!(service.suggestions().get_suggestions().is_empty());
+        use_together(&service, start + chrono::Duration::hours(1));
+        assert_eq!(service.publish_edge_suggestions(), 0);
+
+        // Nor by any other producer
+        let proposal = monitoring::focus_cooccurrence::RelationshipProposal {
+            source: "pdf-viewer".to_string(),
+            target: "/home/user/report.pdf".to_string(),
+            co_occurrences: 10,
+            confidence: 1.0,
+        };
+        assert!(!service.suggestions().generate_suggestion(proposal.to_suggestion(chrono::Duration::hours(1))));
+        assert!(service.suggestions().get_suggestions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_agents_start_with_the_service() {
+        fn assert_send<T: Send>(_: T) {}
+        // Answers are delivered on the runtime the compositor hands work to
+        let _ = |binding: &agents::AgentNodeBinding, system: &agents::AIAgentSystem, result: &agents::AgentExecutionResult| {
+            assert_send(binding.deliver_reply(system, result));
+        };
+
+        let service = AIService::new();
+        assert!(service.agent_system().is_none());
+        assert!(service.subscribe_agent_events().is_none());
+        assert_eq!(service.agent_nodes().bind_scene(&horizonos_graph_engine::Scene::new()), 0);
+    }
+
+    #[tokio::test]
+    async fn test_model_requests_go_through_the_inference_queue() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Answers every request once its body has arrived
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                loop {
+                    let read = socket.read(&mut buffer).await.unwrap_or(0);
+                    request.extend_from_slice(&buffer[..read]);
+                    let text = String::from_utf8_lossy(&request);
+                    let complete = text.find("\r\n\r\n").is_some_and(|end| {
+                        let length = text[..end]
+                            .lines()
+                            .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap_or(0)))
+                            .unwrap_or(0);
+                        request.len() >= end + 4 + length
+                    });
+                    if read == 0 || complete {
+                        break;
+                    }
+                }
+                let body = r#"{"response":"hello","done":true}"#;
+                let response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let service = AIService::new();
+        let error = service.generate(inference::InferencePriority::Interactive, "llama3.2", "hi", None).await.unwrap_err();
+        assert!(matches!(error, AIError::Unavailable(_)));
+
+        service.set_ollama_client(ollama::OllamaClient::new(&format!("http://{}", address)));
+        let reply = service.generate(inference::InferencePriority::Interactive, "llama3.2", "hi", None).await.unwrap();
+        assert_eq!(reply, "hello");
+        let metrics = service.inference().metrics();
+        assert_eq!(metrics.priority(inference::InferencePriority::Interactive).completed, 1);
+        assert_eq!(metrics.running, 0);
+    }
+}
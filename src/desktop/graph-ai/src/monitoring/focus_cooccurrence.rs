@@ -0,0 +1,326 @@
+//! Relationship inference from window focus co-occurrence
+//!
+//! Windows and files that are repeatedly focused within a short interval of
+//! each other are likely related. The tracker counts focus co-occurrences per
+//! pair and proposes a `RelatedTo` edge once a pair has been seen together
+//! often enough, with a confidence derived from how consistently the two are
+//! used together.
+
+use crate::storage::{ActionType, UserAction};
+use crate::suggestions::{Suggestion, SuggestionAction, SuggestionPriority, SuggestionType};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Co-occurrence inference configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusCoOccurrenceConfig {
+    /// Infer relationships at all
+    pub enabled: bool,
+    /// Two focus events this many seconds apart or less co-occur
+    pub window_secs: u64,
+    /// Co-occurrences needed before a pair is proposed
+    pub min_occurrences: u32,
+    /// Confidence needed before a pair is proposed
+    pub min_confidence: f32,
+    /// Focus events older than this many hours stop counting
+    pub history_hours: u64,
+    /// Hours a proposed suggestion stays valid
+    pub suggestion_ttl_hours: u64,
+    /// Seconds between publishing new proposals as suggestions
+    pub publish_interval_secs: u64,
+}
+
+impl Default for FocusCoOccurrenceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 30,
+            min_occurrences: 5,
+            min_confidence: 0.4,
+            history_hours: 24 * 7,
+            suggestion_ttl_hours: 24,
+            publish_interval_secs: 60,
+        }
+    }
+}
+
+/// Proposed relationship between two focus targets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelationshipProposal {
+    /// First target, ordered lexically
+    pub source: String,
+    /// Second target
+    pub target: String,
+    /// Times the two were focused within the window of each other
+    pub co_occurrences: u32,
+    /// Co-occurrence confidence (0.0 to 1.0)
+    pub confidence: f32,
+}
+
+impl RelationshipProposal {
+    /// Suggest creating a `RelatedTo` edge for this proposal
+    pub fn to_suggestion(&self, ttl: ChronoDuration) -> Suggestion {
+        let now = Utc::now();
+        let mut parameters = HashMap::new();
+        parameters.insert("source".to_string(), serde_json::json!(self.source));
+        parameters.insert("target".to_string(), serde_json::json!(self.target));
+        parameters.insert("edge_type".to_string(), serde_json::json!("RelatedTo"));
+        parameters.insert("similarity".to_string(), serde_json::json!(self.confidence));
+
+        Suggestion {
+            id: format!("focus-cooccurrence:{}|{}", self.source, self.target),
+            suggestion_type: SuggestionType::Relationship,
+            title: format!("Link {} and {}", display_name(&self.source), display_name(&self.target)),
+            description: format!(
+                "These were used together {} times recently",
+                self.co_occurrences
+            ),
+            confidence: self.confidence,
+            priority: if self.confidence >= 0.75 {
+                SuggestionPriority::Medium
+            } else {
+                SuggestionPriority::Low
+            },
+            generated_at: now,
+            expires_at: Some(now + ttl),
+            action: SuggestionAction {
+                action_type: "create_edge".to_string(),
+                parameters,
+            },
+            context: serde_json::json!({
+                "inferred_from": "focus_cooccurrence",
+                "co_occurrences": self.co_occurrences,
+            }),
+        }
+    }
+}
+
+/// One focus event and the earlier targets it co-occurred with
+#[derive(Debug, Clone)]
+struct FocusRecord {
+    target: String,
+    at: DateTime<Utc>,
+    partners: Vec<String>,
+}
+
+/// Counts focus co-occurrences and proposes related pairs
+#[derive(Debug)]
+pub struct FocusCoOccurrenceTracker {
+    /// Configuration
+    config: FocusCoOccurrenceConfig,
+    /// Focus events inside the history horizon, oldest first
+    history: VecDeque<FocusRecord>,
+    /// Focus counts per target
+    focus_counts: HashMap<String, u32>,
+    /// Co-occurrence counts per ordered pair
+    pair_counts: HashMap<(String, String), u32>,
+    /// Pairs already proposed or dismissed
+    proposed: HashSet<(String, String)>,
+}
+
+impl FocusCoOccurrenceTracker {
+    /// Create a tracker
+    pub fn new(config: FocusCoOccurrenceConfig) -> Self {
+        Self {
+            config,
+            history: VecDeque::new(),
+            focus_counts: HashMap::new(),
+            pair_counts: HashMap::new(),
+            proposed: HashSet::new(),
+        }
+    }
+
+    /// Current configuration
+    pub fn config(&self) -> &FocusCoOccurrenceConfig {
+        &self.config
+    }
+
+    /// Replace the configuration
+    pub fn set_config(&mut self, config: FocusCoOccurrenceConfig) {
+        self.config = config;
+    }
+
+    /// Record a monitored action, counting window focus and file open events
+    pub fn record_action(&mut self, action: &UserAction) {
+        if matches!(action.action_type, ActionType::WindowFocus | ActionType::FileOpen)
+            && action.target != "unknown"
+        {
+            self.record_focus(&action.target, action.timestamp);
+        }
+    }
+
+    /// Record that a window or file was focused
+    pub fn record_focus(&mut self, target: &str, at: DateTime<Utc>) {
+        if !self.config.enabled || target.is_empty() {
+            return;
+        }
+        self.expire(at);
+
+        // Repeated focus of the same target is not a new visit
+        if self.history.back().is_some_and(|last| last.target == target) {
+            return;
+        }
+
+        let window = ChronoDuration::seconds(self.config.window_secs as i64);
+        let mut partners: Vec<String> = Vec::new();
+        for other in self.history.iter().rev() {
+            if at - other.at > window {
+                break;
+            }
+            if other.target != target && !partners.contains(&other.target) {
+                partners.push(other.target.clone());
+            }
+        }
+        for partner in &partners {
+            *self.pair_counts.entry(pair_key(target, partner)).or_default() += 1;
+        }
+
+        *self.focus_counts.entry(target.to_string()).or_default() += 1;
+        self.history.push_back(FocusRecord { target: target.to_string(), at, partners });
+    }
+
+    /// Confidence that two targets are related
+    ///
+    /// Co-occurrences relative to the geometric mean of each target's focus
+    /// count, so a pair that is always used together scores near 1.0 while a
+    /// target that is focused alongside everything scores low with each.
+    pub fn confidence(&self, a: &str, b: &str) -> f32 {
+        let co = self.pair_counts.get(&pair_key(a, b)).copied().unwrap_or(0) as f32;
+        let count_a = self.focus_counts.get(a).copied().unwrap_or(0) as f32;
+        let count_b = self.focus_counts.get(b).copied().unwrap_or(0) as f32;
+        if co == 0.0 || count_a == 0.0 || count_b == 0.0 {
+            return 0.0;
+        }
+        (co / (count_a * count_b).sqrt()).min(1.0)
+    }
+
+    /// Pairs that passed the thresholds and were not proposed before, most confident first
+    pub fn take_proposals(&mut self) -> Vec<RelationshipProposal> {
+        let mut proposals: Vec<RelationshipProposal> = self.pair_counts.iter()
+            .filter(|(pair, count)| **count >= self.config.min_occurrences && !self.proposed.contains(*pair))
+            .map(|((a, b), count)| RelationshipProposal {
+                source: a.clone(),
+                target: b.clone(),
+                co_occurrences: *count,
+                confidence: self.confidence(a, b),
+            })
+            .filter(|proposal| proposal.confidence >= self.config.min_confidence)
+            .collect();
+
+        proposals.sort_by(|a, b| {
+            b.confidence.partial_cmp(&a.confidence)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.source.cmp(&b.source))
+                .then_with(|| a.target.cmp(&b.target))
+        });
+        for proposal in &proposals {
+            self.proposed.insert(pair_key(&proposal.source, &proposal.target));
+        }
+        proposals
+    }
+
+    /// Never propose a pair again, e.g. after the user rejected the suggestion
+    pub fn dismiss(&mut self, a: &str, b: &str) {
+        self.proposed.insert(pair_key(a, b));
+    }
+
+    /// Number of focus events inside the history horizon
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Drop focus events older than the history horizon, undoing their counts
+    fn expire(&mut self, now: DateTime<Utc>) {
+        let horizon = now - ChronoDuration::hours(self.config.history_hours as i64);
+        while self.history.front().is_some_and(|record| record.at < horizon) {
+            let Some(record) = self.history.pop_front() else {
+                break;
+            };
+            decrement(&mut self.focus_counts, &record.target);
+            for partner in &record.partners {
+                decrement(&mut self.pair_counts, &pair_key(&record.target, partner));
+            }
+        }
+    }
+}
+
+impl Default for FocusCoOccurrenceTracker {
+    fn default() -> Self {
+        Self::new(FocusCoOccurrenceConfig::default())
+    }
+}
+
+/// Order-independent key for a pair
+fn pair_key(a: &str, b: &str) -> (String, String) {
+    if a <= b {
+        (a.to_string(), b.to_string())
+    } else {
+        (b.to_string(), a.to_string())
+    }
+}
+
+/// Decrement a count, removing it at zero
+fn decrement<K: std::hash::Hash + Eq + Clone>(counts: &mut HashMap<K, u32>, key: &K) {
+    if let Some(count) = counts.get_mut(key) {
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            counts.remove(key);
+        }
+    }
+}
+
+/// Last path component of a file target, or the target itself
+fn display_name(target: &str) -> &str {
+    target.rsplit('/').find(|part| !part.is_empty()).unwrap_or(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_co_focus_is_proposed_once() {
+        let mut tracker = FocusCoOccurrenceTracker::new(FocusCoOccurrenceConfig {
+            min_occurrences: 3,
+            ..Default::default()
+        });
+        let start = Utc::now();
+
+        // Editor and terminal alternate; the browser shows up once, minutes later
+        for i in 0..4 {
+            let at = start + ChronoDuration::minutes(i * 5);
+            tracker.record_focus("/home/user/src/main.rs", at);
+            tracker.record_focus("terminal", at + ChronoDuration::seconds(10));
+        }
+        tracker.record_focus("browser", start + ChronoDuration::minutes(60));
+
+        let proposals = tracker.take_proposals();
+        assert_eq!(proposals.len(), 1);
+        assert_eq!(proposals[0].source, "/home/user/src/main.rs");
+        assert_eq!(proposals[0].target, "terminal");
+        assert!(proposals[0].confidence > 0.9);
+        assert!(tracker.take_proposals().is_empty());
+
+        let suggestion = proposals[0].to_suggestion(ChronoDuration::hours(1));
+        assert_eq!(suggestion.title, "Link main.rs and terminal");
+        assert_eq!(suggestion.action.action_type, "create_edge");
+    }
+
+    #[test]
+    fn test_old_focus_events_expire() {
+        let mut tracker = FocusCoOccurrenceTracker::new(FocusCoOccurrenceConfig {
+            history_hours: 1,
+            ..Default::default()
+        });
+        let start = Utc::now();
+        tracker.record_focus("a", start);
+        tracker.record_focus("b", start + ChronoDuration::seconds(5));
+        assert!(tracker.confidence("a", "b") > 0.0);
+
+        tracker.record_focus("c", start + ChronoDuration::hours(2));
+        assert_eq!(tracker.confidence("a", "b"), 0.0);
+        assert_eq!(tracker.history_len(), 1);
+    }
+}
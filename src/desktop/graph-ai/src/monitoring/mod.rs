@@ -7,9 +7,13 @@ pub mod event_monitor;
 pub mod privacy_filter;
 pub mod idle_detector;
 pub mod resource_monitor;
+pub mod focus_cooccurrence;
 
 use crate::AIError;
+use crate::patterns::co_usage::CoUsageDetector;
+use crate::privacy::NodeAccessPolicy;
 use crate::storage::{UserAction, StorageManager};
+use crate::suggestions::SuggestionEngine;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use parking_lot::RwLock;
@@ -36,6 +40,9 @@ pub struct MonitoringConfig {
     pub idle_detection: idle_detector::IdleConfig,
     /// Resource monitoring configuration
     pub resource_monitoring: resource_monitor::ResourceConfig,
+    /// Relationship inference from focus co-occurrence
+    #[serde(default)]
+    pub focus_cooccurrence: focus_cooccurrence::FocusCoOccurrenceConfig,
 }
 
 impl Default for MonitoringConfig {
@@ -48,6 +55,7 @@ impl Default for MonitoringConfig {
             privacy: privacy_filter::PrivacyFilterConfig::default(),
             idle_detection: idle_detector::IdleConfig::default(),
             resource_monitoring: resource_monitor::ResourceConfig::default(),
+            focus_cooccurrence: focus_cooccurrence::FocusCoOccurrenceConfig::default(),
         }
     }
 }
@@ -125,6 +133,16 @@ pub struct MonitoringSystem {
     stats: Arc<RwLock<MonitoringStats>>,
     /// Monitoring task handle
     monitoring_handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
+    /// Focus co-occurrence tracker
+    focus_tracker: Arc<RwLock<focus_cooccurrence::FocusCoOccurrenceTracker>>,
+    /// Applications and files used together
+    co_usage: Arc<RwLock<CoUsageDetector>>,
+    /// Nodes whose actions are not recorded
+    node_access: NodeAccessPolicy,
+    /// Highest sampling rate allowed, e.g. while the desktop saves power
+    sampling_limit: Arc<RwLock<Option<u32>>>,
+    /// Engine that receives inferred relationships
+    suggestions: Arc<RwLock<Option<Arc<SuggestionEngine>>>>,
 }
 
 impl MonitoringSystem {
@@ -133,29 +151,46 @@ impl MonitoringSystem {
         config: MonitoringConfig,
         storage: Arc<StorageManager>,
     ) -> Result<Self, AIError> {
-        let config = Arc::new(RwLock::new(config));
-        
+        let focus_tracker = Arc::new(RwLock::new(
+            focus_cooccurrence::FocusCoOccurrenceTracker::new(config.focus_cooccurrence.clone())
+        ));
+        Self::with_detectors(config, storage, focus_tracker, Arc::new(RwLock::new(CoUsageDetector::default()))).await
+    }
+    
+    /// Create a monitoring system that feeds detectors owned by the caller,
+    /// so relationships are learned and dismissed in one place
+    pub async fn with_detectors(
+        config: MonitoringConfig,
+        storage: Arc<StorageManager>,
+        focus_tracker: Arc<RwLock<focus_cooccurrence::FocusCoOccurrenceTracker>>,
+        co_usage: Arc<RwLock<CoUsageDetector>>,
+    ) -> Result<Self, AIError> {
         // Create event channel
         let (event_tx, event_rx) = mpsc::unbounded_channel();
         
         // Initialize components
         let event_monitor = Arc::new(event_monitor::EventMonitor::new(
-            config.read().clone(),
+            config.clone(),
             event_tx.clone(),
         ).await?);
         
         let privacy_filter = Arc::new(
-            privacy_filter::PrivacyFilter::new(config.read().privacy.clone()).await?
+            privacy_filter::PrivacyFilter::new(config.privacy.clone()).await?
         );
         
         let idle_detector = Arc::new(
-            idle_detector::IdleDetector::new(config.read().idle_detection.clone()).await?
+            idle_detector::IdleDetector::new(config.idle_detection.clone()).await?
         );
         
         let resource_monitor = Arc::new(
-            resource_monitor::ResourceMonitor::new(config.read().resource_monitoring.clone()).await?
+            resource_monitor::ResourceMonitor::new(config.resource_monitoring.clone()).await?
         );
         
+        let config = Arc::new(RwLock::new(config));
+        
+        let node_access = NodeAccessPolicy::new();
+        let suggestions = Arc::new(RwLock::new(None));
+        
         let stats = Arc::new(RwLock::new(MonitoringStats {
             started_at: Utc::now(),
             current_sampling_rate: config.read().sampling_rate,
@@ -172,6 +207,11 @@ impl MonitoringSystem {
             event_tx,
             stats: stats.clone(),
             monitoring_handle: Arc::new(RwLock::new(None)),
+            focus_tracker: focus_tracker.clone(),
+            co_usage: co_usage.clone(),
+            node_access: node_access.clone(),
+            sampling_limit: Arc::new(RwLock::new(None)),
+            suggestions: suggestions.clone(),
         };
         
         // Start event processing task
@@ -180,6 +220,10 @@ impl MonitoringSystem {
             privacy_filter,
             storage,
             stats,
+            focus_tracker,
+            co_usage,
+            node_access,
+            suggestions,
             event_rx,
         )));
         
@@ -257,6 +301,7 @@ impl MonitoringSystem {
         self.privacy_filter.update_config(self.config.read().privacy.clone()).await?;
         self.idle_detector.update_config(self.config.read().idle_detection.clone()).await?;
         self.resource_monitor.update_config(self.config.read().resource_monitoring.clone()).await?;
+        self.focus_tracker.write().set_config(self.config.read().focus_cooccurrence.clone());
         
         // Restart monitoring if it was running
         if was_monitoring {
@@ -284,12 +329,75 @@ impl MonitoringSystem {
         Box::pin(futures::stream::empty())
     }
     
+    /// Nodes whose actions are dropped before pattern detection
+    ///
+    /// Shares state with the event processing task, so flags synced through
+    /// this handle apply to the next event.
+    pub fn node_access(&self) -> &NodeAccessPolicy {
+        &self.node_access
+    }
+    
+    /// Relationships inferred from focus co-occurrence since the last call
+    pub fn take_relationship_proposals(&self) -> Vec<focus_cooccurrence::RelationshipProposal> {
+        self.focus_tracker.write().take_proposals()
+    }
+    
+    /// Publish inferred relationships to `engine` every
+    /// `focus_cooccurrence.publish_interval_secs` from the event processing task
+    pub fn set_suggestion_engine(&self, engine: Arc<SuggestionEngine>) {
+        *self.suggestions.write() = Some(engine);
+    }
+    
+    /// Hand newly inferred relationships to the suggestion pipeline
+    pub fn publish_relationship_suggestions(&self, engine: &SuggestionEngine) -> usize {
+        Self::publish_proposals(&self.config, &self.focus_tracker, engine)
+    }
+    
+    /// Turn pending proposals of `focus_tracker` into suggestions
+    fn publish_proposals(
+        config: &RwLock<MonitoringConfig>,
+        focus_tracker: &RwLock<focus_cooccurrence::FocusCoOccurrenceTracker>,
+        engine: &SuggestionEngine,
+    ) -> usize {
+        let ttl = chrono::Duration::hours(config.read().focus_cooccurrence.suggestion_ttl_hours as i64);
+        let proposals = focus_tracker.write().take_proposals();
+        let mut published = 0;
+        for proposal in &proposals {
+            debug!(
+                "Suggesting relationship {} <-> {} (confidence {:.2})",
+                proposal.source, proposal.target, proposal.confidence
+            );
+            if engine.generate_suggestion(proposal.to_suggestion(ttl)) {
+                published += 1;
+            }
+        }
+        published
+    }
+    
+    /// Stop proposing a relationship, e.g. after its suggestion was rejected
+    pub fn dismiss_relationship(&self, source: &str, target: &str) {
+        self.focus_tracker.write().dismiss(source, target);
+        {
+            let mut co_usage = self.co_usage.write();
+            co_usage.forget(source, target);
+            co_usage.forget(target, source);
+        }
+        if let Some(engine) = self.suggestions.read().as_ref() {
+            engine.dismiss_edge(source, target);
+        }
+    }
+    
     /// Process events from the event channel
+    #[allow(clippy::too_many_arguments)]
     async fn process_events(
         config: Arc<RwLock<MonitoringConfig>>,
         privacy_filter: Arc<privacy_filter::PrivacyFilter>,
         storage: Arc<StorageManager>,
         stats: Arc<RwLock<MonitoringStats>>,
+        focus_tracker: Arc<RwLock<focus_cooccurrence::FocusCoOccurrenceTracker>>,
+        co_usage: Arc<RwLock<CoUsageDetector>>,
+        node_access: NodeAccessPolicy,
+        suggestions: Arc<RwLock<Option<Arc<SuggestionEngine>>>>,
         mut event_rx: mpsc::UnboundedReceiver<RawEvent>,
     ) {
         info!("Starting event processing task");
@@ -297,7 +405,28 @@ impl MonitoringSystem {
         let mut event_buffer = Vec::new();
         let mut last_batch_time = Utc::now();
         
-        while let Some(raw_event) = event_rx.recv().await {
+        let publish_secs = config.read().focus_cooccurrence.publish_interval_secs.max(1);
+        let mut publish_tick = tokio::time::interval(std::time::Duration::from_secs(publish_secs));
+        publish_tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        
+        loop {
+            let raw_event = tokio::select! {
+                event = event_rx.recv() => match event {
+                    Some(event) => event,
+                    None => break,
+                },
+                _ = publish_tick.tick() => {
+                    let engine = suggestions.read().clone();
+                    if let Some(engine) = engine {
+                        let published = Self::publish_proposals(&config, &focus_tracker, &engine);
+                        if published > 0 {
+                            debug!("Published {} relationship suggestions", published);
+                        }
+                    }
+                    continue;
+                }
+            };
+            
             // Update statistics
             {
                 let mut stats = stats.write();
@@ -309,7 +438,14 @@ impl MonitoringSystem {
             if let Some(filtered_event) = privacy_filter.filter_event(&raw_event).await {
                 // Convert to user action
                 if let Ok(user_action) = Self::convert_to_user_action(&filtered_event) {
-                    event_buffer.push(user_action);
+                    // Actions on AI-invisible nodes never reach pattern detection
+                    if node_access.allows_action(&user_action) {
+                        focus_tracker.write().record_action(&user_action);
+                        co_usage.write().record_action(&user_action);
+                        event_buffer.push(user_action);
+                    } else {
+                        stats.write().filtered_events += 1;
+                    }
                 } else {
                     stats.write().dropped_events += 1;
                 }
@@ -406,6 +542,10 @@ impl MonitoringSystem {
             // Return to normal sampling rate
             current_config.sampling_rate
         };
+        let new_sampling_rate = match *self.sampling_limit.read() {
+            Some(limit) => new_sampling_rate.min(limit.max(1)),
+            None => new_sampling_rate,
+        };
         
         if new_sampling_rate != self.stats.read().current_sampling_rate {
             self.stats.write().current_sampling_rate = new_sampling_rate;
@@ -417,6 +557,13 @@ impl MonitoringSystem {
         Ok(())
     }
     
+    /// Limit the sampling rate, e.g. to the power-saving rate of the
+    /// performance manager, or lift the limit with `None`
+    pub async fn set_sampling_limit(&self, limit: Option<u32>) -> Result<(), AIError> {
+        *self.sampling_limit.write() = limit;
+        self.adjust_sampling_rate().await
+    }
+    
     /// Health check for the monitoring system
     pub async fn health_check(&self) -> Result<MonitoringHealth, AIError> {
         let stats = self.stats.read();
@@ -521,6 +668,24 @@ mod tests {
         // assert!(result.is_ok());
     }
     
+    #[tokio::test]
+    async fn test_sampling_limit_caps_and_lifts() {
+        let storage = Arc::new(StorageManager::new_default());
+        let Ok(monitoring) = MonitoringSystem::new(MonitoringConfig::default(), storage).await else {
+            return;
+        };
+        
+        // Limits of zero still sample once a second
+        monitoring.set_sampling_limit(Some(0)).await.unwrap();
+        assert_eq!(monitoring.get_stats().current_sampling_rate, 1);
+        monitoring.set_sampling_limit(Some(2)).await.unwrap();
+        assert!(monitoring.get_stats().current_sampling_rate <= 2);
+        
+        // Without a limit the rate follows the system load again, which halves it at most
+        monitoring.set_sampling_limit(None).await.unwrap();
+        assert!(monitoring.get_stats().current_sampling_rate > 2);
+    }
+    
     #[test]
     fn test_monitoring_config_default() {
         let config = MonitoringConfig::default();
@@ -541,4 +706,25 @@ mod tests {
         assert!(matches!(event.source, EventSource::Wayland));
         assert_eq!(event.data["target"], "test_app");
     }
+    
+    #[test]
+    fn test_publish_proposals_drains_tracker() {
+        let config = RwLock::new(MonitoringConfig::default());
+        let focus_tracker = RwLock::new(focus_cooccurrence::FocusCoOccurrenceTracker::new(
+            focus_cooccurrence::FocusCoOccurrenceConfig {
+                min_occurrences: 2,
+                ..Default::default()
+            },
+        ));
+        let start = Utc::now();
+        for i in 0..2 {
+            let at = start + chrono::Duration::hours(i);
+            focus_tracker.write().record_focus("gimp", at);
+            focus_tracker.write().record_focus("/home/user/logo.xcf", at + chrono::Duration::seconds(5));
+        }
+        
+        let engine = SuggestionEngine::new();
+        assert_eq!(MonitoringSystem::publish_proposals(&config, &focus_tracker, &engine), 1);
+        assert_eq!(MonitoringSystem::publish_proposals(&config, &focus_tracker, &engine), 0);
+    }
 }
\ No newline at end of file
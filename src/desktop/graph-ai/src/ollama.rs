@@ -1,16 +1,20 @@
 //! Ollama integration for local LLM processing
 
 use crate::{AIError, Message, MessageRole, HardwareOptimization};
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use crate::resilience::{HealthTracker, RetryPolicy, SubsystemStatus};
+#[cfg(feature = "chaos")]
+use crate::chaos::{FailureInjector, FaultPoint};
+use reqwest::{Client, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::time::{Duration, Instant};
 use futures::stream::Stream;
-// use futures::StreamExt; // Unused import
+use futures::StreamExt;
 use std::pin::Pin;
+use std::task::{Context, Poll};
 use async_stream;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use std::collections::HashMap;
+use tokio::sync::{watch, RwLock};
+use std::collections::{HashMap, VecDeque};
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use parking_lot::Mutex;
@@ -30,6 +34,13 @@ pub struct OllamaClient {
     metrics: Arc<Mutex<PerformanceMetrics>>,
     /// Hardware optimization settings
     hardware_optimization: HardwareOptimization,
+    /// Retry schedule for transient failures
+    retry_policy: RetryPolicy,
+    /// Server health for degraded-mode reporting
+    health: HealthTracker,
+    /// Injected faults for resilience tests
+    #[cfg(feature = "chaos")]
+    faults: Option<Arc<FailureInjector>>,
 }
 
 /// Connection pool for managing concurrent requests
@@ -178,9 +189,37 @@ impl OllamaClient {
             model_cache: DashMap::new(),
             metrics: Arc::new(Mutex::new(PerformanceMetrics::default())),
             hardware_optimization,
+            retry_policy: RetryPolicy::default(),
+            health: HealthTracker::new("Ollama"),
+            #[cfg(feature = "chaos")]
+            faults: None,
         }
     }
 
+    /// Use a different retry schedule
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Use a health tracker with different circuit settings
+    pub fn with_health_tracker(mut self, health: HealthTracker) -> Self {
+        self.health = health;
+        self
+    }
+
+    /// Inject faults into requests
+    #[cfg(feature = "chaos")]
+    pub fn with_failure_injector(mut self, faults: Arc<FailureInjector>) -> Self {
+        self.faults = Some(faults);
+        self
+    }
+
+    /// Server health, degraded while requests are failing
+    pub fn status(&self) -> SubsystemStatus {
+        self.health.status()
+    }
+
     /// Get hardware optimization mode
     pub fn hardware_optimization(&self) -> HardwareOptimization {
         self.hardware_optimization
@@ -195,45 +234,79 @@ impl OllamaClient {
     pub async fn test_connection(&self) -> Result<(), AIError> {
         let url = format!("{}/api/tags", self.base_url);
         
-        match self.client.get(&url).send().await {
-            Ok(response) => {
-                if response.status().is_success() {
-                    Ok(())
-                } else {
-                    Err(AIError::OllamaConnection(format!(
-                        "Ollama server returned status: {}",
-                        response.status()
-                    )))
+        RetryPolicy::none().run(&self.health, || async {
+            self.inject_faults().await?;
+            match self.client.get(&url).send().await {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        Ok(())
+                    } else {
+                        Err(status_error("Ollama server returned status", response.status()))
+                    }
                 }
+                Err(e) => Err(request_error("Failed to connect to Ollama", e)),
             }
-            Err(e) => Err(AIError::OllamaConnection(format!(
-                "Failed to connect to Ollama: {}",
-                e
-            ))),
+        }).await
+    }
+
+    /// Run injected faults for the next request
+    async fn inject_faults(&self) -> Result<(), AIError> {
+        #[cfg(feature = "chaos")]
+        if let Some(faults) = &self.faults {
+            faults.intercept(FaultPoint::OllamaRequest).await?;
         }
+        Ok(())
+    }
+
+    /// Send a JSON request and decode the response, retrying transient failures
+    async fn post_json<Req, Resp>(&self, path: &str, request: &Req, operation: &str) -> Result<Resp, AIError>
+    where
+        Req: Serialize,
+        Resp: DeserializeOwned,
+    {
+        let url = format!("{}{}", self.base_url, path);
+        
+        self.retry_policy.run(&self.health, || async {
+            self.inject_faults().await?;
+            let response = self.client
+                .post(&url)
+                .json(request)
+                .send()
+                .await
+                .map_err(|e| request_error(operation, e))?;
+
+            if !response.status().is_success() {
+                return Err(status_error(operation, response.status()));
+            }
+
+            response
+                .json()
+                .await
+                .map_err(|e| AIError::OllamaConnection(e.to_string()))
+        }).await
     }
 
     /// List available models with caching
     pub async fn list_models(&self) -> Result<Vec<ModelInfo>, AIError> {
         let url = format!("{}/api/tags", self.base_url);
         
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| AIError::OllamaConnection(e.to_string()))?;
-
-        if !response.status().is_success() {
-            return Err(AIError::OllamaConnection(format!(
-                "Failed to list models: {}",
-                response.status()
-            )));
-        }
+        let data: ListModelsResponse = self.retry_policy.run(&self.health, || async {
+            self.inject_faults().await?;
+            let response = self.client
+                .get(&url)
+                .send()
+                .await
+                .map_err(|e| request_error("Failed to list models", e))?;
+
+            if !response.status().is_success() {
+                return Err(status_error("Failed to list models", response.status()));
+            }
 
-        let data: ListModelsResponse = response
-            .json()
-            .await
-            .map_err(|e| AIError::OllamaConnection(e.to_string()))?;
+            response
+                .json()
+                .await
+                .map_err(|e| AIError::OllamaConnection(e.to_string()))
+        }).await?;
 
         // Update model cache
         let now = Utc::now();
@@ -523,8 +596,6 @@ impl OllamaClient {
         
         log::debug!("Generating completion with model: {}", model);
         
-        let url = format!("{}/api/generate", self.base_url);
-        
         let request = GenerateRequest {
             model: model.to_string(),
             prompt: prompt.to_string(),
@@ -532,31 +603,10 @@ impl OllamaClient {
             options,
         };
 
-        let response = self.client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| {
-                self.update_metrics(false, start_time.elapsed());
-                AIError::OllamaConnection(e.to_string())
-            })?;
-
-        if !response.status().is_success() {
-            self.update_metrics(false, start_time.elapsed());
-            return Err(AIError::OllamaConnection(format!(
-                "Generation failed: {}",
-                response.status()
-            )));
-        }
-
-        let data: GenerateResponse = response
-            .json()
+        let data: GenerateResponse = self
+            .post_json("/api/generate", &request, "Generation failed")
             .await
-            .map_err(|e| {
-                self.update_metrics(false, start_time.elapsed());
-                AIError::OllamaConnection(e.to_string())
-            })?;
+            .inspect_err(|_| self.update_metrics(false, start_time.elapsed()))?;
 
         let elapsed = start_time.elapsed();
         self.update_metrics(true, elapsed);
@@ -612,8 +662,17 @@ impl OllamaClient {
         prompt: &str,
         options: Option<GenerateOptions>,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<String, AIError>> + Send>>, AIError> {
-        let url = format!("{}/api/generate", self.base_url);
-        
+        let tokens = self.generate_tokens(model, prompt, options).await?;
+        Ok(Box::pin(tokens.map(|token| token.map(|token| token.text))))
+    }
+
+    /// Generate a completion token by token as the model produces it
+    pub async fn generate_tokens(
+        &self,
+        model: &str,
+        prompt: &str,
+        options: Option<GenerateOptions>,
+    ) -> Result<TokenStream, AIError> {
         let request = GenerateRequest {
             model: model.to_string(),
             prompt: prompt.to_string(),
@@ -621,32 +680,61 @@ impl OllamaClient {
             options,
         };
 
-        let response = self.client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| AIError::OllamaConnection(e.to_string()))?;
+        let response = self.post_streaming("/api/generate", &request, "Generation failed").await?;
+        Ok(TokenStream::new(response, |line| {
+            let chunk: GenerateStreamResponse = serde_json::from_str(line)?;
+            match chunk.error {
+                Some(error) => Err(AIError::OllamaConnection(format!("Generation failed: {}", error))),
+                None => Ok((chunk.response, chunk.done)),
+            }
+        }))
+    }
 
-        if !response.status().is_success() {
-            return Err(AIError::OllamaConnection(format!(
-                "Generation failed: {}",
-                response.status()
-            )));
-        }
+    /// Chat with the model, receiving the reply token by token
+    pub async fn chat_tokens(
+        &self,
+        model: &str,
+        messages: &[Message],
+        options: Option<GenerateOptions>,
+    ) -> Result<TokenStream, AIError> {
+        let request = ChatRequest {
+            model: model.to_string(),
+            messages: messages.iter().map(OllamaMessage::from).collect(),
+            stream: true,
+            options,
+        };
 
-        // Create a stream that reads the response body line by line
-        let stream = async_stream::stream! {
-            let body = response.text().await.map_err(|e| AIError::OllamaConnection(e.to_string()))?;
-            
-            for line in body.lines() {
-                if let Ok(response) = serde_json::from_str::<GenerateStreamResponse>(line) {
-                    yield Ok(response.response);
-                }
+        let response = self.post_streaming("/api/chat", &request, "Chat failed").await?;
+        Ok(TokenStream::new(response, |line| {
+            let chunk: ChatStreamResponse = serde_json::from_str(line)?;
+            match chunk.error {
+                Some(error) => Err(AIError::OllamaConnection(format!("Chat failed: {}", error))),
+                None => Ok((chunk.message.map(|message| message.content).unwrap_or_default(), chunk.done)),
             }
-        };
+        }))
+    }
 
-        Ok(Box::pin(stream))
+    /// Send a request whose response is streamed
+    ///
+    /// Only establishing the stream is retried; a stream broken midway is
+    /// reported as is.
+    async fn post_streaming<Req: Serialize>(&self, path: &str, request: &Req, operation: &str) -> Result<reqwest::Response, AIError> {
+        let url = format!("{}{}", self.base_url, path);
+
+        self.retry_policy.run(&self.health, || async {
+            self.inject_faults().await?;
+            let response = self.client
+                .post(&url)
+                .json(request)
+                .send()
+                .await
+                .map_err(|e| request_error(operation, e))?;
+
+            if !response.status().is_success() {
+                return Err(status_error(operation, response.status()));
+            }
+            Ok(response)
+        }).await
     }
 
     /// Chat with the model
@@ -656,19 +744,7 @@ impl OllamaClient {
         messages: &[Message],
         options: Option<GenerateOptions>,
     ) -> Result<String, AIError> {
-        let url = format!("{}/api/chat", self.base_url);
-        
-        let ollama_messages: Vec<OllamaMessage> = messages
-            .iter()
-            .map(|msg| OllamaMessage {
-                role: match msg.role {
-                    MessageRole::User => "user".to_string(),
-                    MessageRole::Assistant => "assistant".to_string(),
-                    MessageRole::System => "system".to_string(),
-                },
-                content: msg.content.clone(),
-            })
-            .collect();
+        let ollama_messages: Vec<OllamaMessage> = messages.iter().map(OllamaMessage::from).collect();
 
         let request = ChatRequest {
             model: model.to_string(),
@@ -677,24 +753,7 @@ impl OllamaClient {
             options,
         };
 
-        let response = self.client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| AIError::OllamaConnection(e.to_string()))?;
-
-        if !response.status().is_success() {
-            return Err(AIError::OllamaConnection(format!(
-                "Chat failed: {}",
-                response.status()
-            )));
-        }
-
-        let data: ChatResponse = response
-            .json()
-            .await
-            .map_err(|e| AIError::OllamaConnection(e.to_string()))?;
+        let data: ChatResponse = self.post_json("/api/chat", &request, "Chat failed").await?;
 
         Ok(data.message.content)
     }
@@ -705,33 +764,32 @@ impl OllamaClient {
         model: &str,
         prompt: &str,
     ) -> Result<Vec<f32>, AIError> {
-        let url = format!("{}/api/embeddings", self.base_url);
-        
         let request = EmbeddingsRequest {
             model: model.to_string(),
             prompt: prompt.to_string(),
         };
 
-        let response = self.client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| AIError::OllamaConnection(e.to_string()))?;
+        let data: EmbeddingsResponse = self.post_json("/api/embeddings", &request, "Embeddings failed").await?;
 
-        if !response.status().is_success() {
-            return Err(AIError::OllamaConnection(format!(
-                "Embeddings failed: {}",
-                response.status()
-            )));
-        }
+        Ok(data.embedding)
+    }
+}
 
-        let data: EmbeddingsResponse = response
-            .json()
-            .await
-            .map_err(|e| AIError::OllamaConnection(e.to_string()))?;
+/// Map a request error, treating connection failures and timeouts as transient
+fn request_error(operation: &str, error: reqwest::Error) -> AIError {
+    if error.is_connect() || error.is_timeout() {
+        AIError::Unavailable(format!("{}: {}", operation, error))
+    } else {
+        AIError::OllamaConnection(format!("{}: {}", operation, error))
+    }
+}
 
-        Ok(data.embedding)
+/// Map an error status, treating server errors and throttling as transient
+fn status_error(operation: &str, status: StatusCode) -> AIError {
+    if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+        AIError::Unavailable(format!("{}: {}", operation, status))
+    } else {
+        AIError::OllamaConnection(format!("{}: {}", operation, status))
     }
 }
 
@@ -832,11 +890,15 @@ struct GenerateResponse {
     pub eval_duration: Option<u64>,
 }
 
-/// Streaming generate response
+/// Streaming generate response, one per line
 #[derive(Debug, Deserialize)]
 struct GenerateStreamResponse {
+    #[serde(default)]
     response: String,
+    #[serde(default)]
     done: bool,
+    #[serde(default)]
+    error: Option<String>,
 }
 
 /// Ollama message format
@@ -846,6 +908,19 @@ struct OllamaMessage {
     content: String,
 }
 
+impl From<&Message> for OllamaMessage {
+    fn from(message: &Message) -> Self {
+        Self {
+            role: match message.role {
+                MessageRole::User => "user".to_string(),
+                MessageRole::Assistant => "assistant".to_string(),
+                MessageRole::System => "system".to_string(),
+            },
+            content: message.content.clone(),
+        }
+    }
+}
+
 /// Chat request
 #[derive(Debug, Serialize)]
 struct ChatRequest {
@@ -882,11 +957,157 @@ struct EmbeddingsResponse {
     embedding: Vec<f32>,
 }
 
-/// Chat stream response
+/// Chat stream response, one per line
 #[derive(Debug, Deserialize)]
 struct ChatStreamResponse {
-    message: OllamaMessage,
+    #[serde(default)]
+    message: Option<OllamaMessage>,
+    #[serde(default)]
     done: bool,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Text and done flag of one line of a streamed response
+type ParseLine = fn(&str) -> Result<(String, bool), AIError>;
+
+/// Cancels a token stream, e.g. from the close button of an agent panel
+#[derive(Debug, Clone)]
+pub struct StreamCancel(Arc<watch::Sender<bool>>);
+
+impl StreamCancel {
+    /// Stop the stream; it ends with [`AIError::Cancelled`] and the request
+    /// to the server is dropped
+    pub fn cancel(&self) {
+        self.0.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.0.borrow()
+    }
+}
+
+/// Tokens of a response as the model produces them
+///
+/// The last token has `is_final` set. Dropping the stream or cancelling it
+/// closes the connection, which stops generation on the server.
+pub struct TokenStream {
+    inner: Pin<Box<dyn Stream<Item = Result<StreamingToken, AIError>> + Send>>,
+    cancel: StreamCancel,
+}
+
+impl TokenStream {
+    fn new(response: reqwest::Response, parse: ParseLine) -> Self {
+        let (cancel_tx, mut cancel_rx) = watch::channel(false);
+        let inner = async_stream::stream! {
+            let mut response = response;
+            let mut lines = LineBuffer::default();
+            let mut index = 0;
+            let mut last_token = Instant::now();
+            let mut done = false;
+            while !done {
+                let cancelled = async { cancel_rx.wait_for(|cancelled| *cancelled).await.is_ok() };
+                let chunk = tokio::select! {
+                    biased;
+                    true = cancelled => None,
+                    chunk = response.chunk() => Some(chunk),
+                };
+                let Some(chunk) = chunk else {
+                    yield Err(AIError::Cancelled("Streaming response was cancelled".to_string()));
+                    break;
+                };
+                match chunk {
+                    Ok(Some(bytes)) => lines.push(&bytes),
+                    Ok(None) => {
+                        lines.finish();
+                        done = true;
+                    }
+                    Err(e) => {
+                        yield Err(AIError::OllamaConnection(format!("Stream interrupted: {}", e)));
+                        break;
+                    }
+                }
+                while let Some(line) = lines.take_line() {
+                    match parse(&line) {
+                        Ok((text, is_final)) => {
+                            yield Ok(StreamingToken {
+                                text,
+                                index,
+                                processing_time: last_token.elapsed(),
+                                is_final,
+                            });
+                            index += 1;
+                            last_token = Instant::now();
+                            done |= is_final;
+                        }
+                        Err(e) => {
+                            yield Err(e);
+                            done = true;
+                        }
+                    }
+                    if done {
+                        break;
+                    }
+                }
+            }
+        };
+        Self {
+            inner: Box::pin(inner),
+            cancel: StreamCancel(Arc::new(cancel_tx)),
+        }
+    }
+
+    /// Handle to cancel the stream from elsewhere
+    pub fn cancel_handle(&self) -> StreamCancel {
+        self.cancel.clone()
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+}
+
+impl Stream for TokenStream {
+    type Item = Result<StreamingToken, AIError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+/// Splits streamed bytes into the JSON lines Ollama sends
+#[derive(Debug, Default)]
+struct LineBuffer {
+    pending: Vec<u8>,
+    lines: VecDeque<String>,
+}
+
+impl LineBuffer {
+    fn push(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+        while let Some(end) = self.pending.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            self.keep(&line);
+        }
+    }
+
+    /// Take the last line when the body ends without a newline
+    fn finish(&mut self) {
+        let line = std::mem::take(&mut self.pending);
+        self.keep(&line);
+    }
+
+    fn keep(&mut self, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim();
+        if !line.is_empty() {
+            self.lines.push_back(line.to_string());
+        }
+    }
+
+    fn take_line(&mut self) -> Option<String> {
+        self.lines.pop_front()
+    }
 }
 
 /// Server information
@@ -920,4 +1141,21 @@ mod tests {
         assert!(options.top_p.is_none());
         assert!(options.top_k.is_none());
     }
+
+    #[test]
+    fn test_line_buffer_joins_split_lines() {
+        let mut lines = LineBuffer::default();
+        lines.push(b"{\"response\":\"Hel");
+        assert!(lines.take_line().is_none());
+        lines.push(b"lo\",\"done\":false}\n\n{\"response\":\"!\",");
+        lines.push(b"\"done\":true}");
+        lines.finish();
+
+        let first: GenerateStreamResponse = serde_json::from_str(&lines.take_line().unwrap()).unwrap();
+        let last: GenerateStreamResponse = serde_json::from_str(&lines.take_line().unwrap()).unwrap();
+        assert_eq!(first.response, "Hello");
+        assert!(!first.done);
+        assert!(last.done);
+        assert!(lines.take_line().is_none());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,295 @@
+//! Detecting applications that are used with the same files
+//!
+//! A file opened by an application, or opened shortly after the application
+//! was launched, counts as the two being used together. Once a pair has been
+//! used together often enough, and consistently enough relative to how often
+//! each is used at all, it is reported as a contextual [`DetectedPattern`],
+//! which the edge suggestion pipeline turns into a suggested relationship.
+
+use crate::patterns::DetectedPattern;
+use crate::storage::{ActionType, PatternType, UserAction};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Value of `kind` in the details of co-usage patterns
+pub const CO_USAGE_KIND: &str = "co_usage";
+
+/// Co-usage detection configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoUsageConfig {
+    /// Detect co-usage at all
+    pub enabled: bool,
+    /// A file opened this many seconds after an application launch is used with it
+    pub window_secs: u64,
+    /// Times a pair must be used together before it is reported
+    pub min_occurrences: u32,
+    /// Confidence needed before a pair is reported
+    pub min_confidence: f32,
+    /// Usage older than this many hours stops counting
+    pub history_hours: u64,
+}
+
+impl Default for CoUsageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 120,
+            min_occurrences: 3,
+            min_confidence: 0.5,
+            history_hours: 24 * 14,
+        }
+    }
+}
+
+/// One launch or file open and the pairs it counted
+#[derive(Debug, Clone)]
+struct UsageRecord {
+    at: DateTime<Utc>,
+    application: Option<String>,
+    file: Option<String>,
+    pairs: Vec<(String, String)>,
+}
+
+/// Counts applications and files used together and reports consistent pairs
+#[derive(Debug)]
+pub struct CoUsageDetector {
+    /// Configuration
+    config: CoUsageConfig,
+    /// Usage inside the history horizon, oldest first
+    history: VecDeque<UsageRecord>,
+    /// Launches per application
+    launches: HashMap<String, u32>,
+    /// Opens per file
+    opens: HashMap<String, u32>,
+    /// Times each (application, file) pair was used together
+    pair_counts: HashMap<(String, String), u32>,
+    /// Pairs already reported or forgotten
+    reported: HashSet<(String, String)>,
+}
+
+impl CoUsageDetector {
+    /// Create a detector
+    pub fn new(config: CoUsageConfig) -> Self {
+        Self {
+            config,
+            history: VecDeque::new(),
+            launches: HashMap::new(),
+            opens: HashMap::new(),
+            pair_counts: HashMap::new(),
+            reported: HashSet::new(),
+        }
+    }
+
+    /// Current configuration
+    pub fn config(&self) -> &CoUsageConfig {
+        &self.config
+    }
+
+    /// Replace the configuration
+    pub fn set_config(&mut self, config: CoUsageConfig) {
+        self.config = config;
+    }
+
+    /// Record a monitored action, counting application launches and file opens
+    pub fn record_action(&mut self, action: &UserAction) {
+        let known = |name: &str| !name.is_empty() && name != "unknown";
+        match action.action_type {
+            ActionType::AppLaunch if known(&action.target) => self.record_launch(&action.target, action.timestamp),
+            ActionType::FileOpen if known(&action.target) => {
+                let application = Some(action.application.as_str()).filter(|app| known(app));
+                self.record_open(&action.target, application, action.timestamp);
+            }
+            _ => {}
+        }
+    }
+
+    /// Record that an application was launched
+    pub fn record_launch(&mut self, application: &str, at: DateTime<Utc>) {
+        if !self.config.enabled {
+            return;
+        }
+        self.expire(at);
+        *self.launches.entry(application.to_string()).or_default() += 1;
+        self.history.push_back(UsageRecord {
+            at,
+            application: Some(application.to_string()),
+            file: None,
+            pairs: Vec::new(),
+        });
+    }
+
+    /// Record that a file was opened, by `application` if known
+    pub fn record_open(&mut self, file: &str, application: Option<&str>, at: DateTime<Utc>) {
+        if !self.config.enabled {
+            return;
+        }
+        self.expire(at);
+
+        // The opening application, and every application launched shortly before
+        let window = ChronoDuration::seconds(self.config.window_secs as i64);
+        let mut applications: Vec<String> = application.map(str::to_string).into_iter().collect();
+        for record in self.history.iter().rev() {
+            if at - record.at > window {
+                break;
+            }
+            if let Some(app) = &record.application {
+                if !applications.contains(app) {
+                    applications.push(app.clone());
+                }
+            }
+        }
+
+        let pairs: Vec<(String, String)> = applications.into_iter().map(|app| (app, file.to_string())).collect();
+        for pair in &pairs {
+            *self.pair_counts.entry(pair.clone()).or_default() += 1;
+        }
+        *self.opens.entry(file.to_string()).or_default() += 1;
+        self.history.push_back(UsageRecord {
+            at,
+            application: None,
+            file: Some(file.to_string()),
+            pairs,
+        });
+    }
+
+    /// Confidence that an application and a file belong together
+    ///
+    /// Times used together relative to how often the more frequently used of
+    /// the two was used, so an editor that opens every file scores low with
+    /// each of them, while a viewer only ever used for one file scores high.
+    pub fn confidence(&self, application: &str, file: &str) -> f32 {
+        let together = self.pair_counts.get(&(application.to_string(), file.to_string())).copied().unwrap_or(0) as f32;
+        let launches = self.launches.get(application).copied().unwrap_or(0);
+        let opens = self.opens.get(file).copied().unwrap_or(0);
+        let uses = launches.max(opens) as f32;
+        if together == 0.0 || uses == 0.0 {
+            return 0.0;
+        }
+        (together / uses).min(1.0)
+    }
+
+    /// Pairs that passed the thresholds and were not reported before, most confident first
+    pub fn take_patterns(&mut self) -> Vec<DetectedPattern> {
+        let now = Utc::now();
+        let mut pairs: Vec<(&(String, String), u32, f32)> = self.pair_counts.iter()
+            .filter(|(pair, count)| **count >= self.config.min_occurrences && !self.reported.contains(*pair))
+            .map(|(pair, count)| (pair, *count, self.confidence(&pair.0, &pair.1)))
+            .filter(|(_, _, confidence)| *confidence >= self.config.min_confidence)
+            .collect();
+        pairs.sort_by(|a, b| {
+            b.2.partial_cmp(&a.2)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.cmp(b.0))
+        });
+
+        let patterns: Vec<DetectedPattern> = pairs.into_iter()
+            .map(|((application, file), count, confidence)| {
+                let first_detected = self.history.iter()
+                    .find(|record| record.pairs.iter().any(|(a, f)| a == application && f == file))
+                    .map(|record| record.at)
+                    .unwrap_or(now);
+                DetectedPattern {
+                    id: pattern_id(application, file),
+                    name: format!("{} is used with {}", application, display_name(file)),
+                    confidence,
+                    occurrences: count,
+                    first_detected,
+                    last_detected: now,
+                    pattern_type: PatternType::Contextual,
+                    details: serde_json::json!({
+                        "kind": CO_USAGE_KIND,
+                        "application": application,
+                        "file": file,
+                        "co_occurrences": count,
+                    }),
+                }
+            })
+            .collect();
+        for pattern in &patterns {
+            if let (Some(application), Some(file)) = (pattern.details["application"].as_str(), pattern.details["file"].as_str()) {
+                self.reported.insert((application.to_string(), file.to_string()));
+            }
+        }
+        patterns
+    }
+
+    /// Never report a pair again, e.g. after its suggestion was rejected
+    pub fn forget(&mut self, application: &str, file: &str) {
+        self.reported.insert((application.to_string(), file.to_string()));
+    }
+
+    /// Drop usage older than the history horizon, undoing its counts
+    fn expire(&mut self, now: DateTime<Utc>) {
+        let horizon = now - ChronoDuration::hours(self.config.history_hours as i64);
+        while self.history.front().is_some_and(|record| record.at < horizon) {
+            let Some(record) = self.history.pop_front() else {
+                break;
+            };
+            if let Some(application) = &record.application {
+                decrement(&mut self.launches, application);
+            }
+            if let Some(file) = &record.file {
+                decrement(&mut self.opens, file);
+            }
+            for pair in &record.pairs {
+                decrement(&mut self.pair_counts, pair);
+            }
+        }
+    }
+}
+
+impl Default for CoUsageDetector {
+    fn default() -> Self {
+        Self::new(CoUsageConfig::default())
+    }
+}
+
+/// Id of the pattern for a pair
+pub fn pattern_id(application: &str, file: &str) -> String {
+    format!("co-usage:{}|{}", application, file)
+}
+
+/// Decrement a count, removing it at zero
+fn decrement<K: std::hash::Hash + Eq>(counts: &mut HashMap<K, u32>, key: &K) {
+    if let Some(count) = counts.get_mut(key) {
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            counts.remove(key);
+        }
+    }
+}
+
+/// Last path component of a file, or the file itself
+fn display_name(file: &str) -> &str {
+    file.rsplit('/').find(|part| !part.is_empty()).unwrap_or(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_launched_with_file_is_reported_once() {
+        let mut detector = CoUsageDetector::default();
+        let start = Utc::now();
+
+        // The viewer is always launched for the report; the editor opens many files
+        for day in 0..3 {
+            let at = start + ChronoDuration::hours(day * 24);
+            detector.record_launch("pdf-viewer", at);
+            detector.record_open("/home/user/report.pdf", None, at + ChronoDuration::seconds(20));
+            detector.record_open(&format!("/home/user/notes-{}.txt", day), Some("editor"), at + ChronoDuration::minutes(30));
+        }
+        detector.record_open("/home/user/report.pdf", Some("editor"), start + ChronoDuration::hours(80));
+
+        let patterns = detector.take_patterns();
+        assert_eq!(patterns.len(), 1);
+        assert_eq!(patterns[0].id, "co-usage:pdf-viewer|/home/user/report.pdf");
+        assert_eq!(patterns[0].occurrences, 3);
+        // Four opens of the report, three of them with the viewer
+        assert!((patterns[0].confidence - 0.75).abs() < 1e-6);
+        assert_eq!(patterns[0].details["kind"], CO_USAGE_KIND);
+        assert!(detector.take_patterns().is_empty());
+    }
+}
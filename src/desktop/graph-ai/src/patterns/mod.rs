@@ -3,7 +3,11 @@
 //! This module provides pattern detection, behavioral learning, and
 //! intelligent suggestions based on user actions and preferences.
 
+pub mod workspace_switch;
+pub mod co_usage;
+
 use crate::AIError;
+use crate::privacy::NodeAccessPolicy;
 use crate::storage::{Pattern, PatternType};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -17,6 +21,8 @@ pub struct PatternStorage {
     patterns: Arc<RwLock<HashMap<String, DetectedPattern>>>,
     /// Pattern statistics
     stats: Arc<RwLock<PatternStats>>,
+    /// Nodes patterns must not involve
+    node_access: NodeAccessPolicy,
 }
 
 // UserAction and ActionType are now imported from storage module
@@ -60,9 +66,15 @@ pub struct PatternStats {
 impl PatternStorage {
     /// Create a new pattern storage
     pub fn new() -> Self {
+        Self::with_node_access(NodeAccessPolicy::new())
+    }
+    
+    /// Create a pattern storage that drops patterns involving AI-invisible nodes
+    pub fn with_node_access(node_access: NodeAccessPolicy) -> Self {
         Self {
             patterns: Arc::new(RwLock::new(HashMap::new())),
             stats: Arc::new(RwLock::new(PatternStats::default())),
+            node_access,
         }
     }
     
@@ -72,8 +84,13 @@ impl PatternStorage {
         Ok(())
     }
     
-    /// Add a detected pattern
-    pub fn add_pattern(&self, pattern: DetectedPattern) {
+    /// Add a detected pattern, unless it involves an AI-invisible node
+    pub fn add_pattern(&self, pattern: DetectedPattern) -> bool {
+        if !self.node_access.allows_value(&pattern.details) {
+            log::debug!("Pattern {} involves an AI-invisible node, dropped", pattern.id);
+            return false;
+        }
+        
         let mut patterns = self.patterns.write();
         let mut stats = self.stats.write();
         
@@ -83,6 +100,7 @@ impl PatternStorage {
         let pattern_type_key = format!("{:?}", pattern.pattern_type);
         stats.patterns_by_type.entry(pattern_type_key).and_modify(|e| *e += 1).or_insert(1);
         stats.last_detection = Some(Utc::now());
+        true
     }
     
     /// Get patterns by type
@@ -90,6 +108,7 @@ impl PatternStorage {
         let patterns = self.patterns.read();
         patterns.values()
             .filter(|p| std::mem::discriminant(&p.pattern_type) == std::mem::discriminant(&pattern_type))
+            .filter(|p| self.node_access.allows_value(&p.details))
             .cloned()
             .collect()
     }
@@ -97,6 +116,9 @@ impl PatternStorage {
     /// Get all patterns
     pub fn get_all_patterns(&self) -> Vec<DetectedPattern> {
         let patterns = self.patterns.read();
-        patterns.values().cloned().collect()
+        patterns.values()
+            .filter(|p| self.node_access.allows_value(&p.details))
+            .cloned()
+            .collect()
     }
 }
\ No newline at end of file
@@ -0,0 +1,191 @@
+//! Learning which workspace the user switches to next
+//!
+//! Every workspace switch is recorded as a transition from one workspace
+//! to another. Predictions for the current workspace weigh how often each
+//! destination followed it, with extra weight for switches made around the
+//! same hour of day, since many switches follow a daily routine (mail in
+//! the morning, the project workspace after lunch). The workspace manager
+//! uses the predictions to preload the likely next workspace.
+
+use chrono::{DateTime, Duration as ChronoDuration, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Workspace switch learning configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceSwitchConfig {
+    /// Learn switch patterns at all
+    pub enabled: bool,
+    /// Switches older than this many hours stop counting
+    pub history_hours: u64,
+    /// Switches away from a workspace needed before predicting from it
+    pub min_switches: u32,
+    /// Extra weight of a switch made within an hour of the current time of day
+    pub same_hour_weight: f32,
+}
+
+impl Default for WorkspaceSwitchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            history_hours: 24 * 14,
+            min_switches: 3,
+            same_hour_weight: 2.0,
+        }
+    }
+}
+
+/// Likely next workspace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspacePrediction {
+    /// Workspace ID
+    pub workspace_id: String,
+    /// Share of the weighted switches away from the current workspace (0.0 to 1.0)
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone)]
+struct SwitchRecord {
+    from: String,
+    to: String,
+    at: DateTime<Utc>,
+}
+
+/// Records workspace switches and predicts the next one
+#[derive(Debug)]
+pub struct WorkspaceSwitchPredictor {
+    /// Configuration
+    config: WorkspaceSwitchConfig,
+    /// Switches inside the history horizon, oldest first
+    history: VecDeque<SwitchRecord>,
+}
+
+impl WorkspaceSwitchPredictor {
+    /// Create a predictor
+    pub fn new(config: WorkspaceSwitchConfig) -> Self {
+        Self {
+            config,
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Current configuration
+    pub fn config(&self) -> &WorkspaceSwitchConfig {
+        &self.config
+    }
+
+    /// Replace the configuration
+    pub fn set_config(&mut self, config: WorkspaceSwitchConfig) {
+        self.config = config;
+    }
+
+    /// Record a switch between two workspaces
+    pub fn record_switch(&mut self, from: &str, to: &str, at: DateTime<Utc>) {
+        if !self.config.enabled || from == to {
+            return;
+        }
+        self.expire(at);
+        self.history.push_back(SwitchRecord {
+            from: from.to_string(),
+            to: to.to_string(),
+            at,
+        });
+    }
+
+    /// Likely next workspaces after `current`, most likely first
+    ///
+    /// Empty until enough switches away from `current` were recorded.
+    pub fn predict(&self, current: &str, now: DateTime<Utc>) -> Vec<WorkspacePrediction> {
+        let horizon = now - ChronoDuration::hours(self.config.history_hours as i64);
+        let switches: Vec<&SwitchRecord> = self.history.iter()
+            .filter(|record| record.from == current && record.at >= horizon)
+            .collect();
+        if !self.config.enabled || (switches.len() as u32) < self.config.min_switches {
+            return Vec::new();
+        }
+
+        let mut weights: HashMap<&str, f32> = HashMap::new();
+        for record in &switches {
+            let weight = if hour_distance(record.at, now) <= 1 {
+                1.0 + self.config.same_hour_weight
+            } else {
+                1.0
+            };
+            *weights.entry(record.to.as_str()).or_default() += weight;
+        }
+        let total: f32 = weights.values().sum();
+
+        let mut predictions: Vec<WorkspacePrediction> = weights.into_iter()
+            .map(|(workspace_id, weight)| WorkspacePrediction {
+                workspace_id: workspace_id.to_string(),
+                confidence: weight / total,
+            })
+            .collect();
+        predictions.sort_by(|a, b| {
+            b.confidence.partial_cmp(&a.confidence)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.workspace_id.cmp(&b.workspace_id))
+        });
+        predictions
+    }
+
+    /// Stop predicting a deleted workspace
+    pub fn forget(&mut self, workspace_id: &str) {
+        self.history.retain(|record| record.from != workspace_id && record.to != workspace_id);
+    }
+
+    /// Number of switches inside the history horizon
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Drop switches older than the history horizon
+    fn expire(&mut self, now: DateTime<Utc>) {
+        let horizon = now - ChronoDuration::hours(self.config.history_hours as i64);
+        while self.history.front().is_some_and(|record| record.at < horizon) {
+            self.history.pop_front();
+        }
+    }
+}
+
+impl Default for WorkspaceSwitchPredictor {
+    fn default() -> Self {
+        Self::new(WorkspaceSwitchConfig::default())
+    }
+}
+
+/// Hours between two times of day, wrapping at midnight
+fn hour_distance(a: DateTime<Utc>, b: DateTime<Utc>) -> u32 {
+    let diff = a.hour().abs_diff(b.hour());
+    diff.min(24 - diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_switches_at_the_same_hour_weigh_more() {
+        let mut predictor = WorkspaceSwitchPredictor::default();
+        let morning = Utc.with_ymd_and_hms(2024, 3, 4, 9, 0, 0).unwrap();
+        let evening = Utc.with_ymd_and_hms(2024, 3, 4, 18, 0, 0).unwrap();
+
+        // Mail is usually followed by the project in the morning and by music at night
+        for day in 0..3 {
+            predictor.record_switch("mail", "project", morning + ChronoDuration::days(day));
+        }
+        for day in 0..2 {
+            predictor.record_switch("mail", "music", evening + ChronoDuration::days(day));
+        }
+        assert!(predictor.predict("project", morning).is_empty());
+
+        let now = evening + ChronoDuration::days(3);
+        let predictions = predictor.predict("mail", now);
+        assert_eq!(predictions[0].workspace_id, "music");
+        assert!((predictions.iter().map(|p| p.confidence).sum::<f32>() - 1.0).abs() < 1e-5);
+
+        predictor.forget("music");
+        assert_eq!(predictor.predict("mail", now)[0].workspace_id, "project");
+    }
+}
@@ -2,6 +2,12 @@
 //! 
 //! This module provides encryption and decryption services for data at rest
 //! and in transit, with key management and secure storage.
+//!
+//! Data is sealed with an AEAD cipher under a data encryption key. With
+//! [`KeyManagement::SystemKeyring`] keys live in the Secret Service keyring
+//! (through `secret-tool`), so data written in one session can be read in
+//! the next. [`KeyManagement::FileStorage`] keeps them in a key file only
+//! the user can read, for systems without a keyring.
 
 use crate::AIError;
 use crate::privacy::{EncryptionConfig, EncryptionAlgorithm, KeyManagement};
@@ -12,6 +18,18 @@ use chrono::{DateTime, Utc};
 use log::{info, warn, error, debug};
 use rand::Rng;
 use base64::{Engine as _, engine::general_purpose};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, CHACHA20_POLY1305};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// ID of the master key
+const MASTER_KEY_ID: &str = "master-key";
+/// Key entry naming the data encryption key in use
+const ACTIVE_DATA_KEY_ID: &str = "active-data-key";
+/// Service attribute of keys in the system keyring
+const KEYRING_SERVICE: &str = "horizonos-ai";
 
 /// Encryption key
 #[derive(Debug, Clone)]
@@ -103,7 +121,7 @@ pub struct EncryptionManager {
     /// Configuration
     config: Arc<RwLock<EncryptionConfig>>,
     /// Key storage
-    key_storage: Arc<RwLock<KeyStorage>>,
+    key_storage: Arc<KeyStorage>,
     /// Active keys
     active_keys: Arc<RwLock<HashMap<String, EncryptionKey>>>,
     /// Key metadata
@@ -116,8 +134,10 @@ pub struct EncryptionManager {
 struct KeyStorage {
     /// Storage type
     storage_type: KeyManagement,
-    /// Stored keys (for non-keyring storage)
-    keys: HashMap<String, Vec<u8>>,
+    /// Stored keys (for non-keyring storage); a copy of the key file with file storage
+    keys: RwLock<HashMap<String, Vec<u8>>>,
+    /// Key file for file storage
+    key_file: PathBuf,
 }
 
 /// Encryption statistics
@@ -140,14 +160,24 @@ pub struct EncryptionStats {
 impl EncryptionManager {
     /// Create a new encryption manager
     pub async fn new(config: EncryptionConfig) -> Result<Self, AIError> {
+        Self::with_key_file(config, default_key_file()).await
+    }
+    
+    /// Create an encryption manager keeping file storage keys in `key_file`
+    pub async fn with_key_file(config: EncryptionConfig, key_file: PathBuf) -> Result<Self, AIError> {
+        let keys = match config.key_management {
+            KeyManagement::FileStorage => read_key_file(&key_file)?,
+            _ => HashMap::new(),
+        };
         let key_storage = KeyStorage {
             storage_type: config.key_management.clone(),
-            keys: HashMap::new(),
+            keys: RwLock::new(keys),
+            key_file,
         };
         
         let manager = Self {
             config: Arc::new(RwLock::new(config)),
-            key_storage: Arc::new(RwLock::new(key_storage)),
+            key_storage: Arc::new(key_storage),
             active_keys: Arc::new(RwLock::new(HashMap::new())),
             key_metadata: Arc::new(RwLock::new(HashMap::new())),
             stats: Arc::new(RwLock::new(EncryptionStats::default())),
@@ -162,7 +192,7 @@ impl EncryptionManager {
     
     /// Initialize master key
     async fn initialize_master_key(&self) -> Result<(), AIError> {
-        let master_key_id = "master-key".to_string();
+        let master_key_id = MASTER_KEY_ID.to_string();
         
         // Check if master key exists
         if self.key_exists(&master_key_id).await? {
@@ -178,7 +208,7 @@ impl EncryptionManager {
     
     /// Generate master key
     async fn generate_master_key(&self) -> Result<(), AIError> {
-        let key_id = "master-key".to_string();
+        let key_id = MASTER_KEY_ID.to_string();
         let key_data = self.generate_key_data(32)?; // 256-bit key
         
         let key = EncryptionKey {
@@ -213,6 +243,11 @@ impl EncryptionManager {
         Ok(())
     }
     
+    /// Whether [`encrypt`](Self::encrypt) seals data, or passes it through
+    pub fn encrypts_at_rest(&self) -> bool {
+        self.config.read().at_rest
+    }
+    
     /// Encrypt data
     pub async fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>, AIError> {
         if !self.config.read().at_rest {
@@ -228,22 +263,30 @@ impl EncryptionManager {
         let iv = self.generate_iv()?;
         
         // Encrypt data
-        let ciphertext = match self.config.read().algorithm {
+        let algorithm = self.config.read().algorithm.clone();
+        let ciphertext = match algorithm {
             EncryptionAlgorithm::AES256GCM => {
-                self.encrypt_aes_gcm(data, &dek.data, &iv).await?
+                self.encrypt_aes_gcm(data, &dek.data, &iv).await
             }
             EncryptionAlgorithm::ChaCha20Poly1305 => {
-                self.encrypt_chacha20(data, &dek.data, &iv).await?
+                self.encrypt_chacha20(data, &dek.data, &iv).await
             }
             EncryptionAlgorithm::XChaCha20Poly1305 => {
-                self.encrypt_xchacha20(data, &dek.data, &iv).await?
+                self.encrypt_xchacha20(data, &dek.data, &iv).await
+            }
+        };
+        let ciphertext = match ciphertext {
+            Ok(ciphertext) => ciphertext,
+            Err(e) => {
+                self.stats.write().encryption_errors += 1;
+                return Err(e);
             }
         };
         
         // Create envelope
         let envelope = EncryptedEnvelope {
             version: 1,
-            algorithm: self.config.read().algorithm.clone(),
+            algorithm,
             key_id: dek.id.clone(),
             iv: general_purpose::STANDARD.encode(&iv),
             auth_tag: None, // Included in ciphertext for AEAD ciphers
@@ -291,13 +334,20 @@ impl EncryptionManager {
         // Decrypt data
         let plaintext = match envelope.algorithm {
             EncryptionAlgorithm::AES256GCM => {
-                self.decrypt_aes_gcm(&ciphertext, &key.data, &iv).await?
+                self.decrypt_aes_gcm(&ciphertext, &key.data, &iv).await
             }
             EncryptionAlgorithm::ChaCha20Poly1305 => {
-                self.decrypt_chacha20(&ciphertext, &key.data, &iv).await?
+                self.decrypt_chacha20(&ciphertext, &key.data, &iv).await
             }
             EncryptionAlgorithm::XChaCha20Poly1305 => {
-                self.decrypt_xchacha20(&ciphertext, &key.data, &iv).await?
+                self.decrypt_xchacha20(&ciphertext, &key.data, &iv).await
+            }
+        };
+        let plaintext = match plaintext {
+            Ok(plaintext) => plaintext,
+            Err(e) => {
+                self.stats.write().decryption_errors += 1;
+                return Err(e);
             }
         };
         
@@ -311,36 +361,20 @@ impl EncryptionManager {
     }
     
     /// Rotate encryption keys
+    ///
+    /// Data sealed with earlier keys stays readable; new data uses the new key.
     pub async fn rotate_keys(&self) -> Result<(), AIError> {
         info!("Starting key rotation");
         
-        // Generate new data encryption key
-        let new_dek = self.generate_data_key().await?;
-        
         // Mark old keys as rotated
-        let mut active_keys = self.active_keys.write();
-        let mut key_metadata = self.key_metadata.write();
-        
-        for (_key_id, metadata) in key_metadata.iter_mut() {
+        for metadata in self.key_metadata.write().values_mut() {
             if matches!(metadata.key_type, KeyType::DataEncryption) && matches!(metadata.status, KeyStatus::Active) {
                 metadata.status = KeyStatus::Rotated;
             }
         }
         
-        // Activate new key
-        active_keys.insert(new_dek.id.clone(), new_dek.clone());
-        
-        let metadata = KeyMetadata {
-            id: new_dek.id.clone(),
-            key_type: KeyType::DataEncryption,
-            status: KeyStatus::Active,
-            created_at: new_dek.created_at,
-            last_used: None,
-            usage_count: 0,
-            version: new_dek.version,
-        };
-        
-        key_metadata.insert(metadata.id.clone(), metadata);
+        // Generate and activate a new data encryption key
+        self.generate_data_key().await?;
         
         // Update statistics
         self.stats.write().key_rotations += 1;
@@ -352,24 +386,36 @@ impl EncryptionManager {
     /// Get or create data encryption key
     async fn get_or_create_dek(&self) -> Result<EncryptionKey, AIError> {
         // Look for active DEK
-        let active_keys = self.active_keys.read();
-        for (_, key) in active_keys.iter() {
-            if matches!(key.key_type, KeyType::DataEncryption) {
-                let metadata = self.key_metadata.read();
-                if let Some(meta) = metadata.get(&key.id) {
-                    if matches!(meta.status, KeyStatus::Active) {
-                        return Ok(key.clone());
-                    }
+        {
+            let active_keys = self.active_keys.read();
+            let metadata = self.key_metadata.read();
+            let active = active_keys.values().find(|key| {
+                matches!(key.key_type, KeyType::DataEncryption)
+                    && metadata.get(&key.id).is_some_and(|meta| matches!(meta.status, KeyStatus::Active))
+            });
+            if let Some(key) = active {
+                return Ok(key.clone());
+            }
+        }
+        
+        // Pick up the key a previous session left in use
+        if let Some(key_id) = self.key_storage.read_key(ACTIVE_DATA_KEY_ID).await? {
+            let key_id = String::from_utf8_lossy(&key_id).into_owned();
+            if self.key_exists(&key_id).await? {
+                let key = self.load_key(&key_id).await?;
+                if let Some(metadata) = self.key_metadata.write().get_mut(&key_id) {
+                    metadata.status = KeyStatus::Active;
                 }
+                return Ok(key);
             }
+            warn!("Data encryption key {} is missing, creating a new one", key_id);
         }
-        drop(active_keys);
         
         // No active DEK, create one
         self.generate_data_key().await
     }
     
-    /// Generate data encryption key
+    /// Generate data encryption key and make it the active one
     async fn generate_data_key(&self) -> Result<EncryptionKey, AIError> {
         let key_id = format!("dek-{}", uuid::Uuid::new_v4());
         let key_data = self.generate_key_data(32)?; // 256-bit key
@@ -383,11 +429,20 @@ impl EncryptionManager {
             version: 1,
         };
         
-        // Encrypt DEK with master key before storing
         self.store_key(&key).await?;
+        self.key_storage.write_key(ACTIVE_DATA_KEY_ID, key_id.as_bytes()).await?;
         
         // Add to active keys
         self.active_keys.write().insert(key_id.clone(), key.clone());
+        self.key_metadata.write().insert(key_id.clone(), KeyMetadata {
+            id: key_id.clone(),
+            key_type: KeyType::DataEncryption,
+            status: KeyStatus::Active,
+            created_at: key.created_at,
+            last_used: None,
+            usage_count: 0,
+            version: key.version,
+        });
         
         info!("Data encryption key generated: {}", key_id);
         Ok(key)
@@ -406,68 +461,35 @@ impl EncryptionManager {
     
     /// Check if key exists
     async fn key_exists(&self, key_id: &str) -> Result<bool, AIError> {
-        match self.config.read().key_management {
-            KeyManagement::SystemKeyring => {
-                // TODO: Check system keyring
-                Ok(false)
-            }
-            KeyManagement::FileStorage | KeyManagement::MemoryOnly => {
-                Ok(self.key_storage.read().keys.contains_key(key_id))
-            }
-            KeyManagement::HSM => {
-                // TODO: Check HSM
-                Ok(false)
-            }
+        if self.active_keys.read().contains_key(key_id) {
+            return Ok(true);
         }
+        Ok(self.key_storage.read_key(key_id).await?.is_some())
     }
     
     /// Store key
     async fn store_key(&self, key: &EncryptionKey) -> Result<(), AIError> {
-        match self.config.read().key_management {
-            KeyManagement::SystemKeyring => {
-                // TODO: Store in system keyring
-                log::warn!("System keyring not implemented, using memory storage");
-                self.key_storage.write().keys.insert(key.id.clone(), key.data.clone());
-            }
-            KeyManagement::FileStorage => {
-                // TODO: Encrypt and store to file
-                self.key_storage.write().keys.insert(key.id.clone(), key.data.clone());
-            }
-            KeyManagement::MemoryOnly => {
-                self.key_storage.write().keys.insert(key.id.clone(), key.data.clone());
-            }
-            KeyManagement::HSM => {
-                // TODO: Store in HSM
-                return Err(AIError::UnsupportedOperation("HSM not implemented".to_string()));
-            }
-        }
-        
-        Ok(())
+        self.key_storage.write_key(&key.id, &key.data).await
     }
     
     /// Load key
     async fn load_key(&self, key_id: &str) -> Result<EncryptionKey, AIError> {
-        let key_data = match self.config.read().key_management {
-            KeyManagement::SystemKeyring => {
-                // TODO: Load from system keyring
-                self.key_storage.read().keys.get(key_id)
-                    .ok_or_else(|| AIError::Configuration(format!("Key not found: {}", key_id)))?
-                    .clone()
-            }
-            KeyManagement::FileStorage | KeyManagement::MemoryOnly => {
-                self.key_storage.read().keys.get(key_id)
-                    .ok_or_else(|| AIError::Configuration(format!("Key not found: {}", key_id)))?
-                    .clone()
-            }
-            KeyManagement::HSM => {
-                // TODO: Load from HSM
-                return Err(AIError::UnsupportedOperation("HSM not implemented".to_string()));
-            }
-        };
-        
-        // Get metadata
-        let metadata = self.key_metadata.read().get(key_id)
-            .ok_or_else(|| AIError::Configuration(format!("Key metadata not found: {}", key_id)))?
+        let key_data = self.key_storage.read_key(key_id).await?
+            .ok_or_else(|| AIError::Encryption(format!("Key not found: {}", key_id)))?;
+        
+        // Keys from an earlier session have no metadata yet; data keys count
+        // as rotated until found to be the active one
+        let metadata = self.key_metadata.write()
+            .entry(key_id.to_string())
+            .or_insert_with(|| KeyMetadata {
+                id: key_id.to_string(),
+                key_type: if key_id == MASTER_KEY_ID { KeyType::Master } else { KeyType::DataEncryption },
+                status: if key_id == MASTER_KEY_ID { KeyStatus::Active } else { KeyStatus::Rotated },
+                created_at: Utc::now(),
+                last_used: None,
+                usage_count: 0,
+                version: 1,
+            })
             .clone();
         
         let key = EncryptionKey {
@@ -507,44 +529,32 @@ impl EncryptionManager {
     
     /// Encrypt with AES-GCM
     async fn encrypt_aes_gcm(&self, plaintext: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, AIError> {
-        // TODO: Implement actual AES-GCM encryption
-        // For now, simulate with XOR
-        let mut ciphertext = plaintext.to_vec();
-        for (i, byte) in ciphertext.iter_mut().enumerate() {
-            *byte ^= key[i % key.len()] ^ iv[i % iv.len()];
-        }
-        Ok(ciphertext)
+        seal(&AES_256_GCM, plaintext, key, iv)
     }
     
     /// Decrypt with AES-GCM
     async fn decrypt_aes_gcm(&self, ciphertext: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, AIError> {
-        // TODO: Implement actual AES-GCM decryption
-        // For now, simulate with XOR (same as encryption for XOR)
-        self.encrypt_aes_gcm(ciphertext, key, iv).await
+        open(&AES_256_GCM, ciphertext, key, iv)
     }
     
     /// Encrypt with ChaCha20-Poly1305
     async fn encrypt_chacha20(&self, plaintext: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, AIError> {
-        // TODO: Implement actual ChaCha20-Poly1305 encryption
-        self.encrypt_aes_gcm(plaintext, key, iv).await
+        seal(&CHACHA20_POLY1305, plaintext, key, iv)
     }
     
     /// Decrypt with ChaCha20-Poly1305
     async fn decrypt_chacha20(&self, ciphertext: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, AIError> {
-        // TODO: Implement actual ChaCha20-Poly1305 decryption
-        self.decrypt_aes_gcm(ciphertext, key, iv).await
+        open(&CHACHA20_POLY1305, ciphertext, key, iv)
     }
     
     /// Encrypt with XChaCha20-Poly1305
-    async fn encrypt_xchacha20(&self, plaintext: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, AIError> {
-        // TODO: Implement actual XChaCha20-Poly1305 encryption
-        self.encrypt_aes_gcm(plaintext, key, iv).await
+    async fn encrypt_xchacha20(&self, _plaintext: &[u8], _key: &[u8], _iv: &[u8]) -> Result<Vec<u8>, AIError> {
+        Err(AIError::UnsupportedOperation("XChaCha20-Poly1305 is not available, use AES-256-GCM or ChaCha20-Poly1305".to_string()))
     }
     
     /// Decrypt with XChaCha20-Poly1305
-    async fn decrypt_xchacha20(&self, ciphertext: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, AIError> {
-        // TODO: Implement actual XChaCha20-Poly1305 decryption
-        self.decrypt_aes_gcm(ciphertext, key, iv).await
+    async fn decrypt_xchacha20(&self, _ciphertext: &[u8], _key: &[u8], _iv: &[u8]) -> Result<Vec<u8>, AIError> {
+        Err(AIError::UnsupportedOperation("XChaCha20-Poly1305 is not available, use AES-256-GCM or ChaCha20-Poly1305".to_string()))
     }
     
     /// Update configuration
@@ -560,6 +570,191 @@ impl EncryptionManager {
     }
 }
 
+impl KeyStorage {
+    /// Read a key, `None` if it does not exist
+    async fn read_key(&self, key_id: &str) -> Result<Option<Vec<u8>>, AIError> {
+        match self.storage_type {
+            KeyManagement::SystemKeyring => keyring_lookup(key_id).await,
+            KeyManagement::FileStorage | KeyManagement::MemoryOnly => Ok(self.keys.read().get(key_id).cloned()),
+            KeyManagement::HSM => {
+                // TODO: Load from HSM
+                Err(AIError::UnsupportedOperation("HSM not implemented".to_string()))
+            }
+        }
+    }
+    
+    /// Write a key, replacing any with the same ID
+    async fn write_key(&self, key_id: &str, data: &[u8]) -> Result<(), AIError> {
+        match self.storage_type {
+            KeyManagement::SystemKeyring => keyring_store(key_id, data).await,
+            KeyManagement::FileStorage => {
+                let mut keys = self.keys.write();
+                let previous = keys.insert(key_id.to_string(), data.to_vec());
+                if let Err(e) = write_key_file(&self.key_file, &keys) {
+                    // Keep memory and file in step so a retry writes the same keys
+                    match previous {
+                        Some(previous) => keys.insert(key_id.to_string(), previous),
+                        None => keys.remove(key_id),
+                    };
+                    return Err(e);
+                }
+                Ok(())
+            }
+            KeyManagement::MemoryOnly => {
+                self.keys.write().insert(key_id.to_string(), data.to_vec());
+                Ok(())
+            }
+            KeyManagement::HSM => {
+                // TODO: Store in HSM
+                Err(AIError::UnsupportedOperation("HSM not implemented".to_string()))
+            }
+        }
+    }
+}
+
+/// Look a key up in the Secret Service keyring
+async fn keyring_lookup(key_id: &str) -> Result<Option<Vec<u8>>, AIError> {
+    let output = Command::new("secret-tool")
+        .args(["lookup", "service", KEYRING_SERVICE, "key", key_id])
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .map_err(|e| AIError::Encryption(format!("System keyring is unavailable: {}", e)))?;
+    lookup_result(key_id, &output)
+}
+
+/// Key found by a `secret-tool lookup`, `None` if nothing matched
+fn lookup_result(key_id: &str, output: &std::process::Output) -> Result<Option<Vec<u8>>, AIError> {
+    let encoded = String::from_utf8_lossy(&output.stdout);
+    let encoded = encoded.trim();
+    if !output.status.success() {
+        // secret-tool exits with 1 and prints nothing when nothing matches;
+        // anything else means the keyring could not be asked, e.g. it is locked
+        if output.status.code() == Some(1) && encoded.is_empty() && output.stderr.is_empty() {
+            return Ok(None);
+        }
+        return Err(AIError::Encryption(format!(
+            "Could not look key {} up in the system keyring ({}): {}",
+            key_id,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    general_purpose::STANDARD.decode(encoded)
+        .ok()
+        .filter(|key| !key.is_empty())
+        .map(Some)
+        .ok_or_else(|| AIError::Encryption(format!("Key {} in the system keyring is corrupt", key_id)))
+}
+
+/// Key file used by [`KeyManagement::FileStorage`]
+pub fn default_key_file() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("horizonos")
+        .join("ai")
+        .join("keys.json")
+}
+
+/// Keys in a key file, none if it does not exist yet
+fn read_key_file(path: &Path) -> Result<HashMap<String, Vec<u8>>, AIError> {
+    let content = match std::fs::read(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(AIError::Encryption(format!("Cannot read key file {}: {}", path.display(), e))),
+    };
+    let encoded: HashMap<String, String> = serde_json::from_slice(&content)
+        .map_err(|e| AIError::Encryption(format!("Key file {} is corrupt: {}", path.display(), e)))?;
+    encoded
+        .into_iter()
+        .map(|(id, key)| {
+            general_purpose::STANDARD.decode(&key)
+                .map(|key| (id.clone(), key))
+                .map_err(|e| AIError::Encryption(format!("Key {} in {} is corrupt: {}", id, path.display(), e)))
+        })
+        .collect()
+}
+
+/// Replace the key file, readable by the user only
+fn write_key_file(path: &Path, keys: &HashMap<String, Vec<u8>>) -> Result<(), AIError> {
+    use std::io::Write;
+    use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+    
+    let failed = |e: std::io::Error| AIError::Encryption(format!("Cannot write key file {}: {}", path.display(), e));
+    if let Some(dir) = path.parent() {
+        std::fs::DirBuilder::new().recursive(true).mode(0o700).create(dir).map_err(failed)?;
+    }
+    let encoded: HashMap<&String, String> = keys.iter().map(|(id, key)| (id, general_purpose::STANDARD.encode(key))).collect();
+    let content = serde_json::to_vec_pretty(&encoded).map_err(AIError::Serialization)?;
+    
+    // Written aside and renamed so a crash never leaves a half-written key file
+    let temporary = path.with_extension("json.tmp");
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&temporary)
+        .map_err(failed)?;
+    file.write_all(&content).and_then(|_| file.sync_all()).map_err(failed)?;
+    std::fs::rename(&temporary, path).map_err(failed)
+}
+
+/// Store a key in the Secret Service keyring
+async fn keyring_store(key_id: &str, data: &[u8]) -> Result<(), AIError> {
+    let unavailable = |e: std::io::Error| AIError::Encryption(format!("System keyring is unavailable: {}", e));
+    let mut child = Command::new("secret-tool")
+        .args(["store", "--label", &format!("HorizonOS AI key {}", key_id), "service", KEYRING_SERVICE, "key", key_id])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(unavailable)?;
+    
+    // The secret is read from standard input, never passed as an argument
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(general_purpose::STANDARD.encode(data).as_bytes()).await.map_err(unavailable)?;
+    }
+    let output = child.wait_with_output().await.map_err(unavailable)?;
+    if !output.status.success() {
+        return Err(AIError::Encryption(format!(
+            "Could not store key {} in the system keyring: {}",
+            key_id,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    debug!("Stored key {} in the system keyring", key_id);
+    Ok(())
+}
+
+/// Seal data with an AEAD cipher, appending the authentication tag
+fn seal(algorithm: &'static ring::aead::Algorithm, plaintext: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, AIError> {
+    let key = aead_key(algorithm, key)?;
+    let nonce = Nonce::try_assume_unique_for_key(iv)
+        .map_err(|_| AIError::Encryption("Invalid nonce length".to_string()))?;
+    let mut in_out = plaintext.to_vec();
+    key.seal_in_place_append_tag(nonce, Aad::empty(), &mut in_out)
+        .map_err(|_| AIError::Encryption("Encryption failed".to_string()))?;
+    Ok(in_out)
+}
+
+/// Open data sealed by [`seal`], checking its authentication tag
+fn open(algorithm: &'static ring::aead::Algorithm, ciphertext: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, AIError> {
+    let key = aead_key(algorithm, key)?;
+    let nonce = Nonce::try_assume_unique_for_key(iv)
+        .map_err(|_| AIError::Encryption("Invalid nonce length".to_string()))?;
+    let mut in_out = ciphertext.to_vec();
+    let plaintext = key.open_in_place(nonce, Aad::empty(), &mut in_out)
+        .map_err(|_| AIError::Encryption("Decryption failed, the data is corrupt or the key is wrong".to_string()))?;
+    Ok(plaintext.to_vec())
+}
+
+fn aead_key(algorithm: &'static ring::aead::Algorithm, key: &[u8]) -> Result<LessSafeKey, AIError> {
+    UnboundKey::new(algorithm, key)
+        .map(LessSafeKey::new)
+        .map_err(|_| AIError::Encryption("Invalid key length".to_string()))
+}
+
 impl Clone for EncryptionStats {
     fn clone(&self) -> Self {
         Self {
@@ -631,4 +826,72 @@ mod tests {
         assert!(matches!(metadata.status, KeyStatus::Active));
         assert_eq!(metadata.usage_count, 0);
     }
+    
+    #[tokio::test]
+    async fn test_encrypt_round_trip_and_tampering() {
+        let config = EncryptionConfig {
+            key_management: KeyManagement::MemoryOnly,
+            ..EncryptionConfig::default()
+        };
+        let manager = EncryptionManager::new(config).await.unwrap();
+        
+        let sealed = manager.encrypt(b"opened ~/notes.txt").await.unwrap();
+        assert!(!String::from_utf8_lossy(&sealed).contains("notes.txt"));
+        assert_eq!(manager.decrypt(&sealed).await.unwrap(), b"opened ~/notes.txt");
+        
+        // Data sealed before a rotation stays readable
+        manager.rotate_keys().await.unwrap();
+        assert_eq!(manager.decrypt(&sealed).await.unwrap(), b"opened ~/notes.txt");
+        
+        let mut envelope: EncryptedEnvelope = serde_json::from_slice(&sealed).unwrap();
+        let mut ciphertext = general_purpose::STANDARD.decode(&envelope.ciphertext).unwrap();
+        ciphertext[0] ^= 1;
+        envelope.ciphertext = general_purpose::STANDARD.encode(&ciphertext);
+        let tampered = serde_json::to_vec(&envelope).unwrap();
+        assert!(matches!(manager.decrypt(&tampered).await, Err(AIError::Encryption(_))));
+        assert_eq!(manager.get_stats().decryption_errors, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_file_storage_keeps_keys_across_sessions() {
+        use std::os::unix::fs::PermissionsExt;
+        
+        let dir = std::env::temp_dir().join(format!("horizonos-keys-{}", uuid::Uuid::new_v4()));
+        let key_file = dir.join("keys.json");
+        let config = EncryptionConfig {
+            key_management: KeyManagement::FileStorage,
+            ..EncryptionConfig::default()
+        };
+        
+        let sealed = {
+            let manager = EncryptionManager::with_key_file(config.clone(), key_file.clone()).await.unwrap();
+            manager.encrypt(b"opened ~/notes.txt").await.unwrap()
+        };
+        let mode = std::fs::metadata(&key_file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        
+        let manager = EncryptionManager::with_key_file(config, key_file.clone()).await.unwrap();
+        assert_eq!(manager.decrypt(&sealed).await.unwrap(), b"opened ~/notes.txt");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    
+    #[test]
+    fn test_keyring_lookup_results() {
+        use std::os::unix::process::ExitStatusExt;
+        use std::process::{ExitStatus, Output};
+        
+        let output = |code: i32, stdout: &str, stderr: &str| Output {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        };
+        let key = general_purpose::STANDARD.encode([7u8; 32]);
+        assert_eq!(lookup_result("k", &output(0, &format!("{}\n", key), "")).unwrap(), Some(vec![7u8; 32]));
+        assert_eq!(lookup_result("k", &output(1, "", "")).unwrap(), None);
+        
+        // A locked or missing keyring is an error, not a missing key
+        assert!(lookup_result("k", &output(1, "", "Cannot autolaunch D-Bus without X11 $DISPLAY")).is_err());
+        assert!(lookup_result("k", &output(2, "", "")).is_err());
+        assert!(lookup_result("k", &output(0, "not base64!", "")).is_err());
+    }
 }
\ No newline at end of file
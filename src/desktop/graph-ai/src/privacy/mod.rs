@@ -7,8 +7,10 @@ pub mod consent;
 pub mod encryption;
 pub mod audit;
 pub mod anonymization;
+pub mod node_access;
 
 use crate::AIError;
+pub use node_access::NodeAccessPolicy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -143,7 +145,7 @@ pub enum KeyManagement {
     SystemKeyring,
     /// Hardware security module
     HSM,
-    /// Encrypted file storage
+    /// Key file readable by the user only
     FileStorage,
     /// Memory only (session keys)
     MemoryOnly,
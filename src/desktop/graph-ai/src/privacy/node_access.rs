@@ -0,0 +1,228 @@
+//! Per-node AI access control
+//!
+//! Users can mark single nodes, or whole clusters, as AI-invisible. The
+//! per-node flag lives in the node's metadata (`NodeMetadata::privacy`);
+//! clusters are hidden here by listing their members. [`NodeAccessPolicy`]
+//! collects both, together with the file paths, application names and URLs
+//! the hidden nodes stand for, because the AI mostly sees those rather than
+//! node ids: user actions name a target path, suggestions a source and
+//! target. Pattern detection, suggestion generation and agent memory all
+//! check the same shared policy before keeping anything.
+
+use horizonos_graph_engine::{NodeType, Scene, SceneId};
+use parking_lot::RwLock;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::agents::langchain::MemoryEntry;
+use crate::storage::UserAction;
+use crate::suggestions::Suggestion;
+
+/// Keys whose values name a node, in action context, suggestion parameters and memory metadata
+const NODE_ID_KEYS: &[&str] = &["node_id", "node_ids", "source_node", "target_node"];
+
+/// Keys whose values name a path, application or URL
+const TARGET_KEYS: &[&str] = &["target", "source", "path", "url", "application", "file"];
+
+#[derive(Debug, Default)]
+struct AccessState {
+    /// Nodes flagged in their metadata
+    flagged: HashSet<SceneId>,
+    /// Members of hidden clusters, by cluster id
+    clusters: HashMap<String, HashSet<SceneId>>,
+    /// Paths, application names and URLs of hidden nodes
+    targets: HashSet<String>,
+}
+
+impl AccessState {
+    fn is_hidden(&self, node_id: SceneId) -> bool {
+        self.flagged.contains(&node_id) || self.clusters.values().any(|members| members.contains(&node_id))
+    }
+
+    fn is_hidden_target(&self, target: &str) -> bool {
+        let target = target.trim();
+        !target.is_empty()
+            && self.targets.iter().any(|hidden| {
+                target.eq_ignore_ascii_case(hidden)
+                    || target
+                        .strip_prefix(hidden.as_str())
+                        .is_some_and(|rest| rest.starts_with('/') || hidden.ends_with('/'))
+            })
+    }
+
+    /// Whether a JSON value names a hidden node or target under one of the known keys
+    fn mentions_hidden(&self, key: Option<&str>, value: &Value) -> bool {
+        match value {
+            Value::Object(map) => map.iter().any(|(key, value)| self.mentions_hidden(Some(key), value)),
+            Value::Array(items) => items.iter().any(|item| self.mentions_hidden(key, item)),
+            Value::Number(number) => {
+                key.is_some_and(|key| NODE_ID_KEYS.contains(&key))
+                    && number.as_u64().is_some_and(|id| self.is_hidden(id))
+            }
+            Value::String(text) => key.is_some_and(|key| TARGET_KEYS.contains(&key)) && self.is_hidden_target(text),
+            _ => false,
+        }
+    }
+}
+
+/// Nodes the AI service must not read, shared by everything that feeds it
+///
+/// Clones share state, so the policy handed to the monitoring system sees
+/// later changes made through the service.
+#[derive(Debug, Clone, Default)]
+pub struct NodeAccessPolicy {
+    state: Arc<RwLock<AccessState>>,
+}
+
+impl NodeAccessPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read the AI-invisible flags and hidden targets from the scene
+    ///
+    /// Call after loading a scene and whenever node privacy flags change.
+    pub fn sync_scene(&self, scene: &Scene) {
+        let mut state = self.state.write();
+        state.flagged = scene
+            .nodes()
+            .filter(|(_, node)| node.metadata.privacy.ai_invisible)
+            .map(|(id, _)| *id)
+            .collect();
+        let hidden: HashSet<SceneId> = state
+            .flagged
+            .iter()
+            .chain(state.clusters.values().flatten())
+            .copied()
+            .collect();
+        state.targets = scene
+            .nodes()
+            .filter(|(id, _)| hidden.contains(id))
+            .filter_map(|(_, node)| node_target(&node.node_type))
+            .collect();
+    }
+
+    /// Hide or show all members of a cluster; takes effect for targets on the next [`sync_scene`](Self::sync_scene)
+    pub fn set_cluster_hidden(&self, cluster_id: &str, members: impl IntoIterator<Item = SceneId>, hidden: bool) {
+        let mut state = self.state.write();
+        if hidden {
+            state.clusters.insert(cluster_id.to_string(), members.into_iter().collect());
+        } else {
+            state.clusters.remove(cluster_id);
+        }
+    }
+
+    /// Clusters currently hidden
+    pub fn hidden_clusters(&self) -> Vec<String> {
+        self.state.read().clusters.keys().cloned().collect()
+    }
+
+    pub fn is_node_visible(&self, node_id: SceneId) -> bool {
+        !self.state.read().is_hidden(node_id)
+    }
+
+    /// Whether a path, application name or URL belongs to no hidden node
+    pub fn is_target_visible(&self, target: &str) -> bool {
+        !self.state.read().is_hidden_target(target)
+    }
+
+    /// Whether a user action may be used for pattern detection
+    pub fn allows_action(&self, action: &UserAction) -> bool {
+        let state = self.state.read();
+        !state.is_hidden_target(&action.target) && !state.mentions_hidden(None, &action.context)
+    }
+
+    /// Whether a suggestion may be shown
+    pub fn allows_suggestion(&self, suggestion: &Suggestion) -> bool {
+        let state = self.state.read();
+        !suggestion
+            .action
+            .parameters
+            .iter()
+            .any(|(key, value)| state.mentions_hidden(Some(key), value))
+            && !state.mentions_hidden(None, &suggestion.context)
+    }
+
+    /// Whether an agent may remember an entry
+    pub fn allows_memory(&self, entry: &MemoryEntry) -> bool {
+        let state = self.state.read();
+        !entry.metadata.iter().any(|(key, value)| state.mentions_hidden(Some(key), value))
+    }
+
+    /// Whether details of a detected pattern may be kept
+    pub fn allows_value(&self, value: &Value) -> bool {
+        !self.state.read().mentions_hidden(None, value)
+    }
+}
+
+/// What the AI sees a node as, if anything
+pub(crate) fn node_target(node_type: &NodeType) -> Option<String> {
+    match node_type {
+        NodeType::File { path, .. } => Some(path.clone()),
+        NodeType::Application { name, .. } => Some(name.clone()),
+        NodeType::URL { url, .. } => Some(url.clone()),
+        NodeType::Person { contact_info, .. } => contact_info.email.clone(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::ActionType;
+    use horizonos_graph_engine::{FileType, NodeMetadata, NodePrivacy, Position, SceneNode, Vec3};
+    use uuid::Uuid;
+
+    fn file_node(scene: &mut Scene, path: &str, ai_invisible: bool) -> SceneId {
+        scene.add_node(SceneNode {
+            id: 0,
+            position: Position::new(0.0, 0.0, 0.0),
+            velocity: Vec3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type: NodeType::File { path: path.to_string(), file_type: FileType::Directory },
+            metadata: NodeMetadata { privacy: NodePrivacy { ai_invisible }, ..NodeMetadata::default() },
+            visible: true,
+            selected: false,
+        })
+    }
+
+    fn action(target: &str) -> UserAction {
+        UserAction {
+            id: Uuid::new_v4(),
+            user_id: "user".to_string(),
+            action_type: ActionType::FileOpen,
+            target: target.to_string(),
+            application: "editor".to_string(),
+            timestamp: chrono::Utc::now(),
+            context: serde_json::json!({}),
+            duration_ms: None,
+            success: true,
+            error_message: None,
+        }
+    }
+
+    #[test]
+    fn test_hidden_nodes_and_clusters_block_ai_input() {
+        let mut scene = Scene::new();
+        let journal = file_node(&mut scene, "/home/me/journal", true);
+        let taxes = file_node(&mut scene, "/home/me/taxes", false);
+        let code = file_node(&mut scene, "/home/me/code", false);
+
+        let policy = NodeAccessPolicy::new();
+        policy.set_cluster_hidden("finances", [taxes], true);
+        policy.sync_scene(&scene);
+
+        assert!(!policy.is_node_visible(journal) && !policy.is_node_visible(taxes));
+        assert!(policy.is_node_visible(code));
+        assert!(!policy.allows_action(&action("/home/me/journal/2024-01-01.md")));
+        assert!(!policy.allows_action(&action("/home/me/taxes")));
+        assert!(policy.allows_action(&action("/home/me/journal-notes.md")));
+        assert!(!policy.allows_value(&serde_json::json!({ "node_ids": [code, journal] })));
+
+        policy.set_cluster_hidden("finances", [], false);
+        policy.sync_scene(&scene);
+        assert!(policy.allows_action(&action("/home/me/taxes")));
+    }
+}
@@ -0,0 +1,437 @@
+//! Retry, backoff and degraded-mode reporting for external subsystems
+//!
+//! Ollama and the pattern database can disappear or stall at any time. Calls
+//! to them go through a [`RetryPolicy`], which retries transient failures
+//! (see [`AIError::is_transient`]) with exponential backoff, and a
+//! [`HealthTracker`], which records how the subsystem is doing. After
+//! repeated failed operations the tracker opens its circuit and calls fail
+//! fast until a cooldown has passed, so a dead server does not stall every
+//! caller for the full retry schedule.
+//!
+//! A tracker given a [`ProblemsPanel`] reports the subsystem there while it
+//! is degraded or unavailable and resolves the problem once it recovers.
+
+use crate::AIError;
+use chrono::{DateTime, Utc};
+use horizonos_graph_errors::{Category, Problem, ProblemsPanel, RecoveryAction, Severity};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Retry schedule for transient failures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Attempts per operation, including the first
+    pub max_attempts: u32,
+    /// Wait before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound on the wait between retries
+    pub max_backoff: Duration,
+    /// Growth factor of the wait per retry
+    pub multiplier: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Single attempt, no retries
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Wait before retry number `retry` (starting at 1)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = self.multiplier.max(1.0).powi(retry.saturating_sub(1) as i32);
+        self.initial_backoff.mul_f64(factor).min(self.max_backoff)
+    }
+
+    /// Run `operation`, retrying transient failures and reporting to `health`
+    pub async fn run<T, F, Fut>(&self, health: &HealthTracker, mut operation: F) -> Result<T, AIError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, AIError>>,
+    {
+        if let Some(remaining) = health.circuit_open_for() {
+            return Err(AIError::Unavailable(format!(
+                "{} is unavailable, next attempt in {:.1}s",
+                health.name(),
+                remaining.as_secs_f64()
+            )));
+        }
+
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Ok(value) => {
+                    health.record_success();
+                    return Ok(value);
+                }
+                Err(e) if e.is_transient() && attempt < self.max_attempts => {
+                    let wait = self.backoff(attempt);
+                    log::warn!(
+                        "{} attempt {}/{} failed, retrying in {:?}: {}",
+                        health.name(),
+                        attempt,
+                        self.max_attempts,
+                        wait,
+                        e
+                    );
+                    health.record_retry(&e);
+                    tokio::time::sleep(wait).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    if e.is_transient() {
+                        health.record_failure(&e);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+/// Condition of a subsystem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SubsystemState {
+    /// Working normally
+    Healthy,
+    /// Failing intermittently; requests are retried
+    Degraded,
+    /// Failing consistently; requests fail fast until the cooldown ends
+    Unavailable,
+}
+
+/// Snapshot of a subsystem's health for status reporting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubsystemStatus {
+    /// Subsystem name
+    pub name: String,
+    /// Current condition
+    pub state: SubsystemState,
+    /// Operations that failed in a row
+    pub consecutive_failures: u32,
+    /// Retries performed since creation
+    pub total_retries: u64,
+    /// Most recent error
+    pub last_error: Option<String>,
+    /// When the subsystem left the healthy state
+    pub degraded_since: Option<DateTime<Utc>>,
+}
+
+impl SubsystemStatus {
+    /// Problem describing an unhealthy subsystem
+    pub fn problem(&self) -> Option<Problem> {
+        let (severity, message) = match self.state {
+            SubsystemState::Healthy => return None,
+            SubsystemState::Degraded => (Severity::Warning, format!("{} is responding unreliably", self.name)),
+            SubsystemState::Unavailable => (Severity::Error, format!("{} is unavailable; AI features are limited", self.name)),
+        };
+        let mut problem = Problem::new(DEGRADED_CODE, Category::Ai, severity, message)
+            .in_subsystem(&self.name)
+            .transient();
+        if let Some(error) = &self.last_error {
+            problem = problem.with_detail(error);
+        }
+        if self.state == SubsystemState::Unavailable {
+            problem = problem.with_recovery(RecoveryAction::open_settings("ai"));
+        }
+        Some(problem)
+    }
+}
+
+/// Problem code of unhealthy subsystems
+const DEGRADED_CODE: &str = "ai.backend_degraded";
+
+#[derive(Debug)]
+struct HealthState {
+    state: SubsystemState,
+    consecutive_failures: u32,
+    total_retries: u64,
+    last_error: Option<String>,
+    degraded_since: Option<DateTime<Utc>>,
+    circuit_opened_at: Option<Instant>,
+}
+
+/// Tracks the health of one subsystem
+#[derive(Debug)]
+pub struct HealthTracker {
+    name: String,
+    /// Failed operations in a row before the circuit opens
+    failure_threshold: u32,
+    /// How long the circuit stays open
+    cooldown: Duration,
+    state: Mutex<HealthState>,
+    /// Where changes in health are reported
+    problems: Option<Arc<ProblemsPanel>>,
+}
+
+impl HealthTracker {
+    /// Create a tracker that opens after 3 failed operations for 30 seconds
+    pub fn new(name: &str) -> Self {
+        Self::with_thresholds(name, 3, Duration::from_secs(30))
+    }
+
+    /// Create a tracker with custom circuit settings
+    pub fn with_thresholds(name: &str, failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            name: name.to_string(),
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: Mutex::new(HealthState {
+                state: SubsystemState::Healthy,
+                consecutive_failures: 0,
+                total_retries: 0,
+                last_error: None,
+                degraded_since: None,
+                circuit_opened_at: None,
+            }),
+            problems: None,
+        }
+    }
+
+    /// Report degraded and unavailable states to a problems panel
+    pub fn with_problems(mut self, problems: Arc<ProblemsPanel>) -> Self {
+        self.problems = Some(problems);
+        self
+    }
+
+    /// Subsystem name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Current condition
+    pub fn state(&self) -> SubsystemState {
+        self.state.lock().state
+    }
+
+    /// Snapshot for status reporting
+    pub fn status(&self) -> SubsystemStatus {
+        let state = self.state.lock();
+        SubsystemStatus {
+            name: self.name.clone(),
+            state: state.state,
+            consecutive_failures: state.consecutive_failures,
+            total_retries: state.total_retries,
+            last_error: state.last_error.clone(),
+            degraded_since: state.degraded_since,
+        }
+    }
+
+    /// Remaining cooldown if the circuit is open
+    ///
+    /// Once the cooldown has passed one request is let through; its outcome
+    /// closes the circuit or opens it again.
+    pub fn circuit_open_for(&self) -> Option<Duration> {
+        let mut state = self.state.lock();
+        let opened_at = state.circuit_opened_at?;
+        let elapsed = opened_at.elapsed();
+        if elapsed >= self.cooldown {
+            state.circuit_opened_at = None;
+            None
+        } else {
+            Some(self.cooldown - elapsed)
+        }
+    }
+
+    /// Record a successful operation
+    pub fn record_success(&self) {
+        let mut state = self.state.lock();
+        let recovered = state.state != SubsystemState::Healthy;
+        if recovered {
+            log::info!("{} recovered", self.name);
+        }
+        state.state = SubsystemState::Healthy;
+        state.consecutive_failures = 0;
+        state.degraded_since = None;
+        state.circuit_opened_at = None;
+        drop(state);
+
+        if let (true, Some(problems)) = (recovered, &self.problems) {
+            problems.resolve_code(&self.name, DEGRADED_CODE);
+        }
+    }
+
+    /// Record a failed attempt that will be retried
+    pub fn record_retry(&self, error: &AIError) {
+        let mut state = self.state.lock();
+        state.total_retries += 1;
+        state.last_error = Some(error.to_string());
+        let degraded = state.state == SubsystemState::Healthy;
+        if degraded {
+            state.state = SubsystemState::Degraded;
+            state.degraded_since = Some(Utc::now());
+        }
+        drop(state);
+
+        if degraded {
+            self.report();
+        }
+    }
+
+    /// Record an operation that failed after all retries
+    pub fn record_failure(&self, error: &AIError) {
+        let mut state = self.state.lock();
+        let previous = state.state;
+        state.consecutive_failures += 1;
+        state.last_error = Some(error.to_string());
+        state.degraded_since.get_or_insert_with(Utc::now);
+        if state.consecutive_failures >= self.failure_threshold {
+            if state.state != SubsystemState::Unavailable {
+                log::error!("{} unavailable after {} failed operations: {}", self.name, state.consecutive_failures, error);
+            }
+            state.state = SubsystemState::Unavailable;
+            state.circuit_opened_at = Some(Instant::now());
+        } else {
+            state.state = SubsystemState::Degraded;
+        }
+        let changed = state.state != previous;
+        drop(state);
+
+        if changed {
+            self.report();
+        }
+    }
+
+    /// Report the current state to the problems panel
+    fn report(&self) {
+        if let (Some(problems), Some(problem)) = (&self.problems, self.status().problem()) {
+            problems.report(problem);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn no_backoff(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            multiplier: 2.0,
+        }
+    }
+
+    /// Operation failing with `error` for the first `failures` calls
+    async fn flaky(calls: &AtomicU32, failures: u32, error: fn() -> AIError) -> Result<u32, AIError> {
+        let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+        if call <= failures { Err(error()) } else { Ok(call) }
+    }
+
+    fn unavailable() -> AIError {
+        AIError::Unavailable("connection refused".to_string())
+    }
+
+    #[test]
+    fn test_backoff_grows_up_to_the_limit() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(3), Duration::from_millis(800));
+        assert_eq!(policy.backoff(10), Duration::from_secs(5));
+        assert_eq!(RetryPolicy::none().max_attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried() {
+        let health = HealthTracker::new("Ollama");
+        let calls = AtomicU32::new(0);
+
+        let result = no_backoff(3).run(&health, || flaky(&calls, 2, unavailable)).await;
+
+        assert_eq!(result.unwrap(), 3);
+        let status = health.status();
+        assert_eq!(status.state, SubsystemState::Healthy);
+        assert_eq!(status.total_retries, 2);
+        assert_eq!(status.last_error.as_deref(), Some(unavailable().to_string().as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_permanent_failures_are_not_retried() {
+        let health = HealthTracker::new("Ollama");
+        let calls = AtomicU32::new(0);
+
+        let result = no_backoff(3).run(&health, || flaky(&calls, 1, || AIError::Configuration("bad model".to_string()))).await;
+
+        assert!(!result.unwrap_err().is_transient());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(health.state(), SubsystemState::Healthy);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_opens_after_repeated_failures() {
+        let health = HealthTracker::with_thresholds("Ollama", 2, Duration::from_secs(60));
+        let calls = AtomicU32::new(0);
+        let policy = no_backoff(2);
+
+        assert!(policy.run(&health, || flaky(&calls, u32::MAX, unavailable)).await.is_err());
+        assert_eq!(health.state(), SubsystemState::Degraded);
+        assert!(policy.run(&health, || flaky(&calls, u32::MAX, unavailable)).await.is_err());
+        assert_eq!(health.state(), SubsystemState::Unavailable);
+        assert_eq!(health.status().consecutive_failures, 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        // Open circuits fail fast without calling the subsystem
+        let error = policy.run(&health, || flaky(&calls, 0, unavailable)).await.unwrap_err();
+        assert!(error.is_transient());
+        assert!(health.circuit_open_for().is_some());
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_lets_one_request_through_after_the_cooldown() {
+        let health = HealthTracker::with_thresholds("Ollama", 1, Duration::ZERO);
+        let calls = AtomicU32::new(0);
+        let policy = no_backoff(1);
+
+        assert!(policy.run(&health, || flaky(&calls, 1, unavailable)).await.is_err());
+        assert_eq!(health.state(), SubsystemState::Unavailable);
+
+        assert_eq!(policy.run(&health, || flaky(&calls, 1, unavailable)).await.unwrap(), 2);
+        let status = health.status();
+        assert_eq!(status.state, SubsystemState::Healthy);
+        assert_eq!(status.consecutive_failures, 0);
+        assert!(status.degraded_since.is_none());
+    }
+
+    #[test]
+    fn test_unhealthy_states_are_reported_and_resolved() {
+        let problems = Arc::new(ProblemsPanel::new());
+        let health = HealthTracker::with_thresholds("Ollama", 2, Duration::from_secs(60)).with_problems(problems.clone());
+
+        health.record_retry(&unavailable());
+        let reported = problems.problems();
+        assert_eq!(reported.len(), 1);
+        assert_eq!((reported[0].code.as_str(), reported[0].severity), (DEGRADED_CODE, Severity::Warning));
+
+        // Becoming unavailable raises the same problem and offers the AI settings
+        health.record_failure(&unavailable());
+        health.record_failure(&unavailable());
+        let reported = problems.problems();
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].severity, Severity::Error);
+        assert!(!reported[0].recovery.is_empty());
+
+        health.record_success();
+        assert!(problems.problems().is_empty());
+        assert!(health.status().problem().is_none());
+    }
+}
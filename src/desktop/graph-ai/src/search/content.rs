@@ -0,0 +1,442 @@
+//! Text extraction and the inverted index over file contents
+
+use crate::AIError;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// On-disk format version; indexes with another version are rebuilt
+const INDEX_VERSION: u32 = 1;
+
+/// Bytes inspected when deciding whether a file is binary
+const BINARY_PROBE: usize = 8192;
+
+/// Extensions read as plain text
+const TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "md", "markdown", "rst", "org", "adoc", "tex", "log", "csv", "tsv", "json", "toml", "yaml", "yml",
+    "ini", "conf", "cfg", "xml", "rs", "py", "js", "ts", "jsx", "tsx", "c", "h", "cpp", "hpp", "cc", "java",
+    "kt", "kts", "go", "rb", "php", "sh", "bash", "zsh", "fish", "lua", "sql", "css", "scss", "nix",
+];
+
+/// Extensions read as markup with tags stripped
+const MARKUP_EXTENSIONS: &[&str] = &["html", "htm", "xhtml", "svg"];
+
+/// Whether text can be extracted from files with this name
+pub fn is_supported(path: &Path) -> bool {
+    let Some(extension) = path.extension().and_then(|e| e.to_str()) else {
+        return false;
+    };
+    let extension = extension.to_ascii_lowercase();
+    TEXT_EXTENSIONS.contains(&extension.as_str()) || MARKUP_EXTENSIONS.contains(&extension.as_str())
+}
+
+/// Extract searchable text from a file
+///
+/// Returns `None` for unsupported types, files larger than `max_size` and
+/// files that turn out to be binary.
+pub fn extract_text(path: &Path, max_size: u64) -> Result<Option<String>, AIError> {
+    if !is_supported(path) {
+        return Ok(None);
+    }
+    let metadata = std::fs::metadata(path)?;
+    if !metadata.is_file() || metadata.len() > max_size {
+        return Ok(None);
+    }
+
+    let mut bytes = Vec::with_capacity(metadata.len() as usize);
+    std::fs::File::open(path)?.take(max_size).read_to_end(&mut bytes)?;
+    if bytes[..bytes.len().min(BINARY_PROBE)].contains(&0) {
+        return Ok(None);
+    }
+    let text = String::from_utf8_lossy(&bytes).into_owned();
+
+    let markup = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| MARKUP_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()));
+    Ok(Some(if markup { strip_tags(&text) } else { text }))
+}
+
+/// Remove markup tags, keeping the text between them
+fn strip_tags(markup: &str) -> String {
+    let mut text = String::with_capacity(markup.len());
+    let mut in_tag = false;
+    for c in markup.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Split text into lowercase index terms
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| (2..=64).contains(&word.chars().count()))
+        .map(str::to_lowercase)
+}
+
+/// Excerpt of `text` around the first occurrence of a query term
+pub fn snippet(text: &str, terms: &[String], max_chars: usize) -> Option<String> {
+    let lower = text.to_lowercase();
+    // Lowercasing can change byte lengths, so positions are mapped back through char indices
+    let (position, term) = terms
+        .iter()
+        .filter_map(|term| lower.find(term.as_str()).map(|position| (position, term)))
+        .min_by_key(|(position, _)| *position)?;
+    let match_start = lower[..position].chars().count();
+    let match_len = term.chars().count();
+
+    let chars: Vec<char> = text.chars().collect();
+    let context = max_chars.saturating_sub(match_len) / 2;
+    let start = match_start.saturating_sub(context);
+    let end = (match_start + match_len + context).min(chars.len());
+
+    let excerpt: String = chars[start.min(end)..end]
+        .iter()
+        .map(|c| if c.is_whitespace() { ' ' } else { *c })
+        .collect();
+    let mut snippet = excerpt.split(' ').filter(|w| !w.is_empty()).collect::<Vec<_>>().join(" ");
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    Some(snippet)
+}
+
+/// File modification stamp used to skip unchanged files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    /// Modification time in nanoseconds since the epoch
+    pub modified: u128,
+    /// Size in bytes
+    pub size: u64,
+}
+
+impl FileStamp {
+    /// Stamp of the file at `path`
+    pub fn of(path: &Path) -> Result<Self, AIError> {
+        let metadata = std::fs::metadata(path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        Ok(Self { modified, size: metadata.len() })
+    }
+}
+
+/// A file in the index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedDocument {
+    /// File path
+    pub path: PathBuf,
+    /// Stamp when the file was indexed
+    pub stamp: FileStamp,
+    /// Number of terms in the file
+    pub length: u32,
+    /// Distinct terms, kept so the document can be removed from postings
+    terms: Vec<String>,
+}
+
+/// A file matching a content query
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentHit {
+    /// File path
+    pub path: PathBuf,
+    /// Relevance score
+    pub score: f32,
+}
+
+/// Inverted index from terms to the files containing them
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ContentIndex {
+    version: u32,
+    next_id: u32,
+    documents: HashMap<u32, IndexedDocument>,
+    paths: HashMap<PathBuf, u32>,
+    /// Term -> document -> occurrences
+    postings: HashMap<String, HashMap<u32, u32>>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl Default for ContentIndex {
+    fn default() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            next_id: 0,
+            documents: HashMap::new(),
+            paths: HashMap::new(),
+            postings: HashMap::new(),
+            dirty: false,
+        }
+    }
+}
+
+impl ContentIndex {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load an index saved with [`save`](Self::save)
+    ///
+    /// A missing, corrupt or outdated index yields an empty one that is
+    /// rebuilt by the next crawl.
+    pub fn load(path: &Path) -> Self {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    log::warn!("Failed to read content index {}: {}", path.display(), e);
+                }
+                return Self::new();
+            }
+        };
+        match bincode::deserialize::<Self>(&bytes) {
+            Ok(index) if index.version == INDEX_VERSION => index,
+            Ok(index) => {
+                log::info!("Content index version {} is outdated, rebuilding", index.version);
+                Self::new()
+            }
+            Err(e) => {
+                log::warn!("Content index {} is corrupt, rebuilding: {}", path.display(), e);
+                Self::new()
+            }
+        }
+    }
+
+    /// Write the index to `path`, replacing the previous file atomically
+    pub fn save(&mut self, path: &Path) -> Result<(), AIError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let bytes = bincode::serialize(self)
+            .map_err(|e| AIError::Configuration(format!("Failed to serialize content index: {}", e)))?;
+        let temporary = path.with_extension("tmp");
+        std::fs::write(&temporary, bytes)?;
+        std::fs::rename(&temporary, path)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Whether there are changes not yet saved
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Number of indexed files
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Whether no files are indexed
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Whether `path` is indexed
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.contains_key(path)
+    }
+
+    /// Whether `path` is indexed with the given stamp
+    pub fn is_current(&self, path: &Path, stamp: FileStamp) -> bool {
+        self.paths
+            .get(path)
+            .and_then(|id| self.documents.get(id))
+            .is_some_and(|document| document.stamp == stamp)
+    }
+
+    /// Index the text of a file, replacing any previous entry
+    pub fn insert(&mut self, path: &Path, stamp: FileStamp, text: &str) {
+        self.remove(path);
+
+        let mut frequencies: HashMap<String, u32> = HashMap::new();
+        let mut length = 0u32;
+        for term in tokenize(text) {
+            *frequencies.entry(term).or_insert(0) += 1;
+            length += 1;
+        }
+
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        let terms: Vec<String> = frequencies.keys().cloned().collect();
+        for (term, count) in frequencies {
+            self.postings.entry(term).or_default().insert(id, count);
+        }
+        self.documents.insert(id, IndexedDocument { path: path.to_path_buf(), stamp, length, terms });
+        self.paths.insert(path.to_path_buf(), id);
+        self.dirty = true;
+    }
+
+    /// Remove a file; returns whether it was indexed
+    pub fn remove(&mut self, path: &Path) -> bool {
+        let Some(id) = self.paths.remove(path) else {
+            return false;
+        };
+        if let Some(document) = self.documents.remove(&id) {
+            for term in &document.terms {
+                if let Some(postings) = self.postings.get_mut(term) {
+                    postings.remove(&id);
+                    if postings.is_empty() {
+                        self.postings.remove(term);
+                    }
+                }
+            }
+        }
+        self.dirty = true;
+        true
+    }
+
+    /// Remove every file below `directory`; returns how many were removed
+    pub fn remove_under(&mut self, directory: &Path) -> usize {
+        let paths: Vec<PathBuf> = self.paths.keys().filter(|p| p.starts_with(directory)).cloned().collect();
+        paths.iter().filter(|path| self.remove(path)).count()
+    }
+
+    /// Indexed paths
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.paths.keys().map(PathBuf::as_path)
+    }
+
+    /// Files containing every query term, best first
+    ///
+    /// The last term also matches as a prefix so results update while typing.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<ContentHit> {
+        let terms: Vec<String> = tokenize(query).collect();
+        let Some((last, complete)) = terms.split_last() else {
+            return Vec::new();
+        };
+
+        let total = self.documents.len().max(1) as f32;
+        let mut scores: Option<HashMap<u32, f32>> = None;
+        for (term, prefix) in complete.iter().map(|t| (t, false)).chain(std::iter::once((last, true))) {
+            let mut matches: HashMap<u32, f32> = HashMap::new();
+            let postings: Vec<&HashMap<u32, u32>> = if prefix {
+                self.postings.iter().filter(|(t, _)| t.starts_with(term.as_str())).map(|(_, p)| p).collect()
+            } else {
+                self.postings.get(term).into_iter().collect()
+            };
+            for documents in postings {
+                let idf = (1.0 + total / documents.len() as f32).ln();
+                for (id, count) in documents {
+                    let length = self.documents.get(id).map(|d| d.length).unwrap_or(1).max(1) as f32;
+                    *matches.entry(*id).or_insert(0.0) += idf * *count as f32 / length.sqrt();
+                }
+            }
+            scores = Some(match scores {
+                None => matches,
+                Some(previous) => previous
+                    .into_iter()
+                    .filter_map(|(id, score)| matches.get(&id).map(|s| (id, score + s)))
+                    .collect(),
+            });
+            if scores.as_ref().is_some_and(HashMap::is_empty) {
+                return Vec::new();
+            }
+        }
+
+        let mut hits: Vec<ContentHit> = scores
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(id, score)| {
+                self.documents.get(&id).map(|document| ContentHit { path: document.path.clone(), score })
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// Distinct query terms, for snippet extraction
+pub fn query_terms(query: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tokenize(query).filter(|term| seen.insert(term.clone())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stamp(modified: u128) -> FileStamp {
+        FileStamp { modified, size: 0 }
+    }
+
+    #[test]
+    fn test_search_requires_all_terms_and_prefix_matches_last() {
+        let mut index = ContentIndex::new();
+        index.insert(Path::new("/a.md"), stamp(1), "Quarterly budget review for the graph desktop");
+        index.insert(Path::new("/b.md"), stamp(1), "Budget notes");
+
+        let hits = index.search("budget rev", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].path, PathBuf::from("/a.md"));
+        assert_eq!(index.search("budget", 10).len(), 2);
+        assert!(index.search("missing budget", 10).is_empty());
+    }
+
+    #[test]
+    fn test_reindexing_replaces_old_terms() {
+        let mut index = ContentIndex::new();
+        index.insert(Path::new("/a.txt"), stamp(1), "apples");
+        index.insert(Path::new("/a.txt"), stamp(2), "oranges");
+
+        assert!(index.search("apples", 10).is_empty());
+        assert_eq!(index.search("oranges", 10).len(), 1);
+        assert!(index.is_current(Path::new("/a.txt"), stamp(2)));
+
+        assert!(index.remove(Path::new("/a.txt")));
+        assert!(index.is_empty());
+        assert!(index.postings.is_empty());
+    }
+
+    #[test]
+    fn test_index_round_trips_through_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("index.bin");
+        let mut index = ContentIndex::new();
+        index.insert(Path::new("/notes.txt"), stamp(7), "persistent search index");
+        index.save(&file).unwrap();
+        assert!(!index.is_dirty());
+
+        let loaded = ContentIndex::load(&file);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.search("persistent", 10)[0].path, PathBuf::from("/notes.txt"));
+
+        std::fs::write(&file, b"garbage").unwrap();
+        assert!(ContentIndex::load(&file).is_empty());
+    }
+
+    #[test]
+    fn test_extraction_and_snippets() {
+        let dir = tempfile::tempdir().unwrap();
+        let page = dir.path().join("page.html");
+        std::fs::write(&page, "<html><body><p>Hello <b>world</b></p></body></html>").unwrap();
+        let binary = dir.path().join("blob.txt");
+        std::fs::write(&binary, [b'a', 0, b'b']).unwrap();
+
+        let text = extract_text(&page, 1024).unwrap().unwrap();
+        assert!(!text.contains('<'));
+        assert!(extract_text(&binary, 1024).unwrap().is_none());
+        assert!(extract_text(&dir.path().join("image.png"), 1024).unwrap().is_none());
+
+        let text = "The first line.\nSomething about the Compositor here.\nLast line.";
+        let snippet = snippet(text, &query_terms("compositor"), 30).unwrap();
+        assert!(snippet.contains("Compositor"));
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        assert!(!snippet.contains('\n'));
+    }
+}
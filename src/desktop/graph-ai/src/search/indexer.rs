@@ -0,0 +1,460 @@
+//! Background job keeping the content index in sync with the filesystem
+
+use super::content::{self, ContentIndex, FileStamp};
+use super::ContentIndexConfig;
+use crate::AIError;
+use notify::{EventKind, RecursiveMode, Watcher};
+use parking_lot::{Mutex, RwLock};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Work for the indexing job
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexRequest {
+    /// Index everything below a directory
+    Crawl(PathBuf),
+    /// A file was created or modified
+    Update(PathBuf),
+    /// A file or directory was removed
+    Remove(PathBuf),
+    /// Write the index to disk
+    Save,
+}
+
+/// Counters reported by the indexer
+#[derive(Debug, Clone, Default)]
+pub struct IndexerStats {
+    /// Files currently in the index
+    pub indexed_files: usize,
+    /// Files (re)indexed since start
+    pub files_processed: u64,
+    /// Files skipped because they match a privacy exclusion
+    pub files_excluded: u64,
+    /// Files that could not be read
+    pub errors: u64,
+    /// Requests waiting to be processed
+    pub pending: usize,
+}
+
+struct Inner {
+    config: ContentIndexConfig,
+    exclusions: Vec<Regex>,
+    index: RwLock<ContentIndex>,
+    stats: RwLock<IndexerStats>,
+}
+
+/// Maintains the content index in the background
+///
+/// Crawls the configured roots, then follows file watcher events so only
+/// changed files are re-read. Paths matching a privacy exclusion are never
+/// read and are dropped from the index if they were indexed before.
+pub struct ContentIndexer {
+    inner: Arc<Inner>,
+    sender: mpsc::UnboundedSender<IndexRequest>,
+    receiver: Mutex<Option<mpsc::UnboundedReceiver<IndexRequest>>>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+    watcher: Mutex<Option<notify::RecommendedWatcher>>,
+}
+
+impl ContentIndexer {
+    /// Create an indexer, loading the saved index if there is one
+    pub fn new(config: ContentIndexConfig) -> Self {
+        let exclusions = config
+            .excluded_paths
+            .iter()
+            .filter_map(|pattern| match Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    log::warn!("Invalid search exclusion pattern '{}': {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+        let index = ContentIndex::load(&config.index_path);
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            inner: Arc::new(Inner {
+                stats: RwLock::new(IndexerStats { indexed_files: index.len(), ..IndexerStats::default() }),
+                config,
+                exclusions,
+                index: RwLock::new(index),
+            }),
+            sender,
+            receiver: Mutex::new(Some(receiver)),
+            handle: Mutex::new(None),
+            watcher: Mutex::new(None),
+        }
+    }
+
+    /// Settings
+    pub fn config(&self) -> &ContentIndexConfig {
+        &self.inner.config
+    }
+
+    /// The index, for querying
+    pub fn index(&self) -> &RwLock<ContentIndex> {
+        &self.inner.index
+    }
+
+    /// Current counters
+    pub fn stats(&self) -> IndexerStats {
+        let mut stats = self.inner.stats.read().clone();
+        stats.indexed_files = self.inner.index.read().len();
+        stats
+    }
+
+    /// Whether `path` matches a privacy exclusion
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.inner.is_excluded(path)
+    }
+
+    /// Queue work for the background job
+    pub fn request(&self, request: IndexRequest) {
+        self.inner.stats.write().pending += 1;
+        if self.sender.send(request).is_err() {
+            self.inner.stats.write().pending -= 1;
+        }
+    }
+
+    /// Start the background job, crawl the roots and follow file changes
+    pub fn start(&self) -> Result<(), AIError> {
+        let Some(mut receiver) = self.receiver.lock().take() else {
+            return Err(AIError::UnsupportedOperation("Content indexer already started".to_string()));
+        };
+
+        let inner = self.inner.clone();
+        let sender = self.sender.clone();
+        let handle = tokio::spawn(async move {
+            log::info!("Content indexer started");
+            let mut save_timer = tokio::time::interval(Duration::from_secs(inner.config.save_interval_secs.max(1)));
+            loop {
+                tokio::select! {
+                    request = receiver.recv() => {
+                        let Some(request) = request else { break };
+                        inner.stats.write().pending = receiver.len();
+                        inner.process(request, &sender).await;
+                    }
+                    _ = save_timer.tick() => inner.save(),
+                }
+            }
+            inner.save();
+            log::info!("Content indexer stopped");
+        });
+        *self.handle.lock() = Some(handle);
+
+        for root in &self.inner.config.roots {
+            self.request(IndexRequest::Crawl(root.clone()));
+        }
+        self.watch()
+    }
+
+    /// Stop the background job and file watcher, saving the index
+    pub fn stop(&self) {
+        self.watcher.lock().take();
+        if let Some(handle) = self.handle.lock().take() {
+            handle.abort();
+        }
+        self.inner.save();
+    }
+
+    /// Index everything below `root` now
+    ///
+    /// Returns the number of files indexed. Files that were removed while
+    /// the indexer was not running are dropped from the index.
+    pub async fn crawl(&self, root: &Path) -> Result<usize, AIError> {
+        self.inner.crawl(root).await
+    }
+
+    /// Index one file now; returns whether it is in the index afterwards
+    pub async fn update(&self, path: &Path) -> Result<bool, AIError> {
+        self.inner.update(path).await
+    }
+
+    /// Remove a file or directory from the index now
+    pub fn remove(&self, path: &Path) {
+        let mut index = self.inner.index.write();
+        if !index.remove(path) {
+            index.remove_under(path);
+        }
+    }
+
+    /// Feed file watcher events for the roots into the job
+    fn watch(&self) -> Result<(), AIError> {
+        if self.inner.config.roots.is_empty() {
+            return Ok(());
+        }
+        let sender = self.sender.clone();
+        let inner = self.inner.clone();
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            let event = match result {
+                Ok(event) => event,
+                Err(e) => {
+                    log::warn!("Content index watcher error: {}", e);
+                    return;
+                }
+            };
+            for path in event.paths {
+                let request = match event.kind {
+                    EventKind::Remove(_) => IndexRequest::Remove(path),
+                    EventKind::Create(_) | EventKind::Modify(_) if path.exists() => IndexRequest::Update(path),
+                    // Renamed away
+                    EventKind::Modify(_) => IndexRequest::Remove(path),
+                    _ => continue,
+                };
+                inner.stats.write().pending += 1;
+                let _ = sender.send(request);
+            }
+        })
+        .map_err(|e| AIError::Configuration(format!("Failed to create content index watcher: {}", e)))?;
+
+        for root in &self.inner.config.roots {
+            if let Err(e) = watcher.watch(root, RecursiveMode::Recursive) {
+                log::warn!("Failed to watch {} for content indexing: {}", root.display(), e);
+            }
+        }
+        *self.watcher.lock() = Some(watcher);
+        Ok(())
+    }
+}
+
+impl Drop for ContentIndexer {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.get_mut().take() {
+            handle.abort();
+        }
+    }
+}
+
+impl Inner {
+    fn is_excluded(&self, path: &Path) -> bool {
+        let path = path.to_string_lossy();
+        self.exclusions.iter().any(|pattern| pattern.is_match(&path))
+    }
+
+    async fn process(&self, request: IndexRequest, sender: &mpsc::UnboundedSender<IndexRequest>) {
+        match request {
+            IndexRequest::Crawl(root) => {
+                if let Err(e) = self.crawl(&root).await {
+                    log::warn!("Failed to crawl {} for content indexing: {}", root.display(), e);
+                }
+            }
+            IndexRequest::Update(path) if path.is_dir() => {
+                // New directories are crawled once the queue reaches them
+                self.stats.write().pending += 1;
+                let _ = sender.send(IndexRequest::Crawl(path));
+            }
+            IndexRequest::Update(path) => {
+                if let Err(e) = self.update(&path).await {
+                    log::debug!("Failed to index {}: {}", path.display(), e);
+                }
+            }
+            IndexRequest::Remove(path) => {
+                let mut index = self.index.write();
+                if !index.remove(&path) {
+                    index.remove_under(&path);
+                }
+            }
+            IndexRequest::Save => self.save(),
+        }
+    }
+
+    async fn crawl(&self, root: &Path) -> Result<usize, AIError> {
+        let walk_root = root.to_path_buf();
+        let files = tokio::task::spawn_blocking(move || collect_files(&walk_root))
+            .await
+            .map_err(|e| AIError::Configuration(format!("Content crawl failed: {}", e)))?;
+
+        // Drop files that disappeared while nobody was watching
+        let stale: Vec<PathBuf> = self
+            .index
+            .read()
+            .paths()
+            .filter(|path| path.starts_with(root) && !path.exists())
+            .map(Path::to_path_buf)
+            .collect();
+        if !stale.is_empty() {
+            let mut index = self.index.write();
+            for path in &stale {
+                index.remove(path);
+            }
+        }
+
+        let mut indexed = 0;
+        for file in files {
+            match self.update(&file).await {
+                Ok(true) => indexed += 1,
+                Ok(false) => {}
+                Err(e) => log::debug!("Failed to index {}: {}", file.display(), e),
+            }
+        }
+        log::info!("Content index crawl of {} found {} files", root.display(), indexed);
+        Ok(indexed)
+    }
+
+    async fn update(&self, path: &Path) -> Result<bool, AIError> {
+        if self.is_excluded(path) {
+            self.stats.write().files_excluded += 1;
+            self.index.write().remove(path);
+            return Ok(false);
+        }
+        if !content::is_supported(path) {
+            return Ok(false);
+        }
+
+        let stamp = match FileStamp::of(path) {
+            Ok(stamp) => stamp,
+            Err(e) => {
+                self.index.write().remove(path);
+                return Err(e);
+            }
+        };
+        if self.index.read().is_current(path, stamp) {
+            return Ok(true);
+        }
+
+        let file = path.to_path_buf();
+        let max_size = self.config.max_file_size;
+        let text = tokio::task::spawn_blocking(move || content::extract_text(&file, max_size))
+            .await
+            .map_err(|e| AIError::Configuration(format!("Text extraction failed: {}", e)))?;
+
+        let mut index = self.index.write();
+        match text {
+            Ok(Some(text)) => {
+                index.insert(path, stamp, &text);
+                self.stats.write().files_processed += 1;
+                Ok(true)
+            }
+            Ok(None) => {
+                index.remove(path);
+                Ok(false)
+            }
+            Err(e) => {
+                index.remove(path);
+                self.stats.write().errors += 1;
+                Err(e)
+            }
+        }
+    }
+
+    fn save(&self) {
+        let mut index = self.index.write();
+        if !index.is_dirty() {
+            return;
+        }
+        if let Err(e) = index.save(&self.config.index_path) {
+            log::warn!("Failed to save content index: {}", e);
+        }
+    }
+}
+
+/// Regular files below `root`, without following symlinks or entering hidden directories
+fn collect_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut directories = vec![root.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        let Ok(entries) = std::fs::read_dir(&directory) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if file_type.is_dir() {
+                if !entry.file_name().to_string_lossy().starts_with('.') {
+                    directories.push(path);
+                }
+            } else if file_type.is_file() {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_indexer(dir: &Path, excluded_paths: Vec<String>) -> ContentIndexer {
+        ContentIndexer::new(ContentIndexConfig {
+            enabled: true,
+            roots: Vec::new(),
+            index_path: dir.join("index").join("content.idx"),
+            excluded_paths,
+            ..ContentIndexConfig::default()
+        })
+    }
+
+    /// Wait for the background job to get the index into the expected state
+    async fn wait_for(indexer: &ContentIndexer, done: impl Fn(&ContentIndex) -> bool) {
+        for _ in 0..500 {
+            if done(&indexer.index().read()) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("Content indexer did not process its requests");
+    }
+
+    #[tokio::test]
+    async fn test_background_job_indexes_queries_and_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("home").join("project");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(project.join("readme.md"), "Background indexing of the graph desktop").unwrap();
+
+        let indexer = new_indexer(dir.path(), Vec::new());
+        indexer.start().unwrap();
+        assert!(indexer.start().is_err());
+
+        // New directories are crawled
+        indexer.request(IndexRequest::Update(project.clone()));
+        wait_for(&indexer, |index| !index.search("indexing grap", 10).is_empty()).await;
+        let hits = indexer.index().read().search("background", 10);
+        assert_eq!(hits[0].path, project.join("readme.md"));
+
+        indexer.request(IndexRequest::Save);
+        let index_path = indexer.config().index_path.clone();
+        for _ in 0..500 {
+            if !ContentIndex::load(&index_path).is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(ContentIndex::load(&index_path).search("desktop", 10).len(), 1);
+
+        indexer.request(IndexRequest::Remove(project));
+        wait_for(&indexer, ContentIndex::is_empty).await;
+        indexer.stop();
+    }
+
+    #[test]
+    fn test_exclusions_skip_invalid_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        let indexer = new_indexer(dir.path(), vec!["(".to_string(), r".*/Private/.*".to_string()]);
+
+        assert!(indexer.is_excluded(Path::new("/home/user/Private/diary.txt")));
+        assert!(!indexer.is_excluded(Path::new("/home/user/notes.txt")));
+    }
+
+    #[test]
+    fn test_crawl_skips_hidden_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".git")).unwrap();
+        std::fs::create_dir_all(dir.path().join("docs")).unwrap();
+        std::fs::write(dir.path().join(".git").join("config"), "").unwrap();
+        std::fs::write(dir.path().join("docs").join("guide.md"), "").unwrap();
+        std::fs::write(dir.path().join(".profile"), "").unwrap();
+
+        let mut files = collect_files(dir.path());
+        files.sort();
+        // Hidden files are still read; only hidden directories are skipped
+        assert_eq!(files, [dir.path().join(".profile"), dir.path().join("docs").join("guide.md")]);
+    }
+}
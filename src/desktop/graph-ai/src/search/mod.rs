@@ -0,0 +1,299 @@
+//! Search over graph nodes
+//!
+//! Nodes are matched by name. With content indexing enabled, file nodes
+//! also match on the text inside the file: a [`ContentIndexer`] crawls the
+//! configured roots in the background, keeps an on-disk inverted index up to
+//! date from file watcher events, and results carry a snippet around the
+//! matched text. Paths covered by the privacy exclusions are never read.
+
+pub mod content;
+pub mod indexer;
+
+pub use content::{ContentHit, ContentIndex};
+pub use indexer::{ContentIndexer, IndexRequest, IndexerStats};
+
+use crate::monitoring::privacy_filter::PrivacyFilterConfig;
+use horizonos_graph_engine::{NodeType, Scene, SceneId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Content indexing settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentIndexConfig {
+    /// Index file contents; only names are searched when disabled
+    pub enabled: bool,
+    /// Directories crawled and watched
+    pub roots: Vec<PathBuf>,
+    /// Where the index is stored
+    pub index_path: PathBuf,
+    /// Larger files are not indexed
+    pub max_file_size: u64,
+    /// Paths never read (regex patterns), on top of hidden directories
+    pub excluded_paths: Vec<String>,
+    /// Seconds between saves of a changed index
+    pub save_interval_secs: u64,
+    /// Length of result snippets in characters
+    pub snippet_length: usize,
+}
+
+impl Default for ContentIndexConfig {
+    fn default() -> Self {
+        let data_dir = dirs::data_local_dir().unwrap_or_else(std::env::temp_dir);
+        Self {
+            enabled: false,
+            roots: dirs::home_dir().into_iter().collect(),
+            index_path: data_dir.join("horizonos").join("search").join("content.idx"),
+            max_file_size: 4 * 1024 * 1024,
+            excluded_paths: PrivacyFilterConfig::default().excluded_paths,
+            save_interval_secs: 60,
+            snippet_length: 160,
+        }
+    }
+}
+
+impl ContentIndexConfig {
+    /// Use the path exclusions of the privacy filter
+    pub fn with_privacy(mut self, privacy: &PrivacyFilterConfig) -> Self {
+        self.excluded_paths = privacy.excluded_paths.clone();
+        self
+    }
+}
+
+/// What a result matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMatch {
+    /// The node name
+    Name,
+    /// The file contents
+    Content,
+    /// Both name and contents
+    Both,
+}
+
+/// A node matching a query
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    /// Matching node
+    pub node_id: SceneId,
+    /// Node name
+    pub name: String,
+    /// File path for file nodes
+    pub path: Option<PathBuf>,
+    /// What matched
+    pub matched: SearchMatch,
+    /// Relevance score
+    pub score: f32,
+    /// Text around the content match
+    pub snippet: Option<String>,
+}
+
+/// Searches the nodes of a scene
+#[derive(Default)]
+pub struct SearchEngine {
+    content: Option<Arc<ContentIndexer>>,
+}
+
+impl SearchEngine {
+    /// Create a search engine matching node names only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also match file contents from `indexer`
+    pub fn with_content_index(indexer: Arc<ContentIndexer>) -> Self {
+        Self { content: Some(indexer) }
+    }
+
+    /// The content indexer, if content search is enabled
+    pub fn content_indexer(&self) -> Option<&Arc<ContentIndexer>> {
+        self.content.as_ref()
+    }
+
+    /// Nodes matching `query`, best first
+    pub fn search(&self, scene: &Scene, query: &str, limit: usize) -> Vec<SearchResult> {
+        let needle = query.trim().to_lowercase();
+        if needle.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+
+        let mut results: HashMap<SceneId, SearchResult> = HashMap::new();
+        let mut files: HashMap<PathBuf, Vec<SceneId>> = HashMap::new();
+        for (id, node) in scene.nodes() {
+            let (name, path) = node_name(&node.node_type);
+            if let Some(path) = &path {
+                files.entry(path.clone()).or_default().push(*id);
+            }
+            let lower = name.to_lowercase();
+            let score = if lower == needle {
+                3.0
+            } else if lower.starts_with(&needle) {
+                2.0
+            } else if lower.contains(&needle) {
+                1.0
+            } else {
+                continue;
+            };
+            results.insert(*id, SearchResult { node_id: *id, name, path, matched: SearchMatch::Name, score, snippet: None });
+        }
+
+        if let Some(indexer) = &self.content {
+            let hits = indexer.index().read().search(query, limit.saturating_mul(4));
+            for hit in hits {
+                let Some(node_ids) = files.get(&hit.path) else {
+                    continue;
+                };
+                for node_id in node_ids {
+                    let result = results.entry(*node_id).or_insert_with(|| {
+                        let name = hit.path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                        SearchResult {
+                            node_id: *node_id,
+                            name,
+                            path: Some(hit.path.clone()),
+                            matched: SearchMatch::Content,
+                            score: 0.0,
+                            snippet: None,
+                        }
+                    });
+                    if result.matched == SearchMatch::Name {
+                        result.matched = SearchMatch::Both;
+                    }
+                    // Content scores are unbounded; squash them below an exact name match
+                    result.score += hit.score / (1.0 + hit.score);
+                }
+            }
+        }
+
+        let mut results: Vec<SearchResult> = results.into_values().collect();
+        results.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+        results.truncate(limit);
+
+        // Snippets are only read for the results that are shown
+        if let Some(indexer) = &self.content {
+            let terms = content::query_terms(query);
+            let config = indexer.config();
+            for result in results.iter_mut().filter(|r| r.matched != SearchMatch::Name) {
+                let Some(path) = &result.path else { continue };
+                if indexer.is_excluded(path) {
+                    continue;
+                }
+                result.snippet = content::extract_text(path, config.max_file_size)
+                    .ok()
+                    .flatten()
+                    .and_then(|text| content::snippet(&text, &terms, config.snippet_length));
+            }
+        }
+        results
+    }
+}
+
+/// Name shown for a node, and its path for file nodes
+pub(crate) fn node_name(node_type: &NodeType) -> (String, Option<PathBuf>) {
+    match node_type {
+        NodeType::File { path, .. } => {
+            let path = PathBuf::from(path);
+            let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            (name, Some(path))
+        }
+        NodeType::URL { url, title, .. } => (title.clone().unwrap_or_else(|| url.clone()), None),
+        NodeType::Application { name, .. }
+        | NodeType::Device { name, .. }
+        | NodeType::AIAgent { name, .. }
+        | NodeType::Automation { name, .. }
+        | NodeType::ConfigGroup { name, .. }
+        | NodeType::Group { name, .. }
+        | NodeType::Person { name, .. } => (name.clone(), None),
+        NodeType::Task { title, .. } | NodeType::Concept { title, .. } => (title.clone(), None),
+        NodeType::System { component, .. } => (component.clone(), None),
+        NodeType::Setting { key, .. } => (key.clone(), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::{FileType, NodeMetadata, Position, SceneNode, Vec3};
+
+    fn file_node(scene: &mut Scene, path: &std::path::Path) -> SceneId {
+        scene.add_node(SceneNode {
+            id: 0,
+            position: Position::new(0.0, 0.0, 0.0),
+            velocity: Vec3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type: NodeType::File { path: path.to_string_lossy().into_owned(), file_type: FileType::Document },
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        })
+    }
+
+    fn new_indexer(dir: &std::path::Path) -> Arc<ContentIndexer> {
+        Arc::new(ContentIndexer::new(ContentIndexConfig {
+            enabled: true,
+            roots: vec![dir.join("home")],
+            index_path: dir.join("index").join("content.idx"),
+            ..ContentIndexConfig::default()
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_content_matches_with_snippets() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("home");
+        std::fs::create_dir_all(home.join(".ssh")).unwrap();
+        let plan = home.join("plan.md");
+        std::fs::write(&plan, "# Plan\n\nMigrate the compositor to the new renderer next week.").unwrap();
+        let key = home.join(".ssh").join("notes.txt");
+        std::fs::write(&key, "compositor secrets").unwrap();
+        let private = home.join("Private").join("diary.txt");
+        std::fs::create_dir_all(private.parent().unwrap()).unwrap();
+        std::fs::write(&private, "compositor diary").unwrap();
+
+        let indexer = new_indexer(dir.path());
+        assert_eq!(indexer.crawl(&home).await.unwrap(), 1);
+        assert!(!indexer.update(&private).await.unwrap());
+        assert_eq!(indexer.stats().files_excluded, 2);
+
+        let mut scene = Scene::new();
+        let plan_node = file_node(&mut scene, &plan);
+        file_node(&mut scene, &private);
+        let engine = SearchEngine::with_content_index(indexer.clone());
+
+        let results = engine.search(&scene, "compositor", 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].node_id, plan_node);
+        assert_eq!(results[0].matched, SearchMatch::Content);
+        assert!(results[0].snippet.as_ref().unwrap().contains("compositor"));
+
+        // Name matches still work and rank above content-only matches
+        assert_eq!(engine.search(&scene, "plan", 10)[0].matched, SearchMatch::Both);
+    }
+
+    #[tokio::test]
+    async fn test_index_follows_changes_and_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("home");
+        std::fs::create_dir_all(&home).unwrap();
+        let notes = home.join("notes.txt");
+        std::fs::write(&notes, "first draft").unwrap();
+
+        let indexer = new_indexer(dir.path());
+        indexer.crawl(&home).await.unwrap();
+        assert_eq!(indexer.index().read().search("draft", 10).len(), 1);
+
+        std::fs::write(&notes, "final version, much longer than before").unwrap();
+        indexer.update(&notes).await.unwrap();
+        assert!(indexer.index().read().search("draft", 10).is_empty());
+        assert_eq!(indexer.index().read().search("final", 10).len(), 1);
+        indexer.stop();
+
+        // A fresh indexer picks up the saved index and drops files removed meanwhile
+        std::fs::remove_file(&notes).unwrap();
+        let reopened = new_indexer(dir.path());
+        assert_eq!(reopened.stats().indexed_files, 1);
+        reopened.crawl(&home).await.unwrap();
+        assert_eq!(reopened.stats().indexed_files, 0);
+    }
+}
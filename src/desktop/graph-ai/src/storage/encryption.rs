@@ -0,0 +1,138 @@
+//! Transparent encryption of stored behavioral data
+//!
+//! Sealed values are tagged so rows written before encryption was enabled
+//! still read back as plaintext, and can be re-encrypted in place.
+
+use crate::privacy::encryption::EncryptionManager;
+use crate::AIError;
+use base64::{engine::general_purpose, Engine as _};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Prefix of sealed text values
+const SEALED_TEXT_PREFIX: &str = "enc:v1:";
+/// Key of the object wrapping a sealed JSON value
+const SEALED_JSON_KEY: &str = "$encrypted";
+
+/// Seals values before they are written and opens them when read
+#[derive(Clone, Default)]
+pub struct StorageEncryption {
+    manager: Option<Arc<EncryptionManager>>,
+}
+
+impl StorageEncryption {
+    /// Store values as they are
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Seal values with the given encryption manager
+    pub fn new(manager: Arc<EncryptionManager>) -> Self {
+        Self { manager: Some(manager) }
+    }
+
+    /// Whether new values are sealed
+    pub fn is_enabled(&self) -> bool {
+        self.manager.as_ref().is_some_and(|manager| manager.encrypts_at_rest())
+    }
+
+    /// Whether a text value is sealed
+    pub fn is_sealed_text(value: &str) -> bool {
+        value.starts_with(SEALED_TEXT_PREFIX)
+    }
+
+    /// Whether a JSON value is sealed
+    pub fn is_sealed_json(value: &Value) -> bool {
+        value.get(SEALED_JSON_KEY).is_some_and(Value::is_string)
+    }
+
+    /// Seal a text value
+    pub async fn seal_text(&self, value: &str) -> Result<String, AIError> {
+        match self.active_manager() {
+            Some(manager) if !Self::is_sealed_text(value) => {
+                let sealed = manager.encrypt(value.as_bytes()).await?;
+                Ok(format!("{}{}", SEALED_TEXT_PREFIX, general_purpose::STANDARD.encode(sealed)))
+            }
+            _ => Ok(value.to_string()),
+        }
+    }
+
+    /// Open a text value, passing plaintext through
+    pub async fn open_text(&self, value: &str) -> Result<String, AIError> {
+        let Some(encoded) = value.strip_prefix(SEALED_TEXT_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let plaintext = self.open_encoded(encoded).await?;
+        String::from_utf8(plaintext).map_err(|e| AIError::Encryption(format!("Decrypted text is not UTF-8: {}", e)))
+    }
+
+    /// Seal a JSON value
+    pub async fn seal_json(&self, value: &Value) -> Result<Value, AIError> {
+        match self.active_manager() {
+            Some(manager) if !Self::is_sealed_json(value) => {
+                let plaintext = serde_json::to_vec(value).map_err(AIError::Serialization)?;
+                let sealed = manager.encrypt(&plaintext).await?;
+                Ok(serde_json::json!({ SEALED_JSON_KEY: general_purpose::STANDARD.encode(sealed) }))
+            }
+            _ => Ok(value.clone()),
+        }
+    }
+
+    /// Open a JSON value, passing plaintext through
+    pub async fn open_json(&self, value: Value) -> Result<Value, AIError> {
+        let Some(encoded) = value.get(SEALED_JSON_KEY).and_then(Value::as_str) else {
+            return Ok(value);
+        };
+        let plaintext = self.open_encoded(encoded).await?;
+        serde_json::from_slice(&plaintext).map_err(AIError::Serialization)
+    }
+
+    /// Manager to seal new values with, if sealing is on
+    fn active_manager(&self) -> Option<&Arc<EncryptionManager>> {
+        self.manager.as_ref().filter(|manager| manager.encrypts_at_rest())
+    }
+
+    async fn open_encoded(&self, encoded: &str) -> Result<Vec<u8>, AIError> {
+        // Sealed data stays readable after encryption is switched off, as long as the keys are around
+        let manager = self.manager.as_ref()
+            .ok_or_else(|| AIError::Encryption("Stored data is encrypted but no encryption manager is configured".to_string()))?;
+        let sealed = general_purpose::STANDARD.decode(encoded)
+            .map_err(|e| AIError::Encryption(format!("Invalid encrypted value: {}", e)))?;
+        manager.decrypt(&sealed).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::{EncryptionConfig, KeyManagement};
+
+    #[tokio::test]
+    async fn test_seal_and_open_with_plaintext_fallback() {
+        let config = EncryptionConfig {
+            key_management: KeyManagement::MemoryOnly,
+            ..EncryptionConfig::default()
+        };
+        let encryption = StorageEncryption::new(Arc::new(EncryptionManager::new(config).await.unwrap()));
+        assert!(encryption.is_enabled());
+
+        let sealed = encryption.seal_text("/home/user/diary.md").await.unwrap();
+        assert!(StorageEncryption::is_sealed_text(&sealed));
+        assert!(!sealed.contains("diary"));
+        assert_eq!(encryption.open_text(&sealed).await.unwrap(), "/home/user/diary.md");
+        // Sealing twice leaves the value alone
+        assert_eq!(encryption.seal_text(&sealed).await.unwrap(), sealed);
+
+        let context = serde_json::json!({ "window": "Diary" });
+        let sealed = encryption.seal_json(&context).await.unwrap();
+        assert!(StorageEncryption::is_sealed_json(&sealed));
+        assert_eq!(encryption.open_json(sealed).await.unwrap(), context);
+
+        // Rows written before encryption was enabled read back unchanged
+        assert_eq!(encryption.open_text("firefox").await.unwrap(), "firefox");
+        assert_eq!(encryption.open_json(context.clone()).await.unwrap(), context);
+
+        let disabled = StorageEncryption::disabled();
+        assert_eq!(disabled.seal_text("firefox").await.unwrap(), "firefox");
+    }
+}
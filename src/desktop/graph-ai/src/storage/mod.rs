@@ -9,8 +9,10 @@
 pub mod timescale;
 pub mod cache;
 pub mod config;
+pub mod encryption;
 
 use crate::AIError;
+use crate::resilience::SubsystemStatus;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
@@ -286,6 +288,21 @@ impl StorageManager {
         Ok(health_checks)
     }
     
+    /// Encrypt behavioral data from now on
+    pub fn set_encryption(&self, encryption: encryption::StorageEncryption) {
+        self.timescale.set_encryption(encryption);
+    }
+    
+    /// Encrypt behavioral data stored before encryption was enabled
+    pub async fn migrate_plaintext(&self) -> Result<u64, AIError> {
+        self.timescale.migrate_plaintext().await
+    }
+    
+    /// Health of the backends that can degrade, for status reporting
+    pub fn subsystem_status(&self) -> Vec<SubsystemStatus> {
+        vec![self.timescale.status()]
+    }
+    
     /// Get combined storage statistics
     pub async fn get_combined_stats(&self) -> Result<StorageStats, AIError> {
         let timescale_stats = self.timescale.get_stats().await?;
@@ -3,29 +3,46 @@
 //! This module provides time-series storage for behavioral data,
 //! patterns, and AI-related metrics using TimescaleDB.
 
+use super::encryption::StorageEncryption;
 use super::{
     Storage, StorageConfig, StorageHealth, StorageStats, RetentionPolicy,
     UserAction, Pattern, PatternData, PatternType, ActionType,
 };
 use crate::AIError;
+use crate::resilience::{HealthTracker, RetryPolicy, SubsystemStatus};
+#[cfg(feature = "chaos")]
+use crate::chaos::{FailureInjector, FaultPoint};
 
 use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
 use dashmap::DashMap;
-use log::{debug, error, info};
-use parking_lot::RwLock;
+use log::{debug, error, info, warn};
+use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
 use sqlx::{postgres::PgPoolOptions, PgPool, Row};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// Actions kept in memory while the database is unavailable
+const MAX_PENDING_ACTIONS: usize = 10_000;
+/// Rows re-encrypted per query while migrating plaintext data
+const MIGRATION_BATCH_SIZE: i64 = 500;
+
 /// TimescaleDB client for storing time-series AI data
 pub struct TimescaleClient {
     pool: PgPool,
     config: StorageConfig,
     cache: Arc<DashMap<String, CachedQuery>>,
     metrics: Arc<RwLock<PerformanceMetrics>>,
+    retry_policy: RetryPolicy,
+    health: HealthTracker,
+    /// Actions that could not be written yet, oldest first
+    pending: Mutex<VecDeque<UserAction>>,
+    /// Encryption of action targets, contexts and pattern data
+    encryption: RwLock<StorageEncryption>,
+    #[cfg(feature = "chaos")]
+    faults: Option<Arc<FailureInjector>>,
 }
 
 /// Cached query result
@@ -56,14 +73,67 @@ impl TimescaleClient {
             .connect_lazy("postgresql://localhost/horizonos")
             .expect("Failed to create lazy connection pool");
         
+        Self::with_pool(pool, StorageConfig::default())
+    }
+
+    fn with_pool(pool: PgPool, config: StorageConfig) -> Self {
         Self {
             pool,
-            config: StorageConfig::default(),
+            config,
             cache: Arc::new(DashMap::new()),
             metrics: Arc::new(RwLock::new(PerformanceMetrics::default())),
+            retry_policy: RetryPolicy::default(),
+            health: HealthTracker::new("TimescaleDB"),
+            pending: Mutex::new(VecDeque::new()),
+            encryption: RwLock::new(StorageEncryption::disabled()),
+            #[cfg(feature = "chaos")]
+            faults: None,
         }
     }
 
+    /// Use a different retry schedule
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Use a health tracker with different circuit settings
+    pub fn with_health_tracker(mut self, health: HealthTracker) -> Self {
+        self.health = health;
+        self
+    }
+
+    /// Inject faults into queries
+    #[cfg(feature = "chaos")]
+    pub fn with_failure_injector(mut self, faults: Arc<FailureInjector>) -> Self {
+        self.faults = Some(faults);
+        self
+    }
+
+    /// Encrypt behavioral data written from now on
+    pub fn set_encryption(&self, encryption: StorageEncryption) {
+        *self.encryption.write() = encryption;
+    }
+
+    /// Database health, degraded while queries are failing
+    pub fn status(&self) -> SubsystemStatus {
+        self.health.status()
+    }
+
+    /// Number of actions waiting to be written
+    pub fn pending_actions(&self) -> usize {
+        self.pending.lock().len()
+    }
+
+    /// Run injected faults for the next query
+    async fn inject_faults(&self) -> Result<(), AIError> {
+        #[cfg(feature = "chaos")]
+        if let Some(faults) = &self.faults {
+            faults.intercept(FaultPoint::StorageQuery).await?;
+        }
+        Ok(())
+    }
+
     /// Create a new TimescaleDB client
     pub async fn new(config: StorageConfig) -> Result<Self, AIError> {
         let pool = PgPoolOptions::new()
@@ -73,12 +143,7 @@ impl TimescaleClient {
             .await
             .map_err(|e| AIError::Configuration(format!("Failed to connect to TimescaleDB: {}", e)))?;
 
-        let client = Self {
-            pool,
-            config,
-            cache: Arc::new(DashMap::new()),
-            metrics: Arc::new(RwLock::new(PerformanceMetrics::default())),
-        };
+        let client = Self::with_pool(pool, config);
 
         // Initialize schema if needed
         client.initialize_schema().await?;
@@ -94,7 +159,57 @@ impl TimescaleClient {
     }
 
     /// Store a user action
+    ///
+    /// While the database is unavailable the action is kept in memory and
+    /// written once a later store succeeds.
     pub async fn store_action(&self, action: &UserAction) -> Result<(), AIError> {
+        match self.retry_policy.run(&self.health, || self.insert_action(action)).await {
+            Ok(()) => {
+                if self.pending_actions() > 0 {
+                    if let Err(e) = self.flush_pending().await {
+                        warn!("Failed to write buffered user actions: {}", e);
+                    }
+                }
+                Ok(())
+            }
+            Err(e) if e.is_transient() => {
+                let mut pending = self.pending.lock();
+                if pending.len() >= MAX_PENDING_ACTIONS {
+                    pending.pop_front();
+                }
+                pending.push_back(action.clone());
+                warn!("Database unavailable, buffered user action ({} pending): {}", pending.len(), e);
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write actions buffered while the database was unavailable
+    pub async fn flush_pending(&self) -> Result<u64, AIError> {
+        let mut written = 0;
+        loop {
+            let Some(action) = self.pending.lock().pop_front() else {
+                return Ok(written);
+            };
+            if let Err(e) = self.retry_policy.run(&self.health, || self.insert_action(&action)).await {
+                self.pending.lock().push_front(action);
+                return Err(e);
+            }
+            written += 1;
+        }
+    }
+
+    /// Insert a user action in a single attempt
+    async fn insert_action(&self, action: &UserAction) -> Result<(), AIError> {
+        self.inject_faults().await?;
+        let encryption = self.encryption.read().clone();
+        let target = encryption.seal_text(&action.target).await?;
+        let context = encryption.seal_json(&action.context).await?;
+        let error_message = match &action.error_message {
+            Some(message) => Some(encryption.seal_text(message).await?),
+            None => None,
+        };
         let start_time = std::time::Instant::now();
         
         let query = r#"
@@ -108,12 +223,12 @@ impl TimescaleClient {
             .bind(action.timestamp)
             .bind(&action.user_id)
             .bind(format!("{:?}", action.action_type))
-            .bind(&action.target)
+            .bind(&target)
             .bind(&action.application)
-            .bind(&action.context)
+            .bind(&context)
             .bind(action.duration_ms.map(|d| d as i32))
             .bind(action.success)
-            .bind(&action.error_message)
+            .bind(&error_message)
             .execute(&self.pool)
             .await;
         
@@ -127,7 +242,7 @@ impl TimescaleClient {
             }
             Err(e) => {
                 error!("Failed to store user action: {}", e);
-                Err(AIError::Configuration(format!("Database error: {}", e)))
+                Err(database_error("Database error", e))
             }
         }
     }
@@ -190,7 +305,9 @@ impl TimescaleClient {
         
         match result {
             Ok(rows) => {
-                let actions: Result<Vec<UserAction>, _> = rows.into_iter().map(|row| {
+                let encryption = self.encryption.read().clone();
+                let mut actions = Vec::with_capacity(rows.len());
+                for row in rows {
                     let action_type_str: String = row.get("action_type");
                     let action_type = match action_type_str.as_str() {
                         "AppLaunch" => ActionType::AppLaunch,
@@ -205,21 +322,26 @@ impl TimescaleClient {
                         _ => ActionType::AppLaunch, // Default fallback
                     };
                     
-                    Ok(UserAction {
+                    let error_message = match row.get::<Option<String>, _>("error_message") {
+                        Some(message) => Some(encryption.open_text(&message).await?),
+                        None => None,
+                    };
+                    
+                    actions.push(UserAction {
                         id: Uuid::new_v4(),
                         user_id: row.get("user_id"),
                         action_type,
-                        target: row.get("target"),
+                        target: encryption.open_text(row.get("target")).await?,
                         application: row.get("application"),
                         timestamp: row.get("time"),
-                        context: row.get("context"),
+                        context: encryption.open_json(row.get("context")).await?,
                         duration_ms: row.get::<Option<i32>, _>("duration_ms").map(|d| d as u64),
                         success: row.get("success"),
-                        error_message: row.get("error_message"),
-                    })
-                }).collect();
+                        error_message,
+                    });
+                }
                 
-                actions.map_err(|e: AIError| e)
+                Ok(actions)
             }
             Err(e) => {
                 error!("Failed to get user actions: {}", e);
@@ -247,6 +369,8 @@ impl TimescaleClient {
         
         let pattern_data_json = serde_json::to_value(&pattern.data)
             .map_err(AIError::Serialization)?;
+        let encryption = self.encryption.read().clone();
+        let pattern_data_json = encryption.seal_json(&pattern_data_json).await?;
         
         let result = sqlx::query(query)
             .bind(&pattern.id)
@@ -328,7 +452,9 @@ impl TimescaleClient {
         
         match result {
             Ok(rows) => {
-                let patterns: Result<Vec<Pattern>, _> = rows.into_iter().map(|row| {
+                let encryption = self.encryption.read().clone();
+                let mut patterns = Vec::with_capacity(rows.len());
+                for row in rows {
                     let pattern_type_str: String = row.get("pattern_type");
                     let pattern_type = match pattern_type_str.as_str() {
                         "Temporal" => PatternType::Temporal,
@@ -338,11 +464,11 @@ impl TimescaleClient {
                         _ => PatternType::Usage, // Default fallback
                     };
                     
-                    let pattern_data_json: serde_json::Value = row.get("pattern_data");
+                    let pattern_data_json = encryption.open_json(row.get("pattern_data")).await?;
                     let pattern_data: PatternData = serde_json::from_value(pattern_data_json)
                         .map_err(AIError::Serialization)?;
                     
-                    Ok(Pattern {
+                    patterns.push(Pattern {
                         id: row.get("id"),
                         pattern_type,
                         data: pattern_data,
@@ -351,10 +477,10 @@ impl TimescaleClient {
                         last_seen: row.get("last_seen"),
                         occurrence_count: row.get::<i32, _>("occurrence_count") as u32,
                         enabled: row.get("enabled"),
-                    })
-                }).collect();
+                    });
+                }
                 
-                patterns
+                Ok(patterns)
             }
             Err(e) => {
                 error!("Failed to get patterns: {}", e);
@@ -363,6 +489,80 @@ impl TimescaleClient {
         }
     }
     
+    /// Encrypt rows written before encryption was enabled, returning how many were rewritten
+    pub async fn migrate_plaintext(&self) -> Result<u64, AIError> {
+        let encryption = self.encryption.read().clone();
+        if !encryption.is_enabled() {
+            return Ok(0);
+        }
+        info!("Encrypting plaintext behavioral data");
+        
+        let mut migrated = 0;
+        loop {
+            // Rows are addressed by ctid since user_actions has no primary key
+            let rows = sqlx::query(r#"
+                SELECT ctid::text AS row_id, target, context, error_message
+                FROM user_actions
+                WHERE target NOT LIKE 'enc:v1:%' OR NOT (context ? '$encrypted')
+                LIMIT $1
+            "#)
+                .bind(MIGRATION_BATCH_SIZE)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| database_error("Failed to read plaintext user actions", e))?;
+            if rows.is_empty() {
+                break;
+            }
+            
+            for row in rows {
+                let row_id: String = row.get("row_id");
+                let error_message = match row.get::<Option<String>, _>("error_message") {
+                    Some(message) => Some(encryption.seal_text(&message).await?),
+                    None => None,
+                };
+                sqlx::query("UPDATE user_actions SET target = $1, context = $2, error_message = $3 WHERE ctid = $4::tid")
+                    .bind(encryption.seal_text(row.get("target")).await?)
+                    .bind(encryption.seal_json(&row.get("context")).await?)
+                    .bind(error_message)
+                    .bind(row_id)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(|e| database_error("Failed to encrypt user action", e))?;
+                migrated += 1;
+            }
+        }
+        
+        loop {
+            let rows = sqlx::query(r#"
+                SELECT id, pattern_data
+                FROM learned_patterns
+                WHERE NOT (pattern_data ? '$encrypted')
+                LIMIT $1
+            "#)
+                .bind(MIGRATION_BATCH_SIZE)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| database_error("Failed to read plaintext patterns", e))?;
+            if rows.is_empty() {
+                break;
+            }
+            
+            for row in rows {
+                let id: Uuid = row.get("id");
+                sqlx::query("UPDATE learned_patterns SET pattern_data = $1 WHERE id = $2")
+                    .bind(encryption.seal_json(&row.get("pattern_data")).await?)
+                    .bind(id)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(|e| database_error("Failed to encrypt pattern", e))?;
+                migrated += 1;
+            }
+        }
+        
+        info!("Encrypted {} plaintext rows", migrated);
+        Ok(migrated)
+    }
+    
     /// Update performance metrics
     fn update_metrics(&self, success: bool, duration: std::time::Duration) {
         let mut metrics = self.metrics.write();
@@ -531,6 +731,21 @@ impl Storage for TimescaleClient {
     }
 }
 
+/// Map a database error, treating lock contention and lost connections as transient
+fn database_error(context: &str, error: sqlx::Error) -> AIError {
+    let transient = match &error {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::WorkerCrashed => true,
+        // lock_not_available, deadlock_detected, serialization_failure, cannot_connect_now
+        sqlx::Error::Database(db) => matches!(db.code().as_deref(), Some("55P03" | "40P01" | "40001" | "57P03")),
+        _ => false,
+    };
+    if transient {
+        AIError::Unavailable(format!("{}: {}", context, error))
+    } else {
+        AIError::Configuration(format!("{}: {}", context, error))
+    }
+}
+
 /// Database size information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseSize {
@@ -0,0 +1,255 @@
+//! Relationship suggestions shown as node badges
+//!
+//! A suggested edge puts a badge on both of its nodes. Clicking the badge
+//! opens the node's suggestions; accepting one creates the edge in the
+//! scene, rejecting it drops the suggestion and tells the caller which pair
+//! not to propose again. Status badges from external commands take
+//! precedence over suggestion badges on the same node.
+
+use crate::privacy::node_access::node_target;
+use crate::suggestions::edges::edge_endpoints;
+use crate::suggestions::{SuggestionEngine, SuggestionType};
+use horizonos_graph_engine::{EdgeType, Scene, SceneEdge, SceneId};
+use horizonos_graph_nodes::NodeVisualData;
+use std::collections::HashMap;
+
+/// Color of edges created from accepted suggestions
+const SUGGESTED_EDGE_COLOR: [f32; 4] = [0.55, 0.75, 1.0, 0.9];
+
+/// A suggested edge waiting for the user's answer
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingEdge {
+    /// Suggestion ID
+    pub suggestion_id: String,
+    /// Node the edge starts at
+    pub source: SceneId,
+    /// Node the edge ends at
+    pub target: SceneId,
+    /// Suggestion title
+    pub title: String,
+    /// Confidence score (0.0 to 1.0)
+    pub confidence: f32,
+    /// Path, application or URL of the source node
+    pub source_target: String,
+    /// Path, application or URL of the target node
+    pub target_target: String,
+}
+
+/// Outcome of answering a suggested edge
+#[derive(Debug, Clone, PartialEq)]
+pub enum EdgeSuggestionResponse {
+    /// The edge was created
+    Accepted { edge_id: SceneId },
+    /// The suggestion was dropped; do not propose these targets again
+    Rejected { source: String, target: String },
+}
+
+/// Suggested edges per node and the badge the user has open
+#[derive(Debug, Default)]
+pub struct EdgeSuggestionBadges {
+    /// Pending edges by node, most confident first
+    pending: HashMap<SceneId, Vec<PendingEdge>>,
+    /// Node whose suggestions are shown
+    open: Option<SceneId>,
+}
+
+impl EdgeSuggestionBadges {
+    /// Create empty badges
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match the relationship suggestions of `engine` to nodes of `scene`
+    ///
+    /// Suggestions whose endpoints are not both in the scene, or that are
+    /// already connected, get no badge.
+    pub fn sync(&mut self, engine: &SuggestionEngine, scene: &Scene) {
+        let nodes: HashMap<String, SceneId> = scene
+            .nodes()
+            .filter_map(|(id, node)| node_target(&node.node_type).map(|target| (target, *id)))
+            .collect();
+        let connected = |a: SceneId, b: SceneId| {
+            scene.edges().any(|edge| (edge.source == a && edge.target == b) || (edge.source == b && edge.target == a))
+        };
+
+        let now = chrono::Utc::now();
+        self.pending.clear();
+        for suggestion in engine.get_suggestions() {
+            if !matches!(suggestion.suggestion_type, SuggestionType::Relationship)
+                || suggestion.action.action_type != "create_edge"
+                || suggestion.expires_at.is_some_and(|expires| expires <= now)
+            {
+                continue;
+            }
+            let (source_target, target_target) = edge_endpoints(&suggestion);
+            let (Some(&source), Some(&target)) = (nodes.get(&source_target), nodes.get(&target_target)) else {
+                continue;
+            };
+            if source == target || connected(source, target) {
+                continue;
+            }
+            let pending = PendingEdge {
+                suggestion_id: suggestion.id.clone(),
+                source,
+                target,
+                title: suggestion.title.clone(),
+                confidence: suggestion.confidence,
+                source_target,
+                target_target,
+            };
+            self.pending.entry(target).or_default().push(pending.clone());
+            self.pending.entry(source).or_default().push(pending);
+        }
+        for edges in self.pending.values_mut() {
+            edges.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap_or(std::cmp::Ordering::Equal));
+        }
+        if self.open.is_some_and(|node_id| !self.pending.contains_key(&node_id)) {
+            self.open = None;
+        }
+    }
+
+    /// Suggested edges of a node, most confident first
+    pub fn pending(&self, node_id: SceneId) -> &[PendingEdge] {
+        self.pending.get(&node_id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Nodes with suggested edges
+    pub fn nodes(&self) -> impl Iterator<Item = SceneId> + '_ {
+        self.pending.keys().copied()
+    }
+
+    /// Show a node's suggestion count on its badge, unless a status badge is shown
+    pub fn apply(&self, node_id: SceneId, visual: &mut NodeVisualData) {
+        let count = self.pending(node_id).len();
+        if count > 0 && visual.badge.is_none() {
+            visual.badge = Some(if count == 1 { "+1 link".to_string() } else { format!("+{} links", count) });
+        }
+    }
+
+    /// Open a node's suggestions after its badge was clicked, returning whether it has any
+    pub fn open(&mut self, node_id: SceneId) -> bool {
+        self.open = self.pending.contains_key(&node_id).then_some(node_id);
+        self.open.is_some()
+    }
+
+    /// Close the open suggestions
+    pub fn close(&mut self) {
+        self.open = None;
+    }
+
+    /// Node whose suggestions are open, with its suggestions
+    pub fn opened(&self) -> Option<(SceneId, &[PendingEdge])> {
+        self.open.map(|node_id| (node_id, self.pending(node_id)))
+    }
+
+    /// Accept or reject a suggested edge
+    ///
+    /// Returns `None` if the suggestion is no longer pending.
+    pub fn respond(
+        &mut self,
+        suggestion_id: &str,
+        accept: bool,
+        engine: &SuggestionEngine,
+        scene: &mut Scene,
+    ) -> Option<EdgeSuggestionResponse> {
+        let pending = self.pending.values().flatten().find(|edge| edge.suggestion_id == suggestion_id)?.clone();
+        for edges in self.pending.values_mut() {
+            edges.retain(|edge| edge.suggestion_id != suggestion_id);
+        }
+        self.pending.retain(|_, edges| !edges.is_empty());
+        if self.open.is_some_and(|node_id| !self.pending.contains_key(&node_id)) {
+            self.open = None;
+        }
+
+        if !accept {
+            engine.reject(suggestion_id);
+            return Some(EdgeSuggestionResponse::Rejected {
+                source: pending.source_target,
+                target: pending.target_target,
+            });
+        }
+
+        let suggestion = engine.accept(suggestion_id)?;
+        let similarity = suggestion
+            .action
+            .parameters
+            .get("similarity")
+            .and_then(|value| value.as_f64())
+            .map(|similarity| similarity as f32)
+            .unwrap_or(suggestion.confidence);
+        let edge_id = scene.add_edge(SceneEdge {
+            id: 0,
+            source: pending.source,
+            target: pending.target,
+            edge_type: EdgeType::RelatedTo { similarity },
+            weight: similarity,
+            color: SUGGESTED_EDGE_COLOR,
+            visible: true,
+            animated: false,
+        });
+        log::info!("Created suggested edge {} between nodes {} and {}", edge_id, pending.source, pending.target);
+        Some(EdgeSuggestionResponse::Accepted { edge_id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitoring::focus_cooccurrence::{FocusCoOccurrenceConfig, FocusCoOccurrenceTracker};
+    use chrono::{Duration as ChronoDuration, Utc};
+    use horizonos_graph_engine::{FileType, NodeMetadata, NodeType, Position, SceneNode, Vec3};
+
+    fn node(node_type: NodeType) -> SceneNode {
+        SceneNode {
+            id: 0,
+            position: Position::new(0.0, 0.0, 0.0),
+            velocity: Vec3::zeros(),
+            node_type,
+            radius: 1.0,
+            color: [1.0; 4],
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        }
+    }
+
+    #[test]
+    fn test_co_focus_becomes_accepted_edge() {
+        let mut tracker = FocusCoOccurrenceTracker::new(FocusCoOccurrenceConfig {
+            min_occurrences: 3,
+            ..Default::default()
+        });
+        let start = Utc::now();
+        for i in 0..3 {
+            let at = start + ChronoDuration::hours(i);
+            tracker.record_focus("gimp", at);
+            tracker.record_focus("/home/user/logo.xcf", at + ChronoDuration::seconds(5));
+        }
+        let engine = SuggestionEngine::new();
+        let proposals = tracker.take_proposals();
+        assert_eq!(proposals.len(), 1);
+        assert!(engine.generate_suggestion(proposals[0].to_suggestion(ChronoDuration::hours(1))));
+
+        let mut scene = Scene::new();
+        let app = scene.add_node(node(NodeType::Application { pid: 1, name: "gimp".to_string() }));
+        let file = scene.add_node(node(NodeType::File {
+            path: "/home/user/logo.xcf".to_string(),
+            file_type: FileType::Image,
+        }));
+
+        let mut badges = EdgeSuggestionBadges::new();
+        badges.sync(&engine, &scene);
+        let mut visual = NodeVisualData::default();
+        badges.apply(file, &mut visual);
+        assert_eq!(visual.badge.as_deref(), Some("+1 link"));
+
+        assert!(badges.open(app));
+        let suggestion_id = badges.opened().unwrap().1[0].suggestion_id.clone();
+        let response = badges.respond(&suggestion_id, true, &engine, &mut scene);
+        assert!(matches!(response, Some(EdgeSuggestionResponse::Accepted { .. })));
+        assert_eq!(scene.edges().count(), 1);
+        assert!(badges.opened().is_none());
+        assert!(engine.get_suggestions().is_empty());
+        assert_eq!(engine.acceptance_rate(), 1.0);
+    }
+}
@@ -0,0 +1,163 @@
+//! Turning co-usage patterns into relationship suggestions
+//!
+//! Co-usage patterns found by [`CoUsageDetector`](crate::patterns::co_usage::CoUsageDetector)
+//! become `create_edge` suggestions between the application and the file,
+//! in the same shape as the relationships inferred from focus co-occurrence,
+//! so both reach the user through the same node badges. A pair the user
+//! rejected is never suggested again.
+
+use crate::patterns::co_usage::CO_USAGE_KIND;
+use crate::patterns::{DetectedPattern, PatternStorage};
+use crate::storage::PatternType;
+use crate::suggestions::{Suggestion, SuggestionAction, SuggestionEngine, SuggestionPriority, SuggestionType};
+use chrono::{Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Edge suggestion configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdgeSuggestionConfig {
+    /// Pattern confidence needed before an edge is suggested
+    pub min_confidence: f32,
+    /// Hours a suggestion stays valid
+    pub suggestion_ttl_hours: u64,
+}
+
+impl Default for EdgeSuggestionConfig {
+    fn default() -> Self {
+        Self {
+            min_confidence: 0.5,
+            suggestion_ttl_hours: 72,
+        }
+    }
+}
+
+/// Publishes co-usage patterns as relationship suggestions
+#[derive(Debug, Default)]
+pub struct EdgeSuggestionPipeline {
+    /// Configuration
+    config: EdgeSuggestionConfig,
+    /// Patterns already suggested
+    suggested: HashSet<String>,
+    /// Pairs the user rejected, ordered lexically
+    rejected: HashSet<(String, String)>,
+}
+
+impl EdgeSuggestionPipeline {
+    /// Create a pipeline
+    pub fn new(config: EdgeSuggestionConfig) -> Self {
+        Self {
+            config,
+            suggested: HashSet::new(),
+            rejected: HashSet::new(),
+        }
+    }
+
+    /// Current configuration
+    pub fn config(&self) -> &EdgeSuggestionConfig {
+        &self.config
+    }
+
+    /// Replace the configuration
+    pub fn set_config(&mut self, config: EdgeSuggestionConfig) {
+        self.config = config;
+    }
+
+    /// Suggest edges for co-usage patterns not suggested before, returning how many were published
+    pub fn publish(&mut self, patterns: &PatternStorage, engine: &SuggestionEngine) -> usize {
+        let ttl = ChronoDuration::hours(self.config.suggestion_ttl_hours as i64);
+        let mut candidates = patterns.get_patterns_by_type(PatternType::Contextual);
+        candidates.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut published = 0;
+        for pattern in candidates {
+            if pattern.confidence < self.config.min_confidence || self.suggested.contains(&pattern.id) {
+                continue;
+            }
+            let Some(suggestion) = co_usage_suggestion(&pattern, ttl) else {
+                continue;
+            };
+            let (source, target) = edge_endpoints(&suggestion);
+            if self.rejected.contains(&pair_key(&source, &target)) {
+                continue;
+            }
+            self.suggested.insert(pattern.id.clone());
+            if engine.generate_suggestion(suggestion) {
+                published += 1;
+            }
+        }
+        published
+    }
+
+    /// Never suggest an edge between two targets again
+    pub fn reject(&mut self, source: &str, target: &str) {
+        self.rejected.insert(pair_key(source, target));
+    }
+}
+
+/// Suggestion to link the application and file of a co-usage pattern
+fn co_usage_suggestion(pattern: &DetectedPattern, ttl: ChronoDuration) -> Option<Suggestion> {
+    if pattern.details["kind"] != CO_USAGE_KIND {
+        return None;
+    }
+    let application = pattern.details["application"].as_str()?;
+    let file = pattern.details["file"].as_str()?;
+    let file_name = file.rsplit('/').find(|part| !part.is_empty()).unwrap_or(file);
+
+    let now = Utc::now();
+    let mut parameters = HashMap::new();
+    parameters.insert("source".to_string(), serde_json::json!(application));
+    parameters.insert("target".to_string(), serde_json::json!(file));
+    parameters.insert("edge_type".to_string(), serde_json::json!("RelatedTo"));
+    parameters.insert("similarity".to_string(), serde_json::json!(pattern.confidence));
+
+    Some(Suggestion {
+        id: format!("edge:{}", pattern.id),
+        suggestion_type: SuggestionType::Relationship,
+        title: format!("Link {} and {}", application, file_name),
+        description: format!("{} was opened with {} {} times", file_name, application, pattern.occurrences),
+        confidence: pattern.confidence,
+        priority: if pattern.confidence >= 0.8 {
+            SuggestionPriority::Medium
+        } else {
+            SuggestionPriority::Low
+        },
+        generated_at: now,
+        expires_at: Some(now + ttl),
+        action: SuggestionAction {
+            action_type: "create_edge".to_string(),
+            parameters,
+        },
+        context: serde_json::json!({
+            "inferred_from": CO_USAGE_KIND,
+            "pattern_id": pattern.id,
+            "co_occurrences": pattern.occurrences,
+        }),
+    })
+}
+
+/// Source and target named by a `create_edge` suggestion
+pub fn edge_endpoints(suggestion: &Suggestion) -> (String, String) {
+    let parameter = |key: &str| {
+        suggestion.action.parameters.get(key).and_then(|value| value.as_str()).unwrap_or_default().to_string()
+    };
+    (parameter("source"), parameter("target"))
+}
+
+/// Order-independent key of the pair a `create_edge` suggestion links
+pub fn edge_key(suggestion: &Suggestion) -> Option<(String, String)> {
+    if suggestion.action.action_type != "create_edge" {
+        return None;
+    }
+    let (source, target) = edge_endpoints(suggestion);
+    Some(pair_key(&source, &target))
+}
+
+/// Order-independent key for a pair
+pub fn pair_key(a: &str, b: &str) -> (String, String) {
+    if a <= b {
+        (a.to_string(), b.to_string())
+    } else {
+        (b.to_string(), a.to_string())
+    }
+}
@@ -3,10 +3,14 @@
 //! This module provides intelligent suggestions based on user patterns,
 //! context, and learned behaviors.
 
+pub mod edges;
+pub mod badges;
+
 use crate::AIError;
+use crate::privacy::NodeAccessPolicy;
 use crate::SuggestionConfig;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use parking_lot::RwLock;
 use chrono::{DateTime, Utc};
@@ -19,6 +23,10 @@ pub struct SuggestionEngine {
     config: Arc<RwLock<SuggestionConfig>>,
     /// Statistics
     stats: Arc<RwLock<SuggestionStats>>,
+    /// Nodes suggestions must not involve
+    node_access: NodeAccessPolicy,
+    /// Edges the user rejected, whichever producer suggested them
+    dismissed_edges: Arc<RwLock<HashSet<(String, String)>>>,
 }
 
 /// Generated suggestion
@@ -61,6 +69,8 @@ pub enum SuggestionType {
     ConfigChange,
     /// Optimization suggestion
     Optimization,
+    /// Relationship (edge) between two nodes
+    Relationship,
     /// Custom suggestion
     Custom(String),
 }
@@ -107,10 +117,17 @@ pub struct SuggestionStats {
 impl SuggestionEngine {
     /// Create a new suggestion engine
     pub fn new() -> Self {
+        Self::with_node_access(NodeAccessPolicy::new())
+    }
+    
+    /// Create a suggestion engine that drops suggestions involving AI-invisible nodes
+    pub fn with_node_access(node_access: NodeAccessPolicy) -> Self {
         Self {
             suggestions: Arc::new(RwLock::new(Vec::new())),
             config: Arc::new(RwLock::new(SuggestionConfig::default())),
             stats: Arc::new(RwLock::new(SuggestionStats::default())),
+            node_access,
+            dismissed_edges: Arc::new(RwLock::new(HashSet::new())),
         }
     }
     
@@ -120,20 +137,76 @@ impl SuggestionEngine {
         Ok(())
     }
     
-    /// Generate a suggestion
-    pub fn generate_suggestion(&self, suggestion: Suggestion) {
+    /// Generate a suggestion, unless it involves an AI-invisible node or
+    /// suggests an edge already pending or rejected
+    pub fn generate_suggestion(&self, suggestion: Suggestion) -> bool {
+        if !self.node_access.allows_suggestion(&suggestion) {
+            log::debug!("Suggestion {} involves an AI-invisible node, dropped", suggestion.id);
+            return false;
+        }
+        
+        let edge = edges::edge_key(&suggestion);
+        if edge.as_ref().is_some_and(|edge| self.dismissed_edges.read().contains(edge)) {
+            log::debug!("Suggestion {} links a rejected pair, dropped", suggestion.id);
+            return false;
+        }
+        
         let mut suggestions = self.suggestions.write();
+        if edge.is_some() && suggestions.iter().any(|pending| edges::edge_key(pending) == edge) {
+            log::debug!("Suggestion {} links an already suggested pair, dropped", suggestion.id);
+            return false;
+        }
         let mut stats = self.stats.write();
         
         suggestions.push(suggestion);
         stats.total_generated += 1;
         stats.last_suggestion = Some(Utc::now());
+        true
     }
     
     /// Get current suggestions
     pub fn get_suggestions(&self) -> Vec<Suggestion> {
         let suggestions = self.suggestions.read();
-        suggestions.clone()
+        suggestions.iter()
+            .filter(|suggestion| self.node_access.allows_suggestion(suggestion))
+            .cloned()
+            .collect()
+    }
+    
+    /// Take a suggestion the user accepted, for the caller to carry out
+    pub fn accept(&self, id: &str) -> Option<Suggestion> {
+        let suggestion = self.take(id)?;
+        let mut stats = self.stats.write();
+        stats.accepted += 1;
+        stats.update_acceptance_rate();
+        Some(suggestion)
+    }
+    
+    /// Drop a suggestion the user rejected
+    pub fn reject(&self, id: &str) -> Option<Suggestion> {
+        let suggestion = self.take(id)?;
+        let mut stats = self.stats.write();
+        stats.rejected += 1;
+        stats.update_acceptance_rate();
+        Some(suggestion)
+    }
+    
+    /// Never suggest an edge between two targets again, dropping pending ones
+    pub fn dismiss_edge(&self, source: &str, target: &str) {
+        let edge = edges::pair_key(source, target);
+        self.suggestions.write().retain(|suggestion| edges::edge_key(suggestion).as_ref() != Some(&edge));
+        self.dismissed_edges.write().insert(edge);
+    }
+    
+    /// Share of answered suggestions that were accepted
+    pub fn acceptance_rate(&self) -> f32 {
+        self.stats.read().acceptance_rate
+    }
+    
+    fn take(&self, id: &str) -> Option<Suggestion> {
+        let mut suggestions = self.suggestions.write();
+        let index = suggestions.iter().position(|suggestion| suggestion.id == id)?;
+        Some(suggestions.remove(index))
     }
     
     /// Clear suggestions
@@ -141,4 +214,13 @@ impl SuggestionEngine {
         let mut suggestions = self.suggestions.write();
         suggestions.clear();
     }
-}
\ No newline at end of file
+}
+
+impl SuggestionStats {
+    fn update_acceptance_rate(&mut self) {
+        let answered = self.accepted + self.rejected;
+        if answered > 0 {
+            self.acceptance_rate = self.accepted as f32 / answered as f32;
+        }
+    }
+}
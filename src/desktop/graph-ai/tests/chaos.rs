@@ -0,0 +1,188 @@
+//! Resilience tests driven by injected failures
+//!
+//! Run with `cargo test -p horizonos-graph-ai --features chaos`.
+
+use horizonos_graph_ai::chaos::{FailureInjector, Fault, FaultPoint};
+use horizonos_graph_ai::ollama::OllamaClient;
+use horizonos_graph_ai::resilience::{HealthTracker, RetryPolicy, SubsystemState};
+use horizonos_graph_ai::storage::timescale::TimescaleClient;
+use horizonos_graph_ai::storage::{ActionType, UserAction};
+use horizonos_graph_engine::{DeviceLossMonitor, DeviceRecovery, RecoveryEvent, RecoveryState, RecoveryStep};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Serve canned `/api/generate` responses and return the base URL
+async fn mock_ollama() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                // Read the headers, then the body they announce
+                loop {
+                    let read = socket.read(&mut buffer).await.unwrap_or(0);
+                    if read == 0 {
+                        return;
+                    }
+                    request.extend_from_slice(&buffer[..read]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text[..end]
+                            .lines()
+                            .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap_or(0)))
+                            .unwrap_or(0);
+                        if request.len() >= end + 4 + length {
+                            break;
+                        }
+                    }
+                }
+                let body = r#"{"response":"hello","done":true}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    format!("http://{}", address)
+}
+
+fn fast_retries(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(5),
+        multiplier: 2.0,
+    }
+}
+
+fn sample_action() -> UserAction {
+    UserAction {
+        id: uuid::Uuid::new_v4(),
+        user_id: "tester".to_string(),
+        action_type: ActionType::FileOpen,
+        target: "/home/tester/notes.md".to_string(),
+        application: "editor".to_string(),
+        timestamp: chrono::Utc::now(),
+        context: serde_json::Value::Null,
+        duration_ms: None,
+        success: true,
+        error_message: None,
+    }
+}
+
+#[tokio::test]
+async fn ollama_recovers_from_transient_outage() {
+    let faults = Arc::new(FailureInjector::new());
+    faults.fail_times(FaultPoint::OllamaRequest, Fault::Unavailable("connection refused".to_string()), 2);
+    let client = OllamaClient::new(&mock_ollama().await)
+        .with_retry_policy(fast_retries(3))
+        .with_failure_injector(faults.clone());
+
+    let response = client.generate("llama3.2", "hi", None).await.unwrap();
+
+    assert_eq!(response, "hello");
+    assert_eq!(faults.hits(FaultPoint::OllamaRequest), 3);
+    let status = client.status();
+    assert_eq!(status.state, SubsystemState::Healthy);
+    assert_eq!(status.total_retries, 2);
+}
+
+#[tokio::test]
+async fn ollama_down_opens_circuit_and_recovers_after_cooldown() {
+    let faults = Arc::new(FailureInjector::new());
+    faults.ollama_down();
+    let client = OllamaClient::new(&mock_ollama().await)
+        .with_retry_policy(fast_retries(2))
+        .with_health_tracker(HealthTracker::with_thresholds("Ollama", 2, Duration::from_millis(100)))
+        .with_failure_injector(faults.clone());
+
+    let error = client.generate("llama3.2", "hi", None).await.unwrap_err();
+    assert!(error.is_transient());
+    assert_eq!(client.status().state, SubsystemState::Degraded);
+
+    client.generate("llama3.2", "hi", None).await.unwrap_err();
+    assert_eq!(client.status().state, SubsystemState::Unavailable);
+    assert_eq!(faults.hits(FaultPoint::OllamaRequest), 4);
+
+    // While the circuit is open requests fail without reaching the server
+    assert!(client.generate("llama3.2", "hi", None).await.unwrap_err().is_transient());
+    assert_eq!(faults.hits(FaultPoint::OllamaRequest), 4);
+
+    faults.clear_all();
+    tokio::time::sleep(Duration::from_millis(120)).await;
+    assert_eq!(client.generate("llama3.2", "hi", None).await.unwrap(), "hello");
+    let status = client.status();
+    assert_eq!(status.state, SubsystemState::Healthy);
+    assert!(status.degraded_since.is_none());
+}
+
+#[tokio::test]
+async fn permanent_failures_are_not_retried() {
+    let faults = Arc::new(FailureInjector::new());
+    faults.fail(FaultPoint::OllamaRequest, Fault::Error("model not found".to_string()));
+    let client = OllamaClient::new(&mock_ollama().await)
+        .with_retry_policy(fast_retries(3))
+        .with_failure_injector(faults.clone());
+
+    let error = client.generate("missing", "hi", None).await.unwrap_err();
+
+    assert!(!error.is_transient());
+    assert_eq!(faults.hits(FaultPoint::OllamaRequest), 1);
+    assert_eq!(client.status().state, SubsystemState::Healthy);
+}
+
+#[tokio::test]
+async fn locked_database_buffers_actions() {
+    let faults = Arc::new(FailureInjector::new());
+    faults.database_locked();
+    let client = TimescaleClient::new_default()
+        .with_retry_policy(fast_retries(3))
+        .with_failure_injector(faults.clone());
+
+    client.store_action(&sample_action()).await.unwrap();
+    client.store_action(&sample_action()).await.unwrap();
+
+    assert_eq!(client.pending_actions(), 2);
+    assert_eq!(faults.hits(FaultPoint::StorageQuery), 6);
+    let status = client.status();
+    assert_eq!(status.state, SubsystemState::Degraded);
+    assert_eq!(status.consecutive_failures, 2);
+    assert!(status.last_error.unwrap().contains("locked"));
+
+    // Flushing while still locked keeps every buffered action
+    assert!(client.flush_pending().await.is_err());
+    assert_eq!(client.pending_actions(), 2);
+}
+
+#[test]
+fn lost_device_is_recovered_by_the_renderer() {
+    let faults = FailureInjector::new();
+    let mut recovery = DeviceRecovery::new(DeviceLossMonitor::new()).with_retries(3, Duration::ZERO);
+    let events = recovery.subscribe();
+    let now = Instant::now();
+    assert!(!faults.intercept_frame(recovery.monitor()));
+    assert_eq!(recovery.poll(now), RecoveryStep::Render);
+
+    faults.device_lost();
+    assert!(faults.intercept_frame(recovery.monitor()));
+    assert_eq!(recovery.poll(now), RecoveryStep::Recreate);
+    assert!(recovery.attempt_finished(now, Ok(())));
+
+    // The loss is injected once; later frames render again
+    assert!(!faults.intercept_frame(recovery.monitor()));
+    assert_eq!(recovery.poll(now), RecoveryStep::Render);
+    assert_eq!(recovery.state(), &RecoveryState::Healthy);
+    assert_eq!(faults.hits(FaultPoint::GpuDevice), 3);
+    let events: Vec<_> = events.try_iter().collect();
+    assert!(matches!(
+        events.as_slice(),
+        [RecoveryEvent::DeviceLost { reason }, RecoveryEvent::Recovered { attempts: 1, .. }] if reason.contains("injected")
+    ));
+}
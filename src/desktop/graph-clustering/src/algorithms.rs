@@ -168,6 +168,8 @@ impl ClusteringAlgorithms {
                     horizonos_graph_engine::NodeType::Automation { .. } => "Automations".to_string(),
                     horizonos_graph_engine::NodeType::Setting { .. } => "Settings".to_string(),
                     horizonos_graph_engine::NodeType::ConfigGroup { .. } => "Configuration".to_string(),
+                    // Collapsed clusters already are a group
+                    horizonos_graph_engine::NodeType::Group { .. } => continue,
                 };
                 
                 clusters.entry(cluster_key).or_insert_with(Vec::new).push(node_id);
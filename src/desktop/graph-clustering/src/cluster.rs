@@ -49,6 +49,10 @@ pub enum ClusterType {
     Manual,
     /// AI-suggested clustering
     AISuggested,
+    /// Project detected from a version-controlled repository
+    Project,
+    /// Placed by user-defined clustering rules
+    Rule,
 }
 
 /// How the cluster was created
@@ -273,6 +277,15 @@ impl Cluster {
 }
 
 impl ClusterStyle {
+    /// Copy of this style with the active workspace tint applied to the boundary
+    pub fn tinted(&self, tint: &horizonos_graph_visual::WorkspaceTint) -> Self {
+        Self {
+            boundary_color: tint.tint_boundary(self.boundary_color),
+            fill_color: tint.tint_boundary(self.fill_color),
+            ..self.clone()
+        }
+    }
+    
     /// Default style for a cluster type
     pub fn default_for_type(cluster_type: ClusterType) -> Self {
         match cluster_type {
@@ -324,6 +337,22 @@ impl ClusterStyle {
                 label_position: LabelPosition::Floating,
                 boundary_width: 1.5,
             },
+            ClusterType::Project => Self {
+                boundary_color: [0.95, 0.75, 0.3, 0.85],
+                fill_color: [0.95, 0.75, 0.3, 0.1],
+                boundary_style: BoundaryStyle::Solid,
+                show_label: true,
+                label_position: LabelPosition::TopLeft,
+                boundary_width: 2.0,
+            },
+            ClusterType::Rule => Self {
+                boundary_color: [0.4, 0.8, 0.9, 0.85],
+                fill_color: [0.4, 0.8, 0.9, 0.1],
+                boundary_style: BoundaryStyle::Solid,
+                show_label: true,
+                label_position: LabelPosition::TopLeft,
+                boundary_width: 2.0,
+            },
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,549 @@
+//! Collapsing clusters into group nodes
+//!
+//! Collapsing a cluster pulls its members into their centroid and replaces
+//! them with a [`GroupNode`]. Edges inside the cluster are hidden; edges to
+//! nodes outside it are hidden too and stood in for by one proxy edge per
+//! outside node, from or to the group node. Expanding reverses this, with
+//! the members springing out of wherever the group node was moved to.
+//!
+//! Member positions are kept while a cluster is collapsed, so a saved
+//! graph keeps its layout; which clusters are collapsed is saved with the
+//! workspace and re-applied with [`ClusterGrouping::restore`].
+
+use crate::{Cluster, ClusterId};
+use anyhow::{anyhow, bail, Result};
+use horizonos_graph_engine::{Position, Scene, SceneEdge, SceneId, Vec3};
+use horizonos_graph_nodes::{group_radius, GraphNode, GroupNode};
+use std::collections::{HashMap, HashSet};
+
+/// Grouping configuration
+#[derive(Debug, Clone)]
+pub struct GroupingConfig {
+    /// Length of the collapse and expand animations in seconds; 0 disables them
+    pub animation_secs: f32,
+}
+
+impl Default for GroupingConfig {
+    fn default() -> Self {
+        Self { animation_secs: 0.35 }
+    }
+}
+
+/// Where a group is in its life
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GroupPhase {
+    /// Members are moving into the group node
+    Collapsing { progress: f32 },
+    /// Members are hidden behind the group node
+    Collapsed,
+    /// Members are moving out of the group node
+    Expanding { progress: f32 },
+}
+
+/// A cluster shown as a group node
+#[derive(Debug, Clone)]
+struct CollapsedGroup {
+    group_node: SceneId,
+    phase: GroupPhase,
+    /// Member positions relative to the centroid at collapse time, and whether they were visible
+    members: HashMap<SceneId, (Vec3, bool)>,
+    /// Where the group node was when the current animation started
+    anchor: Position,
+    /// Edges hidden by the collapse and whether they were visible before
+    hidden_edges: Vec<(SceneId, bool)>,
+    /// Edges standing in for the hidden edges to outside nodes
+    proxy_edges: Vec<SceneId>,
+}
+
+/// Collapses clusters into group nodes and expands them again
+#[derive(Debug, Default)]
+pub struct ClusterGrouping {
+    config: GroupingConfig,
+    groups: HashMap<ClusterId, CollapsedGroup>,
+}
+
+impl ClusterGrouping {
+    /// Create a grouping with the given configuration
+    pub fn new(config: GroupingConfig) -> Self {
+        Self {
+            config,
+            groups: HashMap::new(),
+        }
+    }
+
+    /// Current configuration
+    pub fn config(&self) -> &GroupingConfig {
+        &self.config
+    }
+
+    /// Replace the configuration
+    pub fn set_config(&mut self, config: GroupingConfig) {
+        self.config = config;
+    }
+
+    /// Collapse a cluster into a group node, returning the group node's ID
+    pub fn collapse(&mut self, cluster: &Cluster, scene: &mut Scene) -> Result<SceneId> {
+        self.collapse_with(cluster, scene, self.config.animation_secs > 0.0, false)
+    }
+
+    /// Expand a collapsed cluster again
+    pub fn expand(&mut self, cluster_id: ClusterId, scene: &mut Scene) -> Result<()> {
+        self.expand_with(cluster_id, scene, self.config.animation_secs > 0.0)
+    }
+
+    fn expand_with(&mut self, cluster_id: ClusterId, scene: &mut Scene, animate: bool) -> Result<()> {
+        let group = self.groups.get_mut(&cluster_id)
+            .ok_or_else(|| anyhow!("Cluster {} is not collapsed", cluster_id))?;
+        match group.phase {
+            GroupPhase::Expanding { .. } => return Ok(()),
+            GroupPhase::Collapsing { .. } => finish_collapse(group, scene),
+            GroupPhase::Collapsed => {}
+        }
+
+        // Put the real edges back before the members move out
+        for edge_id in group.proxy_edges.drain(..) {
+            scene.remove_edge(edge_id);
+        }
+        for (edge_id, was_visible) in group.hidden_edges.drain(..) {
+            if let Some(edge) = scene.get_edge_mut(edge_id) {
+                edge.visible = was_visible;
+            }
+        }
+        group.anchor = scene.get_node_position(group.group_node).unwrap_or(group.anchor);
+        for (&member, &(_, was_visible)) in &group.members {
+            if let Some(node) = scene.get_node_mut(member) {
+                node.position = group.anchor;
+                node.visible = was_visible;
+            }
+        }
+        group.phase = GroupPhase::Expanding { progress: 0.0 };
+        if !animate && advance(group, 1.0, scene) {
+            self.groups.remove(&cluster_id);
+        }
+        log::debug!("Expanding cluster {}", cluster_id);
+        Ok(())
+    }
+
+    /// Collapse an expanded cluster or expand a collapsed one, returning whether it is now collapsed
+    pub fn toggle(&mut self, cluster: &Cluster, scene: &mut Scene) -> Result<bool> {
+        if self.is_collapsed(cluster.id) {
+            self.expand(cluster.id, scene)?;
+            Ok(false)
+        } else {
+            self.collapse(cluster, scene)?;
+            Ok(true)
+        }
+    }
+
+    /// Advance the collapse and expand animations
+    pub fn update(&mut self, delta_time: f32, scene: &mut Scene) {
+        let step = if self.config.animation_secs > 0.0 {
+            delta_time / self.config.animation_secs
+        } else {
+            1.0
+        };
+        self.groups.retain(|_, group| !advance(group, step, scene));
+    }
+
+    /// Whether a cluster is collapsed or collapsing
+    pub fn is_collapsed(&self, cluster_id: ClusterId) -> bool {
+        self.groups.get(&cluster_id)
+            .is_some_and(|group| !matches!(group.phase, GroupPhase::Expanding { .. }))
+    }
+
+    /// Phase of a cluster shown as a group node
+    pub fn phase(&self, cluster_id: ClusterId) -> Option<GroupPhase> {
+        self.groups.get(&cluster_id).map(|group| group.phase)
+    }
+
+    /// Whether any group is animating
+    pub fn is_animating(&self) -> bool {
+        self.groups.values().any(|group| group.phase != GroupPhase::Collapsed)
+    }
+
+    /// Group node of a cluster
+    pub fn group_node(&self, cluster_id: ClusterId) -> Option<SceneId> {
+        self.groups.get(&cluster_id).map(|group| group.group_node)
+    }
+
+    /// Cluster a group node stands for
+    pub fn cluster_of(&self, group_node: SceneId) -> Option<ClusterId> {
+        self.groups.iter()
+            .find(|(_, group)| group.group_node == group_node)
+            .map(|(cluster_id, _)| *cluster_id)
+    }
+
+    /// Proxy edges of a collapsed cluster
+    pub fn proxy_edges(&self, cluster_id: ClusterId) -> &[SceneId] {
+        self.groups.get(&cluster_id).map(|group| group.proxy_edges.as_slice()).unwrap_or_default()
+    }
+
+    /// Collapsed clusters, for saving with the workspace
+    pub fn collapsed(&self) -> Vec<ClusterId> {
+        let mut collapsed: Vec<ClusterId> = self.groups.keys()
+            .copied()
+            .filter(|cluster_id| self.is_collapsed(*cluster_id))
+            .collect();
+        collapsed.sort();
+        collapsed
+    }
+
+    /// Collapse clusters saved as collapsed, without animating, returning how many were collapsed
+    ///
+    /// The saved graph has the members and their edges hidden already, so
+    /// they are shown again on expand whatever their saved visibility.
+    pub fn restore<'a>(&mut self, clusters: impl IntoIterator<Item = &'a Cluster>, scene: &mut Scene) -> usize {
+        let mut restored = 0;
+        for cluster in clusters {
+            if self.groups.contains_key(&cluster.id) {
+                continue;
+            }
+            match self.collapse_with(cluster, scene, false, true) {
+                Ok(_) => restored += 1,
+                Err(e) => log::warn!("Could not restore collapsed cluster {}: {}", cluster.id, e),
+            }
+        }
+        restored
+    }
+
+    /// Drop the groups of clusters that no longer exist, expanding them without animating
+    pub fn retain(&mut self, mut keep: impl FnMut(ClusterId) -> bool, scene: &mut Scene) {
+        let gone: Vec<ClusterId> = self.groups.keys().copied().filter(|cluster_id| !keep(*cluster_id)).collect();
+        for cluster_id in gone {
+            if let Some(group) = self.groups.get_mut(&cluster_id) {
+                // Expansions already running are finished as well
+                group.phase = GroupPhase::Collapsed;
+            }
+            let _ = self.expand_with(cluster_id, scene, false);
+        }
+    }
+
+    fn collapse_with(&mut self, cluster: &Cluster, scene: &mut Scene, animate: bool, restoring: bool) -> Result<SceneId> {
+        if let Some(group) = self.groups.get(&cluster.id) {
+            match group.phase {
+                GroupPhase::Expanding { .. } => {
+                    // Let the running expansion finish before collapsing again
+                    let group_node = group.group_node;
+                    scene.remove_node(group_node);
+                    self.groups.remove(&cluster.id);
+                }
+                _ => bail!("Cluster {} is already collapsed", cluster.id),
+            }
+        }
+
+        // Members of another collapsed group stay in that group
+        let grouped: HashSet<SceneId> = self.groups.values()
+            .flat_map(|group| group.members.keys().copied().chain(std::iter::once(group.group_node)))
+            .collect();
+        let mut positions: Vec<(SceneId, Position, bool)> = cluster.nodes.iter()
+            .filter(|node_id| !grouped.contains(node_id))
+            .filter_map(|&node_id| scene.get_node(node_id).map(|node| (node_id, node.position, node.visible || restoring)))
+            .collect();
+        positions.sort_by_key(|(node_id, _, _)| *node_id);
+        if positions.is_empty() {
+            bail!("Cluster {} has no nodes to collapse", cluster.id);
+        }
+
+        let centroid = positions.iter().fold(Vec3::zeros(), |sum, (_, position, _)| sum + position.coords)
+            / positions.len() as f32;
+        let anchor = Position::from(centroid);
+        let members: HashMap<SceneId, (Vec3, bool)> = positions.iter()
+            .map(|&(node_id, position, visible)| (node_id, (position - anchor, visible)))
+            .collect();
+
+        let mut color = cluster.style.boundary_color;
+        color[3] = 1.0;
+        let group = GroupNode::new(0, cluster.id.to_string(), cluster.name.clone(), positions.iter().map(|(id, _, _)| *id).collect())
+            .with_position([anchor.x, anchor.y, anchor.z])
+            .with_color(color);
+        let mut node = group.to_scene_node();
+        if animate {
+            node.radius = 0.0;
+        }
+        let group_node = scene.add_node(node);
+
+        let mut group = CollapsedGroup {
+            group_node,
+            phase: GroupPhase::Collapsing { progress: 0.0 },
+            members,
+            anchor,
+            hidden_edges: Vec::new(),
+            proxy_edges: Vec::new(),
+        };
+        if restoring {
+            // Hidden edges were hidden by the collapse that was saved
+            for edge in scene.edges() {
+                if group.members.contains_key(&edge.source) || group.members.contains_key(&edge.target) {
+                    group.hidden_edges.push((edge.id, true));
+                }
+            }
+        }
+        if animate {
+            log::debug!("Collapsing cluster {} into node {}", cluster.id, group_node);
+        } else {
+            finish_collapse(&mut group, scene);
+        }
+        self.groups.insert(cluster.id, group);
+        Ok(group_node)
+    }
+}
+
+/// Move a group's animation on by `step` of its length, returning whether its expansion finished
+fn advance(group: &mut CollapsedGroup, step: f32, scene: &mut Scene) -> bool {
+    match group.phase {
+        GroupPhase::Collapsed => false,
+        GroupPhase::Collapsing { progress } => {
+            let progress = (progress + step).min(1.0);
+            if progress >= 1.0 {
+                finish_collapse(group, scene);
+                return false;
+            }
+            group.phase = GroupPhase::Collapsing { progress };
+            let t = ease(progress);
+            for (&member, &(offset, _)) in &group.members {
+                if let Some(node) = scene.get_node_mut(member) {
+                    node.position = group.anchor + offset * (1.0 - t);
+                }
+            }
+            set_radius(scene, group.group_node, group_radius(group.members.len()) * t);
+            false
+        }
+        GroupPhase::Expanding { progress } => {
+            let progress = (progress + step).min(1.0);
+            group.phase = GroupPhase::Expanding { progress };
+            let t = ease(progress);
+            for (&member, &(offset, _)) in &group.members {
+                if let Some(node) = scene.get_node_mut(member) {
+                    node.position = group.anchor + offset * t;
+                }
+            }
+            if progress < 1.0 {
+                set_radius(scene, group.group_node, group_radius(group.members.len()) * (1.0 - t));
+                return false;
+            }
+            scene.remove_node(group.group_node);
+            true
+        }
+    }
+}
+
+/// Hide the members and their edges, and add the proxy edges
+fn finish_collapse(group: &mut CollapsedGroup, scene: &mut Scene) {
+    for (&member, &(offset, _)) in &group.members {
+        if let Some(node) = scene.get_node_mut(member) {
+            node.position = group.anchor + offset;
+            node.velocity = Vec3::zeros();
+            node.visible = false;
+        }
+    }
+    set_radius(scene, group.group_node, group_radius(group.members.len()));
+
+    // Strongest edge to each outside node, keyed by outside node and direction
+    let previously_hidden: HashMap<SceneId, bool> = group.hidden_edges.drain(..).collect();
+    let mut proxies: HashMap<(SceneId, bool), SceneEdge> = HashMap::new();
+    let mut hidden = Vec::new();
+    for edge in scene.edges() {
+        let source_inside = group.members.contains_key(&edge.source);
+        let target_inside = group.members.contains_key(&edge.target);
+        if !source_inside && !target_inside {
+            continue;
+        }
+        let was_visible = previously_hidden.get(&edge.id).copied().unwrap_or(edge.visible);
+        hidden.push((edge.id, was_visible));
+        if source_inside && target_inside || !was_visible {
+            continue;
+        }
+        let (outside, outgoing) = if source_inside { (edge.target, true) } else { (edge.source, false) };
+        let proxy = proxies.entry((outside, outgoing)).or_insert_with(|| SceneEdge {
+            id: 0,
+            source: if outgoing { group.group_node } else { outside },
+            target: if outgoing { outside } else { group.group_node },
+            ..edge.clone()
+        });
+        if edge.weight > proxy.weight {
+            proxy.edge_type = edge.edge_type.clone();
+            proxy.weight = edge.weight;
+            proxy.color = edge.color;
+        }
+        proxy.animated |= edge.animated;
+    }
+    for &(edge_id, _) in &hidden {
+        if let Some(edge) = scene.get_edge_mut(edge_id) {
+            edge.visible = false;
+        }
+    }
+    group.hidden_edges = hidden;
+
+    let mut proxies: Vec<((SceneId, bool), SceneEdge)> = proxies.into_iter().collect();
+    proxies.sort_by_key(|(key, _)| *key);
+    group.proxy_edges = proxies.into_iter()
+        .map(|(_, proxy)| scene.add_edge(SceneEdge { visible: true, ..proxy }))
+        .collect();
+    group.phase = GroupPhase::Collapsed;
+}
+
+fn set_radius(scene: &mut Scene, node_id: SceneId, radius: f32) {
+    if let Some(node) = scene.get_node_mut(node_id) {
+        node.radius = radius;
+    }
+}
+
+/// Smoothstep easing
+fn ease(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClusterType;
+    use horizonos_graph_engine::{EdgeType, NodeMetadata, NodeType, SceneNode};
+
+    fn node(scene: &mut Scene, x: f32) -> SceneId {
+        scene.add_node(SceneNode {
+            id: 0,
+            position: Position::new(x, 0.0, 0.0),
+            velocity: Vec3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type: NodeType::Concept { title: String::new(), content: String::new() },
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        })
+    }
+
+    fn edge(scene: &mut Scene, source: SceneId, target: SceneId, weight: f32) -> SceneId {
+        scene.add_edge(SceneEdge {
+            id: 0,
+            source,
+            target,
+            edge_type: EdgeType::Contains,
+            weight,
+            color: [weight; 4],
+            visible: true,
+            animated: false,
+        })
+    }
+
+    fn visible_edges(scene: &Scene) -> Vec<(SceneId, SceneId, f32)> {
+        let mut edges: Vec<_> = scene.edges()
+            .filter(|edge| edge.visible)
+            .map(|edge| (edge.source, edge.target, edge.weight))
+            .collect();
+        edges.sort_by_key(|(source, target, _)| (*source, *target));
+        edges
+    }
+
+    /// Members `a` and `b` with an edge between them, and edges to outside nodes `c` and `d`
+    struct Fixture {
+        scene: Scene,
+        cluster: Cluster,
+        a: SceneId,
+        b: SceneId,
+        c: SceneId,
+        d: SceneId,
+    }
+
+    fn fixture() -> Fixture {
+        let mut scene = Scene::new();
+        let a = node(&mut scene, 0.0);
+        let b = node(&mut scene, 2.0);
+        let c = node(&mut scene, 10.0);
+        let d = node(&mut scene, -10.0);
+        edge(&mut scene, a, b, 1.0);
+        edge(&mut scene, a, c, 0.3);
+        edge(&mut scene, b, c, 0.8);
+        edge(&mut scene, d, a, 0.5);
+        let cluster = Cluster::new_auto("Pair".to_string(), vec![a, b], ClusterType::Manual);
+        Fixture { scene, cluster, a, b, c, d }
+    }
+
+    fn immediate() -> ClusterGrouping {
+        ClusterGrouping::new(GroupingConfig { animation_secs: 0.0 })
+    }
+
+    #[test]
+    fn test_collapse_replaces_outside_edges_with_one_proxy_per_neighbor() {
+        let Fixture { mut scene, cluster, a, b, c, d } = fixture();
+        let mut grouping = immediate();
+
+        let group = grouping.collapse(&cluster, &mut scene).unwrap();
+        assert!(grouping.is_collapsed(cluster.id));
+        assert_eq!(grouping.cluster_of(group), Some(cluster.id));
+        assert_eq!(scene.get_node_position(group), Some(Position::new(1.0, 0.0, 0.0)));
+        assert!(!scene.get_node(a).unwrap().visible && !scene.get_node(b).unwrap().visible);
+
+        // The strongest edge to `c` stands for both, direction kept
+        assert_eq!(grouping.proxy_edges(cluster.id).len(), 2);
+        assert_eq!(visible_edges(&scene), {
+            let mut expected = vec![(group, c, 0.8), (d, group, 0.5)];
+            expected.sort_by_key(|(source, target, _)| (*source, *target));
+            expected
+        });
+    }
+
+    #[test]
+    fn test_expand_removes_proxies_and_restores_edges() {
+        let Fixture { mut scene, cluster, a, b, .. } = fixture();
+        let hidden = edge(&mut scene, a, b, 0.1);
+        scene.get_edge_mut(hidden).unwrap().visible = false;
+        let before = visible_edges(&scene);
+        let mut grouping = immediate();
+
+        let group = grouping.collapse(&cluster, &mut scene).unwrap();
+        scene.get_node_mut(group).unwrap().position = Position::new(5.0, 0.0, 0.0);
+        grouping.expand(cluster.id, &mut scene).unwrap();
+
+        assert!(grouping.phase(cluster.id).is_none());
+        assert!(scene.get_node(group).is_none());
+        assert_eq!(visible_edges(&scene), before);
+        assert!(!scene.get_edge(hidden).unwrap().visible);
+        // Members keep their layout around where the group node was moved to
+        assert_eq!(scene.get_node_position(a), Some(Position::new(4.0, 0.0, 0.0)));
+        assert_eq!(scene.get_node_position(b), Some(Position::new(6.0, 0.0, 0.0)));
+        assert!(scene.get_node(a).unwrap().visible);
+    }
+
+    #[test]
+    fn test_collapse_animates_before_adding_proxies() {
+        let Fixture { mut scene, cluster, a, .. } = fixture();
+        let mut grouping = ClusterGrouping::new(GroupingConfig { animation_secs: 1.0 });
+
+        grouping.collapse(&cluster, &mut scene).unwrap();
+        assert!(grouping.is_animating());
+        assert!(grouping.proxy_edges(cluster.id).is_empty());
+
+        grouping.update(0.5, &mut scene);
+        assert_eq!(grouping.phase(cluster.id), Some(GroupPhase::Collapsing { progress: 0.5 }));
+        assert_eq!(scene.get_node_position(a), Some(Position::new(0.5, 0.0, 0.0)));
+
+        grouping.update(0.5, &mut scene);
+        assert_eq!(grouping.phase(cluster.id), Some(GroupPhase::Collapsed));
+        assert_eq!(grouping.proxy_edges(cluster.id).len(), 2);
+        assert!(!grouping.is_animating());
+    }
+
+    #[test]
+    fn test_restore_collapses_a_saved_graph_without_animating() {
+        let Fixture { mut scene, cluster, a, .. } = fixture();
+        let mut saved = immediate();
+        saved.collapse(&cluster, &mut scene).unwrap();
+        let group = saved.group_node(cluster.id).unwrap();
+        for proxy in saved.proxy_edges(cluster.id).to_vec() {
+            scene.remove_edge(proxy);
+        }
+        scene.remove_node(group);
+
+        let mut grouping = ClusterGrouping::new(GroupingConfig::default());
+        assert_eq!(grouping.restore([&cluster], &mut scene), 1);
+        assert_eq!(grouping.collapsed(), vec![cluster.id]);
+        assert_eq!(grouping.proxy_edges(cluster.id).len(), 2);
+
+        grouping.retain(|_| false, &mut scene);
+        assert!(grouping.collapsed().is_empty());
+        assert!(scene.get_node(a).unwrap().visible);
+        assert_eq!(visible_edges(&scene).len(), 4);
+    }
+}
@@ -6,21 +6,35 @@
 //! - Cluster boundary visualization
 //! - Multi-membership support
 //! - Smart clustering suggestions
+//! - Project detection from git repositories
+//! - Timeline playback of how a cluster evolved
+//! - User-defined rules placing nodes in clusters
+//! - Collapsing clusters into group nodes
 
 pub mod algorithms;
 pub mod cluster;
 pub mod manager;
 pub mod boundaries;
 pub mod suggestions;
+pub mod projects;
+pub mod timeline;
+pub mod rules;
+pub mod grouping;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
 
 pub use algorithms::*;
 pub use cluster::*;
 pub use manager::*;
 pub use boundaries::*;
 pub use suggestions::*;
+pub use projects::*;
+pub use timeline::*;
+pub use rules::*;
+pub use grouping::*;
 
 use anyhow::Result;
-use horizonos_graph_engine::{SceneId, Scene};
+use horizonos_graph_engine::{SceneId, Scene, SceneNode};
 use nalgebra::Point3;
 use std::collections::{HashMap, HashSet};
 
@@ -34,6 +48,12 @@ pub struct ClusteringSystem {
     boundaries: BoundaryRenderer,
     /// Suggestion engine
     suggestions: SuggestionEngine,
+    /// Repository project detector
+    projects: ProjectDetector,
+    /// Clusters collapsed into group nodes
+    grouping: ClusterGrouping,
+    /// Accent tint of the active workspace
+    workspace_tint: Option<horizonos_graph_visual::WorkspaceTint>,
 }
 
 impl ClusteringSystem {
@@ -44,6 +64,9 @@ impl ClusteringSystem {
             algorithms: ClusteringAlgorithms::new(),
             boundaries: BoundaryRenderer::new(),
             suggestions: SuggestionEngine::new(),
+            projects: ProjectDetector::new(),
+            grouping: ClusterGrouping::default(),
+            workspace_tint: None,
         })
     }
     
@@ -72,6 +95,100 @@ impl ClusteringSystem {
         &self.suggestions
     }
     
+    /// Get project detector
+    pub fn projects(&self) -> &ProjectDetector {
+        &self.projects
+    }
+    
+    /// Get mutable project detector
+    pub fn projects_mut(&mut self) -> &mut ProjectDetector {
+        &mut self.projects
+    }
+    
+    /// Get cluster grouping
+    pub fn grouping(&self) -> &ClusterGrouping {
+        &self.grouping
+    }
+    
+    /// Get mutable cluster grouping
+    pub fn grouping_mut(&mut self) -> &mut ClusterGrouping {
+        &mut self.grouping
+    }
+    
+    /// Collapse a cluster into a group node, returning the group node's ID
+    pub fn collapse_cluster(&mut self, cluster_id: ClusterId, scene: &mut Scene) -> Result<SceneId> {
+        let cluster = self.manager.get_cluster(cluster_id)
+            .ok_or_else(|| anyhow::anyhow!("Cluster not found: {}", cluster_id))?;
+        let group_node = self.grouping.collapse(&cluster, scene)?;
+        self.manager.set_cluster_expanded(cluster_id, false)?;
+        Ok(group_node)
+    }
+    
+    /// Expand a cluster collapsed into a group node
+    pub fn expand_cluster(&mut self, cluster_id: ClusterId, scene: &mut Scene) -> Result<()> {
+        self.grouping.expand(cluster_id, scene)?;
+        self.manager.set_cluster_expanded(cluster_id, true)
+    }
+    
+    /// Advance the collapse and expand animations, expanding groups of removed clusters
+    pub fn update_groups(&mut self, delta_time: f32, scene: &mut Scene) {
+        let manager = &self.manager;
+        self.grouping.retain(|cluster_id| manager.get_cluster(cluster_id).is_some(), scene);
+        self.grouping.update(delta_time, scene);
+    }
+    
+    /// IDs of the collapsed clusters, for saving with the workspace
+    pub fn collapsed_groups(&self) -> Vec<String> {
+        self.grouping.collapsed().iter().map(ClusterId::to_string).collect()
+    }
+    
+    /// Collapse the clusters a workspace saved as collapsed, returning how many were collapsed
+    pub fn restore_collapsed_groups(&mut self, group_ids: &[String], scene: &mut Scene) -> usize {
+        let clusters: Vec<Cluster> = group_ids.iter()
+            .filter_map(|id| id.parse::<ClusterId>().ok())
+            .filter_map(|cluster_id| self.manager.get_cluster(cluster_id))
+            .collect();
+        let restored = self.grouping.restore(&clusters, scene);
+        for cluster in &clusters {
+            if self.grouping.is_collapsed(cluster.id) {
+                let _ = self.manager.set_cluster_expanded(cluster.id, false);
+            }
+        }
+        restored
+    }
+    
+    /// Detect git repositories and create or refresh their project clusters
+    pub fn detect_projects(&mut self, scene: &mut Scene) -> Result<Vec<ClusterId>> {
+        self.projects.update(scene, &self.manager)
+    }
+    
+    /// Build a playback timeline for a cluster from its nodes and connecting edges
+    pub fn timeline(&self, cluster_id: ClusterId, scene: &Scene, edges: &[horizonos_graph_edges::GraphEdge]) -> Option<ClusterTimeline> {
+        let cluster = self.manager.get_cluster(cluster_id)?;
+        Some(ClusterTimeline::from_scene(&cluster, scene).with_edges(&cluster, edges))
+    }
+    
+    /// Use the clustering rules and project roots of the configuration, re-evaluating every node
+    pub fn apply_config(&mut self, config: &horizonos_graph_config::ClusteringConfig, scene: &Scene) -> RuleOutcome {
+        let home = std::env::var_os("HOME").map(std::path::PathBuf::from);
+        let mut settings = self.projects.settings().clone();
+        settings.set_scan_roots(&config.project_roots, home.as_deref());
+        self.projects.update_settings(settings);
+        
+        self.manager.set_rules(config.rules.clone());
+        self.manager.apply_rules_to_scene(scene)
+    }
+    
+    /// Place a new node in the clusters its rules name
+    pub fn on_node_added(&mut self, node: &SceneNode) -> RuleOutcome {
+        self.manager.apply_rules(node)
+    }
+    
+    /// Move a changed node between rule clusters
+    pub fn on_node_updated(&mut self, node: &SceneNode) -> RuleOutcome {
+        self.manager.apply_rules(node)
+    }
+    
     /// Automatically cluster nodes based on various criteria
     pub fn auto_cluster(&mut self, scene: &Scene) -> Result<Vec<ClusterId>> {
         let mut new_clusters = Vec::new();
@@ -133,6 +250,36 @@ impl ClusteringSystem {
         }
     }
     
+    /// Set or clear the tint of the active workspace applied to cluster styles
+    pub fn set_workspace_tint(&mut self, tint: Option<horizonos_graph_visual::WorkspaceTint>) {
+        self.workspace_tint = tint;
+    }
+    
+    /// Style a cluster is drawn with, tinted for the active workspace
+    pub fn cluster_style(&self, cluster: &Cluster) -> ClusterStyle {
+        match &self.workspace_tint {
+            Some(tint) => cluster.style.tinted(tint),
+            None => cluster.style.clone(),
+        }
+    }
+    
+    /// Outlines of the visible clusters for the minimap
+    pub fn minimap_clusters(&self) -> Vec<horizonos_graph_engine::MinimapCluster> {
+        self.manager.clusters().into_iter()
+            .filter(|cluster| cluster.visible && !cluster.is_empty())
+            .map(|cluster| {
+                let color = self.cluster_style(&cluster).boundary_color;
+                let mut members: Vec<SceneId> = cluster.nodes.into_iter().collect();
+                members.sort_unstable();
+                horizonos_graph_engine::MinimapCluster {
+                    label: cluster.name,
+                    members,
+                    color,
+                }
+            })
+            .collect()
+    }
+    
     /// Get clustering suggestions for a node
     pub fn get_suggestions(&self, node_id: SceneId, scene: &Scene) -> Vec<ClusterSuggestion> {
         self.suggestions.suggest_for_node(node_id, scene, &self.manager)
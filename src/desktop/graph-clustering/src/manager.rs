@@ -1,11 +1,59 @@
 //! Cluster management and operations
 
-use crate::{Cluster, ClusterId, ClusterType};
+use crate::{Cluster, ClusterId, ClusterType, RuleOutcome, RulesEngine, RULE_CLUSTER_PROPERTY};
 use anyhow::{Result, anyhow};
 use dashmap::DashMap;
-use horizonos_graph_engine::SceneId;
+use horizonos_graph_config::ClusterRule;
+use horizonos_graph_engine::{Scene, SceneId, SceneNode};
 use indexmap::IndexMap;
 use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+
+/// Public cluster operations, implemented by `ClusterManager` and by the
+/// recording double in `testing`
+pub trait ClusterStore: Send + Sync {
+    /// Add a new cluster
+    fn add_cluster(&self, cluster: Cluster) -> ClusterId;
+    
+    /// Remove a cluster
+    fn remove_cluster(&self, cluster_id: ClusterId) -> Result<Cluster>;
+    
+    /// Get a cluster by ID
+    fn get_cluster(&self, cluster_id: ClusterId) -> Option<Cluster>;
+    
+    /// Get all clusters
+    fn clusters(&self) -> Vec<Cluster>;
+    
+    /// Get clusters containing a specific node
+    fn get_node_clusters(&self, node_id: SceneId) -> Vec<ClusterId>;
+    
+    /// Add a node to an existing cluster
+    fn add_node_to_cluster(&self, cluster_id: ClusterId, node_id: SceneId) -> Result<()>;
+    
+    /// Remove a node from a cluster
+    fn remove_node_from_cluster(&self, cluster_id: ClusterId, node_id: SceneId) -> Result<()>;
+    
+    /// Set custom properties on a cluster, overwriting existing keys
+    fn set_cluster_properties(&self, cluster_id: ClusterId, properties: HashMap<String, String>) -> Result<()>;
+    
+    /// Merge two clusters
+    fn merge_clusters(&self, cluster1_id: ClusterId, cluster2_id: ClusterId) -> Result<ClusterId>;
+    
+    /// Create a hierarchical relationship between clusters
+    fn set_cluster_parent(&self, child_id: ClusterId, parent_id: ClusterId) -> Result<()>;
+    
+    /// Get child clusters
+    fn get_child_clusters(&self, parent_id: ClusterId) -> Vec<ClusterId>;
+    
+    /// Get parent cluster
+    fn get_parent_cluster(&self, child_id: ClusterId) -> Option<ClusterId>;
+    
+    /// Get all root clusters (clusters with no parent)
+    fn get_root_clusters(&self) -> Vec<ClusterId>;
+    
+    /// Clear all clusters
+    fn clear(&self);
+}
 
 /// Manages all clusters in the system
 pub struct ClusterManager {
@@ -17,6 +65,8 @@ pub struct ClusterManager {
     cluster_hierarchy: DashMap<ClusterId, HashSet<ClusterId>>,
     /// Reverse hierarchy (child -> parent)
     cluster_parents: DashMap<ClusterId, ClusterId>,
+    /// User-defined rules placing nodes in clusters
+    rules: RwLock<RulesEngine>,
 }
 
 impl ClusterManager {
@@ -27,6 +77,7 @@ impl ClusterManager {
             node_clusters: DashMap::new(),
             cluster_hierarchy: DashMap::new(),
             cluster_parents: DashMap::new(),
+            rules: RwLock::new(RulesEngine::new(Vec::new())),
         }
     }
     
@@ -127,6 +178,27 @@ impl ClusterManager {
         Ok(())
     }
     
+    /// Set custom properties on a cluster, overwriting existing keys
+    pub fn set_cluster_properties(&self, cluster_id: ClusterId, properties: HashMap<String, String>) -> Result<()> {
+        let mut cluster = self.clusters.get_mut(&cluster_id)
+            .ok_or_else(|| anyhow!("Cluster not found: {}", cluster_id))?;
+
+        for (key, value) in properties {
+            cluster.set_property(key, value);
+        }
+
+        Ok(())
+    }
+
+    /// Mark a cluster as expanded or collapsed into a group node
+    pub fn set_cluster_expanded(&self, cluster_id: ClusterId, expanded: bool) -> Result<()> {
+        let mut cluster = self.clusters.get_mut(&cluster_id)
+            .ok_or_else(|| anyhow!("Cluster not found: {}", cluster_id))?;
+        cluster.expanded = expanded;
+        cluster.metadata.modified_at = chrono::Utc::now();
+        Ok(())
+    }
+
     /// Merge two clusters
     pub fn merge_clusters(&self, cluster1_id: ClusterId, cluster2_id: ClusterId) -> Result<ClusterId> {
         if cluster1_id == cluster2_id {
@@ -290,6 +362,97 @@ impl ClusterManager {
             .collect()
     }
     
+    /// Replace the clustering rules
+    ///
+    /// Nodes already placed by the old rules stay until they are evaluated
+    /// again; see [`ClusterManager::apply_rules_to_scene`].
+    pub fn set_rules_engine(&self, engine: RulesEngine) {
+        *self.rules.write().unwrap() = engine;
+    }
+    
+    /// Replace the clustering rules, keeping how `~` is expanded
+    pub fn set_rules(&self, rules: Vec<ClusterRule>) {
+        self.rules.write().unwrap().set_rules(rules);
+    }
+    
+    /// Current clustering rules
+    pub fn rules(&self) -> Vec<ClusterRule> {
+        self.rules.read().unwrap().rules().to_vec()
+    }
+    
+    /// Cluster created for a rule cluster name
+    pub fn rule_cluster(&self, name: &str) -> Option<ClusterId> {
+        self.clusters.iter()
+            .find(|entry| entry.get_property(RULE_CLUSTER_PROPERTY).is_some_and(|value| value == name))
+            .map(|entry| *entry.key())
+    }
+    
+    /// Place a node in the clusters of the rules it matches
+    ///
+    /// Call when a node is added or changed. The node leaves rule clusters it
+    /// no longer matches; rule clusters are created for their first node and
+    /// removed with their last.
+    pub fn apply_rules(&self, node: &SceneNode) -> RuleOutcome {
+        let targets: Vec<String> = self.rules.read().unwrap()
+            .clusters_for(node)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let mut outcome = RuleOutcome::default();
+        
+        for cluster_id in self.get_node_clusters(node.id) {
+            let Some(rule_name) = self.clusters.get(&cluster_id)
+                .and_then(|cluster| cluster.get_property(RULE_CLUSTER_PROPERTY).cloned())
+            else {
+                continue;
+            };
+            if targets.contains(&rule_name) {
+                continue;
+            }
+            if self.remove_node_from_cluster(cluster_id, node.id).is_ok() {
+                outcome.left.push((cluster_id, node.id));
+            }
+            let now_empty = self.clusters.get(&cluster_id).is_some_and(|cluster| cluster.is_empty());
+            if now_empty && self.remove_cluster(cluster_id).is_ok() {
+                outcome.removed.push(cluster_id);
+            }
+        }
+        
+        for name in targets {
+            let cluster_id = match self.rule_cluster(&name) {
+                Some(cluster_id) => {
+                    if self.clusters.get(&cluster_id).is_some_and(|cluster| cluster.contains_node(node.id)) {
+                        continue;
+                    }
+                    if self.add_node_to_cluster(cluster_id, node.id).is_err() {
+                        continue;
+                    }
+                    cluster_id
+                }
+                None => {
+                    let mut cluster = Cluster::new_auto(name.clone(), vec![node.id], ClusterType::Rule);
+                    cluster.metadata.confidence = None;
+                    cluster.set_property(RULE_CLUSTER_PROPERTY.to_string(), name);
+                    let cluster_id = self.add_cluster(cluster);
+                    outcome.created.push(cluster_id);
+                    cluster_id
+                }
+            };
+            outcome.joined.push((cluster_id, node.id));
+        }
+        
+        outcome
+    }
+    
+    /// Evaluate the rules for every node in a scene, e.g. after they changed
+    pub fn apply_rules_to_scene(&self, scene: &Scene) -> RuleOutcome {
+        let mut outcome = RuleOutcome::default();
+        for (_, node) in scene.nodes() {
+            outcome.extend(self.apply_rules(node));
+        }
+        outcome
+    }
+    
     /// Get cluster statistics
     pub fn get_statistics(&self) -> ClusterStatistics {
         let total_clusters = self.clusters.len();
@@ -343,6 +506,64 @@ pub struct ClusterStatistics {
     pub nodes_in_multiple_clusters: usize,
 }
 
+impl ClusterStore for ClusterManager {
+    fn add_cluster(&self, cluster: Cluster) -> ClusterId {
+        ClusterManager::add_cluster(self, cluster)
+    }
+    
+    fn remove_cluster(&self, cluster_id: ClusterId) -> Result<Cluster> {
+        ClusterManager::remove_cluster(self, cluster_id)
+    }
+    
+    fn get_cluster(&self, cluster_id: ClusterId) -> Option<Cluster> {
+        ClusterManager::get_cluster(self, cluster_id)
+    }
+    
+    fn clusters(&self) -> Vec<Cluster> {
+        ClusterManager::clusters(self)
+    }
+    
+    fn get_node_clusters(&self, node_id: SceneId) -> Vec<ClusterId> {
+        ClusterManager::get_node_clusters(self, node_id)
+    }
+    
+    fn add_node_to_cluster(&self, cluster_id: ClusterId, node_id: SceneId) -> Result<()> {
+        ClusterManager::add_node_to_cluster(self, cluster_id, node_id)
+    }
+    
+    fn remove_node_from_cluster(&self, cluster_id: ClusterId, node_id: SceneId) -> Result<()> {
+        ClusterManager::remove_node_from_cluster(self, cluster_id, node_id)
+    }
+    
+    fn set_cluster_properties(&self, cluster_id: ClusterId, properties: HashMap<String, String>) -> Result<()> {
+        ClusterManager::set_cluster_properties(self, cluster_id, properties)
+    }
+    
+    fn merge_clusters(&self, cluster1_id: ClusterId, cluster2_id: ClusterId) -> Result<ClusterId> {
+        ClusterManager::merge_clusters(self, cluster1_id, cluster2_id)
+    }
+    
+    fn set_cluster_parent(&self, child_id: ClusterId, parent_id: ClusterId) -> Result<()> {
+        ClusterManager::set_cluster_parent(self, child_id, parent_id)
+    }
+    
+    fn get_child_clusters(&self, parent_id: ClusterId) -> Vec<ClusterId> {
+        ClusterManager::get_child_clusters(self, parent_id)
+    }
+    
+    fn get_parent_cluster(&self, child_id: ClusterId) -> Option<ClusterId> {
+        ClusterManager::get_parent_cluster(self, child_id)
+    }
+    
+    fn get_root_clusters(&self) -> Vec<ClusterId> {
+        ClusterManager::get_root_clusters(self)
+    }
+    
+    fn clear(&self) {
+        ClusterManager::clear(self)
+    }
+}
+
 impl Default for ClusterManager {
     fn default() -> Self {
         Self::new()
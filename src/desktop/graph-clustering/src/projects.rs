@@ -0,0 +1,856 @@
+//! Automatic project detection from version-controlled repositories
+//!
+//! Scans configured directories for git repositories and seeds a project cluster
+//! for each one: the repository root folder node, branch and commit metadata,
+//! optional task nodes from TODO/FIXME markers, and edges to the editor and
+//! terminal application nodes working inside the repository.
+
+use crate::{Cluster, ClusterId, ClusterManager, ClusterType, CreationMethod};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, TimeZone, Utc};
+use horizonos_graph_engine::{
+    EdgeType, FileType, NodeMetadata, NodeType, Scene, SceneEdge, SceneId, SceneNode, TaskStatus,
+};
+use nalgebra::{Point3, Vector3};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Settings for repository scanning
+#[derive(Debug, Clone)]
+pub struct ProjectDetectionSettings {
+    /// Directories to scan for repositories
+    pub scan_roots: Vec<PathBuf>,
+    /// Maximum directory depth below each scan root
+    pub max_depth: usize,
+    /// Create task nodes from TODO/FIXME markers (opt-in)
+    pub scan_todos: bool,
+    /// Markers recognised when scanning for tasks
+    pub todo_markers: Vec<String>,
+    /// Maximum task nodes per project
+    pub max_todo_items: usize,
+    /// Files larger than this are skipped when scanning for tasks
+    pub max_todo_file_size: u64,
+    /// Number of recent commits kept per project
+    pub recent_commit_limit: usize,
+    /// Application names treated as editors
+    pub editor_apps: Vec<String>,
+    /// Application names treated as terminals
+    pub terminal_apps: Vec<String>,
+    /// Directory names never descended into
+    pub ignored_dirs: Vec<String>,
+}
+
+/// A git repository found during scanning
+#[derive(Debug, Clone)]
+pub struct DetectedRepository {
+    /// Repository working tree root
+    pub root: PathBuf,
+    /// Display name (root folder name)
+    pub name: String,
+    /// Checked out branch, `None` when HEAD is detached
+    pub branch: Option<String>,
+    /// Commit HEAD points at
+    pub head_commit: Option<String>,
+    /// Most recent commits, newest first
+    pub recent_commits: Vec<CommitSummary>,
+    /// TODO/FIXME items, empty unless task scanning is enabled
+    pub todos: Vec<TodoItem>,
+    /// Last fetch time
+    pub last_fetch: Option<DateTime<Utc>>,
+    /// Modification stamps used to detect fetch/commit events
+    stamps: RepositoryStamps,
+}
+
+/// Summary of a commit taken from the HEAD reflog
+#[derive(Debug, Clone)]
+pub struct CommitSummary {
+    /// Commit hash
+    pub hash: String,
+    /// Author name
+    pub author: String,
+    /// Commit time
+    pub timestamp: DateTime<Utc>,
+    /// First line of the commit message
+    pub message: String,
+}
+
+/// A TODO/FIXME marker found in a repository file
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TodoItem {
+    /// File path relative to the repository root
+    pub file: PathBuf,
+    /// 1-based line number
+    pub line: usize,
+    /// Marker that matched
+    pub marker: String,
+    /// Text following the marker
+    pub text: String,
+}
+
+/// Graph objects created for a detected project
+#[derive(Debug, Clone)]
+pub struct ProjectSeed {
+    /// Project cluster
+    pub cluster_id: ClusterId,
+    /// Repository root folder node
+    pub root_node: SceneId,
+    /// Task nodes keyed by their source location
+    pub task_nodes: HashMap<(PathBuf, usize), SceneId>,
+    /// Application nodes linked to the project
+    pub linked_apps: HashSet<SceneId>,
+}
+
+/// Modification times of the git files that change on fetch and commit
+#[derive(Debug, Clone, Default, PartialEq)]
+struct RepositoryStamps {
+    fetch_head: Option<SystemTime>,
+    head_log: Option<SystemTime>,
+    head: Option<SystemTime>,
+}
+
+/// Detects repositories and maintains their project clusters
+pub struct ProjectDetector {
+    /// Scan settings
+    settings: ProjectDetectionSettings,
+    /// Known repositories by root path
+    repositories: HashMap<PathBuf, DetectedRepository>,
+    /// Seeded graph objects by root path
+    seeds: HashMap<PathBuf, ProjectSeed>,
+}
+
+impl ProjectDetector {
+    /// Create a detector with default settings
+    pub fn new() -> Self {
+        Self::with_settings(ProjectDetectionSettings::default())
+    }
+
+    /// Create a detector with custom settings
+    pub fn with_settings(settings: ProjectDetectionSettings) -> Self {
+        Self {
+            settings,
+            repositories: HashMap::new(),
+            seeds: HashMap::new(),
+        }
+    }
+
+    /// Get detection settings
+    pub fn settings(&self) -> &ProjectDetectionSettings {
+        &self.settings
+    }
+
+    /// Update detection settings
+    pub fn update_settings(&mut self, settings: ProjectDetectionSettings) {
+        self.settings = settings;
+    }
+
+    /// Get all known repositories
+    pub fn repositories(&self) -> impl Iterator<Item = &DetectedRepository> {
+        self.repositories.values()
+    }
+
+    /// Get a repository by root path
+    pub fn get_repository(&self, root: &Path) -> Option<&DetectedRepository> {
+        self.repositories.get(root)
+    }
+
+    /// Get the seed for a repository
+    pub fn get_seed(&self, root: &Path) -> Option<&ProjectSeed> {
+        self.seeds.get(root)
+    }
+
+    /// Scan the configured roots, returning repositories not seen before
+    pub fn scan(&mut self) -> Result<Vec<PathBuf>> {
+        let mut found = Vec::new();
+        for scan_root in self.settings.scan_roots.clone() {
+            if scan_root.is_dir() {
+                self.find_repositories(&scan_root, 0, &mut found);
+            }
+        }
+
+        let mut new_repos = Vec::new();
+        for root in found {
+            if self.repositories.contains_key(&root) {
+                continue;
+            }
+            match self.read_repository(&root) {
+                Ok(repo) => {
+                    log::info!("Detected git repository: {}", root.display());
+                    self.repositories.insert(root.clone(), repo);
+                    new_repos.push(root);
+                }
+                Err(e) => log::warn!("Failed to read repository {}: {}", root.display(), e),
+            }
+        }
+
+        // Forget repositories that disappeared from disk
+        self.repositories.retain(|root, _| Self::git_dir(root).is_some());
+
+        Ok(new_repos)
+    }
+
+    /// Re-read repositories whose fetch or commit state changed
+    pub fn refresh(&mut self) -> Result<Vec<PathBuf>> {
+        let mut changed = Vec::new();
+
+        for (root, repo) in self.repositories.iter_mut() {
+            let Some(git_dir) = Self::git_dir(root) else {
+                continue;
+            };
+            let stamps = Self::read_stamps(&git_dir);
+            if stamps == repo.stamps {
+                continue;
+            }
+
+            match Self::read_repository_with(&self.settings, root) {
+                Ok(updated) => {
+                    log::debug!("Repository changed: {}", root.display());
+                    *repo = updated;
+                    changed.push(root.clone());
+                }
+                Err(e) => log::warn!("Failed to refresh repository {}: {}", root.display(), e),
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// Create or update the project cluster for a repository
+    pub fn seed_project(&mut self, root: &Path, scene: &mut Scene, manager: &ClusterManager) -> Result<ClusterId> {
+        let repo = self.repositories.get(root)
+            .ok_or_else(|| anyhow!("Repository not detected: {}", root.display()))?
+            .clone();
+
+        // Reuse the seed only if its cluster still exists
+        let existing = self.seeds.get(root)
+            .filter(|seed| manager.get_cluster(seed.cluster_id).is_some())
+            .cloned();
+
+        let mut seed = match existing {
+            Some(seed) => seed,
+            None => {
+                let root_node = Self::find_or_create_root_node(&repo, scene);
+                let mut cluster = Cluster::new_auto(repo.name.clone(), vec![root_node], ClusterType::Project);
+                cluster.creation_method = CreationMethod::Imported;
+                cluster.description = Some(format!("Git repository at {}", repo.root.display()));
+                cluster.metadata.confidence = Some(1.0);
+                cluster.add_tag("project".to_string());
+                cluster.add_tag("git".to_string());
+                let cluster_id = manager.add_cluster(cluster);
+
+                ProjectSeed {
+                    cluster_id,
+                    root_node,
+                    task_nodes: HashMap::new(),
+                    linked_apps: HashSet::new(),
+                }
+            }
+        };
+
+        manager.set_cluster_properties(seed.cluster_id, Self::cluster_properties(&repo))?;
+        self.sync_task_nodes(&repo, &mut seed, scene, manager)?;
+        self.link_applications(&repo, &mut seed, scene, manager)?;
+
+        let cluster_id = seed.cluster_id;
+        self.seeds.insert(root.to_path_buf(), seed);
+        Ok(cluster_id)
+    }
+
+    /// Scan, refresh and seed every repository, returning touched clusters
+    pub fn update(&mut self, scene: &mut Scene, manager: &ClusterManager) -> Result<Vec<ClusterId>> {
+        let mut roots = self.scan()?;
+        roots.extend(self.refresh()?);
+
+        // Application links can change without any git activity
+        roots.extend(self.seeds.keys().cloned());
+        roots.sort();
+        roots.dedup();
+
+        let mut clusters = Vec::new();
+        for root in roots {
+            if self.repositories.contains_key(&root) {
+                clusters.push(self.seed_project(&root, scene, manager)?);
+            }
+        }
+        Ok(clusters)
+    }
+
+    /// Walk a directory looking for repository roots
+    fn find_repositories(&self, dir: &Path, depth: usize, found: &mut Vec<PathBuf>) {
+        if Self::git_dir(dir).is_some() {
+            found.push(dir.to_path_buf());
+            return;
+        }
+
+        if depth >= self.settings.max_depth {
+            return;
+        }
+
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if is_dir && !self.is_ignored(&path) {
+                self.find_repositories(&path, depth + 1, found);
+            }
+        }
+    }
+
+    /// Whether a directory should be skipped while walking
+    fn is_ignored(&self, path: &Path) -> bool {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        name.starts_with('.') || self.settings.ignored_dirs.iter().any(|d| d == name)
+    }
+
+    /// Read repository state from disk
+    fn read_repository(&self, root: &Path) -> Result<DetectedRepository> {
+        Self::read_repository_with(&self.settings, root)
+    }
+
+    fn read_repository_with(settings: &ProjectDetectionSettings, root: &Path) -> Result<DetectedRepository> {
+        let git_dir = Self::git_dir(root)
+            .ok_or_else(|| anyhow!("No git directory in {}", root.display()))?;
+        let common_dir = Self::common_dir(&git_dir);
+        let head = fs::read_to_string(git_dir.join("HEAD"))?;
+        let head = head.trim();
+
+        let (branch, head_commit) = match head.strip_prefix("ref: ") {
+            Some(reference) => {
+                let branch = reference.strip_prefix("refs/heads/").unwrap_or(reference).to_string();
+                (Some(branch), Self::resolve_ref(&common_dir, reference))
+            }
+            None => (None, Some(head.to_string())),
+        };
+
+        let recent_commits = Self::read_recent_commits(&git_dir, settings.recent_commit_limit);
+
+        let last_fetch = fs::metadata(common_dir.join("FETCH_HEAD"))
+            .and_then(|m| m.modified())
+            .ok()
+            .map(DateTime::<Utc>::from);
+
+        let todos = if settings.scan_todos {
+            Self::scan_todos(settings, root)
+        } else {
+            Vec::new()
+        };
+
+        Ok(DetectedRepository {
+            root: root.to_path_buf(),
+            name: root.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| root.display().to_string()),
+            branch,
+            head_commit,
+            recent_commits,
+            todos,
+            last_fetch,
+            stamps: Self::read_stamps(&git_dir),
+        })
+    }
+
+    /// Git directory of a working tree: the `.git` directory, or the `gitdir:`
+    /// target of a `.git` file as written for worktrees and submodules
+    fn git_dir(root: &Path) -> Option<PathBuf> {
+        let dot_git = root.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+
+        let contents = fs::read_to_string(&dot_git).ok()?;
+        let target = contents.lines().find_map(|line| line.strip_prefix("gitdir:"))?.trim();
+        let git_dir = root.join(target);
+        git_dir.join("HEAD").is_file().then_some(git_dir)
+    }
+
+    /// Directory holding refs and FETCH_HEAD, shared by all worktrees of a repository
+    fn common_dir(git_dir: &Path) -> PathBuf {
+        match fs::read_to_string(git_dir.join("commondir")) {
+            Ok(common) => git_dir.join(common.trim()),
+            Err(_) => git_dir.to_path_buf(),
+        }
+    }
+
+    /// Resolve a ref to a commit hash via loose refs, then packed-refs
+    fn resolve_ref(git_dir: &Path, reference: &str) -> Option<String> {
+        if let Ok(hash) = fs::read_to_string(git_dir.join(reference)) {
+            return Some(hash.trim().to_string());
+        }
+
+        let packed = fs::read_to_string(git_dir.join("packed-refs")).ok()?;
+        packed.lines()
+            .filter(|line| !line.starts_with('#') && !line.starts_with('^'))
+            .find_map(|line| {
+                let (hash, name) = line.split_once(' ')?;
+                (name == reference).then(|| hash.to_string())
+            })
+    }
+
+    /// Read recent commits from the HEAD reflog, newest first
+    fn read_recent_commits(git_dir: &Path, limit: usize) -> Vec<CommitSummary> {
+        let Ok(log) = fs::read_to_string(git_dir.join("logs").join("HEAD")) else {
+            return Vec::new();
+        };
+
+        log.lines()
+            .rev()
+            .filter_map(Self::parse_reflog_line)
+            .take(limit)
+            .collect()
+    }
+
+    /// Parse a reflog line of the form `old new Name <email> ts tz\tcommit: message`
+    fn parse_reflog_line(line: &str) -> Option<CommitSummary> {
+        let (header, message) = line.split_once('\t')?;
+        let message = message.strip_prefix("commit")?;
+        let message = message.split_once(": ").map(|(_, m)| m).unwrap_or(message);
+
+        let mut parts = header.splitn(3, ' ');
+        let _old = parts.next()?;
+        let hash = parts.next()?.to_string();
+        let rest = parts.next()?;
+
+        let (author, rest) = rest.split_once(" <")?;
+        let (_, time) = rest.split_once("> ")?;
+        let seconds: i64 = time.split_whitespace().next()?.parse().ok()?;
+
+        Some(CommitSummary {
+            hash,
+            author: author.to_string(),
+            timestamp: Utc.timestamp_opt(seconds, 0).single()?,
+            message: message.lines().next().unwrap_or_default().to_string(),
+        })
+    }
+
+    /// Collect modification times for fetch/commit detection
+    fn read_stamps(git_dir: &Path) -> RepositoryStamps {
+        let common_dir = Self::common_dir(git_dir);
+        let modified = |dir: &Path, name: &str| fs::metadata(dir.join(name)).and_then(|m| m.modified()).ok();
+        RepositoryStamps {
+            fetch_head: modified(&common_dir, "FETCH_HEAD"),
+            head_log: modified(git_dir, "logs/HEAD"),
+            head: modified(git_dir, "HEAD"),
+        }
+    }
+
+    /// Scan repository files for TODO/FIXME markers
+    fn scan_todos(settings: &ProjectDetectionSettings, root: &Path) -> Vec<TodoItem> {
+        let mut todos = Vec::new();
+        let mut stack = vec![root.to_path_buf()];
+
+        while let Some(dir) = stack.pop() {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let name = entry.file_name().to_string_lossy().to_string();
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+
+                if file_type.is_dir() {
+                    if !name.starts_with('.') && !settings.ignored_dirs.contains(&name) {
+                        stack.push(path);
+                    }
+                    continue;
+                }
+
+                let too_large = entry.metadata().map(|m| m.len() > settings.max_todo_file_size).unwrap_or(true);
+                if !file_type.is_file() || too_large {
+                    continue;
+                }
+
+                // Skip anything that isn't valid UTF-8 text
+                let Ok(content) = fs::read_to_string(&path) else {
+                    continue;
+                };
+
+                for (index, line) in content.lines().enumerate() {
+                    let Some((marker, position)) = settings.todo_markers.iter()
+                        .find_map(|m| line.find(m.as_str()).map(|p| (m, p))) else {
+                        continue;
+                    };
+
+                    let text = line[position + marker.len()..]
+                        .trim_start_matches(|c: char| c == ':' || c == '(' || c.is_whitespace())
+                        .trim()
+                        .to_string();
+
+                    todos.push(TodoItem {
+                        file: path.strip_prefix(root).unwrap_or(&path).to_path_buf(),
+                        line: index + 1,
+                        marker: marker.clone(),
+                        text,
+                    });
+
+                    if todos.len() >= settings.max_todo_items {
+                        return todos;
+                    }
+                }
+            }
+        }
+
+        todos
+    }
+
+    /// Cluster properties describing the repository state
+    fn cluster_properties(repo: &DetectedRepository) -> HashMap<String, String> {
+        let mut properties = HashMap::new();
+        properties.insert("vcs".to_string(), "git".to_string());
+        properties.insert("repository_root".to_string(), repo.root.display().to_string());
+
+        if let Some(branch) = &repo.branch {
+            properties.insert("branch".to_string(), branch.clone());
+        }
+        if let Some(head) = &repo.head_commit {
+            properties.insert("head_commit".to_string(), head.clone());
+        }
+        if let Some(commit) = repo.recent_commits.first() {
+            properties.insert("last_commit_message".to_string(), commit.message.clone());
+            properties.insert("last_commit_author".to_string(), commit.author.clone());
+            properties.insert("last_commit_at".to_string(), commit.timestamp.to_rfc3339());
+        }
+        if let Some(fetch) = repo.last_fetch {
+            properties.insert("last_fetch".to_string(), fetch.to_rfc3339());
+        }
+        properties.insert("recent_commits".to_string(), repo.recent_commits.len().to_string());
+        properties.insert("open_todos".to_string(), repo.todos.len().to_string());
+
+        properties
+    }
+
+    /// Find the directory node for the repository root, creating it if missing
+    fn find_or_create_root_node(repo: &DetectedRepository, scene: &mut Scene) -> SceneId {
+        let root_path = repo.root.display().to_string();
+
+        let existing = scene.nodes().find_map(|(id, node)| match &node.node_type {
+            NodeType::File { path, file_type: FileType::Directory } if *path == root_path => Some(*id),
+            _ => None,
+        });
+
+        if let Some(id) = existing {
+            return id;
+        }
+
+        let mut metadata = NodeMetadata::default();
+        metadata.tags.push("project".to_string());
+        metadata.description = Some(format!("Project: {}", repo.name));
+
+        scene.add_node(SceneNode {
+            id: 0,
+            position: Point3::origin(),
+            velocity: Vector3::zeros(),
+            radius: 1.5,
+            color: [0.95, 0.75, 0.3, 1.0],
+            node_type: NodeType::File { path: root_path, file_type: FileType::Directory },
+            metadata,
+            visible: true,
+            selected: false,
+        })
+    }
+
+    /// Add task nodes for new markers and drop the ones that were resolved
+    fn sync_task_nodes(
+        &self,
+        repo: &DetectedRepository,
+        seed: &mut ProjectSeed,
+        scene: &mut Scene,
+        manager: &ClusterManager,
+    ) -> Result<()> {
+        let current: HashSet<(PathBuf, usize)> = repo.todos.iter()
+            .map(|todo| (todo.file.clone(), todo.line))
+            .collect();
+
+        let stale: Vec<(PathBuf, usize)> = seed.task_nodes.keys()
+            .filter(|key| !current.contains(*key))
+            .cloned()
+            .collect();
+
+        for key in stale {
+            if let Some(node_id) = seed.task_nodes.remove(&key) {
+                manager.remove_node_from_cluster(seed.cluster_id, node_id)?;
+                scene.remove_node(node_id);
+            }
+        }
+
+        let root_position = scene.get_node_position(seed.root_node).unwrap_or_else(Point3::origin);
+        let total = repo.todos.len().max(1) as f32;
+
+        for (index, todo) in repo.todos.iter().enumerate() {
+            let key = (todo.file.clone(), todo.line);
+            if seed.task_nodes.contains_key(&key) {
+                continue;
+            }
+
+            // Place tasks in a ring around the project root
+            let angle = index as f32 / total * std::f32::consts::TAU;
+            let position = root_position + Vector3::new(angle.cos() * 4.0, angle.sin() * 4.0, 0.0);
+
+            let mut metadata = NodeMetadata::default();
+            metadata.tags.push(todo.marker.to_lowercase());
+            metadata.description = Some(format!("{}:{}", todo.file.display(), todo.line));
+            metadata.properties.insert("file".to_string(), repo.root.join(&todo.file).display().to_string());
+            metadata.properties.insert("line".to_string(), todo.line.to_string());
+
+            let title = if todo.text.is_empty() {
+                format!("{} in {}", todo.marker, todo.file.display())
+            } else {
+                todo.text.clone()
+            };
+
+            let task_node = scene.add_node(SceneNode {
+                id: 0,
+                position,
+                velocity: Vector3::zeros(),
+                radius: 0.6,
+                color: [0.9, 0.5, 0.2, 1.0],
+                node_type: NodeType::Task { title, status: TaskStatus::Todo },
+                metadata,
+                visible: true,
+                selected: false,
+            });
+
+            scene.add_edge(SceneEdge {
+                id: 0,
+                source: seed.root_node,
+                target: task_node,
+                edge_type: EdgeType::Contains,
+                weight: 0.5,
+                color: [0.9, 0.5, 0.2, 0.6],
+                visible: true,
+                animated: false,
+            });
+
+            manager.add_node_to_cluster(seed.cluster_id, task_node)?;
+            seed.task_nodes.insert(key, task_node);
+        }
+
+        Ok(())
+    }
+
+    /// Link editor and terminal application nodes working inside the repository
+    fn link_applications(
+        &self,
+        repo: &DetectedRepository,
+        seed: &mut ProjectSeed,
+        scene: &mut Scene,
+        manager: &ClusterManager,
+    ) -> Result<()> {
+        let candidates: Vec<SceneId> = scene.nodes()
+            .filter_map(|(id, node)| match &node.node_type {
+                NodeType::Application { pid, name } if self.is_project_tool(name) => {
+                    Self::process_cwd(*pid)
+                        .filter(|cwd| cwd.starts_with(&repo.root))
+                        .map(|_| *id)
+                }
+                _ => None,
+            })
+            .filter(|id| !seed.linked_apps.contains(id))
+            .collect();
+
+        for app_node in candidates {
+            scene.add_edge(SceneEdge {
+                id: 0,
+                source: app_node,
+                target: seed.root_node,
+                edge_type: EdgeType::WorksOn,
+                weight: 0.8,
+                color: [0.95, 0.75, 0.3, 0.8],
+                visible: true,
+                animated: false,
+            });
+
+            manager.add_node_to_cluster(seed.cluster_id, app_node)?;
+            seed.linked_apps.insert(app_node);
+            log::debug!("Linked application node {} to project {}", app_node, repo.name);
+        }
+
+        // Forget applications that have left the scene
+        seed.linked_apps.retain(|id| scene.get_node(*id).is_some());
+
+        Ok(())
+    }
+
+    /// Whether an application name matches a configured editor or terminal
+    fn is_project_tool(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.settings.editor_apps.iter()
+            .chain(self.settings.terminal_apps.iter())
+            .any(|app| name == *app || name.starts_with(&format!("{} ", app)))
+    }
+
+    /// Current working directory of a process
+    fn process_cwd(pid: u32) -> Option<PathBuf> {
+        fs::read_link(format!("/proc/{}/cwd", pid)).ok()
+    }
+}
+
+impl Default for ProjectDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProjectDetectionSettings {
+    /// Scan the configured roots, expanding a leading `~` to `home`
+    pub fn set_scan_roots(&mut self, roots: &[String], home: Option<&Path>) {
+        self.scan_roots = roots.iter()
+            .map(|root| match (root.strip_prefix('~'), home) {
+                (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+                    home.join(rest.trim_start_matches('/'))
+                }
+                _ => PathBuf::from(root),
+            })
+            .collect();
+    }
+}
+
+impl Default for ProjectDetectionSettings {
+    fn default() -> Self {
+        let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("/"));
+
+        Self {
+            scan_roots: vec![home.join("Projects"), home.join("src"), home.join("code")],
+            max_depth: 3,
+            scan_todos: false,
+            todo_markers: vec!["TODO".to_string(), "FIXME".to_string()],
+            max_todo_items: 50,
+            max_todo_file_size: 1024 * 1024,
+            recent_commit_limit: 10,
+            editor_apps: ["code", "codium", "zed", "nvim", "vim", "emacs", "helix", "hx", "kate", "gedit"]
+                .iter().map(|s| s.to_string()).collect(),
+            terminal_apps: ["alacritty", "kitty", "foot", "wezterm", "konsole", "gnome-terminal", "xterm"]
+                .iter().map(|s| s.to_string()).collect(),
+            ignored_dirs: ["node_modules", "target", "build", "dist", "vendor"]
+                .iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEAD_COMMIT: &str = "0123456789abcdef0123456789abcdef01234567";
+
+    /// Write a minimal git directory with `main` checked out
+    fn init_git_dir(git_dir: &Path) {
+        fs::create_dir_all(git_dir.join("refs/heads")).unwrap();
+        fs::create_dir_all(git_dir.join("logs")).unwrap();
+        fs::write(git_dir.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        fs::write(git_dir.join("refs/heads/main"), format!("{}\n", HEAD_COMMIT)).unwrap();
+        fs::write(
+            git_dir.join("logs/HEAD"),
+            format!("{} {} Ada <ada@example.com> 1700000000 +0000\tcommit (initial): Initial commit\n", "0".repeat(40), HEAD_COMMIT),
+        ).unwrap();
+    }
+
+    fn detector(scan_root: &Path) -> ProjectDetector {
+        ProjectDetector::with_settings(ProjectDetectionSettings {
+            scan_roots: vec![scan_root.to_path_buf()],
+            ..ProjectDetectionSettings::default()
+        })
+    }
+
+    #[test]
+    fn test_scan_finds_repository_with_git_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("app");
+        init_git_dir(&repo.join(".git"));
+
+        let mut detector = detector(dir.path());
+        assert_eq!(detector.scan().unwrap(), vec![repo.clone()]);
+
+        let detected = detector.get_repository(&repo).unwrap();
+        assert_eq!(detected.name, "app");
+        assert_eq!(detected.branch.as_deref(), Some("main"));
+        assert_eq!(detected.head_commit.as_deref(), Some(HEAD_COMMIT));
+        assert_eq!(detected.recent_commits.len(), 1);
+        assert_eq!(detected.recent_commits[0].message, "Initial commit");
+
+        // Known repositories are not reported again
+        assert!(detector.scan().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_scan_follows_git_file_of_a_worktree() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("main");
+        init_git_dir(&main.join(".git"));
+
+        // `git worktree add` leaves a `.git` file pointing into the main repository
+        let worktree_git_dir = main.join(".git/worktrees/feature");
+        fs::create_dir_all(worktree_git_dir.join("logs")).unwrap();
+        fs::write(worktree_git_dir.join("HEAD"), "ref: refs/heads/feature\n").unwrap();
+        fs::write(worktree_git_dir.join("commondir"), "../..\n").unwrap();
+        fs::write(main.join(".git/refs/heads/feature"), format!("{}\n", HEAD_COMMIT)).unwrap();
+        let worktree = dir.path().join("feature");
+        fs::create_dir_all(&worktree).unwrap();
+        fs::write(worktree.join(".git"), format!("gitdir: {}\n", worktree_git_dir.display())).unwrap();
+
+        let mut detector = detector(dir.path());
+        let mut found = detector.scan().unwrap();
+        found.sort();
+        assert_eq!(found, vec![worktree.clone(), main]);
+
+        let detected = detector.get_repository(&worktree).unwrap();
+        assert_eq!(detected.branch.as_deref(), Some("feature"));
+        assert_eq!(detected.head_commit.as_deref(), Some(HEAD_COMMIT));
+    }
+
+    #[test]
+    fn test_scan_skips_ignored_and_dangling_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        init_git_dir(&dir.path().join("node_modules/dep/.git"));
+        let dangling = dir.path().join("moved");
+        fs::create_dir_all(&dangling).unwrap();
+        fs::write(dangling.join(".git"), "gitdir: ../gone/.git\n").unwrap();
+
+        assert!(detector(dir.path()).scan().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_update_seeds_a_project_cluster() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("app");
+        init_git_dir(&repo.join(".git"));
+        let mut detector = detector(dir.path());
+        let mut scene = Scene::new();
+        let manager = ClusterManager::new();
+
+        let clusters = detector.update(&mut scene, &manager).unwrap();
+        assert_eq!(clusters.len(), 1);
+        let cluster = manager.get_cluster(clusters[0]).unwrap();
+        assert_eq!(cluster.cluster_type, ClusterType::Project);
+        assert_eq!(cluster.name, "app");
+
+        let root_node = detector.get_seed(&repo).unwrap().root_node;
+        assert!(cluster.nodes.contains(&root_node));
+        assert!(matches!(
+            &scene.get_node(root_node).unwrap().node_type,
+            NodeType::File { path, file_type: FileType::Directory } if *path == repo.display().to_string()
+        ));
+
+        // A second pass refreshes the same cluster instead of adding one
+        assert_eq!(detector.update(&mut scene, &manager).unwrap(), clusters);
+        assert_eq!(manager.clusters().len(), 1);
+    }
+
+    #[test]
+    fn test_scan_roots_expand_home() {
+        let mut settings = ProjectDetectionSettings::default();
+        settings.set_scan_roots(&["~/work".to_string(), "~".to_string(), "/srv/git".to_string(), "~other".to_string()], Some(Path::new("/home/ada")));
+        assert_eq!(settings.scan_roots, vec![
+            PathBuf::from("/home/ada/work"),
+            PathBuf::from("/home/ada"),
+            PathBuf::from("/srv/git"),
+            PathBuf::from("~other"),
+        ]);
+    }
+}
@@ -0,0 +1,120 @@
+//! User-defined clustering rules
+//!
+//! Rules come from the clustering section of the configuration, e.g. "file
+//! nodes under ~/projects/foo go to cluster Foo" or "nodes tagged work go to
+//! cluster Work". The cluster manager evaluates them whenever a node is added
+//! or changes: a matching node joins the rule's cluster, created on first use,
+//! and leaves it again once it stops matching. Rule clusters are recognised
+//! by the [`RULE_CLUSTER_PROPERTY`] property, so renaming one keeps it bound
+//! to its rules.
+
+use crate::ClusterId;
+use horizonos_graph_config::{ClusterRule, ClusterRuleCondition, ClusteringConfig};
+use horizonos_graph_engine::{NodeType, SceneId, SceneNode};
+use horizonos_graph_nodes::hooks::node_type_name;
+use std::path::{Path, PathBuf};
+
+/// Cluster property holding the cluster name rules refer to
+pub const RULE_CLUSTER_PROPERTY: &str = "rule_cluster";
+
+/// Evaluates cluster rules against nodes
+#[derive(Debug, Clone, Default)]
+pub struct RulesEngine {
+    rules: Vec<ClusterRule>,
+    /// Directory `~` expands to
+    home: Option<PathBuf>,
+}
+
+impl RulesEngine {
+    pub fn new(rules: Vec<ClusterRule>) -> Self {
+        Self {
+            rules,
+            home: std::env::var_os("HOME").map(PathBuf::from),
+        }
+    }
+
+    pub fn from_config(config: &ClusteringConfig) -> Self {
+        Self::new(config.rules.clone())
+    }
+
+    /// Expand `~` to this directory instead of the user's home
+    pub fn with_home(mut self, home: PathBuf) -> Self {
+        self.home = Some(home);
+        self
+    }
+
+    pub fn rules(&self) -> &[ClusterRule] {
+        &self.rules
+    }
+
+    pub fn set_rules(&mut self, rules: Vec<ClusterRule>) {
+        self.rules = rules;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.iter().all(|rule| !rule.enabled)
+    }
+
+    /// Names of the clusters a node belongs to, in rule order
+    pub fn clusters_for(&self, node: &SceneNode) -> Vec<&str> {
+        let mut clusters: Vec<&str> = Vec::new();
+        for rule in self.rules.iter().filter(|rule| rule.enabled) {
+            if !clusters.contains(&rule.cluster.as_str()) && self.matches(&rule.condition, node) {
+                clusters.push(&rule.cluster);
+            }
+        }
+        clusters
+    }
+
+    /// Whether a node meets a condition
+    pub fn matches(&self, condition: &ClusterRuleCondition, node: &SceneNode) -> bool {
+        match condition {
+            ClusterRuleCondition::NodeType { node_type } => {
+                node_type_name(&node.node_type).eq_ignore_ascii_case(node_type)
+            }
+            ClusterRuleCondition::PathPrefix { path } => match &node.node_type {
+                NodeType::File { path: file, .. } => Path::new(file).starts_with(self.expand(path)),
+                _ => false,
+            },
+            ClusterRuleCondition::Tag { tag } => node.metadata.tags.iter().any(|t| t == tag),
+            ClusterRuleCondition::All { conditions } => conditions.iter().all(|c| self.matches(c, node)),
+            ClusterRuleCondition::Any { conditions } => conditions.iter().any(|c| self.matches(c, node)),
+            ClusterRuleCondition::Not { condition } => !self.matches(condition, node),
+        }
+    }
+
+    fn expand(&self, path: &str) -> PathBuf {
+        match (path.strip_prefix('~'), &self.home) {
+            (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+                home.join(rest.trim_start_matches('/'))
+            }
+            _ => PathBuf::from(path),
+        }
+    }
+}
+
+/// What evaluating rules changed
+#[derive(Debug, Clone, Default)]
+pub struct RuleOutcome {
+    /// Nodes added to rule clusters
+    pub joined: Vec<(ClusterId, SceneId)>,
+    /// Nodes that stopped matching their rule cluster
+    pub left: Vec<(ClusterId, SceneId)>,
+    /// Rule clusters created for their first node
+    pub created: Vec<ClusterId>,
+    /// Rule clusters removed after their last node left
+    pub removed: Vec<ClusterId>,
+}
+
+impl RuleOutcome {
+    pub fn is_empty(&self) -> bool {
+        self.joined.is_empty() && self.left.is_empty()
+    }
+
+    pub(crate) fn extend(&mut self, other: RuleOutcome) {
+        self.joined.extend(other.joined);
+        self.left.extend(other.left);
+        self.created.extend(other.created);
+        self.removed.extend(other.removed);
+    }
+}
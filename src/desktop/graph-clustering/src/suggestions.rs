@@ -257,6 +257,7 @@ impl SuggestionEngine {
                     horizonos_graph_engine::NodeType::Automation { .. } => "Automations",
                     horizonos_graph_engine::NodeType::Setting { .. } => "Settings",
                     horizonos_graph_engine::NodeType::ConfigGroup { .. } => "Configuration Groups",
+                    horizonos_graph_engine::NodeType::Group { .. } => "Groups",
                 };
                 
                 suggestions.push(ClusterSuggestion {
@@ -0,0 +1,183 @@
+//! Recording cluster store for unit tests
+//!
+//! Enabled with the `test-util` feature. `MockClusterStore` keeps real
+//! in-memory cluster semantics, records every call for assertions and can be
+//! told to fail specific operations.
+
+use crate::{Cluster, ClusterId, ClusterManager, ClusterStore};
+use anyhow::{anyhow, Result};
+use horizonos_graph_engine::SceneId;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// A call made on a `MockClusterStore`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClusterCall {
+    AddCluster { cluster_id: ClusterId },
+    RemoveCluster { cluster_id: ClusterId },
+    AddNodeToCluster { cluster_id: ClusterId, node_id: SceneId },
+    RemoveNodeFromCluster { cluster_id: ClusterId, node_id: SceneId },
+    SetClusterProperties { cluster_id: ClusterId, properties: HashMap<String, String> },
+    MergeClusters { cluster1_id: ClusterId, cluster2_id: ClusterId },
+    SetClusterParent { child_id: ClusterId, parent_id: ClusterId },
+    Clear,
+}
+
+/// Fallible operations that can be made to fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClusterOperation {
+    RemoveCluster,
+    AddNodeToCluster,
+    RemoveNodeFromCluster,
+    SetClusterProperties,
+    MergeClusters,
+    SetClusterParent,
+}
+
+/// `ClusterStore` that records mutating calls
+pub struct MockClusterStore {
+    /// Backing store
+    inner: ClusterManager,
+    /// Mutating calls in order
+    calls: Mutex<Vec<ClusterCall>>,
+    /// Operations that return an error
+    failing: Mutex<HashSet<ClusterOperation>>,
+}
+
+impl MockClusterStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self {
+            inner: ClusterManager::new(),
+            calls: Mutex::new(Vec::new()),
+            failing: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Create a store preloaded with clusters, without recording calls
+    pub fn with_clusters(clusters: impl IntoIterator<Item = Cluster>) -> Self {
+        let store = Self::new();
+        for cluster in clusters {
+            store.inner.add_cluster(cluster);
+        }
+        store
+    }
+
+    /// Make an operation fail until `succeed` is called
+    pub fn fail(&self, operation: ClusterOperation) {
+        self.failing.lock().unwrap().insert(operation);
+    }
+
+    /// Let an operation succeed again
+    pub fn succeed(&self, operation: ClusterOperation) {
+        self.failing.lock().unwrap().remove(&operation);
+    }
+
+    /// Mutating calls made so far
+    pub fn calls(&self) -> Vec<ClusterCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Forget recorded calls
+    pub fn clear_calls(&self) {
+        self.calls.lock().unwrap().clear();
+    }
+
+    /// Record a call and check whether its operation should fail
+    fn record(&self, call: ClusterCall, operation: Option<ClusterOperation>) -> Result<()> {
+        self.calls.lock().unwrap().push(call);
+        match operation {
+            Some(operation) if self.failing.lock().unwrap().contains(&operation) => {
+                Err(anyhow!("Injected failure: {:?}", operation))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for MockClusterStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClusterStore for MockClusterStore {
+    fn add_cluster(&self, cluster: Cluster) -> ClusterId {
+        let _ = self.record(ClusterCall::AddCluster { cluster_id: cluster.id }, None);
+        self.inner.add_cluster(cluster)
+    }
+
+    fn remove_cluster(&self, cluster_id: ClusterId) -> Result<Cluster> {
+        self.record(ClusterCall::RemoveCluster { cluster_id }, Some(ClusterOperation::RemoveCluster))?;
+        self.inner.remove_cluster(cluster_id)
+    }
+
+    fn get_cluster(&self, cluster_id: ClusterId) -> Option<Cluster> {
+        self.inner.get_cluster(cluster_id)
+    }
+
+    fn clusters(&self) -> Vec<Cluster> {
+        self.inner.clusters()
+    }
+
+    fn get_node_clusters(&self, node_id: SceneId) -> Vec<ClusterId> {
+        self.inner.get_node_clusters(node_id)
+    }
+
+    fn add_node_to_cluster(&self, cluster_id: ClusterId, node_id: SceneId) -> Result<()> {
+        self.record(
+            ClusterCall::AddNodeToCluster { cluster_id, node_id },
+            Some(ClusterOperation::AddNodeToCluster),
+        )?;
+        self.inner.add_node_to_cluster(cluster_id, node_id)
+    }
+
+    fn remove_node_from_cluster(&self, cluster_id: ClusterId, node_id: SceneId) -> Result<()> {
+        self.record(
+            ClusterCall::RemoveNodeFromCluster { cluster_id, node_id },
+            Some(ClusterOperation::RemoveNodeFromCluster),
+        )?;
+        self.inner.remove_node_from_cluster(cluster_id, node_id)
+    }
+
+    fn set_cluster_properties(&self, cluster_id: ClusterId, properties: HashMap<String, String>) -> Result<()> {
+        self.record(
+            ClusterCall::SetClusterProperties { cluster_id, properties: properties.clone() },
+            Some(ClusterOperation::SetClusterProperties),
+        )?;
+        self.inner.set_cluster_properties(cluster_id, properties)
+    }
+
+    fn merge_clusters(&self, cluster1_id: ClusterId, cluster2_id: ClusterId) -> Result<ClusterId> {
+        self.record(
+            ClusterCall::MergeClusters { cluster1_id, cluster2_id },
+            Some(ClusterOperation::MergeClusters),
+        )?;
+        self.inner.merge_clusters(cluster1_id, cluster2_id)
+    }
+
+    fn set_cluster_parent(&self, child_id: ClusterId, parent_id: ClusterId) -> Result<()> {
+        self.record(
+            ClusterCall::SetClusterParent { child_id, parent_id },
+            Some(ClusterOperation::SetClusterParent),
+        )?;
+        self.inner.set_cluster_parent(child_id, parent_id)
+    }
+
+    fn get_child_clusters(&self, parent_id: ClusterId) -> Vec<ClusterId> {
+        self.inner.get_child_clusters(parent_id)
+    }
+
+    fn get_parent_cluster(&self, child_id: ClusterId) -> Option<ClusterId> {
+        self.inner.get_parent_cluster(child_id)
+    }
+
+    fn get_root_clusters(&self) -> Vec<ClusterId> {
+        self.inner.get_root_clusters()
+    }
+
+    fn clear(&self) {
+        let _ = self.record(ClusterCall::Clear, None);
+        self.inner.clear()
+    }
+}
@@ -0,0 +1,615 @@
+//! Timeline playback of how a cluster evolved
+//!
+//! A [`ClusterTimeline`] orders what happened to a cluster's nodes and edges:
+//! node creation and modification times, edge creation times and any events
+//! recorded elsewhere (e.g. an activity journal). A [`TimelinePlayer`] maps
+//! the timeline onto a playback duration and produces a [`TimelineFrame`]
+//! per step, in which elements grow in as they appear and modified nodes
+//! pulse. Frames can be applied to the scene for live playback or stepped at
+//! a fixed rate into a [`TimelineRecorder`] for video export; the
+//! [`VideoRecorder`] draws them with a headless engine and encodes them with
+//! `ffmpeg`.
+//!
+//! The play position and the look of the scene before playback are kept in
+//! the engine's [`PlaybackClock`] and [`PlaybackBase`].
+
+use crate::Cluster;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use horizonos_graph_edges::GraphEdge;
+use horizonos_graph_engine::{GraphEngine, PlaybackBase, PlaybackClock, PlaybackConfig, Scene, SceneId};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+/// What happened at a point on the timeline
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimelineEventKind {
+    /// A node joined the cluster
+    NodeAdded { node_id: SceneId },
+    /// A node in the cluster changed
+    NodeModified { node_id: SceneId },
+    /// A node left the cluster
+    NodeRemoved { node_id: SceneId },
+    /// An edge between cluster nodes was created
+    EdgeAdded { edge_id: SceneId, source: SceneId, target: SceneId },
+    /// An edge was removed
+    EdgeRemoved { edge_id: SceneId },
+}
+
+/// An event on the timeline
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineEvent {
+    pub at: DateTime<Utc>,
+    pub kind: TimelineEventKind,
+}
+
+/// Ordered history of a cluster
+#[derive(Debug, Clone)]
+pub struct ClusterTimeline {
+    cluster_id: crate::ClusterId,
+    /// Events sorted by time
+    events: Vec<TimelineEvent>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+impl ClusterTimeline {
+    /// Build a timeline from the creation and modification times of a cluster's nodes
+    pub fn from_scene(cluster: &Cluster, scene: &Scene) -> Self {
+        let mut events = Vec::new();
+        for node_id in &cluster.nodes {
+            let Some(node) = scene.get_node(*node_id) else {
+                continue;
+            };
+            events.push(TimelineEvent {
+                at: node.metadata.created_at,
+                kind: TimelineEventKind::NodeAdded { node_id: *node_id },
+            });
+            if node.metadata.updated_at > node.metadata.created_at {
+                events.push(TimelineEvent {
+                    at: node.metadata.updated_at,
+                    kind: TimelineEventKind::NodeModified { node_id: *node_id },
+                });
+            }
+        }
+
+        let mut timeline = Self {
+            cluster_id: cluster.id,
+            events: Vec::new(),
+            start: cluster.metadata.created_at,
+            end: cluster.metadata.created_at,
+        };
+        timeline.with_events(events);
+        timeline
+    }
+
+    /// Add the edges connecting cluster nodes, at their creation times
+    pub fn with_edges<'a>(mut self, cluster: &Cluster, edges: impl IntoIterator<Item = &'a GraphEdge>) -> Self {
+        let events = edges
+            .into_iter()
+            .filter(|edge| cluster.nodes.contains(&edge.source) && cluster.nodes.contains(&edge.target))
+            .map(|edge| TimelineEvent {
+                at: edge.relationship_data.created_at,
+                kind: TimelineEventKind::EdgeAdded {
+                    edge_id: edge.id,
+                    source: edge.source,
+                    target: edge.target,
+                },
+            })
+            .collect();
+        self.with_events(events);
+        self
+    }
+
+    /// Merge recorded events, e.g. from an activity journal
+    pub fn with_events(&mut self, events: Vec<TimelineEvent>) {
+        self.events.extend(events);
+        // Stable, so an element's addition stays before changes recorded at the same time
+        self.events.sort_by_key(|event| event.at);
+        if let (Some(first), Some(last)) = (self.events.first(), self.events.last()) {
+            self.start = self.start.min(first.at);
+            self.end = self.end.max(last.at);
+        }
+    }
+
+    /// Cluster this timeline belongs to
+    pub fn cluster_id(&self) -> crate::ClusterId {
+        self.cluster_id
+    }
+
+    /// All events, oldest first
+    pub fn events(&self) -> &[TimelineEvent] {
+        &self.events
+    }
+
+    /// Covered time range
+    pub fn range(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        (self.start, self.end)
+    }
+
+    /// Restrict playback to a time range
+    pub fn clamp(&mut self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<()> {
+        if end <= start {
+            bail!("Timeline range must end after it starts");
+        }
+        self.start = start;
+        self.end = end;
+        Ok(())
+    }
+}
+
+/// How an element looks at a point in playback
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ElementAppearance {
+    /// Growth from 0 (just appeared) to 1 (fully shown)
+    pub progress: f32,
+    /// Highlight from recent modification, fading from 1 to 0
+    pub highlight: f32,
+}
+
+/// Scene state at one point of playback
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineFrame {
+    /// Timeline position
+    pub at: DateTime<Utc>,
+    /// Position as a fraction of the range
+    pub fraction: f64,
+    /// Nodes present at this point
+    pub nodes: HashMap<SceneId, ElementAppearance>,
+    /// Edges present at this point
+    pub edges: HashMap<SceneId, ElementAppearance>,
+    /// Events that happened so far
+    pub events_so_far: usize,
+}
+
+impl TimelineFrame {
+    /// Show the frame in the scene; cluster elements not yet present are hidden
+    ///
+    /// `base` holds the look of the cluster's elements captured by
+    /// [`TimelinePlayer::capture`] before playback started.
+    pub fn apply(&self, scene: &mut Scene, base: &PlaybackBase) {
+        for (node_id, look) in base.nodes() {
+            let Some(node) = scene.get_node_mut(node_id) else {
+                continue;
+            };
+            match self.nodes.get(&node_id) {
+                Some(appearance) => {
+                    node.visible = look.visible;
+                    node.radius = look.radius * (0.2 + 0.8 * appearance.progress) * (1.0 + 0.25 * appearance.highlight);
+                    node.color = lighten(look.color, appearance.highlight);
+                    node.color[3] = look.color[3] * appearance.progress;
+                }
+                None => node.visible = false,
+            }
+        }
+        for (edge_id, look) in base.edges() {
+            let Some(edge) = scene.get_edge_mut(edge_id) else {
+                continue;
+            };
+            match self.edges.get(&edge_id) {
+                Some(appearance) => {
+                    edge.visible = look.visible;
+                    edge.color[3] = look.color[3] * appearance.progress;
+                }
+                None => edge.visible = false,
+            }
+        }
+    }
+}
+
+fn lighten(color: [f32; 4], amount: f32) -> [f32; 4] {
+    let mix = |channel: f32| channel + (1.0 - channel) * amount * 0.5;
+    [mix(color[0]), mix(color[1]), mix(color[2]), color[3]]
+}
+
+/// Receives frames during video export
+pub trait TimelineRecorder {
+    /// Prepare for `frame_count` frames played at `fps`
+    fn begin(&mut self, _fps: u32, _frame_count: usize) -> Result<()> {
+        Ok(())
+    }
+
+    /// Render and encode one frame
+    fn record_frame(&mut self, index: usize, frame: &TimelineFrame) -> Result<()>;
+
+    /// Finish the video
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Records playback to a video file
+///
+/// Each frame is applied to the scene of a headless engine, drawn and piped
+/// as raw RGBA to `ffmpeg`, which encodes it as H.264. The scene is put back
+/// as `base` captured it once the video is finished.
+pub struct VideoRecorder {
+    engine: GraphEngine,
+    base: PlaybackBase,
+    output: PathBuf,
+    fps: u32,
+    encoder: Option<Child>,
+}
+
+impl VideoRecorder {
+    /// Record with a headless engine showing the cluster, e.g. from [`GraphEngine::new_headless`]
+    pub fn new(engine: GraphEngine, base: PlaybackBase, output: impl Into<PathBuf>) -> Result<Self> {
+        if !engine.is_headless() {
+            bail!("Timeline videos are recorded with a headless engine");
+        }
+        Ok(Self {
+            engine,
+            base,
+            output: output.into(),
+            fps: 30,
+            encoder: None,
+        })
+    }
+
+    /// Engine the frames are drawn with, to position the camera
+    pub fn engine_mut(&mut self) -> &mut GraphEngine {
+        &mut self.engine
+    }
+
+    /// Start ffmpeg for frames of the given size
+    fn spawn_encoder(&self, width: u32, height: u32) -> Result<Child> {
+        let child = Command::new("ffmpeg")
+            .args(["-loglevel", "error", "-y", "-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &format!("{}x{}", width, height), "-r", &self.fps.to_string(), "-i", "-"])
+            .args(["-c:v", "libx264", "-pix_fmt", "yuv420p"])
+            .arg(&self.output)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to start ffmpeg")?;
+        Ok(child)
+    }
+}
+
+impl TimelineRecorder for VideoRecorder {
+    fn begin(&mut self, fps: u32, _frame_count: usize) -> Result<()> {
+        self.fps = fps;
+        Ok(())
+    }
+
+    fn record_frame(&mut self, _index: usize, frame: &TimelineFrame) -> Result<()> {
+        frame.apply(self.engine.scene_mut(), &self.base);
+        self.engine.render()?;
+        let captured = self.engine.capture_frame()?;
+        if self.encoder.is_none() {
+            self.encoder = Some(self.spawn_encoder(captured.width, captured.height)?);
+        }
+        let stdin = self.encoder.as_mut().and_then(|encoder| encoder.stdin.as_mut())
+            .context("ffmpeg closed its input")?;
+        stdin.write_all(&captured.rgba).context("Failed to send a frame to ffmpeg")?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.base.restore(self.engine.scene_mut());
+        let Some(encoder) = self.encoder.take() else {
+            bail!("No frames were recorded");
+        };
+        // Closing its input lets ffmpeg finish the file
+        let output = encoder.wait_with_output()?;
+        if !output.status.success() {
+            bail!("ffmpeg failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        log::info!("Recorded timeline video to {}", self.output.display());
+        Ok(())
+    }
+}
+
+impl Drop for VideoRecorder {
+    fn drop(&mut self) {
+        // Abandoned exports leave no encoder behind
+        if let Some(mut encoder) = self.encoder.take() {
+            let _ = encoder.kill();
+            let _ = encoder.wait();
+        }
+    }
+}
+
+/// Play, pause and seek through a cluster timeline
+pub struct TimelinePlayer {
+    timeline: ClusterTimeline,
+    clock: PlaybackClock,
+}
+
+impl TimelinePlayer {
+    /// Create a paused player at the start of the timeline
+    pub fn new(timeline: ClusterTimeline, config: PlaybackConfig) -> Self {
+        Self {
+            timeline,
+            clock: PlaybackClock::new(config, 0.0),
+        }
+    }
+
+    /// Timeline being played
+    pub fn timeline(&self) -> &ClusterTimeline {
+        &self.timeline
+    }
+
+    /// Play position and settings
+    pub fn clock(&self) -> &PlaybackClock {
+        &self.clock
+    }
+
+    /// Capture the look of the cluster's elements so playback can be undone
+    pub fn capture(&self, scene: &Scene) -> PlaybackBase {
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        for event in self.timeline.events() {
+            match event.kind {
+                TimelineEventKind::NodeAdded { node_id } => nodes.push(node_id),
+                TimelineEventKind::EdgeAdded { edge_id, .. } => edges.push(edge_id),
+                _ => {}
+            }
+        }
+        PlaybackBase::capture(scene, nodes, edges)
+    }
+
+    /// Start playing, from the beginning if at the end
+    pub fn play(&mut self) {
+        self.clock.play();
+    }
+
+    /// Pause playback
+    pub fn pause(&mut self) {
+        self.clock.pause();
+    }
+
+    /// Toggle between playing and paused
+    pub fn toggle(&mut self) {
+        self.clock.toggle();
+    }
+
+    /// Whether playback is running
+    pub fn is_playing(&self) -> bool {
+        self.clock.is_playing()
+    }
+
+    /// Playback speed multiplier
+    pub fn set_speed(&mut self, speed: f64) {
+        self.clock.set_speed(speed);
+    }
+
+    /// Jump to a fraction of the range
+    pub fn seek_fraction(&mut self, fraction: f64) {
+        self.clock.seek_fraction(fraction);
+    }
+
+    /// Jump to a point in time
+    pub fn seek(&mut self, at: DateTime<Utc>) {
+        let (start, end) = self.timeline.range();
+        let span = (end - start).num_milliseconds().max(1) as f64;
+        self.seek_fraction((at - start).num_milliseconds() as f64 / span);
+    }
+
+    /// Position as a fraction of the range
+    pub fn position(&self) -> f64 {
+        self.clock.position()
+    }
+
+    /// Advance playback by wall-clock time and return the frame to show
+    pub fn advance(&mut self, elapsed: Duration) -> TimelineFrame {
+        self.clock.advance(elapsed);
+        self.frame()
+    }
+
+    /// Frame at the current position
+    pub fn frame(&self) -> TimelineFrame {
+        let (start, end) = self.timeline.range();
+        let at = self.clock.time_in(start, end);
+
+        // Animation windows are wall-clock times, converted to timeline time
+        let config = self.clock.config();
+        let grow_ms = self.clock.timeline_time(config.grow_time, end - start).num_milliseconds().max(1) as f64;
+        let highlight_ms = self.clock.timeline_time(config.highlight_time, end - start).num_milliseconds().max(1) as f64;
+        let since = |event_at: DateTime<Utc>| (at - event_at).num_milliseconds() as f64;
+
+        let mut nodes = HashMap::new();
+        let mut edges = HashMap::new();
+        let mut events_so_far = 0;
+        for event in self.timeline.events().iter().take_while(|event| event.at <= at) {
+            events_so_far += 1;
+            let progress = (since(event.at) / grow_ms).clamp(0.0, 1.0) as f32;
+            match event.kind {
+                TimelineEventKind::NodeAdded { node_id } => {
+                    nodes.insert(node_id, ElementAppearance { progress, highlight: 0.0 });
+                }
+                TimelineEventKind::NodeModified { node_id } => {
+                    let highlight = (1.0 - since(event.at) / highlight_ms).clamp(0.0, 1.0) as f32;
+                    nodes
+                        .entry(node_id)
+                        .or_insert(ElementAppearance { progress: 1.0, highlight: 0.0 })
+                        .highlight = highlight;
+                }
+                TimelineEventKind::NodeRemoved { node_id } => {
+                    nodes.remove(&node_id);
+                }
+                TimelineEventKind::EdgeAdded { edge_id, .. } => {
+                    edges.insert(edge_id, ElementAppearance { progress, highlight: 0.0 });
+                }
+                TimelineEventKind::EdgeRemoved { edge_id } => {
+                    edges.remove(&edge_id);
+                }
+            }
+        }
+
+        TimelineFrame {
+            at,
+            fraction: self.clock.position(),
+            nodes,
+            edges,
+            events_so_far,
+        }
+    }
+
+    /// Step through the whole range at `fps` and hand every frame to a recorder
+    ///
+    /// Returns the number of frames recorded. The live playback position is
+    /// left unchanged.
+    pub fn export(&mut self, fps: u32, recorder: &mut dyn TimelineRecorder) -> Result<usize> {
+        if fps == 0 {
+            bail!("Export frame rate must be positive");
+        }
+        let live = self.clock.clone();
+        let frame_count = self.clock.frame_count(fps);
+
+        let result = (|| {
+            recorder.begin(fps, frame_count + 1)?;
+            for index in 0..=frame_count {
+                self.seek_fraction(index as f64 / frame_count as f64);
+                recorder.record_frame(index, &self.frame())?;
+            }
+            recorder.finish()
+        })();
+
+        self.clock = live;
+        result.map(|_| frame_count + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration as TimeDelta, TimeZone};
+    use horizonos_graph_engine::{EdgeType, NodeMetadata, NodeType, Position, SceneEdge, SceneNode};
+    use nalgebra::Vector3;
+
+    fn node(scene: &mut Scene, created_at: DateTime<Utc>, updated_at: DateTime<Utc>) -> SceneId {
+        scene.add_node(SceneNode {
+            id: 0,
+            position: Position::origin(),
+            velocity: Vector3::zeros(),
+            radius: 1.0,
+            color: [0.0, 0.0, 0.0, 1.0],
+            node_type: NodeType::Concept { title: String::new(), content: String::new() },
+            metadata: NodeMetadata { created_at, updated_at, ..NodeMetadata::default() },
+            visible: true,
+            selected: false,
+        })
+    }
+
+    #[derive(Default)]
+    struct Frames {
+        begun: Option<(u32, usize)>,
+        fractions: Vec<f64>,
+        finished: bool,
+    }
+
+    impl TimelineRecorder for Frames {
+        fn begin(&mut self, fps: u32, frame_count: usize) -> Result<()> {
+            self.begun = Some((fps, frame_count));
+            Ok(())
+        }
+
+        fn record_frame(&mut self, index: usize, frame: &TimelineFrame) -> Result<()> {
+            assert_eq!(index, self.fractions.len());
+            self.fractions.push(frame.fraction);
+            Ok(())
+        }
+
+        fn finish(&mut self) -> Result<()> {
+            self.finished = true;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_playback_grows_and_highlights_elements() {
+        let start = Utc.with_ymd_and_hms(2024, 5, 1, 8, 0, 0).unwrap();
+        let mut scene = Scene::new();
+        let a = node(&mut scene, start, start);
+        let b = node(&mut scene, start + TimeDelta::hours(4), start + TimeDelta::hours(8));
+        let edge = scene.add_edge(SceneEdge {
+            id: 0,
+            source: a,
+            target: b,
+            edge_type: EdgeType::Contains,
+            weight: 1.0,
+            color: [1.0; 4],
+            visible: true,
+            animated: false,
+        });
+        let cluster = Cluster::new_manual("Project".to_string(), vec![a, b]);
+        let mut timeline = ClusterTimeline::from_scene(&cluster, &scene);
+        timeline.with_events(vec![TimelineEvent {
+            at: start + TimeDelta::hours(4),
+            kind: TimelineEventKind::EdgeAdded { edge_id: edge, source: a, target: b },
+        }]);
+        timeline.clamp(start, start + TimeDelta::hours(10)).unwrap();
+
+        // Ten hours in ten seconds: elements grow and highlights fade over an hour
+        let config = PlaybackConfig {
+            duration: Duration::from_secs(10),
+            grow_time: Duration::from_secs(1),
+            highlight_time: Duration::from_secs(1),
+            looping: false,
+        };
+        let mut player = TimelinePlayer::new(timeline, config);
+        let base = player.capture(&scene);
+
+        player.seek(start + TimeDelta::minutes(270));
+        let frame = player.frame();
+        assert_eq!(frame.events_so_far, 3);
+        assert_eq!(frame.nodes[&a], ElementAppearance { progress: 1.0, highlight: 0.0 });
+        assert_eq!(frame.nodes[&b].progress, 0.5);
+        assert_eq!(frame.edges[&edge].progress, 0.5);
+        frame.apply(&mut scene, &base);
+        assert_eq!(scene.get_node(b).unwrap().radius, 0.6);
+        assert_eq!(scene.get_node(b).unwrap().color[3], 0.5);
+
+        // A quarter of an hour after its modification the node is still highlighted
+        player.seek(start + TimeDelta::minutes(495));
+        let frame = player.frame();
+        assert_eq!(frame.nodes[&b], ElementAppearance { progress: 1.0, highlight: 0.75 });
+        frame.apply(&mut scene, &base);
+        assert_eq!(scene.get_node(b).unwrap().color, [0.375, 0.375, 0.375, 1.0]);
+
+        // Before the second node appeared it and its edge are hidden
+        player.seek(start + TimeDelta::hours(2));
+        player.frame().apply(&mut scene, &base);
+        assert!(scene.get_node(a).unwrap().visible);
+        assert!(!scene.get_node(b).unwrap().visible);
+        assert!(!scene.get_edge(edge).unwrap().visible);
+        base.restore(&mut scene);
+        assert!(scene.get_node(b).unwrap().visible && scene.get_edge(edge).unwrap().visible);
+        assert_eq!(scene.get_node(b).unwrap().radius, 1.0);
+
+        // Playing advances with wall-clock time and stops at the end
+        player.play();
+        assert_eq!(player.advance(Duration::from_secs(3)).fraction, 0.5);
+        assert_eq!(player.advance(Duration::from_secs(6)).fraction, 1.0);
+        assert!(!player.is_playing());
+    }
+
+    #[test]
+    fn test_export_steps_through_the_range() {
+        let cluster = Cluster::new_manual("Empty".to_string(), Vec::new());
+        let mut timeline = ClusterTimeline::from_scene(&cluster, &Scene::new());
+        let start = Utc.with_ymd_and_hms(2024, 5, 1, 8, 0, 0).unwrap();
+        timeline.clamp(start, start + TimeDelta::hours(1)).unwrap();
+        let config = PlaybackConfig { duration: Duration::from_secs(2), ..Default::default() };
+        let mut player = TimelinePlayer::new(timeline, config);
+        player.seek_fraction(0.3);
+        player.play();
+
+        let mut frames = Frames::default();
+        assert_eq!(player.export(5, &mut frames).unwrap(), 11);
+        assert_eq!(frames.begun, Some((5, 11)));
+        assert_eq!(frames.fractions.first(), Some(&0.0));
+        assert_eq!(frames.fractions.last(), Some(&1.0));
+        assert!(frames.finished);
+
+        // Live playback carries on where it was
+        assert_eq!(player.position(), 0.3);
+        assert!(player.is_playing());
+        assert!(player.export(0, &mut Frames::default()).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+//! Audio devices and streams shown in the graph
+//!
+//! [`AudioGraphManager`] keeps a device node per PipeWire sink and source
+//! and a stream node per application stream. `pw-dump` is polled on the
+//! services' runtime, so frames never wait on it; the newest snapshot is
+//! applied to the scene on the next frame. Audio node actions are added to
+//! the node's context menu, and letting go of a stream node on a device
+//! node moves the stream there.
+
+use anyhow::Result;
+use horizonos_graph_engine::{Scene, SceneId};
+use horizonos_graph_interaction::MenuItem;
+use horizonos_graph_nodes::{NodeAction, NodeActionResult, NodeActionType};
+use horizonos_graph_system::audio::{ACTION_SET_DEFAULT, ACTION_SET_PROFILE_PREFIX, ACTION_TOGGLE_MUTE};
+use horizonos_graph_system::{AudioGraphManager, AudioGraphSnapshot, PipeWireClient};
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// How often PipeWire is read for device and stream changes
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Prefix of the audio actions' item IDs, see [`AudioNodes::menu_items`]
+const ACTION_PREFIX: &str = "audio.";
+
+/// Audio nodes of the graph and the PipeWire snapshots feeding them
+pub struct AudioNodes {
+    manager: AudioGraphManager,
+    snapshots: Option<mpsc::Receiver<AudioGraphSnapshot>>,
+}
+
+impl AudioNodes {
+    pub fn new() -> Self {
+        Self {
+            manager: AudioGraphManager::default(),
+            snapshots: None,
+        }
+    }
+
+    /// Read PipeWire on the runtime until the compositor is gone
+    pub fn start(&mut self, runtime: &tokio::runtime::Handle) {
+        let (sender, receiver) = mpsc::channel();
+        runtime.spawn(async move {
+            let client = PipeWireClient::new();
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            let mut available = true;
+            loop {
+                interval.tick().await;
+                match client.snapshot().await {
+                    Ok(snapshot) => {
+                        available = true;
+                        if sender.send(snapshot).is_err() {
+                            break;
+                        }
+                    }
+                    // Reported once until PipeWire answers again
+                    Err(e) if available => {
+                        log::warn!("Audio devices unavailable: {}", e);
+                        available = false;
+                    }
+                    Err(_) => {}
+                }
+            }
+        });
+        self.snapshots = Some(receiver);
+    }
+
+    /// Bring the scene in line with the newest snapshot, keeping volume sliders next to their nodes
+    pub fn update(&mut self, scene: &mut Scene) {
+        if let Some(snapshot) = self.snapshots.as_ref().and_then(|snapshots| snapshots.try_iter().last()) {
+            self.manager.apply_snapshot(scene, snapshot);
+        }
+        self.manager.update_slider_positions(scene);
+    }
+
+    /// Context menu items for the actions an audio node offers
+    ///
+    /// Empty for nodes that are not audio devices or streams.
+    pub fn menu_items(&self, node_id: SceneId) -> Vec<MenuItem> {
+        self.manager.available_actions(node_id)
+            .into_iter()
+            .filter_map(|action| match action {
+                NodeActionType::Custom(name) => Some(name),
+                _ => None,
+            })
+            .map(|name| {
+                let label = self.action_label(&name);
+                MenuItem::new(name, label)
+            })
+            .collect()
+    }
+
+    /// Whether a menu item is one of the audio actions
+    pub fn is_action(item_id: &str) -> bool {
+        item_id.starts_with(ACTION_PREFIX)
+    }
+
+    /// Run an audio action chosen from a node's menu
+    pub fn run_action(&mut self, runtime: &tokio::runtime::Handle, node_id: SceneId, action: &str) -> Result<NodeActionResult> {
+        let action = NodeAction::Custom { action_type: action.to_string(), parameters: HashMap::new() };
+        runtime.block_on(self.manager.handle_action(node_id, action))
+    }
+
+    /// Move a stream to the device it was dropped on
+    ///
+    /// Returns `false` when the nodes are not a stream and a device of the same direction.
+    pub fn drop_node(&mut self, runtime: &tokio::runtime::Handle, scene: &mut Scene, dragged: SceneId, target: SceneId) -> Result<bool> {
+        runtime.block_on(self.manager.handle_drop(scene, dragged, target))
+    }
+
+    fn action_label(&self, action: &str) -> String {
+        if action == ACTION_SET_DEFAULT {
+            return "Make default device".to_string();
+        }
+        if action == ACTION_TOGGLE_MUTE {
+            return "Mute or unmute".to_string();
+        }
+        let Some(profile_name) = action.strip_prefix(ACTION_SET_PROFILE_PREFIX) else {
+            return action.to_string();
+        };
+        let description = self.manager.snapshot().cards.iter()
+            .flat_map(|card| &card.profiles)
+            .find(|profile| profile.name == profile_name)
+            .map_or(profile_name, |profile| profile.description.as_str());
+        format!("Use profile {}", description)
+    }
+}
+
+impl Default for AudioNodes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_system::audio::{AudioCard, AudioDirection, AudioEndpoint, AudioProfile};
+
+    fn snapshot(volume: f32) -> AudioGraphSnapshot {
+        let profile = |index: u32, name: &str, description: &str| AudioProfile {
+            index,
+            name: name.to_string(),
+            description: description.to_string(),
+            available: true,
+        };
+        AudioGraphSnapshot {
+            endpoints: vec![AudioEndpoint {
+                id: 50,
+                name: "alsa_output.analog".to_string(),
+                description: "Speakers".to_string(),
+                direction: AudioDirection::Output,
+                card_id: Some(40),
+                volume,
+                muted: false,
+                is_default: false,
+            }],
+            streams: Vec::new(),
+            cards: vec![AudioCard {
+                id: 40,
+                description: "Built-in Audio".to_string(),
+                profiles: vec![profile(1, "output:analog-stereo", "Analog Stereo"), profile(2, "output:hdmi-stereo", "HDMI Stereo")],
+                active_profile: Some(1),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_update_applies_the_newest_snapshot() {
+        let (sender, receiver) = mpsc::channel();
+        let mut audio = AudioNodes { snapshots: Some(receiver), ..AudioNodes::new() };
+        let mut scene = Scene::new();
+        sender.send(snapshot(0.2)).unwrap();
+        sender.send(snapshot(0.7)).unwrap();
+
+        audio.update(&mut scene);
+
+        let nodes: Vec<_> = scene.nodes().collect();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].1.metadata.properties.get("audio.volume").map(String::as_str), Some("0.70"));
+
+        // Nothing new leaves the scene as it is
+        audio.update(&mut scene);
+        assert_eq!(scene.nodes().count(), 1);
+    }
+
+    #[test]
+    fn test_menu_offers_the_device_actions() {
+        let (sender, receiver) = mpsc::channel();
+        let mut audio = AudioNodes { snapshots: Some(receiver), ..AudioNodes::new() };
+        let mut scene = Scene::new();
+        sender.send(snapshot(0.5)).unwrap();
+        audio.update(&mut scene);
+        let speakers = *scene.nodes().next().unwrap().0;
+
+        let items = audio.menu_items(speakers);
+        let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, ["Make default device", "Mute or unmute", "Use profile HDMI Stereo"]);
+        assert!(items.iter().all(|item| AudioNodes::is_action(&item.id)));
+
+        assert!(audio.menu_items(speakers + 1).is_empty());
+        assert!(!AudioNodes::is_action(crate::state::COLLAPSE_CLUSTER_ITEM));
+    }
+}
@@ -0,0 +1,186 @@
+//! Automation scripts of the desktop's automation nodes
+//!
+//! The [`WorkflowScheduler`] service runs the scripts against the
+//! compositor's scene. Scripts are registered as their nodes are added or
+//! edited, and a node's `execute` action queues a run whose result is
+//! recorded back on the node. Script notifications and focus mode switches
+//! go to the [`NotificationManager`].
+
+use horizonos_graph_ai::automation::scheduler::WorkflowScheduler;
+use horizonos_graph_ai::automation::script::{
+    script_workflow_id, AutomationScript, SceneScriptGraph, ScriptFocusMode, ScriptNotification, ScriptRuntime,
+};
+use horizonos_graph_engine::{Scene, SceneId};
+use horizonos_graph_nodes::{AutomationEvent, ExecutionResult, NodeManager};
+use horizonos_graph_notifications::{Notification, NotificationManager, NotificationSource, NotificationType};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+/// Group of script notifications in the notification center
+pub const SCRIPT_NOTIFICATIONS_GROUP: &str = "automation-scripts";
+
+/// Scripts of automation nodes, run by the scheduler service
+pub struct DesktopScripts {
+    scheduler: Arc<WorkflowScheduler>,
+    node_manager: Arc<Mutex<NodeManager>>,
+    events: crossbeam_channel::Receiver<AutomationEvent>,
+    runtime: tokio::runtime::Handle,
+}
+
+impl DesktopScripts {
+    /// Let `scheduler` run scripts against `scene` and follow the automation nodes of `node_manager`
+    ///
+    /// Must be attached before automation nodes are added, since only nodes
+    /// added or edited afterwards are registered.
+    pub fn attach(
+        scheduler: Arc<WorkflowScheduler>,
+        scene: Arc<Mutex<Scene>>,
+        node_manager: Arc<Mutex<NodeManager>>,
+        notifications: Arc<NotificationManager>,
+        runtime: tokio::runtime::Handle,
+    ) -> Self {
+        let (notification_tx, mut notification_rx) = mpsc::unbounded_channel::<ScriptNotification>();
+        let (focus_tx, mut focus_rx) = mpsc::unbounded_channel::<ScriptFocusMode>();
+        let graph = SceneScriptGraph::new(scene, notification_tx).with_focus_modes(focus_tx);
+        scheduler.attach_scripts(ScriptRuntime::default(), Arc::new(graph));
+
+        let manager = notifications.clone();
+        runtime.spawn(async move {
+            while let Some(notification) = notification_rx.recv().await {
+                if let Err(e) = manager.notify(script_notification(notification)).await {
+                    log::warn!("Could not show script notification: {}", e);
+                }
+            }
+        });
+        runtime.spawn(async move {
+            while let Some(request) = focus_rx.recv().await {
+                if let Err(e) = notifications.set_focus_mode(request.mode.as_deref()).await {
+                    log::warn!("Automation {} could not switch focus modes: {}", request.automation_id, e);
+                }
+            }
+        });
+
+        let events = node_manager.lock().unwrap_or_else(|e| e.into_inner()).subscribe_automations();
+        Self { scheduler, node_manager, events, runtime }
+    }
+
+    /// Register edited scripts and start queued runs
+    pub fn dispatch(&self) {
+        for event in self.events.try_iter() {
+            match event {
+                AutomationEvent::Saved(node) => {
+                    let Some(script) = AutomationScript::from_node(&node) else {
+                        continue;
+                    };
+                    // Broken scripts stay registered in their last working version
+                    if let Err(e) = self.scheduler.register_script(script) {
+                        log::warn!("Script of automation {} not registered: {}", node.id, e);
+                    }
+                }
+                AutomationEvent::RunQueued(id) => self.run(id),
+                AutomationEvent::Removed(id) => {
+                    self.scheduler.unregister_script(&script_workflow_id(id));
+                }
+            }
+        }
+    }
+
+    fn run(&self, id: SceneId) {
+        let scheduler = self.scheduler.clone();
+        let node_manager = self.node_manager.clone();
+        self.runtime.spawn(async move {
+            let result = match scheduler.run_script(&script_workflow_id(id)).await {
+                Ok(outcome) => outcome.to_execution_result(),
+                Err(e) => failed_run(e.to_string()),
+            };
+            if let Err(e) = node_manager.lock().unwrap_or_else(|e| e.into_inner()).record_automation_run(id, result) {
+                log::warn!("Result of automation {} not recorded: {}", id, e);
+            }
+        });
+    }
+}
+
+/// Notification posted by a script, sent from its automation
+pub fn script_notification(notification: ScriptNotification) -> Notification {
+    let mut shown = Notification::new(notification.summary, notification.body)
+        .with_type(NotificationType::Info)
+        .with_node(notification.automation_id)
+        .with_source(NotificationSource {
+            name: notification.automation,
+            app_id: None,
+            pid: None,
+            icon: None,
+        });
+    shown.group = Some(SCRIPT_NOTIFICATIONS_GROUP.to_string());
+    shown.tags = vec!["automation".to_string()];
+    shown
+}
+
+/// Result of a run the scheduler could not start
+fn failed_run(error: String) -> ExecutionResult {
+    let now = chrono::Utc::now();
+    ExecutionResult {
+        success: false,
+        exit_code: None,
+        execution_time: 0,
+        stdout: String::new(),
+        stderr: String::new(),
+        start_time: now,
+        end_time: now,
+        error: Some(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_ai::automation::scheduler::SchedulerConfig;
+    use horizonos_graph_engine::AutomationStatus;
+    use horizonos_graph_nodes::{AutomationNode, NodeAction};
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    #[test]
+    fn test_script_notifications_name_their_automation() {
+        let notification = script_notification(ScriptNotification {
+            automation_id: 7,
+            automation: "Linker".to_string(),
+            summary: "Linked 3 files".to_string(),
+            body: String::new(),
+        });
+        assert_eq!(notification.title, "Linked 3 files");
+        assert_eq!(notification.source.name, "Linker");
+        assert_eq!(notification.group.as_deref(), Some(SCRIPT_NOTIFICATIONS_GROUP));
+    }
+
+    #[test]
+    fn test_execute_runs_the_registered_script() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+        let config = SchedulerConfig { persistent: false, ..SchedulerConfig::default() };
+        let scheduler = Arc::new(runtime.block_on(WorkflowScheduler::new(config)).unwrap());
+        let scene = Arc::new(Mutex::new(Scene::new()));
+        let node_manager = Arc::new(Mutex::new(NodeManager::new()));
+        let notifications = Arc::new(NotificationManager::new(Default::default()));
+        let scripts = DesktopScripts::attach(scheduler, scene, node_manager.clone(), notifications, runtime.handle().clone());
+
+        let script = AutomationNode::new_script(1, "Counter".to_string(), "let x = 1 + 1; print(x);".to_string());
+        node_manager.lock().unwrap().add_node(Box::new(script)).unwrap();
+        scripts.dispatch();
+        let execute = NodeAction::Custom { action_type: "execute".to_string(), parameters: HashMap::new() };
+        node_manager.lock().unwrap().handle_node_action(1, execute).unwrap();
+        scripts.dispatch();
+
+        let status = runtime.block_on(async {
+            for _ in 0..100 {
+                let node = node_manager.lock().unwrap().automation(1).unwrap();
+                if node.success_count == 1 {
+                    return node.status;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            panic!("Script run was not recorded");
+        });
+        assert!(matches!(status, AutomationStatus::Active));
+    }
+}
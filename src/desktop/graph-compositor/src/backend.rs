@@ -3,14 +3,17 @@
 use smithay::{
     backend::{
         winit::{self, WinitEvent, WinitEventLoop, WinitGraphicsBackend},
-        renderer::{gles::GlesRenderer, Bind},
+        renderer::{gles::GlesRenderer, Bind, Frame, Renderer},
         egl::surface::EGLSurface,
     },
     output::{Mode, Output, PhysicalProperties, Subpixel},
     utils::{Rectangle, Transform},
 };
-use crate::{AppState, render::GraphRenderIntegration};
-use std::time::Duration;
+use crate::{AppState, CompositorError, profile::flag_value, remote::RemoteFrame, render::GraphRenderIntegration};
+use calloop::EventLoop;
+use horizonos_graph_engine::GraphEngine;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use anyhow::Result;
 
 /// Initialize winit backend for development
@@ -18,11 +21,21 @@ pub fn init_winit_backend() -> Result<(WinitGraphicsBackend<GlesRenderer>, Winit
     winit::init().map_err(|e| anyhow::anyhow!("Failed to initialize winit backend: {:?}", e))
 }
 
+/// Serve Wayland clients whose requests are waiting, without blocking
+fn dispatch_clients(clients: &mut EventLoop<'static, AppState>, state: &mut AppState) -> Result<()> {
+    clients.dispatch(Some(Duration::ZERO), state)?;
+    state.display_handle.flush_clients()?;
+    Ok(())
+}
+
 /// Run the compositor with winit backend
+///
+/// `clients` is the event loop the Wayland socket was added to.
 pub fn run_winit(
     mut state: AppState,
     mut backend: WinitGraphicsBackend<GlesRenderer>,
     mut event_loop: WinitEventLoop,
+    clients: &mut EventLoop<'static, AppState>,
 ) -> Result<()> {
     let size = backend.window_size();
     
@@ -50,13 +63,22 @@ pub fn run_winit(
     );
     output.set_preferred(mode);
     
-    state.space.map_output(&output, (0, 0));
+    state.map_output(&output);
     
-    // Initialize graph rendering
-    let graph_render = GraphRenderIntegration::new()?;
+    // Initialize graph rendering; the graph is drawn off-screen and shown below the windows
+    let mut graph_render = GraphRenderIntegration::new()?;
+    match pollster::block_on(GraphEngine::new_headless(size.w.max(1) as u32, size.h.max(1) as u32)) {
+        Ok(engine) => graph_render = graph_render.with_engine(engine),
+        Err(e) => log::warn!("Graph rendering unavailable, showing windows only: {}", e),
+    }
+    if let Some(camera) = state.restored_camera.take() {
+        graph_render.restore_camera(&camera);
+    }
     
     // Main loop
     while state.running {
+        let frame_start = Instant::now();
+        
         // Process winit events
         let _ = event_loop.dispatch_new_events(|event| match event {
             WinitEvent::Resized { size, .. } => {
@@ -66,6 +88,12 @@ pub fn run_winit(
                     refresh: 60_000,
                 };
                 output.change_current_state(Some(mode), None, None, None);
+                let position = state.space.output_geometry(&output).map_or((0, 0), |geometry| (geometry.loc.x, geometry.loc.y));
+                let scale = output.current_scale().fractional_scale() as f32;
+                if let Err(e) = state.monitors.update_monitor(&output.name(), (size.w as u32, size.h as u32), position, scale) {
+                    log::debug!("Output {} not resized in the monitor layout: {}", output.name(), e);
+                }
+                graph_render.resize(size.w.max(1) as u32, size.h.max(1) as u32);
             }
             WinitEvent::Input(input_event) => {
                 // Handle input
@@ -76,20 +104,231 @@ pub fn run_winit(
             }
             _ => {}
         });
+        dispatch_clients(clients, &mut state)?;
+        
+        // Handle remote viewers, their input, consent and permission prompts, screen reader speech, configuration edits, portal and control requests and automation scripts
+        state.dispatch_remote_view();
+        state.dispatch_prompts();
+        state.dispatch_notification_review();
+        state.dispatch_accessibility();
+        state.dispatch_permissions();
+        state.dispatch_config_changes();
+        state.dispatch_portal();
+        state.dispatch_control();
+        state.dispatch_scripts();
+        state.dispatch_preloading();
+        state.dispatch_agent_events();
+        state.dispatch_edge_decay();
+        state.dispatch_projects();
+        state.dispatch_audio();
+        state.dispatch_quick_look();
+        state.dispatch_workspace_identity();
         
         // Update camera from interaction
-        graph_render.update_camera(&state);
+        graph_render.update_camera(&mut state);
         
         // Render frame
         let renderer = backend.renderer();
         graph_render.render_frame(renderer, &mut state, &output, 0)?;
+        let elements = GraphRenderIntegration::output_elements(backend.renderer(), &state, &output);
         
-        // Submit frame
-        backend.submit(None)?;
+        // Draw the graph with the windows over it and submit the frame
+        let size = backend.window_size();
+        let damage = [Rectangle::from_size(size)];
+        {
+            let (renderer, mut framebuffer) = backend.bind()?;
+            let mut frame = renderer.render(&mut framebuffer, size, Transform::Flipped180)?;
+            graph_render.draw_output(&mut frame, output.current_scale().fractional_scale(), &elements, &damage)?;
+            let _ = frame.finish()?;
+        }
+        backend.submit(Some(&damage))?;
         
-        // Process other events
-        // state.display_handle.dispatch_clients(&mut state).ok();
+        // Checkpoint the session for crash recovery
+        state.auto_save_session(graph_render.camera_state());
+        
+        // Hold the frame rate the power state allows
+        if let Some(rest) = graph_render.frame_interval().checked_sub(frame_start.elapsed()) {
+            std::thread::sleep(rest);
+        }
     }
     
+    state.close_session(graph_render.camera_state());
+    
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Settings of the headless backend
+#[derive(Debug, Clone)]
+pub struct HeadlessConfig {
+    /// Size of the virtual output in pixels
+    pub width: u32,
+    pub height: u32,
+    /// Frames to render before exiting; runs until stopped when `None`
+    pub frames: Option<u64>,
+    /// Directory rendered frames are written to as numbered PNG files
+    pub capture_dir: Option<PathBuf>,
+    /// Time between frames
+    pub frame_interval: Duration,
+}
+
+impl Default for HeadlessConfig {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            frames: None,
+            capture_dir: None,
+            frame_interval: Duration::from_millis(16),
+        }
+    }
+}
+
+impl HeadlessConfig {
+    /// Settings from `--headless` and its options, `None` without `--headless`
+    pub fn from_args(args: &[String]) -> Result<Option<Self>, CompositorError> {
+        if !args.iter().any(|arg| arg == "--headless") {
+            return Ok(None);
+        }
+        let mut config = Self::default();
+        if let Some(size) = flag_value(args, "--headless-size") {
+            let parsed = size
+                .split_once('x')
+                .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+                .filter(|&(width, height): &(u32, u32)| width > 0 && height > 0);
+            let Some((width, height)) = parsed else {
+                return Err(CompositorError::Config(format!("Invalid --headless-size '{}', expected e.g. 1280x720", size)));
+            };
+            config.width = width;
+            config.height = height;
+        }
+        if let Some(frames) = flag_value(args, "--headless-frames") {
+            let frames = frames
+                .parse()
+                .map_err(|_| CompositorError::Config(format!("Invalid --headless-frames '{}'", frames)))?;
+            config.frames = Some(frames);
+        }
+        config.capture_dir = flag_value(args, "--capture-dir").map(PathBuf::from);
+        Ok(Some(config))
+    }
+}
+
+/// Run the compositor without a display, drawing the graph off-screen
+///
+/// A stub backend for integration tests: it maps a virtual output, runs the
+/// usual dispatch loop and renders the scene with a headless graph engine.
+/// Clients connect through the socket on `clients`, but their surfaces are
+/// not composited.
+pub fn run_headless(mut state: AppState, config: HeadlessConfig, clients: &mut EventLoop<'static, AppState>) -> Result<()> {
+    let output = Output::new(
+        "headless".to_string(),
+        PhysicalProperties {
+            size: (0, 0).into(),
+            subpixel: Subpixel::Unknown,
+            make: "HorizonOS".to_string(),
+            model: "Headless".to_string(),
+        },
+    );
+    let mode = Mode {
+        size: (config.width as i32, config.height as i32).into(),
+        refresh: (1_000_000 / config.frame_interval.as_millis().max(1)) as i32,
+    };
+    output.change_current_state(Some(mode), Some(Transform::Normal), None, Some((0, 0).into()));
+    output.set_preferred(mode);
+    state.map_output(&output);
+    
+    let engine = pollster::block_on(GraphEngine::new_headless(config.width, config.height))
+        .map_err(|e| anyhow::anyhow!("Failed to initialize headless rendering: {}", e))?;
+    let mut graph_render = GraphRenderIntegration::new()?.with_engine(engine);
+    if let Some(camera) = state.restored_camera.take() {
+        graph_render.restore_camera(&camera);
+    }
+    if let Some(dir) = &config.capture_dir {
+        std::fs::create_dir_all(dir)?;
+    }
+    log::info!("Running headless at {}x{}", config.width, config.height);
+    
+    let mut frame = 0u64;
+    while state.running && config.frames.map_or(true, |frames| frame < frames) {
+        let frame_start = Instant::now();
+        
+        dispatch_clients(clients, &mut state)?;
+        state.dispatch_remote_view();
+        state.dispatch_prompts();
+        state.dispatch_notification_review();
+        state.dispatch_accessibility();
+        state.dispatch_permissions();
+        state.dispatch_config_changes();
+        state.dispatch_portal();
+        state.dispatch_control();
+        state.dispatch_scripts();
+        state.dispatch_preloading();
+        state.dispatch_agent_events();
+        state.dispatch_edge_decay();
+        state.dispatch_projects();
+        state.dispatch_audio();
+        state.dispatch_quick_look();
+        state.dispatch_workspace_identity();
+        
+        graph_render.update_camera(&mut state);
+        
+        // Draw the compositor's scene as it stands this frame
+        graph_render.render_graph(&mut state, &output.name())?;
+        let Some(engine) = graph_render.engine_mut() else {
+            break;
+        };
+        let streaming = state.remote_view.as_ref().is_some_and(|server| server.has_viewers());
+        if config.capture_dir.is_some() || streaming {
+            let image = engine.capture_frame()?;
+            if let Some(dir) = &config.capture_dir {
+                image.save_png(dir.join(format!("frame-{:06}.png", frame)))?;
+            }
+            if let Some(server) = state.remote_view.as_mut().filter(|_| streaming) {
+                let workspace = state.workspaces.get_active_workspace().map(|workspace| workspace.name);
+                server.submit_frame(RemoteFrame {
+                    width: image.width,
+                    height: image.height,
+                    pixels: &image.rgba,
+                    workspace: workspace.as_deref(),
+                });
+            }
+        }
+        
+        state.auto_save_session(graph_render.camera_state());
+        frame += 1;
+        
+        let interval = config.frame_interval.max(graph_render.frame_interval());
+        if let Some(rest) = interval.checked_sub(frame_start.elapsed()) {
+            std::thread::sleep(rest);
+        }
+    }
+    
+    state.close_session(graph_render.camera_state());
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_headless_config_from_args() {
+        assert!(HeadlessConfig::from_args(&args(&["--software-render"])).unwrap().is_none());
+
+        let config = HeadlessConfig::from_args(&args(&[
+            "--headless", "--headless-size=640x480", "--headless-frames", "3", "--capture-dir", "/tmp/frames",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!((config.width, config.height), (640, 480));
+        assert_eq!(config.frames, Some(3));
+        assert_eq!(config.capture_dir, Some(PathBuf::from("/tmp/frames")));
+
+        assert!(HeadlessConfig::from_args(&args(&["--headless", "--headless-size", "640"])).is_err());
+        assert!(HeadlessConfig::from_args(&args(&["--headless", "--headless-frames", "many"])).is_err());
+    }
+}
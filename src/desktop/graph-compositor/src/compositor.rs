@@ -2,10 +2,15 @@
 
 use smithay::{
     desktop::Window,
+    reexports::wayland_server::Display,
     utils::Rectangle,
-    wayland::shell::xdg::ToplevelSurface,
+    wayland::{shell::xdg::ToplevelSurface, socket::ListeningSocketSource},
 };
-use crate::AppState;
+use calloop::generic::Generic;
+use calloop::{Interest, Mode, PostAction};
+use crate::{AppState, ClientState, CompositorError};
+use std::ffi::OsString;
+use std::sync::Arc;
 
 /// Initialize the compositor
 pub fn init_compositor(state: &mut AppState) {
@@ -17,15 +22,85 @@ pub fn init_compositor(state: &mut AppState) {
     // Initialize graph scene
     // TODO: Set up initial graph layout
     
+    let toggles = state.profile.subsystems();
+    
     // Initialize XWayland if available
-    if let Err(e) = state.xwayland_manager.init(&state.display_handle, &state.loop_handle) {
-        log::warn!("Failed to initialize XWayland: {}", e);
+    if !toggles.xwayland {
+        state.startup_report.record("XWayland", crate::profile::SubsystemState::Disabled);
+    } else if let Err(e) = state.xwayland_manager.init(&state.display_handle, &state.loop_handle) {
         log::info!("Continuing without X11 application support");
+        state.startup_report.record("XWayland", crate::profile::SubsystemState::Failed(e.to_string()));
     } else {
-        log::info!("XWayland initialized successfully");
+        state.startup_report.record("XWayland", crate::profile::SubsystemState::Enabled);
+    }
+    
+    // The AI and automation services live in the service registry, which
+    // main builds after exporting the profile
+    let ai_running = state.services.contains::<horizonos_graph_ai::AIService>();
+    let automation_running = state.services.contains::<horizonos_graph_ai::automation::scheduler::WorkflowScheduler>();
+    for (name, enabled) in [("AI services", ai_running), ("Automation", automation_running)] {
+        let subsystem_state = if enabled {
+            crate::profile::SubsystemState::Enabled
+        } else {
+            crate::profile::SubsystemState::Disabled
+        };
+        state.startup_report.record(name, subsystem_state);
     }
 }
 
+/// Accept Wayland clients on a new socket and name it in `WAYLAND_DISPLAY`
+///
+/// Clients are served whenever the event loop of `state` is dispatched.
+/// Applications started afterwards, like the kiosk application, connect
+/// to this compositor.
+pub fn init_wayland_socket(state: &mut AppState, display: Display<AppState>) -> Result<OsString, CompositorError> {
+    let socket = ListeningSocketSource::new_auto()
+        .map_err(|e| CompositorError::DisplayCreation(format!("Could not bind a Wayland socket: {}", e)))?;
+    let socket_name = socket.socket_name().to_os_string();
+    state.loop_handle
+        .insert_source(socket, |stream, _, state| {
+            if let Err(e) = state.display_handle.insert_client(stream, Arc::new(ClientState::default())) {
+                log::warn!("Failed to add client: {}", e);
+            }
+        })
+        .map_err(|e| e.error)?;
+    state.loop_handle
+        .insert_source(Generic::new(display, Interest::READ, Mode::Level), |_, display, state| {
+            // Safety: the display is owned by the event loop and never dropped while it runs
+            if let Err(e) = unsafe { display.get_mut().dispatch_clients(state) } {
+                log::warn!("Failed to dispatch clients: {}", e);
+            }
+            Ok(PostAction::Continue)
+        })
+        .map_err(|e| e.error)?;
+    
+    std::env::set_var("WAYLAND_DISPLAY", &socket_name);
+    log::info!("Listening on Wayland socket {}", socket_name.to_string_lossy());
+    Ok(socket_name)
+}
+
+/// Start the kiosk application of the kiosk profile
+///
+/// Must run after [`init_wayland_socket`], so the application connects to
+/// this compositor rather than whatever `WAYLAND_DISPLAY` named before.
+pub fn launch_kiosk(state: &mut AppState) {
+    // Kiosk mode runs a single application and nothing else
+    if state.profile.profile == crate::profile::StartupProfile::Kiosk {
+        let command = state.profile.kiosk_app.clone().unwrap_or_default();
+        state.startup_report.record_result("Kiosk application", true, || launch_kiosk_app(&command));
+    }
+}
+
+/// Launch the kiosk application as a client of this compositor
+fn launch_kiosk_app(command: &str) -> Result<(), crate::CompositorError> {
+    let mut parts = command.split_whitespace();
+    let program = parts.next()
+        .ok_or_else(|| crate::CompositorError::Config("Empty kiosk application command".to_string()))?;
+    std::process::Command::new(program).args(parts).spawn()?;
+    log::info!("Launched kiosk application: {}", command);
+    Ok(())
+}
+
 /// Handle window creation
 pub fn handle_new_window(state: &mut AppState, window: Window) {
     use horizonos_graph_engine::{SceneNode, NodeType};
@@ -47,12 +122,15 @@ pub fn handle_new_window(state: &mut AppState, window: Window) {
             tags: vec![],
             description: None,
             properties: std::collections::HashMap::new(),
+            privacy: Default::default(),
+            // Windows come back with a crashed session, not with a saved graph
+            persistence: horizonos_graph_engine::NodePersistence::Session,
         },
         visible: true,
         selected: false,
     };
     
-    let node_id = state.graph_scene.lock().unwrap().add_node(node);
+    let node_id = state.add_graph_node(node);
     
     // Add to space
     state.space.map_element(window.clone(), (0, 0), true);
@@ -133,12 +211,15 @@ pub fn handle_new_x11_window(state: &mut AppState, surface: &smithay::xwayland::
             tags: vec!["x11".to_string()],
             description: x11_node.class.clone(),
             properties: std::collections::HashMap::new(),
+            privacy: Default::default(),
+            // Windows come back with a crashed session, not with a saved graph
+            persistence: horizonos_graph_engine::NodePersistence::Session,
         },
         visible: true,
         selected: false,
     };
     
-    let node_id = state.graph_scene.lock().unwrap().add_node(node);
+    let node_id = state.add_graph_node(node);
     log::debug!("Created graph node {} for X11 window {}", node_id, window_id);
     
     // Handle the X11 surface in the manager
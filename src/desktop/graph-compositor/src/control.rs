@@ -0,0 +1,408 @@
+//! Control API requests answered by the compositor
+//!
+//! The control server runs on the services' runtime, but the scene and the
+//! workspaces belong to the compositor thread. [`CompositorControl`] hands
+//! each request over as a [`ControlCall`] and waits for the reply, which
+//! `AppState::dispatch_control` sends once per frame.
+
+use async_trait::async_trait;
+use horizonos_graph_ctl::{ApiError, ControlHandler, DesktopEvent, ErrorCode, ExportFormat, Request};
+use horizonos_graph_engine::layout::HierarchicalLayout;
+use horizonos_graph_engine::{CircularLayout, ForceDirectedLayout, LayoutAlgorithm, LayoutConfig, Position, Scene, SceneNode};
+use horizonos_graph_nodes::hooks::node_type_name;
+use horizonos_graph_nodes::query::edge_type_name;
+use horizonos_graph_nodes::{ConceptNode, FileNode, GraphNode, SearchDocument, TaskNode, UrlNode};
+use horizonos_graph_workspaces::WorkspaceEvent;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use tokio::sync::{broadcast, oneshot};
+
+/// Paths a single import may create nodes for
+pub const MAX_IMPORT: usize = 10_000;
+
+/// A control request waiting for the compositor thread
+pub struct ControlCall {
+    pub request: Request,
+    pub reply: oneshot::Sender<Result<Value, ApiError>>,
+}
+
+/// Control handler forwarding requests to the compositor thread
+pub struct CompositorControl {
+    calls: mpsc::Sender<ControlCall>,
+}
+
+impl CompositorControl {
+    /// Handler and the receiving end for `AppState::attach_control`
+    pub fn new() -> (Self, mpsc::Receiver<ControlCall>) {
+        let (calls, receiver) = mpsc::channel();
+        (Self { calls }, receiver)
+    }
+}
+
+#[async_trait]
+impl ControlHandler for CompositorControl {
+    async fn handle(&self, request: Request) -> Result<Value, ApiError> {
+        let (reply, answer) = oneshot::channel();
+        self.calls
+            .send(ControlCall { request, reply })
+            .map_err(|_| ApiError::failed("The compositor is shutting down"))?;
+        answer.await.map_err(|_| ApiError::failed("The compositor dropped the request"))?
+    }
+}
+
+/// Scene node for a `create_node` request
+pub fn new_node(node_type: &str, name: &str, path: Option<&str>, position: Option<[f32; 3]>) -> Result<SceneNode, ApiError> {
+    let mut node = match node_type {
+        "file" => {
+            let path = path.ok_or_else(|| ApiError::invalid("File nodes need a path"))?;
+            file_node(Path::new(path))?
+        }
+        "concept" => ConceptNode::new(0).with_content(name.to_string(), String::new()).to_scene_node(),
+        "task" => TaskNode::new(0, name.to_string()).to_scene_node(),
+        "url" => {
+            let url = path.ok_or_else(|| ApiError::invalid("URL nodes need a URL as path"))?;
+            UrlNode::with_title(0, url.to_string(), name.to_string()).to_scene_node()
+        }
+        other => {
+            return Err(ApiError::new(
+                ErrorCode::Unsupported,
+                format!("Cannot create '{}' nodes, expected file, concept, task or url", other),
+            ))
+        }
+    };
+    if node_type == "file" && !name.is_empty() {
+        node.metadata.properties.insert("title".to_string(), name.to_string());
+    }
+    if let Some([x, y, z]) = position {
+        node.position = Position::new(x, y, z);
+    }
+    Ok(node)
+}
+
+/// File node for an existing path
+pub fn file_node(path: &Path) -> Result<SceneNode, ApiError> {
+    FileNode::new(0, path.to_path_buf())
+        .map(|node| node.to_scene_node())
+        .map_err(|e| ApiError::invalid(format!("Cannot import {}: {}", path.display(), e)))
+}
+
+/// Paths an `import_files` request creates nodes for, directories before their contents
+pub fn import_paths(paths: &[PathBuf], recursive: bool) -> Result<Vec<PathBuf>, ApiError> {
+    let mut found = Vec::new();
+    for path in paths {
+        collect_path(path, recursive, &mut found)?;
+    }
+    Ok(found)
+}
+
+fn collect_path(path: &Path, recursive: bool, found: &mut Vec<PathBuf>) -> Result<(), ApiError> {
+    if found.len() >= MAX_IMPORT {
+        return Err(ApiError::invalid(format!("Refusing to import more than {} paths at once", MAX_IMPORT)));
+    }
+    if !path.exists() {
+        return Err(ApiError::not_found(format!("{} does not exist", path.display())));
+    }
+    found.push(path.to_path_buf());
+    // Symlinked directories are imported as nodes but not followed
+    if recursive && path.is_dir() && !path.is_symlink() {
+        let entries = std::fs::read_dir(path).map_err(|e| ApiError::failed(format!("Cannot read {}: {}", path.display(), e)))?;
+        let mut children: Vec<PathBuf> = entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect();
+        children.sort();
+        for child in children {
+            collect_path(&child, recursive, found)?;
+        }
+    }
+    Ok(())
+}
+
+/// Summary of a node as listed by queries and exports
+pub fn node_summary(node: &SceneNode) -> Value {
+    let document = SearchDocument::from_scene_node(node);
+    json!({
+        "id": node.id,
+        "name": document.display_name,
+        "node_type": node_type_name(&node.node_type),
+        "position": [node.position.x, node.position.y, node.position.z],
+    })
+}
+
+/// Nodes matching a `query` request, best first
+pub fn query_nodes(scene: &Scene, query: &str, limit: usize) -> Vec<Value> {
+    let documents: Vec<SearchDocument> = scene.nodes().map(|(_, node)| SearchDocument::from_scene_node(node)).collect();
+    horizonos_graph_nodes::search::search_documents(&documents, query, limit)
+        .into_iter()
+        .filter_map(|hit| scene.get_node(hit.node_id))
+        .map(node_summary)
+        .collect()
+}
+
+/// Layout algorithm by the name horizonctl passes
+pub fn layout_algorithm(name: &str) -> Result<Box<dyn LayoutAlgorithm<Config = LayoutConfig>>, ApiError> {
+    let algorithm: Box<dyn LayoutAlgorithm<Config = LayoutConfig>> = match name.to_lowercase().replace('_', "-").as_str() {
+        "force-directed" | "force" => Box::new(ForceDirectedLayout::new().map_err(|e| ApiError::failed(e.to_string()))?),
+        "circular" => Box::new(CircularLayout::new().map_err(|e| ApiError::failed(e.to_string()))?),
+        "hierarchical" => Box::new(HierarchicalLayout::new().map_err(|e| ApiError::failed(e.to_string()))?),
+        _ => {
+            return Err(ApiError::new(
+                ErrorCode::Unsupported,
+                format!("Unknown layout '{}', expected force-directed, circular or hierarchical", name),
+            ))
+        }
+    };
+    Ok(algorithm)
+}
+
+/// Run a layout on `nodes` only, or on the whole scene when empty
+///
+/// The chosen nodes and the edges between them are laid out on their own;
+/// the other nodes keep their positions. Returns the nodes that were laid out.
+pub fn apply_layout(scene: &mut Scene, algorithm: &str, nodes: &[u64]) -> Result<Vec<u64>, ApiError> {
+    let mut algorithm = layout_algorithm(algorithm)?;
+    let config = LayoutConfig::default();
+    if nodes.is_empty() {
+        algorithm.apply_layout(scene, &config).map_err(|e| ApiError::failed(e.to_string()))?;
+        return Ok(scene.get_all_nodes());
+    }
+
+    if let Some(missing) = nodes.iter().find(|id| scene.get_node(**id).is_none()) {
+        return Err(ApiError::not_found(format!("No node {}", missing)));
+    }
+    let mut subset = Scene::new();
+    for id in nodes {
+        if let Some(node) = scene.get_node(*id) {
+            subset.insert_node(node.clone());
+        }
+    }
+    for edge in scene.edges().filter(|edge| nodes.contains(&edge.source) && nodes.contains(&edge.target)) {
+        subset.insert_edge(edge.clone());
+    }
+    algorithm.apply_layout(&mut subset, &config).map_err(|e| ApiError::failed(e.to_string()))?;
+    for (id, laid_out) in subset.nodes() {
+        if let Some(node) = scene.get_node_mut(*id) {
+            node.position = laid_out.position;
+        }
+    }
+    Ok(nodes.to_vec())
+}
+
+/// Graph export in one of the control API formats
+pub fn export_graph(scene: &Scene, format: ExportFormat) -> Result<String, ApiError> {
+    let mut nodes: Vec<&SceneNode> = scene.nodes().map(|(_, node)| node).collect();
+    nodes.sort_by_key(|node| node.id);
+    let mut edges: Vec<_> = scene.edges().collect();
+    edges.sort_by_key(|edge| edge.id);
+
+    Ok(match format {
+        ExportFormat::Json => {
+            let graph = json!({
+                "nodes": nodes.iter().map(|node| node_summary(node)).collect::<Vec<_>>(),
+                "edges": edges.iter().map(|edge| json!({
+                    "id": edge.id,
+                    "source": edge.source,
+                    "target": edge.target,
+                    "edge_type": edge_type_name(&edge.edge_type),
+                    "weight": edge.weight,
+                })).collect::<Vec<_>>(),
+            });
+            serde_json::to_string_pretty(&graph).map_err(|e| ApiError::failed(e.to_string()))?
+        }
+        ExportFormat::Dot => {
+            let mut dot = String::from("digraph horizonos {\n");
+            for node in &nodes {
+                let name = SearchDocument::from_scene_node(node).display_name;
+                dot.push_str(&format!(
+                    "  {} [label=\"{}\", node_type=\"{}\"];\n",
+                    node.id,
+                    escape_dot(&name),
+                    node_type_name(&node.node_type)
+                ));
+            }
+            for edge in &edges {
+                dot.push_str(&format!(
+                    "  {} -> {} [label=\"{}\", weight={}];\n",
+                    edge.source,
+                    edge.target,
+                    edge_type_name(&edge.edge_type),
+                    edge.weight
+                ));
+            }
+            dot.push_str("}\n");
+            dot
+        }
+        ExportFormat::GraphMl => {
+            let mut xml = String::from(concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+                "  <key id=\"name\" for=\"node\" attr.name=\"name\" attr.type=\"string\"/>\n",
+                "  <key id=\"node_type\" for=\"node\" attr.name=\"node_type\" attr.type=\"string\"/>\n",
+                "  <key id=\"edge_type\" for=\"edge\" attr.name=\"edge_type\" attr.type=\"string\"/>\n",
+                "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\n",
+                "  <graph id=\"horizonos\" edgedefault=\"directed\">\n",
+            ));
+            for node in &nodes {
+                let name = SearchDocument::from_scene_node(node).display_name;
+                xml.push_str(&format!(
+                    "    <node id=\"n{}\"><data key=\"name\">{}</data><data key=\"node_type\">{}</data></node>\n",
+                    node.id,
+                    escape_xml(&name),
+                    node_type_name(&node.node_type)
+                ));
+            }
+            for edge in &edges {
+                xml.push_str(&format!(
+                    "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\"><data key=\"edge_type\">{}</data><data key=\"weight\">{}</data></edge>\n",
+                    edge.id,
+                    edge.source,
+                    edge.target,
+                    edge_type_name(&edge.edge_type),
+                    edge.weight
+                ));
+            }
+            xml.push_str("  </graph>\n</graphml>\n");
+            xml
+        }
+    })
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Desktop event announcing a workspace change to subscribers
+pub fn workspace_event(event: &WorkspaceEvent) -> Option<DesktopEvent> {
+    match event {
+        WorkspaceEvent::Created { workspace_id } => Some(DesktopEvent::new("workspace.created", json!({ "id": workspace_id }))),
+        WorkspaceEvent::Deleted { workspace_id } => Some(DesktopEvent::new("workspace.deleted", json!({ "id": workspace_id }))),
+        WorkspaceEvent::Switched { from, to } => {
+            Some(DesktopEvent::new("workspace.switched", json!({ "from": from, "id": to })))
+        }
+        _ => None,
+    }
+}
+
+/// Pass workspace changes on to control API subscribers until either side closes
+pub async fn forward_workspace_events(
+    mut workspace_events: broadcast::Receiver<WorkspaceEvent>,
+    events: broadcast::Sender<DesktopEvent>,
+) {
+    loop {
+        match workspace_events.recv().await {
+            Ok(event) => {
+                if let Some(event) = workspace_event(&event) {
+                    // No subscribers is not an error
+                    let _ = events.send(event);
+                }
+            }
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                log::debug!("Control API missed {} workspace events", missed);
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::{EdgeType, SceneEdge};
+
+    fn scene() -> (Scene, u64, u64) {
+        let mut scene = Scene::new();
+        let notes = scene.add_node(new_node("concept", "Notes & \"ideas\"", None, Some([1.0, 2.0, 3.0])).unwrap());
+        let task = scene.add_node(new_node("task", "Write report", None, None).unwrap());
+        scene.add_edge(SceneEdge {
+            id: 0,
+            source: notes,
+            target: task,
+            edge_type: EdgeType::DependsOn,
+            weight: 0.5,
+            color: [1.0; 4],
+            visible: true,
+            animated: false,
+        });
+        (scene, notes, task)
+    }
+
+    #[test]
+    fn test_new_nodes_by_type() {
+        let node = new_node("concept", "Notes", None, Some([1.0, 2.0, 3.0])).unwrap();
+        assert_eq!(node_type_name(&node.node_type), "concept");
+        assert_eq!(node.position, Position::new(1.0, 2.0, 3.0));
+        assert_eq!(node_summary(&node)["name"], "Notes");
+
+        let url = new_node("url", "Docs", Some("https://example.org"), None).unwrap();
+        assert_eq!(node_summary(&url)["name"], "Docs");
+
+        assert_eq!(new_node("file", "x", None, None).unwrap_err().code, ErrorCode::InvalidRequest);
+        assert_eq!(new_node("url", "x", None, None).unwrap_err().code, ErrorCode::InvalidRequest);
+        assert_eq!(new_node("person", "x", None, None).unwrap_err().code, ErrorCode::Unsupported);
+    }
+
+    #[test]
+    fn test_import_paths_walks_directories_when_recursive() {
+        let dir = std::env::temp_dir().join(format!("horizonos-control-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("a.txt"), "a").unwrap();
+        std::fs::write(dir.join("sub").join("b.rs"), "b").unwrap();
+
+        assert_eq!(import_paths(std::slice::from_ref(&dir), false).unwrap(), vec![dir.clone()]);
+        let found = import_paths(std::slice::from_ref(&dir), true).unwrap();
+        assert_eq!(found, vec![dir.clone(), dir.join("a.txt"), dir.join("sub"), dir.join("sub").join("b.rs")]);
+        let node = file_node(&dir.join("a.txt")).unwrap();
+        assert_eq!(node_summary(&node)["name"], "a.txt");
+
+        let missing = import_paths(&[dir.join("missing")], false).unwrap_err();
+        assert_eq!(missing.code, ErrorCode::NotFound);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_query_and_layouts() {
+        let (mut scene, notes, task) = scene();
+        let hits = query_nodes(&scene, "report", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["id"], task);
+
+        assert_eq!(layout_algorithm("Force_Directed").unwrap().name(), ForceDirectedLayout::new().unwrap().name());
+        assert_eq!(apply_layout(&mut scene, "spiral", &[]).unwrap_err().code, ErrorCode::Unsupported);
+        assert_eq!(apply_layout(&mut scene, "circular", &[99]).unwrap_err().code, ErrorCode::NotFound);
+
+        // Nodes left out of a partial layout stay where they are
+        let before = scene.get_node(notes).unwrap().position;
+        assert_eq!(apply_layout(&mut scene, "circular", &[task]).unwrap(), vec![task]);
+        assert_eq!(scene.get_node(notes).unwrap().position, before);
+    }
+
+    #[test]
+    fn test_exports_escape_names() {
+        let (scene, notes, task) = scene();
+        let json: Value = serde_json::from_str(&export_graph(&scene, ExportFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["nodes"].as_array().unwrap().len(), 2);
+        assert_eq!(json["edges"][0]["edge_type"], "depends_on");
+
+        let dot = export_graph(&scene, ExportFormat::Dot).unwrap();
+        assert!(dot.contains("[label=\"Notes & \\\"ideas\\\"\""));
+        assert!(dot.contains(&format!("{} -> {}", notes, task)));
+
+        let graphml = export_graph(&scene, ExportFormat::GraphMl).unwrap();
+        assert!(graphml.contains("Notes &amp; &quot;ideas&quot;"));
+        assert!(graphml.contains(&format!("source=\"n{}\" target=\"n{}\"", notes, task)));
+    }
+
+    #[test]
+    fn test_workspace_events_for_subscribers() {
+        let event = workspace_event(&WorkspaceEvent::Switched { from: None, to: "research".to_string() }).unwrap();
+        assert_eq!(event.kind, "workspace.switched");
+        assert_eq!(event.data["id"], "research");
+        assert!(workspace_event(&WorkspaceEvent::Modified { workspace_id: "research".to_string() }).is_none());
+    }
+}
@@ -2,16 +2,25 @@
 
 use smithay::{
     backend::input::{
-        Event, InputBackend, InputEvent, KeyState, KeyboardKeyEvent,
+        Axis, AxisSource, ButtonState, Event, InputBackend, InputEvent, KeyState, KeyboardKeyEvent, MouseButton,
         PointerMotionEvent, PointerButtonEvent, PointerAxisEvent,
+        GestureBeginEvent, GestureEndEvent, GesturePinchUpdateEvent, GestureSwipeUpdateEvent,
     },
     input::{
-        keyboard::{keysyms, FilterResult},
+        keyboard::{keysyms, xkb, FilterResult, Keycode, Keysym, ModifiersState},
         pointer::{AxisFrame, ButtonEvent, MotionEvent},
     },
     utils::{Point, Serial, SERIAL_COUNTER},
 };
 use crate::AppState;
+use crate::portal::PickerKey;
+use crate::prompt::PromptKey;
+use crate::quick_look::QuickLookKey;
+use crate::remote::RemoteInputEvent;
+use crate::switcher::SwitcherKey;
+use horizonos_graph_accessibility::keyboard_nav::{ArrowDirection, Key, Modifier};
+use horizonos_graph_notifications::ReviewCommand;
+use horizonos_graph_interaction::{ChordKey, ChordModifiers, KeyChord, TrackpadGesture};
 
 /// Process input events
 pub fn process_input_event<I: InputBackend>(
@@ -30,15 +39,121 @@ pub fn process_input_event<I: InputBackend>(
                 serial,
                 event.time_msec(),
                 |state, modifiers, handle| {
+                    // Consent prompts are modal and take all keys
+                    if state.prompts.current().is_some() {
+                        if event.state() == KeyState::Pressed {
+                            if let Some(key) = prompt_key(handle.modified_sym().raw()) {
+                                state.handle_prompt_key(key);
+                            }
+                        }
+                        return FilterResult::Intercept(());
+                    }
+                    
+                    // The portal's source picker is modal and takes all keys
+                    if state.portal.picker.is_some() {
+                        if event.state() == KeyState::Pressed {
+                            if let Some(key) = picker_key(handle.modified_sym().raw()) {
+                                state.handle_picker_key(key);
+                            }
+                        }
+                        return FilterResult::Intercept(());
+                    }
+                    
+                    // Notification review mode takes all keys until it is left
+                    if state.notification_review.is_active() {
+                        if event.state() == KeyState::Pressed {
+                            if let Some(command) = ReviewCommand::from_key(&xkb::keysym_get_name(handle.modified_sym())) {
+                                state.handle_review_command(command);
+                            }
+                        }
+                        return FilterResult::Intercept(());
+                    }
+                    
+                    // The window switcher takes all keys while open; releasing Alt switches
+                    if state.window_switcher_open() {
+                        let keysym = handle.modified_sym();
+                        match event.state() {
+                            KeyState::Released if is_alt(keysym.raw()) => {
+                                state.handle_switcher_key(SwitcherKey::Commit);
+                                return FilterResult::Forward;
+                            }
+                            KeyState::Pressed => {
+                                if let Some(key) = switcher_key(keysym, modifiers.shift) {
+                                    state.handle_switcher_key(key);
+                                }
+                            }
+                            KeyState::Released => {}
+                        }
+                        return FilterResult::Intercept(());
+                    }
+                    
+                    // Quick Look takes all keys while open
+                    if state.quick_look_open() {
+                        if event.state() == KeyState::Pressed {
+                            if let Some(key) = quick_look_key(handle.modified_sym().raw()) {
+                                state.handle_quick_look_key(key);
+                            }
+                        }
+                        return FilterResult::Intercept(());
+                    }
+                    
+                    // Alt+Tab opens the window switcher, Alt+Shift+Tab on the last window
+                    let keysym = handle.modified_sym().raw();
+                    if modifiers.alt && event.state() == KeyState::Pressed
+                        && matches!(keysym, keysyms::KEY_Tab | keysyms::KEY_ISO_Left_Tab)
+                    {
+                        state.open_window_switcher(modifiers.shift || keysym == keysyms::KEY_ISO_Left_Tab);
+                        return FilterResult::Intercept(());
+                    }
+                    
+                    // Super+N reads notifications one at a time
+                    if modifiers.logo && event.state() == KeyState::Pressed && handle.modified_sym().raw() == keysyms::KEY_n {
+                        state.toggle_notification_review();
+                        return FilterResult::Intercept(());
+                    }
+                    
                     // Check for compositor shortcuts
                     if modifiers.alt && event.state() == KeyState::Pressed {
-                        if handle.modified_sym().raw() == keysyms::KEY_q {
+                        if handle.modified_sym().raw() == keysyms::KEY_q
+                            && state.profile.subsystems().quit_shortcut
+                        {
                             // Alt+Q to quit
                             state.running = false;
                             return FilterResult::Intercept(());
                         }
                     }
                     
+                    // Keyboard graph navigation and connect mode (Shift+C) while no
+                    // window has the keyboard; connect mode then takes all keys
+                    if event.state() == KeyState::Pressed {
+                        let connecting = state.accessibility.as_ref().is_some_and(|a| a.edge_creator.is_active());
+                        let graph_focused = state.seat.get_keyboard().is_some_and(|k| k.current_focus().is_none());
+                        // Space previews a focused file node with Quick Look
+                        let plain = !(modifiers.ctrl || modifiers.alt || modifiers.logo || modifiers.shift);
+                        if graph_focused && !connecting && plain && handle.modified_sym().raw() == keysyms::KEY_space
+                            && state.open_quick_look()
+                        {
+                            return FilterResult::Intercept(());
+                        }
+                        if connecting || graph_focused {
+                            if let Some((key, key_modifiers)) = navigation_key(handle.modified_sym(), modifiers) {
+                                if state.handle_graph_key(key, key_modifiers) {
+                                    return FilterResult::Intercept(());
+                                }
+                            }
+                        }
+                    }
+                    
+                    // Configured shortcuts, e.g. Super+G to collapse the selected node's cluster
+                    if event.state() == KeyState::Pressed {
+                        if let Some(chord) = key_chord(handle.modified_sym(), modifiers) {
+                            let interaction = state.interaction_manager.lock().unwrap_or_else(|e| e.into_inner());
+                            if interaction.run_shortcut(&chord) {
+                                return FilterResult::Intercept(());
+                            }
+                        }
+                    }
+                    
                     FilterResult::Forward
                 },
             );
@@ -64,6 +179,26 @@ pub fn process_input_event<I: InputBackend>(
         InputEvent::PointerButton { event } => {
             let pointer = state.seat.get_pointer().unwrap();
             
+            // Clicks on the minimap move the camera instead of reaching clients
+            if event.button() == Some(MouseButton::Left) && event.state() == ButtonState::Pressed {
+                let location = pointer.current_location();
+                if let Some(point) = state.minimap_point((location.x as f32, location.y as f32)) {
+                    state.minimap_clicks.push(point);
+                    return;
+                }
+                // Super+drag moves a window's node, across outputs too
+                let logo = state.seat.get_keyboard().is_some_and(|keyboard| keyboard.modifier_state().logo);
+                if logo && state.begin_node_drag(location) {
+                    return;
+                }
+            }
+            if event.button() == Some(MouseButton::Left)
+                && event.state() == ButtonState::Released
+                && state.end_node_drag(pointer.current_location())
+            {
+                return;
+            }
+            
             pointer.button(
                 state,
                 &ButtonEvent {
@@ -74,6 +209,266 @@ pub fn process_input_event<I: InputBackend>(
                 },
             );
         }
+        // Trackpad gestures drive the graph camera and workspaces, they are not forwarded to clients
+        InputEvent::GesturePinchBegin { event } => {
+            state.trackpad_gestures.push(TrackpadGesture::PinchBegin { fingers: event.fingers() });
+        }
+        InputEvent::GesturePinchUpdate { event } => {
+            state.trackpad_gestures.push(TrackpadGesture::PinchUpdate {
+                delta: (event.delta_x() as f32, event.delta_y() as f32),
+                scale: event.scale() as f32,
+                rotation: event.rotation() as f32,
+            });
+        }
+        InputEvent::GesturePinchEnd { event } => {
+            state.trackpad_gestures.push(TrackpadGesture::PinchEnd { cancelled: event.cancelled() });
+        }
+        InputEvent::GestureSwipeBegin { event } => {
+            state.trackpad_gestures.push(TrackpadGesture::SwipeBegin { fingers: event.fingers() });
+        }
+        InputEvent::GestureSwipeUpdate { event } => {
+            state.trackpad_gestures.push(TrackpadGesture::SwipeUpdate {
+                delta: (event.delta_x() as f32, event.delta_y() as f32),
+            });
+        }
+        InputEvent::GestureSwipeEnd { event } => {
+            state.trackpad_gestures.push(TrackpadGesture::SwipeEnd { cancelled: event.cancelled() });
+        }
         _ => {} // Handle other events as needed
     }
-}
\ No newline at end of file
+}
+
+/// Chord of a key with the modifiers held, for matching configured shortcuts
+fn key_chord(keysym: Keysym, modifiers: &ModifiersState) -> Option<KeyChord> {
+    let modifiers = ChordModifiers {
+        ctrl: modifiers.ctrl,
+        alt: modifiers.alt,
+        shift: modifiers.shift,
+        logo: modifiers.logo,
+    };
+    match keysym.key_char().filter(|c| !c.is_control()) {
+        Some(c) => Some(KeyChord::new(ChordKey::Char(c), modifiers)),
+        None => KeyChord::named(&xkb::keysym_get_name(keysym), modifiers),
+    }
+}
+
+/// Key and modifiers as keyboard graph navigation names them
+fn navigation_key(keysym: Keysym, modifiers: &ModifiersState) -> Option<(Key, Vec<Modifier>)> {
+    let mut held = Vec::new();
+    if modifiers.shift || keysym.raw() == keysyms::KEY_ISO_Left_Tab {
+        held.push(Modifier::Shift);
+    }
+    if modifiers.ctrl {
+        held.push(Modifier::Ctrl);
+    }
+    if modifiers.alt {
+        held.push(Modifier::Alt);
+    }
+    if modifiers.logo {
+        held.push(Modifier::Meta);
+    }
+    let key = match keysym.raw() {
+        keysyms::KEY_Tab | keysyms::KEY_ISO_Left_Tab => Key::Tab,
+        keysyms::KEY_Up => Key::Arrow(ArrowDirection::Up),
+        keysyms::KEY_Down => Key::Arrow(ArrowDirection::Down),
+        keysyms::KEY_Left => Key::Arrow(ArrowDirection::Left),
+        keysyms::KEY_Right => Key::Arrow(ArrowDirection::Right),
+        keysyms::KEY_Home => Key::Home,
+        keysyms::KEY_End => Key::End,
+        keysyms::KEY_Page_Up => Key::PageUp,
+        keysyms::KEY_Page_Down => Key::PageDown,
+        keysyms::KEY_Return | keysyms::KEY_KP_Enter => Key::Enter,
+        keysyms::KEY_space => Key::Space,
+        keysyms::KEY_Escape => Key::Escape,
+        keysyms::KEY_F1 => Key::F1,
+        keysyms::KEY_F2 => Key::F2,
+        keysyms::KEY_F3 => Key::F3,
+        keysyms::KEY_F4 => Key::F4,
+        keysyms::KEY_F5 => Key::F5,
+        keysyms::KEY_F6 => Key::F6,
+        keysyms::KEY_F7 => Key::F7,
+        keysyms::KEY_F8 => Key::F8,
+        keysyms::KEY_F9 => Key::F9,
+        keysyms::KEY_F10 => Key::F10,
+        keysyms::KEY_F11 => Key::F11,
+        keysyms::KEY_F12 => Key::F12,
+        _ => Key::Character(keysym.key_char().filter(|c| !c.is_control())?),
+    };
+    Some((key, held))
+}
+
+/// Whether a keysym is one of the Alt keys
+fn is_alt(keysym: u32) -> bool {
+    matches!(keysym, keysyms::KEY_Alt_L | keysyms::KEY_Alt_R | keysyms::KEY_Meta_L | keysyms::KEY_Meta_R)
+}
+
+/// Switcher action bound to a key, typed characters filtering the windows
+fn switcher_key(keysym: Keysym, shift: bool) -> Option<SwitcherKey> {
+    match keysym.raw() {
+        keysyms::KEY_Tab if shift => Some(SwitcherKey::Previous),
+        keysyms::KEY_Tab | keysyms::KEY_Right | keysyms::KEY_Down => Some(SwitcherKey::Next),
+        keysyms::KEY_ISO_Left_Tab | keysyms::KEY_Left | keysyms::KEY_Up => Some(SwitcherKey::Previous),
+        keysyms::KEY_Escape => Some(SwitcherKey::Cancel),
+        keysyms::KEY_BackSpace => Some(SwitcherKey::Erase),
+        _ => keysym.key_char().filter(|c| !c.is_control()).map(SwitcherKey::Type),
+    }
+}
+
+/// Quick Look action bound to a key
+fn quick_look_key(keysym: u32) -> Option<QuickLookKey> {
+    match keysym {
+        keysyms::KEY_Right | keysyms::KEY_Down => Some(QuickLookKey::Next),
+        keysyms::KEY_Left | keysyms::KEY_Up => Some(QuickLookKey::Previous),
+        keysyms::KEY_space | keysyms::KEY_Escape => Some(QuickLookKey::Close),
+        _ => None,
+    }
+}
+
+/// Picker action bound to a key
+fn picker_key(keysym: u32) -> Option<PickerKey> {
+    match keysym {
+        keysyms::KEY_Right | keysyms::KEY_Down | keysyms::KEY_Tab => Some(PickerKey::Next),
+        keysyms::KEY_Left | keysyms::KEY_Up | keysyms::KEY_ISO_Left_Tab => Some(PickerKey::Previous),
+        keysyms::KEY_space => Some(PickerKey::Toggle),
+        keysyms::KEY_Return | keysyms::KEY_KP_Enter => Some(PickerKey::Confirm),
+        keysyms::KEY_Escape => Some(PickerKey::Cancel),
+        _ => None,
+    }
+}
+
+/// Prompt answer bound to a key
+fn prompt_key(keysym: u32) -> Option<PromptKey> {
+    match keysym {
+        keysyms::KEY_y | keysyms::KEY_Y | keysyms::KEY_Return | keysyms::KEY_KP_Enter => Some(PromptKey::Allow),
+        keysyms::KEY_a | keysyms::KEY_A => Some(PromptKey::AllowAlways),
+        keysyms::KEY_n | keysyms::KEY_N | keysyms::KEY_Escape => Some(PromptKey::Deny),
+        _ => None,
+    }
+}
+
+/// Inject an input event from a remote viewer whose control was accepted locally
+pub fn process_remote_input(state: &mut AppState, event: RemoteInputEvent) {
+    let time = remote_input_time();
+    
+    match event {
+        RemoteInputEvent::PointerMotion { x, y } => {
+            let pointer = state.seat.get_pointer().unwrap();
+            pointer.motion(
+                state,
+                None, // TODO: Find surface under pointer
+                &MotionEvent {
+                    location: Point::from((x, y)),
+                    serial: SERIAL_COUNTER.next_serial(),
+                    time,
+                },
+            );
+            pointer.frame(state);
+        }
+        RemoteInputEvent::PointerButton { button, pressed } => {
+            let pointer = state.seat.get_pointer().unwrap();
+            pointer.button(
+                state,
+                &ButtonEvent {
+                    button,
+                    state: if pressed { ButtonState::Pressed } else { ButtonState::Released },
+                    serial: SERIAL_COUNTER.next_serial(),
+                    time,
+                },
+            );
+            pointer.frame(state);
+        }
+        RemoteInputEvent::Key { keycode, pressed } => {
+            let keyboard = state.seat.get_keyboard().unwrap();
+            // XKB keycodes are offset by 8 from evdev codes
+            keyboard.input::<(), _>(
+                state,
+                Keycode::new(keycode + 8),
+                if pressed { KeyState::Pressed } else { KeyState::Released },
+                SERIAL_COUNTER.next_serial(),
+                time,
+                |_, _, _| FilterResult::Forward,
+            );
+        }
+        RemoteInputEvent::Scroll { horizontal, vertical } => {
+            let pointer = state.seat.get_pointer().unwrap();
+            let mut frame = AxisFrame::new(time).source(AxisSource::Wheel);
+            if horizontal != 0.0 {
+                frame = frame.value(Axis::Horizontal, horizontal);
+            }
+            if vertical != 0.0 {
+                frame = frame.value(Axis::Vertical, vertical);
+            }
+            pointer.axis(state, frame);
+            pointer.frame(state);
+        }
+    }
+}
+
+/// Millisecond timestamp for synthesized input events
+fn remote_input_time() -> u32 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u32)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logo() -> ModifiersState {
+        ModifiersState { logo: true, ..Default::default() }
+    }
+
+    #[test]
+    fn test_keys_match_configured_chords() {
+        let chord = |keysym: u32, modifiers: ModifiersState| key_chord(Keysym::new(keysym), &modifiers);
+        assert_eq!(chord(keysyms::KEY_g, logo()), Some("Super+G".parse().unwrap()));
+        // Shift does not change a chord held with Super
+        let shifted = ModifiersState { shift: true, ..logo() };
+        assert_eq!(chord(keysyms::KEY_G, shifted), Some("Super+G".parse().unwrap()));
+        assert_eq!(chord(keysyms::KEY_F5, ModifiersState::default()), Some("F5".parse().unwrap()));
+        assert_eq!(chord(keysyms::KEY_Escape, logo()), Some("Super+Escape".parse().unwrap()));
+        assert_eq!(chord(keysyms::KEY_Shift_L, ModifiersState::default()), None);
+    }
+
+    #[test]
+    fn test_keys_for_graph_navigation() {
+        let key = |keysym: u32, modifiers: ModifiersState| navigation_key(Keysym::new(keysym), &modifiers);
+        let shift = ModifiersState { shift: true, ..Default::default() };
+        // Shift+C enters connect mode
+        assert_eq!(key(keysyms::KEY_C, shift), Some((Key::Character('C'), vec![Modifier::Shift])));
+        assert_eq!(key(keysyms::KEY_Right, ModifiersState::default()), Some((Key::Arrow(ArrowDirection::Right), vec![])));
+        assert_eq!(key(keysyms::KEY_KP_Enter, ModifiersState::default()), Some((Key::Enter, vec![])));
+        // Shift+Tab arrives as ISO_Left_Tab, sometimes without Shift
+        assert_eq!(key(keysyms::KEY_ISO_Left_Tab, ModifiersState::default()), Some((Key::Tab, vec![Modifier::Shift])));
+        assert_eq!(key(keysyms::KEY_Escape, logo()), Some((Key::Escape, vec![Modifier::Meta])));
+        assert_eq!(key(keysyms::KEY_Shift_L, shift), None);
+    }
+
+    /// Actions the configured shortcuts of the default configuration run for these keys
+    fn shortcut_actions(keys: &[u32]) -> Vec<String> {
+        let actions = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut interaction = horizonos_graph_interaction::InteractionManager::new();
+        interaction.configure_keys(&horizonos_graph_config::GraphDesktopConfig::default());
+        let ran = actions.clone();
+        interaction.on_shortcut(move |action| ran.lock().unwrap().push(action.to_string()));
+        for &keysym in keys {
+            let chord = key_chord(Keysym::new(keysym), &logo()).unwrap();
+            assert!(interaction.run_shortcut(&chord), "nothing bound to {}", chord);
+        }
+        let actions = actions.lock().unwrap().clone();
+        actions
+    }
+
+    #[test]
+    fn test_camera_bookmark_keys_run_their_shortcuts() {
+        let actions = shortcut_actions(&[keysyms::KEY_b, keysyms::KEY_bracketright, keysyms::KEY_bracketleft]);
+        assert_eq!(actions, ["save_camera_bookmark", "next_camera_bookmark", "previous_camera_bookmark"]);
+    }
+
+    #[test]
+    fn test_minimap_key_runs_its_shortcut() {
+        assert_eq!(shortcut_actions(&[keysyms::KEY_m]), ["toggle_minimap"]);
+    }
+}
@@ -12,7 +12,20 @@ pub mod window_manager;
 pub mod protocols;
 pub mod state;
 pub mod render;
+pub mod remote;
 pub mod xwayland;
+pub mod profile;
+pub mod security;
+pub mod services;
+pub mod portal;
+pub mod prompt;
+pub mod control;
+pub mod automation;
+pub mod video;
+pub mod preload;
+pub mod switcher;
+pub mod audio;
+pub mod quick_look;
 
 pub use compositor::*;
 pub use backend::*;
@@ -39,6 +52,12 @@ pub enum CompositorError {
     #[error("Protocol error: {0}")]
     Protocol(String),
     
+    #[error("Configuration error: {0}")]
+    Config(String),
+    
+    #[error("Desktop portal error: {0}")]
+    Portal(String),
+    
     #[error("System error: {0}")]
     System(#[from] std::io::Error),
 }
\ No newline at end of file
@@ -1,9 +1,22 @@
 //! Graph Desktop Compositor Executable
 
-use horizonos_graph_compositor::{AppState, backend};
+use horizonos_graph_compositor::{AppState, backend, init_compositor, init_wayland_socket, launch_kiosk, remote::RemoteViewConfig};
+use horizonos_graph_compositor::backend::HeadlessConfig;
+use horizonos_graph_compositor::profile::{ProfileSettings, StartupProfile, SubsystemState};
+use horizonos_graph_compositor::control::{forward_workspace_events, CompositorControl};
+use horizonos_graph_compositor::portal::PortalServer;
+use horizonos_graph_compositor::preload::learn_workspace_switches;
+use horizonos_graph_accessibility::at_spi::AtSpiBus;
+use horizonos_graph_ctl::{ControlServer, Endpoint};
+use horizonos_graph_config::ConfigManager;
+use horizonos_graph_notifications::ProblemNotifier;
+use horizonos_graph_ai::AIService;
+use horizonos_graph_compositor::services::desktop_services;
 use smithay::reexports::wayland_server::Display;
 use calloop::EventLoop;
 use anyhow::Result;
+use std::sync::Arc;
+use std::time::Duration;
 
 fn main() -> Result<()> {
     // Initialize logging
@@ -25,12 +38,34 @@ fn main() -> Result<()> {
         println!("");
         println!("Options:");
         println!("  --software-render  Use software rendering (no GPU required)");
+        println!("  --remote-view     Stream the desktop to remote viewers");
+        println!("                    (token from HORIZONOS_REMOTE_TOKEN)");
+        println!("  --profile <name>  Startup profile (or HORIZONOS_PROFILE, or");
+        println!("                    profile in ~/.config/horizonos/startup.conf):");
+        for profile in StartupProfile::all() {
+            println!("                      {:8} {}", profile.name(), profile.description());
+        }
+        println!("  --kiosk-app <cmd> Application run by the kiosk profile");
+        println!("  --headless        Run without a display, rendering the graph off-screen");
+        println!("  --headless-size <WxH>    Size of the virtual output (default 1920x1080)");
+        println!("  --headless-frames <n>    Exit after rendering n frames");
+        println!("  --capture-dir <dir>      Write rendered frames there as PNG files");
         println!("  --help            Show this help message");
         return Ok(());
     }
     
+    let profile = ProfileSettings::select(&args)?;
+    log::info!("Using startup profile '{}' (from {})", profile.profile, profile.source);
+    
+    let remote_view = args.contains(&"--remote-view".to_string());
+    if remote_view && !profile.subsystems().remote_view {
+        anyhow::bail!("--remote-view is not available in the {} profile", profile.profile);
+    }
+    
+    let headless = HeadlessConfig::from_args(&args)?;
+    
     // For development, use winit backend with error handling
-    match run_winit_compositor() {
+    match run_winit_compositor(profile, remote_view, headless) {
         Ok(()) => Ok(()),
         Err(e) => {
             log::error!("Compositor error: {}", e);
@@ -48,26 +83,111 @@ fn main() -> Result<()> {
     }
 }
 
-fn run_winit_compositor() -> Result<()> {
-    // Initialize backend
-    let (backend, winit_event_loop) = backend::init_winit_backend()?;
+fn run_winit_compositor(profile: ProfileSettings, remote_view: bool, headless: Option<HeadlessConfig>) -> Result<()> {
+    // Initialize backend; headless runs need no window
+    let winit = match &headless {
+        Some(_) => None,
+        None => Some(backend::init_winit_backend()?),
+    };
     
     // Create event loop
-    let event_loop = EventLoop::<AppState>::try_new()?;
+    let mut event_loop = EventLoop::<AppState>::try_new()?;
     let loop_handle = event_loop.handle();
     
     // Create display
     let display: Display<AppState> = Display::new()?;
     let display_handle = display.handle();
     
+    // Start the desktop services; their background tasks run on this runtime
+    // and read the profile from the environment
+    profile.export_env();
+    let runtime = tokio::runtime::Runtime::new()?;
+    let services = Arc::new(runtime.block_on(desktop_services(&profile).build())?);
+    // Problems the services report show up as notifications
+    runtime.spawn(services.get::<ProblemNotifier>()?.run());
+    // Windows used together turn into suggested edges, badged on their nodes
+    if let Some(ai) = services.try_get::<AIService>() {
+        runtime.spawn(ai.run_edge_suggestions(Duration::from_secs(60)));
+    }
+    
     // Create compositor state  
-    let state = AppState::new(display_handle, loop_handle)?;
+    let mut state = AppState::with_profile(display_handle, loop_handle, profile, services.clone())?;
+    init_compositor(&mut state);
+    init_wayland_socket(&mut state, display)?;
+    launch_kiosk(&mut state);
+    
+    if remote_view {
+        let access_token = std::env::var("HORIZONOS_REMOTE_TOKEN")
+            .map_err(|_| anyhow::anyhow!("--remote-view requires HORIZONOS_REMOTE_TOKEN"))?;
+        let settings = services.get::<ConfigManager>()?.config().remote_view;
+        state.start_remote_view(RemoteViewConfig::from_config(&settings, access_token)?)?;
+        state.startup_report.record("Remote view", SubsystemState::Enabled);
+    } else if !state.profile.subsystems().remote_view {
+        state.startup_report.record("Remote view", SubsystemState::Disabled);
+    }
+    state.attach_runtime(runtime.handle().clone());
+    // Hooks first: loading them replaces the registry integrations add to
+    state.attach_hooks(runtime.handle().clone());
+    // Integrations ask for their permissions once prompts can be shown
+    state.load_integrations();
+    if let Err(e) = state.attach_scripts(runtime.handle().clone()) {
+        log::warn!("Automation scripts will not run: {}", e);
+    }
+    if let Err(e) = state.attach_node_actions(runtime.handle().clone()) {
+        log::warn!("Node actions unavailable in context menus: {}", e);
+    }
+    // Switches teach the AI service which workspace comes next, and that
+    // workspace is warmed while the desktop is idle
+    if let Some(ai) = services.try_get::<AIService>() {
+        runtime.spawn(learn_workspace_switches(ai, state.workspaces.subscribe()));
+    }
+    if let Err(e) = state.attach_preloading(runtime.handle().clone()) {
+        log::warn!("Workspaces will not be preloaded: {}", e);
+    }
+    state.attach_audio(runtime.handle().clone());
+    state.attach_quick_look(runtime.handle().clone());
+    // Screenshot and screen cast tools reach the compositor through the desktop portal
+    match runtime.block_on(PortalServer::start()) {
+        Ok((server, requests)) => state.attach_portal(server, requests),
+        Err(e) => log::warn!("Desktop portal unavailable: {}", e),
+    }
+    // Screen readers follow the desktop through AT-SPI events on the accessibility bus
+    match runtime.block_on(AtSpiBus::connect()) {
+        Ok(bus) => state.attach_at_spi(bus),
+        Err(e) => log::warn!("Accessibility bus unavailable: {}", e),
+    }
+    // horizonctl and scripts find the control API through the endpoint file
+    match runtime.block_on(ControlServer::bind()) {
+        Ok(mut server) => {
+            if let Err(e) = server.publish_endpoint(Endpoint::default_path()) {
+                log::warn!("Control API endpoint not published, horizonctl cannot connect: {}", e);
+            }
+            let (control, calls) = CompositorControl::new();
+            let events = server.event_sender();
+            runtime.spawn(forward_workspace_events(state.workspaces.subscribe(), events.clone()));
+            state.attach_control(calls, events);
+            runtime.spawn(async move {
+                if let Err(e) = server.serve(Arc::new(control)).await {
+                    log::warn!("Control API stopped: {}", e);
+                }
+            });
+        }
+        Err(e) => log::warn!("Control API unavailable: {}", e),
+    }
+    state.startup_report.log();
     
-    // Socket handling is automatic in Smithay 0.7
     log::info!("Starting Wayland compositor");
     
-    // Run winit backend with integrated event loop
-    backend::run_winit(state, backend, winit_event_loop)?;
+    // Run winit backend with integrated event loop, or the headless stub
+    let result = match winit {
+        Some((backend, winit_event_loop)) => backend::run_winit(state, backend, winit_event_loop, &mut event_loop),
+        None => backend::run_headless(state, headless.unwrap_or_default(), &mut event_loop),
+    };
+    
+    if let Err(e) = runtime.block_on(services.shutdown()) {
+        log::warn!("{}", e);
+    }
+    result?;
     
     Ok(())
 }
\ No newline at end of file
@@ -1,10 +1,19 @@
 //! Output management - simplified
+//!
+//! Every output mapped by a backend is registered with the monitor manager,
+//! which lays the outputs out and keeps what each one shows: the active
+//! workspace or the one it is pinned to, seen through the primary output's
+//! camera or its own.
 
 use smithay::{
     output::{Output, Mode, PhysicalProperties, Subpixel},
     utils::{Rectangle, Transform},
 };
 use crate::AppState;
+use horizonos_graph_engine::{Camera, SceneId};
+use horizonos_graph_system::monitors::{ConnectionType, DisplayMode, MonitorRotation};
+use horizonos_graph_system::{GraphViewport, Monitor};
+use std::collections::HashSet;
 
 /// Create a default output
 pub fn create_default_output(state: &mut AppState) -> Output {
@@ -31,7 +40,108 @@ pub fn create_default_output(state: &mut AppState) -> Output {
     );
     output.set_preferred(mode);
     
-    state.space.map_output(&output, (0, 0));
+    state.map_output(&output);
     
     output
-}
\ No newline at end of file
+}
+
+/// The monitor manager's record of an output, by the output's name
+pub fn monitor_for_output(output: &Output, primary: bool) -> Monitor {
+    let properties = output.physical_properties();
+    let mode = output.current_mode().unwrap_or(Mode { size: (1920, 1080).into(), refresh: 60_000 });
+    let refresh_rate = mode.refresh as f32 / 1000.0;
+    Monitor {
+        id: output.name(),
+        name: output.description(),
+        manufacturer: Some(properties.make),
+        model: Some(properties.model),
+        serial: None,
+        physical_size: (properties.size.w.max(0) as u32, properties.size.h.max(0) as u32),
+        resolution: (mode.size.w.max(1) as u32, mode.size.h.max(1) as u32),
+        refresh_rate,
+        position: (0, 0),
+        rotation: MonitorRotation::Normal,
+        scale: output.current_scale().fractional_scale() as f32,
+        connection: ConnectionType::Virtual,
+        modes: vec![DisplayMode {
+            width: mode.size.w.max(1) as u32,
+            height: mode.size.h.max(1) as u32,
+            refresh_rate,
+            preferred: true,
+        }],
+        is_primary: primary,
+        enabled: true,
+    }
+}
+
+/// What an output shows of the graph
+#[derive(Debug, Clone)]
+pub struct OutputView {
+    /// Camera the output looks through
+    pub camera: Camera,
+    /// Nodes of the workspace the output is pinned to; `None` shows the whole graph
+    pub nodes: Option<HashSet<SceneId>>,
+}
+
+impl OutputView {
+    /// The whole graph through the interactive camera, for the primary
+    /// output and outputs the monitor manager does not know
+    pub fn interactive(camera: &Camera) -> Self {
+        Self { camera: camera.clone(), nodes: None }
+    }
+    
+    /// Point a copy of the interactive camera along a viewport's camera
+    ///
+    /// Zooming in moves the camera towards its target.
+    pub fn camera_of(viewport: &GraphViewport, interactive: &Camera) -> Camera {
+        let mut camera = interactive.clone();
+        let offset = (viewport.camera_position - viewport.camera_target) / viewport.zoom.max(f32::EPSILON);
+        camera.position = viewport.camera_target + offset;
+        camera.look_at(viewport.camera_target);
+        camera
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_system::monitors::ViewportBounds;
+    use nalgebra::Point3;
+
+    #[test]
+    fn test_monitor_for_output_takes_mode_and_name() {
+        let output = Output::new(
+            "DP-1".to_string(),
+            PhysicalProperties {
+                size: (600, 340).into(),
+                subpixel: Subpixel::Unknown,
+                make: "Acme".to_string(),
+                model: "Wide".to_string(),
+            },
+        );
+        let mode = Mode { size: (2560, 1440).into(), refresh: 144_000 };
+        output.change_current_state(Some(mode), Some(Transform::Normal), None, Some((0, 0).into()));
+
+        let monitor = monitor_for_output(&output, true);
+        assert_eq!(monitor.id, "DP-1");
+        assert_eq!(monitor.resolution, (2560, 1440));
+        assert_eq!(monitor.refresh_rate, 144.0);
+        assert_eq!(monitor.physical_size, (600, 340));
+        assert!(monitor.is_primary);
+    }
+
+    #[test]
+    fn test_viewport_camera_zooms_towards_its_target() {
+        let viewport = GraphViewport {
+            monitor_id: "DP-1".to_string(),
+            bounds: ViewportBounds { min: Point3::new(-10.0, -10.0, -10.0), max: Point3::new(10.0, 10.0, 10.0) },
+            camera_position: Point3::new(5.0, 0.0, 20.0),
+            camera_target: Point3::new(5.0, 0.0, 0.0),
+            zoom: 2.0,
+            workspace: None,
+        };
+        let camera = OutputView::camera_of(&viewport, &Camera::new());
+        assert_eq!(camera.position, Point3::new(5.0, 0.0, 10.0));
+        assert!((camera.forward.z + 1.0).abs() < 1e-6);
+    }
+}
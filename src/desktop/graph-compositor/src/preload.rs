@@ -0,0 +1,216 @@
+//! Warming the workspace the user is likely to switch to next
+//!
+//! The workspace manager decides what to preload from the AI service's
+//! predictions; the warmers here prepare the compositor's share of it. File
+//! nodes get their thumbnails generated in the background, and windows of
+//! application nodes are marked for a fresh capture so their live
+//! thumbnails are current when the workspace comes into view.
+
+use horizonos_graph_ai::AIService;
+use horizonos_graph_engine::{NodeType, Scene, SceneId};
+use horizonos_graph_visual::{ThumbnailGenerator, ThumbnailSize};
+use horizonos_graph_workspaces::{PreloadStage, PreloadWarmer, Workspace, WorkspaceEvent};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// How often the next workspace is predicted and preloaded
+pub const PRELOAD_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Memory an RGBA thumbnail of the given size holds
+fn thumbnail_bytes(size: ThumbnailSize) -> u64 {
+    let pixels = size.pixels() as u64;
+    pixels * pixels * 4
+}
+
+/// Nodes of the workspace that are still in the scene
+fn workspace_nodes<'a>(scene: &'a Scene, workspace: &'a Workspace) -> impl Iterator<Item = (SceneId, &'a NodeType)> + 'a {
+    workspace.nodes.iter()
+        .filter_map(|&node_id| scene.get_node(node_id).map(|node| (node_id, &node.node_type)))
+}
+
+/// Generates the thumbnails of a workspace's file nodes
+pub struct ThumbnailWarmer {
+    scene: Arc<Mutex<Scene>>,
+    generator: Arc<ThumbnailGenerator>,
+    runtime: tokio::runtime::Handle,
+    size: ThumbnailSize,
+}
+
+impl ThumbnailWarmer {
+    pub fn new(scene: Arc<Mutex<Scene>>, generator: Arc<ThumbnailGenerator>, runtime: tokio::runtime::Handle) -> Self {
+        Self {
+            scene,
+            generator,
+            runtime,
+            size: ThumbnailSize::Large,
+        }
+    }
+}
+
+impl PreloadWarmer for ThumbnailWarmer {
+    fn stage(&self) -> PreloadStage {
+        PreloadStage::Thumbnails
+    }
+
+    fn warm(&self, workspace: &Workspace, budget_bytes: u64) -> u64 {
+        let per_file = thumbnail_bytes(self.size);
+        let paths: Vec<PathBuf> = {
+            let scene = self.scene.lock().unwrap_or_else(|e| e.into_inner());
+            workspace_nodes(&scene, workspace)
+                .filter_map(|(_, node_type)| match node_type {
+                    NodeType::File { path, .. } => Some(PathBuf::from(path)),
+                    _ => None,
+                })
+                .take((budget_bytes / per_file) as usize)
+                .collect()
+        };
+
+        let bytes = paths.len() as u64 * per_file;
+        for path in paths {
+            let generator = self.generator.clone();
+            let size = self.size;
+            self.runtime.spawn(async move {
+                if let Err(e) = generator.get_thumbnail(&path, size).await {
+                    log::debug!("Could not preload thumbnail of {}: {}", path.display(), e);
+                }
+            });
+        }
+        bytes
+    }
+
+    fn release(&self, _workspace_id: &str) {
+        // The generator's memory cache evicts thumbnails by itself
+    }
+}
+
+/// Marks the windows of a workspace's application nodes for a fresh capture
+///
+/// Captures need the renderer, so the nodes are queued and the compositor
+/// damages their live thumbnails on its own thread.
+pub struct SnapshotWarmer {
+    scene: Arc<Mutex<Scene>>,
+    queue: Arc<Mutex<Vec<SceneId>>>,
+}
+
+impl SnapshotWarmer {
+    pub fn new(scene: Arc<Mutex<Scene>>, queue: Arc<Mutex<Vec<SceneId>>>) -> Self {
+        Self { scene, queue }
+    }
+}
+
+impl PreloadWarmer for SnapshotWarmer {
+    fn stage(&self) -> PreloadStage {
+        PreloadStage::Snapshots
+    }
+
+    fn warm(&self, workspace: &Workspace, budget_bytes: u64) -> u64 {
+        let per_window = thumbnail_bytes(ThumbnailSize::Large);
+        let windows: Vec<SceneId> = {
+            let scene = self.scene.lock().unwrap_or_else(|e| e.into_inner());
+            workspace_nodes(&scene, workspace)
+                .filter(|(_, node_type)| matches!(node_type, NodeType::Application { .. }))
+                .map(|(node_id, _)| node_id)
+                .take((budget_bytes / per_window) as usize)
+                .collect()
+        };
+
+        let bytes = windows.len() as u64 * per_window;
+        self.queue.lock().unwrap_or_else(|e| e.into_inner()).extend(windows);
+        bytes
+    }
+
+    fn release(&self, _workspace_id: &str) {
+        // Live thumbnails stay with their windows until they are unmapped
+    }
+}
+
+/// Preloading state kept by the compositor
+pub struct WorkspacePreloading {
+    /// Windows the snapshot warmer wants captured again
+    pub snapshots: Arc<Mutex<Vec<SceneId>>>,
+    pub last_tick: Instant,
+}
+
+impl WorkspacePreloading {
+    pub fn new() -> Self {
+        Self {
+            snapshots: Arc::new(Mutex::new(Vec::new())),
+            last_tick: Instant::now(),
+        }
+    }
+
+    /// Whether the next idle tick is due, restarting the interval if so
+    pub fn tick_due(&mut self, now: Instant) -> bool {
+        if now.duration_since(self.last_tick) < PRELOAD_INTERVAL {
+            return false;
+        }
+        self.last_tick = now;
+        true
+    }
+}
+
+impl Default for WorkspacePreloading {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Teach the AI service the user's workspace switches, so it can predict
+/// the next one
+pub async fn learn_workspace_switches(ai: Arc<AIService>, mut workspace_events: broadcast::Receiver<WorkspaceEvent>) {
+    loop {
+        match workspace_events.recv().await {
+            Ok(WorkspaceEvent::Switched { from: Some(from), to }) => ai.record_workspace_switch(&from, &to),
+            Ok(WorkspaceEvent::Deleted { workspace_id }) => ai.forget_workspace(&workspace_id),
+            Ok(_) => {}
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                log::debug!("Workspace predictions missed {} workspace events", missed);
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::new_node;
+
+    fn app_node(scene: &mut Scene, name: &str) -> SceneId {
+        let mut node = new_node("task", name, None, None).unwrap();
+        node.node_type = NodeType::Application { pid: 1, name: name.to_string() };
+        scene.add_node(node)
+    }
+
+    #[test]
+    fn test_snapshot_warmer_queues_windows_within_budget() {
+        let mut scene = Scene::new();
+        let editor = app_node(&mut scene, "editor");
+        let browser = app_node(&mut scene, "browser");
+        let task = scene.add_node(new_node("task", "Write report", None, None).unwrap());
+        let mut workspace = Workspace::new("Work", "");
+        workspace.nodes = vec![editor, task, browser, 999];
+
+        let queue = Arc::new(Mutex::new(Vec::new()));
+        let warmer = SnapshotWarmer::new(Arc::new(Mutex::new(scene)), queue.clone());
+        let per_window = thumbnail_bytes(ThumbnailSize::Large);
+
+        assert_eq!(warmer.warm(&workspace, per_window * 10), per_window * 2);
+        assert_eq!(*queue.lock().unwrap(), vec![editor, browser]);
+
+        queue.lock().unwrap().clear();
+        assert_eq!(warmer.warm(&workspace, per_window + 1), per_window);
+        assert_eq!(*queue.lock().unwrap(), vec![editor]);
+    }
+
+    #[test]
+    fn test_idle_tick_waits_for_the_interval() {
+        let mut preloading = WorkspacePreloading::new();
+        let start = preloading.last_tick;
+        assert!(!preloading.tick_due(start + PRELOAD_INTERVAL / 2));
+        assert!(preloading.tick_due(start + PRELOAD_INTERVAL));
+        assert!(!preloading.tick_due(start + PRELOAD_INTERVAL + Duration::from_secs(1)));
+    }
+}
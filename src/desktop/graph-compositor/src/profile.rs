@@ -0,0 +1,390 @@
+//! Startup profiles selecting which subsystems the compositor brings up
+//!
+//! A profile is chosen from `--profile <name>`, then `HORIZONOS_PROFILE`,
+//! then the `profile` key in `$XDG_CONFIG_HOME/horizonos/startup.conf`,
+//! defaulting to `full`. The config file is a list of `key = value` lines:
+//!
+//! ```text
+//! profile = kiosk
+//! kiosk_app = firefox --kiosk https://example.org
+//! ```
+
+use crate::CompositorError;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Environment variable overriding the configured profile
+pub const PROFILE_ENV: &str = "HORIZONOS_PROFILE";
+
+/// Startup profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StartupProfile {
+    /// No AI, no automation, a single workspace
+    Minimal,
+    /// Every subsystem
+    #[default]
+    Full,
+    /// Locked-down single-application graph for public terminals
+    Kiosk,
+}
+
+impl StartupProfile {
+    /// All profiles
+    pub fn all() -> [StartupProfile; 3] {
+        [StartupProfile::Minimal, StartupProfile::Full, StartupProfile::Kiosk]
+    }
+
+    /// Profile name as used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            StartupProfile::Minimal => "minimal",
+            StartupProfile::Full => "full",
+            StartupProfile::Kiosk => "kiosk",
+        }
+    }
+
+    /// One-line description for `--help`
+    pub fn description(&self) -> &'static str {
+        match self {
+            StartupProfile::Minimal => "no AI or automation, single workspace",
+            StartupProfile::Full => "all subsystems",
+            StartupProfile::Kiosk => "single locked-down application, no shortcuts or remote access",
+        }
+    }
+
+    /// Subsystems enabled by this profile
+    pub fn subsystems(&self) -> SubsystemToggles {
+        match self {
+            StartupProfile::Full => SubsystemToggles {
+                ai: true,
+                automation: true,
+                workspace_count: 4,
+                xwayland: true,
+                remote_view: true,
+                quit_shortcut: true,
+            },
+            StartupProfile::Minimal => SubsystemToggles {
+                ai: false,
+                automation: false,
+                workspace_count: 1,
+                xwayland: true,
+                remote_view: true,
+                quit_shortcut: true,
+            },
+            StartupProfile::Kiosk => SubsystemToggles {
+                ai: false,
+                automation: false,
+                workspace_count: 1,
+                xwayland: false,
+                remote_view: false,
+                quit_shortcut: false,
+            },
+        }
+    }
+}
+
+impl fmt::Display for StartupProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for StartupProfile {
+    type Err = CompositorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        StartupProfile::all()
+            .into_iter()
+            .find(|profile| profile.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| CompositorError::Config(format!(
+                "Unknown startup profile '{}' (expected minimal, full or kiosk)",
+                s
+            )))
+    }
+}
+
+/// Subsystems toggled by a profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubsystemToggles {
+    /// AI services started by the session
+    pub ai: bool,
+    /// Automation services started by the session
+    pub automation: bool,
+    /// Number of workspaces
+    pub workspace_count: u32,
+    /// X11 application support
+    pub xwayland: bool,
+    /// Remote viewing may be started
+    pub remote_view: bool,
+    /// Alt+Q quits the compositor
+    pub quit_shortcut: bool,
+}
+
+/// Where the profile was selected
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ProfileSource {
+    CommandLine,
+    Environment,
+    ConfigFile(PathBuf),
+    #[default]
+    Default,
+}
+
+impl fmt::Display for ProfileSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileSource::CommandLine => f.write_str("command line"),
+            ProfileSource::Environment => write!(f, "{}", PROFILE_ENV),
+            ProfileSource::ConfigFile(path) => write!(f, "{}", path.display()),
+            ProfileSource::Default => f.write_str("default"),
+        }
+    }
+}
+
+/// Selected profile with its origin and kiosk application
+#[derive(Debug, Clone, Default)]
+pub struct ProfileSettings {
+    /// Selected profile
+    pub profile: StartupProfile,
+    /// Where it was selected
+    pub source: ProfileSource,
+    /// Command run as the only application in kiosk mode
+    pub kiosk_app: Option<String>,
+}
+
+impl ProfileSettings {
+    /// Select the profile from command line arguments, environment and config file
+    pub fn select(args: &[String]) -> Result<Self, CompositorError> {
+        let config_path = default_config_path();
+        let config = match &config_path {
+            Some(path) if path.exists() => parse_config(&std::fs::read_to_string(path)?)?,
+            _ => StartupConfig::default(),
+        };
+        let env = std::env::var(PROFILE_ENV).ok();
+        Self::resolve(args, env.as_deref(), config, config_path)
+    }
+
+    /// Resolve the profile from already gathered inputs, in precedence order
+    pub fn resolve(
+        args: &[String],
+        env: Option<&str>,
+        config: StartupConfig,
+        config_path: Option<PathBuf>,
+    ) -> Result<Self, CompositorError> {
+        let kiosk_app = flag_value(args, "--kiosk-app").or(config.kiosk_app);
+
+        let (profile, source) = if let Some(name) = flag_value(args, "--profile") {
+            (name.parse()?, ProfileSource::CommandLine)
+        } else if let Some(name) = env.filter(|name| !name.trim().is_empty()) {
+            (name.parse()?, ProfileSource::Environment)
+        } else if let Some(profile) = config.profile {
+            (profile, ProfileSource::ConfigFile(config_path.unwrap_or_default()))
+        } else {
+            (StartupProfile::default(), ProfileSource::Default)
+        };
+
+        if profile == StartupProfile::Kiosk && kiosk_app.is_none() {
+            return Err(CompositorError::Config(
+                "The kiosk profile needs an application (--kiosk-app or kiosk_app in startup.conf)".to_string(),
+            ));
+        }
+
+        Ok(Self { profile, source, kiosk_app })
+    }
+
+    /// Subsystems enabled by the selected profile
+    pub fn subsystems(&self) -> SubsystemToggles {
+        self.profile.subsystems()
+    }
+
+    /// Export the profile to session services started from the compositor
+    pub fn export_env(&self) {
+        let toggles = self.subsystems();
+        let flag = |enabled: bool| if enabled { "1" } else { "0" };
+        std::env::set_var(PROFILE_ENV, self.profile.name());
+        std::env::set_var(horizonos_graph_ai::AI_ENV, flag(toggles.ai));
+        std::env::set_var(horizonos_graph_ai::AUTOMATION_ENV, flag(toggles.automation));
+    }
+}
+
+/// Values read from `startup.conf`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StartupConfig {
+    pub profile: Option<StartupProfile>,
+    pub kiosk_app: Option<String>,
+}
+
+/// Default location of `startup.conf`
+pub fn default_config_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .map(|dir| dir.join("horizonos").join("startup.conf"))
+}
+
+/// Parse `key = value` lines, ignoring blank lines and `#` comments
+pub fn parse_config(text: &str) -> Result<StartupConfig, CompositorError> {
+    let mut config = StartupConfig::default();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(CompositorError::Config(format!("startup.conf line {}: expected key = value", number + 1)));
+        };
+        match key.trim() {
+            "profile" => config.profile = Some(value.parse()?),
+            "kiosk_app" => config.kiosk_app = Some(value.trim().to_string()),
+            other => log::warn!("startup.conf line {}: unknown key '{}'", number + 1, other),
+        }
+    }
+    Ok(config)
+}
+
+/// Value of `--flag value` or `--flag=value`
+pub(crate) fn flag_value(args: &[String], flag: &str) -> Option<String> {
+    let prefix = format!("{}=", flag);
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == flag {
+            args.get(i + 1).cloned()
+        } else {
+            arg.strip_prefix(&prefix).map(str::to_string)
+        }
+    })
+}
+
+/// Outcome of bringing up one subsystem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubsystemState {
+    /// Enabled and running, or handed to the session to start
+    Enabled,
+    /// Turned off by the profile
+    Disabled,
+    /// Enabled but failed to start
+    Failed(String),
+}
+
+/// Per-subsystem startup status, logged once startup completes
+#[derive(Debug, Clone, Default)]
+pub struct StartupReport {
+    /// Selected profile
+    pub profile: StartupProfile,
+    /// Where it was selected
+    pub source: ProfileSource,
+    /// Subsystems in startup order
+    pub entries: Vec<(String, SubsystemState)>,
+}
+
+impl StartupReport {
+    /// Start a report for the selected profile
+    pub fn new(settings: &ProfileSettings) -> Self {
+        Self {
+            profile: settings.profile,
+            source: settings.source.clone(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Record a subsystem's state, replacing an earlier entry for it
+    pub fn record(&mut self, subsystem: impl Into<String>, state: SubsystemState) {
+        let subsystem = subsystem.into();
+        match &state {
+            SubsystemState::Failed(error) => log::warn!("{}: failed: {}", subsystem, error),
+            _ => log::debug!("{}: {:?}", subsystem, state),
+        }
+        match self.entries.iter_mut().find(|(name, _)| *name == subsystem) {
+            Some(entry) => entry.1 = state,
+            None => self.entries.push((subsystem, state)),
+        }
+    }
+
+    /// Record whether a toggled subsystem started
+    pub fn record_result<E: fmt::Display>(&mut self, subsystem: &str, enabled: bool, result: impl FnOnce() -> Result<(), E>) {
+        let state = if !enabled {
+            SubsystemState::Disabled
+        } else {
+            match result() {
+                Ok(()) => SubsystemState::Enabled,
+                Err(e) => SubsystemState::Failed(e.to_string()),
+            }
+        };
+        self.record(subsystem, state);
+    }
+
+    /// Subsystems that failed to start
+    pub fn failures(&self) -> Vec<&str> {
+        self.entries.iter()
+            .filter(|(_, state)| matches!(state, SubsystemState::Failed(_)))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Log the report, one line per subsystem
+    pub fn log(&self) {
+        for line in self.to_string().lines() {
+            log::info!("{}", line);
+        }
+    }
+}
+
+impl fmt::Display for StartupReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Startup profile: {} (from {})", self.profile, self.source)?;
+        let width = self.entries.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        for (name, state) in &self.entries {
+            let state = match state {
+                SubsystemState::Enabled => "enabled".to_string(),
+                SubsystemState::Disabled => "disabled by profile".to_string(),
+                SubsystemState::Failed(error) => format!("FAILED: {}", error),
+            };
+            writeln!(f, "  {:width$}  {}", name, state, width = width)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_profile_precedence() {
+        let config = parse_config("# session\nprofile = minimal\nkiosk_app = browser --kiosk\n").unwrap();
+        let path = Some(PathBuf::from("/tmp/startup.conf"));
+
+        let settings = ProfileSettings::resolve(&args(&["--profile=kiosk"]), Some("full"), config.clone(), path.clone()).unwrap();
+        assert_eq!(settings.profile, StartupProfile::Kiosk);
+        assert_eq!(settings.source, ProfileSource::CommandLine);
+        assert_eq!(settings.kiosk_app.as_deref(), Some("browser --kiosk"));
+
+        let settings = ProfileSettings::resolve(&[], Some("full"), config.clone(), path.clone()).unwrap();
+        assert_eq!(settings.source, ProfileSource::Environment);
+
+        let settings = ProfileSettings::resolve(&[], None, config, path).unwrap();
+        assert_eq!(settings.profile, StartupProfile::Minimal);
+        assert!(!settings.subsystems().ai);
+        assert_eq!(settings.subsystems().workspace_count, 1);
+
+        assert!(ProfileSettings::resolve(&args(&["--profile", "kiosk"]), None, StartupConfig::default(), None).is_err());
+        assert!(ProfileSettings::resolve(&args(&["--profile", "tiny"]), None, StartupConfig::default(), None).is_err());
+    }
+
+    #[test]
+    fn test_startup_report() {
+        let mut report = StartupReport::new(&ProfileSettings::default());
+        report.record_result("XWayland", true, || Err::<(), _>("no X server"));
+        report.record_result("AI services", false, || Ok::<(), String>(()));
+        report.record("Graph scene", SubsystemState::Enabled);
+
+        assert_eq!(report.failures(), vec!["XWayland"]);
+        let text = report.to_string();
+        assert!(text.starts_with("Startup profile: full (from default)"));
+        assert!(text.contains("AI services  disabled by profile"));
+    }
+}
@@ -0,0 +1,233 @@
+//! Consent prompts shown to the local user
+//!
+//! Questions that only the person at the machine may answer, such as a remote
+//! viewer asking for input control or a client asking for a privileged
+//! protocol, are queued here. The oldest prompt is modal: it is shown as a
+//! critical notification and takes all keys until it is answered with
+//! Y/Enter (allow), A (always allow, where offered) or N/Escape (deny). The
+//! notification's buttons answer it as well.
+
+use horizonos_graph_notifications::actions::ActionType;
+use horizonos_graph_notifications::{Notification, NotificationAction, NotificationPriority, NotificationType};
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+
+/// Notification action ids of the answers
+const ALLOW_ACTION: &str = "prompt:allow";
+const ALWAYS_ACTION: &str = "prompt:always";
+const DENY_ACTION: &str = "prompt:deny";
+
+/// Notification group all prompts share
+pub const PROMPTS_GROUP: &str = "consent-prompts";
+
+/// What a prompt asks about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PromptSubject {
+    /// A remote viewer wants to control input
+    RemoteInput { viewer_id: u64 },
+    /// A client wants a privileged protocol
+    Permission { request_id: u64 },
+    /// An installed integration wants its permissions
+    Integration { index: u64 },
+}
+
+/// Answers a prompt can be given
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptKey {
+    Allow,
+    AllowAlways,
+    Deny,
+}
+
+/// The user's answer to a prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PromptAnswer {
+    pub subject: PromptSubject,
+    pub allowed: bool,
+    /// Remember the answer beyond this request
+    pub always: bool,
+    /// Notification that showed the prompt, to dismiss
+    pub notification_id: Uuid,
+}
+
+/// A question waiting for the user
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub subject: PromptSubject,
+    pub title: String,
+    pub body: String,
+    /// Whether "always allow" is offered
+    pub offers_always: bool,
+    pub notification_id: Uuid,
+}
+
+impl Prompt {
+    /// Notification showing the prompt with its answers as buttons
+    pub fn notification(&self) -> Notification {
+        let mut notification = Notification::new(self.title.clone(), self.body.clone())
+            .with_type(NotificationType::Security)
+            .with_priority(NotificationPriority::Critical)
+            .persistent()
+            .add_action(answer_action(ALLOW_ACTION, "Allow (Y)", true, false));
+        if self.offers_always {
+            notification = notification.add_action(answer_action(ALWAYS_ACTION, "Always allow (A)", false, false));
+        }
+        notification = notification.add_action(answer_action(DENY_ACTION, "Deny (N)", false, true));
+        notification.id = self.notification_id;
+        notification.group = Some(PROMPTS_GROUP.to_string());
+        notification.tags = vec!["prompt".to_string()];
+        notification
+    }
+}
+
+fn answer_action(id: &str, label: &str, primary: bool, destructive: bool) -> NotificationAction {
+    NotificationAction {
+        id: id.to_string(),
+        label: label.to_string(),
+        icon: None,
+        action_type: ActionType::Custom { handler: "prompt.answer".to_string() },
+        parameters: HashMap::new(),
+        destructive,
+        primary,
+    }
+}
+
+/// Prompts waiting for the user, oldest first
+#[derive(Debug, Default)]
+pub struct PromptQueue {
+    prompts: VecDeque<Prompt>,
+}
+
+impl PromptQueue {
+    /// Queue a prompt, returning it for its notification
+    ///
+    /// Asking again about a subject that is still waiting returns `None`.
+    pub fn ask(&mut self, subject: PromptSubject, title: String, body: String, offers_always: bool) -> Option<&Prompt> {
+        if self.prompts.iter().any(|prompt| prompt.subject == subject) {
+            return None;
+        }
+        self.prompts.push_back(Prompt { subject, title, body, offers_always, notification_id: Uuid::new_v4() });
+        self.prompts.back()
+    }
+
+    /// Prompt that is answered by the next key
+    pub fn current(&self) -> Option<&Prompt> {
+        self.prompts.front()
+    }
+
+    /// Prompts waiting
+    pub fn len(&self) -> usize {
+        self.prompts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prompts.is_empty()
+    }
+
+    /// Answer the current prompt
+    pub fn handle_key(&mut self, key: PromptKey) -> Option<PromptAnswer> {
+        // "Always" is only an answer where it is offered
+        if key == PromptKey::AllowAlways && !self.current()?.offers_always {
+            return None;
+        }
+        let prompt = self.prompts.pop_front()?;
+        Some(Self::answer(&prompt, key))
+    }
+
+    /// Answer a prompt through a button on its notification
+    pub fn handle_action(&mut self, notification_id: Uuid, action_id: &str) -> Option<PromptAnswer> {
+        let key = match action_id {
+            ALLOW_ACTION => PromptKey::Allow,
+            ALWAYS_ACTION => PromptKey::AllowAlways,
+            DENY_ACTION => PromptKey::Deny,
+            _ => return None,
+        };
+        self.answer_notification(notification_id, key)
+    }
+
+    /// Deny a prompt whose notification was dismissed unanswered
+    pub fn handle_dismissed(&mut self, notification_id: Uuid) -> Option<PromptAnswer> {
+        self.answer_notification(notification_id, PromptKey::Deny)
+    }
+
+    /// Drop a prompt that no longer needs an answer, e.g. its viewer left
+    pub fn withdraw(&mut self, subject: PromptSubject) -> Option<Prompt> {
+        let index = self.prompts.iter().position(|prompt| prompt.subject == subject)?;
+        self.prompts.remove(index)
+    }
+
+    fn answer_notification(&mut self, notification_id: Uuid, key: PromptKey) -> Option<PromptAnswer> {
+        let index = self.prompts.iter().position(|prompt| prompt.notification_id == notification_id)?;
+        let prompt = self.prompts.remove(index)?;
+        Some(Self::answer(&prompt, key))
+    }
+
+    fn answer(prompt: &Prompt, key: PromptKey) -> PromptAnswer {
+        PromptAnswer {
+            subject: prompt.subject,
+            allowed: key != PromptKey::Deny,
+            always: key == PromptKey::AllowAlways,
+            notification_id: prompt.notification_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ask(queue: &mut PromptQueue, subject: PromptSubject, offers_always: bool) -> Uuid {
+        queue.ask(subject, "Title".to_string(), "Body".to_string(), offers_always).unwrap().notification_id
+    }
+
+    #[test]
+    fn test_prompts_are_answered_oldest_first() {
+        let mut queue = PromptQueue::default();
+        let remote = PromptSubject::RemoteInput { viewer_id: 1 };
+        let permission = PromptSubject::Permission { request_id: 7 };
+        ask(&mut queue, remote, false);
+        ask(&mut queue, permission, true);
+        assert!(queue.ask(remote, String::new(), String::new(), false).is_none());
+        assert_eq!(queue.len(), 2);
+
+        // Remote input cannot be allowed for good
+        assert_eq!(queue.handle_key(PromptKey::AllowAlways), None);
+        let answer = queue.handle_key(PromptKey::Allow).unwrap();
+        assert_eq!((answer.subject, answer.allowed, answer.always), (remote, true, false));
+
+        let answer = queue.handle_key(PromptKey::AllowAlways).unwrap();
+        assert_eq!((answer.subject, answer.allowed, answer.always), (permission, true, true));
+        assert!(queue.is_empty());
+        assert_eq!(queue.handle_key(PromptKey::Deny), None);
+    }
+
+    #[test]
+    fn test_notification_buttons_and_dismissal_answer_prompts() {
+        let mut queue = PromptQueue::default();
+        let first = ask(&mut queue, PromptSubject::Permission { request_id: 1 }, true);
+        let second = ask(&mut queue, PromptSubject::Permission { request_id: 2 }, true);
+
+        let notification = queue.current().unwrap().notification();
+        assert_eq!(notification.id, first);
+        let actions: Vec<_> = notification.actions.iter().map(|action| action.id.as_str()).collect();
+        assert_eq!(actions, vec![ALLOW_ACTION, ALWAYS_ACTION, DENY_ACTION]);
+
+        assert_eq!(queue.handle_action(second, "recovery:retry"), None);
+        let answer = queue.handle_action(second, DENY_ACTION).unwrap();
+        assert_eq!(answer.subject, PromptSubject::Permission { request_id: 2 });
+        assert!(!answer.allowed);
+
+        let answer = queue.handle_dismissed(first).unwrap();
+        assert!(!answer.allowed);
+        assert_eq!(queue.handle_dismissed(first), None);
+    }
+
+    #[test]
+    fn test_withdrawn_prompts_are_not_answered() {
+        let mut queue = PromptQueue::default();
+        let subject = PromptSubject::RemoteInput { viewer_id: 3 };
+        ask(&mut queue, subject, false);
+        assert_eq!(queue.withdraw(subject).unwrap().subject, subject);
+        assert_eq!(queue.handle_key(PromptKey::Allow), None);
+    }
+}
@@ -9,25 +9,38 @@
 //! - Session Lock protocol for security
 
 use crate::AppState;
+use horizonos_graph_engine::SceneId;
 use smithay::desktop::Window;
 use smithay::{
     reexports::{
+        wayland_protocols::xdg::shell::server::xdg_toplevel,
+        wayland_protocols_wlr::foreign_toplevel::v1::server::{
+            zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+            zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+        },
         wayland_server::{
+            backend::{ClientId, GlobalId},
             protocol::{
                 wl_buffer::WlBuffer,
                 wl_output::WlOutput,
                 wl_surface::WlSurface,
             },
-            DisplayHandle, Resource,
+            Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
         },
     },
+    utils::{Logical, Point},
     wayland::{
+        compositor::with_states,
         shell::wlr_layer::LayerSurface,
+        shell::xdg::{ToplevelSurface, XdgToplevelSurfaceData},
     },
     output::Output,
 };
 use std::collections::HashMap;
 
+/// Highest zwlr_foreign_toplevel_manager_v1 version supported
+const FOREIGN_TOPLEVEL_VERSION: u32 = 3;
+
 /// Layer enum replacement with Hash support
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum LayerType {
@@ -56,12 +69,23 @@ pub struct ScreencopyState {
 }
 
 /// Foreign toplevel management state
-#[derive(Debug)]
+///
+/// Every window node is announced to bound taskbars, which can activate,
+/// close, minimize, maximize and fullscreen it.
+#[derive(Debug, Default)]
 pub struct ForeignToplevelState {
-    /// Managed toplevel windows
-    pub toplevels: HashMap<u32, ToplevelHandle>,
-    /// Client managers (simplified)
-    pub managers: Vec<String>,
+    /// Announced windows by graph node
+    pub toplevels: HashMap<SceneId, ToplevelHandle>,
+    /// Bound managers
+    managers: Vec<ZwlrForeignToplevelManagerV1>,
+    /// Manager global, once created
+    global: Option<GlobalId>,
+}
+
+/// Global data of the foreign toplevel manager
+pub struct ForeignToplevelGlobalData {
+    /// Whether a client may see the global
+    filter: Box<dyn Fn(&Client) -> bool + Send + Sync>,
 }
 
 /// Output management state
@@ -131,16 +155,22 @@ pub struct ScreencopyRegion {
 /// Toplevel handle for foreign toplevel management
 #[derive(Debug)]
 pub struct ToplevelHandle {
-    /// Handle ID (simplified)
-    pub id: u32,
-    /// Window reference ID
-    pub window_id: u32,
+    /// Graph node of the window
+    pub node_id: SceneId,
+    /// The window
+    pub window: Window,
     /// Title
     pub title: String,
     /// App ID
     pub app_id: String,
     /// State
     pub state: ToplevelState,
+    /// Location of a minimized window, restored when it is shown again
+    pub restore_location: Option<Point<i32, Logical>>,
+    /// Where a taskbar shows the window, relative to one of its surfaces
+    pub rectangle: Option<(WlSurface, ScreencopyRegion)>,
+    /// Handle objects, one per bound manager
+    resources: Vec<ZwlrForeignToplevelHandleV1>,
 }
 
 /// Toplevel state flags
@@ -254,10 +284,7 @@ impl ProtocolManager {
                     0x34324152, // AR24 (ARGB8888)
                 ],
             },
-            foreign_toplevel: ForeignToplevelState {
-                toplevels: HashMap::new(),
-                managers: Vec::new(),
-            },
+            foreign_toplevel: ForeignToplevelState::default(),
             output_management: OutputManagementState {
                 heads: HashMap::new(),
                 configurations: Vec::new(),
@@ -280,7 +307,6 @@ impl ProtocolManager {
         log::info!("Initialized Wayland protocol extensions");
         log::info!("  - Layer shell protocol (placeholder)");
         log::info!("  - Screencopy protocol (placeholder)");
-        log::info!("  - Foreign toplevel management (placeholder)");
         log::info!("  - Output management (placeholder)");
         Ok(())
     }
@@ -323,13 +349,6 @@ impl ProtocolManager {
         Ok(())
     }
     
-    /// Handle toplevel creation for foreign management
-    pub fn register_toplevel(&mut self, window: Window) -> anyhow::Result<()> {
-        // Create toplevel handle (simplified)
-        log::debug!("Registered toplevel window for foreign management");
-        Ok(())
-    }
-    
     /// Handle output configuration
     pub fn configure_output(
         &mut self,
@@ -451,6 +470,7 @@ pub fn init_protocols(state: &mut AppState) -> anyhow::Result<()> {
     log::info!("  - wlr-output-management-unstable-v1 (display configuration)");
     log::info!("  - virtual-keyboard-unstable-v1 (virtual input)");
     log::info!("  - session-lock-v1 (screen locking)");
+    log::info!("  - security-context-v1 (sandboxed client privileges)");
     
     Ok(())
 }
@@ -481,4 +501,425 @@ impl Default for ToplevelState {
             fullscreen: false,
         }
     }
-}
\ No newline at end of file
+}
+impl ForeignToplevelState {
+    /// Create the manager global; `filter` decides which clients see it
+    pub fn init_global<F>(&mut self, display: &DisplayHandle, filter: F)
+    where
+        F: Fn(&Client) -> bool + Send + Sync + 'static,
+    {
+        let global = display.create_global::<AppState, ZwlrForeignToplevelManagerV1, _>(
+            FOREIGN_TOPLEVEL_VERSION,
+            ForeignToplevelGlobalData { filter: Box::new(filter) },
+        );
+        self.global = Some(global);
+    }
+    
+    /// Remove the manager global, telling bound taskbars they are done
+    pub fn remove_global(&mut self, display: &DisplayHandle) {
+        if let Some(global) = self.global.take() {
+            display.remove_global::<AppState>(global);
+        }
+        for manager in self.managers.drain(..) {
+            manager.finished();
+        }
+    }
+    
+    /// Announce a window node to all taskbars
+    pub fn add_toplevel(&mut self, display: &DisplayHandle, node_id: SceneId, window: Window, outputs: &[Output]) {
+        let (title, app_id) = window.toplevel().map(toplevel_details).unwrap_or_default();
+        let mut handle = ToplevelHandle {
+            node_id,
+            window,
+            title,
+            app_id,
+            state: ToplevelState::default(),
+            restore_location: None,
+            rectangle: None,
+            resources: Vec::new(),
+        };
+        for manager in &self.managers {
+            announce(display, manager, &mut handle, outputs);
+        }
+        self.toplevels.insert(node_id, handle);
+    }
+    
+    /// Tell taskbars a window node is gone
+    pub fn remove_toplevel(&mut self, node_id: SceneId) {
+        if let Some(handle) = self.toplevels.remove(&node_id) {
+            for resource in &handle.resources {
+                send(resource, &[HandleEvent::Closed]);
+            }
+        }
+    }
+    
+    /// Re-read the title and app id of a window after the client changed them
+    pub fn refresh_details(&mut self, node_id: SceneId) {
+        let Some(handle) = self.toplevels.get_mut(&node_id) else {
+            return;
+        };
+        let Some((title, app_id)) = handle.window.toplevel().map(toplevel_details) else {
+            return;
+        };
+        let events = detail_events((&handle.title, &handle.app_id), (&title, &app_id));
+        for resource in &handle.resources {
+            send(resource, &events);
+        }
+        handle.title = title;
+        handle.app_id = app_id;
+    }
+    
+    /// Change the state of a window node and tell taskbars
+    pub fn update_state(&mut self, node_id: SceneId, update: impl FnOnce(&mut ToplevelState)) {
+        if let Some(handle) = self.toplevels.get_mut(&node_id) {
+            update(&mut handle.state);
+            for resource in &handle.resources {
+                send(resource, &[HandleEvent::State(state_bytes(&handle.state, resource.version())), HandleEvent::Done]);
+            }
+        }
+    }
+    
+    /// Mark the window node with keyboard focus as the active one
+    pub fn set_activated(&mut self, focused: Option<SceneId>) {
+        let states = self.toplevels.values().map(|handle| (handle.node_id, handle.state.activated));
+        for node_id in activation_changes(states, focused) {
+            self.update_state(node_id, |state| state.activated = Some(node_id) == focused);
+        }
+    }
+    
+    /// Window of a node announced to taskbars
+    pub fn window(&self, node_id: SceneId) -> Option<&Window> {
+        self.toplevels.get(&node_id).map(|handle| &handle.window)
+    }
+    
+    fn add_manager(&mut self, display: &DisplayHandle, manager: ZwlrForeignToplevelManagerV1, outputs: &[Output]) {
+        let mut node_ids: Vec<SceneId> = self.toplevels.keys().copied().collect();
+        node_ids.sort_unstable();
+        for node_id in node_ids {
+            if let Some(handle) = self.toplevels.get_mut(&node_id) {
+                announce(display, &manager, handle, outputs);
+            }
+        }
+        self.managers.push(manager);
+    }
+    
+    fn remove_manager(&mut self, manager: &ZwlrForeignToplevelManagerV1) {
+        self.managers.retain(|m| m != manager);
+    }
+    
+    fn forget_resource(&mut self, node_id: SceneId, resource: &ZwlrForeignToplevelHandleV1) {
+        if let Some(handle) = self.toplevels.get_mut(&node_id) {
+            handle.resources.retain(|r| r != resource);
+        }
+    }
+}
+
+/// Create a handle object for a window on a manager and send its details
+fn announce(display: &DisplayHandle, manager: &ZwlrForeignToplevelManagerV1, handle: &mut ToplevelHandle, outputs: &[Output]) {
+    let Some(client) = manager.client() else {
+        return;
+    };
+    let resource = match client.create_resource::<ZwlrForeignToplevelHandleV1, SceneId, AppState>(
+        display,
+        manager.version(),
+        handle.node_id,
+    ) {
+        Ok(resource) => resource,
+        Err(e) => {
+            log::warn!("Failed to announce window node {} to a taskbar: {}", handle.node_id, e);
+            return;
+        }
+    };
+    manager.toplevel(&resource);
+    send(&resource, &[HandleEvent::Title(handle.title.clone()), HandleEvent::AppId(handle.app_id.clone())]);
+    // Window nodes live in the graph, which is shown on every output
+    for output in outputs {
+        for wl_output in output.client_outputs(&client) {
+            resource.output_enter(&wl_output);
+        }
+    }
+    send(&resource, &[HandleEvent::State(state_bytes(&handle.state, resource.version())), HandleEvent::Done]);
+    handle.resources.push(resource);
+}
+
+/// Event of a toplevel handle object
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HandleEvent {
+    Title(String),
+    AppId(String),
+    State(Vec<u8>),
+    Done,
+    Closed,
+}
+
+/// Send events on a handle object
+fn send(resource: &ZwlrForeignToplevelHandleV1, events: &[HandleEvent]) {
+    for event in events {
+        match event {
+            HandleEvent::Title(title) => resource.title(title.clone()),
+            HandleEvent::AppId(app_id) => resource.app_id(app_id.clone()),
+            HandleEvent::State(state) => resource.state(state.clone()),
+            HandleEvent::Done => resource.done(),
+            HandleEvent::Closed => resource.closed(),
+        }
+    }
+}
+
+/// Events telling taskbars the title and app id changed from `old` to `new`
+///
+/// Only what changed is sent; nothing at all when neither did.
+fn detail_events(old: (&str, &str), new: (&str, &str)) -> Vec<HandleEvent> {
+    let mut events = Vec::new();
+    if new.0 != old.0 {
+        events.push(HandleEvent::Title(new.0.to_string()));
+    }
+    if new.1 != old.1 {
+        events.push(HandleEvent::AppId(new.1.to_string()));
+    }
+    if !events.is_empty() {
+        events.push(HandleEvent::Done);
+    }
+    events
+}
+
+/// Window nodes whose activated flag is wrong for the focused node, by id
+fn activation_changes(states: impl Iterator<Item = (SceneId, bool)>, focused: Option<SceneId>) -> Vec<SceneId> {
+    let mut changed: Vec<SceneId> = states
+        .filter(|(node_id, activated)| *activated != (Some(*node_id) == focused))
+        .map(|(node_id, _)| node_id)
+        .collect();
+    changed.sort_unstable();
+    changed
+}
+
+/// State array of the protocol, as native-endian u32 values
+fn state_bytes(state: &ToplevelState, version: u32) -> Vec<u8> {
+    use zwlr_foreign_toplevel_handle_v1::State;
+    
+    let flags = [
+        (state.maximized, State::Maximized),
+        (state.minimized, State::Minimized),
+        (state.activated, State::Activated),
+        // Fullscreen was added in version 2
+        (state.fullscreen && version >= 2, State::Fullscreen),
+    ];
+    flags.iter()
+        .filter(|(set, _)| *set)
+        .flat_map(|(_, flag)| (*flag as u32).to_ne_bytes())
+        .collect()
+}
+
+/// Title and app id of an xdg toplevel
+fn toplevel_details(toplevel: &ToplevelSurface) -> (String, String) {
+    with_states(toplevel.wl_surface(), |states| {
+        states.data_map.get::<XdgToplevelSurfaceData>()
+            .map(|data| {
+                let data = data.lock().unwrap();
+                (data.title.clone().unwrap_or_default(), data.app_id.clone().unwrap_or_default())
+            })
+            .unwrap_or_default()
+    })
+}
+
+impl GlobalDispatch<ZwlrForeignToplevelManagerV1, ForeignToplevelGlobalData> for AppState {
+    fn bind(
+        state: &mut Self,
+        display: &DisplayHandle,
+        _client: &Client,
+        resource: New<ZwlrForeignToplevelManagerV1>,
+        _global_data: &ForeignToplevelGlobalData,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        let manager = data_init.init(resource, ());
+        let outputs: Vec<Output> = state.space.outputs().cloned().collect();
+        state.protocol_manager.foreign_toplevel.add_manager(display, manager, &outputs);
+    }
+    
+    fn can_view(client: Client, global_data: &ForeignToplevelGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for AppState {
+    fn request(
+        state: &mut Self,
+        _client: &Client,
+        manager: &ZwlrForeignToplevelManagerV1,
+        request: zwlr_foreign_toplevel_manager_v1::Request,
+        _data: &(),
+        _display: &DisplayHandle,
+        _data_init: &mut DataInit<'_, Self>,
+    ) {
+        if let zwlr_foreign_toplevel_manager_v1::Request::Stop = request {
+            state.protocol_manager.foreign_toplevel.remove_manager(manager);
+            manager.finished();
+        }
+    }
+    
+    fn destroyed(state: &mut Self, _client: ClientId, manager: &ZwlrForeignToplevelManagerV1, _data: &()) {
+        state.protocol_manager.foreign_toplevel.remove_manager(manager);
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, SceneId> for AppState {
+    fn request(
+        state: &mut Self,
+        _client: &Client,
+        resource: &ZwlrForeignToplevelHandleV1,
+        request: zwlr_foreign_toplevel_handle_v1::Request,
+        node_id: &SceneId,
+        _display: &DisplayHandle,
+        _data_init: &mut DataInit<'_, Self>,
+    ) {
+        use zwlr_foreign_toplevel_handle_v1::Request;
+        
+        let node_id = *node_id;
+        match request {
+            Request::Activate { .. } => state.activate_window_node(node_id),
+            Request::Close => {
+                if let Some(toplevel) = state.protocol_manager.foreign_toplevel.window(node_id).and_then(|w| w.toplevel()) {
+                    toplevel.send_close();
+                }
+            }
+            Request::SetMinimized => state.set_window_node_minimized(node_id, true),
+            Request::UnsetMinimized => state.set_window_node_minimized(node_id, false),
+            Request::SetMaximized => state.set_window_node_xdg_state(node_id, xdg_toplevel::State::Maximized, true),
+            Request::UnsetMaximized => state.set_window_node_xdg_state(node_id, xdg_toplevel::State::Maximized, false),
+            Request::SetFullscreen { .. } => state.set_window_node_xdg_state(node_id, xdg_toplevel::State::Fullscreen, true),
+            Request::UnsetFullscreen => state.set_window_node_xdg_state(node_id, xdg_toplevel::State::Fullscreen, false),
+            Request::SetRectangle { surface, x, y, width, height } => {
+                if width < 0 || height < 0 {
+                    resource.post_error(
+                        zwlr_foreign_toplevel_handle_v1::Error::InvalidRectangle,
+                        "rectangle width and height must not be negative",
+                    );
+                    return;
+                }
+                if let Some(handle) = state.protocol_manager.foreign_toplevel.toplevels.get_mut(&node_id) {
+                    handle.rectangle = Some((surface, ScreencopyRegion { x, y, width, height }));
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    fn destroyed(state: &mut Self, _client: ClientId, resource: &ZwlrForeignToplevelHandleV1, node_id: &SceneId) {
+        state.protocol_manager.foreign_toplevel.forget_resource(*node_id, resource);
+    }
+}
+
+impl AppState {
+    /// Show a window node again if it was minimized, raise it and give it keyboard focus
+    pub fn activate_window_node(&mut self, node_id: SceneId) {
+        self.set_window_node_minimized(node_id, false);
+        let Some(window) = self.protocol_manager.foreign_toplevel.window(node_id).cloned() else {
+            return;
+        };
+        // Keyboard focus marks the node as active for taskbars
+        crate::window::focus_window(self, &window);
+    }
+    
+    /// Hide a window node from the desktop or bring it back
+    pub fn set_window_node_minimized(&mut self, node_id: SceneId, minimized: bool) {
+        let Some(handle) = self.protocol_manager.foreign_toplevel.toplevels.get_mut(&node_id) else {
+            return;
+        };
+        if handle.state.minimized == minimized {
+            return;
+        }
+        let window = handle.window.clone();
+        if minimized {
+            handle.restore_location = self.space.element_location(&window);
+            self.space.unmap_elem(&window);
+        } else {
+            let location = handle.restore_location.take().unwrap_or_default();
+            self.space.map_element(window, location, false);
+        }
+        if let Some(node) = self.graph_scene.lock().unwrap().get_node_mut(node_id) {
+            node.visible = !minimized;
+        }
+        self.protocol_manager.foreign_toplevel.update_state(node_id, |state| state.minimized = minimized);
+    }
+    
+    /// Maximize or fullscreen a window node to the first output, or undo it
+    fn set_window_node_xdg_state(&mut self, node_id: SceneId, xdg_state: xdg_toplevel::State, enabled: bool) {
+        let Some(toplevel) = self.protocol_manager.foreign_toplevel.window(node_id).and_then(|w| w.toplevel()).cloned() else {
+            return;
+        };
+        let output_size = self.space.outputs().next()
+            .and_then(|output| self.space.output_geometry(output))
+            .map(|geometry| geometry.size);
+        toplevel.with_pending_state(|pending| {
+            if enabled {
+                pending.states.set(xdg_state);
+                pending.size = output_size;
+            } else {
+                pending.states.unset(xdg_state);
+                pending.size = None;
+            }
+        });
+        toplevel.send_configure();
+        
+        self.protocol_manager.foreign_toplevel.update_state(node_id, |state| match xdg_state {
+            xdg_toplevel::State::Fullscreen => state.fullscreen = enabled,
+            _ => state.maximized = enabled,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zwlr_foreign_toplevel_handle_v1::State;
+
+    fn flags(bytes: &[u8]) -> Vec<u32> {
+        bytes.chunks(4).map(|chunk| u32::from_ne_bytes(chunk.try_into().unwrap())).collect()
+    }
+
+    #[test]
+    fn test_title_and_app_id_updates_send_only_changes() {
+        assert_eq!(detail_events(("Editor", "org.example.Editor"), ("Editor", "org.example.Editor")), []);
+        assert_eq!(
+            detail_events(("Editor", "org.example.Editor"), ("notes.md - Editor", "org.example.Editor")),
+            [HandleEvent::Title("notes.md - Editor".to_string()), HandleEvent::Done]
+        );
+        assert_eq!(
+            detail_events(("", ""), ("Terminal", "foot")),
+            [HandleEvent::Title("Terminal".to_string()), HandleEvent::AppId("foot".to_string()), HandleEvent::Done]
+        );
+    }
+
+    #[test]
+    fn test_state_updates_list_set_flags() {
+        assert!(state_bytes(&ToplevelState::default(), FOREIGN_TOPLEVEL_VERSION).is_empty());
+
+        let state = ToplevelState { maximized: true, activated: true, fullscreen: true, ..Default::default() };
+        assert_eq!(
+            flags(&state_bytes(&state, 2)),
+            [State::Maximized as u32, State::Activated as u32, State::Fullscreen as u32]
+        );
+        // Taskbars bound at version 1 do not know fullscreen
+        assert_eq!(flags(&state_bytes(&state, 1)), [State::Maximized as u32, State::Activated as u32]);
+    }
+
+    #[test]
+    fn test_activation_follows_keyboard_focus() {
+        let states = || [(1, true), (2, false), (3, false)].into_iter();
+        assert_eq!(activation_changes(states(), Some(1)), Vec::<SceneId>::new());
+        assert_eq!(activation_changes(states(), Some(3)), [1, 3]);
+        // Focus on something other than a window node deactivates them all
+        assert_eq!(activation_changes(states(), None), [1]);
+    }
+
+    #[test]
+    fn test_closed_windows_are_forgotten() {
+        let mut state = ForeignToplevelState::default();
+        // Windows never announced, or closed already, are ignored
+        state.remove_toplevel(4);
+        state.update_state(4, |state| state.minimized = true);
+        state.refresh_details(4);
+        state.set_activated(Some(4));
+        assert!(state.window(4).is_none());
+        assert!(state.toplevels.is_empty());
+    }
+}
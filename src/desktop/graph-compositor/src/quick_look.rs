@@ -0,0 +1,289 @@
+//! Quick Look overlay as the compositor shows it
+//!
+//! The interaction crate tracks which file the overlay shows; the compositor
+//! opens it on Space over a focused file node, loads the preview on the
+//! services' runtime and draws it over the windows. Audio and video play
+//! through [`MediaPlayer`] while their file is shown. The compositor has no
+//! glyph rendering yet, so text previews are drawn as their outline: one bar
+//! per highlighted span, as long as the span and in its highlight colour.
+
+use anyhow::Result;
+use horizonos_graph_interaction::{QuickLookEvent, QuickLookItem};
+use horizonos_graph_visual::{MediaPlayer, PreviewContent, PreviewLoader, ThumbnailGenerator, TokenKind};
+use image::DynamicImage;
+use std::sync::{mpsc, Arc};
+
+/// Share of the output the preview panel covers
+const PANEL_SCALE: f32 = 0.8;
+/// Space between the panel's edge and its content, and between pages
+const PANEL_PADDING: i32 = 24;
+/// Height of a text line and of the bar drawn for its spans, in pixels
+const LINE_HEIGHT: i32 = 14;
+const BAR_HEIGHT: i32 = 8;
+/// Width of a character of text, in pixels
+const CHAR_WIDTH: i32 = 7;
+
+/// Keys the open overlay responds to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickLookKey {
+    Next,
+    Previous,
+    Close,
+}
+
+/// File shown in the overlay and its preview
+pub struct QuickLookPreview {
+    pub item: QuickLookItem,
+    /// Changes with every file shown, so textures of the last one are replaced
+    pub generation: u64,
+    /// `None` while the preview is loading
+    pub content: Option<PreviewContent>,
+}
+
+/// Loads and plays what the overlay shows
+pub struct QuickLookView {
+    loader: Arc<PreviewLoader>,
+    runtime: Option<tokio::runtime::Handle>,
+    loaded: (mpsc::Sender<(u64, Result<PreviewContent>)>, mpsc::Receiver<(u64, Result<PreviewContent>)>),
+    preview: Option<QuickLookPreview>,
+    generation: u64,
+    player: Option<MediaPlayer>,
+}
+
+impl QuickLookView {
+    pub fn new() -> Self {
+        Self {
+            loader: Arc::new(PreviewLoader::default()),
+            runtime: None,
+            loaded: mpsc::channel(),
+            preview: None,
+            generation: 0,
+            player: None,
+        }
+    }
+
+    /// Load previews on the runtime, with video poster frames from `thumbnails`
+    pub fn start(&mut self, runtime: tokio::runtime::Handle, thumbnails: Option<Arc<ThumbnailGenerator>>) {
+        let loader = PreviewLoader::default();
+        self.loader = Arc::new(match thumbnails {
+            Some(thumbnails) => loader.with_thumbnails(thumbnails),
+            None => loader,
+        });
+        self.runtime = Some(runtime);
+    }
+
+    /// Follow a change of the shown file, loading the new one
+    pub fn handle(&mut self, event: QuickLookEvent) {
+        self.player = None;
+        match event {
+            QuickLookEvent::Opened(item) | QuickLookEvent::Changed(item) => {
+                self.generation += 1;
+                let generation = self.generation;
+                if let Some(runtime) = &self.runtime {
+                    let loader = self.loader.clone();
+                    let sender = self.loaded.0.clone();
+                    let path = item.path.clone();
+                    runtime.spawn(async move {
+                        let _ = sender.send((generation, loader.load(&path).await));
+                    });
+                }
+                self.preview = Some(QuickLookPreview { item, generation, content: None });
+            }
+            QuickLookEvent::Closed => self.preview = None,
+        }
+    }
+
+    /// Take loaded previews, starting playback of audio and video
+    ///
+    /// Previews of files no longer shown are dropped; a file that could not
+    /// be loaded is shown like one without a preview.
+    pub fn update(&mut self) {
+        for (generation, content) in self.loaded.1.try_iter() {
+            let Some(preview) = self.preview.as_mut().filter(|preview| preview.generation == generation) else {
+                continue;
+            };
+            let content = content.unwrap_or_else(|e| {
+                log::warn!("No preview of {}: {}", preview.item.path.display(), e);
+                PreviewContent::Unsupported { mime: String::new() }
+            });
+            if let PreviewContent::Media { path, .. } = &content {
+                match MediaPlayer::play(path) {
+                    Ok(player) => self.player = Some(player),
+                    Err(e) => log::warn!("Could not play {}: {}", path.display(), e),
+                }
+            }
+            preview.content = Some(content);
+        }
+    }
+
+    /// File shown, while the overlay is open
+    pub fn preview(&self) -> Option<&QuickLookPreview> {
+        self.preview.as_ref()
+    }
+}
+
+impl Default for QuickLookView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What a rectangle of the overlay is filled with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickLookFill {
+    Panel,
+    /// One of the preview's [`images`], by index
+    Image(usize),
+    /// A span of text
+    Span(TokenKind),
+}
+
+/// Part of the overlay as drawn, in output pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuickLookRect {
+    /// Position and size
+    pub rect: [i32; 4],
+    pub fill: QuickLookFill,
+}
+
+/// Images a preview shows: the image, the document's pages or the poster frame
+pub fn images(content: &PreviewContent) -> Vec<Arc<DynamicImage>> {
+    match content {
+        PreviewContent::Image { image } => vec![image.clone()],
+        PreviewContent::Document { pages } => pages.clone(),
+        PreviewContent::Media { poster, .. } => poster.iter().cloned().collect(),
+        PreviewContent::Text { .. } | PreviewContent::Unsupported { .. } => Vec::new(),
+    }
+}
+
+/// Lay out a preview on a centered panel, the panel first
+///
+/// Images sit side by side, each fitted to its share of the panel; text
+/// lines run from the top until the panel is full. Previews still loading
+/// and files without one show the panel alone.
+pub fn layout(content: Option<&PreviewContent>, output_size: (i32, i32)) -> Vec<QuickLookRect> {
+    let width = (output_size.0 as f32 * PANEL_SCALE) as i32;
+    let height = (output_size.1 as f32 * PANEL_SCALE) as i32;
+    let panel = [(output_size.0 - width) / 2, (output_size.1 - height) / 2, width, height];
+    let mut rects = vec![QuickLookRect { rect: panel, fill: QuickLookFill::Panel }];
+
+    let left = panel[0] + PANEL_PADDING;
+    let top = panel[1] + PANEL_PADDING;
+    let inner = ((width - 2 * PANEL_PADDING).max(0), (height - 2 * PANEL_PADDING).max(0));
+    match content {
+        Some(PreviewContent::Text { lines, .. }) => {
+            let visible = (inner.1 / LINE_HEIGHT).max(0) as usize;
+            for (row, line) in lines.iter().take(visible).enumerate() {
+                let y = top + row as i32 * LINE_HEIGHT + (LINE_HEIGHT - BAR_HEIGHT) / 2;
+                let mut column = 0;
+                for span in line {
+                    // Bars start at the span's first visible character and end at its last
+                    let chars: Vec<char> = span.text.chars().collect();
+                    let start = chars.iter().take_while(|c| c.is_whitespace()).count() as i32;
+                    let end = chars.len() as i32 - chars.iter().rev().take_while(|c| c.is_whitespace()).count() as i32;
+                    let x = (column + start) * CHAR_WIDTH;
+                    let bar_width = ((end - start) * CHAR_WIDTH).min(inner.0 - x);
+                    if end > start && bar_width > 0 {
+                        rects.push(QuickLookRect { rect: [left + x, y, bar_width, BAR_HEIGHT], fill: QuickLookFill::Span(span.kind) });
+                    }
+                    column += chars.len() as i32;
+                }
+            }
+        }
+        Some(content) => {
+            let images = images(content);
+            if images.is_empty() {
+                return rects;
+            }
+            let count = images.len() as i32;
+            let cell = (((inner.0 - PANEL_PADDING * (count - 1)) / count).max(1), inner.1.max(1));
+            for (i, image) in images.iter().enumerate() {
+                let (w, h) = crate::switcher::fit(image.width(), image.height(), (cell.0 as u32, cell.1 as u32));
+                let x = left + i as i32 * (cell.0 + PANEL_PADDING) + (cell.0 - w as i32) / 2;
+                let y = top + (cell.1 - h as i32) / 2;
+                rects.push(QuickLookRect { rect: [x, y, w as i32, h as i32], fill: QuickLookFill::Image(i) });
+            }
+        }
+        None => {}
+    }
+    rects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_visual::TextSpan;
+    use std::path::PathBuf;
+
+    fn image(width: u32, height: u32) -> Arc<DynamicImage> {
+        Arc::new(DynamicImage::new_rgba8(width, height))
+    }
+
+    #[test]
+    fn test_images_fit_the_centered_panel() {
+        let content = PreviewContent::Image { image: image(4000, 1000) };
+        let rects = layout(Some(&content), (1000, 1000));
+
+        assert_eq!(rects[0], QuickLookRect { rect: [100, 100, 800, 800], fill: QuickLookFill::Panel });
+        assert_eq!(rects[1], QuickLookRect { rect: [124, 406, 752, 188], fill: QuickLookFill::Image(0) });
+    }
+
+    #[test]
+    fn test_document_pages_sit_side_by_side() {
+        let content = PreviewContent::Document { pages: vec![image(100, 100), image(100, 100)] };
+        let rects = layout(Some(&content), (1000, 1000));
+
+        let pages: Vec<[i32; 4]> = rects[1..].iter().map(|rect| rect.rect).collect();
+        assert_eq!(pages, [[124, 318, 364, 364], [512, 318, 364, 364]]);
+    }
+
+    #[test]
+    fn test_text_spans_become_bars() {
+        let span = |text: &str, kind| TextSpan { text: text.to_string(), kind };
+        let content = PreviewContent::Text {
+            language: Some("rust"),
+            lines: vec![
+                vec![span("fn", TokenKind::Keyword), span(" main() {", TokenKind::Plain)],
+                vec![],
+                vec![span("    ", TokenKind::Plain), span("// done", TokenKind::Comment)],
+            ],
+            truncated: false,
+        };
+        let rects = layout(Some(&content), (1000, 1000));
+
+        let bars: Vec<QuickLookRect> = rects[1..].to_vec();
+        assert_eq!(bars, [
+            QuickLookRect { rect: [124, 127, 14, 8], fill: QuickLookFill::Span(TokenKind::Keyword) },
+            QuickLookRect { rect: [145, 127, 56, 8], fill: QuickLookFill::Span(TokenKind::Plain) },
+            QuickLookRect { rect: [152, 155, 49, 8], fill: QuickLookFill::Span(TokenKind::Comment) },
+        ]);
+    }
+
+    #[test]
+    fn test_loading_and_unsupported_show_the_panel() {
+        assert_eq!(layout(None, (1000, 1000)).len(), 1);
+        let unsupported = PreviewContent::Unsupported { mime: "application/zip".to_string() };
+        assert_eq!(layout(Some(&unsupported), (1000, 1000)).len(), 1);
+    }
+
+    #[test]
+    fn test_previews_of_files_no_longer_shown_are_dropped() {
+        let mut view = QuickLookView::new();
+        let item = |node_id| QuickLookItem { node_id, path: PathBuf::from(format!("/tmp/{}.txt", node_id)) };
+        view.handle(QuickLookEvent::Opened(item(1)));
+        view.handle(QuickLookEvent::Changed(item(2)));
+        let shown = view.preview().unwrap().generation;
+
+        let text = |line: &str| PreviewContent::Text { language: None, lines: vec![vec![TextSpan { text: line.to_string(), kind: TokenKind::Plain }]], truncated: false };
+        view.loaded.0.send((shown - 1, Ok(text("old")))).unwrap();
+        view.loaded.0.send((shown, Ok(text("new")))).unwrap();
+        view.update();
+
+        let preview = view.preview().unwrap();
+        assert_eq!(preview.item, item(2));
+        assert!(matches!(&preview.content, Some(PreviewContent::Text { lines, .. }) if lines[0][0].text == "new"));
+
+        view.handle(QuickLookEvent::Closed);
+        assert!(view.preview().is_none());
+    }
+}
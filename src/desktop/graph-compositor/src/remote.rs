@@ -0,0 +1,1211 @@
+//! Remote view server for the graph desktop
+//!
+//! Streams the composited desktop, or a single workspace, to remote viewers over
+//! TCP. Viewers authenticate with a shared token, frames are sent as changed
+//! tiles only and throttled to a per-viewer bandwidth budget, and input sent by
+//! a viewer is only injected after the local user accepts a consent prompt.
+//! The stream is not encrypted, so the server only binds loopback addresses;
+//! viewers on other machines connect through an SSH tunnel.
+//!
+//! Wire protocol (all integers big-endian):
+//! - Handshake: the viewer sends `HORIZON-REMOTE 1 <token>\n`, the server
+//!   answers `OK <width> <height>\n` or `DENIED\n` and closes.
+//! - Frames: `b'F'`, frame number (u32), width (u16), height (u16), bytes per
+//!   pixel (u8), tile count (u16), then per tile x, y, w, h (u16 each),
+//!   payload length (u32) and the raw pixels. Two bytes per pixel is RGB565,
+//!   three is RGB888; coordinates are in the (possibly downscaled) frame.
+//! - Viewer messages: `b'I'` followed by an input event, `b'Q'` followed by a
+//!   quality level (0 = low .. 3 = lossless).
+//!   Input events are `0` pointer motion (x, y as f32 in desktop pixels),
+//!   `1` pointer button (button u32, pressed u8), `2` key (evdev keycode
+//!   u32, pressed u8) and `3` scroll (horizontal, vertical as f32).
+
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::CompositorError;
+
+/// Protocol version spoken by the server
+const PROTOCOL_VERSION: u32 = 1;
+/// Tile edge length in pixels
+const TILE_SIZE: usize = 64;
+/// Longest handshake line accepted from a viewer
+const MAX_HANDSHAKE_LEN: usize = 512;
+/// Connections allowed to be authenticating at once; later ones are closed
+const MAX_PENDING_HANDSHAKES: usize = 4;
+/// Frames dropped in a row before quality is lowered
+const DOWNGRADE_AFTER_DROPS: u32 = 3;
+/// Frames sent in a row within budget before quality is raised again
+const UPGRADE_AFTER_FRAMES: u32 = 120;
+
+/// Remote view server configuration
+#[derive(Debug, Clone)]
+pub struct RemoteViewConfig {
+    /// Address to listen on
+    pub bind_address: SocketAddr,
+    /// Shared token viewers must present
+    pub access_token: String,
+    /// Bandwidth budget per viewer in kilobytes per second
+    pub max_bandwidth_kbps: u32,
+    /// Highest quality streamed to viewers
+    pub quality: RemoteQuality,
+    /// Lower quality automatically when the budget is exceeded
+    pub adaptive_quality: bool,
+    /// What is streamed
+    pub scope: RemoteScope,
+    /// Allow viewers to request input control
+    pub allow_input: bool,
+    /// Maximum simultaneous viewers
+    pub max_viewers: usize,
+}
+
+/// Stream quality levels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RemoteQuality {
+    /// Quarter resolution, 16-bit color
+    Low,
+    /// Half resolution, 16-bit color
+    Medium,
+    /// Full resolution, 16-bit color
+    High,
+    /// Full resolution, 24-bit color
+    Lossless,
+}
+
+/// What part of the desktop is streamed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteScope {
+    /// The whole composited desktop
+    Desktop,
+    /// Only while the given workspace is active
+    Workspace(String),
+}
+
+/// Consent state for a viewer's input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputConsent {
+    /// Viewer has not asked for control
+    NotRequested,
+    /// Waiting for the local user to answer the prompt
+    Pending,
+    /// Local user allowed input
+    Granted,
+    /// Local user refused input
+    Denied,
+}
+
+/// Input event sent by a remote viewer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RemoteInputEvent {
+    /// Absolute pointer position in desktop pixels
+    PointerMotion { x: f64, y: f64 },
+    /// Pointer button press or release
+    PointerButton { button: u32, pressed: bool },
+    /// Key press or release (evdev keycode)
+    Key { keycode: u32, pressed: bool },
+    /// Scroll amounts
+    Scroll { horizontal: f64, vertical: f64 },
+}
+
+/// Events surfaced to the compositor
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteViewEvent {
+    /// A viewer authenticated and started watching
+    ViewerConnected { viewer_id: u64, peer: SocketAddr },
+    /// A viewer disconnected
+    ViewerDisconnected { viewer_id: u64 },
+    /// A viewer failed authentication
+    AuthenticationFailed { peer: SocketAddr },
+    /// A viewer wants to control input; show a consent prompt
+    ConsentRequested { viewer_id: u64, peer: SocketAddr },
+    /// Stream quality for a viewer changed
+    QualityChanged { viewer_id: u64, quality: RemoteQuality },
+}
+
+/// Summary of a connected viewer
+#[derive(Debug, Clone)]
+pub struct RemoteViewerInfo {
+    /// Viewer ID
+    pub viewer_id: u64,
+    /// Remote address
+    pub peer: SocketAddr,
+    /// Input consent state
+    pub consent: InputConsent,
+    /// Current stream quality
+    pub quality: RemoteQuality,
+    /// Bytes sent so far
+    pub bytes_sent: u64,
+    /// Connection time
+    pub connected_at: Instant,
+}
+
+/// One composited frame handed to the server
+#[derive(Debug, Clone, Copy)]
+pub struct RemoteFrame<'a> {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Tightly packed RGBA8 pixels
+    pub pixels: &'a [u8],
+    /// Workspace shown in this frame, if known
+    pub workspace: Option<&'a str>,
+}
+
+/// Remote view server
+pub struct RemoteViewServer {
+    config: RemoteViewConfig,
+    shared: Arc<Mutex<SharedState>>,
+    running: Arc<AtomicBool>,
+    next_frame: u32,
+    local_addr: SocketAddr,
+}
+
+/// State shared between the compositor and network threads
+struct SharedState {
+    viewers: HashMap<u64, ViewerSlot>,
+    events: VecDeque<RemoteViewEvent>,
+    input: VecDeque<(u64, RemoteInputEvent)>,
+    desktop_size: (u32, u32),
+    next_viewer_id: u64,
+}
+
+/// Server-side bookkeeping for a viewer
+struct ViewerSlot {
+    peer: SocketAddr,
+    consent: InputConsent,
+    quality: Arc<Mutex<RemoteQuality>>,
+    frames: SyncSender<Arc<OwnedFrame>>,
+    bytes_sent: Arc<AtomicU64>,
+    stream: TcpStream,
+    connected_at: Instant,
+}
+
+/// Frame copied out of the compositor for the writer threads
+struct OwnedFrame {
+    number: u32,
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+/// Token bucket limiting bytes per second
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    rate: f64,
+    last_refill: Instant,
+}
+
+impl RemoteViewConfig {
+    /// Server configuration from the `remote_view` section of the desktop config
+    pub fn from_config(
+        config: &horizonos_graph_config::RemoteViewConfig,
+        access_token: String,
+    ) -> Result<Self, CompositorError> {
+        let bind_address = config.bind_address.parse().map_err(|_| {
+            CompositorError::Config(format!("Invalid remote view bind address: {}", config.bind_address))
+        })?;
+        let quality = match config.quality.as_str() {
+            "low" => RemoteQuality::Low,
+            "medium" => RemoteQuality::Medium,
+            "high" => RemoteQuality::High,
+            "lossless" => RemoteQuality::Lossless,
+            other => {
+                return Err(CompositorError::Config(format!("Invalid remote view quality: {}", other)));
+            }
+        };
+        let scope = match &config.workspace {
+            Some(workspace) => RemoteScope::Workspace(workspace.clone()),
+            None => RemoteScope::Desktop,
+        };
+
+        Ok(Self {
+            bind_address,
+            access_token,
+            max_bandwidth_kbps: config.max_bandwidth_kbps,
+            quality,
+            adaptive_quality: config.adaptive_quality,
+            scope,
+            allow_input: config.allow_input,
+            max_viewers: config.max_viewers,
+        })
+    }
+}
+
+impl RemoteViewServer {
+    /// Bind the listener and start accepting viewers
+    pub fn start(config: RemoteViewConfig) -> Result<Self, CompositorError> {
+        if config.access_token.len() < 8 {
+            return Err(CompositorError::Protocol(
+                "Remote view access token must be at least 8 characters".to_string(),
+            ));
+        }
+        if !config.bind_address.ip().is_loopback() {
+            // Frames, the token and injected input would cross the network in the clear
+            return Err(CompositorError::Config(format!(
+                "Remote view must bind to a loopback address, not {}; connect through an SSH tunnel",
+                config.bind_address
+            )));
+        }
+
+        let listener = TcpListener::bind(config.bind_address)?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+
+        let shared = Arc::new(Mutex::new(SharedState {
+            viewers: HashMap::new(),
+            events: VecDeque::new(),
+            input: VecDeque::new(),
+            desktop_size: (0, 0),
+            next_viewer_id: 1,
+        }));
+        let running = Arc::new(AtomicBool::new(true));
+
+        {
+            let config = config.clone();
+            let shared = shared.clone();
+            let running = running.clone();
+            thread::Builder::new()
+                .name("remote-view-accept".to_string())
+                .spawn(move || accept_loop(listener, config, shared, running))?;
+        }
+
+        log::info!("Remote view listening on {}", local_addr);
+
+        Ok(Self {
+            config,
+            shared,
+            running,
+            next_frame: 0,
+            local_addr,
+        })
+    }
+
+    /// Address the server is listening on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Server configuration
+    pub fn config(&self) -> &RemoteViewConfig {
+        &self.config
+    }
+
+    /// Hand a composited frame to connected viewers
+    ///
+    /// Viewers that are still sending the previous frame skip this one.
+    pub fn submit_frame(&mut self, frame: RemoteFrame<'_>) {
+        if let RemoteScope::Workspace(ref workspace) = self.config.scope {
+            if frame.workspace != Some(workspace.as_str()) {
+                return;
+            }
+        }
+
+        let expected = frame.width as usize * frame.height as usize * 4;
+        if frame.pixels.len() < expected {
+            log::warn!("Remote view frame is too small: {} < {}", frame.pixels.len(), expected);
+            return;
+        }
+
+        let mut shared = self.shared.lock().unwrap();
+        shared.desktop_size = (frame.width, frame.height);
+        if shared.viewers.is_empty() {
+            return;
+        }
+
+        self.next_frame = self.next_frame.wrapping_add(1);
+        let owned = Arc::new(OwnedFrame {
+            number: self.next_frame,
+            width: frame.width,
+            height: frame.height,
+            pixels: frame.pixels[..expected].to_vec(),
+        });
+
+        for slot in shared.viewers.values() {
+            // A full channel means the viewer is still busy with an earlier frame
+            let _ = slot.frames.try_send(owned.clone());
+        }
+    }
+
+    /// Whether anyone is watching, so frames are worth capturing
+    pub fn has_viewers(&self) -> bool {
+        !self.shared.lock().unwrap().viewers.is_empty()
+    }
+
+    /// Take pending server events
+    pub fn poll_events(&mut self) -> Vec<RemoteViewEvent> {
+        self.shared.lock().unwrap().events.drain(..).collect()
+    }
+
+    /// Take input from viewers whose consent was granted
+    pub fn drain_input(&mut self) -> Vec<RemoteInputEvent> {
+        let mut shared = self.shared.lock().unwrap();
+        let queued: Vec<_> = shared.input.drain(..).collect();
+        queued
+            .into_iter()
+            .filter(|(viewer_id, _)| {
+                shared
+                    .viewers
+                    .get(viewer_id)
+                    .map(|slot| slot.consent == InputConsent::Granted)
+                    .unwrap_or(false)
+            })
+            .map(|(_, event)| event)
+            .collect()
+    }
+
+    /// Answer a consent prompt for a viewer
+    pub fn respond_to_consent(&mut self, viewer_id: u64, granted: bool) {
+        let mut shared = self.shared.lock().unwrap();
+        if let Some(slot) = shared.viewers.get_mut(&viewer_id) {
+            slot.consent = if granted { InputConsent::Granted } else { InputConsent::Denied };
+            log::info!(
+                "Remote input for viewer {} {}",
+                viewer_id,
+                if granted { "granted" } else { "denied" }
+            );
+        }
+        if !granted {
+            shared.input.retain(|(id, _)| *id != viewer_id);
+        }
+    }
+
+    /// Withdraw input control from a viewer
+    pub fn revoke_input(&mut self, viewer_id: u64) {
+        self.respond_to_consent(viewer_id, false);
+    }
+
+    /// Change the stream quality of a viewer
+    pub fn set_viewer_quality(&mut self, viewer_id: u64, quality: RemoteQuality) {
+        let shared = self.shared.lock().unwrap();
+        if let Some(slot) = shared.viewers.get(&viewer_id) {
+            *slot.quality.lock().unwrap() = quality.min(self.config.quality);
+        }
+    }
+
+    /// Disconnect a viewer
+    pub fn disconnect(&mut self, viewer_id: u64) {
+        let mut shared = self.shared.lock().unwrap();
+        if let Some(slot) = shared.viewers.remove(&viewer_id) {
+            let _ = slot.stream.shutdown(Shutdown::Both);
+            shared.input.retain(|(id, _)| *id != viewer_id);
+            shared.events.push_back(RemoteViewEvent::ViewerDisconnected { viewer_id });
+        }
+    }
+
+    /// Connected viewers
+    pub fn viewers(&self) -> Vec<RemoteViewerInfo> {
+        let shared = self.shared.lock().unwrap();
+        shared
+            .viewers
+            .iter()
+            .map(|(&viewer_id, slot)| RemoteViewerInfo {
+                viewer_id,
+                peer: slot.peer,
+                consent: slot.consent,
+                quality: *slot.quality.lock().unwrap(),
+                bytes_sent: slot.bytes_sent.load(Ordering::Relaxed),
+                connected_at: slot.connected_at,
+            })
+            .collect()
+    }
+
+    /// Stop accepting viewers and disconnect everyone
+    pub fn shutdown(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        let mut shared = self.shared.lock().unwrap();
+        for (_, slot) in shared.viewers.drain() {
+            let _ = slot.stream.shutdown(Shutdown::Both);
+        }
+        shared.input.clear();
+    }
+}
+
+impl Drop for RemoteViewServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl RemoteQuality {
+    /// Downscale divisor applied to both dimensions
+    pub fn scale_divisor(self) -> usize {
+        match self {
+            RemoteQuality::Low => 4,
+            RemoteQuality::Medium => 2,
+            RemoteQuality::High | RemoteQuality::Lossless => 1,
+        }
+    }
+
+    /// Bytes per streamed pixel
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            RemoteQuality::Lossless => 3,
+            _ => 2,
+        }
+    }
+
+    /// Next lower quality level
+    fn lower(self) -> Self {
+        match self {
+            RemoteQuality::Lossless => RemoteQuality::High,
+            RemoteQuality::High => RemoteQuality::Medium,
+            _ => RemoteQuality::Low,
+        }
+    }
+
+    /// Next higher quality level
+    fn higher(self) -> Self {
+        match self {
+            RemoteQuality::Low => RemoteQuality::Medium,
+            RemoteQuality::Medium => RemoteQuality::High,
+            _ => RemoteQuality::Lossless,
+        }
+    }
+
+    fn from_wire(level: u8) -> Self {
+        match level {
+            0 => RemoteQuality::Low,
+            1 => RemoteQuality::Medium,
+            2 => RemoteQuality::High,
+            _ => RemoteQuality::Lossless,
+        }
+    }
+}
+
+impl TokenBucket {
+    fn new(bytes_per_second: f64) -> Self {
+        // Allow bursts of up to one second of budget, or one larger frame
+        Self {
+            capacity: bytes_per_second,
+            tokens: bytes_per_second,
+            rate: bytes_per_second,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Take `bytes` from the bucket if the budget allows
+    ///
+    /// A message larger than the whole bucket goes out once the bucket is
+    /// full; the overdraft is paid back before anything else is sent.
+    fn try_take(&mut self, bytes: usize) -> bool {
+        self.refill();
+        let bytes = bytes as f64;
+        if self.tokens >= bytes.min(self.capacity) {
+            self.tokens -= bytes;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Accept viewers until the server stops
+fn accept_loop(
+    listener: TcpListener,
+    config: RemoteViewConfig,
+    shared: Arc<Mutex<SharedState>>,
+    running: Arc<AtomicBool>,
+) {
+    let pending = Arc::new(AtomicUsize::new(0));
+    while running.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                let Some(handshake) = HandshakeSlot::claim(&pending) else {
+                    // Unauthenticated peers must not be able to pile up threads
+                    log::warn!("Remote view refused {}: too many pending handshakes", peer);
+                    let _ = stream.shutdown(Shutdown::Both);
+                    continue;
+                };
+                let config = config.clone();
+                let shared = shared.clone();
+                let running = running.clone();
+                let spawned = thread::Builder::new()
+                    .name(format!("remote-view-{}", peer))
+                    .spawn(move || {
+                        if let Err(e) = serve_viewer(stream, peer, handshake, config, shared, running) {
+                            log::debug!("Remote viewer {} ended: {}", peer, e);
+                        }
+                    });
+                if let Err(e) = spawned {
+                    log::warn!("Failed to spawn remote viewer thread: {}", e);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(50));
+            }
+            Err(e) => {
+                log::warn!("Remote view accept failed: {}", e);
+                thread::sleep(Duration::from_millis(250));
+            }
+        }
+    }
+}
+
+/// A connection counted against [`MAX_PENDING_HANDSHAKES`] until dropped
+struct HandshakeSlot(Arc<AtomicUsize>);
+
+impl HandshakeSlot {
+    fn claim(pending: &Arc<AtomicUsize>) -> Option<Self> {
+        pending
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                (count < MAX_PENDING_HANDSHAKES).then_some(count + 1)
+            })
+            .ok()
+            .map(|_| Self(pending.clone()))
+    }
+}
+
+impl Drop for HandshakeSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Authenticate a viewer, then run its reader and writer
+fn serve_viewer(
+    stream: TcpStream,
+    peer: SocketAddr,
+    handshake: HandshakeSlot,
+    config: RemoteViewConfig,
+    shared: Arc<Mutex<SharedState>>,
+    running: Arc<AtomicBool>,
+) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_nodelay(true)?;
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream.try_clone()?;
+
+    let mut line = String::new();
+    (&mut reader).take(MAX_HANDSHAKE_LEN as u64).read_line(&mut line)?;
+
+    if !check_handshake(&line, &config.access_token) {
+        log::warn!("Remote view authentication failed from {}", peer);
+        shared.lock().unwrap().events.push_back(RemoteViewEvent::AuthenticationFailed { peer });
+        // Slow down token guessing
+        thread::sleep(Duration::from_secs(1));
+        writer.write_all(b"DENIED\n")?;
+        return Ok(());
+    }
+
+    let quality = Arc::new(Mutex::new(config.quality));
+    let bytes_sent = Arc::new(AtomicU64::new(0));
+    let (frame_tx, frame_rx) = mpsc::sync_channel(1);
+
+    let viewer_id = {
+        let mut state = shared.lock().unwrap();
+        if state.viewers.len() >= config.max_viewers {
+            drop(state);
+            writer.write_all(b"DENIED\n")?;
+            return Ok(());
+        }
+
+        let viewer_id = state.next_viewer_id;
+        state.next_viewer_id += 1;
+        let (width, height) = state.desktop_size;
+        writer.write_all(format!("OK {} {}\n", width, height).as_bytes())?;
+
+        state.viewers.insert(viewer_id, ViewerSlot {
+            peer,
+            consent: InputConsent::NotRequested,
+            quality: quality.clone(),
+            frames: frame_tx,
+            bytes_sent: bytes_sent.clone(),
+            stream: stream.try_clone()?,
+            connected_at: Instant::now(),
+        });
+        state.events.push_back(RemoteViewEvent::ViewerConnected { viewer_id, peer });
+        viewer_id
+    };
+    drop(handshake);
+
+    log::info!("Remote viewer {} connected from {}", viewer_id, peer);
+
+    {
+        let shared = shared.clone();
+        let config = config.clone();
+        let quality = quality.clone();
+        thread::Builder::new()
+            .name(format!("remote-view-write-{}", viewer_id))
+            .spawn(move || {
+                let mut writer = FrameWriter::new(viewer_id, config, quality, shared, bytes_sent);
+                writer.run(frame_rx, stream);
+            })?;
+    }
+
+    reader.get_ref().set_read_timeout(None)?;
+    let result = read_viewer_messages(&mut reader, viewer_id, peer, &config, &shared, &running);
+
+    let mut state = shared.lock().unwrap();
+    if state.viewers.remove(&viewer_id).is_some() {
+        state.input.retain(|(id, _)| *id != viewer_id);
+        state.events.push_back(RemoteViewEvent::ViewerDisconnected { viewer_id });
+    }
+    log::info!("Remote viewer {} disconnected", viewer_id);
+
+    result
+}
+
+/// Validate the handshake line against the access token
+fn check_handshake(line: &str, token: &str) -> bool {
+    let mut parts = line.trim_end().splitn(3, ' ');
+    let magic = parts.next();
+    let version = parts.next().and_then(|v| v.parse::<u32>().ok());
+    let presented = parts.next().unwrap_or("");
+
+    magic == Some("HORIZON-REMOTE")
+        && version == Some(PROTOCOL_VERSION)
+        && constant_time_eq(presented.as_bytes(), token.as_bytes())
+}
+
+/// Compare secrets without leaking where they differ
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let mut diff = (a.len() != b.len()) as u8;
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).copied().unwrap_or(0);
+        let y = b.get(i).copied().unwrap_or(0);
+        diff |= x ^ y;
+    }
+    diff == 0
+}
+
+/// Read input and quality messages from a viewer until it disconnects
+fn read_viewer_messages(
+    reader: &mut impl Read,
+    viewer_id: u64,
+    peer: SocketAddr,
+    config: &RemoteViewConfig,
+    shared: &Arc<Mutex<SharedState>>,
+    running: &Arc<AtomicBool>,
+) -> std::io::Result<()> {
+    while running.load(Ordering::SeqCst) {
+        let mut tag = [0u8; 1];
+        reader.read_exact(&mut tag)?;
+
+        match tag[0] {
+            b'I' => {
+                let event = read_input_event(reader)?;
+                if !config.allow_input {
+                    continue;
+                }
+
+                let mut state = shared.lock().unwrap();
+                let Some(slot) = state.viewers.get_mut(&viewer_id) else {
+                    break;
+                };
+                match slot.consent {
+                    InputConsent::Granted => state.input.push_back((viewer_id, event)),
+                    InputConsent::NotRequested => {
+                        slot.consent = InputConsent::Pending;
+                        state.events.push_back(RemoteViewEvent::ConsentRequested { viewer_id, peer });
+                    }
+                    // Input is dropped until the local user answers, and after a refusal
+                    InputConsent::Pending | InputConsent::Denied => {}
+                }
+            }
+            b'Q' => {
+                let mut level = [0u8; 1];
+                reader.read_exact(&mut level)?;
+                let requested = RemoteQuality::from_wire(level[0]).min(config.quality);
+
+                let mut state = shared.lock().unwrap();
+                if let Some(slot) = state.viewers.get(&viewer_id) {
+                    *slot.quality.lock().unwrap() = requested;
+                    state.events.push_back(RemoteViewEvent::QualityChanged { viewer_id, quality: requested });
+                }
+            }
+            other => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("unknown message tag {:#x}", other),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Decode one input event
+fn read_input_event(reader: &mut impl Read) -> std::io::Result<RemoteInputEvent> {
+    let mut kind = [0u8; 1];
+    reader.read_exact(&mut kind)?;
+
+    let event = match kind[0] {
+        0 => RemoteInputEvent::PointerMotion {
+            x: f32::from_bits(read_u32(reader)?) as f64,
+            y: f32::from_bits(read_u32(reader)?) as f64,
+        },
+        1 => {
+            let button = read_u32(reader)?;
+            let mut pressed = [0u8; 1];
+            reader.read_exact(&mut pressed)?;
+            RemoteInputEvent::PointerButton { button, pressed: pressed[0] != 0 }
+        }
+        2 => {
+            let keycode = read_u32(reader)?;
+            let mut pressed = [0u8; 1];
+            reader.read_exact(&mut pressed)?;
+            RemoteInputEvent::Key { keycode, pressed: pressed[0] != 0 }
+        }
+        3 => RemoteInputEvent::Scroll {
+            horizontal: f32::from_bits(read_u32(reader)?) as f64,
+            vertical: f32::from_bits(read_u32(reader)?) as f64,
+        },
+        other => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unknown input event {}", other),
+            ));
+        }
+    };
+
+    Ok(event)
+}
+
+/// Read a big-endian u32
+fn read_u32(reader: &mut impl Read) -> std::io::Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_be_bytes(buf))
+}
+
+/// Top-left corner of a tile in the streamed frame
+type TileKey = (usize, usize);
+
+/// Encodes frames for one viewer and sends them within its budget
+struct FrameWriter {
+    viewer_id: u64,
+    config: RemoteViewConfig,
+    quality: Arc<Mutex<RemoteQuality>>,
+    shared: Arc<Mutex<SharedState>>,
+    bytes_sent: Arc<AtomicU64>,
+    bucket: TokenBucket,
+    /// Tile hashes the viewer currently shows, keyed by quality they were encoded at
+    sent_tiles: HashMap<TileKey, u64>,
+    sent_quality: Option<RemoteQuality>,
+    dropped_in_row: u32,
+    sent_in_row: u32,
+}
+
+impl FrameWriter {
+    fn new(
+        viewer_id: u64,
+        config: RemoteViewConfig,
+        quality: Arc<Mutex<RemoteQuality>>,
+        shared: Arc<Mutex<SharedState>>,
+        bytes_sent: Arc<AtomicU64>,
+    ) -> Self {
+        let bucket = TokenBucket::new(config.max_bandwidth_kbps as f64 * 1024.0);
+        Self {
+            viewer_id,
+            config,
+            quality,
+            shared,
+            bytes_sent,
+            bucket,
+            sent_tiles: HashMap::new(),
+            sent_quality: None,
+            dropped_in_row: 0,
+            sent_in_row: 0,
+        }
+    }
+
+    fn run(&mut self, frames: Receiver<Arc<OwnedFrame>>, mut stream: TcpStream) {
+        while let Ok(frame) = frames.recv() {
+            let quality = *self.quality.lock().unwrap();
+            if self.sent_quality != Some(quality) {
+                // Everything has to be resent at the new resolution
+                self.sent_tiles.clear();
+                self.sent_quality = Some(quality);
+            }
+
+            let (message, tiles) = self.encode(&frame, quality);
+            if tiles.is_empty() {
+                continue;
+            }
+
+            if !self.bucket.try_take(message.len()) {
+                // Over budget: skip the frame and keep old tile hashes so the
+                // changed tiles are resent with a later frame
+                self.dropped_in_row += 1;
+                self.sent_in_row = 0;
+                if self.config.adaptive_quality && self.dropped_in_row >= DOWNGRADE_AFTER_DROPS {
+                    self.change_quality(quality.lower());
+                }
+                continue;
+            }
+
+            if stream.write_all(&message).is_err() {
+                break;
+            }
+
+            self.bytes_sent.fetch_add(message.len() as u64, Ordering::Relaxed);
+            self.sent_tiles.extend(tiles);
+            self.dropped_in_row = 0;
+            self.sent_in_row += 1;
+
+            if self.config.adaptive_quality
+                && self.sent_in_row >= UPGRADE_AFTER_FRAMES
+                && quality < self.config.quality
+            {
+                self.change_quality(quality.higher());
+            }
+        }
+    }
+
+    fn change_quality(&mut self, quality: RemoteQuality) {
+        let mut current = self.quality.lock().unwrap();
+        if *current == quality {
+            return;
+        }
+        *current = quality;
+        drop(current);
+
+        self.dropped_in_row = 0;
+        self.sent_in_row = 0;
+        log::debug!("Remote viewer {} quality now {:?}", self.viewer_id, quality);
+        self.shared
+            .lock()
+            .unwrap()
+            .events
+            .push_back(RemoteViewEvent::QualityChanged { viewer_id: self.viewer_id, quality });
+    }
+
+    /// Encode the tiles that changed since the last sent frame
+    fn encode(&self, frame: &OwnedFrame, quality: RemoteQuality) -> (Vec<u8>, Vec<(TileKey, u64)>) {
+        let scaled = downscale(frame, quality);
+        let width = scaled.width as usize;
+        let height = scaled.height as usize;
+        let bpp = quality.bytes_per_pixel();
+
+        let mut body = Vec::new();
+        let mut changed = Vec::new();
+
+        for tile_y in (0..height).step_by(TILE_SIZE) {
+            for tile_x in (0..width).step_by(TILE_SIZE) {
+                let tile_w = TILE_SIZE.min(width - tile_x);
+                let tile_h = TILE_SIZE.min(height - tile_y);
+
+                let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                for row in tile_y..tile_y + tile_h {
+                    let start = (row * width + tile_x) * 4;
+                    scaled.pixels[start..start + tile_w * 4].hash(&mut hasher);
+                }
+                let hash = hasher.finish();
+
+                let key = (tile_x, tile_y);
+                if self.sent_tiles.get(&key) == Some(&hash) {
+                    continue;
+                }
+
+                body.extend_from_slice(&(tile_x as u16).to_be_bytes());
+                body.extend_from_slice(&(tile_y as u16).to_be_bytes());
+                body.extend_from_slice(&(tile_w as u16).to_be_bytes());
+                body.extend_from_slice(&(tile_h as u16).to_be_bytes());
+                body.extend_from_slice(&((tile_w * tile_h * bpp) as u32).to_be_bytes());
+
+                for row in tile_y..tile_y + tile_h {
+                    let start = (row * width + tile_x) * 4;
+                    for pixel in scaled.pixels[start..start + tile_w * 4].chunks_exact(4) {
+                        encode_pixel(pixel, bpp, &mut body);
+                    }
+                }
+
+                changed.push((key, hash));
+            }
+        }
+
+        let mut message = Vec::with_capacity(body.len() + 12);
+        message.push(b'F');
+        message.extend_from_slice(&frame.number.to_be_bytes());
+        message.extend_from_slice(&(width as u16).to_be_bytes());
+        message.extend_from_slice(&(height as u16).to_be_bytes());
+        message.push(bpp as u8);
+        message.extend_from_slice(&(changed.len() as u16).to_be_bytes());
+        message.extend_from_slice(&body);
+
+        (message, changed)
+    }
+}
+
+/// Box-filter a frame down for the given quality
+fn downscale(frame: &OwnedFrame, quality: RemoteQuality) -> OwnedFrame {
+    let divisor = quality.scale_divisor();
+    let src_width = frame.width as usize;
+    let src_height = frame.height as usize;
+
+    if divisor == 1 {
+        return OwnedFrame {
+            number: frame.number,
+            width: frame.width,
+            height: frame.height,
+            pixels: frame.pixels.clone(),
+        };
+    }
+
+    let width = src_width.div_ceil(divisor);
+    let height = src_height.div_ceil(divisor);
+    let mut pixels = Vec::with_capacity(width * height * 4);
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0u32; 4];
+            let mut count = 0u32;
+            for sy in y * divisor..((y + 1) * divisor).min(src_height) {
+                for sx in x * divisor..((x + 1) * divisor).min(src_width) {
+                    let i = (sy * src_width + sx) * 4;
+                    for (total, &value) in sum.iter_mut().zip(&frame.pixels[i..i + 4]) {
+                        *total += value as u32;
+                    }
+                    count += 1;
+                }
+            }
+            for channel in sum {
+                pixels.push((channel / count.max(1)) as u8);
+            }
+        }
+    }
+
+    OwnedFrame {
+        number: frame.number,
+        width: width as u32,
+        height: height as u32,
+        pixels,
+    }
+}
+
+/// Append one RGBA pixel in the stream's color depth
+fn encode_pixel(rgba: &[u8], bytes_per_pixel: usize, out: &mut Vec<u8>) {
+    if bytes_per_pixel == 3 {
+        out.extend_from_slice(&rgba[..3]);
+    } else {
+        let r = (rgba[0] as u16 >> 3) << 11;
+        let g = (rgba[1] as u16 >> 2) << 5;
+        let b = rgba[2] as u16 >> 3;
+        out.extend_from_slice(&(r | g | b).to_be_bytes());
+    }
+}
+
+impl Default for RemoteViewConfig {
+    fn default() -> Self {
+        Self {
+            bind_address: SocketAddr::from(([127, 0, 0, 1], 5917)),
+            access_token: String::new(),
+            max_bandwidth_kbps: 2048,
+            quality: RemoteQuality::High,
+            adaptive_quality: true,
+            scope: RemoteScope::Desktop,
+            allow_input: false,
+            max_viewers: 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "correct horse battery";
+
+    fn start_server(allow_input: bool) -> RemoteViewServer {
+        RemoteViewServer::start(RemoteViewConfig {
+            bind_address: SocketAddr::from(([127, 0, 0, 1], 0)),
+            access_token: TOKEN.to_string(),
+            allow_input,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn connect(server: &RemoteViewServer) -> (TcpStream, BufReader<TcpStream>, String) {
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        // A refused connection shows up as an empty answer
+        let _ = stream.write_all(format!("HORIZON-REMOTE {} {}\n", PROTOCOL_VERSION, TOKEN).as_bytes());
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut answer = String::new();
+        let _ = reader.read_line(&mut answer);
+        (stream, reader, answer)
+    }
+
+    /// Poll the server until `check` holds or a few seconds pass
+    fn wait_for<T>(server: &mut RemoteViewServer, mut check: impl FnMut(&mut RemoteViewServer) -> Option<T>) -> T {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(value) = check(server) {
+                return value;
+            }
+            assert!(Instant::now() < deadline, "timed out waiting for the remote view server");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_handshake_requires_token_and_version() {
+        assert!(check_handshake(&format!("HORIZON-REMOTE 1 {}\n", TOKEN), TOKEN));
+        assert!(!check_handshake("HORIZON-REMOTE 1 wrong token\n", TOKEN));
+        assert!(!check_handshake(&format!("HORIZON-REMOTE 2 {}\n", TOKEN), TOKEN));
+        assert!(!check_handshake(&format!("VNC 1 {}\n", TOKEN), TOKEN));
+        assert!(!check_handshake("", TOKEN));
+    }
+
+    #[test]
+    fn test_config_comes_from_desktop_settings() {
+        let settings = horizonos_graph_config::RemoteViewConfig {
+            bind_address: "127.0.0.1:6000".to_string(),
+            quality: "medium".to_string(),
+            workspace: Some("work".to_string()),
+            max_bandwidth_kbps: 512,
+            allow_input: true,
+            ..Default::default()
+        };
+        let config = RemoteViewConfig::from_config(&settings, TOKEN.to_string()).unwrap();
+        assert_eq!(config.bind_address, SocketAddr::from(([127, 0, 0, 1], 6000)));
+        assert_eq!(config.quality, RemoteQuality::Medium);
+        assert_eq!(config.scope, RemoteScope::Workspace("work".to_string()));
+        assert_eq!(config.max_bandwidth_kbps, 512);
+        assert!(config.allow_input);
+        assert_eq!(config.access_token, TOKEN);
+
+        let defaults = horizonos_graph_config::RemoteViewConfig::default();
+        let config = RemoteViewConfig::from_config(&defaults, TOKEN.to_string()).unwrap();
+        assert_eq!(config.scope, RemoteScope::Desktop);
+        assert!(!config.allow_input);
+
+        let bad_quality = horizonos_graph_config::RemoteViewConfig { quality: "best".to_string(), ..Default::default() };
+        assert!(RemoteViewConfig::from_config(&bad_quality, TOKEN.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_server_only_binds_loopback() {
+        let exposed = RemoteViewServer::start(RemoteViewConfig {
+            bind_address: SocketAddr::from(([0, 0, 0, 0], 0)),
+            access_token: TOKEN.to_string(),
+            ..Default::default()
+        });
+        assert!(matches!(exposed, Err(CompositorError::Config(_))));
+
+        let local = start_server(false);
+        assert!(local.local_addr().ip().is_loopback());
+    }
+
+    #[test]
+    fn test_token_bucket_sends_oversized_frames_when_full() {
+        let mut bucket = TokenBucket::new(100.0);
+        assert!(bucket.try_take(250));
+        // The overdraft is paid back first
+        assert!(!bucket.try_take(10));
+        bucket.last_refill -= Duration::from_secs(2);
+        assert!(bucket.try_take(10));
+        assert!(!bucket.try_take(250));
+        bucket.last_refill -= Duration::from_secs(2);
+        assert!(bucket.try_take(250));
+    }
+
+    #[test]
+    fn test_downscale_and_pixel_encoding() {
+        let frame = OwnedFrame { number: 1, width: 4, height: 2, pixels: [255u8, 0, 0, 255].repeat(8) };
+        let scaled = downscale(&frame, RemoteQuality::Medium);
+        assert_eq!((scaled.width, scaled.height), (2, 1));
+        assert_eq!(&scaled.pixels[..4], &[255, 0, 0, 255]);
+
+        let mut out = Vec::new();
+        encode_pixel(&[255, 0, 0, 255], 2, &mut out);
+        encode_pixel(&[1, 2, 3, 255], 3, &mut out);
+        assert_eq!(out, vec![0xf8, 0x00, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_viewer_receives_frames() {
+        let mut server = start_server(false);
+        server.submit_frame(RemoteFrame { width: 8, height: 8, pixels: &[0; 8 * 8 * 4], workspace: None });
+
+        let (_stream, mut reader, answer) = connect(&server);
+        assert_eq!(answer, "OK 8 8\n");
+        wait_for(&mut server, |server| (!server.viewers().is_empty()).then_some(()));
+
+        let pixels = [255u8; 8 * 8 * 4];
+        server.submit_frame(RemoteFrame { width: 8, height: 8, pixels: &pixels, workspace: None });
+        let mut header = [0u8; 12];
+        reader.read_exact(&mut header).unwrap();
+        assert_eq!(header[0], b'F');
+        assert_eq!(u16::from_be_bytes([header[5], header[6]]), 8);
+        assert_eq!(header[9], 2);
+        assert_eq!(u16::from_be_bytes([header[10], header[11]]), 1);
+    }
+
+    #[test]
+    fn test_input_waits_for_consent() {
+        let mut server = start_server(true);
+        let (mut stream, _reader, answer) = connect(&server);
+        assert!(answer.starts_with("OK"));
+
+        let mut motion = vec![b'I', 0];
+        motion.extend_from_slice(&10.0f32.to_bits().to_be_bytes());
+        motion.extend_from_slice(&20.0f32.to_bits().to_be_bytes());
+        stream.write_all(&motion).unwrap();
+
+        let viewer_id = wait_for(&mut server, |server| {
+            server.poll_events().into_iter().find_map(|event| match event {
+                RemoteViewEvent::ConsentRequested { viewer_id, .. } => Some(viewer_id),
+                _ => None,
+            })
+        });
+        // Input sent before the answer is dropped
+        assert!(server.drain_input().is_empty());
+
+        server.respond_to_consent(viewer_id, true);
+        stream.write_all(&motion).unwrap();
+        let input = wait_for(&mut server, |server| {
+            let input = server.drain_input();
+            (!input.is_empty()).then_some(input)
+        });
+        assert_eq!(input, vec![RemoteInputEvent::PointerMotion { x: 10.0, y: 20.0 }]);
+    }
+
+    #[test]
+    fn test_pending_handshakes_are_limited() {
+        let server = start_server(false);
+        // Connections that never authenticate hold every handshake slot
+        let idle: Vec<_> = (0..MAX_PENDING_HANDSHAKES)
+            .map(|_| TcpStream::connect(server.local_addr()).unwrap())
+            .collect();
+        thread::sleep(Duration::from_millis(200));
+
+        let mut refused = TcpStream::connect(server.local_addr()).unwrap();
+        refused.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let mut buf = [0u8; 1];
+        assert_eq!(refused.read(&mut buf).unwrap_or(0), 0);
+
+        // Slots free up once the idle peers hang up and are turned away
+        drop(idle);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let (_stream, _reader, answer) = connect(&server);
+            if answer.starts_with("OK") {
+                break;
+            }
+            assert!(Instant::now() < deadline, "handshake slots were not released");
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
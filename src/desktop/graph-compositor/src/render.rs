@@ -2,38 +2,327 @@
 
 use smithay::{
     backend::{
+        allocator::Fourcc,
         renderer::{
-            element::{Element, RenderElement},
-            gles::GlesRenderer,
-            Frame, ImportAll, Renderer, Bind,
+            element::{surface::WaylandSurfaceRenderElement, AsRenderElements, Element, RenderElement},
+            gles::{GlesFrame, GlesRenderer, GlesTexture},
+            utils::draw_render_elements,
+            Color32F, ExportMem, Frame, ImportAll, ImportMem, Offscreen, Renderer, Bind, Texture,
         },
     },
     desktop::Window,
     output::Output,
-    utils::{Rectangle, Transform, Physical, Scale, Logical, Point, Size},
+    utils::{Buffer, Rectangle, Transform, Physical, Scale, Logical, Point, Size},
 };
 use crate::AppState;
-use horizonos_graph_engine::{Scene, Camera};
+use horizonos_graph_engine::{Scene, Camera, CameraState, GraphEngine, Minimap, MinimapConfig, RecoveryEvent, SceneId, SceneSnapshot, ShaderEvent};
+use horizonos_graph_errors::Classify;
+use horizonos_graph_notifications::{problem_notification, Notification, NotificationType};
+use horizonos_graph_visual::{AnimationId, AnimationSystem, TokenKind, WindowFrame};
+use horizonos_graph_performance::PerformanceManager;
+use crate::portal::CaptureSource;
+use crate::remote::RemoteFrame;
+use crate::quick_look::{self, QuickLookFill, QuickLookRect};
+use crate::switcher::{self, SwitcherTile};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use anyhow::Result;
 
+/// Color behind the graph while no graph frame is available
+const BACKGROUND: Color32F = Color32F::new(0.05, 0.05, 0.07, 1.0);
+/// Window switcher: dimmed desktop, tiles without a preview and the selection outline
+const SWITCHER_SCRIM: Color32F = Color32F::new(0.0, 0.0, 0.0, 0.55);
+const SWITCHER_TILE: Color32F = Color32F::new(0.16, 0.16, 0.2, 1.0);
+const SWITCHER_SELECTED: Color32F = Color32F::new(0.35, 0.6, 1.0, 1.0);
+/// Quick Look: the panel behind the preview and the colours of highlighted text
+const QUICK_LOOK_PANEL: Color32F = Color32F::new(0.1, 0.1, 0.13, 0.96);
+const QUICK_LOOK_TEXT: Color32F = Color32F::new(0.85, 0.86, 0.9, 1.0);
+const QUICK_LOOK_KEYWORD: Color32F = Color32F::new(0.78, 0.47, 0.87, 1.0);
+const QUICK_LOOK_STRING: Color32F = Color32F::new(0.6, 0.8, 0.45, 1.0);
+const QUICK_LOOK_COMMENT: Color32F = Color32F::new(0.45, 0.48, 0.55, 1.0);
+const QUICK_LOOK_NUMBER: Color32F = Color32F::new(0.95, 0.65, 0.35, 1.0);
+
+/// How often suggested edge badges follow new suggestions and scene changes,
+/// and status badges are checked for commands that are due
+const BADGE_SYNC_INTERVAL: Duration = Duration::from_secs(1);
+/// How often the accessible tree follows workspace, cluster and node changes
+const ACCESSIBILITY_SYNC_INTERVAL: Duration = Duration::from_secs(1);
+/// How long notices of a lost and recovered GPU stay up
+const DEVICE_RECOVERY_NOTICE: Duration = Duration::from_secs(5);
+/// How often battery and power profile are checked for the power-saving path
+const POWER_SYNC_INTERVAL: Duration = Duration::from_secs(5);
+/// Owner of camera animations in the animation system
+const CAMERA_ANIMATION_OWNER: &str = "camera";
+
+/// Springs gliding the interactive camera to a view
+struct CameraAnimation {
+    /// View the camera is heading for
+    target: CameraState,
+    position: AnimationId,
+    forward: AnimationId,
+    fov: AnimationId,
+}
+
+impl CameraAnimation {
+    /// Start from where the camera is, or send running springs to the new view
+    fn start(animations: &mut AnimationSystem, camera: &Camera, target: CameraState, running: Option<CameraAnimation>) -> Self {
+        if let Some(running) = running {
+            if animations.retarget(running.position, target.position)
+                && animations.retarget(running.forward, target.forward)
+                && animations.retarget(running.fov, target.fov)
+            {
+                return Self { target, ..running };
+            }
+            running.cancel(animations);
+        }
+        Self {
+            target,
+            position: animations.spring(CAMERA_ANIMATION_OWNER, camera.position, target.position),
+            forward: animations.spring(CAMERA_ANIMATION_OWNER, camera.forward, target.forward),
+            fov: animations.spring(CAMERA_ANIMATION_OWNER, camera.fov, target.fov),
+        }
+    }
+    
+    /// Put the camera where the springs are, returning whether they are still moving
+    ///
+    /// Springs that already settled, and were dropped by the system, hold their target.
+    fn apply(&self, animations: &AnimationSystem, camera: &mut Camera) -> bool {
+        camera.restore_state(&CameraState {
+            position: animations.value(self.position).unwrap_or(self.target.position),
+            forward: animations.value(self.forward).unwrap_or(self.target.forward),
+            up: self.target.up,
+            fov: animations.value(self.fov).unwrap_or(self.target.fov),
+        });
+        [self.position, self.forward, self.fov].into_iter().any(|id| animations.is_running(id))
+    }
+    
+    fn cancel(&self, animations: &mut AnimationSystem) {
+        for id in [self.position, self.forward, self.fov] {
+            animations.cancel(id);
+        }
+    }
+}
+
 /// Graph rendering integration
 pub struct GraphRenderIntegration {
-    // For now, keep this simple
+    /// Camera the graph is viewed through, for level of detail
+    camera: Camera,
+    /// Smooth camera movement in progress
+    camera_animation: Option<CameraAnimation>,
+    /// When the shared animations were last advanced
+    last_animation_update: Instant,
+    /// Off-screen engine drawing the graph, composited below the windows
+    engine: Option<GraphEngine>,
+    /// Last graph frame, uploaded for the compositor's renderer
+    graph_texture: Option<GlesTexture>,
+    /// When suggested edge badges were last synced
+    last_badge_sync: Option<Instant>,
+    /// When the accessible tree was last exported
+    last_accessibility_sync: Option<Instant>,
+    /// Culling, level of detail and the power-saving render path
+    performance: PerformanceManager,
+    /// When the power state was last checked
+    last_power_sync: Option<Instant>,
+    /// When group animations were last advanced
+    last_group_update: Instant,
+    /// Whether the minimap is drawn over the graph
+    minimap_visible: bool,
+    /// Shader reloads, watched in debug builds
+    shader_events: Option<crossbeam_channel::Receiver<ShaderEvent>>,
+    /// Loss and recovery of the engine's GPU device
+    device_recovery: Option<crossbeam_channel::Receiver<RecoveryEvent>>,
+    /// Tiles of the open window switcher, empty while it is closed
+    switcher_tiles: Vec<SwitcherTile>,
+    /// Uploaded switcher previews with the thumbnail generation they show
+    switcher_textures: HashMap<SceneId, (u64, GlesTexture)>,
+    /// Parts of the open Quick Look overlay, empty while it is closed
+    quick_look_rects: Vec<QuickLookRect>,
+    /// Uploaded images of the Quick Look preview with the preview generation they show
+    quick_look_textures: Option<(u64, Vec<GlesTexture>)>,
+    /// Workspace whose edge filter the engine applies
+    edge_filter_workspace: Option<String>,
 }
 
 impl GraphRenderIntegration {
     pub fn new() -> Result<Self> {
-        Ok(Self {})
+        Ok(Self {
+            camera: Camera::new(),
+            camera_animation: None,
+            last_animation_update: Instant::now(),
+            engine: None,
+            graph_texture: None,
+            last_badge_sync: None,
+            last_accessibility_sync: None,
+            performance: PerformanceManager::new(),
+            last_power_sync: None,
+            last_group_update: Instant::now(),
+            minimap_visible: true,
+            shader_events: None,
+            device_recovery: None,
+            switcher_tiles: Vec::new(),
+            switcher_textures: HashMap::new(),
+            quick_look_rects: Vec::new(),
+            quick_look_textures: None,
+            edge_filter_workspace: None,
+        })
+    }
+    
+    /// Draw the graph with an off-screen engine sized to the output
+    ///
+    /// Debug builds reload shaders edited in the `shaders` directory of the
+    /// configuration; rejected shaders are reported as notifications, as are
+    /// losing and recovering the GPU device.
+    pub fn with_engine(mut self, mut engine: GraphEngine) -> Self {
+        self.device_recovery = Some(engine.subscribe_device_recovery());
+        if cfg!(debug_assertions) {
+            if let Some(dir) = crate::services::config_dir() {
+                match engine.enable_shader_hot_reload(dir.join("shaders")) {
+                    Ok(events) => self.shader_events = Some(events),
+                    Err(e) => log::warn!("Shader hot reload unavailable: {}", e),
+                }
+            }
+        }
+        self.engine = Some(engine);
+        self
+    }
+    
+    /// The graph engine, if the graph is drawn
+    pub fn engine_mut(&mut self) -> Option<&mut GraphEngine> {
+        self.engine.as_mut()
+    }
+    
+    /// Time to leave between frames, longer while saving power
+    pub fn frame_interval(&self) -> Duration {
+        self.performance.frame_interval()
+    }
+    
+    /// Saved state of the camera, for the session journal
+    ///
+    /// While the camera glides this is the view it is heading for.
+    pub fn camera_state(&self) -> CameraState {
+        self.camera_animation.as_ref().map_or_else(|| self.camera.state(), |animation| animation.target)
+    }
+    
+    /// Put the camera back where a previous session left it
+    pub fn restore_camera(&mut self, state: &CameraState) {
+        self.camera_animation = None;
+        self.camera.restore_state(state);
+    }
+    
+    /// Follow a resized output
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if let Some(engine) = self.engine.as_mut() {
+            if let Err(e) = engine.resize_offscreen(width, height) {
+                log::warn!("Failed to resize the graph to {}x{}: {}", width, height, e);
+            }
+        }
+    }
+    
+    /// Draw the compositor's scene as it stands this frame
+    ///
+    /// Theme palettes published since the last frame are applied first, to
+    /// the compositor's scene as well so that recolored nodes stay recolored.
+    /// Suggested edge badges are synced, and status badges that are due
+    /// refreshed, at most every [`BADGE_SYNC_INTERVAL`]; the accessible tree
+    /// is exported at most every [`ACCESSIBILITY_SYNC_INTERVAL`]; the edge filter
+    /// follows the active workspace;
+    /// the shared animations advance, and cluster groups, the selection
+    /// overlay, the minimap, the workspace switch effects and video frames are
+    /// refreshed, every frame. The power state is checked every
+    /// [`POWER_SYNC_INTERVAL`], switching the power-saving render path and the
+    /// AI's monitoring rate when it changes.
+    pub fn render_graph(&mut self, state: &mut AppState, output: &str) -> Result<()> {
+        self.advance_animations(state, Instant::now());
+        self.report_shader_events(state);
+        self.report_device_recovery(state);
+        let Some(engine) = self.engine.as_mut() else {
+            return Ok(());
+        };
+        if self.last_power_sync.map_or(true, |synced| synced.elapsed() >= POWER_SYNC_INTERVAL) {
+            let power = state.render_power_state();
+            if power != self.performance.power_state() || self.last_power_sync.is_none() {
+                self.performance.set_power_state(power);
+                state.apply_power_state(&self.performance);
+            }
+            self.last_power_sync = Some(Instant::now());
+        }
+        if self.last_badge_sync.map_or(true, |synced| synced.elapsed() >= BADGE_SYNC_INTERVAL) {
+            state.sync_edge_badges();
+            state.refresh_status_badges();
+            self.last_badge_sync = Some(Instant::now());
+        }
+        if self.last_accessibility_sync.is_none_or(|synced| synced.elapsed() >= ACCESSIBILITY_SYNC_INTERVAL) {
+            state.export_accessibility_tree();
+            self.last_accessibility_sync = Some(Instant::now());
+        }
+        let now = Instant::now();
+        state.update_groups(now.duration_since(self.last_group_update).as_secs_f32());
+        self.last_group_update = now;
+        if let Some(palette) = state.palette_updates.try_iter().last() {
+            palette.recolor(engine.palette(), &mut state.graph_scene.lock().unwrap());
+            engine.set_palette(palette);
+        }
+        // Switching workspaces brings back the edges the workspace was left showing
+        let active_workspace = state.workspaces.active_workspace_id();
+        if active_workspace != self.edge_filter_workspace {
+            let filter = active_workspace.as_deref()
+                .and_then(|workspace_id| state.workspaces.get_workspace(workspace_id))
+                .map(|workspace| workspace.settings.edge_filter)
+                .unwrap_or_default();
+            engine.set_edge_filter(filter);
+            self.edge_filter_workspace = active_workspace;
+        }
+        // The output shows its workspace through its camera, see `AppState::output_view`
+        let view = state.output_view(output, &self.camera);
+        let mut snapshot = SceneSnapshot::capture(&state.graph_scene.lock().unwrap());
+        if let Some(nodes) = &view.nodes {
+            for node in snapshot.nodes.iter_mut().filter(|node| !nodes.contains(&node.id)) {
+                node.visible = false;
+            }
+        }
+        state.apply_layout_animations(&mut snapshot);
+        engine.restore_snapshot(&snapshot);
+        engine.camera_mut().restore_state(&view.camera.state());
+        state.minimap = self.minimap_visible.then(|| {
+            let size = engine.window_size();
+            let minimap = Minimap::build(engine.scene(), &view.camera, &state.clustering.minimap_clusters(), &MinimapConfig::default());
+            (minimap, (size.0 as f32, size.1 as f32))
+        });
+        engine.set_minimap(state.minimap.as_ref().map(|(minimap, _)| minimap.clone()));
+        engine.set_switch_overlay(state.switch_overlay());
+        self.performance.update(engine, &view.camera);
+        state.video.submit(engine);
+        state.interaction_manager.lock().unwrap_or_else(|e| e.into_inner()).publish_selection_visuals(engine);
+        engine.render()?;
+        Ok(())
+    }
+    
+    /// Handle a click on an item of the active context menu
+    ///
+    /// Items the interaction system does not handle itself, like collapsing
+    /// a node's cluster, are handed to the compositor.
+    pub fn handle_menu_item(&mut self, state: &mut AppState, item_id: &str) {
+        let Some(engine) = self.engine.as_ref() else {
+            return;
+        };
+        let item = state.interaction_manager.lock().unwrap_or_else(|e| e.into_inner()).handle_menu_item(item_id, engine);
+        if let Some((node_id, item_id)) = item {
+            state.handle_node_menu_item(node_id, &item_id);
+        }
     }
     
     /// Render a frame combining Wayland surfaces and graph visualization
+    ///
+    /// Updates window positions, draws the graph and serves captures; the
+    /// output itself is drawn by [`Self::draw_output`] once the backend's
+    /// framebuffer is bound.
     pub fn render_frame(
-        &self,
+        &mut self,
         renderer: &mut GlesRenderer,
         state: &mut AppState,
         output: &Output,
-        age: usize,
+        _age: usize,
     ) -> Result<()> {
         let output_geometry = state.space.output_geometry(output)
             .unwrap_or(Rectangle::from_loc_and_size(Point::from((0, 0)), Size::from((1920, 1080))));
@@ -45,12 +334,259 @@ impl GraphRenderIntegration {
         // Update window positions based on graph layout
         self.update_window_positions(state, output_rect);
         
-        // Actual rendering would be done by Smithay's render pipeline
-        // This is just updating positions
+        // Draw the graph as this output shows it and hand it to the compositor's renderer
+        self.render_graph(state, &output.name())?;
+        self.upload_graph_frame(renderer);
+        
+        // Refresh the window thumbnails shown on application nodes
+        self.capture_thumbnails(renderer, state);
+        
+        // Lay out the window switcher over the windows while it is open
+        self.prepare_switcher(renderer, state, output_rect.size);
         
+        // Lay out the Quick Look preview while it is open
+        self.prepare_quick_look(renderer, state, output_rect.size);
+        
+        // Screenshots and screen casts requested through the desktop portal
+        self.capture_for_portal(renderer, state);
+        
+        // Frames for remote viewers
+        self.capture_for_remote_view(renderer, state, output);
+        
+        Ok(())
+    }
+    
+    /// Read the graph frame back from the engine into a texture of the compositor's renderer
+    fn upload_graph_frame(&mut self, renderer: &mut GlesRenderer) {
+        let Some(engine) = self.engine.as_ref() else {
+            return;
+        };
+        let uploaded = engine
+            .capture_frame()
+            .map_err(anyhow::Error::from)
+            .and_then(|frame| {
+                let size: Size<i32, Buffer> = (frame.width as i32, frame.height as i32).into();
+                Ok(renderer.import_memory(&frame.rgba, Fourcc::Abgr8888, size, false)?)
+            });
+        match uploaded {
+            Ok(texture) => self.graph_texture = Some(texture),
+            Err(e) => log::debug!("Failed to upload the graph frame: {}", e),
+        }
+    }
+    
+    /// Draw the graph and the windows of an output into a bound frame
+    pub fn draw_output(
+        &self,
+        frame: &mut GlesFrame<'_, '_>,
+        scale: f64,
+        elements: &[WaylandSurfaceRenderElement<GlesRenderer>],
+        damage: &[Rectangle<i32, Physical>],
+    ) -> Result<()> {
+        Self::draw_desktop(frame, self.graph_texture.as_ref(), scale, elements, damage)?;
+        self.draw_switcher(frame, damage)?;
+        self.draw_quick_look(frame, damage)
+    }
+    
+    /// Publish the latest thumbnails to the switcher, upload the ones it shows and lay out its tiles
+    fn prepare_switcher(&mut self, renderer: &mut GlesRenderer, state: &AppState, output_size: Size<i32, Physical>) {
+        let mut interaction = state.interaction_manager.lock().unwrap_or_else(|e| e.into_inner());
+        let window_switcher = interaction.window_switcher_mut();
+        if !window_switcher.is_open() {
+            self.switcher_tiles.clear();
+            self.switcher_textures.clear();
+            return;
+        }
+        state.switcher_previews.update(&state.live_thumbnails, state.surface_to_node.values().copied());
+        window_switcher.refresh_previews();
+        
+        let candidates = window_switcher.visible_candidates();
+        self.switcher_tiles = switcher::layout_tiles(
+            &candidates,
+            window_switcher.selected_index(),
+            window_switcher.config().preview_size,
+            (output_size.w, output_size.h),
+        );
+        self.switcher_textures.retain(|node_id, _| candidates.iter().any(|c| c.node_id == *node_id));
+        for tile in &self.switcher_tiles {
+            let Some((preview, _)) = &tile.preview else {
+                continue;
+            };
+            if self.switcher_textures.get(&tile.node_id).is_some_and(|(generation, _)| *generation == preview.texture_id) {
+                continue;
+            }
+            let Some(thumbnail) = state.live_thumbnails.thumbnail(tile.node_id) else {
+                continue;
+            };
+            let image = thumbnail.image.to_rgba8();
+            let size: Size<i32, Buffer> = (image.width() as i32, image.height() as i32).into();
+            match renderer.import_memory(image.as_raw(), Fourcc::Abgr8888, size, false) {
+                Ok(texture) => {
+                    self.switcher_textures.insert(tile.node_id, (thumbnail.generation, texture));
+                }
+                Err(e) => log::debug!("Failed to upload the switcher preview of node {}: {}", tile.node_id, e),
+            }
+        }
+    }
+    
+    /// Draw the open window switcher: tiles with live previews, the selected one outlined
+    fn draw_switcher(&self, frame: &mut GlesFrame<'_, '_>, damage: &[Rectangle<i32, Physical>]) -> Result<()> {
+        if self.switcher_tiles.is_empty() {
+            return Ok(());
+        }
+        let screen = damage.iter().copied().reduce(|a, b| a.merge(b)).unwrap_or_default();
+        Self::draw_rect(frame, screen, damage, SWITCHER_SCRIM)?;
+        for tile in &self.switcher_tiles {
+            let bounds = Rectangle::new((tile.x, tile.y).into(), (tile.width, tile.height).into());
+            if tile.selected {
+                let outline = Rectangle::new(
+                    (tile.x - switcher::SELECTED_OUTLINE, tile.y - switcher::SELECTED_OUTLINE).into(),
+                    (tile.width + 2 * switcher::SELECTED_OUTLINE, tile.height + 2 * switcher::SELECTED_OUTLINE).into(),
+                );
+                Self::draw_rect(frame, outline, damage, SWITCHER_SELECTED)?;
+            }
+            Self::draw_rect(frame, bounds, damage, SWITCHER_TILE)?;
+            
+            let texture = self.switcher_textures.get(&tile.node_id).map(|(_, texture)| texture);
+            if let (Some((_, [x, y, w, h])), Some(texture)) = (&tile.preview, texture) {
+                let dst = Rectangle::new((*x, *y).into(), (*w, *h).into());
+                let src = Rectangle::from_size(texture.size()).to_f64();
+                let local = Self::local_damage(dst, damage);
+                if !local.is_empty() {
+                    Frame::render_texture_from_to(frame, texture, src, dst, &local, &[], Transform::Normal, 1.0)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    
+    /// Upload the images of the Quick Look preview once it has loaded and lay out the overlay
+    fn prepare_quick_look(&mut self, renderer: &mut GlesRenderer, state: &AppState, output_size: Size<i32, Physical>) {
+        let Some(preview) = state.quick_look.preview() else {
+            self.quick_look_rects.clear();
+            self.quick_look_textures = None;
+            return;
+        };
+        self.quick_look_rects = quick_look::layout(preview.content.as_ref(), (output_size.w, output_size.h));
+        
+        let Some(content) = &preview.content else {
+            return;
+        };
+        if self.quick_look_textures.as_ref().is_some_and(|(generation, _)| *generation == preview.generation) {
+            return;
+        }
+        let mut textures = Vec::new();
+        for image in quick_look::images(content) {
+            let image = image.to_rgba8();
+            let size: Size<i32, Buffer> = (image.width() as i32, image.height() as i32).into();
+            match renderer.import_memory(image.as_raw(), Fourcc::Abgr8888, size, false) {
+                Ok(texture) => textures.push(texture),
+                Err(e) => {
+                    log::debug!("Failed to upload the preview of {}: {}", preview.item.path.display(), e);
+                    break;
+                }
+            }
+        }
+        self.quick_look_textures = Some((preview.generation, textures));
+    }
+    
+    /// Draw the open Quick Look overlay: the preview on a panel over the dimmed desktop
+    fn draw_quick_look(&self, frame: &mut GlesFrame<'_, '_>, damage: &[Rectangle<i32, Physical>]) -> Result<()> {
+        if self.quick_look_rects.is_empty() {
+            return Ok(());
+        }
+        let screen = damage.iter().copied().reduce(|a, b| a.merge(b)).unwrap_or_default();
+        Self::draw_rect(frame, screen, damage, SWITCHER_SCRIM)?;
+        let textures = self.quick_look_textures.as_ref().map_or(&[][..], |(_, textures)| textures.as_slice());
+        for part in &self.quick_look_rects {
+            let [x, y, w, h] = part.rect;
+            let dst = Rectangle::new((x, y).into(), (w, h).into());
+            let color = match part.fill {
+                QuickLookFill::Panel => QUICK_LOOK_PANEL,
+                QuickLookFill::Span(TokenKind::Plain) => QUICK_LOOK_TEXT,
+                QuickLookFill::Span(TokenKind::Keyword) => QUICK_LOOK_KEYWORD,
+                QuickLookFill::Span(TokenKind::String) => QUICK_LOOK_STRING,
+                QuickLookFill::Span(TokenKind::Comment) => QUICK_LOOK_COMMENT,
+                QuickLookFill::Span(TokenKind::Number) => QUICK_LOOK_NUMBER,
+                QuickLookFill::Image(index) => {
+                    let Some(texture) = textures.get(index) else {
+                        continue;
+                    };
+                    let src = Rectangle::from_size(texture.size()).to_f64();
+                    let local = Self::local_damage(dst, damage);
+                    if !local.is_empty() {
+                        Frame::render_texture_from_to(frame, texture, src, dst, &local, &[], Transform::Normal, 1.0)?;
+                    }
+                    continue;
+                }
+            };
+            Self::draw_rect(frame, dst, damage, color)?;
+        }
+        Ok(())
+    }
+    
+    /// Fill a rectangle where it overlaps the damage
+    fn draw_rect(
+        frame: &mut GlesFrame<'_, '_>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+        color: Color32F,
+    ) -> Result<()> {
+        let local = Self::local_damage(dst, damage);
+        if !local.is_empty() {
+            frame.draw_solid(dst, &local, color)?;
+        }
+        Ok(())
+    }
+    
+    /// Damage clipped to `dst`, relative to its origin as the frame expects it
+    fn local_damage(dst: Rectangle<i32, Physical>, damage: &[Rectangle<i32, Physical>]) -> Vec<Rectangle<i32, Physical>> {
+        damage
+            .iter()
+            .filter_map(|rect| rect.intersection(dst))
+            .map(|rect| Rectangle::new(rect.loc - dst.loc, rect.size))
+            .collect()
+    }
+    
+    fn draw_desktop(
+        frame: &mut GlesFrame<'_, '_>,
+        background: Option<&GlesTexture>,
+        scale: f64,
+        elements: &[WaylandSurfaceRenderElement<GlesRenderer>],
+        damage: &[Rectangle<i32, Physical>],
+    ) -> Result<()> {
+        match background {
+            Some(texture) => {
+                frame.clear(BACKGROUND, damage)?;
+                frame.render_texture_at(texture, Point::from((0, 0)), 1, 1.0, Transform::Normal, damage, &[], 1.0)?;
+            }
+            None => frame.clear(Color32F::TRANSPARENT, damage)?,
+        }
+        draw_render_elements(frame, scale, elements, damage)?;
         Ok(())
     }
     
+    /// Render elements of the windows on an output, topmost first
+    pub fn output_elements(
+        renderer: &mut GlesRenderer,
+        state: &AppState,
+        output: &Output,
+    ) -> Vec<WaylandSurfaceRenderElement<GlesRenderer>> {
+        let Some(geometry) = state.space.output_geometry(output) else {
+            return Vec::new();
+        };
+        let scale = output.current_scale().fractional_scale();
+        let windows: Vec<Window> = state.space.elements_for_output(output).cloned().collect();
+        let mut elements = Vec::new();
+        for window in windows.iter().rev() {
+            let Some(location) = state.space.element_location(window) else {
+                continue;
+            };
+            let offset = (location - geometry.loc - window.geometry().loc).to_physical_precise_round(scale);
+            elements.extend(window.render_elements(renderer, offset, Scale::from(scale), 1.0));
+        }
+        elements
+    }
+    
     /// Update window positions based on graph layout
     fn update_window_positions(
         &self,
@@ -82,9 +618,339 @@ impl GraphRenderIntegration {
         }
     }
     
+    /// Capture the windows whose thumbnails are due
+    ///
+    /// How often a window is captured depends on the level of detail of its
+    /// node; windows are rendered offscreen at thumbnail size and read back.
+    fn capture_thumbnails(&self, renderer: &mut GlesRenderer, state: &mut AppState) {
+        {
+            let scene = state.graph_scene.lock().unwrap();
+            for &node_id in state.surface_to_node.values() {
+                let appearance = state.thumbnail_lod.get_node_appearance(node_id, &self.camera, &scene);
+                let mut complexity = state.thumbnail_lod.get_render_complexity(appearance.level);
+                // Node types shown as icons at this distance keep their last image
+                if !appearance.live_texture() {
+                    complexity.live_texture_interval = None;
+                }
+                state.live_thumbnails.set_lod(node_id, &complexity);
+            }
+        }
+        
+        let now = Instant::now();
+        for request in state.live_thumbnails.due(now) {
+            let Some(window) = Self::window_for_node(state, request.node_id) else {
+                state.live_thumbnails.untrack(request.node_id);
+                continue;
+            };
+            match Self::capture_window(renderer, &window, request.max_size) {
+                Ok(frame) => {
+                    if let Err(e) = state.live_thumbnails.submit(request.node_id, frame, now) {
+                        log::warn!("Dropping thumbnail of node {}: {}", request.node_id, e);
+                    }
+                }
+                Err(e) => log::debug!("Failed to capture window of node {}: {}", request.node_id, e),
+            }
+        }
+    }
+    
+    fn window_for_node(state: &AppState, node_id: SceneId) -> Option<Window> {
+        state.space.elements()
+            .find(|window| {
+                window.toplevel()
+                    .and_then(|toplevel| state.surface_to_node.get(toplevel.wl_surface()))
+                    == Some(&node_id)
+            })
+            .cloned()
+    }
+    
+    /// Save pending portal screenshots and feed running screen casts
+    fn capture_for_portal(&self, renderer: &mut GlesRenderer, state: &mut AppState) {
+        for screenshot in std::mem::take(&mut state.portal.screenshots) {
+            let source = screenshot.source.clone().or_else(|| {
+                state.space.outputs().next().map(|output| CaptureSource::Output { name: output.name() })
+            });
+            let saved = source
+                .ok_or_else(|| anyhow::anyhow!("no output to capture"))
+                .and_then(|source| self.capture_source(renderer, state, &source))
+                .and_then(|frame| Self::save_png(&frame, &screenshot.path));
+            if let Err(e) = &saved {
+                log::warn!("Screenshot to {} failed: {}", screenshot.path.display(), e);
+            }
+            let _ = screenshot.reply.send(saved.map_err(|e| e.to_string()));
+        }
+        
+        if state.portal.stream_backend.is_none() {
+            return;
+        }
+        let streams: Vec<_> = state.portal.casts.values().flatten().cloned().collect();
+        for stream in streams {
+            match self.capture_source(renderer, state, &stream.source) {
+                Ok(frame) => {
+                    if let Some(backend) = state.portal.stream_backend.as_mut() {
+                        backend.push_frame(stream.pipewire_node, &frame);
+                    }
+                }
+                Err(e) => log::debug!("Failed to capture {:?} for screen cast: {}", stream.source, e),
+            }
+        }
+    }
+    
+    /// Hand the output's picture to the remote view server while anyone watches
+    fn capture_for_remote_view(&self, renderer: &mut GlesRenderer, state: &mut AppState, output: &Output) {
+        if !state.remote_view.as_ref().is_some_and(|server| server.has_viewers()) {
+            return;
+        }
+        let frame = match self.capture_output(renderer, state, &output.name()) {
+            Ok(frame) => frame,
+            Err(e) => {
+                log::debug!("Failed to capture {} for remote view: {}", output.name(), e);
+                return;
+            }
+        };
+        let workspace = state.workspaces.get_active_workspace().map(|workspace| workspace.name);
+        if let Some(server) = state.remote_view.as_mut() {
+            server.submit_frame(RemoteFrame {
+                width: frame.width,
+                height: frame.height,
+                pixels: &frame.pixels,
+                workspace: workspace.as_deref(),
+            });
+        }
+    }
+    
+    /// Capture an output or a node's window at full size
+    fn capture_source(&self, renderer: &mut GlesRenderer, state: &AppState, source: &CaptureSource) -> Result<WindowFrame> {
+        match source {
+            CaptureSource::Output { name } => self.capture_output(renderer, state, name),
+            CaptureSource::Node { node_id } => {
+                let window = Self::window_for_node(state, *node_id)
+                    .ok_or_else(|| anyhow::anyhow!("node {} has no window", node_id))?;
+                Self::capture_window(renderer, &window, u32::MAX)
+            }
+        }
+    }
+    
+    /// Render the graph and the windows on an output offscreen and read them back
+    fn capture_output(&self, renderer: &mut GlesRenderer, state: &AppState, name: &str) -> Result<WindowFrame> {
+        let output = state.space.outputs()
+            .find(|output| output.name() == name)
+            .ok_or_else(|| anyhow::anyhow!("no output named {}", name))?;
+        let geometry = state.space.output_geometry(output)
+            .ok_or_else(|| anyhow::anyhow!("output {} is not mapped", name))?;
+        let scale = output.current_scale().fractional_scale();
+        let size: Size<i32, Physical> = geometry.size.to_physical_precise_round(scale);
+        
+        let elements = Self::output_elements(renderer, state, output);
+        Self::render_offscreen(renderer, size, scale, self.graph_texture.as_ref(), &elements)
+    }
+    
+    fn save_png(frame: &WindowFrame, path: &std::path::Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let image = image::RgbaImage::from_raw(frame.width, frame.height, frame.pixels.clone())
+            .ok_or_else(|| anyhow::anyhow!("frame does not match its size"))?;
+        image.save_with_format(path, image::ImageFormat::Png)?;
+        Ok(())
+    }
+    
+    /// Render a window offscreen, scaled to fit `max_size`, and read it back
+    fn capture_window(renderer: &mut GlesRenderer, window: &Window, max_size: u32) -> Result<WindowFrame> {
+        let geometry = window.geometry();
+        let longest = geometry.size.w.max(geometry.size.h).max(1) as f64;
+        let scale = (max_size as f64 / longest).min(1.0);
+        let width = ((geometry.size.w as f64 * scale).round() as i32).max(1);
+        let height = ((geometry.size.h as f64 * scale).round() as i32).max(1);
+        
+        let elements: Vec<WaylandSurfaceRenderElement<GlesRenderer>> = window.render_elements(
+            renderer,
+            Point::<i32, Logical>::from((-geometry.loc.x, -geometry.loc.y)).to_physical_precise_round(scale),
+            Scale::from(scale),
+            1.0,
+        );
+        Self::render_offscreen(renderer, (width, height).into(), scale, None, &elements)
+    }
+    
+    /// Draw elements into an offscreen texture and read the pixels back
+    fn render_offscreen(
+        renderer: &mut GlesRenderer,
+        size: Size<i32, Physical>,
+        scale: f64,
+        background: Option<&GlesTexture>,
+        elements: &[WaylandSurfaceRenderElement<GlesRenderer>],
+    ) -> Result<WindowFrame> {
+        let (width, height) = (size.w.max(1), size.h.max(1));
+        let size: Size<i32, Physical> = (width, height).into();
+        let buffer_size: Size<i32, Buffer> = (width, height).into();
+        
+        let mut texture: GlesTexture = renderer.create_buffer(Fourcc::Abgr8888, buffer_size)?;
+        let mut target = renderer.bind(&mut texture)?;
+        let damage = [Rectangle::from_size(size)];
+        let mut frame = renderer.render(&mut target, size, Transform::Normal)?;
+        Self::draw_desktop(&mut frame, background, scale, elements, &damage)?;
+        let _ = frame.finish()?;
+        
+        // Abgr8888 is R, G, B, A in memory, which is what thumbnails store
+        let mapping = renderer.copy_framebuffer(&target, Rectangle::from_size(buffer_size), Fourcc::Abgr8888)?;
+        let pixels = renderer.map_texture(&mapping)?.to_vec();
+        Ok(WindowFrame { width: width as u32, height: height as u32, pixels })
+    }
+    
     /// Update camera based on interaction
-    pub fn update_camera(&self, state: &AppState) {
-        // Get camera updates from interaction manager
-        // This is handled by the interaction system
+    pub fn update_camera(&mut self, state: &mut AppState) {
+        let mut interaction = state.interaction_manager.lock().unwrap_or_else(|e| e.into_inner());
+        
+        // Trackpad gestures queued by input handling, e.g. pinch to zoom; they take over from a glide
+        if !state.trackpad_gestures.is_empty() {
+            if let Some(animation) = self.camera_animation.take() {
+                animation.cancel(&mut state.animations.lock().unwrap_or_else(|e| e.into_inner()));
+            }
+        }
+        for event in state.trackpad_gestures.drain(..) {
+            interaction.handle_trackpad_gesture(event, &mut self.camera);
+        }
+        
+        // Camera bookmarks of the active workspace
+        let shortcuts = std::mem::take(&mut *state.camera_shortcuts.lock().unwrap_or_else(|e| e.into_inner()));
+        for action in shortcuts {
+            let offset = match action.as_str() {
+                "save_camera_bookmark" => {
+                    match state.workspaces.save_camera_bookmark(None, self.camera.state()) {
+                        Ok(name) => log::info!("Saved camera bookmark {}", name),
+                        Err(e) => log::warn!("Failed to save camera bookmark: {}", e),
+                    }
+                    continue;
+                }
+                "toggle_minimap" => {
+                    self.minimap_visible = !self.minimap_visible;
+                    continue;
+                }
+                "next_camera_bookmark" => 1,
+                "previous_camera_bookmark" => -1,
+                _ => continue,
+            };
+            match state.workspaces.cycle_camera_bookmark(offset) {
+                Some(bookmark) => interaction.camera_controller().fly_to(&mut self.camera, &bookmark.camera),
+                None => log::debug!("No camera bookmarks in the active workspace"),
+            }
+        }
+        
+        // Clicks on the minimap glide the camera to what was clicked
+        if let Some((minimap, _)) = &state.minimap {
+            for point in state.minimap_clicks.drain(..) {
+                interaction.camera_controller().navigate_minimap(minimap, (point[0], point[1]), &mut self.camera);
+            }
+        }
+        
+        // Smooth camera movement runs in the shared animation system
+        if let Some(target) = self.camera.take_transition() {
+            let mut animations = state.animations.lock().unwrap_or_else(|e| e.into_inner());
+            self.camera_animation = Some(CameraAnimation::start(&mut animations, &self.camera, target, self.camera_animation.take()));
+        }
+    }
+    
+    /// Tell the user about shaders that failed to reload
+    fn report_shader_events(&self, state: &AppState) {
+        let Some(events) = &self.shader_events else {
+            return;
+        };
+        for event in events.try_iter() {
+            match event {
+                ShaderEvent::Reloaded { name, generation } => log::info!("Reloaded shader {} (generation {})", name, generation),
+                ShaderEvent::Failed { name, error } => {
+                    log::warn!("Rejected shader {}: {}", name, error);
+                    let notification = Notification::new(format!("Shader {} rejected", name), format!("Keeping the last working version: {}", error))
+                        .with_type(NotificationType::Error);
+                    state.show_notification(notification);
+                }
+            }
+        }
+    }
+    
+    /// Show the engine's device loss and recovery as notifications
+    ///
+    /// Losing and recovering the device are passing notices; a recovery
+    /// that gave up stays until dismissed.
+    fn report_device_recovery(&self, state: &AppState) {
+        let Some(events) = &self.device_recovery else {
+            return;
+        };
+        for event in events.try_iter() {
+            state.show_notification(device_recovery_notification(&event));
+        }
+    }
+    
+    /// Advance the shared animations by the time since the last frame
+    ///
+    /// Runs once per frame, before anything reads animated values; the camera
+    /// follows its springs here.
+    fn advance_animations(&mut self, state: &mut AppState, now: Instant) {
+        let mut animations = state.animations.lock().unwrap_or_else(|e| e.into_inner());
+        animations.update(now.duration_since(self.last_animation_update).as_secs_f32());
+        self.last_animation_update = now;
+        if let Some(animation) = &self.camera_animation {
+            if !animation.apply(&animations, &mut self.camera) {
+                self.camera_animation = None;
+            }
+        }
+    }
+}
+/// Notification for a device loss or recovery event
+fn device_recovery_notification(event: &RecoveryEvent) -> Notification {
+    let notification = problem_notification(&event.classify());
+    match event {
+        RecoveryEvent::RecoveryFailed { .. } => notification,
+        _ => notification.expires_in(DEVICE_RECOVERY_NOTICE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::{Point3, Vector3};
+    
+    #[test]
+    fn test_device_recovery_notices() {
+        let recovered = device_recovery_notification(&RecoveryEvent::Recovered { attempts: 2, downtime: Duration::from_millis(600) });
+        assert_eq!(recovered.title, "Graphics were restarted; nothing was lost");
+        assert!(recovered.expires_at.is_some() && !recovered.persistent);
+        
+        let lost = device_recovery_notification(&RecoveryEvent::DeviceLost { reason: "GPU hang".to_string() });
+        assert!(lost.expires_at.is_some());
+        
+        let failed = device_recovery_notification(&RecoveryEvent::RecoveryFailed { reason: "no adapter".to_string(), attempts: 5 });
+        assert!(failed.expires_at.is_none() && failed.persistent);
+    }
+    
+    #[test]
+    fn test_camera_glides_through_the_animation_system() {
+        let mut animations = AnimationSystem::new();
+        let mut camera = Camera::new();
+        let target = CameraState { position: Point3::new(4.0, 0.0, 10.0), ..camera.state() };
+        camera.transition_to(&target);
+        
+        let transition = camera.take_transition().unwrap();
+        assert!(!camera.is_animating());
+        let animation = CameraAnimation::start(&mut animations, &camera, transition, None);
+        animations.update(1.0 / 60.0);
+        assert!(animation.apply(&animations, &mut camera));
+        assert!(camera.position.x > 0.0 && camera.position.x < 4.0);
+        
+        // A new view mid-glide turns the running springs towards it
+        let retarget = CameraState { position: Point3::new(-4.0, 0.0, 10.0), forward: Vector3::new(0.0, 0.0, -1.0), ..target };
+        let position = animation.position;
+        let animation = CameraAnimation::start(&mut animations, &camera, retarget, Some(animation));
+        assert_eq!(animation.position, position);
+        
+        let mut moving = true;
+        for _ in 0..600 {
+            animations.update(1.0 / 60.0);
+            moving = animation.apply(&animations, &mut camera);
+            if !moving {
+                break;
+            }
+        }
+        assert!(!moving);
+        assert_eq!(camera.position, retarget.position);
     }
-}
\ No newline at end of file
+}
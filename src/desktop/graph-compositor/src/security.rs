@@ -0,0 +1,695 @@
+//! Client privilege tiers and access to privileged protocols
+//!
+//! Every client is assigned a tier: clients connecting through a
+//! `wp-security-context-v1` listener (Flatpak and other sandboxes) are
+//! `Sandboxed`, other clients are `Standard` and applications named in the
+//! policy file can be raised to `Trusted`. Each tier maps privileged protocols
+//! such as screencopy, global shortcuts and data control to allow, deny or
+//! ask. Globals a client is denied are hidden from its registry; "ask"
+//! raises a prompt through the [`PermissionManager`], which the shell answers
+//! with [`PermissionManager::respond`]. Commands of custom context menu
+//! entries go through the same policy, each entry (or the integration that
+//! registered it) standing in for a client.
+//!
+//! The policy lives in `$XDG_CONFIG_HOME/horizonos/security.toml`:
+//!
+//! ```toml
+//! [sandboxed]
+//! global_shortcuts = "deny"
+//!
+//! [apps."org.obsproject.Studio"]
+//! screencopy = "allow"
+//!
+//! [apps.waybar]
+//! tier = "trusted"
+//! ```
+
+use crate::CompositorError;
+use horizonos_graph_interaction::{CustomMenuEntry, MenuEntrySource, MenuPermission, MenuPermissions};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// How much a client is trusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivilegeTier {
+    /// Shell components and applications the user marked trusted
+    Trusted,
+    /// Unsandboxed applications
+    Standard,
+    /// Applications in a sandbox (Flatpak, Snap, ...)
+    Sandboxed,
+}
+
+/// Protocols that expose other clients' content or control the session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivilegedProtocol {
+    /// Capture the screen
+    Screencopy,
+    /// Register shortcuts that work while other clients are focused
+    GlobalShortcuts,
+    /// Read and set the clipboard without focus
+    DataControl,
+    /// Inject keyboard input
+    VirtualKeyboard,
+    /// List and control other clients' windows
+    ForeignToplevel,
+    /// Change output configuration
+    OutputManagement,
+    /// Lock the session
+    SessionLock,
+    /// Place panels and overlays above other windows
+    LayerShell,
+    /// Act as an input method
+    InputMethod,
+    /// Create further security contexts
+    SecurityContext,
+    /// Run the command of a custom context menu entry
+    MenuCommands,
+}
+
+impl PrivilegedProtocol {
+    /// All privileged protocols
+    pub fn all() -> &'static [PrivilegedProtocol] {
+        &[
+            PrivilegedProtocol::Screencopy,
+            PrivilegedProtocol::GlobalShortcuts,
+            PrivilegedProtocol::DataControl,
+            PrivilegedProtocol::VirtualKeyboard,
+            PrivilegedProtocol::ForeignToplevel,
+            PrivilegedProtocol::OutputManagement,
+            PrivilegedProtocol::SessionLock,
+            PrivilegedProtocol::LayerShell,
+            PrivilegedProtocol::InputMethod,
+            PrivilegedProtocol::SecurityContext,
+            PrivilegedProtocol::MenuCommands,
+        ]
+    }
+
+    /// Wayland interfaces covered by this protocol
+    pub fn interfaces(&self) -> &'static [&'static str] {
+        match self {
+            PrivilegedProtocol::Screencopy => &[
+                "zwlr_screencopy_manager_v1",
+                "ext_image_copy_capture_manager_v1",
+                "ext_output_image_capture_source_manager_v1",
+            ],
+            PrivilegedProtocol::GlobalShortcuts => &["hyprland_global_shortcuts_manager_v1"],
+            PrivilegedProtocol::DataControl => &["zwlr_data_control_manager_v1", "ext_data_control_manager_v1"],
+            PrivilegedProtocol::VirtualKeyboard => &["zwp_virtual_keyboard_manager_v1", "zwlr_virtual_pointer_manager_v1"],
+            PrivilegedProtocol::ForeignToplevel => &["zwlr_foreign_toplevel_manager_v1", "ext_foreign_toplevel_list_v1"],
+            PrivilegedProtocol::OutputManagement => &["zwlr_output_manager_v1", "zwlr_gamma_control_manager_v1"],
+            PrivilegedProtocol::SessionLock => &["ext_session_lock_manager_v1"],
+            PrivilegedProtocol::LayerShell => &["zwlr_layer_shell_v1"],
+            PrivilegedProtocol::InputMethod => &["zwp_input_method_manager_v2"],
+            PrivilegedProtocol::SecurityContext => &["wp_security_context_manager_v1"],
+            PrivilegedProtocol::MenuCommands => &[],
+        }
+    }
+
+    /// Protocol a Wayland interface belongs to, if it is privileged
+    pub fn from_interface(interface: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|protocol| protocol.interfaces().contains(&interface))
+    }
+
+    /// Short description for permission prompts
+    pub fn description(&self) -> &'static str {
+        match self {
+            PrivilegedProtocol::Screencopy => "record the screen",
+            PrivilegedProtocol::GlobalShortcuts => "register global shortcuts",
+            PrivilegedProtocol::DataControl => "access the clipboard in the background",
+            PrivilegedProtocol::VirtualKeyboard => "simulate keyboard and pointer input",
+            PrivilegedProtocol::ForeignToplevel => "see and control other windows",
+            PrivilegedProtocol::OutputManagement => "change display settings",
+            PrivilegedProtocol::SessionLock => "lock the session",
+            PrivilegedProtocol::LayerShell => "draw panels and overlays",
+            PrivilegedProtocol::InputMethod => "act as an input method",
+            PrivilegedProtocol::SecurityContext => "start sandboxed clients",
+            PrivilegedProtocol::MenuCommands => "run a command from the context menu",
+        }
+    }
+}
+
+/// Policy decision for a protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Access {
+    Allow,
+    Ask,
+    Deny,
+}
+
+/// Built-in access of a tier to a protocol
+fn default_access(tier: PrivilegeTier, protocol: PrivilegedProtocol) -> Access {
+    use PrivilegedProtocol::*;
+    match (tier, protocol) {
+        (PrivilegeTier::Trusted, _) => Access::Allow,
+        (PrivilegeTier::Standard, Screencopy | DataControl | VirtualKeyboard | MenuCommands) => Access::Ask,
+        (PrivilegeTier::Standard, _) => Access::Allow,
+        (PrivilegeTier::Sandboxed, Screencopy | GlobalShortcuts | MenuCommands) => Access::Ask,
+        (PrivilegeTier::Sandboxed, _) => Access::Deny,
+    }
+}
+
+/// Per-application policy
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AppPolicy {
+    /// Tier override
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tier: Option<PrivilegeTier>,
+    /// Protocol overrides
+    #[serde(flatten)]
+    pub protocols: BTreeMap<PrivilegedProtocol, Access>,
+}
+
+/// Privilege policy, built-in defaults overridden by the policy file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecurityPolicy {
+    /// Overrides for trusted clients
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub trusted: BTreeMap<PrivilegedProtocol, Access>,
+    /// Overrides for standard clients
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub standard: BTreeMap<PrivilegedProtocol, Access>,
+    /// Overrides for sandboxed clients
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub sandboxed: BTreeMap<PrivilegedProtocol, Access>,
+    /// Overrides by application ID
+    pub apps: BTreeMap<String, AppPolicy>,
+}
+
+impl SecurityPolicy {
+    /// Default policy file location
+    pub fn default_path() -> Option<PathBuf> {
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_dir.join("horizonos").join("security.toml"))
+    }
+
+    /// Read a policy file; a missing file gives the built-in policy
+    pub fn load(path: &Path) -> Result<Self, CompositorError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(path)?;
+        toml::from_str(&text)
+            .map_err(|e| CompositorError::Config(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Write the policy file
+    pub fn save(&self, path: &Path) -> Result<(), CompositorError> {
+        let text = toml::to_string_pretty(self)
+            .map_err(|e| CompositorError::Config(format!("Failed to serialize security policy: {}", e)))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Tier of a client, after application overrides
+    pub fn tier(&self, identity: &ClientIdentity) -> PrivilegeTier {
+        identity.app_id.as_ref()
+            .and_then(|app_id| self.apps.get(app_id))
+            .and_then(|app| app.tier)
+            .unwrap_or(identity.base_tier())
+    }
+
+    /// Access of a client to a protocol
+    pub fn access(&self, identity: &ClientIdentity, protocol: PrivilegedProtocol) -> Access {
+        if let Some(access) = identity.app_id.as_ref()
+            .and_then(|app_id| self.apps.get(app_id))
+            .and_then(|app| app.protocols.get(&protocol))
+        {
+            return *access;
+        }
+
+        let tier = self.tier(identity);
+        let overrides = match tier {
+            PrivilegeTier::Trusted => &self.trusted,
+            PrivilegeTier::Standard => &self.standard,
+            PrivilegeTier::Sandboxed => &self.sandboxed,
+        };
+        overrides.get(&protocol).copied().unwrap_or_else(|| default_access(tier, protocol))
+    }
+}
+
+/// Who a client is, as far as the compositor can tell
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientIdentity {
+    /// Application ID reported by the sandbox, or the executable name
+    pub app_id: Option<String>,
+    /// Sandbox engine, e.g. `org.flatpak`, for clients from a security context
+    pub sandbox_engine: Option<String>,
+    /// Sandbox instance
+    pub instance_id: Option<String>,
+    /// Process ID from socket credentials
+    pub pid: Option<i32>,
+}
+
+impl ClientIdentity {
+    /// Identity of a client connected through a security context
+    pub fn sandboxed(sandbox_engine: Option<String>, app_id: Option<String>, instance_id: Option<String>) -> Self {
+        Self {
+            // Sandboxes without an engine name still get the sandboxed tier
+            sandbox_engine: Some(sandbox_engine.unwrap_or_else(|| "unknown".to_string())),
+            app_id,
+            instance_id,
+            pid: None,
+        }
+    }
+
+    /// Identity of an unsandboxed client from its process ID
+    pub fn native(pid: Option<i32>) -> Self {
+        let app_id = pid.and_then(|pid| std::fs::read_link(format!("/proc/{}/exe", pid)).ok())
+            .and_then(|exe| exe.file_name().map(|name| name.to_string_lossy().into_owned()));
+        Self {
+            app_id,
+            sandbox_engine: None,
+            instance_id: None,
+            pid,
+        }
+    }
+
+    /// Whether the client is in a sandbox
+    pub fn is_sandboxed(&self) -> bool {
+        self.sandbox_engine.is_some()
+    }
+
+    /// Tier before policy overrides
+    fn base_tier(&self) -> PrivilegeTier {
+        if self.is_sandboxed() {
+            PrivilegeTier::Sandboxed
+        } else {
+            PrivilegeTier::Standard
+        }
+    }
+
+    /// Name used for remembered decisions and prompts
+    pub fn key(&self) -> String {
+        match (&self.app_id, self.pid) {
+            (Some(app_id), _) => app_id.clone(),
+            (None, Some(pid)) => format!("pid:{}", pid),
+            (None, None) => "unknown".to_string(),
+        }
+    }
+}
+
+/// How long a prompt answer applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Remember {
+    /// Only this request
+    Once,
+    /// Until the compositor restarts
+    Session,
+    /// Written to the policy file
+    Always,
+}
+
+/// A request waiting for the user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionRequest {
+    pub id: u64,
+    pub client: ClientIdentity,
+    pub protocol: PrivilegedProtocol,
+}
+
+impl PermissionRequest {
+    /// Prompt text for the shell
+    pub fn prompt(&self) -> String {
+        let sandbox = match &self.client.sandbox_engine {
+            Some(engine) => format!(" (sandboxed by {})", engine),
+            None => String::new(),
+        };
+        format!("{}{} wants to {}", self.client.key(), sandbox, self.protocol.description())
+    }
+}
+
+/// Result of a permission check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionCheck {
+    Allowed,
+    Denied,
+    /// Waiting on the prompt with this request ID
+    Pending(u64),
+}
+
+/// Events for the shell
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermissionEvent {
+    /// Show a prompt for this request
+    PromptRequested(PermissionRequest),
+    /// A prompt was answered; the protocol handler can proceed or fail the request
+    PromptResolved { request: PermissionRequest, allowed: bool },
+}
+
+/// Applies the security policy and routes prompts to the shell
+pub struct PermissionManager {
+    policy: SecurityPolicy,
+    policy_path: Option<PathBuf>,
+    /// Answers remembered for this session, by client key and protocol
+    session_decisions: HashMap<(String, PrivilegedProtocol), bool>,
+    pending: BTreeMap<u64, PermissionRequest>,
+    next_request_id: u64,
+    events: Vec<PermissionEvent>,
+}
+
+impl PermissionManager {
+    /// Create a manager; `Always` answers are saved to `policy_path`
+    pub fn new(policy: SecurityPolicy, policy_path: Option<PathBuf>) -> Self {
+        Self {
+            policy,
+            policy_path,
+            session_decisions: HashMap::new(),
+            pending: BTreeMap::new(),
+            next_request_id: 1,
+            events: Vec::new(),
+        }
+    }
+
+    /// Load the policy from its default location, falling back to the built-in policy
+    pub fn load_default() -> Self {
+        let path = SecurityPolicy::default_path();
+        let policy = match path.as_deref().map(SecurityPolicy::load) {
+            Some(Ok(policy)) => policy,
+            Some(Err(e)) => {
+                log::warn!("Ignoring invalid security policy: {}", e);
+                SecurityPolicy::default()
+            }
+            None => SecurityPolicy::default(),
+        };
+        Self::new(policy, path)
+    }
+
+    /// Current policy
+    pub fn policy(&self) -> &SecurityPolicy {
+        &self.policy
+    }
+
+    /// Replace the policy, e.g. after the file changed
+    pub fn set_policy(&mut self, policy: SecurityPolicy) {
+        self.policy = policy;
+    }
+
+    /// Tier of a client
+    pub fn tier(&self, client: &ClientIdentity) -> PrivilegeTier {
+        self.policy.tier(client)
+    }
+
+    /// Whether a global should appear in a client's registry
+    ///
+    /// "Ask" globals stay hidden until the user allowed them for this client,
+    /// so a client cannot bind one before the prompt was answered.
+    pub fn can_see_global(&self, client: &ClientIdentity, interface: &str) -> bool {
+        let Some(protocol) = PrivilegedProtocol::from_interface(interface) else {
+            return true;
+        };
+        match self.policy.access(client, protocol) {
+            Access::Allow => true,
+            Access::Deny => false,
+            Access::Ask => self.session_decisions.get(&(client.key(), protocol)) == Some(&true),
+        }
+    }
+
+    /// Check whether a client may use a protocol, prompting if the policy asks
+    pub fn check(&mut self, client: &ClientIdentity, protocol: PrivilegedProtocol) -> PermissionCheck {
+        match self.policy.access(client, protocol) {
+            Access::Allow => return PermissionCheck::Allowed,
+            Access::Deny => return PermissionCheck::Denied,
+            Access::Ask => {}
+        }
+
+        let key = client.key();
+        match self.session_decisions.get(&(key.clone(), protocol)) {
+            Some(true) => return PermissionCheck::Allowed,
+            Some(false) => return PermissionCheck::Denied,
+            None => {}
+        }
+
+        // One prompt per client and protocol at a time
+        if let Some(request) = self.pending.values().find(|request| request.client.key() == key && request.protocol == protocol) {
+            return PermissionCheck::Pending(request.id);
+        }
+
+        let request = PermissionRequest {
+            id: self.next_request_id,
+            client: client.clone(),
+            protocol,
+        };
+        self.next_request_id += 1;
+        log::info!("Permission prompt {}: {}", request.id, request.prompt());
+        self.pending.insert(request.id, request.clone());
+        self.events.push(PermissionEvent::PromptRequested(request.clone()));
+        PermissionCheck::Pending(request.id)
+    }
+
+    /// Answer a prompt
+    pub fn respond(&mut self, request_id: u64, allowed: bool, remember: Remember) -> Result<(), CompositorError> {
+        let request = self.pending.remove(&request_id)
+            .ok_or_else(|| CompositorError::Protocol(format!("No pending permission request {}", request_id)))?;
+
+        let key = request.client.key();
+        match remember {
+            Remember::Once => {}
+            Remember::Session => {
+                self.session_decisions.insert((key, request.protocol), allowed);
+            }
+            Remember::Always => {
+                let access = if allowed { Access::Allow } else { Access::Deny };
+                self.policy.apps.entry(key.clone()).or_default().protocols.insert(request.protocol, access);
+                self.session_decisions.insert((key, request.protocol), allowed);
+                if let Some(path) = &self.policy_path {
+                    self.policy.save(path)?;
+                }
+            }
+        }
+
+        self.events.push(PermissionEvent::PromptResolved { request, allowed });
+        Ok(())
+    }
+
+    /// Requests waiting for an answer
+    pub fn pending_requests(&self) -> Vec<PermissionRequest> {
+        self.pending.values().cloned().collect()
+    }
+
+    /// Drop pending prompts of a disconnected client
+    pub fn client_disconnected(&mut self, client: &ClientIdentity) {
+        let key = client.key();
+        self.pending.retain(|_, request| request.client.key() != key);
+    }
+
+    /// Remember an answer for this session without asking, e.g. for a grant given elsewhere
+    pub fn remember(&mut self, app_key: &str, protocol: PrivilegedProtocol, allowed: bool) {
+        self.session_decisions.insert((app_key.to_string(), protocol), allowed);
+    }
+
+    /// Forget session answers for an application
+    pub fn revoke(&mut self, app_key: &str) {
+        self.session_decisions.retain(|(key, _), _| key != app_key);
+    }
+
+    /// Take events for the shell
+    pub fn poll_events(&mut self) -> Vec<PermissionEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl Default for PermissionManager {
+    fn default() -> Self {
+        Self::new(SecurityPolicy::default(), None)
+    }
+}
+
+/// Checks custom context menu entries against the security policy
+pub struct MenuCommandPermissions {
+    permissions: Arc<Mutex<PermissionManager>>,
+}
+
+impl MenuCommandPermissions {
+    pub fn new(permissions: Arc<Mutex<PermissionManager>>) -> Self {
+        Self { permissions }
+    }
+
+    /// Identity an entry is checked as: its integration, or else the entry
+    /// with its command, so a changed command is asked about again
+    pub fn identity(entry: &CustomMenuEntry) -> ClientIdentity {
+        let app_id = match &entry.source {
+            MenuEntrySource::Plugin { plugin_id } => Self::plugin_key(plugin_id),
+            _ => format!("menu:{} ({})", entry.id, entry.describe_command()),
+        };
+        ClientIdentity {
+            app_id: Some(app_id),
+            ..ClientIdentity::default()
+        }
+    }
+
+    /// Key the entries of an integration are checked under
+    pub fn plugin_key(plugin_id: &str) -> String {
+        format!("plugin:{}", plugin_id)
+    }
+}
+
+impl MenuPermissions for MenuCommandPermissions {
+    fn check(&self, entry: &CustomMenuEntry) -> MenuPermission {
+        let identity = Self::identity(entry);
+        match self.permissions.lock().unwrap_or_else(|e| e.into_inner()).check(&identity, PrivilegedProtocol::MenuCommands) {
+            PermissionCheck::Allowed => MenuPermission::Allowed,
+            PermissionCheck::Denied => MenuPermission::Denied,
+            PermissionCheck::Pending(request_id) => MenuPermission::Pending(request_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flatpak(app_id: &str) -> ClientIdentity {
+        ClientIdentity::sandboxed(Some("org.flatpak".to_string()), Some(app_id.to_string()), None)
+    }
+
+    #[test]
+    fn test_policy_tiers_and_overrides() {
+        let policy: SecurityPolicy = toml::from_str(r#"
+            [sandboxed]
+            global_shortcuts = "deny"
+
+            [apps."org.obsproject.Studio"]
+            screencopy = "allow"
+
+            [apps.waybar]
+            tier = "trusted"
+        "#).unwrap();
+
+        let native = ClientIdentity { app_id: Some("foot".to_string()), ..Default::default() };
+        let panel = ClientIdentity { app_id: Some("waybar".to_string()), ..Default::default() };
+        let sandboxed = flatpak("org.example.App");
+
+        assert_eq!(policy.tier(&sandboxed), PrivilegeTier::Sandboxed);
+        assert_eq!(policy.tier(&panel), PrivilegeTier::Trusted);
+        assert_eq!(policy.access(&panel, PrivilegedProtocol::DataControl), Access::Allow);
+        assert_eq!(policy.access(&native, PrivilegedProtocol::DataControl), Access::Ask);
+        assert_eq!(policy.access(&sandboxed, PrivilegedProtocol::DataControl), Access::Deny);
+        assert_eq!(policy.access(&sandboxed, PrivilegedProtocol::GlobalShortcuts), Access::Deny);
+        assert_eq!(policy.access(&flatpak("org.obsproject.Studio"), PrivilegedProtocol::Screencopy), Access::Allow);
+
+        let manager = PermissionManager::new(policy, None);
+        assert!(!manager.can_see_global(&sandboxed, "zwlr_data_control_manager_v1"));
+        assert!(!manager.can_see_global(&sandboxed, "zwlr_screencopy_manager_v1"));
+        assert!(manager.can_see_global(&sandboxed, "wl_compositor"));
+    }
+
+    #[test]
+    fn test_global_visibility_per_tier() {
+        let policy: SecurityPolicy = toml::from_str(r#"
+            [apps.waybar]
+            tier = "trusted"
+        "#).unwrap();
+        let mut manager = PermissionManager::new(policy, None);
+        let trusted = ClientIdentity { app_id: Some("waybar".to_string()), ..Default::default() };
+        let standard = ClientIdentity { app_id: Some("foot".to_string()), ..Default::default() };
+        let sandboxed = flatpak("org.example.App");
+
+        // Trusted clients see every privileged global
+        for protocol in PrivilegedProtocol::all() {
+            for interface in protocol.interfaces() {
+                assert!(manager.can_see_global(&trusted, interface), "{}", interface);
+            }
+        }
+
+        // Standard clients: allowed globals are visible, "ask" globals are not
+        assert!(manager.can_see_global(&standard, "zwlr_foreign_toplevel_manager_v1"));
+        assert!(manager.can_see_global(&standard, "zwlr_layer_shell_v1"));
+        assert!(!manager.can_see_global(&standard, "zwlr_screencopy_manager_v1"));
+        assert!(!manager.can_see_global(&standard, "zwlr_data_control_manager_v1"));
+        assert!(!manager.can_see_global(&standard, "zwp_virtual_keyboard_manager_v1"));
+
+        // Sandboxed clients: "ask" and denied globals are hidden
+        assert!(!manager.can_see_global(&sandboxed, "zwlr_screencopy_manager_v1"));
+        assert!(!manager.can_see_global(&sandboxed, "hyprland_global_shortcuts_manager_v1"));
+        assert!(!manager.can_see_global(&sandboxed, "zwlr_foreign_toplevel_manager_v1"));
+        assert!(!manager.can_see_global(&sandboxed, "wp_security_context_manager_v1"));
+
+        // Unprivileged globals are always visible
+        for client in [&trusted, &standard, &sandboxed] {
+            assert!(manager.can_see_global(client, "wl_compositor"));
+        }
+
+        // An approved "ask" global appears; a refused or denied one stays hidden
+        let PermissionCheck::Pending(id) = manager.check(&sandboxed, PrivilegedProtocol::Screencopy) else {
+            panic!("expected a prompt");
+        };
+        assert!(!manager.can_see_global(&sandboxed, "zwlr_screencopy_manager_v1"));
+        manager.respond(id, true, Remember::Session).unwrap();
+        assert!(manager.can_see_global(&sandboxed, "zwlr_screencopy_manager_v1"));
+        assert!(manager.can_see_global(&sandboxed, "ext_image_copy_capture_manager_v1"));
+
+        let PermissionCheck::Pending(id) = manager.check(&standard, PrivilegedProtocol::DataControl) else {
+            panic!("expected a prompt");
+        };
+        manager.respond(id, false, Remember::Session).unwrap();
+        assert!(!manager.can_see_global(&standard, "zwlr_data_control_manager_v1"));
+
+        manager.remember(&sandboxed.key(), PrivilegedProtocol::ForeignToplevel, true);
+        assert!(!manager.can_see_global(&sandboxed, "zwlr_foreign_toplevel_manager_v1"));
+
+        manager.revoke("org.example.App");
+        assert!(!manager.can_see_global(&sandboxed, "zwlr_screencopy_manager_v1"));
+    }
+
+    #[test]
+    fn test_prompts_are_routed_and_remembered() {
+        let mut manager = PermissionManager::default();
+        let client = flatpak("org.example.Recorder");
+
+        let PermissionCheck::Pending(id) = manager.check(&client, PrivilegedProtocol::Screencopy) else {
+            panic!("expected a prompt");
+        };
+        assert_eq!(manager.check(&client, PrivilegedProtocol::Screencopy), PermissionCheck::Pending(id));
+        let events = manager.poll_events();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0], PermissionEvent::PromptRequested(request) if request.prompt().contains("record the screen")));
+
+        manager.respond(id, true, Remember::Session).unwrap();
+        assert!(matches!(manager.poll_events()[0], PermissionEvent::PromptResolved { allowed: true, .. }));
+        assert_eq!(manager.check(&client, PrivilegedProtocol::Screencopy), PermissionCheck::Allowed);
+        assert!(manager.respond(id, true, Remember::Once).is_err());
+
+        manager.revoke("org.example.Recorder");
+        assert!(matches!(manager.check(&client, PrivilegedProtocol::Screencopy), PermissionCheck::Pending(_)));
+    }
+
+    #[test]
+    fn test_menu_entries_are_checked_like_clients() {
+        use horizonos_graph_interaction::MenuCommand;
+
+        let permissions = Arc::new(Mutex::new(PermissionManager::default()));
+        let menus = MenuCommandPermissions::new(permissions.clone());
+        let exec = |argv: &[&str]| MenuCommand::Exec { argv: argv.iter().map(|a| a.to_string()).collect() };
+        let entry = CustomMenuEntry::new("open-in-vscode", "Open in VS Code", exec(&["code", "%f"]));
+
+        let MenuPermission::Pending(id) = menus.check(&entry) else {
+            panic!("expected a prompt");
+        };
+        let events = permissions.lock().unwrap().poll_events();
+        assert!(matches!(&events[0], PermissionEvent::PromptRequested(request) if request.prompt().contains("code %f")));
+        permissions.lock().unwrap().respond(id, true, Remember::Session).unwrap();
+        assert_eq!(menus.check(&entry), MenuPermission::Allowed);
+
+        // A different command under the same id is asked about again
+        let changed = CustomMenuEntry::new("open-in-vscode", "Open in VS Code", exec(&["rm", "%f"]));
+        assert!(matches!(menus.check(&changed), MenuPermission::Pending(_)));
+
+        // Integration entries share the integration's answer
+        let plugin = CustomMenuEntry::new("notes:open", "Open Notes", exec(&["notes"]))
+            .from_source(MenuEntrySource::Plugin { plugin_id: "notes".to_string() });
+        permissions.lock().unwrap().remember(&MenuCommandPermissions::plugin_key("notes"), PrivilegedProtocol::MenuCommands, false);
+        assert_eq!(menus.check(&plugin), MenuPermission::Denied);
+    }
+}
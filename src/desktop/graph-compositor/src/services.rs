@@ -0,0 +1,134 @@
+//! Desktop services owned by the compositor
+//!
+//! The compositor builds one [`ServiceRegistry`] before creating its state.
+//! Services come up in this order, each able to use the ones before it, and
+//! shut down in reverse:
+//! 1. [`EventBus`]
+//! 2. [`ProblemsPanel`], where services report problems the user should see
+//! 3. [`ConfigManager`], whose change events are forwarded onto the bus
+//! 4. [`PermissionManager`], shared with the Wayland global filters
+//! 5. [`NotificationManager`]
+//! 6. [`ProblemNotifier`], showing reported problems as notifications once
+//!    the compositor runs it
+//! 7. [`ConflictCenter`], where sync providers report conflicts
+//! 8. [`WorkspaceManager`], journaling the session for crash recovery
+//! 9. [`PowerManager`], whose battery and power profile the renderer's
+//!    power-saving path follows
+//! 10. [`AIService`], when the startup profile enables AI
+//! 11. [`WorkflowScheduler`], running automation scripts, when the startup
+//!     profile enables automation
+
+use crate::profile::ProfileSettings;
+use crate::security::PermissionManager;
+use horizonos_graph_ai::automation::scheduler::{SchedulerConfig, WorkflowScheduler};
+use horizonos_graph_ai::AIService;
+use horizonos_graph_config::{ConfigChangeEvent, ConfigManager};
+use horizonos_graph_errors::ProblemsPanel;
+use horizonos_graph_notifications::{NotificationConfig, NotificationManager, ProblemNotifier};
+use horizonos_graph_services::{EventBus, ServiceRegistry, ServiceRegistryBuilder};
+use horizonos_graph_sync::ConflictCenter;
+use horizonos_graph_system::PowerManager;
+use horizonos_graph_workspaces::{SessionJournal, WorkspaceManager};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// `$XDG_CONFIG_HOME/horizonos`
+pub fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("horizonos"))
+}
+
+/// `$XDG_DATA_HOME/horizonos`
+pub fn data_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .map(|dir| dir.join("horizonos"))
+}
+
+/// The desktop's services for a startup profile
+///
+/// Must be built inside a Tokio runtime that outlives the registry, since
+/// several services run background tasks.
+pub fn desktop_services(profile: &ProfileSettings) -> ServiceRegistryBuilder {
+    let mut builder = ServiceRegistry::builder()
+        .instance(Arc::new(EventBus::new()))
+        .instance(Arc::new(ProblemsPanel::new()))
+        .provide(|services| async move {
+            let events = services.get::<EventBus>()?;
+            let (mut manager, mut changes) = ConfigManager::new();
+            if let Some(dir) = config_dir() {
+                manager.initialize(&dir).await?;
+                // The first launch benchmarks the machine for performance defaults; later ones reuse the result
+                match manager.calibrate_if_needed(&dir).await {
+                    Ok(calibration) => log::info!("Performance defaults calibrated for a {:?} machine", calibration.tier),
+                    Err(e) => log::warn!("Keeping built-in performance defaults, calibration failed: {}", e),
+                }
+            }
+            tokio::spawn(async move {
+                while changes.changed().await.is_ok() {
+                    let event: ConfigChangeEvent = changes.borrow_and_update().clone();
+                    events.publish(event);
+                }
+            });
+            Ok(manager)
+        })
+        .provide(|_| async { Ok(Mutex::new(PermissionManager::load_default())) })
+        .provide(|_| async {
+            let config = NotificationConfig {
+                reminders_path: data_dir().map(|dir| dir.join("reminders.json")),
+                ..NotificationConfig::default()
+            };
+            Ok(NotificationManager::new(config))
+        })
+        .provide(|services| async move {
+            Ok(ProblemNotifier::new(services.get::<ProblemsPanel>()?, services.get::<NotificationManager>()?))
+        })
+        .instance(Arc::new(ConflictCenter::new()))
+        .provide(|services| async move {
+            let interval = services.get::<ConfigManager>()?.config().workspace.auto_save_interval;
+            let mut manager = WorkspaceManager::new().with_auto_save_interval(Duration::from_secs(interval.max(1).into()));
+            // Without a journal the session still runs, it just cannot be recovered
+            match SessionJournal::open(SessionJournal::default_dir()) {
+                Ok(journal) => manager = manager.with_session_journal(Arc::new(journal)),
+                Err(e) => log::warn!("Session journal unavailable: {}", e),
+            }
+            manager = manager.with_conflict_center(services.get::<ConflictCenter>()?);
+            manager.initialize().await?;
+            Ok(manager)
+        })
+        .provide(|_| PowerManager::new());
+
+    if profile.subsystems().ai {
+        builder = builder
+            .provide(|services| async move {
+                let service = AIService::new();
+                service.set_problems_panel(services.get::<ProblemsPanel>()?);
+                // Without a model server the desktop carries on without suggestions
+                if let Err(e) = service.initialize().await {
+                    log::warn!("AI service unavailable: {}", e);
+                }
+                Ok(service)
+            })
+            .on_shutdown::<AIService, _, _>(|service| async move { Ok(service.shutdown().await?) });
+    }
+    if profile.subsystems().automation {
+        builder = builder
+            .provide(|services| async move {
+                let config = SchedulerConfig {
+                    schedule_file: data_dir().map(|dir| dir.join("schedules.json").to_string_lossy().into_owned()),
+                    ..SchedulerConfig::default()
+                };
+                let scheduler = WorkflowScheduler::new(config).await?;
+                scheduler.set_problems_panel(services.get::<ProblemsPanel>()?);
+                scheduler.start().await?;
+                Ok(scheduler)
+            })
+            .on_shutdown::<WorkflowScheduler, _, _>(|scheduler| async move { Ok(scheduler.stop().await?) });
+    }
+    builder
+}
+
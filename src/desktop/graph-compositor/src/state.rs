@@ -2,9 +2,11 @@
 
 use smithay::{
     delegate_compositor, delegate_shm, delegate_xdg_shell, delegate_seat,
-    delegate_data_device, delegate_output,
+    delegate_data_device, delegate_output, delegate_security_context,
     desktop::{Space, Window, PopupManager},
     input::{Seat, SeatHandler, SeatState, pointer::CursorImageStatus},
+    output::Output,
+    utils::{Logical, Point},
     reexports::{
         wayland_server::{
             backend::ClientData,
@@ -21,23 +23,81 @@ use smithay::{
         },
         output::{OutputHandler, OutputManagerState},
         seat,
+        security_context::{
+            SecurityContext, SecurityContextHandler, SecurityContextListenerSource, SecurityContextState,
+        },
         shell::xdg::{ToplevelSurface, XdgShellHandler, XdgShellState, PopupSurface},
         shm::{ShmHandler, ShmState},
     },
 };
 use calloop::LoopHandle;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
 use std::collections::HashMap;
-use horizonos_graph_engine::{Scene, SceneId};
+use horizonos_graph_engine::{Camera, CameraState, Minimap, Scene, SceneEdge, SceneId, SceneNode, SceneSnapshot, SwitchOverlay};
+use nalgebra::Point3;
+use horizonos_graph_nodes::NodeActionResult;
 use horizonos_graph_nodes::manager::NodeManager;
-use horizonos_graph_interaction::InteractionManager;
+use horizonos_graph_nodes::hooks::{HookAction, HookRegistry};
+use horizonos_graph_nodes::status_badges::StatusBadgeRegistry;
+use horizonos_graph_nodes::NodeVisualData;
+use horizonos_graph_edges::{EdgeManager, EdgeValidator};
+use horizonos_graph_clustering::ClusteringSystem;
+use horizonos_graph_interaction::{InteractionManager, IntegrationRegistry, IntegrationState, MenuItem, TrackpadGesture};
+use horizonos_graph_notifications::{focus_mode_request, node_action_notification, Notification, NotificationEvent, NotificationManager};
+use horizonos_graph_notifications::{NotificationReview, ReviewCommand, ReviewEffect, ReviewUtterance};
+use horizonos_graph_accessibility::at_spi::AtSpiBus;
+use horizonos_graph_accessibility::{AccessibilityManager, AccessibilitySettings};
+use horizonos_graph_accessibility::edge_creation::EdgeContext;
+use horizonos_graph_accessibility::keyboard_nav::{Key, Modifier};
+use horizonos_graph_performance::{LodSystem, PerformanceManager, PowerState};
+use horizonos_graph_visual::{AnimationId, AnimationSystem, Color, IconLoader, LiveThumbnails, ThumbnailGenerator, VisualManager, WorkspaceTint};
+use horizonos_graph_engine::{AssetStore, RenderPalette};
+use horizonos_graph_config::{ConfigChangeEvent, ConfigManager};
+use horizonos_graph_services::{EventBus, ServiceRegistry};
+use horizonos_graph_workspaces::{JournalOp, PreloadCandidate, TransitionFrame, WorkspaceManager};
+use horizonos_graph_ctl::{ApiError, DesktopEvent, Request};
+use horizonos_graph_ai::automation::scheduler::WorkflowScheduler;
+use horizonos_graph_ai::automation::{AutomationConfig, AutomationManager, HookWorkflowLauncher};
+use horizonos_graph_ai::suggestions::badges::{EdgeSuggestionBadges, EdgeSuggestionResponse};
+use horizonos_graph_ai::agents::AgentEvent;
+use horizonos_graph_ai::AIService;
+use horizonos_graph_system::{MonitorManager, PowerManager};
+use crate::output::OutputView;
+use crate::control::ControlCall;
+use crate::portal::{CaptureSource, PendingPick, PendingScreenshot, PickerEntry, PickerKey, PickerOutcome, PortalRequest, PortalServer, SourcePicker, StreamInfo};
+use crate::prompt::{PromptAnswer, PromptKey, PromptQueue, PromptSubject};
 use crate::protocols::ProtocolManager;
+use crate::quick_look::QuickLookKey;
+use crate::switcher::SwitcherKey;
+use crate::security::{ClientIdentity, MenuCommandPermissions, PermissionEvent, PermissionManager, PrivilegedProtocol};
 use crate::window_manager::{WindowManager, WindowManagerConfig};
 
+/// Context menu item expanding a group node into its cluster
+pub const EXPAND_GROUP_ITEM: &str = "expand_group";
+
+/// Context menu item collapsing the cluster of a node into a group node
+pub const COLLAPSE_CLUSTER_ITEM: &str = "collapse_cluster";
+
+/// Context menu item hiding a node from the AI service, or showing it again
+pub const TOGGLE_AI_VISIBILITY_ITEM: &str = "toggle_ai_visibility";
+
+/// Owner of layout animations in the animation system
+const LAYOUT_ANIMATION_OWNER: &str = "layout";
+
+/// How often the project roots are scanned for new or changed repositories
+const PROJECT_SCAN_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Node types whose context menus offer to collapse their cluster and hide them from the AI
+const CLUSTERED_NODE_TYPES: &[&str] = &[
+    "application", "file", "person", "task", "device", "ai_agent", "concept", "system", "url", "automation", "setting",
+];
+
 /// Client data stored per connected client
 #[derive(Default)]
 pub struct ClientState {
     pub compositor_state: CompositorClientState,
+    /// Security context the client connected through, set for sandboxed clients
+    pub security_context: Option<SecurityContext>,
 }
 
 impl ClientData for ClientState {
@@ -45,6 +105,30 @@ impl ClientData for ClientState {
     fn disconnected(&self, _client_id: smithay::reexports::wayland_server::backend::ClientId, _reason: smithay::reexports::wayland_server::backend::DisconnectReason) {}
 }
 
+/// Identity of a client for privilege checks
+pub fn client_identity(client: &Client, display_handle: &DisplayHandle) -> ClientIdentity {
+    match client.get_data::<ClientState>().and_then(|data| data.security_context.as_ref()) {
+        Some(context) => ClientIdentity::sandboxed(
+            context.sandbox_engine.clone(),
+            context.app_id.clone(),
+            context.instance_id.clone(),
+        ),
+        None => ClientIdentity::native(client.get_credentials(display_handle).ok().map(|credentials| credentials.pid)),
+    }
+}
+
+/// Global filter hiding a privileged interface from clients until the policy or the user allows it
+pub fn privileged_global_filter(
+    permissions: Arc<Mutex<PermissionManager>>,
+    display_handle: DisplayHandle,
+    interface: &'static str,
+) -> impl Fn(&Client) -> bool + Send + Sync + 'static {
+    move |client| {
+        let identity = client_identity(client, &display_handle);
+        permissions.lock().unwrap_or_else(|e| e.into_inner()).can_see_global(&identity, interface)
+    }
+}
+
 /// Main compositor state
 pub struct AppState {
     /// Running flag
@@ -61,17 +145,76 @@ pub struct AppState {
     pub output_manager: OutputManagerState,
     pub seat_state: SeatState<Self>,
     pub data_device_state: DataDeviceState,
+    pub security_context_state: SecurityContextState,
+    
+    // Desktop services shared with the rest of the session
+    pub services: Arc<ServiceRegistry>,
+    
+    // Client privileges and permission prompts, owned by the service registry
+    pub permissions: Arc<Mutex<PermissionManager>>,
+    
+    // Graph workspaces and the crash journal of the session, owned by the service registry
+    pub workspaces: Arc<WorkspaceManager>,
+    /// Camera of a recovered session, until the renderer takes it
+    pub restored_camera: Option<CameraState>,
     
     // Desktop management
     pub space: Space<Window>,
     pub popups: PopupManager,
+    /// Layout of the outputs and what each one shows of the graph
+    pub monitors: MonitorManager,
+    /// Node dragged with Super held, and where the drag started in the global space
+    node_drag: Option<(SceneId, (i32, i32))>,
     
     // Graph integration
     pub graph_scene: Arc<Mutex<Scene>>,
     pub node_manager: Arc<Mutex<NodeManager>>,
     pub interaction_manager: Arc<Mutex<InteractionManager>>,
+    /// Relationships drawn by the user, checked against the configured edge rules
+    pub edges: Arc<RwLock<EdgeManager>>,
     pub surface_to_node: HashMap<WlSurface, SceneId>,
     
+    // Clusters of the graph, which collapse into group nodes
+    pub clustering: ClusteringSystem,
+    // When the project roots were last scanned for repositories
+    last_project_scan: Option<std::time::Instant>,
+    
+    // Live window thumbnails on application nodes, refreshed by level of detail
+    pub live_thumbnails: LiveThumbnails,
+    
+    // PipeWire devices and streams as graph nodes
+    pub audio: crate::audio::AudioNodes,
+    // Nodes let go on top of other nodes, with the node they landed on
+    node_drops: Arc<Mutex<Vec<(SceneId, SceneId)>>>,
+    // File previewed by the Quick Look overlay
+    pub quick_look: crate::quick_look::QuickLookView,
+    pub thumbnail_lod: LodSystem,
+    
+    // Thumbnails the window switcher shows as previews, published by the renderer
+    pub switcher_previews: crate::switcher::SwitcherPreviews,
+    
+    // Configuration changes applied while running
+    config_events: tokio::sync::broadcast::Receiver<ConfigChangeEvent>,
+    
+    // Suggested edges shown as badges on their nodes, synced by the renderer
+    pub edge_badges: EdgeSuggestionBadges,
+    
+    // Video frames players send for media nodes, submitted by the renderer
+    pub video: crate::video::VideoFeeds,
+    
+    // Third-party integrations, and the manifest ids their permission prompts ask about
+    pub integrations: IntegrationRegistry,
+    integration_prompts: Vec<String>,
+    
+    // Theme of the desktop; the renderer applies each palette it publishes
+    pub visuals: VisualManager,
+    pub palette_updates: std::sync::mpsc::Receiver<RenderPalette>,
+    // Workspace identity frame the theme was last tinted with
+    workspace_identity: Option<TransitionFrame>,
+    
+    // Node and application icons, from the icon theme or else the asset pack
+    pub icons: IconLoader,
+    
     // Protocol extensions
     pub protocol_manager: ProtocolManager,
     
@@ -80,16 +223,84 @@ pub struct AppState {
     
     // Input
     pub seat: Seat<Self>,
+    /// Trackpad gestures waiting to be applied to the camera
+    pub trackpad_gestures: Vec<TrackpadGesture>,
+    /// Camera bookmark and minimap shortcuts waiting for the renderer, which owns the camera
+    pub camera_shortcuts: Arc<Mutex<Vec<String>>>,
+    /// Minimap last drawn, with the size of the screen it was drawn on
+    pub minimap: Option<(Minimap, (f32, f32))>,
+    /// Clicks on the minimap, in minimap pixels, waiting to move the camera
+    pub minimap_clicks: Vec<[f32; 2]>,
+    /// Group shortcuts waiting for the next frame, which reads the selection
+    pub group_shortcuts: Arc<Mutex<Vec<String>>>,
+    
+    // Animation
+    /// Animations of the camera, layouts and notifications, advanced once per frame by the renderer
+    pub animations: Arc<Mutex<AnimationSystem>>,
+    /// Nodes gliding to where a layout put them, drawn at the animated position
+    pub layout_animations: HashMap<SceneId, AnimationId>,
     
     // XWayland support
     pub xwayland_manager: crate::xwayland::XWaylandManager,
+    
+    // Remote viewing
+    pub remote_view: Option<crate::remote::RemoteViewServer>,
+    
+    // Screenshot and screen cast requests from the desktop portal
+    pub portal: crate::portal::PortalState,
+    
+    // Requests from the control API, and the events its subscribers receive
+    control: Option<std::sync::mpsc::Receiver<ControlCall>>,
+    control_events: Option<tokio::sync::broadcast::Sender<DesktopEvent>>,
+    
+    // Scripts of automation nodes, when the profile enables automation
+    scripts: Option<crate::automation::DesktopScripts>,
+    
+    // Consent prompts waiting for the local user, and answers given on their notifications
+    pub prompts: PromptQueue,
+    prompt_events: Option<tokio::sync::broadcast::Receiver<NotificationEvent>>,
+    
+    // Screen reader output, and the notification review mode it reads
+    pub accessibility: Option<AccessibilityManager>,
+    at_spi_bus: Option<Arc<AtSpiBus>>,
+    pub notification_review: NotificationReview,
+    review_events: Option<tokio::sync::broadcast::Receiver<NotificationEvent>>,
+    
+    // Status and task progress of the agents behind AI agent nodes
+    agent_events: Option<tokio::sync::broadcast::Receiver<AgentEvent>>,
+    
+    /// Runtime of the desktop services, for work the compositor thread hands off
+    runtime: Option<tokio::runtime::Handle>,
+    
+    // Warming of the workspace predicted to be switched to next
+    preloading: Option<crate::preload::WorkspacePreloading>,
+    
+    // Startup profile and what it brought up
+    pub profile: crate::profile::ProfileSettings,
+    pub startup_report: crate::profile::StartupReport,
 }
 
 impl AppState {
     pub fn new(
         display_handle: DisplayHandle,
         loop_handle: LoopHandle<'static, Self>,
+        services: Arc<ServiceRegistry>,
     ) -> Result<Self, anyhow::Error> {
+        Self::with_profile(display_handle, loop_handle, crate::profile::ProfileSettings::default(), services)
+    }
+    
+    /// Create the compositor state with the subsystems enabled by a startup profile
+    ///
+    /// `services` comes from [`crate::services::desktop_services`] for the same profile.
+    pub fn with_profile(
+        display_handle: DisplayHandle,
+        loop_handle: LoopHandle<'static, Self>,
+        profile: crate::profile::ProfileSettings,
+        services: Arc<ServiceRegistry>,
+    ) -> Result<Self, anyhow::Error> {
+        let toggles = profile.subsystems();
+        let mut startup_report = crate::profile::StartupReport::new(&profile);
+        
         // Initialize Wayland protocols
         let compositor_state = CompositorState::new::<Self>(&display_handle);
         let xdg_shell_state = XdgShellState::new::<Self>(&display_handle);
@@ -98,6 +309,13 @@ impl AppState {
         let mut seat_state = SeatState::new();
         let data_device_state = DataDeviceState::new::<Self>(&display_handle);
         
+        // Sandboxed clients may not create nested security contexts
+        let permissions = services.get::<Mutex<PermissionManager>>()?;
+        let security_context_state = SecurityContextState::new::<Self, _>(
+            &display_handle,
+            privileged_global_filter(permissions.clone(), display_handle.clone(), "wp_security_context_manager_v1"),
+        );
+        
         // Create seat
         let mut seat = seat_state.new_wl_seat(&display_handle, "seat0");
         seat.add_keyboard(Default::default(), 200, 25)?;
@@ -107,16 +325,140 @@ impl AppState {
         let space = Space::<Window>::default();
         let popups = PopupManager::default();
         
-        // Initialize graph components
-        let graph_scene = Arc::new(Mutex::new(Scene::new()));
-        let node_manager = Arc::new(Mutex::new(NodeManager::new()));
-        let interaction_manager = Arc::new(Mutex::new(InteractionManager::new()));
+        // Initialize graph components, picking up where the last session left off
+        let workspaces = services.get::<WorkspaceManager>()?;
+        let (mut scene, restored_camera) = Self::recover_session(&workspaces);
+        // Nodes flagged in the last session stay hidden from the AI service
+        // and agent nodes talk to their agents again
+        if let Some(ai) = services.try_get::<AIService>() {
+            ai.node_access().sync_scene(&scene);
+            let bound = ai.agent_nodes().bind_scene(&scene);
+            if bound > 0 {
+                log::info!("Bound {} agent nodes to their agents", bound);
+            }
+        }
+        // Clusters follow the configured rules; those the workspace saved as collapsed stay so
+        let mut clustering = ClusteringSystem::new()?;
+        let config = services.get::<ConfigManager>()?.config();
+        clustering.apply_config(&config.clustering, &scene);
+        if let Some(workspace) = workspaces.get_active_workspace() {
+            clustering.restore_collapsed_groups(&workspace.layout.collapsed_groups, &mut scene);
+        }
+        let graph_scene = Arc::new(Mutex::new(scene));
+        // Outputs get their saved position, workspace and camera back as backends map them
+        let monitors = MonitorManager::with_config(config.monitors.clone());
+        let mut node_manager = NodeManager::new();
+        // Status badges run the commands configured in badges.toml
+        if let Some(path) = StatusBadgeRegistry::default_path() {
+            match node_manager.status_badges_mut().load(&path) {
+                Ok(0) => {}
+                Ok(count) => log::info!("Loaded {} status badges from {}", count, path.display()),
+                Err(e) => log::warn!("Failed to load status badges from {}: {}", path.display(), e),
+            }
+        }
+        let node_manager = Arc::new(Mutex::new(node_manager));
+        // Level of detail of window thumbnails follows the configured node type policies
+        let config_events = services.get::<EventBus>()?.subscribe::<ConfigChangeEvent>();
+        let mut thumbnail_lod = LodSystem::new();
+        thumbnail_lod.set_appearance(config.performance.lod_appearance.clone());
+        
+        // Start with the configured theme; the palette channel yields it first
+        let mut visuals = VisualManager::new()?;
+        let theme = ConfigChangeEvent::ThemeChanged(config.appearance.theme.clone());
+        if let Err(e) = visuals.apply_config_change(&theme, &*services.get::<ConfigManager>()?) {
+            log::warn!("Failed to apply theme {}: {}", config.appearance.theme, e);
+        }
+        let palette_updates = visuals.subscribe_palette();
+        let icons = IconLoader::new(Some(&config.appearance.icon_theme)).with_asset_store(AssetStore::from_env());
         
-        // Initialize protocol manager
-        let protocol_manager = ProtocolManager::new();
+        // Shortcuts, pointer buttons and gestures follow the configuration
+        let mut interaction = InteractionManager::new();
+        interaction.configure_keys(&config);
+        interaction.configure_focus_mode(&config);
+        let mut edges = EdgeManager::new();
+        edges.set_validator(EdgeValidator::from_config(&config.graph.edge_validation));
+        edges.set_strength_decay(config.graph.strength_decay.clone());
+        let edges = Arc::new(RwLock::new(edges));
+        interaction.set_edge_manager(edges.clone());
+        // Custom context menu entries run only as the security policy allows
+        let menu_permissions = MenuCommandPermissions::new(permissions.clone());
+        interaction.context_menu_mut().extensions_mut().set_permissions(Arc::new(menu_permissions));
+        let switcher = workspaces.clone();
+        let camera_shortcuts = Arc::new(Mutex::new(Vec::new()));
+        let queued_camera_shortcuts = camera_shortcuts.clone();
+        let group_shortcuts = Arc::new(Mutex::new(Vec::new()));
+        let queued_group_shortcuts = group_shortcuts.clone();
+        interaction.on_shortcut(move |action| {
+            let offset = match action {
+                "next_workspace" => 1,
+                "previous_workspace" => -1,
+                "save_camera_bookmark" | "next_camera_bookmark" | "previous_camera_bookmark" | "toggle_minimap" => {
+                    queued_camera_shortcuts.lock().unwrap_or_else(|e| e.into_inner()).push(action.to_string());
+                    return;
+                }
+                "toggle_cluster_group" => {
+                    queued_group_shortcuts.lock().unwrap_or_else(|e| e.into_inner()).push(action.to_string());
+                    return;
+                }
+                _ => return,
+            };
+            if switcher.switch_adjacent_workspace(offset).is_none() {
+                log::debug!("No workspace to switch to for {}", action);
+            }
+        });
+        // Edge filters are saved with the workspace they were changed in
+        let filter_workspaces = workspaces.clone();
+        interaction.on_edge_filter_changed(move |filter| {
+            let Some(workspace_id) = filter_workspaces.active_workspace_id() else {
+                return;
+            };
+            if let Err(e) = filter_workspaces.set_workspace_edge_filter(&workspace_id, filter.clone()) {
+                log::warn!("Failed to save the edge filter of workspace {}: {}", workspace_id, e);
+            }
+        });
+        // Group nodes expand from their menu; other nodes collapse their cluster
+        // and can be hidden from the AI service
+        let menu = interaction.context_menu_mut();
+        menu.register_items("group".to_string(), vec![MenuItem::new(EXPAND_GROUP_ITEM, "Expand group")]);
+        for node_type in CLUSTERED_NODE_TYPES {
+            menu.register_items(node_type.to_string(), vec![
+                MenuItem::new(COLLAPSE_CLUSTER_ITEM, "Collapse cluster"),
+                MenuItem::new(TOGGLE_AI_VISIBILITY_ITEM, "Hide from AI or show again").with_icon("eye-off"),
+            ]);
+        }
+        let switcher_previews = crate::switcher::SwitcherPreviews::default();
+        interaction.window_switcher_mut().set_preview_provider(Box::new(switcher_previews.clone()));
+        let interaction_manager = Arc::new(Mutex::new(interaction));
+        
+        // Announcements reach the screen reader when the configuration turns it on
+        let accessibility = AccessibilityManager::new(AccessibilitySettings::default())
+            .and_then(|mut accessibility| {
+                accessibility.update_settings(AccessibilitySettings {
+                    screen_reader_enabled: config.accessibility.screen_reader,
+                    reduced_motion: config.accessibility.reduce_motion,
+                    ..Default::default()
+                })?;
+                Ok(accessibility)
+            })
+            .map_err(|e| log::warn!("Accessibility unavailable, notifications will not be read: {}", e))
+            .ok();
+        
+        // Initialize protocol manager; taskbars must be allowed to see other windows
+        let mut protocol_manager = ProtocolManager::new();
+        protocol_manager.foreign_toplevel.init_global(
+            &display_handle,
+            privileged_global_filter(permissions.clone(), display_handle.clone(), "zwlr_foreign_toplevel_manager_v1"),
+        );
         
         // Initialize window manager
-        let window_manager = WindowManager::new(WindowManagerConfig::default());
+        let mut window_manager_config = WindowManagerConfig::default();
+        window_manager_config.workspaces.count = toggles.workspace_count;
+        window_manager_config.workspaces.auto_create = toggles.workspace_count > 1;
+        let window_manager = WindowManager::new(window_manager_config);
+        startup_report.record(
+            format!("Workspaces ({})", toggles.workspace_count),
+            crate::profile::SubsystemState::Enabled,
+        );
         
         // Initialize XWayland manager
         let xwayland_manager = crate::xwayland::XWaylandManager::new();
@@ -131,20 +473,1586 @@ impl AppState {
             output_manager,
             seat_state,
             data_device_state,
+            security_context_state,
+            services,
+            permissions,
+            workspaces,
+            restored_camera,
             space,
+            monitors,
+            node_drag: None,
             popups,
             graph_scene,
             node_manager,
             interaction_manager,
+            edges,
             surface_to_node: HashMap::new(),
+            clustering,
+            last_project_scan: None,
+            live_thumbnails: LiveThumbnails::default(),
+            audio: crate::audio::AudioNodes::new(),
+            node_drops: Arc::new(Mutex::new(Vec::new())),
+            quick_look: crate::quick_look::QuickLookView::new(),
+            thumbnail_lod,
+            switcher_previews,
+            config_events,
+            edge_badges: EdgeSuggestionBadges::new(),
+            video: crate::video::VideoFeeds::new(),
+            integrations: IntegrationRegistry::new(),
+            integration_prompts: Vec::new(),
+            visuals,
+            palette_updates,
+            workspace_identity: None,
+            icons,
             protocol_manager,
             window_manager,
             seat,
+            trackpad_gestures: Vec::new(),
+            camera_shortcuts,
+            minimap: None,
+            minimap_clicks: Vec::new(),
+            group_shortcuts,
+            animations: Arc::new(Mutex::new(AnimationSystem::from_config(&config))),
+            layout_animations: HashMap::new(),
             xwayland_manager,
+            remote_view: None,
+            portal: Default::default(),
+            control: None,
+            control_events: None,
+            scripts: None,
+            prompts: PromptQueue::default(),
+            prompt_events: None,
+            accessibility,
+            at_spi_bus: None,
+            notification_review: NotificationReview::default(),
+            review_events: None,
+            agent_events: None,
+            runtime: None,
+            preloading: None,
+            profile,
+            startup_report,
         })
     }
 }
 
+impl AppState {
+    /// Scene and camera of the previous session, if the journal holds one
+    fn recover_session(workspaces: &WorkspaceManager) -> (Scene, Option<CameraState>) {
+        let Some(journal) = workspaces.session_journal() else {
+            return (Scene::new(), None);
+        };
+        match journal.recover() {
+            Ok(Some(recovered)) => {
+                if recovered.crashed {
+                    log::warn!("Restoring session after a crash ({} journaled changes replayed)", recovered.replayed);
+                }
+                workspaces.restore_session(recovered.state)
+            }
+            Ok(None) => (Scene::new(), None),
+            Err(e) => {
+                log::error!("Failed to restore the previous session, starting empty: {}", e);
+                (Scene::new(), None)
+            }
+        }
+    }
+    
+    /// Add a node to the graph and the workspace its routing rules pick,
+    /// journaling it
+    pub fn add_graph_node(&mut self, node: SceneNode) -> SceneId {
+        let mut scene = self.graph_scene.lock().unwrap();
+        let node_id = scene.add_node(node);
+        let Some(node) = scene.get_node(node_id).cloned() else {
+            return node_id;
+        };
+        drop(scene);
+        if let Some(journal) = self.workspaces.session_journal() {
+            if let Err(e) = journal.record(&JournalOp::NodeSet { node: Box::new(node.clone()) }) {
+                log::warn!("Failed to journal node {}: {}", node_id, e);
+            }
+        }
+        self.workspaces.add_new_node(&node);
+        self.clustering.on_node_added(&node);
+        if node.metadata.privacy.ai_invisible {
+            self.sync_ai_access();
+        }
+        self.emit_control_event("node.created", crate::control::node_summary(&node));
+        node_id
+    }
+    
+    /// Collapse the cluster of a node into a group node, or expand the cluster a group node stands for
+    ///
+    /// Which clusters are collapsed is saved with the active workspace.
+    /// Returns whether the cluster is now collapsed.
+    pub fn toggle_cluster_group(&mut self, node_id: SceneId) -> Result<bool, anyhow::Error> {
+        let mut scene = self.graph_scene.lock().unwrap();
+        let (cluster_id, collapsed) = match self.clustering.grouping().cluster_of(node_id) {
+            Some(cluster_id) => {
+                self.clustering.expand_cluster(cluster_id, &mut scene)?;
+                (cluster_id, false)
+            }
+            None => {
+                let cluster_id = self.clustering.manager().get_node_clusters(node_id).into_iter().next()
+                    .ok_or_else(|| anyhow::anyhow!("Node {} is in no cluster", node_id))?;
+                self.clustering.collapse_cluster(cluster_id, &mut scene)?;
+                (cluster_id, true)
+            }
+        };
+        drop(scene);
+        if let Err(e) = self.workspaces.set_group_collapsed(&cluster_id.to_string(), collapsed) {
+            log::warn!("Collapsed state of cluster {} not saved: {}", cluster_id, e);
+        }
+        Ok(collapsed)
+    }
+    
+    /// Hide a node from the AI service, or let the AI use it again
+    ///
+    /// The flag is journaled with the node. Returns whether the node exists.
+    pub fn set_node_ai_invisible(&mut self, node_id: SceneId, invisible: bool) -> bool {
+        let node = {
+            let mut scene = self.graph_scene.lock().unwrap();
+            let Some(node) = scene.get_node_mut(node_id) else {
+                return false;
+            };
+            node.metadata.privacy.ai_invisible = invisible;
+            node.clone()
+        };
+        if let Some(journal) = self.workspaces.session_journal() {
+            if let Err(e) = journal.record(&JournalOp::NodeSet { node: Box::new(node) }) {
+                log::warn!("Failed to journal node {}: {}", node_id, e);
+            }
+        }
+        self.sync_ai_access();
+        log::info!("Node {} is {} the AI service", node_id, if invisible { "hidden from" } else { "visible to" });
+        true
+    }
+    
+    /// Tell the AI service which nodes are hidden from it, after privacy flags changed
+    pub fn sync_ai_access(&self) {
+        if let Some(ai) = self.services.try_get::<AIService>() {
+            ai.node_access().sync_scene(&self.graph_scene.lock().unwrap());
+        }
+    }
+    
+    /// Act on a context menu item the interaction system left to the compositor
+    pub fn handle_node_menu_item(&mut self, node_id: SceneId, item_id: &str) {
+        match item_id {
+            EXPAND_GROUP_ITEM | COLLAPSE_CLUSTER_ITEM => {
+                if let Err(e) = self.toggle_cluster_group(node_id) {
+                    log::warn!("Failed to {} of node {}: {}", item_id.replace('_', " "), node_id, e);
+                }
+            }
+            TOGGLE_AI_VISIBILITY_ITEM => {
+                let invisible = self.graph_scene.lock().unwrap().get_node(node_id)
+                    .is_some_and(|node| !node.metadata.privacy.ai_invisible);
+                self.set_node_ai_invisible(node_id, invisible);
+            }
+            action if crate::audio::AudioNodes::is_action(action) => {
+                let Some(runtime) = self.runtime.clone() else {
+                    return;
+                };
+                match self.audio.run_action(&runtime, node_id, action) {
+                    Ok(NodeActionResult::Success { message: Some(message) }) => log::info!("{}", message),
+                    Ok(_) => {}
+                    Err(e) => log::warn!("Audio action {} on node {} failed: {}", action, node_id, e),
+                }
+            }
+            _ => log::debug!("Unhandled menu item {} on node {}", item_id, node_id),
+        }
+    }
+    
+    /// Glide nodes a layout moved from where they were, see [`Self::layout_animations`]
+    ///
+    /// The scene keeps the laid out positions; only drawing follows the animation.
+    fn animate_layout(&mut self, moves: Vec<(SceneId, Point3<f32>, Point3<f32>)>) {
+        let mut animations = self.animations.lock().unwrap_or_else(|e| e.into_inner());
+        for (id, from, to) in moves {
+            // A node still moving from the previous layout turns towards the new position
+            let retargeted = self.layout_animations.get(&id).is_some_and(|animation| animations.retarget(*animation, to));
+            if !retargeted {
+                self.layout_animations.insert(id, animations.spring(LAYOUT_ANIMATION_OWNER, from, to));
+            }
+        }
+    }
+    
+    /// Move the nodes of a snapshot to where their layout animations are
+    ///
+    /// Call after the animation system was advanced for the frame; finished
+    /// animations are forgotten.
+    pub fn apply_layout_animations(&mut self, snapshot: &mut SceneSnapshot) {
+        if self.layout_animations.is_empty() {
+            return;
+        }
+        let animations = self.animations.lock().unwrap_or_else(|e| e.into_inner());
+        for node in snapshot.nodes.iter_mut() {
+            if let Some(position) = self.layout_animations.get(&node.id).and_then(|id| animations.value::<Point3<f32>>(*id)) {
+                node.position = position;
+            }
+        }
+        self.layout_animations.retain(|_, id| animations.is_running(*id));
+    }
+    
+    /// Toggle the groups of the selected node from queued shortcuts, and advance group animations
+    pub fn update_groups(&mut self, delta_time: f32) {
+        let shortcuts = std::mem::take(&mut *self.group_shortcuts.lock().unwrap_or_else(|e| e.into_inner()));
+        if !shortcuts.is_empty() {
+            let selected = self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner())
+                .selection()
+                .get_selection()
+                .first()
+                .copied();
+            match selected {
+                Some(node_id) => {
+                    if let Err(e) = self.toggle_cluster_group(node_id) {
+                        log::debug!("Nothing to collapse or expand: {}", e);
+                    }
+                }
+                None => log::debug!("Select a node to collapse or expand its cluster"),
+            }
+        }
+        self.clustering.update_groups(delta_time, &mut self.graph_scene.lock().unwrap());
+    }
+    
+    /// Remove a node from the graph and its workspaces, journaling it
+    ///
+    /// Returns whether the node was in the graph.
+    pub fn remove_graph_node(&mut self, node_id: SceneId) -> bool {
+        if self.graph_scene.lock().unwrap().remove_node(node_id).is_none() {
+            return false;
+        }
+        if let Some(journal) = self.workspaces.session_journal() {
+            if let Err(e) = journal.record(&JournalOp::NodeRemoved { id: node_id }) {
+                log::warn!("Failed to journal removal of node {}: {}", node_id, e);
+            }
+        }
+        self.workspaces.remove_node(node_id);
+        self.emit_control_event("node.deleted", serde_json::json!({ "id": node_id }));
+        true
+    }
+    
+    /// Checkpoint the session when the workspace auto-save interval has passed
+    pub fn auto_save_session(&self, camera: CameraState) {
+        let scene = self.graph_scene.lock().unwrap();
+        if let Err(e) = self.workspaces.auto_save_session(&scene, Some(camera)) {
+            log::warn!("Failed to checkpoint the session: {}", e);
+        }
+    }
+    
+    /// Register an output with the monitor manager and map it where the layout puts it
+    ///
+    /// The first output becomes the primary one, which shows the graph
+    /// through the interactive camera.
+    pub fn map_output(&mut self, output: &Output) {
+        let primary = self.monitors.get_primary().is_none();
+        if let Err(e) = self.monitors.add_monitor(crate::output::monitor_for_output(output, primary)) {
+            log::warn!("Output {} is not laid out: {}", output.name(), e);
+        }
+        let position = self.monitors.get_monitor(&output.name()).map_or((0, 0), |monitor| monitor.position);
+        self.space.map_output(output, position);
+    }
+    
+    /// What an output shows: the primary output follows the interactive
+    /// camera, others look through their own; an output pinned to a
+    /// workspace shows only that workspace's nodes
+    pub fn output_view(&self, output: &str, camera: &Camera) -> OutputView {
+        let Some(viewport) = self.monitors.get_viewport(output) else {
+            return OutputView::interactive(camera);
+        };
+        let primary = self.monitors.get_primary().is_none_or(|monitor| monitor.id == output);
+        let camera = if primary { camera.clone() } else { OutputView::camera_of(&viewport, camera) };
+        let nodes = viewport.workspace.as_deref()
+            .and_then(|workspace_id| self.workspaces.get_workspace(workspace_id))
+            .map(|workspace| workspace.nodes.into_iter().collect());
+        OutputView { camera, nodes }
+    }
+    
+    /// Start dragging the node of the window under the pointer
+    ///
+    /// Returns false when there is no node there.
+    pub fn begin_node_drag(&mut self, location: Point<f64, Logical>) -> bool {
+        let node_id = self.space.element_under(location)
+            .and_then(|(window, _)| window.toplevel())
+            .and_then(|toplevel| self.surface_to_node.get(toplevel.wl_surface()).copied());
+        let Some(node_id) = node_id else {
+            return false;
+        };
+        self.node_drag = Some((node_id, (location.x as i32, location.y as i32)));
+        true
+    }
+    
+    /// Drop the dragged node
+    ///
+    /// A node dropped on another output lands under the pointer as that
+    /// output's camera sees it, and moves to the workspace the output shows.
+    pub fn end_node_drag(&mut self, location: Point<f64, Logical>) -> bool {
+        let Some((node_id, from)) = self.node_drag.take() else {
+            return false;
+        };
+        let active = self.workspaces.get_active_workspace().map(|workspace| workspace.id);
+        let to = (location.x as i32, location.y as i32);
+        let Some(crossing) = self.monitors.drag_node(from, to, active.as_deref()) else {
+            return true;
+        };
+        
+        let node = {
+            let mut scene = self.graph_scene.lock().unwrap();
+            let Some(node) = scene.get_node_mut(node_id) else {
+                return true;
+            };
+            node.position = crossing.position;
+            node.clone()
+        };
+        if let Some(journal) = self.workspaces.session_journal() {
+            if let Err(e) = journal.record(&JournalOp::NodeSet { node: Box::new(node) }) {
+                log::warn!("Failed to journal node {}: {}", node_id, e);
+            }
+        }
+        if let Some(workspace_id) = crossing.to_workspace.as_deref().filter(|_| crossing.changes_workspace()) {
+            if let Err(e) = self.workspaces.move_node(node_id, workspace_id) {
+                log::warn!("Node {} stays in its workspace: {}", node_id, e);
+            }
+        }
+        log::debug!("Moved node {} from output {} to {}", node_id, crossing.from_monitor, crossing.to_monitor);
+        true
+    }
+    
+    /// Save the session for the next launch and mark it as closed cleanly
+    pub fn close_session(&self, camera: CameraState) {
+        let scene = self.graph_scene.lock().unwrap();
+        if let Err(e) = self.workspaces.close_session(&scene, Some(camera)) {
+            log::warn!("Failed to save the session: {}", e);
+        }
+    }
+    
+    /// Start streaming the desktop to remote viewers
+    pub fn start_remote_view(&mut self, config: crate::remote::RemoteViewConfig) -> Result<(), crate::CompositorError> {
+        self.remote_view = Some(crate::remote::RemoteViewServer::start(config)?);
+        Ok(())
+    }
+    
+    /// Stop streaming and disconnect remote viewers
+    pub fn stop_remote_view(&mut self) {
+        if let Some(mut server) = self.remote_view.take() {
+            server.shutdown();
+        }
+    }
+    
+    /// Handle remote view events and inject consented remote input
+    pub fn dispatch_remote_view(&mut self) {
+        let Some(server) = self.remote_view.as_mut() else {
+            return;
+        };
+        
+        let events = server.poll_events();
+        let input = server.drain_input();
+        for event in events {
+            match event {
+                crate::remote::RemoteViewEvent::ConsentRequested { viewer_id, peer } => {
+                    // Input stays blocked until the prompt is answered
+                    log::warn!("Remote viewer {} ({}) requests input control", viewer_id, peer);
+                    self.ask(
+                        PromptSubject::RemoteInput { viewer_id },
+                        "Remote control requested".to_string(),
+                        format!("Remote viewer {} ({}) wants to control the pointer and keyboard", viewer_id, peer),
+                        false,
+                    );
+                }
+                crate::remote::RemoteViewEvent::ViewerDisconnected { viewer_id } => {
+                    log::info!("Remote view: viewer {} disconnected", viewer_id);
+                    if let Some(prompt) = self.prompts.withdraw(PromptSubject::RemoteInput { viewer_id }) {
+                        self.dismiss_notification(prompt.notification_id);
+                    }
+                }
+                event => log::info!("Remote view: {:?}", event),
+            }
+        }
+        
+        for event in input {
+            crate::input::process_remote_input(self, event);
+        }
+    }
+}
+
+impl AppState {
+    /// Hand background work, such as showing notifications, to the services' runtime
+    ///
+    /// Until this is called prompts are only logged and cannot be answered
+    /// through notification buttons.
+    pub fn attach_runtime(&mut self, runtime: tokio::runtime::Handle) {
+        if let Ok(notifications) = self.services.get::<NotificationManager>() {
+            self.prompt_events = Some(notifications.subscribe());
+            self.review_events = Some(notifications.subscribe());
+        }
+        if let Some(ai) = self.services.try_get::<AIService>() {
+            self.agent_events = ai.subscribe_agent_events();
+        }
+        self.runtime = Some(runtime);
+    }
+    
+    /// Minimap pixels under a point of the screen, if the minimap is shown there
+    pub fn minimap_point(&self, point: (f32, f32)) -> Option<[f32; 2]> {
+        let (minimap, viewport) = self.minimap.as_ref()?;
+        minimap.from_screen(point, *viewport)
+    }
+    
+    /// Preload the workspace the AI service predicts to be switched to next
+    ///
+    /// Registers the compositor's thumbnail and snapshot warmers with the
+    /// workspace manager; [`Self::dispatch_preloading`] then preloads on an
+    /// idle tick.
+    pub fn attach_preloading(&mut self, runtime: tokio::runtime::Handle) -> Result<(), anyhow::Error> {
+        use crate::preload::{SnapshotWarmer, ThumbnailWarmer, WorkspacePreloading};
+        
+        let generator = Arc::new(ThumbnailGenerator::new()?);
+        let preloading = WorkspacePreloading::new();
+        self.workspaces.add_preload_warmer(Arc::new(ThumbnailWarmer::new(self.graph_scene.clone(), generator, runtime)));
+        self.workspaces.add_preload_warmer(Arc::new(SnapshotWarmer::new(self.graph_scene.clone(), preloading.snapshots.clone())));
+        self.preloading = Some(preloading);
+        Ok(())
+    }
+    
+    /// Capture the windows warmed for a switch again, and preload the
+    /// predicted workspaces when the idle tick is due
+    pub fn dispatch_preloading(&mut self) {
+        let Some(preloading) = self.preloading.as_mut() else {
+            return;
+        };
+        for node_id in preloading.snapshots.lock().unwrap_or_else(|e| e.into_inner()).drain(..) {
+            self.live_thumbnails.damage(node_id);
+        }
+        if !preloading.tick_due(std::time::Instant::now()) {
+            return;
+        }
+        
+        let (Some(ai), Some(active)) = (self.services.try_get::<AIService>(), self.workspaces.get_active_workspace()) else {
+            return;
+        };
+        let candidates: Vec<PreloadCandidate> = ai.predict_next_workspaces(&active.id)
+            .into_iter()
+            .map(|prediction| PreloadCandidate {
+                workspace_id: prediction.workspace_id,
+                confidence: prediction.confidence,
+            })
+            .collect();
+        let preloaded = self.workspaces.preload_predicted(&candidates);
+        if preloaded > 0 {
+            log::debug!("Preloaded {} predicted workspaces", preloaded);
+        }
+    }
+    
+    /// Decay unused relationships once the configured interval has passed,
+    /// fading their edges and removing those that were archived
+    pub fn dispatch_edge_decay(&mut self) {
+        let removed = {
+            let mut edges = self.edges.write().unwrap_or_else(|e| e.into_inner());
+            let Some(report) = edges.decay_strengths(chrono::Utc::now()) else {
+                return;
+            };
+            if report.decayed > 0 {
+                log::debug!("Decayed {} unused relationships", report.decayed);
+            }
+            let mut scene = self.graph_scene.lock().unwrap();
+            edges.sync_decay_to_scene(&report, &mut scene)
+        };
+        if let Some(journal) = self.workspaces.session_journal() {
+            for edge in &removed {
+                if let Err(e) = journal.record(&JournalOp::EdgeRemoved { id: edge.id }) {
+                    log::warn!("Failed to journal archived edge {}: {}", edge.id, e);
+                }
+            }
+        }
+    }
+    
+    /// Show PipeWire devices and streams as nodes, reading PipeWire on the runtime
+    ///
+    /// Streams let go on top of a device node move to that device.
+    pub fn attach_audio(&mut self, runtime: tokio::runtime::Handle) {
+        self.audio.start(&runtime);
+        let drops = self.node_drops.clone();
+        self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner()).on_node_dropped(move |dragged, target| {
+            drops.lock().unwrap_or_else(|e| e.into_inner()).push((dragged, target));
+        });
+    }
+    
+    /// Load Quick Look previews on the runtime, with poster frames for videos
+    pub fn attach_quick_look(&mut self, runtime: tokio::runtime::Handle) {
+        let thumbnails = ThumbnailGenerator::new()
+            .map_err(|e| log::warn!("Quick Look shows videos without a poster frame: {}", e))
+            .ok();
+        self.quick_look.start(runtime, thumbnails.map(Arc::new));
+    }
+    
+    /// Take Quick Look previews loaded since the last frame
+    pub fn dispatch_quick_look(&mut self) {
+        self.quick_look.update();
+    }
+    
+    /// Apply the newest PipeWire snapshot, offer audio actions in an open
+    /// audio node menu and reroute streams dropped on devices
+    pub fn dispatch_audio(&mut self) {
+        self.audio.update(&mut self.graph_scene.lock().unwrap());
+        
+        {
+            let mut interaction = self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner());
+            let unextended = interaction.context_menu().get_active()
+                .filter(|menu| !menu.items.iter().any(|item| crate::audio::AudioNodes::is_action(&item.id)))
+                .map(|menu| menu.node_id);
+            if let Some(node_id) = unextended {
+                let items = self.audio.menu_items(node_id);
+                if !items.is_empty() {
+                    interaction.context_menu_mut().extend_active(node_id, items);
+                }
+            }
+        }
+        
+        let drops = std::mem::take(&mut *self.node_drops.lock().unwrap_or_else(|e| e.into_inner()));
+        let Some(runtime) = self.runtime.clone() else {
+            return;
+        };
+        for (dragged, target) in drops {
+            let mut scene = self.graph_scene.lock().unwrap();
+            if let Err(e) = self.audio.drop_node(&runtime, &mut scene, dragged, target) {
+                log::warn!("Failed to move audio stream {} to node {}: {}", dragged, target, e);
+            }
+        }
+    }
+    
+    /// Seed project clusters for the git repositories under the configured
+    /// roots, rescanning every [`PROJECT_SCAN_INTERVAL`] for new repositories,
+    /// fetches and commits
+    pub fn dispatch_projects(&mut self) {
+        if self.last_project_scan.is_some_and(|scanned| scanned.elapsed() < PROJECT_SCAN_INTERVAL) {
+            return;
+        }
+        self.last_project_scan = Some(std::time::Instant::now());
+        let mut scene = self.graph_scene.lock().unwrap();
+        match self.clustering.detect_projects(&mut scene) {
+            Ok(clusters) if !clusters.is_empty() => log::debug!("Updated {} project clusters", clusters.len()),
+            Ok(_) => {}
+            Err(e) => log::warn!("Failed to detect projects: {}", e),
+        }
+    }
+    
+    /// Frame of the running workspace switch transition, for the renderer to draw
+    pub fn switch_overlay(&self) -> Option<SwitchOverlay> {
+        self.workspace_identity
+            .filter(|frame| frame.progress < 1.0)
+            .map(|frame| SwitchOverlay {
+                accent_color: frame.accent_color,
+                emblem_opacity: frame.emblem_opacity,
+                emblem_scale: frame.emblem_scale,
+                sweep_position: frame.sweep_position,
+            })
+    }
+    
+    /// Tint the theme and cluster boundaries with the active workspace's
+    /// accent, following the transition of the last workspace switch
+    pub fn dispatch_workspace_identity(&mut self) {
+        let frame = self.workspaces.current_identity_frame();
+        if frame == self.workspace_identity {
+            return;
+        }
+        self.workspace_identity = frame;
+        let tint = frame.zip(self.workspaces.get_active_workspace()).map(|(frame, workspace)| {
+            let [r, g, b, a] = frame.accent_color;
+            WorkspaceTint {
+                background: workspace.identity.tint_background,
+                cluster_boundaries: workspace.identity.tint_cluster_boundaries,
+                panels: workspace.identity.tint_panels,
+                ..WorkspaceTint::new(Color::rgba(r, g, b, a), frame.tint_strength)
+            }
+        });
+        self.clustering.set_workspace_tint(tint);
+        self.visuals.theme_system_mut().set_workspace_tint(tint);
+    }
+    
+    /// Follow agent events on the agent nodes, adding the artifacts of
+    /// finished tasks to the graph and answers to their conversations
+    pub fn dispatch_agent_events(&mut self) {
+        let Some(events) = self.agent_events.as_mut() else {
+            return;
+        };
+        let mut received = Vec::new();
+        loop {
+            match events.try_recv() {
+                Ok(event) => received.push(event),
+                Err(tokio::sync::broadcast::error::TryRecvError::Lagged(missed)) => {
+                    log::debug!("Agent nodes missed {} agent events", missed);
+                }
+                Err(_) => break,
+            }
+        }
+        let Some(ai) = self.services.try_get::<AIService>() else {
+            return;
+        };
+        
+        for event in received {
+            let spawned = {
+                let mut nodes = self.node_manager.lock().unwrap_or_else(|e| e.into_inner());
+                let mut scene = self.graph_scene.lock().unwrap();
+                ai.agent_nodes().handle_event(&event, &mut nodes, &mut scene)
+            };
+            for node_id in spawned {
+                self.track_spawned_node(node_id);
+            }
+            
+            if let (AgentEvent::TaskFinished(result), Some(runtime), Some(system)) =
+                (event, self.runtime.as_ref(), ai.agent_system())
+            {
+                let binding = ai.agent_nodes().clone();
+                runtime.spawn(async move {
+                    if let Err(e) = binding.deliver_reply(&system, &result).await {
+                        log::warn!("Could not deliver the answer of task {}: {}", result.task_id, e);
+                    }
+                });
+            }
+        }
+    }
+    
+    /// Journal a node something else added to the scene, with its edges, and
+    /// give it to the workspace its routing rules pick
+    fn track_spawned_node(&mut self, node_id: SceneId) {
+        let (node, edges) = {
+            let scene = self.graph_scene.lock().unwrap();
+            let Some(node) = scene.get_node(node_id).cloned() else {
+                return;
+            };
+            let edges: Vec<SceneEdge> = scene.get_connected_edges(node_id).into_iter().cloned().collect();
+            (node, edges)
+        };
+        if let Some(journal) = self.workspaces.session_journal() {
+            let ops = std::iter::once(JournalOp::NodeSet { node: Box::new(node.clone()) })
+                .chain(edges.into_iter().map(|edge| JournalOp::EdgeSet { edge }));
+            for op in ops {
+                if let Err(e) = journal.record(&op) {
+                    log::warn!("Failed to journal node {}: {}", node_id, e);
+                }
+            }
+        }
+        self.workspaces.add_new_node(&node);
+        self.clustering.on_node_added(&node);
+        self.emit_control_event("node.created", crate::control::node_summary(&node));
+    }
+    
+    /// Queue a consent prompt and show its notification
+    pub fn ask(&mut self, subject: PromptSubject, title: String, body: String, offers_always: bool) {
+        if let Some(prompt) = self.prompts.ask(subject, title, body, offers_always) {
+            let notification = prompt.notification();
+            self.show_notification(notification);
+        }
+    }
+    
+    /// Answer the current consent prompt from the keyboard
+    pub fn handle_prompt_key(&mut self, key: PromptKey) {
+        if let Some(answer) = self.prompts.handle_key(key) {
+            self.apply_prompt_answer(answer);
+        }
+    }
+    
+    /// Take answers given through the buttons of prompt notifications
+    pub fn dispatch_prompts(&mut self) {
+        let Some(events) = self.prompt_events.as_mut() else {
+            return;
+        };
+        let mut answers = Vec::new();
+        loop {
+            let answer = match events.try_recv() {
+                Ok(NotificationEvent::ActionTriggered { notification_id, action_id }) => {
+                    self.prompts.handle_action(notification_id, &action_id)
+                }
+                Ok(NotificationEvent::Dismissed(notification_id)) => self.prompts.handle_dismissed(notification_id),
+                Ok(_) | Err(tokio::sync::broadcast::error::TryRecvError::Lagged(_)) => None,
+                Err(_) => break,
+            };
+            answers.extend(answer);
+        }
+        for answer in answers {
+            self.apply_prompt_answer(answer);
+        }
+    }
+    
+    fn apply_prompt_answer(&mut self, answer: PromptAnswer) {
+        self.dismiss_notification(answer.notification_id);
+        match answer.subject {
+            PromptSubject::RemoteInput { viewer_id } => {
+                if let Some(server) = self.remote_view.as_mut() {
+                    server.respond_to_consent(viewer_id, answer.allowed);
+                }
+            }
+            PromptSubject::Permission { request_id } => {
+                let remember = if answer.always { crate::security::Remember::Always } else { crate::security::Remember::Once };
+                if let Err(e) = self.respond_to_permission(request_id, answer.allowed, remember) {
+                    log::warn!("Failed to answer permission request {}: {}", request_id, e);
+                }
+            }
+            PromptSubject::Integration { index } => {
+                let Some(manifest_id) = self.integration_prompts.get(index as usize).cloned() else {
+                    return;
+                };
+                if self.integrations.answer(&manifest_id, answer.allowed) {
+                    if let Some(path) = IntegrationRegistry::default_grants_path() {
+                        if let Err(e) = self.integrations.save_grants(&path) {
+                            log::warn!("Answer for integration {} not saved: {}", manifest_id, e);
+                        }
+                    }
+                    self.apply_integrations();
+                }
+            }
+        }
+    }
+    
+    /// Show a notification from the compositor thread
+    pub fn show_notification(&self, notification: Notification) {
+        let (Some(runtime), Ok(notifications)) = (&self.runtime, self.services.get::<NotificationManager>()) else {
+            log::info!("{}: {}", notification.title, notification.body);
+            return;
+        };
+        runtime.spawn(async move {
+            if let Err(e) = notifications.notify(notification).await {
+                log::warn!("Could not show notification: {}", e);
+            }
+        });
+    }
+    
+    fn dismiss_notification(&self, id: uuid::Uuid) {
+        let (Some(runtime), Ok(notifications)) = (&self.runtime, self.services.get::<NotificationManager>()) else {
+            return;
+        };
+        runtime.spawn(async move {
+            let _ = notifications.dismiss(id).await;
+        });
+    }
+    
+    /// Turn notification review mode on with the notifications showing, or off
+    pub fn toggle_notification_review(&mut self) {
+        let utterance = if self.notification_review.is_active() {
+            self.notification_review.exit()
+        } else {
+            let active = match (&self.runtime, self.services.get::<NotificationManager>()) {
+                (Some(runtime), Ok(notifications)) => runtime.block_on(notifications.get_active()),
+                _ => Vec::new(),
+            };
+            self.notification_review.enter(active)
+        };
+        self.announce(utterance);
+    }
+    
+    /// Run a review mode command from the keyboard
+    pub fn handle_review_command(&mut self, command: ReviewCommand) {
+        let step = self.notification_review.command(command);
+        if let Some(utterance) = step.utterance {
+            self.announce(utterance);
+        }
+        let Some(effect) = step.effect else {
+            return;
+        };
+        match effect {
+            ReviewEffect::Dismiss(id) => self.dismiss_notification(id),
+            ReviewEffect::InvokeAction { notification_id, action_id } => {
+                if let Ok(notifications) = self.services.get::<NotificationManager>() {
+                    notifications.trigger_action(notification_id, &action_id);
+                }
+            }
+            ReviewEffect::Open(id) => {
+                if let Ok(notifications) = self.services.get::<NotificationManager>() {
+                    notifications.open(id);
+                }
+            }
+        }
+    }
+    
+    /// Keep the review queue in step with notifications, announcing arrivals
+    pub fn dispatch_notification_review(&mut self) {
+        let Some(events) = self.review_events.as_mut() else {
+            return;
+        };
+        let mut utterances = Vec::new();
+        loop {
+            match events.try_recv() {
+                Ok(event) => utterances.extend(self.notification_review.handle_event(&event)),
+                Err(tokio::sync::broadcast::error::TryRecvError::Lagged(_)) => continue,
+                Err(_) => break,
+            }
+        }
+        for utterance in utterances {
+            self.announce(utterance);
+        }
+    }
+    
+    /// Give a key to keyboard graph navigation and connect mode
+    ///
+    /// Focus starts on the selected node. Returns whether the key was used:
+    /// it moved the focus, or connect mode was or is active.
+    pub fn handle_graph_key(&mut self, key: Key, modifiers: Vec<Modifier>) -> bool {
+        let Some(accessibility) = self.accessibility.as_mut() else {
+            return false;
+        };
+        let old_focus = accessibility.keyboard_nav.get_focus();
+        if old_focus.is_none() {
+            let selected = self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner())
+                .selection()
+                .get_primary_selection();
+            if let Err(e) = accessibility.set_focus(selected) {
+                log::warn!("Could not focus the selected node: {}", e);
+            }
+        }
+        let was_connecting = accessibility.edge_creator.is_active();
+        let mut scene = self.graph_scene.lock().unwrap_or_else(|e| e.into_inner());
+        let mut edges = self.edges.write().unwrap_or_else(|e| e.into_inner());
+        let context = EdgeContext { scene: &scene, edges: &mut edges };
+        match accessibility.handle_key_press(key, modifiers, context) {
+            // Connect mode added it to the edge manager
+            Ok(Some(created)) => {
+                scene.add_edge(created.scene_edge);
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("Keyboard navigation failed: {}", e),
+        }
+        was_connecting
+            || accessibility.edge_creator.is_active()
+            || accessibility.keyboard_nav.get_focus() != old_focus
+    }
+    
+    /// Read review mode text through the screen reader and braille display
+    fn announce(&mut self, utterance: ReviewUtterance) {
+        let Some(accessibility) = self.accessibility.as_mut() else {
+            log::info!("{}", utterance.speech);
+            return;
+        };
+        if let Err(e) = accessibility.announce(&utterance.speech, utterance.braille, utterance.urgent) {
+            log::warn!("Could not announce notification: {}", e);
+        }
+    }
+    
+    /// Move the screen reader on to its next announcement when the last one
+    /// was spoken, and send pending AT-SPI events
+    pub fn dispatch_accessibility(&mut self) {
+        let Some(accessibility) = self.accessibility.as_mut() else {
+            return;
+        };
+        if let Err(e) = accessibility.poll_speech() {
+            log::warn!("Screen reader speech failed: {}", e);
+        }
+        let signals = accessibility.take_at_spi_signals();
+        let (Some(bus), Some(runtime)) = (&self.at_spi_bus, &self.runtime) else {
+            return;
+        };
+        if signals.is_empty() {
+            return;
+        }
+        let bus = bus.clone();
+        runtime.spawn(async move {
+            for signal in signals {
+                if let Err(e) = bus.emit(&signal).await {
+                    log::debug!("Could not send AT-SPI event {}: {}", signal.member, e);
+                }
+            }
+        });
+    }
+    
+    /// Send AT-SPI events to screen readers over a connected accessibility bus
+    pub fn attach_at_spi(&mut self, bus: AtSpiBus) {
+        let Some(accessibility) = self.accessibility.as_mut() else {
+            return;
+        };
+        if let Err(e) = accessibility.enable_at_spi() {
+            log::warn!("AT-SPI unavailable: {}", e);
+            return;
+        }
+        self.at_spi_bus = Some(Arc::new(bus));
+        self.export_accessibility_tree();
+    }
+    
+    /// Describe the scene's nodes to assistive technology and export the
+    /// tree of workspaces, clusters and nodes; AT-SPI events go out for
+    /// whatever changed since the last export
+    pub fn export_accessibility_tree(&mut self) {
+        let Some(accessibility) = self.accessibility.as_mut() else {
+            return;
+        };
+        let scene = self.graph_scene.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = accessibility.sync_scene(&scene) {
+            log::warn!("Could not update node accessibility: {}", e);
+        }
+        drop(scene);
+        let mut workspaces: Vec<_> = self.workspaces.list_workspaces()
+            .into_iter()
+            .filter_map(|info| self.workspaces.get_workspace(&info.id))
+            .collect();
+        workspaces.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        let active_workspace = self.workspaces.active_workspace_id();
+        let clusters = self.clustering.manager().clusters();
+        if let Err(e) = accessibility.export_tree(&workspaces, active_workspace.as_deref(), &clusters) {
+            log::warn!("Could not export the accessible tree: {}", e);
+        }
+    }
+}
+
+impl AppState {
+    /// Answer a permission prompt
+    pub fn respond_to_permission(
+        &mut self,
+        request_id: u64,
+        allowed: bool,
+        remember: crate::security::Remember,
+    ) -> Result<(), crate::CompositorError> {
+        self.permissions.lock().unwrap_or_else(|e| e.into_inner()).respond(request_id, allowed, remember)
+    }
+    
+    /// Apply configuration edits that take effect without a restart
+    pub fn dispatch_config_changes(&mut self) {
+        loop {
+            let event = match self.config_events.try_recv() {
+                Ok(event) => event,
+                Err(tokio::sync::broadcast::error::TryRecvError::Lagged(_)) => ConfigChangeEvent::ConfigReloaded,
+                Err(_) => break,
+            };
+            let Ok(config_manager) = self.services.get::<ConfigManager>() else {
+                continue;
+            };
+            // Theme switches and theme file edits reach the renderer as a new palette
+            if let Err(e) = self.visuals.apply_config_change(&event, &config_manager) {
+                log::warn!("Failed to apply theme change: {}", e);
+            }
+            // Cached icons reload from the new icon theme in the background
+            self.icons.apply_config_change(&event, &config_manager);
+            match event {
+                // Previewed while the settings are edited
+                ConfigChangeEvent::LodAppearanceChanged => {
+                    self.thumbnail_lod.set_appearance(config_manager.config().performance.lod_appearance);
+                }
+                ConfigChangeEvent::ConfigReloaded => {
+                    let config = config_manager.config();
+                    self.thumbnail_lod.set_appearance(config.performance.lod_appearance.clone());
+                    {
+                        let mut interaction = self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner());
+                        interaction.configure_keys(&config);
+                        interaction.configure_focus_mode(&config);
+                    }
+                    let validator = EdgeValidator::from_config(&config.graph.edge_validation);
+                    let mut edges = self.edges.write().unwrap_or_else(|e| e.into_inner());
+                    edges.set_validator(validator);
+                    self.animations.lock().unwrap_or_else(|e| e.into_inner()).configure(&config);
+                    edges.set_strength_decay(config.graph.strength_decay);
+                    drop(edges);
+                    // Rules re-evaluate and new project roots are scanned on the next frame
+                    self.clustering.apply_config(&config.clustering, &self.graph_scene.lock().unwrap());
+                    self.last_project_scan = None;
+                }
+                ConfigChangeEvent::ValueChanged(key) if key.starts_with("appearance.animations") || key.starts_with("accessibility") => {
+                    self.animations.lock().unwrap_or_else(|e| e.into_inner()).configure(&config_manager.config());
+                }
+                // Outputs move to their new places
+                ConfigChangeEvent::MonitorsChanged => {
+                    self.monitors.apply_config(config_manager.config().monitors);
+                    let outputs: Vec<Output> = self.space.outputs().cloned().collect();
+                    for output in outputs {
+                        let position = self.monitors.get_monitor(&output.name()).map_or((0, 0), |monitor| monitor.position);
+                        self.space.map_output(&output, position);
+                    }
+                }
+                // Handled by the visual manager above
+                ConfigChangeEvent::ThemeChanged(_) => {}
+                _ => {}
+            }
+        }
+    }
+    
+    /// Handle permission prompts and answers
+    pub fn dispatch_permissions(&mut self) {
+        let events = self.permissions.lock().unwrap_or_else(|e| e.into_inner()).poll_events();
+        for event in events {
+            match event {
+                PermissionEvent::PromptRequested(request) => {
+                    // The client's access stays pending until the prompt is answered
+                    log::info!("Permission request {}: {}", request.id, request.prompt());
+                    self.ask(
+                        PromptSubject::Permission { request_id: request.id },
+                        "Permission requested".to_string(),
+                        request.prompt(),
+                        true,
+                    );
+                }
+                PermissionEvent::PromptResolved { request, allowed } => {
+                    log::info!(
+                        "Permission request {} {}: {}",
+                        request.id,
+                        if allowed { "allowed" } else { "denied" },
+                        request.prompt()
+                    );
+                    // Answered without the prompt, e.g. by a caller of respond_to_permission
+                    if let Some(prompt) = self.prompts.withdraw(PromptSubject::Permission { request_id: request.id }) {
+                        self.dismiss_notification(prompt.notification_id);
+                    }
+                    // A context menu entry may have been waiting on the answer
+                    let interaction = self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner());
+                    if let Some(execution) = interaction.context_menu().extensions().permission_resolved(request.id, allowed) {
+                        log::debug!("Context menu entry after permission request {}: {:?}", request.id, execution);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl AppState {
+    /// Match the AI's edge suggestions to the nodes of the scene
+    pub fn sync_edge_badges(&mut self) {
+        let Some(ai) = self.services.try_get::<AIService>() else {
+            return;
+        };
+        let scene = self.graph_scene.lock().unwrap_or_else(|e| e.into_inner());
+        self.edge_badges.sync(ai.suggestions(), &scene);
+    }
+    
+    /// Start the status badge commands whose interval has passed
+    pub fn refresh_status_badges(&self) {
+        let started = self.node_manager.lock().unwrap_or_else(|e| e.into_inner()).refresh_status_badges();
+        if started > 0 {
+            log::debug!("Refreshing {} status badges", started);
+        }
+    }
+    
+    /// Visual data of a node, with its status or suggested edge badge applied
+    pub fn node_visual_data(&self, node_id: SceneId) -> Option<NodeVisualData> {
+        let mut visual = self.node_manager.lock().unwrap_or_else(|e| e.into_inner()).visual_data(node_id)?;
+        self.edge_badges.apply(node_id, &mut visual);
+        if let Some(ai) = self.services.try_get::<AIService>() {
+            ai.agent_nodes().apply(node_id, &mut visual);
+        }
+        Some(visual)
+    }
+    
+    /// Accept or reject an edge suggested on a node's badge
+    ///
+    /// Rejected pairs are never suggested again. Returns `None` if the
+    /// suggestion is no longer pending.
+    pub fn respond_edge_suggestion(&mut self, suggestion_id: &str, accept: bool) -> Option<EdgeSuggestionResponse> {
+        let ai = self.services.try_get::<AIService>()?;
+        let response = {
+            let mut scene = self.graph_scene.lock().unwrap_or_else(|e| e.into_inner());
+            self.edge_badges.respond(suggestion_id, accept, ai.suggestions(), &mut scene)?
+        };
+        if let EdgeSuggestionResponse::Rejected { source, target } = &response {
+            ai.dismiss_relationship(source, target);
+        }
+        Some(response)
+    }
+}
+
+impl AppState {
+    /// Battery and power profile of the system, for the renderer's power-saving path
+    pub fn render_power_state(&self) -> PowerState {
+        self.services
+            .try_get::<PowerManager>()
+            .map_or_else(PowerState::default, |power| power.render_power_state())
+    }
+    
+    /// Slow down the AI's behavioral monitoring while `performance` saves power
+    pub fn apply_power_state(&self, performance: &PerformanceManager) {
+        let (Some(runtime), Some(ai)) = (&self.runtime, self.services.try_get::<AIService>()) else {
+            return;
+        };
+        let limit = ai
+            .monitoring_sampling_rate()
+            .filter(|_| performance.is_power_saving())
+            .map(|rate| performance.ai_sampling_rate(rate));
+        runtime.spawn(async move {
+            if let Err(e) = ai.set_sampling_limit(limit).await {
+                log::warn!("Failed to adjust AI monitoring to the power state: {}", e);
+            }
+        });
+    }
+}
+
+impl AppState {
+    /// Fill node context menus with the nodes' own actions and toast their results
+    ///
+    /// Notifications are sent from `runtime`, as the interaction callback
+    /// runs on the compositor thread outside of it.
+    pub fn attach_node_actions(&mut self, runtime: tokio::runtime::Handle) -> Result<(), anyhow::Error> {
+        let notifications = self.services.get::<NotificationManager>()?;
+        let mut interaction = self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner());
+        interaction.set_node_manager(self.node_manager.clone());
+        interaction.on_node_action(move |outcome| {
+            let mut notification = node_action_notification(outcome.node_id, &outcome.node_name, &outcome.action, &outcome.result);
+            // System nodes only describe focus mode switches; the notification manager owns the modes
+            let focus_action = (outcome.result.is_ok() && focus_mode_request(&outcome.request).is_some())
+                .then(|| outcome.request.clone());
+            let (node_id, node_name, action) = (outcome.node_id, outcome.node_name.clone(), outcome.action.clone());
+            let notifications = notifications.clone();
+            runtime.spawn(async move {
+                if let Some(focus_action) = focus_action {
+                    if let Err(e) = notifications.handle_focus_action(&focus_action).await {
+                        notification = node_action_notification(node_id, &node_name, &action, &Err(e.to_string()));
+                    }
+                }
+                if let Err(e) = notifications.notify(notification).await {
+                    log::warn!("Could not show node action result: {}", e);
+                }
+            });
+        });
+        Ok(())
+    }
+    
+    /// Install the integrations found in the manifest directories
+    ///
+    /// Integrations the user allowed before are activated right away; the
+    /// others, and those asking for more than they were allowed, are
+    /// prompted for.
+    pub fn load_integrations(&mut self) {
+        if let Some(path) = IntegrationRegistry::default_grants_path() {
+            if let Err(e) = self.integrations.load_grants(&path) {
+                log::warn!("Integration permissions not loaded, asking again: {}", e);
+            }
+        }
+        let report = self.integrations.scan(&IntegrationRegistry::default_dirs());
+        for (path, problems) in &report.rejected {
+            log::warn!("Skipped integration manifest {}: {}", path.display(), problems.join("; "));
+        }
+        log::info!("Found {} integrations", report.installed);
+        self.apply_integrations();
+
+        for prompt in self.integrations.pending_prompts() {
+            let index = self.integration_prompts.len() as u64;
+            self.integration_prompts.push(prompt.manifest_id.clone());
+            let title = if prompt.is_update {
+                format!("{} wants more permissions", prompt.name)
+            } else {
+                format!("Allow {}?", prompt.name)
+            };
+            let body = prompt.permissions.iter().map(|p| p.describe()).collect::<Vec<_>>().join("\n");
+            self.ask(PromptSubject::Integration { index }, title, body, false);
+        }
+    }
+    
+    /// Register the menu entries and hooks of allowed integrations
+    ///
+    /// The grant of an integration covers the commands of its menu entries.
+    fn apply_integrations(&mut self) {
+        let mut interaction = self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner());
+        let mut nodes = self.node_manager.lock().unwrap_or_else(|e| e.into_inner());
+        self.integrations.apply(interaction.context_menu_mut().extensions_mut(), nodes.hooks_mut());
+        let mut permissions = self.permissions.lock().unwrap_or_else(|e| e.into_inner());
+        for integration in self.integrations.integrations() {
+            let key = MenuCommandPermissions::plugin_key(&integration.manifest.id);
+            if integration.state == IntegrationState::Active {
+                permissions.remember(&key, PrivilegedProtocol::MenuCommands, true);
+            } else {
+                permissions.revoke(&key);
+            }
+        }
+    }
+    
+    /// Load the lifecycle hooks from hooks.toml
+    ///
+    /// Workflow hooks need n8n, which may be slow to answer or not running,
+    /// so their launcher is connected in the background when the profile
+    /// enables automation; until then they fail and are recorded as such.
+    pub fn attach_hooks(&mut self, runtime: tokio::runtime::Handle) {
+        let wants_workflows = {
+            let mut nodes = self.node_manager.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(path) = HookRegistry::default_path() {
+                match nodes.hooks_mut().load(&path) {
+                    Ok(0) => {}
+                    Ok(count) => log::info!("Loaded {} lifecycle hooks from {}", count, path.display()),
+                    Err(e) => log::warn!("Failed to load lifecycle hooks from {}: {}", path.display(), e),
+                }
+            }
+            nodes.hooks().hooks().iter().any(|hook| matches!(hook.action, HookAction::Workflow { .. }))
+        };
+        if !wants_workflows || !self.profile.subsystems().automation {
+            return;
+        }
+        
+        let node_manager = self.node_manager.clone();
+        let handle = runtime.clone();
+        runtime.spawn(async move {
+            match AutomationManager::new(AutomationConfig::default()).await {
+                Ok(automation) => {
+                    let launcher = HookWorkflowLauncher::new(Arc::new(automation), handle);
+                    let mut nodes = node_manager.lock().unwrap_or_else(|e| e.into_inner());
+                    nodes.hooks_mut().set_workflow_launcher(Arc::new(launcher));
+                }
+                Err(e) => log::warn!("Lifecycle hooks cannot start workflows: {}", e),
+            }
+        });
+    }
+    
+    /// Run scripts of automation nodes with the scheduler service, if the profile started it
+    pub fn attach_scripts(&mut self, runtime: tokio::runtime::Handle) -> Result<(), anyhow::Error> {
+        let Ok(scheduler) = self.services.get::<WorkflowScheduler>() else {
+            return Ok(());
+        };
+        let notifications = self.services.get::<NotificationManager>()?;
+        self.scripts = Some(crate::automation::DesktopScripts::attach(
+            scheduler,
+            self.graph_scene.clone(),
+            self.node_manager.clone(),
+            notifications,
+            runtime,
+        ));
+        Ok(())
+    }
+    
+    /// Register edited automation scripts and start runs their nodes queued
+    pub fn dispatch_scripts(&mut self) {
+        if let Some(scripts) = &self.scripts {
+            scripts.dispatch();
+        }
+    }
+    
+    /// Answer requests of a running portal server
+    pub fn attach_portal(&mut self, server: PortalServer, requests: std::sync::mpsc::Receiver<PortalRequest>) {
+        self.portal.server = Some(server);
+        self.portal.requests = Some(requests);
+    }
+    
+    /// Answer requests of a running control server and send it desktop events
+    pub fn attach_control(
+        &mut self,
+        calls: std::sync::mpsc::Receiver<ControlCall>,
+        events: tokio::sync::broadcast::Sender<DesktopEvent>,
+    ) {
+        self.control = Some(calls);
+        self.control_events = Some(events);
+    }
+
+    /// Answer requests from the control API
+    pub fn dispatch_control(&mut self) {
+        let calls: Vec<ControlCall> = match &self.control {
+            Some(calls) => calls.try_iter().collect(),
+            None => return,
+        };
+        for call in calls {
+            let result = self.handle_control(call.request);
+            // The client may have gone away meanwhile
+            let _ = call.reply.send(result);
+        }
+    }
+
+    fn handle_control(&mut self, request: Request) -> Result<serde_json::Value, ApiError> {
+        use serde_json::json;
+        match request {
+            Request::Status => {
+                let scene = self.graph_scene.lock().unwrap();
+                let workspace = self.workspaces.get_active_workspace();
+                Ok(json!({
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "profile": self.profile.profile.name(),
+                    "nodes": scene.nodes().count(),
+                    "edges": scene.edges().count(),
+                    "windows": self.space.elements().count(),
+                    "workspace": workspace.map(|workspace| workspace.name),
+                }))
+            }
+            Request::CreateNode { node_type, name, path, position } => {
+                let node = crate::control::new_node(&node_type, &name, path.as_deref(), position)?;
+                Ok(json!({ "id": self.add_graph_node(node) }))
+            }
+            Request::DeleteNodes { ids } => {
+                let deleted: Vec<SceneId> = ids.into_iter().filter(|id| self.remove_graph_node(*id)).collect();
+                Ok(json!({ "deleted": deleted }))
+            }
+            Request::ImportFiles { paths, recursive } => {
+                let mut created = Vec::new();
+                for path in crate::control::import_paths(&paths, recursive)? {
+                    match crate::control::file_node(&path) {
+                        Ok(node) => created.push(json!({ "id": self.add_graph_node(node), "path": path })),
+                        // Files vanishing or unreadable mid-import are skipped, not fatal
+                        Err(e) => log::warn!("{}", e.message),
+                    }
+                }
+                Ok(json!({ "created": created }))
+            }
+            Request::ListWorkspaces => {
+                let mut workspaces = self.workspaces.list_workspaces();
+                workspaces.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+                Ok(workspaces
+                    .into_iter()
+                    .map(|workspace| json!({ "id": workspace.id, "name": workspace.name, "active": workspace.is_active }))
+                    .collect())
+            }
+            Request::SwitchWorkspace { workspace } => {
+                let target = self
+                    .workspaces
+                    .list_workspaces()
+                    .into_iter()
+                    .find(|info| info.id == workspace || info.name.eq_ignore_ascii_case(&workspace))
+                    .ok_or_else(|| ApiError::not_found(format!("No workspace '{}'", workspace)))?;
+                self.workspaces.switch_workspace(&target.id).map_err(|e| ApiError::failed(e.to_string()))?;
+                Ok(json!({ "id": target.id, "name": target.name }))
+            }
+            Request::Query { query, limit } => {
+                let scene = self.graph_scene.lock().unwrap();
+                Ok(crate::control::query_nodes(&scene, &query, limit).into())
+            }
+            Request::ApplyLayout { algorithm, nodes } => {
+                let (laid_out, moves) = {
+                    let mut scene = self.graph_scene.lock().unwrap();
+                    let before: HashMap<SceneId, Point3<f32>> = scene.nodes().map(|(id, node)| (*id, node.position)).collect();
+                    let laid_out = crate::control::apply_layout(&mut scene, &algorithm, &nodes)?;
+                    let moves: Vec<_> = laid_out
+                        .iter()
+                        .filter_map(|id| Some((*id, *before.get(id)?, scene.get_node(*id)?.position)))
+                        .filter(|(_, from, to)| from != to)
+                        .collect();
+                    (laid_out, moves)
+                };
+                self.animate_layout(moves);
+                self.emit_control_event("layout.applied", json!({ "algorithm": algorithm, "nodes": laid_out }));
+                Ok(json!({ "algorithm": algorithm, "nodes": laid_out.len() }))
+            }
+            Request::ExportGraph { format } => {
+                let scene = self.graph_scene.lock().unwrap();
+                let content = crate::control::export_graph(&scene, format)?;
+                Ok(json!({ "format": format, "content": content }))
+            }
+            // The server answers subscriptions itself
+            Request::Subscribe { .. } => Err(ApiError::invalid("Subscriptions are handled by the control server")),
+        }
+    }
+
+    /// Tell control API subscribers about a change
+    fn emit_control_event(&self, kind: &str, data: serde_json::Value) {
+        if let Some(events) = &self.control_events {
+            // No subscribers is not an error
+            let _ = events.send(DesktopEvent::new(kind, data));
+        }
+    }
+
+    /// Take requests from the desktop portal
+    ///
+    /// Pickers and casts are handled here; screenshots and cast frames are
+    /// captured by the renderer.
+    pub fn dispatch_portal(&mut self) {
+        let requests: Vec<PortalRequest> = match &self.portal.requests {
+            Some(requests) => requests.try_iter().collect(),
+            None => return,
+        };
+        for request in requests {
+            match request {
+                PortalRequest::PickSources { app_id, types, multiple, reply } => {
+                    if self.portal.picker.is_some() {
+                        // One picker at a time; the frontend reports the request as cancelled
+                        let _ = reply.send(None);
+                        continue;
+                    }
+                    let picker = SourcePicker::new(&app_id, types, multiple, self.picker_entries());
+                    log::info!("{} asks to capture, {} sources offered", app_id, picker.entries().len());
+                    self.portal.picker = Some(PendingPick { picker, reply });
+                    self.highlight_picker_focus();
+                }
+                PortalRequest::Screenshot { source, path, reply } => {
+                    self.portal.screenshots.push(PendingScreenshot { source, path, reply });
+                }
+                PortalRequest::StartCast { session, sources, reply } => {
+                    let _ = reply.send(self.start_cast(&session, &sources));
+                }
+                PortalRequest::StopCast { session } => self.stop_cast(&session),
+            }
+        }
+    }
+    
+    /// Forward a key to the open source picker
+    ///
+    /// Returns whether a picker was open to take it.
+    pub fn handle_picker_key(&mut self, key: PickerKey) -> bool {
+        let Some(pending) = self.portal.picker.as_mut() else {
+            return false;
+        };
+        let sources = match pending.picker.handle_key(key) {
+            PickerOutcome::Pending => {
+                self.highlight_picker_focus();
+                return true;
+            }
+            PickerOutcome::Confirmed(sources) => Some(sources),
+            PickerOutcome::Cancelled => None,
+        };
+        if let Some(pending) = self.portal.picker.take() {
+            let _ = pending.reply.send(sources);
+        }
+        self.highlight_picker_focus();
+        true
+    }
+    
+    /// Whether the window switcher is showing
+    pub fn window_switcher_open(&self) -> bool {
+        self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner()).window_switcher().is_open()
+    }
+    
+    /// Open the window switcher on Alt+Tab, or on the last window for Alt+Shift+Tab
+    pub fn open_window_switcher(&mut self, reverse: bool) {
+        let mut interaction = self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner());
+        let scene = self.graph_scene.lock().unwrap_or_else(|e| e.into_inner());
+        let switcher = interaction.window_switcher_mut();
+        switcher.open(&scene, Some(self.clustering.manager()));
+        if reverse {
+            switcher.select_last();
+        }
+    }
+    
+    /// Forward a key to the open window switcher, focusing the chosen window on commit
+    pub fn handle_switcher_key(&mut self, key: SwitcherKey) {
+        let mut chosen = None;
+        {
+            let mut interaction = self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner());
+            let switcher = interaction.window_switcher_mut();
+            match key {
+                SwitcherKey::Next => switcher.next(),
+                SwitcherKey::Previous => switcher.previous(),
+                SwitcherKey::Commit => chosen = switcher.commit(),
+                SwitcherKey::Cancel => switcher.cancel(),
+                SwitcherKey::Erase => switcher.pop_filter_char(),
+                SwitcherKey::Type(c) => switcher.push_filter_char(c),
+            }
+        }
+        if let Some(node_id) = chosen {
+            self.activate_window_node(node_id);
+        }
+    }
+    
+    /// Whether the Quick Look overlay is showing
+    pub fn quick_look_open(&self) -> bool {
+        self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner()).quick_look().is_open()
+    }
+    
+    /// Open Quick Look on the focused node, stepping through the selected files
+    ///
+    /// Returns `false` when the focused node is not a file, leaving Space to
+    /// keyboard navigation.
+    pub fn open_quick_look(&mut self) -> bool {
+        let event = {
+            let mut interaction = self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner());
+            let focus = self.accessibility.as_ref()
+                .and_then(|accessibility| accessibility.keyboard_nav.get_focus())
+                .or_else(|| interaction.selection().get_primary_selection());
+            let selection = interaction.selection().get_selection();
+            let scene = self.graph_scene.lock().unwrap_or_else(|e| e.into_inner());
+            focus.and_then(|focus| interaction.quick_look_mut().open(focus, &selection, &scene))
+        };
+        let Some(event) = event else {
+            return false;
+        };
+        self.quick_look.handle(event);
+        true
+    }
+    
+    /// Forward a key to the open Quick Look overlay
+    pub fn handle_quick_look_key(&mut self, key: QuickLookKey) {
+        let event = {
+            let mut interaction = self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner());
+            let quick_look = interaction.quick_look_mut();
+            match key {
+                QuickLookKey::Next => quick_look.next_file(),
+                QuickLookKey::Previous => quick_look.previous_file(),
+                QuickLookKey::Close => quick_look.close(),
+            }
+        };
+        if let Some(event) = event {
+            self.quick_look.handle(event);
+        }
+    }
+    
+    /// Outputs and window nodes that can be captured
+    fn picker_entries(&self) -> Vec<PickerEntry> {
+        let mut entries: Vec<PickerEntry> = self.space.outputs()
+            .map(|output| PickerEntry {
+                source: CaptureSource::Output { name: output.name() },
+                label: output.description(),
+            })
+            .collect();
+        let scene = self.graph_scene.lock().unwrap();
+        let mut nodes: Vec<SceneId> = self.surface_to_node.values().copied().collect();
+        nodes.sort_unstable();
+        for node_id in nodes {
+            let label = match scene.get_node(node_id).map(|node| &node.node_type) {
+                Some(horizonos_graph_engine::NodeType::Application { name, .. }) => name.clone(),
+                _ => format!("Window {}", node_id),
+            };
+            entries.push(PickerEntry { source: CaptureSource::Node { node_id }, label });
+        }
+        entries
+    }
+    
+    /// Select the node focused in the picker, restoring the previously highlighted one
+    fn highlight_picker_focus(&mut self) {
+        let focused = self.portal.picker.as_ref()
+            .and_then(|pending| pending.picker.focused())
+            .and_then(|entry| match entry.source {
+                CaptureSource::Node { node_id } => Some(node_id),
+                CaptureSource::Output { .. } => None,
+            });
+        if self.portal.highlight.map(|(node_id, _)| node_id) == focused {
+            return;
+        }
+        
+        let mut scene = self.graph_scene.lock().unwrap();
+        if let Some((node_id, was_selected)) = self.portal.highlight.take() {
+            if let Some(node) = scene.get_node_mut(node_id) {
+                node.selected = was_selected;
+            }
+        }
+        if let Some(node) = focused.and_then(|node_id| scene.get_node_mut(node_id)) {
+            self.portal.highlight = Some((node.id, node.selected));
+            node.selected = true;
+        }
+    }
+    
+    /// Open a stream per source of a cast session
+    fn start_cast(&mut self, session: &str, sources: &[CaptureSource]) -> Result<Vec<StreamInfo>, String> {
+        let mut streams = Vec::new();
+        for source in sources {
+            let (position, size) = self.source_geometry(source)
+                .ok_or_else(|| format!("{:?} is no longer available", source))?;
+            let Some(backend) = self.portal.stream_backend.as_mut() else {
+                return Err("no PipeWire stream backend is available".to_string());
+            };
+            match backend.open(source, size) {
+                Ok(pipewire_node) => streams.push(StreamInfo { pipewire_node, source: source.clone(), position, size }),
+                Err(e) => {
+                    for stream in &streams {
+                        backend.close(stream.pipewire_node);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        log::info!("Screen cast {} started with {} streams", session, streams.len());
+        self.portal.casts.insert(session.to_string(), streams.clone());
+        Ok(streams)
+    }
+    
+    fn stop_cast(&mut self, session: &str) {
+        let Some(streams) = self.portal.casts.remove(session) else {
+            return;
+        };
+        if let Some(backend) = self.portal.stream_backend.as_mut() {
+            for stream in streams {
+                backend.close(stream.pipewire_node);
+            }
+        }
+        log::info!("Screen cast {} stopped", session);
+    }
+    
+    /// Position and size of a source in the desktop
+    fn source_geometry(&self, source: &CaptureSource) -> Option<((i32, i32), (i32, i32))> {
+        match source {
+            CaptureSource::Output { name } => {
+                let output = self.space.outputs().find(|output| &output.name() == name)?;
+                let geometry = self.space.output_geometry(output)?;
+                Some(((geometry.loc.x, geometry.loc.y), (geometry.size.w, geometry.size.h)))
+            }
+            CaptureSource::Node { node_id } => {
+                let window = self.space.elements().find(|window| {
+                    window.toplevel().and_then(|toplevel| self.surface_to_node.get(toplevel.wl_surface())) == Some(node_id)
+                })?;
+                let size = window.geometry().size;
+                Some(((0, 0), (size.w, size.h)))
+            }
+        }
+    }
+}
+
 // Handler implementations
 impl CompositorHandler for AppState {
     fn compositor_state(&mut self) -> &mut CompositorState {
@@ -156,9 +2064,17 @@ impl CompositorHandler for AppState {
     }
     
     fn commit(&mut self, surface: &WlSurface) {
-        // Handle surface commits
         // Space and popup handling in Smithay 0.7
         // TODO: Check if specific handling needed
+        
+        // A commit to a window or any of its subsurfaces changes its thumbnail
+        let mut root = surface.clone();
+        while let Some(parent) = smithay::wayland::compositor::get_parent(&root) {
+            root = parent;
+        }
+        if let Some(&node_id) = self.surface_to_node.get(&root) {
+            self.live_thumbnails.damage(node_id);
+        }
     }
 }
 
@@ -199,24 +2115,52 @@ impl XdgShellHandler for AppState {
                 tags: vec![],
                 description: None,
                 properties: std::collections::HashMap::new(),
+                privacy: Default::default(),
+                // Windows come back with a crashed session, not with a saved graph
+                persistence: horizonos_graph_engine::NodePersistence::Session,
             },
             visible: true,
             selected: false,
         };
-        let node_id = self.graph_scene.lock().unwrap().add_node(node);
+        let node_id = self.add_graph_node(node);
         
         // Map surface to node
         if let Some(toplevel) = window.toplevel() {
             let wl_surface = toplevel.wl_surface();
             self.surface_to_node.insert(wl_surface.clone(), node_id);
+            self.live_thumbnails.track(node_id);
+            if let Some(workspace) = self.workspaces.get_active_workspace() {
+                self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner())
+                    .window_switcher_mut().set_workspace(node_id, workspace.id);
+            }
+        }
+        
+        // List the window in external taskbars
+        let outputs: Vec<_> = self.space.outputs().cloned().collect();
+        self.protocol_manager.foreign_toplevel.add_toplevel(&self.display_handle, node_id, window, &outputs);
+    }
+    
+    fn title_changed(&mut self, surface: ToplevelSurface) {
+        if let Some(&node_id) = self.surface_to_node.get(surface.wl_surface()) {
+            self.protocol_manager.foreign_toplevel.refresh_details(node_id);
+        }
+    }
+    
+    fn app_id_changed(&mut self, surface: ToplevelSurface) {
+        if let Some(&node_id) = self.surface_to_node.get(surface.wl_surface()) {
+            self.protocol_manager.foreign_toplevel.refresh_details(node_id);
         }
     }
     
     fn toplevel_destroyed(&mut self, surface: ToplevelSurface) {
-        // Find and remove the window
+        // Find and remove the window; minimized windows are only known to the taskbar state
         let window = self.space.elements()
             .find(|w| w.toplevel() == Some(&surface))
-            .cloned();
+            .cloned()
+            .or_else(|| {
+                let node_id = self.surface_to_node.get(surface.wl_surface())?;
+                self.protocol_manager.foreign_toplevel.window(*node_id).cloned()
+            });
         
         if let Some(window) = window {
             self.space.unmap_elem(&window);
@@ -225,7 +2169,11 @@ impl XdgShellHandler for AppState {
             if let Some(toplevel) = window.toplevel() {
                 let wl_surface = toplevel.wl_surface();
                 if let Some(node_id) = self.surface_to_node.remove(wl_surface) {
-                    self.graph_scene.lock().unwrap().remove_node(node_id);
+                    self.live_thumbnails.untrack(node_id);
+                    self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner())
+                        .window_switcher_mut().forget(node_id);
+                    self.protocol_manager.foreign_toplevel.remove_toplevel(node_id);
+                    self.remove_graph_node(node_id);
                 }
             }
         }
@@ -265,8 +2213,27 @@ impl SeatHandler for AppState {
         // TODO: Handle cursor image changes
     }
     
-    fn focus_changed(&mut self, _seat: &Seat<Self>, _focused: Option<&WlSurface>) {
-        // TODO: Handle focus changes
+    fn focus_changed(&mut self, _seat: &Seat<Self>, focused: Option<&WlSurface>) {
+        // Taskbars show the window with keyboard focus as active
+        let node_id = focused.and_then(|surface| self.surface_to_node.get(surface).copied());
+        self.protocol_manager.foreign_toplevel.set_activated(node_id);
+        
+        // The switcher ranks windows by focus history and the workspace they were last used in
+        if let Some(node_id) = node_id {
+            let mut interaction = self.interaction_manager.lock().unwrap_or_else(|e| e.into_inner());
+            let switcher = interaction.window_switcher_mut();
+            switcher.record_focus(node_id);
+            if let Some(workspace) = self.workspaces.get_active_workspace() {
+                switcher.set_workspace(node_id, workspace.id);
+            }
+        }
+        
+        // Windows focused together are suggested as related
+        if let (Some(node_id), Some(ai)) = (node_id, self.services.try_get::<AIService>()) {
+            if let Some(node) = self.graph_scene.lock().unwrap_or_else(|e| e.into_inner()).get_node(node_id) {
+                ai.record_node_focus(node_id, &node.node_type);
+            }
+        }
     }
 }
 
@@ -286,10 +2253,32 @@ impl ServerDndGrabHandler for AppState {}
 
 impl OutputHandler for AppState {}
 
+impl SecurityContextHandler for AppState {
+    fn context_created(&mut self, source: SecurityContextListenerSource, security_context: SecurityContext) {
+        log::info!(
+            "New security context: engine={:?} app={:?}",
+            security_context.sandbox_engine, security_context.app_id
+        );
+        let result = self.loop_handle.insert_source(source, move |client_stream, _, state| {
+            let client_state = ClientState {
+                security_context: Some(security_context.clone()),
+                ..ClientState::default()
+            };
+            if let Err(e) = state.display_handle.insert_client(client_stream, Arc::new(client_state)) {
+                log::warn!("Failed to add sandboxed client: {}", e);
+            }
+        });
+        if let Err(e) = result {
+            log::error!("Failed to listen on security context socket: {}", e);
+        }
+    }
+}
+
 // Delegate macro implementations
 delegate_compositor!(AppState);
 delegate_shm!(AppState);
 delegate_xdg_shell!(AppState);
 delegate_seat!(AppState);
 delegate_data_device!(AppState);
+delegate_security_context!(AppState);
 delegate_output!(AppState);
\ No newline at end of file
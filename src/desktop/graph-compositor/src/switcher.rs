@@ -0,0 +1,169 @@
+//! Window switcher as the compositor shows it
+//!
+//! The interaction crate ranks the windows; the compositor feeds it the live
+//! thumbnails of application nodes as previews and draws the open switcher
+//! as a row of tiles over the windows. A preview's texture id is the
+//! generation of the thumbnail it shows, so the renderer uploads each
+//! thumbnail once and knows when a newer one replaces it.
+
+use horizonos_graph_engine::SceneId;
+use horizonos_graph_interaction::{PreviewProvider, SwitcherCandidate, WindowPreview};
+use horizonos_graph_visual::LiveThumbnails;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Space between tiles and around the row, in pixels
+const TILE_GAP: i32 = 16;
+/// Width of the outline of the selected tile, in pixels
+pub const SELECTED_OUTLINE: i32 = 4;
+
+/// Latest thumbnail of each window, shared with the switcher
+#[derive(Clone, Default)]
+pub struct SwitcherPreviews {
+    previews: Arc<Mutex<HashMap<SceneId, WindowPreview>>>,
+}
+
+impl SwitcherPreviews {
+    /// Take the newest thumbnails of these window nodes
+    pub fn update(&self, thumbnails: &LiveThumbnails, nodes: impl IntoIterator<Item = SceneId>) {
+        let previews = nodes
+            .into_iter()
+            .filter_map(|node_id| {
+                let thumbnail = thumbnails.thumbnail(node_id)?;
+                let preview = WindowPreview {
+                    texture_id: thumbnail.generation,
+                    width: thumbnail.image.width(),
+                    height: thumbnail.image.height(),
+                };
+                Some((node_id, preview))
+            })
+            .collect();
+        *self.previews.lock().unwrap_or_else(|e| e.into_inner()) = previews;
+    }
+}
+
+impl PreviewProvider for SwitcherPreviews {
+    fn preview(&self, node_id: SceneId, size: (u32, u32)) -> Option<WindowPreview> {
+        let preview = self.previews.lock().unwrap_or_else(|e| e.into_inner()).get(&node_id)?.clone();
+        let (width, height) = fit(preview.width, preview.height, size);
+        Some(WindowPreview { width, height, ..preview })
+    }
+}
+
+/// Keys the open switcher responds to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwitcherKey {
+    Next,
+    Previous,
+    /// Releasing Alt switches to the selected window
+    Commit,
+    Cancel,
+    /// Remove the last type-ahead character
+    Erase,
+    /// Narrow the candidates by a typed character
+    Type(char),
+}
+
+/// A candidate as drawn, in output pixels
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitcherTile {
+    pub node_id: SceneId,
+    /// Tile position and size
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    /// Preview inside the tile, centered; `None` draws a placeholder
+    pub preview: Option<(WindowPreview, [i32; 4])>,
+    pub selected: bool,
+}
+
+/// Lay out the visible candidates as a centered row, shrinking tiles to fit the output
+pub fn layout_tiles(
+    candidates: &[&SwitcherCandidate],
+    selected: usize,
+    tile_size: (u32, u32),
+    output_size: (i32, i32),
+) -> Vec<SwitcherTile> {
+    if candidates.is_empty() {
+        return Vec::new();
+    }
+    let count = candidates.len() as i32;
+    let available = (output_size.0 - TILE_GAP * (count + 1)).max(count);
+    let width = (tile_size.0 as i32).min(available / count).max(1);
+    let height = (tile_size.1 as i64 * width as i64 / tile_size.0.max(1) as i64) as i32;
+    let row_width = count * width + (count - 1) * TILE_GAP;
+    let left = (output_size.0 - row_width) / 2;
+    let top = (output_size.1 - height) / 2;
+
+    candidates
+        .iter()
+        .enumerate()
+        .map(|(i, candidate)| {
+            let x = left + i as i32 * (width + TILE_GAP);
+            let preview = candidate.preview.clone().map(|preview| {
+                let (w, h) = fit(preview.width, preview.height, (width as u32, height as u32));
+                let rect = [x + (width - w as i32) / 2, top + (height - h as i32) / 2, w as i32, h as i32];
+                (preview, rect)
+            });
+            SwitcherTile {
+                node_id: candidate.node_id,
+                x,
+                y: top,
+                width,
+                height,
+                preview,
+                selected: i == selected,
+            }
+        })
+        .collect()
+}
+
+/// Size of `width` x `height` scaled to fit `bounds`, keeping its aspect ratio
+pub(crate) fn fit(width: u32, height: u32, bounds: (u32, u32)) -> (u32, u32) {
+    if width == 0 || height == 0 {
+        return (0, 0);
+    }
+    let scale = (bounds.0 as f32 / width as f32).min(bounds.1 as f32 / height as f32);
+    (((width as f32 * scale) as u32).max(1), ((height as f32 * scale) as u32).max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_interaction::RelevanceBreakdown;
+
+    fn candidate(node_id: SceneId, preview: Option<WindowPreview>) -> SwitcherCandidate {
+        SwitcherCandidate {
+            node_id,
+            title: format!("Window {}", node_id),
+            app_name: "app".to_string(),
+            score: 0.0,
+            relevance: RelevanceBreakdown::default(),
+            mru_rank: None,
+            preview,
+        }
+    }
+
+    #[test]
+    fn test_tiles_are_centered_and_fit_the_output() {
+        let wide = WindowPreview { texture_id: 7, width: 320, height: 100 };
+        let candidates = [candidate(1, Some(wide)), candidate(2, None)];
+        let candidates: Vec<_> = candidates.iter().collect();
+
+        let tiles = layout_tiles(&candidates, 1, (320, 200), (1920, 1080));
+        assert_eq!(tiles.len(), 2);
+        assert_eq!(tiles[0].x + tiles[1].x + tiles[1].width, 1920);
+        assert_eq!(tiles[0].y, (1080 - 200) / 2);
+        assert!(!tiles[0].selected && tiles[1].selected);
+        // The preview keeps its aspect ratio inside the tile
+        let (_, rect) = tiles[0].preview.as_ref().unwrap();
+        assert_eq!(*rect, [tiles[0].x, tiles[0].y + 50, 320, 100]);
+        assert!(tiles[1].preview.is_none());
+
+        // Narrow outputs shrink the tiles instead of cutting the row off
+        let tiles = layout_tiles(&candidates, 0, (320, 200), (400, 300));
+        assert!(tiles[0].x >= 0 && tiles[1].x + tiles[1].width <= 400);
+        assert_eq!(tiles[0].height * 320, tiles[0].width * 200);
+    }
+}
@@ -0,0 +1,140 @@
+//! Video shown on media nodes
+//!
+//! Players decoding video for a node, such as a GStreamer pipeline playing
+//! a media file or a consumer of a PipeWire stream, run on their own
+//! threads and send frames through a [`VideoFeed`]. The render loop hands
+//! the newest frame of each node to the graph engine once per frame;
+//! frames that were overtaken meanwhile are dropped.
+
+use horizonos_graph_engine::{GraphEngine, SceneId, VideoFrame};
+use std::collections::HashMap;
+
+/// Frames queued before players have to drop theirs
+const FEED_CAPACITY: usize = 64;
+
+enum VideoUpdate {
+    Frame(SceneId, VideoFrame),
+    Ended(SceneId),
+}
+
+/// Sends a player's frames to the compositor
+#[derive(Clone)]
+pub struct VideoFeed {
+    sender: crossbeam_channel::Sender<VideoUpdate>,
+}
+
+impl VideoFeed {
+    /// Show a decoded frame on a node
+    ///
+    /// Returns `false` once the compositor is gone. While the render loop
+    /// falls behind, the frame is dropped.
+    pub fn send_frame(&self, node_id: SceneId, frame: VideoFrame) -> bool {
+        match self.sender.try_send(VideoUpdate::Frame(node_id, frame)) {
+            Ok(()) => true,
+            Err(crossbeam_channel::TrySendError::Full(_)) => {
+                log::debug!("Dropping a video frame of node {}, the compositor is behind", node_id);
+                true
+            }
+            Err(crossbeam_channel::TrySendError::Disconnected(_)) => false,
+        }
+    }
+
+    /// Stop showing video on a node whose playback ended
+    pub fn end(&self, node_id: SceneId) {
+        let _ = self.sender.send(VideoUpdate::Ended(node_id));
+    }
+}
+
+/// Frames waiting for the next render
+pub struct VideoFeeds {
+    sender: crossbeam_channel::Sender<VideoUpdate>,
+    receiver: crossbeam_channel::Receiver<VideoUpdate>,
+}
+
+impl VideoFeeds {
+    pub fn new() -> Self {
+        let (sender, receiver) = crossbeam_channel::bounded(FEED_CAPACITY);
+        Self { sender, receiver }
+    }
+
+    /// A feed to hand to a player
+    pub fn feed(&self) -> VideoFeed {
+        VideoFeed { sender: self.sender.clone() }
+    }
+
+    /// Submit the newest queued frame of each node to `engine`
+    pub fn submit(&self, engine: &mut GraphEngine) {
+        let (frames, ended) = self.take_latest();
+        for node_id in ended {
+            engine.remove_video(node_id);
+        }
+        for (node_id, frame) in frames {
+            if let Err(e) = engine.submit_video_frame(node_id, frame) {
+                log::debug!("Dropped a video frame of node {}: {}", node_id, e);
+            }
+        }
+    }
+
+    /// The newest frame per node, and the nodes whose playback ended since
+    fn take_latest(&self) -> (HashMap<SceneId, VideoFrame>, Vec<SceneId>) {
+        let mut frames = HashMap::new();
+        let mut ended = Vec::new();
+        for update in self.receiver.try_iter() {
+            match update {
+                VideoUpdate::Frame(node_id, frame) => {
+                    ended.retain(|&id| id != node_id);
+                    frames.insert(node_id, frame);
+                }
+                VideoUpdate::Ended(node_id) => {
+                    frames.remove(&node_id);
+                    ended.push(node_id);
+                }
+            }
+        }
+        (frames, ended)
+    }
+}
+
+impl Default for VideoFeeds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::renderer::video::DRM_FORMAT_XRGB8888;
+    use horizonos_graph_engine::MemoryFrame;
+
+    fn frame(shade: u8) -> VideoFrame {
+        VideoFrame::Memory(MemoryFrame {
+            width: 1,
+            height: 1,
+            format: DRM_FORMAT_XRGB8888,
+            stride: 4,
+            data: vec![shade; 4],
+        })
+    }
+
+    #[test]
+    fn test_only_the_newest_frame_per_node_is_submitted() {
+        let feeds = VideoFeeds::new();
+        let feed = feeds.feed();
+        assert!(feed.send_frame(1, frame(1)));
+        assert!(feed.send_frame(1, frame(2)));
+        assert!(feed.send_frame(2, frame(3)));
+        feed.end(2);
+
+        let (frames, ended) = feeds.take_latest();
+        assert_eq!(frames.len(), 1);
+        let Some(VideoFrame::Memory(latest)) = frames.get(&1) else {
+            panic!("node 1 has no frame");
+        };
+        assert_eq!(latest.data, vec![2; 4]);
+        assert_eq!(ended, vec![2]);
+
+        drop(feeds);
+        assert!(!feed.send_frame(1, frame(4)));
+    }
+}
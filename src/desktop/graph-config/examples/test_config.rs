@@ -78,6 +78,18 @@ async fn main() -> anyhow::Result<()> {
                     ConfigChangeEvent::ValueChanged(key) => {
                         println!("\n[Event] Configuration value changed: {}", key);
                     }
+                    ConfigChangeEvent::LanguageChanged(locale) => {
+                        println!("\n[Event] Language changed to: {}", locale);
+                    }
+                    ConfigChangeEvent::Calibrated => {
+                        println!("\n[Event] Performance settings calibrated");
+                    }
+                    ConfigChangeEvent::LodAppearanceChanged => {
+                        println!("\n[Event] LOD appearance changed");
+                    }
+                    ConfigChangeEvent::MonitorsChanged => {
+                        println!("\n[Event] Monitor layout changed");
+                    }
                     ConfigChangeEvent::Initialized => {
                         println!("\n[Event] Configuration initialized");
                     }
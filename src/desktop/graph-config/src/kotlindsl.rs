@@ -480,6 +480,15 @@ impl KotlinDslLoader {
                     }
                 }
             }
+            
+            // Process gesture bindings of every interaction
+            for interaction in graph_desktop.interactions.values() {
+                if let Some(gesture_actions) = &interaction.gesture_actions {
+                    for (gesture, action) in gesture_actions {
+                        config.gestures.insert(gesture.clone(), action.to_lowercase().replace(" ", "_"));
+                    }
+                }
+            }
         }
         
         Ok(config)
@@ -5,7 +5,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 use tokio::sync::watch;
 use anyhow::Result;
@@ -20,13 +20,14 @@ use theme::{Theme, ThemeManager};
 use loader::ConfigLoader;
 use watcher::ConfigWatcher;
 use validation::ConfigValidator;
+use horizonos_graph_performance::{AiAcceleration, CalibratedDefaults, Calibration, HardwareBenchmark, LodAppearance, LodPolicy};
 
 /// Main configuration manager
 pub struct ConfigManager {
     /// Current configuration
     config: Arc<RwLock<GraphDesktopConfig>>,
-    /// Theme manager
-    theme_manager: ThemeManager,
+    /// Theme manager, shared with the watcher to reload edited theme files
+    theme_manager: Arc<RwLock<ThemeManager>>,
     /// Configuration loader
     loader: ConfigLoader,
     /// Configuration watcher for hot-reload
@@ -45,7 +46,7 @@ impl ConfigManager {
         
         let manager = Self {
             config: Arc::new(RwLock::new(default_config)),
-            theme_manager: ThemeManager::new(),
+            theme_manager: Arc::new(RwLock::new(ThemeManager::new())),
             loader: ConfigLoader::new(),
             watcher: None,
             change_tx,
@@ -70,13 +71,18 @@ impl ConfigManager {
         // Validate configuration
         self.validator.validate(&config)?;
         
+        apply_language(&config.general);
         *self.config.write().unwrap() = config;
         self.change_tx.send(ConfigChangeEvent::ConfigReloaded)?;
         
         // Load themes
         let themes_dir = config_dir.join("themes");
         if themes_dir.exists() {
-            self.theme_manager.load_themes(&themes_dir).await?;
+            let themes = ThemeManager::read_themes(&themes_dir).await?;
+            let mut theme_manager = self.theme_manager.write().unwrap();
+            for (name, theme) in themes {
+                theme_manager.insert_theme(name, theme);
+            }
         }
         
         // Set up file watcher for hot-reload
@@ -131,6 +137,8 @@ impl ConfigManager {
         let change_tx = self.change_tx.clone();
         let loader = self.loader.clone();
         let validator = self.validator.clone();
+        let theme_manager = self.theme_manager.clone();
+        let themes_dir = config_dir.join("themes");
         
         let watcher = ConfigWatcher::new(config_dir, move |path| {
             // Handle configuration file changes
@@ -139,9 +147,27 @@ impl ConfigManager {
             let loader = loader.clone();
             let validator = validator.clone();
             
+            if path.starts_with(&themes_dir) {
+                let theme_manager = theme_manager.clone();
+                tokio::spawn(async move {
+                    match ThemeManager::read_theme_file(&path).await {
+                        Ok((name, theme)) => {
+                            theme_manager.write().unwrap().insert_theme(name.clone(), theme);
+                            // Only an edit of the active theme changes what is on screen
+                            if config.read().unwrap().appearance.theme == name {
+                                let _ = change_tx.send(ConfigChangeEvent::ThemeChanged(name));
+                            }
+                        }
+                        Err(e) => log::warn!("Keeping previous theme, failed to reload {:?}: {}", path, e),
+                    }
+                });
+                return;
+            }
+            
             tokio::spawn(async move {
                 if let Ok(new_config) = loader.load_config(&path).await {
                     if validator.validate(&new_config).is_ok() {
+                        apply_language(&new_config.general);
                         *config.write().unwrap() = new_config;
                         let _ = change_tx.send(ConfigChangeEvent::ConfigReloaded);
                     }
@@ -176,13 +202,19 @@ impl ConfigManager {
     /// Get current theme
     pub fn current_theme(&self) -> Theme {
         let config = self.config.read().unwrap();
-        self.theme_manager.get_theme(&config.appearance.theme)
-            .unwrap_or_else(|| self.theme_manager.default_theme())
+        let theme_manager = self.theme_manager.read().unwrap();
+        theme_manager.get_theme(&config.appearance.theme)
+            .unwrap_or_else(|| theme_manager.default_theme())
+    }
+    
+    /// Get a theme by name
+    pub fn theme(&self, theme_name: &str) -> Option<Theme> {
+        self.theme_manager.read().unwrap().get_theme(theme_name)
     }
     
     /// Switch theme
     pub fn switch_theme(&mut self, theme_name: &str) -> Result<()> {
-        if self.theme_manager.has_theme(theme_name) {
+        if self.theme_manager.read().unwrap().has_theme(theme_name) {
             self.config.write().unwrap().appearance.theme = theme_name.to_string();
             self.change_tx.send(ConfigChangeEvent::ThemeChanged(theme_name.to_string()))?;
             Ok(())
@@ -191,6 +223,95 @@ impl ConfigManager {
         }
     }
     
+    /// Switch the UI language at runtime; `None` follows the system locale
+    pub fn set_language(&self, language: Option<&str>) -> Result<()> {
+        let locale = horizonos_graph_i18n::set_language(language)?;
+        self.config.write().unwrap().general.language = language.map(str::to_string);
+        self.change_tx.send(ConfigChangeEvent::LanguageChanged(locale.to_string()))?;
+        Ok(())
+    }
+    
+    /// Replace the per-node-type LOD appearance
+    ///
+    /// Renderers follow the change event, so edits show up live while the
+    /// settings are open; `save` keeps them.
+    pub fn set_lod_appearance(&self, appearance: LodAppearance) -> Result<()> {
+        self.config.write().unwrap().performance.lod_appearance = appearance;
+        self.change_tx.send(ConfigChangeEvent::LodAppearanceChanged)?;
+        Ok(())
+    }
+    
+    /// Set the LOD policy of one node type, by its palette name (`application`, `concept`, ...)
+    pub fn set_lod_type_policy(&self, node_type: &str, policy: LodPolicy) -> Result<()> {
+        self.config.write().unwrap().performance.lod_appearance.node_types.insert(node_type.to_string(), policy);
+        self.change_tx.send(ConfigChangeEvent::LodAppearanceChanged)?;
+        Ok(())
+    }
+    
+    /// Replace the monitor layout, e.g. after outputs were rearranged or given another workspace
+    ///
+    /// Like other settings it is written out by `save`.
+    pub fn set_monitors(&self, monitors: MonitorsConfig) -> Result<()> {
+        self.config.write().unwrap().monitors = monitors;
+        self.change_tx.send(ConfigChangeEvent::MonitorsChanged)?;
+        Ok(())
+    }
+    
+    /// Where the hardware calibration of a configuration directory is stored
+    pub fn calibration_path(config_dir: &Path) -> PathBuf {
+        config_dir.join("calibration.json")
+    }
+    
+    /// Calibrate on first launch, or when the benchmark has changed since
+    ///
+    /// A stored calibration is applied again, to settings still at their
+    /// built-in defaults; returns the calibration in effect.
+    pub async fn calibrate_if_needed(&self, config_dir: &Path) -> Result<Calibration> {
+        let path = Self::calibration_path(config_dir);
+        let calibration = match Calibration::load(&path) {
+            Ok(Some(calibration)) if calibration.is_current() => calibration,
+            Ok(_) => self.run_calibration(&path).await?,
+            Err(e) => {
+                log::warn!("Discarding unreadable calibration {:?}: {}", path, e);
+                self.run_calibration(&path).await?
+            }
+        };
+        self.apply_calibration(&calibration.defaults, false)?;
+        Ok(calibration)
+    }
+    
+    /// Re-run the benchmark from settings, e.g. after a hardware change,
+    /// replacing the calibrated settings even where they were edited
+    pub async fn recalibrate(&self, config_dir: &Path) -> Result<Calibration> {
+        let calibration = self.run_calibration(&Self::calibration_path(config_dir)).await?;
+        self.apply_calibration(&calibration.defaults, true)?;
+        Ok(calibration)
+    }
+    
+    async fn run_calibration(&self, path: &Path) -> Result<Calibration> {
+        log::info!("Calibrating performance defaults");
+        let calibration = tokio::task::spawn_blocking(|| HardwareBenchmark::new().calibrate()).await?;
+        calibration.save(path)?;
+        Ok(calibration)
+    }
+    
+    /// Apply calibrated settings; unless `overwrite`, only to settings still at their defaults
+    pub fn apply_calibration(&self, defaults: &CalibratedDefaults, overwrite: bool) -> Result<()> {
+        let changed = {
+            let mut config = self.config.write().unwrap();
+            let before = (config.performance.clone(), config.ai.hardware_optimization);
+            config.performance.apply_calibration(defaults, overwrite);
+            if overwrite || config.ai.hardware_optimization == AiAcceleration::default() {
+                config.ai.hardware_optimization = defaults.ai_acceleration;
+            }
+            before != (config.performance.clone(), config.ai.hardware_optimization)
+        };
+        if changed {
+            self.change_tx.send(ConfigChangeEvent::Calibrated)?;
+        }
+        Ok(())
+    }
+    
     /// Save current configuration
     pub async fn save(&self, path: &Path) -> Result<()> {
         let config = self.config.read().unwrap().clone();
@@ -215,10 +336,23 @@ pub struct GraphDesktopConfig {
     pub ai: AIConfig,
     /// Workspace settings
     pub workspace: WorkspaceConfig,
+    /// Clustering settings
+    #[serde(default)]
+    pub clustering: ClusteringConfig,
     /// Accessibility settings
     pub accessibility: AccessibilityConfig,
-    /// Keyboard shortcuts
+    /// Monitor layout and what each output shows
+    #[serde(default)]
+    pub monitors: MonitorsConfig,
+    /// Remote view server, used when the compositor runs with `--remote-view`
+    #[serde(default)]
+    pub remote_view: RemoteViewConfig,
+    /// Keyboard shortcuts, and pointer bindings for keys naming a mouse button
     pub shortcuts: HashMap<String, KeyboardShortcut>,
+    /// Touch and trackpad gesture bindings: gesture name (e.g. `pinch`,
+    /// `three_finger_swipe_left`) to `zoom`, `pan`, `rotate`, `none` or a shortcut action
+    #[serde(default = "default_gestures")]
+    pub gestures: HashMap<String, String>,
     /// Custom configuration values
     pub custom: HashMap<String, serde_json::Value>,
 }
@@ -233,8 +367,12 @@ impl Default for GraphDesktopConfig {
             performance: PerformanceConfig::default(),
             ai: AIConfig::default(),
             workspace: WorkspaceConfig::default(),
+            clustering: ClusteringConfig::default(),
             accessibility: AccessibilityConfig::default(),
+            monitors: MonitorsConfig::default(),
+            remote_view: RemoteViewConfig::default(),
             shortcuts: default_shortcuts(),
+            gestures: default_gestures(),
             custom: HashMap::new(),
         }
     }
@@ -253,6 +391,9 @@ pub struct GeneralConfig {
     pub log_level: String,
     /// Enable debug mode
     pub debug: bool,
+    /// UI language as a locale tag (e.g. "de" or "pt-BR"); `None` follows the system locale
+    #[serde(default)]
+    pub language: Option<String>,
 }
 
 impl Default for GeneralConfig {
@@ -263,6 +404,7 @@ impl Default for GeneralConfig {
             browser: "firefox".to_string(),
             log_level: "info".to_string(),
             debug: false,
+            language: None,
         }
     }
 }
@@ -369,7 +511,7 @@ impl Default for TransparencyConfig {
 /// Graph rendering configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphConfig {
-    /// Default layout algorithm
+    /// Name of the registered layout algorithm workspaces use unless they choose their own
     pub layout_algorithm: String,
     /// Node size
     pub node_size: f32,
@@ -383,6 +525,15 @@ pub struct GraphConfig {
     pub physics_enabled: bool,
     /// Physics settings
     pub physics: PhysicsConfig,
+    /// Edge routing around nodes and cluster hulls; `quality` trades smoothness for speed
+    #[serde(default)]
+    pub edge_routing: horizonos_graph_engine::EdgeRoutingConfig,
+    /// Rules for which nodes edge types may connect; `mode = "warn"` or `"off"` relaxes them
+    #[serde(default)]
+    pub edge_validation: horizonos_graph_edges::EdgeValidationConfig,
+    /// Half-lives per edge type of unused relationships, and the strength below which they are archived
+    #[serde(default)]
+    pub strength_decay: horizonos_graph_edges::StrengthDecayConfig,
 }
 
 impl Default for GraphConfig {
@@ -395,6 +546,9 @@ impl Default for GraphConfig {
             label_size: 12.0,
             physics_enabled: true,
             physics: PhysicsConfig::default(),
+            edge_routing: horizonos_graph_engine::EdgeRoutingConfig::default(),
+            edge_validation: horizonos_graph_edges::EdgeValidationConfig::default(),
+            strength_decay: horizonos_graph_edges::StrengthDecayConfig::default(),
         }
     }
 }
@@ -436,6 +590,12 @@ pub struct InteractionConfig {
     pub drag_threshold: f32,
     /// Edge creation mode
     pub edge_creation_mode: EdgeCreationMode,
+    /// Modal keyboard navigation
+    #[serde(default)]
+    pub keymap: KeymapConfig,
+    /// Dimming the graph beyond a node's neighbourhood
+    #[serde(default)]
+    pub focus_mode: FocusModeConfig,
 }
 
 impl Default for InteractionConfig {
@@ -446,6 +606,50 @@ impl Default for InteractionConfig {
             double_click_interval: 400,
             drag_threshold: 5.0,
             edge_creation_mode: EdgeCreationMode::DragFromNode,
+            keymap: KeymapConfig::default(),
+            focus_mode: FocusModeConfig::default(),
+        }
+    }
+}
+
+/// Modal (vim-style) keyboard navigation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeymapConfig {
+    /// Enable modal navigation, starting in normal mode
+    pub modal: bool,
+    /// Bindings added to or replacing the built-in ones, by mode
+    /// (`normal`, `visual`, `insert`), e.g. `normal = { "w" = "focus up" }`
+    pub bindings: HashMap<String, HashMap<String, String>>,
+}
+
+/// Focus mode (Shift+F on a node)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusModeConfig {
+    /// Relationship hops from the focused node that stay in focus
+    pub hops: u32,
+    /// Edge types followed, by palette key (e.g. `depends_on`); empty follows all
+    pub follow: Vec<String>,
+    /// Hide nodes out of focus instead of dimming them
+    pub hide_beyond: bool,
+    /// Opacity of nodes and edges out of focus
+    pub dimmed_opacity: f32,
+    /// Fade between focus states (ms)
+    pub fade_duration_ms: u64,
+    /// Focus roots kept to walk back through
+    pub max_breadcrumb: usize,
+}
+
+impl Default for FocusModeConfig {
+    fn default() -> Self {
+        Self {
+            hops: 2,
+            follow: Vec::new(),
+            hide_beyond: false,
+            dimmed_opacity: 0.15,
+            fade_duration_ms: 250,
+            max_breadcrumb: 32,
         }
     }
 }
@@ -459,7 +663,7 @@ pub enum EdgeCreationMode {
 }
 
 /// Performance configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PerformanceConfig {
     /// Enable GPU acceleration
     pub gpu_acceleration: bool,
@@ -473,6 +677,11 @@ pub struct PerformanceConfig {
     pub max_nodes: usize,
     /// Enable culling
     pub frustum_culling: bool,
+    /// How soon each node type loses detail and how it looks at each LOD
+    /// tier, e.g. keeping application thumbnails further away or shrinking
+    /// concept nodes to dots early
+    #[serde(default)]
+    pub lod_appearance: LodAppearance,
 }
 
 impl Default for PerformanceConfig {
@@ -484,6 +693,23 @@ impl Default for PerformanceConfig {
             lod_distances: [100.0, 500.0, 1000.0],
             max_nodes: 10000,
             frustum_culling: true,
+            lod_appearance: LodAppearance::default(),
+        }
+    }
+}
+
+impl PerformanceConfig {
+    /// Take the calibrated limits; unless `overwrite`, only those still at their defaults
+    pub fn apply_calibration(&mut self, defaults: &CalibratedDefaults, overwrite: bool) {
+        let builtin = Self::default();
+        if overwrite || self.max_nodes == builtin.max_nodes {
+            self.max_nodes = defaults.max_nodes;
+        }
+        if overwrite || self.lod_distances == builtin.lod_distances {
+            self.lod_distances = defaults.lod_distances;
+        }
+        if overwrite || self.max_fps == builtin.max_fps {
+            self.max_fps = defaults.max_fps;
         }
     }
 }
@@ -501,6 +727,9 @@ pub struct AIConfig {
     pub suggestions_enabled: bool,
     /// Suggestion frequency
     pub suggestion_frequency: u32,
+    /// How models use the hardware; set by calibration
+    #[serde(default)]
+    pub hardware_optimization: AiAcceleration,
 }
 
 impl Default for AIConfig {
@@ -511,6 +740,7 @@ impl Default for AIConfig {
             default_model: "llama3.2:latest".to_string(),
             suggestions_enabled: true,
             suggestion_frequency: 30,
+            hardware_optimization: AiAcceleration::default(),
         }
     }
 }
@@ -539,6 +769,59 @@ impl Default for WorkspaceConfig {
     }
 }
 
+/// Clustering configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusteringConfig {
+    /// Rules placing nodes in named clusters, evaluated as nodes are added or changed
+    #[serde(default)]
+    pub rules: Vec<ClusterRule>,
+    /// Directories scanned for git repositories to seed project clusters; `~` is the home directory
+    #[serde(default = "default_project_roots")]
+    pub project_roots: Vec<String>,
+}
+
+impl Default for ClusteringConfig {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            project_roots: default_project_roots(),
+        }
+    }
+}
+
+/// Places every node matching a condition in a named cluster
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClusterRule {
+    /// Rule name shown in settings
+    pub name: String,
+    /// Cluster the matching nodes go to; rules naming the same cluster share it
+    pub cluster: String,
+    /// Condition a node has to meet
+    #[serde(rename = "match")]
+    pub condition: ClusterRuleCondition,
+    /// Disabled rules are kept but not evaluated
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+/// What a cluster rule matches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ClusterRuleCondition {
+    /// Nodes of a type, e.g. `"file"` or `"application"`
+    NodeType { node_type: String },
+    /// File nodes at or below a path; `~` is the home directory
+    PathPrefix { path: String },
+    /// Nodes carrying a tag
+    Tag { tag: String },
+    /// Nodes meeting every condition
+    All { conditions: Vec<ClusterRuleCondition> },
+    /// Nodes meeting any condition
+    Any { conditions: Vec<ClusterRuleCondition> },
+    /// Nodes not meeting the condition
+    Not { condition: Box<ClusterRuleCondition> },
+}
+
 /// Accessibility configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccessibilityConfig {
@@ -569,6 +852,108 @@ impl Default for AccessibilityConfig {
     }
 }
 
+/// Monitor configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonitorsConfig {
+    /// How outputs are arranged: `automatic`, `extend`, `mirror` or `custom`
+    pub layout_mode: String,
+    /// Vertical alignment of extended outputs: `top`, `middle` or `bottom`
+    pub alignment: String,
+    /// Output made primary whenever it is connected
+    pub primary: Option<String>,
+    /// Settings of outputs seen before, by output ID, kept while they are unplugged
+    pub outputs: HashMap<String, OutputConfig>,
+}
+
+impl Default for MonitorsConfig {
+    fn default() -> Self {
+        Self {
+            layout_mode: "automatic".to_string(),
+            alignment: "middle".to_string(),
+            primary: None,
+            outputs: HashMap::new(),
+        }
+    }
+}
+
+/// Remembered settings of one output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputConfig {
+    /// Position in the global coordinate space, used by the `custom` layout
+    pub position: (i32, i32),
+    /// Scale factor
+    pub scale: f32,
+    /// Whether the output is used
+    pub enabled: bool,
+    /// Workspace shown on the output; `None` follows the active workspace
+    pub workspace: Option<String>,
+    /// Camera of the output into the graph; `None` starts from the default camera
+    pub camera: Option<OutputCamera>,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            position: (0, 0),
+            scale: 1.0,
+            enabled: true,
+            workspace: None,
+            camera: None,
+        }
+    }
+}
+
+/// Camera an output looks into the graph with
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OutputCamera {
+    /// Camera position in graph space
+    pub position: [f32; 3],
+    /// Point the camera looks at
+    pub target: [f32; 3],
+    /// Zoom level
+    pub zoom: f32,
+}
+
+/// Remote view server configuration
+///
+/// The stream is not encrypted, so the server only listens on loopback
+/// addresses; remote viewers connect through an SSH tunnel. The access token
+/// comes from `HORIZONOS_REMOTE_TOKEN` and is never stored in the config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteViewConfig {
+    /// Loopback address and port to listen on
+    pub bind_address: String,
+    /// Highest stream quality: `low`, `medium`, `high` or `lossless`
+    pub quality: String,
+    /// Lower quality automatically when a viewer's budget is exceeded
+    pub adaptive_quality: bool,
+    /// Workspace to stream; `None` streams the whole desktop
+    pub workspace: Option<String>,
+    /// Bandwidth budget per viewer in kilobytes per second
+    pub max_bandwidth_kbps: u32,
+    /// Let viewers ask for input control
+    pub allow_input: bool,
+    /// Maximum simultaneous viewers
+    pub max_viewers: usize,
+}
+
+impl Default for RemoteViewConfig {
+    fn default() -> Self {
+        Self {
+            bind_address: "127.0.0.1:5917".to_string(),
+            quality: "high".to_string(),
+            adaptive_quality: true,
+            workspace: None,
+            max_bandwidth_kbps: 2048,
+            allow_input: false,
+            max_viewers: 2,
+        }
+    }
+}
+
 /// Keyboard shortcut
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyboardShortcut {
@@ -585,8 +970,31 @@ pub struct KeyboardShortcut {
 pub enum ConfigChangeEvent {
     Initialized,
     ConfigReloaded,
+    /// The active theme was switched to, or its file edited
     ThemeChanged(String),
     ValueChanged(String),
+    LanguageChanged(String),
+    /// Hardware calibration changed performance or AI settings
+    Calibrated,
+    /// Per-node-type LOD appearance was edited
+    LodAppearanceChanged,
+    /// The monitor layout or an output's workspace or camera was changed
+    MonitorsChanged,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_project_roots() -> Vec<String> {
+    vec!["~/Projects".to_string(), "~/src".to_string(), "~/code".to_string()]
+}
+
+/// Apply the configured language to the global localizer
+fn apply_language(general: &GeneralConfig) {
+    if let Err(e) = horizonos_graph_i18n::set_language(general.language.as_deref()) {
+        log::warn!("Keeping current language: {}", e);
+    }
 }
 
 /// Get default keyboard shortcuts
@@ -617,9 +1025,68 @@ fn default_shortcuts() -> HashMap<String, KeyboardShortcut> {
         description: "Close focused window".to_string(),
     });
     
+    // Camera bookmarks of the active workspace
+    for (name, keys, description) in [
+        ("save_camera_bookmark", "Super+B", "Bookmark the current camera view"),
+        ("next_camera_bookmark", "Super+]", "Fly to the next camera bookmark"),
+        ("previous_camera_bookmark", "Super+[", "Fly to the previous camera bookmark"),
+    ] {
+        shortcuts.insert(name.to_string(), KeyboardShortcut {
+            keys: keys.to_string(),
+            action: name.to_string(),
+            description: description.to_string(),
+        });
+    }
+    
+    shortcuts.insert("toggle_cluster_group".to_string(), KeyboardShortcut {
+        keys: "Super+G".to_string(),
+        action: "toggle_cluster_group".to_string(),
+        description: "Collapse or expand the cluster of the selected node".to_string(),
+    });
+    
+    shortcuts.insert("toggle_minimap".to_string(), KeyboardShortcut {
+        keys: "Super+M".to_string(),
+        action: "toggle_minimap".to_string(),
+        description: "Show or hide the minimap".to_string(),
+    });
+    
+    // Pointer bindings; a button with several actions tries them in turn
+    for (name, keys, action, description) in [
+        ("pointer_select", "MouseLeft", "select", "Select and drag nodes, box-select on empty space"),
+        ("pointer_context_menu", "MouseRight", "context_menu", "Open the context menu of a node"),
+        ("pointer_rotate", "MouseRight", "rotate", "Rotate the camera"),
+        ("pointer_pan", "MouseMiddle", "pan", "Pan the camera"),
+    ] {
+        shortcuts.insert(name.to_string(), KeyboardShortcut {
+            keys: keys.to_string(),
+            action: action.to_string(),
+            description: description.to_string(),
+        });
+    }
+    
     shortcuts
 }
 
+/// Get default touch gesture bindings
+fn default_gestures() -> HashMap<String, String> {
+    [
+        ("pinch", "zoom"),
+        ("pan", "pan"),
+        ("two_finger_pan", "pan"),
+        ("rotate", "rotate"),
+        ("three_finger_swipe_left", "next_workspace"),
+        ("three_finger_swipe_right", "previous_workspace"),
+    ]
+    .into_iter()
+    .map(|(gesture, action)| (gesture.to_string(), action.to_string()))
+    .collect()
+}
+
+/// Whether shortcut keys name a mouse button, such as `MouseMiddle` or `Ctrl+MouseLeft`
+pub fn is_pointer_binding(keys: &str) -> bool {
+    keys.rsplit('+').next().is_some_and(|key| key.len() > 5 && key[..5].eq_ignore_ascii_case("mouse"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -642,4 +1109,153 @@ mod tests {
         let value: String = manager.get("test_key").unwrap();
         assert_eq!(value, "test_value");
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_calibration_keeps_edited_settings() {
+        let (manager, _rx) = ConfigManager::new();
+        manager.config.write().unwrap().performance.max_fps = 144;
+        let defaults = CalibratedDefaults::for_tier(horizonos_graph_performance::PerformanceTier::Low);
+        
+        manager.apply_calibration(&defaults, false).unwrap();
+        let config = manager.config();
+        assert_eq!(config.performance.max_fps, 144);
+        assert_eq!(config.performance.max_nodes, defaults.max_nodes);
+        assert_eq!(config.ai.hardware_optimization, AiAcceleration::PowerSaving);
+        
+        manager.apply_calibration(&defaults, true).unwrap();
+        assert_eq!(manager.config().performance.max_fps, defaults.max_fps);
+    }
+    
+    #[test]
+    fn test_lod_appearance_per_node_type() {
+        use horizonos_graph_performance::{LodLevel, Representation};
+        
+        let performance: PerformanceConfig = toml::from_str(r#"
+            gpu_acceleration = true
+            max_fps = 60
+            level_of_detail = true
+            lod_distances = [100.0, 500.0, 1000.0]
+            max_nodes = 10000
+            frustum_culling = true
+            
+            [lod_appearance.node_types.file]
+            min_representation = "icon"
+            labels = { low = true }
+        "#).unwrap();
+        let appearance = &performance.lod_appearance;
+        
+        let file = appearance.policy("file");
+        assert_eq!(file.representation(LodLevel::VeryLow), Some(Representation::Icon));
+        assert!(file.labels.shown_at(LodLevel::Low));
+        assert_eq!(appearance.policy("application").representation(LodLevel::VeryLow), Some(Representation::Dot));
+        assert_eq!(appearance.policy("application").representation(LodLevel::Medium), Some(Representation::Thumbnail));
+        
+        let (manager, mut rx) = ConfigManager::new();
+        manager.set_lod_appearance(appearance.clone()).unwrap();
+        assert!(matches!(*rx.borrow_and_update(), ConfigChangeEvent::LodAppearanceChanged));
+        assert_eq!(manager.config().performance.lod_appearance, *appearance);
+    }
+    
+    #[test]
+    fn test_lod_type_policies_scale_distances() {
+        use horizonos_graph_performance::{LodLevel, Representation};
+        
+        let performance: PerformanceConfig = toml::from_str(r#"
+            gpu_acceleration = true
+            max_fps = 60
+            level_of_detail = true
+            lod_distances = [100.0, 500.0, 1000.0]
+            max_nodes = 10000
+            frustum_culling = true
+            
+            [lod_appearance.node_types.application]
+            distance_scale = 2.0
+            thumbnail_from = "low"
+            
+            [lod_appearance.node_types.concept]
+            distance_scale = 0.5
+            dot_from = "low"
+        "#).unwrap();
+        let appearance = &performance.lod_appearance;
+        
+        let application = appearance.policy("application");
+        assert_eq!(application.scaled_distance(200.0), 100.0);
+        assert_eq!(application.representation(LodLevel::Low), Some(Representation::Thumbnail));
+        let concept = appearance.policy("concept");
+        assert_eq!(concept.scaled_distance(200.0), 400.0);
+        assert_eq!(concept.representation(LodLevel::Low), Some(Representation::Dot));
+        assert_eq!(concept.representation(LodLevel::High), Some(Representation::Full));
+        assert_eq!(appearance.policy("file").scaled_distance(200.0), 200.0);
+        
+        let (manager, mut rx) = ConfigManager::new();
+        manager.set_lod_type_policy("concept", concept.clone()).unwrap();
+        assert!(matches!(*rx.borrow_and_update(), ConfigChangeEvent::LodAppearanceChanged));
+        assert_eq!(manager.config().performance.lod_appearance.policy("concept"), concept);
+        
+        let mut config = manager.config();
+        config.performance.lod_appearance.default.distance_scale = 0.0;
+        assert!(ConfigValidator::new().validate(&config).is_err());
+    }
+    
+    #[test]
+    fn test_cluster_rules_parse_from_toml() {
+        let clustering: ClusteringConfig = toml::from_str(r#"
+            [[rules]]
+            name = "Foo files"
+            cluster = "Foo"
+            match = { kind = "all", conditions = [
+                { kind = "node_type", node_type = "file" },
+                { kind = "path_prefix", path = "~/projects/foo" },
+            ] }
+            
+            [[rules]]
+            name = "Work"
+            cluster = "Work"
+            enabled = false
+            match = { kind = "tag", tag = "work" }
+        "#).unwrap();
+        
+        assert_eq!(clustering.rules.len(), 2);
+        assert!(clustering.rules[0].enabled);
+        assert!(matches!(&clustering.rules[0].condition, ClusterRuleCondition::All { conditions } if conditions.len() == 2));
+        assert!(!clustering.rules[1].enabled);
+        assert_eq!(clustering.rules[1].condition, ClusterRuleCondition::Tag { tag: "work".to_string() });
+        assert_eq!(clustering.project_roots, ClusteringConfig::default().project_roots);
+        
+        let clustering: ClusteringConfig = toml::from_str(r#"project_roots = ["~/work", "/srv/git"]"#).unwrap();
+        assert!(clustering.rules.is_empty());
+        assert_eq!(clustering.project_roots, vec!["~/work".to_string(), "/srv/git".to_string()]);
+    }
+    
+    #[test]
+    fn test_focus_mode_parses_from_toml() {
+        let interaction: InteractionConfig = toml::from_str(r#"
+            mouse_sensitivity = 1.0
+            scroll_speed = 1.0
+            double_click_interval = 400
+            drag_threshold = 5.0
+            edge_creation_mode = "DragFromNode"
+            
+            [focus_mode]
+            hops = 3
+            follow = ["contains", "depends_on"]
+        "#).unwrap();
+        
+        assert_eq!(interaction.focus_mode.hops, 3);
+        assert_eq!(interaction.focus_mode.follow, ["contains", "depends_on"]);
+        assert_eq!(interaction.focus_mode.fade_duration_ms, FocusModeConfig::default().fade_duration_ms);
+    }
+    
+    #[test]
+    fn test_edge_validation_parses_from_toml() {
+        let validation: horizonos_graph_edges::EdgeValidationConfig = toml::from_str(r#"
+            mode = "off"
+            
+            [rules.works_on]
+            sources = []
+        "#).unwrap();
+        
+        assert_eq!(validation.mode, horizonos_graph_edges::ValidationMode::Off);
+        assert_eq!(validation.rules["works_on"], horizonos_graph_edges::EdgeRule::default());
+    }
+}
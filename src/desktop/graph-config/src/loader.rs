@@ -97,6 +97,9 @@ impl ConfigLoader {
             if let Some(debug) = general.debug {
                 config.general.debug = debug;
             }
+            if let Some(language) = general.language {
+                config.general.language = Some(language);
+            }
         }
         
         // Merge appearance settings
@@ -109,6 +112,29 @@ impl ConfigLoader {
             }
         }
         
+        // Cluster rules replace the base rules as a whole, so a rule can be removed
+        if let Some(clustering) = overlay.clustering {
+            config.clustering = clustering;
+        }
+        
+        // Monitor layout replaces the base one; outputs override by ID
+        if let Some(mut monitors) = overlay.monitors {
+            monitors.outputs = config.monitors.outputs.drain().chain(monitors.outputs).collect();
+            config.monitors = monitors;
+        }
+        
+        if let Some(remote_view) = overlay.remote_view {
+            config.remote_view = remote_view;
+        }
+        
+        // Shortcuts and gesture bindings override the base ones by name
+        if let Some(shortcuts) = overlay.shortcuts {
+            config.shortcuts.extend(shortcuts);
+        }
+        if let Some(gestures) = overlay.gestures {
+            config.gestures.extend(gestures);
+        }
+        
         // Merge custom values
         if let Some(custom) = overlay.custom {
             config.custom.extend(custom);
@@ -231,8 +257,12 @@ pub struct PartialConfig {
     pub performance: Option<crate::PerformanceConfig>,
     pub ai: Option<crate::AIConfig>,
     pub workspace: Option<crate::WorkspaceConfig>,
+    pub clustering: Option<crate::ClusteringConfig>,
     pub accessibility: Option<crate::AccessibilityConfig>,
+    pub monitors: Option<crate::MonitorsConfig>,
+    pub remote_view: Option<crate::RemoteViewConfig>,
     pub shortcuts: Option<std::collections::HashMap<String, crate::KeyboardShortcut>>,
+    pub gestures: Option<std::collections::HashMap<String, String>>,
     pub custom: Option<std::collections::HashMap<String, serde_json::Value>>,
 }
 
@@ -244,6 +274,7 @@ pub struct PartialGeneralConfig {
     pub browser: Option<String>,
     pub log_level: Option<String>,
     pub debug: Option<bool>,
+    pub language: Option<String>,
 }
 
 /// Partial appearance configuration
@@ -348,4 +379,31 @@ mod tests {
         let yaml_result = loader.serialize_config(&config, ConfigFormat::Yaml);
         assert!(yaml_result.is_ok());
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_monitor_layout_round_trip() {
+        let loader = ConfigLoader::new();
+        let mut config = GraphDesktopConfig::default();
+        config.monitors.layout_mode = "custom".to_string();
+        config.monitors.outputs.insert("HDMI-A-1".to_string(), crate::OutputConfig {
+            position: (1920, 0),
+            workspace: Some("work".to_string()),
+            camera: Some(crate::OutputCamera { position: [5.0, 0.0, 20.0], target: [5.0, 0.0, 0.0], zoom: 2.0 }),
+            ..Default::default()
+        });
+        
+        let toml = loader.serialize_config(&config, ConfigFormat::Toml).unwrap();
+        let parsed = loader.parse_config(&toml, ConfigFormat::Toml).unwrap();
+        assert_eq!(parsed.monitors, config.monitors);
+        
+        // Outputs of an overlay are added to the remembered ones
+        let mut overlay_monitors = crate::MonitorsConfig::default();
+        overlay_monitors.outputs.insert("DP-1".to_string(), crate::OutputConfig::default());
+        let overlay: PartialConfig = serde_json::from_value(serde_json::json!({
+            "monitors": overlay_monitors,
+        })).unwrap();
+        let merged = loader.merge_configs(config, overlay);
+        assert_eq!(merged.monitors.layout_mode, "automatic");
+        assert_eq!(merged.monitors.outputs.len(), 2);
+    }
+}
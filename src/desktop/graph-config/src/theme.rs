@@ -56,38 +56,49 @@ impl ThemeManager {
     
     /// Load themes from directory
     pub async fn load_themes(&mut self, themes_dir: &Path) -> Result<()> {
+        for (name, theme) in Self::read_themes(themes_dir).await? {
+            self.themes.insert(name, theme);
+        }
+        
+        Ok(())
+    }
+    
+    /// Read all theme files of a directory, skipping unreadable ones
+    pub async fn read_themes(themes_dir: &Path) -> Result<Vec<(String, Theme)>> {
         use tokio::fs;
         
+        let mut themes = Vec::new();
         let mut entries = fs::read_dir(themes_dir).await?;
         
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
             
             if path.extension().and_then(|s| s.to_str()) == Some("toml") {
-                match fs::read_to_string(&path).await {
-                    Ok(content) => {
-                        match toml::from_str::<Theme>(&content) {
-                            Ok(theme) => {
-                                let name = path.file_stem()
-                                    .and_then(|s| s.to_str())
-                                    .unwrap_or("unknown")
-                                    .to_string();
-                                
-                                self.themes.insert(name, theme);
-                            }
-                            Err(e) => {
-                                log::warn!("Failed to parse theme file {:?}: {}", path, e);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        log::warn!("Failed to read theme file {:?}: {}", path, e);
-                    }
+                match Self::read_theme_file(&path).await {
+                    Ok(theme) => themes.push(theme),
+                    Err(e) => log::warn!("Failed to load theme file {:?}: {}", path, e),
                 }
             }
         }
         
-        Ok(())
+        Ok(themes)
+    }
+    
+    /// Read a theme file; the theme is named after the file stem
+    pub async fn read_theme_file(path: &Path) -> Result<(String, Theme)> {
+        let content = tokio::fs::read_to_string(path).await?;
+        let theme = toml::from_str::<Theme>(&content)?;
+        let name = path.file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        
+        Ok((name, theme))
+    }
+    
+    /// Add or replace a theme
+    pub fn insert_theme(&mut self, name: impl Into<String>, theme: Theme) {
+        self.themes.insert(name.into(), theme);
     }
     
     /// Get theme by name
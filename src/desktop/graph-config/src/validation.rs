@@ -41,6 +41,12 @@ impl ConfigValidator {
         // Accessibility validation
         self.validate_accessibility(&config.accessibility)?;
         
+        // Monitor validation
+        self.validate_monitors(&config.monitors)?;
+        
+        // Remote view validation
+        self.validate_remote_view(&config.remote_view)?;
+        
         // Keyboard shortcuts validation
         self.validate_shortcuts(&config.shortcuts)?;
         
@@ -57,6 +63,9 @@ impl ConfigValidator {
                 valid_levels
             ));
         }
+        if let Some(language) = &config.language {
+            horizonos_graph_i18n::parse_locale(language)?;
+        }
         Ok(())
     }
     
@@ -110,6 +119,17 @@ impl ConfigValidator {
         if config.label_size <= 0.0 {
             return Err(anyhow::anyhow!("Label size must be positive"));
         }
+        if config.layout_algorithm.trim().is_empty() {
+            return Err(anyhow::anyhow!("Layout algorithm must be named"));
+        }
+        
+        // Validate edge routing
+        if !(0.0..=1.0).contains(&config.edge_routing.quality) {
+            return Err(anyhow::anyhow!("Edge routing quality must be between 0 and 1"));
+        }
+        if config.edge_routing.clearance < 0.0 {
+            return Err(anyhow::anyhow!("Edge routing clearance must not be negative"));
+        }
         
         // Validate physics
         if config.physics.friction < 0.0 || config.physics.friction > 1.0 {
@@ -151,6 +171,15 @@ impl ConfigValidator {
             return Err(anyhow::anyhow!("LOD distances must be in ascending order"));
         }
         
+        let appearance = &config.lod_appearance;
+        let policies = std::iter::once(("default", &appearance.default))
+            .chain(appearance.node_types.iter().map(|(node_type, policy)| (node_type.as_str(), policy)));
+        for (node_type, policy) in policies {
+            if policy.distance_scale.is_nan() || policy.distance_scale <= 0.0 {
+                return Err(anyhow::anyhow!("LOD distance scale of {} nodes must be positive", node_type));
+            }
+        }
+        
         Ok(())
     }
     
@@ -191,11 +220,72 @@ impl ConfigValidator {
         Ok(())
     }
     
+    /// Validate monitor configuration
+    fn validate_monitors(&self, config: &crate::MonitorsConfig) -> Result<()> {
+        let valid_modes = ["automatic", "extend", "mirror", "custom"];
+        if !valid_modes.contains(&config.layout_mode.as_str()) {
+            return Err(anyhow::anyhow!(
+                "Invalid monitor layout mode: {}. Must be one of: {:?}",
+                config.layout_mode,
+                valid_modes
+            ));
+        }
+        let valid_alignments = ["top", "middle", "bottom"];
+        if !valid_alignments.contains(&config.alignment.as_str()) {
+            return Err(anyhow::anyhow!(
+                "Invalid monitor alignment: {}. Must be one of: {:?}",
+                config.alignment,
+                valid_alignments
+            ));
+        }
+        for (id, output) in &config.outputs {
+            if output.scale <= 0.0 {
+                return Err(anyhow::anyhow!("Scale of output {} must be positive", id));
+            }
+            if output.camera.is_some_and(|camera| camera.zoom <= 0.0) {
+                return Err(anyhow::anyhow!("Camera zoom of output {} must be positive", id));
+            }
+        }
+        Ok(())
+    }
+    
+    /// Validate remote view configuration
+    fn validate_remote_view(&self, config: &crate::RemoteViewConfig) -> Result<()> {
+        let address: std::net::SocketAddr = config.bind_address.parse().map_err(|_| {
+            anyhow::anyhow!("Invalid remote view bind address: {}", config.bind_address)
+        })?;
+        if !address.ip().is_loopback() {
+            return Err(anyhow::anyhow!(
+                "Remote view must bind to a loopback address, not {}; connect through an SSH tunnel",
+                address
+            ));
+        }
+        let valid_qualities = ["low", "medium", "high", "lossless"];
+        if !valid_qualities.contains(&config.quality.as_str()) {
+            return Err(anyhow::anyhow!(
+                "Invalid remote view quality: {}. Must be one of: {:?}",
+                config.quality,
+                valid_qualities
+            ));
+        }
+        if config.max_bandwidth_kbps == 0 {
+            return Err(anyhow::anyhow!("Remote view bandwidth must be positive"));
+        }
+        if config.max_viewers == 0 {
+            return Err(anyhow::anyhow!("Remote view must allow at least one viewer"));
+        }
+        Ok(())
+    }
+    
     /// Validate keyboard shortcuts
     fn validate_shortcuts(&self, shortcuts: &HashMap<String, crate::KeyboardShortcut>) -> Result<()> {
         // Check for duplicate key bindings
         let mut seen_keys = HashMap::new();
         for (name, shortcut) in shortcuts {
+            // A mouse button may carry several pointer actions, tried in turn
+            if crate::is_pointer_binding(&shortcut.keys) {
+                continue;
+            }
             if let Some(existing) = seen_keys.get(&shortcut.keys) {
                 return Err(anyhow::anyhow!(
                     "Duplicate key binding '{}' for '{}' and '{}'",
@@ -337,4 +427,39 @@ mod tests {
         
         assert!(validator.validate(&config).is_ok());
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_pointer_bindings_share_buttons() {
+        let validator = ConfigValidator::new();
+        let config = GraphDesktopConfig::default();
+        
+        // The right button opens context menus and rotates the camera
+        let right = config.shortcuts.values().filter(|shortcut| shortcut.keys == "MouseRight").count();
+        assert_eq!(right, 2);
+        assert!(crate::is_pointer_binding("Ctrl+MouseLeft"));
+        assert!(!crate::is_pointer_binding("Ctrl+M"));
+        assert!(validator.validate(&config).is_ok());
+    }
+    
+    #[test]
+    fn test_remote_view_must_stay_local() {
+        let validator = ConfigValidator::new();
+        let mut config = GraphDesktopConfig::default();
+        
+        config.remote_view.bind_address = "[::1]:5917".to_string();
+        assert!(validator.validate(&config).is_ok());
+        
+        // The stream is unencrypted, so it is never exposed to the network
+        config.remote_view.bind_address = "0.0.0.0:5917".to_string();
+        assert!(validator.validate(&config).is_err());
+        config.remote_view.bind_address = "localhost".to_string();
+        assert!(validator.validate(&config).is_err());
+        
+        config.remote_view.bind_address = "127.0.0.1:5917".to_string();
+        config.remote_view.quality = "ultra".to_string();
+        assert!(validator.validate(&config).is_err());
+        config.remote_view.quality = "lossless".to_string();
+        config.remote_view.max_viewers = 0;
+        assert!(validator.validate(&config).is_err());
+    }
+}
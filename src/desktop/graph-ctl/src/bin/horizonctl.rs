@@ -0,0 +1,263 @@
+//! Command-line control for HorizonOS graph desktop
+//!
+//! Usage: horizonctl [--json] [--endpoint <file>] <command>
+//!
+//! Run `horizonctl --help` for the commands and
+//! `horizonctl completions <shell>` to generate shell completions.
+
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use horizonos_graph_ctl::{ControlClient, CtlError, Endpoint, ExportFormat, Request};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "horizonctl", version, about = "Control a running HorizonOS graph desktop")]
+struct Cli {
+    /// Print machine-readable JSON instead of text
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Endpoint file written by the desktop
+    #[arg(long, global = true, value_name = "FILE")]
+    endpoint: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Show desktop status
+    Status,
+    /// Create or delete nodes
+    #[command(subcommand)]
+    Node(NodeCommand),
+    /// Create file nodes for paths
+    Import {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Import directory contents
+        #[arg(short, long)]
+        recursive: bool,
+    },
+    /// List or switch workspaces
+    #[command(subcommand)]
+    Workspace(WorkspaceCommand),
+    /// Search nodes
+    Query {
+        query: String,
+        #[arg(short, long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Run a layout algorithm
+    Layout {
+        /// Algorithm name, e.g. force-directed, circular or hierarchical
+        algorithm: String,
+        /// Only lay out these nodes
+        #[arg(long = "node", value_name = "ID")]
+        nodes: Vec<u64>,
+    },
+    /// Export the graph
+    Export {
+        #[arg(short, long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+        /// Write to a file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Stream desktop events to stdout
+    Watch {
+        /// Only show events of this kind or prefix, e.g. `node` or `workspace.switched`
+        #[arg(short, long = "event", value_name = "KIND")]
+        events: Vec<String>,
+    },
+    /// Print shell completions
+    Completions { shell: Shell },
+}
+
+#[derive(Subcommand)]
+enum NodeCommand {
+    /// Create a node and print its id
+    Create {
+        /// Node type, e.g. file, concept, task or url
+        node_type: String,
+        name: String,
+        /// File path or URL the node refers to
+        #[arg(long)]
+        path: Option<String>,
+        /// Position as x,y,z
+        #[arg(long, value_parser = parse_position, value_name = "X,Y,Z")]
+        at: Option<[f32; 3]>,
+    },
+    /// Delete nodes
+    Delete {
+        #[arg(required = true)]
+        ids: Vec<u64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum WorkspaceCommand {
+    /// List workspaces
+    List,
+    /// Switch to a workspace by id or name
+    Switch { workspace: String },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Json,
+    Dot,
+    Graphml,
+}
+
+impl From<Format> for ExportFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Json => ExportFormat::Json,
+            Format::Dot => ExportFormat::Dot,
+            Format::Graphml => ExportFormat::GraphMl,
+        }
+    }
+}
+
+fn parse_position(value: &str) -> Result<[f32; 3], String> {
+    let parts: Vec<f32> = value
+        .split(',')
+        .map(|part| part.trim().parse::<f32>().map_err(|e| format!("'{}': {}", part, e)))
+        .collect::<Result<_, _>>()?;
+    <[f32; 3]>::try_from(parts).map_err(|_| "expected three comma-separated numbers".to_string())
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    if let Command::Completions { shell } = cli.command {
+        clap_complete::generate(shell, &mut Cli::command(), "horizonctl", &mut std::io::stdout());
+        return ExitCode::SUCCESS;
+    }
+
+    match run(&cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            if cli.json {
+                let error = match &e {
+                    CtlError::Api(error) => serde_json::to_value(error).unwrap_or(Value::Null),
+                    other => serde_json::json!({ "code": "connection", "message": other.to_string() }),
+                };
+                println!("{}", serde_json::json!({ "error": error }));
+            } else {
+                eprintln!("horizonctl: {}", e);
+            }
+            match e {
+                CtlError::Api(_) => ExitCode::from(1),
+                _ => ExitCode::from(3),
+            }
+        }
+    }
+}
+
+async fn run(cli: &Cli) -> Result<(), CtlError> {
+    let endpoint_path = cli.endpoint.clone().unwrap_or_else(Endpoint::default_path);
+    let mut client = ControlClient::connect(&Endpoint::read(&endpoint_path)?).await?;
+
+    let request = match &cli.command {
+        Command::Status => Request::Status,
+        Command::Node(NodeCommand::Create { node_type, name, path, at }) => Request::CreateNode {
+            node_type: node_type.clone(),
+            name: name.clone(),
+            path: path.clone(),
+            position: *at,
+        },
+        Command::Node(NodeCommand::Delete { ids }) => Request::DeleteNodes { ids: ids.clone() },
+        Command::Import { paths, recursive } => Request::ImportFiles {
+            // The desktop may run with another working directory
+            paths: paths.iter().map(|p| std::path::absolute(p).unwrap_or_else(|_| p.clone())).collect(),
+            recursive: *recursive,
+        },
+        Command::Workspace(WorkspaceCommand::List) => Request::ListWorkspaces,
+        Command::Workspace(WorkspaceCommand::Switch { workspace }) => Request::SwitchWorkspace { workspace: workspace.clone() },
+        Command::Query { query, limit } => Request::Query { query: query.clone(), limit: *limit },
+        Command::Layout { algorithm, nodes } => Request::ApplyLayout { algorithm: algorithm.clone(), nodes: nodes.clone() },
+        Command::Export { format, .. } => Request::ExportGraph { format: (*format).into() },
+        Command::Watch { events } => return watch(&mut client, events.clone(), cli.json).await,
+        Command::Completions { .. } => unreachable!("handled before connecting"),
+    };
+    let result = client.call(request).await?;
+
+    if let Command::Export { output, .. } = &cli.command {
+        let content = result.get("content").and_then(Value::as_str).unwrap_or_default();
+        return match output {
+            Some(path) => Ok(std::fs::write(path, content)?),
+            None => {
+                print!("{}", content);
+                Ok(())
+            }
+        };
+    }
+
+    if cli.json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        print_text(&cli.command, &result);
+    }
+    Ok(())
+}
+
+async fn watch(client: &mut ControlClient, kinds: Vec<String>, json: bool) -> Result<(), CtlError> {
+    client.subscribe(kinds).await?;
+    while let Some(event) = client.next_event().await? {
+        if json {
+            // One event per line for piping into jq and friends
+            println!("{}", serde_json::to_string(&event)?);
+        } else {
+            println!("{} {} {}", event.timestamp.format("%H:%M:%S"), event.kind, event.data);
+        }
+    }
+    Ok(())
+}
+
+fn print_text(command: &Command, result: &Value) {
+    let field = |value: &Value, key: &str| value.get(key).map(display).unwrap_or_default();
+    match command {
+        Command::Node(NodeCommand::Create { .. }) => println!("{}", field(result, "id")),
+        Command::Workspace(WorkspaceCommand::List) => {
+            for workspace in result.as_array().into_iter().flatten() {
+                let marker = if workspace.get("active").and_then(Value::as_bool).unwrap_or(false) { "*" } else { " " };
+                println!("{} {}\t{}", marker, field(workspace, "id"), field(workspace, "name"));
+            }
+        }
+        Command::Query { .. } => {
+            for hit in result.as_array().into_iter().flatten() {
+                println!("{}\t{}\t{}", field(hit, "id"), field(hit, "node_type"), field(hit, "name"));
+                if let Some(snippet) = hit.get("snippet").and_then(Value::as_str) {
+                    println!("\t{}", snippet);
+                }
+            }
+        }
+        Command::Import { .. } => {
+            for node in result.get("created").and_then(Value::as_array).into_iter().flatten() {
+                println!("{}\t{}", field(node, "id"), field(node, "path"));
+            }
+        }
+        _ => match result {
+            Value::Object(fields) => {
+                for (key, value) in fields {
+                    println!("{}: {}", key, display(value));
+                }
+            }
+            Value::Null => {}
+            other => println!("{}", display(other)),
+        },
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items.iter().map(display).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,91 @@
+//! Connecting to a running desktop
+
+use crate::protocol::{ClientMessage, DesktopEvent, Request, ServerMessage};
+use crate::{CtlError, Endpoint};
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::VecDeque;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{header, HeaderValue};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+/// Client for the control API
+pub struct ControlClient {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    next_id: u64,
+    /// Events received while waiting for a reply
+    pending_events: VecDeque<DesktopEvent>,
+}
+
+impl ControlClient {
+    /// Connect to the desktop described by the default endpoint file
+    pub async fn connect_default() -> Result<Self, CtlError> {
+        Self::connect(&Endpoint::read(&Endpoint::default_path())?).await
+    }
+
+    /// Connect to `endpoint`
+    pub async fn connect(endpoint: &Endpoint) -> Result<Self, CtlError> {
+        let mut request = endpoint.url.as_str().into_client_request()?;
+        let authorization = HeaderValue::from_str(&format!("Bearer {}", endpoint.token))
+            .map_err(|e| CtlError::Protocol(format!("Invalid token: {}", e)))?;
+        request.headers_mut().insert(header::AUTHORIZATION, authorization);
+        let (socket, _) = tokio_tungstenite::connect_async(request).await?;
+        Ok(Self { socket, next_id: 1, pending_events: VecDeque::new() })
+    }
+
+    /// Send a request and wait for its reply
+    pub async fn call(&mut self, request: Request) -> Result<Value, CtlError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let message = serde_json::to_string(&ClientMessage { id, request })?;
+        self.socket.send(Message::Text(message)).await?;
+
+        loop {
+            match self.receive().await? {
+                Some(ServerMessage::Reply { id: reply_id, result, error }) if reply_id == id => {
+                    return match error {
+                        Some(error) => Err(error.into()),
+                        None => Ok(result.unwrap_or(Value::Null)),
+                    };
+                }
+                Some(ServerMessage::Reply { id: reply_id, .. }) => {
+                    log::debug!("Ignoring reply to unknown request {}", reply_id);
+                }
+                Some(ServerMessage::Event(event)) => self.pending_events.push_back(event),
+                None => return Err(CtlError::Connection("Desktop closed the connection".to_string())),
+            }
+        }
+    }
+
+    /// Receive events whose kind starts with one of `kinds`, or all events when empty
+    pub async fn subscribe(&mut self, kinds: Vec<String>) -> Result<(), CtlError> {
+        self.call(Request::Subscribe { kinds }).await.map(|_| ())
+    }
+
+    /// Next event after [`subscribe`](Self::subscribe); `None` when the desktop disconnects
+    pub async fn next_event(&mut self) -> Result<Option<DesktopEvent>, CtlError> {
+        if let Some(event) = self.pending_events.pop_front() {
+            return Ok(Some(event));
+        }
+        loop {
+            match self.receive().await? {
+                Some(ServerMessage::Event(event)) => return Ok(Some(event)),
+                Some(ServerMessage::Reply { .. }) => continue,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    async fn receive(&mut self) -> Result<Option<ServerMessage>, CtlError> {
+        loop {
+            match self.socket.next().await {
+                Some(Ok(Message::Text(text))) => return Ok(Some(serde_json::from_str(&text)?)),
+                Some(Ok(Message::Close(_))) | None => return Ok(None),
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => return Err(e.into()),
+            }
+        }
+    }
+}
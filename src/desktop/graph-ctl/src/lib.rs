@@ -0,0 +1,104 @@
+//! Control API for HorizonOS graph desktop
+//!
+//! The desktop exposes a JSON API over a local WebSocket so scripts and the
+//! `horizonctl` tool can drive it:
+//! - [`protocol`] defines the requests, replies and desktop events
+//! - [`server`] hosts the API; the desktop supplies a [`ControlHandler`]
+//! - [`client`] connects to a running desktop
+//!
+//! The server listens on the loopback interface only and writes its address
+//! and a per-session token to `$XDG_RUNTIME_DIR/horizonos/control.json`
+//! (readable by the user only). Clients must present the token when
+//! connecting.
+
+pub mod client;
+pub mod protocol;
+pub mod server;
+
+pub use client::ControlClient;
+pub use protocol::*;
+pub use server::{ControlHandler, ControlServer};
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Control API errors
+#[derive(Error, Debug)]
+pub enum CtlError {
+    #[error("Desktop is not running (no endpoint at {0})")]
+    NotRunning(PathBuf),
+
+    #[error("Connection error: {0}")]
+    Connection(String),
+
+    #[error("Protocol error: {0}")]
+    Protocol(String),
+
+    #[error("{0}")]
+    Api(#[from] ApiError),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for CtlError {
+    fn from(error: tokio_tungstenite::tungstenite::Error) -> Self {
+        CtlError::Connection(error.to_string())
+    }
+}
+
+/// Where a running desktop can be reached
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Endpoint {
+    /// WebSocket URL
+    pub url: String,
+    /// Token clients present as `Authorization: Bearer <token>`
+    pub token: String,
+}
+
+impl Endpoint {
+    /// Default location of the endpoint file
+    pub fn default_path() -> PathBuf {
+        dirs::runtime_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("horizonos")
+            .join("control.json")
+    }
+
+    /// Read the endpoint written by a running desktop
+    pub fn read(path: &Path) -> Result<Self, CtlError> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(CtlError::NotRunning(path.to_path_buf())),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Write the endpoint so only the current user can read it
+    pub fn write(&self, path: &Path) -> Result<(), CtlError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path)?;
+        // A file left over from an earlier session keeps its old mode otherwise
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        }
+        serde_json::to_writer_pretty(&mut file, self)?;
+        Ok(())
+    }
+}
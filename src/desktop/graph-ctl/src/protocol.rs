@@ -0,0 +1,223 @@
+//! Messages exchanged with the control API
+//!
+//! Every client message carries an `id` echoed in the reply, so replies can
+//! be matched while desktop events are interleaved on the same connection:
+//!
+//! ```json
+//! {"id": 1, "method": "switch_workspace", "params": {"workspace": "Research"}}
+//! {"type": "reply", "id": 1, "result": {"workspace": "Research"}}
+//! {"type": "event", "kind": "workspace.switched", "timestamp": "...", "data": {...}}
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Graph export formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Dot,
+    GraphMl,
+}
+
+/// Operations offered by the desktop
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum Request {
+    /// Desktop version and counters
+    Status,
+    /// Create a node; replies with `{"id": <node id>}`
+    CreateNode {
+        node_type: String,
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        position: Option<[f32; 3]>,
+    },
+    /// Delete nodes; replies with `{"deleted": [<node id>...]}`
+    DeleteNodes { ids: Vec<u64> },
+    /// Create file nodes; replies with `{"created": [{"id", "path"}...]}`
+    ImportFiles {
+        paths: Vec<PathBuf>,
+        #[serde(default)]
+        recursive: bool,
+    },
+    /// List workspaces; replies with `[{"id", "name", "active"}...]`
+    ListWorkspaces,
+    /// Switch to a workspace by id or name
+    SwitchWorkspace { workspace: String },
+    /// Search nodes; replies with `[{"id", "name", "node_type", ...}...]`
+    Query {
+        query: String,
+        #[serde(default = "default_limit")]
+        limit: usize,
+    },
+    /// Run a layout algorithm on the given nodes, or on all nodes when empty
+    ApplyLayout {
+        algorithm: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        nodes: Vec<u64>,
+    },
+    /// Export the graph; replies with `{"format", "content"}`
+    ExportGraph { format: ExportFormat },
+    /// Stream events whose kind starts with one of `kinds`, or all events when empty
+    Subscribe {
+        #[serde(default)]
+        kinds: Vec<String>,
+    },
+}
+
+fn default_limit() -> usize {
+    20
+}
+
+/// A request with its correlation id
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClientMessage {
+    pub id: u64,
+    #[serde(flatten)]
+    pub request: Request,
+}
+
+/// Why a request failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The message could not be parsed or has invalid parameters
+    InvalidRequest,
+    /// A referenced node or workspace does not exist
+    NotFound,
+    /// The desktop does not support the operation
+    Unsupported,
+    /// The operation was attempted and failed
+    Failed,
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ErrorCode::InvalidRequest => "invalid request",
+            ErrorCode::NotFound => "not found",
+            ErrorCode::Unsupported => "unsupported",
+            ErrorCode::Failed => "failed",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Error reply from the desktop
+#[derive(Error, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[error("{code}: {message}")]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidRequest, message)
+    }
+
+    pub fn failed(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Failed, message)
+    }
+}
+
+/// Something that happened on the desktop
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DesktopEvent {
+    /// Dotted event kind, e.g. `node.created` or `workspace.switched`
+    pub kind: String,
+    pub timestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub data: Value,
+}
+
+impl DesktopEvent {
+    pub fn new(kind: impl Into<String>, data: Value) -> Self {
+        Self { kind: kind.into(), timestamp: Utc::now(), data }
+    }
+
+    /// Whether the event is selected by a subscription filter
+    ///
+    /// `node` selects `node.created` and `node.deleted`; an empty filter selects everything.
+    pub fn matches(&self, kinds: &[String]) -> bool {
+        kinds.is_empty()
+            || kinds.iter().any(|kind| {
+                self.kind == *kind || (self.kind.starts_with(kind.as_str()) && self.kind[kind.len()..].starts_with('.'))
+            })
+    }
+}
+
+/// Messages sent by the desktop
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Reply {
+        id: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        result: Option<Value>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<ApiError>,
+    },
+    Event(DesktopEvent),
+}
+
+impl ServerMessage {
+    pub fn reply(id: u64, outcome: Result<Value, ApiError>) -> Self {
+        match outcome {
+            Ok(result) => ServerMessage::Reply { id, result: Some(result), error: None },
+            Err(error) => ServerMessage::Reply { id, result: None, error: Some(error) },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_wire_format() {
+        let message = ClientMessage { id: 3, request: Request::SwitchWorkspace { workspace: "Research".into() } };
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            json!({"id": 3, "method": "switch_workspace", "params": {"workspace": "Research"}})
+        );
+        let parsed: ClientMessage = serde_json::from_value(json!({"id": 4, "method": "query", "params": {"query": "notes"}})).unwrap();
+        assert_eq!(parsed.request, Request::Query { query: "notes".into(), limit: 20 });
+        let parsed: ClientMessage = serde_json::from_value(json!({"id": 5, "method": "list_workspaces"})).unwrap();
+        assert_eq!(parsed.request, Request::ListWorkspaces);
+
+        let reply = ServerMessage::reply(3, Err(ApiError::not_found("no workspace 'Research'")));
+        assert_eq!(
+            serde_json::to_value(&reply).unwrap(),
+            json!({"type": "reply", "id": 3, "error": {"code": "not_found", "message": "no workspace 'Research'"}})
+        );
+    }
+
+    #[test]
+    fn test_event_filters() {
+        let event = DesktopEvent::new("node.created", Value::Null);
+        assert!(event.matches(&[]));
+        assert!(event.matches(&["node".to_string()]));
+        assert!(event.matches(&["node.created".to_string()]));
+        assert!(!event.matches(&["nodes".to_string()]));
+        assert!(!event.matches(&["no".to_string()]));
+        assert!(!event.matches(&["workspace".to_string()]));
+    }
+}
@@ -0,0 +1,232 @@
+//! Hosting the control API inside the desktop
+
+use crate::protocol::{ApiError, ClientMessage, DesktopEvent, Request, ServerMessage};
+use crate::{CtlError, Endpoint};
+use async_trait::async_trait;
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request as HandshakeRequest, Response};
+use tokio_tungstenite::tungstenite::http::{header, StatusCode};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Events buffered per subscriber before slow clients start missing some
+const EVENT_BUFFER: usize = 256;
+
+/// Performs control requests on the desktop
+///
+/// `Subscribe` is handled by the server and never reaches the handler.
+#[async_trait]
+pub trait ControlHandler: Send + Sync + 'static {
+    async fn handle(&self, request: Request) -> Result<Value, ApiError>;
+}
+
+/// Serves the control API on a loopback WebSocket
+pub struct ControlServer {
+    listener: TcpListener,
+    endpoint: Endpoint,
+    endpoint_path: Option<PathBuf>,
+    events: broadcast::Sender<DesktopEvent>,
+}
+
+impl ControlServer {
+    /// Listen on an ephemeral loopback port with a fresh token
+    pub async fn bind() -> Result<Self, CtlError> {
+        Self::bind_addr(SocketAddr::from(([127, 0, 0, 1], 0))).await
+    }
+
+    /// Listen on `addr`, which must be a loopback address
+    pub async fn bind_addr(addr: SocketAddr) -> Result<Self, CtlError> {
+        if !addr.ip().is_loopback() {
+            return Err(CtlError::Connection(format!("Refusing to expose the control API on {}", addr)));
+        }
+        let listener = TcpListener::bind(addr).await?;
+        let endpoint = Endpoint {
+            url: format!("ws://{}", listener.local_addr()?),
+            token: uuid::Uuid::new_v4().simple().to_string(),
+        };
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        Ok(Self { listener, endpoint, endpoint_path: None, events })
+    }
+
+    /// Address and token clients need
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+
+    /// Write the endpoint file clients look for; it is removed when the server stops
+    pub fn publish_endpoint(&mut self, path: PathBuf) -> Result<(), CtlError> {
+        self.endpoint.write(&path)?;
+        self.endpoint_path = Some(path);
+        Ok(())
+    }
+
+    /// Sender for desktop events; clone it into the parts of the desktop that emit events
+    pub fn event_sender(&self) -> broadcast::Sender<DesktopEvent> {
+        self.events.clone()
+    }
+
+    /// Accept connections until the task is cancelled
+    pub async fn serve<H: ControlHandler>(self, handler: Arc<H>) -> Result<(), CtlError> {
+        let _cleanup = EndpointGuard(self.endpoint_path.clone());
+        log::info!("Control API listening on {}", self.endpoint.url);
+        loop {
+            let (stream, peer) = self.listener.accept().await?;
+            let handler = handler.clone();
+            let events = self.events.subscribe();
+            let token = self.endpoint.token.clone();
+            tokio::spawn(async move {
+                if let Err(e) = serve_connection(stream, &token, handler, events).await {
+                    log::debug!("Control connection from {} closed: {}", peer, e);
+                }
+            });
+        }
+    }
+}
+
+/// Removes the endpoint file when the server goes away
+struct EndpointGuard(Option<PathBuf>);
+
+impl Drop for EndpointGuard {
+    fn drop(&mut self) {
+        if let Some(path) = &self.0 {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+async fn serve_connection<H: ControlHandler>(
+    stream: TcpStream,
+    token: &str,
+    handler: Arc<H>,
+    mut events: broadcast::Receiver<DesktopEvent>,
+) -> Result<(), CtlError> {
+    let expected = format!("Bearer {}", token);
+    // The rejection type is dictated by tungstenite
+    #[allow(clippy::result_large_err)]
+    let authorize = |request: &HandshakeRequest, response: Response| -> Result<Response, ErrorResponse> {
+        let authorized = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value == expected);
+        if authorized {
+            Ok(response)
+        } else {
+            let mut rejection = ErrorResponse::new(Some("invalid control token".to_string()));
+            *rejection.status_mut() = StatusCode::UNAUTHORIZED;
+            Err(rejection)
+        }
+    };
+    let mut socket = tokio_tungstenite::accept_hdr_async(stream, authorize).await?;
+
+    // Event kinds the client subscribed to; `None` until it subscribes
+    let mut subscription: Option<Vec<String>> = None;
+    loop {
+        tokio::select! {
+            message = socket.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(e.into()),
+                };
+                let reply = match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(ClientMessage { id, request: Request::Subscribe { kinds } }) => {
+                        let reply = ServerMessage::reply(id, Ok(json!({ "subscribed": kinds })));
+                        // Only deliver events from now on, not those queued before subscribing
+                        events = events.resubscribe();
+                        subscription = Some(kinds);
+                        reply
+                    }
+                    Ok(ClientMessage { id, request }) => ServerMessage::reply(id, handler.handle(request).await),
+                    Err(e) => {
+                        // Answer with the id if the message has one so the client is not left waiting
+                        let id = serde_json::from_str::<Value>(&text).ok().and_then(|v| v.get("id")?.as_u64()).unwrap_or(0);
+                        ServerMessage::reply(id, Err(ApiError::invalid(e.to_string())))
+                    }
+                };
+                socket.send(Message::Text(serde_json::to_string(&reply)?)).await?;
+            }
+            event = events.recv(), if subscription.is_some() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        DesktopEvent::new("control.events_dropped", json!({ "missed": missed }))
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                };
+                if subscription.as_deref().is_some_and(|kinds| event.matches(kinds)) {
+                    socket.send(Message::Text(serde_json::to_string(&ServerMessage::Event(event))?)).await?;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::ControlClient;
+    use crate::ErrorCode;
+
+    struct Desktop;
+
+    #[async_trait]
+    impl ControlHandler for Desktop {
+        async fn handle(&self, request: Request) -> Result<Value, ApiError> {
+            match request {
+                Request::SwitchWorkspace { workspace } if workspace == "Research" => Ok(json!({ "workspace": workspace })),
+                Request::SwitchWorkspace { workspace } => Err(ApiError::not_found(format!("no workspace '{}'", workspace))),
+                _ => Err(ApiError::new(ErrorCode::Unsupported, "not implemented")),
+            }
+        }
+    }
+
+    async fn start() -> (Endpoint, broadcast::Sender<DesktopEvent>) {
+        let server = ControlServer::bind().await.unwrap();
+        let endpoint = server.endpoint().clone();
+        let events = server.event_sender();
+        tokio::spawn(server.serve(Arc::new(Desktop)));
+        (endpoint, events)
+    }
+
+    #[tokio::test]
+    async fn test_calls_and_errors() {
+        let (endpoint, _) = start().await;
+        let mut client = ControlClient::connect(&endpoint).await.unwrap();
+
+        let result = client.call(Request::SwitchWorkspace { workspace: "Research".into() }).await.unwrap();
+        assert_eq!(result["workspace"], "Research");
+
+        match client.call(Request::SwitchWorkspace { workspace: "Missing".into() }).await {
+            Err(CtlError::Api(error)) => assert_eq!(error.code, ErrorCode::NotFound),
+            other => panic!("unexpected reply: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejects_wrong_token() {
+        let (endpoint, _) = start().await;
+        let forged = Endpoint { token: "guess".into(), ..endpoint };
+        assert!(ControlClient::connect(&forged).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_streams_subscribed_events() {
+        let (endpoint, events) = start().await;
+        let mut client = ControlClient::connect(&endpoint).await.unwrap();
+        client.subscribe(vec!["workspace".into()]).await.unwrap();
+
+        events.send(DesktopEvent::new("node.created", json!({ "id": 1 }))).unwrap();
+        events.send(DesktopEvent::new("workspace.switched", json!({ "workspace": "Research" }))).unwrap();
+
+        let event = client.next_event().await.unwrap().unwrap();
+        assert_eq!(event.kind, "workspace.switched");
+        assert_eq!(event.data["workspace"], "Research");
+    }
+}
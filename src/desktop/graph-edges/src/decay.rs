@@ -0,0 +1,102 @@
+//! Relationship strength decay
+//!
+//! Strength grows with use through [`GraphEdge::record_access`] and decays
+//! while a relationship goes unused: every half-life without access halves
+//! it. Half-lives are configured per edge type; types without one, by
+//! default the structural ones like `contains`, never decay. Weak edges fade
+//! out visually, and edges that decay below the archive threshold are
+//! removed into the edge history, from where they can be restored. Pinned
+//! edges are exempt from both.
+
+use crate::{edge_type_name, GraphEdge};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Strength decay settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrengthDecayConfig {
+    pub enabled: bool,
+    /// Days without access for an edge to lose half its strength, by edge type (`related_to`, `works_on`, ...)
+    pub half_life_days: HashMap<String, f32>,
+    /// Half-life of edge types not listed; `None` keeps them from decaying
+    pub default_half_life_days: Option<f32>,
+    /// Strength below which decayed edges are archived; 0 never archives
+    pub archive_below: f32,
+    /// Minutes between decay evaluations
+    pub interval_minutes: u32,
+}
+
+impl Default for StrengthDecayConfig {
+    fn default() -> Self {
+        let half_life_days = [
+            ("related_to", 14.0),
+            ("temporal", 7.0),
+            ("communicates_with", 30.0),
+            ("works_on", 30.0),
+            ("tagged_as", 60.0),
+        ];
+        Self {
+            enabled: true,
+            half_life_days: half_life_days.into_iter().map(|(name, days)| (name.to_string(), days)).collect(),
+            default_half_life_days: None,
+            archive_below: 0.05,
+            interval_minutes: 60,
+        }
+    }
+}
+
+impl StrengthDecayConfig {
+    /// Half-life of an edge's type in days, if it decays
+    pub fn half_life(&self, edge: &GraphEdge) -> Option<f32> {
+        self.half_life_days
+            .get(edge_type_name(&edge.edge_type))
+            .copied()
+            .or(self.default_half_life_days)
+            .filter(|days| *days > 0.0)
+    }
+
+    /// Whether an edge decays at all
+    pub fn applies_to(&self, edge: &GraphEdge) -> bool {
+        self.enabled && !edge.metadata.pinned && self.half_life(edge).is_some()
+    }
+
+    /// Decay an edge's strength up to `now`, returning whether it changed
+    ///
+    /// Time is counted from the later of the last access and the last
+    /// decay, so evaluating often decays no faster than evaluating rarely.
+    pub fn decay(&self, edge: &mut GraphEdge, now: DateTime<Utc>) -> bool {
+        if !self.enabled || edge.metadata.pinned {
+            return false;
+        }
+        let Some(half_life) = self.half_life(edge) else {
+            return false;
+        };
+        let data = &edge.relationship_data;
+        let since = data.decayed_at.map_or(data.last_accessed, |decayed| decayed.max(data.last_accessed));
+        let elapsed_days = (now - since).num_seconds() as f32 / 86_400.0;
+        if elapsed_days <= 0.0 {
+            return false;
+        }
+
+        let strength = data.strength * 0.5_f32.powf(elapsed_days / half_life);
+        edge.relationship_data.decayed_at = Some(now);
+        edge.set_decayed_strength(strength);
+        true
+    }
+
+    /// Whether a decayed edge is weak enough to archive
+    pub fn should_archive(&self, edge: &GraphEdge) -> bool {
+        self.applies_to(edge) && edge.relationship_data.strength < self.archive_below
+    }
+}
+
+/// What a decay evaluation did
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DecayReport {
+    /// Edges whose strength decayed
+    pub decayed: usize,
+    /// Edges archived for falling below the threshold
+    pub archived: Vec<crate::SceneId>,
+}
@@ -1,7 +1,7 @@
 //! Automated relationship discovery system
 
-use crate::{EdgeError, RelationshipAnalyzer, RelationshipAnalysis, EdgeManager};
-use horizonos_graph_engine::SceneId;
+use crate::{EdgeError, EdgeOrigin, RelationshipAnalyzer, RelationshipAnalysis, EdgeManager};
+use horizonos_graph_engine::{Scene, SceneId};
 use horizonos_graph_nodes::GraphNode;
 use std::collections::{HashMap};
 use std::sync::{Arc, RwLock};
@@ -209,8 +209,8 @@ impl RelationshipDiscovery {
         Ok(task_id)
     }
     
-    /// Apply discovered relationships to an edge manager
-    pub async fn apply_discoveries_to_manager(&self, edge_manager: &mut EdgeManager) -> Result<usize, EdgeError> {
+    /// Apply discovered relationships between nodes of `scene` to an edge manager
+    pub async fn apply_discoveries_to_manager(&self, scene: &Scene, edge_manager: &mut EdgeManager) -> Result<usize, EdgeError> {
         let discovered = self.discovered_relationships.read().unwrap();
         let mut edges_created = 0;
         
@@ -221,11 +221,15 @@ impl RelationshipDiscovery {
                 .any(|edge| edge.target == analysis.target_id && 
                      std::mem::discriminant(&edge.edge_type) == std::mem::discriminant(&analysis.suggested_edge_type));
             
-            if !edge_exists {
-                match edge_manager.add_edge(analysis.source_id, analysis.target_id, analysis.suggested_edge_type.clone()) {
+            // Relationships the user removed are not brought back
+            let rejected = edge_manager.is_rejected(analysis.source_id, analysis.target_id, &analysis.suggested_edge_type);
+            
+            if !edge_exists && !rejected {
+                match edge_manager.add_scene_edge(scene, analysis.source_id, analysis.target_id, analysis.suggested_edge_type.clone()) {
                     Ok(edge_id) => {
                         // Update the edge with discovery data
                         let _ = edge_manager.update_edge_strength(edge_id, analysis.strength);
+                        let _ = edge_manager.set_edge_origin(edge_id, EdgeOrigin::Discovered { confidence: analysis.confidence });
                         edges_created += 1;
                         log::info!("Applied discovered relationship: {} -> {} (confidence: {:.2})", 
                                  analysis.source_id, analysis.target_id, analysis.confidence);
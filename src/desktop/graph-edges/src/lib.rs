@@ -8,14 +8,27 @@ pub use horizonos_graph_engine::{SceneEdge, EdgeType, SceneId};
 pub mod manager;
 pub mod relationship;
 pub mod discovery;
+pub mod validation;
+pub mod provenance;
+pub mod decay;
 
 pub use manager::*;
 pub use relationship::*;
 pub use discovery::*;
+pub use validation::{EdgeRule, EdgeValidationConfig, EdgeValidator, EdgeViolation, ValidationMode, edge_type_name};
+pub use provenance::{EdgeHistory, EdgeOrigin, EdgeProvenance, RemovalReason, RemovedEdge, StrengthSample};
+pub use decay::{DecayReport, StrengthDecayConfig};
 
+use horizonos_graph_errors::{Category, Classify, Problem, Severity};
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 
+/// Strength below which edges start to fade out
+const FADE_BELOW: f32 = 0.3;
+
+/// Share of their opacity fully faded edges keep
+const FADED_OPACITY: f32 = 0.15;
+
 /// Enhanced edge with additional relationship data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphEdge {
@@ -26,6 +39,9 @@ pub struct GraphEdge {
     pub relationship_data: RelationshipData,
     pub visual_style: EdgeVisualStyle,
     pub metadata: EdgeMetadata,
+    /// What created the edge and how its strength changed
+    #[serde(default)]
+    pub provenance: EdgeProvenance,
 }
 
 /// Data describing the relationship between nodes
@@ -38,6 +54,9 @@ pub struct RelationshipData {
     pub last_accessed: chrono::DateTime<chrono::Utc>,
     pub bidirectional: bool,     // Can the relationship work both ways?
     pub properties: HashMap<String, String>, // Additional key-value properties
+    /// When strength decay was last applied
+    #[serde(default)]
+    pub decayed_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 /// Visual styling for edges
@@ -71,6 +90,7 @@ pub enum EdgeError {
     InvalidRelationship { source: SceneId, target: SceneId },
     CircularDependency,
     MaxEdgesExceeded { node_id: SceneId },
+    ConstraintViolation { source: SceneId, target: SceneId, violation: EdgeViolation },
     SerializationError(serde_json::Error),
     SystemError { message: String },
 }
@@ -82,6 +102,7 @@ impl std::fmt::Display for EdgeError {
             EdgeError::InvalidRelationship { source, target } => write!(f, "Invalid relationship: {} -> {}", source, target),
             EdgeError::CircularDependency => write!(f, "Circular dependency detected"),
             EdgeError::MaxEdgesExceeded { node_id } => write!(f, "Maximum edges exceeded for node: {}", node_id),
+            EdgeError::ConstraintViolation { source, target, violation } => write!(f, "Edge {} -> {} not allowed: {}", source, target, violation),
             EdgeError::SerializationError(e) => write!(f, "Serialization error: {}", e),
             EdgeError::SystemError { message } => write!(f, "System error: {}", message),
         }
@@ -103,6 +124,27 @@ impl From<serde_json::Error> for EdgeError {
     }
 }
 
+impl Classify for EdgeError {
+    fn classify(&self) -> Problem {
+        let problem = match self {
+            EdgeError::EdgeNotFound { .. } => Problem::new("edges.not_found", Category::Graph, Severity::Info, "Relationship no longer exists"),
+            EdgeError::InvalidRelationship { source, .. } => {
+                Problem::new("edges.invalid", Category::Graph, Severity::Warning, "These nodes cannot be connected this way").with_node(*source)
+            }
+            EdgeError::CircularDependency => Problem::new("edges.cycle", Category::Graph, Severity::Warning, "This dependency would create a cycle"),
+            EdgeError::MaxEdgesExceeded { node_id } => {
+                Problem::new("edges.limit", Category::Graph, Severity::Warning, "Node has too many relationships").with_node(*node_id)
+            }
+            EdgeError::ConstraintViolation { source, .. } => {
+                Problem::new("edges.constraint", Category::Graph, Severity::Warning, "These nodes cannot be connected this way").with_node(*source)
+            }
+            EdgeError::SerializationError(_) => Problem::new("edges.data_corrupt", Category::Storage, Severity::Error, "Relationship data is unreadable"),
+            EdgeError::SystemError { .. } => Problem::new("edges.internal", Category::Internal, Severity::Error, "Relationship operation failed"),
+        };
+        problem.in_subsystem("edges").with_detail(self)
+    }
+}
+
 impl Default for RelationshipData {
     fn default() -> Self {
         let now = chrono::Utc::now();
@@ -114,6 +156,7 @@ impl Default for RelationshipData {
             last_accessed: now,
             bidirectional: false,
             properties: HashMap::new(),
+            decayed_at: None,
         }
     }
 }
@@ -163,14 +206,19 @@ impl GraphEdge {
             EdgeType::WorksOn => [0.9, 0.9, 0.2, 0.8],         // Yellow
         };
         
+        let relationship_data = RelationshipData::default();
+        let mut provenance = EdgeProvenance::default();
+        provenance.record_strength(relationship_data.strength);
+        
         GraphEdge {
             id,
             source,
             target,
             edge_type,
-            relationship_data: RelationshipData::default(),
+            relationship_data,
             visual_style,
             metadata: EdgeMetadata::default(),
+            provenance,
         }
     }
     
@@ -178,6 +226,7 @@ impl GraphEdge {
     pub fn update_strength(&mut self, new_strength: f32) {
         self.relationship_data.strength = new_strength.clamp(0.0, 1.0);
         self.relationship_data.last_accessed = chrono::Utc::now();
+        self.provenance.record_strength(self.relationship_data.strength);
         self.update_visual_style();
     }
     
@@ -189,17 +238,33 @@ impl GraphEdge {
         // Increase strength slightly for frequently accessed relationships
         let strength_boost = 0.01 * (self.relationship_data.frequency as f32).ln();
         self.relationship_data.strength = (self.relationship_data.strength + strength_boost).min(1.0);
+        self.provenance.record_strength(self.relationship_data.strength);
         
         self.update_visual_style();
     }
     
+    /// Set the strength decay left, without counting it as an access
+    pub(crate) fn set_decayed_strength(&mut self, strength: f32) {
+        self.relationship_data.strength = strength.clamp(0.0, 1.0);
+        self.provenance.record_strength(self.relationship_data.strength);
+        self.update_visual_style();
+    }
+    
+    /// How far a weak edge has faded out, from 1 (not at all) down to [`FADED_OPACITY`]
+    pub fn fade(&self) -> f32 {
+        if self.metadata.pinned {
+            return 1.0;
+        }
+        (self.relationship_data.strength / FADE_BELOW).clamp(FADED_OPACITY, 1.0)
+    }
+    
     /// Update visual style based on relationship data
     fn update_visual_style(&mut self) {
         // Make thickness proportional to strength
         self.visual_style.thickness = 0.5 + (self.relationship_data.strength * 2.0);
         
-        // Make opacity proportional to confidence
-        self.visual_style.opacity = 0.3 + (self.relationship_data.confidence * 0.7);
+        // Make opacity proportional to confidence, fading out weak edges
+        self.visual_style.opacity = (0.3 + (self.relationship_data.confidence * 0.7)) * self.fade();
         
         // Add glow for strong relationships
         self.visual_style.glow = self.relationship_data.strength > 0.8;
@@ -227,13 +292,14 @@ impl GraphEdge {
     
     /// Convert to scene edge for rendering
     pub fn to_scene_edge(&self) -> SceneEdge {
+        let [r, g, b, a] = self.visual_style.color;
         SceneEdge {
             id: self.id,
             source: self.source,
             target: self.target,
             edge_type: self.edge_type.clone(),
             weight: self.relationship_data.strength,
-            color: self.visual_style.color,
+            color: [r, g, b, a * self.fade()],
             visible: self.visual_style.visible,
             animated: self.visual_style.animation_speed > 0.0,
         }
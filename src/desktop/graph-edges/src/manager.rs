@@ -1,7 +1,7 @@
 //! Edge manager for the graph desktop
 
-use crate::{GraphEdge, EdgeError};
-use horizonos_graph_engine::{SceneId, EdgeType, Scene};
+use crate::{GraphEdge, EdgeError, EdgeValidator, EdgeHistory, EdgeOrigin, RemovalReason, RemovedEdge, DecayReport, StrengthDecayConfig};
+use horizonos_graph_engine::{SceneId, EdgeType, NodeType, Scene};
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 
@@ -12,6 +12,13 @@ pub struct EdgeManager {
     reverse_adjacency: Arc<RwLock<HashMap<SceneId, HashSet<SceneId>>>>, // incoming edges
     next_id: SceneId,
     max_edges_per_node: usize,
+    validator: EdgeValidator,
+    /// Removed edges, kept for restoring and to suppress rejected suggestions
+    history: EdgeHistory,
+    /// How unused relationships lose strength
+    decay: StrengthDecayConfig,
+    /// When strength decay was last evaluated
+    last_decay: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl EdgeManager {
@@ -22,6 +29,10 @@ impl EdgeManager {
             reverse_adjacency: Arc::new(RwLock::new(HashMap::new())),
             next_id: 1,
             max_edges_per_node: 100, // Prevent excessive connections
+            validator: EdgeValidator::new(),
+            history: EdgeHistory::default(),
+            decay: StrengthDecayConfig::default(),
+            last_decay: None,
         }
     }
     
@@ -32,20 +43,78 @@ impl EdgeManager {
         id
     }
     
-    /// Add a new edge
+    /// Add a new edge between nodes of unknown type
+    ///
+    /// Edge rules that do not depend on node types still apply. Callers that
+    /// have the scene should use [`Self::add_scene_edge`] instead.
     pub fn add_edge(&mut self, source: SceneId, target: SceneId, edge_type: EdgeType) -> Result<SceneId, EdgeError> {
-        // Check for circular dependencies in dependency chains
-        if matches!(edge_type, EdgeType::DependsOn) && self.would_create_cycle(source, target)? {
-            return Err(EdgeError::CircularDependency);
-        }
-        
+        self.validator.validate(self, source, None, target, None, &edge_type)?;
+        self.insert_edge(source, target, edge_type)
+    }
+    
+    /// Add a new edge, checking all edge rules against the node types
+    pub fn add_typed_edge(
+        &mut self,
+        source: SceneId,
+        source_type: &NodeType,
+        target: SceneId,
+        target_type: &NodeType,
+        edge_type: EdgeType,
+    ) -> Result<SceneId, EdgeError> {
+        self.validator.validate(self, source, Some(source_type), target, Some(target_type), &edge_type)?;
+        self.insert_edge(source, target, edge_type)
+    }
+    
+    /// Add a new edge between scene nodes, checking all edge rules against their types
+    ///
+    /// Ends missing from the scene are checked like in [`Self::add_edge`].
+    pub fn add_scene_edge(
+        &mut self,
+        scene: &Scene,
+        source: SceneId,
+        target: SceneId,
+        edge_type: EdgeType,
+    ) -> Result<SceneId, EdgeError> {
+        let node_type = |id| scene.get_node(id).map(|node| &node.node_type);
+        self.validator.validate(self, source, node_type(source), target, node_type(target), &edge_type)?;
+        self.insert_edge(source, target, edge_type)
+    }
+    
+    /// Check whether an edge could be added, e.g. to preview it while the user drags
+    pub fn check_edge(
+        &self,
+        source: SceneId,
+        source_type: &NodeType,
+        target: SceneId,
+        target_type: &NodeType,
+        edge_type: &EdgeType,
+    ) -> Result<(), EdgeError> {
+        self.validator.validate(self, source, Some(source_type), target, Some(target_type), edge_type)
+    }
+    
+    /// Rules new edges are checked against
+    pub fn validator(&self) -> &EdgeValidator {
+        &self.validator
+    }
+    
+    /// Replace the rules new edges are checked against
+    pub fn set_validator(&mut self, validator: EdgeValidator) {
+        self.validator = validator;
+    }
+    
+    fn insert_edge(&mut self, source: SceneId, target: SceneId, edge_type: EdgeType) -> Result<SceneId, EdgeError> {
         // Check edge limits
         if self.get_node_edge_count(source) >= self.max_edges_per_node {
             return Err(EdgeError::MaxEdgesExceeded { node_id: source });
         }
         
         let edge_id = self.next_id();
-        let edge = GraphEdge::new(edge_id, source, target, edge_type.clone());
+        self.insert_graph_edge(GraphEdge::new(edge_id, source, target, edge_type));
+        Ok(edge_id)
+    }
+    
+    fn insert_graph_edge(&mut self, edge: GraphEdge) {
+        let (edge_id, source, target) = (edge.id, edge.source, edge.target);
         
         // Log before moving the edge
         log::info!("Added edge {} -> {} (type: {:?})", source, target, edge.edge_type);
@@ -65,12 +134,10 @@ impl EdgeManager {
             let mut rev_adj = self.reverse_adjacency.write().unwrap();
             rev_adj.entry(target).or_insert_with(HashSet::new).insert(source);
         }
-        
-        Ok(edge_id)
     }
     
-    /// Remove an edge
-    pub fn remove_edge(&mut self, edge_id: SceneId) -> Result<GraphEdge, EdgeError> {
+    /// Remove an edge, keeping it in the history with the reason
+    pub fn remove_edge(&mut self, edge_id: SceneId, reason: RemovalReason) -> Result<GraphEdge, EdgeError> {
         let edge = {
             let mut edges = self.edges.write().unwrap();
             edges.remove(&edge_id).ok_or(EdgeError::EdgeNotFound { id: edge_id })?
@@ -97,10 +164,65 @@ impl EdgeManager {
             }
         }
         
-        log::info!("Removed edge {} -> {}", edge.source, edge.target);
+        log::info!("Removed edge {} -> {} ({:?})", edge.source, edge.target, reason);
+        self.history.record(edge.clone(), reason);
         Ok(edge)
     }
     
+    /// Put a removed edge back, with its ID and provenance
+    ///
+    /// The edge rules are checked again, since the graph may have changed since.
+    pub fn restore_edge(&mut self, edge_id: SceneId) -> Result<SceneId, EdgeError> {
+        let removed = self.history.take(edge_id).ok_or(EdgeError::EdgeNotFound { id: edge_id })?;
+        let edge = &removed.edge;
+        let allowed = self.validator.validate(self, edge.source, None, edge.target, None, &edge.edge_type)
+            .and_then(|()| {
+                if self.get_node_edge_count(edge.source) >= self.max_edges_per_node {
+                    Err(EdgeError::MaxEdgesExceeded { node_id: edge.source })
+                } else {
+                    Ok(())
+                }
+            });
+        match allowed {
+            Ok(()) => {
+                let mut edge = removed.edge;
+                if removed.reason == RemovalReason::Archived {
+                    // Restoring shows the relationship matters; it would be archived again right away
+                    edge.update_strength(crate::RelationshipData::default().strength);
+                }
+                self.insert_graph_edge(edge);
+                Ok(edge_id)
+            }
+            Err(e) => {
+                self.history.record(removed.edge, removed.reason);
+                Err(e)
+            }
+        }
+    }
+    
+    /// Removed edges, e.g. for a node's details
+    pub fn history(&self) -> &EdgeHistory {
+        &self.history
+    }
+    
+    /// Removed edges of a node, newest first
+    pub fn removed_edges(&self, node_id: SceneId) -> Vec<RemovedEdge> {
+        self.history.for_node(node_id).into_iter().cloned().collect()
+    }
+    
+    /// Whether the user removed this relationship, so it should not be suggested again
+    pub fn is_rejected(&self, source: SceneId, target: SceneId, edge_type: &EdgeType) -> bool {
+        self.history.is_rejected(source, target, edge_type)
+    }
+    
+    /// Record what created an edge
+    pub fn set_edge_origin(&mut self, edge_id: SceneId, origin: EdgeOrigin) -> Result<(), EdgeError> {
+        let mut edges = self.edges.write().unwrap();
+        let edge = edges.get_mut(&edge_id).ok_or(EdgeError::EdgeNotFound { id: edge_id })?;
+        edge.provenance.origin = origin;
+        Ok(())
+    }
+    
     /// Get an edge by ID
     pub fn get_edge(&self, edge_id: SceneId) -> Option<GraphEdge> {
         let edges = self.edges.read().unwrap();
@@ -186,34 +308,40 @@ impl EdgeManager {
         outgoing + incoming
     }
     
-    /// Check if adding an edge would create a cycle (for dependency edges)
-    fn would_create_cycle(&self, source: SceneId, target: SceneId) -> Result<bool, EdgeError> {
-        // Use DFS to check if target can reach source
-        let mut visited = HashSet::new();
-        let mut stack = vec![target];
-        
-        let adj_list = self.adjacency_list.read().unwrap();
+    /// Number of edges of a type leaving (`outgoing`) or entering a node
+    pub fn count_edges_of_type(&self, node_id: SceneId, edge_type: &EdgeType, outgoing: bool) -> usize {
+        let edges = self.edges.read().unwrap();
+        edges.values()
+            .filter(|edge| if outgoing { edge.source == node_id } else { edge.target == node_id })
+            .filter(|edge| std::mem::discriminant(&edge.edge_type) == std::mem::discriminant(edge_type))
+            .count()
+    }
+    
+    /// Check whether `to` can be reached from `from` following edges of one type
+    pub fn reaches(&self, from: SceneId, to: SceneId, edge_type: &EdgeType) -> bool {
+        let edges = self.edges.read().unwrap();
+        let mut successors: HashMap<SceneId, Vec<SceneId>> = HashMap::new();
+        for edge in edges.values() {
+            if std::mem::discriminant(&edge.edge_type) == std::mem::discriminant(edge_type) {
+                successors.entry(edge.source).or_default().push(edge.target);
+            }
+        }
         
+        // Depth-first search from `from`
+        let mut visited = HashSet::new();
+        let mut stack = vec![from];
         while let Some(current) = stack.pop() {
-            if current == source {
-                return Ok(true); // Cycle detected
+            if current == to {
+                return true;
             }
-            
-            if visited.contains(&current) {
+            if !visited.insert(current) {
                 continue;
             }
-            visited.insert(current);
-            
-            if let Some(neighbors) = adj_list.get(&current) {
-                for &neighbor in neighbors {
-                    if !visited.contains(&neighbor) {
-                        stack.push(neighbor);
-                    }
-                }
+            if let Some(next) = successors.get(&current) {
+                stack.extend(next.iter().copied().filter(|node| !visited.contains(node)));
             }
         }
-        
-        Ok(false)
+        false
     }
     
     /// Clean up expired edges
@@ -231,7 +359,7 @@ impl EdgeManager {
         
         let count = expired_ids.len();
         for id in expired_ids {
-            let _ = self.remove_edge(id);
+            let _ = self.remove_edge(id, RemovalReason::Expired);
         }
         
         if count > 0 {
@@ -241,6 +369,87 @@ impl EdgeManager {
         count
     }
     
+    /// Replace the strength decay settings
+    pub fn set_strength_decay(&mut self, config: StrengthDecayConfig) {
+        self.decay = config;
+    }
+    
+    /// Strength decay settings
+    pub fn strength_decay(&self) -> &StrengthDecayConfig {
+        &self.decay
+    }
+    
+    /// Decay edge strengths if the evaluation interval has passed since the last run
+    ///
+    /// Meant to be called periodically; returns `None` when not due yet.
+    pub fn decay_strengths(&mut self, now: chrono::DateTime<chrono::Utc>) -> Option<DecayReport> {
+        let interval = chrono::Duration::minutes(self.decay.interval_minutes.into());
+        if !self.decay.enabled || self.last_decay.is_some_and(|last| now - last < interval) {
+            return None;
+        }
+        Some(self.apply_strength_decay(now))
+    }
+    
+    /// Decay edge strengths up to `now` and archive edges that became too weak
+    pub fn apply_strength_decay(&mut self, now: chrono::DateTime<chrono::Utc>) -> DecayReport {
+        self.last_decay = Some(now);
+        let mut report = DecayReport::default();
+        let mut archive = Vec::new();
+        {
+            let mut edges = self.edges.write().unwrap();
+            for (id, edge) in edges.iter_mut() {
+                if self.decay.decay(edge, now) {
+                    report.decayed += 1;
+                }
+                if self.decay.should_archive(edge) {
+                    archive.push(*id);
+                }
+            }
+        }
+        
+        for id in archive {
+            if self.remove_edge(id, RemovalReason::Archived).is_ok() {
+                report.archived.push(id);
+            }
+        }
+        if !report.archived.is_empty() {
+            log::info!("Archived {} relationships that decayed below {}", report.archived.len(), self.decay.archive_below);
+        }
+        report
+    }
+    
+    /// Show decayed strengths on the scene's copies of the edges and remove
+    /// those of archived edges, returning the removed scene edges
+    ///
+    /// Scene edges get their own IDs, so they are matched by endpoints and type.
+    pub fn sync_decay_to_scene(&self, report: &DecayReport, scene: &mut Scene) -> Vec<horizonos_graph_engine::SceneEdge> {
+        let key = |source: SceneId, target: SceneId, edge_type: &EdgeType| (source, target, crate::edge_type_name(edge_type));
+        let mut current = HashMap::new();
+        for edge in self.edges.read().unwrap().values() {
+            current.insert(key(edge.source, edge.target, &edge.edge_type), edge.to_scene_edge());
+        }
+        let archived: HashSet<_> = report.archived.iter()
+            .filter_map(|id| self.history.get(*id))
+            .map(|removed| key(removed.edge.source, removed.edge.target, &removed.edge.edge_type))
+            .collect();
+        
+        let mut removed = Vec::new();
+        let scene_edges: Vec<SceneId> = scene.edges().map(|edge| edge.id).collect();
+        for id in scene_edges {
+            let Some(scene_edge) = scene.get_edge_mut(id) else {
+                continue;
+            };
+            let edge_key = key(scene_edge.source, scene_edge.target, &scene_edge.edge_type);
+            if let Some(edge) = current.get(&edge_key) {
+                scene_edge.weight = edge.weight;
+                scene_edge.color = edge.color;
+            } else if archived.contains(&edge_key) {
+                removed.extend(scene.remove_edge(id));
+            }
+        }
+        removed
+    }
+    
     /// Get statistics about the edge graph
     pub fn get_statistics(&self) -> EdgeStatistics {
         let edges = self.edges.read().unwrap();
@@ -353,4 +562,76 @@ mod tests {
         assert!(stats.edge_type_counts.contains_key("Contains"));
         assert!(stats.edge_type_counts.contains_key("DependsOn"));
     }
+
+    #[test]
+    fn test_removed_edge_history() {
+        let mut manager = EdgeManager::new();
+        let rejected = manager.add_edge(1, 2, EdgeType::WorksOn).unwrap();
+        let pruned = manager.add_edge(1, 3, EdgeType::WorksOn).unwrap();
+        manager.set_edge_origin(rejected, EdgeOrigin::Ai { agent: "suggestions".to_string() }).unwrap();
+        
+        manager.remove_edge(rejected, RemovalReason::Rejected).unwrap();
+        manager.remove_edge(pruned, RemovalReason::Pruned { reason: "weak".to_string() }).unwrap();
+        
+        assert_eq!(manager.removed_edges(1).len(), 2);
+        assert!(manager.is_rejected(2, 1, &EdgeType::WorksOn));
+        assert!(!manager.is_rejected(1, 3, &EdgeType::WorksOn));
+        
+        assert_eq!(manager.restore_edge(rejected).unwrap(), rejected);
+        let restored = manager.get_edge(rejected).unwrap();
+        assert_eq!(restored.provenance.origin, EdgeOrigin::Ai { agent: "suggestions".to_string() });
+        assert!(!manager.is_rejected(1, 2, &EdgeType::WorksOn));
+        assert_eq!(manager.removed_edges(1).len(), 1);
+    }
+
+    #[test]
+    fn test_strength_decay_fades_and_archives_unused_edges() {
+        let mut manager = EdgeManager::new();
+        let related = manager.add_edge(1, 2, EdgeType::RelatedTo { similarity: 0.5 }).unwrap();
+        let pinned = manager.add_edge(1, 3, EdgeType::RelatedTo { similarity: 0.5 }).unwrap();
+        let contains = manager.add_edge(1, 4, EdgeType::Contains).unwrap();
+        manager.edges.write().unwrap().get_mut(&pinned).unwrap().metadata.pinned = true;
+        let start = manager.get_edge(related).unwrap().relationship_data.last_accessed;
+
+        // One half-life of related_to halves the strength and fades the edge
+        let report = manager.decay_strengths(start + chrono::Duration::days(14)).unwrap();
+        assert_eq!(report.decayed, 1);
+        let faded = manager.get_edge(related).unwrap();
+        assert!((faded.relationship_data.strength - 0.25).abs() < 1e-3);
+        assert!(faded.to_scene_edge().color[3] < faded.visual_style.color[3]);
+        assert_eq!(manager.get_edge(pinned).unwrap().relationship_data.strength, 0.5);
+        assert_eq!(manager.get_edge(contains).unwrap().relationship_data.strength, 0.5);
+
+        // Not due again within the interval
+        assert!(manager.decay_strengths(start + chrono::Duration::days(14) + chrono::Duration::minutes(5)).is_none());
+
+        // Three more half-lives take it below the archive threshold
+        let report = manager.decay_strengths(start + chrono::Duration::days(56)).unwrap();
+        assert_eq!(report.archived, vec![related]);
+        assert!(manager.get_edge(related).is_none());
+        assert_eq!(manager.removed_edges(1)[0].reason, RemovalReason::Archived);
+        assert!(manager.get_edge(pinned).is_some());
+    }
+
+    #[test]
+    fn test_decay_reaches_the_scene() {
+        let mut manager = EdgeManager::new();
+        let related = manager.add_edge(1, 2, EdgeType::RelatedTo { similarity: 0.5 }).unwrap();
+        manager.add_edge(1, 3, EdgeType::WorksOn).unwrap();
+        let mut scene = Scene::new();
+        let shown = scene.add_edge(manager.get_edge(related).unwrap().to_scene_edge());
+        let works_on = manager.find_edges_by_type(&EdgeType::WorksOn).remove(0).to_scene_edge();
+        let other = scene.add_edge(works_on);
+        let start = manager.get_edge(related).unwrap().relationship_data.last_accessed;
+
+        let report = manager.decay_strengths(start + chrono::Duration::days(14)).unwrap();
+        assert!(manager.sync_decay_to_scene(&report, &mut scene).is_empty());
+        assert!((scene.get_edge(shown).unwrap().weight - 0.25).abs() < 1e-3);
+
+        let report = manager.decay_strengths(start + chrono::Duration::days(56)).unwrap();
+        let removed = manager.sync_decay_to_scene(&report, &mut scene);
+        assert_eq!(removed.iter().map(|edge| edge.id).collect::<Vec<_>>(), vec![shown]);
+        assert!(scene.get_edge(shown).is_none());
+        assert!(scene.get_edge(other).is_some());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,170 @@
+//! Where relationships came from and where removed ones went
+//!
+//! Every edge records what created it and how its strength changed. Removing
+//! an edge, by the user or by automatic pruning, moves it into the
+//! [`EdgeHistory`] instead of dropping it, so it can be shown in a node's
+//! details, restored, and kept from being suggested again once a user has
+//! rejected it.
+
+use crate::{edge_type_name, GraphEdge};
+use chrono::{DateTime, Utc};
+use horizonos_graph_engine::{EdgeType, SceneId};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Strength samples kept per edge
+const MAX_STRENGTH_SAMPLES: usize = 64;
+
+/// Who or what created an edge
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum EdgeOrigin {
+    /// Created before provenance was tracked, or by a caller that did not say
+    #[default]
+    Unknown,
+    /// Drawn by the user
+    User,
+    /// Found by relationship discovery
+    Discovered { confidence: f32 },
+    /// Suggested by an AI agent and accepted
+    Ai { agent: String },
+    /// Created by a system component, e.g. an integration
+    System { component: String },
+}
+
+/// Why an edge was removed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RemovalReason {
+    /// Deleted by the user
+    UserDeleted,
+    /// Removed by the user as a wrong suggestion; it is not suggested again
+    Rejected,
+    /// A temporary edge ran out
+    Expired,
+    /// Removed automatically, e.g. for being too weak
+    Pruned { reason: String },
+    /// Went unused until its strength decayed below the archive threshold
+    Archived,
+    /// One of its nodes was removed
+    NodeRemoved,
+}
+
+impl RemovalReason {
+    /// Whether the user decided against this relationship
+    pub fn is_user_decision(&self) -> bool {
+        matches!(self, RemovalReason::UserDeleted | RemovalReason::Rejected)
+    }
+}
+
+/// Strength of an edge at some point
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StrengthSample {
+    pub at: DateTime<Utc>,
+    pub strength: f32,
+}
+
+/// Origin and strength history of an edge
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EdgeProvenance {
+    pub origin: EdgeOrigin,
+    /// Strength over time, oldest first; only changes are recorded
+    pub strength_history: Vec<StrengthSample>,
+}
+
+impl EdgeProvenance {
+    /// Record the current strength if it changed noticeably
+    pub fn record_strength(&mut self, strength: f32) {
+        let changed = self.strength_history.last()
+            .is_none_or(|last| (last.strength - strength).abs() >= 0.01);
+        if changed {
+            if self.strength_history.len() == MAX_STRENGTH_SAMPLES {
+                self.strength_history.remove(0);
+            }
+            self.strength_history.push(StrengthSample { at: Utc::now(), strength });
+        }
+    }
+}
+
+/// An edge as it was when removed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemovedEdge {
+    pub edge: GraphEdge,
+    pub removed_at: DateTime<Utc>,
+    pub reason: RemovalReason,
+}
+
+/// Removed edges, newest last
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdgeHistory {
+    removed: VecDeque<RemovedEdge>,
+    /// Removed edges kept; the oldest are forgotten first
+    max_entries: usize,
+}
+
+impl Default for EdgeHistory {
+    fn default() -> Self {
+        Self::new(1000)
+    }
+}
+
+impl EdgeHistory {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            removed: VecDeque::new(),
+            max_entries,
+        }
+    }
+
+    /// Keep a removed edge
+    pub fn record(&mut self, edge: GraphEdge, reason: RemovalReason) {
+        if self.max_entries == 0 {
+            return;
+        }
+        if self.removed.len() == self.max_entries {
+            self.removed.pop_front();
+        }
+        self.removed.push_back(RemovedEdge { edge, removed_at: Utc::now(), reason });
+    }
+
+    /// Removed edges of a node, newest first
+    pub fn for_node(&self, node_id: SceneId) -> Vec<&RemovedEdge> {
+        self.removed.iter().rev()
+            .filter(|removed| removed.edge.source == node_id || removed.edge.target == node_id)
+            .collect()
+    }
+
+    /// A removed edge by its ID
+    pub fn get(&self, edge_id: SceneId) -> Option<&RemovedEdge> {
+        self.removed.iter().rev().find(|removed| removed.edge.id == edge_id)
+    }
+
+    /// Take a removed edge out of the history, e.g. to restore it
+    pub fn take(&mut self, edge_id: SceneId) -> Option<RemovedEdge> {
+        let index = self.removed.iter().rposition(|removed| removed.edge.id == edge_id)?;
+        self.removed.remove(index)
+    }
+
+    /// Whether the user removed this relationship, in either direction, so it
+    /// should not be suggested again
+    pub fn is_rejected(&self, source: SceneId, target: SceneId, edge_type: &EdgeType) -> bool {
+        let type_name = edge_type_name(edge_type);
+        self.removed.iter().any(|removed| {
+            let edge = &removed.edge;
+            removed.reason.is_user_decision()
+                && edge_type_name(&edge.edge_type) == type_name
+                && ((edge.source == source && edge.target == target) || (edge.source == target && edge.target == source))
+        })
+    }
+
+    /// All removed edges, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &RemovedEdge> {
+        self.removed.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.removed.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty()
+    }
+}
@@ -1,6 +1,6 @@
 //! Relationship analysis and discovery for graph edges
 
-use crate::{GraphEdge};
+use crate::{EdgeOrigin, GraphEdge};
 use horizonos_graph_engine::{SceneId, EdgeType};
 use horizonos_graph_nodes::{GraphNode};
 use std::collections::HashMap;
@@ -395,8 +395,9 @@ impl RelationshipAnalyzer {
     pub fn create_edge_from_analysis(&self, edge_id: SceneId, analysis: &RelationshipAnalysis) -> GraphEdge {
         let mut edge = GraphEdge::new(edge_id, analysis.source_id, analysis.target_id, analysis.suggested_edge_type.clone());
         
-        edge.relationship_data.strength = analysis.strength;
+        edge.update_strength(analysis.strength);
         edge.relationship_data.confidence = analysis.confidence;
+        edge.provenance.origin = EdgeOrigin::Discovered { confidence: analysis.confidence };
         edge.relationship_data.bidirectional = analysis.bidirectional;
         
         // Add evidence as properties
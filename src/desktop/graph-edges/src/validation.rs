@@ -0,0 +1,356 @@
+//! Constraints on which edges may be created
+//!
+//! Each edge type can restrict the node types at either end, how many edges
+//! of the type a node may have, and whether the type may form cycles. The
+//! built-in rules reject nonsense like a Setting that `WorksOn` a Person,
+//! a node with two `Contains` parents, or circular `DependsOn` chains.
+//! Configuration can replace rules per edge type, or downgrade violations to
+//! warnings or turn checking off entirely.
+
+use crate::{EdgeError, EdgeManager};
+use horizonos_graph_engine::{EdgeType, NodeType, SceneId};
+use horizonos_graph_nodes::hooks::node_type_name;
+pub use horizonos_graph_nodes::query::edge_type_name;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// What happens to edges that break a rule
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationMode {
+    /// Refuse to create the edge
+    #[default]
+    Enforce,
+    /// Create the edge but log the violation
+    Warn,
+    /// Do not check rules
+    Off,
+}
+
+/// Constraints on one edge type
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EdgeRule {
+    /// Node types the edge may start at (`file`, `person`, ...); empty allows any
+    pub sources: Vec<String>,
+    /// Node types the edge may end at; empty allows any
+    pub targets: Vec<String>,
+    /// Most edges of this type leaving one node
+    pub max_outgoing: Option<usize>,
+    /// Most edges of this type entering one node
+    pub max_incoming: Option<usize>,
+    /// Whether edges of this type may not form a cycle
+    pub acyclic: bool,
+}
+
+impl EdgeRule {
+    fn allows(allowed: &[String], node_type: &str) -> bool {
+        allowed.is_empty() || allowed.iter().any(|name| name == node_type)
+    }
+}
+
+/// Edge validation settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EdgeValidationConfig {
+    pub mode: ValidationMode,
+    /// Rules replacing the built-in ones, by edge type (`contains`, `depends_on`, ...)
+    pub rules: HashMap<String, EdgeRule>,
+}
+
+/// A broken edge rule
+#[derive(Debug, Clone, PartialEq)]
+pub enum EdgeViolation {
+    SourceType { edge_type: &'static str, node_type: &'static str, allowed: Vec<String> },
+    TargetType { edge_type: &'static str, node_type: &'static str, allowed: Vec<String> },
+    TooManyOutgoing { edge_type: &'static str, node_id: SceneId, limit: usize },
+    TooManyIncoming { edge_type: &'static str, node_id: SceneId, limit: usize },
+    Cycle { edge_type: &'static str },
+}
+
+impl std::fmt::Display for EdgeViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EdgeViolation::SourceType { edge_type, node_type, allowed } => write!(
+                f,
+                "{} edges cannot start at a {} node (allowed: {})",
+                edge_type,
+                node_type,
+                allowed.join(", ")
+            ),
+            EdgeViolation::TargetType { edge_type, node_type, allowed } => write!(
+                f,
+                "{} edges cannot end at a {} node (allowed: {})",
+                edge_type,
+                node_type,
+                allowed.join(", ")
+            ),
+            EdgeViolation::TooManyOutgoing { edge_type, node_id, limit } => {
+                write!(f, "Node {} already has {} outgoing {} edge(s)", node_id, limit, edge_type)
+            }
+            EdgeViolation::TooManyIncoming { edge_type, node_id, limit } => {
+                write!(f, "Node {} already has {} incoming {} edge(s)", node_id, limit, edge_type)
+            }
+            EdgeViolation::Cycle { edge_type } => write!(f, "{} edges cannot form a cycle", edge_type),
+        }
+    }
+}
+
+/// Checks new edges against per-type rules
+#[derive(Debug, Clone)]
+pub struct EdgeValidator {
+    mode: ValidationMode,
+    rules: HashMap<String, EdgeRule>,
+}
+
+impl EdgeValidator {
+    /// Validator enforcing the built-in rules
+    pub fn new() -> Self {
+        let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+        let mut rules = HashMap::new();
+        // A node sits in at most one container, and containment nests like a tree
+        rules.insert("contains".to_string(), EdgeRule { max_incoming: Some(1), acyclic: true, ..Default::default() });
+        rules.insert("depends_on".to_string(), EdgeRule { acyclic: true, ..Default::default() });
+        rules.insert(
+            "works_on".to_string(),
+            EdgeRule {
+                sources: names(&["person", "ai_agent"]),
+                targets: names(&["task", "file", "concept", "url", "application", "automation"]),
+                ..Default::default()
+            },
+        );
+        rules.insert(
+            "created_by".to_string(),
+            EdgeRule {
+                targets: names(&["person", "ai_agent", "application", "automation", "system"]),
+                ..Default::default()
+            },
+        );
+        let communicators = names(&["application", "person", "ai_agent", "device", "system"]);
+        rules.insert(
+            "communicates_with".to_string(),
+            EdgeRule { sources: communicators.clone(), targets: communicators, ..Default::default() },
+        );
+        Self { mode: ValidationMode::Enforce, rules }
+    }
+
+    /// Built-in rules with the configured mode and replacements applied
+    pub fn from_config(config: &EdgeValidationConfig) -> Self {
+        let mut validator = Self::new();
+        validator.mode = config.mode;
+        for (edge_type, rule) in &config.rules {
+            validator.set_rule(edge_type, rule.clone());
+        }
+        validator
+    }
+
+    pub fn mode(&self) -> ValidationMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: ValidationMode) {
+        self.mode = mode;
+    }
+
+    /// Rule for an edge type name
+    pub fn rule(&self, edge_type: &str) -> Option<&EdgeRule> {
+        self.rules.get(edge_type)
+    }
+
+    /// Replace the rule of an edge type
+    pub fn set_rule(&mut self, edge_type: &str, rule: EdgeRule) {
+        if !KNOWN_EDGE_TYPES.contains(&edge_type) {
+            log::warn!("Edge rule for unknown edge type '{}' will never apply", edge_type);
+        }
+        self.rules.insert(edge_type.to_string(), rule);
+    }
+
+    /// Drop the rule of an edge type, allowing any such edge
+    pub fn remove_rule(&mut self, edge_type: &str) -> Option<EdgeRule> {
+        self.rules.remove(edge_type)
+    }
+
+    /// Every rule a new edge would break
+    ///
+    /// Node type constraints are skipped for ends whose type is not known.
+    pub fn violations(
+        &self,
+        edges: &EdgeManager,
+        source: SceneId,
+        source_type: Option<&NodeType>,
+        target: SceneId,
+        target_type: Option<&NodeType>,
+        edge_type: &EdgeType,
+    ) -> Vec<EdgeViolation> {
+        let name = edge_type_name(edge_type);
+        let Some(rule) = self.rules.get(name) else {
+            return Vec::new();
+        };
+        let mut violations = Vec::new();
+
+        if let Some(node_type) = source_type.map(node_type_name) {
+            if !EdgeRule::allows(&rule.sources, node_type) {
+                violations.push(EdgeViolation::SourceType { edge_type: name, node_type, allowed: rule.sources.clone() });
+            }
+        }
+        if let Some(node_type) = target_type.map(node_type_name) {
+            if !EdgeRule::allows(&rule.targets, node_type) {
+                violations.push(EdgeViolation::TargetType { edge_type: name, node_type, allowed: rule.targets.clone() });
+            }
+        }
+        if let Some(limit) = rule.max_outgoing {
+            if edges.count_edges_of_type(source, edge_type, true) >= limit {
+                violations.push(EdgeViolation::TooManyOutgoing { edge_type: name, node_id: source, limit });
+            }
+        }
+        if let Some(limit) = rule.max_incoming {
+            if edges.count_edges_of_type(target, edge_type, false) >= limit {
+                violations.push(EdgeViolation::TooManyIncoming { edge_type: name, node_id: target, limit });
+            }
+        }
+        if rule.acyclic && (source == target || edges.reaches(target, source, edge_type)) {
+            violations.push(EdgeViolation::Cycle { edge_type: name });
+        }
+        violations
+    }
+
+    /// Check a new edge according to the validation mode
+    pub fn validate(
+        &self,
+        edges: &EdgeManager,
+        source: SceneId,
+        source_type: Option<&NodeType>,
+        target: SceneId,
+        target_type: Option<&NodeType>,
+        edge_type: &EdgeType,
+    ) -> Result<(), EdgeError> {
+        if self.mode == ValidationMode::Off {
+            return Ok(());
+        }
+        let violations = self.violations(edges, source, source_type, target, target_type, edge_type);
+        if self.mode == ValidationMode::Warn {
+            for violation in &violations {
+                log::warn!("Allowing edge {} -> {} despite rule: {}", source, target, violation);
+            }
+            return Ok(());
+        }
+        match violations.into_iter().next() {
+            None => Ok(()),
+            Some(EdgeViolation::Cycle { .. }) => Err(EdgeError::CircularDependency),
+            Some(violation) => Err(EdgeError::ConstraintViolation { source, target, violation }),
+        }
+    }
+}
+
+impl Default for EdgeValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const KNOWN_EDGE_TYPES: &[&str] =
+    &["contains", "depends_on", "communicates_with", "created_by", "related_to", "temporal", "tagged_as", "works_on"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::{ContactInfo, NodeMetadata, Position, Scene, SceneNode, SettingScope, SettingType, TaskStatus, Vec3};
+
+    fn person() -> NodeType {
+        NodeType::Person {
+            name: "Ada".to_string(),
+            contact_info: ContactInfo { email: None, phone: None, social: HashMap::new() },
+        }
+    }
+
+    fn setting() -> NodeType {
+        NodeType::Setting {
+            key: "theme".to_string(),
+            value: "dark".to_string(),
+            setting_type: SettingType::String,
+            scope: SettingScope::User,
+        }
+    }
+
+    fn task() -> NodeType {
+        NodeType::Task { title: "Ship it".to_string(), status: TaskStatus::Todo }
+    }
+
+    #[test]
+    fn test_node_types_are_checked() {
+        let mut manager = EdgeManager::new();
+        let result = manager.add_typed_edge(1, &setting(), 2, &person(), EdgeType::WorksOn);
+        match result {
+            Err(EdgeError::ConstraintViolation { violation: EdgeViolation::SourceType { node_type, .. }, .. }) => {
+                assert_eq!(node_type, "setting")
+            }
+            other => panic!("expected a source type violation, got {:?}", other),
+        }
+        assert!(manager.add_typed_edge(2, &person(), 3, &task(), EdgeType::WorksOn).is_ok());
+    }
+
+    #[test]
+    fn test_cardinality_and_cycles() {
+        let mut manager = EdgeManager::new();
+        manager.add_edge(1, 3, EdgeType::Contains).unwrap();
+        assert!(matches!(
+            manager.add_edge(2, 3, EdgeType::Contains),
+            Err(EdgeError::ConstraintViolation { violation: EdgeViolation::TooManyIncoming { limit: 1, .. }, .. })
+        ));
+        // Cycles are only checked within the edge type
+        manager.add_edge(3, 4, EdgeType::RelatedTo { similarity: 0.5 }).unwrap();
+        manager.add_edge(4, 1, EdgeType::DependsOn).unwrap();
+        assert!(matches!(manager.add_edge(1, 1, EdgeType::DependsOn), Err(EdgeError::CircularDependency)));
+    }
+
+    #[test]
+    fn test_config_escape_hatch() {
+        let mut config = EdgeValidationConfig::default();
+        config.rules.insert("works_on".to_string(), EdgeRule::default());
+        let mut manager = EdgeManager::new();
+        manager.set_validator(EdgeValidator::from_config(&config));
+        assert!(manager.add_typed_edge(1, &setting(), 2, &person(), EdgeType::WorksOn).is_ok());
+
+        config.mode = ValidationMode::Warn;
+        manager.set_validator(EdgeValidator::from_config(&config));
+        manager.add_edge(1, 3, EdgeType::Contains).unwrap();
+        assert!(manager.add_edge(2, 3, EdgeType::Contains).is_ok());
+    }
+    
+    #[test]
+    fn test_scene_edges_are_checked_against_node_types() {
+        let mut scene = Scene::new();
+        let mut add = |node_type| {
+            scene.add_node(SceneNode {
+                id: 0,
+                position: Position::origin(),
+                velocity: Vec3::zeros(),
+                radius: 1.0,
+                color: [1.0; 4],
+                node_type,
+                metadata: NodeMetadata::default(),
+                visible: true,
+                selected: false,
+            })
+        };
+        let (setting, task) = (add(setting()), add(task()));
+        let mut manager = EdgeManager::new();
+        assert!(matches!(
+            manager.add_scene_edge(&scene, setting, task, EdgeType::WorksOn),
+            Err(EdgeError::ConstraintViolation { violation: EdgeViolation::SourceType { .. }, .. })
+        ));
+        // Unknown ends only skip the node type checks
+        assert!(manager.add_scene_edge(&scene, 99, task, EdgeType::WorksOn).is_ok());
+    }
+    
+    #[test]
+    fn test_mode_off_from_config() {
+        let config: EdgeValidationConfig = serde_json::from_str(r#"{ "mode": "off" }"#).unwrap();
+        let mut manager = EdgeManager::new();
+        manager.set_validator(EdgeValidator::from_config(&config));
+        assert_eq!(manager.validator().mode(), ValidationMode::Off);
+        assert!(manager.add_typed_edge(1, &setting(), 2, &person(), EdgeType::WorksOn).is_ok());
+        manager.add_edge(1, 3, EdgeType::DependsOn).unwrap();
+        assert!(manager.add_edge(3, 1, EdgeType::DependsOn).is_ok());
+    }
+}
@@ -0,0 +1,568 @@
+//! Memory-mapped asset pack for built-in icons, themes and shaders
+//!
+//! Built-in assets are bundled into a single file that is memory-mapped at
+//! startup instead of opening every asset individually. The pack stores data
+//! grouped by kind (shaders first, then themes, then icons) with each entry
+//! aligned to a cache line, followed by an index of names and offsets.
+//!
+//! An override directory, laid out as `<dir>/<kind>/<name>`, takes precedence
+//! over the pack so assets can be edited during development without rebuilding it.
+
+use crate::GraphEngineError;
+use memmap2::Mmap;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Magic bytes at the start of a pack
+const PACK_MAGIC: [u8; 4] = *b"HZAP";
+/// Pack format version
+const PACK_VERSION: u32 = 1;
+/// Size of the fixed header
+const HEADER_LEN: usize = 32;
+/// Alignment of entry data
+const ENTRY_ALIGNMENT: usize = 64;
+
+/// Default location of the system asset pack
+pub const DEFAULT_ASSET_PACK: &str = "/usr/share/horizonos/assets.hzp";
+
+/// Kinds of assets stored in a pack, in on-disk order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AssetKind {
+    Shader,
+    Theme,
+    Icon,
+    Other,
+}
+
+/// Location of one asset inside the pack
+#[derive(Debug, Clone, Copy)]
+struct AssetEntry {
+    offset: usize,
+    len: usize,
+    checksum: u32,
+}
+
+/// A memory-mapped asset pack
+#[derive(Debug)]
+pub struct AssetPack {
+    /// Path the pack was opened from
+    path: PathBuf,
+    /// Mapped file contents
+    mmap: Mmap,
+    /// Entry index keyed by kind, then name
+    entries: HashMap<AssetKind, HashMap<String, AssetEntry>>,
+    /// Byte range covered by each kind, for prefetching
+    kind_ranges: HashMap<AssetKind, (usize, usize)>,
+}
+
+/// Builder that writes asset packs
+#[derive(Debug, Default)]
+pub struct AssetPackBuilder {
+    assets: Vec<(AssetKind, String, Vec<u8>)>,
+}
+
+/// Asset bytes, either borrowed from the pack mapping or read from an override file
+#[derive(Debug, Clone)]
+pub enum AssetData {
+    /// Slice of a mapped pack
+    Mapped {
+        pack: Arc<AssetPack>,
+        offset: usize,
+        len: usize,
+    },
+    /// Bytes read from the override directory
+    Owned(Arc<[u8]>),
+}
+
+/// Asset lookup across the pack and the override directory
+#[derive(Debug, Clone, Default)]
+pub struct AssetStore {
+    /// Mapped asset pack
+    pack: Option<Arc<AssetPack>>,
+    /// Development override directory
+    override_dir: Option<PathBuf>,
+}
+
+impl AssetKind {
+    /// Directory name used in override directories and when packing directories
+    pub fn dir_name(self) -> &'static str {
+        match self {
+            AssetKind::Shader => "shaders",
+            AssetKind::Theme => "themes",
+            AssetKind::Icon => "icons",
+            AssetKind::Other => "other",
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            AssetKind::Shader => 0,
+            AssetKind::Theme => 1,
+            AssetKind::Icon => 2,
+            AssetKind::Other => 3,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(AssetKind::Shader),
+            1 => Some(AssetKind::Theme),
+            2 => Some(AssetKind::Icon),
+            3 => Some(AssetKind::Other),
+            _ => None,
+        }
+    }
+}
+
+impl AssetPack {
+    /// Map a pack file and read its index
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, GraphEngineError> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        // Safety: packs are written once at install time and only read afterwards
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let invalid = |reason: &str| {
+            GraphEngineError::AssetError(format!("Invalid asset pack {}: {}", path.display(), reason))
+        };
+
+        if mmap.len() < HEADER_LEN || mmap[0..4] != PACK_MAGIC {
+            return Err(invalid("bad header"));
+        }
+
+        let version = read_u32(&mmap, 4);
+        if version != PACK_VERSION {
+            return Err(invalid(&format!("unsupported version {}", version)));
+        }
+
+        let entry_count = read_u32(&mmap, 8) as usize;
+        let index_offset = read_u64(&mmap, 16) as usize;
+        let index_len = read_u64(&mmap, 24) as usize;
+        if index_offset.checked_add(index_len).is_none_or(|end| end > mmap.len()) {
+            return Err(invalid("index out of bounds"));
+        }
+
+        let mut entries: HashMap<AssetKind, HashMap<String, AssetEntry>> = HashMap::new();
+        let mut kind_ranges: HashMap<AssetKind, (usize, usize)> = HashMap::new();
+        let mut cursor = index_offset;
+        let index_end = index_offset + index_len;
+
+        for _ in 0..entry_count {
+            if cursor + 24 > index_end {
+                return Err(invalid("truncated index"));
+            }
+
+            let kind = AssetKind::from_byte(mmap[cursor]).ok_or_else(|| invalid("unknown asset kind"))?;
+            let name_len = u16::from_le_bytes([mmap[cursor + 2], mmap[cursor + 3]]) as usize;
+            let checksum = read_u32(&mmap, cursor + 4);
+            let offset = read_u64(&mmap, cursor + 8) as usize;
+            let len = read_u64(&mmap, cursor + 16) as usize;
+            cursor += 24;
+
+            if cursor + name_len > index_end {
+                return Err(invalid("truncated index"));
+            }
+            let name = std::str::from_utf8(&mmap[cursor..cursor + name_len])
+                .map_err(|_| invalid("asset name is not UTF-8"))?
+                .to_string();
+            cursor += name_len;
+
+            if offset.checked_add(len).is_none_or(|end| end > index_offset) {
+                return Err(invalid(&format!("entry {} out of bounds", name)));
+            }
+
+            let range = kind_ranges.entry(kind).or_insert((offset, offset + len));
+            range.0 = range.0.min(offset);
+            range.1 = range.1.max(offset + len);
+
+            entries.entry(kind).or_default().insert(name, AssetEntry { offset, len, checksum });
+        }
+
+        log::debug!("Mapped asset pack {} with {} entries", path.display(), entry_count);
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            mmap,
+            entries,
+            kind_ranges,
+        })
+    }
+
+    /// Path the pack was opened from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of assets in the pack
+    pub fn len(&self) -> usize {
+        self.entries.values().map(HashMap::len).sum()
+    }
+
+    /// Whether the pack is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the bytes of an asset without copying
+    pub fn get(&self, kind: AssetKind, name: &str) -> Option<&[u8]> {
+        self.entry(kind, name).map(|entry| &self.mmap[entry.offset..entry.offset + entry.len])
+    }
+
+    /// Whether the pack contains an asset
+    pub fn contains(&self, kind: AssetKind, name: &str) -> bool {
+        self.entry(kind, name).is_some()
+    }
+
+    /// Names of all assets of a kind
+    pub fn names(&self, kind: AssetKind) -> Vec<&str> {
+        let mut names: Vec<&str> = self.entries
+            .get(&kind)
+            .map(|entries| entries.keys().map(String::as_str).collect())
+            .unwrap_or_default();
+        names.sort_unstable();
+        names
+    }
+
+    /// Ask the kernel to read all assets of a kind ahead of use
+    pub fn prefetch(&self, kind: AssetKind) {
+        #[cfg(unix)]
+        if let Some(&(start, end)) = self.kind_ranges.get(&kind) {
+            if let Err(e) = self.mmap.advise_range(memmap2::Advice::WillNeed, start, end - start) {
+                log::debug!("Asset prefetch failed: {}", e);
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = kind;
+    }
+
+    /// Check every entry against its stored checksum
+    pub fn verify(&self) -> Result<(), GraphEngineError> {
+        for (kind, name, entry) in self.entries.iter().flat_map(|(kind, entries)| {
+            entries.iter().map(move |(name, entry)| (kind, name, entry))
+        }) {
+            let data = &self.mmap[entry.offset..entry.offset + entry.len];
+            if fnv1a(data) != entry.checksum {
+                return Err(GraphEngineError::AssetError(format!(
+                    "Checksum mismatch for {}/{} in {}",
+                    kind.dir_name(),
+                    name,
+                    self.path.display()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn entry(&self, kind: AssetKind, name: &str) -> Option<&AssetEntry> {
+        self.entries.get(&kind)?.get(name)
+    }
+}
+
+impl AssetPackBuilder {
+    /// Create an empty builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an asset
+    pub fn add(mut self, kind: AssetKind, name: impl Into<String>, data: impl Into<Vec<u8>>) -> Self {
+        self.assets.push((kind, name.into(), data.into()));
+        self
+    }
+
+    /// Add every file below a directory, named by its path relative to the directory
+    pub fn add_directory(mut self, kind: AssetKind, dir: impl AsRef<Path>) -> Result<Self, GraphEngineError> {
+        let dir = dir.as_ref();
+        let mut pending = vec![dir.to_path_buf()];
+
+        while let Some(current) = pending.pop() {
+            for entry in std::fs::read_dir(&current)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else if let Ok(relative) = path.strip_prefix(dir) {
+                    let name = relative.to_string_lossy().replace('\\', "/");
+                    let data = std::fs::read(&path)?;
+                    self.assets.push((kind, name, data));
+                }
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Write the pack to a file
+    pub fn write(mut self, path: impl AsRef<Path>) -> Result<(), GraphEngineError> {
+        // Group by kind so assets needed together at startup share pages
+        self.assets.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+        self.assets.dedup_by(|a, b| a.0 == b.0 && a.1 == b.1);
+
+        let mut out = BufWriter::new(File::create(path.as_ref())?);
+        let mut position = HEADER_LEN;
+        let mut index = Vec::new();
+
+        out.write_all(&[0u8; HEADER_LEN])?;
+
+        for (kind, name, data) in &self.assets {
+            let padding = (ENTRY_ALIGNMENT - position % ENTRY_ALIGNMENT) % ENTRY_ALIGNMENT;
+            out.write_all(&vec![0u8; padding])?;
+            position += padding;
+
+            index.push(kind.to_byte());
+            index.push(0);
+            index.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            index.extend_from_slice(&fnv1a(data).to_le_bytes());
+            index.extend_from_slice(&(position as u64).to_le_bytes());
+            index.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            index.extend_from_slice(name.as_bytes());
+
+            out.write_all(data)?;
+            position += data.len();
+        }
+
+        out.write_all(&index)?;
+
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(&PACK_MAGIC);
+        header.extend_from_slice(&PACK_VERSION.to_le_bytes());
+        header.extend_from_slice(&(self.assets.len() as u32).to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&(position as u64).to_le_bytes());
+        header.extend_from_slice(&(index.len() as u64).to_le_bytes());
+
+        let mut file = out.into_inner().map_err(|e| e.into_error())?;
+        std::io::Seek::seek(&mut file, std::io::SeekFrom::Start(0))?;
+        file.write_all(&header)?;
+        file.sync_all()?;
+
+        Ok(())
+    }
+}
+
+impl AssetData {
+    /// Asset bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            AssetData::Mapped { pack, offset, len } => &pack.mmap[*offset..*offset + *len],
+            AssetData::Owned(bytes) => bytes,
+        }
+    }
+
+    /// Asset bytes as UTF-8 text
+    pub fn as_str(&self) -> Result<&str, GraphEngineError> {
+        std::str::from_utf8(self.as_bytes())
+            .map_err(|e| GraphEngineError::AssetError(format!("Asset is not UTF-8: {}", e)))
+    }
+}
+
+impl std::ops::Deref for AssetData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl AssetStore {
+    /// Create a store with no pack and no override directory
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configure from `HORIZONOS_ASSET_PACK` and `HORIZONOS_ASSET_OVERRIDE`
+    ///
+    /// A missing pack is not an error; lookups then only use the override
+    /// directory and callers fall back to their compiled-in defaults.
+    pub fn from_env() -> Self {
+        let pack_path = std::env::var("HORIZONOS_ASSET_PACK")
+            .unwrap_or_else(|_| DEFAULT_ASSET_PACK.to_string());
+
+        let mut store = Self::new();
+        match AssetPack::open(&pack_path) {
+            Ok(pack) => store.pack = Some(Arc::new(pack)),
+            Err(e) => log::debug!("No asset pack loaded from {}: {}", pack_path, e),
+        }
+
+        if let Ok(dir) = std::env::var("HORIZONOS_ASSET_OVERRIDE") {
+            store.override_dir = Some(PathBuf::from(dir));
+        }
+
+        store
+    }
+
+    /// Use a mapped pack
+    pub fn with_pack(mut self, pack: AssetPack) -> Self {
+        self.pack = Some(Arc::new(pack));
+        self
+    }
+
+    /// Look in a development override directory before the pack
+    pub fn with_override_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.override_dir = Some(dir.into());
+        self
+    }
+
+    /// The mapped pack, if any
+    pub fn pack(&self) -> Option<&Arc<AssetPack>> {
+        self.pack.as_ref()
+    }
+
+    /// Load an asset
+    pub fn load(&self, kind: AssetKind, name: &str) -> Result<AssetData, GraphEngineError> {
+        if let Some(dir) = &self.override_dir {
+            let path = dir.join(kind.dir_name()).join(name);
+            if path.is_file() {
+                log::debug!("Loading overridden asset {}", path.display());
+                return Ok(AssetData::Owned(std::fs::read(&path)?.into()));
+            }
+        }
+
+        let pack = self.pack.as_ref().ok_or_else(|| {
+            GraphEngineError::AssetError(format!("Asset {}/{} not found", kind.dir_name(), name))
+        })?;
+
+        let entry = pack.entry(kind, name).copied().ok_or_else(|| {
+            GraphEngineError::AssetError(format!("Asset {}/{} not found", kind.dir_name(), name))
+        })?;
+
+        Ok(AssetData::Mapped {
+            pack: pack.clone(),
+            offset: entry.offset,
+            len: entry.len,
+        })
+    }
+
+    /// Load text, falling back to a compiled-in default when the asset is missing
+    pub fn load_text_or(&self, kind: AssetKind, name: &str, default: &str) -> String {
+        match self.load(kind, name).and_then(|data| data.as_str().map(str::to_string)) {
+            Ok(text) => text,
+            Err(_) => default.to_string(),
+        }
+    }
+
+    /// Load an asset without blocking the async runtime
+    pub async fn load_async(&self, kind: AssetKind, name: &str) -> Result<AssetData, GraphEngineError> {
+        let store = self.clone();
+        let name = name.to_string();
+        tokio::task::spawn_blocking(move || {
+            let data = store.load(kind, &name)?;
+            // Fault the pages in here rather than on the caller's thread
+            let _ = data.as_bytes().iter().step_by(4096).fold(0u8, |acc, b| acc ^ b);
+            Ok(data)
+        })
+        .await
+        .map_err(|e| GraphEngineError::ThreadPoolError(e.to_string()))?
+    }
+
+    /// Load several assets concurrently
+    pub async fn load_many_async(
+        &self,
+        requests: &[(AssetKind, &str)],
+    ) -> Vec<Result<AssetData, GraphEngineError>> {
+        futures::future::join_all(
+            requests.iter().map(|&(kind, name)| self.load_async(kind, name)),
+        )
+        .await
+    }
+
+    /// Prefetch all assets of a kind from the pack
+    pub fn prefetch(&self, kind: AssetKind) {
+        if let Some(pack) = &self.pack {
+            pack.prefetch(kind);
+        }
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+/// FNV-1a hash used as entry checksum
+fn fnv1a(data: &[u8]) -> u32 {
+    data.iter().fold(0x811c9dc5u32, |hash, &byte| (hash ^ byte as u32).wrapping_mul(0x01000193))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("horizonos-assets-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_pack_round_trip() {
+        let dir = temp_dir("round-trip");
+        let pack_path = dir.join("assets.hzp");
+
+        AssetPackBuilder::new()
+            .add(AssetKind::Icon, "apps/terminal.png", vec![1u8, 2, 3])
+            .add(AssetKind::Shader, "node.wgsl", "fn main() {}")
+            .add(AssetKind::Theme, "dark.toml", "name = \"dark\"")
+            .write(&pack_path)
+            .unwrap();
+
+        let pack = AssetPack::open(&pack_path).unwrap();
+        assert_eq!(pack.len(), 3);
+        assert_eq!(pack.get(AssetKind::Icon, "apps/terminal.png"), Some(&[1u8, 2, 3][..]));
+        assert_eq!(pack.get(AssetKind::Shader, "node.wgsl"), Some(&b"fn main() {}"[..]));
+        assert!(pack.get(AssetKind::Icon, "node.wgsl").is_none());
+        assert_eq!(pack.names(AssetKind::Theme), vec!["dark.toml"]);
+        pack.verify().unwrap();
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_override_dir_takes_precedence() {
+        let dir = temp_dir("override");
+        let pack_path = dir.join("assets.hzp");
+        AssetPackBuilder::new()
+            .add(AssetKind::Shader, "edge.wgsl", "packed")
+            .write(&pack_path)
+            .unwrap();
+
+        let override_dir = dir.join("override");
+        std::fs::create_dir_all(override_dir.join("shaders")).unwrap();
+        std::fs::write(override_dir.join("shaders/edge.wgsl"), "overridden").unwrap();
+
+        let store = AssetStore::new().with_pack(AssetPack::open(&pack_path).unwrap());
+        assert_eq!(store.load(AssetKind::Shader, "edge.wgsl").unwrap().as_str().unwrap(), "packed");
+
+        let store = store.with_override_dir(&override_dir);
+        assert_eq!(store.load(AssetKind::Shader, "edge.wgsl").unwrap().as_str().unwrap(), "overridden");
+        assert_eq!(store.load_text_or(AssetKind::Shader, "missing.wgsl", "builtin"), "builtin");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_renderer_shaders_come_from_the_pack() {
+        use crate::renderer::shaders::{self, ShaderSources};
+
+        let dir = temp_dir("shaders");
+        let pack_path = dir.join("assets.hzp");
+        AssetPackBuilder::new()
+            .add(AssetKind::Shader, shaders::EDGE_VERTEX_SHADER_NAME, "packed edge shader")
+            .write(&pack_path)
+            .unwrap();
+
+        let store = AssetStore::new().with_pack(AssetPack::open(&pack_path).unwrap());
+        let sources = ShaderSources::load(&store);
+        assert_eq!(sources.edge_vertex, "packed edge shader");
+        assert_eq!(sources.node_vertex, shaders::NODE_VERTEX_SHADER);
+        assert_eq!(ShaderSources::default().edge_vertex, shaders::EDGE_VERTEX_SHADER);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
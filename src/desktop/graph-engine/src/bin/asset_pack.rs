@@ -0,0 +1,63 @@
+//! Build the asset pack of built-in shaders, themes and icons
+//!
+//! Usage: horizonos-asset-pack <output> [--shaders <dir>] [--themes <dir>] [--icons <dir>]
+
+use horizonos_graph_engine::renderer::shaders::builtin_shaders;
+use horizonos_graph_engine::{AssetKind, AssetPack, AssetPackBuilder, GraphEngineError};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "--help") {
+        println!("Usage: horizonos-asset-pack <output> [options]");
+        println!();
+        println!("Options:");
+        println!("  --shaders <dir>  Shaders replacing the compiled-in ones, e.g. node_vertex.wgsl");
+        println!("  --themes <dir>   Theme files, e.g. config/themes");
+        println!("  --icons <dir>    Icons laid out as <size>/<name>.png and scalable/<name>.svg");
+        return ExitCode::SUCCESS;
+    }
+
+    let output = PathBuf::from(&args[0]);
+    let option = |name: &str| {
+        args.iter().position(|arg| arg == name).and_then(|i| args.get(i + 1)).map(PathBuf::from)
+    };
+
+    match build(&output, option("--shaders"), option("--themes"), option("--icons")) {
+        Ok(pack) => {
+            println!("{}: {} assets", output.display(), pack.len());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn build(
+    output: &PathBuf,
+    shaders: Option<PathBuf>,
+    themes: Option<PathBuf>,
+    icons: Option<PathBuf>,
+) -> Result<AssetPack, GraphEngineError> {
+    let mut builder = AssetPackBuilder::new();
+    // Of assets with the same name the first added is kept, so directories go before the compiled-in shaders
+    for (kind, dir) in [(AssetKind::Shader, shaders), (AssetKind::Theme, themes), (AssetKind::Icon, icons)] {
+        if let Some(dir) = dir {
+            builder = builder.add_directory(kind, dir)?;
+        }
+    }
+    for (name, source) in builtin_shaders() {
+        builder = builder.add(AssetKind::Shader, name, source);
+    }
+    if let Some(parent) = output.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    builder.write(output)?;
+
+    let pack = AssetPack::open(output)?;
+    pack.verify()?;
+    Ok(pack)
+}
@@ -1,6 +1,16 @@
 //! Camera system for 3D navigation in the graph space
 
 use nalgebra::{Matrix4, Point3, Vector3, Perspective3};
+use serde::{Deserialize, Serialize};
+
+/// Where a camera is and where it looks, saved with the session
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraState {
+    pub position: Point3<f32>,
+    pub forward: Vector3<f32>,
+    pub up: Vector3<f32>,
+    pub fov: f32,
+}
 
 /// Camera for navigating the 3D graph space
 #[derive(Debug, Clone)]
@@ -34,6 +44,8 @@ pub struct Camera {
     target_position: Option<Point3<f32>>,
     /// Target look direction for smooth rotation
     target_forward: Option<Vector3<f32>>,
+    /// Target field of view for smooth zooming
+    target_fov: Option<f32>,
     /// Movement interpolation speed
     interpolation_speed: f32,
 }
@@ -60,6 +72,7 @@ impl Camera {
             zoom_speed: 1.0,
             target_position: None,
             target_forward: None,
+            target_fov: None,
             interpolation_speed: 5.0,
         }
     }
@@ -91,6 +104,22 @@ impl Camera {
                 self.update_coordinate_system();
             }
         }
+        
+        // Smooth zoom to target field of view
+        if let Some(target) = self.target_fov {
+            let diff = target - self.fov;
+            if diff.abs() < 0.001 {
+                self.fov = target;
+                self.target_fov = None;
+            } else {
+                self.fov += diff * (self.interpolation_speed * delta_time).min(1.0);
+            }
+        }
+    }
+    
+    /// Whether a smooth movement, rotation or zoom is still in progress
+    pub fn is_animating(&self) -> bool {
+        self.target_position.is_some() || self.target_forward.is_some() || self.target_fov.is_some()
     }
     
     /// Get the view matrix
@@ -109,6 +138,15 @@ impl Camera {
         self.projection_matrix() * self.view_matrix()
     }
     
+    /// World units one pixel spans at a point, for a viewport of the given
+    /// height in pixels
+    pub fn pixel_size_at(&self, point: Point3<f32>, viewport_height: f32) -> f32 {
+        // Depth along the view direction, not distance, so sizes don't
+        // change towards the edges of the screen
+        let depth = (point - self.position).dot(&self.forward).max(self.near);
+        2.0 * depth * (self.fov * 0.5).tan() / viewport_height.max(1.0)
+    }
+    
     /// Move the camera forward/backward
     pub fn move_forward(&mut self, distance: f32) {
         self.position += self.forward * distance;
@@ -167,8 +205,53 @@ impl Camera {
         self.aspect_ratio = aspect_ratio;
     }
     
+    /// Current view, ignoring any movement still in progress
+    pub fn state(&self) -> CameraState {
+        CameraState {
+            position: self.target_position.unwrap_or(self.position),
+            forward: self.target_forward.unwrap_or(self.forward),
+            up: self.up,
+            fov: self.target_fov.unwrap_or(self.fov),
+        }
+    }
+    
+    /// Jump to a saved view
+    pub fn restore_state(&mut self, state: &CameraState) {
+        self.position = state.position;
+        self.forward = state.forward.normalize();
+        self.up = state.up.normalize();
+        self.fov = state.fov;
+        self.target_position = None;
+        self.target_forward = None;
+        self.target_fov = None;
+        self.update_coordinate_system();
+    }
+    
+    /// Glide to a saved view, moving, turning and zooming together
+    pub fn transition_to(&mut self, state: &CameraState) {
+        self.up = state.up.normalize();
+        self.target_position = Some(state.position);
+        self.target_forward = Some(state.forward.normalize());
+        self.target_fov = Some(state.fov);
+    }
+    
+    /// Take the view a smooth movement is heading for, leaving the camera where it is
+    ///
+    /// For owners that animate the camera themselves instead of through [`Self::update`].
+    pub fn take_transition(&mut self) -> Option<CameraState> {
+        if !self.is_animating() {
+            return None;
+        }
+        let target = self.state();
+        self.target_position = None;
+        self.target_forward = None;
+        self.target_fov = None;
+        Some(target)
+    }
+
     /// Zoom in/out by adjusting FOV
     pub fn zoom(&mut self, delta: f32) {
+        self.target_fov = None;
         self.fov = (self.fov + delta * self.zoom_speed).clamp(
             std::f32::consts::FRAC_PI_6, // 30 degrees min
             std::f32::consts::FRAC_PI_2, // 90 degrees max
@@ -195,6 +278,17 @@ impl Camera {
         }
     }
     
+    /// Pixel position of a point on a screen of the given size, `None` when it is behind the camera
+    pub fn world_to_screen(&self, point: Point3<f32>, screen_width: f32, screen_height: f32) -> Option<(f32, f32)> {
+        let clip = self.view_projection_matrix() * point.to_homogeneous();
+        if clip.w <= 0.0 {
+            return None;
+        }
+        let ndc_x = clip.x / clip.w;
+        let ndc_y = clip.y / clip.w;
+        Some(((ndc_x + 1.0) * 0.5 * screen_width, (1.0 - ndc_y) * 0.5 * screen_height))
+    }
+    
     /// Focus on a specific node or region
     pub fn focus_on_bounds(&mut self, center: Point3<f32>, radius: f32) {
         // Calculate optimal distance to view the bounds
@@ -0,0 +1,142 @@
+//! Edge visibility filters and presets
+//!
+//! An [`EdgeFilter`] hides whole edge types and edges weaker than a
+//! threshold. The renderer applies it while collecting edge vertices each
+//! frame, so switching filters never touches the scene or the edges'
+//! `visible` flags. Types are named by their palette key ([`edge_kind`]),
+//! which is also how filters are written in workspaces and on the command
+//! line.
+
+use crate::{edge_kind, EdgeType, SceneEdge};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Palette keys of all edge types
+pub const EDGE_KINDS: &[&str] = &[
+    "contains",
+    "depends_on",
+    "communicates_with",
+    "created_by",
+    "related_to",
+    "temporal",
+    "tagged_as",
+    "works_on",
+];
+
+/// Which edges are drawn
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EdgeFilter {
+    /// Edge types not drawn, by palette key
+    pub hidden_types: BTreeSet<String>,
+    /// Edges with a lower weight are not drawn
+    pub min_strength: f32,
+}
+
+impl EdgeFilter {
+    /// Filter drawing only the given edge types
+    pub fn only(kinds: &[&str]) -> Self {
+        Self {
+            hidden_types: EDGE_KINDS.iter().filter(|kind| !kinds.contains(kind)).map(|kind| kind.to_string()).collect(),
+            min_strength: 0.0,
+        }
+    }
+
+    /// Whether the filter draws every edge
+    pub fn shows_all(&self) -> bool {
+        self.hidden_types.is_empty() && self.min_strength <= 0.0
+    }
+
+    /// Whether an edge is drawn
+    pub fn shows(&self, edge: &SceneEdge) -> bool {
+        edge.weight >= self.min_strength && self.shows_type(&edge.edge_type)
+    }
+
+    pub fn shows_type(&self, edge_type: &EdgeType) -> bool {
+        !self.hidden_types.contains(edge_kind(edge_type))
+    }
+
+    /// Show or hide an edge type; `false` for unknown type names
+    pub fn set_type_visible(&mut self, kind: &str, visible: bool) -> bool {
+        if !EDGE_KINDS.contains(&kind) {
+            return false;
+        }
+        if visible {
+            self.hidden_types.remove(kind);
+        } else {
+            self.hidden_types.insert(kind.to_string());
+        }
+        true
+    }
+
+    /// Flip the visibility of an edge type; `false` for unknown type names
+    pub fn toggle_type(&mut self, kind: &str) -> bool {
+        let hidden = self.hidden_types.contains(kind);
+        self.set_type_visible(kind, hidden)
+    }
+
+    /// Hide edges weaker than `strength`, clamped to 0..=1
+    pub fn set_min_strength(&mut self, strength: f32) {
+        self.min_strength = strength.clamp(0.0, 1.0);
+    }
+}
+
+/// A named filter offered in the command palette
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgePreset {
+    /// Identifier used on the command line, e.g. `structure`
+    pub name: &'static str,
+    /// Palette label
+    pub label: &'static str,
+    pub filter: EdgeFilter,
+}
+
+impl EdgePreset {
+    /// Presets offered by default
+    pub fn builtin() -> Vec<EdgePreset> {
+        vec![
+            EdgePreset { name: "all", label: "All Edges", filter: EdgeFilter::default() },
+            EdgePreset { name: "structure", label: "Structure Only", filter: EdgeFilter::only(&["contains", "depends_on"]) },
+            EdgePreset { name: "social", label: "Social", filter: EdgeFilter::only(&["communicates_with", "works_on", "created_by"]) },
+            EdgePreset { name: "semantic", label: "Related Content", filter: EdgeFilter::only(&["related_to", "tagged_as"]) },
+            EdgePreset {
+                name: "strong",
+                label: "Strong Edges Only",
+                filter: EdgeFilter { min_strength: 0.5, ..EdgeFilter::default() },
+            },
+            EdgePreset { name: "none", label: "Hide All Edges", filter: EdgeFilter::only(&[]) },
+        ]
+    }
+
+    /// Built-in preset by name
+    pub fn named(name: &str) -> Option<EdgePreset> {
+        Self::builtin().into_iter().find(|preset| preset.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(edge_type: EdgeType, weight: f32) -> SceneEdge {
+        SceneEdge { id: 1, source: 1, target: 2, edge_type, weight, color: [1.0; 4], visible: true, animated: false }
+    }
+
+    #[test]
+    fn test_presets_and_thresholds() {
+        let structure = EdgePreset::named("structure").unwrap().filter;
+        assert!(structure.shows(&edge(EdgeType::Contains, 1.0)));
+        assert!(!structure.shows(&edge(EdgeType::CommunicatesWith, 1.0)));
+
+        let mut filter = EdgeFilter::default();
+        assert!(filter.shows_all());
+        assert!(filter.toggle_type("depends_on"));
+        assert!(!filter.shows(&edge(EdgeType::DependsOn, 1.0)));
+        assert!(!filter.toggle_type("nonsense"));
+
+        filter.set_min_strength(0.4);
+        assert!(!filter.shows(&edge(EdgeType::RelatedTo { similarity: 0.2 }, 0.3)));
+        assert!(filter.shows(&edge(EdgeType::RelatedTo { similarity: 0.2 }, 0.4)));
+        assert!(EdgePreset::builtin().iter().all(|preset| preset.filter.hidden_types.iter().all(|kind| EDGE_KINDS.contains(&kind.as_str()))));
+    }
+}
@@ -1,5 +1,6 @@
 //! Error types for the graph engine
 
+use horizonos_graph_errors::{Category, Classify, Problem, RecoveryAction, Severity};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -45,4 +46,50 @@ pub enum GraphEngineError {
     
     #[error("Lock error: {0}")]
     LockError(String),
-}
\ No newline at end of file
+    
+    #[error("Asset error: {0}")]
+    AssetError(String),
+    
+    #[error("Persistence error: {0}")]
+    PersistenceError(String),
+    
+    #[error("GPU device lost: {0}")]
+    DeviceLost(String),
+}
+
+impl Classify for GraphEngineError {
+    fn classify(&self) -> Problem {
+        let problem = match self {
+            GraphEngineError::AdapterNotFound | GraphEngineError::DeviceError(_) | GraphEngineError::SurfaceError(_) => {
+                Problem::new("engine.gpu_unavailable", Category::Rendering, Severity::Critical, "No usable graphics device")
+            }
+            GraphEngineError::DeviceLost(_) => {
+                Problem::new("engine.device_lost", Category::Rendering, Severity::Warning, "Graphics stopped responding, restarting them")
+            }
+            GraphEngineError::RenderError(_) | GraphEngineError::ShaderError(_) => {
+                Problem::new("engine.render_failed", Category::Rendering, Severity::Error, "Drawing the graph failed")
+            }
+            GraphEngineError::PhysicsError(_) => Problem::new("engine.physics", Category::Graph, Severity::Warning, "Physics simulation failed"),
+            GraphEngineError::SceneError(_) | GraphEngineError::CameraError(_) => {
+                Problem::new("engine.scene", Category::Graph, Severity::Warning, "Scene operation failed")
+            }
+            GraphEngineError::NodeNotFound(id) => {
+                Problem::new("engine.node_not_found", Category::Graph, Severity::Info, format!("Node {} no longer exists", id))
+            }
+            GraphEngineError::IoError(_) => Problem::new("engine.io", Category::Io, Severity::Error, "Could not read engine data"),
+            GraphEngineError::AssetError(_) => Problem::new("engine.asset", Category::Storage, Severity::Warning, "An icon or texture could not be loaded"),
+            GraphEngineError::PersistenceError(_) => Problem::new("engine.persistence", Category::Storage, Severity::Error, "The saved graph could not be read or written"),
+            GraphEngineError::System(_)
+            | GraphEngineError::ThreadPoolError(_)
+            | GraphEngineError::SchedulerShutdown
+            | GraphEngineError::LockError(_) => Problem::new("engine.internal", Category::Internal, Severity::Error, "Internal engine error"),
+        };
+        let problem = match self {
+            GraphEngineError::NodeNotFound(id) => problem.with_node(*id),
+            GraphEngineError::RenderError(_) | GraphEngineError::DeviceLost(_) => problem.transient(),
+            GraphEngineError::AdapterNotFound | GraphEngineError::DeviceError(_) => problem.with_recovery(RecoveryAction::open_settings("display")),
+            _ => problem,
+        };
+        problem.in_subsystem("engine").with_detail(self)
+    }
+}
@@ -0,0 +1,380 @@
+//! Undo/redo history for scene mutations
+//!
+//! Mutations go through [`CommandHistory::execute`], which applies a
+//! [`GraphCommand`] to the scene and records the resulting [`Change`] with
+//! everything needed to revert it, such as the full node and the edges a node
+//! deletion took with it. Changes made outside the history, like a node drag
+//! that moves the node every frame, are added afterwards with
+//! [`CommandHistory::record`].
+//!
+//! Changes between [`CommandHistory::begin`] and [`CommandHistory::commit`]
+//! form one transaction, so deleting a whole selection is undone in one step.
+
+use crate::{GraphEngineError, Position, Scene, SceneEdge, SceneId, SceneNode};
+use std::collections::VecDeque;
+
+/// A requested scene mutation
+#[derive(Debug, Clone)]
+pub enum GraphCommand {
+    /// Add a node; its ID is assigned by the scene
+    AddNode(Box<SceneNode>),
+    /// Remove a node and its edges
+    RemoveNode(SceneId),
+    /// Move a node
+    MoveNode { id: SceneId, to: Position },
+    /// Add an edge between existing nodes; its ID is assigned by the scene
+    AddEdge(SceneEdge),
+    /// Remove an edge
+    RemoveEdge(SceneId),
+}
+
+/// An applied scene mutation, with the state needed to revert it
+#[derive(Debug, Clone)]
+pub enum Change {
+    NodeAdded(SceneNode),
+    NodeRemoved { node: SceneNode, edges: Vec<SceneEdge> },
+    NodeMoved { id: SceneId, from: Position, to: Position },
+    /// Appearance or other node fields changed, e.g. in a bulk visual update
+    NodeUpdated { from: Box<SceneNode>, to: Box<SceneNode> },
+    EdgeAdded(SceneEdge),
+    EdgeRemoved(SceneEdge),
+}
+
+impl Change {
+    /// Apply the change again after it was reverted
+    pub fn apply(&self, scene: &mut Scene) {
+        match self {
+            Change::NodeAdded(node) => {
+                scene.insert_node(node.clone());
+            }
+            Change::NodeRemoved { node, .. } => {
+                scene.remove_node(node.id);
+            }
+            Change::NodeMoved { id, to, .. } => {
+                if let Some(node) = scene.get_node_mut(*id) {
+                    node.position = *to;
+                }
+            }
+            Change::NodeUpdated { to, .. } => {
+                scene.insert_node((**to).clone());
+            }
+            Change::EdgeAdded(edge) => {
+                scene.insert_edge(edge.clone());
+            }
+            Change::EdgeRemoved(edge) => {
+                scene.remove_edge(edge.id);
+            }
+        }
+    }
+
+    /// Restore the scene to its state before the change
+    pub fn revert(&self, scene: &mut Scene) {
+        match self {
+            Change::NodeAdded(node) => {
+                scene.remove_node(node.id);
+            }
+            Change::NodeRemoved { node, edges } => {
+                scene.insert_node(node.clone());
+                for edge in edges {
+                    scene.insert_edge(edge.clone());
+                }
+            }
+            Change::NodeMoved { id, from, .. } => {
+                if let Some(node) = scene.get_node_mut(*id) {
+                    node.position = *from;
+                }
+            }
+            Change::NodeUpdated { from, .. } => {
+                scene.insert_node((**from).clone());
+            }
+            Change::EdgeAdded(edge) => {
+                scene.remove_edge(edge.id);
+            }
+            Change::EdgeRemoved(edge) => {
+                scene.insert_edge(edge.clone());
+            }
+        }
+    }
+}
+
+/// Changes undone and redone together
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    /// Description for an "Undo …" menu entry
+    pub label: String,
+    pub changes: Vec<Change>,
+}
+
+/// Undo and redo stacks of scene transactions
+#[derive(Debug)]
+pub struct CommandHistory {
+    undo_stack: VecDeque<Transaction>,
+    redo_stack: Vec<Transaction>,
+    /// Transaction being collected between `begin` and `commit`
+    pending: Option<Transaction>,
+    /// Most transactions kept; the oldest are forgotten beyond this
+    max_depth: usize,
+}
+
+impl CommandHistory {
+    pub fn new() -> Self {
+        Self::with_max_depth(100)
+    }
+
+    pub fn with_max_depth(max_depth: usize) -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            pending: None,
+            max_depth: max_depth.max(1),
+        }
+    }
+
+    /// Apply a command and record it
+    ///
+    /// Returns the ID of the affected node or edge.
+    pub fn execute(&mut self, command: GraphCommand, scene: &mut Scene) -> Result<SceneId, GraphEngineError> {
+        let (id, change) = match command {
+            GraphCommand::AddNode(mut node) => {
+                node.id = scene.add_node((*node).clone());
+                (node.id, Change::NodeAdded(*node))
+            }
+            GraphCommand::RemoveNode(id) => {
+                let edges = scene.get_connected_edges(id).into_iter().cloned().collect();
+                let node = scene.remove_node(id).ok_or(GraphEngineError::NodeNotFound(id))?;
+                (id, Change::NodeRemoved { node, edges })
+            }
+            GraphCommand::MoveNode { id, to } => {
+                let node = scene.get_node_mut(id).ok_or(GraphEngineError::NodeNotFound(id))?;
+                let from = std::mem::replace(&mut node.position, to);
+                (id, Change::NodeMoved { id, from, to })
+            }
+            GraphCommand::AddEdge(mut edge) => {
+                for node in [edge.source, edge.target] {
+                    if scene.get_node(node).is_none() {
+                        return Err(GraphEngineError::NodeNotFound(node));
+                    }
+                }
+                edge.id = scene.add_edge(edge.clone());
+                (edge.id, Change::EdgeAdded(edge))
+            }
+            GraphCommand::RemoveEdge(id) => {
+                let edge = scene
+                    .remove_edge(id)
+                    .ok_or_else(|| GraphEngineError::SceneError(format!("Edge not found: {}", id)))?;
+                (id, Change::EdgeRemoved(edge))
+            }
+        };
+        self.record(change);
+        Ok(id)
+    }
+
+    /// Record a change that was already applied to the scene
+    pub fn record(&mut self, change: Change) {
+        match &mut self.pending {
+            Some(transaction) => transaction.changes.push(change),
+            None => {
+                let label = describe(&change).to_string();
+                self.push(Transaction { label, changes: vec![change] });
+            }
+        }
+    }
+
+    /// Start grouping changes into one transaction
+    ///
+    /// Nested calls join the transaction that is already open.
+    pub fn begin(&mut self, label: impl Into<String>) {
+        if self.pending.is_none() {
+            self.pending = Some(Transaction { label: label.into(), changes: Vec::new() });
+        }
+    }
+
+    /// Close the open transaction; empty transactions are dropped
+    pub fn commit(&mut self) {
+        if let Some(transaction) = self.pending.take() {
+            if !transaction.changes.is_empty() {
+                self.push(transaction);
+            }
+        }
+    }
+
+    /// Close the open transaction, reverting its changes
+    pub fn rollback(&mut self, scene: &mut Scene) {
+        if let Some(transaction) = self.pending.take() {
+            for change in transaction.changes.iter().rev() {
+                change.revert(scene);
+            }
+        }
+    }
+
+    /// Revert the latest transaction, returning its label
+    pub fn undo(&mut self, scene: &mut Scene) -> Option<String> {
+        self.commit();
+        let transaction = self.undo_stack.pop_back()?;
+        for change in transaction.changes.iter().rev() {
+            change.revert(scene);
+        }
+        let label = transaction.label.clone();
+        self.redo_stack.push(transaction);
+        Some(label)
+    }
+
+    /// Reapply the latest undone transaction, returning its label
+    pub fn redo(&mut self, scene: &mut Scene) -> Option<String> {
+        self.commit();
+        let transaction = self.redo_stack.pop()?;
+        for change in &transaction.changes {
+            change.apply(scene);
+        }
+        let label = transaction.label.clone();
+        self.undo_stack.push_back(transaction);
+        Some(label)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty() || self.pending.as_ref().is_some_and(|t| !t.changes.is_empty())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Label of the transaction `undo` would revert
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo_stack.back().map(|t| t.label.as_str())
+    }
+
+    /// Label of the transaction `redo` would reapply
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo_stack.last().map(|t| t.label.as_str())
+    }
+
+    /// Forget all history, e.g. after loading a different workspace
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.pending = None;
+    }
+
+    fn push(&mut self, transaction: Transaction) {
+        // A new change makes the undone future unreachable
+        self.redo_stack.clear();
+        self.undo_stack.push_back(transaction);
+        while self.undo_stack.len() > self.max_depth {
+            self.undo_stack.pop_front();
+        }
+    }
+}
+
+impl Default for CommandHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn describe(change: &Change) -> &'static str {
+    match change {
+        Change::NodeAdded(_) => "Add node",
+        Change::NodeRemoved { .. } => "Delete node",
+        Change::NodeMoved { .. } => "Move node",
+        Change::NodeUpdated { .. } => "Change node",
+        Change::EdgeAdded(_) => "Connect nodes",
+        Change::EdgeRemoved(_) => "Delete edge",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EdgeType, NodeMetadata, NodeType};
+    use nalgebra::{Point3, Vector3};
+
+    fn node(x: f32) -> SceneNode {
+        SceneNode {
+            id: 0,
+            position: Point3::new(x, 0.0, 0.0),
+            velocity: Vector3::zeros(),
+            radius: 1.0,
+            color: [1.0, 1.0, 1.0, 1.0],
+            node_type: NodeType::Concept { title: "idea".to_string(), content: String::new() },
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        }
+    }
+
+    fn edge(source: SceneId, target: SceneId) -> SceneEdge {
+        SceneEdge {
+            id: 0,
+            source,
+            target,
+            edge_type: EdgeType::DependsOn,
+            weight: 1.0,
+            color: [1.0, 1.0, 1.0, 1.0],
+            visible: true,
+            animated: false,
+        }
+    }
+
+    #[test]
+    fn test_delete_undo_restores_ids_and_edges() {
+        let mut scene = Scene::new();
+        let mut history = CommandHistory::new();
+        let a = history.execute(GraphCommand::AddNode(Box::new(node(0.0))), &mut scene).unwrap();
+        let b = history.execute(GraphCommand::AddNode(Box::new(node(1.0))), &mut scene).unwrap();
+        let e = history.execute(GraphCommand::AddEdge(edge(a, b)), &mut scene).unwrap();
+
+        history.execute(GraphCommand::RemoveNode(a), &mut scene).unwrap();
+        assert!(scene.get_edge(e).is_none());
+
+        assert_eq!(history.undo(&mut scene).as_deref(), Some("Delete node"));
+        assert!(scene.get_node(a).is_some());
+        assert_eq!(scene.get_edge(e).map(|edge| (edge.source, edge.target)), Some((a, b)));
+
+        // New nodes never reuse restored IDs
+        let c = scene.add_node(node(2.0));
+        assert!(c > e);
+    }
+
+    #[test]
+    fn test_transactions_and_redo() {
+        let mut scene = Scene::new();
+        let mut history = CommandHistory::new();
+        let a = history.execute(GraphCommand::AddNode(Box::new(node(0.0))), &mut scene).unwrap();
+        let b = history.execute(GraphCommand::AddNode(Box::new(node(1.0))), &mut scene).unwrap();
+
+        history.begin("Delete selection");
+        history.execute(GraphCommand::RemoveNode(a), &mut scene).unwrap();
+        history.execute(GraphCommand::RemoveNode(b), &mut scene).unwrap();
+        history.commit();
+        assert_eq!(scene.nodes().count(), 0);
+
+        history.undo(&mut scene);
+        assert_eq!(scene.nodes().count(), 2);
+        assert_eq!(history.redo_label(), Some("Delete selection"));
+        history.redo(&mut scene);
+        assert_eq!(scene.nodes().count(), 0);
+        history.undo(&mut scene);
+
+        // A new change discards the redo stack
+        scene.get_node_mut(a).unwrap().position = Point3::new(5.0, 0.0, 0.0);
+        history.record(Change::NodeMoved { id: a, from: Point3::new(0.0, 0.0, 0.0), to: Point3::new(5.0, 0.0, 0.0) });
+        assert!(!history.can_redo());
+        history.undo(&mut scene);
+        assert_eq!(scene.get_node_position(a), Some(Point3::new(0.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut scene = Scene::new();
+        let mut history = CommandHistory::with_max_depth(2);
+        for x in 0..3 {
+            history.execute(GraphCommand::AddNode(Box::new(node(x as f32))), &mut scene).unwrap();
+        }
+        assert!(history.undo(&mut scene).is_some());
+        assert!(history.undo(&mut scene).is_some());
+        assert!(history.undo(&mut scene).is_none());
+        assert_eq!(scene.nodes().count(), 1);
+        assert!(history.execute(GraphCommand::AddEdge(edge(7, 8)), &mut scene).is_err());
+    }
+}
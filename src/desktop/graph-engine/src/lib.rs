@@ -9,13 +9,30 @@ pub mod camera;
 pub mod scene;
 pub mod error;
 pub mod layout;
+pub mod assets;
+pub mod routing;
+pub mod edge_filter;
+pub mod history;
+pub mod persistence;
+pub mod ui_scale;
+pub mod minimap;
+pub mod spatial;
+pub mod playback;
 
 pub use renderer::*;
 pub use physics::{PhysicsEngine, PhysicsBody, PhysicsSettings, LayoutConfig as PhysicsLayoutConfig, ForceDirectedConfig as PhysicsForceDirectedConfig};
 pub use camera::*;
 pub use scene::*;
 pub use error::*;
-pub use layout::{LayoutManager, LayoutConfig, LayoutAlgorithm, ForceDirectedLayout, CircularLayout, ForceDirectedConfig};
+pub use assets::{AssetStore, AssetPack, AssetPackBuilder, AssetKind, AssetData};
+pub use history::{CommandHistory, GraphCommand, Change, Transaction};
+pub use persistence::{SceneFormat, SceneSnapshot, Migration, SCENE_FORMAT_VERSION};
+pub use ui_scale::{UiElement, UiSizing};
+pub use playback::{PlaybackBase, PlaybackClock, PlaybackConfig, NodeLook, EdgeLook};
+pub use minimap::{Minimap, MinimapConfig, MinimapPlane, MinimapCluster, MinimapNode, MinimapHull};
+pub use routing::{EdgeRouter, EdgeRoutingConfig, EdgeRoute, HullObstacle, RoutingStats};
+pub use edge_filter::{EdgeFilter, EdgePreset, EDGE_KINDS};
+pub use layout::{LayoutManager, LayoutConfig, LayoutAlgorithm, ForceDirectedLayout, CircularLayout, ForceDirectedConfig, SceneChange};
 
 use std::sync::Arc;
 use wgpu::{Device, Queue, Surface};
@@ -24,13 +41,11 @@ use winit::window::Window;
 /// Main graph engine that coordinates rendering, physics, and scene management
 pub struct GraphEngine {
     /// WebGPU device for GPU operations
-    #[allow(dead_code)]
     device: Arc<Device>,
     /// Command queue for GPU commands
-    #[allow(dead_code)]
     queue: Arc<Queue>,
-    /// Window surface for rendering
-    surface: Surface<'static>,
+    /// Window surface or off-screen texture frames are drawn into
+    presentation: Presentation,
     /// Scene graph containing all nodes and edges
     scene: Scene,
     /// Physics simulation engine
@@ -39,14 +54,64 @@ pub struct GraphEngine {
     camera: Camera,
     /// Main renderer
     renderer: Renderer,
+    /// Undo/redo history of scene mutations
+    history: CommandHistory,
+    /// Instance the device and surface are recreated from after device loss
+    instance: wgpu::Instance,
+    /// Device-loss detection and retry state
+    recovery: DeviceRecovery,
+    /// Screen-space sizes of labels, badges and handles
+    ui_sizing: UiSizing,
+}
+
+/// Where the engine's frames go
+enum Presentation {
+    /// A window surface, recreated from the window after device loss
+    Window {
+        surface: Surface<'static>,
+        window: Arc<Window>,
+    },
+    /// A texture frames are read back from, for running without a window
+    Offscreen(OffscreenTarget),
 }
 
 impl GraphEngine {
-    /// Create a new graph engine for headless operation (compositor mode)
-    pub fn new_headless() -> Result<Self, GraphEngineError> {
-        // For headless mode, we only need scene management
-        // The compositor will handle its own rendering
-        Err(GraphEngineError::System("Headless mode requires refactoring to make renderer optional".into()))
+    /// Create a graph engine that renders into an off-screen texture instead of a window
+    ///
+    /// Frames can be read back with `capture_frame` and written out as PNG
+    /// files, e.g. for integration tests of rendering and layout. Falls back
+    /// to a software adapter when no GPU is available.
+    pub async fn new_headless(width: u32, height: u32) -> Result<Self, GraphEngineError> {
+        log::info!("Initializing headless HorizonOS Graph Engine at {}x{}", width, height);
+        
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+        let (_adapter, device, queue) = Self::request_device(&instance, None).await?;
+        
+        let recovery = DeviceRecovery::new(DeviceLossMonitor::new());
+        recovery.monitor().watch(&device);
+        
+        let renderer = Renderer::new_offscreen(device.clone(), queue.clone(), width, height).await?;
+        let (width, height) = renderer.window_size();
+        let target = OffscreenTarget::new(&device, width, height);
+        let mut camera = Camera::new();
+        camera.set_aspect_ratio(width as f32 / height as f32);
+        
+        Ok(GraphEngine {
+            device,
+            queue,
+            presentation: Presentation::Offscreen(target),
+            scene: Scene::new(),
+            physics: PhysicsEngine::new(),
+            camera,
+            renderer,
+            history: CommandHistory::new(),
+            instance,
+            recovery,
+            ui_sizing: UiSizing::default(),
+        })
     }
     
     /// Initialize the graph engine with a window
@@ -60,49 +125,78 @@ impl GraphEngine {
         });
         
         let surface = instance.create_surface(window.clone())?;
+        let (adapter, device, queue) = Self::request_device(&instance, Some(&surface)).await?;
         
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .ok_or(GraphEngineError::AdapterNotFound)?;
-            
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    label: Some("HorizonOS Graph Engine Device"),
-                    required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits::default(),
-                },
-                None,
-            )
-            .await?;
-            
-        let device = Arc::new(device);
-        let queue = Arc::new(queue);
+        let recovery = DeviceRecovery::new(DeviceLossMonitor::new());
+        recovery.monitor().watch(&device);
         
         // Initialize components
         let scene = Scene::new();
         let physics = PhysicsEngine::new();
         let camera = Camera::new();
         let renderer = Renderer::new(device.clone(), queue.clone(), &surface, &window, &adapter).await?;
+        let mut ui_sizing = UiSizing::default();
+        ui_sizing.set_display_scale(window.scale_factor() as f32);
         
         log::info!("Graph engine initialized successfully");
         
         Ok(GraphEngine {
             device,
             queue,
-            surface,
+            presentation: Presentation::Window { surface, window },
             scene,
             physics,
             camera,
             renderer,
+            history: CommandHistory::new(),
+            instance,
+            recovery,
+            ui_sizing,
         })
     }
     
+    /// Pick an adapter for the surface, or any adapter without one, and open a device on it
+    async fn request_device(
+        instance: &wgpu::Instance,
+        surface: Option<&Surface<'static>>,
+    ) -> Result<(wgpu::Adapter, Arc<Device>, Arc<Queue>), GraphEngineError> {
+        let mut adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                compatible_surface: surface,
+                force_fallback_adapter: false,
+            })
+            .await;
+        if adapter.is_none() && surface.is_none() {
+            // Headless engines can still draw on a software adapter
+            adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::LowPower,
+                    compatible_surface: None,
+                    force_fallback_adapter: true,
+                })
+                .await;
+        }
+        let adapter = adapter.ok_or(GraphEngineError::AdapterNotFound)?;
+            
+        let descriptor = wgpu::DeviceDescriptor {
+            label: Some("HorizonOS Graph Engine Device"),
+            required_features: wgpu::Features::empty(),
+            required_limits: wgpu::Limits::default(),
+        };
+        // On Vulkan, enable dmabuf import so video frames need no copies
+        let (device, queue) = match renderer::video::open_device_with_dmabuf(&adapter, &descriptor) {
+            Some(Ok(opened)) => opened,
+            Some(Err(e)) => {
+                log::warn!("Opening the device with dmabuf import failed, video frames will be copied: {}", e);
+                adapter.request_device(&descriptor, None).await?
+            }
+            None => adapter.request_device(&descriptor, None).await?,
+        };
+            
+        Ok((adapter, Arc::new(device), Arc::new(queue)))
+    }
+    
     /// Update the engine state (physics, animations, etc.)
     pub fn update(&mut self, delta_time: f32) -> Result<(), GraphEngineError> {
         // Update physics simulation
@@ -118,18 +212,151 @@ impl GraphEngine {
     }
     
     /// Render the current frame
+    ///
+    /// While the GPU device is lost frames are skipped and the device is
+    /// recreated in the background of the frame loop; the scene is untouched.
     pub fn render(&mut self) -> Result<(), GraphEngineError> {
-        self.renderer.render(&self.surface, &self.scene, &self.camera)?;
+        match self.recovery.poll(std::time::Instant::now()) {
+            RecoveryStep::Wait => return Ok(()),
+            RecoveryStep::Recreate => {
+                let result = self.recreate_device();
+                if !self.recovery.attempt_finished(std::time::Instant::now(), result) {
+                    return Ok(());
+                }
+            }
+            RecoveryStep::Render => {}
+        }
+        
+        let rendered = match &self.presentation {
+            Presentation::Window { surface, .. } => self.renderer.render(surface, &self.scene, &self.camera),
+            Presentation::Offscreen(target) => self.renderer.render_to_target(target, &self.scene, &self.camera),
+        };
+        match rendered {
+            Err(GraphEngineError::DeviceLost(reason)) => {
+                self.recovery.monitor().report(reason);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+    
+    /// Replace the lost device, surface or off-screen target and all GPU state of the renderer
+    fn recreate_device(&mut self) -> Result<(), GraphEngineError> {
+        let presentation = match &self.presentation {
+            Presentation::Window { window, .. } => {
+                let surface = self.instance.create_surface(window.clone())?;
+                let (adapter, device, queue) = pollster::block_on(Self::request_device(&self.instance, Some(&surface)))?;
+                self.recovery.monitor().watch(&device);
+                pollster::block_on(self.renderer.recover(device.clone(), queue.clone(), Some(&surface), &adapter))?;
+                self.device = device;
+                self.queue = queue;
+                Presentation::Window { surface, window: window.clone() }
+            }
+            Presentation::Offscreen(target) => {
+                let (width, height) = target.size();
+                let (adapter, device, queue) = pollster::block_on(Self::request_device(&self.instance, None))?;
+                self.recovery.monitor().watch(&device);
+                pollster::block_on(self.renderer.recover(device.clone(), queue.clone(), None, &adapter))?;
+                let target = OffscreenTarget::new(&device, width, height);
+                self.device = device;
+                self.queue = queue;
+                Presentation::Offscreen(target)
+            }
+        };
+        self.presentation = presentation;
         Ok(())
     }
     
+    /// Whether frames are drawn into an off-screen texture instead of a window
+    pub fn is_headless(&self) -> bool {
+        matches!(self.presentation, Presentation::Offscreen(_))
+    }
+    
+    /// Texture a headless engine draws into, e.g. to hand it to other GPU code
+    pub fn offscreen_target(&self) -> Option<&OffscreenTarget> {
+        match &self.presentation {
+            Presentation::Offscreen(target) => Some(target),
+            Presentation::Window { .. } => None,
+        }
+    }
+    
+    /// Read the last rendered frame of a headless engine back from the GPU
+    pub fn capture_frame(&self) -> Result<CapturedFrame, GraphEngineError> {
+        match &self.presentation {
+            Presentation::Offscreen(target) => target.read_pixels(&self.device, &self.queue),
+            Presentation::Window { .. } => Err(GraphEngineError::RenderError(
+                "Frames can only be captured from a headless engine".into(),
+            )),
+        }
+    }
+    
+    /// Render a frame and write it to a PNG file; headless engines only
+    pub fn export_frame_png(&mut self, path: impl AsRef<std::path::Path>) -> Result<(), GraphEngineError> {
+        self.render()?;
+        self.capture_frame()?.save_png(path)
+    }
+    
+    /// Receive device loss and recovery events, e.g. to show notifications
+    pub fn subscribe_device_recovery(&mut self) -> crossbeam_channel::Receiver<RecoveryEvent> {
+        self.recovery.subscribe()
+    }
+    
+    /// Where the GPU device is in recovering from a loss
+    pub fn device_recovery_state(&self) -> &RecoveryState {
+        self.recovery.state()
+    }
+    
+    /// Monitor to report device loss to, e.g. from platform code noticing a GPU reset
+    pub fn device_loss_monitor(&self) -> &DeviceLossMonitor {
+        self.recovery.monitor()
+    }
+    
     /// Resize the rendering surface
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) -> Result<(), GraphEngineError> {
-        self.renderer.resize(&self.surface, new_size)?;
+        match &mut self.presentation {
+            Presentation::Window { surface, window } => {
+                self.renderer.resize(surface, new_size)?;
+                // Moving to a screen of different density resizes the window too
+                self.ui_sizing.set_display_scale(window.scale_factor() as f32);
+            }
+            Presentation::Offscreen(target) => {
+                if new_size.width == 0 || new_size.height == 0 {
+                    return Ok(());
+                }
+                self.renderer.resize_offscreen(new_size.width, new_size.height);
+                *target = OffscreenTarget::new(&self.device, new_size.width, new_size.height);
+            }
+        }
         self.camera.set_aspect_ratio(new_size.width as f32 / new_size.height as f32);
         Ok(())
     }
     
+    /// Resize the off-screen target of a headless engine
+    pub fn resize_offscreen(&mut self, width: u32, height: u32) -> Result<(), GraphEngineError> {
+        self.resize(winit::dpi::PhysicalSize::new(width, height))
+    }
+    
+    /// Screen-space sizes of labels, badges and handles
+    pub fn ui_sizing(&self) -> &UiSizing {
+        &self.ui_sizing
+    }
+    
+    /// Change screen-space UI sizes
+    pub fn ui_sizing_mut(&mut self) -> &mut UiSizing {
+        &mut self.ui_sizing
+    }
+    
+    /// Apply the accessibility text scale to all text drawn in the scene
+    pub fn set_text_scale(&mut self, scale: f32) {
+        self.ui_sizing.set_text_scale(scale);
+    }
+    
+    /// World size to draw a UI element at a point, from the current camera and window
+    pub fn ui_world_size(&self, element: UiElement, world_size: f32, point: Position) -> f32 {
+        let (_, height) = self.window_size();
+        self.ui_sizing.world_size_at(element, world_size, &self.camera, point, height as f32)
+    }
+    
     /// Get mutable reference to the scene
     pub fn scene_mut(&mut self) -> &mut Scene {
         &mut self.scene
@@ -140,6 +367,102 @@ impl GraphEngine {
         &self.scene
     }
     
+    /// Apply a scene mutation and record it for undo
+    ///
+    /// Moved nodes and changed edges wake their physics islands.
+    pub fn execute(&mut self, command: GraphCommand) -> Result<SceneId, GraphEngineError> {
+        let moved = match &command {
+            GraphCommand::MoveNode { id, .. } => Some(*id),
+            _ => None,
+        };
+        let id = self.history.execute(command, &mut self.scene)?;
+        if let Some(moved) = moved {
+            self.nodes_moved(&[moved]);
+        }
+        self.physics.sync_edges(&self.scene);
+        Ok(id)
+    }
+    
+    /// Revert the latest recorded scene mutation
+    pub fn undo(&mut self) -> Option<String> {
+        let label = self.history.undo(&mut self.scene);
+        self.physics.sync_edges(&self.scene);
+        label
+    }
+    
+    /// Reapply the latest undone scene mutation
+    pub fn redo(&mut self) -> Option<String> {
+        let label = self.history.redo(&mut self.scene);
+        self.physics.sync_edges(&self.scene);
+        label
+    }
+    
+    /// Move the physics bodies of nodes moved directly in the scene, e.g. while dragging, waking their islands
+    pub fn nodes_moved(&mut self, ids: &[SceneId]) {
+        for id in ids {
+            if let Some(node) = self.scene.get_node(*id) {
+                self.physics.update_body(node);
+            }
+        }
+    }
+    
+    /// Get reference to the undo/redo history
+    pub fn history(&self) -> &CommandHistory {
+        &self.history
+    }
+    
+    /// Get mutable reference to the undo/redo history
+    pub fn history_mut(&mut self) -> &mut CommandHistory {
+        &mut self.history
+    }
+    
+    /// Scene and history together, for bulk mutations recorded as one transaction
+    ///
+    /// Pass the resulting changes to `apply_scene_changes` so physics follows.
+    pub fn scene_with_history(&mut self) -> (&mut Scene, &mut CommandHistory) {
+        (&mut self.scene, &mut self.history)
+    }
+    
+    /// Bring physics up to date with a batch of scene changes made directly on the scene
+    ///
+    /// Added nodes get bodies, moved nodes wake their islands, and edges are
+    /// linked or unlinked once for the whole batch.
+    pub fn apply_scene_changes(&mut self, changes: &[SceneChange]) {
+        for change in changes {
+            match change {
+                SceneChange::NodeAdded { id } => {
+                    if let Some(node) = self.scene.get_node(*id) {
+                        if !self.physics.has_body(*id) {
+                            self.physics.add_body(node);
+                        }
+                    }
+                }
+                SceneChange::NodeMoved { id, .. } => {
+                    if let Some(node) = self.scene.get_node(*id) {
+                        self.physics.update_body(node);
+                    }
+                }
+                SceneChange::NodeRemoved { .. }
+                | SceneChange::EdgeAdded { .. }
+                | SceneChange::EdgeRemoved { .. }
+                | SceneChange::EdgeWeightChanged { .. } => {}
+            }
+        }
+        self.physics.sync_edges(&self.scene);
+    }
+    
+    /// Snapshot of the scene for saving, without ephemeral and session-only nodes
+    pub fn snapshot(&self) -> SceneSnapshot {
+        SceneSnapshot::capture_for(&self.scene, NodePersistence::Durable)
+    }
+    
+    /// Replace the scene with a loaded snapshot; undo history does not carry over
+    pub fn restore_snapshot(&mut self, snapshot: &SceneSnapshot) {
+        snapshot.restore(&mut self.scene);
+        self.history.clear();
+        self.physics.sync_edges(&self.scene);
+    }
+    
     /// Get mutable reference to the camera
     pub fn camera_mut(&mut self) -> &mut Camera {
         &mut self.camera
@@ -159,6 +482,106 @@ impl GraphEngine {
     pub fn window_size(&self) -> (u32, u32) {
         self.renderer.window_size()
     }
+    
+    /// Get mutable reference to the edge router
+    pub fn edge_router_mut(&mut self) -> &mut EdgeRouter {
+        self.renderer.edge_router_mut()
+    }
+    
+    /// Start picking the node at a screen position on the GPU
+    ///
+    /// `None` while the device is lost or when GPU picking is unavailable;
+    /// callers fall back to ray casting then.
+    pub fn request_pick(&self, screen_pos: (f32, f32)) -> Option<PickRequest> {
+        if *self.recovery.state() != RecoveryState::Healthy {
+            return None;
+        }
+        self.renderer.request_pick(&self.scene, &self.camera, screen_pos)
+    }
+    
+    /// Colors of the active theme
+    pub fn palette(&self) -> &RenderPalette {
+        self.renderer.palette()
+    }
+    
+    /// Apply new theme colors from the next frame on, recoloring nodes and edges that used the old ones
+    pub fn set_palette(&mut self, palette: RenderPalette) {
+        let changed = palette.recolor(self.renderer.palette(), &mut self.scene);
+        log::debug!("Theme palette changed, recolored {} scene objects", changed);
+        self.renderer.set_palette(palette);
+    }
+
+    /// Edge types and strengths currently drawn
+    pub fn edge_filter(&self) -> &EdgeFilter {
+        self.renderer.edge_filter()
+    }
+
+    /// Change which edges are drawn from the next frame on
+    pub fn set_edge_filter(&mut self, filter: EdgeFilter) {
+        self.renderer.set_edge_filter(filter);
+    }
+
+    /// Draw only the nodes that survived culling, from the next frame on
+    pub fn set_visible_nodes(&mut self, visible: VisibleNodes) {
+        self.renderer.set_visible_nodes(visible);
+    }
+
+    /// Draw selection feedback over the scene from the next frame on
+    pub fn set_selection_visual_state(&mut self, state: SelectionVisualState) {
+        self.renderer.set_selection_visual_state(state);
+    }
+
+    /// Draw the edge being created from the next frame on, or stop with `None`
+    pub fn set_ghost_edge(&mut self, ghost: Option<GhostEdgeLine>) {
+        self.renderer.set_ghost_edge(ghost);
+    }
+
+    /// Draw the minimap over the scene from the next frame on, or hide it with `None`
+    pub fn set_minimap(&mut self, minimap: Option<Minimap>) {
+        self.renderer.set_minimap(minimap);
+    }
+
+    /// Draw a frame of a workspace switch transition from the next frame on, or stop with `None`
+    pub fn set_switch_overlay(&mut self, switch: Option<SwitchOverlay>) {
+        self.renderer.set_switch_overlay(switch);
+    }
+
+    /// Nodes and edges the last frame drew and culled
+    pub fn draw_stats(&self) -> DrawStats {
+        self.renderer.draw_stats()
+    }
+
+    /// Draw the next frame's nodes from these instanced batches
+    pub fn set_node_batches(&mut self, batches: NodeBatches) {
+        self.renderer.set_node_batches(batches);
+    }
+
+    /// Show a decoded video frame on a node, e.g. from a media player or a PipeWire stream
+    pub fn submit_video_frame(&mut self, node_id: SceneId, frame: VideoFrame) -> Result<FramePath, GraphEngineError> {
+        self.renderer.submit_video_frame(node_id, frame)
+    }
+
+    /// Stop showing video on a node whose playback ended
+    pub fn remove_video(&mut self, node_id: SceneId) {
+        self.renderer.remove_video(node_id);
+    }
+
+    /// How video frames reached their textures, zero-copy or copied
+    pub fn video_stats(&self) -> VideoStats {
+        self.renderer.video_stats()
+    }
+
+    /// Watch a directory for shader overrides while developing themes and effects
+    ///
+    /// The returned receiver reports reloaded and rejected shaders.
+    pub fn enable_shader_hot_reload(&mut self, dir: impl Into<std::path::PathBuf>) -> Result<crossbeam_channel::Receiver<ShaderEvent>, GraphEngineError> {
+        self.renderer.enable_shader_hot_reload(dir)
+    }
+
+    /// Upload an icon atlas page as RGBA8 pixels
+    pub fn set_icon_atlas_page(&mut self, page: u32, width: u32, height: u32, rgba: Vec<u8>) -> Result<(), GraphEngineError> {
+        self.renderer.set_icon_atlas_page(page, width, height, rgba)
+    }
 }
 
 #[cfg(test)]
@@ -301,6 +724,69 @@ mod tests {
         assert!(physics.has_body(node.id));
     }
 
+    #[test]
+    fn test_physics_islands_sleep_and_wake() {
+        let mut scene = Scene::new();
+        let mut physics = PhysicsEngine::new();
+        let ids: Vec<SceneId> = [0.0, 3.0, 100.0]
+            .iter()
+            .map(|x| {
+                let id = scene.add_node(SceneNode {
+                    id: 0,
+                    position: nalgebra::Point3::new(*x, 0.0, 0.0),
+                    velocity: nalgebra::Vector3::zeros(),
+                    radius: 0.5,
+                    color: [1.0; 4],
+                    node_type: NodeType::System {
+                        component: "test".to_string(),
+                        status: SystemStatus::Running,
+                    },
+                    metadata: NodeMetadata::default(),
+                    visible: true,
+                    selected: false,
+                });
+                physics.add_body(scene.get_node(id).unwrap());
+                id
+            })
+            .collect();
+        assert_eq!(physics.island_count(), 3);
+
+        let edge = SceneEdge {
+            id: 0,
+            source: ids[0],
+            target: ids[1],
+            edge_type: EdgeType::CreatedBy,
+            weight: 1.0,
+            color: [0.5; 4],
+            visible: true,
+            animated: false,
+        };
+        scene.add_edge(edge);
+        physics.sync_edges(&scene);
+        assert_eq!(physics.island_count(), 2);
+
+        // The linked pair settles and the lone node feels no forces
+        for _ in 0..900 {
+            physics.step(1.0 / 60.0);
+        }
+        assert!(ids.iter().all(|id| physics.is_sleeping(*id)));
+        assert_eq!(physics.awake_body_count(), 0);
+
+        // Dragging a node wakes its island only
+        scene.get_node_mut(ids[0]).unwrap().position = nalgebra::Point3::new(-1.0, 0.0, 0.0);
+        physics.update_body(scene.get_node(ids[0]).unwrap());
+        assert!(!physics.is_sleeping(ids[1]));
+        assert!(physics.is_sleeping(ids[2]));
+        assert_eq!(physics.awake_body_count(), 2);
+
+        // Paused physics leaves awake islands as they are
+        physics.set_paused(true);
+        for _ in 0..900 {
+            physics.step(1.0 / 60.0);
+        }
+        assert_eq!(physics.awake_body_count(), 2);
+    }
+
     #[test]
     fn test_camera_controls() {
         let mut camera = Camera::new();
@@ -356,4 +842,71 @@ mod tests {
         let far_away = scene.find_nodes_in_radius(nalgebra::Point3::new(0.0, 0.0, 0.0), 1.0);
         assert_eq!(far_away.len(), 1); // Should find only first node
     }
+
+    #[test]
+    fn test_spatial_index_follows_moved_nodes() {
+        let mut scene = Scene::new();
+        for i in 0..50u64 {
+            scene.add_node(SceneNode {
+                id: 0,
+                position: nalgebra::Point3::new(i as f32 * 3.0, 0.0, 0.0),
+                velocity: nalgebra::Vector3::zeros(),
+                radius: 1.0,
+                color: [1.0, 1.0, 1.0, 1.0],
+                node_type: NodeType::Concept { title: format!("Node {}", i), content: String::new() },
+                metadata: NodeMetadata::default(),
+                visible: true,
+                selected: false,
+            });
+        }
+        let ray = Ray {
+            origin: nalgebra::Point3::new(30.0, 0.0, -20.0),
+            direction: nalgebra::Vector3::new(0.0, 0.0, 1.0),
+        };
+        assert_eq!(scene.ray_cast(&ray).map(|(id, _)| id), Some(10));
+        assert!(scene.find_overlapping_nodes(10, 0.5).is_empty());
+
+        // Moved nodes are found before and after the index is refreshed
+        scene.get_node_mut(10).unwrap().position = nalgebra::Point3::new(500.0, 0.0, 0.0);
+        scene.get_node_mut(20).unwrap().position = nalgebra::Point3::new(31.0, 0.0, 0.0);
+        for refresh in [false, true] {
+            if refresh {
+                scene.update(0.016);
+            }
+            assert_eq!(scene.ray_cast(&ray).map(|(id, _)| id), Some(20));
+            assert_eq!(scene.find_nodes_in_radius(nalgebra::Point3::new(500.0, 0.0, 0.0), 1.0), vec![10]);
+            let mut overlapping = scene.find_overlapping_nodes(20, 2.0);
+            overlapping.sort();
+            assert_eq!(overlapping, vec![9, 11]);
+        }
+    }
+
+    #[test]
+    fn test_headless_engine_renders_to_png() {
+        let mut engine = match pollster::block_on(GraphEngine::new_headless(64, 48)) {
+            Ok(engine) => engine,
+            // Machines without any adapter, not even a software one, cannot render
+            Err(GraphEngineError::AdapterNotFound) => return,
+            Err(e) => panic!("Headless engine failed to start: {}", e),
+        };
+        assert!(engine.is_headless());
+        assert_eq!(engine.window_size(), (64, 48));
+
+        engine.render().unwrap();
+        let frame = engine.capture_frame().unwrap();
+        assert_eq!((frame.width, frame.height), (64, 48));
+        assert_eq!(frame.rgba.len(), 64 * 48 * 4);
+        // An empty scene leaves the theme background
+        let [r, _, b, a] = frame.pixel(0, 0).unwrap();
+        assert!(b > r);
+        assert_eq!(a, 255);
+
+        engine.resize(winit::dpi::PhysicalSize::new(30, 20)).unwrap();
+        let path = std::env::temp_dir().join(format!("horizonos-headless-{}.png", std::process::id()));
+        engine.export_frame_png(&path).unwrap();
+        let png = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(png.starts_with(b"\x89PNG"));
+        assert_eq!(engine.capture_frame().unwrap().width, 30);
+    }
 }
\ No newline at end of file
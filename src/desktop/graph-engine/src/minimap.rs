@@ -0,0 +1,397 @@
+//! Minimap overlay of the whole graph
+//!
+//! The minimap flattens the scene onto a plane, by default the one the
+//! camera looks at when the desktop starts (dropping Z), and fits every
+//! node into a rectangle in the corner of the screen. On top of the nodes it
+//! outlines clusters with a padded convex hull and shows what the camera
+//! sees: the four corner rays of the view are cut with the plane through the
+//! middle of the graph. [`Minimap::build`] produces the shapes in minimap
+//! pixels, with the origin at the top left, for the overlay to draw; shapes
+//! may reach past the rectangle and are expected to be clipped to it. On
+//! screen the minimap sits in the bottom right corner; screen points are
+//! mapped into it with [`Minimap::from_screen`] and clicks are mapped back
+//! with [`Minimap::target_at`].
+
+use crate::{Camera, Position, Scene, SceneId};
+use nalgebra::{Point3, Vector3};
+
+/// Points on the circle drawn around each cluster member before taking the hull
+const HULL_CIRCLE_POINTS: usize = 8;
+
+/// Plane the scene is flattened onto
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MinimapPlane {
+    /// Seen from the front, along -Z like the starting camera
+    #[default]
+    XY,
+    /// Seen from above, along -Y
+    XZ,
+}
+
+impl MinimapPlane {
+    /// Minimap right and down axes, and the dropped axis
+    fn axes(self) -> (usize, usize, usize) {
+        match self {
+            MinimapPlane::XY => (0, 1, 2),
+            MinimapPlane::XZ => (0, 2, 1),
+        }
+    }
+
+    /// Whether world coordinates grow opposite to minimap rows
+    fn flips_vertical(self) -> bool {
+        self == MinimapPlane::XY
+    }
+}
+
+/// Minimap size and look
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinimapConfig {
+    pub plane: MinimapPlane,
+    /// Size of the minimap in pixels
+    pub width: f32,
+    pub height: f32,
+    /// Margin between the outermost nodes and the edge, in pixels
+    pub padding: f32,
+    /// Radius nodes are drawn with, in pixels, whatever their size in the scene
+    pub node_radius: f32,
+    /// Space between cluster members and the cluster outline, in pixels
+    pub hull_padding: f32,
+    /// Click distance from a node that still hits it, in pixels
+    pub hit_radius: f32,
+    /// Space between the minimap and the bottom right corner of the screen, in pixels
+    pub margin: f32,
+}
+
+impl Default for MinimapConfig {
+    fn default() -> Self {
+        Self {
+            plane: MinimapPlane::XY,
+            width: 220.0,
+            height: 160.0,
+            padding: 8.0,
+            node_radius: 2.0,
+            hull_padding: 6.0,
+            hit_radius: 6.0,
+            margin: 16.0,
+        }
+    }
+}
+
+/// A cluster to outline, e.g. from the clustering crate
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinimapCluster {
+    pub label: String,
+    pub members: Vec<SceneId>,
+    pub color: [f32; 4],
+}
+
+/// A node as drawn in the minimap
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinimapNode {
+    pub id: SceneId,
+    /// Position in minimap pixels
+    pub position: [f32; 2],
+    /// Position in the scene, where clicking the node navigates to
+    pub world: Position,
+    pub color: [f32; 4],
+    pub selected: bool,
+}
+
+/// Outline of a cluster, a closed convex polygon in minimap pixels
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinimapHull {
+    pub label: String,
+    pub outline: Vec<[f32; 2]>,
+    pub color: [f32; 4],
+}
+
+/// The graph flattened into minimap pixels
+#[derive(Debug, Clone)]
+pub struct Minimap {
+    config: MinimapConfig,
+    /// World coordinates, along the two kept axes, at the minimap center
+    center: [f32; 2],
+    /// Minimap pixels per world unit
+    scale: f32,
+    /// Coordinate of the plane along the dropped axis
+    depth: f32,
+    nodes: Vec<MinimapNode>,
+    hulls: Vec<MinimapHull>,
+    /// Corners of the camera view on the plane, in drawing order
+    view: [[f32; 2]; 4],
+    camera: [f32; 2],
+}
+
+impl Minimap {
+    /// Flatten the visible nodes of a scene, the clusters and the camera view
+    pub fn build(scene: &Scene, camera: &Camera, clusters: &[MinimapCluster], config: &MinimapConfig) -> Self {
+        let (u, v, w) = config.plane.axes();
+        let positions: Vec<(&SceneId, &crate::SceneNode)> = scene.nodes().filter(|(_, node)| node.visible).collect();
+
+        // Fit the bounds of all nodes into the rectangle, keeping the aspect ratio
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for (_, node) in &positions {
+            for axis in 0..3 {
+                min[axis] = min[axis].min(node.position[axis]);
+                max[axis] = max[axis].max(node.position[axis]);
+            }
+        }
+        let (center, scale, depth) = if positions.is_empty() {
+            ([0.0, 0.0], 1.0, 0.0)
+        } else {
+            let extent_u = (max[u] - min[u]).max(f32::EPSILON);
+            let extent_v = (max[v] - min[v]).max(f32::EPSILON);
+            let usable_w = (config.width - 2.0 * config.padding).max(1.0);
+            let usable_h = (config.height - 2.0 * config.padding).max(1.0);
+            (
+                [(min[u] + max[u]) * 0.5, (min[v] + max[v]) * 0.5],
+                (usable_w / extent_u).min(usable_h / extent_v),
+                (min[w] + max[w]) * 0.5,
+            )
+        };
+
+        let mut minimap = Self {
+            config: config.clone(),
+            center,
+            scale,
+            depth,
+            nodes: Vec::new(),
+            hulls: Vec::new(),
+            view: [[0.0; 2]; 4],
+            camera: [0.0; 2],
+        };
+
+        minimap.nodes = positions.iter()
+            .map(|(id, node)| MinimapNode {
+                id: **id,
+                position: minimap.to_minimap(node.position),
+                world: node.position,
+                color: node.color,
+                selected: node.selected,
+            })
+            .collect();
+        minimap.nodes.sort_by_key(|node| node.id);
+
+        minimap.hulls = clusters.iter()
+            .filter_map(|cluster| {
+                let points: Vec<[f32; 2]> = cluster.members.iter()
+                    .filter_map(|id| minimap.node(*id))
+                    .flat_map(|node| circle(node.position, config.hull_padding))
+                    .collect();
+                (!points.is_empty()).then(|| MinimapHull {
+                    label: cluster.label.clone(),
+                    outline: convex_hull(points),
+                    color: cluster.color,
+                })
+            })
+            .collect();
+
+        minimap.view = minimap.view_corners(camera);
+        minimap.camera = minimap.to_minimap(camera.position);
+        minimap
+    }
+
+    pub fn config(&self) -> &MinimapConfig {
+        &self.config
+    }
+
+    /// Nodes in ID order
+    pub fn nodes(&self) -> &[MinimapNode] {
+        &self.nodes
+    }
+
+    pub fn node(&self, id: SceneId) -> Option<&MinimapNode> {
+        self.nodes.binary_search_by_key(&id, |node| node.id).ok().map(|index| &self.nodes[index])
+    }
+
+    pub fn hulls(&self) -> &[MinimapHull] {
+        &self.hulls
+    }
+
+    /// What the camera sees, as a quadrilateral on the minimap plane
+    pub fn view(&self) -> &[[f32; 2]; 4] {
+        &self.view
+    }
+
+    /// Where the camera is, possibly outside the minimap
+    pub fn camera(&self) -> [f32; 2] {
+        self.camera
+    }
+
+    /// Minimap pixels of a scene position
+    pub fn to_minimap(&self, position: Position) -> [f32; 2] {
+        let (u, v, _) = self.config.plane.axes();
+        let x = (position[u] - self.center[0]) * self.scale;
+        let mut y = (position[v] - self.center[1]) * self.scale;
+        if self.config.plane.flips_vertical() {
+            y = -y;
+        }
+        [self.config.width * 0.5 + x, self.config.height * 0.5 + y]
+    }
+
+    /// Scene position on the minimap plane under a point, if inside the minimap
+    pub fn to_world(&self, point: [f32; 2]) -> Option<Position> {
+        if !self.contains(point) {
+            return None;
+        }
+        let (u, v, w) = self.config.plane.axes();
+        let mut y = (point[1] - self.config.height * 0.5) / self.scale;
+        if self.config.plane.flips_vertical() {
+            y = -y;
+        }
+        let mut world = Point3::origin();
+        world[u] = self.center[0] + (point[0] - self.config.width * 0.5) / self.scale;
+        world[v] = self.center[1] + y;
+        world[w] = self.depth;
+        Some(world)
+    }
+
+    /// Top left corner of the minimap on a screen of the given size
+    pub fn screen_origin(&self, viewport: (f32, f32)) -> [f32; 2] {
+        [
+            viewport.0 - self.config.width - self.config.margin,
+            viewport.1 - self.config.height - self.config.margin,
+        ]
+    }
+
+    /// Minimap pixels under a screen point, if the point is on the minimap
+    pub fn from_screen(&self, point: (f32, f32), viewport: (f32, f32)) -> Option<[f32; 2]> {
+        let origin = self.screen_origin(viewport);
+        let local = [point.0 - origin[0], point.1 - origin[1]];
+        self.contains(local).then_some(local)
+    }
+
+    /// Whether a point in minimap pixels is inside the minimap
+    pub fn contains(&self, point: [f32; 2]) -> bool {
+        (0.0..=self.config.width).contains(&point[0]) && (0.0..=self.config.height).contains(&point[1])
+    }
+
+    /// Node drawn under a point, the nearest if several are within reach
+    pub fn node_at(&self, point: [f32; 2]) -> Option<&MinimapNode> {
+        let reach = self.config.hit_radius.max(self.config.node_radius);
+        self.nodes.iter()
+            .map(|node| (node, distance(node.position, point)))
+            .filter(|(_, distance)| *distance <= reach)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(node, _)| node)
+    }
+
+    /// Where a click should take the camera: the clicked node, or the point on the plane
+    pub fn target_at(&self, point: [f32; 2]) -> Option<Position> {
+        match self.node_at(point) {
+            Some(node) => Some(node.world),
+            None => self.to_world(point),
+        }
+    }
+
+    /// Corner rays of the camera cut with the minimap plane, or cut off at the far plane
+    fn view_corners(&self, camera: &Camera) -> [[f32; 2]; 4] {
+        let (_, _, w) = self.config.plane.axes();
+        let half_height = (camera.fov * 0.5).tan();
+        let half_width = half_height * camera.aspect_ratio;
+        let corners = [(-1.0, 1.0), (1.0, 1.0), (1.0, -1.0), (-1.0, -1.0)];
+        corners.map(|(x, y)| {
+            let direction: Vector3<f32> = (camera.forward + camera.right * (x * half_width) + camera.up * (y * half_height)).normalize();
+            let t = (self.depth - camera.position[w]) / direction[w];
+            let t = if t.is_finite() && t > 0.0 { t.min(camera.far) } else { camera.far };
+            self.to_minimap(camera.position + direction * t)
+        })
+    }
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+}
+
+/// Points around a center, so hulls keep a margin around their members
+fn circle(center: [f32; 2], radius: f32) -> impl Iterator<Item = [f32; 2]> {
+    (0..HULL_CIRCLE_POINTS).map(move |i| {
+        let angle = i as f32 * std::f32::consts::TAU / HULL_CIRCLE_POINTS as f32;
+        [center[0] + radius * angle.cos(), center[1] + radius * angle.sin()]
+    })
+}
+
+/// Convex hull by the monotone chain algorithm
+fn convex_hull(mut points: Vec<[f32; 2]>) -> Vec<[f32; 2]> {
+    points.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let cross = |o: [f32; 2], a: [f32; 2], b: [f32; 2]| (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0]);
+    let mut hull: Vec<[f32; 2]> = Vec::with_capacity(points.len() * 2);
+    for pass in 0..2 {
+        let start = hull.len();
+        let chain: Box<dyn Iterator<Item = &[f32; 2]>> = if pass == 0 {
+            Box::new(points.iter())
+        } else {
+            Box::new(points.iter().rev())
+        };
+        for &point in chain {
+            while hull.len() >= start + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        // The last point of each chain starts the other one
+        hull.pop();
+    }
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NodeMetadata, NodeType, SceneNode};
+
+    fn add_node(scene: &mut Scene, x: f32, y: f32) -> SceneId {
+        scene.add_node(SceneNode {
+            id: 0,
+            position: Point3::new(x, y, 0.0),
+            velocity: Vector3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type: NodeType::Concept { title: String::new(), content: String::new() },
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        })
+    }
+
+    #[test]
+    fn test_nodes_fit_and_clicks_map_back() {
+        let mut scene = Scene::new();
+        let left = add_node(&mut scene, -10.0, 0.0);
+        let right = add_node(&mut scene, 10.0, 5.0);
+        let config = MinimapConfig { width: 220.0, height: 120.0, padding: 10.0, ..Default::default() };
+        let clusters = [MinimapCluster { label: "pair".to_string(), members: vec![left, right], color: [1.0; 4] }];
+        let minimap = Minimap::build(&scene, &Camera::new(), &clusters, &config);
+
+        // 20 units across 200 pixels; higher Y is further up
+        assert_eq!(minimap.node(left).unwrap().position, [10.0, 85.0]);
+        assert_eq!(minimap.node(right).unwrap().position, [210.0, 35.0]);
+        // Each member contributes the half of its circle facing away from the other
+        assert_eq!(minimap.hulls()[0].outline.len(), 10);
+
+        assert_eq!(minimap.target_at([209.0, 36.0]), Some(Point3::new(10.0, 5.0, 0.0)));
+        assert_eq!(minimap.target_at([110.0, 60.0]), Some(Point3::new(0.0, 2.5, 0.0)));
+        assert_eq!(minimap.target_at([300.0, 60.0]), None);
+
+        // The starting camera looks at the origin from +Z
+        let view = minimap.view();
+        assert!(view[0][0] < 110.0 && view[1][0] > 110.0 && view[0][1] < view[3][1]);
+    }
+
+    #[test]
+    fn test_screen_points_map_into_the_corner() {
+        let config = MinimapConfig { width: 200.0, height: 100.0, margin: 20.0, ..Default::default() };
+        let minimap = Minimap::build(&Scene::new(), &Camera::new(), &[], &config);
+
+        assert_eq!(minimap.screen_origin((1920.0, 1080.0)), [1700.0, 960.0]);
+        assert_eq!(minimap.from_screen((1710.0, 1000.0), (1920.0, 1080.0)), Some([10.0, 40.0]));
+        assert_eq!(minimap.from_screen((1690.0, 1000.0), (1920.0, 1080.0)), None);
+        assert_eq!(minimap.from_screen((1910.0, 1070.0), (1920.0, 1080.0)), None);
+    }
+}
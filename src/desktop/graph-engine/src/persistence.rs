@@ -0,0 +1,278 @@
+//! Saving and loading scenes
+//!
+//! A scene is saved as a JSON [`SceneSnapshot`] tagged with
+//! [`SCENE_FORMAT_VERSION`]. Loading reads the version first and runs the
+//! registered migrations one version at a time, so saves made before a change
+//! to `NodeType` or `EdgeType` still load: each migration receives the raw
+//! JSON of one version and rewrites it into the shape of the next.
+
+use crate::{GraphEngineError, NodePersistence, Scene, SceneEdge, SceneNode};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Version of the scene format written by this build
+pub const SCENE_FORMAT_VERSION: u32 = 1;
+
+/// Everything needed to rebuild a scene
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneSnapshot {
+    /// Format version the snapshot was written with
+    pub version: u32,
+    pub saved_at: DateTime<Utc>,
+    /// Nodes in ID order
+    pub nodes: Vec<SceneNode>,
+    /// Edges in ID order
+    pub edges: Vec<SceneEdge>,
+    /// Data other subsystems save with the scene, by subsystem name
+    #[serde(default)]
+    pub extensions: BTreeMap<String, Value>,
+}
+
+impl SceneSnapshot {
+    /// Snapshot of the current scene, including nodes that are never saved
+    pub fn capture(scene: &Scene) -> Self {
+        let mut nodes: Vec<SceneNode> = scene.nodes().map(|(_, node)| node.clone()).collect();
+        nodes.sort_by_key(|node| node.id);
+        let mut edges: Vec<SceneEdge> = scene.edges().cloned().collect();
+        edges.sort_by_key(|edge| edge.id);
+        Self {
+            version: SCENE_FORMAT_VERSION,
+            saved_at: Utc::now(),
+            nodes,
+            edges,
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    /// Snapshot for a save of class `save`
+    ///
+    /// Nodes whose persistence class is not written to that save are left
+    /// out, together with the edges touching them.
+    pub fn capture_for(scene: &Scene, save: NodePersistence) -> Self {
+        let mut snapshot = Self::capture(scene);
+        let before = snapshot.nodes.len();
+        snapshot.nodes.retain(|node| node.metadata.persistence.is_saved_in(save));
+        if snapshot.nodes.len() < before {
+            let kept: std::collections::HashSet<_> = snapshot.nodes.iter().map(|node| node.id).collect();
+            snapshot.edges.retain(|edge| kept.contains(&edge.source) && kept.contains(&edge.target));
+        }
+        snapshot
+    }
+
+    /// Replace the contents of `scene` with the snapshot, keeping IDs
+    pub fn restore(&self, scene: &mut Scene) {
+        scene.clear();
+        for node in &self.nodes {
+            scene.insert_node(node.clone());
+        }
+        for edge in &self.edges {
+            scene.insert_edge(edge.clone());
+        }
+    }
+
+    /// Build a new scene from the snapshot
+    pub fn to_scene(&self) -> Scene {
+        let mut scene = Scene::new();
+        self.restore(&mut scene);
+        scene
+    }
+}
+
+/// Rewrites the JSON of one format version into the next
+pub type Migration = Box<dyn Fn(&mut Value) -> Result<(), String> + Send + Sync>;
+
+/// Reads and writes scene snapshots, upgrading old versions
+#[derive(Default)]
+pub struct SceneFormat {
+    /// Migrations by the version they upgrade from
+    migrations: BTreeMap<u32, Migration>,
+}
+
+impl SceneFormat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the migration from `from_version` to `from_version + 1`, replacing any earlier one
+    pub fn register_migration<F>(&mut self, from_version: u32, migration: F)
+    where
+        F: Fn(&mut Value) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.migrations.insert(from_version, Box::new(migration));
+    }
+
+    /// Serialize a snapshot
+    pub fn encode(&self, snapshot: &SceneSnapshot) -> Result<String, GraphEngineError> {
+        serde_json::to_string_pretty(snapshot).map_err(|e| GraphEngineError::PersistenceError(e.to_string()))
+    }
+
+    /// Parse a snapshot of any known version
+    pub fn decode(&self, text: &str) -> Result<SceneSnapshot, GraphEngineError> {
+        let value: Value = serde_json::from_str(text).map_err(|e| GraphEngineError::PersistenceError(e.to_string()))?;
+        let value = self.migrate(value)?;
+        serde_json::from_value(value).map_err(|e| GraphEngineError::PersistenceError(e.to_string()))
+    }
+
+    /// Upgrade snapshot JSON to the current version
+    pub fn migrate(&self, mut value: Value) -> Result<Value, GraphEngineError> {
+        let error = |message: String| GraphEngineError::PersistenceError(message);
+        let mut version = value
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| error("Scene file has no format version".into()))? as u32;
+        if version > SCENE_FORMAT_VERSION {
+            return Err(error(format!(
+                "Scene file version {} is newer than the supported version {}",
+                version, SCENE_FORMAT_VERSION
+            )));
+        }
+        while version < SCENE_FORMAT_VERSION {
+            let migration = self
+                .migrations
+                .get(&version)
+                .ok_or_else(|| error(format!("No migration from scene format version {}", version)))?;
+            migration(&mut value).map_err(|e| error(format!("Migrating from version {} failed: {}", version, e)))?;
+            version += 1;
+            value["version"] = Value::from(version);
+            log::debug!("Migrated scene file to format version {}", version);
+        }
+        Ok(value)
+    }
+
+    /// Save a snapshot, replacing the file only once it is fully written
+    pub fn save(&self, snapshot: &SceneSnapshot, path: &Path) -> Result<(), GraphEngineError> {
+        let text = self.encode(snapshot)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temporary = path.with_extension("tmp");
+        std::fs::write(&temporary, text)?;
+        std::fs::rename(&temporary, path)?;
+        Ok(())
+    }
+
+    /// Load a snapshot saved by this or an earlier version
+    pub fn load(&self, path: &Path) -> Result<SceneSnapshot, GraphEngineError> {
+        self.decode(&std::fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EdgeType, NodeMetadata, NodeType};
+    use nalgebra::{Point3, Vector3};
+    use serde_json::json;
+
+    fn node(name: &str) -> SceneNode {
+        SceneNode {
+            id: 0,
+            position: Point3::new(1.0, 2.0, 3.0),
+            velocity: Vector3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type: NodeType::Concept { title: name.to_string(), content: String::new() },
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_round_trip_keeps_ids() {
+        let mut scene = Scene::new();
+        let a = scene.add_node(node("a"));
+        let b = scene.add_node(node("b"));
+        scene.remove_node(a);
+        let c = scene.add_node(node("c"));
+        let edge = scene.add_edge(SceneEdge {
+            id: 0,
+            source: b,
+            target: c,
+            edge_type: EdgeType::RelatedTo { similarity: 0.5 },
+            weight: 1.0,
+            color: [1.0; 4],
+            visible: true,
+            animated: false,
+        });
+
+        let format = SceneFormat::new();
+        let text = format.encode(&SceneSnapshot::capture(&scene)).unwrap();
+        let mut restored = format.decode(&text).unwrap().to_scene();
+
+        assert!(restored.get_node(a).is_none());
+        assert_eq!(restored.get_node_position(c), Some(Point3::new(1.0, 2.0, 3.0)));
+        assert_eq!(restored.get_edge(edge).map(|e| (e.source, e.target)), Some((b, c)));
+        // New IDs do not collide with restored ones
+        assert!(restored.add_node(node("d")) > edge);
+    }
+
+    #[test]
+    fn test_saves_leave_out_transient_nodes() {
+        let mut scene = Scene::new();
+        let durable = scene.add_node(node("durable"));
+        let session = scene.add_node_with_persistence(node("window"), NodePersistence::Session);
+        let ephemeral = scene.add_node_with_persistence(node("stream"), NodePersistence::Ephemeral);
+        for (source, target) in [(durable, session), (durable, ephemeral)] {
+            scene.add_edge(SceneEdge {
+                id: 0,
+                source,
+                target,
+                edge_type: EdgeType::RelatedTo { similarity: 0.5 },
+                weight: 1.0,
+                color: [1.0; 4],
+                visible: true,
+                animated: false,
+            });
+        }
+
+        let ids = |snapshot: &SceneSnapshot| snapshot.nodes.iter().map(|node| node.id).collect::<Vec<_>>();
+        let session_save = SceneSnapshot::capture_for(&scene, NodePersistence::Session);
+        assert_eq!(ids(&session_save), vec![durable, session]);
+        assert_eq!(session_save.edges.len(), 1);
+        let durable_save = SceneSnapshot::capture_for(&scene, NodePersistence::Durable);
+        assert_eq!(ids(&durable_save), vec![durable]);
+        assert!(durable_save.edges.is_empty());
+
+        // Pinning a node makes it part of regular saves
+        assert!(scene.set_node_persistence(ephemeral, NodePersistence::Durable));
+        assert_eq!(ids(&SceneSnapshot::capture_for(&scene, NodePersistence::Durable)), vec![durable, ephemeral]);
+    }
+
+    #[test]
+    fn test_old_versions_are_migrated() {
+        let snapshot = SceneSnapshot::capture(&Scene::new());
+        let mut old = serde_json::to_value(&snapshot).unwrap();
+        old["version"] = json!(0);
+        old["edges"] = json!([{ "id": 1, "source": 2, "target": 3, "kind": "Similar", "weight": 1.0,
+            "color": [1.0, 1.0, 1.0, 1.0], "visible": true, "animated": false }]);
+        let text = old.to_string();
+
+        let mut format = SceneFormat::new();
+        assert!(format.decode(&text).is_err());
+
+        // Version 0 called edge types `kind` and had a plain `Similar` type
+        format.register_migration(0, |value| {
+            for edge in value["edges"].as_array_mut().ok_or("edges missing")? {
+                let edge = edge.as_object_mut().ok_or("edge is not an object")?;
+                let kind = edge.remove("kind").ok_or("edge kind missing")?;
+                let edge_type = match kind.as_str() {
+                    Some("Similar") => json!({ "RelatedTo": { "similarity": 1.0 } }),
+                    _ => kind,
+                };
+                edge.insert("edge_type".into(), edge_type);
+            }
+            Ok(())
+        });
+        let migrated = format.decode(&text).unwrap();
+        assert_eq!(migrated.version, SCENE_FORMAT_VERSION);
+        assert!(matches!(migrated.edges[0].edge_type, EdgeType::RelatedTo { .. }));
+
+        let mut newer = serde_json::to_value(&snapshot).unwrap();
+        newer["version"] = json!(SCENE_FORMAT_VERSION + 1);
+        assert!(format.decode(&newer.to_string()).is_err());
+    }
+}
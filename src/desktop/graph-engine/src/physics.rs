@@ -1,8 +1,15 @@
 //! Physics simulation for graph layout and interactions
+//!
+//! Bodies connected by edges form islands. Layout forces only act within an
+//! island, so each island settles on its own; once all of its bodies have
+//! stayed slower than [`PhysicsSettings::sleep_velocity`] for
+//! [`PhysicsSettings::sleep_delay`] seconds the island falls asleep and is
+//! skipped until something wakes it: moving one of its bodies, a new or
+//! removed edge, an applied force, or an awake body colliding with it.
 
 use nalgebra::{Point3, Vector3};
 use std::collections::HashMap;
-use crate::{SceneId, Scene, SceneNode};
+use crate::{SceneId, Scene, SceneEdge, SceneNode};
 
 /// Physics engine for graph layout and node interactions
 #[derive(Debug)]
@@ -15,6 +22,25 @@ pub struct PhysicsEngine {
     settings: PhysicsSettings,
     /// Layout algorithm configuration
     layout_config: LayoutConfig,
+    /// Endpoints of the edges linking bodies, by edge ID
+    springs: HashMap<SceneId, (SceneId, SceneId)>,
+    /// Connected groups of bodies
+    islands: Vec<Island>,
+    /// Index into `islands` of each body
+    island_of: HashMap<SceneId, usize>,
+    /// Whether bodies or edges changed since the islands were built
+    islands_dirty: bool,
+    /// Whether stepping is suspended, e.g. for an idle scene while saving power
+    paused: bool,
+}
+
+/// Bodies connected by edges, simulated and put to sleep together
+#[derive(Debug, Clone, Default)]
+struct Island {
+    bodies: Vec<SceneId>,
+    asleep: bool,
+    /// Seconds every body has been below the sleep velocity
+    still_time: f32,
 }
 
 /// Physics body representing a node
@@ -41,6 +67,10 @@ pub struct PhysicsSettings {
     pub min_distance: f32,
     /// Enable collision detection
     pub collision_detection: bool,
+    /// Speed below which a body counts as settled
+    pub sleep_velocity: f32,
+    /// Seconds all bodies of an island must stay settled before it sleeps
+    pub sleep_delay: f32,
 }
 
 /// Configuration for layout algorithms
@@ -94,6 +124,11 @@ impl PhysicsEngine {
             forces: HashMap::new(),
             settings: PhysicsSettings::default(),
             layout_config: LayoutConfig::default(),
+            springs: HashMap::new(),
+            islands: Vec::new(),
+            island_of: HashMap::new(),
+            islands_dirty: false,
+            paused: false,
         }
     }
     
@@ -110,35 +145,149 @@ impl PhysicsEngine {
         
         self.bodies.insert(node.id, body);
         self.forces.insert(node.id, Vector3::zeros());
+        self.islands_dirty = true;
     }
     
-    /// Remove a physics body
+    /// Remove a physics body and the edges linking it
     pub fn remove_body(&mut self, id: SceneId) {
+        // Its neighbours lose a force, so they have to settle again
+        self.wake(id);
         self.bodies.remove(&id);
         self.forces.remove(&id);
+        self.springs.retain(|_, (source, target)| *source != id && *target != id);
+        self.islands_dirty = true;
     }
     
-    /// Update physics body from scene node
+    /// Update physics body from scene node, waking its island if the node was moved
     pub fn update_body(&mut self, node: &SceneNode) {
-        if let Some(body) = self.bodies.get_mut(&node.id) {
-            if !body.fixed {
-                body.position = node.position;
-                body.velocity = node.velocity;
-                body.radius = node.radius;
-            }
+        let Some(body) = self.bodies.get_mut(&node.id) else {
+            return;
+        };
+        if body.fixed {
+            return;
+        }
+        let moved = body.position != node.position;
+        body.position = node.position;
+        body.velocity = node.velocity;
+        body.radius = node.radius;
+        if moved {
+            self.wake(node.id);
+        }
+    }
+    
+    /// Link the bodies of an edge's endpoints, merging and waking their islands
+    ///
+    /// Edges whose endpoints have no bodies are ignored.
+    pub fn add_edge(&mut self, edge: &SceneEdge) {
+        if edge.source == edge.target || !self.has_body(edge.source) || !self.has_body(edge.target) {
+            return;
+        }
+        self.springs.insert(edge.id, (edge.source, edge.target));
+        self.islands_dirty = true;
+        self.wake(edge.source);
+    }
+    
+    /// Unlink the bodies of an edge, waking the island it held together
+    pub fn remove_edge(&mut self, edge_id: SceneId) {
+        if let Some((source, _)) = self.springs.remove(&edge_id) {
+            self.wake(source);
+            self.islands_dirty = true;
+        }
+    }
+    
+    /// Follow the nodes and edges removed from or added to the scene
+    ///
+    /// Bodies of removed nodes are dropped and edges between bodies are
+    /// linked or unlinked, waking the islands that changed.
+    pub fn sync_edges(&mut self, scene: &Scene) {
+        let removed: Vec<SceneId> = self.bodies.keys().filter(|id| scene.get_node(**id).is_none()).copied().collect();
+        for id in removed {
+            self.remove_body(id);
+        }
+        let stale: Vec<SceneId> = self.springs.keys().filter(|id| scene.get_edge(**id).is_none()).copied().collect();
+        for id in stale {
+            self.remove_edge(id);
+        }
+        let added: Vec<SceneEdge> = scene.edges().filter(|edge| !self.springs.contains_key(&edge.id)).cloned().collect();
+        for edge in &added {
+            self.add_edge(edge);
         }
     }
     
+    /// Wake the island of a body
+    pub fn wake(&mut self, id: SceneId) {
+        self.ensure_islands();
+        if let Some(island) = self.island_of.get(&id).and_then(|index| self.islands.get_mut(*index)) {
+            island.asleep = false;
+            island.still_time = 0.0;
+        }
+    }
+    
+    /// Wake every island, e.g. after changing the layout configuration
+    pub fn wake_all(&mut self) {
+        for island in &mut self.islands {
+            island.asleep = false;
+            island.still_time = 0.0;
+        }
+    }
+    
+    /// Whether the island of a body is asleep
+    pub fn is_sleeping(&self, id: SceneId) -> bool {
+        self.island_of
+            .get(&id)
+            .and_then(|index| self.islands.get(*index))
+            .is_some_and(|island| island.asleep)
+    }
+    
+    /// Number of islands, building them if bodies or edges changed
+    pub fn island_count(&mut self) -> usize {
+        self.ensure_islands();
+        self.islands.len()
+    }
+    
+    /// Number of bodies simulated by the next step
+    pub fn awake_body_count(&mut self) -> usize {
+        self.ensure_islands();
+        self.islands.iter().filter(|island| !island.asleep).map(|island| island.bodies.len()).sum()
+    }
+    
+    /// Suspend or resume stepping; bodies keep their state while paused
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+    
+    /// Whether stepping is suspended
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+    
     /// Step the physics simulation
+    ///
+    /// Only awake islands are simulated; an island whose bodies have all
+    /// settled falls asleep afterwards. Nothing moves while paused.
     pub fn step(&mut self, delta_time: f32) {
+        if self.paused {
+            return;
+        }
+        self.ensure_islands();
+        
         // Clear previous forces
         for force in self.forces.values_mut() {
             *force = Vector3::zeros();
         }
         
-        // Apply layout forces
-        self.apply_force_directed_layout();
-        self.apply_repulsion_forces();
+        let awake: Vec<usize> = (0..self.islands.len()).filter(|index| !self.islands[*index].asleep).collect();
+        for &index in &awake {
+            let bodies: Vec<PhysicsBody> = self.islands[index]
+                .bodies
+                .iter()
+                .filter_map(|id| self.bodies.get(id).cloned())
+                .collect();
+            
+            // Apply layout forces
+            self.apply_force_directed_layout(&bodies);
+            self.apply_repulsion_forces(&bodies);
+        }
         
         // Apply collision detection if enabled
         if self.settings.collision_detection {
@@ -146,7 +295,82 @@ impl PhysicsEngine {
         }
         
         // Integrate forces and update positions
-        self.integrate_forces(delta_time);
+        for &index in &awake {
+            self.integrate_forces(index, delta_time);
+        }
+        self.update_sleep(&awake, delta_time);
+    }
+    
+    /// Rebuild the islands from the bodies and edges if either changed
+    ///
+    /// A rebuilt island stays asleep only if all of its bodies were asleep.
+    fn ensure_islands(&mut self) {
+        if !self.islands_dirty {
+            return;
+        }
+        self.islands_dirty = false;
+        
+        // Union-find over the edges
+        let ids: Vec<SceneId> = self.bodies.keys().copied().collect();
+        let index: HashMap<SceneId, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let mut parent: Vec<usize> = (0..ids.len()).collect();
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        for (source, target) in self.springs.values() {
+            if let (Some(&a), Some(&b)) = (index.get(source), index.get(target)) {
+                let (a, b) = (find(&mut parent, a), find(&mut parent, b));
+                parent[a] = b;
+            }
+        }
+        
+        let mut islands: Vec<Island> = Vec::new();
+        let mut by_root: HashMap<usize, usize> = HashMap::new();
+        let mut island_of = HashMap::with_capacity(ids.len());
+        for (i, id) in ids.iter().enumerate() {
+            let root = find(&mut parent, i);
+            let island_index = *by_root.entry(root).or_insert_with(|| {
+                islands.push(Island { asleep: true, ..Island::default() });
+                islands.len() - 1
+            });
+            let was_asleep = self.is_sleeping(*id);
+            let island = &mut islands[island_index];
+            island.bodies.push(*id);
+            island.asleep &= was_asleep;
+            island_of.insert(*id, island_index);
+        }
+        self.islands = islands;
+        self.island_of = island_of;
+    }
+    
+    /// Put islands whose bodies all settled to sleep
+    fn update_sleep(&mut self, awake: &[usize], delta_time: f32) {
+        let sleep_velocity = self.settings.sleep_velocity;
+        for &index in awake {
+            let island = &mut self.islands[index];
+            let settled = island
+                .bodies
+                .iter()
+                .filter_map(|id| self.bodies.get(id))
+                .all(|body| body.fixed || body.velocity.magnitude() < sleep_velocity);
+            if !settled {
+                island.still_time = 0.0;
+                continue;
+            }
+            island.still_time += delta_time;
+            if island.still_time >= self.settings.sleep_delay {
+                island.asleep = true;
+                for id in &island.bodies {
+                    if let Some(body) = self.bodies.get_mut(id) {
+                        body.velocity = Vector3::zeros();
+                    }
+                }
+            }
+        }
     }
     
     /// Synchronize physics bodies back to scene nodes
@@ -163,20 +387,23 @@ impl PhysicsEngine {
     pub fn set_node_fixed(&mut self, id: SceneId, fixed: bool) {
         if let Some(body) = self.bodies.get_mut(&id) {
             body.fixed = fixed;
+            self.wake(id);
         }
     }
     
-    /// Apply a force to a specific node
+    /// Apply a force to a specific node, waking its island
     pub fn apply_force(&mut self, id: SceneId, force: Vector3<f32>) {
         if let Some(existing_force) = self.forces.get_mut(&id) {
             *existing_force += force;
+            if force != Vector3::zeros() {
+                self.wake(id);
+            }
         }
     }
     
-    /// Apply force-directed layout algorithm
-    fn apply_force_directed_layout(&mut self) {
+    /// Apply force-directed layout algorithm between the bodies of one island
+    fn apply_force_directed_layout(&mut self, bodies: &[PhysicsBody]) {
         let config = &self.layout_config.force_directed;
-        let bodies: Vec<_> = self.bodies.values().cloned().collect();
         
         for i in 0..bodies.len() {
             for j in (i + 1)..bodies.len() {
@@ -212,10 +439,9 @@ impl PhysicsEngine {
         }
     }
     
-    /// Apply repulsion forces between all nodes
-    fn apply_repulsion_forces(&mut self) {
+    /// Apply repulsion forces between the bodies of one island
+    fn apply_repulsion_forces(&mut self, bodies: &[PhysicsBody]) {
         let config = &self.layout_config.repulsion;
-        let bodies: Vec<_> = self.bodies.values().cloned().collect();
         
         for i in 0..bodies.len() {
             for j in (i + 1)..bodies.len() {
@@ -243,11 +469,19 @@ impl PhysicsEngine {
     }
     
     /// Apply collision detection and response
+    ///
+    /// Awake bodies collide with every body; a sleeping island that is hit
+    /// wakes up and responds from the next step.
     fn apply_collision_forces(&mut self) {
         let bodies: Vec<_> = self.bodies.values().cloned().collect();
+        let asleep: Vec<bool> = bodies.iter().map(|body| self.is_sleeping(body.id)).collect();
+        let mut hit = Vec::new();
         
         for i in 0..bodies.len() {
             for j in (i + 1)..bodies.len() {
+                if asleep[i] && asleep[j] {
+                    continue;
+                }
                 let body1 = &bodies[i];
                 let body2 = &bodies[j];
                 
@@ -268,14 +502,26 @@ impl PhysicsEngine {
                     if let Some(force2) = self.forces.get_mut(&body2.id) {
                         *force2 -= force;
                     }
+                    if asleep[i] {
+                        hit.push(body1.id);
+                    } else if asleep[j] {
+                        hit.push(body2.id);
+                    }
                 }
             }
         }
+        
+        for id in hit {
+            self.wake(id);
+        }
     }
     
-    /// Integrate forces and update positions
-    fn integrate_forces(&mut self, delta_time: f32) {
-        for (id, body) in self.bodies.iter_mut() {
+    /// Integrate forces and update positions of the bodies of one island
+    fn integrate_forces(&mut self, island: usize, delta_time: f32) {
+        for id in &self.islands[island].bodies {
+            let Some(body) = self.bodies.get_mut(id) else {
+                continue;
+            };
             if body.fixed {
                 continue;
             }
@@ -334,6 +580,8 @@ impl Default for PhysicsSettings {
             max_velocity: 50.0,
             min_distance: 0.1,
             collision_detection: true,
+            sleep_velocity: 0.05,
+            sleep_delay: 0.5,
         }
     }
 }
@@ -0,0 +1,270 @@
+//! Timeline playback shared by the graph and cluster timelines
+//!
+//! A [`PlaybackClock`] moves a play position through a timeline's range, as
+//! a fraction of it: played at a speed over a wall-clock duration, sought
+//! directly, wrapping or stopping at the ends. Timelines map the position
+//! to a point in time and decide what the scene looks like there. Before
+//! playback changes the scene, a [`PlaybackBase`] captures the look of the
+//! elements involved, so states can be applied over and over while seeking
+//! and the scene put back when playback ends.
+
+use crate::{Scene, SceneId};
+use chrono::{DateTime, Duration as TimeDelta, Utc};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Look of a node before playback
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeLook {
+    pub radius: f32,
+    pub color: [f32; 4],
+    pub visible: bool,
+}
+
+/// Look of an edge before playback
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeLook {
+    pub color: [f32; 4],
+    pub visible: bool,
+}
+
+/// Look of scene elements before playback, restored afterwards
+///
+/// Playback shows elements relative to this look, so frames can be applied
+/// repeatedly while seeking.
+#[derive(Debug, Clone, Default)]
+pub struct PlaybackBase {
+    nodes: HashMap<SceneId, NodeLook>,
+    edges: HashMap<SceneId, EdgeLook>,
+}
+
+impl PlaybackBase {
+    /// Capture the look of some nodes and edges; unknown ids are skipped
+    pub fn capture(
+        scene: &Scene,
+        nodes: impl IntoIterator<Item = SceneId>,
+        edges: impl IntoIterator<Item = SceneId>,
+    ) -> Self {
+        Self {
+            nodes: nodes.into_iter()
+                .filter_map(|id| scene.get_node(id).map(|node| (id, NodeLook { radius: node.radius, color: node.color, visible: node.visible })))
+                .collect(),
+            edges: edges.into_iter()
+                .filter_map(|id| scene.get_edge(id).map(|edge| (id, EdgeLook { color: edge.color, visible: edge.visible })))
+                .collect(),
+        }
+    }
+
+    /// Capture the look of every node and edge in the scene
+    pub fn capture_scene(scene: &Scene) -> Self {
+        let nodes: Vec<SceneId> = scene.nodes().map(|(id, _)| *id).collect();
+        let edges: Vec<SceneId> = scene.edges().map(|edge| edge.id).collect();
+        Self::capture(scene, nodes, edges)
+    }
+
+    /// Captured nodes
+    pub fn nodes(&self) -> impl Iterator<Item = (SceneId, NodeLook)> + '_ {
+        self.nodes.iter().map(|(id, look)| (*id, *look))
+    }
+
+    /// Captured edges
+    pub fn edges(&self) -> impl Iterator<Item = (SceneId, EdgeLook)> + '_ {
+        self.edges.iter().map(|(id, look)| (*id, *look))
+    }
+
+    /// Put the captured look back
+    pub fn restore(&self, scene: &mut Scene) {
+        for (node_id, look) in &self.nodes {
+            if let Some(node) = scene.get_node_mut(*node_id) {
+                node.visible = look.visible;
+                node.radius = look.radius;
+                node.color = look.color;
+            }
+        }
+        for (edge_id, look) in &self.edges {
+            if let Some(edge) = scene.get_edge_mut(*edge_id) {
+                edge.visible = look.visible;
+                edge.color = look.color;
+            }
+        }
+    }
+}
+
+/// Playback settings
+#[derive(Debug, Clone)]
+pub struct PlaybackConfig {
+    /// Wall-clock length of playing the whole range at speed 1
+    pub duration: Duration,
+    /// Wall-clock time an element takes to grow in
+    pub grow_time: Duration,
+    /// Wall-clock time a modification highlight lasts
+    pub highlight_time: Duration,
+    /// Start over at the end
+    pub looping: bool,
+}
+
+impl Default for PlaybackConfig {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_secs(20),
+            grow_time: Duration::from_millis(800),
+            highlight_time: Duration::from_millis(600),
+            looping: false,
+        }
+    }
+}
+
+/// Play position moving through a timeline, as a fraction of its range
+#[derive(Debug, Clone)]
+pub struct PlaybackClock {
+    config: PlaybackConfig,
+    position: f64,
+    playing: bool,
+    speed: f64,
+}
+
+impl PlaybackClock {
+    /// Create a paused clock at a position
+    pub fn new(config: PlaybackConfig, position: f64) -> Self {
+        Self {
+            config,
+            position: position.clamp(0.0, 1.0),
+            playing: false,
+            speed: 1.0,
+        }
+    }
+
+    /// Playback settings
+    pub fn config(&self) -> &PlaybackConfig {
+        &self.config
+    }
+
+    /// Change the playback settings, keeping the position
+    pub fn set_config(&mut self, config: PlaybackConfig) {
+        self.config = config;
+    }
+
+    /// Start playing, from the far end if at the end playback heads for
+    pub fn play(&mut self) {
+        if self.speed > 0.0 && self.position >= 1.0 {
+            self.position = 0.0;
+        } else if self.speed < 0.0 && self.position <= 0.0 {
+            self.position = 1.0;
+        }
+        self.playing = true;
+    }
+
+    /// Pause playback
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Toggle between playing and paused
+    pub fn toggle(&mut self) {
+        if self.playing {
+            self.pause();
+        } else {
+            self.play();
+        }
+    }
+
+    /// Whether playback is running
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Playback speed multiplier; negative plays backwards
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.abs().clamp(0.1, 16.0).copysign(speed);
+    }
+
+    /// Playback speed multiplier
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Jump to a fraction of the range
+    pub fn seek_fraction(&mut self, fraction: f64) {
+        self.position = fraction.clamp(0.0, 1.0);
+    }
+
+    /// Position as a fraction of the range
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+
+    /// Move the position by wall-clock time while playing
+    ///
+    /// Playback wraps around when looping and otherwise stops at either
+    /// end. Returns whether the position moved.
+    pub fn advance(&mut self, elapsed: Duration) -> bool {
+        if !self.playing || self.config.duration.is_zero() {
+            return false;
+        }
+        let mut position = self.position + elapsed.as_secs_f64() / self.config.duration.as_secs_f64() * self.speed;
+        if self.config.looping {
+            position = position.rem_euclid(1.0);
+        } else if !(0.0..1.0).contains(&position) {
+            position = position.clamp(0.0, 1.0);
+            self.playing = false;
+        }
+        let before = self.position;
+        self.position = position;
+        self.position != before
+    }
+
+    /// Timeline time that passes in `wall` wall-clock time, for a range of `span`
+    pub fn timeline_time(&self, wall: Duration, span: TimeDelta) -> TimeDelta {
+        let duration = self.config.duration.as_secs_f64().max(0.001);
+        scale(span, wall.as_secs_f64() / duration * self.speed.abs())
+    }
+
+    /// Point of a range at the play position
+    pub fn time_in(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> DateTime<Utc> {
+        start + scale(end - start, self.position)
+    }
+
+    /// Number of frames that play the whole range at `fps`
+    pub fn frame_count(&self, fps: u32) -> usize {
+        (self.config.duration.as_secs_f64() / self.speed.abs() * fps as f64).ceil().max(1.0) as usize
+    }
+}
+
+fn scale(delta: TimeDelta, factor: f64) -> TimeDelta {
+    TimeDelta::milliseconds((delta.num_milliseconds() as f64 * factor).round() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_plays_both_ways_and_loops() {
+        let mut clock = PlaybackClock::new(PlaybackConfig { duration: Duration::from_secs(10), ..Default::default() }, 1.0);
+
+        // Playing from the end starts over; the position follows wall-clock time
+        clock.play();
+        assert_eq!(clock.position(), 0.0);
+        assert!(clock.advance(Duration::from_secs(4)));
+        assert!((clock.position() - 0.4).abs() < 1e-9);
+
+        // Backwards playback stops at the start
+        clock.set_speed(-2.0);
+        assert!(clock.advance(Duration::from_secs(4)));
+        assert_eq!(clock.position(), 0.0);
+        assert!(!clock.is_playing());
+        assert!(!clock.advance(Duration::from_secs(1)));
+
+        // Looping wraps around instead
+        clock.set_config(PlaybackConfig { duration: Duration::from_secs(10), looping: true, ..Default::default() });
+        clock.set_speed(1.0);
+        clock.seek_fraction(0.9);
+        clock.play();
+        clock.advance(Duration::from_secs(2));
+        assert!((clock.position() - 0.1).abs() < 1e-9);
+        assert!(clock.is_playing());
+
+        // A second of playback covers a tenth of a ten hour range
+        assert_eq!(clock.timeline_time(Duration::from_secs(1), TimeDelta::hours(10)), TimeDelta::hours(1));
+    }
+}
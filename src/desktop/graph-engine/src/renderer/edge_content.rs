@@ -202,6 +202,17 @@ impl EdgeContentAnalyzer {
                     },
                     count: None,
                 },
+                // Analysis settings read by the shader
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
             ],
         });
         
@@ -677,6 +688,11 @@ impl EdgeContentAnalyzer {
             .unwrap_or(0.0)
     }
     
+    /// Current configuration
+    pub fn config(&self) -> &EdgeContentConfig {
+        &self.config
+    }
+    
     /// Update configuration
     pub fn update_config(&mut self, new_config: EdgeContentConfig) {
         // Clear cache if settings changed significantly
@@ -0,0 +1,281 @@
+//! Hot reloading of WGSL shaders during theme and effect development
+//!
+//! `ShaderHotReloader` watches a directory for `<name>.wgsl` files matching
+//! registered shaders. Changed files are parsed and validated with naga before
+//! they replace the current source; invalid shaders are rejected and the
+//! last-good source keeps being used. Results are reported as `ShaderEvent`s so
+//! the desktop can surface failures as notifications.
+
+use crate::GraphEngineError;
+use crossbeam_channel::{Receiver, Sender};
+use notify::{RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Shader reload outcome
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShaderEvent {
+    /// New source validated and swapped in
+    Reloaded { name: String, generation: u64 },
+    /// Source rejected, last-good shader kept
+    Failed { name: String, error: String },
+}
+
+/// Tracked state of one shader
+#[derive(Debug, Clone)]
+struct ShaderState {
+    /// Compiled-in source used when no file overrides it
+    builtin: &'static str,
+    /// Entry points the pipeline requires
+    entry_points: Vec<String>,
+    /// Source currently in use
+    source: String,
+    /// Source in use before the last reload, for `revert`
+    previous: Option<String>,
+    /// Incremented on every successful swap
+    generation: u64,
+    /// Error from the last rejected reload
+    last_error: Option<String>,
+}
+
+/// Parse and validate WGSL, checking the required entry points exist
+pub fn validate_wgsl(source: &str, entry_points: &[&str]) -> Result<(), GraphEngineError> {
+    let module = naga::front::wgsl::parse_str(source)
+        .map_err(|e| GraphEngineError::ShaderError(e.emit_to_string(source)))?;
+
+    naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all())
+        .validate(&module)
+        .map_err(|e| GraphEngineError::ShaderError(e.emit_to_string(source)))?;
+
+    for entry_point in entry_points {
+        if !module.entry_points.iter().any(|ep| ep.name == *entry_point) {
+            return Err(GraphEngineError::ShaderError(format!("Missing entry point `{}`", entry_point)));
+        }
+    }
+    Ok(())
+}
+
+/// Watches a shader directory and keeps the last-good source of each shader
+pub struct ShaderHotReloader {
+    /// Directory holding `<name>.wgsl` overrides
+    dir: PathBuf,
+    /// Registered shaders by file name
+    shaders: HashMap<String, ShaderState>,
+    /// File system watcher, alive while watching
+    watcher: Option<notify::RecommendedWatcher>,
+    /// Changed paths reported by the watcher
+    fs_events: Option<Receiver<PathBuf>>,
+    /// Event subscribers
+    subscribers: Vec<Sender<ShaderEvent>>,
+}
+
+impl ShaderHotReloader {
+    /// Create a reloader for a shader directory with the renderer's built-in shaders registered
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let mut reloader = Self {
+            dir: dir.into(),
+            shaders: HashMap::new(),
+            watcher: None,
+            fs_events: None,
+            subscribers: Vec::new(),
+        };
+        reloader.register(super::shaders::NODE_VERTEX_SHADER_NAME, super::shaders::NODE_VERTEX_SHADER, &["vs_main"]);
+        reloader.register(super::shaders::NODE_FRAGMENT_SHADER_NAME, super::shaders::NODE_FRAGMENT_SHADER, &["fs_main"]);
+        reloader.register(super::shaders::EDGE_VERTEX_SHADER_NAME, super::shaders::EDGE_VERTEX_SHADER, &["vs_main"]);
+        reloader.register(super::shaders::EDGE_FRAGMENT_SHADER_NAME, super::shaders::EDGE_FRAGMENT_SHADER, &["fs_main"]);
+        reloader
+    }
+
+    /// Register a shader by file name with its built-in source
+    pub fn register(&mut self, name: &str, builtin: &'static str, entry_points: &[&str]) {
+        self.shaders.insert(name.to_string(), ShaderState {
+            builtin,
+            entry_points: entry_points.iter().map(|s| s.to_string()).collect(),
+            source: builtin.to_string(),
+            previous: None,
+            generation: 0,
+            last_error: None,
+        });
+    }
+
+    /// Directory being watched
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Start watching the shader directory and load any overrides already present
+    pub fn watch(&mut self) -> Result<Vec<ShaderEvent>, GraphEngineError> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            if let Ok(event) = result {
+                if event.kind.is_create() || event.kind.is_modify() || event.kind.is_remove() {
+                    for path in event.paths {
+                        let _ = tx.send(path);
+                    }
+                }
+            }
+        })
+        .map_err(|e| GraphEngineError::System(format!("Failed to create shader watcher: {}", e)))?;
+
+        watcher
+            .watch(&self.dir, RecursiveMode::NonRecursive)
+            .map_err(|e| GraphEngineError::System(format!("Failed to watch {:?}: {}", self.dir, e)))?;
+
+        self.watcher = Some(watcher);
+        self.fs_events = Some(rx);
+
+        let names: Vec<String> = self.shaders.keys()
+            .filter(|name| self.dir.join(name).exists())
+            .cloned()
+            .collect();
+        Ok(names.iter().filter_map(|name| self.reload(name)).collect())
+    }
+
+    /// Stop watching
+    pub fn unwatch(&mut self) {
+        self.watcher = None;
+        self.fs_events = None;
+    }
+
+    /// Subscribe to reload events
+    pub fn subscribe(&mut self) -> Receiver<ShaderEvent> {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        self.subscribers.push(tx);
+        rx
+    }
+
+    /// Process pending file changes, returning what was reloaded or rejected
+    pub fn poll(&mut self) -> Vec<ShaderEvent> {
+        let Some(fs_events) = &self.fs_events else {
+            return Vec::new();
+        };
+
+        // Editors often write a file several times per save
+        let changed: HashSet<String> = fs_events.try_iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "wgsl"))
+            .filter_map(|path| path.file_name().map(|name| name.to_string_lossy().into_owned()))
+            .filter(|name| self.shaders.contains_key(name))
+            .collect();
+
+        let mut names: Vec<String> = changed.into_iter().collect();
+        names.sort();
+        names.iter().filter_map(|name| self.reload(name)).collect()
+    }
+
+    /// Reload one shader from disk, or from its built-in source if the file was removed
+    pub fn reload(&mut self, name: &str) -> Option<ShaderEvent> {
+        let path = self.dir.join(name);
+        let state = self.shaders.get_mut(name)?;
+
+        let candidate = if path.exists() {
+            match std::fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(e) => {
+                    let event = Self::reject(state, name, format!("Failed to read {:?}: {}", path, e));
+                    self.publish(&event);
+                    return Some(event);
+                }
+            }
+        } else {
+            state.builtin.to_string()
+        };
+
+        if candidate == state.source {
+            return None;
+        }
+
+        let entry_points: Vec<&str> = state.entry_points.iter().map(String::as_str).collect();
+        let event = match validate_wgsl(&candidate, &entry_points) {
+            Ok(()) => {
+                state.previous = Some(std::mem::replace(&mut state.source, candidate));
+                state.generation += 1;
+                state.last_error = None;
+                log::info!("Reloaded shader {} (generation {})", name, state.generation);
+                ShaderEvent::Reloaded { name: name.to_string(), generation: state.generation }
+            }
+            Err(e) => Self::reject(state, name, e.to_string()),
+        };
+        self.publish(&event);
+        Some(event)
+    }
+
+    /// Restore the source in use before the last reload, e.g. when pipeline creation failed
+    pub fn revert(&mut self, name: &str, error: impl Into<String>) -> Option<ShaderEvent> {
+        let state = self.shaders.get_mut(name)?;
+        let previous = state.previous.take()?;
+        state.source = previous;
+        state.generation += 1;
+        let event = Self::reject(state, name, error.into());
+        self.publish(&event);
+        Some(event)
+    }
+
+    /// Current source of a shader
+    pub fn source(&self, name: &str) -> Option<&str> {
+        self.shaders.get(name).map(|state| state.source.as_str())
+    }
+
+    /// Number of successful swaps for a shader
+    pub fn generation(&self, name: &str) -> Option<u64> {
+        self.shaders.get(name).map(|state| state.generation)
+    }
+
+    /// Error from the last rejected reload of a shader
+    pub fn last_error(&self, name: &str) -> Option<&str> {
+        self.shaders.get(name).and_then(|state| state.last_error.as_deref())
+    }
+
+    /// Record a rejected reload
+    fn reject(state: &mut ShaderState, name: &str, error: String) -> ShaderEvent {
+        log::warn!("Shader {} rejected, keeping last-good version: {}", name, error);
+        state.last_error = Some(error.clone());
+        ShaderEvent::Failed { name: name.to_string(), error }
+    }
+
+    /// Send an event to subscribers, dropping disconnected ones
+    fn publish(&mut self, event: &ShaderEvent) {
+        self.subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::shaders;
+
+    #[test]
+    fn test_builtin_shaders_validate() {
+        validate_wgsl(shaders::NODE_VERTEX_SHADER, &["vs_main"]).unwrap();
+        validate_wgsl(shaders::NODE_FRAGMENT_SHADER, &["fs_main"]).unwrap();
+        validate_wgsl(shaders::EDGE_VERTEX_SHADER, &["vs_main"]).unwrap();
+        validate_wgsl(shaders::EDGE_FRAGMENT_SHADER, &["fs_main"]).unwrap();
+        assert!(validate_wgsl(shaders::EDGE_FRAGMENT_SHADER, &["main"]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_shader_keeps_last_good() {
+        let dir = std::env::temp_dir().join(format!("horizonos-shaders-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let name = shaders::EDGE_FRAGMENT_SHADER_NAME;
+        let mut reloader = ShaderHotReloader::new(&dir);
+        let events = reloader.subscribe();
+
+        let tinted = shaders::EDGE_FRAGMENT_SHADER.replace("in.color.rgb", "in.color.bgr");
+        std::fs::write(dir.join(name), &tinted).unwrap();
+        assert_eq!(reloader.reload(name), Some(ShaderEvent::Reloaded { name: name.to_string(), generation: 1 }));
+
+        std::fs::write(dir.join(name), "fn fs_main( {").unwrap();
+        assert!(matches!(reloader.reload(name), Some(ShaderEvent::Failed { .. })));
+        assert_eq!(reloader.source(name), Some(tinted.as_str()));
+        assert!(reloader.last_error(name).is_some());
+        assert_eq!(events.try_iter().count(), 2);
+
+        std::fs::remove_file(dir.join(name)).unwrap();
+        reloader.reload(name);
+        assert_eq!(reloader.source(name), Some(shaders::EDGE_FRAGMENT_SHADER));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,281 @@
+//! Instanced node drawing in batches
+//!
+//! Nodes are drawn with one instanced draw per batch rather than one draw
+//! per node: all sphere nodes share a batch, and icon nodes get a batch per
+//! icon atlas page, since a draw samples a single page. Batches are built
+//! outside the engine, e.g. by the performance crate's `InstancingSystem`,
+//! and handed to the renderer for the next frame. Without them the renderer
+//! draws every visible node as a sphere.
+
+use crate::{Camera, Scene, SceneNode};
+use super::primitives::NodeInstance;
+use super::upload::{UploadPriority, UploadScheduler};
+use super::visibility::VisibleNodes;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use wgpu::{Buffer, Device};
+
+/// Per-instance data of an icon node
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct IconInstance {
+    pub position: [f32; 3],
+    pub radius: f32,
+    pub color: [f32; 4],
+    /// Icon rectangle on its atlas page: min u, min v, max u, max v
+    pub uv_rect: [f32; 4],
+    pub selected: f32,
+    pub _padding: [f32; 3],
+}
+
+impl IconInstance {
+    /// Quad showing a node's video frame, just in front of the node as seen from `camera`
+    ///
+    /// The frame keeps its own colors; only the node's opacity applies.
+    pub fn video_frame(node: &SceneNode, camera: &Camera) -> Self {
+        let toward_camera = (camera.position - node.position)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(nalgebra::Vector3::zeros);
+        let position = node.position + toward_camera * node.radius * 1.01;
+        Self {
+            position: [position.x, position.y, position.z],
+            radius: node.radius,
+            color: [1.0, 1.0, 1.0, node.color[3]],
+            uv_rect: [0.0, 0.0, 1.0, 1.0],
+            selected: if node.selected { 1.0 } else { 0.0 },
+            _padding: [0.0; 3],
+        }
+    }
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        use std::mem;
+
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<IconInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 8,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    shader_location: 9,
+                    format: wgpu::VertexFormat::Float32,
+                },
+            ],
+        }
+    }
+}
+
+/// Where a node's icon sits in the icon atlas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IconSlot {
+    /// Atlas page holding the icon
+    pub page: u32,
+    /// Icon rectangle on the page: min u, min v, max u, max v
+    pub uv_rect: [f32; 4],
+}
+
+/// Node instances of one frame, grouped into instanced draws
+#[derive(Debug, Clone, Default)]
+pub struct NodeBatches {
+    /// Nodes drawn as lit spheres
+    pub spheres: Vec<NodeInstance>,
+    /// Icon nodes by atlas page
+    pub icon_pages: BTreeMap<u32, Vec<IconInstance>>,
+}
+
+impl NodeBatches {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every visible node as a sphere, the batches drawn when none were handed over
+    pub fn from_scene(scene: &Scene, visible: &VisibleNodes) -> Self {
+        let mut batches = Self::new();
+        for (&id, node) in scene.nodes() {
+            if node.visible && visible.contains(id) {
+                batches.push_sphere(node);
+            }
+        }
+        batches
+    }
+
+    /// Add a node to the sphere batch
+    pub fn push_sphere(&mut self, node: &SceneNode) {
+        self.spheres.push(NodeInstance {
+            position: [node.position.x, node.position.y, node.position.z],
+            color: node.color,
+            radius: node.radius,
+            selected: if node.selected { 1.0 } else { 0.0 },
+            _padding: [0.0; 2],
+        });
+    }
+
+    /// Add a node to the batch of its icon's atlas page
+    pub fn push_icon(&mut self, node: &SceneNode, slot: IconSlot) {
+        self.icon_pages.entry(slot.page).or_default().push(IconInstance {
+            position: [node.position.x, node.position.y, node.position.z],
+            radius: node.radius,
+            color: node.color,
+            uv_rect: slot.uv_rect,
+            selected: if node.selected { 1.0 } else { 0.0 },
+            _padding: [0.0; 3],
+        });
+    }
+
+    /// Instances across all batches
+    pub fn instance_count(&self) -> usize {
+        self.spheres.len() + self.icon_pages.values().map(Vec::len).sum::<usize>()
+    }
+
+    /// Instanced draws needed, one per non-empty batch
+    pub fn draw_calls(&self) -> usize {
+        usize::from(!self.spheres.is_empty()) + self.icon_pages.values().filter(|page| !page.is_empty()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instance_count() == 0
+    }
+}
+
+/// Vertex buffer for per-instance data that grows with the batches
+///
+/// Data goes through the upload scheduler as a visible upload, keyed by the
+/// buffer's label, so a frame's instances replace the previous frame's if
+/// those have not been uploaded yet.
+pub(crate) struct InstanceBuffer {
+    buffer: Arc<Buffer>,
+    /// Size in bytes
+    capacity: u64,
+    label: &'static str,
+}
+
+impl InstanceBuffer {
+    pub(crate) fn new(device: &Device, label: &'static str, capacity: u64) -> Self {
+        Self {
+            buffer: Self::create(device, label, capacity),
+            capacity,
+            label,
+        }
+    }
+
+    /// Queue instance data for upload, reallocating at twice the size when it does not fit
+    pub(crate) fn write(&mut self, device: &Device, uploads: &mut UploadScheduler, data: &[u8]) {
+        if data.is_empty() {
+            uploads.cancel(self.label);
+            return;
+        }
+        // Buffer copies need whole words
+        let mut data = data.to_vec();
+        data.resize(data.len().next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT as usize), 0);
+        let needed = data.len() as u64;
+        if needed > self.capacity {
+            self.capacity = needed.max(self.capacity * 2);
+            self.buffer = Self::create(device, self.label, self.capacity);
+        }
+        if let Err(e) = uploads.upload_buffer(self.label, UploadPriority::Visible, self.buffer.clone(), 0, data) {
+            log::warn!("Could not upload {}: {}", self.label, e);
+        }
+    }
+
+    pub(crate) fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    fn create(device: &Device, label: &'static str, size: u64) -> Arc<Buffer> {
+        Arc::new(device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NodeMetadata, NodeType, Position, SceneId};
+    use nalgebra::Vector3;
+
+    fn node(scene: &mut Scene, x: f32) -> SceneId {
+        scene.add_node(SceneNode {
+            id: 0,
+            position: Position::new(x, 0.0, 0.0),
+            velocity: Vector3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type: NodeType::Concept { title: String::new(), content: String::new() },
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        })
+    }
+
+    #[test]
+    fn test_batches_group_icons_by_atlas_page() {
+        let mut scene = Scene::new();
+        let ids: Vec<_> = (0..4).map(|i| node(&mut scene, i as f32)).collect();
+
+        let mut batches = NodeBatches::new();
+        batches.push_sphere(scene.get_node(ids[0]).unwrap());
+        batches.push_icon(scene.get_node(ids[1]).unwrap(), IconSlot { page: 0, uv_rect: [0.0, 0.0, 0.5, 0.5] });
+        batches.push_icon(scene.get_node(ids[2]).unwrap(), IconSlot { page: 2, uv_rect: [0.5, 0.5, 1.0, 1.0] });
+        batches.push_icon(scene.get_node(ids[3]).unwrap(), IconSlot { page: 0, uv_rect: [0.5, 0.0, 1.0, 0.5] });
+
+        assert_eq!(batches.instance_count(), 4);
+        assert_eq!(batches.draw_calls(), 3);
+        assert_eq!(batches.icon_pages[&0].len(), 2);
+        assert_eq!(batches.icon_pages[&2][0].uv_rect, [0.5, 0.5, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_scene_batches_skip_culled_nodes() {
+        let mut scene = Scene::new();
+        let a = node(&mut scene, 0.0);
+        node(&mut scene, 1.0);
+
+        assert_eq!(NodeBatches::from_scene(&scene, &VisibleNodes::all()).spheres.len(), 2);
+        let culled = NodeBatches::from_scene(&scene, &VisibleNodes::only([a]));
+        assert_eq!(culled.spheres.len(), 1);
+        assert_eq!(culled.draw_calls(), 1);
+        assert!(NodeBatches::new().is_empty());
+    }
+
+    #[test]
+    fn test_video_frames_face_the_camera_in_front_of_the_node() {
+        let mut scene = Scene::new();
+        let id = node(&mut scene, 0.0);
+        let mut camera = Camera::new();
+        camera.position = Position::new(0.0, 0.0, 10.0);
+
+        let frame = IconInstance::video_frame(scene.get_node(id).unwrap(), &camera);
+        assert_eq!(&frame.position[..2], &[0.0, 0.0]);
+        assert!(frame.position[2] > 1.0, "frame at {:?} is inside the node", frame.position);
+        assert_eq!(frame.uv_rect, [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(frame.color, [1.0; 4]);
+    }
+
+    #[test]
+    fn test_icon_shader_is_valid() {
+        super::super::hot_reload::validate_wgsl(super::super::shaders::ICON_SHADER, &["vs_main", "fs_main"]).unwrap();
+    }
+}
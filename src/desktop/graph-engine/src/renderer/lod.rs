@@ -408,6 +408,11 @@ impl LodManager {
         stats
     }
     
+    /// Current configuration
+    pub fn config(&self) -> &LodConfig {
+        &self.config
+    }
+    
     /// Update configuration
     pub fn update_config(&mut self, config: LodConfig) {
         self.config = config;
@@ -5,8 +5,18 @@ pub mod primitives;
 pub mod pipelines;
 pub mod lod;
 pub mod edge_content;
+pub mod hot_reload;
+pub mod upload;
+pub mod recovery;
+pub mod palette;
+pub mod visibility;
+pub mod instancing;
+pub mod picking;
+pub mod video;
+pub mod overlay;
+pub mod offscreen;
 
-use crate::{Scene, Camera, GraphEngineError};
+use crate::{Scene, Camera, GraphEngineError, EdgeRouter, EdgeRoutingConfig, EdgeFilter};
 use std::sync::Arc;
 use wgpu::{Device, Queue, Surface, SurfaceConfiguration};
 use winit::window::Window;
@@ -19,7 +29,9 @@ pub struct Renderer {
     
     // Render pipelines
     node_pipeline: pipelines::NodePipeline,
+    icon_pipeline: pipelines::IconPipeline,
     edge_pipeline: pipelines::EdgePipeline,
+    overlay_pipeline: overlay::OverlayPipeline,
     
     // Depth buffer
     depth_texture: wgpu::Texture,
@@ -31,14 +43,71 @@ pub struct Renderer {
     // Edge content analysis
     edge_content_analyzer: edge_content::EdgeContentAnalyzer,
     
+    // Built-in assets, and the shaders loaded from them
+    assets: crate::AssetStore,
+    shader_sources: shaders::ShaderSources,
+    
+    // Shader hot reloading, enabled during development
+    shader_reloader: Option<hot_reload::ShaderHotReloader>,
+    
+    // Budgeted texture and buffer uploads
+    upload_scheduler: upload::UploadScheduler,
+    
+    // Edge curves around nodes and cluster hulls
+    edge_router: EdgeRouter,
+    
+    // CPU copies of GPU resources, re-uploaded after device loss
+    resource_cache: recovery::ResourceCache,
+    
+    // Theme colors, replaced live when the theme changes
+    palette: palette::RenderPalette,
+    
+    // Edge types and strengths drawn, applied while collecting edge vertices
+    edge_filter: EdgeFilter,
+    
+    // Nodes that survived culling, and what the last frame drew
+    visible_nodes: visibility::VisibleNodes,
+    draw_stats: visibility::DrawStats,
+    
+    // Instanced node batches for the next frame, built from the scene when not handed over
+    node_batches: Option<instancing::NodeBatches>,
+    
+    // Node ID pass for picking, if the device supports it
+    picker: Option<picking::GpuPicker>,
+    
+    // Video frames of media nodes, imported or copied into textures
+    video: video::VideoTextures,
+    
+    // Selection feedback drawn over the scene
+    selection_visual: overlay::SelectionVisualState,
+    
+    // Edge being created, drawn with the scene's edges
+    ghost_edge: Option<pipelines::GhostEdgeLine>,
+    
+    // Minimap drawn over the selection feedback, when shown
+    minimap: Option<crate::Minimap>,
+    
+    // Workspace switch effects drawn under the selection feedback, while switching
+    switch_overlay: Option<overlay::SwitchOverlay>,
+    
     // Performance monitoring
     frame_count: u64,
     last_frame_time: std::time::Instant,
 }
 
 // Re-export pipelines and LOD
-pub use pipelines::{NodePipeline, EdgePipeline};
+pub use pipelines::{NodePipeline, IconPipeline, EdgePipeline, GhostEdgeLine};
 pub use lod::{LodManager, LodConfig, LodLevel, LodStatistics};
+pub use hot_reload::{ShaderHotReloader, ShaderEvent};
+pub use upload::{UploadScheduler, UploadConfig, UploadPriority, FrameTimeStats, FrameUploadStats};
+pub use recovery::{DeviceLossMonitor, DeviceRecovery, RecoveryState, RecoveryStep, RecoveryEvent, ResourceCache, CachedResourceKind};
+pub use palette::{RenderPalette, node_kind, edge_kind};
+pub use visibility::{VisibleNodes, DrawStats};
+pub use instancing::{NodeBatches, IconInstance, IconSlot};
+pub use picking::{GpuPicker, PickRequest, PickResult};
+pub use video::{VideoTextures, VideoTexture, VideoFrame, DmabufFrame, DmabufPlane, MemoryFrame, FramePath, VideoStats};
+pub use overlay::{OverlayPipeline, OverlayInstance, OverlayShape, SelectionVisualState, SwitchOverlay, build_overlay, build_minimap_overlay, build_switch_overlay};
+pub use offscreen::{OffscreenTarget, CapturedFrame, OFFSCREEN_FORMAT};
 
 impl Renderer {
     /// Create a new renderer
@@ -49,9 +118,27 @@ impl Renderer {
         window: &Window,
         adapter: &wgpu::Adapter,
     ) -> Result<Self, GraphEngineError> {
-        let window_size = window.inner_size();
-        
-        // Configure the surface
+        let surface_config = Self::configure_surface(&device, surface, adapter, window.inner_size());
+        Self::build(device, queue, surface_config, crate::AssetStore::from_env()).await
+    }
+    
+    /// Create a renderer drawing into off-screen targets of the given size
+    pub async fn new_offscreen(
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, GraphEngineError> {
+        Self::build(device, queue, Self::offscreen_config(width, height), crate::AssetStore::from_env()).await
+    }
+    
+    /// Pick a format for the surface and configure it at the given size
+    fn configure_surface(
+        device: &Device,
+        surface: &Surface<'static>,
+        adapter: &wgpu::Adapter,
+        window_size: winit::dpi::PhysicalSize<u32>,
+    ) -> SurfaceConfiguration {
         let surface_caps = surface.get_capabilities(adapter);
         let surface_format = surface_caps
             .formats
@@ -71,14 +158,42 @@ impl Renderer {
             desired_maximum_frame_latency: 2,
         };
         
-        surface.configure(&device, &surface_config);
+        surface.configure(device, &surface_config);
+        surface_config
+    }
+    
+    /// Target description for off-screen rendering; never applied to a surface
+    fn offscreen_config(width: u32, height: u32) -> SurfaceConfiguration {
+        SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format: offscreen::OFFSCREEN_FORMAT,
+            width: width.max(1),
+            height: height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        }
+    }
+    
+    /// Create a renderer for targets of the given configuration, with shaders from `assets`
+    async fn build(
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+        surface_config: SurfaceConfiguration,
+        assets: crate::AssetStore,
+    ) -> Result<Self, GraphEngineError> {
+        let surface_format = surface_config.format;
         
         // Create depth buffer
         let (depth_texture, depth_view) = Self::create_depth_texture(&device, &surface_config);
         
         // Create render pipelines
-        let node_pipeline = pipelines::NodePipeline::new(&device, surface_format).await?;
-        let edge_pipeline = pipelines::EdgePipeline::new(&device, surface_format).await?;
+        let shader_sources = shaders::ShaderSources::load(&assets);
+        let node_pipeline = pipelines::NodePipeline::new(&device, surface_format, &shader_sources).await?;
+        let icon_pipeline = pipelines::IconPipeline::new(&device, surface_format, &shader_sources);
+        let edge_pipeline = pipelines::EdgePipeline::new(&device, surface_format, &shader_sources).await?;
+        let overlay_pipeline = overlay::OverlayPipeline::new(&device, surface_format, &shader_sources);
         
         // Create LOD manager
         let lod_config = lod::LodConfig::default();
@@ -87,16 +202,41 @@ impl Renderer {
         // Create edge content analyzer
         let edge_content_analyzer = edge_content::EdgeContentAnalyzer::new(device.clone())?;
         
+        // Picking falls back to ray casting without it
+        let picker = picking::GpuPicker::new(device.clone(), queue.clone(), &shader_sources)
+            .map_err(|e| log::warn!("{}", e))
+            .ok();
+        let video = video::VideoTextures::new(device.clone());
+        
         Ok(Renderer {
             device,
             queue,
             surface_config,
             node_pipeline,
+            icon_pipeline,
             edge_pipeline,
+            overlay_pipeline,
             depth_texture,
             depth_view,
             lod_manager,
             edge_content_analyzer,
+            assets,
+            shader_sources,
+            shader_reloader: None,
+            upload_scheduler: upload::UploadScheduler::default(),
+            edge_router: EdgeRouter::new(EdgeRoutingConfig::default()),
+            resource_cache: recovery::ResourceCache::new(),
+            palette: palette::RenderPalette::default(),
+            edge_filter: EdgeFilter::default(),
+            visible_nodes: visibility::VisibleNodes::default(),
+            draw_stats: visibility::DrawStats::default(),
+            node_batches: None,
+            picker,
+            video,
+            selection_visual: overlay::SelectionVisualState::default(),
+            ghost_edge: None,
+            minimap: None,
+            switch_overlay: None,
             frame_count: 0,
             last_frame_time: std::time::Instant::now(),
         })
@@ -105,29 +245,96 @@ impl Renderer {
     /// Render a frame with LOD optimization
     pub fn render(&mut self, surface: &Surface, scene: &Scene, camera: &Camera) -> Result<(), GraphEngineError> {
         let frame_start = std::time::Instant::now();
-        let output = surface
-            .get_current_texture()
-            .map_err(|e| GraphEngineError::RenderError(format!("Surface error: {:?}", e)))?;
+        self.apply_shader_reloads();
+        let output = match surface.get_current_texture() {
+            Ok(output) => output,
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                // The surface changed under us, e.g. after a mode switch; draw again next frame
+                surface.configure(&self.device, &self.surface_config);
+                return Ok(());
+            }
+            Err(wgpu::SurfaceError::Timeout) => return Ok(()),
+            Err(wgpu::SurfaceError::OutOfMemory) => {
+                return Err(GraphEngineError::DeviceLost("Out of memory acquiring a frame".into()));
+            }
+        };
             
         let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
-        
+        self.draw_frame(&view, scene, camera)?;
+        output.present();
+        self.frame_finished(frame_start);
+        Ok(())
+    }
+    
+    /// Render a frame into an off-screen target instead of a surface
+    ///
+    /// The target has to match the renderer's size and use `OFFSCREEN_FORMAT`.
+    pub fn render_to_target(&mut self, target: &offscreen::OffscreenTarget, scene: &Scene, camera: &Camera) -> Result<(), GraphEngineError> {
+        if target.size() != self.window_size() {
+            return Err(GraphEngineError::RenderError(format!(
+                "Off-screen target is {:?} but the renderer draws {:?}", target.size(), self.window_size()
+            )));
+        }
+        let frame_start = std::time::Instant::now();
+        self.apply_shader_reloads();
+        self.draw_frame(target.view(), scene, camera)?;
+        self.frame_finished(frame_start);
+        Ok(())
+    }
+    
+    /// Record and submit the passes of a frame drawing into `view`
+    fn draw_frame(&mut self, view: &wgpu::TextureView, scene: &Scene, camera: &Camera) -> Result<(), GraphEngineError> {
         let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Graph Render Encoder"),
         });
         
+        self.resource_cache.restore(&self.device, &mut self.upload_scheduler)?;
+        self.edge_router.update(scene);
+        
+        // Node instances are queued before the pass, growing their buffers as needed
+        let mut draw_stats = visibility::DrawStats::default();
+        let batches = self.node_batches.take()
+            .unwrap_or_else(|| instancing::NodeBatches::from_scene(scene, &self.visible_nodes));
+        self.node_pipeline.prepare(&self.device, &self.queue, &mut self.upload_scheduler, camera, &batches.spheres);
+        // Nodes playing video show their current frame in front of them
+        let videos: Vec<_> = scene.nodes()
+            .filter(|(&id, node)| node.visible && self.visible_nodes.contains(id))
+            .filter_map(|(&id, node)| {
+                let texture = self.video.texture(id)?;
+                Some((instancing::IconInstance::video_frame(node, camera), &texture.view))
+            })
+            .collect();
+        let icons_drawn = self.icon_pipeline.prepare(&self.device, &self.queue, &mut self.upload_scheduler, camera, &batches, &videos);
+        draw_stats.nodes_drawn = batches.spheres.len() as u32 + icons_drawn;
+        draw_stats.node_draw_calls = u32::from(!batches.spheres.is_empty()) + self.icon_pipeline.draw_calls();
+        if self.visible_nodes.is_culling() {
+            draw_stats.nodes_culled = scene.nodes()
+                .filter(|(&id, node)| node.visible && !self.visible_nodes.contains(id))
+                .count() as u32;
+        }
+        
+        // Selection feedback goes over the finished scene, in a pass without depth
+        let viewport = (self.surface_config.width as f32, self.surface_config.height as f32);
+        let mut overlay = self.switch_overlay
+            .map(|switch| overlay::build_switch_overlay(&switch, viewport))
+            .unwrap_or_default();
+        overlay.extend(overlay::build_overlay(&self.selection_visual, scene, camera, viewport, &self.palette));
+        if let Some(minimap) = &self.minimap {
+            overlay.extend(overlay::build_minimap_overlay(minimap, viewport, &self.palette));
+        }
+        let draw_overlay = self.overlay_pipeline.prepare(&self.device, &self.queue, &mut self.upload_scheduler, viewport, &overlay);
+        
+        // Queued uploads are recorded before the passes so this frame sees them
+        self.upload_scheduler.flush(&self.device, &self.queue, &mut encoder);
+        
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Graph Render Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
+                    view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.02,
-                            g: 0.02,
-                            b: 0.05,
-                            a: 1.0,
-                        }),
+                        load: wgpu::LoadOp::Clear(self.palette.clear_color()),
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -144,35 +351,60 @@ impl Renderer {
             });
             
             // Render edges first (behind nodes)
-            self.edge_pipeline.render(&mut render_pass, &self.queue, scene, camera)?;
+            self.edge_pipeline.render(&mut render_pass, &self.queue, scene, camera, &self.edge_router, &self.edge_filter, &self.visible_nodes, self.ghost_edge.as_ref(), &mut draw_stats)?;
             
             // Analyze edge content for semantic relationships
             self.edge_content_analyzer.analyze_edges(scene, camera)?;
             
-            // Render nodes
-            self.node_pipeline.render(&mut render_pass, &self.queue, scene, camera)?;
+            // Render nodes, one instanced draw for the spheres and one per icon atlas page
+            self.node_pipeline.draw(&mut render_pass);
+            self.icon_pipeline.draw(&mut render_pass);
+            self.draw_stats = draw_stats;
         }
         
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+        if draw_overlay {
+            let mut overlay_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Selection Overlay Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            self.overlay_pipeline.draw(&mut overlay_pass);
+        }
         
-        // Update performance counters and LOD system
+        self.upload_scheduler.finish();
+        self.queue.submit(std::iter::once(encoder.finish()));
+        self.upload_scheduler.recall();
+        Ok(())
+    }
+    
+    /// Update performance counters and the LOD system after a frame was submitted
+    fn frame_finished(&mut self, frame_start: std::time::Instant) {
         self.frame_count += 1;
         let now = std::time::Instant::now();
         let frame_time = frame_start.elapsed().as_secs_f32() * 1000.0; // Convert to milliseconds
         
         self.lod_manager.update_performance(frame_time);
+        self.upload_scheduler.record_frame_time(frame_start.elapsed());
         
         if now.duration_since(self.last_frame_time).as_secs() >= 1 {
             let stats = self.lod_manager.get_statistics();
-            log::debug!("FPS: {}, LOD Stats: High:{} Med:{} Low:{} Culled:{}, Perf:{:.2}", 
+            let frame_times = self.upload_scheduler.frame_time_stats();
+            log::debug!("FPS: {}, LOD Stats: High:{} Med:{} Low:{} Culled:{}, Perf:{:.2}, Frame: {:.2}ms ±{:.2}ms, Uploads pending: {} bytes", 
                        self.frame_count, stats.high_count, stats.medium_count, 
-                       stats.low_count, stats.culled_count, stats.performance_scaling);
+                       stats.low_count, stats.culled_count, stats.performance_scaling,
+                       frame_times.mean_ms, frame_times.std_dev_ms(), self.upload_scheduler.pending_bytes());
             self.frame_count = 0;
             self.last_frame_time = now;
         }
-        
-        Ok(())
     }
     
     /// Resize the renderer
@@ -181,15 +413,290 @@ impl Renderer {
             self.surface_config.width = new_size.width;
             self.surface_config.height = new_size.height;
             surface.configure(&self.device, &self.surface_config);
-            
-            // Recreate depth buffer
-            let (depth_texture, depth_view) = Self::create_depth_texture(&self.device, &self.surface_config);
-            self.depth_texture = depth_texture;
-            self.depth_view = depth_view;
+            self.resize_depth();
         }
         Ok(())
     }
     
+    /// Resize an off-screen renderer; targets have to be recreated at the new size
+    pub fn resize_offscreen(&mut self, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            self.surface_config.width = width;
+            self.surface_config.height = height;
+            self.resize_depth();
+        }
+    }
+    
+    /// Recreate the depth buffer at the current size
+    fn resize_depth(&mut self) {
+        let (depth_texture, depth_view) = Self::create_depth_texture(&self.device, &self.surface_config);
+        self.depth_texture = depth_texture;
+        self.depth_view = depth_view;
+    }
+    
+    /// Watch a directory for shader overrides, swapping pipelines as shaders change
+    ///
+    /// The returned receiver reports reloads and rejected shaders.
+    pub fn enable_shader_hot_reload(&mut self, dir: impl Into<std::path::PathBuf>) -> Result<crossbeam_channel::Receiver<ShaderEvent>, GraphEngineError> {
+        let mut reloader = ShaderHotReloader::new(dir);
+        let events = reloader.subscribe();
+        let initial = reloader.watch()?;
+        self.shader_reloader = Some(reloader);
+        self.rebuild_reloaded_pipelines(&initial);
+        Ok(events)
+    }
+    
+    /// Stop watching for shader changes, keeping the current pipelines
+    pub fn disable_shader_hot_reload(&mut self) {
+        self.shader_reloader = None;
+    }
+    
+    /// Scheduler for large texture and buffer uploads, drained within a per-frame budget
+    pub fn uploads(&mut self) -> &mut upload::UploadScheduler {
+        &mut self.upload_scheduler
+    }
+    
+    /// Replace the upload scheduler configuration; pending uploads are dropped
+    pub fn set_upload_config(&mut self, config: upload::UploadConfig) {
+        self.upload_scheduler = upload::UploadScheduler::new(config);
+    }
+    
+    /// Frame-time statistics with the current upload setting and, if it changed, before it
+    pub fn frame_time_comparison(&self) -> (upload::FrameTimeStats, Option<(bool, upload::FrameTimeStats)>) {
+        (self.upload_scheduler.frame_time_stats(), self.upload_scheduler.baseline_frame_time_stats())
+    }
+    
+    /// GPU resources kept with CPU copies so they survive device loss
+    pub fn resources(&mut self) -> &mut recovery::ResourceCache {
+        &mut self.resource_cache
+    }
+    
+    /// Colors the frame is drawn with
+    pub fn palette(&self) -> &palette::RenderPalette {
+        &self.palette
+    }
+    
+    /// Switch to new theme colors, returning the previous ones
+    pub fn set_palette(&mut self, palette: palette::RenderPalette) -> palette::RenderPalette {
+        std::mem::replace(&mut self.palette, palette)
+    }
+    
+    /// Draw only the nodes that survived culling, or every node with `VisibleNodes::all()`
+    pub fn set_visible_nodes(&mut self, visible: visibility::VisibleNodes) {
+        self.visible_nodes = visible;
+    }
+    
+    /// Draw this selection feedback over the scene from the next frame on
+    pub fn set_selection_visual_state(&mut self, state: overlay::SelectionVisualState) {
+        self.selection_visual = state;
+    }
+    
+    /// Draw this ghost edge with the edges from the next frame on, or none
+    pub fn set_ghost_edge(&mut self, ghost: Option<pipelines::GhostEdgeLine>) {
+        self.ghost_edge = ghost;
+    }
+    
+    /// Draw this minimap over the scene from the next frame on, or none
+    pub fn set_minimap(&mut self, minimap: Option<crate::Minimap>) {
+        self.minimap = minimap;
+    }
+    
+    /// Draw this frame of a workspace switch from the next frame on, or none
+    pub fn set_switch_overlay(&mut self, switch: Option<overlay::SwitchOverlay>) {
+        self.switch_overlay = switch;
+    }
+    
+    /// Selection feedback currently drawn
+    pub fn selection_visual_state(&self) -> &overlay::SelectionVisualState {
+        &self.selection_visual
+    }
+    
+    /// Nodes and edges the last frame drew and culled
+    pub fn draw_stats(&self) -> visibility::DrawStats {
+        self.draw_stats
+    }
+    
+    /// Draw the next frame's nodes from these batches instead of the scene
+    ///
+    /// The batches are used for one frame; hand over new ones every frame,
+    /// e.g. from the performance crate's `InstancingSystem`.
+    pub fn set_node_batches(&mut self, batches: instancing::NodeBatches) {
+        self.node_batches = Some(batches);
+    }
+    
+    /// Queue an icon atlas page of RGBA8 pixels for icon node batches
+    pub fn set_icon_atlas_page(&mut self, page: u32, width: u32, height: u32, rgba: Vec<u8>) -> Result<(), GraphEngineError> {
+        self.icon_pipeline.set_atlas_page(&self.device, &mut self.upload_scheduler, page, width, height, rgba)
+    }
+    
+    /// Drop an icon atlas page; icons on it are not drawn until it is uploaded again
+    pub fn remove_icon_atlas_page(&mut self, page: u32) {
+        self.icon_pipeline.remove_atlas_page(page);
+    }
+    
+    /// Video frame textures of media nodes
+    pub fn video(&mut self) -> &mut video::VideoTextures {
+        &mut self.video
+    }
+    
+    /// Show a new video frame on a node; copied frames are uploaded by the scheduler
+    pub fn submit_video_frame(&mut self, node_id: crate::SceneId, frame: video::VideoFrame) -> Result<video::FramePath, GraphEngineError> {
+        self.video.submit(node_id, frame, &mut self.upload_scheduler)
+    }
+    
+    /// Stop showing video on a node
+    pub fn remove_video(&mut self, node_id: crate::SceneId) {
+        self.video.remove(node_id, &mut self.upload_scheduler);
+    }
+    
+    /// How video frames reached their textures, zero-copy or copied
+    pub fn video_stats(&self) -> video::VideoStats {
+        self.video.stats()
+    }
+    
+    /// Edges currently drawn
+    pub fn edge_filter(&self) -> &EdgeFilter {
+        &self.edge_filter
+    }
+    
+    /// Change which edges are drawn from the next frame on, without touching the scene
+    pub fn set_edge_filter(&mut self, filter: EdgeFilter) -> EdgeFilter {
+        std::mem::replace(&mut self.edge_filter, filter)
+    }
+    
+    /// Start a GPU pick of the node at a screen position; `None` when GPU picking is unavailable
+    pub fn request_pick(&self, scene: &Scene, camera: &Camera, screen_pos: (f32, f32)) -> Option<picking::PickRequest> {
+        let picker = self.picker.as_ref()?;
+        Some(picker.request(scene, camera, screen_pos, self.window_size()))
+    }
+    
+    /// Rebuild all GPU state on a new device after the old one was lost
+    ///
+    /// Settings, edge routes, shader overrides and cached resources carry over;
+    /// cached resources are re-uploaded over the next frames. Off-screen
+    /// renderers have no surface to reconfigure and pass `None`.
+    pub async fn recover(
+        &mut self,
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+        surface: Option<&Surface<'static>>,
+        adapter: &wgpu::Adapter,
+    ) -> Result<(), GraphEngineError> {
+        let surface_config = match surface {
+            Some(surface) => {
+                let size = winit::dpi::PhysicalSize::new(self.surface_config.width, self.surface_config.height);
+                Self::configure_surface(&device, surface, adapter, size)
+            }
+            None => self.surface_config.clone(),
+        };
+        let mut rebuilt = Self::build(device, queue, surface_config, self.assets.clone()).await?;
+        
+        rebuilt.lod_manager.update_config(self.lod_manager.config().clone());
+        rebuilt.edge_content_analyzer.update_config(self.edge_content_analyzer.config().clone());
+        // Pending uploads target objects of the lost device and are dropped
+        rebuilt.upload_scheduler = upload::UploadScheduler::new(self.upload_scheduler.config().clone());
+        rebuilt.upload_scheduler.set_enabled(self.upload_scheduler.is_enabled());
+        rebuilt.edge_router = std::mem::replace(&mut self.edge_router, EdgeRouter::new(EdgeRoutingConfig::default()));
+        rebuilt.resource_cache = std::mem::take(&mut self.resource_cache);
+        rebuilt.resource_cache.invalidate();
+        rebuilt.shader_reloader = self.shader_reloader.take();
+        rebuilt.apply_shader_overrides();
+        rebuilt.palette = std::mem::take(&mut self.palette);
+        rebuilt.edge_filter = std::mem::take(&mut self.edge_filter);
+        rebuilt.visible_nodes = std::mem::take(&mut self.visible_nodes);
+        rebuilt.selection_visual = std::mem::take(&mut self.selection_visual);
+        rebuilt.ghost_edge = self.ghost_edge.take();
+        rebuilt.icon_pipeline.restore_atlas_pages(&rebuilt.device, &mut rebuilt.upload_scheduler, &self.icon_pipeline)?;
+        // Imported and copied frames belong to the lost device; counters carry over
+        let mut video = std::mem::replace(&mut self.video, rebuilt.video);
+        video.recover(rebuilt.device.clone());
+        rebuilt.video = video;
+        
+        *self = rebuilt;
+        Ok(())
+    }
+    
+    /// Put shader overrides of the hot reloader into freshly built pipelines
+    fn apply_shader_overrides(&mut self) {
+        let Some(reloader) = self.shader_reloader.as_ref() else {
+            return;
+        };
+        let node_vertex = reloader.source(shaders::NODE_VERTEX_SHADER_NAME);
+        let node_fragment = reloader.source(shaders::NODE_FRAGMENT_SHADER_NAME);
+        if node_vertex.is_some() || node_fragment.is_some() {
+            let vertex = node_vertex.unwrap_or(&self.shader_sources.node_vertex);
+            let fragment = node_fragment.unwrap_or(&self.shader_sources.node_fragment);
+            if let Err(e) = self.node_pipeline.reload_shaders(&self.device, vertex, fragment) {
+                log::warn!("Keeping built-in node shaders after device recovery: {}", e);
+            }
+        }
+        let edge_vertex = reloader.source(shaders::EDGE_VERTEX_SHADER_NAME);
+        let edge_fragment = reloader.source(shaders::EDGE_FRAGMENT_SHADER_NAME);
+        if edge_vertex.is_some() || edge_fragment.is_some() {
+            let vertex = edge_vertex.unwrap_or(&self.shader_sources.edge_vertex);
+            let fragment = edge_fragment.unwrap_or(&self.shader_sources.edge_fragment);
+            if let Err(e) = self.edge_pipeline.reload_shaders(&self.device, vertex, fragment) {
+                log::warn!("Keeping built-in edge shaders after device recovery: {}", e);
+            }
+        }
+    }
+    
+    /// Router that bends edges around nodes and cluster hulls
+    pub fn edge_router_mut(&mut self) -> &mut EdgeRouter {
+        &mut self.edge_router
+    }
+    
+    /// Change the edge routing quality/performance settings
+    pub fn set_edge_routing(&mut self, config: EdgeRoutingConfig) {
+        self.edge_router.set_config(config);
+    }
+    
+    /// Pick up shader changes between frames
+    fn apply_shader_reloads(&mut self) {
+        let events = match self.shader_reloader.as_mut() {
+            Some(reloader) => reloader.poll(),
+            None => return,
+        };
+        self.rebuild_reloaded_pipelines(&events);
+    }
+    
+    /// Rebuild pipelines whose shaders were reloaded, reverting shaders the GPU rejects
+    fn rebuild_reloaded_pipelines(&mut self, events: &[ShaderEvent]) {
+        let Some(reloader) = self.shader_reloader.as_mut() else {
+            return;
+        };
+        let reloaded = |names: [&str; 2]| -> Vec<String> {
+            events.iter()
+                .filter_map(|event| match event {
+                    ShaderEvent::Reloaded { name, .. } if names.contains(&name.as_str()) => Some(name.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+        
+        let node_shaders = reloaded([shaders::NODE_VERTEX_SHADER_NAME, shaders::NODE_FRAGMENT_SHADER_NAME]);
+        if !node_shaders.is_empty() {
+            let vertex = reloader.source(shaders::NODE_VERTEX_SHADER_NAME).unwrap_or(&self.shader_sources.node_vertex);
+            let fragment = reloader.source(shaders::NODE_FRAGMENT_SHADER_NAME).unwrap_or(&self.shader_sources.node_fragment);
+            if let Err(e) = self.node_pipeline.reload_shaders(&self.device, vertex, fragment) {
+                for name in &node_shaders {
+                    reloader.revert(name, e.to_string());
+                }
+            }
+        }
+        
+        let edge_shaders = reloaded([shaders::EDGE_VERTEX_SHADER_NAME, shaders::EDGE_FRAGMENT_SHADER_NAME]);
+        if !edge_shaders.is_empty() {
+            let vertex = reloader.source(shaders::EDGE_VERTEX_SHADER_NAME).unwrap_or(&self.shader_sources.edge_vertex);
+            let fragment = reloader.source(shaders::EDGE_FRAGMENT_SHADER_NAME).unwrap_or(&self.shader_sources.edge_fragment);
+            if let Err(e) = self.edge_pipeline.reload_shaders(&self.device, vertex, fragment) {
+                for name in &edge_shaders {
+                    reloader.revert(name, e.to_string());
+                }
+            }
+        }
+    }
+    
     /// Get current window size
     pub fn window_size(&self) -> (u32, u32) {
         (self.surface_config.width, self.surface_config.height)
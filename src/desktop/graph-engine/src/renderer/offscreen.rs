@@ -0,0 +1,178 @@
+//! Off-screen render targets and frame read-back
+//!
+//! Lets the engine draw without a window, e.g. for integration tests of
+//! rendering and layout, and read finished frames back as RGBA pixels that
+//! can be written out as PNG files.
+
+use crate::GraphEngineError;
+use image::ImageEncoder;
+use wgpu::{Device, Queue};
+use std::path::Path;
+
+/// Color format of off-screen targets; four bytes per pixel, RGBA order
+pub const OFFSCREEN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+const BYTES_PER_PIXEL: u32 = 4;
+
+/// A texture frames are drawn into instead of a window surface
+pub struct OffscreenTarget {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    width: u32,
+    height: u32,
+}
+
+/// A frame read back from the GPU
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturedFrame {
+    pub width: u32,
+    pub height: u32,
+    /// Tightly packed RGBA8 pixels, rows top to bottom
+    pub rgba: Vec<u8>,
+}
+
+impl OffscreenTarget {
+    /// Create a target of the given size; zero dimensions are raised to one pixel
+    pub fn new(device: &Device, width: u32, height: u32) -> Self {
+        let width = width.max(1);
+        let height = height.max(1);
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: OFFSCREEN_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self { texture, view, width, height }
+    }
+
+    /// View to use as the color attachment
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    /// Texture frames are drawn into, e.g. to sample it elsewhere
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    /// Size in pixels
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Copy the target's current contents back to the CPU, waiting for the GPU
+    pub fn read_pixels(&self, device: &Device, queue: &Queue) -> Result<CapturedFrame, GraphEngineError> {
+        let row_bytes = self.width * BYTES_PER_PIXEL;
+        // Buffer copies need rows aligned to 256 bytes
+        let padded_row_bytes = row_bytes.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Offscreen Readback"),
+            size: padded_row_bytes as u64 * self.height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Offscreen Readback Encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_bytes),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|_| GraphEngineError::RenderError("Frame read-back was dropped".into()))?
+            .map_err(|e| GraphEngineError::RenderError(format!("Could not map frame for read-back: {}", e)))?;
+
+        let mut rgba = Vec::with_capacity((row_bytes * self.height) as usize);
+        {
+            let mapped = slice.get_mapped_range();
+            for row in mapped.chunks(padded_row_bytes as usize) {
+                rgba.extend_from_slice(&row[..row_bytes as usize]);
+            }
+        }
+        buffer.unmap();
+
+        Ok(CapturedFrame {
+            width: self.width,
+            height: self.height,
+            rgba,
+        })
+    }
+}
+
+impl CapturedFrame {
+    /// RGBA value of a pixel, `None` outside the frame
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let start = ((y * self.width + x) * BYTES_PER_PIXEL) as usize;
+        self.rgba.get(start..start + 4).map(|p| [p[0], p[1], p[2], p[3]])
+    }
+
+    /// Encode the frame as a PNG image
+    pub fn encode_png(&self) -> Result<Vec<u8>, GraphEngineError> {
+        let mut png = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut png)
+            .write_image(&self.rgba, self.width, self.height, image::ExtendedColorType::Rgba8)
+            .map_err(|e| GraphEngineError::RenderError(format!("Could not encode frame as PNG: {}", e)))?;
+        Ok(png)
+    }
+
+    /// Write the frame to a PNG file
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), GraphEngineError> {
+        std::fs::write(path, self.encode_png()?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_png_encoding_round_trips() {
+        let frame = CapturedFrame {
+            width: 2,
+            height: 1,
+            rgba: vec![255, 0, 0, 255, 0, 0, 255, 128],
+        };
+        assert_eq!(frame.pixel(1, 0), Some([0, 0, 255, 128]));
+        assert_eq!(frame.pixel(2, 0), None);
+
+        let png = frame.encode_png().unwrap();
+        let decoded = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(decoded.dimensions(), (2, 1));
+        assert_eq!(decoded.into_raw(), frame.rgba);
+    }
+}
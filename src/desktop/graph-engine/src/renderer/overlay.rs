@@ -0,0 +1,527 @@
+//! Screen-space selection overlay
+//!
+//! Selection feedback is drawn in a pass of its own after the scene, in
+//! pixels and without depth testing, so nodes never hide it: the rubber band
+//! of a box selection, a ring around the node under the pointer and outlines
+//! around selected nodes, the primary one thicker. The interaction crate
+//! publishes what to draw as a [`SelectionVisualState`]; the overlay turns it
+//! into instanced quads the shader fills or outlines as rectangles and rings.
+//! The minimap, when shown, is drawn in the same pass on top of the selection.
+//! Workspace switch effects go first, under everything else: the accent
+//! sweeping across the screen and the emblem badge pulsing in its middle.
+
+use crate::{Camera, Minimap, Scene, SceneId};
+use super::instancing::InstanceBuffer;
+use super::upload::UploadScheduler;
+use super::palette::RenderPalette;
+use super::shaders;
+use wgpu::{BindGroup, Buffer, Device, Queue, RenderPass, RenderPipeline};
+
+/// Gap between a node and its selection outline, in pixels
+const OUTLINE_GAP: f32 = 3.0;
+/// Width of selection outlines, in pixels
+const OUTLINE_WIDTH: f32 = 2.0;
+/// Width of the primary selection's outline, in pixels
+const PRIMARY_OUTLINE_WIDTH: f32 = 3.5;
+/// Gap between a node and its hover ring, in pixels
+const HOVER_GAP: f32 = 6.0;
+/// Width of the hover ring, in pixels
+const HOVER_WIDTH: f32 = 1.5;
+/// Width of the box selection border, in pixels
+const RUBBER_BAND_BORDER: f32 = 1.0;
+/// Opacity of the minimap background
+const MINIMAP_OPACITY: f32 = 0.85;
+/// Spacing of the dots that draw minimap lines, in pixels
+const MINIMAP_DOT_SPACING: f32 = 3.0;
+/// Radius of the ring marking the camera in the minimap, in pixels
+const MINIMAP_CAMERA_RADIUS: f32 = 3.0;
+/// Opacity of the accent behind the sweep edge as the sweep starts
+const SWEEP_FILL_OPACITY: f32 = 0.25;
+/// Width of the sweep edge, in pixels
+const SWEEP_EDGE_WIDTH: f32 = 3.0;
+/// Radius of the emblem badge at scale 1, in pixels
+const EMBLEM_RADIUS: f32 = 48.0;
+/// Opacity of the emblem badge's fill relative to its outline
+const EMBLEM_FILL_OPACITY: f32 = 0.35;
+/// Width of the emblem badge's outline, in pixels
+const EMBLEM_BORDER: f32 = 3.0;
+
+/// Selection feedback to draw, published by the interaction crate
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SelectionVisualState {
+    /// Corners of the box selection in progress, in pixels
+    pub rubber_band: Option<((f32, f32), (f32, f32))>,
+    /// Node under the pointer
+    pub hovered: Option<SceneId>,
+    /// Selected nodes
+    pub selected: Vec<SceneId>,
+    /// Selected node multi-selection operations act on
+    pub primary: Option<SceneId>,
+}
+
+impl SelectionVisualState {
+    /// Whether there is nothing to draw
+    pub fn is_empty(&self) -> bool {
+        self.rubber_band.is_none() && self.hovered.is_none() && self.selected.is_empty()
+    }
+}
+
+/// Frame of a workspace switch transition to draw
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwitchOverlay {
+    /// Accent of the workspace being entered
+    pub accent_color: [f32; 4],
+    /// Opacity of the emblem badge
+    pub emblem_opacity: f32,
+    /// Scale of the emblem badge
+    pub emblem_scale: f32,
+    /// Edge of the accent sweeping across the screen (0.0 to 1.0), for sweep transitions
+    pub sweep_position: Option<f32>,
+}
+
+/// Shape of an overlay instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayShape {
+    Rect,
+    Ring,
+}
+
+/// Per-instance data of an overlay shape
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct OverlayInstance {
+    /// Center in pixels
+    pub center: [f32; 2],
+    /// Half width and height in pixels; rings use the width as radius
+    pub half_size: [f32; 2],
+    pub color: [f32; 4],
+    /// Outline width in pixels, 0 filling the shape
+    pub border: f32,
+    /// 0 for a rectangle, 1 for a ring
+    pub shape: f32,
+}
+
+impl OverlayInstance {
+    pub fn new(shape: OverlayShape, center: [f32; 2], half_size: [f32; 2], color: [f32; 4], border: f32) -> Self {
+        Self {
+            center,
+            half_size,
+            color,
+            border,
+            shape: match shape {
+                OverlayShape::Rect => 0.0,
+                OverlayShape::Ring => 1.0,
+            },
+        }
+    }
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        use std::mem;
+
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<OverlayInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 9]>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32,
+                },
+            ],
+        }
+    }
+}
+
+/// Overlay shapes for a selection state, in drawing order
+///
+/// Outlines come first and the rubber band last, so it stays on top. Nodes
+/// that are hidden, behind the camera or gone from the scene get no outline.
+pub fn build_overlay(
+    state: &SelectionVisualState,
+    scene: &Scene,
+    camera: &Camera,
+    viewport: (f32, f32),
+    palette: &RenderPalette,
+) -> Vec<OverlayInstance> {
+    let mut instances = Vec::new();
+    let ring = |id: SceneId, gap: f32, width: f32, color: [f32; 4]| {
+        let node = scene.get_node(id).filter(|node| node.visible)?;
+        let (x, y) = camera.world_to_screen(node.position, viewport.0, viewport.1)?;
+        let radius = node.radius / camera.pixel_size_at(node.position, viewport.1) + gap + width;
+        Some(OverlayInstance::new(OverlayShape::Ring, [x, y], [radius, radius], color, width))
+    };
+
+    for &id in &state.selected {
+        let width = if state.primary == Some(id) { PRIMARY_OUTLINE_WIDTH } else { OUTLINE_WIDTH };
+        instances.extend(ring(id, OUTLINE_GAP, width, palette.selected));
+    }
+    if let Some(id) = state.hovered {
+        instances.extend(ring(id, HOVER_GAP, HOVER_WIDTH, palette.hovered));
+    }
+
+    if let Some(((x0, y0), (x1, y1))) = state.rubber_band {
+        let center = [(x0 + x1) * 0.5, (y0 + y1) * 0.5];
+        let half_size = [(x1 - x0).abs() * 0.5, (y1 - y0).abs() * 0.5];
+        instances.push(OverlayInstance::new(OverlayShape::Rect, center, half_size, palette.selection_fill, 0.0));
+        instances.push(OverlayInstance::new(OverlayShape::Rect, center, half_size, palette.selected, RUBBER_BAND_BORDER));
+    }
+    instances
+}
+
+/// Overlay shapes for the minimap in the bottom right corner of the screen
+///
+/// The background comes first, then cluster outlines, nodes, the camera
+/// view and the camera itself. The overlay only has rectangles and rings, so
+/// lines are drawn as rows of dots; shapes outside the minimap are dropped.
+pub fn build_minimap_overlay(minimap: &Minimap, viewport: (f32, f32), palette: &RenderPalette) -> Vec<OverlayInstance> {
+    let origin = minimap.screen_origin(viewport);
+    let config = minimap.config();
+    let half_size = [config.width * 0.5, config.height * 0.5];
+    let center = [origin[0] + half_size[0], origin[1] + half_size[1]];
+    let mut background = palette.background;
+    background[3] = MINIMAP_OPACITY;
+    let mut instances = vec![
+        OverlayInstance::new(OverlayShape::Rect, center, half_size, background, 0.0),
+        OverlayInstance::new(OverlayShape::Rect, center, half_size, palette.edge_default, RUBBER_BAND_BORDER),
+    ];
+
+    let to_screen = |point: [f32; 2]| [origin[0] + point[0], origin[1] + point[1]];
+    let dot = |point: [f32; 2], color: [f32; 4]| {
+        let half = RUBBER_BAND_BORDER * 0.5;
+        minimap.contains(point).then(|| OverlayInstance::new(OverlayShape::Rect, to_screen(point), [half, half], color, 0.0))
+    };
+    let outline = |corners: &[[f32; 2]], color: [f32; 4]| {
+        let mut dots = Vec::new();
+        for (i, &start) in corners.iter().enumerate() {
+            let end = corners[(i + 1) % corners.len()];
+            let length = ((end[0] - start[0]).powi(2) + (end[1] - start[1]).powi(2)).sqrt();
+            let steps = (length / MINIMAP_DOT_SPACING).ceil().max(1.0) as usize;
+            dots.extend((0..steps).filter_map(|step| {
+                let t = step as f32 / steps as f32;
+                dot([start[0] + (end[0] - start[0]) * t, start[1] + (end[1] - start[1]) * t], color)
+            }));
+        }
+        dots
+    };
+
+    for hull in minimap.hulls() {
+        instances.extend(outline(&hull.outline, hull.color));
+    }
+    let radius = config.node_radius;
+    instances.extend(minimap.nodes().iter()
+        .filter(|node| minimap.contains(node.position))
+        .map(|node| {
+            let color = if node.selected { palette.selected } else { node.color };
+            OverlayInstance::new(OverlayShape::Ring, to_screen(node.position), [radius, radius], color, 0.0)
+        }));
+    instances.extend(outline(minimap.view(), palette.hovered));
+    if minimap.contains(minimap.camera()) {
+        let radius = MINIMAP_CAMERA_RADIUS;
+        instances.push(OverlayInstance::new(OverlayShape::Ring, to_screen(minimap.camera()), [radius, radius], palette.hovered, RUBBER_BAND_BORDER));
+    }
+    instances
+}
+
+/// Overlay shapes for a frame of a workspace switch
+///
+/// The sweep tints the screen left of its edge and draws the edge itself,
+/// both fading as the sweep reaches the right side. The emblem is a badge
+/// in the accent color in the middle of the screen.
+pub fn build_switch_overlay(switch: &SwitchOverlay, viewport: (f32, f32)) -> Vec<OverlayInstance> {
+    let accent = |opacity: f32| {
+        let [r, g, b, a] = switch.accent_color;
+        [r, g, b, a * opacity.clamp(0.0, 1.0)]
+    };
+    let mut instances = Vec::new();
+
+    if let Some(position) = switch.sweep_position.map(|position| position.clamp(0.0, 1.0)) {
+        let edge = position * viewport.0;
+        let half_height = viewport.1 * 0.5;
+        let fade = 1.0 - position;
+        if edge > 0.0 && fade > 0.0 {
+            instances.push(OverlayInstance::new(OverlayShape::Rect, [edge * 0.5, half_height], [edge * 0.5, half_height], accent(SWEEP_FILL_OPACITY * fade), 0.0));
+            instances.push(OverlayInstance::new(OverlayShape::Rect, [edge, half_height], [SWEEP_EDGE_WIDTH * 0.5, half_height], accent(fade), 0.0));
+        }
+    }
+
+    if switch.emblem_opacity > 0.0 {
+        let center = [viewport.0 * 0.5, viewport.1 * 0.5];
+        let radius = EMBLEM_RADIUS * switch.emblem_scale;
+        instances.push(OverlayInstance::new(OverlayShape::Ring, center, [radius, radius], accent(EMBLEM_FILL_OPACITY * switch.emblem_opacity), 0.0));
+        instances.push(OverlayInstance::new(OverlayShape::Ring, center, [radius, radius], accent(switch.emblem_opacity), EMBLEM_BORDER));
+    }
+    instances
+}
+
+/// Viewport uniform of the overlay shader
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct OverlayUniform {
+    viewport: [f32; 2],
+    _padding: [f32; 2],
+}
+
+/// Render pipeline for the selection overlay
+pub struct OverlayPipeline {
+    render_pipeline: RenderPipeline,
+    instance_buffer: InstanceBuffer,
+    uniform_buffer: Buffer,
+    uniform_bind_group: BindGroup,
+    /// Instances uploaded for the next draw
+    instance_count: u32,
+}
+
+impl OverlayPipeline {
+    pub fn new(device: &Device, surface_format: wgpu::TextureFormat, sources: &shaders::ShaderSources) -> Self {
+        let instance_buffer = InstanceBuffer::new(
+            device,
+            "Overlay Instance Buffer",
+            (std::mem::size_of::<OverlayInstance>() * 64) as u64,
+        );
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Overlay Uniform Buffer"),
+            size: std::mem::size_of::<OverlayUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let uniform_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("Overlay Bind Group Layout"),
+        });
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &uniform_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("Overlay Bind Group"),
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Overlay Render Pipeline Layout"),
+            bind_group_layouts: &[&uniform_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let shader = shaders::create_shader_module(device, &sources.overlay, "Overlay Shader");
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Overlay Render Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[OverlayInstance::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            // Drawn over the finished scene, so nothing hides it
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            render_pipeline,
+            instance_buffer,
+            uniform_buffer,
+            uniform_bind_group,
+            instance_count: 0,
+        }
+    }
+
+    /// Upload the viewport and shapes for the next draw; returns whether there is anything to draw
+    pub fn prepare(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        uploads: &mut UploadScheduler,
+        viewport: (f32, f32),
+        instances: &[OverlayInstance],
+    ) -> bool {
+        self.instance_count = instances.len() as u32;
+        if instances.is_empty() {
+            return false;
+        }
+
+        let uniform = OverlayUniform { viewport: [viewport.0, viewport.1], _padding: [0.0; 2] };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+        self.instance_buffer.write(device, uploads, bytemuck::cast_slice(instances));
+        true
+    }
+
+    /// Draw the prepared shapes with a single instanced draw
+    pub fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
+        if self.instance_count == 0 {
+            return;
+        }
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.instance_buffer.buffer().slice(..));
+        render_pass.draw(0..6, 0..self.instance_count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NodeMetadata, NodeType, Position, SceneNode};
+    use nalgebra::{Point3, Vector3};
+
+    fn node(scene: &mut Scene, x: f32) -> SceneId {
+        scene.add_node(SceneNode {
+            id: 0,
+            position: Position::new(x, 0.0, 0.0),
+            velocity: Vector3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type: NodeType::Concept { title: String::new(), content: String::new() },
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        })
+    }
+
+    #[test]
+    fn test_overlay_outlines_selection_and_draws_rubber_band_last() {
+        let mut scene = Scene::new();
+        let a = node(&mut scene, 0.0);
+        let b = node(&mut scene, 3.0);
+        let mut camera = Camera::new();
+        camera.move_to(Point3::new(0.0, 0.0, 20.0));
+        camera.look_at(Point3::origin());
+        camera.set_aspect_ratio(800.0 / 600.0);
+        let palette = RenderPalette::default();
+
+        let state = SelectionVisualState {
+            rubber_band: Some(((500.0, 400.0), (100.0, 200.0))),
+            hovered: Some(b),
+            selected: vec![a, b, 999],
+            primary: Some(a),
+        };
+        let overlay = build_overlay(&state, &scene, &camera, (800.0, 600.0), &palette);
+        assert_eq!(overlay.len(), 5);
+
+        // The primary selection sits in the middle of the screen with a thicker outline
+        let primary = overlay[0];
+        assert!((primary.center[0] - 400.0).abs() < 1.0 && (primary.center[1] - 300.0).abs() < 1.0);
+        assert_eq!(primary.border, PRIMARY_OUTLINE_WIDTH);
+        assert_eq!(overlay[1].border, OUTLINE_WIDTH);
+        assert!(overlay[1].center[0] > primary.center[0]);
+        assert_eq!(overlay[2].color, palette.hovered);
+        assert!(overlay[2].half_size[0] > overlay[1].half_size[0]);
+
+        let band = overlay[4];
+        assert_eq!(band.center, [300.0, 300.0]);
+        assert_eq!(band.half_size, [200.0, 100.0]);
+        assert_eq!(band.shape, 0.0);
+        assert!(SelectionVisualState::default().is_empty());
+    }
+
+    #[test]
+    fn test_minimap_overlay_sits_in_the_corner_and_clips() {
+        let mut scene = Scene::new();
+        let a = node(&mut scene, -10.0);
+        node(&mut scene, 10.0);
+        scene.get_node_mut(a).unwrap().selected = true;
+        let config = crate::MinimapConfig { width: 200.0, height: 100.0, margin: 20.0, ..Default::default() };
+        let minimap = Minimap::build(&scene, &Camera::new(), &[], &config);
+        let palette = RenderPalette::default();
+
+        let overlay = build_minimap_overlay(&minimap, (800.0, 600.0), &palette);
+        assert_eq!(overlay[0].center, [680.0, 530.0]);
+        assert_eq!(overlay[0].half_size, [100.0, 50.0]);
+        assert_eq!(overlay[0].color[3], MINIMAP_OPACITY);
+
+        let nodes: Vec<_> = overlay.iter().filter(|shape| shape.shape == 1.0 && shape.border == 0.0).collect();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].color, palette.selected);
+        // Everything stays on the minimap, even the parts of the view cut off by it
+        assert!(overlay.iter().all(|shape| {
+            (580.0..=780.0).contains(&shape.center[0]) && (480.0..=580.0).contains(&shape.center[1])
+        }));
+    }
+
+    #[test]
+    fn test_switch_overlay_draws_sweep_and_emblem() {
+        let accent = [0.2, 0.4, 0.8, 1.0];
+        let sweep = SwitchOverlay { accent_color: accent, emblem_opacity: 0.0, emblem_scale: 1.0, sweep_position: Some(0.5) };
+
+        let overlay = build_switch_overlay(&sweep, (800.0, 600.0));
+        assert_eq!(overlay.len(), 2);
+        assert_eq!(overlay[0].center, [200.0, 300.0]);
+        assert_eq!(overlay[0].half_size, [200.0, 300.0]);
+        assert_eq!(overlay[0].color[3], SWEEP_FILL_OPACITY * 0.5);
+        assert_eq!(overlay[1].center, [400.0, 300.0]);
+        assert_eq!(overlay[1].color[3], 0.5);
+        // A finished sweep leaves nothing behind
+        assert!(build_switch_overlay(&SwitchOverlay { sweep_position: Some(1.0), ..sweep }, (800.0, 600.0)).is_empty());
+
+        let pulse = SwitchOverlay { accent_color: accent, emblem_opacity: 0.8, emblem_scale: 1.2, sweep_position: None };
+        let overlay = build_switch_overlay(&pulse, (800.0, 600.0));
+        assert_eq!(overlay.len(), 2);
+        assert!(overlay.iter().all(|shape| shape.center == [400.0, 300.0] && shape.shape == 1.0));
+        assert!((overlay[1].half_size[0] - EMBLEM_RADIUS * 1.2).abs() < 1e-4);
+        assert_eq!(overlay[0].border, 0.0);
+        assert_eq!(overlay[1].border, EMBLEM_BORDER);
+        assert!((overlay[1].color[3] - 0.8).abs() < 1e-6);
+        assert!(build_switch_overlay(&SwitchOverlay { emblem_opacity: 0.0, ..pulse }, (800.0, 600.0)).is_empty());
+    }
+
+    #[test]
+    fn test_overlay_shader_is_valid() {
+        super::super::hot_reload::validate_wgsl(shaders::OVERLAY_SHADER, &["vs_main", "fs_main"]).unwrap();
+    }
+}
@@ -0,0 +1,156 @@
+//! Colors the renderer draws with
+//!
+//! The palette is derived from the active theme and swapped at runtime when
+//! the theme changes. Node and edge colors live on the scene objects, so a
+//! palette change recolors the scene; objects given a custom color keep it.
+
+use crate::{EdgeType, NodeType, Scene};
+use std::collections::HashMap;
+
+/// Background, node and edge colors of the active theme
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderPalette {
+    /// Clear color of the frame
+    pub background: [f32; 4],
+    /// Color of node kinds without an entry in `nodes`
+    pub node_default: [f32; 4],
+    /// Node colors by kind, see [`node_kind`]
+    pub nodes: HashMap<String, [f32; 4]>,
+    /// Color of edge kinds without an entry in `edges`
+    pub edge_default: [f32; 4],
+    /// Edge colors by kind, see [`edge_kind`]
+    pub edges: HashMap<String, [f32; 4]>,
+    /// Outline of selected nodes and the box selection border
+    pub selected: [f32; 4],
+    /// Outline of the node under the pointer
+    pub hovered: [f32; 4],
+    /// Fill of the box selection
+    pub selection_fill: [f32; 4],
+}
+
+impl Default for RenderPalette {
+    fn default() -> Self {
+        Self {
+            background: [0.02, 0.02, 0.05, 1.0],
+            node_default: [0.6, 0.6, 0.7, 1.0],
+            nodes: HashMap::new(),
+            edge_default: [0.5, 0.5, 0.6, 0.8],
+            edges: HashMap::new(),
+            selected: [0.29, 0.56, 0.89, 1.0],
+            hovered: [1.0, 1.0, 1.0, 0.6],
+            selection_fill: [0.29, 0.56, 0.89, 0.2],
+        }
+    }
+}
+
+impl RenderPalette {
+    /// Color of a node of this type
+    pub fn node_color(&self, node_type: &NodeType) -> [f32; 4] {
+        self.nodes.get(node_kind(node_type)).copied().unwrap_or(self.node_default)
+    }
+
+    /// Color of an edge of this type
+    pub fn edge_color(&self, edge_type: &EdgeType) -> [f32; 4] {
+        self.edges.get(edge_kind(edge_type)).copied().unwrap_or(self.edge_default)
+    }
+
+    /// Frame clear color
+    pub fn clear_color(&self) -> wgpu::Color {
+        let [r, g, b, a] = self.background.map(f64::from);
+        wgpu::Color { r, g, b, a }
+    }
+
+    /// Recolor nodes and edges that still show their color from `previous`, returning how many changed
+    pub fn recolor(&self, previous: &RenderPalette, scene: &mut Scene) -> usize {
+        let mut changed = 0;
+        for id in scene.get_all_nodes() {
+            if let Some(node) = scene.get_node_mut(id) {
+                let color = self.node_color(&node.node_type);
+                if node.color == previous.node_color(&node.node_type) && node.color != color {
+                    node.color = color;
+                    changed += 1;
+                }
+            }
+        }
+        let edge_ids: Vec<_> = scene.edges().map(|edge| edge.id).collect();
+        for id in edge_ids {
+            if let Some(edge) = scene.get_edge_mut(id) {
+                let color = self.edge_color(&edge.edge_type);
+                if edge.color == previous.edge_color(&edge.edge_type) && edge.color != color {
+                    edge.color = color;
+                    changed += 1;
+                }
+            }
+        }
+        changed
+    }
+}
+
+/// Palette key of a node type
+pub fn node_kind(node_type: &NodeType) -> &'static str {
+    match node_type {
+        NodeType::Application { .. } => "application",
+        NodeType::File { .. } => "file",
+        NodeType::Person { .. } => "person",
+        NodeType::Task { .. } => "task",
+        NodeType::Device { .. } => "device",
+        NodeType::AIAgent { .. } => "ai_agent",
+        NodeType::Concept { .. } => "concept",
+        NodeType::System { .. } => "system",
+        NodeType::URL { .. } => "url",
+        NodeType::Automation { .. } => "automation",
+        NodeType::Setting { .. } => "setting",
+        NodeType::ConfigGroup { .. } => "config_group",
+        NodeType::Group { .. } => "group",
+    }
+}
+
+/// Palette key of an edge type
+pub fn edge_kind(edge_type: &EdgeType) -> &'static str {
+    match edge_type {
+        EdgeType::Contains => "contains",
+        EdgeType::DependsOn => "depends_on",
+        EdgeType::CommunicatesWith => "communicates_with",
+        EdgeType::CreatedBy => "created_by",
+        EdgeType::RelatedTo { .. } => "related_to",
+        EdgeType::Temporal { .. } => "temporal",
+        EdgeType::TaggedAs { .. } => "tagged_as",
+        EdgeType::WorksOn => "works_on",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NodeMetadata, SceneNode};
+    use nalgebra::{Point3, Vector3};
+
+    fn concept(color: [f32; 4]) -> SceneNode {
+        SceneNode {
+            id: 0,
+            position: Point3::origin(),
+            velocity: Vector3::zeros(),
+            radius: 1.0,
+            color,
+            node_type: NodeType::Concept { title: String::new(), content: String::new() },
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        }
+    }
+
+    #[test]
+    fn test_recolor_keeps_custom_colors() {
+        let old = RenderPalette::default();
+        let mut new = RenderPalette::default();
+        new.nodes.insert("concept".to_string(), [1.0, 0.0, 0.0, 1.0]);
+
+        let mut scene = Scene::new();
+        let themed = scene.add_node(concept(old.node_default));
+        let custom = scene.add_node(concept([0.0, 0.0, 1.0, 1.0]));
+
+        assert_eq!(new.recolor(&old, &mut scene), 1);
+        assert_eq!(scene.get_node(themed).unwrap().color, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(scene.get_node(custom).unwrap().color, [0.0, 0.0, 1.0, 1.0]);
+    }
+}
@@ -0,0 +1,402 @@
+//! GPU picking of nodes under the pointer
+//!
+//! Ray casting tests every node on the CPU, which gets slow with tens of
+//! thousands of nodes. Instead the nodes are drawn with their IDs as colors
+//! into a one-pixel target whose projection is narrowed to the pixel under
+//! the pointer, and that pixel is read back asynchronously. The depth test
+//! makes the front-most node win, as it does on screen.
+
+use crate::{Camera, GraphEngineError, Scene, SceneId};
+use super::primitives::{generate_sphere, SphereVertex};
+use super::shaders;
+use futures::channel::oneshot;
+use nalgebra::Matrix4;
+use std::sync::Arc;
+use wgpu::util::DeviceExt;
+use wgpu::{Buffer, Device, Queue};
+
+/// Format of the ID target: the low and high halves of the node ID plus one, zero for nothing
+const ID_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg32Uint;
+
+/// Readback rows must be padded to this many bytes
+const READBACK_SIZE: u64 = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64;
+
+/// Per-node data of the ID pass
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PickInstance {
+    position: [f32; 3],
+    radius: f32,
+    id: [u32; 2],
+    _padding: [u32; 2],
+}
+
+impl PickInstance {
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        use std::mem;
+
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<PickInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Uint32x2,
+                },
+            ],
+        }
+    }
+}
+
+/// Encode a node ID for the ID target, keeping zero for "no node"
+fn encode_id(id: SceneId) -> [u32; 2] {
+    let value = id.wrapping_add(1);
+    [value as u32, (value >> 32) as u32]
+}
+
+/// Node ID of an ID target texel
+fn decode_id(texel: [u32; 2]) -> Option<SceneId> {
+    let value = texel[0] as u64 | ((texel[1] as u64) << 32);
+    value.checked_sub(1)
+}
+
+/// Projection narrowing clip space to one pixel of a `width` by `height` screen
+///
+/// Applied after the camera's view-projection, it maps the pixel at
+/// `screen_pos` onto the whole one-pixel target.
+pub fn pick_matrix(screen_pos: (f32, f32), (width, height): (u32, u32)) -> Matrix4<f32> {
+    let (width, height) = (width.max(1) as f32, height.max(1) as f32);
+    let x = screen_pos.0.floor() + 0.5;
+    let y = screen_pos.1.floor() + 0.5;
+    let center_x = 2.0 * x / width - 1.0;
+    let center_y = 1.0 - 2.0 * y / height;
+    Matrix4::new(
+        width, 0.0, 0.0, -center_x * width,
+        0.0, height, 0.0, -center_y * height,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0,
+    )
+}
+
+/// Draws node IDs and reads back the one under a screen position
+pub struct GpuPicker {
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    pipeline: wgpu::RenderPipeline,
+    vertex_buffer: Buffer,
+    index_buffer: Buffer,
+    index_count: u32,
+    uniform_buffer: Buffer,
+    bind_group: wgpu::BindGroup,
+    id_texture: wgpu::Texture,
+    id_view: wgpu::TextureView,
+    depth_view: wgpu::TextureView,
+}
+
+impl GpuPicker {
+    /// Build the ID pass; fails where the device cannot render integer targets
+    pub fn new(device: Arc<Device>, queue: Arc<Queue>, sources: &shaders::ShaderSources) -> Result<Self, GraphEngineError> {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        // Coarser than the visible spheres; a pick only needs the silhouette
+        let (vertices, indices) = generate_sphere(8);
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Pick Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Pick Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Pick Uniform Buffer"),
+            size: std::mem::size_of::<[[f32; 4]; 4]>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("Pick Bind Group Layout"),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("Pick Bind Group"),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Pick Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = shaders::create_shader_module(&device, &sources.pick, "Pick Shader");
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Pick Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[SphereVertex::desc(), PickInstance::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: ID_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let target = |label, format, usage| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage,
+                view_formats: &[],
+            })
+        };
+        let id_texture = target("Pick ID Target", ID_FORMAT, wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC);
+        let id_view = id_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_texture = target("Pick Depth Target", wgpu::TextureFormat::Depth32Float, wgpu::TextureUsages::RENDER_ATTACHMENT);
+        let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+            return Err(GraphEngineError::RenderError(format!("GPU picking unavailable: {}", error)));
+        }
+
+        Ok(Self {
+            device,
+            queue,
+            pipeline,
+            vertex_buffer,
+            index_buffer,
+            index_count: indices.len() as u32,
+            uniform_buffer,
+            bind_group,
+            id_texture,
+            id_view,
+            depth_view,
+        })
+    }
+
+    /// Draw the ID pass for a screen position and start reading it back
+    pub fn request(&self, scene: &Scene, camera: &Camera, screen_pos: (f32, f32), screen_size: (u32, u32)) -> PickRequest {
+        let view_proj = pick_matrix(screen_pos, screen_size) * camera.view_projection_matrix();
+        let view_proj: [[f32; 4]; 4] = view_proj.into();
+        self.queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[view_proj]));
+
+        let instances: Vec<PickInstance> = scene.nodes()
+            .filter(|(_, node)| node.visible)
+            .map(|(id, node)| PickInstance {
+                position: [node.position.x, node.position.y, node.position.z],
+                radius: node.radius,
+                id: encode_id(*id),
+                _padding: [0; 2],
+            })
+            .collect();
+        let instance_buffer = (!instances.is_empty()).then(|| {
+            self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Pick Instance Buffer"),
+                contents: bytemuck::cast_slice(&instances),
+                usage: wgpu::BufferUsages::VERTEX,
+            })
+        });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Pick Readback Buffer"),
+            size: READBACK_SIZE,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Pick Encoder"),
+        });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Pick Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.id_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            if let Some(instance_buffer) = &instance_buffer {
+                pass.set_pipeline(&self.pipeline);
+                pass.set_bind_group(0, &self.bind_group, &[]);
+                pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+                pass.set_vertex_buffer(1, instance_buffer.slice(..));
+                pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                pass.draw_indexed(0..self.index_count, 0, 0..instances.len() as u32);
+            }
+        }
+        encoder.copy_texture_to_buffer(
+            self.id_texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(READBACK_SIZE as u32),
+                    rows_per_image: Some(1),
+                },
+            },
+            wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 },
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let (sender, receiver) = oneshot::channel();
+        readback.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+
+        PickRequest {
+            screen_pos,
+            device: self.device.clone(),
+            readback,
+            receiver,
+        }
+    }
+}
+
+/// Outcome of a pick, once the GPU has answered
+pub type PickResult = Result<Option<SceneId>, GraphEngineError>;
+
+/// A pick in flight
+///
+/// The readback completes when the device is next polled, which every
+/// rendered frame does; [`PickRequest::poll`] polls it without blocking.
+pub struct PickRequest {
+    screen_pos: (f32, f32),
+    device: Arc<Device>,
+    readback: Buffer,
+    receiver: oneshot::Receiver<Result<(), wgpu::BufferAsyncError>>,
+}
+
+impl PickRequest {
+    /// Screen position the pick is for
+    pub fn screen_pos(&self) -> (f32, f32) {
+        self.screen_pos
+    }
+
+    /// The picked node if the readback has finished, without waiting for it
+    pub fn poll(&mut self) -> Option<PickResult> {
+        self.device.poll(wgpu::Maintain::Poll);
+        match self.receiver.try_recv() {
+            Ok(Some(mapped)) => Some(self.read(Ok(mapped))),
+            Ok(None) => None,
+            Err(canceled) => Some(self.read(Err(canceled))),
+        }
+    }
+
+    /// Wait for the picked node
+    pub async fn result(mut self) -> PickResult {
+        let mapped = (&mut self.receiver).await;
+        self.read(mapped)
+    }
+
+    fn read(&self, mapped: Result<Result<(), wgpu::BufferAsyncError>, oneshot::Canceled>) -> PickResult {
+        match mapped {
+            Ok(Ok(())) => {
+                let texel = {
+                    let data = self.readback.slice(..).get_mapped_range();
+                    let words: &[u32] = bytemuck::cast_slice(&data[..8]);
+                    [words[0], words[1]]
+                };
+                self.readback.unmap();
+                Ok(decode_id(texel))
+            }
+            Ok(Err(e)) => Err(GraphEngineError::RenderError(format!("Pick readback failed: {}", e))),
+            Err(_) => Err(GraphEngineError::DeviceLost("Pick readback was dropped".into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector4;
+
+    #[test]
+    fn test_pick_matrix_centers_pixel() {
+        let size = (800, 600);
+        // Clip position of the center of pixel (200, 150)
+        let clip = Vector4::new(2.0 * 200.5 / 800.0 - 1.0, 1.0 - 2.0 * 150.5 / 600.0, 0.5, 1.0);
+        let picked = pick_matrix((200.3, 150.9), size) * clip;
+        assert!(picked.x.abs() < 1e-3 && picked.y.abs() < 1e-3);
+
+        // The neighbouring pixel lands outside the one-pixel target
+        let neighbour = Vector4::new(clip.x + 2.0 / 800.0, clip.y, 0.5, 1.0);
+        assert!((pick_matrix((200.3, 150.9), size) * neighbour).x > 1.0);
+    }
+
+    #[test]
+    fn test_id_encoding_round_trip() {
+        assert_eq!(decode_id([0, 0]), None);
+        for id in [0, 41, u32::MAX as u64, 1 << 40] {
+            assert_eq!(decode_id(encode_id(id)), Some(id));
+        }
+    }
+}
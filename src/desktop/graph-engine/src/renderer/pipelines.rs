@@ -1,7 +1,12 @@
 //! Render pipelines for nodes and edges
 
-use crate::{Scene, Camera, GraphEngineError};
+use crate::{Scene, Camera, GraphEngineError, EdgeRouter, EdgeFilter};
+use super::visibility::{DrawStats, VisibleNodes};
 use super::primitives::{SphereVertex, NodeInstance, EdgeVertex, generate_sphere};
+use super::instancing::{IconInstance, InstanceBuffer, NodeBatches};
+use super::upload::{UploadPriority, UploadScheduler};
+use std::collections::HashMap;
+use std::sync::Arc;
 use super::shaders;
 use nalgebra::Matrix4;
 use wgpu::{Device, RenderPass, Buffer, BindGroup, RenderPipeline};
@@ -33,21 +38,20 @@ impl CameraUniform {
 /// Render pipeline for drawing nodes
 pub struct NodePipeline {
     render_pipeline: RenderPipeline,
+    pipeline_layout: wgpu::PipelineLayout,
+    surface_format: wgpu::TextureFormat,
     vertex_buffer: Buffer,
     index_buffer: Buffer,
-    instance_buffer: Buffer,
+    instance_buffer: InstanceBuffer,
     camera_buffer: Buffer,
     camera_bind_group: BindGroup,
     index_count: u32,
-    max_instances: usize,
+    /// Instances uploaded for the next draw
+    instance_count: u32,
 }
 
 impl NodePipeline {
-    pub async fn new(device: &Device, surface_format: wgpu::TextureFormat) -> Result<Self, GraphEngineError> {
-        // Create shaders
-        let vertex_shader = shaders::create_shader_module(device, shaders::NODE_VERTEX_SHADER, "Node Vertex Shader");
-        let fragment_shader = shaders::create_shader_module(device, shaders::NODE_FRAGMENT_SHADER, "Node Fragment Shader");
-        
+    pub async fn new(device: &Device, surface_format: wgpu::TextureFormat, sources: &shaders::ShaderSources) -> Result<Self, GraphEngineError> {
         // Generate sphere geometry
         let (vertices, indices) = generate_sphere(16); // Medium quality sphere
         let index_count = indices.len() as u32;
@@ -66,14 +70,12 @@ impl NodePipeline {
             usage: wgpu::BufferUsages::INDEX,
         });
         
-        // Create instance buffer (max 10000 instances)
-        let max_instances = 10000;
-        let instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Node Instance Buffer"),
-            size: (std::mem::size_of::<NodeInstance>() * max_instances) as u64,
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+        // Create instance buffer, grown when the sphere batch outgrows it
+        let instance_buffer = InstanceBuffer::new(
+            device,
+            "Node Instance Buffer",
+            (std::mem::size_of::<NodeInstance>() * 1024) as u64,
+        );
         
         // Create camera uniform buffer
         let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
@@ -120,9 +122,84 @@ impl NodePipeline {
         });
         
         // Create render pipeline
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        let render_pipeline = Self::create_render_pipeline(
+            device,
+            &render_pipeline_layout,
+            surface_format,
+            &sources.node_vertex,
+            &sources.node_fragment,
+        );
+        
+        Ok(NodePipeline {
+            render_pipeline,
+            pipeline_layout: render_pipeline_layout,
+            surface_format,
+            vertex_buffer,
+            index_buffer,
+            instance_buffer,
+            camera_buffer,
+            camera_bind_group,
+            index_count,
+            instance_count: 0,
+        })
+    }
+    
+    /// Upload the camera and queue the sphere batch for the next draw
+    pub fn prepare(&mut self, device: &Device, queue: &wgpu::Queue, uploads: &mut UploadScheduler, camera: &Camera, instances: &[NodeInstance]) {
+        let mut camera_uniform = CameraUniform::new();
+        camera_uniform.update_view_proj(camera);
+        queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[camera_uniform]));
+        
+        self.instance_buffer.write(device, uploads, bytemuck::cast_slice(instances));
+        self.instance_count = instances.len() as u32;
+    }
+    
+    /// Draw all prepared spheres with a single instanced draw
+    pub fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
+        if self.instance_count == 0 {
+            return;
+        }
+        
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instance_buffer.buffer().slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..self.index_count, 0, 0..self.instance_count);
+    }
+    
+    /// Swap in new shader sources, keeping the current pipeline if creation fails
+    pub fn reload_shaders(&mut self, device: &Device, vertex_source: &str, fragment_source: &str) -> Result<(), GraphEngineError> {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let render_pipeline = Self::create_render_pipeline(
+            device,
+            &self.pipeline_layout,
+            self.surface_format,
+            vertex_source,
+            fragment_source,
+        );
+        if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+            return Err(GraphEngineError::ShaderError(error.to_string()));
+        }
+        
+        self.render_pipeline = render_pipeline;
+        Ok(())
+    }
+    
+    /// Create the node render pipeline from shader sources
+    fn create_render_pipeline(
+        device: &Device,
+        layout: &wgpu::PipelineLayout,
+        surface_format: wgpu::TextureFormat,
+        vertex_source: &str,
+        fragment_source: &str,
+    ) -> RenderPipeline {
+        let vertex_shader = shaders::create_shader_module(device, vertex_source, "Node Vertex Shader");
+        let fragment_shader = shaders::create_shader_module(device, fragment_source, "Node Fragment Shader");
+        
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Node Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
+            layout: Some(layout),
             vertex: wgpu::VertexState {
                 module: &vertex_shader,
                 entry_point: "vs_main",
@@ -159,34 +236,360 @@ impl NodePipeline {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
+        })
+    }
+}
+
+/// Corner of the unit quad icon nodes are drawn on
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct QuadVertex {
+    corner: [f32; 2],
+}
+
+impl QuadVertex {
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<QuadVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+            ],
+        }
+    }
+}
+
+/// Icon atlas page, with the pixels kept to rebuild it on a new device
+struct AtlasPage {
+    width: u32,
+    height: u32,
+    rgba: Arc<[u8]>,
+    bind_group: BindGroup,
+}
+
+/// Render pipeline for drawing icon nodes, one instanced draw per atlas page
+pub struct IconPipeline {
+    render_pipeline: RenderPipeline,
+    vertex_buffer: Buffer,
+    index_buffer: Buffer,
+    instance_buffer: InstanceBuffer,
+    camera_buffer: Buffer,
+    camera_bind_group: BindGroup,
+    atlas_bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    pages: HashMap<u32, AtlasPage>,
+    /// Instance ranges of the next frame by atlas page
+    draws: Vec<(u32, std::ops::Range<u32>)>,
+    /// Video frames of the next frame, with the instance showing each
+    video_draws: Vec<(BindGroup, u32)>,
+}
+
+impl IconPipeline {
+    pub fn new(device: &Device, surface_format: wgpu::TextureFormat, sources: &shaders::ShaderSources) -> Self {
+        let corners = [
+            QuadVertex { corner: [-1.0, -1.0] },
+            QuadVertex { corner: [1.0, -1.0] },
+            QuadVertex { corner: [1.0, 1.0] },
+            QuadVertex { corner: [-1.0, 1.0] },
+        ];
+        let indices: [u16; 6] = [0, 1, 2, 0, 2, 3];
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Icon Vertex Buffer"),
+            contents: bytemuck::cast_slice(&corners),
+            usage: wgpu::BufferUsages::VERTEX,
         });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Icon Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let instance_buffer = InstanceBuffer::new(
+            device,
+            "Icon Instance Buffer",
+            (std::mem::size_of::<IconInstance>() * 256) as u64,
+        );
         
-        Ok(NodePipeline {
+        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Camera Buffer"),
+            size: std::mem::size_of::<CameraUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let camera_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("Camera Bind Group Layout"),
+        });
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("Camera Bind Group"),
+        });
+        
+        // Each atlas page is bound as its own texture
+        let atlas_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("Icon Atlas Bind Group Layout"),
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Icon Atlas Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Icon Render Pipeline Layout"),
+            bind_group_layouts: &[&camera_bind_group_layout, &atlas_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let shader = shaders::create_shader_module(device, &sources.icon, "Icon Shader");
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Icon Render Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[QuadVertex::desc(), IconInstance::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+        
+        IconPipeline {
             render_pipeline,
             vertex_buffer,
             index_buffer,
             instance_buffer,
             camera_buffer,
             camera_bind_group,
-            index_count,
-            max_instances,
+            atlas_bind_group_layout,
+            sampler,
+            pages: HashMap::new(),
+            draws: Vec::new(),
+            video_draws: Vec::new(),
+        }
+    }
+    
+    /// Queue an icon atlas page of RGBA8 pixels for upload, replacing any page with the same index
+    ///
+    /// Thumbnails share the atlas with icons; a page is drawn blank until
+    /// the scheduler has uploaded it.
+    pub fn set_atlas_page(
+        &mut self,
+        device: &Device,
+        uploads: &mut UploadScheduler,
+        page: u32,
+        width: u32,
+        height: u32,
+        rgba: impl Into<Arc<[u8]>>,
+    ) -> Result<(), GraphEngineError> {
+        let rgba = rgba.into();
+        if rgba.len() as u64 != width as u64 * height as u64 * 4 {
+            return Err(GraphEngineError::RenderError(format!(
+                "Icon atlas page {} holds {} bytes, expected {}x{} RGBA pixels", page, rgba.len(), width, height,
+            )));
+        }
+        
+        let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+        let texture = Arc::new(device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Icon Atlas Page"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        }));
+        uploads.upload_texture(format!("icon-atlas-{}", page), UploadPriority::Visible, texture.clone(), [0, 0], [width, height], 0, rgba.clone())?;
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = self.texture_bind_group(device, &view, "Icon Atlas Bind Group");
+        
+        self.pages.insert(page, AtlasPage { width, height, rgba, bind_group });
+        Ok(())
+    }
+    
+    /// Bind a texture for sampling in place of an atlas page
+    fn texture_bind_group(&self, device: &Device, view: &wgpu::TextureView, label: &str) -> BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.atlas_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+            label: Some(label),
         })
     }
     
-    pub fn render<'a>(
-        &'a self,
-        render_pass: &mut RenderPass<'a>,
+    /// Drop an atlas page; icons on it are skipped until it is uploaded again
+    pub fn remove_atlas_page(&mut self, page: u32) {
+        self.pages.remove(&page);
+    }
+    
+    /// Whether an atlas page is uploaded
+    pub fn has_atlas_page(&self, page: u32) -> bool {
+        self.pages.contains_key(&page)
+    }
+    
+    /// Upload the pages of another pipeline, e.g. one built on a lost device
+    pub fn restore_atlas_pages(&mut self, device: &Device, uploads: &mut UploadScheduler, from: &IconPipeline) -> Result<(), GraphEngineError> {
+        for (&page, atlas) in &from.pages {
+            self.set_atlas_page(device, uploads, page, atlas.width, atlas.height, atlas.rgba.clone())?;
+        }
+        Ok(())
+    }
+    
+    /// Upload the camera and queue the icon batches of uploaded pages and
+    /// the video frames shown on nodes; returns the instances that will be drawn
+    ///
+    /// Each video frame is its own draw, bound to the frame's texture.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prepare(
+        &mut self,
+        device: &Device,
         queue: &wgpu::Queue,
-        scene: &Scene,
+        uploads: &mut UploadScheduler,
         camera: &Camera,
-    ) -> Result<(), GraphEngineError> {
-        self.render_fixed(render_pass, queue, scene, camera)
+        batches: &NodeBatches,
+        videos: &[(IconInstance, &wgpu::TextureView)],
+    ) -> u32 {
+        self.draws.clear();
+        self.video_draws.clear();
+        let mut instances = Vec::new();
+        for (&page, batch) in &batches.icon_pages {
+            if batch.is_empty() {
+                continue;
+            }
+            if !self.pages.contains_key(&page) {
+                log::debug!("Skipping {} icons on missing atlas page {}", batch.len(), page);
+                continue;
+            }
+            let start = instances.len() as u32;
+            instances.extend_from_slice(batch);
+            self.draws.push((page, start..instances.len() as u32));
+        }
+        for (instance, view) in videos {
+            let index = instances.len() as u32;
+            instances.push(*instance);
+            self.video_draws.push((self.texture_bind_group(device, view, "Video Frame Bind Group"), index));
+        }
+        if instances.is_empty() {
+            return 0;
+        }
+        
+        let mut camera_uniform = CameraUniform::new();
+        camera_uniform.update_view_proj(camera);
+        queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[camera_uniform]));
+        self.instance_buffer.write(device, uploads, bytemuck::cast_slice(&instances));
+        instances.len() as u32
+    }
+    
+    /// Draws the next frame issues, one per atlas page and video frame
+    pub fn draw_calls(&self) -> u32 {
+        (self.draws.len() + self.video_draws.len()) as u32
+    }
+    
+    /// Draw the prepared icons, one instanced draw per atlas page, then the video frames
+    pub fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
+        if self.draws.is_empty() && self.video_draws.is_empty() {
+            return;
+        }
+        
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instance_buffer.buffer().slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        for (page, range) in &self.draws {
+            render_pass.set_bind_group(1, &self.pages[page].bind_group, &[]);
+            render_pass.draw_indexed(0..6, 0, range.clone());
+        }
+        for (bind_group, index) in &self.video_draws {
+            render_pass.set_bind_group(1, bind_group, &[]);
+            render_pass.draw_indexed(0..6, 0, *index..*index + 1);
+        }
     }
 }
 
 /// Render pipeline for drawing edges
 pub struct EdgePipeline {
     render_pipeline: RenderPipeline,
+    pipeline_layout: wgpu::PipelineLayout,
+    surface_format: wgpu::TextureFormat,
     vertex_buffer: Buffer,
     camera_buffer: Buffer,
     camera_bind_group: BindGroup,
@@ -194,13 +597,9 @@ pub struct EdgePipeline {
 }
 
 impl EdgePipeline {
-    pub async fn new(device: &Device, surface_format: wgpu::TextureFormat) -> Result<Self, GraphEngineError> {
-        // Create shaders
-        let vertex_shader = shaders::create_shader_module(device, shaders::EDGE_VERTEX_SHADER, "Edge Vertex Shader");
-        let fragment_shader = shaders::create_shader_module(device, shaders::EDGE_FRAGMENT_SHADER, "Edge Fragment Shader");
-        
-        // Create vertex buffer (max 20000 vertices for 10000 edges)
-        let max_vertices = 20000;
+    pub async fn new(device: &Device, surface_format: wgpu::TextureFormat, sources: &shaders::ShaderSources) -> Result<Self, GraphEngineError> {
+        // Create vertex buffer (room for 10000 straight edges, fewer when routed edges bend)
+        let max_vertices = 100_000;
         let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Edge Vertex Buffer"),
             size: (std::mem::size_of::<EdgeVertex>() * max_vertices) as u64,
@@ -253,9 +652,73 @@ impl EdgePipeline {
         });
         
         // Create render pipeline
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        let render_pipeline = Self::create_render_pipeline(
+            device,
+            &render_pipeline_layout,
+            surface_format,
+            &sources.edge_vertex,
+            &sources.edge_fragment,
+        );
+        
+        Ok(EdgePipeline {
+            render_pipeline,
+            pipeline_layout: render_pipeline_layout,
+            surface_format,
+            vertex_buffer,
+            camera_buffer,
+            camera_bind_group,
+            max_vertices,
+        })
+    }
+    
+    #[allow(clippy::too_many_arguments)]
+    pub fn render<'a>(
+        &'a self,
+        render_pass: &mut RenderPass<'a>,
+        queue: &wgpu::Queue,
+        scene: &Scene,
+        camera: &Camera,
+        router: &EdgeRouter,
+        filter: &EdgeFilter,
+        visible: &VisibleNodes,
+        ghost: Option<&GhostEdgeLine>,
+        stats: &mut DrawStats,
+    ) -> Result<(), GraphEngineError> {
+        self.render_fixed(render_pass, queue, scene, camera, router, filter, visible, ghost, stats)
+    }
+    
+    /// Swap in new shader sources, keeping the current pipeline if creation fails
+    pub fn reload_shaders(&mut self, device: &Device, vertex_source: &str, fragment_source: &str) -> Result<(), GraphEngineError> {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let render_pipeline = Self::create_render_pipeline(
+            device,
+            &self.pipeline_layout,
+            self.surface_format,
+            vertex_source,
+            fragment_source,
+        );
+        if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+            return Err(GraphEngineError::ShaderError(error.to_string()));
+        }
+        
+        self.render_pipeline = render_pipeline;
+        Ok(())
+    }
+    
+    /// Create the edge render pipeline from shader sources
+    fn create_render_pipeline(
+        device: &Device,
+        layout: &wgpu::PipelineLayout,
+        surface_format: wgpu::TextureFormat,
+        vertex_source: &str,
+        fragment_source: &str,
+    ) -> RenderPipeline {
+        let vertex_shader = shaders::create_shader_module(device, vertex_source, "Edge Vertex Shader");
+        let fragment_shader = shaders::create_shader_module(device, fragment_source, "Edge Fragment Shader");
+        
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Edge Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
+            layout: Some(layout),
             vertex: wgpu::VertexState {
                 module: &vertex_shader,
                 entry_point: "vs_main",
@@ -292,85 +755,36 @@ impl EdgePipeline {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
-        });
-        
-        Ok(EdgePipeline {
-            render_pipeline,
-            vertex_buffer,
-            camera_buffer,
-            camera_bind_group,
-            max_vertices,
         })
     }
-    
-    pub fn render<'a>(
-        &'a self,
-        render_pass: &mut RenderPass<'a>,
-        queue: &wgpu::Queue,
-        scene: &Scene,
-        camera: &Camera,
-    ) -> Result<(), GraphEngineError> {
-        self.render_fixed(render_pass, queue, scene, camera)
-    }
 }
 
 // Add required trait implementations
 use wgpu::util::DeviceExt;
 
-// Fix the write_buffer usage - it should be called on queue, not render_pass
-impl NodePipeline {
-    pub fn render_fixed<'a>(
-        &'a self,
-        render_pass: &mut RenderPass<'a>,
-        queue: &wgpu::Queue,
-        scene: &Scene,
-        camera: &Camera,
-    ) -> Result<(), GraphEngineError> {
-        // Update camera uniform
-        let mut camera_uniform = CameraUniform::new();
-        camera_uniform.update_view_proj(camera);
-        
-        queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[camera_uniform]));
-        
-        // Collect instance data
-        let instances: Vec<NodeInstance> = scene.nodes()
-            .filter(|(_, node)| node.visible)
-            .take(self.max_instances)
-            .map(|(_, node)| NodeInstance {
-                position: [node.position.x, node.position.y, node.position.z],
-                color: node.color,
-                radius: node.radius,
-                selected: if node.selected { 1.0 } else { 0.0 },
-                _padding: [0.0; 2],
-            })
-            .collect();
-        
-        if instances.is_empty() {
-            return Ok(());
-        }
-        
-        // Update instance buffer
-        queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instances));
-        
-        // Set pipeline and render
-        render_pass.set_pipeline(&self.render_pipeline);
-        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
-        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-        render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
-        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-        render_pass.draw_indexed(0..self.index_count, 0, 0..instances.len() as u32);
-        
-        Ok(())
-    }
+/// An edge drawn over the scene's edges without being part of it, like the
+/// preview of an edge being created
+#[derive(Debug, Clone, PartialEq)]
+pub struct GhostEdgeLine {
+    pub from: crate::Position,
+    pub to: crate::Position,
+    pub color: [f32; 4],
+    pub thickness: f32,
 }
 
 impl EdgePipeline {
+    #[allow(clippy::too_many_arguments)]
     pub fn render_fixed<'a>(
         &'a self,
         render_pass: &mut RenderPass<'a>,
         queue: &wgpu::Queue,
         scene: &Scene,
         camera: &Camera,
+        router: &EdgeRouter,
+        filter: &EdgeFilter,
+        visible: &VisibleNodes,
+        ghost: Option<&GhostEdgeLine>,
+        stats: &mut DrawStats,
     ) -> Result<(), GraphEngineError> {
         // Update camera uniform
         let mut camera_uniform = CameraUniform::new();
@@ -378,43 +792,11 @@ impl EdgePipeline {
         
         queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[camera_uniform]));
         
-        // Collect edge vertices
-        let mut vertices = Vec::new();
-        
-        for edge in scene.edges().filter(|edge| edge.visible) {
-            if let (Some(source_node), Some(target_node)) = 
-                (scene.get_node(edge.source), scene.get_node(edge.target)) {
-                
-                let thickness = match edge.edge_type {
-                    crate::EdgeType::Contains => 2.0,
-                    crate::EdgeType::DependsOn => 1.5,
-                    crate::EdgeType::RelatedTo { similarity } => 1.0 + similarity * 2.0,
-                    _ => 1.0,
-                };
-                
-                vertices.push(EdgeVertex {
-                    position: [source_node.position.x, source_node.position.y, source_node.position.z],
-                    color: edge.color,
-                    thickness,
-                    _padding: [0.0; 3],
-                });
-                
-                vertices.push(EdgeVertex {
-                    position: [target_node.position.x, target_node.position.y, target_node.position.z],
-                    color: edge.color,
-                    thickness,
-                    _padding: [0.0; 3],
-                });
-            }
-        }
-        
+        let vertices = edge_vertices(scene, router, filter, visible, ghost, self.max_vertices, stats);
         if vertices.is_empty() {
             return Ok(());
         }
         
-        // Limit to max vertices
-        vertices.truncate(self.max_vertices);
-        
         // Update vertex buffer
         queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
         
@@ -426,4 +808,140 @@ impl EdgePipeline {
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Line list of the edges to draw, at most `max_vertices` long
+///
+/// The ghost edge comes last and is never the one cut off.
+fn edge_vertices(
+    scene: &Scene,
+    router: &EdgeRouter,
+    filter: &EdgeFilter,
+    visible: &VisibleNodes,
+    ghost: Option<&GhostEdgeLine>,
+    max_vertices: usize,
+    stats: &mut DrawStats,
+) -> Vec<EdgeVertex> {
+    let mut vertices = Vec::new();
+    
+    for edge in scene.edges().filter(|edge| edge.visible && filter.shows(edge)) {
+        if !visible.contains_edge(edge.source, edge.target) {
+            stats.edges_culled += 1;
+            continue;
+        }
+        if let (Some(source_node), Some(target_node)) = 
+            (scene.get_node(edge.source), scene.get_node(edge.target)) {
+            
+            let thickness = match edge.edge_type {
+                crate::EdgeType::Contains => 2.0,
+                crate::EdgeType::DependsOn => 1.5,
+                crate::EdgeType::RelatedTo { similarity } => 1.0 + similarity * 2.0,
+                _ => 1.0,
+            };
+            
+            let vertex = |position: &crate::Position| EdgeVertex {
+                position: [position.x, position.y, position.z],
+                color: edge.color,
+                thickness,
+                _padding: [0.0; 3],
+            };
+            
+            // Routed edges are drawn as a line segment per pair of route points
+            match router.route(edge.id) {
+                Some(route) if route.bent => {
+                    for segment in route.points.windows(2) {
+                        vertices.push(vertex(&segment[0]));
+                        vertices.push(vertex(&segment[1]));
+                    }
+                }
+                _ => {
+                    vertices.push(vertex(&source_node.position));
+                    vertices.push(vertex(&target_node.position));
+                }
+            }
+            stats.edges_drawn += 1;
+        }
+    }
+    
+    // Filters and culling are for the scene's edges; the ghost always shows
+    if let Some(ghost) = ghost {
+        let vertex = |position: &crate::Position| EdgeVertex {
+            position: [position.x, position.y, position.z],
+            color: ghost.color,
+            thickness: ghost.thickness,
+            _padding: [0.0; 3],
+        };
+        vertices.truncate(max_vertices.saturating_sub(2));
+        vertices.push(vertex(&ghost.from));
+        vertices.push(vertex(&ghost.to));
+    } else {
+        vertices.truncate(max_vertices);
+    }
+    
+    vertices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EdgeRoutingConfig, EdgeType, NodeMetadata, NodeType, Position, SceneEdge, SceneNode};
+    use nalgebra::Vector3;
+
+    fn node(scene: &mut Scene, x: f32) -> crate::SceneId {
+        scene.add_node(SceneNode {
+            id: 0,
+            position: Position::new(x, 0.0, 0.0),
+            velocity: Vector3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type: NodeType::Concept { title: String::new(), content: String::new() },
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        })
+    }
+
+    #[test]
+    fn test_ghost_edge_is_drawn_after_the_scene_edges() {
+        let mut scene = Scene::new();
+        let a = node(&mut scene, 0.0);
+        let b = node(&mut scene, 4.0);
+        scene.add_edge(SceneEdge {
+            id: 0,
+            source: a,
+            target: b,
+            edge_type: EdgeType::Contains,
+            weight: 1.0,
+            color: [0.5; 4],
+            visible: true,
+            animated: false,
+        });
+        let router = EdgeRouter::new(EdgeRoutingConfig::default());
+        let ghost = GhostEdgeLine {
+            from: Position::new(0.0, 0.0, 0.0),
+            to: Position::new(2.0, 3.0, 0.0),
+            color: [1.0, 0.0, 0.0, 0.8],
+            thickness: 2.5,
+        };
+
+        let mut stats = DrawStats::default();
+        let vertices = edge_vertices(&scene, &router, &EdgeFilter::default(), &VisibleNodes::all(), Some(&ghost), 100, &mut stats);
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(stats.edges_drawn, 1);
+        assert_eq!(vertices[3].position, [2.0, 3.0, 0.0]);
+        assert_eq!(vertices[3].color, ghost.color);
+        assert_eq!(vertices[2].thickness, 2.5);
+
+        // Hiding the scene's edges leaves the ghost
+        let hidden = EdgeFilter { min_strength: 2.0, ..EdgeFilter::default() };
+        let vertices = edge_vertices(&scene, &router, &hidden, &VisibleNodes::all(), Some(&ghost), 100, &mut DrawStats::default());
+        assert_eq!(vertices.len(), 2);
+        assert_eq!(vertices[0].color, ghost.color);
+        assert!(edge_vertices(&scene, &router, &hidden, &VisibleNodes::all(), None, 100, &mut DrawStats::default()).is_empty());
+
+        // A full buffer drops scene edges, not the ghost
+        let vertices = edge_vertices(&scene, &router, &EdgeFilter::default(), &VisibleNodes::all(), Some(&ghost), 2, &mut DrawStats::default());
+        assert_eq!(vertices.len(), 2);
+        assert_eq!(vertices[1].color, ghost.color);
+    }
+}
@@ -0,0 +1,465 @@
+//! GPU device-loss detection and recovery
+//!
+//! A lost device (driver reset, GPU hang, eGPU unplugged) makes every GPU
+//! object unusable but leaves the CPU side intact: the scene, camera, undo
+//! history and the CPU copies of uploaded data. [`DeviceLossMonitor`] notices
+//! the loss, [`DeviceRecovery`] retries recreating the device with backoff
+//! while frames are skipped, and [`ResourceCache`] re-uploads cached buffers
+//! and textures on the new device. Recovery events classify as problems, so
+//! the desktop shows them as transient notifications.
+
+use super::upload::{UploadPriority, UploadScheduler};
+use crate::GraphEngineError;
+use crossbeam_channel::{Receiver, Sender};
+use horizonos_graph_errors::{Category, Classify, Problem, Severity};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use wgpu::{Buffer, Device, Texture};
+
+/// Records that the GPU device was lost
+///
+/// Cloned into the device-lost callback; renderer code also reports fatal
+/// surface errors through it.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceLossMonitor {
+    lost: Arc<Mutex<Option<String>>>,
+}
+
+impl DeviceLossMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report losses of `device` to this monitor
+    pub fn watch(&self, device: &Device) {
+        let monitor = self.clone();
+        device.set_device_lost_callback(move |reason, message| {
+            monitor.report(format!("{:?}: {}", reason, message));
+        });
+    }
+
+    /// Mark the device lost; the first reason is kept
+    pub fn report(&self, reason: impl Into<String>) {
+        let mut lost = self.lost.lock().unwrap();
+        if lost.is_none() {
+            let reason = reason.into();
+            log::error!("GPU device lost: {}", reason);
+            *lost = Some(reason);
+        }
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.lost.lock().unwrap().is_some()
+    }
+
+    /// Take the loss reason, clearing the flag
+    pub fn take(&self) -> Option<String> {
+        self.lost.lock().unwrap().take()
+    }
+}
+
+/// Where the device is in recovering
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecoveryState {
+    Healthy,
+    /// Lost and waiting for the next attempt to recreate it
+    Lost { reason: String, attempts: u32 },
+    /// Gave up after too many failed attempts
+    Failed { reason: String },
+}
+
+/// Progress of a recovery, for notifications
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecoveryEvent {
+    DeviceLost { reason: String },
+    Recovered { attempts: u32, downtime: Duration },
+    RecoveryFailed { reason: String, attempts: u32 },
+}
+
+impl Classify for RecoveryEvent {
+    fn classify(&self) -> Problem {
+        // Losing and regaining the device are shown as passing notices
+        let problem = match self {
+            RecoveryEvent::DeviceLost { reason } => {
+                Problem::new("engine.device_lost", Category::Rendering, Severity::Warning, "Graphics stopped responding, restarting them")
+                    .user_visible(true)
+                    .with_detail(reason)
+            }
+            RecoveryEvent::Recovered { attempts, downtime } => {
+                Problem::new("engine.device_recovered", Category::Rendering, Severity::Info, "Graphics were restarted; nothing was lost")
+                    .user_visible(true)
+                    .with_detail(format!("Recovered after {} attempt(s) in {} ms", attempts, downtime.as_millis()))
+            }
+            RecoveryEvent::RecoveryFailed { reason, attempts } => {
+                Problem::new("engine.gpu_unavailable", Category::Rendering, Severity::Critical, "Graphics could not be restarted")
+                    .with_detail(format!("Gave up after {} attempts: {}", attempts, reason))
+            }
+        };
+        problem.in_subsystem("engine")
+    }
+}
+
+/// What to do with the device this frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryStep {
+    /// The device is fine, render
+    Render,
+    /// Lost; skip the frame until the next attempt is due
+    Wait,
+    /// Lost; recreate the device now and report the outcome with [`DeviceRecovery::attempt_finished`]
+    Recreate,
+}
+
+/// Retries recreating a lost device with exponential backoff
+///
+/// Each frame the engine calls [`poll`](Self::poll) and renders, skips the
+/// frame or recreates the device as told.
+pub struct DeviceRecovery {
+    monitor: DeviceLossMonitor,
+    state: RecoveryState,
+    lost_at: Option<Instant>,
+    next_attempt: Option<Instant>,
+    /// Attempts before giving up
+    max_attempts: u32,
+    /// Delay after the first failed attempt, doubled after each further one
+    retry_delay: Duration,
+    subscribers: Vec<Sender<RecoveryEvent>>,
+}
+
+impl DeviceRecovery {
+    pub fn new(monitor: DeviceLossMonitor) -> Self {
+        Self {
+            monitor,
+            state: RecoveryState::Healthy,
+            lost_at: None,
+            next_attempt: None,
+            max_attempts: 5,
+            retry_delay: Duration::from_millis(250),
+            subscribers: Vec::new(),
+        }
+    }
+
+    /// Set how often and how soon recreating the device is retried
+    pub fn with_retries(mut self, max_attempts: u32, retry_delay: Duration) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    pub fn monitor(&self) -> &DeviceLossMonitor {
+        &self.monitor
+    }
+
+    pub fn state(&self) -> &RecoveryState {
+        &self.state
+    }
+
+    /// Receive recovery events
+    pub fn subscribe(&mut self) -> Receiver<RecoveryEvent> {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        self.subscribers.push(tx);
+        rx
+    }
+
+    /// Check for a loss and whether an attempt to recreate the device is due
+    pub fn poll(&mut self, now: Instant) -> RecoveryStep {
+        if self.state == RecoveryState::Healthy {
+            let Some(reason) = self.monitor.take() else {
+                return RecoveryStep::Render;
+            };
+            self.state = RecoveryState::Lost { reason: reason.clone(), attempts: 0 };
+            self.lost_at = Some(now);
+            self.next_attempt = Some(now);
+            self.emit(RecoveryEvent::DeviceLost { reason });
+        }
+
+        match self.state {
+            RecoveryState::Lost { .. } if self.next_attempt.is_none_or(|next| now >= next) => RecoveryStep::Recreate,
+            _ => RecoveryStep::Wait,
+        }
+    }
+
+    /// Record the outcome of recreating the device, returning whether it is usable again
+    pub fn attempt_finished(&mut self, now: Instant, result: Result<(), GraphEngineError>) -> bool {
+        let RecoveryState::Lost { attempts, .. } = self.state else {
+            return self.state == RecoveryState::Healthy;
+        };
+        let attempts = attempts + 1;
+        match result {
+            Ok(()) => {
+                // Reports from the old device's last moments do not count
+                self.monitor.take();
+                let downtime = self.lost_at.map_or(Duration::ZERO, |lost_at| now.saturating_duration_since(lost_at));
+                log::info!("GPU device recreated after {} attempt(s)", attempts);
+                self.state = RecoveryState::Healthy;
+                self.lost_at = None;
+                self.next_attempt = None;
+                self.emit(RecoveryEvent::Recovered { attempts, downtime });
+                true
+            }
+            Err(e) if attempts >= self.max_attempts => {
+                log::error!("Giving up recreating the GPU device: {}", e);
+                self.state = RecoveryState::Failed { reason: e.to_string() };
+                self.emit(RecoveryEvent::RecoveryFailed { reason: e.to_string(), attempts });
+                false
+            }
+            Err(e) => {
+                log::warn!("Recreating the GPU device failed (attempt {}): {}", attempts, e);
+                self.state = RecoveryState::Lost { reason: e.to_string(), attempts };
+                self.next_attempt = Some(now + self.retry_delay * 2u32.saturating_pow(attempts - 1));
+                false
+            }
+        }
+    }
+
+    /// Send an event to subscribers, dropping disconnected ones
+    fn emit(&mut self, event: RecoveryEvent) {
+        self.subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+    }
+}
+
+/// GPU object a cached resource is uploaded into
+#[derive(Debug, Clone)]
+pub enum CachedResourceKind {
+    Buffer { usage: wgpu::BufferUsages },
+    Texture { size: [u32; 2], format: wgpu::TextureFormat, usage: wgpu::TextureUsages },
+}
+
+/// CPU copy of a GPU resource and its current GPU object
+#[derive(Debug)]
+struct CachedResource {
+    kind: CachedResourceKind,
+    priority: UploadPriority,
+    data: Arc<[u8]>,
+    buffer: Option<Arc<Buffer>>,
+    texture: Option<Arc<Texture>>,
+}
+
+impl CachedResource {
+    fn is_resident(&self) -> bool {
+        self.buffer.is_some() || self.texture.is_some()
+    }
+}
+
+/// GPU buffers and textures kept with their CPU data so they survive device loss
+///
+/// Resources are created on the GPU lazily by [`ResourceCache::restore`],
+/// which the renderer calls every frame; after a device loss the cache is
+/// invalidated and the same call recreates and re-uploads everything.
+#[derive(Debug, Default)]
+pub struct ResourceCache {
+    resources: BTreeMap<String, CachedResource>,
+}
+
+impl ResourceCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cache a buffer's contents, replacing any resource with the same key
+    pub fn insert_buffer(&mut self, key: impl Into<String>, usage: wgpu::BufferUsages, priority: UploadPriority, data: impl Into<Arc<[u8]>>) {
+        self.insert(key.into(), CachedResourceKind::Buffer { usage }, priority, data.into());
+    }
+
+    /// Cache tightly packed texels of a 2D texture, replacing any resource with the same key
+    pub fn insert_texture(
+        &mut self,
+        key: impl Into<String>,
+        size: [u32; 2],
+        format: wgpu::TextureFormat,
+        usage: wgpu::TextureUsages,
+        priority: UploadPriority,
+        data: impl Into<Arc<[u8]>>,
+    ) {
+        self.insert(key.into(), CachedResourceKind::Texture { size, format, usage }, priority, data.into());
+    }
+
+    fn insert(&mut self, key: String, kind: CachedResourceKind, priority: UploadPriority, data: Arc<[u8]>) {
+        self.resources.insert(key, CachedResource { kind, priority, data, buffer: None, texture: None });
+    }
+
+    pub fn remove(&mut self, key: &str) -> bool {
+        self.resources.remove(key).is_some()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.resources.contains_key(key)
+    }
+
+    /// Current GPU buffer of a resource, once created
+    pub fn buffer(&self, key: &str) -> Option<Arc<Buffer>> {
+        self.resources.get(key)?.buffer.clone()
+    }
+
+    /// Current GPU texture of a resource, once created
+    pub fn texture(&self, key: &str) -> Option<Arc<Texture>> {
+        self.resources.get(key)?.texture.clone()
+    }
+
+    /// Forget all GPU objects, e.g. because the device they belong to was lost
+    pub fn invalidate(&mut self) {
+        for resource in self.resources.values_mut() {
+            resource.buffer = None;
+            resource.texture = None;
+        }
+    }
+
+    /// Keys of resources without a GPU object
+    pub fn missing(&self) -> Vec<&str> {
+        self.resources.iter().filter(|(_, resource)| !resource.is_resident()).map(|(key, _)| key.as_str()).collect()
+    }
+
+    /// Bytes of CPU data held
+    pub fn cached_bytes(&self) -> usize {
+        self.resources.values().map(|resource| resource.data.len()).sum()
+    }
+
+    pub fn len(&self) -> usize {
+        self.resources.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    /// Create missing GPU objects on `device` and queue their uploads, returning how many were created
+    pub fn restore(&mut self, device: &Device, uploads: &mut UploadScheduler) -> Result<usize, GraphEngineError> {
+        let mut created = 0;
+        for (key, resource) in self.resources.iter_mut().filter(|(_, resource)| !resource.is_resident()) {
+            match resource.kind.clone() {
+                CachedResourceKind::Buffer { usage } => {
+                    // Copies are in multiples of four bytes
+                    let size = (resource.data.len() as u64).div_ceil(wgpu::COPY_BUFFER_ALIGNMENT) * wgpu::COPY_BUFFER_ALIGNMENT;
+                    let buffer = Arc::new(device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some(key),
+                        size: size.max(wgpu::COPY_BUFFER_ALIGNMENT),
+                        usage: usage | wgpu::BufferUsages::COPY_DST,
+                        mapped_at_creation: false,
+                    }));
+                    uploads.upload_buffer(key.clone(), resource.priority, buffer.clone(), 0, resource.data.clone())?;
+                    resource.buffer = Some(buffer);
+                }
+                CachedResourceKind::Texture { size, format, usage } => {
+                    let texture = Arc::new(device.create_texture(&wgpu::TextureDescriptor {
+                        label: Some(key),
+                        size: wgpu::Extent3d { width: size[0], height: size[1], depth_or_array_layers: 1 },
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: wgpu::TextureDimension::D2,
+                        format,
+                        usage: usage | wgpu::TextureUsages::COPY_DST,
+                        view_formats: &[],
+                    }));
+                    uploads.upload_texture(key.clone(), resource.priority, texture.clone(), [0, 0], size, 0, resource.data.clone())?;
+                    resource.texture = Some(texture);
+                }
+            }
+            created += 1;
+        }
+        Ok(created)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failed() -> Result<(), GraphEngineError> {
+        Err(GraphEngineError::RenderError("no adapter".into()))
+    }
+
+    #[test]
+    fn test_monitor_keeps_the_first_reason() {
+        let monitor = DeviceLossMonitor::new();
+        assert!(!monitor.is_lost());
+        monitor.clone().report("driver reset");
+        monitor.report("surface lost");
+        assert!(monitor.is_lost());
+        assert_eq!(monitor.take().as_deref(), Some("driver reset"));
+        assert!(!monitor.is_lost());
+    }
+
+    #[test]
+    fn test_recovery_retries_with_backoff() {
+        let mut recovery = DeviceRecovery::new(DeviceLossMonitor::new()).with_retries(5, Duration::from_millis(100));
+        let events = recovery.subscribe();
+        let start = Instant::now();
+        assert_eq!(recovery.poll(start), RecoveryStep::Render);
+
+        recovery.monitor().report("GPU hang");
+        assert_eq!(recovery.poll(start), RecoveryStep::Recreate);
+        assert_eq!(events.try_recv(), Ok(RecoveryEvent::DeviceLost { reason: "GPU hang".to_string() }));
+
+        // Failed attempts wait 100 ms, then 200 ms
+        assert!(!recovery.attempt_finished(start, failed()));
+        assert!(matches!(recovery.state(), RecoveryState::Lost { attempts: 1, .. }));
+        assert_eq!(recovery.poll(start + Duration::from_millis(50)), RecoveryStep::Wait);
+        assert_eq!(recovery.poll(start + Duration::from_millis(100)), RecoveryStep::Recreate);
+        let retried = start + Duration::from_millis(100);
+        assert!(!recovery.attempt_finished(retried, failed()));
+        assert_eq!(recovery.poll(retried + Duration::from_millis(199)), RecoveryStep::Wait);
+        assert_eq!(recovery.poll(retried + Duration::from_millis(200)), RecoveryStep::Recreate);
+
+        // A report from the old device's last moments does not start another recovery
+        recovery.monitor().report("late callback");
+        let recovered = retried + Duration::from_millis(200);
+        assert!(recovery.attempt_finished(recovered, Ok(())));
+        assert_eq!(recovery.state(), &RecoveryState::Healthy);
+        assert_eq!(events.try_recv(), Ok(RecoveryEvent::Recovered { attempts: 3, downtime: Duration::from_millis(300) }));
+        assert_eq!(recovery.poll(recovered), RecoveryStep::Render);
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_recovery_gives_up() {
+        let mut recovery = DeviceRecovery::new(DeviceLossMonitor::new()).with_retries(2, Duration::ZERO);
+        let events = recovery.subscribe();
+        let now = Instant::now();
+        recovery.monitor().report("eGPU unplugged");
+        assert_eq!(recovery.poll(now), RecoveryStep::Recreate);
+        assert!(!recovery.attempt_finished(now, failed()));
+        assert_eq!(recovery.poll(now), RecoveryStep::Recreate);
+        assert!(!recovery.attempt_finished(now, failed()));
+
+        assert!(matches!(recovery.state(), RecoveryState::Failed { .. }));
+        assert_eq!(recovery.poll(now), RecoveryStep::Wait);
+        let events: Vec<_> = events.try_iter().collect();
+        assert!(matches!(events.as_slice(), [RecoveryEvent::DeviceLost { .. }, RecoveryEvent::RecoveryFailed { attempts: 2, .. }]));
+    }
+
+    #[test]
+    fn test_recovery_events_classify_as_notices() {
+        let lost = RecoveryEvent::DeviceLost { reason: "reset".to_string() }.classify();
+        assert_eq!((lost.severity, lost.user_visible), (Severity::Warning, true));
+        let recovered = RecoveryEvent::Recovered { attempts: 1, downtime: Duration::from_millis(40) }.classify();
+        assert_eq!((recovered.code.as_str(), recovered.severity), ("engine.device_recovered", Severity::Info));
+        assert_eq!(recovered.detail.as_deref(), Some("Recovered after 1 attempt(s) in 40 ms"));
+        let failed = RecoveryEvent::RecoveryFailed { reason: "no adapter".to_string(), attempts: 5 }.classify();
+        assert_eq!((failed.severity, failed.subsystem.as_str()), (Severity::Critical, "engine"));
+    }
+
+    #[test]
+    fn test_cache_keeps_cpu_data_across_invalidation() {
+        let mut cache = ResourceCache::new();
+        cache.insert_buffer("nodes", wgpu::BufferUsages::VERTEX, UploadPriority::Visible, vec![0u8; 64]);
+        cache.insert_texture(
+            "atlas",
+            [4, 4],
+            wgpu::TextureFormat::Rgba8Unorm,
+            wgpu::TextureUsages::TEXTURE_BINDING,
+            UploadPriority::Nearby,
+            vec![0u8; 64],
+        );
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.cached_bytes(), 128);
+        assert_eq!(cache.missing(), ["atlas", "nodes"]);
+        assert!(cache.buffer("nodes").is_none());
+
+        cache.invalidate();
+        assert_eq!(cache.missing(), ["atlas", "nodes"]);
+        assert!(cache.remove("atlas"));
+        assert!(!cache.contains("atlas"));
+        assert_eq!(cache.cached_bytes(), 64);
+    }
+}
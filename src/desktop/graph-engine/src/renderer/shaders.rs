@@ -1,5 +1,14 @@
 //! Shader programs for rendering nodes and edges
 
+/// File names used when shaders are overridden or hot-reloaded
+pub const NODE_VERTEX_SHADER_NAME: &str = "node_vertex.wgsl";
+pub const NODE_FRAGMENT_SHADER_NAME: &str = "node_fragment.wgsl";
+pub const EDGE_VERTEX_SHADER_NAME: &str = "edge_vertex.wgsl";
+pub const EDGE_FRAGMENT_SHADER_NAME: &str = "edge_fragment.wgsl";
+pub const ICON_SHADER_NAME: &str = "icon.wgsl";
+pub const PICK_SHADER_NAME: &str = "pick.wgsl";
+pub const OVERLAY_SHADER_NAME: &str = "overlay.wgsl";
+
 /// Vertex shader for rendering spherical nodes
 pub const NODE_VERTEX_SHADER: &str = r#"
 struct CameraUniform {
@@ -211,6 +220,257 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
 }
 "#;
 
+/// Shader drawing icon nodes as camera-facing quads textured from an atlas page
+pub const ICON_SHADER: &str = r#"
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+    view_pos: vec3<f32>,
+};
+
+struct VertexInput {
+    @location(0) corner: vec2<f32>,
+};
+
+struct InstanceInput {
+    @location(5) position: vec3<f32>,
+    @location(6) radius: f32,
+    @location(7) color: vec4<f32>,
+    @location(8) uv_rect: vec4<f32>,
+    @location(9) selected: f32,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) selected: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0)
+var atlas: texture_2d<f32>;
+@group(1) @binding(1)
+var atlas_sampler: sampler;
+
+@vertex
+fn vs_main(vertex: VertexInput, instance: InstanceInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    // Face the camera, falling back to another up vector when looking straight down
+    let forward = normalize(instance.position - camera.view_pos);
+    var world_up = vec3<f32>(0.0, 1.0, 0.0);
+    if abs(forward.y) > 0.99 {
+        world_up = vec3<f32>(0.0, 0.0, 1.0);
+    }
+    let right = normalize(cross(forward, world_up));
+    let up = cross(right, forward);
+
+    let world_position = instance.position
+        + (right * vertex.corner.x + up * vertex.corner.y) * instance.radius;
+    out.clip_position = camera.view_proj * vec4<f32>(world_position, 1.0);
+
+    // Corners run from -1 to 1; the atlas rect is (min u, min v, max u, max v)
+    let t = vertex.corner * vec2<f32>(0.5, -0.5) + vec2<f32>(0.5, 0.5);
+    out.uv = mix(instance.uv_rect.xy, instance.uv_rect.zw, t);
+    out.color = instance.color;
+    out.selected = instance.selected;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = textureSample(atlas, atlas_sampler, in.uv);
+    if texel.a < 0.05 {
+        discard;
+    }
+
+    var final_color = texel.rgb * in.color.rgb;
+    if in.selected > 0.5 {
+        final_color = mix(final_color, vec3<f32>(1.0, 0.8, 0.2), 0.3);
+    }
+
+    return vec4<f32>(final_color, texel.a * in.color.a);
+}
+"#;
+
+/// Shader drawing node IDs into the picking target
+pub const PICK_SHADER: &str = r#"
+struct PickUniform {
+    view_proj: mat4x4<f32>,
+};
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+};
+
+struct InstanceInput {
+    @location(5) position: vec3<f32>,
+    @location(6) radius: f32,
+    @location(7) id: vec2<u32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) @interpolate(flat) id: vec2<u32>,
+};
+
+@group(0) @binding(0)
+var<uniform> pick: PickUniform;
+
+@vertex
+fn vs_main(vertex: VertexInput, instance: InstanceInput) -> VertexOutput {
+    var out: VertexOutput;
+    let world_position = vertex.position * instance.radius + instance.position;
+    out.clip_position = pick.view_proj * vec4<f32>(world_position, 1.0);
+    out.id = instance.id;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec2<u32> {
+    return in.id;
+}
+"#;
+
+/// Shader drawing the screen-space selection overlay
+pub const OVERLAY_SHADER: &str = r#"
+struct OverlayUniform {
+    viewport: vec2<f32>,
+};
+
+struct InstanceInput {
+    @location(0) center: vec2<f32>,
+    @location(1) half_size: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) border: f32,
+    @location(4) shape: f32,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) local: vec2<f32>,
+    @location(1) half_size: vec2<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) border: f32,
+    @location(4) shape: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> overlay: OverlayUniform;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, instance: InstanceInput) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, 1.0), vec2<f32>(-1.0, 1.0),
+    );
+
+    // One pixel of margin for the antialiased edge
+    let local = corners[index] * (instance.half_size + vec2<f32>(1.0, 1.0));
+    let pixel = instance.center + local;
+
+    var out: VertexOutput;
+    // Pixels run right and down from the top-left corner of the screen
+    out.clip_position = vec4<f32>(
+        pixel.x / overlay.viewport.x * 2.0 - 1.0,
+        1.0 - pixel.y / overlay.viewport.y * 2.0,
+        0.0,
+        1.0,
+    );
+    out.local = local;
+    out.half_size = instance.half_size;
+    out.color = instance.color;
+    out.border = instance.border;
+    out.shape = instance.shape;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Signed distance to the edge of the shape, negative inside
+    var distance: f32;
+    if in.shape > 0.5 {
+        distance = length(in.local) - in.half_size.x;
+    } else {
+        let q = abs(in.local) - in.half_size;
+        distance = length(max(q, vec2<f32>(0.0, 0.0))) + min(max(q.x, q.y), 0.0);
+    }
+
+    // Outlines keep a band of the border width just inside the edge
+    if in.border > 0.0 {
+        distance = abs(distance + in.border * 0.5) - in.border * 0.5;
+    }
+
+    let coverage = clamp(0.5 - distance, 0.0, 1.0);
+    if coverage <= 0.0 {
+        discard;
+    }
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+}
+"#;
+
+/// Shader source from the asset pack, falling back to the compiled-in copy
+///
+/// Lets shaders be replaced through the asset override directory during development.
+pub fn shader_source(assets: &crate::AssetStore, name: &str, builtin: &str) -> String {
+    assets.load_text_or(crate::AssetKind::Shader, name, builtin)
+}
+
+/// Shader sources the renderer builds its pipelines from
+///
+/// Each comes from the asset pack or override directory when present there,
+/// and from the compiled-in copy otherwise.
+#[derive(Debug, Clone)]
+pub struct ShaderSources {
+    pub node_vertex: String,
+    pub node_fragment: String,
+    pub edge_vertex: String,
+    pub edge_fragment: String,
+    pub icon: String,
+    pub pick: String,
+    pub overlay: String,
+}
+
+impl ShaderSources {
+    /// Load every shader from `assets`
+    pub fn load(assets: &crate::AssetStore) -> Self {
+        Self {
+            node_vertex: shader_source(assets, NODE_VERTEX_SHADER_NAME, NODE_VERTEX_SHADER),
+            node_fragment: shader_source(assets, NODE_FRAGMENT_SHADER_NAME, NODE_FRAGMENT_SHADER),
+            edge_vertex: shader_source(assets, EDGE_VERTEX_SHADER_NAME, EDGE_VERTEX_SHADER),
+            edge_fragment: shader_source(assets, EDGE_FRAGMENT_SHADER_NAME, EDGE_FRAGMENT_SHADER),
+            icon: shader_source(assets, ICON_SHADER_NAME, ICON_SHADER),
+            pick: shader_source(assets, PICK_SHADER_NAME, PICK_SHADER),
+            overlay: shader_source(assets, OVERLAY_SHADER_NAME, OVERLAY_SHADER),
+        }
+    }
+}
+
+impl Default for ShaderSources {
+    /// The compiled-in shaders
+    fn default() -> Self {
+        Self::load(&crate::AssetStore::new())
+    }
+}
+
+/// Compiled-in shaders by asset name, e.g. to build the asset pack from
+pub fn builtin_shaders() -> [(&'static str, &'static str); 7] {
+    [
+        (NODE_VERTEX_SHADER_NAME, NODE_VERTEX_SHADER),
+        (NODE_FRAGMENT_SHADER_NAME, NODE_FRAGMENT_SHADER),
+        (EDGE_VERTEX_SHADER_NAME, EDGE_VERTEX_SHADER),
+        (EDGE_FRAGMENT_SHADER_NAME, EDGE_FRAGMENT_SHADER),
+        (ICON_SHADER_NAME, ICON_SHADER),
+        (PICK_SHADER_NAME, PICK_SHADER),
+        (OVERLAY_SHADER_NAME, OVERLAY_SHADER),
+    ]
+}
+
 /// Utility function to create a shader module
 pub fn create_shader_module(device: &wgpu::Device, source: &str, label: &str) -> wgpu::ShaderModule {
     device.create_shader_module(wgpu::ShaderModuleDescriptor {
@@ -0,0 +1,616 @@
+//! Budgeted GPU uploads spread across frames
+//!
+//! Large uploads (thumbnails, window textures, instance data) are queued
+//! instead of written immediately. Each frame the scheduler uploads at most
+//! `frame_budget_bytes`, taking visible content first, and splits large
+//! transfers into chunks: buffers at copy alignment, textures by row bands.
+//! Buffer chunks go through a reused `StagingBelt`; texture bands use
+//! `Queue::write_texture`, which stages through wgpu's own reused memory.
+//!
+//! Frame times are tracked with scheduling on and off so the effect on
+//! frame-time variance can be compared.
+
+use crate::GraphEngineError;
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Upload urgency, most urgent first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum UploadPriority {
+    /// Content currently on screen
+    Visible,
+    /// Content just outside the viewport, likely visible soon
+    Nearby,
+    /// Everything else
+    Background,
+}
+
+impl UploadPriority {
+    /// Next more urgent priority, used to keep long-waiting uploads from starving
+    fn promoted(self) -> Self {
+        match self {
+            UploadPriority::Background => UploadPriority::Nearby,
+            _ => UploadPriority::Visible,
+        }
+    }
+}
+
+/// Layout of upload data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadShape {
+    /// Bytes written to a buffer starting at `offset`
+    Buffer { offset: u64 },
+    /// Tightly packed rows written to a 2D texture region
+    Texture {
+        origin: [u32; 2],
+        width: u32,
+        height: u32,
+        bytes_per_row: u32,
+        mip_level: u32,
+    },
+}
+
+/// Scheduler configuration
+#[derive(Debug, Clone)]
+pub struct UploadConfig {
+    /// Bytes uploaded per frame; at least one chunk is always uploaded
+    pub frame_budget_bytes: usize,
+    /// Largest chunk a single transfer is split into
+    pub max_chunk_bytes: usize,
+    /// Staging belt chunk size
+    pub staging_chunk_bytes: u64,
+    /// Frames an upload may wait before its priority is raised
+    pub promote_after_frames: u32,
+    /// Frames kept for frame-time statistics
+    pub frame_time_window: usize,
+}
+
+impl Default for UploadConfig {
+    fn default() -> Self {
+        Self {
+            frame_budget_bytes: 8 * 1024 * 1024,
+            max_chunk_bytes: 2 * 1024 * 1024,
+            staging_chunk_bytes: 1024 * 1024,
+            promote_after_frames: 30,
+            frame_time_window: 240,
+        }
+    }
+}
+
+/// A queued upload
+#[derive(Debug, Clone)]
+struct PendingUpload<T> {
+    key: String,
+    priority: UploadPriority,
+    target: T,
+    shape: UploadShape,
+    data: Arc<[u8]>,
+    /// Bytes already uploaded
+    uploaded: usize,
+    /// Frames spent waiting since enqueue or last promotion
+    waited_frames: u32,
+}
+
+/// A piece of an upload scheduled for this frame
+#[derive(Debug, Clone)]
+pub struct UploadChunk<T> {
+    /// Upload key
+    pub key: String,
+    /// Upload target
+    pub target: T,
+    /// Upload layout
+    pub shape: UploadShape,
+    /// Full upload data
+    pub data: Arc<[u8]>,
+    /// Bytes of `data` covered by this chunk
+    pub range: Range<usize>,
+    /// Whether this chunk finishes the upload
+    pub completes: bool,
+}
+
+impl<T> UploadChunk<T> {
+    /// Rows covered by a texture chunk
+    pub fn rows(&self) -> Option<Range<u32>> {
+        match self.shape {
+            UploadShape::Texture { bytes_per_row, .. } => {
+                let bytes_per_row = bytes_per_row as usize;
+                Some((self.range.start / bytes_per_row) as u32..(self.range.end / bytes_per_row) as u32)
+            }
+            UploadShape::Buffer { .. } => None,
+        }
+    }
+}
+
+/// Priority queue of uploads, planned into per-frame chunks
+///
+/// Independent of the GPU; `T` is the upload target (a buffer or texture).
+#[derive(Debug)]
+pub struct UploadQueue<T> {
+    config: UploadConfig,
+    pending: VecDeque<PendingUpload<T>>,
+}
+
+impl<T: Clone> UploadQueue<T> {
+    /// Create an empty queue
+    pub fn new(config: UploadConfig) -> Self {
+        Self {
+            config,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Queue an upload, replacing any pending upload with the same key
+    pub fn enqueue(
+        &mut self,
+        key: impl Into<String>,
+        priority: UploadPriority,
+        target: T,
+        shape: UploadShape,
+        data: impl Into<Arc<[u8]>>,
+    ) -> Result<(), GraphEngineError> {
+        let key = key.into();
+        let data = data.into();
+        validate_shape(&key, shape, data.len())?;
+
+        self.cancel(&key);
+        self.pending.push_back(PendingUpload {
+            key,
+            priority,
+            target,
+            shape,
+            data,
+            uploaded: 0,
+            waited_frames: 0,
+        });
+        Ok(())
+    }
+
+    /// Drop a pending upload
+    pub fn cancel(&mut self, key: &str) -> bool {
+        let before = self.pending.len();
+        self.pending.retain(|upload| upload.key != key);
+        self.pending.len() != before
+    }
+
+    /// Change the priority of a pending upload
+    pub fn set_priority(&mut self, key: &str, priority: UploadPriority) {
+        for upload in self.pending.iter_mut().filter(|upload| upload.key == key) {
+            upload.priority = priority;
+        }
+    }
+
+    /// Recompute priorities, e.g. after the camera moved; `None` keeps the current one
+    pub fn reprioritize(&mut self, mut priority_of: impl FnMut(&str) -> Option<UploadPriority>) {
+        for upload in &mut self.pending {
+            if let Some(priority) = priority_of(&upload.key) {
+                upload.priority = priority;
+            }
+        }
+    }
+
+    /// Number of pending uploads
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether nothing is pending
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Bytes still to upload
+    pub fn pending_bytes(&self) -> usize {
+        self.pending.iter().map(|upload| upload.data.len() - upload.uploaded).sum()
+    }
+
+    /// Take the chunks to upload this frame, within `budget` bytes
+    ///
+    /// Uploads are taken in priority order, oldest first within a priority.
+    /// The first chunk is always taken so oversized uploads still progress.
+    pub fn plan_frame(&mut self, budget: usize) -> Vec<UploadChunk<T>> {
+        // Stable sort keeps enqueue order within a priority
+        self.pending.make_contiguous().sort_by_key(|upload| upload.priority);
+
+        let mut chunks = Vec::new();
+        let mut remaining = budget;
+        while let Some(upload) = self.pending.front_mut() {
+            let left = upload.data.len() - upload.uploaded;
+            let step = chunk_step(upload.shape);
+            let mut len = left.min(self.config.max_chunk_bytes.max(step)).min(remaining);
+            len -= len % step;
+            if len == 0 {
+                if !chunks.is_empty() {
+                    break;
+                }
+                len = step.min(left);
+            }
+
+            let start = upload.uploaded;
+            upload.uploaded += len;
+            remaining = remaining.saturating_sub(len);
+            let completes = upload.uploaded == upload.data.len();
+            chunks.push(UploadChunk {
+                key: upload.key.clone(),
+                target: upload.target.clone(),
+                shape: upload.shape,
+                data: upload.data.clone(),
+                range: start..start + len,
+                completes,
+            });
+
+            if completes {
+                self.pending.pop_front();
+            }
+            if remaining == 0 {
+                break;
+            }
+        }
+
+        for upload in &mut self.pending {
+            upload.waited_frames += 1;
+            if upload.waited_frames >= self.config.promote_after_frames && upload.priority != UploadPriority::Visible {
+                upload.priority = upload.priority.promoted();
+                upload.waited_frames = 0;
+            }
+        }
+        chunks
+    }
+}
+
+/// Smallest unit an upload is split at
+fn chunk_step(shape: UploadShape) -> usize {
+    match shape {
+        UploadShape::Buffer { .. } => wgpu::COPY_BUFFER_ALIGNMENT as usize,
+        UploadShape::Texture { bytes_per_row, .. } => bytes_per_row as usize,
+    }
+}
+
+/// Check data length and alignment against the upload layout
+fn validate_shape(key: &str, shape: UploadShape, len: usize) -> Result<(), GraphEngineError> {
+    let valid = match shape {
+        UploadShape::Buffer { offset } => {
+            let align = wgpu::COPY_BUFFER_ALIGNMENT;
+            len > 0 && offset.is_multiple_of(align) && (len as u64).is_multiple_of(align)
+        }
+        UploadShape::Texture { height, bytes_per_row, .. } => {
+            bytes_per_row > 0 && len == bytes_per_row as usize * height as usize
+        }
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(GraphEngineError::RenderError(format!(
+            "Upload '{}' has {} bytes, which does not match {:?}",
+            key, len, shape
+        )))
+    }
+}
+
+/// Rolling frame-time statistics
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameTimeStats {
+    /// Frames measured
+    pub frames: usize,
+    /// Mean frame time in milliseconds
+    pub mean_ms: f32,
+    /// Frame-time variance in squared milliseconds
+    pub variance_ms2: f32,
+    /// Slowest frame in milliseconds
+    pub max_ms: f32,
+}
+
+impl FrameTimeStats {
+    /// Standard deviation in milliseconds
+    pub fn std_dev_ms(&self) -> f32 {
+        self.variance_ms2.sqrt()
+    }
+
+    fn from_samples(samples: &VecDeque<f32>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let frames = samples.len();
+        let mean_ms = samples.iter().sum::<f32>() / frames as f32;
+        let variance_ms2 = samples.iter().map(|t| (t - mean_ms).powi(2)).sum::<f32>() / frames as f32;
+        let max_ms = samples.iter().copied().fold(0.0, f32::max);
+        Self { frames, mean_ms, variance_ms2, max_ms }
+    }
+}
+
+/// Bytes and chunks uploaded in one frame
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameUploadStats {
+    /// Bytes written
+    pub bytes: usize,
+    /// Chunks written
+    pub chunks: usize,
+    /// Uploads finished
+    pub completed: usize,
+}
+
+/// Executes queued uploads against the GPU within a per-frame budget
+pub struct UploadScheduler {
+    config: UploadConfig,
+    buffers: UploadQueue<Arc<wgpu::Buffer>>,
+    textures: UploadQueue<Arc<wgpu::Texture>>,
+    staging_belt: wgpu::util::StagingBelt,
+    /// Spread uploads across frames; when off everything is uploaded at once
+    enabled: bool,
+    /// Frame times with the current setting
+    frame_times: VecDeque<f32>,
+    /// Frame times from before the setting last changed
+    baseline: Option<(bool, FrameTimeStats)>,
+    last_frame: FrameUploadStats,
+}
+
+impl UploadScheduler {
+    /// Create a scheduler
+    pub fn new(config: UploadConfig) -> Self {
+        Self {
+            buffers: UploadQueue::new(config.clone()),
+            textures: UploadQueue::new(config.clone()),
+            staging_belt: wgpu::util::StagingBelt::new(config.staging_chunk_bytes),
+            enabled: true,
+            frame_times: VecDeque::new(),
+            baseline: None,
+            last_frame: FrameUploadStats::default(),
+            config,
+        }
+    }
+
+    /// Queue a buffer upload, replacing any pending upload with the same key
+    pub fn upload_buffer(
+        &mut self,
+        key: impl Into<String>,
+        priority: UploadPriority,
+        buffer: Arc<wgpu::Buffer>,
+        offset: u64,
+        data: impl Into<Arc<[u8]>>,
+    ) -> Result<(), GraphEngineError> {
+        self.buffers.enqueue(key, priority, buffer, UploadShape::Buffer { offset }, data)
+    }
+
+    /// Queue an upload of tightly packed texels into a texture region
+    #[allow(clippy::too_many_arguments)]
+    pub fn upload_texture(
+        &mut self,
+        key: impl Into<String>,
+        priority: UploadPriority,
+        texture: Arc<wgpu::Texture>,
+        origin: [u32; 2],
+        size: [u32; 2],
+        mip_level: u32,
+        data: impl Into<Arc<[u8]>>,
+    ) -> Result<(), GraphEngineError> {
+        let block_size = texture.format().block_copy_size(None).ok_or_else(|| {
+            GraphEngineError::RenderError(format!("Cannot upload to {:?} textures", texture.format()))
+        })?;
+        let shape = UploadShape::Texture {
+            origin,
+            width: size[0],
+            height: size[1],
+            bytes_per_row: size[0] * block_size,
+            mip_level,
+        };
+        self.textures.enqueue(key, priority, texture, shape, data)
+    }
+
+    /// Current configuration
+    pub fn config(&self) -> &UploadConfig {
+        &self.config
+    }
+
+    /// Drop a pending upload
+    pub fn cancel(&mut self, key: &str) {
+        self.buffers.cancel(key);
+        self.textures.cancel(key);
+    }
+
+    /// Change the priority of a pending upload
+    pub fn set_priority(&mut self, key: &str, priority: UploadPriority) {
+        self.buffers.set_priority(key, priority);
+        self.textures.set_priority(key, priority);
+    }
+
+    /// Recompute priorities of pending uploads; `None` keeps the current one
+    pub fn reprioritize(&mut self, mut priority_of: impl FnMut(&str) -> Option<UploadPriority>) {
+        self.buffers.reprioritize(&mut priority_of);
+        self.textures.reprioritize(&mut priority_of);
+    }
+
+    /// Bytes still to upload
+    pub fn pending_bytes(&self) -> usize {
+        self.buffers.pending_bytes() + self.textures.pending_bytes()
+    }
+
+    /// Uploads of the previous frame
+    pub fn last_frame(&self) -> FrameUploadStats {
+        self.last_frame
+    }
+
+    /// Turn budgeted scheduling on or off, keeping current frame times as the comparison baseline
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled == self.enabled {
+            return;
+        }
+        self.baseline = Some((self.enabled, FrameTimeStats::from_samples(&self.frame_times)));
+        self.frame_times.clear();
+        self.enabled = enabled;
+    }
+
+    /// Whether uploads are spread across frames
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Record how long a frame took
+    pub fn record_frame_time(&mut self, frame_time: Duration) {
+        self.frame_times.push_back(frame_time.as_secs_f32() * 1000.0);
+        while self.frame_times.len() > self.config.frame_time_window {
+            self.frame_times.pop_front();
+        }
+    }
+
+    /// Frame-time statistics with the current setting
+    pub fn frame_time_stats(&self) -> FrameTimeStats {
+        FrameTimeStats::from_samples(&self.frame_times)
+    }
+
+    /// Frame-time statistics before the last `set_enabled` change and whether scheduling was on then
+    pub fn baseline_frame_time_stats(&self) -> Option<(bool, FrameTimeStats)> {
+        self.baseline
+    }
+
+    /// Record this frame's uploads into `encoder`; call `finish` before submitting it
+    pub fn flush(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, encoder: &mut wgpu::CommandEncoder) -> FrameUploadStats {
+        let budget = if self.enabled { self.config.frame_budget_bytes } else { usize::MAX };
+        let mut stats = FrameUploadStats::default();
+
+        // Textures are usually what is visible; they get the budget first
+        let texture_chunks = self.textures.plan_frame(budget);
+        let spent: usize = texture_chunks.iter().map(|chunk| chunk.range.len()).sum();
+        let buffer_chunks = if spent < budget || texture_chunks.is_empty() {
+            self.buffers.plan_frame(budget - spent.min(budget))
+        } else {
+            Vec::new()
+        };
+
+        for chunk in &texture_chunks {
+            write_texture_chunk(queue, chunk);
+            stats.bytes += chunk.range.len();
+            stats.chunks += 1;
+            stats.completed += chunk.completes as usize;
+        }
+
+        for chunk in &buffer_chunks {
+            let UploadShape::Buffer { offset } = chunk.shape else {
+                continue;
+            };
+            let Some(size) = wgpu::BufferSize::new(chunk.range.len() as u64) else {
+                continue;
+            };
+            self.staging_belt
+                .write_buffer(encoder, &chunk.target, offset + chunk.range.start as u64, size, device)
+                .copy_from_slice(&chunk.data[chunk.range.clone()]);
+            stats.bytes += chunk.range.len();
+            stats.chunks += 1;
+            stats.completed += chunk.completes as usize;
+        }
+
+        self.last_frame = stats;
+        stats
+    }
+
+    /// Close staging buffers written this frame; call before submitting the encoder
+    pub fn finish(&mut self) {
+        self.staging_belt.finish();
+    }
+
+    /// Reclaim staging buffers for reuse; call after submitting the encoder
+    pub fn recall(&mut self) {
+        self.staging_belt.recall();
+    }
+}
+
+impl Default for UploadScheduler {
+    fn default() -> Self {
+        Self::new(UploadConfig::default())
+    }
+}
+
+/// Write a band of texture rows
+fn write_texture_chunk(queue: &wgpu::Queue, chunk: &UploadChunk<Arc<wgpu::Texture>>) {
+    let UploadShape::Texture { origin, width, bytes_per_row, mip_level, .. } = chunk.shape else {
+        return;
+    };
+    let Some(rows) = chunk.rows() else {
+        return;
+    };
+
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture: &chunk.target,
+            mip_level,
+            origin: wgpu::Origin3d { x: origin[0], y: origin[1] + rows.start, z: 0 },
+            aspect: wgpu::TextureAspect::All,
+        },
+        &chunk.data[chunk.range.clone()],
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(bytes_per_row),
+            rows_per_image: None,
+        },
+        wgpu::Extent3d {
+            width,
+            height: rows.end - rows.start,
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texture_shape(height: u32) -> UploadShape {
+        UploadShape::Texture { origin: [0, 0], width: 256, height, bytes_per_row: 1024, mip_level: 0 }
+    }
+
+    #[test]
+    fn test_visible_uploads_first_within_budget() {
+        let mut queue: UploadQueue<()> = UploadQueue::new(UploadConfig {
+            max_chunk_bytes: 4096,
+            ..Default::default()
+        });
+        queue.enqueue("background", UploadPriority::Background, (), UploadShape::Buffer { offset: 0 }, vec![0u8; 8192]).unwrap();
+        queue.enqueue("thumbnail", UploadPriority::Visible, (), texture_shape(6), vec![0u8; 6144]).unwrap();
+
+        // A 5000 byte budget fits four texture rows
+        let chunks = queue.plan_frame(5000);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].key, "thumbnail");
+        assert_eq!(chunks[0].rows(), Some(0..4));
+        assert!(!chunks[0].completes);
+
+        let chunks = queue.plan_frame(5000);
+        assert_eq!(chunks[0].rows(), Some(4..6));
+        assert!(chunks[0].completes);
+        assert_eq!(chunks[1].key, "background");
+        assert_eq!(chunks[1].range, 0..2952);
+        assert_eq!(queue.pending_bytes(), 8192 - 2952);
+    }
+
+    #[test]
+    fn test_oversized_row_and_replacement() {
+        let mut queue: UploadQueue<()> = UploadQueue::new(UploadConfig::default());
+        assert!(queue.enqueue("bad", UploadPriority::Visible, (), UploadShape::Buffer { offset: 0 }, vec![0u8; 3]).is_err());
+
+        queue.enqueue("window", UploadPriority::Nearby, (), texture_shape(2), vec![0u8; 2048]).unwrap();
+        queue.enqueue("window", UploadPriority::Nearby, (), texture_shape(2), vec![1u8; 2048]).unwrap();
+        assert_eq!(queue.len(), 1);
+
+        // A budget smaller than one row still makes progress
+        let chunks = queue.plan_frame(100);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].range, 0..1024);
+        assert_eq!(chunks[0].data[0], 1);
+    }
+
+    #[test]
+    fn test_waiting_uploads_are_promoted() {
+        let mut queue: UploadQueue<()> = UploadQueue::new(UploadConfig {
+            promote_after_frames: 2,
+            ..Default::default()
+        });
+        queue.enqueue("late", UploadPriority::Background, (), UploadShape::Buffer { offset: 0 }, vec![0u8; 64]).unwrap();
+        for _ in 0..4 {
+            queue.enqueue("busy", UploadPriority::Visible, (), UploadShape::Buffer { offset: 0 }, vec![0u8; 64]).unwrap();
+            assert_eq!(queue.plan_frame(64)[0].key, "busy");
+        }
+
+        queue.enqueue("busy", UploadPriority::Visible, (), UploadShape::Buffer { offset: 0 }, vec![0u8; 64]).unwrap();
+        assert_eq!(queue.plan_frame(64)[0].key, "late");
+    }
+}
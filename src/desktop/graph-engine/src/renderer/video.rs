@@ -0,0 +1,697 @@
+//! Video frames on node surfaces
+//!
+//! Media nodes play video through GStreamer or PipeWire, which hand out
+//! decoded frames either as dmabufs, GPU buffers shared by file
+//! descriptor, or as plain memory. On Vulkan, a dmabuf frame is imported
+//! as a texture without copying its pixels: the buffer is bound as the
+//! memory of a Vulkan image, which is wrapped into a wgpu texture. Decoders
+//! recycle a small pool of buffers, so imports are kept per node and reused
+//! when a buffer comes back.
+//!
+//! Anything else is copied: memory frames always, and dmabufs when the
+//! device cannot import them (another backend, missing extensions, an
+//! unsupported format or modifier). Linear dmabufs can still be mapped and
+//! copied; tiled ones cannot and are dropped. Copies go through the upload
+//! scheduler, so a large window or video frame is spread over frames rather
+//! than stalling one. [`VideoStats`] counts frames per path, so it can be
+//! checked that playback stays zero-copy.
+
+use super::upload::{UploadPriority, UploadScheduler};
+use crate::{GraphEngineError, SceneId};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::os::fd::OwnedFd;
+use std::os::unix::fs::MetadataExt;
+use std::sync::Arc;
+use wgpu::Device;
+
+/// Imported buffers kept per node, enough for common decoder pools
+const MAX_IMPORTS_PER_NODE: usize = 8;
+
+/// DRM modifier of buffers laid out row by row
+pub const DRM_FORMAT_MOD_LINEAR: u64 = 0;
+
+const fn fourcc(code: &[u8; 4]) -> u32 {
+    u32::from_le_bytes(*code)
+}
+
+/// 32-bit ARGB, stored as B, G, R, A bytes
+pub const DRM_FORMAT_ARGB8888: u32 = fourcc(b"AR24");
+/// 32-bit RGB with an unused alpha byte, stored as B, G, R, X bytes
+pub const DRM_FORMAT_XRGB8888: u32 = fourcc(b"XR24");
+/// 32-bit ABGR, stored as R, G, B, A bytes
+pub const DRM_FORMAT_ABGR8888: u32 = fourcc(b"AB24");
+/// 32-bit BGR with an unused alpha byte, stored as R, G, B, X bytes
+pub const DRM_FORMAT_XBGR8888: u32 = fourcc(b"XB24");
+
+/// Texture format of a DRM format, for the single-plane formats video sinks can be asked for
+fn texture_format(drm_format: u32) -> Option<wgpu::TextureFormat> {
+    match drm_format {
+        DRM_FORMAT_ARGB8888 | DRM_FORMAT_XRGB8888 => Some(wgpu::TextureFormat::Bgra8UnormSrgb),
+        DRM_FORMAT_ABGR8888 | DRM_FORMAT_XBGR8888 => Some(wgpu::TextureFormat::Rgba8UnormSrgb),
+        _ => None,
+    }
+}
+
+/// One plane of a dmabuf
+#[derive(Debug)]
+pub struct DmabufPlane {
+    pub fd: OwnedFd,
+    /// Start of the plane in the buffer, in bytes
+    pub offset: u32,
+    /// Bytes per row
+    pub stride: u32,
+}
+
+/// A frame in GPU memory shared by file descriptor
+#[derive(Debug)]
+pub struct DmabufFrame {
+    pub width: u32,
+    pub height: u32,
+    /// DRM fourcc code
+    pub format: u32,
+    /// DRM format modifier describing the tiling
+    pub modifier: u64,
+    pub planes: Vec<DmabufPlane>,
+}
+
+/// A frame in memory
+#[derive(Debug, Clone)]
+pub struct MemoryFrame {
+    pub width: u32,
+    pub height: u32,
+    /// DRM fourcc code
+    pub format: u32,
+    /// Bytes per row
+    pub stride: u32,
+    pub data: Vec<u8>,
+}
+
+/// A decoded video frame
+#[derive(Debug)]
+pub enum VideoFrame {
+    Dmabuf(DmabufFrame),
+    Memory(MemoryFrame),
+}
+
+/// How a frame reached its texture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FramePath {
+    /// The frame's own buffer is sampled
+    ZeroCopy,
+    /// The pixels were copied into a texture
+    Copied,
+}
+
+/// Frame counters, to confirm which path playback takes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VideoStats {
+    pub zero_copy_frames: u64,
+    pub copied_frames: u64,
+    pub copied_bytes: u64,
+    /// Dmabufs newly imported, as opposed to reused from the node's pool
+    pub imports: u64,
+    /// Dmabuf imports the device rejected
+    pub import_failures: u64,
+    /// Frames that could neither be imported nor copied
+    pub dropped_frames: u64,
+}
+
+impl VideoStats {
+    /// Share of frames shown without a copy
+    pub fn zero_copy_ratio(&self) -> f32 {
+        let total = self.zero_copy_frames + self.copied_frames;
+        if total == 0 {
+            0.0
+        } else {
+            self.zero_copy_frames as f32 / total as f32
+        }
+    }
+}
+
+/// Texture showing the current frame of a node
+#[derive(Debug)]
+pub struct VideoTexture {
+    pub texture: Arc<wgpu::Texture>,
+    pub view: wgpu::TextureView,
+    pub width: u32,
+    pub height: u32,
+    pub path: FramePath,
+}
+
+impl VideoTexture {
+    fn new(texture: wgpu::Texture, path: FramePath) -> Self {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self {
+            width: texture.width(),
+            height: texture.height(),
+            texture: Arc::new(texture),
+            view,
+            path,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct VideoSurface {
+    /// Imported buffers by inode, most recently used last
+    imports: VecDeque<(u64, VideoTexture)>,
+    /// Texture memory frames are copied into
+    copy_target: Option<VideoTexture>,
+    /// Which of the two shows the current frame
+    current: Option<Current>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Current {
+    Import(u64),
+    Copy,
+}
+
+/// Textures of the video frames shown on nodes
+pub struct VideoTextures {
+    device: Arc<Device>,
+    importer: Option<vulkan::DmabufImporter>,
+    surfaces: HashMap<SceneId, VideoSurface>,
+    /// Format and modifier pairs the device failed to import
+    unsupported: HashSet<(u32, u64)>,
+    stats: VideoStats,
+}
+
+impl VideoTextures {
+    pub fn new(device: Arc<Device>) -> Self {
+        let importer = vulkan::DmabufImporter::new(&device);
+        if importer.is_none() {
+            log::info!("Dmabuf import unavailable, video frames will be copied");
+        }
+        Self {
+            device,
+            importer,
+            surfaces: HashMap::new(),
+            unsupported: HashSet::new(),
+            stats: VideoStats::default(),
+        }
+    }
+
+    /// Whether dmabuf frames can be shown without a copy
+    pub fn zero_copy_available(&self) -> bool {
+        self.importer.is_some()
+    }
+
+    pub fn stats(&self) -> VideoStats {
+        self.stats
+    }
+
+    /// Show a new frame on a node, queueing copied frames with `uploads`
+    pub fn submit(&mut self, node_id: SceneId, frame: VideoFrame, uploads: &mut UploadScheduler) -> Result<FramePath, GraphEngineError> {
+        let result = match frame {
+            VideoFrame::Dmabuf(frame) => self.submit_dmabuf(node_id, frame, uploads),
+            VideoFrame::Memory(frame) => self.copy_frame(node_id, &frame, uploads),
+        };
+        match result {
+            Ok(FramePath::ZeroCopy) => self.stats.zero_copy_frames += 1,
+            Ok(FramePath::Copied) => self.stats.copied_frames += 1,
+            Err(_) => self.stats.dropped_frames += 1,
+        }
+        result
+    }
+
+    /// Texture with the current frame of a node
+    pub fn texture(&self, node_id: SceneId) -> Option<&VideoTexture> {
+        let surface = self.surfaces.get(&node_id)?;
+        match surface.current? {
+            Current::Import(inode) => surface.imports.iter().find(|(key, _)| *key == inode).map(|(_, texture)| texture),
+            Current::Copy => surface.copy_target.as_ref(),
+        }
+    }
+
+    /// Release the textures of a node whose playback ended, dropping its frame still waiting for upload
+    pub fn remove(&mut self, node_id: SceneId, uploads: &mut UploadScheduler) {
+        self.surfaces.remove(&node_id);
+        uploads.cancel(&upload_key(node_id));
+    }
+
+    /// Move to a new device after device loss; nodes show video again with their next frame
+    pub fn recover(&mut self, device: Arc<Device>) {
+        let stats = self.stats;
+        *self = Self::new(device);
+        self.stats = stats;
+    }
+
+    fn submit_dmabuf(&mut self, node_id: SceneId, frame: DmabufFrame, uploads: &mut UploadScheduler) -> Result<FramePath, GraphEngineError> {
+        let key = (frame.format, frame.modifier);
+        if let (Some(importer), Some(format)) = (&self.importer, texture_format(frame.format)) {
+            if !self.unsupported.contains(&key) {
+                let inode = buffer_inode(&frame)?;
+                let surface = self.surfaces.entry(node_id).or_default();
+                if let Some(index) = surface.imports.iter().position(|(key, _)| *key == inode) {
+                    let reused = surface.imports.remove(index).expect("index found above");
+                    surface.imports.push_back(reused);
+                    surface.current = Some(Current::Import(inode));
+                    return Ok(FramePath::ZeroCopy);
+                }
+
+                match importer.import(&self.device, &frame, format) {
+                    Ok(texture) => {
+                        self.stats.imports += 1;
+                        if surface.imports.len() >= MAX_IMPORTS_PER_NODE {
+                            surface.imports.pop_front();
+                        }
+                        surface.imports.push_back((inode, VideoTexture::new(texture, FramePath::ZeroCopy)));
+                        surface.current = Some(Current::Import(inode));
+                        return Ok(FramePath::ZeroCopy);
+                    }
+                    Err(e) => {
+                        self.stats.import_failures += 1;
+                        log::warn!(
+                            "Dmabuf import of format {:#010x} with modifier {:#x} failed, copying instead: {}",
+                            frame.format, frame.modifier, e
+                        );
+                        self.unsupported.insert(key);
+                    }
+                }
+            }
+        }
+
+        let copied = map_linear_dmabuf(&frame)?;
+        self.copy_frame(node_id, &copied, uploads)
+    }
+
+    /// Queue a frame for upload into the node's copy target, recreating it
+    /// when the size or format changes
+    ///
+    /// A frame not uploaded yet is replaced by the next one of the node.
+    fn copy_frame(&mut self, node_id: SceneId, frame: &MemoryFrame, uploads: &mut UploadScheduler) -> Result<FramePath, GraphEngineError> {
+        let format = texture_format(frame.format)
+            .ok_or_else(|| GraphEngineError::RenderError(format!("Unsupported video frame format {:#010x}", frame.format)))?;
+        if frame.stride < frame.width * 4 || frame.data.len() < frame.stride as usize * frame.height as usize {
+            return Err(GraphEngineError::RenderError("Video frame is smaller than its size".into()));
+        }
+
+        let surface = self.surfaces.entry(node_id).or_default();
+        let reusable = surface.copy_target.as_ref().is_some_and(|target| {
+            target.width == frame.width && target.height == frame.height && target.texture.format() == format
+        });
+        if !reusable {
+            let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Video Frame Copy"),
+                size: wgpu::Extent3d { width: frame.width, height: frame.height, depth_or_array_layers: 1 },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+            surface.copy_target = Some(VideoTexture::new(texture, FramePath::Copied));
+        }
+        let target = surface.copy_target.as_ref().expect("copy target created above");
+
+        uploads.upload_texture(
+            upload_key(node_id),
+            UploadPriority::Visible,
+            target.texture.clone(),
+            [0, 0],
+            [frame.width, frame.height],
+            0,
+            packed_rows(frame),
+        )?;
+        surface.current = Some(Current::Copy);
+        self.stats.copied_bytes += frame.width as u64 * frame.height as u64 * 4;
+        Ok(FramePath::Copied)
+    }
+}
+
+/// Upload scheduler key of a node's copied frames
+fn upload_key(node_id: SceneId) -> String {
+    format!("video-frame-{}", node_id)
+}
+
+/// Pixels of a frame without the padding at the end of its rows, as uploads expect
+fn packed_rows(frame: &MemoryFrame) -> Vec<u8> {
+    let row = frame.width as usize * 4;
+    let stride = frame.stride as usize;
+    if stride == row {
+        return frame.data[..row * frame.height as usize].to_vec();
+    }
+    frame.data
+        .chunks(stride)
+        .take(frame.height as usize)
+        .flat_map(|line| &line[..row])
+        .copied()
+        .collect()
+}
+
+/// Inode of a dmabuf, which stays the same while a decoder recycles the buffer
+fn buffer_inode(frame: &DmabufFrame) -> Result<u64, GraphEngineError> {
+    let plane = frame.planes.first()
+        .ok_or_else(|| GraphEngineError::RenderError("Dmabuf frame has no planes".into()))?;
+    let file = std::fs::File::from(plane.fd.try_clone()?);
+    Ok(file.metadata()?.ino())
+}
+
+/// Read a linear single-plane dmabuf into memory
+fn map_linear_dmabuf(frame: &DmabufFrame) -> Result<MemoryFrame, GraphEngineError> {
+    let [plane] = frame.planes.as_slice() else {
+        return Err(GraphEngineError::RenderError(format!("Cannot copy a dmabuf with {} planes", frame.planes.len())));
+    };
+    if frame.modifier != DRM_FORMAT_MOD_LINEAR {
+        return Err(GraphEngineError::RenderError(format!("Cannot copy a dmabuf with tiling modifier {:#x}", frame.modifier)));
+    }
+
+    let start = plane.offset as usize;
+    let length = plane.stride as usize * frame.height as usize;
+    let file = std::fs::File::from(plane.fd.try_clone()?);
+    // SAFETY: the mapping is read once right away; a producer writing to
+    // the buffer meanwhile only tears the frame
+    let map = unsafe { memmap2::MmapOptions::new().offset(start as u64).len(length).map(&file)? };
+    Ok(MemoryFrame {
+        width: frame.width,
+        height: frame.height,
+        format: frame.format,
+        stride: plane.stride,
+        data: map.to_vec(),
+    })
+}
+
+/// Device opening and dmabuf import through wgpu's Vulkan backend
+mod vulkan {
+    use super::DmabufFrame;
+    use crate::GraphEngineError;
+    use ash::extensions::khr;
+    use ash::vk;
+    use std::ffi::{c_char, CStr};
+    use std::os::fd::{FromRawFd, IntoRawFd, OwnedFd};
+    use wgpu_hal::api::Vulkan;
+
+    /// Device extensions needed to import dmabufs
+    fn dmabuf_extensions() -> [&'static CStr; 3] {
+        [
+            khr::ExternalMemoryFd::name(),
+            vk::ExtExternalMemoryDmaBufFn::name(),
+            vk::ExtImageDrmFormatModifierFn::name(),
+        ]
+    }
+
+    fn vk_error(action: &str, result: vk::Result) -> GraphEngineError {
+        GraphEngineError::RenderError(format!("{} failed: {}", action, result))
+    }
+
+    /// Open a device on a Vulkan adapter with the dmabuf extensions enabled
+    ///
+    /// Returns `None` if the adapter is not Vulkan or lacks the extensions,
+    /// in which case the device is opened the usual way.
+    pub(crate) fn open_device(
+        adapter: &wgpu::Adapter,
+        descriptor: &wgpu::DeviceDescriptor<'_>,
+    ) -> Option<Result<(wgpu::Device, wgpu::Queue), GraphEngineError>> {
+        // SAFETY: the raw device is created from this adapter, on the queue
+        // family wgpu itself uses, with the features wgpu computes for the
+        // extensions, which are a superset of the ones it requires
+        let opened = unsafe {
+            adapter.as_hal::<Vulkan, _, _>(|hal_adapter| {
+                let hal_adapter = hal_adapter?;
+                let instance = hal_adapter.shared_instance().raw_instance();
+                let physical_device = hal_adapter.raw_physical_device();
+                let available = instance.enumerate_device_extension_properties(physical_device).ok()?;
+                let supported = |name: &CStr| {
+                    available.iter().any(|extension| CStr::from_ptr(extension.extension_name.as_ptr()) == name)
+                };
+                if !dmabuf_extensions().into_iter().all(supported) {
+                    return None;
+                }
+
+                let features = descriptor.required_features;
+                let mut extensions = hal_adapter.required_device_extensions(features);
+                let image_format_list = vk::KhrImageFormatListFn::name();
+                let optional = supported(image_format_list).then_some(image_format_list);
+                for extension in dmabuf_extensions().into_iter().chain(optional) {
+                    if !extensions.contains(&extension) {
+                        extensions.push(extension);
+                    }
+                }
+                let mut physical_features = hal_adapter.physical_device_features(&extensions, features);
+
+                // wgpu opens its devices on the first queue family too
+                let family_index = 0;
+                let family_infos = [vk::DeviceQueueCreateInfo::builder()
+                    .queue_family_index(family_index)
+                    .queue_priorities(&[1.0])
+                    .build()];
+                let names: Vec<*const c_char> = extensions.iter().map(|name| name.as_ptr()).collect();
+                let info = physical_features
+                    .add_to_device_create_builder(
+                        vk::DeviceCreateInfo::builder()
+                            .queue_create_infos(&family_infos)
+                            .enabled_extension_names(&names),
+                    )
+                    .build();
+                let raw_device = match instance.create_device(physical_device, &info, None) {
+                    Ok(raw_device) => raw_device,
+                    Err(e) => return Some(Err(vk_error("Creating a device with dmabuf import", e))),
+                };
+                Some(
+                    hal_adapter
+                        .device_from_raw(raw_device, true, &extensions, features, family_index, 0)
+                        .map_err(|e| GraphEngineError::RenderError(format!("Wrapping the Vulkan device failed: {}", e))),
+                )
+            })
+        }?;
+
+        Some(opened.and_then(|hal_device| {
+            // SAFETY: the device was opened from this adapter with the descriptor's features
+            unsafe { adapter.create_device_from_hal(hal_device, descriptor, None) }.map_err(GraphEngineError::from)
+        }))
+    }
+
+    /// Imports dmabufs on a device opened by [`open_device`]
+    pub(crate) struct DmabufImporter;
+
+    impl DmabufImporter {
+        /// `None` unless the device is Vulkan with the dmabuf extensions enabled
+        pub(crate) fn new(device: &wgpu::Device) -> Option<Self> {
+            // SAFETY: only the list of enabled extensions is read
+            let enabled = unsafe {
+                device.as_hal::<Vulkan, _, _>(|hal_device| {
+                    hal_device.is_some_and(|hal_device| {
+                        let enabled = hal_device.enabled_device_extensions();
+                        dmabuf_extensions().iter().all(|extension| enabled.contains(extension))
+                    })
+                })
+            };
+            enabled.unwrap_or(false).then_some(Self)
+        }
+
+        /// Wrap a dmabuf into a texture sampling the buffer directly
+        pub(crate) fn import(
+            &self,
+            device: &wgpu::Device,
+            frame: &DmabufFrame,
+            format: wgpu::TextureFormat,
+        ) -> Result<wgpu::Texture, GraphEngineError> {
+            let vk_format = match format {
+                wgpu::TextureFormat::Bgra8UnormSrgb => vk::Format::B8G8R8A8_SRGB,
+                wgpu::TextureFormat::Rgba8UnormSrgb => vk::Format::R8G8B8A8_SRGB,
+                other => return Err(GraphEngineError::RenderError(format!("No Vulkan format for {:?}", other))),
+            };
+            let [plane] = frame.planes.as_slice() else {
+                return Err(GraphEngineError::RenderError(format!("Cannot import a dmabuf with {} planes", frame.planes.len())));
+            };
+            let size = wgpu::Extent3d { width: frame.width, height: frame.height, depth_or_array_layers: 1 };
+
+            // SAFETY: the image is created, backed and bound on wgpu's own
+            // device and matches the descriptors it is wrapped with; the drop
+            // guard frees it once wgpu is done with the texture
+            let hal_texture = unsafe {
+                device.as_hal::<Vulkan, _, _>(|hal_device| {
+                    let hal_device = hal_device.ok_or_else(|| GraphEngineError::RenderError("Device is not Vulkan".into()))?;
+                    let image = import_image(hal_device, frame, plane, vk_format)?;
+                    let descriptor = wgpu_hal::TextureDescriptor {
+                        label: Some("Video Frame Import"),
+                        size,
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: wgpu::TextureDimension::D2,
+                        format,
+                        usage: wgpu_hal::TextureUses::RESOURCE,
+                        memory_flags: wgpu_hal::MemoryFlags::empty(),
+                        view_formats: Vec::new(),
+                    };
+                    let raw = image.image;
+                    Ok::<_, GraphEngineError>(wgpu_hal::vulkan::Device::texture_from_raw(raw, &descriptor, Some(Box::new(image))))
+                })
+            }
+            .ok_or_else(|| GraphEngineError::RenderError("Device is not backed by wgpu-core".into()))??;
+
+            // SAFETY: the texture was created on this device with this descriptor
+            Ok(unsafe {
+                device.create_texture_from_hal::<Vulkan>(
+                    hal_texture,
+                    &wgpu::TextureDescriptor {
+                        label: Some("Video Frame Import"),
+                        size,
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: wgpu::TextureDimension::D2,
+                        format,
+                        usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                        view_formats: &[],
+                    },
+                )
+            })
+        }
+    }
+
+    /// A Vulkan image backed by imported dmabuf memory
+    struct ImportedImage {
+        device: ash::Device,
+        image: vk::Image,
+        memory: vk::DeviceMemory,
+    }
+
+    impl Drop for ImportedImage {
+        fn drop(&mut self) {
+            // SAFETY: wgpu drops the guard after its last use of the image
+            unsafe {
+                self.device.destroy_image(self.image, None);
+                self.device.free_memory(self.memory, None);
+            }
+        }
+    }
+
+    /// Create an image laid out like the dmabuf and bind the buffer as its memory
+    ///
+    /// # Safety
+    ///
+    /// The device must have the dmabuf extensions enabled.
+    unsafe fn import_image(
+        hal_device: &wgpu_hal::vulkan::Device,
+        frame: &DmabufFrame,
+        plane: &super::DmabufPlane,
+        format: vk::Format,
+    ) -> Result<ImportedImage, GraphEngineError> {
+        let device = hal_device.raw_device();
+        let instance = hal_device.shared_instance().raw_instance();
+
+        let layouts = [vk::SubresourceLayout {
+            offset: plane.offset as u64,
+            size: 0,
+            row_pitch: plane.stride as u64,
+            array_pitch: 0,
+            depth_pitch: 0,
+        }];
+        let mut modifier_info = vk::ImageDrmFormatModifierExplicitCreateInfoEXT::builder()
+            .drm_format_modifier(frame.modifier)
+            .plane_layouts(&layouts);
+        let mut external_info = vk::ExternalMemoryImageCreateInfo::builder()
+            .handle_types(vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT);
+        let image_info = vk::ImageCreateInfo::builder()
+            .image_type(vk::ImageType::TYPE_2D)
+            .format(format)
+            .extent(vk::Extent3D { width: frame.width, height: frame.height, depth: 1 })
+            .mip_levels(1)
+            .array_layers(1)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .tiling(vk::ImageTiling::DRM_FORMAT_MODIFIER_EXT)
+            .usage(vk::ImageUsageFlags::SAMPLED)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .push_next(&mut external_info)
+            .push_next(&mut modifier_info);
+        let image = device.create_image(&image_info, None).map_err(|e| vk_error("Creating the dmabuf image", e))?;
+
+        let requirements = device.get_image_memory_requirements(image);
+        let external_fd = khr::ExternalMemoryFd::new(instance, device);
+        let fd = match plane.fd.try_clone() {
+            Ok(fd) => fd.into_raw_fd(),
+            Err(e) => {
+                device.destroy_image(image, None);
+                return Err(e.into());
+            }
+        };
+        let memory_types = external_fd
+            .get_memory_fd_properties(vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT, fd)
+            .map(|properties| properties.memory_type_bits & requirements.memory_type_bits)
+            .unwrap_or(0);
+        if memory_types == 0 {
+            drop(OwnedFd::from_raw_fd(fd));
+            device.destroy_image(image, None);
+            return Err(GraphEngineError::RenderError("No memory type can hold the dmabuf".into()));
+        }
+
+        // A successful import takes ownership of the descriptor
+        let mut import_info = vk::ImportMemoryFdInfoKHR::builder()
+            .handle_type(vk::ExternalMemoryHandleTypeFlags::DMA_BUF_EXT)
+            .fd(fd);
+        let mut dedicated_info = vk::MemoryDedicatedAllocateInfo::builder().image(image);
+        let allocate_info = vk::MemoryAllocateInfo::builder()
+            .allocation_size(requirements.size)
+            .memory_type_index(memory_types.trailing_zeros())
+            .push_next(&mut import_info)
+            .push_next(&mut dedicated_info);
+        let memory = match device.allocate_memory(&allocate_info, None) {
+            Ok(memory) => memory,
+            Err(e) => {
+                drop(OwnedFd::from_raw_fd(fd));
+                device.destroy_image(image, None);
+                return Err(vk_error("Importing the dmabuf", e));
+            }
+        };
+        if let Err(e) = device.bind_image_memory(image, memory, 0) {
+            device.destroy_image(image, None);
+            device.free_memory(memory, None);
+            return Err(vk_error("Binding the dmabuf", e));
+        }
+
+        Ok(ImportedImage { device: device.clone(), image, memory })
+    }
+}
+
+pub(crate) use vulkan::open_device as open_device_with_dmabuf;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_dmabufs_are_read_for_copying() {
+        // A regular file stands in for a dmabuf; both are mapped the same way
+        let path = std::env::temp_dir().join(format!("video_frame_{}", std::process::id()));
+        let mut pixels = vec![0u8; 16];
+        pixels.extend((0..2 * 12).map(|i| i as u8));
+        std::fs::write(&path, &pixels).unwrap();
+        let fd = OwnedFd::from(std::fs::File::open(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        let mut frame = DmabufFrame {
+            width: 2,
+            height: 2,
+            format: DRM_FORMAT_XRGB8888,
+            modifier: DRM_FORMAT_MOD_LINEAR,
+            planes: vec![DmabufPlane { fd, offset: 16, stride: 12 }],
+        };
+        let copied = map_linear_dmabuf(&frame).unwrap();
+        assert_eq!(copied.stride, 12);
+        assert_eq!(copied.data, (0..24).collect::<Vec<u8>>());
+        assert_eq!(texture_format(copied.format), Some(wgpu::TextureFormat::Bgra8UnormSrgb));
+
+        // Tiled buffers cannot be read row by row
+        frame.modifier = 0x0100_0000_0000_0001;
+        assert!(map_linear_dmabuf(&frame).is_err());
+    }
+
+    #[test]
+    fn test_copied_frames_drop_row_padding() {
+        let padded = MemoryFrame {
+            width: 2,
+            height: 2,
+            format: DRM_FORMAT_XRGB8888,
+            stride: 12,
+            data: (0..24).collect(),
+        };
+        let rows: Vec<u8> = (0..8).chain(12..20).collect();
+        assert_eq!(packed_rows(&padded), rows);
+
+        let tight = MemoryFrame { stride: 8, data: (0..20).collect(), ..padded };
+        assert_eq!(packed_rows(&tight), (0..16).collect::<Vec<u8>>());
+    }
+}
@@ -0,0 +1,70 @@
+//! Skipping culled nodes and edges when filling the draw buffers
+//!
+//! Culling runs outside the engine, e.g. in the performance crate's
+//! `CullingSystem`, and hands the renderer the nodes that survived it.
+//! Other nodes are left out of the instance buffer, and edges whose ends
+//! were both culled are left out of the vertex buffer. An edge with one end
+//! on screen is still drawn, since it crosses the view.
+
+use crate::SceneId;
+use std::collections::HashSet;
+
+/// Nodes that survived culling this frame
+#[derive(Debug, Clone, Default)]
+pub struct VisibleNodes {
+    /// `None` draws every node, e.g. before culling has run
+    nodes: Option<HashSet<SceneId>>,
+}
+
+impl VisibleNodes {
+    /// Draw every node
+    pub fn all() -> Self {
+        Self { nodes: None }
+    }
+
+    /// Draw only the given nodes
+    pub fn only(nodes: impl IntoIterator<Item = SceneId>) -> Self {
+        Self { nodes: Some(nodes.into_iter().collect()) }
+    }
+
+    /// Whether culling results are applied
+    pub fn is_culling(&self) -> bool {
+        self.nodes.is_some()
+    }
+
+    pub fn contains(&self, id: SceneId) -> bool {
+        self.nodes.as_ref().is_none_or(|nodes| nodes.contains(&id))
+    }
+
+    /// Whether an edge between two nodes is drawn
+    pub fn contains_edge(&self, source: SceneId, target: SceneId) -> bool {
+        self.contains(source) || self.contains(target)
+    }
+}
+
+/// What the last frame drew and skipped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrawStats {
+    pub nodes_drawn: u32,
+    /// Visible scene nodes left out because culling rejected them
+    pub nodes_culled: u32,
+    /// Instanced draws issued for nodes, one per batch
+    pub node_draw_calls: u32,
+    pub edges_drawn: u32,
+    /// Edges left out because both their ends were culled
+    pub edges_culled: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edges_with_one_visible_end_are_drawn() {
+        let visible = VisibleNodes::only([1, 2]);
+        assert!(visible.contains(1) && !visible.contains(3));
+        assert!(visible.contains_edge(2, 3));
+        assert!(!visible.contains_edge(3, 4));
+        assert!(VisibleNodes::all().contains_edge(3, 4));
+    }
+}
@@ -0,0 +1,528 @@
+//! Edge routing around node bodies and cluster hulls
+//!
+//! Straight edges are kept when they clear every obstacle. Edges that would
+//! pass through an unrelated node or cluster are bent with a force-based
+//! router: control points along the edge are pushed out of obstacles and
+//! pulled towards their neighbours for smoothness, then a Catmull-Rom curve
+//! is fitted through them.
+//!
+//! Routing is incremental. Only edges whose endpoints moved, or whose route
+//! passes near a node that moved, are recomputed, and at most a budget of
+//! edges is routed per update so large moves settle over a few frames.
+
+use crate::{Position, Scene, SceneId};
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Edge routing settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EdgeRoutingConfig {
+    /// Bend edges around obstacles; straight edges when disabled
+    pub enabled: bool,
+    /// Quality/performance trade-off from 0.0 (fast) to 1.0 (smooth)
+    pub quality: f32,
+    /// Space kept around node bodies, as a fraction of the node radius
+    pub clearance: f32,
+}
+
+impl Default for EdgeRoutingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            quality: 0.5,
+            clearance: 0.5,
+        }
+    }
+}
+
+impl EdgeRoutingConfig {
+    /// Interior control points per bent edge
+    fn control_points(&self) -> usize {
+        2 + (self.quality() * 8.0).round() as usize
+    }
+
+    /// Relaxation iterations per bent edge
+    fn iterations(&self) -> usize {
+        4 + (self.quality() * 28.0).round() as usize
+    }
+
+    /// Curve samples between two control points
+    fn samples_per_segment(&self) -> usize {
+        2 + (self.quality() * 6.0).round() as usize
+    }
+
+    /// Edges routed per update
+    fn budget(&self) -> usize {
+        64 + (self.quality().powi(2) * 1984.0) as usize
+    }
+
+    fn quality(&self) -> f32 {
+        self.quality.clamp(0.0, 1.0)
+    }
+}
+
+/// A cluster hull edges should not cut through
+#[derive(Debug, Clone)]
+pub struct HullObstacle {
+    /// Hull centre
+    pub center: Position,
+    /// Radius of a sphere enclosing the hull
+    pub radius: f32,
+    /// Nodes in the cluster; edges touching a member may enter the hull
+    pub members: HashSet<SceneId>,
+}
+
+/// Path of a routed edge
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeRoute {
+    /// Polyline from source to target
+    pub points: Vec<Position>,
+    /// Points the curve passes through, from source to target
+    pub controls: Vec<Position>,
+    /// Whether the route bends around an obstacle
+    pub bent: bool,
+}
+
+impl EdgeRoute {
+    fn straight(a: Position, b: Position) -> Self {
+        Self { points: vec![a, b], controls: vec![a, b], bent: false }
+    }
+
+    /// The curve as cubic Bézier segments, for drawing it at any resolution
+    pub fn cubic_segments(&self) -> Vec<[Position; 4]> {
+        let points = &self.controls;
+        let at = |i: isize| points[i.clamp(0, points.len() as isize - 1) as usize];
+        (0..points.len().saturating_sub(1) as isize)
+            .map(|i| {
+                let (p0, p1, p2, p3) = (at(i - 1), at(i), at(i + 1), at(i + 2));
+                [p1, p1 + (p2 - p0) / 6.0, p2 - (p3 - p1) / 6.0, p2]
+            })
+            .collect()
+    }
+
+    /// Axis-aligned bounds of the route
+    fn bounds(&self) -> (Position, Position) {
+        let mut min = self.points[0];
+        let mut max = self.points[0];
+        for p in &self.points[1..] {
+            min = min.inf(p);
+            max = max.sup(p);
+        }
+        (min, max)
+    }
+}
+
+/// Counters from the last update
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RoutingStats {
+    /// Edges recomputed in the last update
+    pub routed: usize,
+    /// Edges still waiting to be recomputed
+    pub pending: usize,
+    /// Edges currently bent around obstacles
+    pub bent: usize,
+}
+
+/// Keeps routes for the scene's edges up to date
+#[derive(Debug, Default)]
+pub struct EdgeRouter {
+    config: EdgeRoutingConfig,
+    routes: HashMap<SceneId, EdgeRoute>,
+    /// Node position and radius at the last update
+    nodes: HashMap<SceneId, (Position, f32)>,
+    /// Edge endpoints at the last update
+    endpoints: HashMap<SceneId, (SceneId, SceneId)>,
+    dirty: VecDeque<SceneId>,
+    dirty_set: HashSet<SceneId>,
+    hulls: Vec<HullObstacle>,
+    stats: RoutingStats,
+}
+
+impl EdgeRouter {
+    /// Create a router with the given settings
+    pub fn new(config: EdgeRoutingConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Current settings
+    pub fn config(&self) -> &EdgeRoutingConfig {
+        &self.config
+    }
+
+    /// Change settings; all edges are routed again
+    pub fn set_config(&mut self, config: EdgeRoutingConfig) {
+        self.config = config;
+        self.invalidate_all();
+    }
+
+    /// Replace the cluster hulls to avoid; all edges are routed again
+    pub fn set_hulls(&mut self, hulls: Vec<HullObstacle>) {
+        self.hulls = hulls;
+        self.invalidate_all();
+    }
+
+    /// Route of an edge, if it has been computed
+    pub fn route(&self, edge_id: SceneId) -> Option<&EdgeRoute> {
+        self.routes.get(&edge_id)
+    }
+
+    /// Counters from the last update
+    pub fn stats(&self) -> RoutingStats {
+        self.stats
+    }
+
+    /// Recompute routes for everything on the next updates
+    pub fn invalidate_all(&mut self) {
+        self.nodes.clear();
+        self.endpoints.clear();
+    }
+
+    /// Bring routes in line with the scene, within the per-update budget
+    pub fn update(&mut self, scene: &Scene) {
+        self.update_from(
+            scene.nodes().filter(|(_, node)| node.visible).map(|(id, node)| (*id, node.position, node.radius)),
+            scene.edges().filter(|edge| edge.visible).map(|edge| (edge.id, edge.source, edge.target)),
+        );
+    }
+
+    /// Bring routes in line with the given nodes, as `(id, position, radius)`,
+    /// and edges, as `(id, source, target)`, within the per-update budget
+    pub fn update_from(
+        &mut self,
+        nodes: impl IntoIterator<Item = (SceneId, Position, f32)>,
+        edges: impl IntoIterator<Item = (SceneId, SceneId, SceneId)>,
+    ) {
+        if !self.config.enabled {
+            self.routes.clear();
+            self.nodes.clear();
+            self.endpoints.clear();
+            self.dirty.clear();
+            self.dirty_set.clear();
+            self.stats = RoutingStats::default();
+            return;
+        }
+
+        // Nodes that moved, appeared or disappeared, with old and new positions
+        let mut moved: Vec<(SceneId, Position, f32)> = Vec::new();
+        let mut seen = HashSet::new();
+        for (id, node_position, node_radius) in nodes {
+            seen.insert(id);
+            let current = (node_position, node_radius);
+            match self.nodes.insert(id, current) {
+                Some(previous) if previous == current => {}
+                Some((position, radius)) => {
+                    moved.push((id, position, radius));
+                    moved.push((id, node_position, node_radius));
+                }
+                None => moved.push((id, node_position, node_radius)),
+            }
+        }
+        self.nodes.retain(|id, (position, radius)| {
+            let keep = seen.contains(id);
+            if !keep {
+                moved.push((*id, *position, *radius));
+            }
+            keep
+        });
+
+        // Edges that appeared, changed endpoints or disappeared
+        let mut edge_ids = HashSet::new();
+        for (edge_id, source, target) in edges {
+            edge_ids.insert(edge_id);
+            if self.endpoints.insert(edge_id, (source, target)) != Some((source, target)) {
+                self.mark_dirty(edge_id);
+            }
+        }
+        self.endpoints.retain(|id, _| edge_ids.contains(id));
+        self.routes.retain(|id, _| edge_ids.contains(id));
+
+        if !moved.is_empty() {
+            let moved_ids: HashSet<SceneId> = moved.iter().map(|(id, _, _)| *id).collect();
+            // When most of the graph moved, checking each route is not worth it
+            let check_routes = moved_ids.len() * 2 <= self.nodes.len();
+            let affected: Vec<SceneId> = self
+                .endpoints
+                .iter()
+                .filter(|(edge_id, (source, target))| {
+                    if !check_routes || moved_ids.contains(source) || moved_ids.contains(target) {
+                        return true;
+                    }
+                    let Some(route) = self.routes.get(edge_id) else {
+                        return true;
+                    };
+                    let (min, max) = route.bounds();
+                    moved.iter().any(|(_, position, radius)| {
+                        let reach = radius * (1.0 + self.config.clearance);
+                        (0..3).all(|axis| position[axis] + reach >= min[axis] && position[axis] - reach <= max[axis])
+                    })
+                })
+                .map(|(edge_id, _)| *edge_id)
+                .collect();
+            for edge_id in affected {
+                self.mark_dirty(edge_id);
+            }
+        }
+
+        if self.dirty.is_empty() {
+            self.stats.routed = 0;
+            return;
+        }
+
+        let obstacles = ObstacleGrid::new(&self.nodes, self.config.clearance);
+        let mut routed = 0;
+        while routed < self.config.budget() {
+            let Some(edge_id) = self.dirty.pop_front() else {
+                break;
+            };
+            self.dirty_set.remove(&edge_id);
+            let Some(&(source, target)) = self.endpoints.get(&edge_id) else {
+                continue;
+            };
+            let (Some(&(a, _)), Some(&(b, _))) = (self.nodes.get(&source), self.nodes.get(&target)) else {
+                self.routes.remove(&edge_id);
+                continue;
+            };
+            let route = self.route_edge(source, target, a, b, &obstacles);
+            self.routes.insert(edge_id, route);
+            routed += 1;
+        }
+
+        self.stats = RoutingStats {
+            routed,
+            pending: self.dirty.len(),
+            bent: self.routes.values().filter(|route| route.bent).count(),
+        };
+    }
+
+    fn mark_dirty(&mut self, edge_id: SceneId) {
+        if self.dirty_set.insert(edge_id) {
+            self.dirty.push_back(edge_id);
+        }
+    }
+
+    /// Compute the route for one edge
+    fn route_edge(&self, source: SceneId, target: SceneId, a: Position, b: Position, grid: &ObstacleGrid) -> EdgeRoute {
+        let length = (b - a).norm();
+        if length <= f32::EPSILON {
+            return EdgeRoute::straight(a, b);
+        }
+
+        // Obstacles near the edge, excluding its own endpoints and the hulls it belongs to
+        let margin = grid.max_radius.max(self.hulls.iter().map(|h| h.radius).fold(0.0, f32::max));
+        let mut obstacles: Vec<(Position, f32)> = grid
+            .query(a.inf(&b) - Vector3::repeat(margin), a.sup(&b) + Vector3::repeat(margin))
+            .filter(|(id, _, _)| *id != source && *id != target)
+            .map(|(_, center, radius)| (center, radius))
+            .collect();
+        obstacles.extend(
+            self.hulls
+                .iter()
+                .filter(|hull| !hull.members.contains(&source) && !hull.members.contains(&target))
+                .map(|hull| (hull.center, hull.radius)),
+        );
+
+        if obstacles.iter().all(|(center, radius)| segment_distance(a, b, *center) >= *radius) {
+            return EdgeRoute::straight(a, b);
+        }
+
+        // Relax control points out of the obstacles
+        let count = self.config.control_points();
+        let direction = (b - a) / length;
+        let fallback = any_perpendicular(&direction);
+        let mut points: Vec<Position> = (0..count + 2).map(|i| a + (b - a) * (i as f32 / (count + 1) as f32)).collect();
+        for _ in 0..self.config.iterations() {
+            for i in 1..=count {
+                let mut push = Vector3::zeros();
+                for (center, radius) in &obstacles {
+                    let offset = points[i] - center;
+                    let distance = offset.norm();
+                    if distance < *radius {
+                        // Push sideways so points do not just slide along the edge
+                        let sideways = offset - direction * offset.dot(&direction);
+                        let away = if sideways.norm() > 1e-4 { sideways.normalize() } else { fallback };
+                        push += away * (radius - distance);
+                    }
+                }
+                let smooth = (points[i - 1].coords + points[i + 1].coords) * 0.5 - points[i].coords;
+                points[i] += push + smooth * 0.3;
+            }
+        }
+
+        EdgeRoute {
+            points: catmull_rom(&points, self.config.samples_per_segment()),
+            controls: points,
+            bent: true,
+        }
+    }
+}
+
+/// Obstacle id, centre and padded radius
+type Obstacle = (SceneId, Position, f32);
+
+/// Node obstacles bucketed in a uniform grid
+struct ObstacleGrid {
+    cell: f32,
+    cells: HashMap<(i32, i32, i32), Vec<Obstacle>>,
+    max_radius: f32,
+    count: usize,
+}
+
+impl ObstacleGrid {
+    fn new(nodes: &HashMap<SceneId, (Position, f32)>, clearance: f32) -> Self {
+        let scale = 1.0 + clearance.max(0.0);
+        let max_radius = nodes.values().map(|(_, radius)| radius * scale).fold(0.0, f32::max);
+        let cell = (max_radius * 4.0).max(1.0);
+        let mut cells: HashMap<_, Vec<_>> = HashMap::new();
+        for (id, (position, radius)) in nodes {
+            cells.entry(Self::key(cell, position)).or_default().push((*id, *position, radius * scale));
+        }
+        Self { cell, cells, max_radius, count: nodes.len() }
+    }
+
+    fn key(cell: f32, position: &Position) -> (i32, i32, i32) {
+        (
+            (position.x / cell).floor() as i32,
+            (position.y / cell).floor() as i32,
+            (position.z / cell).floor() as i32,
+        )
+    }
+
+    /// Obstacles whose centre lies in the box
+    fn query(&self, min: Position, max: Position) -> Box<dyn Iterator<Item = Obstacle> + '_> {
+        let (x0, y0, z0) = Self::key(self.cell, &min);
+        let (x1, y1, z1) = Self::key(self.cell, &max);
+        let span = |lo: i32, hi: i32| (hi - lo + 1).max(0) as usize;
+        let inside = move |p: &Position| (0..3).all(|axis| p[axis] >= min[axis] && p[axis] <= max[axis]);
+        // Scanning every node is cheaper than visiting mostly empty cells
+        if span(x0, x1).saturating_mul(span(y0, y1)).saturating_mul(span(z0, z1)) > self.count {
+            return Box::new(self.cells.values().flatten().copied().filter(move |(_, p, _)| inside(p)));
+        }
+        Box::new(
+            (x0..=x1)
+                .flat_map(move |x| (y0..=y1).flat_map(move |y| (z0..=z1).map(move |z| (x, y, z))))
+                .filter_map(|key| self.cells.get(&key))
+                .flatten()
+                .copied()
+                .filter(move |(_, p, _)| inside(p)),
+        )
+    }
+}
+
+/// Distance from `point` to the segment `a`-`b`
+fn segment_distance(a: Position, b: Position, point: Position) -> f32 {
+    let ab = b - a;
+    let t = ((point - a).dot(&ab) / ab.norm_squared()).clamp(0.0, 1.0);
+    (point - (a + ab * t)).norm()
+}
+
+/// A unit vector perpendicular to `direction`
+fn any_perpendicular(direction: &Vector3<f32>) -> Vector3<f32> {
+    let axis = if direction.y.abs() < 0.9 { Vector3::y() } else { Vector3::x() };
+    direction.cross(&axis).normalize()
+}
+
+/// Sample a Catmull-Rom spline through `points`
+fn catmull_rom(points: &[Position], samples: usize) -> Vec<Position> {
+    let mut curve = Vec::with_capacity((points.len() - 1) * samples + 1);
+    let at = |i: isize| points[i.clamp(0, points.len() as isize - 1) as usize].coords;
+    for i in 0..points.len() - 1 {
+        let (p0, p1, p2, p3) = (at(i as isize - 1), at(i as isize), at(i as isize + 1), at(i as isize + 2));
+        for s in 0..samples {
+            let t = s as f32 / samples as f32;
+            let (t2, t3) = (t * t, t * t * t);
+            let point = (p1 * 2.0 + (p2 - p0) * t + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2 + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3) * 0.5;
+            curve.push(Position::from(point));
+        }
+    }
+    curve.push(points[points.len() - 1]);
+    curve
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EdgeType, NodeMetadata, NodeType, SceneEdge, SceneNode};
+    use nalgebra::Vector3;
+
+    fn node(scene: &mut Scene, x: f32) -> SceneId {
+        scene.add_node(SceneNode {
+            id: 0,
+            position: Position::new(x, 0.0, 0.0),
+            velocity: Vector3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type: NodeType::Concept { title: String::new(), content: String::new() },
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        })
+    }
+
+    fn edge(scene: &mut Scene, source: SceneId, target: SceneId) -> SceneId {
+        scene.add_edge(SceneEdge {
+            id: 0,
+            source,
+            target,
+            edge_type: EdgeType::Contains,
+            weight: 1.0,
+            color: [1.0; 4],
+            visible: true,
+            animated: false,
+        })
+    }
+
+    #[test]
+    fn test_edges_bend_around_nodes_in_the_way() {
+        let mut scene = Scene::new();
+        let a = node(&mut scene, 0.0);
+        let blocker = node(&mut scene, 5.0);
+        let b = node(&mut scene, 10.0);
+        let blocked = edge(&mut scene, a, b);
+        let clear = edge(&mut scene, a, blocker);
+
+        let mut router = EdgeRouter::new(EdgeRoutingConfig::default());
+        router.update(&scene);
+
+        let route = router.route(blocked).unwrap();
+        assert!(route.bent);
+        let center = scene.get_node(blocker).unwrap().position;
+        assert!(route.points.iter().all(|p| (p - center).norm() > 1.0));
+        assert_eq!(route.points.first(), Some(&Position::new(0.0, 0.0, 0.0)));
+        assert_eq!(route.points.last(), Some(&Position::new(10.0, 0.0, 0.0)));
+        assert!(!router.route(clear).unwrap().bent);
+
+        // Bézier segments join up and end at the edge's endpoints
+        let segments = route.cubic_segments();
+        assert_eq!(segments.len(), route.controls.len() - 1);
+        assert_eq!(segments[0][0], Position::new(0.0, 0.0, 0.0));
+        assert_eq!(segments[segments.len() - 1][3], Position::new(10.0, 0.0, 0.0));
+        assert!(segments.windows(2).all(|pair| pair[0][3] == pair[1][0]));
+    }
+
+    #[test]
+    fn test_only_affected_edges_are_rerouted() {
+        let mut scene = Scene::new();
+        let a = node(&mut scene, 0.0);
+        let b = node(&mut scene, 10.0);
+        let c = node(&mut scene, 100.0);
+        let d = node(&mut scene, 110.0);
+        edge(&mut scene, a, b);
+        edge(&mut scene, c, d);
+
+        let mut router = EdgeRouter::new(EdgeRoutingConfig::default());
+        router.update(&scene);
+        assert_eq!(router.stats().routed, 2);
+
+        router.update(&scene);
+        assert_eq!(router.stats().routed, 0);
+
+        scene.get_node_mut(d).unwrap().position.y = 3.0;
+        router.update(&scene);
+        assert_eq!(router.stats().routed, 1);
+    }
+}
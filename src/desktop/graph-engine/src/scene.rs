@@ -1,8 +1,11 @@
 //! Scene graph management for nodes and edges
 
+use crate::camera::Ray;
 use nalgebra::{Point3, Vector3};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+pub use crate::spatial::{BoundingBox, SpatialIndex};
 
 /// Unique identifier for scene objects
 pub type SceneId = u64;
@@ -22,6 +25,8 @@ pub struct Scene {
     edges: HashMap<SceneId, SceneEdge>,
     /// Spatial index for efficient queries
     spatial_index: SpatialIndex,
+    /// Nodes handed out mutably since the index was last refreshed, which may have moved
+    moved: HashSet<SceneId>,
     /// Next available ID
     next_id: SceneId,
 }
@@ -55,6 +60,8 @@ pub enum NodeType {
     Automation { name: String, automation_type: AutomationType, status: AutomationStatus },
     Setting { key: String, value: String, setting_type: SettingType, scope: SettingScope },
     ConfigGroup { name: String, config_type: ConfigType, items: Vec<String> },
+    /// A collapsed cluster standing in for its members
+    Group { name: String, member_count: usize },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -92,6 +99,8 @@ pub enum DeviceType {
     IoTDevice,
     NetworkDevice,
     Storage,
+    AudioOutput,
+    AudioInput,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -184,6 +193,42 @@ pub struct NodeMetadata {
     pub tags: Vec<String>,
     pub description: Option<String>,
     pub properties: HashMap<String, String>,
+    #[serde(default)]
+    pub privacy: NodePrivacy,
+    #[serde(default)]
+    pub persistence: NodePersistence,
+}
+
+/// What may read a node's content
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodePrivacy {
+    /// Hidden from the AI service: not used for pattern detection,
+    /// suggestions or agent memory
+    #[serde(default)]
+    pub ai_invisible: bool,
+}
+
+/// Which saves a node is written to
+///
+/// Subsystems declare the class when they create a node so that transient
+/// nodes, such as live device streams, do not pile up in saved graphs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodePersistence {
+    /// Never saved; gone when the compositor exits
+    Ephemeral,
+    /// Saved in the session journal only, so it survives a crash but not a fresh session
+    Session,
+    /// Saved with workspaces, archives and scene files
+    #[default]
+    Durable,
+}
+
+impl NodePersistence {
+    /// Whether a node of this class is written to a save of class `save`
+    pub fn is_saved_in(self, save: NodePersistence) -> bool {
+        self != NodePersistence::Ephemeral && self >= save
+    }
 }
 
 /// An edge connecting two nodes
@@ -212,19 +257,6 @@ pub enum EdgeType {
     WorksOn,
 }
 
-/// Spatial indexing for efficient queries
-#[derive(Debug, Default)]
-pub struct SpatialIndex {
-    // Simple implementation - can be replaced with more sophisticated structures
-    bounds: HashMap<SceneId, BoundingBox>,
-}
-
-#[derive(Debug, Clone)]
-pub struct BoundingBox {
-    pub min: Position,
-    pub max: Position,
-}
-
 impl Scene {
     /// Create a new empty scene
     pub fn new() -> Self {
@@ -234,23 +266,38 @@ impl Scene {
     /// Add a node to the scene
     pub fn add_node(&mut self, mut node: SceneNode) -> SceneId {
         node.id = self.next_id;
+        self.insert_node(node)
+    }
+    
+    /// Add a node with a persistence class
+    pub fn add_node_with_persistence(&mut self, mut node: SceneNode, persistence: NodePersistence) -> SceneId {
+        node.metadata.persistence = persistence;
+        self.add_node(node)
+    }
+    
+    /// Change which saves a node is written to, e.g. when the user pins a transient node
+    ///
+    /// Returns false if the node does not exist.
+    pub fn set_node_persistence(&mut self, id: SceneId, persistence: NodePersistence) -> bool {
+        match self.nodes.get_mut(&id) {
+            Some(node) => {
+                node.metadata.persistence = persistence;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Insert a node keeping its ID, e.g. to restore a removed node
+    ///
+    /// Replaces any node with the same ID.
+    pub fn insert_node(&mut self, node: SceneNode) -> SceneId {
         let id = node.id;
-        self.next_id += 1;
+        self.next_id = self.next_id.max(id + 1);
         
         // Update spatial index
-        let bbox = BoundingBox {
-            min: Point3::new(
-                node.position.x - node.radius,
-                node.position.y - node.radius,
-                node.position.z - node.radius,
-            ),
-            max: Point3::new(
-                node.position.x + node.radius,
-                node.position.y + node.radius,
-                node.position.z + node.radius,
-            ),
-        };
-        self.spatial_index.bounds.insert(id, bbox);
+        self.spatial_index.insert(id, node_bounds(&node));
+        self.moved.remove(&id);
         
         self.nodes.insert(id, node);
         id
@@ -259,8 +306,13 @@ impl Scene {
     /// Add an edge to the scene
     pub fn add_edge(&mut self, mut edge: SceneEdge) -> SceneId {
         edge.id = self.next_id;
+        self.insert_edge(edge)
+    }
+    
+    /// Insert an edge keeping its ID, e.g. to restore a removed edge
+    pub fn insert_edge(&mut self, edge: SceneEdge) -> SceneId {
         let id = edge.id;
-        self.next_id += 1;
+        self.next_id = self.next_id.max(id + 1);
         
         self.edges.insert(id, edge);
         id
@@ -272,8 +324,13 @@ impl Scene {
     }
     
     /// Get a mutable node by ID
+    ///
+    /// The node may be moved; its place in the spatial index is refreshed on
+    /// the next [`Scene::update`], and queries check it directly until then.
     pub fn get_node_mut(&mut self, id: SceneId) -> Option<&mut SceneNode> {
-        self.nodes.get_mut(&id)
+        let node = self.nodes.get_mut(&id)?;
+        self.moved.insert(id);
+        Some(node)
     }
     
     /// Get an edge by ID
@@ -281,6 +338,11 @@ impl Scene {
         self.edges.get(&id)
     }
     
+    /// Get a mutable edge by ID
+    pub fn get_edge_mut(&mut self, id: SceneId) -> Option<&mut SceneEdge> {
+        self.edges.get_mut(&id)
+    }
+    
     /// Get all nodes
     pub fn nodes(&self) -> impl Iterator<Item = (&SceneId, &SceneNode)> {
         self.nodes.iter()
@@ -295,23 +357,71 @@ impl Scene {
     pub fn update(&mut self, _delta_time: f32) {
         // Update node positions, animations, etc.
         // This will be expanded with actual animation logic
+        self.refresh_spatial_index();
+    }
+    
+    /// Re-index nodes that may have moved since the last refresh
+    pub fn refresh_spatial_index(&mut self) {
+        for id in self.moved.drain() {
+            if let Some(node) = self.nodes.get(&id) {
+                self.spatial_index.insert(id, node_bounds(node));
+            }
+        }
+    }
+    
+    /// Spatial index of node bounds
+    pub fn spatial_index(&self) -> &SpatialIndex {
+        &self.spatial_index
+    }
+    
+    /// Nodes whose bounds pass `overlaps`, see [`SpatialIndex::query`]
+    pub fn find_nodes_in_bounds(&self, mut overlaps: impl FnMut(&BoundingBox) -> bool) -> Vec<SceneId> {
+        let mut found: Vec<SceneId> = self.spatial_index
+            .query(&mut overlaps)
+            .into_iter()
+            .filter(|id| !self.moved.contains(id))
+            .collect();
+        found.extend(self.moved.iter().copied().filter(|id| {
+            self.nodes.get(id).is_some_and(|node| overlaps(&node_bounds(node)))
+        }));
+        found
     }
     
     /// Find nodes within a radius of a position
     pub fn find_nodes_in_radius(&self, center: Position, radius: f32) -> Vec<SceneId> {
-        self.nodes
-            .iter()
-            .filter_map(|(id, node)| {
-                let distance = (node.position - center).magnitude();
-                if distance <= radius {
-                    Some(*id)
-                } else {
-                    None
-                }
+        self.find_nodes_in_bounds(|bounds| bounds.intersects_sphere(center, radius))
+            .into_iter()
+            .filter(|id| (self.nodes[id].position - center).magnitude() <= radius)
+            .collect()
+    }
+    
+    /// Find nodes whose spheres come within `margin` of a node's sphere
+    pub fn find_overlapping_nodes(&self, node_id: SceneId, margin: f32) -> Vec<SceneId> {
+        let Some(node) = self.nodes.get(&node_id) else {
+            return Vec::new();
+        };
+        let area = node_bounds(node).expanded(margin);
+        self.find_nodes_in_bounds(|bounds| bounds.intersects(&area))
+            .into_iter()
+            .filter(|id| *id != node_id)
+            .filter(|id| {
+                let other = &self.nodes[id];
+                (other.position - node.position).magnitude() <= node.radius + other.radius + margin
             })
             .collect()
     }
     
+    /// Closest node hit by a ray, with the distance along the ray
+    pub fn ray_cast(&self, ray: &Ray) -> Option<(SceneId, f32)> {
+        self.find_nodes_in_bounds(|bounds| bounds.intersects_ray(ray))
+            .into_iter()
+            .filter_map(|id| {
+                let node = &self.nodes[&id];
+                ray.intersect_sphere(node.position, node.radius).map(|t| (id, t))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+    
     /// Get edges connected to a node
     pub fn get_connected_edges(&self, node_id: SceneId) -> Vec<&SceneEdge> {
         self.edges
@@ -339,12 +449,18 @@ impl Scene {
         }
         
         // Remove from spatial index
-        self.spatial_index.bounds.remove(&node_id);
+        self.spatial_index.remove(node_id);
+        self.moved.remove(&node_id);
         
         // Remove the node
         self.nodes.remove(&node_id)
     }
     
+    /// Remove a single edge
+    pub fn remove_edge(&mut self, edge_id: SceneId) -> Option<SceneEdge> {
+        self.edges.remove(&edge_id)
+    }
+    
     /// Get node position by ID
     pub fn get_node_position(&self, node_id: SceneId) -> Option<Position> {
         self.nodes.get(&node_id).map(|node| node.position)
@@ -364,11 +480,17 @@ impl Scene {
     pub fn clear(&mut self) {
         self.nodes.clear();
         self.edges.clear();
-        self.spatial_index.bounds.clear();
+        self.spatial_index.clear();
+        self.moved.clear();
         self.next_id = 0;
     }
 }
 
+/// Bounds of a node's sphere
+fn node_bounds(node: &SceneNode) -> BoundingBox {
+    BoundingBox::around(node.position, node.radius)
+}
+
 impl Default for NodeMetadata {
     fn default() -> Self {
         let now = chrono::Utc::now();
@@ -378,6 +500,8 @@ impl Default for NodeMetadata {
             tags: Vec::new(),
             description: None,
             properties: HashMap::new(),
+            privacy: NodePrivacy::default(),
+            persistence: NodePersistence::default(),
         }
     }
 }
\ No newline at end of file
@@ -7,8 +7,9 @@ struct EdgeData {
     strength: f32,
     source_pos: vec3<f32>,
     target_pos: vec3<f32>,
-    source_created_at: u64,
-    target_created_at: u64,
+    // Creation times in Unix seconds; WGSL has no 64-bit integers
+    source_created_at: u32,
+    target_created_at: u32,
     source_content_hash: u32,
     target_content_hash: u32,
     source_tag_hash: u32,
@@ -33,7 +34,7 @@ struct AnalysisConfig {
     max_analysis_distance: f32,
     min_edge_strength: f32,
     camera_pos: vec3<f32>,
-    current_time: u64,
+    current_time: u32,
 }
 
 @group(0) @binding(0)
@@ -53,8 +54,8 @@ fn hash_similarity(hash1: u32, hash2: u32) -> f32 {
 }
 
 // Calculate temporal similarity
-fn calculate_temporal_similarity(created1: u64, created2: u64, current_time: u64) -> f32 {
-    let time_diff = abs(i64(created1) - i64(created2));
+fn calculate_temporal_similarity(created1: u32, created2: u32, current_time: u32) -> f32 {
+    let time_diff = max(created1, created2) - min(created1, created2);
     let hours_diff = f32(time_diff) / 3600.0;
     let similarity = 1.0 / (1.0 + hours_diff / 24.0);
     return min(similarity, 1.0);
@@ -100,9 +101,10 @@ fn classify_relationship_type(edge_type: u32, temporal_score: f32, spatial_score
 }
 
 // Calculate directionality
-fn calculate_directionality(source_created: u64, target_created: u64) -> f32 {
-    let time_diff = i64(source_created) - i64(target_created);
-    let time_directionality = sign(f32(time_diff)) * tanh(abs(f32(time_diff)) / 3600.0);
+fn calculate_directionality(source_created: u32, target_created: u32) -> f32 {
+    let time_diff = f32(max(source_created, target_created) - min(source_created, target_created));
+    let direction = select(-1.0, 1.0, source_created > target_created);
+    let time_directionality = select(direction, 0.0, time_diff == 0.0) * tanh(time_diff / 3600.0);
     return time_directionality;
 }
 
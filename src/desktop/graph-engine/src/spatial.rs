@@ -0,0 +1,350 @@
+//! Octree over node bounds for radius, ray and frustum queries
+//!
+//! Each node is kept in the smallest cell that fully contains its bounds, so
+//! a cell's bounds cover everything below it and a query can skip whole
+//! subtrees. The root grows to fit nodes placed outside of it.
+
+use crate::camera::Ray;
+use crate::scene::{Position, SceneId};
+use nalgebra::{Point3, Vector3};
+use std::collections::HashMap;
+
+/// Nodes a leaf cell holds before it is split
+const MAX_CELL_ITEMS: usize = 8;
+/// Depth below which cells are no longer split
+const MAX_DEPTH: u32 = 12;
+/// Smallest half size of the root cell
+const MIN_ROOT_HALF_SIZE: f32 = 64.0;
+
+/// Axis-aligned bounding box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: Position,
+    pub max: Position,
+}
+
+impl BoundingBox {
+    /// Box around a sphere
+    pub fn around(center: Position, radius: f32) -> Self {
+        let extent = Vector3::repeat(radius.max(0.0));
+        Self {
+            min: center - extent,
+            max: center + extent,
+        }
+    }
+
+    /// Center of the box
+    pub fn center(&self) -> Position {
+        nalgebra::center(&self.min, &self.max)
+    }
+
+    /// The box grown by `margin` on every side
+    pub fn expanded(&self, margin: f32) -> Self {
+        let margin = Vector3::repeat(margin);
+        Self {
+            min: self.min - margin,
+            max: self.max + margin,
+        }
+    }
+
+    /// Whether `other` lies completely inside this box
+    pub fn contains(&self, other: &BoundingBox) -> bool {
+        (0..3).all(|axis| self.min[axis] <= other.min[axis] && other.max[axis] <= self.max[axis])
+    }
+
+    /// Whether the boxes overlap
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        (0..3).all(|axis| self.min[axis] <= other.max[axis] && other.min[axis] <= self.max[axis])
+    }
+
+    /// Whether the box overlaps a sphere
+    pub fn intersects_sphere(&self, center: Position, radius: f32) -> bool {
+        let closest = Point3::from(center.coords.sup(&self.min.coords).inf(&self.max.coords));
+        (closest - center).norm_squared() <= radius * radius
+    }
+
+    /// Whether a ray hits the box in front of its origin
+    pub fn intersects_ray(&self, ray: &Ray) -> bool {
+        let mut near = 0.0f32;
+        let mut far = f32::INFINITY;
+        for axis in 0..3 {
+            let origin = ray.origin[axis];
+            let direction = ray.direction[axis];
+            if direction.abs() < f32::EPSILON {
+                if origin < self.min[axis] || origin > self.max[axis] {
+                    return false;
+                }
+                continue;
+            }
+            let t1 = (self.min[axis] - origin) / direction;
+            let t2 = (self.max[axis] - origin) / direction;
+            near = near.max(t1.min(t2));
+            far = far.min(t1.max(t2));
+            if near > far {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Whether every coordinate is finite
+    pub fn is_finite(&self) -> bool {
+        self.min.iter().chain(self.max.iter()).all(|c| c.is_finite())
+    }
+
+    /// Smallest box holding both boxes
+    fn union(&self, other: &BoundingBox) -> Self {
+        Self {
+            min: Point3::from(self.min.coords.inf(&other.min.coords)),
+            max: Point3::from(self.max.coords.sup(&other.max.coords)),
+        }
+    }
+}
+
+/// Octree of node bounds, updated as nodes are added, moved and removed
+#[derive(Debug, Default)]
+pub struct SpatialIndex {
+    /// Cells, the root first
+    cells: Vec<Cell>,
+    /// Cell and bounds of every indexed node
+    entries: HashMap<SceneId, Entry>,
+}
+
+#[derive(Debug)]
+struct Cell {
+    bounds: BoundingBox,
+    depth: u32,
+    items: Vec<SceneId>,
+    children: Option<[usize; 8]>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    cell: usize,
+    bounds: BoundingBox,
+}
+
+impl SpatialIndex {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of indexed nodes
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no nodes are indexed
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Indexed bounds of a node
+    pub fn bounds(&self, id: SceneId) -> Option<&BoundingBox> {
+        self.entries.get(&id).map(|entry| &entry.bounds)
+    }
+
+    /// Index a node, or move it to new bounds
+    pub fn insert(&mut self, id: SceneId, bounds: BoundingBox) {
+        if let Some(entry) = self.entries.get_mut(&id) {
+            // Stay in the same cell while the node still fits and no child could take it
+            let cell = &self.cells[entry.cell];
+            let fits_child = cell
+                .children
+                .is_some_and(|children| children.iter().any(|&child| self.cells[child].bounds.contains(&bounds)));
+            if cell.bounds.contains(&bounds) && !fits_child {
+                entry.bounds = bounds;
+                return;
+            }
+            self.remove(id);
+        }
+
+        match self.cells.first() {
+            // Nodes at invalid positions are kept at the root, where no query finds them
+            _ if !bounds.is_finite() => {
+                if self.cells.is_empty() {
+                    self.cells.push(Cell::new(root_around(&BoundingBox::around(Point3::origin(), 0.0)), 0));
+                }
+                self.cells[0].items.push(id);
+                self.entries.insert(id, Entry { cell: 0, bounds });
+                return;
+            }
+            None => self.cells.push(Cell::new(root_around(&bounds), 0)),
+            Some(root) if !root.bounds.contains(&bounds) => {
+                let grown = root_around(&root.bounds.union(&bounds));
+                self.rebuild(grown);
+            }
+            Some(_) => {}
+        }
+        self.place(id, bounds);
+    }
+
+    /// Remove a node, returning whether it was indexed
+    pub fn remove(&mut self, id: SceneId) -> bool {
+        let Some(entry) = self.entries.remove(&id) else {
+            return false;
+        };
+        let items = &mut self.cells[entry.cell].items;
+        if let Some(index) = items.iter().position(|&item| item == id) {
+            items.swap_remove(index);
+        }
+        true
+    }
+
+    /// Remove every node
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.entries.clear();
+    }
+
+    /// Nodes whose bounds pass `overlaps`; the test also prunes cells, so it
+    /// has to hold for any box containing a box it accepts
+    pub fn query(&self, mut overlaps: impl FnMut(&BoundingBox) -> bool) -> Vec<SceneId> {
+        let mut found = Vec::new();
+        if self.cells.is_empty() {
+            return found;
+        }
+
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let cell = &self.cells[index];
+            if !overlaps(&cell.bounds) {
+                continue;
+            }
+            found.extend(cell.items.iter().copied().filter(|id| overlaps(&self.entries[id].bounds)));
+            if let Some(children) = cell.children {
+                stack.extend(children);
+            }
+        }
+        found
+    }
+
+    /// Nodes whose bounds overlap a sphere
+    pub fn query_sphere(&self, center: Position, radius: f32) -> Vec<SceneId> {
+        self.query(|bounds| bounds.intersects_sphere(center, radius))
+    }
+
+    /// Nodes whose bounds a ray passes through
+    pub fn query_ray(&self, ray: &Ray) -> Vec<SceneId> {
+        self.query(|bounds| bounds.intersects_ray(ray))
+    }
+
+    /// Put a node in the deepest cell containing it, splitting full leaves
+    fn place(&mut self, id: SceneId, bounds: BoundingBox) {
+        let mut index = self.descend(0, &bounds);
+        let cell = &self.cells[index];
+        if cell.children.is_none() && cell.items.len() >= MAX_CELL_ITEMS && cell.depth < MAX_DEPTH {
+            self.split(index);
+            index = self.descend(index, &bounds);
+        }
+        self.cells[index].items.push(id);
+        self.entries.insert(id, Entry { cell: index, bounds });
+    }
+
+    /// Deepest cell below `index` that contains `bounds`
+    fn descend(&self, mut index: usize, bounds: &BoundingBox) -> usize {
+        while let Some(children) = self.cells[index].children {
+            match children.iter().find(|&&child| self.cells[child].bounds.contains(bounds)) {
+                Some(&child) => index = child,
+                None => break,
+            }
+        }
+        index
+    }
+
+    /// Give a leaf eight children and move down the nodes that fit in one
+    fn split(&mut self, index: usize) {
+        let bounds = self.cells[index].bounds;
+        let depth = self.cells[index].depth + 1;
+        let center = bounds.center();
+
+        let first = self.cells.len();
+        for octant in 0..8 {
+            let pick = |axis: usize| {
+                if octant & (1 << axis) == 0 {
+                    (bounds.min[axis], center[axis])
+                } else {
+                    (center[axis], bounds.max[axis])
+                }
+            };
+            let (x, y, z) = (pick(0), pick(1), pick(2));
+            let child = BoundingBox {
+                min: Point3::new(x.0, y.0, z.0),
+                max: Point3::new(x.1, y.1, z.1),
+            };
+            self.cells.push(Cell::new(child, depth));
+        }
+        self.cells[index].children = Some(std::array::from_fn(|octant| first + octant));
+
+        for id in std::mem::take(&mut self.cells[index].items) {
+            let bounds = self.entries[&id].bounds;
+            let cell = self.descend(index, &bounds);
+            self.cells[cell].items.push(id);
+            self.entries.insert(id, Entry { cell, bounds });
+        }
+    }
+
+    /// Start over from a new root, re-adding every node
+    fn rebuild(&mut self, root: BoundingBox) {
+        let entries: Vec<(SceneId, BoundingBox)> = self.entries.drain().map(|(id, entry)| (id, entry.bounds)).collect();
+        self.cells.clear();
+        self.cells.push(Cell::new(root, 0));
+        for (id, bounds) in entries {
+            self.place(id, bounds);
+        }
+    }
+}
+
+impl Cell {
+    fn new(bounds: BoundingBox, depth: u32) -> Self {
+        Self {
+            bounds,
+            depth,
+            items: Vec::new(),
+            children: None,
+        }
+    }
+}
+
+/// Cubic root cell around `bounds`, with room to grow before the next rebuild
+fn root_around(bounds: &BoundingBox) -> BoundingBox {
+    // Twice the size of the bounds
+    let half = (bounds.max - bounds.min).max().max(MIN_ROOT_HALF_SIZE);
+    BoundingBox::around(bounds.center(), half)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_follows_moves_and_grows() {
+        let mut index = SpatialIndex::new();
+        for i in 0..100u64 {
+            let position = Point3::new((i % 10) as f32 * 4.0, (i / 10) as f32 * 4.0, 0.0);
+            index.insert(i, BoundingBox::around(position, 0.5));
+        }
+        assert_eq!(index.len(), 100);
+
+        let mut near = index.query_sphere(Point3::origin(), 1.0);
+        near.sort();
+        assert_eq!(near, vec![0]);
+
+        // Moving far outside the root grows the tree without losing nodes
+        index.insert(0, BoundingBox::around(Point3::new(10_000.0, 0.0, 0.0), 0.5));
+        assert!(index.query_sphere(Point3::origin(), 1.0).is_empty());
+        assert_eq!(index.query_sphere(Point3::new(10_000.0, 0.0, 0.0), 1.0), vec![0]);
+        assert_eq!(index.query(|_| true).len(), 100);
+
+        let ray = Ray {
+            origin: Point3::new(4.0, 4.0, -10.0),
+            direction: Vector3::new(0.0, 0.0, 1.0),
+        };
+        assert_eq!(index.query_ray(&ray), vec![11]);
+
+        assert!(index.remove(11));
+        assert!(!index.remove(11));
+        assert!(index.query_ray(&ray).is_empty());
+    }
+}
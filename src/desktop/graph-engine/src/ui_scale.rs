@@ -0,0 +1,147 @@
+//! Screen-space sizing of UI drawn in the scene
+//!
+//! Badges and manipulation handles keep the same size on screen however far
+//! the camera zooms out. Labels scale with the scene like nodes do until
+//! they would shrink below a legible size, from where they stay at that size.
+//! Sizes are given in logical pixels and multiplied by the display scale, so
+//! they look the same on HiDPI screens; text is further multiplied by the
+//! accessibility text scale.
+
+use crate::Camera;
+use nalgebra::Point3;
+
+/// UI drawn in the scene that is sized in screen space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UiElement {
+    /// Text naming a node or edge
+    Label,
+    /// Small count or status marker on a node
+    Badge,
+    /// Manipulation handle of a selected node
+    Handle,
+}
+
+impl UiElement {
+    /// Whether the element is text, and so follows the text scale
+    pub fn is_text(self) -> bool {
+        matches!(self, UiElement::Label | UiElement::Badge)
+    }
+}
+
+/// Sizes of screen-space UI and the factors applied to them
+#[derive(Debug, Clone, PartialEq)]
+pub struct UiSizing {
+    /// Physical pixels per logical pixel of the display
+    pub display_scale: f32,
+    /// Accessibility text scale
+    pub text_scale: f32,
+    /// Badge height in logical pixels
+    pub badge_pixels: f32,
+    /// Handle size in logical pixels
+    pub handle_pixels: f32,
+    /// Smallest label height in logical pixels
+    pub label_min_pixels: f32,
+}
+
+impl Default for UiSizing {
+    fn default() -> Self {
+        Self {
+            display_scale: 1.0,
+            text_scale: 1.0,
+            badge_pixels: 16.0,
+            handle_pixels: 10.0,
+            label_min_pixels: 12.0,
+        }
+    }
+}
+
+impl UiSizing {
+    /// Set the display scale, e.g. from the window's scale factor
+    pub fn set_display_scale(&mut self, scale: f32) {
+        self.display_scale = scale.clamp(0.5, 8.0);
+    }
+
+    /// Set the text scale from accessibility settings
+    pub fn set_text_scale(&mut self, scale: f32) {
+        self.text_scale = scale.clamp(0.5, 4.0);
+    }
+
+    /// Physical pixels of an element at its screen-constant size
+    pub fn pixels(&self, element: UiElement) -> f32 {
+        let logical = match element {
+            UiElement::Label => self.label_min_pixels,
+            UiElement::Badge => self.badge_pixels,
+            UiElement::Handle => self.handle_pixels,
+        };
+        logical * self.factor(element)
+    }
+
+    /// Display scale times the text scale for text
+    pub fn factor(&self, element: UiElement) -> f32 {
+        if element.is_text() {
+            self.display_scale * self.text_scale
+        } else {
+            self.display_scale
+        }
+    }
+
+    /// World size to draw an element at
+    ///
+    /// `world_size` is the size labels have when they scale with the scene;
+    /// `pixel_size` is the world size of one physical pixel where the element
+    /// is drawn, see [`Camera::pixel_size_at`].
+    pub fn world_size(&self, element: UiElement, world_size: f32, pixel_size: f32) -> f32 {
+        let screen_constant = self.pixels(element) * pixel_size;
+        match element {
+            UiElement::Label => (world_size * self.text_scale).max(screen_constant),
+            UiElement::Badge | UiElement::Handle => screen_constant,
+        }
+    }
+
+    /// Whether a label is drawn at its minimum size rather than with the scene
+    pub fn label_is_screen_constant(&self, world_size: f32, pixel_size: f32) -> bool {
+        world_size * self.text_scale < self.pixels(UiElement::Label) * pixel_size
+    }
+
+    /// World size of an element at a point seen through a camera
+    pub fn world_size_at(&self, element: UiElement, world_size: f32, camera: &Camera, point: Point3<f32>, viewport_height: f32) -> f32 {
+        self.world_size(element, world_size, camera.pixel_size_at(point, viewport_height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handles_keep_screen_size_and_labels_stop_shrinking() {
+        let sizing = UiSizing::default();
+        let camera = Camera::new();
+        let near = Point3::new(0.0, 0.0, 0.0);
+        let far = Point3::new(0.0, 0.0, -500.0);
+
+        // A handle covers the same pixels at any distance
+        for point in [near, far] {
+            let pixel_size = camera.pixel_size_at(point, 1080.0);
+            let size = sizing.world_size(UiElement::Handle, 0.25, pixel_size);
+            assert!((size / pixel_size - 10.0).abs() < 1e-3);
+        }
+
+        // A label scales with the scene up close and is clamped far away
+        let near_label = sizing.world_size_at(UiElement::Label, 1.0, &camera, near, 1080.0);
+        assert_eq!(near_label, 1.0);
+        let far_pixel = camera.pixel_size_at(far, 1080.0);
+        assert!(sizing.label_is_screen_constant(1.0, far_pixel));
+        assert!((sizing.world_size(UiElement::Label, 1.0, far_pixel) / far_pixel - 12.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_text_scale_applies_to_text_only() {
+        let mut sizing = UiSizing::default();
+        sizing.set_display_scale(2.0);
+        sizing.set_text_scale(1.5);
+        assert_eq!(sizing.pixels(UiElement::Badge), 48.0);
+        assert_eq!(sizing.pixels(UiElement::Handle), 20.0);
+        assert_eq!(sizing.world_size(UiElement::Label, 2.0, 0.001), 3.0);
+    }
+}
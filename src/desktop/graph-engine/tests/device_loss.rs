@@ -0,0 +1,104 @@
+//! Device-loss recovery without a GPU
+//!
+//! The loss is simulated by reporting it to the monitor, as the device-lost
+//! callback does, and recreation attempts succeed or fail on demand.
+
+use horizonos_graph_engine::{
+    DeviceLossMonitor, DeviceRecovery, GraphEngineError, NodeMetadata, NodeType, RecoveryEvent,
+    RecoveryState, RecoveryStep, ResourceCache, Scene, SceneNode, SceneSnapshot, UploadPriority,
+};
+use horizonos_graph_errors::{Classify, Severity};
+use nalgebra::{Point3, Vector3};
+use std::time::{Duration, Instant};
+
+fn node(title: &str) -> SceneNode {
+    SceneNode {
+        id: 0,
+        position: Point3::new(1.0, 2.0, 3.0),
+        velocity: Vector3::zeros(),
+        radius: 1.0,
+        color: [1.0; 4],
+        node_type: NodeType::Concept { title: title.to_string(), content: String::new() },
+        metadata: NodeMetadata::default(),
+        visible: true,
+        selected: false,
+    }
+}
+
+#[test]
+fn device_loss_is_recovered_without_losing_the_session() {
+    let mut scene = Scene::new();
+    let a = scene.add_node(node("a"));
+    let b = scene.add_node(node("b"));
+    let before = serde_json::to_value(SceneSnapshot::capture(&scene).nodes).unwrap();
+
+    let mut cache = ResourceCache::new();
+    cache.insert_buffer("instances", wgpu::BufferUsages::VERTEX, UploadPriority::Visible, vec![0u8; 64]);
+    cache.insert_texture(
+        "atlas",
+        [4, 4],
+        wgpu::TextureFormat::Rgba8Unorm,
+        wgpu::TextureUsages::TEXTURE_BINDING,
+        UploadPriority::Background,
+        vec![0u8; 64],
+    );
+
+    let monitor = DeviceLossMonitor::new();
+    let mut recovery = DeviceRecovery::new(monitor.clone()).with_retries(3, Duration::from_millis(100));
+    let events = recovery.subscribe();
+    let start = Instant::now();
+    assert_eq!(recovery.poll(start), RecoveryStep::Render);
+
+    // The driver resets the GPU
+    monitor.report("Unknown: GPU hang");
+    monitor.report("a later report");
+    assert_eq!(recovery.poll(start), RecoveryStep::Recreate);
+    assert!(matches!(events.try_recv(), Ok(RecoveryEvent::DeviceLost { reason }) if reason == "Unknown: GPU hang"));
+
+    // The first attempt fails, so frames are skipped until the backoff passes
+    assert!(!recovery.attempt_finished(start, Err(GraphEngineError::AdapterNotFound)));
+    assert_eq!(recovery.poll(start + Duration::from_millis(50)), RecoveryStep::Wait);
+    let retry = start + Duration::from_millis(100);
+    assert_eq!(recovery.poll(retry), RecoveryStep::Recreate);
+
+    // The new device has none of the old GPU objects
+    cache.invalidate();
+    assert_eq!(cache.missing(), vec!["atlas", "instances"]);
+    assert_eq!(cache.cached_bytes(), 128);
+    assert!(recovery.attempt_finished(retry, Ok(())));
+    assert_eq!(recovery.state(), &RecoveryState::Healthy);
+    assert_eq!(recovery.poll(retry), RecoveryStep::Render);
+
+    let recovered = events.try_recv().unwrap();
+    assert_eq!(recovered, RecoveryEvent::Recovered { attempts: 2, downtime: Duration::from_millis(100) });
+    let problem = recovered.classify();
+    assert!(problem.user_visible);
+    assert_eq!(problem.severity, Severity::Info);
+
+    // Everything on the CPU side survived
+    assert_eq!(serde_json::to_value(SceneSnapshot::capture(&scene).nodes).unwrap(), before);
+    assert!(scene.get_node(a).is_some() && scene.get_node(b).is_some());
+    assert_eq!(cache.len(), 2);
+    assert!(cache.buffer("instances").is_none());
+}
+
+#[test]
+fn recovery_gives_up_after_max_attempts() {
+    let monitor = DeviceLossMonitor::new();
+    let mut recovery = DeviceRecovery::new(monitor.clone()).with_retries(2, Duration::ZERO);
+    let events = recovery.subscribe();
+    let now = Instant::now();
+
+    monitor.report("eGPU unplugged");
+    assert_eq!(recovery.poll(now), RecoveryStep::Recreate);
+    recovery.attempt_finished(now, Err(GraphEngineError::AdapterNotFound));
+    assert_eq!(recovery.poll(now), RecoveryStep::Recreate);
+    recovery.attempt_finished(now, Err(GraphEngineError::AdapterNotFound));
+
+    assert!(matches!(recovery.state(), RecoveryState::Failed { .. }));
+    assert_eq!(recovery.poll(now + Duration::from_secs(60)), RecoveryStep::Wait);
+
+    let failed = events.try_iter().last().unwrap();
+    assert!(matches!(failed, RecoveryEvent::RecoveryFailed { attempts: 2, .. }));
+    assert_eq!(failed.classify().severity, Severity::Critical);
+}
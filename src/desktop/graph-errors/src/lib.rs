@@ -0,0 +1,276 @@
+//! Shared error taxonomy for HorizonOS graph desktop
+//!
+//! Subsystems keep their own error enums but describe each failure as a
+//! [`Problem`] through the [`Classify`] trait: a stable dotted code, a
+//! [`Category`], a [`Severity`], whether retrying can help, and the recovery
+//! actions the user can take. Problems are reported to a [`ProblemsPanel`],
+//! which logs them, deduplicates repeats and broadcasts them so the
+//! notification system can surface the user-relevant ones.
+
+pub mod panel;
+
+pub use panel::{ProblemEvent, ProblemsPanel};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// How bad a problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Worth knowing, nothing is broken
+    Info,
+    /// Something did not work but the desktop carries on
+    Warning,
+    /// An operation the user asked for failed
+    Error,
+    /// The desktop cannot work properly until this is fixed
+    Critical,
+}
+
+/// Which kind of failure a problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    /// Reading or writing files
+    Io,
+    /// Reaching a remote service
+    Network,
+    /// Persisted data is missing or unreadable
+    Storage,
+    /// Invalid settings
+    Configuration,
+    /// Missing rights for an operation
+    Permission,
+    /// AI backends and models
+    Ai,
+    /// Automated actions and workflows
+    Automation,
+    /// Synchronisation between devices or collaborators
+    Sync,
+    /// GPU and drawing
+    Rendering,
+    /// Graph operations on nodes, edges and layouts
+    Graph,
+    /// A bug or broken invariant
+    Internal,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Category::Io => "I/O",
+            Category::Network => "Network",
+            Category::Storage => "Storage",
+            Category::Configuration => "Configuration",
+            Category::Permission => "Permission",
+            Category::Ai => "AI",
+            Category::Automation => "Automation",
+            Category::Sync => "Sync",
+            Category::Rendering => "Rendering",
+            Category::Graph => "Graph",
+            Category::Internal => "Internal",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Whether repeating the failed operation can succeed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum Retryability {
+    /// Retrying will fail the same way
+    Permanent,
+    /// The failure is temporary; retry, after `after` when given
+    Transient { after: Option<Duration> },
+    /// Retrying helps only once the user has done something, e.g. fixed a setting
+    NeedsUser,
+}
+
+impl Retryability {
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, Retryability::Permanent)
+    }
+}
+
+/// Something the user can do about a problem
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum RecoveryKind {
+    /// Repeat the failed operation
+    Retry,
+    /// Open a settings section
+    OpenSettings { section: String },
+    /// Open a file, e.g. a log or a conflicting copy
+    OpenFile { path: String },
+    /// Show the node the problem concerns
+    ShowNode { node_id: u64 },
+    /// Handled by the reporting subsystem, which listens for
+    /// [`ProblemEvent::RecoveryRequested`]
+    Custom { handler: String },
+}
+
+/// Recovery action offered with a problem
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveryAction {
+    /// Identifier, unique within the problem
+    pub id: String,
+    /// Button label
+    pub label: String,
+    pub kind: RecoveryKind,
+}
+
+impl RecoveryAction {
+    pub fn new(id: impl Into<String>, label: impl Into<String>, kind: RecoveryKind) -> Self {
+        Self { id: id.into(), label: label.into(), kind }
+    }
+
+    pub fn retry() -> Self {
+        Self::new("retry", "Retry", RecoveryKind::Retry)
+    }
+
+    pub fn open_settings(section: impl Into<String>) -> Self {
+        Self::new("settings", "Open settings", RecoveryKind::OpenSettings { section: section.into() })
+    }
+
+    pub fn show_node(node_id: u64) -> Self {
+        Self::new("show_node", "Show", RecoveryKind::ShowNode { node_id })
+    }
+}
+
+/// A classified failure
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Problem {
+    pub id: Uuid,
+    /// Stable dotted code, e.g. `ai.backend_unavailable`; repeats of the same
+    /// code from the same subsystem are merged in the problems panel
+    pub code: String,
+    /// Reporting subsystem, e.g. `ai` or `workspaces`
+    pub subsystem: String,
+    pub category: Category,
+    pub severity: Severity,
+    pub retry: Retryability,
+    /// One-line summary for the user
+    pub message: String,
+    /// Underlying error text
+    pub detail: Option<String>,
+    /// Whether the user should be told, as opposed to only logging it
+    pub user_visible: bool,
+    pub recovery: Vec<RecoveryAction>,
+    /// Node the problem concerns
+    pub node_id: Option<u64>,
+    /// Identifiers the reporting subsystem needs to recover, e.g. a workflow id
+    pub context: HashMap<String, String>,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// Times the problem was reported
+    pub occurrences: u32,
+}
+
+impl Problem {
+    /// New problem; errors are user visible, lower severities are not
+    pub fn new(code: impl Into<String>, category: Category, severity: Severity, message: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            code: code.into(),
+            subsystem: String::new(),
+            category,
+            severity,
+            retry: Retryability::Permanent,
+            message: message.into(),
+            detail: None,
+            user_visible: severity >= Severity::Error,
+            recovery: Vec::new(),
+            node_id: None,
+            context: HashMap::new(),
+            first_seen: now,
+            last_seen: now,
+            occurrences: 1,
+        }
+    }
+
+    pub fn in_subsystem(mut self, subsystem: impl Into<String>) -> Self {
+        self.subsystem = subsystem.into();
+        self
+    }
+
+    pub fn with_detail(mut self, detail: impl fmt::Display) -> Self {
+        self.detail = Some(detail.to_string());
+        self
+    }
+
+    pub fn with_retry(mut self, retry: Retryability) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Mark as transient and offer a retry
+    pub fn transient(self) -> Self {
+        self.with_retry(Retryability::Transient { after: None }).with_recovery(RecoveryAction::retry())
+    }
+
+    pub fn with_recovery(mut self, action: RecoveryAction) -> Self {
+        if !self.recovery.iter().any(|existing| existing.id == action.id) {
+            self.recovery.push(action);
+        }
+        self
+    }
+
+    pub fn with_node(mut self, node_id: u64) -> Self {
+        self.node_id = Some(node_id);
+        self
+    }
+
+    pub fn with_context(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.context.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn user_visible(mut self, visible: bool) -> Self {
+        self.user_visible = visible;
+        self
+    }
+
+    /// Key under which repeats are merged
+    pub fn key(&self) -> (String, String) {
+        (self.subsystem.clone(), self.code.clone())
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.code, self.message)?;
+        if let Some(detail) = &self.detail {
+            write!(f, ": {}", detail)?;
+        }
+        Ok(())
+    }
+}
+
+/// Describes a subsystem error in the shared taxonomy
+pub trait Classify {
+    fn classify(&self) -> Problem;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problem_defaults() {
+        let problem = Problem::new("ai.backend_unavailable", Category::Ai, Severity::Warning, "AI backend unreachable")
+            .in_subsystem("ai")
+            .transient()
+            .transient();
+        assert!(!problem.user_visible);
+        assert!(problem.retry.is_retryable());
+        assert_eq!(problem.recovery, vec![RecoveryAction::retry()]);
+        assert!(Problem::new("x", Category::Io, Severity::Error, "x").user_visible);
+        assert_eq!(problem.to_string(), "[ai.backend_unavailable] AI backend unreachable");
+    }
+}
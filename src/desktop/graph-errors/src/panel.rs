@@ -0,0 +1,218 @@
+//! Problems panel: the list of current problems shown to the user
+
+use crate::{Classify, Problem, RecoveryAction, Severity};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Changes to the problems panel
+#[derive(Debug, Clone)]
+pub enum ProblemEvent {
+    /// A new problem was reported
+    Reported(Problem),
+    /// A known problem happened again
+    Repeated(Problem),
+    /// A problem went away
+    Resolved(Problem),
+    /// The user chose a recovery action
+    RecoveryRequested { problem: Problem, action: RecoveryAction },
+}
+
+/// Current problems, merged by subsystem and code
+#[derive(Debug)]
+pub struct ProblemsPanel {
+    problems: RwLock<HashMap<Uuid, Problem>>,
+    /// Oldest problems are dropped beyond this
+    capacity: usize,
+    events: broadcast::Sender<ProblemEvent>,
+}
+
+impl ProblemsPanel {
+    pub fn new() -> Self {
+        Self::with_capacity(200)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (events, _) = broadcast::channel(100);
+        Self {
+            problems: RwLock::new(HashMap::new()),
+            capacity: capacity.max(1),
+            events,
+        }
+    }
+
+    /// Record a problem, merging it into an earlier one with the same code
+    ///
+    /// Every report is logged, so nothing is lost when no one shows the panel.
+    pub fn report(&self, problem: Problem) -> Uuid {
+        let level = match problem.severity {
+            Severity::Info => log::Level::Info,
+            Severity::Warning => log::Level::Warn,
+            Severity::Error | Severity::Critical => log::Level::Error,
+        };
+        let subsystem = if problem.subsystem.is_empty() { "desktop" } else { problem.subsystem.as_str() };
+        log::log!(level, "{}: {}", subsystem, problem);
+
+        let mut problems = self.problems.write().unwrap();
+        let key = problem.key();
+        let (id, event) = match problems.values_mut().find(|existing| existing.key() == key) {
+            Some(existing) => {
+                existing.last_seen = problem.last_seen;
+                existing.occurrences += 1;
+                existing.severity = existing.severity.max(problem.severity);
+                existing.user_visible |= problem.user_visible;
+                existing.message = problem.message;
+                existing.detail = problem.detail.or(existing.detail.take());
+                existing.retry = problem.retry;
+                existing.context.extend(problem.context);
+                for action in problem.recovery {
+                    if !existing.recovery.iter().any(|known| known.id == action.id) {
+                        existing.recovery.push(action);
+                    }
+                }
+                (existing.id, ProblemEvent::Repeated(existing.clone()))
+            }
+            None => {
+                if problems.len() >= self.capacity {
+                    let oldest = problems.values().min_by_key(|p| p.last_seen).map(|p| p.id);
+                    if let Some(oldest) = oldest {
+                        problems.remove(&oldest);
+                    }
+                }
+                problems.insert(problem.id, problem.clone());
+                (problem.id, ProblemEvent::Reported(problem))
+            }
+        };
+        drop(problems);
+
+        let _ = self.events.send(event);
+        id
+    }
+
+    /// Classify a subsystem error and report it
+    pub fn report_error<E: Classify + ?Sized>(&self, error: &E) -> Uuid {
+        self.report(error.classify())
+    }
+
+    /// Remove a problem, e.g. after a successful retry
+    pub fn resolve(&self, id: Uuid) -> Option<Problem> {
+        let problem = self.problems.write().unwrap().remove(&id)?;
+        let _ = self.events.send(ProblemEvent::Resolved(problem.clone()));
+        Some(problem)
+    }
+
+    /// Remove the problem with a code, e.g. when a subsystem recovers
+    pub fn resolve_code(&self, subsystem: &str, code: &str) -> Option<Problem> {
+        let id = self
+            .problems
+            .read()
+            .unwrap()
+            .values()
+            .find(|p| p.subsystem == subsystem && p.code == code)
+            .map(|p| p.id)?;
+        self.resolve(id)
+    }
+
+    /// Ask the subsystem behind a problem to run one of its recovery actions
+    pub fn request_recovery(&self, problem_id: Uuid, action_id: &str) -> bool {
+        let Some(problem) = self.get(problem_id) else {
+            return false;
+        };
+        let Some(action) = problem.recovery.iter().find(|a| a.id == action_id).cloned() else {
+            return false;
+        };
+        let _ = self.events.send(ProblemEvent::RecoveryRequested { problem, action });
+        true
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<Problem> {
+        self.problems.read().unwrap().get(&id).cloned()
+    }
+
+    /// Current problems, most severe and most recent first
+    pub fn problems(&self) -> Vec<Problem> {
+        let mut problems: Vec<Problem> = self.problems.read().unwrap().values().cloned().collect();
+        problems.sort_by(|a, b| b.severity.cmp(&a.severity).then(b.last_seen.cmp(&a.last_seen)));
+        problems
+    }
+
+    /// Number of current problems at or above `severity`, for a status badge
+    pub fn count_at_least(&self, severity: Severity) -> usize {
+        self.problems.read().unwrap().values().filter(|p| p.severity >= severity).count()
+    }
+
+    pub fn clear(&self) {
+        let cleared: Vec<Problem> = self.problems.write().unwrap().drain().map(|(_, p)| p).collect();
+        for problem in cleared {
+            let _ = self.events.send(ProblemEvent::Resolved(problem));
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ProblemEvent> {
+        self.events.subscribe()
+    }
+}
+
+impl Default for ProblemsPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Category, RecoveryKind};
+
+    fn unreachable_backend() -> Problem {
+        Problem::new("ai.backend_unavailable", Category::Ai, Severity::Warning, "AI backend unreachable")
+            .in_subsystem("ai")
+            .transient()
+    }
+
+    #[test]
+    fn test_repeats_are_merged() {
+        let panel = ProblemsPanel::new();
+        let mut events = panel.subscribe();
+        let first = panel.report(unreachable_backend());
+        let second = panel.report(unreachable_backend().with_retry(crate::Retryability::Permanent));
+        panel.report(Problem::new("workspaces.save_failed", Category::Io, Severity::Error, "Could not save").in_subsystem("workspaces"));
+
+        assert_eq!(first, second);
+        let problems = panel.problems();
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].code, "workspaces.save_failed");
+        assert_eq!(problems[1].occurrences, 2);
+        assert_eq!(panel.count_at_least(Severity::Error), 1);
+        assert!(matches!(events.try_recv(), Ok(ProblemEvent::Reported(_))));
+        assert!(matches!(events.try_recv(), Ok(ProblemEvent::Repeated(p)) if p.occurrences == 2));
+
+        assert!(panel.resolve_code("ai", "ai.backend_unavailable").is_some());
+        assert_eq!(panel.problems().len(), 1);
+    }
+
+    #[test]
+    fn test_recovery_requests() {
+        let panel = ProblemsPanel::new();
+        let id = panel.report(unreachable_backend());
+        let mut events = panel.subscribe();
+
+        assert!(!panel.request_recovery(id, "missing"));
+        assert!(panel.request_recovery(id, "retry"));
+        match events.try_recv() {
+            Ok(ProblemEvent::RecoveryRequested { action, .. }) => assert_eq!(action.kind, RecoveryKind::Retry),
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_capacity_drops_oldest() {
+        let panel = ProblemsPanel::with_capacity(2);
+        for code in ["a", "b", "c"] {
+            panel.report(Problem::new(code, Category::Internal, Severity::Info, code));
+        }
+        let codes: Vec<String> = panel.problems().into_iter().map(|p| p.code).collect();
+        assert_eq!(codes, vec!["c", "b"]);
+    }
+}
@@ -0,0 +1,75 @@
+//! Report message IDs a crate uses but has no translation for
+//!
+//! Usage: horizonos-i18n-extract <crate-dir> [--domain <name>] [--locale <locale>] [--write] [--check]
+
+use horizonos_graph_i18n::extract::check_crate;
+use horizonos_graph_i18n::DEFAULT_LOCALE;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "--help") {
+        println!("Usage: horizonos-i18n-extract <crate-dir> [options]");
+        println!();
+        println!("Options:");
+        println!("  --domain <name>    Domain name (default: crate directory without 'graph-')");
+        println!("  --locale <locale>  Locale to compare against (default: {})", DEFAULT_LOCALE);
+        println!("  --write            Append stubs for missing messages to the locale file");
+        println!("  --check            Exit with an error if messages are missing");
+        return ExitCode::SUCCESS;
+    }
+
+    let crate_dir = PathBuf::from(&args[0]);
+    let option = |name: &str| {
+        args.iter().position(|arg| arg == name).and_then(|i| args.get(i + 1)).cloned()
+    };
+    let domain = option("--domain").unwrap_or_else(|| {
+        let dir_name = crate_dir
+            .canonicalize()
+            .ok()
+            .and_then(|dir| dir.file_name().map(|name| name.to_string_lossy().into_owned()))
+            .unwrap_or_default();
+        dir_name.trim_start_matches("graph-").to_string()
+    });
+    let locale = option("--locale").unwrap_or_else(|| DEFAULT_LOCALE.to_string());
+
+    let report = match check_crate(&crate_dir, &domain, &locale) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    for usage in &report.missing {
+        println!("missing: {} ({}:{})", usage.id, usage.file.display(), usage.line);
+    }
+    for id in &report.unused {
+        println!("unused:  {} (or looked up at runtime)", id);
+    }
+    println!(
+        "{}: {} missing, {} unused",
+        report.ftl_path.display(),
+        report.missing.len(),
+        report.unused.len()
+    );
+
+    if args.iter().any(|arg| arg == "--write") && !report.is_complete() {
+        let written = report.ftl_path.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::OpenOptions::new().create(true).append(true).open(&report.ftl_path))
+            .and_then(|mut file| write!(file, "\n{}", report.stubs()));
+        if let Err(e) = written {
+            eprintln!("error: failed to write {}: {}", report.ftl_path.display(), e);
+            return ExitCode::FAILURE;
+        }
+        println!("Appended stubs to {}", report.ftl_path.display());
+    }
+
+    if args.iter().any(|arg| arg == "--check") && !report.is_complete() {
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
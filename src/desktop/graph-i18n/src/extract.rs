@@ -0,0 +1,179 @@
+//! Extraction of message IDs used in source code
+//!
+//! Finds `tr!(DOMAIN, "id", ...)` and `.tr("id")` / `.tr_args("id", ...)`
+//! calls in a crate and compares them with the crate's Fluent file, so
+//! contributors can see which messages still need a translation. IDs built at
+//! runtime (looked up with `try_tr`) cannot be found and are reported as
+//! possibly unused instead.
+
+use crate::I18nError;
+use fluent_syntax::ast;
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use walkdir::WalkDir;
+
+/// A message ID referenced from source code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageUse {
+    /// Message ID, `message.attribute` for attributes
+    pub id: String,
+    /// Source file
+    pub file: PathBuf,
+    /// 1-based line number
+    pub line: usize,
+}
+
+/// Comparison of a crate's source against one of its Fluent files
+#[derive(Debug, Clone)]
+pub struct ExtractReport {
+    /// Fluent file compared against
+    pub ftl_path: PathBuf,
+    /// IDs used in source but missing from the Fluent file
+    pub missing: Vec<MessageUse>,
+    /// IDs defined in the Fluent file but not found in source
+    pub unused: Vec<String>,
+}
+
+impl ExtractReport {
+    /// Whether every used ID has a message
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    /// Fluent stubs for the missing IDs, one per message, for translators to fill in
+    pub fn stubs(&self) -> String {
+        let mut seen = BTreeSet::new();
+        let mut stubs = String::new();
+        for usage in &self.missing {
+            let (message, attribute) = match usage.id.split_once('.') {
+                Some((message, attribute)) => (message, Some(attribute)),
+                None => (usage.id.as_str(), None),
+            };
+            if !seen.insert(message.to_string()) {
+                continue;
+            }
+            stubs.push_str(&format!("# {}:{}\n", usage.file.display(), usage.line));
+            match attribute {
+                Some(attribute) => stubs.push_str(&format!("{} =\n    .{} = {}\n", message, attribute, usage.id)),
+                None => stubs.push_str(&format!("{} = {}\n", message, usage.id)),
+            }
+        }
+        stubs
+    }
+}
+
+/// Path of a domain's Fluent file for a locale inside a crate
+pub fn locale_file(crate_dir: &Path, domain: &str, locale: &str) -> PathBuf {
+    crate_dir.join("locales").join(locale).join(format!("{}.ftl", domain))
+}
+
+/// Message IDs referenced in one source file
+pub fn scan_source(file: &Path, source: &str) -> Vec<MessageUse> {
+    static PATTERNS: OnceLock<[Regex; 2]> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        [
+            Regex::new(r#"tr!\(\s*[\w:]+\s*,\s*"([^"]+)""#).expect("valid regex"),
+            Regex::new(r#"\.tr(?:_args)?\(\s*"([^"]+)""#).expect("valid regex"),
+        ]
+    });
+
+    let mut uses = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        for pattern in patterns {
+            for captures in pattern.captures_iter(line) {
+                uses.push(MessageUse {
+                    id: captures[1].to_string(),
+                    file: file.to_path_buf(),
+                    line: index + 1,
+                });
+            }
+        }
+    }
+    uses
+}
+
+/// Message IDs referenced in every `.rs` file under a directory
+pub fn scan_dir(dir: &Path) -> Result<Vec<MessageUse>, I18nError> {
+    let mut uses = Vec::new();
+    for entry in WalkDir::new(dir).sort_by_file_name() {
+        let entry = entry.map_err(|e| I18nError::Io(e.into()))?;
+        if entry.file_type().is_file() && entry.path().extension().is_some_and(|ext| ext == "rs") {
+            let source = std::fs::read_to_string(entry.path())?;
+            uses.extend(scan_source(entry.path(), &source));
+        }
+    }
+    Ok(uses)
+}
+
+/// Message and attribute IDs defined in a Fluent source
+pub fn message_ids(source: &str) -> Result<BTreeSet<String>, I18nError> {
+    let resource = fluent_syntax::parser::parse(source).map_err(|(_, errors)| I18nError::InvalidResource {
+        domain: String::new(),
+        locale: String::new(),
+        message: errors.iter().map(|e| format!("{:?}", e.kind)).collect::<Vec<_>>().join("; "),
+    })?;
+
+    let mut ids = BTreeSet::new();
+    for entry in resource.body {
+        if let ast::Entry::Message(message) = entry {
+            let id = message.id.name.to_string();
+            for attribute in &message.attributes {
+                ids.insert(format!("{}.{}", id, attribute.id.name));
+            }
+            if message.value.is_some() {
+                ids.insert(id);
+            }
+        }
+    }
+    Ok(ids)
+}
+
+/// Compare a crate's `src` against its Fluent file for a domain and locale
+pub fn check_crate(crate_dir: &Path, domain: &str, locale: &str) -> Result<ExtractReport, I18nError> {
+    let ftl_path = locale_file(crate_dir, domain, locale);
+    let defined = if ftl_path.exists() {
+        message_ids(&std::fs::read_to_string(&ftl_path)?)?
+    } else {
+        BTreeSet::new()
+    };
+
+    let uses = scan_dir(&crate_dir.join("src"))?;
+    let used: BTreeSet<&str> = uses.iter().map(|usage| usage.id.as_str()).collect();
+
+    Ok(ExtractReport {
+        missing: uses.iter().filter(|usage| !defined.contains(&usage.id)).cloned().collect(),
+        unused: defined.iter().filter(|id| !used.contains(id.as_str())).cloned().collect(),
+        ftl_path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_and_compare() {
+        let source = r#"
+            let a = tr!(I18N, "action-open");
+            let b = tr!(crate::I18N, "file-deleted", name = name);
+            let c = I18N.tr("action-close.tooltip");
+            let d = I18N.try_tr(&format!("action-{}", name), None);
+        "#;
+        let uses = scan_source(Path::new("lib.rs"), source);
+        let ids: Vec<&str> = uses.iter().map(|usage| usage.id.as_str()).collect();
+        assert_eq!(ids, vec!["action-open", "file-deleted", "action-close.tooltip"]);
+        assert_eq!(uses[1].line, 3);
+
+        let defined = message_ids("action-open = Open\naction-close = Close\n    .tooltip = Close it\n").unwrap();
+        assert!(defined.contains("action-close.tooltip"));
+
+        let report = ExtractReport {
+            ftl_path: PathBuf::from("nodes.ftl"),
+            missing: uses.into_iter().filter(|usage| !defined.contains(&usage.id)).collect(),
+            unused: Vec::new(),
+        };
+        assert_eq!(report.stubs(), "# lib.rs:3\nfile-deleted = file-deleted\n");
+    }
+}
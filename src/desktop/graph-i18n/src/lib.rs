@@ -0,0 +1,110 @@
+//! Localization for HorizonOS graph desktop
+//!
+//! User-facing strings are Fluent messages grouped into per-crate domains.
+//! Each crate keeps its translations in `locales/<locale>/<domain>.ftl` and
+//! declares a [`Domain`] that embeds them:
+//!
+//! ```ignore
+//! pub static I18N: Domain = Domain::new("nodes", &[
+//!     ("en-US", include_str!("../locales/en-US/nodes.ftl")),
+//!     ("de", include_str!("../locales/de/nodes.ftl")),
+//! ]);
+//!
+//! let label = tr!(I18N, "action-open");
+//! let prompt = tr!(I18N, "file-delete-confirm", name = file_name);
+//! ```
+//!
+//! This module provides:
+//! - Locale detection from the environment
+//! - Runtime language switching with change notification
+//! - Pluralization through Fluent selectors on numeric arguments
+//! - Extraction tooling (`horizonos-i18n-extract`) reporting missing messages
+
+pub mod extract;
+pub mod locale;
+pub mod localizer;
+
+pub use locale::*;
+pub use localizer::*;
+
+pub use fluent_bundle::{FluentArgs, FluentValue};
+pub use unic_langid::LanguageIdentifier;
+
+use std::sync::{Mutex, OnceLock, RwLock};
+
+/// Localization errors
+#[derive(Debug, thiserror::Error)]
+pub enum I18nError {
+    #[error("Invalid locale '{0}'")]
+    InvalidLocale(String),
+
+    #[error("Invalid Fluent resource for {domain} ({locale}): {message}")]
+    InvalidResource {
+        domain: String,
+        locale: String,
+        message: String,
+    },
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Callback run after the global language changes
+type LocaleListener = Box<dyn Fn(&LanguageIdentifier) + Send + Sync>;
+
+static GLOBAL: OnceLock<RwLock<Localizer>> = OnceLock::new();
+static LISTENERS: Mutex<Vec<LocaleListener>> = Mutex::new(Vec::new());
+
+/// Process-wide localizer, initialized from the environment locale
+pub fn global() -> &'static RwLock<Localizer> {
+    GLOBAL.get_or_init(|| RwLock::new(Localizer::new(requested_locales())))
+}
+
+/// Switch the global language; `None` returns to the environment locale
+pub fn set_language(language: Option<&str>) -> Result<LanguageIdentifier, I18nError> {
+    let requested = match language {
+        Some(language) => vec![parse_locale(language)?],
+        None => requested_locales(),
+    };
+
+    let (changed, current) = {
+        let mut localizer = global().write().unwrap_or_else(|e| e.into_inner());
+        let changed = localizer.set_locales(requested);
+        (changed, localizer.locale().clone())
+    };
+
+    if changed {
+        log::info!("Language changed to {}", current);
+        let listeners = LISTENERS.lock().unwrap_or_else(|e| e.into_inner());
+        for listener in listeners.iter() {
+            listener(&current);
+        }
+    }
+    Ok(current)
+}
+
+/// Current global language
+pub fn current_locale() -> LanguageIdentifier {
+    global().read().unwrap_or_else(|e| e.into_inner()).locale().clone()
+}
+
+/// Run a callback whenever the global language changes, e.g. to relabel UI
+pub fn on_locale_changed(listener: impl Fn(&LanguageIdentifier) + Send + Sync + 'static) {
+    LISTENERS.lock().unwrap_or_else(|e| e.into_inner()).push(Box::new(listener));
+}
+
+/// Translate a message from a [`Domain`], with optional named arguments
+///
+/// `tr!(DOMAIN, "id")` or `tr!(DOMAIN, "id", count = n, name = value)`.
+/// Numeric arguments select plural variants in the message.
+#[macro_export]
+macro_rules! tr {
+    ($domain:expr, $id:literal) => {
+        $domain.tr($id)
+    };
+    ($domain:expr, $id:literal, $($key:ident = $value:expr),+ $(,)?) => {{
+        let mut args = $crate::FluentArgs::new();
+        $( args.set(stringify!($key), $value); )+
+        $domain.tr_args($id, &args)
+    }};
+}
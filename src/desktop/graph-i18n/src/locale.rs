@@ -0,0 +1,55 @@
+//! Locale detection and parsing
+
+use crate::I18nError;
+use unic_langid::LanguageIdentifier;
+
+/// Locale every domain ships and falls back to
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// The fallback locale
+pub fn default_locale() -> LanguageIdentifier {
+    DEFAULT_LOCALE.parse().expect("default locale is valid")
+}
+
+/// Parse a BCP 47 tag or POSIX locale name (`de_DE.UTF-8@euro`)
+pub fn parse_locale(value: &str) -> Result<LanguageIdentifier, I18nError> {
+    parse_posix_locale(value).ok_or_else(|| I18nError::InvalidLocale(value.to_string()))
+}
+
+/// Parse a POSIX locale name, returning `None` for `C`, `POSIX` and invalid names
+pub fn parse_posix_locale(value: &str) -> Option<LanguageIdentifier> {
+    let name = value.split(['.', '@']).next().unwrap_or("").trim();
+    if name.is_empty() || name == "C" || name == "POSIX" {
+        return None;
+    }
+    name.replace('_', "-").parse().ok()
+}
+
+/// Preferred locales from the environment, most preferred first
+///
+/// Follows gettext: `LANGUAGE` lists preferences unless the locale is `C`,
+/// otherwise the first of `LC_ALL`, `LC_MESSAGES` and `LANG` is used.
+pub fn requested_locales() -> Vec<LanguageIdentifier> {
+    let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+    let locale = var("LC_ALL").or_else(|| var("LC_MESSAGES")).or_else(|| var("LANG"));
+
+    let mut locales = Vec::new();
+    if let Some(primary) = locale.as_deref().and_then(parse_posix_locale) {
+        if let Some(language) = var("LANGUAGE") {
+            locales.extend(language.split(':').filter_map(parse_posix_locale));
+        }
+        if !locales.contains(&primary) {
+            locales.push(primary);
+        }
+    }
+
+    if locales.is_empty() {
+        locales.push(default_locale());
+    }
+    locales
+}
+
+/// Most preferred locale from the environment
+pub fn detect_locale() -> LanguageIdentifier {
+    requested_locales().remove(0)
+}
@@ -0,0 +1,305 @@
+//! Fluent bundles per domain and locale
+
+use crate::locale::{default_locale, parse_locale};
+use crate::{global, I18nError};
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use fluent_langneg::{negotiate_languages, NegotiationStrategy};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use unic_langid::LanguageIdentifier;
+
+type Bundle = FluentBundle<Arc<FluentResource>>;
+
+/// Translations for a set of domains, resolved against the requested locales
+pub struct Localizer {
+    /// Requested locales, most preferred first
+    requested: Vec<LanguageIdentifier>,
+    /// Parsed resources by domain and locale
+    resources: HashMap<String, HashMap<LanguageIdentifier, Arc<FluentResource>>>,
+    /// Bundles by domain, in fallback order
+    bundles: HashMap<String, Vec<Bundle>>,
+    /// Incremented whenever the requested locales change
+    generation: u64,
+}
+
+impl Localizer {
+    /// Create a localizer for the requested locales
+    pub fn new(requested: Vec<LanguageIdentifier>) -> Self {
+        Self {
+            requested,
+            resources: HashMap::new(),
+            bundles: HashMap::new(),
+            generation: 0,
+        }
+    }
+
+    /// Add a domain's Fluent source for a locale, replacing any earlier one
+    pub fn add_resource(&mut self, domain: &str, locale: &str, source: &str) -> Result<(), I18nError> {
+        let locale_id = parse_locale(locale)?;
+        let resource = FluentResource::try_new(source.to_string()).map_err(|(_, errors)| {
+            I18nError::InvalidResource {
+                domain: domain.to_string(),
+                locale: locale.to_string(),
+                message: errors.iter().map(|e| format!("{:?}", e.kind)).collect::<Vec<_>>().join("; "),
+            }
+        })?;
+
+        self.resources
+            .entry(domain.to_string())
+            .or_default()
+            .insert(locale_id, Arc::new(resource));
+        self.rebuild(domain);
+        Ok(())
+    }
+
+    /// Change the requested locales, returning whether anything changed
+    pub fn set_locales(&mut self, requested: Vec<LanguageIdentifier>) -> bool {
+        if requested.is_empty() || requested == self.requested {
+            return false;
+        }
+        self.requested = requested;
+        self.generation += 1;
+
+        let domains: Vec<String> = self.resources.keys().cloned().collect();
+        for domain in domains {
+            self.rebuild(&domain);
+        }
+        true
+    }
+
+    /// Most preferred requested locale
+    pub fn locale(&self) -> &LanguageIdentifier {
+        &self.requested[0]
+    }
+
+    /// Requested locales, most preferred first
+    pub fn requested_locales(&self) -> &[LanguageIdentifier] {
+        &self.requested
+    }
+
+    /// Incremented whenever the requested locales change
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Locales a domain has translations for
+    pub fn available_locales(&self, domain: &str) -> Vec<LanguageIdentifier> {
+        let mut locales: Vec<LanguageIdentifier> = self.resources.get(domain)
+            .map(|resources| resources.keys().cloned().collect())
+            .unwrap_or_default();
+        locales.sort_by_key(|locale| locale.to_string());
+        locales
+    }
+
+    /// Locales resolved for a domain, in fallback order
+    pub fn resolved_locales(&self, domain: &str) -> Vec<LanguageIdentifier> {
+        self.bundles.get(domain)
+            .map(|bundles| bundles.iter().filter_map(|bundle| bundle.locales.first().cloned()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Whether any resolved locale of a domain has a message
+    pub fn has_message(&self, domain: &str, id: &str) -> bool {
+        let (message_id, _) = split_id(id);
+        self.bundles.get(domain)
+            .is_some_and(|bundles| bundles.iter().any(|bundle| bundle.has_message(message_id)))
+    }
+
+    /// Format a message, or `None` if no resolved locale has it
+    ///
+    /// `id` may name an attribute as `message.attribute`.
+    pub fn try_format(&self, domain: &str, id: &str, args: Option<&FluentArgs>) -> Option<String> {
+        let (message_id, attribute) = split_id(id);
+
+        for bundle in self.bundles.get(domain)? {
+            let Some(message) = bundle.get_message(message_id) else {
+                continue;
+            };
+            let pattern = match attribute {
+                Some(attribute) => message.get_attribute(attribute).map(|attr| attr.value()),
+                None => message.value(),
+            };
+            let Some(pattern) = pattern else {
+                continue;
+            };
+
+            let mut errors = Vec::new();
+            let text = bundle.format_pattern(pattern, args, &mut errors);
+            if !errors.is_empty() {
+                log::warn!("Errors formatting {}/{}: {:?}", domain, id, errors);
+            }
+            return Some(text.into_owned());
+        }
+        None
+    }
+
+    /// Format a message, falling back to its ID when it is missing
+    pub fn format(&self, domain: &str, id: &str, args: Option<&FluentArgs>) -> String {
+        self.try_format(domain, id, args).unwrap_or_else(|| {
+            log::debug!("Missing translation {}/{} for {}", domain, id, self.locale());
+            id.to_string()
+        })
+    }
+
+    /// Rebuild a domain's bundles for the requested locales
+    fn rebuild(&mut self, domain: &str) {
+        let Some(resources) = self.resources.get(domain) else {
+            return;
+        };
+
+        let available: Vec<LanguageIdentifier> = resources.keys().cloned().collect();
+        let fallback = default_locale();
+        let negotiated = negotiate_languages(
+            &self.requested,
+            &available,
+            Some(&fallback),
+            NegotiationStrategy::Filtering,
+        );
+
+        let mut bundles = Vec::new();
+        for locale in negotiated {
+            let Some(resource) = resources.get(locale) else {
+                continue;
+            };
+            let mut bundle = Bundle::new_concurrent(vec![locale.clone()]);
+            // Unicode isolation marks render as boxes in the scene's text renderer
+            bundle.set_use_isolating(false);
+            if let Err(errors) = bundle.add_resource(resource.clone()) {
+                log::warn!("Duplicate messages in {} ({}): {:?}", domain, locale, errors);
+            }
+            bundles.push(bundle);
+        }
+        self.bundles.insert(domain.to_string(), bundles);
+    }
+}
+
+impl Default for Localizer {
+    fn default() -> Self {
+        Self::new(vec![default_locale()])
+    }
+}
+
+/// Split `message.attribute` into its parts
+fn split_id(id: &str) -> (&str, Option<&str>) {
+    match id.split_once('.') {
+        Some((message, attribute)) => (message, Some(attribute)),
+        None => (id, None),
+    }
+}
+
+/// A crate's translations, registered with the global localizer on first use
+pub struct Domain {
+    /// Domain name, also the `.ftl` file name
+    name: &'static str,
+    /// Fluent sources by locale
+    resources: &'static [(&'static str, &'static str)],
+    /// Set once the resources were added to the global localizer
+    registered: OnceLock<()>,
+}
+
+impl Domain {
+    /// Declare a domain with its embedded Fluent sources
+    pub const fn new(name: &'static str, resources: &'static [(&'static str, &'static str)]) -> Self {
+        Self {
+            name,
+            resources,
+            registered: OnceLock::new(),
+        }
+    }
+
+    /// Domain name
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Translate a message
+    pub fn tr(&self, id: &str) -> String {
+        self.format(id, None)
+    }
+
+    /// Translate a message with arguments
+    pub fn tr_args(&self, id: &str, args: &FluentArgs) -> String {
+        self.format(id, Some(args))
+    }
+
+    /// Translate a message, or `None` if it has no translation, e.g. for IDs built at runtime
+    pub fn try_tr(&self, id: &str, args: Option<&FluentArgs>) -> Option<String> {
+        self.ensure_registered();
+        global().read().unwrap_or_else(|e| e.into_inner()).try_format(self.name, id, args)
+    }
+
+    fn format(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        self.ensure_registered();
+        global().read().unwrap_or_else(|e| e.into_inner()).format(self.name, id, args)
+    }
+
+    fn ensure_registered(&self) {
+        self.registered.get_or_init(|| {
+            let mut localizer = global().write().unwrap_or_else(|e| e.into_inner());
+            for (locale, source) in self.resources {
+                if let Err(e) = localizer.add_resource(self.name, locale, source) {
+                    log::error!("Failed to load translations: {}", e);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EN: &str = r#"
+window-count = { $count ->
+    [one] One window
+   *[other] { $count } windows
+}
+greeting = Hello, { $name }!
+close = Close
+    .tooltip = Close this window
+"#;
+
+    const DE: &str = r#"
+window-count = { $count ->
+    [one] Ein Fenster
+   *[other] { $count } Fenster
+}
+"#;
+
+    fn localizer(locale: &str) -> Localizer {
+        let mut localizer = Localizer::new(vec![locale.parse().unwrap()]);
+        localizer.add_resource("test", "en-US", EN).unwrap();
+        localizer.add_resource("test", "de", DE).unwrap();
+        localizer
+    }
+
+    #[test]
+    fn test_plurals_and_fallback() {
+        let mut localizer = localizer("de-AT");
+        let mut args = FluentArgs::new();
+        args.set("count", 1);
+        assert_eq!(localizer.format("test", "window-count", Some(&args)), "Ein Fenster");
+        args.set("count", 3);
+        assert_eq!(localizer.format("test", "window-count", Some(&args)), "3 Fenster");
+
+        // Missing in German, taken from the default locale
+        assert_eq!(localizer.format("test", "close.tooltip", None), "Close this window");
+        assert_eq!(localizer.format("test", "missing", None), "missing");
+
+        assert!(localizer.set_locales(vec!["en-GB".parse().unwrap()]));
+        assert_eq!(localizer.generation(), 1);
+        assert_eq!(localizer.format("test", "window-count", Some(&args)), "3 windows");
+
+        let mut args = FluentArgs::new();
+        args.set("name", "Ada");
+        assert_eq!(localizer.format("test", "greeting", Some(&args)), "Hello, Ada!");
+    }
+
+    #[test]
+    fn test_invalid_resource_is_rejected() {
+        let mut localizer = Localizer::default();
+        assert!(localizer.add_resource("test", "en-US", "broken = {").is_err());
+        assert!(localizer.add_resource("test", "not a locale!", EN).is_err());
+    }
+}
@@ -524,8 +524,12 @@ impl AutoFlattenManager {
             return;
         }
 
-        // TODO: Check for overlapping nodes and add to pending flattens
-        self.pending_flattens.insert(node_id, Instant::now());
+        // Only nodes that overlap others are flattened
+        if engine.scene().find_overlapping_nodes(node_id, 0.0).is_empty() {
+            self.pending_flattens.remove(&node_id);
+        } else {
+            self.pending_flattens.insert(node_id, Instant::now());
+        }
     }
 
     pub fn flatten_area(&mut self, center: Position, radius: f32, engine: &mut GraphEngine) {
@@ -0,0 +1,446 @@
+//! Remappable pointer buttons and touch gesture bindings
+//!
+//! Pointer bindings come from the entries of `GraphDesktopConfig::shortcuts`
+//! whose keys name a mouse button, such as `MouseMiddle` or `Ctrl+MouseLeft`,
+//! and whose action is a [`PointerAction`]. A button may carry several
+//! actions; they are tried in [`PointerAction`] order, so `context_menu`
+//! applies over a node and the button's other action over empty space. A
+//! press with modifiers that have no binding of their own uses the button's
+//! plain binding, so Shift and Ctrl keep extending the selection. Built-in
+//! bindings stay in place for actions the configuration does not bind.
+//!
+//! Gesture bindings come from `GraphDesktopConfig::gestures` and map gesture
+//! names such as `pinch` or `three_finger_swipe_left` to a [`GestureAction`].
+
+use crate::keymap::ChordModifiers;
+use crate::{Gesture, SwipeDirection};
+use horizonos_graph_config::KeyboardShortcut;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use winit::event::MouseButton;
+
+/// What pressing a pointer button does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PointerAction {
+    /// Open the context menu of the node under the pointer; does nothing over empty space
+    ContextMenu,
+    /// Select and drag nodes and their handles, box-select on empty space
+    Select,
+    /// Box-select, even when starting over a node
+    BoxSelect,
+    /// Pan the camera while held
+    Pan,
+    /// Rotate the camera while held
+    Rotate,
+}
+
+impl PointerAction {
+    pub const ALL: [PointerAction; 5] = [
+        PointerAction::ContextMenu,
+        PointerAction::Select,
+        PointerAction::BoxSelect,
+        PointerAction::Pan,
+        PointerAction::Rotate,
+    ];
+
+    /// Name used in the configuration
+    pub fn name(&self) -> &'static str {
+        match self {
+            PointerAction::ContextMenu => "context_menu",
+            PointerAction::Select => "select",
+            PointerAction::BoxSelect => "box_select",
+            PointerAction::Pan => "pan",
+            PointerAction::Rotate => "rotate",
+        }
+    }
+}
+
+impl FromStr for PointerAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.name() == s)
+            .ok_or_else(|| format!("Unknown pointer action '{}'", s))
+    }
+}
+
+/// A mouse button with modifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PointerChord {
+    pub button: MouseButton,
+    pub modifiers: ChordModifiers,
+}
+
+impl PointerChord {
+    pub fn new(button: MouseButton, modifiers: ChordModifiers) -> Self {
+        Self { button, modifiers }
+    }
+}
+
+/// Names accepted for mouse buttons, first one used when printing
+const BUTTON_NAMES: &[(&str, MouseButton)] = &[
+    ("MouseLeft", MouseButton::Left),
+    ("MouseRight", MouseButton::Right),
+    ("MouseMiddle", MouseButton::Middle),
+    ("MouseBack", MouseButton::Back),
+    ("MouseForward", MouseButton::Forward),
+];
+
+impl FromStr for PointerChord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (modifier_part, button_part) = s.rsplit_once('+').unwrap_or(("", s));
+        let button = match BUTTON_NAMES.iter().find(|(name, _)| name.eq_ignore_ascii_case(button_part)) {
+            Some((_, button)) => *button,
+            None => button_part
+                .get(5..)
+                .filter(|_| button_part[..5].eq_ignore_ascii_case("mouse"))
+                .and_then(|number| number.parse().ok())
+                .map(MouseButton::Other)
+                .ok_or_else(|| format!("Unknown mouse button '{}' in '{}'", button_part, s))?,
+        };
+
+        let mut modifiers = ChordModifiers::default();
+        for modifier in modifier_part.split('+').filter(|m| !m.is_empty()) {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers.ctrl = true,
+                "alt" => modifiers.alt = true,
+                "shift" => modifiers.shift = true,
+                "super" | "logo" | "meta" | "win" => modifiers.logo = true,
+                other => return Err(format!("Unknown modifier '{}' in '{}'", other, s)),
+            }
+        }
+        Ok(Self::new(button, modifiers))
+    }
+}
+
+impl fmt::Display for PointerChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let m = self.modifiers;
+        for (held, name) in [(m.ctrl, "Ctrl+"), (m.alt, "Alt+"), (m.shift, "Shift+"), (m.logo, "Super+")] {
+            if held {
+                f.write_str(name)?;
+            }
+        }
+        match BUTTON_NAMES.iter().find(|(_, button)| *button == self.button) {
+            Some((name, _)) => f.write_str(name),
+            None => match self.button {
+                MouseButton::Other(number) => write!(f, "Mouse{}", number),
+                button => write!(f, "{:?}", button),
+            },
+        }
+    }
+}
+
+/// Pointer actions by button and modifiers
+#[derive(Debug, Clone)]
+pub struct PointerBindings {
+    bindings: HashMap<PointerChord, Vec<PointerAction>>,
+}
+
+impl Default for PointerBindings {
+    fn default() -> Self {
+        let mut bindings = Self { bindings: HashMap::new() };
+        for (button, action) in [
+            (MouseButton::Left, PointerAction::Select),
+            (MouseButton::Right, PointerAction::ContextMenu),
+            (MouseButton::Right, PointerAction::Rotate),
+            (MouseButton::Middle, PointerAction::Pan),
+        ] {
+            bindings.bind_chord(PointerChord::new(button, ChordModifiers::default()), action);
+        }
+        bindings
+    }
+}
+
+impl PointerBindings {
+    /// Bindings of `GraphDesktopConfig::shortcuts`, keeping the built-in
+    /// bindings of actions it does not bind; unparseable entries are skipped
+    pub fn from_config(shortcuts: &HashMap<String, KeyboardShortcut>) -> Self {
+        let mut configured = Self { bindings: HashMap::new() };
+        for (name, shortcut) in shortcuts {
+            if !horizonos_graph_config::is_pointer_binding(&shortcut.keys) {
+                continue;
+            }
+            if let Err(e) = configured.bind(&shortcut.keys, &shortcut.action) {
+                log::warn!("Ignoring pointer binding {}: {}", name, e);
+            }
+        }
+
+        let mut bindings = Self::default();
+        bindings.bindings.retain(|_, actions| {
+            actions.retain(|action| configured.chord_for(*action).is_none());
+            !actions.is_empty()
+        });
+        for (chord, actions) in configured.bindings {
+            for action in actions {
+                bindings.bind_chord(chord, action);
+            }
+        }
+        bindings
+    }
+
+    /// Bind a chord such as `Ctrl+MouseLeft` to an action such as `pan`
+    pub fn bind(&mut self, keys: &str, action: &str) -> Result<(), String> {
+        self.bind_chord(keys.parse()?, action.parse()?);
+        Ok(())
+    }
+
+    /// Add an action to a chord
+    pub fn bind_chord(&mut self, chord: PointerChord, action: PointerAction) {
+        let actions = self.bindings.entry(chord).or_default();
+        if !actions.contains(&action) {
+            actions.push(action);
+            actions.sort();
+        }
+    }
+
+    /// Actions to try for a press, in order
+    pub fn actions(&self, chord: &PointerChord) -> &[PointerAction] {
+        self.bindings
+            .get(chord)
+            .or_else(|| self.bindings.get(&PointerChord::new(chord.button, ChordModifiers::default())))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Chord bound to an action, for showing it in help and menus
+    pub fn chord_for(&self, action: PointerAction) -> Option<PointerChord> {
+        self.bindings
+            .iter()
+            .find(|(_, actions)| actions.contains(&action))
+            .map(|(chord, _)| *chord)
+    }
+}
+
+/// What a recognized touch gesture does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GestureAction {
+    /// Zoom the camera by the gesture's scale, or one step in for gestures without one
+    Zoom,
+    /// Pan the camera by the gesture's movement
+    Pan,
+    /// Rotate the camera by the gesture's angle
+    Rotate,
+    /// Ignore the gesture
+    None,
+    /// Run a shortcut action, reported through the shortcut callback
+    Shortcut(String),
+}
+
+impl From<&str> for GestureAction {
+    fn from(action: &str) -> Self {
+        match action {
+            "zoom" => GestureAction::Zoom,
+            "pan" => GestureAction::Pan,
+            "rotate" => GestureAction::Rotate,
+            "none" | "" => GestureAction::None,
+            other => GestureAction::Shortcut(other.to_string()),
+        }
+    }
+}
+
+/// Gesture names accepted in the configuration
+pub const GESTURE_NAMES: &[&str] = &[
+    "tap", "double_tap", "triple_tap", "long_press", "pan", "fling", "pinch", "rotate", "two_finger_pan",
+    "swipe_left", "swipe_right", "swipe_up", "swipe_down",
+    "three_finger_swipe_left", "three_finger_swipe_right", "three_finger_swipe_up", "three_finger_swipe_down",
+    "four_finger_swipe_left", "four_finger_swipe_right", "four_finger_swipe_up", "four_finger_swipe_down",
+    "circle_clockwise", "circle_counterclockwise", "zigzag", "rectangle", "line",
+    "pinch_and_rotate", "pan_and_pinch",
+];
+
+/// Actions of touch gestures, by gesture name
+#[derive(Debug, Clone)]
+pub struct GestureBindings {
+    bindings: HashMap<String, GestureAction>,
+}
+
+impl Default for GestureBindings {
+    fn default() -> Self {
+        let bindings = [
+            ("pinch", GestureAction::Zoom),
+            ("pan", GestureAction::Pan),
+            ("two_finger_pan", GestureAction::Pan),
+            ("rotate", GestureAction::Rotate),
+            ("three_finger_swipe_left", GestureAction::Shortcut("next_workspace".to_string())),
+            ("three_finger_swipe_right", GestureAction::Shortcut("previous_workspace".to_string())),
+        ];
+        Self {
+            bindings: bindings.into_iter().map(|(name, action)| (name.to_string(), action)).collect(),
+        }
+    }
+}
+
+impl GestureBindings {
+    /// Bindings of `GraphDesktopConfig::gestures` over the built-in ones; unknown gestures are skipped
+    pub fn from_config(gestures: &HashMap<String, String>) -> Self {
+        let mut bindings = Self::default();
+        for (gesture, action) in gestures {
+            if let Err(e) = bindings.bind(gesture, action.as_str().into()) {
+                log::warn!("Ignoring gesture binding: {}", e);
+            }
+        }
+        bindings
+    }
+
+    /// Bind a gesture name to an action
+    pub fn bind(&mut self, gesture: &str, action: GestureAction) -> Result<(), String> {
+        if !GESTURE_NAMES.contains(&gesture) {
+            return Err(format!("Unknown gesture '{}'", gesture));
+        }
+        self.bindings.insert(gesture.to_string(), action);
+        Ok(())
+    }
+
+    /// Action bound to a recognized gesture
+    pub fn action(&self, gesture: &Gesture) -> Option<&GestureAction> {
+        self.bindings.get(&gesture_name(gesture)?)
+    }
+}
+
+/// Configuration name of a touch gesture; mouse gestures have none, they follow the pointer bindings
+pub fn gesture_name(gesture: &Gesture) -> Option<String> {
+    let direction = |direction: &SwipeDirection| match direction {
+        SwipeDirection::Left => "left",
+        SwipeDirection::Right => "right",
+        SwipeDirection::Up => "up",
+        SwipeDirection::Down => "down",
+    };
+    let name = match gesture {
+        Gesture::Tap { .. } => "tap".to_string(),
+        Gesture::DoubleTap { .. } => "double_tap".to_string(),
+        Gesture::TripleTap { .. } => "triple_tap".to_string(),
+        Gesture::LongPress { .. } => "long_press".to_string(),
+        Gesture::Pan { .. } => "pan".to_string(),
+        Gesture::Swipe { direction: d, .. } => format!("swipe_{}", direction(d)),
+        Gesture::Fling { .. } => "fling".to_string(),
+        Gesture::Pinch { .. } => "pinch".to_string(),
+        Gesture::Rotate { .. } => "rotate".to_string(),
+        Gesture::TwoFingerPan { .. } => "two_finger_pan".to_string(),
+        Gesture::ThreeFingerSwipe { direction: d } => format!("three_finger_swipe_{}", direction(d)),
+        Gesture::FourFingerSwipe { direction: d } => format!("four_finger_swipe_{}", direction(d)),
+        Gesture::ZigZag { .. } => "zigzag".to_string(),
+        Gesture::Circle { clockwise: true, .. } => "circle_clockwise".to_string(),
+        Gesture::Circle { clockwise: false, .. } => "circle_counterclockwise".to_string(),
+        Gesture::Rectangle { .. } => "rectangle".to_string(),
+        Gesture::Line { .. } => "line".to_string(),
+        Gesture::PinchAndRotate { .. } => "pinch_and_rotate".to_string(),
+        Gesture::PanAndPinch { .. } => "pan_and_pinch".to_string(),
+        Gesture::Scroll { .. } | Gesture::RightClick { .. } | Gesture::MiddleClick { .. } | Gesture::MouseDrag { .. } => {
+            return None
+        }
+    };
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InteractionManager, TrackpadGesture};
+    use horizonos_graph_engine::Camera;
+    use std::sync::{Arc, Mutex};
+
+    fn shortcut(keys: &str, action: &str) -> KeyboardShortcut {
+        KeyboardShortcut { keys: keys.to_string(), action: action.to_string(), description: String::new() }
+    }
+
+    fn chord(s: &str) -> PointerChord {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_pointer_chords_parse_and_print() {
+        let ctrl = ChordModifiers { ctrl: true, ..Default::default() };
+        assert_eq!(chord("ctrl+mousemiddle"), PointerChord::new(MouseButton::Middle, ctrl));
+        assert_eq!(chord("Mouse8"), PointerChord::new(MouseButton::Other(8), ChordModifiers::default()));
+        assert_eq!(chord("Super+Shift+MouseBack").to_string(), "Shift+Super+MouseBack");
+        assert_eq!(chord("Mouse8").to_string(), "Mouse8");
+
+        assert!("MouseWheel".parse::<PointerChord>().is_err());
+        assert!("Hyper+MouseLeft".parse::<PointerChord>().is_err());
+        assert_eq!("rotate".parse(), Ok(PointerAction::Rotate));
+        assert!("spin".parse::<PointerAction>().is_err());
+    }
+
+    #[test]
+    fn test_configured_pointer_bindings_replace_built_in_ones() {
+        let shortcuts = HashMap::from([
+            ("pan".to_string(), shortcut("Ctrl+MouseLeft", "pan")),
+            ("box".to_string(), shortcut("MouseMiddle", "box_select")),
+            ("broken".to_string(), shortcut("MouseLeft", "spin")),
+            // Keyboard shortcuts are not pointer bindings
+            ("search".to_string(), shortcut("Super+Space", "search")),
+        ]);
+        let bindings = PointerBindings::from_config(&shortcuts);
+
+        assert_eq!(bindings.actions(&chord("Ctrl+MouseLeft")), [PointerAction::Pan]);
+        assert_eq!(bindings.actions(&chord("MouseMiddle")), [PointerAction::BoxSelect]);
+        assert_eq!(bindings.chord_for(PointerAction::Pan), Some(chord("Ctrl+MouseLeft")));
+        // Actions the configuration leaves alone keep their built-in buttons
+        assert_eq!(bindings.actions(&chord("MouseLeft")), [PointerAction::Select]);
+        assert_eq!(bindings.actions(&chord("MouseRight")), [PointerAction::ContextMenu, PointerAction::Rotate]);
+    }
+
+    #[test]
+    fn test_presses_fall_back_to_the_plain_button() {
+        let mut bindings = PointerBindings::default();
+        bindings.bind("Alt+MouseRight", "pan").unwrap();
+
+        assert_eq!(bindings.actions(&chord("Alt+MouseRight")), [PointerAction::Pan]);
+        // Shift and Ctrl keep extending the selection with the left button
+        assert_eq!(bindings.actions(&chord("Shift+MouseLeft")), [PointerAction::Select]);
+        assert_eq!(bindings.actions(&chord("Ctrl+MouseRight")), [PointerAction::ContextMenu, PointerAction::Rotate]);
+        assert!(bindings.actions(&chord("MouseForward")).is_empty());
+        assert!(bindings.bind("MouseLeft", "spin").is_err());
+    }
+
+    #[test]
+    fn test_gestures_are_bound_by_name() {
+        let gestures = HashMap::from([
+            ("pinch".to_string(), "none".to_string()),
+            ("four_finger_swipe_up".to_string(), "toggle_minimap".to_string()),
+            ("wiggle".to_string(), "zoom".to_string()),
+        ]);
+        let bindings = GestureBindings::from_config(&gestures);
+
+        let pinch = Gesture::Pinch { scale: 1.2, center: (0.0, 0.0), velocity: 0.0 };
+        assert_eq!(bindings.action(&pinch), Some(&GestureAction::None));
+        let swipe_up = Gesture::FourFingerSwipe { direction: SwipeDirection::Up };
+        assert_eq!(bindings.action(&swipe_up), Some(&GestureAction::Shortcut("toggle_minimap".to_string())));
+        let swipe_left = Gesture::ThreeFingerSwipe { direction: SwipeDirection::Left };
+        assert_eq!(bindings.action(&swipe_left), Some(&GestureAction::Shortcut("next_workspace".to_string())));
+        assert_eq!(bindings.action(&Gesture::Tap { position: (0.0, 0.0) }), None);
+        // Mouse gestures follow the pointer bindings instead
+        assert_eq!(gesture_name(&Gesture::RightClick { position: (0.0, 0.0) }), None);
+    }
+
+    #[test]
+    fn test_trackpad_gestures_run_their_bindings() {
+        let mut config = horizonos_graph_config::GraphDesktopConfig::default();
+        config.gestures.insert("three_finger_swipe_up".to_string(), "toggle_minimap".to_string());
+        let mut interaction = InteractionManager::new();
+        interaction.configure_keys(&config);
+        let actions = Arc::new(Mutex::new(Vec::new()));
+        let ran = actions.clone();
+        interaction.on_shortcut(move |action| ran.lock().unwrap().push(action.to_string()));
+        let mut camera = Camera::new();
+
+        for delta in [(-150.0, 10.0), (0.0, -150.0)] {
+            interaction.handle_trackpad_gesture(TrackpadGesture::SwipeBegin { fingers: 3 }, &mut camera);
+            interaction.handle_trackpad_gesture(TrackpadGesture::SwipeUpdate { delta }, &mut camera);
+            interaction.handle_trackpad_gesture(TrackpadGesture::SwipeEnd { cancelled: false }, &mut camera);
+        }
+        assert_eq!(*actions.lock().unwrap(), ["next_workspace", "toggle_minimap"]);
+
+        // Pinching zooms the camera
+        let fov = camera.fov;
+        interaction.handle_trackpad_gesture(TrackpadGesture::PinchBegin { fingers: 2 }, &mut camera);
+        interaction.handle_trackpad_gesture(TrackpadGesture::PinchUpdate { delta: (0.0, 0.0), scale: 1.5, rotation: 0.0 }, &mut camera);
+        assert_ne!(camera.fov, fov);
+    }
+}
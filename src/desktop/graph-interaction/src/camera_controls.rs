@@ -1,6 +1,6 @@
 //! Camera control and manipulation
 
-use horizonos_graph_engine::{Camera, GraphEngine, SceneId, Position, Ray};
+use horizonos_graph_engine::{Camera, GraphEngine, SceneId, Position, Ray, Minimap};
 use nalgebra::{Point3, Vector3, Unit};
 
 /// Camera state for rendering
@@ -87,6 +87,30 @@ impl CameraController {
         camera.look_at(position);
     }
     
+    /// Glide the camera to what was clicked in the minimap, keeping its
+    /// orientation and its distance to the graph
+    ///
+    /// Returns false if the click was outside the minimap.
+    pub fn navigate_minimap(&self, minimap: &Minimap, point: (f32, f32), camera: &mut Camera) -> bool {
+        let Some(target) = minimap.target_at([point.0, point.1]) else {
+            return false;
+        };
+        
+        let mut distance = (target - camera.position).dot(&camera.forward);
+        if distance <= camera.near {
+            distance = 10.0;
+        }
+        camera.move_to(target - camera.forward * distance);
+        true
+    }
+    
+    /// Fly the camera to a saved view, such as a camera bookmark
+    ///
+    /// The camera glides there as it is updated each frame.
+    pub fn fly_to(&self, camera: &mut Camera, view: &horizonos_graph_engine::CameraState) {
+        camera.transition_to(view);
+    }
+    
     /// Convert screen coordinates to a ray in world space
     pub fn screen_to_ray(&self, screen_pos: (f32, f32), camera: &Camera, window_size: (u32, u32)) -> Ray {
         // Use camera's screen_to_ray method
@@ -1,14 +1,45 @@
 //! Context menu system for node interactions
 
-use horizonos_graph_engine::SceneId;
+use crate::menu_extensions::{MenuContext, MenuExecution, MenuExtensionRegistry};
+use horizonos_graph_engine::{SceneId, SceneNode};
+use horizonos_graph_nodes::{NodeAction, NodeActionResult, NodeActionType};
 use std::collections::HashMap;
 
+/// Prefix of item IDs for custom actions a node offers, followed by the action name
+pub const NODE_ACTION_PREFIX: &str = "node-action:";
+
+/// Built-in items that run the node's own action of the same kind
+const NODE_ACTION_ITEMS: [(&str, NodeActionType); 3] = [
+    ("open", NodeActionType::Open),
+    ("edit", NodeActionType::Edit),
+    ("copy", NodeActionType::Copy),
+];
+
 /// Manages context menus for nodes
 pub struct ContextMenuManager {
     /// Active context menu
     active_menu: Option<ContextMenu>,
     /// Registered menu items by node type
     menu_items: HashMap<String, Vec<MenuItem>>,
+    /// Custom entries from scripts, plugins and .desktop actions
+    extensions: MenuExtensionRegistry,
+    /// Node the active menu was opened for
+    active_context: Option<MenuContext>,
+    /// Actions the node of the active menu offers, if they were looked up
+    active_actions: Option<Vec<NodeActionType>>,
+}
+
+/// What came of running a node's action from its context menu
+#[derive(Debug, Clone)]
+pub struct NodeActionOutcome {
+    pub node_id: SceneId,
+    /// Name the node is shown with
+    pub node_name: String,
+    pub action: NodeActionType,
+    /// The action as run, with its parameters
+    pub request: NodeAction,
+    /// The node's result, or why the action failed
+    pub result: Result<NodeActionResult, String>,
 }
 
 /// A context menu instance
@@ -38,9 +69,22 @@ impl ContextMenuManager {
         Self {
             active_menu: None,
             menu_items: HashMap::new(),
+            extensions: MenuExtensionRegistry::new(),
+            active_context: None,
+            active_actions: None,
         }
     }
     
+    /// Custom menu entries
+    pub fn extensions(&self) -> &MenuExtensionRegistry {
+        &self.extensions
+    }
+    
+    /// Custom menu entries, for registration
+    pub fn extensions_mut(&mut self) -> &mut MenuExtensionRegistry {
+        &mut self.extensions
+    }
+    
     /// Show context menu for a node
     pub fn show_for_node(&mut self, node_id: SceneId, position: (f32, f32)) {
         // Get default menu items
@@ -52,6 +96,110 @@ impl ContextMenuManager {
             items,
             visible: true,
         });
+        self.active_context = None;
+        self.active_actions = None;
+    }
+    
+    /// Show context menu for a scene node, including items registered for its
+    /// type and matching custom entries
+    pub fn show_for_scene_node(&mut self, node: &SceneNode, position: (f32, f32), selection_count: usize) {
+        self.show_with_actions(node, position, selection_count, None);
+    }
+    
+    /// Show context menu for a scene node with the actions the node offers
+    ///
+    /// Built-in items for actions the node lacks are disabled, and its custom
+    /// actions are listed under their labels. Clicking one of them is turned
+    /// into a node action by [`node_action`](Self::node_action).
+    pub fn show_for_node_actions(&mut self, node: &SceneNode, position: (f32, f32), selection_count: usize, actions: Vec<NodeActionType>) {
+        self.show_with_actions(node, position, selection_count, Some(actions));
+    }
+    
+    fn show_with_actions(&mut self, node: &SceneNode, position: (f32, f32), selection_count: usize, actions: Option<Vec<NodeActionType>>) {
+        let context = MenuContext::from_scene_node(node, selection_count);
+        let mut items = self.get_default_menu_items();
+        if let Some(actions) = &actions {
+            for item in &mut items {
+                if let Some((_, action)) = NODE_ACTION_ITEMS.iter().find(|(id, _)| *id == item.id) {
+                    item.enabled = actions.contains(action);
+                }
+            }
+            let custom: Vec<MenuItem> = actions.iter()
+                .filter_map(|action| match action {
+                    NodeActionType::Custom(name) => Some(MenuItem::new(format!("{}{}", NODE_ACTION_PREFIX, name), action.label())),
+                    _ => None,
+                })
+                .collect();
+            if !custom.is_empty() {
+                // Right after Open and Edit, before the clipboard items
+                let at = items.iter().position(|item| item.separator).unwrap_or(items.len());
+                items.splice(at..at, std::iter::once(MenuItem::separator()).chain(custom));
+            }
+        }
+        if let Some(type_items) = self.menu_items.get(context.node_type) {
+            items.push(MenuItem::separator());
+            items.extend(type_items.iter().cloned());
+        }
+        self.extensions.extend_menu(&mut items, &context);
+        
+        self.active_menu = Some(ContextMenu {
+            node_id: node.id,
+            position,
+            items,
+            visible: true,
+        });
+        self.active_context = Some(context);
+        self.active_actions = actions;
+    }
+    
+    /// The node action behind a clicked item of the active menu
+    ///
+    /// Returns `None` for items that are not node actions, and for all items
+    /// when the menu was opened without looking up the node's actions.
+    pub fn node_action(&self, item_id: &str) -> Option<(NodeActionType, NodeAction)> {
+        let actions = self.active_actions.as_ref()?;
+        let (action_type, action) = match item_id.strip_prefix(NODE_ACTION_PREFIX) {
+            Some(name) => (
+                NodeActionType::Custom(name.to_string()),
+                NodeAction::Custom { action_type: name.to_string(), parameters: HashMap::new() },
+            ),
+            None => {
+                let (_, action_type) = NODE_ACTION_ITEMS.iter().find(|(id, _)| *id == item_id)?;
+                let action = match action_type {
+                    NodeActionType::Open => NodeAction::Open,
+                    NodeActionType::Edit => NodeAction::Edit,
+                    _ => NodeAction::Copy,
+                };
+                (action_type.clone(), action)
+            }
+        };
+        actions.contains(&action_type).then_some((action_type, action))
+    }
+    
+    /// Run the custom entry behind a clicked item of the active menu
+    ///
+    /// Returns `None` for built-in items, which are handled through
+    /// `handle_item_click`.
+    pub fn activate_custom_item(&self, item_id: &str) -> Option<MenuExecution> {
+        let context = self.active_context.as_ref()?;
+        match self.extensions.activate(item_id, context) {
+            MenuExecution::NotFound => None,
+            execution => Some(execution),
+        }
+    }
+    
+    /// Add items the host offers for a node to its open menu
+    ///
+    /// Returns `false` when no menu is open for the node.
+    pub fn extend_active(&mut self, node_id: SceneId, items: Vec<MenuItem>) -> bool {
+        let Some(menu) = self.active_menu.as_mut().filter(|menu| menu.node_id == node_id) else {
+            return false;
+        };
+        if !items.is_empty() {
+            menu.items.push(MenuItem::separator());
+            menu.items.extend(items);
+        }
+        true
     }
     
     /// Hide the active context menu
@@ -64,6 +212,8 @@ impl ContextMenuManager {
     /// Clear the active context menu
     pub fn clear(&mut self) {
         self.active_menu = None;
+        self.active_context = None;
+        self.active_actions = None;
     }
     
     /// Get the active context menu
@@ -151,6 +301,9 @@ impl ContextMenuManager {
                 separator: true,
                 submenu: None,
             },
+            MenuItem::new("connect", "Connect to…")
+                .with_icon("link")
+                .with_shortcut("E"),
             MenuItem {
                 id: "delete".to_string(),
                 label: "Delete".to_string(),
@@ -1,6 +1,6 @@
 //! Drag and drop handling for nodes
 
-use horizonos_graph_engine::{GraphEngine, SceneId, Position};
+use horizonos_graph_engine::{GraphEngine, Position, Scene, SceneId};
 use nalgebra::Vector3;
 use std::collections::HashMap;
 
@@ -8,6 +8,8 @@ use std::collections::HashMap;
 pub struct DragDropHandler {
     /// Currently dragged node
     dragged_node: Option<SceneId>,
+    /// Nodes moving with the dragged node, including it
+    group: Vec<SceneId>,
     /// Drag start position (screen space)
     drag_start_pos: Option<(f32, f32)>,
     /// Original node positions (for multi-selection drag)
@@ -23,6 +25,7 @@ impl DragDropHandler {
     pub fn new() -> Self {
         Self {
             dragged_node: None,
+            group: Vec::new(),
             drag_start_pos: None,
             original_positions: HashMap::new(),
             drag_offset: (0.0, 0.0),
@@ -32,6 +35,17 @@ impl DragDropHandler {
     
     /// Start dragging a node
     pub fn start_drag(&mut self, node_id: SceneId, screen_pos: (f32, f32)) {
+        self.start_group_drag(node_id, vec![node_id], screen_pos);
+    }
+    
+    /// Start dragging a node together with other nodes, e.g. the rest of the selection
+    ///
+    /// The group moves rigidly: every node keeps its offset from the others.
+    pub fn start_group_drag(&mut self, node_id: SceneId, mut group: Vec<SceneId>, screen_pos: (f32, f32)) {
+        if !group.contains(&node_id) {
+            group.push(node_id);
+        }
+        self.group = group;
         self.dragged_node = Some(node_id);
         self.drag_start_pos = Some(screen_pos);
         self.is_dragging = true;
@@ -40,12 +54,24 @@ impl DragDropHandler {
         self.drag_offset = (0.0, 0.0);
     }
     
-    /// Update drag position
-    pub fn update_drag(&mut self, node_id: SceneId, screen_pos: (f32, f32), engine: &mut GraphEngine) {
+    /// Update drag position, returning the new positions of all moved nodes
+    pub fn update_drag(&mut self, node_id: SceneId, screen_pos: (f32, f32), engine: &mut GraphEngine) -> Vec<(SceneId, Position)> {
         if !self.is_dragging || self.dragged_node != Some(node_id) {
-            return;
+            return Vec::new();
         }
         
+        let moved = self.move_group(engine.scene_mut(), screen_pos);
+        
+        // Dragged nodes wake their physics islands
+        let ids: Vec<SceneId> = moved.iter().map(|(id, _)| *id).collect();
+        engine.nodes_moved(&ids);
+        moved
+    }
+    
+    /// Move every node of the group by the drag's delta from where it started
+    fn move_group(&mut self, scene: &mut Scene, screen_pos: (f32, f32)) -> Vec<(SceneId, Position)> {
+        let mut moved = Vec::new();
+        
         // Calculate delta from start position
         if let Some(start_pos) = self.drag_start_pos {
             let delta = (
@@ -61,27 +87,32 @@ impl DragDropHandler {
                 0.0,
             );
             
-            // Update node position
-            if let Some(node) = engine.scene_mut().get_node_mut(node_id) {
-                if let Some(original_pos) = self.original_positions.get(&node_id) {
-                    node.position = *original_pos + world_delta;
-                } else {
+            for id in &self.group {
+                if let Some(node) = scene.get_node_mut(*id) {
                     // Store original position on first update
-                    self.original_positions.insert(node_id, node.position);
-                    node.position = node.position + world_delta;
+                    let original_pos = *self.original_positions.entry(*id).or_insert(node.position);
+                    node.position = original_pos + world_delta;
+                    moved.push((*id, node.position));
                 }
             }
         }
+        moved
     }
     
     /// End drag operation
     pub fn end_drag(&mut self) {
         self.dragged_node = None;
+        self.group.clear();
         self.drag_start_pos = None;
         self.original_positions.clear();
         self.is_dragging = false;
     }
     
+    /// Positions the dragged nodes had before the drag
+    pub fn original_positions(&self) -> &HashMap<SceneId, Position> {
+        &self.original_positions
+    }
+    
     /// Check if currently dragging
     pub fn is_dragging(&self) -> bool {
         self.is_dragging
@@ -92,6 +123,11 @@ impl DragDropHandler {
         self.dragged_node
     }
     
+    /// Nodes moving with the drag
+    pub fn dragged_group(&self) -> &[SceneId] {
+        &self.group
+    }
+    
     /// Handle drag over event (for drop targets)
     pub fn handle_drag_over(&self, target_node: SceneId, screen_pos: (f32, f32)) -> DragOverResult {
         if !self.is_dragging {
@@ -138,6 +174,8 @@ impl DragDropHandler {
                 node.position = *original_pos;
             }
         }
+        let ids: Vec<SceneId> = self.original_positions.keys().copied().collect();
+        engine.nodes_moved(&ids);
         
         self.end_drag();
     }
@@ -157,4 +195,62 @@ pub enum DropAction {
     CreateEdge { source: SceneId, target: SceneId },
     MoveToFolder { node: SceneId, folder: SceneId },
     AttachToNode { child: SceneId, parent: SceneId },
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::{NodeMetadata, NodeType, SceneNode};
+
+    fn node(scene: &mut Scene, position: Position) -> SceneId {
+        scene.add_node(SceneNode {
+            id: 0,
+            position,
+            velocity: Vector3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type: NodeType::Concept { title: String::new(), content: String::new() },
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        })
+    }
+
+    #[test]
+    fn test_group_drag_keeps_relative_offsets() {
+        let mut scene = Scene::new();
+        let a = node(&mut scene, Position::new(0.0, 0.0, 0.0));
+        let b = node(&mut scene, Position::new(3.0, -1.0, 2.0));
+        let c = node(&mut scene, Position::new(-2.0, 4.0, 0.0));
+        let mut handler = DragDropHandler::new();
+        handler.start_group_drag(a, vec![b, c], (100.0, 100.0));
+        assert_eq!(handler.dragged_group(), [b, c, a]);
+
+        // Intermediate updates move from the start, not from the last update
+        handler.move_group(&mut scene, (300.0, 0.0));
+        let moved = handler.move_group(&mut scene, (200.0, 50.0));
+        assert_eq!(moved.len(), 3);
+
+        let position = |id| scene.get_node(id).unwrap().position;
+        assert_eq!(position(a), Position::new(1.0, 0.5, 0.0));
+        assert_eq!(position(b) - position(a), Vector3::new(3.0, -1.0, 2.0));
+        assert_eq!(position(c) - position(a), Vector3::new(-2.0, 4.0, 0.0));
+        assert_eq!(handler.original_positions()[&b], Position::new(3.0, -1.0, 2.0));
+    }
+
+    #[test]
+    fn test_group_drag_skips_removed_nodes() {
+        let mut scene = Scene::new();
+        let a = node(&mut scene, Position::new(0.0, 0.0, 0.0));
+        let mut handler = DragDropHandler::new();
+        handler.start_group_drag(a, vec![a, 42], (0.0, 0.0));
+
+        let moved = handler.move_group(&mut scene, (100.0, 0.0));
+        assert_eq!(moved, vec![(a, Position::new(1.0, 0.0, 0.0))]);
+
+        handler.end_drag();
+        assert!(!handler.is_dragging());
+        assert!(handler.dragged_group().is_empty());
+        assert!(handler.original_positions().is_empty());
+    }
+}
@@ -0,0 +1,310 @@
+//! Drawing a new edge from a node
+//!
+//! Edge creation starts on a source node, from a shortcut or the context
+//! menu. A ghost edge then follows the pointer; over another node it shows
+//! whether the edge manager's rules allow an edge of the chosen type between
+//! the two. Clicking a valid target adds the edge, anything else cancels.
+//! Keyboard connect mode in the accessibility layer drives the same
+//! controller, picking targets with [`EdgeCreationController::set_target`].
+
+use horizonos_graph_edges::{EdgeError, EdgeManager, EdgeOrigin};
+use horizonos_graph_engine::{EdgeType, GhostEdgeLine, NodeType, Position, RenderPalette, Scene, SceneEdge, SceneId};
+
+/// Type of edges drawn without choosing one
+pub const DEFAULT_EDGE_TYPE: EdgeType = EdgeType::RelatedTo { similarity: 1.0 };
+
+/// Ghost edges pointing at a node they cannot connect to
+const INVALID_GHOST_COLOR: [f32; 4] = [0.9, 0.3, 0.3, 0.9];
+/// Ghost edges are drawn thicker than most edges, to stand out while drawing
+const GHOST_THICKNESS: f32 = 2.5;
+
+/// Whether the ghost edge can be committed where it points
+#[derive(Debug, Clone, PartialEq)]
+pub enum GhostEdgeState {
+    /// Not over a node
+    Searching,
+    /// Over a node the edge can connect to
+    Valid,
+    /// Over a node the edge cannot connect to
+    Invalid { reason: String },
+}
+
+/// Preview of the edge being drawn
+#[derive(Debug, Clone)]
+pub struct GhostEdge {
+    pub source: SceneId,
+    /// Source node position
+    pub from: Position,
+    /// Target node position, or the pointer when not over a node
+    pub to: Position,
+    pub target: Option<SceneId>,
+    pub edge_type: EdgeType,
+    pub state: GhostEdgeState,
+}
+
+impl GhostEdge {
+    /// Line the renderer draws: the edge type's color, faint until it points
+    /// at a node and red when it cannot connect to it
+    pub fn line(&self, palette: &RenderPalette) -> GhostEdgeLine {
+        let mut color = palette.edge_color(&self.edge_type);
+        match self.state {
+            GhostEdgeState::Searching => color[3] *= 0.5,
+            GhostEdgeState::Valid => color[3] = 1.0,
+            GhostEdgeState::Invalid { .. } => color = INVALID_GHOST_COLOR,
+        }
+        GhostEdgeLine { from: self.from, to: self.to, color, thickness: GHOST_THICKNESS }
+    }
+}
+
+/// An edge added through edge creation
+#[derive(Debug, Clone)]
+pub struct CreatedEdge {
+    /// ID in the edge manager
+    pub edge_id: SceneId,
+    pub source: SceneId,
+    pub target: SceneId,
+    /// Edge to add to the scene for rendering
+    pub scene_edge: SceneEdge,
+}
+
+#[derive(Debug, Clone)]
+struct PendingEdge {
+    source: SceneId,
+    edge_type: EdgeType,
+    pointer: Position,
+    target: Option<SceneId>,
+    state: GhostEdgeState,
+}
+
+/// State of edge creation
+#[derive(Debug, Default)]
+pub struct EdgeCreationController {
+    pending: Option<PendingEdge>,
+}
+
+impl EdgeCreationController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Start drawing an edge from a node; false if the node does not exist
+    pub fn begin(&mut self, source: SceneId, edge_type: EdgeType, scene: &Scene) -> bool {
+        let Some(node) = scene.get_node(source) else {
+            return false;
+        };
+        self.pending = Some(PendingEdge {
+            source,
+            edge_type,
+            pointer: node.position,
+            target: None,
+            state: GhostEdgeState::Searching,
+        });
+        true
+    }
+
+    /// Node the edge starts from
+    pub fn source(&self) -> Option<SceneId> {
+        self.pending.as_ref().map(|pending| pending.source)
+    }
+    
+    /// Node the edge would connect to
+    pub fn target(&self) -> Option<SceneId> {
+        self.pending.as_ref().and_then(|pending| pending.target)
+    }
+    
+    /// Whether the edge can be committed to its current target
+    pub fn target_state(&self) -> Option<&GhostEdgeState> {
+        self.pending.as_ref().map(|pending| &pending.state)
+    }
+    
+    /// Point the edge at a node chosen without the pointer, validating it
+    pub fn set_target(&mut self, target: Option<SceneId>, scene: &Scene, edges: &EdgeManager) {
+        let Some(pending) = &mut self.pending else {
+            return;
+        };
+        if let Some(node) = target.and_then(|id| scene.get_node(id)) {
+            pending.pointer = node.position;
+        }
+        pending.target = target;
+        pending.state = validate(pending, scene, edges);
+    }
+    
+    /// Change the type of the edge being drawn, revalidating the target
+    pub fn set_edge_type(&mut self, edge_type: EdgeType, scene: &Scene, edges: &EdgeManager) {
+        if let Some(pending) = &mut self.pending {
+            pending.edge_type = edge_type;
+            pending.state = validate(pending, scene, edges);
+        }
+    }
+
+    /// Follow the pointer, validating the node under it
+    pub fn update(&mut self, pointer: Position, hovered: Option<SceneId>, scene: &Scene, edges: &EdgeManager) {
+        let Some(pending) = &mut self.pending else {
+            return;
+        };
+        pending.pointer = pointer;
+        if pending.target != hovered {
+            pending.target = hovered;
+            pending.state = validate(pending, scene, edges);
+        }
+    }
+
+    /// Preview to render
+    pub fn ghost(&self, scene: &Scene) -> Option<GhostEdge> {
+        let pending = self.pending.as_ref()?;
+        let from = scene.get_node(pending.source)?.position;
+        let to = pending.target
+            .and_then(|target| scene.get_node(target))
+            .map(|node| node.position)
+            .unwrap_or(pending.pointer);
+        Some(GhostEdge {
+            source: pending.source,
+            from,
+            to,
+            target: pending.target,
+            edge_type: pending.edge_type.clone(),
+            state: pending.state.clone(),
+        })
+    }
+
+    /// Add the edge to the node under the pointer and end edge creation
+    ///
+    /// `Ok(None)` when not over a node, which just ends edge creation.
+    pub fn commit(&mut self, scene: &Scene, edges: &mut EdgeManager) -> Result<Option<CreatedEdge>, EdgeError> {
+        let Some(pending) = self.pending.take() else {
+            return Ok(None);
+        };
+        let Some(target) = pending.target else {
+            return Ok(None);
+        };
+        let (source_type, target_type) = node_types(&pending, target, scene)?;
+        let edge_id = edges.add_typed_edge(pending.source, &source_type, target, &target_type, pending.edge_type)?;
+        edges.set_edge_origin(edge_id, EdgeOrigin::User)?;
+        let scene_edge = edges.get_edge(edge_id)
+            .ok_or(EdgeError::EdgeNotFound { id: edge_id })?
+            .to_scene_edge();
+        Ok(Some(CreatedEdge { edge_id, source: pending.source, target, scene_edge }))
+    }
+
+    /// Stop without adding an edge
+    pub fn cancel(&mut self) {
+        self.pending = None;
+    }
+}
+
+fn validate(pending: &PendingEdge, scene: &Scene, edges: &EdgeManager) -> GhostEdgeState {
+    let Some(target) = pending.target else {
+        return GhostEdgeState::Searching;
+    };
+    let result = node_types(pending, target, scene).and_then(|(source_type, target_type)| {
+        edges.check_edge(pending.source, &source_type, target, &target_type, &pending.edge_type)
+    });
+    match result {
+        Ok(()) => GhostEdgeState::Valid,
+        Err(e) => GhostEdgeState::Invalid { reason: e.to_string() },
+    }
+}
+
+/// Types of the source and target, rejecting edges from a node to itself
+fn node_types(pending: &PendingEdge, target: SceneId, scene: &Scene) -> Result<(NodeType, NodeType), EdgeError> {
+    let invalid = EdgeError::InvalidRelationship { source: pending.source, target };
+    if target == pending.source {
+        return Err(invalid);
+    }
+    match (scene.get_node(pending.source), scene.get_node(target)) {
+        (Some(source), Some(target)) => Ok((source.node_type.clone(), target.node_type.clone())),
+        _ => Err(invalid),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::{NodeMetadata, SceneNode};
+    use nalgebra::Vector3;
+
+    fn node(scene: &mut Scene, x: f32) -> SceneId {
+        scene.add_node(SceneNode {
+            id: 0,
+            position: Position::new(x, 0.0, 0.0),
+            velocity: Vector3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type: NodeType::Concept { title: String::new(), content: String::new() },
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        })
+    }
+
+    #[test]
+    fn test_ghost_edge_follows_the_pointer_and_shows_validity() {
+        let mut scene = Scene::new();
+        let a = node(&mut scene, 0.0);
+        let b = node(&mut scene, 5.0);
+        let mut edges = EdgeManager::new();
+        let palette = RenderPalette::default();
+        let mut creation = EdgeCreationController::new();
+        assert!(creation.ghost(&scene).is_none());
+        assert!(!creation.begin(99, DEFAULT_EDGE_TYPE, &scene));
+        assert!(creation.begin(a, DEFAULT_EDGE_TYPE, &scene));
+
+        // Away from nodes the ghost ends at the pointer, drawn faint
+        let pointer = Position::new(1.0, 2.0, 0.0);
+        creation.update(pointer, None, &scene, &edges);
+        let ghost = creation.ghost(&scene).unwrap();
+        assert_eq!((ghost.from, ghost.to, ghost.state.clone()), (Position::new(0.0, 0.0, 0.0), pointer, GhostEdgeState::Searching));
+        let line = ghost.line(&palette);
+        assert_eq!(line.color[3], palette.edge_color(&DEFAULT_EDGE_TYPE)[3] * 0.5);
+        assert_eq!((line.from, line.to), (ghost.from, ghost.to));
+
+        // Its own source is no target
+        creation.update(pointer, Some(a), &scene, &edges);
+        let ghost = creation.ghost(&scene).unwrap();
+        assert!(matches!(ghost.state, GhostEdgeState::Invalid { .. }));
+        assert_eq!(ghost.line(&palette).color, INVALID_GHOST_COLOR);
+
+        // Over another node it snaps to it
+        creation.update(pointer, Some(b), &scene, &edges);
+        let ghost = creation.ghost(&scene).unwrap();
+        assert_eq!((ghost.to, ghost.target, ghost.state.clone()), (Position::new(5.0, 0.0, 0.0), Some(b), GhostEdgeState::Valid));
+        assert_eq!(ghost.line(&palette).color[3], 1.0);
+
+        let created = creation.commit(&scene, &mut edges).unwrap().unwrap();
+        assert_eq!((created.source, created.target), (a, b));
+        assert!(creation.ghost(&scene).is_none());
+    }
+
+    #[test]
+    fn test_keyboard_targets_follow_the_same_rules() {
+        let mut scene = Scene::new();
+        let a = node(&mut scene, 0.0);
+        let b = node(&mut scene, 5.0);
+        let mut edges = EdgeManager::new();
+        let mut creation = EdgeCreationController::new();
+        creation.set_target(Some(b), &scene, &edges);
+        assert_eq!(creation.target(), None);
+
+        assert!(creation.begin(a, DEFAULT_EDGE_TYPE, &scene));
+        assert_eq!((creation.source(), creation.target()), (Some(a), None));
+        creation.set_target(Some(a), &scene, &edges);
+        assert!(matches!(creation.target_state(), Some(GhostEdgeState::Invalid { .. })));
+
+        creation.set_target(Some(b), &scene, &edges);
+        assert_eq!(creation.target_state(), Some(&GhostEdgeState::Valid));
+        assert_eq!(creation.ghost(&scene).unwrap().to, Position::new(5.0, 0.0, 0.0));
+        // Only people work on things
+        creation.set_edge_type(EdgeType::WorksOn, &scene, &edges);
+        assert!(matches!(creation.target_state(), Some(GhostEdgeState::Invalid { .. })));
+        creation.set_edge_type(EdgeType::DependsOn, &scene, &edges);
+
+        let created = creation.commit(&scene, &mut edges).unwrap().unwrap();
+        assert_eq!((created.source, created.target), (a, b));
+        assert!(matches!(created.scene_edge.edge_type, EdgeType::DependsOn));
+        assert!(!creation.is_active());
+    }
+}
@@ -0,0 +1,553 @@
+//! Focus mode: dim everything beyond a few relationship hops of a node
+//!
+//! Activating focus on a node keeps it and its neighbourhood, up to
+//! [`FocusModeConfig::hops`] edges away, at full opacity and fades the rest of
+//! the graph down (or out entirely). Only edges of the configured types are
+//! followed. The focus root can be stepped along edges; each step is recorded
+//! in a breadcrumb so the user can walk back.
+//!
+//! Focus mode changes the alpha and visibility of scene nodes and edges and
+//! restores them when it is deactivated, so call [`FocusMode::update`] every
+//! frame while it is active or fading out. Its settings come from the
+//! `[interaction.focus_mode]` section of the desktop configuration.
+
+use horizonos_graph_engine::{edge_kind, EdgeType, Scene, SceneEdge, SceneId};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::mem::discriminant;
+use std::time::Duration;
+
+/// Focus mode configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FocusModeConfig {
+    /// Relationship hops from the root that stay in focus
+    pub hops: u32,
+    /// Edge types to follow; empty follows all. Payloads such as a
+    /// similarity score are ignored when matching.
+    pub follow: Vec<EdgeType>,
+    /// Hide out-of-focus nodes instead of dimming them
+    pub hide_beyond: bool,
+    /// Opacity multiplier for out-of-focus nodes and edges
+    pub dimmed_opacity: f32,
+    /// Duration of the fade between focus states
+    pub fade_duration: Duration,
+    /// Longest breadcrumb kept
+    pub max_breadcrumb: usize,
+}
+
+impl Default for FocusModeConfig {
+    fn default() -> Self {
+        Self {
+            hops: 2,
+            follow: Vec::new(),
+            hide_beyond: false,
+            dimmed_opacity: 0.15,
+            fade_duration: Duration::from_millis(250),
+            max_breadcrumb: 32,
+        }
+    }
+}
+
+impl FocusModeConfig {
+    /// Settings from the desktop configuration; unknown edge types are skipped
+    pub fn from_config(config: &horizonos_graph_config::FocusModeConfig) -> Self {
+        let follow = config.follow.iter()
+            .filter_map(|kind| {
+                let edge_type = edge_type_for_kind(kind);
+                if edge_type.is_none() {
+                    log::warn!("Focus mode ignores unknown edge type '{}'", kind);
+                }
+                edge_type
+            })
+            .collect();
+        Self {
+            hops: config.hops,
+            follow,
+            hide_beyond: config.hide_beyond,
+            dimmed_opacity: config.dimmed_opacity,
+            fade_duration: Duration::from_millis(config.fade_duration_ms),
+            max_breadcrumb: config.max_breadcrumb.max(1),
+        }
+    }
+
+    /// Whether focus spreads along an edge
+    pub fn follows(&self, edge: &SceneEdge) -> bool {
+        self.follow.is_empty()
+            || self.follow.iter().any(|edge_type| discriminant(edge_type) == discriminant(&edge.edge_type))
+    }
+}
+
+/// One step of the focus root
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreadcrumbEntry {
+    /// Node that was the focus root
+    pub node: SceneId,
+    /// Edge taken to reach it; `None` for the first root
+    pub via_edge: Option<SceneId>,
+}
+
+/// Appearance saved before focus mode touched an element
+#[derive(Debug, Clone, Copy)]
+struct Original {
+    alpha: f32,
+    visible: bool,
+}
+
+/// Fade of one element towards its target opacity
+#[derive(Debug, Clone, Copy)]
+struct Fade {
+    current: f32,
+    target: f32,
+}
+
+/// Dims the graph outside the neighbourhood of a focus root
+pub struct FocusMode {
+    config: FocusModeConfig,
+    /// Path of focus roots, current root last
+    breadcrumb: Vec<BreadcrumbEntry>,
+    /// Hop distance of in-focus nodes from the root
+    distances: HashMap<SceneId, u32>,
+    node_fades: HashMap<SceneId, Fade>,
+    edge_fades: HashMap<SceneId, Fade>,
+    original_nodes: HashMap<SceneId, Original>,
+    original_edges: HashMap<SceneId, Original>,
+    /// Set when the configuration changed without a scene to refocus on
+    stale: bool,
+}
+
+impl FocusMode {
+    pub fn new(config: FocusModeConfig) -> Self {
+        Self {
+            config,
+            breadcrumb: Vec::new(),
+            distances: HashMap::new(),
+            node_fades: HashMap::new(),
+            edge_fades: HashMap::new(),
+            original_nodes: HashMap::new(),
+            original_edges: HashMap::new(),
+            stale: false,
+        }
+    }
+
+    pub fn config(&self) -> &FocusModeConfig {
+        &self.config
+    }
+
+    /// Change the configuration, recomputing an active focus
+    pub fn set_config(&mut self, config: FocusModeConfig, scene: &Scene) {
+        self.config = config;
+        if self.is_active() {
+            self.refocus(scene);
+        }
+    }
+
+    /// Change the configuration, recomputing an active focus on the next [`update`](Self::update)
+    pub fn configure(&mut self, config: FocusModeConfig) {
+        self.config = config;
+        self.stale = self.is_active();
+    }
+
+    /// Whether a focus root is set
+    pub fn is_active(&self) -> bool {
+        !self.breadcrumb.is_empty()
+    }
+
+    /// Whether any node or edge is still fading
+    pub fn is_animating(&self) -> bool {
+        self.node_fades.values().chain(self.edge_fades.values()).any(|fade| fade.current != fade.target)
+    }
+
+    /// Current focus root
+    pub fn root(&self) -> Option<SceneId> {
+        self.breadcrumb.last().map(|entry| entry.node)
+    }
+
+    /// Roots visited since focus was activated, oldest first
+    pub fn breadcrumb(&self) -> &[BreadcrumbEntry] {
+        &self.breadcrumb
+    }
+
+    /// Hop distance of a node from the root, `None` when out of focus
+    pub fn distance(&self, node: SceneId) -> Option<u32> {
+        self.distances.get(&node).copied()
+    }
+
+    /// Focus on `node`, starting a new breadcrumb
+    pub fn activate(&mut self, node: SceneId, scene: &Scene) -> bool {
+        if scene.get_node(node).is_none() {
+            return false;
+        }
+        self.breadcrumb = vec![BreadcrumbEntry { node, via_edge: None }];
+        self.refocus(scene);
+        true
+    }
+
+    /// Leave focus mode; the graph fades back to its normal appearance
+    pub fn deactivate(&mut self) {
+        self.breadcrumb.clear();
+        self.distances.clear();
+        for fade in self.node_fades.values_mut().chain(self.edge_fades.values_mut()) {
+            fade.target = 1.0;
+        }
+    }
+
+    /// Toggle focus on `node`, or leave focus mode when it is already the root
+    pub fn toggle(&mut self, node: SceneId, scene: &Scene) {
+        if self.root() == Some(node) {
+            self.deactivate();
+        } else {
+            self.activate(node, scene);
+        }
+    }
+
+    /// Move the root to a neighbour along a followed edge
+    ///
+    /// Returns false when `node` is not directly connected to the root by
+    /// such an edge.
+    pub fn step_to(&mut self, node: SceneId, scene: &Scene) -> bool {
+        let Some(root) = self.root() else {
+            return false;
+        };
+        let Some(edge) = scene
+            .get_connected_edges(root)
+            .into_iter()
+            .find(|edge| self.config.follows(edge) && other_end(edge, root) == node)
+        else {
+            return false;
+        };
+        let entry = BreadcrumbEntry { node, via_edge: Some(edge.id) };
+
+        // Stepping back onto the previous root unwinds instead of growing the trail
+        if self.breadcrumb.len() >= 2 && self.breadcrumb[self.breadcrumb.len() - 2].node == node {
+            self.breadcrumb.pop();
+        } else {
+            self.breadcrumb.push(entry);
+            if self.breadcrumb.len() > self.config.max_breadcrumb {
+                self.breadcrumb.remove(0);
+            }
+        }
+        self.refocus(scene);
+        true
+    }
+
+    /// Return to the previous root in the breadcrumb
+    pub fn step_back(&mut self, scene: &Scene) -> Option<SceneId> {
+        if self.breadcrumb.len() < 2 {
+            return None;
+        }
+        self.breadcrumb.pop();
+        self.refocus(scene);
+        self.root()
+    }
+
+    /// Jump back to a breadcrumb entry, dropping the roots after it
+    pub fn jump_to_breadcrumb(&mut self, index: usize, scene: &Scene) -> Option<SceneId> {
+        if index >= self.breadcrumb.len() {
+            return None;
+        }
+        self.breadcrumb.truncate(index + 1);
+        self.refocus(scene);
+        self.root()
+    }
+
+    /// Change how many hops stay in focus
+    pub fn set_hops(&mut self, hops: u32, scene: &Scene) {
+        self.config.hops = hops;
+        if self.is_active() {
+            self.refocus(scene);
+        }
+    }
+
+    /// Advance fades and apply them to the scene
+    pub fn update(&mut self, delta_time: f32, scene: &mut Scene) {
+        if self.is_active() {
+            // Nodes and edges may have been added since focus was computed
+            let changed = scene.nodes().any(|(id, _)| !self.node_fades.contains_key(id))
+                || scene.edges().any(|edge| !self.edge_fades.contains_key(&edge.id));
+            if changed || self.stale {
+                self.refocus(scene);
+            }
+        }
+        self.stale = false;
+
+        let step = if self.config.fade_duration.is_zero() {
+            1.0
+        } else {
+            delta_time / self.config.fade_duration.as_secs_f32()
+        };
+        advance(&mut self.node_fades, step);
+        advance(&mut self.edge_fades, step);
+
+        let hide = self.config.hide_beyond;
+        self.node_fades.retain(|id, fade| {
+            let (Some(node), Some(original)) = (scene.get_node_mut(*id), self.original_nodes.get(id)) else {
+                return false;
+            };
+            node.color[3] = original.alpha * fade.current;
+            node.visible = original.visible && !(hide && fade.current <= 0.0);
+            true
+        });
+        self.edge_fades.retain(|id, fade| {
+            let (Some(edge), Some(original)) = (scene.get_edge_mut(*id), self.original_edges.get(id)) else {
+                return false;
+            };
+            edge.color[3] = original.alpha * fade.current;
+            edge.visible = original.visible && !(hide && fade.current <= 0.0);
+            true
+        });
+
+        // Fade-out finished: the scene is back to normal, forget what was saved
+        if !self.is_active() && !self.is_animating() {
+            self.node_fades.clear();
+            self.edge_fades.clear();
+            self.original_nodes.clear();
+            self.original_edges.clear();
+        }
+    }
+
+    /// Recompute the focused neighbourhood and fade targets
+    fn refocus(&mut self, scene: &Scene) {
+        let Some(root) = self.root() else {
+            return;
+        };
+        self.distances = self.neighbourhood(scene, root);
+
+        let dimmed = if self.config.hide_beyond { 0.0 } else { self.config.dimmed_opacity.clamp(0.0, 1.0) };
+        for (id, node) in scene.nodes() {
+            self.original_nodes.entry(*id).or_insert(Original { alpha: node.color[3], visible: node.visible });
+            let target = if self.distances.contains_key(id) { 1.0 } else { dimmed };
+            self.node_fades.entry(*id).or_insert(Fade { current: 1.0, target }).target = target;
+        }
+        for edge in scene.edges() {
+            self.original_edges.entry(edge.id).or_insert(Original { alpha: edge.color[3], visible: edge.visible });
+            let inside = self.distances.contains_key(&edge.source)
+                && self.distances.contains_key(&edge.target)
+                && self.config.follows(edge);
+            let target = if inside { 1.0 } else { dimmed };
+            self.edge_fades.entry(edge.id).or_insert(Fade { current: 1.0, target }).target = target;
+        }
+    }
+
+    /// Breadth-first hop distances from `root` along followed edges
+    fn neighbourhood(&self, scene: &Scene, root: SceneId) -> HashMap<SceneId, u32> {
+        let mut adjacency: HashMap<SceneId, HashSet<SceneId>> = HashMap::new();
+        for edge in scene.edges().filter(|edge| self.config.follows(edge)) {
+            adjacency.entry(edge.source).or_default().insert(edge.target);
+            adjacency.entry(edge.target).or_default().insert(edge.source);
+        }
+
+        let mut distances = HashMap::from([(root, 0)]);
+        let mut queue = VecDeque::from([root]);
+        while let Some(node) = queue.pop_front() {
+            let distance = distances[&node];
+            if distance >= self.config.hops {
+                continue;
+            }
+            for neighbour in adjacency.get(&node).into_iter().flatten() {
+                if !distances.contains_key(neighbour) {
+                    distances.insert(*neighbour, distance + 1);
+                    queue.push_back(*neighbour);
+                }
+            }
+        }
+        distances
+    }
+}
+
+impl Default for FocusMode {
+    fn default() -> Self {
+        Self::new(FocusModeConfig::default())
+    }
+}
+
+/// Edge type of a palette key such as `depends_on`, with empty payloads
+fn edge_type_for_kind(kind: &str) -> Option<EdgeType> {
+    [
+        EdgeType::Contains,
+        EdgeType::DependsOn,
+        EdgeType::CommunicatesWith,
+        EdgeType::CreatedBy,
+        EdgeType::RelatedTo { similarity: 0.0 },
+        EdgeType::Temporal { sequence_order: 0 },
+        EdgeType::TaggedAs { tag: String::new() },
+        EdgeType::WorksOn,
+    ]
+    .into_iter()
+    .find(|edge_type| edge_kind(edge_type) == kind)
+}
+
+fn other_end(edge: &SceneEdge, node: SceneId) -> SceneId {
+    if edge.source == node {
+        edge.target
+    } else {
+        edge.source
+    }
+}
+
+/// Move each fade up to `step` towards its target
+fn advance(fades: &mut HashMap<SceneId, Fade>, step: f32) {
+    for fade in fades.values_mut() {
+        let difference = fade.target - fade.current;
+        fade.current = if difference.abs() <= step { fade.target } else { fade.current + step * difference.signum() };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::{NodeMetadata, NodeType, Position, SceneNode};
+    use nalgebra::Vector3;
+
+    fn node(scene: &mut Scene) -> SceneId {
+        scene.add_node(SceneNode {
+            id: 0,
+            position: Position::new(0.0, 0.0, 0.0),
+            velocity: Vector3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type: NodeType::Concept { title: String::new(), content: String::new() },
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        })
+    }
+
+    fn edge(scene: &mut Scene, source: SceneId, target: SceneId, edge_type: EdgeType) -> SceneId {
+        scene.add_edge(SceneEdge {
+            id: 0,
+            source,
+            target,
+            edge_type,
+            weight: 1.0,
+            color: [1.0; 4],
+            visible: true,
+            animated: false,
+        })
+    }
+
+    /// Chain a - b - c - d of `Contains` edges with e depending on a
+    fn chain() -> (Scene, [SceneId; 5]) {
+        let mut scene = Scene::new();
+        let nodes = [(); 5].map(|_| node(&mut scene));
+        let [a, b, c, d, e] = nodes;
+        edge(&mut scene, a, b, EdgeType::Contains);
+        edge(&mut scene, b, c, EdgeType::Contains);
+        edge(&mut scene, c, d, EdgeType::Contains);
+        edge(&mut scene, e, a, EdgeType::DependsOn);
+        (scene, nodes)
+    }
+
+    fn instant() -> FocusModeConfig {
+        FocusModeConfig { fade_duration: Duration::ZERO, ..FocusModeConfig::default() }
+    }
+
+    fn alpha(scene: &Scene, node: SceneId) -> f32 {
+        scene.get_node(node).unwrap().color[3]
+    }
+
+    #[test]
+    fn test_enter_dims_beyond_the_hops_and_exit_restores() {
+        let (mut scene, [a, b, c, d, e]) = chain();
+        let mut focus = FocusMode::new(instant());
+        assert!(!focus.activate(99, &scene));
+
+        assert!(focus.activate(a, &scene));
+        focus.update(0.0, &mut scene);
+        assert_eq!(focus.root(), Some(a));
+        assert_eq!([a, b, c, d, e].map(|node| focus.distance(node)), [Some(0), Some(1), Some(2), None, Some(1)]);
+        assert_eq!(alpha(&scene, c), 1.0);
+        assert_eq!(alpha(&scene, d), 0.15);
+
+        // Shift+F on the root again leaves focus mode
+        focus.toggle(a, &scene);
+        focus.update(0.0, &mut scene);
+        assert!(!focus.is_active());
+        assert!(!focus.is_animating());
+        assert_eq!(alpha(&scene, d), 1.0);
+        assert!(scene.get_node(d).unwrap().visible);
+    }
+
+    #[test]
+    fn test_depth_grows_and_shrinks() {
+        let (mut scene, [a, _, c, d, _]) = chain();
+        let mut focus = FocusMode::new(instant());
+        focus.activate(a, &scene);
+
+        focus.set_hops(focus.config().hops + 1, &scene);
+        focus.update(0.0, &mut scene);
+        assert_eq!(focus.distance(d), Some(3));
+        assert_eq!(alpha(&scene, d), 1.0);
+
+        for _ in 0..3 {
+            focus.set_hops(focus.config().hops.saturating_sub(1), &scene);
+        }
+        focus.update(0.0, &mut scene);
+        assert_eq!(focus.config().hops, 0);
+        assert_eq!(focus.distance(a), Some(0));
+        assert_eq!(focus.distance(c), None);
+        assert_eq!(alpha(&scene, c), 0.15);
+
+        // Depth stays at the root
+        focus.set_hops(focus.config().hops.saturating_sub(1), &scene);
+        assert_eq!(focus.config().hops, 0);
+    }
+
+    #[test]
+    fn test_fades_take_the_configured_time() {
+        let (mut scene, [a, _, _, d, _]) = chain();
+        let mut focus = FocusMode::new(FocusModeConfig { fade_duration: Duration::from_millis(100), ..FocusModeConfig::default() });
+        focus.activate(a, &scene);
+
+        focus.update(0.05, &mut scene);
+        assert!(focus.is_animating());
+        assert!((alpha(&scene, d) - 0.5).abs() < 1e-4);
+        focus.update(0.05, &mut scene);
+        assert!(!focus.is_animating());
+        assert!((alpha(&scene, d) - 0.15).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_stepping_records_the_breadcrumb() {
+        let (scene, [a, b, c, d, _]) = chain();
+        let mut focus = FocusMode::new(instant());
+        focus.activate(a, &scene);
+
+        assert!(!focus.step_to(c, &scene));
+        assert!(focus.step_to(b, &scene));
+        assert!(focus.step_to(c, &scene));
+        assert_eq!(focus.breadcrumb().iter().map(|entry| entry.node).collect::<Vec<_>>(), [a, b, c]);
+        assert_eq!(focus.distance(d), Some(1));
+
+        // Stepping onto the previous root unwinds the trail
+        assert!(focus.step_to(b, &scene));
+        assert_eq!(focus.breadcrumb().len(), 2);
+        assert_eq!(focus.step_back(&scene), Some(a));
+        assert_eq!(focus.step_back(&scene), None);
+    }
+
+    #[test]
+    fn test_configuration_from_the_desktop_config() {
+        let (mut scene, [a, b, _, _, e]) = chain();
+        let config = horizonos_graph_config::FocusModeConfig {
+            hops: 1,
+            follow: vec!["depends_on".to_string(), "unknown".to_string()],
+            fade_duration_ms: 0,
+            ..Default::default()
+        };
+        let config = FocusModeConfig::from_config(&config);
+        assert_eq!(config.hops, 1);
+        assert_eq!(config.follow.len(), 1);
+
+        // An active focus follows the new configuration on the next frame
+        let mut focus = FocusMode::new(instant());
+        focus.activate(a, &scene);
+        focus.update(0.0, &mut scene);
+        assert_eq!(focus.distance(b), Some(1));
+        focus.configure(config);
+        focus.update(0.0, &mut scene);
+        assert_eq!(focus.distance(b), None);
+        assert_eq!(focus.distance(e), Some(1));
+        assert_eq!(alpha(&scene, b), 0.15);
+    }
+}
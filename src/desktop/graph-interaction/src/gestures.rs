@@ -1,4 +1,4 @@
-//! Gesture recognition for touch and mouse inputs
+//! Gesture recognition for touch, trackpad and mouse inputs
 
 use crate::{Gesture, SwipeDirection, KeyboardModifiers, MouseButton};
 use winit::event::{Touch, TouchPhase, MouseButton as WinitMouseButton, ElementState};
@@ -35,6 +35,50 @@ pub struct GestureRecognizer {
     velocity_tracker: VelocityTracker,
     /// Path tracking for shape recognition
     path_tracker: PathTracker,
+    /// Trackpad gesture in progress
+    trackpad: Option<TrackpadState>,
+}
+
+/// Distance a trackpad swipe has to travel before it counts, in logical pixels
+const TRACKPAD_SWIPE_DISTANCE: f32 = 100.0;
+
+/// Trackpad gesture reported by the compositor's input backend (libinput)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrackpadGesture {
+    /// Fingers went down to pinch
+    PinchBegin { fingers: u32 },
+    /// Pinch moved; `scale` is relative to the begin event, `rotation` in degrees since the last update
+    PinchUpdate { delta: (f32, f32), scale: f32, rotation: f32 },
+    /// Fingers lifted, or the pinch turned into another gesture
+    PinchEnd { cancelled: bool },
+    /// Fingers went down to swipe
+    SwipeBegin { fingers: u32 },
+    /// Swipe moved by `delta` since the last update
+    SwipeUpdate { delta: (f32, f32) },
+    /// Fingers lifted, or the swipe turned into another gesture
+    SwipeEnd { cancelled: bool },
+}
+
+/// Progress of a trackpad gesture
+#[derive(Clone, Debug)]
+struct TrackpadState {
+    fingers: u32,
+    /// Scale of the last pinch update
+    scale: f32,
+    /// Distance swiped since the begin event
+    travel: (f32, f32),
+    start_time: Instant,
+}
+
+impl TrackpadState {
+    fn new(fingers: u32) -> Self {
+        Self {
+            fingers,
+            scale: 1.0,
+            travel: (0.0, 0.0),
+            start_time: Instant::now(),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -244,6 +288,7 @@ impl GestureRecognizer {
             tap_count: 0,
             velocity_tracker: VelocityTracker::new(),
             path_tracker: PathTracker::new(),
+            trackpad: None,
         }
     }
     
@@ -364,6 +409,70 @@ impl GestureRecognizer {
         }
     }
     
+    /// Process a trackpad gesture event
+    ///
+    /// Pinches are reported at the cursor with the scale change since the
+    /// previous update; swipes of three or more fingers are reported when the
+    /// fingers lift after travelling far enough.
+    pub fn process_trackpad(&mut self, event: TrackpadGesture) {
+        match event {
+            TrackpadGesture::PinchBegin { fingers } | TrackpadGesture::SwipeBegin { fingers } => {
+                self.trackpad = Some(TrackpadState::new(fingers));
+            }
+            TrackpadGesture::PinchUpdate { scale, .. } => {
+                let Some(state) = self.trackpad.as_mut() else {
+                    return;
+                };
+                if state.scale <= 0.0 || scale <= 0.0 {
+                    return;
+                }
+                let relative = scale / state.scale;
+                state.scale = scale;
+                if (relative - 1.0).abs() > 0.001 {
+                    self.current_gesture = Some(Gesture::Pinch {
+                        scale: relative,
+                        center: self.cursor_pos,
+                        velocity: (relative - 1.0) * 60.0, // Approximate velocity
+                    });
+                }
+            }
+            TrackpadGesture::SwipeUpdate { delta } => {
+                if let Some(state) = self.trackpad.as_mut() {
+                    state.travel.0 += delta.0;
+                    state.travel.1 += delta.1;
+                }
+            }
+            TrackpadGesture::PinchEnd { .. } => {
+                self.trackpad = None;
+            }
+            TrackpadGesture::SwipeEnd { cancelled } => {
+                let Some(state) = self.trackpad.take() else {
+                    return;
+                };
+                let (dx, dy) = state.travel;
+                if cancelled || distance((0.0, 0.0), state.travel) < TRACKPAD_SWIPE_DISTANCE {
+                    return;
+                }
+                let direction = if dx.abs() > dy.abs() {
+                    if dx > 0.0 { SwipeDirection::Right } else { SwipeDirection::Left }
+                } else if dy > 0.0 {
+                    SwipeDirection::Down
+                } else {
+                    SwipeDirection::Up
+                };
+                self.current_gesture = match state.fingers {
+                    3 => Some(Gesture::ThreeFingerSwipe { direction }),
+                    n if n >= 4 => Some(Gesture::FourFingerSwipe { direction }),
+                    _ => {
+                        let travelled = distance((0.0, 0.0), state.travel);
+                        let velocity = travelled / state.start_time.elapsed().as_secs_f32().max(f32::EPSILON);
+                        Some(Gesture::Swipe { direction, velocity, distance: travelled })
+                    }
+                };
+            }
+        }
+    }
+    
     /// Analyze current touches to determine gesture
     fn analyze_gesture(&mut self) {
         match self.touches.len() {
@@ -498,4 +607,50 @@ fn distance(p1: (f32, f32), p2: (f32, f32)) -> f32 {
 /// Calculate angle between two points
 fn angle(p1: (f32, f32), p2: (f32, f32)) -> f32 {
     (p2.1 - p1.1).atan2(p2.0 - p1.0)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trackpad_pinch_reports_relative_scale() {
+        let mut recognizer = GestureRecognizer::new();
+        recognizer.update_cursor((40.0, 30.0));
+        recognizer.process_trackpad(TrackpadGesture::PinchBegin { fingers: 2 });
+        recognizer.process_trackpad(TrackpadGesture::PinchUpdate { delta: (0.0, 0.0), scale: 1.5, rotation: 0.0 });
+        match recognizer.get_gesture() {
+            Some(Gesture::Pinch { scale, center, .. }) => {
+                assert!((scale - 1.5).abs() < 1e-5);
+                assert_eq!(center, (40.0, 30.0));
+            }
+            other => panic!("expected a pinch, got {:?}", other),
+        }
+
+        // Libinput scales are relative to the begin event, gestures to the last update
+        recognizer.process_trackpad(TrackpadGesture::PinchUpdate { delta: (0.0, 0.0), scale: 3.0, rotation: 0.0 });
+        assert!(matches!(recognizer.get_gesture(), Some(Gesture::Pinch { scale, .. }) if (scale - 2.0).abs() < 1e-5));
+
+        recognizer.process_trackpad(TrackpadGesture::PinchEnd { cancelled: false });
+        recognizer.process_trackpad(TrackpadGesture::PinchUpdate { delta: (0.0, 0.0), scale: 2.0, rotation: 0.0 });
+        assert!(recognizer.get_gesture().is_none());
+    }
+
+    #[test]
+    fn test_trackpad_swipes_count_fingers_and_distance() {
+        let mut recognizer = GestureRecognizer::new();
+        let mut swipe = |fingers, delta, cancelled| {
+            recognizer.process_trackpad(TrackpadGesture::SwipeBegin { fingers });
+            for _ in 0..4 {
+                recognizer.process_trackpad(TrackpadGesture::SwipeUpdate { delta });
+            }
+            recognizer.process_trackpad(TrackpadGesture::SwipeEnd { cancelled });
+            recognizer.get_gesture()
+        };
+
+        assert!(matches!(swipe(3, (-40.0, 5.0), false), Some(Gesture::ThreeFingerSwipe { direction: SwipeDirection::Left })));
+        assert!(matches!(swipe(4, (0.0, 40.0), false), Some(Gesture::FourFingerSwipe { direction: SwipeDirection::Down })));
+        assert!(swipe(3, (10.0, 0.0), false).is_none());
+        assert!(swipe(3, (40.0, 0.0), true).is_none());
+    }
+}
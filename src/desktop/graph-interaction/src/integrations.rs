@@ -0,0 +1,916 @@
+//! Declarative integrations installed by third-party applications
+//!
+//! An application describes how it plugs into the graph with a manifest in
+//! `$XDG_DATA_HOME/horizonos/integrations.d/` or one of the `$XDG_DATA_DIRS`,
+//! written in TOML or JSON. It can provide node types, context menu actions,
+//! URL and file handlers, automation triggers and icons:
+//!
+//! ```toml
+//! id = "org.example.Notes"
+//! name = "Example Notes"
+//! version = "1.4.0"
+//! icon = "notes"
+//!
+//! [icons]
+//! notes = "icons/notes.svg"
+//!
+//! [[node_type]]
+//! id = "note"
+//! label = "Note"
+//! base = "concept"
+//! icon = "notes"
+//!
+//! [[action]]
+//! id = "append"
+//! label = "Append to Note"
+//! target = { node_types = ["file"] }
+//! command = { type = "exec", argv = ["example-notes", "--append", "%f"] }
+//!
+//! [[handler]]
+//! id = "open"
+//! schemes = ["notes"]
+//! extensions = ["md"]
+//! argv = ["example-notes", "%u"]
+//!
+//! [[trigger]]
+//! id = "index"
+//! event = "on-create"
+//! filter = { node_type = "file", path_prefix = "~/Notes" }
+//! action = { type = "script", path = "/usr/libexec/example-notes/index" }
+//! ```
+//!
+//! Manifests are validated when scanned, and nothing in them takes effect
+//! until the user grants the permissions they ask for. A grant covers the
+//! permissions shown at the time; an update that asks for more prompts again.
+
+use horizonos_graph_nodes::hooks::{HookAction, HookRegistry, LifecycleHook};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+use crate::menu_extensions::{
+    CustomMenuEntry, MenuCommand, MenuCondition, MenuContext, MenuEntrySource, MenuExtensionRegistry, MenuPlacement,
+    CUSTOM_ITEM_PREFIX,
+};
+
+/// Node type names a provided node type can be based on
+const BASE_NODE_TYPES: &[&str] = &[
+    "application",
+    "file",
+    "person",
+    "task",
+    "device",
+    "ai_agent",
+    "concept",
+    "system",
+    "url",
+    "automation",
+    "setting",
+    "config_group",
+];
+
+/// A node type provided by an integration
+///
+/// Nodes of the type are nodes of the built-in `base` type carrying its tag.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProvidedNodeType {
+    /// Id, unique within the manifest
+    pub id: String,
+    /// Name shown to the user
+    pub label: String,
+    /// Built-in node type name, e.g. `concept`
+    pub base: String,
+    /// Icon name or key of the manifest's `icons`
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Tag marking nodes of this type; defaults to `<manifest id>:<id>`
+    #[serde(default)]
+    pub tag: Option<String>,
+}
+
+impl ProvidedNodeType {
+    /// Tag carried by nodes of this type
+    pub fn tag_for(&self, manifest_id: &str) -> String {
+        self.tag.clone().unwrap_or_else(|| format!("{}:{}", manifest_id, self.id))
+    }
+}
+
+/// URL schemes and file extensions an integration opens
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntegrationHandler {
+    /// Id, unique within the manifest
+    pub id: String,
+    /// Menu label; defaults to "Open in <name>"
+    #[serde(default)]
+    pub label: Option<String>,
+    /// URL schemes, without the colon
+    #[serde(default)]
+    pub schemes: Vec<String>,
+    /// File extensions, without the dot
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Program and arguments, with the field codes of menu `exec` commands
+    pub argv: Vec<String>,
+}
+
+/// Manifest file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestFormat {
+    Toml,
+    Json,
+}
+
+impl ManifestFormat {
+    /// Format of a manifest file, from its extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Some(ManifestFormat::Toml),
+            Some("json") => Some(ManifestFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// An application's integration manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntegrationManifest {
+    /// Reverse-DNS application id
+    pub id: String,
+    /// Application name
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Application icon name or key of `icons`
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Icon files by name, relative to the manifest
+    #[serde(default)]
+    pub icons: BTreeMap<String, PathBuf>,
+    #[serde(default, rename = "node_type")]
+    pub node_types: Vec<ProvidedNodeType>,
+    /// Context menu actions; their `source` is ignored
+    #[serde(default, rename = "action")]
+    pub actions: Vec<CustomMenuEntry>,
+    #[serde(default, rename = "handler")]
+    pub handlers: Vec<IntegrationHandler>,
+    /// Lifecycle hooks; their sandbox cannot be disabled
+    #[serde(default, rename = "trigger")]
+    pub triggers: Vec<LifecycleHook>,
+}
+
+impl IntegrationManifest {
+    /// Parse a manifest
+    pub fn parse(content: &str, format: ManifestFormat) -> Result<Self, String> {
+        match format {
+            ManifestFormat::Toml => toml::from_str(content).map_err(|e| format!("Invalid manifest: {}", e)),
+            ManifestFormat::Json => serde_json::from_str(content).map_err(|e| format!("Invalid manifest: {}", e)),
+        }
+    }
+
+    /// Read and parse a manifest file
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        let format = ManifestFormat::from_path(path).ok_or_else(|| format!("{} is not a .toml or .json file", path.display()))?;
+        let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse(&content, format)
+    }
+
+    /// Problems that keep the manifest from being installed; `base_dir` is
+    /// the directory icon paths are relative to
+    pub fn validate(&self, base_dir: &Path) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        let valid_id = |id: &str| !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+        if !valid_id(&self.id) {
+            problems.push(format!("Invalid id '{}'", self.id));
+        }
+        if self.name.trim().is_empty() {
+            problems.push("Missing name".to_string());
+        }
+
+        for (name, path) in &self.icons {
+            let escapes = path.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+            if escapes {
+                problems.push(format!("Icon '{}' must be a path inside the manifest directory", name));
+            } else if !base_dir.join(path).is_file() {
+                problems.push(format!("Icon '{}' not found at {}", name, path.display()));
+            }
+        }
+
+        let mut check_ids = |section: &str, ids: Vec<&str>| {
+            let mut seen = HashSet::new();
+            for id in ids {
+                if !valid_id(id) {
+                    problems.push(format!("Invalid {} id '{}'", section, id));
+                } else if !seen.insert(id) {
+                    problems.push(format!("Duplicate {} id '{}'", section, id));
+                }
+            }
+        };
+        check_ids("node type", self.node_types.iter().map(|t| t.id.as_str()).collect());
+        check_ids("action", self.actions.iter().map(|a| a.id.as_str()).collect());
+        check_ids("handler", self.handlers.iter().map(|h| h.id.as_str()).collect());
+        check_ids("trigger", self.triggers.iter().map(|t| t.id.as_str()).collect());
+
+        for node_type in &self.node_types {
+            if !BASE_NODE_TYPES.contains(&node_type.base.as_str()) {
+                problems.push(format!("Node type '{}' has unknown base '{}'", node_type.id, node_type.base));
+            }
+        }
+        for action in &self.actions {
+            let empty = match &action.command {
+                MenuCommand::Exec { argv } => argv.is_empty(),
+                MenuCommand::Script { path, .. } => path.is_empty(),
+                MenuCommand::Workflow { workflow_id } => workflow_id.is_empty(),
+            };
+            if empty {
+                problems.push(format!("Action '{}' has an empty command", action.id));
+            }
+        }
+        for handler in &self.handlers {
+            if handler.schemes.is_empty() && handler.extensions.is_empty() {
+                problems.push(format!("Handler '{}' declares no schemes or extensions", handler.id));
+            }
+            if handler.argv.is_empty() {
+                problems.push(format!("Handler '{}' has an empty command", handler.id));
+            }
+            let valid_scheme = |s: &String| s.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+                && s.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+            if let Some(scheme) = handler.schemes.iter().find(|s| !valid_scheme(s)) {
+                problems.push(format!("Handler '{}' has invalid scheme '{}'", handler.id, scheme));
+            }
+        }
+        for trigger in &self.triggers {
+            if !trigger.sandbox.enabled {
+                problems.push(format!("Trigger '{}' cannot run outside the sandbox", trigger.id));
+            }
+        }
+        problems
+    }
+
+    /// Permissions the user is asked for before the integration is activated
+    pub fn permissions(&self) -> Vec<IntegrationPermission> {
+        let mut permissions = Vec::new();
+        if !self.node_types.is_empty() {
+            permissions.push(IntegrationPermission::NodeTypes {
+                types: self.node_types.iter().map(|t| t.label.clone()).collect(),
+            });
+        }
+        if !self.actions.is_empty() {
+            permissions.push(IntegrationPermission::ContextMenu {
+                actions: self.actions.iter().map(|a| a.label.clone()).collect(),
+            });
+        }
+
+        let schemes = sorted_unique(self.handlers.iter().flat_map(|h| h.schemes.iter().map(|s| s.to_ascii_lowercase())));
+        if !schemes.is_empty() {
+            permissions.push(IntegrationPermission::OpenUrls { schemes });
+        }
+        let extensions = sorted_unique(self.handlers.iter().flat_map(|h| {
+            h.extensions.iter().map(|e| e.trim_start_matches('.').to_ascii_lowercase())
+        }));
+        if !extensions.is_empty() {
+            permissions.push(IntegrationPermission::OpenFiles { extensions });
+        }
+        let events = sorted_unique(self.triggers.iter().map(|t| t.event.as_str().to_string()));
+        if !events.is_empty() {
+            permissions.push(IntegrationPermission::Triggers { events });
+        }
+
+        let mut programs = Vec::new();
+        let mut workflows = Vec::new();
+        for action in &self.actions {
+            match &action.command {
+                MenuCommand::Exec { argv } => programs.extend(argv.first().cloned()),
+                MenuCommand::Script { path, .. } => programs.push(path.clone()),
+                MenuCommand::Workflow { workflow_id } => workflows.push(workflow_id.clone()),
+            }
+        }
+        programs.extend(self.handlers.iter().filter_map(|h| h.argv.first().cloned()));
+        for trigger in &self.triggers {
+            match &trigger.action {
+                HookAction::Shell { command } => programs.push(format!("sh -c '{}'", command)),
+                HookAction::Script { path, .. } => programs.push(path.clone()),
+                HookAction::Workflow { workflow_id } => workflows.push(workflow_id.clone()),
+            }
+        }
+        let programs = sorted_unique(programs);
+        if !programs.is_empty() {
+            permissions.push(IntegrationPermission::RunPrograms { programs });
+        }
+        let workflows = sorted_unique(workflows);
+        if !workflows.is_empty() {
+            permissions.push(IntegrationPermission::StartWorkflows { workflows });
+        }
+
+        if self.triggers.iter().any(|t| t.sandbox.allow_network) {
+            permissions.push(IntegrationPermission::Network);
+        }
+        let paths = sorted_unique(self.triggers.iter().flat_map(|t| t.sandbox.writable_paths.iter().cloned()));
+        if !paths.is_empty() {
+            permissions.push(IntegrationPermission::WritePaths { paths });
+        }
+        permissions
+    }
+
+    fn menu_entries(&self, base_dir: &Path) -> Vec<CustomMenuEntry> {
+        let source = MenuEntrySource::Plugin { plugin_id: self.id.clone() };
+        let mut entries: Vec<CustomMenuEntry> = self
+            .actions
+            .iter()
+            .map(|action| {
+                let mut entry = action.clone().from_source(source.clone());
+                entry.id = format!("{}:{}", self.id, action.id);
+                entry.icon = action.icon.as_deref().map(|icon| self.resolve_icon(base_dir, icon));
+                entry
+            })
+            .collect();
+        entries.extend(self.handler_entries(base_dir));
+        entries
+    }
+
+    /// Menu entries for the handlers; each becomes one entry per kind, as
+    /// entry conditions must all hold
+    fn handler_entries(&self, base_dir: &Path) -> Vec<CustomMenuEntry> {
+        let source = MenuEntrySource::Plugin { plugin_id: self.id.clone() };
+        let mut entries = Vec::new();
+        for handler in &self.handlers {
+            let label = handler.label.clone().unwrap_or_else(|| format!("Open in {}", self.name));
+            let entry_for = |kind: &str, node_type: &str, condition: MenuCondition| {
+                let mut entry = CustomMenuEntry::new(
+                    format!("{}:{}:{}", self.id, handler.id, kind),
+                    label.clone(),
+                    MenuCommand::Exec { argv: handler.argv.clone() },
+                )
+                .for_node_types(&[node_type])
+                .placed(MenuPlacement::After("open".to_string()), 0)
+                .enabled_when(condition)
+                .from_source(source.clone());
+                entry.icon = self.icon.as_deref().map(|icon| self.resolve_icon(base_dir, icon));
+                entry
+            };
+            if !handler.extensions.is_empty() {
+                entries.push(entry_for("files", "file", MenuCondition::Extension(handler.extensions.clone())));
+            }
+            if !handler.schemes.is_empty() {
+                entries.push(entry_for("urls", "url", MenuCondition::UrlScheme(handler.schemes.clone())));
+            }
+        }
+        entries
+    }
+
+    /// Icon file for a key of `icons`, otherwise the name itself
+    fn resolve_icon(&self, base_dir: &Path, icon: &str) -> String {
+        match self.icons.get(icon) {
+            Some(path) => base_dir.join(path).to_string_lossy().into_owned(),
+            None => icon.to_string(),
+        }
+    }
+}
+
+fn sorted_unique(items: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut items: Vec<String> = items.into_iter().collect();
+    items.sort();
+    items.dedup();
+    items
+}
+
+/// Something an integration is allowed to do
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum IntegrationPermission {
+    /// Add node types
+    NodeTypes { types: Vec<String> },
+    /// Add context menu actions
+    ContextMenu { actions: Vec<String> },
+    /// Open URLs with these schemes
+    OpenUrls { schemes: Vec<String> },
+    /// Open files with these extensions
+    OpenFiles { extensions: Vec<String> },
+    /// Run when nodes are created, opened or deleted
+    Triggers { events: Vec<String> },
+    /// Run these programs
+    RunPrograms { programs: Vec<String> },
+    /// Start these automation workflows
+    StartWorkflows { workflows: Vec<String> },
+    /// Use the network from triggers
+    Network,
+    /// Write to these paths from triggers
+    WritePaths { paths: Vec<String> },
+}
+
+impl IntegrationPermission {
+    /// Whether granting `self` also grants `other`
+    pub fn covers(&self, other: &IntegrationPermission) -> bool {
+        use IntegrationPermission::*;
+        let subset = |granted: &Vec<String>, asked: &Vec<String>| asked.iter().all(|item| granted.contains(item));
+        match (self, other) {
+            (NodeTypes { types: a }, NodeTypes { types: b }) => subset(a, b),
+            (ContextMenu { actions: a }, ContextMenu { actions: b }) => subset(a, b),
+            (OpenUrls { schemes: a }, OpenUrls { schemes: b }) => subset(a, b),
+            (OpenFiles { extensions: a }, OpenFiles { extensions: b }) => subset(a, b),
+            (Triggers { events: a }, Triggers { events: b }) => subset(a, b),
+            (RunPrograms { programs: a }, RunPrograms { programs: b }) => subset(a, b),
+            (StartWorkflows { workflows: a }, StartWorkflows { workflows: b }) => subset(a, b),
+            (Network, Network) => true,
+            (WritePaths { paths: a }, WritePaths { paths: b }) => subset(a, b),
+            _ => false,
+        }
+    }
+
+    /// Description shown in the permission prompt
+    pub fn describe(&self) -> String {
+        match self {
+            IntegrationPermission::NodeTypes { types } => format!("Add node types: {}", types.join(", ")),
+            IntegrationPermission::ContextMenu { actions } => format!("Add context menu actions: {}", actions.join(", ")),
+            IntegrationPermission::OpenUrls { schemes } => {
+                format!("Open links: {}", schemes.iter().map(|s| format!("{}:", s)).collect::<Vec<_>>().join(", "))
+            }
+            IntegrationPermission::OpenFiles { extensions } => {
+                format!("Open files: {}", extensions.iter().map(|e| format!(".{}", e)).collect::<Vec<_>>().join(", "))
+            }
+            IntegrationPermission::Triggers { events } => format!("Run automatically on: {}", events.join(", ")),
+            IntegrationPermission::RunPrograms { programs } => format!("Run programs: {}", programs.join(", ")),
+            IntegrationPermission::StartWorkflows { workflows } => format!("Start workflows: {}", workflows.join(", ")),
+            IntegrationPermission::Network => "Access the network from automatic triggers".to_string(),
+            IntegrationPermission::WritePaths { paths } => format!("Write to: {}", paths.join(", ")),
+        }
+    }
+}
+
+/// The user's answer to an integration's permission prompt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntegrationGrant {
+    pub manifest_id: String,
+    /// Whether the integration was allowed
+    pub allowed: bool,
+    /// Permissions shown when the user answered
+    pub permissions: Vec<IntegrationPermission>,
+}
+
+impl IntegrationGrant {
+    /// Whether the answer still applies to a manifest asking for `permissions`
+    pub fn covers(&self, permissions: &[IntegrationPermission]) -> bool {
+        permissions.iter().all(|asked| self.permissions.iter().any(|granted| granted.covers(asked)))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct GrantsFile {
+    #[serde(default, rename = "grant")]
+    grants: Vec<IntegrationGrant>,
+}
+
+/// Whether an installed integration is in effect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrationState {
+    /// Waiting for the user to answer the permission prompt
+    Pending,
+    /// Allowed and registered
+    Active,
+    /// Refused by the user
+    Denied,
+}
+
+/// A valid manifest found by a scan
+#[derive(Debug, Clone)]
+pub struct InstalledIntegration {
+    pub manifest: IntegrationManifest,
+    /// Manifest file
+    pub path: PathBuf,
+    pub state: IntegrationState,
+}
+
+impl InstalledIntegration {
+    fn base_dir(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new("."))
+    }
+}
+
+/// Permission prompt for a pending integration
+#[derive(Debug, Clone, PartialEq)]
+pub struct PermissionPrompt {
+    pub manifest_id: String,
+    pub name: String,
+    pub version: Option<String>,
+    /// Icon name or file
+    pub icon: Option<String>,
+    pub permissions: Vec<IntegrationPermission>,
+    /// The integration was allowed before and now asks for more
+    pub is_update: bool,
+}
+
+/// Outcome of scanning manifest directories
+#[derive(Debug, Clone, Default)]
+pub struct ScanReport {
+    /// Valid manifests found
+    pub installed: usize,
+    /// Manifests that were skipped and why
+    pub rejected: Vec<(PathBuf, Vec<String>)>,
+}
+
+/// Installed integrations, the user's permission answers and their registration
+pub struct IntegrationRegistry {
+    integrations: Vec<InstalledIntegration>,
+    grants: HashMap<String, IntegrationGrant>,
+    /// Hook ids registered per manifest by the last `apply`
+    applied: HashMap<String, Vec<String>>,
+}
+
+impl IntegrationRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self {
+            integrations: Vec::new(),
+            grants: HashMap::new(),
+            applied: HashMap::new(),
+        }
+    }
+
+    /// Manifest directories, highest priority first
+    pub fn default_dirs() -> Vec<PathBuf> {
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")));
+        let data_dirs = std::env::var_os("XDG_DATA_DIRS")
+            .map(|dirs| std::env::split_paths(&dirs).collect::<Vec<_>>())
+            .unwrap_or_else(|| vec![PathBuf::from("/usr/local/share"), PathBuf::from("/usr/share")]);
+        data_home
+            .into_iter()
+            .chain(data_dirs)
+            .map(|dir| dir.join("horizonos").join("integrations.d"))
+            .collect()
+    }
+
+    /// Default location of the user's permission answers
+    pub fn default_grants_path() -> Option<PathBuf> {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|config| config.join("horizonos").join("integrations.toml"))
+    }
+
+    /// Load permission answers; a missing file means none were given
+    pub fn load_grants(&mut self, path: &Path) -> Result<usize, String> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        let file: GrantsFile = toml::from_str(&content).map_err(|e| format!("Invalid grants file: {}", e))?;
+        self.grants = file.grants.into_iter().map(|grant| (grant.manifest_id.clone(), grant)).collect();
+        self.refresh_states();
+        Ok(self.grants.len())
+    }
+
+    /// Write permission answers
+    pub fn save_grants(&self, path: &Path) -> Result<(), String> {
+        let mut grants: Vec<IntegrationGrant> = self.grants.values().cloned().collect();
+        grants.sort_by(|a, b| a.manifest_id.cmp(&b.manifest_id));
+        let content = toml::to_string_pretty(&GrantsFile { grants }).map_err(|e| format!("Failed to serialize grants: {}", e))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Replace the installed integrations with the valid manifests in `dirs`;
+    /// a manifest id found in several directories is taken from the first
+    pub fn scan(&mut self, dirs: &[PathBuf]) -> ScanReport {
+        let mut report = ScanReport::default();
+        let mut integrations: Vec<InstalledIntegration> = Vec::new();
+        for dir in dirs {
+            let read_dir = match std::fs::read_dir(dir) {
+                Ok(read_dir) => read_dir,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    report.rejected.push((dir.clone(), vec![format!("Failed to read directory: {}", e)]));
+                    continue;
+                }
+            };
+            let mut paths: Vec<PathBuf> = read_dir
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| ManifestFormat::from_path(path).is_some())
+                .collect();
+            paths.sort();
+
+            for path in paths {
+                let manifest = match IntegrationManifest::from_path(&path) {
+                    Ok(manifest) => manifest,
+                    Err(e) => {
+                        report.rejected.push((path, vec![e]));
+                        continue;
+                    }
+                };
+                let problems = manifest.validate(path.parent().unwrap_or(dir));
+                if !problems.is_empty() {
+                    report.rejected.push((path, problems));
+                    continue;
+                }
+                if integrations.iter().any(|i| i.manifest.id == manifest.id) {
+                    log::debug!("Integration {} at {} is shadowed", manifest.id, path.display());
+                    continue;
+                }
+                integrations.push(InstalledIntegration {
+                    manifest,
+                    path,
+                    state: IntegrationState::Pending,
+                });
+            }
+        }
+
+        for (path, problems) in &report.rejected {
+            log::warn!("Skipping integration manifest {}: {}", path.display(), problems.join("; "));
+        }
+        report.installed = integrations.len();
+        self.integrations = integrations;
+        self.refresh_states();
+        report
+    }
+
+    fn refresh_states(&mut self) {
+        for integration in &mut self.integrations {
+            integration.state = match self.grants.get(&integration.manifest.id) {
+                Some(grant) if grant.covers(&integration.manifest.permissions()) => {
+                    if grant.allowed {
+                        IntegrationState::Active
+                    } else {
+                        IntegrationState::Denied
+                    }
+                }
+                _ => IntegrationState::Pending,
+            };
+        }
+    }
+
+    /// Installed integrations
+    pub fn integrations(&self) -> &[InstalledIntegration] {
+        &self.integrations
+    }
+
+    /// Get an installed integration by manifest id
+    pub fn get(&self, manifest_id: &str) -> Option<&InstalledIntegration> {
+        self.integrations.iter().find(|i| i.manifest.id == manifest_id)
+    }
+
+    /// Prompts for integrations waiting for an answer
+    pub fn pending_prompts(&self) -> Vec<PermissionPrompt> {
+        self.integrations
+            .iter()
+            .filter(|i| i.state == IntegrationState::Pending)
+            .map(|integration| {
+                let manifest = &integration.manifest;
+                PermissionPrompt {
+                    manifest_id: manifest.id.clone(),
+                    name: manifest.name.clone(),
+                    version: manifest.version.clone(),
+                    icon: manifest.icon.as_deref().map(|icon| manifest.resolve_icon(integration.base_dir(), icon)),
+                    permissions: manifest.permissions(),
+                    is_update: self.grants.get(&manifest.id).is_some_and(|grant| grant.allowed),
+                }
+            })
+            .collect()
+    }
+
+    /// Record the user's answer for an integration; call `apply` afterwards
+    pub fn answer(&mut self, manifest_id: &str, allowed: bool) -> bool {
+        let Some(integration) = self.integrations.iter_mut().find(|i| i.manifest.id == manifest_id) else {
+            return false;
+        };
+        self.grants.insert(
+            manifest_id.to_string(),
+            IntegrationGrant {
+                manifest_id: manifest_id.to_string(),
+                allowed,
+                permissions: integration.manifest.permissions(),
+            },
+        );
+        integration.state = if allowed { IntegrationState::Active } else { IntegrationState::Denied };
+        true
+    }
+
+    /// Forget the answer for an integration so the user is asked again
+    pub fn revoke(&mut self, manifest_id: &str) -> bool {
+        let removed = self.grants.remove(manifest_id).is_some();
+        self.refresh_states();
+        removed
+    }
+
+    /// Register the menu entries and hooks of active integrations, removing
+    /// those of integrations that are no longer active or installed
+    pub fn apply(&mut self, menus: &mut MenuExtensionRegistry, hooks: &mut HookRegistry) {
+        for (manifest_id, hook_ids) in self.applied.drain() {
+            menus.unregister_plugin(&manifest_id);
+            for id in hook_ids {
+                hooks.remove(&id);
+            }
+        }
+
+        for integration in self.integrations.iter().filter(|i| i.state == IntegrationState::Active) {
+            let manifest = &integration.manifest;
+            for entry in manifest.menu_entries(integration.base_dir()) {
+                menus.register(entry);
+            }
+
+            let mut hook_ids = Vec::new();
+            for trigger in &manifest.triggers {
+                let mut hook = trigger.clone();
+                hook.id = format!("{}:{}", manifest.id, trigger.id);
+                let id = hook.id.clone();
+                match hooks.add(hook) {
+                    Ok(()) => hook_ids.push(id),
+                    Err(e) => log::warn!("Integration {} trigger '{}' not registered: {}", manifest.id, trigger.id, e),
+                }
+            }
+            log::info!("Activated integration {} ({})", manifest.id, manifest.name);
+            self.applied.insert(manifest.id.clone(), hook_ids);
+        }
+    }
+
+    /// Node types provided by active integrations
+    pub fn node_types(&self) -> impl Iterator<Item = (&IntegrationManifest, &ProvidedNodeType)> {
+        self.active().flat_map(|i| i.manifest.node_types.iter().map(move |t| (&i.manifest, t)))
+    }
+
+    /// Provided node type of a node, from its built-in type name and tags
+    pub fn node_type_of(&self, base: &str, tags: &[String]) -> Option<(&IntegrationManifest, &ProvidedNodeType)> {
+        self.node_types()
+            .find(|(manifest, node_type)| node_type.base == base && tags.contains(&node_type.tag_for(&manifest.id)))
+    }
+
+    /// File of an icon declared by an active integration
+    pub fn icon_path(&self, manifest_id: &str, name: &str) -> Option<PathBuf> {
+        self.active()
+            .find(|i| i.manifest.id == manifest_id)
+            .and_then(|i| i.manifest.icons.get(name).map(|path| i.base_dir().join(path)))
+    }
+
+    /// Menu item id of the first active handler that opens the node in `context`
+    pub fn handler_for(&self, context: &MenuContext) -> Option<String> {
+        self.active().find_map(|integration| {
+            integration
+                .manifest
+                .handler_entries(integration.base_dir())
+                .into_iter()
+                .find(|entry| entry.target.matches(context) && entry.enabled_when.iter().all(|c| c.holds(context)))
+                .map(|entry| format!("{}{}", CUSTOM_ITEM_PREFIX, entry.id))
+        })
+    }
+
+    fn active(&self) -> impl Iterator<Item = &InstalledIntegration> {
+        self.integrations.iter().filter(|i| i.state == IntegrationState::Active)
+    }
+}
+
+impl Default for IntegrationRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTES: &str = r#"
+id = "org.example.Notes"
+name = "Example Notes"
+version = "1.4.0"
+
+[[node_type]]
+id = "note"
+label = "Note"
+base = "concept"
+
+[[action]]
+id = "append"
+label = "Append to Note"
+target = { node_types = ["file"] }
+command = { type = "exec", argv = ["example-notes", "--append", "%f"] }
+
+[[handler]]
+id = "open"
+schemes = ["notes"]
+extensions = ["md"]
+argv = ["example-notes", "%u"]
+
+[[trigger]]
+id = "index"
+event = "on-create"
+filter = { node_type = "file", path_prefix = "~/Notes" }
+action = { type = "script", path = "/usr/libexec/example-notes/index" }
+"#;
+
+    fn notes() -> IntegrationManifest {
+        IntegrationManifest::parse(NOTES, ManifestFormat::Toml).unwrap()
+    }
+
+    #[test]
+    fn test_manifest_parses_from_toml_and_json() {
+        let manifest = notes();
+        assert_eq!(manifest.id, "org.example.Notes");
+        assert_eq!(manifest.version.as_deref(), Some("1.4.0"));
+        assert_eq!(manifest.node_types[0].tag_for(&manifest.id), "org.example.Notes:note");
+        assert_eq!(manifest.actions[0].id, "append");
+        assert_eq!(manifest.handlers[0].extensions, vec!["md"]);
+        assert_eq!(manifest.triggers[0].event.as_str(), "on-create");
+        assert!(manifest.triggers[0].sandbox.enabled);
+
+        let json = serde_json::to_string(&manifest).unwrap();
+        assert_eq!(IntegrationManifest::parse(&json, ManifestFormat::Json).unwrap(), manifest);
+
+        assert_eq!(ManifestFormat::from_path(Path::new("notes.json")), Some(ManifestFormat::Json));
+        assert_eq!(ManifestFormat::from_path(Path::new("notes.yaml")), None);
+        assert!(IntegrationManifest::parse("id = ", ManifestFormat::Toml).is_err());
+        assert!(IntegrationManifest::parse(r#"{"id": "org.example.Notes"}"#, ManifestFormat::Json).is_err());
+    }
+
+    #[test]
+    fn test_validation_reports_each_problem() {
+        assert!(notes().validate(Path::new(".")).is_empty());
+
+        let mut manifest = notes();
+        manifest.id = "org example".to_string();
+        manifest.icons.insert("logo".to_string(), PathBuf::from("../logo.svg"));
+        manifest.node_types[0].base = "spreadsheet".to_string();
+        manifest.actions.push(manifest.actions[0].clone());
+        manifest.handlers[0].schemes = vec!["1notes".to_string()];
+        manifest.triggers[0].sandbox.enabled = false;
+
+        let problems = manifest.validate(Path::new("."));
+        let expected = [
+            "Invalid id 'org example'",
+            "Icon 'logo' must be a path inside the manifest directory",
+            "Duplicate action id 'append'",
+            "Node type 'note' has unknown base 'spreadsheet'",
+            "Handler 'open' has invalid scheme '1notes'",
+            "Trigger 'index' cannot run outside the sandbox",
+        ];
+        for problem in expected {
+            assert!(problems.iter().any(|p| p == problem), "missing '{}' in {:?}", problem, problems);
+        }
+        assert_eq!(problems.len(), expected.len());
+
+        let mut manifest = notes();
+        manifest.handlers[0].schemes.clear();
+        manifest.handlers[0].extensions.clear();
+        manifest.handlers[0].argv.clear();
+        assert_eq!(
+            manifest.validate(Path::new(".")),
+            vec!["Handler 'open' declares no schemes or extensions", "Handler 'open' has an empty command"]
+        );
+    }
+
+    #[test]
+    fn test_grant_covers_until_more_is_asked() {
+        let manifest = notes();
+        let grant = IntegrationGrant {
+            manifest_id: manifest.id.clone(),
+            allowed: true,
+            permissions: manifest.permissions(),
+        };
+        assert!(grant.covers(&manifest.permissions()));
+
+        let mut updated = manifest.clone();
+        updated.handlers[0].schemes.push("notes-sync".to_string());
+        assert!(!grant.covers(&updated.permissions()));
+    }
+
+    #[test]
+    fn test_scan_prompts_then_applies_allowed_integrations() {
+        let dir = std::env::temp_dir().join(format!("integrations_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("notes.toml"), NOTES).unwrap();
+        std::fs::write(dir.join("broken.json"), "{").unwrap();
+
+        let mut registry = IntegrationRegistry::new();
+        let report = registry.scan(&[dir.clone(), dir.join("missing")]);
+        assert_eq!(report.installed, 1);
+        assert_eq!(report.rejected.len(), 1);
+
+        let prompts = registry.pending_prompts();
+        assert_eq!(prompts.len(), 1);
+        assert!(!prompts[0].is_update);
+
+        let mut menus = MenuExtensionRegistry::new();
+        let mut hooks = HookRegistry::new();
+        registry.apply(&mut menus, &mut hooks);
+        assert!(menus.entries().is_empty());
+
+        assert!(registry.answer("org.example.Notes", true));
+        registry.apply(&mut menus, &mut hooks);
+        assert!(menus.get("org.example.Notes:append").is_some());
+        assert_eq!(hooks.hooks()[0].id, "org.example.Notes:index");
+
+        // The answer survives a restart, so nothing is asked again
+        let grants = dir.join("grants.toml");
+        registry.save_grants(&grants).unwrap();
+        let mut restarted = IntegrationRegistry::new();
+        restarted.load_grants(&grants).unwrap();
+        restarted.scan(std::slice::from_ref(&dir));
+        assert!(restarted.pending_prompts().is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,689 @@
+//! Key chords, the shortcut registry and the modal keymap layer
+//!
+//! The [`ShortcutRegistry`] maps key chords written like `Super+Q` to the
+//! actions of `GraphDesktopConfig::shortcuts`. On top of it an optional
+//! [`ModalKeymap`] adds vim-style modes: in normal mode `hjkl` moves focus
+//! between nodes, digits jump to bookmarks, `v` starts a visual
+//! multi-selection and `:` opens the command palette as an ex-style command
+//! line. Keys a mode does not bind fall through to the registry, so global
+//! shortcuts keep working; insert mode leaves the keyboard to the regular
+//! bindings until Escape.
+//!
+//! Bindings are data: each mode maps chords to [`KeymapAction`]s, which are
+//! written the same way in the configuration and on the command line, e.g.
+//! `focus left`, `bookmark 3`, `select type:file`, `selection invert`,
+//! `edges structure`.
+
+use crate::selection_filters::SelectionFilter;
+use crate::spatial_nav::Direction;
+use horizonos_graph_config::{KeyboardShortcut, KeymapConfig};
+use horizonos_graph_engine::{EdgeFilter, EdgePreset, SceneId, EDGE_KINDS};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use winit::keyboard::{Key, NamedKey};
+
+/// Modifier keys held with a chord
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ChordModifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub logo: bool,
+}
+
+/// Key of a chord
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChordKey {
+    /// A character as typed, so `:` rather than Shift+;
+    Char(char),
+    Named(NamedKey),
+}
+
+/// A key with modifiers
+///
+/// Character chords carry the typed character and drop Shift, so `H` and
+/// `Shift+h` are the same chord; with Ctrl, Alt or Super held the character is
+/// lowercase, so `Super+Q` matches however Shift is held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub key: ChordKey,
+    pub modifiers: ChordModifiers,
+}
+
+impl KeyChord {
+    pub fn new(key: ChordKey, modifiers: ChordModifiers) -> Self {
+        let mut chord = Self { key, modifiers };
+        if let ChordKey::Char(c) = chord.key {
+            chord.modifiers.shift = false;
+            if modifiers.ctrl || modifiers.alt || modifiers.logo {
+                chord.key = ChordKey::Char(c.to_lowercase().next().unwrap_or(c));
+            }
+        }
+        chord
+    }
+
+    /// Chord for a pressed logical key
+    pub fn from_key(key: &Key, modifiers: ChordModifiers) -> Option<Self> {
+        let key = match key {
+            Key::Character(text) => ChordKey::Char(text.chars().next()?),
+            Key::Named(NamedKey::Space) => ChordKey::Char(' '),
+            Key::Named(named) => ChordKey::Named(*named),
+            _ => return None,
+        };
+        Some(Self::new(key, modifiers))
+    }
+
+    /// Chord for a named key such as `Escape` or `F5`, e.g. from a keysym name
+    pub fn named(name: &str, modifiers: ChordModifiers) -> Option<Self> {
+        let key = KEY_NAMES.iter().find(|(known, _)| known.eq_ignore_ascii_case(name))?.1;
+        Some(Self::new(ChordKey::Named(key), modifiers))
+    }
+}
+
+/// Names accepted for named keys, first one used when printing
+const KEY_NAMES: &[(&str, NamedKey)] = &[
+    ("Escape", NamedKey::Escape),
+    ("Esc", NamedKey::Escape),
+    ("Enter", NamedKey::Enter),
+    ("Return", NamedKey::Enter),
+    ("Tab", NamedKey::Tab),
+    ("Backspace", NamedKey::Backspace),
+    ("Delete", NamedKey::Delete),
+    ("Del", NamedKey::Delete),
+    ("Left", NamedKey::ArrowLeft),
+    ("Right", NamedKey::ArrowRight),
+    ("Up", NamedKey::ArrowUp),
+    ("Down", NamedKey::ArrowDown),
+    ("Home", NamedKey::Home),
+    ("End", NamedKey::End),
+    ("PageUp", NamedKey::PageUp),
+    ("PageDown", NamedKey::PageDown),
+    ("F1", NamedKey::F1),
+    ("F2", NamedKey::F2),
+    ("F3", NamedKey::F3),
+    ("F4", NamedKey::F4),
+    ("F5", NamedKey::F5),
+    ("F6", NamedKey::F6),
+    ("F7", NamedKey::F7),
+    ("F8", NamedKey::F8),
+    ("F9", NamedKey::F9),
+    ("F10", NamedKey::F10),
+    ("F11", NamedKey::F11),
+    ("F12", NamedKey::F12),
+];
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The last part is the key, so "Ctrl++" binds the plus key
+        let (modifier_part, key_part) = match s.strip_suffix("++") {
+            Some(rest) => (rest, "+"),
+            None => s.rsplit_once('+').unwrap_or(("", s)),
+        };
+        let mut modifiers = ChordModifiers::default();
+        for modifier in modifier_part.split('+').filter(|m| !m.is_empty()) {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers.ctrl = true,
+                "alt" => modifiers.alt = true,
+                "shift" => modifiers.shift = true,
+                "super" | "logo" | "meta" | "win" => modifiers.logo = true,
+                other => return Err(format!("Unknown modifier '{}' in '{}'", other, s)),
+            }
+        }
+
+        let mut chars = key_part.chars();
+        let key = match (chars.next(), chars.next()) {
+            (Some(c), None) => {
+                // Shift+h is typed as H
+                let c = if modifiers.shift { c.to_uppercase().next().unwrap_or(c) } else { c };
+                ChordKey::Char(c)
+            }
+            _ if key_part.eq_ignore_ascii_case("space") => ChordKey::Char(' '),
+            _ if key_part.eq_ignore_ascii_case("plus") => ChordKey::Char('+'),
+            _ => KEY_NAMES
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key_part))
+                .map(|(_, key)| ChordKey::Named(*key))
+                .ok_or_else(|| format!("Unknown key '{}' in '{}'", key_part, s))?,
+        };
+        Ok(Self::new(key, modifiers))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let m = self.modifiers;
+        for (held, name) in [(m.ctrl, "Ctrl+"), (m.alt, "Alt+"), (m.shift, "Shift+"), (m.logo, "Super+")] {
+            if held {
+                f.write_str(name)?;
+            }
+        }
+        match self.key {
+            ChordKey::Char(' ') => f.write_str("Space"),
+            ChordKey::Char(c) if m.ctrl || m.alt || m.logo => write!(f, "{}", c.to_uppercase()),
+            ChordKey::Char(c) => write!(f, "{}", c),
+            ChordKey::Named(key) => match KEY_NAMES.iter().find(|(_, k)| *k == key) {
+                Some((name, _)) => f.write_str(name),
+                None => write!(f, "{:?}", key),
+            },
+        }
+    }
+}
+
+/// Actions of the configured shortcuts, by chord
+#[derive(Debug, Clone, Default)]
+pub struct ShortcutRegistry {
+    bindings: HashMap<KeyChord, String>,
+}
+
+impl ShortcutRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry of `GraphDesktopConfig::shortcuts`; unparseable chords are skipped
+    pub fn from_config(shortcuts: &HashMap<String, KeyboardShortcut>) -> Self {
+        let mut registry = Self::new();
+        for (name, shortcut) in shortcuts {
+            if horizonos_graph_config::is_pointer_binding(&shortcut.keys) {
+                continue;
+            }
+            if let Err(e) = registry.bind(&shortcut.keys, &shortcut.action) {
+                log::warn!("Ignoring shortcut {}: {}", name, e);
+            }
+        }
+        registry
+    }
+
+    /// Bind a chord such as `Super+Q` to an action
+    pub fn bind(&mut self, keys: &str, action: impl Into<String>) -> Result<(), String> {
+        self.bindings.insert(keys.parse()?, action.into());
+        Ok(())
+    }
+
+    /// Action bound to a chord
+    pub fn action(&self, chord: &KeyChord) -> Option<&str> {
+        self.bindings.get(chord).map(String::as_str)
+    }
+
+    /// Chord bound to an action, for showing it in menus
+    pub fn chord_for(&self, action: &str) -> Option<KeyChord> {
+        self.bindings.iter().find(|(_, a)| a.as_str() == action).map(|(chord, _)| *chord)
+    }
+}
+
+/// Modes of the modal keymap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeymapMode {
+    /// Regular bindings; only Escape is taken, to return to normal mode
+    Insert,
+    /// Single-key navigation
+    Normal,
+    /// Focus moves extend the selection
+    Visual,
+    /// Typing into the command line
+    Command,
+}
+
+impl KeymapMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeymapMode::Insert => "insert",
+            KeymapMode::Normal => "normal",
+            KeymapMode::Visual => "visual",
+            KeymapMode::Command => "command",
+        }
+    }
+}
+
+impl FromStr for KeymapMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "insert" => Ok(KeymapMode::Insert),
+            "normal" => Ok(KeymapMode::Normal),
+            "visual" => Ok(KeymapMode::Visual),
+            "command" => Ok(KeymapMode::Command),
+            _ => Err(format!("Unknown keymap mode '{}'", s)),
+        }
+    }
+}
+
+/// What a modal binding or command line does
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeymapAction {
+    /// Move focus to the nearest node in a direction
+    Focus(Direction),
+    /// Jump to a bookmarked node
+    JumpToBookmark(u8),
+    /// Bookmark the focused node
+    SetBookmark(u8),
+    /// Switch mode
+    Mode(KeymapMode),
+    /// Open the focused node
+    Activate,
+    /// Delete the selection
+    Delete,
+    Undo,
+    Redo,
+    /// Open the search palette, optionally with a query
+    Search(String),
+    /// Replace the selection with the nodes matching a selection filter
+    Select(SelectionFilter),
+    /// Run a smart selection command by ID, with or without the `selection.` prefix
+    SelectionCommand(String),
+    /// Run a configured shortcut action by name
+    Shortcut(String),
+    /// Change which edges are drawn
+    Edges(EdgeFilterCommand),
+    /// Turn modal navigation off
+    DisableModal,
+}
+
+impl FromStr for KeymapAction {
+    type Err = String;
+
+    /// Parse `name [argument]`, as used in bindings and on the command line
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, argument) = s.split_once(char::is_whitespace).map_or((s, ""), |(n, a)| (n, a.trim()));
+        let bookmark = || argument.parse::<u8>().map_err(|_| format!("Expected a bookmark number, got '{}'", argument));
+        let direction = || match argument {
+            "left" | "h" => Ok(Direction::Left),
+            "down" | "j" => Ok(Direction::Down),
+            "up" | "k" => Ok(Direction::Up),
+            "right" | "l" => Ok(Direction::Right),
+            _ => Err(format!("Expected left, right, up or down, got '{}'", argument)),
+        };
+        let required = |what: &str| {
+            if argument.is_empty() {
+                Err(format!("'{}' needs {}", name, what))
+            } else {
+                Ok(argument.to_string())
+            }
+        };
+
+        // `:3` jumps to bookmark 3, like `:3` goes to a line in vi
+        if let Ok(number) = s.parse::<u8>() {
+            return Ok(KeymapAction::JumpToBookmark(number));
+        }
+        match name {
+            "focus" => direction().map(KeymapAction::Focus),
+            "bookmark" | "b" => bookmark().map(KeymapAction::JumpToBookmark),
+            "mark" | "m" => bookmark().map(KeymapAction::SetBookmark),
+            "mode" => argument.parse().map(KeymapAction::Mode),
+            "normal" | "visual" | "insert" | "command" => name.parse().map(KeymapAction::Mode),
+            "activate" | "open" => Ok(KeymapAction::Activate),
+            "delete" | "d" => Ok(KeymapAction::Delete),
+            "undo" | "u" => Ok(KeymapAction::Undo),
+            "redo" | "red" => Ok(KeymapAction::Redo),
+            "search" | "find" => Ok(KeymapAction::Search(argument.to_string())),
+            "select" | "sel" => required("a selection filter")?.parse().map(KeymapAction::Select),
+            "selection" => required("a selection command").map(KeymapAction::SelectionCommand),
+            "shortcut" => required("a shortcut action").map(KeymapAction::Shortcut),
+            "edges" => required("a preset or edge type")?.parse().map(KeymapAction::Edges),
+            "nomodal" => Ok(KeymapAction::DisableModal),
+            "set" if argument == "nomodal" => Ok(KeymapAction::DisableModal),
+            _ => Err(format!("Unknown command '{}'", name)),
+        }
+    }
+}
+
+/// Change to the edge filter, written `edges <preset>`, `edges show|hide|toggle <type>`
+/// or `edges min <strength>`
+#[derive(Debug, Clone, PartialEq)]
+pub enum EdgeFilterCommand {
+    /// Switch to a built-in preset
+    Preset(String),
+    /// Show or hide an edge type by its palette key
+    SetType { kind: String, visible: bool },
+    ToggleType(String),
+    /// Hide edges weaker than this
+    MinStrength(f32),
+}
+
+impl EdgeFilterCommand {
+    /// Filter resulting from applying the command to the current one
+    pub fn apply(&self, current: &EdgeFilter) -> EdgeFilter {
+        let mut filter = current.clone();
+        match self {
+            EdgeFilterCommand::Preset(name) => {
+                if let Some(preset) = EdgePreset::named(name) {
+                    filter = preset.filter;
+                }
+            }
+            EdgeFilterCommand::SetType { kind, visible } => {
+                filter.set_type_visible(kind, *visible);
+            }
+            EdgeFilterCommand::ToggleType(kind) => {
+                filter.toggle_type(kind);
+            }
+            EdgeFilterCommand::MinStrength(strength) => filter.set_min_strength(*strength),
+        }
+        filter
+    }
+}
+
+impl FromStr for EdgeFilterCommand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (verb, argument) = s.split_once(char::is_whitespace).map_or((s, ""), |(v, a)| (v, a.trim()));
+        let kind = || {
+            if EDGE_KINDS.contains(&argument) {
+                Ok(argument.to_string())
+            } else {
+                Err(format!("Unknown edge type '{}', expected one of {}", argument, EDGE_KINDS.join(", ")))
+            }
+        };
+        match verb {
+            "show" => kind().map(|kind| EdgeFilterCommand::SetType { kind, visible: true }),
+            "hide" => kind().map(|kind| EdgeFilterCommand::SetType { kind, visible: false }),
+            "toggle" => kind().map(EdgeFilterCommand::ToggleType),
+            "min" => argument
+                .parse::<f32>()
+                .map(EdgeFilterCommand::MinStrength)
+                .map_err(|_| format!("Expected a strength between 0 and 1, got '{}'", argument)),
+            preset if argument.is_empty() && EdgePreset::named(preset).is_some() => {
+                Ok(EdgeFilterCommand::Preset(preset.to_string()))
+            }
+            _ => Err(format!("Unknown edge preset '{}'", s)),
+        }
+    }
+}
+
+/// Vim-style modal layer over the regular key handling
+#[derive(Debug, Clone)]
+pub struct ModalKeymap {
+    enabled: bool,
+    mode: KeymapMode,
+    bindings: HashMap<KeymapMode, HashMap<KeyChord, KeymapAction>>,
+    /// Node bookmarks by number
+    bookmarks: HashMap<u8, SceneId>,
+    /// Node focus moves start from; in visual mode this is the moving end
+    cursor: Option<SceneId>,
+}
+
+impl ModalKeymap {
+    /// Keymap with the built-in bindings, starting in normal mode when enabled
+    pub fn new(enabled: bool) -> Self {
+        let mut keymap = Self {
+            enabled,
+            mode: if enabled { KeymapMode::Normal } else { KeymapMode::Insert },
+            bindings: HashMap::new(),
+            bookmarks: HashMap::new(),
+            cursor: None,
+        };
+        keymap.bind_defaults();
+        keymap
+    }
+
+    /// Keymap from the interaction configuration; invalid bindings are skipped
+    pub fn from_config(config: &KeymapConfig) -> Self {
+        let mut keymap = Self::new(config.modal);
+        for (mode, bindings) in &config.bindings {
+            let Ok(mode) = mode.parse::<KeymapMode>() else {
+                log::warn!("Ignoring keymap bindings for unknown mode '{}'", mode);
+                continue;
+            };
+            for (keys, action) in bindings {
+                if let Err(e) = keymap.bind_str(mode, keys, action) {
+                    log::warn!("Ignoring {} mode binding '{}': {}", mode.name(), keys, e);
+                }
+            }
+        }
+        keymap
+    }
+
+    fn bind_defaults(&mut self) {
+        let normal_and_visual = [
+            ("h", "focus left"),
+            ("j", "focus down"),
+            ("k", "focus up"),
+            ("l", "focus right"),
+            (":", "command"),
+            ("x", "delete"),
+            ("d", "delete"),
+        ];
+        for (keys, action) in normal_and_visual {
+            self.bind_str(KeymapMode::Normal, keys, action).expect("valid built-in binding");
+            self.bind_str(KeymapMode::Visual, keys, action).expect("valid built-in binding");
+        }
+        let normal = [
+            ("v", "visual"),
+            ("i", "insert"),
+            ("Enter", "activate"),
+            ("o", "activate"),
+            ("u", "undo"),
+            ("Ctrl+r", "redo"),
+            ("/", "search"),
+        ];
+        for (keys, action) in normal {
+            self.bind_str(KeymapMode::Normal, keys, action).expect("valid built-in binding");
+        }
+        for n in 1..=9 {
+            self.bind_str(KeymapMode::Normal, &n.to_string(), &format!("bookmark {}", n)).expect("valid built-in binding");
+            self.bind_str(KeymapMode::Normal, &format!("Ctrl+{}", n), &format!("mark {}", n)).expect("valid built-in binding");
+        }
+        for keys in ["v", "Escape"] {
+            self.bind_str(KeymapMode::Visual, keys, "normal").expect("valid built-in binding");
+        }
+        self.bind_str(KeymapMode::Insert, "Escape", "normal").expect("valid built-in binding");
+    }
+
+    /// Bind a chord to an action in a mode
+    pub fn bind(&mut self, mode: KeymapMode, chord: KeyChord, action: KeymapAction) {
+        self.bindings.entry(mode).or_default().insert(chord, action);
+    }
+
+    /// Bind written chord and action, e.g. `("w", "focus up")`
+    pub fn bind_str(&mut self, mode: KeymapMode, keys: &str, action: &str) -> Result<(), String> {
+        self.bind(mode, keys.parse()?, action.parse()?);
+        Ok(())
+    }
+
+    pub fn unbind(&mut self, mode: KeymapMode, chord: &KeyChord) {
+        if let Some(bindings) = self.bindings.get_mut(&mode) {
+            bindings.remove(chord);
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turn modal navigation on in normal mode, or off
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.mode = if enabled { KeymapMode::Normal } else { KeymapMode::Insert };
+    }
+
+    pub fn mode(&self) -> KeymapMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: KeymapMode) {
+        if self.enabled {
+            self.mode = mode;
+        }
+    }
+
+    /// Whether keys go to the modal bindings before the regular ones
+    pub fn is_active(&self) -> bool {
+        self.enabled && self.mode != KeymapMode::Insert
+    }
+
+    /// Action bound to a chord in the current mode
+    pub fn action(&self, chord: &KeyChord) -> Option<&KeymapAction> {
+        if !self.enabled {
+            return None;
+        }
+        self.bindings.get(&self.mode)?.get(chord)
+    }
+
+    /// Bindings of a mode, for a help overlay
+    pub fn bindings(&self, mode: KeymapMode) -> impl Iterator<Item = (&KeyChord, &KeymapAction)> {
+        self.bindings.get(&mode).into_iter().flatten()
+    }
+
+    pub fn bookmark(&self, number: u8) -> Option<SceneId> {
+        self.bookmarks.get(&number).copied()
+    }
+
+    pub fn set_bookmark(&mut self, number: u8, node: SceneId) {
+        self.bookmarks.insert(number, node);
+    }
+
+    pub fn cursor(&self) -> Option<SceneId> {
+        self.cursor
+    }
+
+    pub fn set_cursor(&mut self, node: Option<SceneId>) {
+        self.cursor = node;
+    }
+
+    /// Drop bookmarks and the cursor on a deleted node
+    pub fn forget_node(&mut self, node: SceneId) {
+        self.bookmarks.retain(|_, bookmarked| *bookmarked != node);
+        if self.cursor == Some(node) {
+            self.cursor = None;
+        }
+    }
+}
+
+impl Default for ModalKeymap {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(keys: &str) -> KeyChord {
+        keys.parse().unwrap()
+    }
+
+    fn ctrl() -> ChordModifiers {
+        ChordModifiers { ctrl: true, ..Default::default() }
+    }
+
+    #[test]
+    fn test_chords_parse_and_print() {
+        assert_eq!(chord("Super+Q"), KeyChord::new(ChordKey::Char('q'), ChordModifiers { logo: true, ..Default::default() }));
+        assert_eq!(chord("ctrl+shift+Escape").to_string(), "Ctrl+Shift+Escape");
+        assert_eq!(chord("Ctrl++"), KeyChord::new(ChordKey::Char('+'), ctrl()));
+        assert_eq!(chord("Ctrl+plus"), chord("Ctrl++"));
+        assert_eq!(chord("Space").to_string(), "Space");
+        assert_eq!(chord("Esc"), KeyChord::named("escape", ChordModifiers::default()).unwrap());
+
+        assert!("Hyper+a".parse::<KeyChord>().unwrap_err().contains("Unknown modifier"));
+        assert!("Ctrl+Banana".parse::<KeyChord>().unwrap_err().contains("Unknown key"));
+    }
+
+    #[test]
+    fn test_shift_and_case_give_the_same_chord() {
+        // Shift+h is typed as H; with Ctrl held the letter's case does not matter
+        assert_eq!(chord("Shift+h"), chord("H"));
+        assert_ne!(chord("h"), chord("H"));
+        assert_eq!(chord("Ctrl+Shift+R"), KeyChord::new(ChordKey::Char('r'), ctrl()));
+        assert_eq!(KeyChord::from_key(&Key::Character("R".into()), ctrl()), Some(chord("Ctrl+r")));
+    }
+
+    #[test]
+    fn test_actions_parse() {
+        assert_eq!("focus h".parse(), Ok(KeymapAction::Focus(Direction::Left)));
+        assert_eq!("3".parse(), Ok(KeymapAction::JumpToBookmark(3)));
+        assert_eq!("m 4".parse(), Ok(KeymapAction::SetBookmark(4)));
+        assert_eq!("visual".parse(), Ok(KeymapAction::Mode(KeymapMode::Visual)));
+        assert_eq!("search notes".parse(), Ok(KeymapAction::Search("notes".to_string())));
+        assert_eq!("set nomodal".parse(), Ok(KeymapAction::DisableModal));
+        assert_eq!("edges hide works_on".parse(), Ok(KeymapAction::Edges(EdgeFilterCommand::SetType { kind: "works_on".to_string(), visible: false })));
+        assert_eq!("edges structure".parse(), Ok(KeymapAction::Edges(EdgeFilterCommand::Preset("structure".to_string()))));
+
+        assert!("focus sideways".parse::<KeymapAction>().is_err());
+        assert!("selection".parse::<KeymapAction>().unwrap_err().contains("needs"));
+        assert!("edges hide wormholes".parse::<KeymapAction>().unwrap_err().contains("Unknown edge type"));
+        assert!("fly".parse::<KeymapAction>().unwrap_err().contains("Unknown command"));
+    }
+
+    #[test]
+    fn test_configured_bindings_replace_built_in_ones_per_mode() {
+        let config = KeymapConfig {
+            modal: true,
+            bindings: HashMap::from([
+                ("normal".to_string(), HashMap::from([
+                    ("h".to_string(), "focus right".to_string()),
+                    ("Hyper+x".to_string(), "delete".to_string()),
+                ])),
+                ("nowhere".to_string(), HashMap::from([("q".to_string(), "undo".to_string())])),
+            ]),
+        };
+        let mut keymap = ModalKeymap::from_config(&config);
+
+        assert_eq!(keymap.mode(), KeymapMode::Normal);
+        assert_eq!(keymap.action(&chord("h")), Some(&KeymapAction::Focus(Direction::Right)));
+        assert_eq!(keymap.action(&chord("j")), Some(&KeymapAction::Focus(Direction::Down)));
+        assert_eq!(keymap.action(&chord("q")), None);
+
+        // Visual mode keeps its own binding of the same key
+        keymap.set_mode(KeymapMode::Visual);
+        assert_eq!(keymap.action(&chord("h")), Some(&KeymapAction::Focus(Direction::Left)));
+    }
+
+    #[test]
+    fn test_disabled_keymap_leaves_keys_to_the_registry() {
+        let mut keymap = ModalKeymap::new(false);
+        assert!(!keymap.is_active());
+        assert_eq!(keymap.action(&chord("h")), None);
+        keymap.set_mode(KeymapMode::Normal);
+        assert_eq!(keymap.mode(), KeymapMode::Insert);
+
+        // Insert mode only takes Escape back to normal mode
+        keymap.set_enabled(true);
+        keymap.set_mode(KeymapMode::Insert);
+        assert!(!keymap.is_active());
+        assert_eq!(keymap.action(&chord("h")), None);
+        assert_eq!(keymap.action(&chord("Escape")), Some(&KeymapAction::Mode(KeymapMode::Normal)));
+    }
+
+    #[test]
+    fn test_registry_binds_shortcuts_but_not_pointer_buttons() {
+        let shortcut = |keys: &str, action: &str| KeyboardShortcut {
+            keys: keys.to_string(),
+            action: action.to_string(),
+            description: String::new(),
+        };
+        let registry = ShortcutRegistry::from_config(&HashMap::from([
+            ("quit".to_string(), shortcut("Super+Q", "quit")),
+            ("menu".to_string(), shortcut("Super+MouseRight", "context_menu")),
+            ("broken".to_string(), shortcut("Hyper+Z", "zoom")),
+        ]));
+
+        assert_eq!(registry.action(&chord("Super+q")), Some("quit"));
+        assert_eq!(registry.chord_for("quit"), Some(chord("Super+Q")));
+        assert_eq!(registry.chord_for("context_menu"), None);
+        assert_eq!(registry.chord_for("zoom"), None);
+    }
+
+    #[test]
+    fn test_bookmarks_and_cursor_forget_deleted_nodes() {
+        let mut keymap = ModalKeymap::new(true);
+        keymap.set_bookmark(1, 7);
+        keymap.set_bookmark(2, 8);
+        keymap.set_cursor(Some(7));
+
+        keymap.forget_node(7);
+
+        assert_eq!(keymap.bookmark(1), None);
+        assert_eq!(keymap.bookmark(2), Some(8));
+        assert_eq!(keymap.cursor(), None);
+    }
+}
@@ -2,34 +2,66 @@
 //! 
 //! This module provides comprehensive input handling including:
 //! - Mouse/trackpad interactions (click, drag, scroll)
-//! - Keyboard navigation and shortcuts
-//! - Touch gestures (pinch, swipe, rotate)
+//! - Keyboard navigation and shortcuts, with an optional vim-style modal keymap
+//! - Touch and trackpad gestures (pinch, swipe, rotate)
 //! - Voice command integration
 //! - Node selection and manipulation
 //! - Camera controls
 
 pub mod input;
 pub mod selection;
+pub mod selection_filters;
 pub mod gestures;
 pub mod camera_controls;
 pub mod context_menu;
+pub mod menu_extensions;
 pub mod drag_drop;
 pub mod advanced;
+pub mod manipulation;
+pub mod window_switcher;
+pub mod focus_mode;
+pub mod search_palette;
+pub mod spatial_nav;
+pub mod keymap;
+pub mod quick_look;
+pub mod picking;
+pub mod edge_creation;
+pub mod integrations;
+pub mod bindings;
 
 pub use input::*;
 pub use selection::*;
+pub use selection_filters::*;
 pub use gestures::*;
 pub use camera_controls::*;
 pub use context_menu::*;
+pub use menu_extensions::*;
 pub use drag_drop::*;
 pub use advanced::*;
+pub use manipulation::*;
+pub use window_switcher::*;
+pub use focus_mode::*;
+pub use search_palette::*;
+pub use spatial_nav::*;
+pub use keymap::*;
+pub use quick_look::*;
+pub use picking::*;
+pub use edge_creation::*;
+pub use integrations::*;
+pub use bindings::*;
 
-use horizonos_graph_engine::{GraphEngine, SceneId, Position, Camera, Ray};
-use horizonos_graph_nodes::GraphNode;
-use std::sync::{Arc, RwLock};
+use horizonos_graph_engine::{Change, EdgeFilter, EdgeType, GraphCommand, GraphEngine, SceneId, Position, Camera, Ray, UiElement};
+use horizonos_graph_nodes::{GraphNode, NodeManager, NodeSearchIndex, NodeTransform};
+use horizonos_graph_clustering::ClusterStore;
+use horizonos_graph_edges::EdgeManager;
+use horizonos_graph_config::GraphDesktopConfig;
+use std::sync::{Arc, Mutex, RwLock};
 use winit::event::{Event, WindowEvent, ElementState};
 use winit::keyboard::{KeyCode, PhysicalKey};
 
+/// Zoom applied by gestures bound to zoom that carry no scale of their own
+const GESTURE_ZOOM_STEP: f32 = 0.1;
+
 /// Main interaction manager that coordinates all input handling
 pub struct InteractionManager {
     /// Input handler for low-level events
@@ -46,6 +78,40 @@ pub struct InteractionManager {
     drag_drop_handler: DragDropHandler,
     /// Advanced interaction features
     advanced_manager: AdvancedInteractionManager,
+    /// Resize, rotation and scaling handles
+    manipulation: ManipulationController,
+    /// Alt-Tab window switcher
+    window_switcher: WindowSwitcher,
+    /// Dims everything beyond a few hops of a focused node
+    focus_mode: FocusMode,
+    /// Super+Space node search and quick-jump palette
+    search_palette: SearchPalette,
+    /// Space file preview overlay
+    quick_look: QuickLook,
+    /// Relationship order Tab walks, kept between presses
+    tab_order: TabOrder,
+    /// GPU picks under the pointer
+    pointer_picker: PointerPicker,
+    /// Edge being drawn from a node
+    edge_creation: EdgeCreationController,
+    /// Edges new ones are validated against and added to
+    edge_manager: Arc<RwLock<EdgeManager>>,
+    /// Optional vim-style modal layer over the key bindings
+    keymap: ModalKeymap,
+    /// Configured shortcuts by chord
+    shortcuts: ShortcutRegistry,
+    /// Smart selections run from the command line or their shortcuts
+    selection_commands: SelectionCommands,
+    /// What each mouse button does
+    pointer_bindings: PointerBindings,
+    /// What each touch gesture does
+    gesture_bindings: GestureBindings,
+    /// Button whose press started the current mode
+    pointer_button: Option<winit::event::MouseButton>,
+    /// Clusters consulted when ranking switcher candidates
+    cluster_store: Option<Arc<dyn ClusterStore>>,
+    /// Nodes whose actions fill the context menu and run its choices
+    node_manager: Option<Arc<Mutex<NodeManager>>>,
     /// Current interaction mode
     mode: InteractionMode,
     /// Callback handlers
@@ -67,6 +133,8 @@ pub enum InteractionMode {
     BoxSelect,
     /// Creating edges
     EdgeCreate,
+    /// Dragging a resize, rotation or scale handle
+    Manipulate,
 }
 
 /// Callbacks for interaction events
@@ -78,8 +146,29 @@ pub struct InteractionCallbacks {
     pub on_selection_changed: Option<Box<dyn Fn(Vec<SceneId>) + Send + Sync>>,
     pub on_edge_create: Option<Box<dyn Fn(SceneId, SceneId) + Send + Sync>>,
     pub on_context_menu: Option<Box<dyn Fn(SceneId, Position) + Send + Sync>>,
+    pub on_node_transform: Option<Box<dyn Fn(SceneId, NodeTransform) + Send + Sync>>,
+    pub on_window_switch: Option<Box<dyn Fn(SceneId) + Send + Sync>>,
+    pub on_shortcut: Option<ShortcutCallback>,
+    pub on_nodes_dragged: Option<NodesDraggedCallback>,
+    pub on_quick_look: Option<QuickLookCallback>,
+    pub on_focus_changed: Option<FocusCallback>,
+    pub on_edge_filter_changed: Option<EdgeFilterCallback>,
+    pub on_node_action: Option<NodeActionCallback>,
+    pub on_node_dropped: Option<Box<dyn Fn(SceneId, SceneId) + Send + Sync>>,
 }
 
+/// Callback for configured shortcuts, given their action name
+pub type ShortcutCallback = Box<dyn Fn(&str) + Send + Sync>;
+
+/// Callback for edge filter changes made from the command line, given the new filter
+pub type EdgeFilterCallback = Box<dyn Fn(&EdgeFilter) + Send + Sync>;
+
+/// Callback for dragged nodes, given the new position of every node moved together
+pub type NodesDraggedCallback = Box<dyn Fn(&[(SceneId, Position)]) + Send + Sync>;
+
+/// Callback for node actions run from the context menu, given their outcome
+pub type NodeActionCallback = Box<dyn Fn(&NodeActionOutcome) + Send + Sync>;
+
 impl InteractionManager {
     /// Create a new interaction manager
     pub fn new() -> Self {
@@ -91,6 +180,23 @@ impl InteractionManager {
             context_menu: ContextMenuManager::new(),
             drag_drop_handler: DragDropHandler::new(),
             advanced_manager: AdvancedInteractionManager::new(),
+            manipulation: ManipulationController::new(),
+            window_switcher: WindowSwitcher::default(),
+            focus_mode: FocusMode::default(),
+            search_palette: SearchPalette::default(),
+            quick_look: QuickLook::new(),
+            tab_order: TabOrder::default(),
+            pointer_picker: PointerPicker::new(),
+            edge_creation: EdgeCreationController::new(),
+            edge_manager: Arc::new(RwLock::new(EdgeManager::new())),
+            keymap: ModalKeymap::default(),
+            shortcuts: ShortcutRegistry::default(),
+            selection_commands: SelectionCommands::new(),
+            pointer_bindings: PointerBindings::default(),
+            gesture_bindings: GestureBindings::default(),
+            pointer_button: None,
+            cluster_store: None,
+            node_manager: None,
             mode: InteractionMode::Normal,
             callbacks: Arc::new(RwLock::new(InteractionCallbacks::default())),
         }
@@ -110,6 +216,7 @@ impl InteractionManager {
     fn handle_window_event(&mut self, event: &WindowEvent, engine: &mut GraphEngine) -> bool {
         // First, update input state
         self.input_handler.handle_event(event);
+        self.poll_pointer_pick(engine);
         
         match event {
             WindowEvent::CursorMoved { position, .. } => {
@@ -146,29 +253,55 @@ impl InteractionManager {
         // Handle different modes
         match self.mode {
             InteractionMode::Pan => {
-                if self.input_handler.is_mouse_pressed(winit::event::MouseButton::Middle) {
+                if self.pointer_button.is_some() {
                     self.camera_controller.pan(engine.camera_mut(), pos, self.input_handler.last_cursor_pos());
+                    self.pointer_picker.invalidate();
                 }
             }
             InteractionMode::Rotate => {
-                if self.input_handler.is_mouse_pressed(winit::event::MouseButton::Right) {
+                if self.pointer_button.is_some() {
                     self.camera_controller.rotate(engine.camera_mut(), pos, self.input_handler.last_cursor_pos());
+                    self.pointer_picker.invalidate();
                 }
             }
             InteractionMode::Drag => {
-                if let Some(selected) = self.selection_manager.get_primary_selection() {
-                    self.drag_drop_handler.update_drag(selected, pos, engine);
+                if let Some(dragged) = self.drag_drop_handler.get_dragged_node() {
+                    let moved = self.drag_drop_handler.update_drag(dragged, pos, engine);
+                    // One batched callback per move, however many nodes the group has
+                    if !moved.is_empty() {
+                        if let Some(callback) = &self.callbacks.read().unwrap().on_nodes_dragged {
+                            callback(&moved);
+                        }
+                    }
                 }
             }
             InteractionMode::BoxSelect => {
                 self.selection_manager.update_box_selection(pos);
             }
+            InteractionMode::EdgeCreate => {
+                let pointer = self.screen_to_world(pos, engine);
+                let hovered = self.pick_node_at(pos, engine);
+                self.selection_manager.set_hover(hovered);
+                let edges = self.edge_manager.read().unwrap();
+                self.edge_creation.update(pointer, hovered, engine.scene(), &edges);
+            }
+            InteractionMode::Manipulate => {
+                let pointer = self.screen_to_world(pos, engine);
+                let modifiers = ManipulationModifiers {
+                    toggle_aspect_lock: self.input_handler.is_key_pressed(KeyCode::ShiftLeft),
+                    disable_snapping: self.input_handler.is_key_pressed(KeyCode::AltLeft),
+                };
+                if let Some((node_id, transform)) = self.manipulation.update(pointer, modifiers) {
+                    if let Some(callback) = &self.callbacks.read().unwrap().on_node_transform {
+                        callback(node_id, transform);
+                    }
+                }
+            }
             _ => {
-                // Update hover state
-                if let Some(node_id) = self.pick_node_at(pos, engine) {
-                    self.selection_manager.set_hover(Some(node_id));
-                } else {
-                    self.selection_manager.set_hover(None);
+                // Update hover state, when the GPU pick arrives if there is one
+                if !self.pointer_picker.request(pos, engine) {
+                    let hovered = self.pick_node_at(pos, engine);
+                    self.selection_manager.set_hover(hovered);
                 }
             }
         }
@@ -176,10 +309,75 @@ impl InteractionManager {
     
     /// Handle mouse button input
     fn handle_mouse_input(&mut self, state: ElementState, button: winit::event::MouseButton, engine: &mut GraphEngine) {
+        if self.mode == InteractionMode::EdgeCreate {
+            if state == ElementState::Pressed {
+                match button {
+                    winit::event::MouseButton::Left => self.finish_edge_creation(engine),
+                    winit::event::MouseButton::Right => self.cancel_edge_creation(),
+                    _ => {}
+                }
+            }
+            return;
+        }
+        
+        match state {
+            ElementState::Pressed if self.pointer_button.is_none() => {
+                let chord = PointerChord::new(button, self.chord_modifiers());
+                let actions = self.pointer_bindings.actions(&chord).to_vec();
+                for action in actions {
+                    if self.start_pointer_action(action, engine) {
+                        break;
+                    }
+                }
+                if self.mode != InteractionMode::Normal {
+                    self.pointer_button = Some(button);
+                }
+            }
+            ElementState::Released if self.pointer_button == Some(button) => {
+                self.pointer_button = None;
+                self.end_pointer_action(engine);
+            }
+            _ => {}
+        }
+    }
+    
+    /// Start a bound pointer action at the cursor, returning whether it applied
+    fn start_pointer_action(&mut self, action: PointerAction, engine: &mut GraphEngine) -> bool {
         let cursor_pos = self.input_handler.cursor_pos();
         
-        match (state, button) {
-            (ElementState::Pressed, winit::event::MouseButton::Left) => {
+        match action {
+            PointerAction::ContextMenu => {
+                let Some(node_id) = self.pick_node_at(cursor_pos, engine) else {
+                    return false;
+                };
+                match engine.scene().get_node(node_id) {
+                    Some(node) => {
+                        let selected = self.selection_manager.get_selection().len();
+                        match &self.node_manager {
+                            Some(nodes) => {
+                                let actions = nodes.lock().unwrap().available_actions(node_id);
+                                self.context_menu.show_for_node_actions(node, cursor_pos, selected, actions);
+                            }
+                            None => self.context_menu.show_for_scene_node(node, cursor_pos, selected),
+                        }
+                    }
+                    None => self.context_menu.show_for_node(node_id, cursor_pos),
+                }
+                if let Some(callback) = &self.callbacks.read().unwrap().on_context_menu {
+                    let world_pos = self.screen_to_world(cursor_pos, engine);
+                    callback(node_id, world_pos);
+                }
+            }
+            PointerAction::Select => {
+                // Handles of selected nodes take precedence over picking
+                let pointer = self.screen_to_world(cursor_pos, engine);
+                if let Some((node_id, kind)) = self.manipulation.hit_test(pointer, engine.scene(), |position| engine.ui_world_size(UiElement::Handle, 0.0, position)) {
+                    if self.manipulation.begin(node_id, kind, pointer, engine.scene()) {
+                        self.mode = InteractionMode::Manipulate;
+                        return true;
+                    }
+                }
+                
                 // Check for node selection
                 if let Some(node_id) = self.pick_node_at(cursor_pos, engine) {
                     let shift_pressed = self.input_handler.is_key_pressed(KeyCode::ShiftLeft);
@@ -191,6 +389,11 @@ impl InteractionManager {
                     } else if shift_pressed {
                         // Add to selection
                         self.selection_manager.add_to_selection(node_id);
+                    } else if self.selection_manager.is_selected(node_id) && self.selection_manager.get_selection().len() > 1 {
+                        // Pressing on a node of a multi-selection keeps it, so the whole selection can be dragged
+                        if let Some(callback) = &self.callbacks.read().unwrap().on_node_click {
+                            callback(node_id);
+                        }
                     } else {
                         // Single selection
                         self.selection_manager.set_selection(vec![node_id]);
@@ -210,58 +413,61 @@ impl InteractionManager {
                         }
                     }
                     
-                    // Start drag mode
+                    self.manipulation.sync_selection(&self.selection_manager.get_selection(), engine.scene());
+                    
+                    // Start drag mode, moving the selection as a group when the node is part of it
                     self.mode = InteractionMode::Drag;
-                    self.drag_drop_handler.start_drag(node_id, cursor_pos);
+                    let group = if self.selection_manager.is_selected(node_id) {
+                        self.selection_manager.get_selection()
+                    } else {
+                        vec![node_id]
+                    };
+                    self.drag_drop_handler.start_group_drag(node_id, group, cursor_pos);
                 } else {
                     // Start box selection
                     self.mode = InteractionMode::BoxSelect;
                     self.selection_manager.start_box_selection(cursor_pos);
                 }
             }
-            (ElementState::Released, winit::event::MouseButton::Left) => {
-                match self.mode {
-                    InteractionMode::Drag => {
-                        self.drag_drop_handler.end_drag();
-                        self.mode = InteractionMode::Normal;
-                    }
-                    InteractionMode::BoxSelect => {
-                        let selected = self.selection_manager.finish_box_selection(engine);
-                        if let Some(callback) = &self.callbacks.read().unwrap().on_selection_changed {
-                            callback(selected);
-                        }
-                        self.mode = InteractionMode::Normal;
-                    }
-                    _ => {}
-                }
+            PointerAction::BoxSelect => {
+                self.mode = InteractionMode::BoxSelect;
+                self.selection_manager.start_box_selection(cursor_pos);
             }
-            (ElementState::Pressed, winit::event::MouseButton::Right) => {
-                // Context menu or camera rotation
-                if let Some(node_id) = self.pick_node_at(cursor_pos, engine) {
-                    self.context_menu.show_for_node(node_id, cursor_pos);
-                    if let Some(callback) = &self.callbacks.read().unwrap().on_context_menu {
-                        let world_pos = self.screen_to_world(cursor_pos, engine);
-                        callback(node_id, world_pos);
-                    }
-                } else {
-                    self.mode = InteractionMode::Rotate;
+            PointerAction::Pan => self.mode = InteractionMode::Pan,
+            PointerAction::Rotate => self.mode = InteractionMode::Rotate,
+        }
+        true
+    }
+    
+    /// End the mode started by the pointer button just released
+    fn end_pointer_action(&mut self, engine: &mut GraphEngine) {
+        match self.mode {
+            InteractionMode::Drag => {
+                self.record_drag(engine);
+                let dropped = self.drop_target(engine);
+                self.drag_drop_handler.end_drag();
+                if let (Some((dragged, target)), Some(callback)) = (dropped, &self.callbacks.read().unwrap().on_node_dropped) {
+                    callback(dragged, target);
                 }
             }
-            (ElementState::Released, winit::event::MouseButton::Right) => {
-                if self.mode == InteractionMode::Rotate {
-                    self.mode = InteractionMode::Normal;
+            InteractionMode::Manipulate => {
+                if let Some((node_id, transform)) = self.manipulation.end() {
+                    if let Some(callback) = &self.callbacks.read().unwrap().on_node_transform {
+                        callback(node_id, transform);
+                    }
                 }
             }
-            (ElementState::Pressed, winit::event::MouseButton::Middle) => {
-                self.mode = InteractionMode::Pan;
-            }
-            (ElementState::Released, winit::event::MouseButton::Middle) => {
-                if self.mode == InteractionMode::Pan {
-                    self.mode = InteractionMode::Normal;
+            InteractionMode::BoxSelect => {
+                let selected = self.selection_manager.finish_box_selection(engine);
+                self.manipulation.sync_selection(&selected, engine.scene());
+                if let Some(callback) = &self.callbacks.read().unwrap().on_selection_changed {
+                    callback(selected);
                 }
             }
-            _ => {}
+            InteractionMode::Pan | InteractionMode::Rotate => {}
+            InteractionMode::Normal | InteractionMode::EdgeCreate => return,
         }
+        self.mode = InteractionMode::Normal;
     }
     
     /// Handle mouse wheel input
@@ -274,64 +480,593 @@ impl InteractionManager {
         };
         
         self.camera_controller.zoom(engine.camera_mut(), zoom_delta);
+        self.pointer_picker.invalidate();
     }
     
     /// Handle keyboard input
     fn handle_keyboard_input(&mut self, event: &winit::event::KeyEvent, engine: &mut GraphEngine) {
+        if self.window_switcher.is_open() {
+            self.handle_switcher_key(event, engine);
+            return;
+        }
+        
+        if self.search_palette.is_open() {
+            self.handle_palette_key(event, engine);
+            return;
+        }
+        
+        if self.quick_look.is_open() {
+            self.handle_quick_look_key(event);
+            return;
+        }
+        
         if event.state != ElementState::Pressed {
             return;
         }
         
+        let chord = KeyChord::from_key(&event.logical_key, self.chord_modifiers());
+        if let Some(action) = chord.and_then(|chord| self.keymap.action(&chord)).cloned() {
+            self.run_keymap_action(action, engine);
+            return;
+        }
+        
         match event.physical_key {
-            PhysicalKey::Code(KeyCode::Delete) => {
-                // Delete selected nodes
-                let selected = self.selection_manager.get_selection();
-                // TODO: Implement node deletion
+            PhysicalKey::Code(KeyCode::Tab) if self.is_alt_pressed() => {
+                // Open the window switcher on the next most relevant window
+                let clusters = self.cluster_store.as_deref();
+                self.window_switcher.open(engine.scene(), clusters);
+            }
+            PhysicalKey::Code(KeyCode::Space) if self.is_super_pressed() => {
+                self.search_palette.open(engine.scene());
+            }
+            PhysicalKey::Code(KeyCode::Space) if !self.is_ctrl_pressed() && !self.is_alt_pressed() => {
+                // Preview the focused file, stepping through the selected ones
+                if let Some(focus) = self.keymap_focus(engine) {
+                    let selection = self.selection_manager.get_selection();
+                    let event = self.quick_look.open(focus, &selection, engine.scene());
+                    self.emit_quick_look(event);
+                }
+            }
+            PhysicalKey::Code(KeyCode::Delete) => self.delete_selection(engine),
+            PhysicalKey::Code(KeyCode::KeyZ) if self.is_ctrl_pressed() && self.is_shift_pressed() => {
+                engine.redo();
+                self.prune_selection(engine);
+            }
+            PhysicalKey::Code(KeyCode::KeyZ) if self.is_ctrl_pressed() => {
+                engine.undo();
+                self.prune_selection(engine);
+            }
+            PhysicalKey::Code(KeyCode::KeyY) if self.is_ctrl_pressed() => {
+                engine.redo();
+                self.prune_selection(engine);
             }
             PhysicalKey::Code(KeyCode::KeyA) if self.input_handler.is_key_pressed(KeyCode::ControlLeft) => {
                 // Select all
                 self.selection_manager.select_all(engine);
+                self.manipulation.sync_selection(&self.selection_manager.get_selection(), engine.scene());
+            }
+            PhysicalKey::Code(KeyCode::Escape) if self.mode == InteractionMode::Manipulate => {
+                // Abandon the handle drag
+                if let Some((node_id, transform)) = self.manipulation.cancel() {
+                    if let Some(callback) = &self.callbacks.read().unwrap().on_node_transform {
+                        callback(node_id, transform);
+                    }
+                }
+                self.mode = InteractionMode::Normal;
+            }
+            PhysicalKey::Code(KeyCode::Escape) if self.mode == InteractionMode::EdgeCreate => {
+                self.cancel_edge_creation();
+            }
+            PhysicalKey::Code(KeyCode::KeyE) if !self.is_ctrl_pressed() && !self.is_alt_pressed() && !self.is_super_pressed() => {
+                // Draw an edge from the focused node
+                if let Some(source) = self.keymap_focus(engine) {
+                    self.begin_edge_creation(source, DEFAULT_EDGE_TYPE, engine);
+                }
             }
             PhysicalKey::Code(KeyCode::Escape) => {
                 // Clear selection
+                self.manipulation.sync_selection(&[], engine.scene());
                 self.selection_manager.clear_selection();
                 self.mode = InteractionMode::Normal;
             }
+            PhysicalKey::Code(KeyCode::KeyF) if self.is_shift_pressed() => {
+                // Focus mode on the selected node; on a neighbour of the root it steps along the edge
+                match self.selection_manager.get_selection().first().copied() {
+                    Some(node) if self.focus_mode.step_to(node, engine.scene()) => {}
+                    Some(node) => self.focus_mode.toggle(node, engine.scene()),
+                    None => self.focus_mode.deactivate(),
+                }
+            }
+            PhysicalKey::Code(KeyCode::ArrowLeft) if self.focus_mode.is_active() && self.is_alt_pressed() => {
+                // Walk back along the focus breadcrumb
+                if let Some(root) = self.focus_mode.step_back(engine.scene()) {
+                    self.selection_manager.set_selection(vec![root]);
+                }
+            }
+            PhysicalKey::Code(KeyCode::Tab) if !self.is_ctrl_pressed() && !self.is_super_pressed() => {
+                let reverse = self.is_shift_pressed();
+                self.tab_focus(reverse, engine);
+            }
+            PhysicalKey::Code(key @ (KeyCode::ArrowLeft | KeyCode::ArrowRight | KeyCode::ArrowUp | KeyCode::ArrowDown))
+                if !self.is_ctrl_pressed() && !self.is_alt_pressed() && !self.is_super_pressed() =>
+            {
+                let direction = match key {
+                    KeyCode::ArrowLeft => Direction::Left,
+                    KeyCode::ArrowRight => Direction::Right,
+                    KeyCode::ArrowUp => Direction::Up,
+                    _ => Direction::Down,
+                };
+                self.move_focus(direction, engine);
+            }
+            PhysicalKey::Code(KeyCode::Equal | KeyCode::NumpadAdd) if self.focus_mode.is_active() => {
+                let hops = self.focus_mode.config().hops + 1;
+                self.focus_mode.set_hops(hops, engine.scene());
+            }
+            PhysicalKey::Code(KeyCode::Minus | KeyCode::NumpadSubtract) if self.focus_mode.is_active() => {
+                let hops = self.focus_mode.config().hops.saturating_sub(1);
+                self.focus_mode.set_hops(hops, engine.scene());
+            }
             PhysicalKey::Code(KeyCode::KeyF) => {
                 // Focus on selected nodes
                 if let Some(selected) = self.selection_manager.get_selection().first() {
                     self.camera_controller.focus_on_node(*selected, engine);
                 }
             }
-            _ => {}
+            _ => {
+                // Smart selections bound to the chord run here, configured shortcuts by the host
+                if let Some(command) = chord.as_ref().and_then(|chord| self.selection_commands.for_chord(chord)).cloned() {
+                    self.run_selection_command(&command, engine);
+                } else if let Some(action) = chord.as_ref().and_then(|chord| self.shortcuts.action(chord)) {
+                    if let Some(callback) = &self.callbacks.read().unwrap().on_shortcut {
+                        callback(action);
+                    }
+                }
+            }
+        }
+    }
+    
+    /// Run an action of the modal keymap
+    fn run_keymap_action(&mut self, action: KeymapAction, engine: &mut GraphEngine) {
+        match action {
+            KeymapAction::Focus(direction) => self.move_focus(direction, engine),
+            KeymapAction::JumpToBookmark(number) => {
+                match self.keymap.bookmark(number).filter(|id| engine.scene().get_node(*id).is_some()) {
+                    Some(node_id) => {
+                        self.keymap.set_cursor(Some(node_id));
+                        self.select_nodes(vec![node_id], engine);
+                        self.camera_controller.focus_on_node(node_id, engine);
+                    }
+                    None => log::debug!("No node bookmarked as {}", number),
+                }
+            }
+            KeymapAction::SetBookmark(number) => {
+                if let Some(node_id) = self.keymap_focus(engine) {
+                    self.keymap.set_bookmark(number, node_id);
+                }
+            }
+            KeymapAction::Mode(KeymapMode::Command) => {
+                // The palette doubles as the command line
+                self.keymap.set_mode(KeymapMode::Command);
+                self.search_palette.open(engine.scene());
+            }
+            KeymapAction::Mode(mode) => {
+                if mode == KeymapMode::Visual {
+                    // Visual selection starts at the focused node
+                    if let Some(node_id) = self.keymap_focus(engine) {
+                        self.keymap.set_cursor(Some(node_id));
+                        self.select_nodes(vec![node_id], engine);
+                    }
+                }
+                self.keymap.set_mode(mode);
+            }
+            KeymapAction::Activate => {
+                if let Some(node_id) = self.keymap_focus(engine) {
+                    if let Some(callback) = &self.callbacks.read().unwrap().on_node_double_click {
+                        callback(node_id);
+                    }
+                }
+            }
+            KeymapAction::Delete => {
+                self.delete_selection(engine);
+                if self.keymap.mode() == KeymapMode::Visual {
+                    self.keymap.set_mode(KeymapMode::Normal);
+                }
+            }
+            KeymapAction::Undo => {
+                engine.undo();
+                self.prune_selection(engine);
+            }
+            KeymapAction::Redo => {
+                engine.redo();
+                self.prune_selection(engine);
+            }
+            KeymapAction::Search(query) => {
+                self.search_palette.open(engine.scene());
+                if !query.is_empty() {
+                    self.search_palette.set_query(query);
+                }
+            }
+            KeymapAction::Select(filter) => {
+                let selection = self.selection_manager.apply_filter(&filter, SelectionMode::Replace, engine.scene(), None);
+                self.select_nodes(selection, engine);
+            }
+            KeymapAction::SelectionCommand(id) => match self.selection_commands.find(&id).cloned() {
+                Some(command) => self.run_selection_command(&command, engine),
+                None => log::debug!("No selection command '{}'", id),
+            },
+            KeymapAction::Shortcut(action) => {
+                if let Some(callback) = &self.callbacks.read().unwrap().on_shortcut {
+                    callback(&action);
+                }
+            }
+            KeymapAction::Edges(command) => {
+                engine.set_edge_filter(command.apply(engine.edge_filter()));
+                if let Some(callback) = &self.callbacks.read().unwrap().on_edge_filter_changed {
+                    callback(engine.edge_filter());
+                }
+            }
+            KeymapAction::DisableModal => self.keymap.set_enabled(false),
+        }
+    }
+    
+    /// Move keyboard focus to the nearest node in a direction; visual mode extends the selection
+    fn move_focus(&mut self, direction: Direction, engine: &mut GraphEngine) {
+        let from = self.keymap_focus(engine);
+        let target = match from {
+            Some(from) => nearest_in_direction(engine.scene(), engine.camera(), from, direction),
+            None => nearest_to_view_center(engine.scene(), engine.camera()),
+        };
+        if let Some(target) = target {
+            // Tab starts over from wherever the arrows led
+            self.tab_order = TabOrder::default();
+            self.focus_node(from, target, FocusMove::Direction(direction), engine);
+        }
+    }
+    
+    /// Move keyboard focus along relationships, keeping the order Tab started with
+    fn tab_focus(&mut self, reverse: bool, engine: &mut GraphEngine) {
+        let from = self.keymap_focus(engine);
+        let target = match from {
+            Some(from) => {
+                if !self.tab_order.contains(from) {
+                    self.tab_order = TabOrder::from_node(engine.scene(), from);
+                }
+                self.tab_order.step(engine.scene(), from, reverse)
+            }
+            None => nearest_to_view_center(engine.scene(), engine.camera()),
+        };
+        if let Some(target) = target {
+            let by = if reverse { FocusMove::PreviousRelated } else { FocusMove::NextRelated };
+            self.focus_node(from, target, by, engine);
+        }
+    }
+    
+    /// Put keyboard focus on a node, bring it into view and announce it
+    fn focus_node(&mut self, previous: Option<SceneId>, target: SceneId, by: FocusMove, engine: &mut GraphEngine) {
+        self.keymap.set_cursor(Some(target));
+        let selection = if self.keymap.mode() == KeymapMode::Visual {
+            self.selection_manager.add_to_selection(target);
+            self.selection_manager.get_selection()
+        } else {
+            vec![target]
+        };
+        self.select_nodes(selection, engine);
+        self.camera_controller.focus_on_node(target, engine);
+        
+        if let Some(callback) = &self.callbacks.read().unwrap().on_focus_changed {
+            let announcement = focus_announcement(engine.scene(), target, previous).unwrap_or_default();
+            callback(&FocusChange { previous, node_id: target, by, announcement });
         }
     }
     
+    /// Node keyboard focus is on: the keymap cursor, else the primary selection
+    fn keymap_focus(&self, engine: &GraphEngine) -> Option<SceneId> {
+        self.keymap
+            .cursor()
+            .or_else(|| self.selection_manager.get_primary_selection())
+            .filter(|id| engine.scene().get_node(*id).is_some())
+    }
+    
+    /// Replace the selection and tell listeners
+    fn select_nodes(&mut self, nodes: Vec<SceneId>, engine: &GraphEngine) {
+        self.selection_manager.set_selection(nodes.clone());
+        self.manipulation.sync_selection(&nodes, engine.scene());
+        if let Some(callback) = &self.callbacks.read().unwrap().on_selection_changed {
+            callback(nodes);
+        }
+    }
+    
+    /// Run a smart selection and announce the new selection
+    fn run_selection_command(&mut self, command: &SelectionCommand, engine: &GraphEngine) {
+        let selection = self.selection_manager.run_command(command, engine.scene(), None);
+        self.select_nodes(selection, engine);
+    }
+    
+    /// Delete selected nodes as one undoable step
+    fn delete_selection(&mut self, engine: &mut GraphEngine) {
+        let selected = self.selection_manager.get_selection();
+        if selected.is_empty() {
+            return;
+        }
+        engine.history_mut().begin("Delete selection");
+        for node_id in selected {
+            if let Err(e) = engine.execute(GraphCommand::RemoveNode(node_id)) {
+                log::warn!("Could not delete node {}: {}", node_id, e);
+            }
+            self.keymap.forget_node(node_id);
+        }
+        engine.history_mut().commit();
+        self.prune_selection(engine);
+    }
+    
+    /// Handle keys while the window switcher is open
+    fn handle_switcher_key(&mut self, event: &winit::event::KeyEvent, engine: &mut GraphEngine) {
+        if event.state == ElementState::Released {
+            // Releasing Alt switches to the selected window
+            if matches!(event.physical_key, PhysicalKey::Code(KeyCode::AltLeft | KeyCode::AltRight)) {
+                if let Some(node_id) = self.window_switcher.commit() {
+                    self.camera_controller.focus_on_node(node_id, engine);
+                    if let Some(callback) = &self.callbacks.read().unwrap().on_window_switch {
+                        callback(node_id);
+                    }
+                }
+            }
+            return;
+        }
+        
+        match event.physical_key {
+            PhysicalKey::Code(KeyCode::Tab) => {
+                if self.input_handler.is_key_pressed(KeyCode::ShiftLeft) || self.input_handler.is_key_pressed(KeyCode::ShiftRight) {
+                    self.window_switcher.previous();
+                } else {
+                    self.window_switcher.next();
+                }
+            }
+            PhysicalKey::Code(KeyCode::ArrowRight | KeyCode::ArrowDown) => self.window_switcher.next(),
+            PhysicalKey::Code(KeyCode::ArrowLeft | KeyCode::ArrowUp) => self.window_switcher.previous(),
+            PhysicalKey::Code(KeyCode::Escape) => self.window_switcher.cancel(),
+            PhysicalKey::Code(KeyCode::Backspace) => self.window_switcher.pop_filter_char(),
+            _ => {
+                // Type-ahead filtering
+                if let Some(text) = &event.text {
+                    for c in text.chars().filter(|c| !c.is_control()) {
+                        self.window_switcher.push_filter_char(c);
+                    }
+                }
+            }
+        }
+    }
+    
+    /// Handle keys while the quick-look overlay is open
+    fn handle_quick_look_key(&mut self, event: &winit::event::KeyEvent) {
+        if event.state != ElementState::Pressed {
+            return;
+        }
+        let change = match event.physical_key {
+            PhysicalKey::Code(KeyCode::Space | KeyCode::Escape) => self.quick_look.close(),
+            PhysicalKey::Code(KeyCode::ArrowRight | KeyCode::ArrowDown) => self.quick_look.next_file(),
+            PhysicalKey::Code(KeyCode::ArrowLeft | KeyCode::ArrowUp) => self.quick_look.previous_file(),
+            _ => None,
+        };
+        self.emit_quick_look(change);
+    }
+    
+    /// Tell the host what the quick-look overlay should show
+    fn emit_quick_look(&self, event: Option<QuickLookEvent>) {
+        if let (Some(event), Some(callback)) = (event, &self.callbacks.read().unwrap().on_quick_look) {
+            callback(&event);
+        }
+    }
+    
+    /// Handle keys while the search palette is open
+    fn handle_palette_key(&mut self, event: &winit::event::KeyEvent, engine: &mut GraphEngine) {
+        if event.state != ElementState::Pressed {
+            return;
+        }
+        
+        match event.physical_key {
+            PhysicalKey::Code(KeyCode::Enter | KeyCode::NumpadEnter) => {
+                // On the command line, run the command; text naming none searches as usual
+                if self.keymap.mode() == KeymapMode::Command {
+                    self.keymap.set_mode(KeymapMode::Normal);
+                    if let Ok(action) = self.search_palette.query().parse::<KeymapAction>() {
+                        self.search_palette.cancel();
+                        self.run_keymap_action(action, engine);
+                        return;
+                    }
+                }
+                // Jump to the chosen node and select it
+                if let Some(node_id) = self.search_palette.commit() {
+                    self.keymap.set_cursor(Some(node_id));
+                    self.camera_controller.focus_on_node(node_id, engine);
+                    self.selection_manager.set_selection(vec![node_id]);
+                    self.manipulation.sync_selection(&[node_id], engine.scene());
+                    if let Some(callback) = &self.callbacks.read().unwrap().on_selection_changed {
+                        callback(vec![node_id]);
+                    }
+                }
+            }
+            PhysicalKey::Code(KeyCode::Space) if self.is_super_pressed() => self.search_palette.cancel(),
+            PhysicalKey::Code(KeyCode::Escape) => self.search_palette.cancel(),
+            PhysicalKey::Code(KeyCode::ArrowDown) => self.search_palette.next(),
+            PhysicalKey::Code(KeyCode::ArrowUp) => self.search_palette.previous(),
+            PhysicalKey::Code(KeyCode::Tab) if self.is_shift_pressed() => self.search_palette.previous(),
+            PhysicalKey::Code(KeyCode::Tab) => self.search_palette.next(),
+            PhysicalKey::Code(KeyCode::Backspace) => self.search_palette.pop_char(),
+            _ => {
+                // Type to search
+                if let Some(text) = &event.text {
+                    for c in text.chars().filter(|c| !c.is_control()) {
+                        self.search_palette.push_char(c);
+                    }
+                }
+            }
+        }
+        
+        if !self.search_palette.is_open() && self.keymap.mode() == KeymapMode::Command {
+            self.keymap.set_mode(KeymapMode::Normal);
+        }
+    }
+    
+    /// Modifiers held, for matching key chords
+    fn chord_modifiers(&self) -> ChordModifiers {
+        ChordModifiers {
+            ctrl: self.is_ctrl_pressed(),
+            alt: self.is_alt_pressed(),
+            shift: self.is_shift_pressed(),
+            logo: self.is_super_pressed(),
+        }
+    }
+    
+    /// Whether either Super key is held
+    fn is_super_pressed(&self) -> bool {
+        self.input_handler.is_key_pressed(KeyCode::SuperLeft) || self.input_handler.is_key_pressed(KeyCode::SuperRight)
+    }
+    
+    /// Whether either Alt key is held
+    fn is_alt_pressed(&self) -> bool {
+        self.input_handler.is_key_pressed(KeyCode::AltLeft) || self.input_handler.is_key_pressed(KeyCode::AltRight)
+    }
+    
+    /// Whether either Control key is held
+    fn is_ctrl_pressed(&self) -> bool {
+        self.input_handler.is_key_pressed(KeyCode::ControlLeft) || self.input_handler.is_key_pressed(KeyCode::ControlRight)
+    }
+    
+    /// Record the nodes moved by the finished drag for undo
+    fn record_drag(&mut self, engine: &mut GraphEngine) {
+        let moves: Vec<Change> = self
+            .drag_drop_handler
+            .original_positions()
+            .iter()
+            .filter_map(|(id, from)| {
+                let to = engine.scene().get_node_position(*id)?;
+                (to != *from).then_some(Change::NodeMoved { id: *id, from: *from, to })
+            })
+            .collect();
+        if moves.is_empty() {
+            return;
+        }
+        let history = engine.history_mut();
+        history.begin("Move nodes");
+        for change in moves {
+            history.record(change);
+        }
+        history.commit();
+    }
+    
+    /// The dragged node and the node it overlaps where it was let go, outside the dragged group
+    fn drop_target(&self, engine: &GraphEngine) -> Option<(SceneId, SceneId)> {
+        let dragged = self.drag_drop_handler.get_dragged_node()?;
+        let scene = engine.scene();
+        let position = scene.get_node_position(dragged)?;
+        let group = self.drag_drop_handler.dragged_group();
+        scene.find_overlapping_nodes(dragged, 0.0)
+            .into_iter()
+            .filter(|id| *id != dragged && !group.contains(id))
+            .filter_map(|id| Some((id, (scene.get_node_position(id)? - position).magnitude())))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(target, _)| (dragged, target))
+    }
+    
+    /// Drop nodes that no longer exist, e.g. after a delete or undo, from the selection
+    fn prune_selection(&mut self, engine: &GraphEngine) {
+        let selected = self.selection_manager.get_selection();
+        let remaining: Vec<SceneId> = selected.iter().copied().filter(|id| engine.scene().get_node(*id).is_some()).collect();
+        if remaining.len() == selected.len() {
+            return;
+        }
+        self.selection_manager.set_selection(remaining.clone());
+        self.manipulation.sync_selection(&remaining, engine.scene());
+        if let Some(callback) = &self.callbacks.read().unwrap().on_selection_changed {
+            callback(remaining);
+        }
+    }
+    
+    /// Whether either Shift key is held
+    fn is_shift_pressed(&self) -> bool {
+        self.input_handler.is_key_pressed(KeyCode::ShiftLeft) || self.input_handler.is_key_pressed(KeyCode::ShiftRight)
+    }
+    
     /// Handle touch input
     fn handle_touch(&mut self, touch: &winit::event::Touch, engine: &mut GraphEngine) {
         self.gesture_recognizer.process_touch(touch);
         
-        // Check for recognized gestures
+        // Check for recognized gestures and run what they are bound to
         if let Some(gesture) = self.gesture_recognizer.get_gesture() {
-            match gesture {
-                Gesture::Pinch { scale, center, .. } => {
-                    self.camera_controller.zoom(engine.camera_mut(), scale - 1.0);
-                }
-                Gesture::Pan { delta, .. } => {
-                    let current = self.input_handler.cursor_pos();
-                    let previous = (current.0 - delta.0, current.1 - delta.1);
-                    self.camera_controller.pan(engine.camera_mut(), current, previous);
-                }
-                Gesture::Rotate { angle, center, .. } => {
-                    self.camera_controller.rotate_around(engine.camera_mut(), center, angle);
+            self.run_gesture(gesture, engine.camera_mut());
+        }
+    }
+    
+    /// Handle a trackpad gesture from the compositor, running what the
+    /// recognized gesture is bound to, e.g. zooming the camera on a pinch
+    pub fn handle_trackpad_gesture(&mut self, event: TrackpadGesture, camera: &mut Camera) {
+        self.gesture_recognizer.process_trackpad(event);
+        if let Some(gesture) = self.gesture_recognizer.get_gesture() {
+            self.run_gesture(gesture, camera);
+        }
+    }
+    
+    /// Run the action bound to a recognized gesture
+    fn run_gesture(&mut self, gesture: Gesture, camera: &mut Camera) {
+        let Some(action) = self.gesture_bindings.action(&gesture).cloned() else {
+            return;
+        };
+        match action {
+            GestureAction::Zoom => {
+                let delta = match gesture {
+                    Gesture::Pinch { scale, .. }
+                    | Gesture::PinchAndRotate { scale, .. }
+                    | Gesture::PanAndPinch { scale, .. } => scale - 1.0,
+                    _ => GESTURE_ZOOM_STEP,
+                };
+                self.camera_controller.zoom(camera, delta);
+            }
+            GestureAction::Pan => {
+                let delta = match gesture {
+                    Gesture::Pan { delta, .. } | Gesture::TwoFingerPan { delta, .. } => delta,
+                    Gesture::PanAndPinch { pan_delta, .. } => pan_delta,
+                    _ => return,
+                };
+                let current = self.input_handler.cursor_pos();
+                let previous = (current.0 - delta.0, current.1 - delta.1);
+                self.camera_controller.pan(camera, current, previous);
+            }
+            GestureAction::Rotate => {
+                let (angle, center) = match gesture {
+                    Gesture::Rotate { angle, center, .. } | Gesture::PinchAndRotate { angle, center, .. } => (angle, center),
+                    _ => return,
+                };
+                self.camera_controller.rotate_around(camera, center, angle);
+            }
+            GestureAction::Shortcut(action) => {
+                if let Some(callback) = &self.callbacks.read().unwrap().on_shortcut {
+                    callback(&action);
                 }
-                _ => {}
+            }
+            GestureAction::None => {}
+        }
+        self.pointer_picker.invalidate();
+    }
+    
+    /// Check for GPU pick results, e.g. once per frame so hover follows a resting pointer
+    pub fn poll_pointer_pick(&mut self, engine: &GraphEngine) {
+        if let Some((position, node_id)) = self.pointer_picker.poll(engine) {
+            if self.mode == InteractionMode::Normal && position == self.input_handler.cursor_pos() {
+                self.selection_manager.set_hover(node_id.filter(|id| engine.scene().get_node(*id).is_some()));
             }
         }
     }
     
     /// Pick a node at screen coordinates
+    ///
+    /// Uses the GPU pick of the pointer when one has resolved for this spot,
+    /// falling back to ray casting.
     fn pick_node_at(&self, screen_pos: (f32, f32), engine: &GraphEngine) -> Option<SceneId> {
+        if let Some(picked) = self.pointer_picker.resolved_at(screen_pos) {
+            return picked.filter(|id| engine.scene().get_node(*id).is_some());
+        }
+        
         // Convert screen coordinates to ray
         let ray = self.camera_controller.screen_to_ray(screen_pos, engine.camera(), engine.window_size());
         
@@ -344,6 +1079,192 @@ impl InteractionManager {
         self.camera_controller.screen_to_world(screen_pos, engine.camera(), engine.window_size())
     }
     
+    /// Get the window switcher
+    pub fn window_switcher(&self) -> &WindowSwitcher {
+        &self.window_switcher
+    }
+    
+    /// Get the window switcher for configuration and focus tracking
+    pub fn window_switcher_mut(&mut self) -> &mut WindowSwitcher {
+        &mut self.window_switcher
+    }
+    
+    /// Set the clusters consulted when ranking switcher candidates
+    pub fn set_cluster_store(&mut self, clusters: Arc<dyn ClusterStore>) {
+        self.cluster_store = Some(clusters);
+    }
+    
+    /// Set the nodes whose available actions the context menu offers
+    pub fn set_node_manager(&mut self, nodes: Arc<Mutex<NodeManager>>) {
+        self.node_manager = Some(nodes);
+    }
+    
+    /// Search palette state
+    pub fn search_palette(&self) -> &SearchPalette {
+        &self.search_palette
+    }
+    
+    /// Search palette for opening and querying it programmatically
+    pub fn search_palette_mut(&mut self) -> &mut SearchPalette {
+        &mut self.search_palette
+    }
+    
+    /// Quick-look overlay state
+    pub fn quick_look(&self) -> &QuickLook {
+        &self.quick_look
+    }
+    
+    /// Open or close the quick-look overlay programmatically
+    pub fn quick_look_mut(&mut self) -> &mut QuickLook {
+        &mut self.quick_look
+    }
+    
+    /// Let the search palette match node implementations through the node manager's index
+    pub fn set_search_index(&mut self, index: Arc<RwLock<NodeSearchIndex>>) {
+        self.search_palette.set_index(index);
+    }
+    
+    /// Load the configured shortcuts, modal keymap, pointer bindings and gesture bindings
+    pub fn configure_keys(&mut self, config: &GraphDesktopConfig) {
+        self.shortcuts = ShortcutRegistry::from_config(&config.shortcuts);
+        self.keymap = ModalKeymap::from_config(&config.interaction.keymap);
+        self.pointer_bindings = PointerBindings::from_config(&config.shortcuts);
+        self.gesture_bindings = GestureBindings::from_config(&config.gestures);
+    }
+    
+    /// Load the configured focus mode depth, followed edge types and fading
+    pub fn configure_focus_mode(&mut self, config: &GraphDesktopConfig) {
+        self.focus_mode.configure(FocusModeConfig::from_config(&config.interaction.focus_mode));
+    }
+    
+    /// Modal keymap state
+    pub fn keymap(&self) -> &ModalKeymap {
+        &self.keymap
+    }
+    
+    /// Modal keymap for enabling it and changing bindings
+    pub fn keymap_mut(&mut self) -> &mut ModalKeymap {
+        &mut self.keymap
+    }
+    
+    /// Configured shortcuts
+    pub fn shortcuts(&self) -> &ShortcutRegistry {
+        &self.shortcuts
+    }
+    
+    /// Run the configured shortcut bound to a chord, e.g. from the compositor's key handling
+    ///
+    /// Returns whether a shortcut is bound to the chord.
+    pub fn run_shortcut(&self, chord: &KeyChord) -> bool {
+        let Some(action) = self.shortcuts.action(chord) else {
+            return false;
+        };
+        if let Some(callback) = &self.callbacks.read().unwrap().on_shortcut {
+            callback(action);
+        }
+        true
+    }
+    
+    /// Smart selection commands, for listing them in the command palette
+    pub fn selection_commands(&self) -> &SelectionCommands {
+        &self.selection_commands
+    }
+    
+    /// Smart selection commands for registering new ones and binding shortcuts
+    pub fn selection_commands_mut(&mut self) -> &mut SelectionCommands {
+        &mut self.selection_commands
+    }
+    
+    /// Configured pointer bindings
+    pub fn pointer_bindings(&self) -> &PointerBindings {
+        &self.pointer_bindings
+    }
+    
+    /// Configured touch gesture bindings
+    pub fn gesture_bindings(&self) -> &GestureBindings {
+        &self.gesture_bindings
+    }
+    
+    /// Focus mode state
+    pub fn focus_mode(&self) -> &FocusMode {
+        &self.focus_mode
+    }
+    
+    /// Focus mode for activation and configuration
+    pub fn focus_mode_mut(&mut self) -> &mut FocusMode {
+        &mut self.focus_mode
+    }
+    
+    /// Advance focus mode fades; call once per frame
+    pub fn update_focus_mode(&mut self, delta_time: f32, engine: &mut GraphEngine) {
+        if self.focus_mode.is_active() || self.focus_mode.is_animating() {
+            self.focus_mode.update(delta_time, engine.scene_mut());
+        }
+    }
+    
+    /// Hand the renderer the selection overlay and the edge being drawn; call once per frame
+    pub fn publish_selection_visuals(&self, engine: &mut GraphEngine) {
+        engine.set_selection_visual_state(self.selection_manager.visual_state());
+        let ghost = self.ghost_edge(engine).map(|ghost| ghost.line(engine.palette()));
+        engine.set_ghost_edge(ghost);
+    }
+    
+    /// Set a callback for windows chosen in the switcher
+    pub fn on_window_switch<F>(&mut self, callback: F)
+    where
+        F: Fn(SceneId) + Send + Sync + 'static,
+    {
+        self.callbacks.write().unwrap().on_window_switch = Some(Box::new(callback));
+    }
+    
+    /// Set a callback for configured shortcuts, called with their action name
+    pub fn on_shortcut<F>(&mut self, callback: F)
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.callbacks.write().unwrap().on_shortcut = Some(Box::new(callback));
+    }
+    
+    /// Set a callback for quick-look changes, to load and show file previews
+    pub fn on_quick_look<F>(&mut self, callback: F)
+    where
+        F: Fn(&QuickLookEvent) + Send + Sync + 'static,
+    {
+        self.callbacks.write().unwrap().on_quick_look = Some(Box::new(callback));
+    }
+    
+    /// Set callback for keyboard focus moves, e.g. to announce them through the screen reader
+    pub fn on_focus_changed<F>(&mut self, callback: F)
+    where
+        F: Fn(&FocusChange) + Send + Sync + 'static,
+    {
+        self.callbacks.write().unwrap().on_focus_changed = Some(Box::new(callback));
+    }
+    
+    /// Set a callback for edge filter changes, to save them with the active workspace
+    pub fn on_edge_filter_changed<F>(&mut self, callback: F)
+    where
+        F: Fn(&EdgeFilter) + Send + Sync + 'static,
+    {
+        self.callbacks.write().unwrap().on_edge_filter_changed = Some(Box::new(callback));
+    }
+    
+    /// Set a callback for dragged nodes, called once per move with all nodes of the dragged group
+    pub fn on_nodes_dragged<F>(&mut self, callback: F)
+    where
+        F: Fn(&[(SceneId, Position)]) + Send + Sync + 'static,
+    {
+        self.callbacks.write().unwrap().on_nodes_dragged = Some(Box::new(callback));
+    }
+    
+    /// Set a callback for a dragged node let go on top of another node, given both
+    pub fn on_node_dropped<F>(&mut self, callback: F)
+    where
+        F: Fn(SceneId, SceneId) + Send + Sync + 'static,
+    {
+        self.callbacks.write().unwrap().on_node_dropped = Some(Box::new(callback));
+    }
+    
     /// Set a callback for node clicks
     pub fn on_node_click<F>(&mut self, callback: F) 
     where
@@ -368,6 +1289,121 @@ impl InteractionManager {
         self.callbacks.write().unwrap().on_selection_changed = Some(Box::new(callback));
     }
     
+    /// Set a callback for transforms made with manipulation handles
+    pub fn on_node_transform<F>(&mut self, callback: F)
+    where
+        F: Fn(SceneId, NodeTransform) + Send + Sync + 'static,
+    {
+        self.callbacks.write().unwrap().on_node_transform = Some(Box::new(callback));
+    }
+    
+    /// Set a callback for node actions run from the context menu, e.g. to show their result
+    pub fn on_node_action<F>(&mut self, callback: F)
+    where
+        F: Fn(&NodeActionOutcome) + Send + Sync + 'static,
+    {
+        self.callbacks.write().unwrap().on_node_action = Some(Box::new(callback));
+    }
+    
+    /// Start drawing an edge of a type from a node, e.g. from its context menu
+    pub fn begin_edge_creation(&mut self, source: SceneId, edge_type: EdgeType, engine: &GraphEngine) -> bool {
+        if !self.edge_creation.begin(source, edge_type, engine.scene()) {
+            return false;
+        }
+        self.drag_drop_handler.end_drag();
+        self.mode = InteractionMode::EdgeCreate;
+        true
+    }
+    
+    /// Handle a click on an item of the active context menu
+    ///
+    /// Items the interaction system handles itself, like starting an edge
+    /// with "connect" or running one of the node's actions, return `None`;
+    /// others return the node and item ID for the host to act on.
+    pub fn handle_menu_item(&mut self, item_id: &str, engine: &GraphEngine) -> Option<(SceneId, String)> {
+        let node_action = self.context_menu.node_action(item_id);
+        let (node_id, item_id) = self.context_menu.handle_item_click(item_id)?;
+        if item_id == "connect" {
+            self.context_menu.clear();
+            self.begin_edge_creation(node_id, DEFAULT_EDGE_TYPE, engine);
+            return None;
+        }
+        if let (Some((action_type, action)), Some(nodes)) = (node_action, &self.node_manager) {
+            self.context_menu.clear();
+            let outcome = {
+                let mut nodes = nodes.lock().unwrap();
+                NodeActionOutcome {
+                    node_id,
+                    node_name: nodes.display_name(node_id).unwrap_or_else(|| format!("Node {}", node_id)),
+                    action: action_type,
+                    request: action.clone(),
+                    result: nodes.handle_node_action(node_id, action).map_err(|e| e.to_string()),
+                }
+            };
+            if let Err(e) = &outcome.result {
+                log::warn!("Action {:?} on node {} failed: {}", outcome.action, node_id, e);
+            }
+            if let Some(callback) = &self.callbacks.read().unwrap().on_node_action {
+                callback(&outcome);
+            }
+            return None;
+        }
+        Some((node_id, item_id))
+    }
+    
+    /// Change the type of the edge being drawn
+    pub fn set_edge_creation_type(&mut self, edge_type: EdgeType, engine: &GraphEngine) {
+        let edges = self.edge_manager.read().unwrap();
+        self.edge_creation.set_edge_type(edge_type, engine.scene(), &edges);
+    }
+    
+    /// Preview of the edge being drawn, to render
+    pub fn ghost_edge(&self, engine: &GraphEngine) -> Option<GhostEdge> {
+        self.edge_creation.ghost(engine.scene())
+    }
+    
+    /// Share the desktop's edge manager, so new edges follow its rules and end up in it
+    pub fn set_edge_manager(&mut self, edges: Arc<RwLock<EdgeManager>>) {
+        self.edge_manager = edges;
+    }
+    
+    /// Add the edge being drawn if it points at a valid target, else stop drawing
+    fn finish_edge_creation(&mut self, engine: &mut GraphEngine) {
+        let created = {
+            let mut edges = self.edge_manager.write().unwrap();
+            self.edge_creation.commit(engine.scene(), &mut edges)
+        };
+        self.mode = InteractionMode::Normal;
+        
+        match created {
+            Ok(Some(created)) => {
+                if let Err(e) = engine.execute(GraphCommand::AddEdge(created.scene_edge)) {
+                    log::warn!("Could not show edge {}: {}", created.edge_id, e);
+                }
+                if let Some(callback) = &self.callbacks.read().unwrap().on_edge_create {
+                    callback(created.source, created.target);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => log::info!("Edge not created: {}", e),
+        }
+    }
+    
+    fn cancel_edge_creation(&mut self) {
+        self.edge_creation.cancel();
+        self.mode = InteractionMode::Normal;
+    }
+    
+    /// Get the manipulation controller
+    pub fn manipulation(&mut self) -> &mut ManipulationController {
+        &mut self.manipulation
+    }
+    
+    /// Get manipulation handles to render for the current selection
+    pub fn manipulation_handles(&self, engine: &GraphEngine) -> Vec<ManipulationHandle> {
+        self.manipulation.handles(engine.scene(), |position| engine.ui_world_size(UiElement::Handle, 0.0, position))
+    }
+    
     /// Get current interaction mode
     pub fn mode(&self) -> InteractionMode {
         self.mode
@@ -378,6 +1414,16 @@ impl InteractionManager {
         self.mode = mode;
     }
     
+    /// Get the context menu manager
+    pub fn context_menu(&self) -> &ContextMenuManager {
+        &self.context_menu
+    }
+    
+    /// Get the context menu manager for registering entries and handling clicks
+    pub fn context_menu_mut(&mut self) -> &mut ContextMenuManager {
+        &mut self.context_menu
+    }
+    
     /// Get the selection manager
     pub fn selection(&self) -> &SelectionManager {
         &self.selection_manager
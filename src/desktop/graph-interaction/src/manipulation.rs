@@ -0,0 +1,571 @@
+//! Resize, rotation and scaling handles for nodes whose type opts in
+
+use horizonos_graph_engine::{Position, Scene, SceneId};
+use horizonos_graph_nodes::{ManipulationCapabilities, NodeTransform};
+use std::collections::HashMap;
+
+/// Corners of a node's manipulation frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HandleCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Sides of a node's manipulation frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HandleSide {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// What a handle manipulates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HandleKind {
+    /// Resize (or scale) from a corner
+    Corner(HandleCorner),
+    /// Resize along one axis
+    Side(HandleSide),
+    /// Rotate around the node center
+    Rotate,
+}
+
+/// A handle to render for a selected node
+#[derive(Debug, Clone, Copy)]
+pub struct ManipulationHandle {
+    pub node_id: SceneId,
+    pub kind: HandleKind,
+    /// World-space position
+    pub position: Position,
+    /// World-space size
+    pub size: f32,
+}
+
+/// Snapping applied while manipulating
+#[derive(Debug, Clone)]
+pub struct ManipulationSnapping {
+    /// Snap while manipulating, can be suspended per gesture
+    pub enabled: bool,
+    /// Size increment in world units
+    pub size_step: f32,
+    /// Rotation increment in radians
+    pub angle_step: f32,
+    /// Scale increment
+    pub scale_step: f32,
+}
+
+/// Modifier state for a manipulation update
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ManipulationModifiers {
+    /// Toggle the aspect-ratio lock for this gesture
+    pub toggle_aspect_lock: bool,
+    /// Suspend snapping for this gesture
+    pub disable_snapping: bool,
+}
+
+/// A node that currently shows handles
+#[derive(Debug, Clone)]
+struct ManipulationTarget {
+    capabilities: ManipulationCapabilities,
+    transform: NodeTransform,
+}
+
+/// Gesture in progress
+#[derive(Debug, Clone)]
+struct ActiveManipulation {
+    node_id: SceneId,
+    kind: HandleKind,
+    center: Position,
+    start_pointer: Position,
+    start_transform: NodeTransform,
+}
+
+/// Tracks handles for selected nodes and turns handle drags into transforms
+pub struct ManipulationController {
+    /// Selected nodes that accept manipulation
+    targets: HashMap<SceneId, ManipulationTarget>,
+    /// Transforms known for nodes, synced from their visual data
+    stored_transforms: HashMap<SceneId, NodeTransform>,
+    /// Gesture in progress
+    active: Option<ActiveManipulation>,
+    /// Snapping settings
+    snapping: ManipulationSnapping,
+    /// Distance of the rotation handle above the frame
+    rotate_handle_offset: f32,
+}
+
+impl ManipulationController {
+    /// Create a new manipulation controller
+    pub fn new() -> Self {
+        Self {
+            targets: HashMap::new(),
+            stored_transforms: HashMap::new(),
+            active: None,
+            snapping: ManipulationSnapping::default(),
+            rotate_handle_offset: 0.6,
+        }
+    }
+
+    /// Get snapping settings
+    pub fn snapping(&mut self) -> &mut ManipulationSnapping {
+        &mut self.snapping
+    }
+
+    /// Record the stored transform of a node, e.g. from its `NodeVisualData` extensions
+    pub fn set_transform(&mut self, node_id: SceneId, transform: NodeTransform) {
+        self.stored_transforms.insert(node_id, transform);
+        if let Some(target) = self.targets.get_mut(&node_id) {
+            target.transform = transform;
+        }
+    }
+
+    /// Transform of a node, if known
+    pub fn transform(&self, node_id: SceneId) -> Option<NodeTransform> {
+        self.targets.get(&node_id)
+            .map(|target| target.transform)
+            .or_else(|| self.stored_transforms.get(&node_id).copied())
+    }
+
+    /// Show handles for the selected nodes whose type opts in
+    pub fn sync_selection(&mut self, selection: &[SceneId], scene: &Scene) {
+        self.targets.retain(|id, _| selection.contains(id));
+
+        for &node_id in selection {
+            if self.targets.contains_key(&node_id) {
+                continue;
+            }
+            let Some(node) = scene.get_node(node_id) else {
+                continue;
+            };
+            let capabilities = ManipulationCapabilities::for_node_type(&node.node_type);
+            if !capabilities.is_manipulable() {
+                continue;
+            }
+
+            let transform = self.stored_transforms.get(&node_id).copied().unwrap_or_else(|| {
+                NodeTransform {
+                    aspect_locked: capabilities.lock_aspect_ratio,
+                    ..NodeTransform::from_radius(node.radius)
+                }
+            });
+            self.targets.insert(node_id, ManipulationTarget { capabilities, transform });
+        }
+
+        if let Some(active) = &self.active {
+            if !self.targets.contains_key(&active.node_id) {
+                self.active = None;
+            }
+        }
+    }
+
+    /// Handles to render for all selected manipulable nodes
+    ///
+    /// `handle_size` gives the world size of a handle at a position, so
+    /// handles can keep their size on screen at any zoom.
+    pub fn handles(&self, scene: &Scene, handle_size: impl Fn(Position) -> f32) -> Vec<ManipulationHandle> {
+        let mut handles = Vec::new();
+
+        for (&node_id, target) in &self.targets {
+            let Some(node) = scene.get_node(node_id) else {
+                continue;
+            };
+            let caps = target.capabilities;
+            let [width, height] = target.transform.scaled_size();
+            let (half_w, half_h) = (width * 0.5, height * 0.5);
+
+            let mut push = |kind: HandleKind, local: (f32, f32)| {
+                let position = to_world(node.position, target.transform.rotation, local);
+                handles.push(ManipulationHandle {
+                    node_id,
+                    kind,
+                    position,
+                    size: handle_size(position),
+                });
+            };
+
+            if caps.resize || caps.scale {
+                push(HandleKind::Corner(HandleCorner::TopLeft), (-half_w, half_h));
+                push(HandleKind::Corner(HandleCorner::TopRight), (half_w, half_h));
+                push(HandleKind::Corner(HandleCorner::BottomLeft), (-half_w, -half_h));
+                push(HandleKind::Corner(HandleCorner::BottomRight), (half_w, -half_h));
+            }
+            if caps.resize && !target.transform.aspect_locked {
+                push(HandleKind::Side(HandleSide::Top), (0.0, half_h));
+                push(HandleKind::Side(HandleSide::Bottom), (0.0, -half_h));
+                push(HandleKind::Side(HandleSide::Left), (-half_w, 0.0));
+                push(HandleKind::Side(HandleSide::Right), (half_w, 0.0));
+            }
+            if caps.rotate {
+                push(HandleKind::Rotate, (0.0, half_h + self.rotate_handle_offset));
+            }
+        }
+
+        handles
+    }
+
+    /// Outline of a node's manipulation frame as four world-space corners
+    pub fn frame(&self, node_id: SceneId, scene: &Scene) -> Option<[Position; 4]> {
+        let target = self.targets.get(&node_id)?;
+        let center = scene.get_node(node_id)?.position;
+        let [width, height] = target.transform.scaled_size();
+        let (half_w, half_h) = (width * 0.5, height * 0.5);
+        let rotation = target.transform.rotation;
+
+        Some([
+            to_world(center, rotation, (-half_w, half_h)),
+            to_world(center, rotation, (half_w, half_h)),
+            to_world(center, rotation, (half_w, -half_h)),
+            to_world(center, rotation, (-half_w, -half_h)),
+        ])
+    }
+
+    /// Find the handle under a world-space point
+    pub fn hit_test(&self, point: Position, scene: &Scene, handle_size: impl Fn(Position) -> f32) -> Option<(SceneId, HandleKind)> {
+        self.handles(scene, handle_size)
+            .into_iter()
+            .map(|handle| {
+                let dx = handle.position.x - point.x;
+                let dy = handle.position.y - point.y;
+                (handle, (dx * dx + dy * dy).sqrt())
+            })
+            .filter(|(handle, distance)| *distance <= handle.size)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(handle, _)| (handle.node_id, handle.kind))
+    }
+
+    /// Start dragging a handle
+    pub fn begin(&mut self, node_id: SceneId, kind: HandleKind, pointer: Position, scene: &Scene) -> bool {
+        let (Some(target), Some(node)) = (self.targets.get(&node_id), scene.get_node(node_id)) else {
+            return false;
+        };
+
+        self.active = Some(ActiveManipulation {
+            node_id,
+            kind,
+            center: node.position,
+            start_pointer: pointer,
+            start_transform: target.transform,
+        });
+        true
+    }
+
+    /// Whether a handle is being dragged
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Update the dragged handle, returning the node's new transform
+    pub fn update(&mut self, pointer: Position, modifiers: ManipulationModifiers) -> Option<(SceneId, NodeTransform)> {
+        let active = self.active.as_ref()?;
+        let target = self.targets.get(&active.node_id)?;
+        let snap = self.snapping.enabled && !modifiers.disable_snapping;
+        let caps = target.capabilities;
+        let start = active.start_transform;
+
+        let mut transform = start;
+        match active.kind {
+            HandleKind::Rotate => {
+                let angle = angle_around(active.center, pointer) - angle_around(active.center, active.start_pointer);
+                transform.rotation = start.rotation + angle;
+                if snap {
+                    transform.rotation = snap_to(transform.rotation, self.snapping.angle_step);
+                }
+            }
+            HandleKind::Corner(_) if !caps.resize => {
+                // Scale-only nodes scale uniformly with the distance from the center
+                let start_distance = distance_2d(active.center, active.start_pointer).max(f32::EPSILON);
+                transform.scale = start.scale * distance_2d(active.center, pointer) / start_distance;
+                if snap {
+                    transform.scale = snap_to(transform.scale, self.snapping.scale_step);
+                }
+            }
+            HandleKind::Corner(_) | HandleKind::Side(_) => {
+                // Frame stays centered on the node, so each half-extent follows the pointer
+                let (local_x, local_y) = to_local(active.center, start.rotation, pointer);
+                let mut width = local_x.abs() * 2.0 / start.scale;
+                let mut height = local_y.abs() * 2.0 / start.scale;
+
+                match active.kind {
+                    HandleKind::Side(HandleSide::Left | HandleSide::Right) => height = start.size[1],
+                    HandleKind::Side(HandleSide::Top | HandleSide::Bottom) => width = start.size[0],
+                    _ => {}
+                }
+
+                let lock = start.aspect_locked != modifiers.toggle_aspect_lock;
+                if lock && matches!(active.kind, HandleKind::Corner(_)) {
+                    let factor = (width / start.size[0]).max(height / start.size[1]);
+                    width = start.size[0] * factor;
+                    height = start.size[1] * factor;
+                    if snap {
+                        let snapped = snap_to(width, self.snapping.size_step);
+                        height *= snapped / width.max(f32::EPSILON);
+                        width = snapped;
+                    }
+                } else if snap {
+                    width = snap_to(width, self.snapping.size_step);
+                    height = snap_to(height, self.snapping.size_step);
+                }
+
+                transform.size = [width, height];
+            }
+        }
+
+        if !caps.rotate {
+            transform.rotation = start.rotation;
+        }
+        let transform = transform.clamped();
+
+        let node_id = active.node_id;
+        if let Some(target) = self.targets.get_mut(&node_id) {
+            target.transform = transform;
+        }
+        Some((node_id, transform))
+    }
+
+    /// Finish the gesture, returning the transform to store on the node
+    pub fn end(&mut self) -> Option<(SceneId, NodeTransform)> {
+        let active = self.active.take()?;
+        let transform = self.targets.get(&active.node_id)?.transform;
+        self.stored_transforms.insert(active.node_id, transform);
+        Some((active.node_id, transform))
+    }
+
+    /// Abandon the gesture and restore the original transform
+    pub fn cancel(&mut self) -> Option<(SceneId, NodeTransform)> {
+        let active = self.active.take()?;
+        let target = self.targets.get_mut(&active.node_id)?;
+        target.transform = active.start_transform;
+        Some((active.node_id, active.start_transform))
+    }
+
+    /// Toggle the stored aspect-ratio lock of a node
+    pub fn toggle_aspect_lock(&mut self, node_id: SceneId) -> Option<NodeTransform> {
+        let target = self.targets.get_mut(&node_id)?;
+        target.transform.aspect_locked = !target.transform.aspect_locked;
+        self.stored_transforms.insert(node_id, target.transform);
+        Some(target.transform)
+    }
+}
+
+impl Default for ManipulationController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for ManipulationSnapping {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            size_step: 0.25,
+            angle_step: 15f32.to_radians(),
+            scale_step: 0.1,
+        }
+    }
+}
+
+/// Round a value to the nearest multiple of a step
+fn snap_to(value: f32, step: f32) -> f32 {
+    if step <= 0.0 {
+        value
+    } else {
+        (value / step).round() * step
+    }
+}
+
+/// Node-local offset to world position, rotating around the view axis
+fn to_world(center: Position, rotation: f32, local: (f32, f32)) -> Position {
+    let (sin, cos) = rotation.sin_cos();
+    Position::new(
+        center.x + local.0 * cos - local.1 * sin,
+        center.y + local.0 * sin + local.1 * cos,
+        center.z,
+    )
+}
+
+/// World position to node-local offset
+fn to_local(center: Position, rotation: f32, point: Position) -> (f32, f32) {
+    let (dx, dy) = (point.x - center.x, point.y - center.y);
+    let (sin, cos) = rotation.sin_cos();
+    (dx * cos + dy * sin, -dx * sin + dy * cos)
+}
+
+/// Angle of a point around a center in the view plane
+fn angle_around(center: Position, point: Position) -> f32 {
+    (point.y - center.y).atan2(point.x - center.x)
+}
+
+/// Distance in the view plane
+fn distance_2d(a: Position, b: Position) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::{ConfigType, FileType, NodeMetadata, NodeType, SceneNode, TaskStatus};
+    use nalgebra::Vector3;
+
+    fn node(scene: &mut Scene, node_type: NodeType) -> SceneId {
+        scene.add_node(SceneNode {
+            id: 0,
+            position: Position::new(0.0, 0.0, 0.0),
+            velocity: Vector3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type,
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        })
+    }
+
+    fn concept(scene: &mut Scene) -> SceneId {
+        node(scene, NodeType::Concept { title: String::new(), content: String::new() })
+    }
+
+    fn assert_size(transform: NodeTransform, size: [f32; 2]) {
+        assert!(
+            (transform.size[0] - size[0]).abs() < 1e-4 && (transform.size[1] - size[1]).abs() < 1e-4,
+            "size {:?}, expected {:?}", transform.size, size
+        );
+    }
+
+    fn kinds(controller: &ManipulationController, scene: &Scene) -> Vec<HandleKind> {
+        controller.handles(scene, |_| 0.1).into_iter().map(|handle| handle.kind).collect()
+    }
+
+    #[test]
+    fn test_selected_nodes_show_the_handles_their_type_allows() {
+        let mut scene = Scene::new();
+        let free = concept(&mut scene);
+        let image = node(&mut scene, NodeType::File { path: "/tmp/a.png".to_string(), file_type: FileType::Image });
+        let group = node(&mut scene, NodeType::ConfigGroup { name: String::new(), config_type: ConfigType::Project, items: Vec::new() });
+        let task = node(&mut scene, NodeType::Task { title: String::new(), status: TaskStatus::Todo });
+        let mut controller = ManipulationController::new();
+
+        controller.sync_selection(&[free], &scene);
+        assert_eq!(kinds(&controller, &scene).len(), 9);
+        // Images keep their proportions, so they have no side handles
+        controller.sync_selection(&[image], &scene);
+        let image_kinds = kinds(&controller, &scene);
+        assert_eq!(image_kinds.len(), 5);
+        assert!(image_kinds.contains(&HandleKind::Rotate));
+        controller.sync_selection(&[group], &scene);
+        assert!(!kinds(&controller, &scene).contains(&HandleKind::Rotate));
+        controller.sync_selection(&[task], &scene);
+        assert!(kinds(&controller, &scene).is_empty());
+        assert!(!controller.begin(task, HandleKind::Rotate, Position::new(0.0, 1.6, 0.0), &scene));
+    }
+
+    #[test]
+    fn test_corner_drag_resizes_until_the_gesture_ends() {
+        let mut scene = Scene::new();
+        let id = concept(&mut scene);
+        let mut controller = ManipulationController::new();
+        controller.sync_selection(&[id], &scene);
+        let corner = HandleKind::Corner(HandleCorner::TopRight);
+        assert_eq!(controller.hit_test(Position::new(1.05, 0.95, 0.0), &scene, |_| 0.1), Some((id, corner)));
+
+        assert!(controller.begin(id, corner, Position::new(1.0, 1.0, 0.0), &scene));
+        assert!(controller.is_active());
+        let (_, transform) = controller.update(Position::new(2.0, 1.5, 0.0), ManipulationModifiers::default()).unwrap();
+        assert_size(transform, [4.0, 3.0]);
+        // Toggling the aspect lock grows both sides by the larger factor
+        let locked = ManipulationModifiers { toggle_aspect_lock: true, ..Default::default() };
+        let (_, transform) = controller.update(Position::new(2.0, 1.5, 0.0), locked).unwrap();
+        assert_size(transform, [4.0, 4.0]);
+
+        let (ended, transform) = controller.end().unwrap();
+        assert_eq!(ended, id);
+        assert_size(transform, [4.0, 4.0]);
+        assert!(!controller.is_active());
+        assert!(controller.update(Position::new(3.0, 3.0, 0.0), ManipulationModifiers::default()).is_none());
+        // The stored transform outlives the selection
+        controller.sync_selection(&[], &scene);
+        assert_size(controller.transform(id).unwrap(), [4.0, 4.0]);
+    }
+
+    #[test]
+    fn test_side_drag_changes_one_axis_and_snaps() {
+        let mut scene = Scene::new();
+        let id = concept(&mut scene);
+        let mut controller = ManipulationController::new();
+        controller.sync_selection(&[id], &scene);
+
+        controller.begin(id, HandleKind::Side(HandleSide::Right), Position::new(1.0, 0.0, 0.0), &scene);
+        let (_, transform) = controller.update(Position::new(1.6, 0.3, 0.0), ManipulationModifiers::default()).unwrap();
+        assert_size(transform, [3.25, 2.0]);
+        let unsnapped = ManipulationModifiers { disable_snapping: true, ..Default::default() };
+        let (_, transform) = controller.update(Position::new(1.6, 0.3, 0.0), unsnapped).unwrap();
+        assert_size(transform, [3.2, 2.0]);
+        // Frames never shrink below the smallest size
+        let (_, transform) = controller.update(Position::new(0.0, 0.0, 0.0), ManipulationModifiers::default()).unwrap();
+        assert_size(transform, [NodeTransform::MIN_SIZE, 2.0]);
+    }
+
+    #[test]
+    fn test_rotation_snaps_unless_suspended() {
+        let mut scene = Scene::new();
+        let id = concept(&mut scene);
+        let group = node(&mut scene, NodeType::ConfigGroup { name: String::new(), config_type: ConfigType::Project, items: Vec::new() });
+        let mut controller = ManipulationController::new();
+        controller.sync_selection(&[id, group], &scene);
+        let pointer = |degrees: f32| {
+            let (sin, cos) = degrees.to_radians().sin_cos();
+            Position::new(cos, sin, 0.0)
+        };
+
+        controller.begin(id, HandleKind::Rotate, pointer(90.0), &scene);
+        let (_, transform) = controller.update(pointer(100.0), ManipulationModifiers::default()).unwrap();
+        assert!((transform.rotation - 15f32.to_radians()).abs() < 1e-4);
+        let unsnapped = ManipulationModifiers { disable_snapping: true, ..Default::default() };
+        let (_, transform) = controller.update(pointer(100.0), unsnapped).unwrap();
+        assert!((transform.rotation - 10f32.to_radians()).abs() < 1e-4);
+        controller.end();
+
+        // Groups cannot rotate, whatever handle is dragged
+        controller.begin(group, HandleKind::Rotate, pointer(90.0), &scene);
+        let (_, transform) = controller.update(pointer(180.0), ManipulationModifiers::default()).unwrap();
+        assert_eq!(transform.rotation, 0.0);
+    }
+
+    #[test]
+    fn test_cancel_and_deselect_abandon_the_gesture() {
+        let mut scene = Scene::new();
+        let id = concept(&mut scene);
+        let mut controller = ManipulationController::new();
+        controller.sync_selection(&[id], &scene);
+        let corner = HandleKind::Corner(HandleCorner::BottomLeft);
+
+        controller.begin(id, corner, Position::new(-1.0, -1.0, 0.0), &scene);
+        controller.update(Position::new(-3.0, -3.0, 0.0), ManipulationModifiers::default());
+        let (_, restored) = controller.cancel().unwrap();
+        assert_size(restored, [2.0, 2.0]);
+        assert_size(controller.transform(id).unwrap(), [2.0, 2.0]);
+        assert!(controller.cancel().is_none());
+
+        controller.begin(id, corner, Position::new(-1.0, -1.0, 0.0), &scene);
+        controller.sync_selection(&[], &scene);
+        assert!(!controller.is_active());
+        assert!(controller.end().is_none());
+    }
+
+    #[test]
+    fn test_aspect_lock_toggle_is_stored() {
+        let mut scene = Scene::new();
+        let id = concept(&mut scene);
+        let mut controller = ManipulationController::new();
+        controller.sync_selection(&[id], &scene);
+
+        assert!(controller.toggle_aspect_lock(id).unwrap().aspect_locked);
+        // Locked frames lose their side handles
+        assert_eq!(kinds(&controller, &scene).len(), 5);
+        controller.sync_selection(&[], &scene);
+        controller.sync_selection(&[id], &scene);
+        assert!(controller.transform(id).unwrap().aspect_locked);
+    }
+}
@@ -0,0 +1,912 @@
+//! Custom context menu entries registered by scripts, plugins and .desktop actions
+//!
+//! Entries are added to node context menus when their target matches the
+//! node, e.g. "Open in VS Code" for file nodes tagged `code`. They can be
+//! registered at runtime, loaded from `$XDG_CONFIG_HOME/horizonos/menu.d/*.toml`
+//! or imported from the actions of a `.desktop` file:
+//!
+//! ```toml
+//! [[entry]]
+//! id = "open-in-vscode"
+//! label = "Open in VS Code"
+//! icon = "com.visualstudio.code"
+//! target = { node_types = ["file"] }
+//! placement = { after = "open" }
+//! enabled_when = ["has-path"]
+//! command = { type = "exec", argv = ["code", "%f"] }
+//! ```
+//!
+//! Commands are run without a shell; node details are substituted as whole
+//! arguments and passed as `HORIZONOS_NODE_*` environment variables. Entries
+//! that did not come with HorizonOS run only when the host's
+//! [`MenuPermissions`] allow them, which may ask the user first; an
+//! activation waiting on the answer runs once it is given.
+
+use horizonos_graph_engine::{NodeType, SceneId, SceneNode};
+use horizonos_graph_nodes::hooks::{node_type_name, WorkflowLauncher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+use crate::MenuItem;
+
+/// Prefix of menu item ids that belong to custom entries
+pub const CUSTOM_ITEM_PREFIX: &str = "ext:";
+
+/// Nodes an entry is shown for
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MenuTarget {
+    /// Node type names (see `node_type_name`); empty matches all types
+    #[serde(default)]
+    pub node_types: Vec<String>,
+    /// Tags of which the node must have at least one; empty matches all nodes
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl MenuTarget {
+    /// Whether a node in `context` is targeted
+    pub fn matches(&self, context: &MenuContext) -> bool {
+        (self.node_types.is_empty() || self.node_types.iter().any(|t| t == context.node_type))
+            && (self.tags.is_empty() || self.tags.iter().any(|t| context.tags.contains(t)))
+    }
+}
+
+/// Where an entry goes in the menu
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MenuPlacement {
+    /// Start of the menu
+    Top,
+    /// End of the menu, after a separator
+    #[default]
+    Bottom,
+    /// Directly before the item with this id
+    Before(String),
+    /// Directly after the item with this id
+    After(String),
+    /// In a submenu with this label, created if needed
+    Submenu(String),
+}
+
+/// Condition for an entry to be enabled
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MenuCondition {
+    /// Exactly one node is selected
+    SingleSelection,
+    /// More than one node is selected
+    MultipleSelection,
+    /// The node has a filesystem path
+    HasPath,
+    /// The node's path exists
+    PathExists,
+    /// The node's path is a directory
+    IsDirectory,
+    /// The node's path has one of these extensions
+    Extension(Vec<String>),
+    /// The node's URL has one of these schemes
+    UrlScheme(Vec<String>),
+    /// A program is available in `PATH`
+    CommandAvailable(String),
+    /// The node has this tag
+    Tag(String),
+}
+
+impl MenuCondition {
+    /// Whether the condition holds for `context`
+    pub fn holds(&self, context: &MenuContext) -> bool {
+        match self {
+            MenuCondition::SingleSelection => context.selection_count <= 1,
+            MenuCondition::MultipleSelection => context.selection_count > 1,
+            MenuCondition::HasPath => context.path.is_some(),
+            MenuCondition::PathExists => context.path.as_ref().is_some_and(|p| p.exists()),
+            MenuCondition::IsDirectory => context.path.as_ref().is_some_and(|p| p.is_dir()),
+            MenuCondition::Extension(extensions) => context
+                .path
+                .as_ref()
+                .and_then(|p| p.extension())
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| extensions.iter().any(|x| x.trim_start_matches('.').eq_ignore_ascii_case(e))),
+            MenuCondition::UrlScheme(schemes) => context
+                .url
+                .as_deref()
+                .and_then(|url| url.split_once(':'))
+                .is_some_and(|(scheme, _)| schemes.iter().any(|s| s.eq_ignore_ascii_case(scheme))),
+            MenuCondition::CommandAvailable(program) => find_in_path(program).is_some(),
+            MenuCondition::Tag(tag) => context.tags.contains(tag),
+        }
+    }
+}
+
+/// What an entry runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum MenuCommand {
+    /// Program and arguments; `%f`/`%F` is replaced by the node path, `%u`/`%U`
+    /// by its URI, `%c` by its name, `%i` by `--icon <icon>` and `%%` by `%`
+    Exec { argv: Vec<String> },
+    /// Script run with node details in the environment
+    Script {
+        path: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Automation workflow started with the node as input
+    Workflow { workflow_id: String },
+}
+
+/// Who registered an entry
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum MenuEntrySource {
+    /// Shipped with HorizonOS; never needs approval
+    Builtin,
+    /// Written by the user in `menu.d`
+    #[default]
+    User,
+    /// Registered by a plugin
+    Plugin { plugin_id: String },
+    /// Imported from a `.desktop` file
+    DesktopFile { path: PathBuf },
+}
+
+/// A custom context menu entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomMenuEntry {
+    /// Unique id
+    pub id: String,
+    /// Menu label
+    pub label: String,
+    /// Icon name
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Shortcut shown next to the label
+    #[serde(default)]
+    pub shortcut: Option<String>,
+    /// Nodes the entry is shown for
+    #[serde(default)]
+    pub target: MenuTarget,
+    /// Position in the menu
+    #[serde(default)]
+    pub placement: MenuPlacement,
+    /// Order among entries with the same placement, lower first
+    #[serde(default)]
+    pub priority: i32,
+    /// Conditions that must all hold for the entry to be enabled
+    #[serde(default)]
+    pub enabled_when: Vec<MenuCondition>,
+    /// What the entry runs
+    pub command: MenuCommand,
+    /// Who registered the entry
+    #[serde(default)]
+    pub source: MenuEntrySource,
+}
+
+impl CustomMenuEntry {
+    /// Create an entry for all nodes, placed at the bottom of the menu
+    pub fn new(id: impl Into<String>, label: impl Into<String>, command: MenuCommand) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            icon: None,
+            shortcut: None,
+            target: MenuTarget::default(),
+            placement: MenuPlacement::default(),
+            priority: 0,
+            enabled_when: Vec::new(),
+            command,
+            source: MenuEntrySource::User,
+        }
+    }
+
+    /// Set icon
+    pub fn with_icon(mut self, icon: impl Into<String>) -> Self {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    /// Only show for these node types
+    pub fn for_node_types(mut self, node_types: &[&str]) -> Self {
+        self.target.node_types = node_types.iter().map(|t| t.to_string()).collect();
+        self
+    }
+
+    /// Only show for nodes with one of these tags
+    pub fn for_tags(mut self, tags: &[&str]) -> Self {
+        self.target.tags = tags.iter().map(|t| t.to_string()).collect();
+        self
+    }
+
+    /// Set placement
+    pub fn placed(mut self, placement: MenuPlacement, priority: i32) -> Self {
+        self.placement = placement;
+        self.priority = priority;
+        self
+    }
+
+    /// Add an enable condition
+    pub fn enabled_when(mut self, condition: MenuCondition) -> Self {
+        self.enabled_when.push(condition);
+        self
+    }
+
+    /// Set source
+    pub fn from_source(mut self, source: MenuEntrySource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Menu item id of this entry
+    pub fn item_id(&self) -> String {
+        format!("{}{}", CUSTOM_ITEM_PREFIX, self.id)
+    }
+
+    /// Whether the user must approve the entry before it runs
+    pub fn needs_approval(&self) -> bool {
+        self.source != MenuEntrySource::Builtin
+    }
+
+    /// Human-readable description of what the entry runs, shown when asking for approval
+    pub fn describe_command(&self) -> String {
+        match &self.command {
+            MenuCommand::Exec { argv } => argv.join(" "),
+            MenuCommand::Script { path, args } => std::iter::once(path.clone()).chain(args.iter().cloned()).collect::<Vec<_>>().join(" "),
+            MenuCommand::Workflow { workflow_id } => format!("workflow {}", workflow_id),
+        }
+    }
+}
+
+/// The node a context menu was opened for
+#[derive(Debug, Clone, PartialEq)]
+pub struct MenuContext {
+    pub node_id: SceneId,
+    pub node_type: &'static str,
+    pub display_name: String,
+    pub tags: Vec<String>,
+    pub path: Option<PathBuf>,
+    pub url: Option<String>,
+    /// Nodes selected when the menu was opened
+    pub selection_count: usize,
+}
+
+impl MenuContext {
+    /// Build the context for a scene node
+    pub fn from_scene_node(node: &SceneNode, selection_count: usize) -> Self {
+        let (display_name, path, url) = match &node.node_type {
+            NodeType::File { path, .. } => {
+                let path = PathBuf::from(path);
+                let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                (name, Some(path), None)
+            }
+            NodeType::URL { url, title, .. } => (title.clone().unwrap_or_else(|| url.clone()), None, Some(url.clone())),
+            NodeType::Application { name, .. }
+            | NodeType::Device { name, .. }
+            | NodeType::AIAgent { name, .. }
+            | NodeType::Automation { name, .. }
+            | NodeType::ConfigGroup { name, .. }
+            | NodeType::Group { name, .. } => (name.clone(), None, None),
+            NodeType::Person { name, .. } => (name.clone(), None, None),
+            NodeType::Task { title, .. } | NodeType::Concept { title, .. } => (title.clone(), None, None),
+            NodeType::System { component, .. } => (component.clone(), None, None),
+            NodeType::Setting { key, .. } => (key.clone(), None, None),
+        };
+        Self {
+            node_id: node.id,
+            node_type: node_type_name(&node.node_type),
+            display_name,
+            tags: node.metadata.tags.clone(),
+            path,
+            url,
+            selection_count: selection_count.max(1),
+        }
+    }
+
+    fn env(&self) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("HORIZONOS_NODE_ID", self.node_id.to_string()),
+            ("HORIZONOS_NODE_TYPE", self.node_type.to_string()),
+            ("HORIZONOS_NODE_NAME", self.display_name.clone()),
+            ("HORIZONOS_NODE_TAGS", self.tags.join(",")),
+        ];
+        if let Some(path) = &self.path {
+            env.push(("HORIZONOS_NODE_PATH", path.to_string_lossy().into_owned()));
+        }
+        if let Some(url) = &self.url {
+            env.push(("HORIZONOS_NODE_URL", url.clone()));
+        }
+        env
+    }
+
+    fn uri(&self) -> Option<String> {
+        self.url.clone().or_else(|| self.path.as_ref().map(|p| format!("file://{}", p.display())))
+    }
+}
+
+/// Decision on running an entry that did not come with HorizonOS
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuPermission {
+    Allowed,
+    Denied,
+    /// The user is being asked; the answer is passed to
+    /// [`MenuExtensionRegistry::permission_resolved`] under this request ID
+    Pending(u64),
+}
+
+/// Decides whether entries that did not come with HorizonOS may run
+pub trait MenuPermissions: Send + Sync {
+    /// Check an entry, asking the user if the policy says so
+    fn check(&self, entry: &CustomMenuEntry) -> MenuPermission;
+}
+
+/// Result of activating a custom entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum MenuExecution {
+    /// The command was started
+    Started,
+    /// The user is being asked; the entry runs if the request is allowed
+    NeedsApproval { entry_id: String, label: String, command: String, request_id: u64 },
+    /// The permission system does not allow the entry to run
+    Denied,
+    /// An enable condition does not hold
+    Disabled,
+    /// No entry with this id
+    NotFound,
+    /// The command could not be started
+    Failed(String),
+}
+
+/// Custom context menu entries
+pub struct MenuExtensionRegistry {
+    entries: Vec<CustomMenuEntry>,
+    permissions: Option<Arc<dyn MenuPermissions>>,
+    /// Activations waiting on a permission request, by request ID
+    waiting: Mutex<HashMap<u64, (CustomMenuEntry, MenuContext)>>,
+    workflow_launcher: Option<Arc<dyn WorkflowLauncher>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MenuFile {
+    #[serde(default)]
+    entry: Vec<CustomMenuEntry>,
+}
+
+impl MenuExtensionRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            permissions: None,
+            waiting: Mutex::new(HashMap::new()),
+            workflow_launcher: None,
+        }
+    }
+
+    /// Directory searched by `load_user_entries`
+    pub fn default_dir() -> Option<PathBuf> {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|config| config.join("horizonos").join("menu.d"))
+    }
+
+    /// Register an entry, replacing one with the same id
+    pub fn register(&mut self, entry: CustomMenuEntry) {
+        self.entries.retain(|e| e.id != entry.id);
+        self.entries.push(entry);
+    }
+
+    /// Remove an entry
+    pub fn unregister(&mut self, id: &str) -> Option<CustomMenuEntry> {
+        let index = self.entries.iter().position(|e| e.id == id)?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Remove all entries registered by a plugin
+    pub fn unregister_plugin(&mut self, plugin_id: &str) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|e| !matches!(&e.source, MenuEntrySource::Plugin { plugin_id: p } if p == plugin_id));
+        before - self.entries.len()
+    }
+
+    /// Registered entries
+    pub fn entries(&self) -> &[CustomMenuEntry] {
+        &self.entries
+    }
+
+    /// Get an entry by id
+    pub fn get(&self, id: &str) -> Option<&CustomMenuEntry> {
+        self.entries.iter().find(|e| e.id == id)
+    }
+
+    /// Set the permission system deciding which entries may run; without
+    /// one only builtin entries run
+    pub fn set_permissions(&mut self, permissions: Arc<dyn MenuPermissions>) {
+        self.permissions = Some(permissions);
+    }
+
+    /// Set the launcher used by workflow entries
+    pub fn set_workflow_launcher(&mut self, launcher: Arc<dyn WorkflowLauncher>) {
+        self.workflow_launcher = Some(launcher);
+    }
+
+    /// Parse entries from TOML `[[entry]]` tables
+    pub fn parse_entries(content: &str) -> Result<Vec<CustomMenuEntry>, String> {
+        toml::from_str::<MenuFile>(content)
+            .map(|file| file.entry)
+            .map_err(|e| format!("Invalid menu entries: {}", e))
+    }
+
+    /// Load every `*.toml` file in `dir`, returning the number of entries registered
+    pub fn load_user_entries(&mut self, dir: &Path) -> Result<usize, String> {
+        let read_dir = match std::fs::read_dir(dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(format!("Failed to read {}: {}", dir.display(), e)),
+        };
+        let mut paths: Vec<PathBuf> = read_dir
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|e| e == "toml"))
+            .collect();
+        paths.sort();
+
+        let mut count = 0;
+        for path in paths {
+            let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            match Self::parse_entries(&content) {
+                Ok(entries) => {
+                    for entry in entries {
+                        // Files cannot claim to be builtin
+                        let source = match entry.source {
+                            MenuEntrySource::Builtin => MenuEntrySource::User,
+                            ref source => source.clone(),
+                        };
+                        self.register(entry.from_source(source));
+                        count += 1;
+                    }
+                }
+                Err(e) => log::warn!("Skipping {}: {}", path.display(), e),
+            }
+        }
+        Ok(count)
+    }
+
+    /// Import a `.desktop` file: its main entry becomes "Open in <Name>" for
+    /// file nodes and each `[Desktop Action]` becomes an entry in a submenu
+    pub fn import_desktop_file(&mut self, path: &Path) -> Result<usize, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let entries = parse_desktop_file(&content, path)?;
+        let count = entries.len();
+        for entry in entries {
+            self.register(entry);
+        }
+        Ok(count)
+    }
+
+    /// Entries shown for `context`, with their enabled state
+    pub fn entries_for<'a>(&'a self, context: &'a MenuContext) -> impl Iterator<Item = (&'a CustomMenuEntry, bool)> + 'a {
+        self.entries
+            .iter()
+            .filter(|entry| entry.target.matches(context))
+            .map(|entry| (entry, entry.enabled_when.iter().all(|c| c.holds(context))))
+    }
+
+    /// Insert the entries for `context` into `items` at their placements
+    pub fn extend_menu(&self, items: &mut Vec<MenuItem>, context: &MenuContext) {
+        let mut entries: Vec<_> = self.entries_for(context).collect();
+        entries.sort_by_key(|(entry, _)| entry.priority);
+
+        let to_item = |entry: &CustomMenuEntry, enabled: bool| {
+            let mut item = MenuItem::new(entry.item_id(), entry.label.clone()).with_enabled(enabled);
+            item.icon = entry.icon.clone();
+            item.shortcut = entry.shortcut.clone();
+            item
+        };
+
+        let mut top = 0;
+        // Entries already placed after an anchor, so later ones follow them
+        let mut after_anchor: HashMap<String, usize> = HashMap::new();
+        let mut bottom = Vec::new();
+        let mut submenus: Vec<(String, Vec<MenuItem>)> = Vec::new();
+        for (entry, enabled) in entries {
+            let item = to_item(entry, enabled);
+            match &entry.placement {
+                MenuPlacement::Top => {
+                    items.insert(top, item);
+                    top += 1;
+                }
+                MenuPlacement::Before(id) | MenuPlacement::After(id) => {
+                    match items.iter().position(|i| !i.separator && &i.id == id) {
+                        Some(index) if matches!(entry.placement, MenuPlacement::After(_)) => {
+                            let placed = after_anchor.entry(id.clone()).or_insert(0);
+                            items.insert(index + 1 + *placed, item);
+                            *placed += 1;
+                        }
+                        Some(index) => items.insert(index, item),
+                        None => bottom.push(item),
+                    }
+                }
+                MenuPlacement::Submenu(label) => match submenus.iter_mut().find(|(l, _)| l == label) {
+                    Some((_, submenu)) => submenu.push(item),
+                    None => submenus.push((label.clone(), vec![item])),
+                },
+                MenuPlacement::Bottom => bottom.push(item),
+            }
+        }
+        if top > 0 && items.len() > top {
+            items.insert(top, MenuItem::separator());
+        }
+
+        bottom.extend(submenus.into_iter().map(|(label, submenu)| {
+            MenuItem::new(format!("{}submenu:{}", CUSTOM_ITEM_PREFIX, label), label).with_submenu(submenu)
+        }));
+        if !bottom.is_empty() {
+            if items.last().is_some_and(|i| !i.separator) {
+                items.push(MenuItem::separator());
+            }
+            items.extend(bottom);
+        }
+    }
+
+    /// Run the entry behind a clicked menu item
+    pub fn activate(&self, item_id: &str, context: &MenuContext) -> MenuExecution {
+        let Some(id) = item_id.strip_prefix(CUSTOM_ITEM_PREFIX) else {
+            return MenuExecution::NotFound;
+        };
+        let Some(entry) = self.get(id) else {
+            return MenuExecution::NotFound;
+        };
+        if !entry.target.matches(context) || !entry.enabled_when.iter().all(|c| c.holds(context)) {
+            return MenuExecution::Disabled;
+        }
+        if entry.needs_approval() {
+            let permission = self.permissions.as_ref().map_or(MenuPermission::Denied, |p| p.check(entry));
+            match permission {
+                MenuPermission::Allowed => {}
+                MenuPermission::Denied => return MenuExecution::Denied,
+                MenuPermission::Pending(request_id) => {
+                    self.waiting.lock().unwrap_or_else(|e| e.into_inner()).insert(request_id, (entry.clone(), context.clone()));
+                    return MenuExecution::NeedsApproval {
+                        entry_id: entry.id.clone(),
+                        label: entry.label.clone(),
+                        command: entry.describe_command(),
+                        request_id,
+                    };
+                }
+            }
+        }
+        self.run(entry, context)
+    }
+
+    /// Run the activation waiting on a permission request once it is answered
+    ///
+    /// Returns `None` when nothing was waiting on the request.
+    pub fn permission_resolved(&self, request_id: u64, allowed: bool) -> Option<MenuExecution> {
+        let (entry, context) = self.waiting.lock().unwrap_or_else(|e| e.into_inner()).remove(&request_id)?;
+        // The user was asked about this entry as it was; a replaced or removed one does not run
+        if self.get(&entry.id) != Some(&entry) {
+            return Some(MenuExecution::NotFound);
+        }
+        if !allowed {
+            return Some(MenuExecution::Denied);
+        }
+        Some(self.run(&entry, &context))
+    }
+
+    fn run(&self, entry: &CustomMenuEntry, context: &MenuContext) -> MenuExecution {
+        let result = match &entry.command {
+            MenuCommand::Exec { argv } => {
+                let argv = expand_field_codes(argv, entry, context);
+                match argv.split_first() {
+                    Some((program, args)) => spawn(program, args, context),
+                    None => Err("Empty command".to_string()),
+                }
+            }
+            MenuCommand::Script { path, args } => spawn(&expand_home(path).to_string_lossy(), args, context),
+            MenuCommand::Workflow { workflow_id } => match &self.workflow_launcher {
+                Some(launcher) => launcher.launch(workflow_id, serde_json::json!({
+                    "menu_entry": entry.id,
+                    "node_id": context.node_id,
+                    "node_type": context.node_type,
+                    "display_name": context.display_name,
+                    "tags": context.tags,
+                    "path": context.path,
+                    "url": context.url,
+                })),
+                None => Err("No workflow launcher configured".to_string()),
+            },
+        };
+
+        match result {
+            Ok(()) => {
+                log::info!("Ran context menu entry '{}' for node {}", entry.id, context.node_id);
+                MenuExecution::Started
+            }
+            Err(e) => {
+                log::warn!("Context menu entry '{}' failed: {}", entry.id, e);
+                MenuExecution::Failed(e)
+            }
+        }
+    }
+}
+
+impl Default for MenuExtensionRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse the entries of a `.desktop` file
+fn parse_desktop_file(content: &str, path: &Path) -> Result<Vec<CustomMenuEntry>, String> {
+    let mut groups: Vec<(String, HashMap<String, String>)> = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(group) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            groups.push((group.to_string(), HashMap::new()));
+        } else if let (Some((key, value)), Some((_, fields))) = (line.split_once('='), groups.last_mut()) {
+            // Localized keys such as Name[de] are ignored
+            if !key.contains('[') {
+                fields.insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+    }
+
+    let main = groups
+        .iter()
+        .find(|(group, _)| group == "Desktop Entry")
+        .map(|(_, fields)| fields)
+        .ok_or_else(|| format!("{} has no [Desktop Entry] group", path.display()))?;
+    let name = main.get("Name").cloned().unwrap_or_else(|| "application".to_string());
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let source = MenuEntrySource::DesktopFile { path: path.to_path_buf() };
+
+    let takes_files = |fields: &HashMap<String, String>| {
+        fields.get("Exec").is_some_and(|exec| ["%f", "%F", "%u", "%U"].iter().any(|code| exec.contains(code)))
+    };
+    // Commands that take files are only offered for file and URL nodes
+    let entry_for = |id: String, label: String, fields: &HashMap<String, String>| {
+        let argv = split_exec(fields.get("Exec")?);
+        let mut entry = CustomMenuEntry::new(id, label, MenuCommand::Exec { argv }).from_source(source.clone());
+        entry.icon = fields.get("Icon").or_else(|| main.get("Icon")).cloned();
+        if takes_files(fields) {
+            entry = entry.for_node_types(&["file", "url"]);
+        }
+        Some(entry)
+    };
+
+    let mut entries = Vec::new();
+    if takes_files(main) {
+        if let Some(entry) = entry_for(format!("desktop:{}", stem), format!("Open in {}", name), main) {
+            entries.push(entry.placed(MenuPlacement::After("open".to_string()), 0));
+        }
+    }
+    for action in main.get("Actions").map(|a| a.split(';').filter(|a| !a.is_empty()).collect::<Vec<_>>()).unwrap_or_default() {
+        let Some((_, fields)) = groups.iter().find(|(group, _)| *group == format!("Desktop Action {}", action)) else {
+            continue;
+        };
+        let label = fields.get("Name").cloned().unwrap_or_else(|| action.to_string());
+        if let Some(entry) = entry_for(format!("desktop:{}:{}", stem, action), label, fields) {
+            entries.push(entry.placed(MenuPlacement::Submenu(name.clone()), 0));
+        }
+    }
+    Ok(entries)
+}
+
+/// Split an `Exec` value into arguments, honouring double quotes
+fn split_exec(exec: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' if quoted => current.extend(chars.next()),
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    args
+}
+
+/// Replace desktop entry field codes with node details
+fn expand_field_codes(argv: &[String], entry: &CustomMenuEntry, context: &MenuContext) -> Vec<String> {
+    let mut expanded = Vec::new();
+    for arg in argv {
+        match arg.as_str() {
+            "%f" | "%F" => expanded.extend(context.path.as_ref().map(|p| p.to_string_lossy().into_owned())),
+            "%u" | "%U" => expanded.extend(context.uri()),
+            "%i" => {
+                if let Some(icon) = &entry.icon {
+                    expanded.push("--icon".to_string());
+                    expanded.push(icon.clone());
+                }
+            }
+            _ => {
+                let mut out = String::new();
+                let mut chars = arg.chars();
+                while let Some(c) = chars.next() {
+                    if c != '%' {
+                        out.push(c);
+                        continue;
+                    }
+                    match chars.next() {
+                        Some('%') => out.push('%'),
+                        Some('c') => out.push_str(&context.display_name),
+                        Some('f') | Some('F') => out.push_str(&context.path.as_ref().map(|p| p.to_string_lossy().into_owned()).unwrap_or_default()),
+                        Some('u') | Some('U') => out.push_str(&context.uri().unwrap_or_default()),
+                        // Deprecated and unsupported codes are dropped
+                        _ => {}
+                    }
+                }
+                expanded.push(out);
+            }
+        }
+    }
+    expanded
+}
+
+fn spawn(program: &str, args: &[String], context: &MenuContext) -> Result<(), String> {
+    Command::new(program)
+        .args(args)
+        .envs(context.env())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|mut child| {
+            // Reap the process when it exits
+            std::thread::spawn(move || child.wait());
+        })
+        .map_err(|e| format!("Failed to start {}: {}", program, e))
+}
+
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    if program.contains('/') {
+        let path = PathBuf::from(program);
+        return path.is_file().then_some(path);
+    }
+    std::env::var_os("PATH").and_then(|paths| std::env::split_paths(&paths).map(|dir| dir.join(program)).find(|p| p.is_file()))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(node_type: &'static str, tags: &[&str], path: Option<&str>) -> MenuContext {
+        MenuContext {
+            node_id: 7,
+            node_type,
+            display_name: "notes.md".to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            path: path.map(PathBuf::from),
+            url: None,
+            selection_count: 1,
+        }
+    }
+
+    fn workflow(id: &str) -> CustomMenuEntry {
+        CustomMenuEntry::new(id, id, MenuCommand::Workflow { workflow_id: id.to_string() })
+    }
+
+    /// Records workflows instead of starting them
+    #[derive(Default)]
+    struct Launches(Mutex<Vec<String>>);
+
+    impl WorkflowLauncher for Launches {
+        fn launch(&self, workflow_id: &str, _input: serde_json::Value) -> Result<(), String> {
+            self.0.lock().unwrap().push(workflow_id.to_string());
+            Ok(())
+        }
+    }
+
+    /// Answers every check the same way, counting checks
+    struct Answer(MenuPermission, Mutex<usize>);
+
+    impl MenuPermissions for Answer {
+        fn check(&self, _entry: &CustomMenuEntry) -> MenuPermission {
+            *self.1.lock().unwrap() += 1;
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_entries_match_targets_and_conditions() {
+        let mut registry = MenuExtensionRegistry::new();
+        registry.register(workflow("code").for_node_types(&["file"]).for_tags(&["code"]).enabled_when(MenuCondition::HasPath));
+        registry.register(workflow("any").enabled_when(MenuCondition::Extension(vec![".MD".to_string()])));
+
+        let ids = |context: &MenuContext| -> Vec<(String, bool)> {
+            registry.entries_for(context).map(|(entry, enabled)| (entry.id.clone(), enabled)).collect()
+        };
+        let file = context("file", &["code"], Some("/tmp/notes.md"));
+        assert_eq!(ids(&file), vec![("code".to_string(), true), ("any".to_string(), true)]);
+        // Shown but disabled without a path; hidden without the tag or type
+        assert_eq!(ids(&context("file", &["code"], None)), vec![("code".to_string(), false), ("any".to_string(), false)]);
+        assert_eq!(ids(&context("file", &["text"], Some("/tmp/notes.md"))), vec![("any".to_string(), true)]);
+        assert_eq!(ids(&context("task", &["code"], Some("/tmp/notes.md"))), vec![("any".to_string(), true)]);
+
+        let mut multiple = file.clone();
+        multiple.selection_count = 3;
+        assert!(MenuCondition::MultipleSelection.holds(&multiple));
+        assert!(!MenuCondition::SingleSelection.holds(&multiple));
+    }
+
+    #[test]
+    fn test_entries_are_placed_in_the_menu() {
+        let mut registry = MenuExtensionRegistry::new();
+        registry.register(workflow("first").placed(MenuPlacement::Top, 0));
+        registry.register(workflow("later").placed(MenuPlacement::After("open".to_string()), 1));
+        registry.register(workflow("sooner").placed(MenuPlacement::After("open".to_string()), 0));
+        registry.register(workflow("nested").placed(MenuPlacement::Submenu("Tools".to_string()), 0));
+        registry.register(workflow("lost").placed(MenuPlacement::Before("missing".to_string()), 0));
+
+        let mut items = vec![MenuItem::new("open", "Open"), MenuItem::new("delete", "Delete")];
+        registry.extend_menu(&mut items, &context("file", &[], None));
+        let ids: Vec<&str> = items.iter().map(|item| if item.separator { "-" } else { item.id.as_str() }).collect();
+        assert_eq!(ids, vec!["ext:first", "-", "open", "ext:sooner", "ext:later", "delete", "-", "ext:lost", "ext:submenu:Tools"]);
+        assert_eq!(items.last().unwrap().submenu.as_ref().unwrap()[0].id, "ext:nested");
+    }
+
+    #[test]
+    fn test_entries_run_as_the_permission_system_allows() {
+        let launches = Arc::new(Launches::default());
+        let file = context("file", &[], None);
+        let build = |permission: Option<MenuPermission>| {
+            let mut registry = MenuExtensionRegistry::new();
+            registry.set_workflow_launcher(launches.clone());
+            registry.register(workflow("builtin").from_source(MenuEntrySource::Builtin));
+            registry.register(workflow("user"));
+            let answer = permission.map(|permission| Arc::new(Answer(permission, Mutex::new(0))));
+            if let Some(answer) = &answer {
+                registry.set_permissions(answer.clone());
+            }
+            (registry, answer)
+        };
+
+        // Builtin entries never ask; others are denied without a permission system
+        let (registry, _) = build(None);
+        assert_eq!(registry.activate("ext:builtin", &file), MenuExecution::Started);
+        assert_eq!(registry.activate("ext:user", &file), MenuExecution::Denied);
+        assert_eq!(registry.activate("user", &file), MenuExecution::NotFound);
+
+        let (registry, answer) = build(Some(MenuPermission::Allowed));
+        assert_eq!(registry.activate("ext:builtin", &file), MenuExecution::Started);
+        assert_eq!(*answer.as_ref().unwrap().1.lock().unwrap(), 0);
+        assert_eq!(registry.activate("ext:user", &file), MenuExecution::Started);
+        assert_eq!(*answer.unwrap().1.lock().unwrap(), 1);
+
+        // A pending answer holds the activation until it is given
+        let (mut registry, _) = build(Some(MenuPermission::Pending(4)));
+        assert!(matches!(registry.activate("ext:user", &file), MenuExecution::NeedsApproval { request_id: 4, .. }));
+        assert_eq!(registry.permission_resolved(5, true), None);
+        assert_eq!(registry.permission_resolved(4, true), Some(MenuExecution::Started));
+        assert_eq!(registry.permission_resolved(4, true), None);
+        registry.activate("ext:user", &file);
+        assert_eq!(registry.permission_resolved(4, false), Some(MenuExecution::Denied));
+        // Entries replaced while the user was asked do not run
+        registry.activate("ext:user", &file);
+        registry.register(CustomMenuEntry::new("user", "user", MenuCommand::Workflow { workflow_id: "other".to_string() }));
+        assert_eq!(registry.permission_resolved(4, true), Some(MenuExecution::NotFound));
+
+        assert_eq!(*launches.0.lock().unwrap(), vec!["builtin", "builtin", "user", "user"]);
+    }
+}
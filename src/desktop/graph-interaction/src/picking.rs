@@ -0,0 +1,71 @@
+//! Pointer picking through the engine's GPU ID pass
+//!
+//! GPU picks resolve a frame or so after they are requested, so the pointer
+//! keeps one pick in flight and remembers the latest answer; a click on the
+//! spot of that answer uses it instead of ray casting every node. Answers go
+//! stale quickly since nodes keep moving under physics. Without GPU picking,
+//! callers ray cast as before.
+
+use horizonos_graph_engine::{GraphEngine, PickRequest, SceneId};
+use std::time::{Duration, Instant};
+
+/// How long a pick answer is trusted
+const MAX_ANSWER_AGE: Duration = Duration::from_millis(100);
+
+/// GPU pick state of the pointer
+#[derive(Default)]
+pub struct PointerPicker {
+    /// Pick in flight
+    pending: Option<PickRequest>,
+    /// Position moved to while a pick was in flight, picked next
+    queued: Option<(f32, f32)>,
+    /// Latest answer, the position it is for and when it arrived
+    resolved: Option<((f32, f32), Option<SceneId>, Instant)>,
+}
+
+impl PointerPicker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pick at a position; returns false when GPU picking is unavailable
+    pub fn request(&mut self, screen_pos: (f32, f32), engine: &GraphEngine) -> bool {
+        if self.pending.is_some() {
+            self.queued = Some(screen_pos);
+            return true;
+        }
+        self.pending = engine.request_pick(screen_pos);
+        self.pending.is_some()
+    }
+
+    /// Check the pick in flight, returning its answer once it arrives
+    pub fn poll(&mut self, engine: &GraphEngine) -> Option<((f32, f32), Option<SceneId>)> {
+        let result = self.pending.as_mut()?.poll()?;
+        let request = self.pending.take()?;
+        if let Some(next) = self.queued.take() {
+            self.pending = engine.request_pick(next);
+        }
+        match result {
+            Ok(node_id) => {
+                self.resolved = Some((request.screen_pos(), node_id, Instant::now()));
+                Some((request.screen_pos(), node_id))
+            }
+            Err(e) => {
+                log::debug!("GPU pick failed: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Latest answer if it is recent and for the pixel at `screen_pos`
+    pub fn resolved_at(&self, screen_pos: (f32, f32)) -> Option<Option<SceneId>> {
+        let (position, node_id, at) = self.resolved?;
+        let same_pixel = position.0.floor() == screen_pos.0.floor() && position.1.floor() == screen_pos.1.floor();
+        (same_pixel && at.elapsed() <= MAX_ANSWER_AGE).then_some(node_id)
+    }
+
+    /// Forget picks, e.g. after the scene or camera changed under the pointer
+    pub fn invalidate(&mut self) {
+        self.resolved = None;
+    }
+}
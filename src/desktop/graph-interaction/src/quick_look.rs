@@ -0,0 +1,109 @@
+//! Quick-look overlay (Space on a file node)
+//!
+//! Space on a focused file node opens a large preview of the file; the
+//! arrow keys step through the other files of the selection and Space or
+//! Escape closes it again. This module only tracks which file is shown: the
+//! host loads and draws the preview when told through [`QuickLookEvent`].
+
+use horizonos_graph_engine::{FileType, NodeType, Scene, SceneId};
+use std::path::PathBuf;
+
+/// A file shown in the overlay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuickLookItem {
+    pub node_id: SceneId,
+    pub path: PathBuf,
+}
+
+/// Change of what the overlay shows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuickLookEvent {
+    Opened(QuickLookItem),
+    /// Moved to another file of the selection
+    Changed(QuickLookItem),
+    Closed,
+}
+
+/// Callback for quick-look changes
+pub type QuickLookCallback = Box<dyn Fn(&QuickLookEvent) + Send + Sync>;
+
+/// Files the overlay steps through and the one shown
+#[derive(Debug, Default)]
+pub struct QuickLook {
+    items: Vec<QuickLookItem>,
+    current: usize,
+    open: bool,
+}
+
+impl QuickLook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the overlay is showing
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Open on `focus` if it is a file, stepping through the files of `selection` when it contains `focus`
+    pub fn open(&mut self, focus: SceneId, selection: &[SceneId], scene: &Scene) -> Option<QuickLookEvent> {
+        let focus_item = file_item(focus, scene)?;
+        self.items = if selection.contains(&focus) {
+            selection.iter().filter_map(|node_id| file_item(*node_id, scene)).collect()
+        } else {
+            vec![focus_item.clone()]
+        };
+        self.current = self.items.iter().position(|item| *item == focus_item).unwrap_or(0);
+        self.open = true;
+        Some(QuickLookEvent::Opened(focus_item))
+    }
+
+    /// File shown
+    pub fn current(&self) -> Option<&QuickLookItem> {
+        self.items.get(self.current).filter(|_| self.open)
+    }
+
+    /// Files the overlay steps through
+    pub fn items(&self) -> &[QuickLookItem] {
+        &self.items
+    }
+
+    /// Show the next file, wrapping around
+    pub fn next_file(&mut self) -> Option<QuickLookEvent> {
+        self.step(1)
+    }
+
+    /// Show the previous file, wrapping around
+    pub fn previous_file(&mut self) -> Option<QuickLookEvent> {
+        self.step(self.items.len().saturating_sub(1))
+    }
+
+    fn step(&mut self, by: usize) -> Option<QuickLookEvent> {
+        if !self.open || self.items.len() < 2 {
+            return None;
+        }
+        self.current = (self.current + by) % self.items.len();
+        Some(QuickLookEvent::Changed(self.items[self.current].clone()))
+    }
+
+    /// Close the overlay
+    pub fn close(&mut self) -> Option<QuickLookEvent> {
+        if !self.open {
+            return None;
+        }
+        self.open = false;
+        self.items.clear();
+        self.current = 0;
+        Some(QuickLookEvent::Closed)
+    }
+}
+
+/// Quick-look item of a file node; directories have nothing to preview
+fn file_item(node_id: SceneId, scene: &Scene) -> Option<QuickLookItem> {
+    match &scene.get_node(node_id)?.node_type {
+        NodeType::File { path, file_type } if *file_type != FileType::Directory => {
+            Some(QuickLookItem { node_id, path: PathBuf::from(path) })
+        }
+        _ => None,
+    }
+}
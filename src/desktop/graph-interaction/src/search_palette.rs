@@ -0,0 +1,164 @@
+//! Node search and quick-jump palette (Super+Space)
+//!
+//! Typing filters all nodes by fuzzy match on their name, tags, description
+//! and metadata; committing jumps to the selected node. Nodes known to the
+//! node manager are matched through its shared [`NodeSearchIndex`], all other
+//! scene nodes through the text of their scene node.
+
+use horizonos_graph_engine::{Scene, SceneId};
+use horizonos_graph_nodes::search::{rank_hits, search_documents};
+use horizonos_graph_nodes::{NodeSearchIndex, SearchDocument, SearchHit};
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+/// Searchable palette of all nodes
+pub struct SearchPalette {
+    /// Index of node implementations, when a node manager is attached
+    index: Option<Arc<RwLock<NodeSearchIndex>>>,
+    /// Nodes in the scene when the palette was opened
+    in_scene: HashSet<SceneId>,
+    /// Scene nodes missing from the index
+    scene_documents: Vec<SearchDocument>,
+    /// Current query
+    query: String,
+    /// Matches for the query, best first
+    results: Vec<SearchHit>,
+    /// Selected position in `results`
+    selected: usize,
+    /// Most results shown
+    max_results: usize,
+    open: bool,
+}
+
+impl SearchPalette {
+    /// Create a palette showing up to `max_results` matches
+    pub fn new(max_results: usize) -> Self {
+        Self {
+            index: None,
+            in_scene: HashSet::new(),
+            scene_documents: Vec::new(),
+            query: String::new(),
+            results: Vec::new(),
+            selected: 0,
+            max_results: max_results.max(1),
+            open: false,
+        }
+    }
+
+    /// Match node implementations through the node manager's index
+    pub fn set_index(&mut self, index: Arc<RwLock<NodeSearchIndex>>) {
+        self.index = Some(index);
+    }
+
+    /// Whether the palette is showing
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Open the palette with an empty query
+    pub fn open(&mut self, scene: &Scene) {
+        self.in_scene = scene.nodes().map(|(id, _)| *id).collect();
+        let index = self.index.as_ref().map(|index| index.read().unwrap());
+        self.scene_documents = scene
+            .nodes()
+            .filter(|(id, _)| !index.as_ref().is_some_and(|index| index.contains(**id)))
+            .map(|(_, node)| SearchDocument::from_scene_node(node))
+            .collect();
+        drop(index);
+        self.query.clear();
+        self.open = true;
+        self.refresh();
+    }
+
+    /// Current query
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Replace the query
+    pub fn set_query(&mut self, query: impl Into<String>) {
+        self.query = query.into();
+        self.refresh();
+    }
+
+    /// Add a character to the query
+    pub fn push_char(&mut self, c: char) {
+        self.query.push(c);
+        self.refresh();
+    }
+
+    /// Remove the last character of the query
+    pub fn pop_char(&mut self) {
+        self.query.pop();
+        self.refresh();
+    }
+
+    /// Matches for the query, best first
+    pub fn results(&self) -> &[SearchHit] {
+        &self.results
+    }
+
+    /// Selected match
+    pub fn current(&self) -> Option<&SearchHit> {
+        self.results.get(self.selected)
+    }
+
+    /// Index of the selected match
+    pub fn selected_index(&self) -> usize {
+        self.selected
+    }
+
+    /// Select the next match, wrapping around
+    pub fn next(&mut self) {
+        if !self.results.is_empty() {
+            self.selected = (self.selected + 1) % self.results.len();
+        }
+    }
+
+    /// Select the previous match, wrapping around
+    pub fn previous(&mut self) {
+        if !self.results.is_empty() {
+            self.selected = (self.selected + self.results.len() - 1) % self.results.len();
+        }
+    }
+
+    /// Close the palette, returning the selected node
+    pub fn commit(&mut self) -> Option<SceneId> {
+        let chosen = self.current().map(|hit| hit.node_id);
+        self.close();
+        chosen
+    }
+
+    /// Close the palette without jumping
+    pub fn cancel(&mut self) {
+        self.close();
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+        self.in_scene.clear();
+        self.scene_documents.clear();
+        self.query.clear();
+        self.results.clear();
+        self.selected = 0;
+    }
+
+    /// Recompute matches for the query
+    fn refresh(&mut self) {
+        let mut results = search_documents(&self.scene_documents, &self.query, self.max_results);
+        if let Some(index) = &self.index {
+            // The index may hold nodes that are not in the scene, e.g. on another workspace
+            let indexed = index.read().unwrap().search(&self.query, usize::MAX);
+            results.extend(indexed.into_iter().filter(|hit| self.in_scene.contains(&hit.node_id)));
+            rank_hits(&mut results, self.max_results);
+        }
+        self.results = results;
+        self.selected = 0;
+    }
+}
+
+impl Default for SearchPalette {
+    fn default() -> Self {
+        Self::new(20)
+    }
+}
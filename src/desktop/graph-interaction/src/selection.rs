@@ -1,7 +1,9 @@
 //! Node selection and highlighting system
 
-use horizonos_graph_engine::{SceneId, Scene, Position, Ray};
-use std::collections::HashSet;
+use crate::selection_filters::{SelectionCommand, SelectionContext, SelectionFilter, SelectionMode};
+use horizonos_graph_clustering::ClusterManager;
+use horizonos_graph_engine::{SceneId, Scene, Position, Ray, SelectionVisualState};
+use std::collections::{HashMap, HashSet};
 
 /// Manages node selection state and operations
 pub struct SelectionManager {
@@ -13,6 +15,8 @@ pub struct SelectionManager {
     hovered_node: Option<SceneId>,
     /// Box selection state
     box_selection: Option<BoxSelection>,
+    /// Saved named selections
+    named_selections: HashMap<String, HashSet<SceneId>>,
 }
 
 /// Box selection state
@@ -30,6 +34,7 @@ impl SelectionManager {
             primary_selection: None,
             hovered_node: None,
             box_selection: None,
+            named_selections: HashMap::new(),
         }
     }
     
@@ -143,59 +148,129 @@ impl SelectionManager {
         })
     }
     
+    /// What the renderer's selection overlay draws: rubber band, hover ring and outlines
+    pub fn visual_state(&self) -> SelectionVisualState {
+        let mut selected = self.get_selection();
+        selected.sort_unstable();
+        SelectionVisualState {
+            rubber_band: self.get_box_selection_bounds(),
+            hovered: self.hovered_node,
+            selected,
+            primary: self.primary_selection,
+        }
+    }
+    
     /// Select all nodes
     pub fn select_all(&mut self, engine: &horizonos_graph_engine::GraphEngine) {
-        self.selected_nodes.clear();
-        let scene = engine.scene();
-        // TODO: Add all node IDs from scene
-        // For now, this is a placeholder
+        self.apply_filter(&SelectionFilter::All, SelectionMode::Replace, engine.scene(), None);
     }
     
-    /// Perform ray-based node picking
-    pub fn ray_pick_node(&self, ray: &Ray, scene: &Scene) -> Option<SceneId> {
-        let mut closest_node = None;
-        let mut closest_distance = f32::MAX;
+    /// Evaluate a filter and combine the result with the selection, returning the new selection
+    pub fn apply_filter(
+        &mut self,
+        filter: &SelectionFilter,
+        mode: SelectionMode,
+        scene: &Scene,
+        clusters: Option<&ClusterManager>,
+    ) -> Vec<SceneId> {
+        let result = filter.evaluate(&SelectionContext {
+            scene,
+            clusters,
+            selection: &self.selected_nodes,
+            primary: self.primary_selection,
+            named: &self.named_selections,
+            now: chrono::Utc::now(),
+        });
         
-        // Simple ray-sphere intersection for each node
-        for (id, node) in scene.nodes() {
-            let sphere_center = node.position;
-            let sphere_radius = node.radius;
-            
-            // Ray-sphere intersection
-            let oc = ray.origin - sphere_center;
-            let a = ray.direction.dot(&ray.direction);
-            let b = 2.0 * oc.dot(&ray.direction);
-            let c = oc.dot(&oc) - sphere_radius * sphere_radius;
-            let discriminant = b * b - 4.0 * a * c;
-            
-            if discriminant >= 0.0 {
-                let sqrt_discriminant = discriminant.sqrt();
-                let t1 = (-b - sqrt_discriminant) / (2.0 * a);
-                let t2 = (-b + sqrt_discriminant) / (2.0 * a);
-                
-                let t = if t1 > 0.0 { t1 } else if t2 > 0.0 { t2 } else { continue };
-                
-                if t < closest_distance {
-                    closest_distance = t;
-                    closest_node = Some(*id);
-                }
+        match mode {
+            SelectionMode::Replace => self.selected_nodes = result,
+            SelectionMode::Add => self.selected_nodes.extend(result),
+            SelectionMode::Subtract => self.selected_nodes.retain(|id| !result.contains(id)),
+            SelectionMode::Intersect => self.selected_nodes.retain(|id| result.contains(id)),
+        }
+        if !self.primary_selection.is_some_and(|id| self.selected_nodes.contains(&id)) {
+            self.primary_selection = self.selected_nodes.iter().next().copied();
+        }
+        self.get_selection()
+    }
+    
+    /// Run a smart selection command, saving the result if the command names one
+    pub fn run_command(&mut self, command: &SelectionCommand, scene: &Scene, clusters: Option<&ClusterManager>) -> Vec<SceneId> {
+        let selection = self.apply_filter(&command.filter, command.mode, scene, clusters);
+        if let Some(name) = &command.save_as {
+            self.save_named_selection(name.clone());
+        }
+        selection
+    }
+    
+    /// Save the current selection under a name, replacing any earlier one
+    pub fn save_named_selection(&mut self, name: impl Into<String>) {
+        self.named_selections.insert(name.into(), self.selected_nodes.clone());
+    }
+    
+    /// Replace the selection with a named selection, returning whether it exists
+    pub fn restore_named_selection(&mut self, name: &str) -> bool {
+        match self.named_selections.get(name) {
+            Some(nodes) => {
+                let nodes = nodes.iter().copied().collect();
+                self.set_selection(nodes);
+                true
             }
+            None => false,
         }
-        
-        closest_node
+    }
+    
+    /// Delete a named selection
+    pub fn remove_named_selection(&mut self, name: &str) -> bool {
+        self.named_selections.remove(name).is_some()
+    }
+    
+    /// Names of saved selections, sorted
+    pub fn named_selections(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.named_selections.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+    
+    /// Forget a removed node in the selection and every named selection
+    pub fn forget_node(&mut self, node: SceneId) {
+        self.remove_from_selection(node);
+        for nodes in self.named_selections.values_mut() {
+            nodes.remove(&node);
+        }
+    }
+    
+    /// Perform ray-based node picking
+    pub fn ray_pick_node(&self, ray: &Ray, scene: &Scene) -> Option<SceneId> {
+        scene.ray_cast(ray).map(|(id, _)| id)
     }
     
     /// Get nodes within a radius of a position
     pub fn get_nodes_in_radius(&self, center: Position, radius: f32, scene: &Scene) -> Vec<SceneId> {
-        let mut nodes = Vec::new();
+        scene.find_nodes_in_radius(center, radius)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_visual_state_follows_box_selection_and_hover() {
+        let mut selection = SelectionManager::new();
+        selection.set_selection(vec![3, 1]);
+        selection.set_hover(Some(2));
+        selection.start_box_selection((10.0, 20.0));
+        selection.update_box_selection((110.0, 70.0));
         
-        for (id, node) in scene.nodes() {
-            let distance = (node.position - center).magnitude();
-            if distance <= radius {
-                nodes.push(*id);
-            }
-        }
+        let state = selection.visual_state();
+        assert_eq!(state.rubber_band, Some(((10.0, 20.0), (110.0, 70.0))));
+        assert_eq!(state.hovered, Some(2));
+        assert_eq!(state.selected, vec![1, 3]);
+        assert!(state.primary.is_some_and(|id| selection.is_selected(id)));
         
-        nodes
+        selection.clear_selection();
+        selection.set_hover(None);
+        selection.box_selection = None;
+        assert!(selection.visual_state().is_empty());
     }
-}
\ No newline at end of file
+}
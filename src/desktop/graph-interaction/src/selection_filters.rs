@@ -0,0 +1,670 @@
+//! Composable selection filters and smart selection commands
+//!
+//! Filters are small expressions over the scene, written like
+//! `type:file & cluster:current`, `grow(selected)` or `!selected`. They are
+//! evaluated to a set of node IDs and combined with the current selection.
+//! Selection commands wrap a filter with a label and an optional shortcut so
+//! they can be listed in the command palette and bound to keys.
+//!
+//! Grammar, loosest binding first:
+//!
+//! ```text
+//! expr  := and ('|' and)*
+//! and   := unary ('&' unary)*
+//! unary := '!' unary | '(' expr ')' | 'grow(' expr [',' hops] ')' | 'query(' QUERY ')' | atom
+//! atom  := all | selected | visible | type:NAME | type:current | tag:NAME
+//!        | cluster:UUID | cluster:current | modified:today | modified:Nh | modified:Nd
+//!        | named:NAME
+//! ```
+//!
+//! `QUERY` is a [`GraphQuery`] such as `file[modified<7d] -- task`.
+
+use crate::keymap::KeyChord;
+use chrono::{DateTime, Local, Utc};
+use horizonos_graph_clustering::{ClusterId, ClusterManager};
+use horizonos_graph_engine::{Scene, SceneId};
+use horizonos_graph_nodes::hooks::node_type_name;
+use horizonos_graph_nodes::query::GraphQuery;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+/// A filter expression selecting a set of nodes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SelectionFilter {
+    /// Every node in the scene
+    All,
+    /// The current selection
+    Selected,
+    /// Nodes currently visible
+    Visible,
+    /// Nodes of a type, by the names used in hook filters (`file`, `application`, ...)
+    NodeType(String),
+    /// Nodes of the same type as the primary selection
+    SameTypeAsPrimary,
+    /// Nodes carrying a tag
+    Tag(String),
+    /// Nodes in a cluster
+    InCluster(ClusterId),
+    /// Nodes sharing a cluster with the primary selection
+    InPrimaryCluster,
+    /// Nodes modified since local midnight
+    ModifiedToday,
+    /// Nodes modified in the last number of seconds
+    ModifiedWithin(u64),
+    /// A saved named selection
+    Named(String),
+    /// Nodes matching a graph query
+    Query(GraphQuery),
+    /// Nodes matching the inner filter plus those up to `hops` edges away
+    Grow { base: Box<SelectionFilter>, hops: u32 },
+    /// Nodes matching every filter
+    And(Vec<SelectionFilter>),
+    /// Nodes matching any filter
+    Or(Vec<SelectionFilter>),
+    /// Nodes not matching the filter
+    Not(Box<SelectionFilter>),
+}
+
+/// What a filter is evaluated against
+pub struct SelectionContext<'a> {
+    pub scene: &'a Scene,
+    pub clusters: Option<&'a ClusterManager>,
+    pub selection: &'a HashSet<SceneId>,
+    pub primary: Option<SceneId>,
+    pub named: &'a HashMap<String, HashSet<SceneId>>,
+    pub now: DateTime<Utc>,
+}
+
+impl SelectionFilter {
+    /// Nodes matching this filter
+    pub fn evaluate(&self, context: &SelectionContext) -> HashSet<SceneId> {
+        let scene = context.scene;
+        let all = || scene.nodes().map(|(id, _)| *id);
+        let matching = |predicate: &dyn Fn(&horizonos_graph_engine::SceneNode) -> bool| {
+            scene.nodes().filter(|(_, node)| predicate(node)).map(|(id, _)| *id).collect()
+        };
+
+        match self {
+            SelectionFilter::All => all().collect(),
+            SelectionFilter::Selected => context.selection.iter()
+                .filter(|id| scene.get_node(**id).is_some())
+                .copied()
+                .collect(),
+            SelectionFilter::Visible => matching(&|node| node.visible),
+            SelectionFilter::NodeType(name) => matching(&|node| node_type_name(&node.node_type) == name),
+            SelectionFilter::SameTypeAsPrimary => {
+                let Some(primary) = context.primary.and_then(|id| scene.get_node(id)) else {
+                    return HashSet::new();
+                };
+                let name = node_type_name(&primary.node_type);
+                matching(&|node| node_type_name(&node.node_type) == name)
+            }
+            SelectionFilter::Tag(tag) => matching(&|node| node.metadata.tags.contains(tag)),
+            SelectionFilter::InCluster(cluster_id) => context.clusters
+                .and_then(|clusters| clusters.get_cluster(*cluster_id))
+                .map(|cluster| cluster.nodes.into_iter().filter(|id| scene.get_node(*id).is_some()).collect())
+                .unwrap_or_default(),
+            SelectionFilter::InPrimaryCluster => {
+                let (Some(clusters), Some(primary)) = (context.clusters, context.primary) else {
+                    return HashSet::new();
+                };
+                clusters.get_node_clusters(primary)
+                    .into_iter()
+                    .filter_map(|cluster_id| clusters.get_cluster(cluster_id))
+                    .flat_map(|cluster| cluster.nodes)
+                    .filter(|id| scene.get_node(*id).is_some())
+                    .collect()
+            }
+            SelectionFilter::ModifiedToday => {
+                let midnight = context.now.with_timezone(&Local)
+                    .date_naive()
+                    .and_hms_opt(0, 0, 0)
+                    .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+                    .map(|midnight| midnight.with_timezone(&Utc))
+                    .unwrap_or(context.now);
+                matching(&|node| node.metadata.updated_at >= midnight)
+            }
+            SelectionFilter::ModifiedWithin(seconds) => {
+                let since = context.now - chrono::Duration::seconds(*seconds as i64);
+                matching(&|node| node.metadata.updated_at >= since)
+            }
+            SelectionFilter::Named(name) => context.named.get(name).cloned().unwrap_or_default(),
+            SelectionFilter::Query(query) => query.evaluate(scene, context.now),
+            SelectionFilter::Grow { base, hops } => grow(scene, base.evaluate(context), *hops),
+            SelectionFilter::And(filters) => {
+                let mut filters = filters.iter();
+                let Some(first) = filters.next() else {
+                    return all().collect();
+                };
+                let mut result = first.evaluate(context);
+                for filter in filters {
+                    if result.is_empty() {
+                        break;
+                    }
+                    let other = filter.evaluate(context);
+                    result.retain(|id| other.contains(id));
+                }
+                result
+            }
+            SelectionFilter::Or(filters) => filters.iter().flat_map(|filter| filter.evaluate(context)).collect(),
+            SelectionFilter::Not(filter) => {
+                let excluded = filter.evaluate(context);
+                all().filter(|id| !excluded.contains(id)).collect()
+            }
+        }
+    }
+}
+
+/// Expand a set of nodes by following edges in either direction
+fn grow(scene: &Scene, seeds: HashSet<SceneId>, hops: u32) -> HashSet<SceneId> {
+    let mut adjacency: HashMap<SceneId, Vec<SceneId>> = HashMap::new();
+    for edge in scene.edges() {
+        adjacency.entry(edge.source).or_default().push(edge.target);
+        adjacency.entry(edge.target).or_default().push(edge.source);
+    }
+
+    let mut frontier: VecDeque<(SceneId, u32)> = seeds.iter().map(|id| (*id, 0)).collect();
+    let mut result = seeds;
+    while let Some((id, depth)) = frontier.pop_front() {
+        if depth == hops {
+            continue;
+        }
+        for neighbor in adjacency.get(&id).into_iter().flatten() {
+            if scene.get_node(*neighbor).is_some() && result.insert(*neighbor) {
+                frontier.push_back((*neighbor, depth + 1));
+            }
+        }
+    }
+    result
+}
+
+impl fmt::Display for SelectionFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter<'_>, filters: &[SelectionFilter], op: &str| {
+            write!(f, "(")?;
+            for (i, filter) in filters.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {} ", op)?;
+                }
+                write!(f, "{}", filter)?;
+            }
+            write!(f, ")")
+        };
+        match self {
+            SelectionFilter::All => write!(f, "all"),
+            SelectionFilter::Selected => write!(f, "selected"),
+            SelectionFilter::Visible => write!(f, "visible"),
+            SelectionFilter::NodeType(name) => write!(f, "type:{}", name),
+            SelectionFilter::SameTypeAsPrimary => write!(f, "type:current"),
+            SelectionFilter::Tag(tag) => write!(f, "tag:{}", tag),
+            SelectionFilter::InCluster(cluster_id) => write!(f, "cluster:{}", cluster_id),
+            SelectionFilter::InPrimaryCluster => write!(f, "cluster:current"),
+            SelectionFilter::ModifiedToday => write!(f, "modified:today"),
+            SelectionFilter::ModifiedWithin(seconds) if seconds % 86400 == 0 => write!(f, "modified:{}d", seconds / 86400),
+            SelectionFilter::ModifiedWithin(seconds) if seconds % 3600 == 0 => write!(f, "modified:{}h", seconds / 3600),
+            SelectionFilter::ModifiedWithin(seconds) => write!(f, "modified:{}s", seconds),
+            SelectionFilter::Named(name) => write!(f, "named:{}", name),
+            SelectionFilter::Query(query) => write!(f, "query({})", query),
+            SelectionFilter::Grow { base, hops } => write!(f, "grow({}, {})", base, hops),
+            SelectionFilter::And(filters) => join(f, filters, "&"),
+            SelectionFilter::Or(filters) => join(f, filters, "|"),
+            SelectionFilter::Not(filter) => write!(f, "!{}", filter),
+        }
+    }
+}
+
+impl FromStr for SelectionFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { input: s, pos: 0 };
+        let filter = parser.expr()?;
+        parser.skip_whitespace();
+        if parser.pos < s.len() {
+            return Err(format!("Unexpected '{}' at {}", &s[parser.pos..], parser.pos));
+        }
+        Ok(filter)
+    }
+}
+
+/// Recursive descent parser for filter expressions
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(format!("Expected '{}' at {}", token, self.pos))
+        }
+    }
+
+    fn expr(&mut self) -> Result<SelectionFilter, String> {
+        let mut filters = vec![self.and()?];
+        while self.eat("|") {
+            filters.push(self.and()?);
+        }
+        Ok(if filters.len() == 1 { filters.remove(0) } else { SelectionFilter::Or(filters) })
+    }
+
+    fn and(&mut self) -> Result<SelectionFilter, String> {
+        let mut filters = vec![self.unary()?];
+        while self.eat("&") {
+            filters.push(self.unary()?);
+        }
+        Ok(if filters.len() == 1 { filters.remove(0) } else { SelectionFilter::And(filters) })
+    }
+
+    fn unary(&mut self) -> Result<SelectionFilter, String> {
+        if self.eat("!") {
+            return Ok(SelectionFilter::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("(") {
+            let filter = self.expr()?;
+            self.expect(")")?;
+            return Ok(filter);
+        }
+        if self.eat("grow(") {
+            let base = self.expr()?;
+            let hops = if self.eat(",") {
+                let word = self.word();
+                word.parse().map_err(|_| format!("Invalid hop count '{}'", word))?
+            } else {
+                1
+            };
+            self.expect(")")?;
+            return Ok(SelectionFilter::Grow { base: Box::new(base), hops });
+        }
+        if self.eat("query(") {
+            // The query runs to the first closing parenthesis outside quotes
+            let rest = self.rest();
+            let mut quoted = false;
+            let mut escaped = false;
+            let end = rest
+                .find(|c: char| {
+                    let close = c == ')' && !quoted;
+                    match c {
+                        '"' if !escaped => quoted = !quoted,
+                        _ => {}
+                    }
+                    escaped = c == '\\' && !escaped;
+                    close
+                })
+                .ok_or_else(|| format!("Expected ')' after query at {}", self.pos))?;
+            let query = rest[..end].parse().map_err(|e| format!("Invalid query at {}: {}", self.pos, e))?;
+            self.pos += end;
+            self.expect(")")?;
+            return Ok(SelectionFilter::Query(query));
+        }
+        self.atom()
+    }
+
+    /// Read a run of identifier characters
+    fn word(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | ':' | '.')))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn atom(&mut self) -> Result<SelectionFilter, String> {
+        let start = self.pos;
+        let word = self.word();
+        let filter = match word.split_once(':') {
+            Some((_, "")) => return Err(format!("Missing value for '{}' at {}", word, start)),
+            None => match word {
+                "all" => SelectionFilter::All,
+                "selected" => SelectionFilter::Selected,
+                "visible" => SelectionFilter::Visible,
+                _ => return Err(format!("Unknown filter '{}' at {}", word, start)),
+            },
+            Some(("type", "current")) => SelectionFilter::SameTypeAsPrimary,
+            Some(("type", name)) => SelectionFilter::NodeType(name.to_lowercase()),
+            Some(("tag", tag)) => SelectionFilter::Tag(tag.to_string()),
+            Some(("cluster", "current")) => SelectionFilter::InPrimaryCluster,
+            Some(("cluster", id)) => SelectionFilter::InCluster(
+                ClusterId::parse_str(id).map_err(|_| format!("Invalid cluster id '{}'", id))?,
+            ),
+            Some(("modified", "today")) => SelectionFilter::ModifiedToday,
+            Some(("modified", age)) => SelectionFilter::ModifiedWithin(parse_age(age)?),
+            Some(("named", name)) => SelectionFilter::Named(name.to_string()),
+            Some(_) => return Err(format!("Unknown filter '{}' at {}", word, start)),
+        };
+        Ok(filter)
+    }
+}
+
+/// Parse `30s`, `12h` or `7d` into seconds
+fn parse_age(age: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid age '{}', expected e.g. 12h or 7d", age);
+    let unit = age.chars().last().ok_or_else(invalid)?;
+    let multiplier = match unit {
+        's' => 1,
+        'h' => 3600,
+        'd' => 86400,
+        _ => return Err(invalid()),
+    };
+    let amount: u64 = age[..age.len() - 1].parse().map_err(|_| invalid())?;
+    Ok(amount * multiplier)
+}
+
+/// How a filter result is combined with the current selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SelectionMode {
+    /// Select exactly the result
+    Replace,
+    /// Add the result to the selection
+    Add,
+    /// Remove the result from the selection
+    Subtract,
+    /// Keep only selected nodes that are in the result
+    Intersect,
+}
+
+/// A smart selection available from the command palette
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelectionCommand {
+    /// Stable identifier, e.g. `selection.invert`
+    pub id: String,
+    /// Palette label
+    pub label: String,
+    /// Filter to evaluate
+    pub filter: SelectionFilter,
+    /// How the result is combined with the selection
+    pub mode: SelectionMode,
+    /// Bound shortcut, e.g. `Ctrl+I`
+    pub shortcut: Option<String>,
+    /// Save the result under this name as well
+    pub save_as: Option<String>,
+}
+
+impl SelectionCommand {
+    /// Create a command that replaces the selection
+    pub fn new(id: impl Into<String>, label: impl Into<String>, filter: SelectionFilter) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            filter,
+            mode: SelectionMode::Replace,
+            shortcut: None,
+            save_as: None,
+        }
+    }
+
+    /// Set how the result is combined with the selection
+    pub fn with_mode(mut self, mode: SelectionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set shortcut
+    pub fn with_shortcut(mut self, shortcut: impl Into<String>) -> Self {
+        self.shortcut = Some(shortcut.into());
+        self
+    }
+
+    /// Save the result as a named selection
+    pub fn saving_as(mut self, name: impl Into<String>) -> Self {
+        self.save_as = Some(name.into());
+        self
+    }
+}
+
+/// Registry of selection commands
+pub struct SelectionCommands {
+    commands: Vec<SelectionCommand>,
+}
+
+impl SelectionCommands {
+    /// Create a registry with the built-in smart selections
+    pub fn new() -> Self {
+        let builtin = |filter: &str| filter.parse::<SelectionFilter>().expect("valid built-in filter");
+        Self {
+            commands: vec![
+                SelectionCommand::new("selection.all", "Select All", builtin("all"))
+                    .with_shortcut("Ctrl+A"),
+                SelectionCommand::new("selection.invert", "Invert Selection", builtin("!selected"))
+                    .with_shortcut("Ctrl+I"),
+                SelectionCommand::new("selection.grow", "Grow Selection by One Hop", builtin("grow(selected)"))
+                    .with_shortcut("Ctrl+Shift+G"),
+                SelectionCommand::new("selection.same-type", "Select Nodes of the Same Type", builtin("type:current")),
+                SelectionCommand::new(
+                    "selection.same-type-in-cluster",
+                    "Select Nodes of the Same Type in This Cluster",
+                    builtin("type:current & cluster:current"),
+                ),
+                SelectionCommand::new("selection.cluster", "Select This Cluster", builtin("cluster:current")),
+                SelectionCommand::new("selection.files-in-cluster", "Select Files in This Cluster", builtin("type:file & cluster:current")),
+                SelectionCommand::new("selection.modified-today", "Select Nodes Modified Today", builtin("modified:today")),
+                SelectionCommand::new("selection.visible", "Select Visible Nodes", builtin("visible")),
+            ],
+        }
+    }
+
+    /// Add a command, replacing one with the same ID
+    pub fn register(&mut self, command: SelectionCommand) {
+        self.commands.retain(|existing| existing.id != command.id);
+        self.commands.push(command);
+    }
+
+    /// Remove a command
+    pub fn unregister(&mut self, id: &str) -> Option<SelectionCommand> {
+        let index = self.commands.iter().position(|command| command.id == id)?;
+        Some(self.commands.remove(index))
+    }
+
+    /// Get a command
+    pub fn get(&self, id: &str) -> Option<&SelectionCommand> {
+        self.commands.iter().find(|command| command.id == id)
+    }
+
+    /// All commands, for the command palette
+    pub fn commands(&self) -> &[SelectionCommand] {
+        &self.commands
+    }
+
+    /// Commands whose label contains the query, ignoring case
+    pub fn search(&self, query: &str) -> Vec<&SelectionCommand> {
+        let query = query.to_lowercase();
+        self.commands.iter().filter(|command| command.label.to_lowercase().contains(&query)).collect()
+    }
+
+    /// Bind a shortcut to a command, unbinding it from any other; `None` removes the binding
+    pub fn bind(&mut self, id: &str, shortcut: Option<&str>) -> bool {
+        if !self.commands.iter().any(|command| command.id == id) {
+            return false;
+        }
+        for command in &mut self.commands {
+            if command.id == id {
+                command.shortcut = shortcut.map(str::to_string);
+            } else if shortcut.is_some() && command.shortcut.as_deref() == shortcut {
+                command.shortcut = None;
+            }
+        }
+        true
+    }
+
+    /// Command bound to a shortcut, ignoring case
+    pub fn for_shortcut(&self, shortcut: &str) -> Option<&SelectionCommand> {
+        self.commands.iter().find(|command| {
+            command.shortcut.as_deref().is_some_and(|bound| bound.eq_ignore_ascii_case(shortcut))
+        })
+    }
+
+    /// Command bound to a pressed chord
+    pub fn for_chord(&self, chord: &KeyChord) -> Option<&SelectionCommand> {
+        self.commands.iter().find(|command| {
+            command.shortcut.as_deref().and_then(|bound| bound.parse::<KeyChord>().ok()).as_ref() == Some(chord)
+        })
+    }
+
+    /// Command by ID, where `invert` also finds `selection.invert`
+    pub fn find(&self, id: &str) -> Option<&SelectionCommand> {
+        self.get(id).or_else(|| self.get(&format!("selection.{}", id)))
+    }
+}
+
+impl Default for SelectionCommands {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::{EdgeType, NodeMetadata, NodeType, Position, SceneEdge, SceneNode};
+    use nalgebra::Vector3;
+
+    fn parse(filter: &str) -> SelectionFilter {
+        filter.parse().unwrap()
+    }
+
+    /// Nodes 0 - 1 - 2 - 3 in a chain
+    fn chain() -> (Scene, Vec<SceneId>) {
+        let mut scene = Scene::new();
+        let ids: Vec<SceneId> = (0..4)
+            .map(|i| {
+                scene.add_node(SceneNode {
+                    id: 0,
+                    position: Position::new(i as f32, 0.0, 0.0),
+                    velocity: Vector3::zeros(),
+                    radius: 1.0,
+                    color: [1.0; 4],
+                    node_type: NodeType::Concept { title: String::new(), content: String::new() },
+                    metadata: NodeMetadata::default(),
+                    visible: true,
+                    selected: false,
+                })
+            })
+            .collect();
+        for pair in ids.windows(2) {
+            scene.add_edge(SceneEdge {
+                id: 0,
+                source: pair[0],
+                target: pair[1],
+                edge_type: EdgeType::Contains,
+                weight: 1.0,
+                color: [1.0; 4],
+                visible: true,
+                animated: false,
+            });
+        }
+        (scene, ids)
+    }
+
+    #[test]
+    fn test_and_binds_tighter_than_or_and_not_tightest() {
+        assert_eq!(
+            parse("selected | type:file & !tag:done"),
+            SelectionFilter::Or(vec![
+                SelectionFilter::Selected,
+                SelectionFilter::And(vec![
+                    SelectionFilter::NodeType("file".to_string()),
+                    SelectionFilter::Not(Box::new(SelectionFilter::Tag("done".to_string()))),
+                ]),
+            ])
+        );
+        assert_eq!(
+            parse("(selected | visible) & modified:12h"),
+            SelectionFilter::And(vec![
+                SelectionFilter::Or(vec![SelectionFilter::Selected, SelectionFilter::Visible]),
+                SelectionFilter::ModifiedWithin(12 * 3600),
+            ])
+        );
+        // Printed filters parse back to the same filter
+        let filter = parse("!(type:current & cluster:current) | named:work");
+        assert_eq!(parse(&filter.to_string()), filter);
+    }
+
+    #[test]
+    fn test_grow_parses_hops_and_follows_edges() {
+        assert_eq!(parse("grow(selected)"), SelectionFilter::Grow { base: Box::new(SelectionFilter::Selected), hops: 1 });
+        assert_eq!(
+            parse("grow(tag:a | tag:b, 3)"),
+            SelectionFilter::Grow {
+                base: Box::new(SelectionFilter::Or(vec![
+                    SelectionFilter::Tag("a".to_string()),
+                    SelectionFilter::Tag("b".to_string()),
+                ])),
+                hops: 3,
+            }
+        );
+
+        let (scene, ids) = chain();
+        let selection = HashSet::from([ids[0]]);
+        let named = HashMap::new();
+        let context = SelectionContext {
+            scene: &scene,
+            clusters: None,
+            selection: &selection,
+            primary: Some(ids[0]),
+            named: &named,
+            now: Utc::now(),
+        };
+        assert_eq!(parse("grow(selected)").evaluate(&context), HashSet::from([ids[0], ids[1]]));
+        assert_eq!(parse("grow(selected, 2)").evaluate(&context), HashSet::from([ids[0], ids[1], ids[2]]));
+        assert_eq!(parse("!grow(selected)").evaluate(&context), HashSet::from([ids[2], ids[3]]));
+    }
+
+    #[test]
+    fn test_invalid_filters_are_rejected() {
+        for invalid in [
+            "",
+            "everything",
+            "type:",
+            "selected &",
+            "(selected",
+            "selected)",
+            "grow(selected, many)",
+            "modified:3w",
+            "cluster:not-a-uuid",
+            "color:red",
+        ] {
+            assert!(invalid.parse::<SelectionFilter>().is_err(), "'{}' should not parse", invalid);
+        }
+    }
+
+    #[test]
+    fn test_commands_are_found_by_id_and_chord() {
+        let mut commands = SelectionCommands::new();
+        assert_eq!(commands.find("invert").map(|command| command.id.as_str()), Some("selection.invert"));
+        assert!(commands.find("nothing").is_none());
+
+        let chord: KeyChord = "Ctrl+Shift+G".parse().unwrap();
+        assert_eq!(commands.for_chord(&chord).map(|command| command.id.as_str()), Some("selection.grow"));
+
+        // Binding a taken shortcut moves it
+        assert!(commands.bind("selection.visible", Some("Ctrl+I")));
+        let chord: KeyChord = "Ctrl+I".parse().unwrap();
+        assert_eq!(commands.for_chord(&chord).map(|command| command.id.as_str()), Some("selection.visible"));
+        assert!(commands.get("selection.invert").unwrap().shortcut.is_none());
+    }
+}
@@ -0,0 +1,333 @@
+//! Spatial navigation between nodes
+//!
+//! Directions are relative to the camera, so "left" is always towards the
+//! left edge of the screen however the camera is oriented. Tab moves along
+//! relationships instead: through the neighbours of the node navigation
+//! started from, strongest edge first, then their neighbours.
+
+use horizonos_graph_engine::{edge_kind, Camera, Scene, SceneId};
+use horizonos_graph_nodes::SearchDocument;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+
+/// Screen direction to move focus in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Weight of sideways offset against distance in the pressed direction
+const ACROSS_WEIGHT: f32 = 2.0;
+
+/// Nearest visible node from `from` in a screen direction
+///
+/// Candidates must lie more in the pressed direction than sideways; among
+/// them the one closest along the direction, with sideways offset counting
+/// double, wins.
+pub fn nearest_in_direction(scene: &Scene, camera: &Camera, from: SceneId, direction: Direction) -> Option<SceneId> {
+    let origin = scene.get_node_position(from)?;
+    scene
+        .nodes()
+        .filter(|(id, node)| **id != from && node.visible)
+        .filter_map(|(id, node)| {
+            let offset = node.position - origin;
+            let (x, y) = (offset.dot(&camera.right), offset.dot(&camera.up));
+            let (along, across) = match direction {
+                Direction::Right => (x, y.abs()),
+                Direction::Left => (-x, y.abs()),
+                Direction::Up => (y, x.abs()),
+                Direction::Down => (-y, x.abs()),
+            };
+            (along > 0.0 && across <= along).then_some((*id, along + ACROSS_WEIGHT * across))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)))
+        .map(|(id, _)| id)
+}
+
+/// Visible node closest to the centre of the view, to start navigating from
+pub fn nearest_to_view_center(scene: &Scene, camera: &Camera) -> Option<SceneId> {
+    scene
+        .nodes()
+        .filter(|(_, node)| node.visible)
+        .filter_map(|(id, node)| {
+            let offset = node.position - camera.position;
+            let depth = offset.dot(&camera.forward);
+            // Angle off the view axis, ignoring nodes behind the camera
+            (depth > 0.0).then(|| (*id, (offset - camera.forward * depth).magnitude() / depth))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)))
+        .map(|(id, _)| id)
+}
+
+/// How keyboard focus moved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusMove {
+    /// Arrow key towards the nearest node on screen
+    Direction(Direction),
+    /// Tab to the next node in relationship order
+    NextRelated,
+    /// Shift+Tab to the previous node in relationship order
+    PreviousRelated,
+}
+
+/// Keyboard focus moved to another node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FocusChange {
+    pub previous: Option<SceneId>,
+    pub node_id: SceneId,
+    pub by: FocusMove,
+    /// Text for the screen reader, see [`focus_announcement`]
+    pub announcement: String,
+}
+
+/// Callback for keyboard focus changes
+pub type FocusCallback = Box<dyn Fn(&FocusChange) + Send + Sync>;
+
+/// Order Tab visits nodes in, following relationships from a start node
+///
+/// The order is kept while Tab is pressed repeatedly so it does not bounce
+/// between two neighbours; navigating any other way starts a new order.
+#[derive(Debug, Clone, Default)]
+pub struct TabOrder {
+    order: Vec<SceneId>,
+}
+
+impl TabOrder {
+    /// Breadth-first walk over edges from `start`, then the remaining visible nodes by id
+    pub fn from_node(scene: &Scene, start: SceneId) -> Self {
+        let mut roots: Vec<SceneId> = scene.nodes().filter(|(_, node)| node.visible).map(|(id, _)| *id).collect();
+        roots.sort_unstable();
+        let mut visited = HashSet::new();
+        let mut order = Vec::new();
+
+        for root in std::iter::once(start).chain(roots) {
+            if !visited.insert(root) {
+                continue;
+            }
+            let mut queue = VecDeque::from([root]);
+            while let Some(node_id) = queue.pop_front() {
+                order.push(node_id);
+                let mut neighbours: Vec<_> = scene
+                    .get_connected_edges(node_id)
+                    .into_iter()
+                    .map(|edge| (if edge.source == node_id { edge.target } else { edge.source }, edge.weight))
+                    .filter(|(other, _)| scene.get_node(*other).is_some_and(|node| node.visible))
+                    .collect();
+                neighbours.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+                for (other, _) in neighbours {
+                    if visited.insert(other) {
+                        queue.push_back(other);
+                    }
+                }
+            }
+        }
+        Self { order }
+    }
+
+    pub fn order(&self) -> &[SceneId] {
+        &self.order
+    }
+
+    pub fn contains(&self, node_id: SceneId) -> bool {
+        self.order.contains(&node_id)
+    }
+
+    /// Node after `from`, or before it when `reverse`, wrapping and skipping removed or hidden nodes
+    pub fn step(&self, scene: &Scene, from: SceneId, reverse: bool) -> Option<SceneId> {
+        let len = self.order.len();
+        let position = self.order.iter().position(|id| *id == from)?;
+        (1..len)
+            .map(|offset| if reverse { (position + len - offset) % len } else { (position + offset) % len })
+            .map(|index| self.order[index])
+            .find(|id| scene.get_node(*id).is_some_and(|node| node.visible))
+    }
+}
+
+/// What a screen reader says when focus lands on `node_id`
+///
+/// Names the node, its type and number of connections, and the relationship
+/// to the node focus came from when they are connected.
+pub fn focus_announcement(scene: &Scene, node_id: SceneId, previous: Option<SceneId>) -> Option<String> {
+    let document = SearchDocument::from_scene_node(scene.get_node(node_id)?);
+    let edges = scene.get_connected_edges(node_id);
+    let mut announcement = format!("{}, {}", document.display_name, document.node_type.replace('_', " "));
+    match edges.len() {
+        0 => announcement.push_str(", no connections"),
+        1 => announcement.push_str(", 1 connection"),
+        n => announcement.push_str(&format!(", {} connections", n)),
+    }
+
+    let link = previous.and_then(|previous| {
+        let edge = edges.iter().find(|edge| edge.source == previous || edge.target == previous)?;
+        Some((SearchDocument::from_scene_node(scene.get_node(previous)?).display_name, edge_kind(&edge.edge_type)))
+    });
+    if let Some((name, relation)) = link {
+        announcement.push_str(&format!(", {} link with {}", relation.replace('_', " "), name));
+    }
+    Some(announcement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::{EdgeType, NodeMetadata, NodeType, Position, SceneEdge, SceneNode};
+    use nalgebra::{Point3, Vector3};
+
+    fn node(scene: &mut Scene, x: f32, y: f32) -> SceneId {
+        scene.add_node(SceneNode {
+            id: 0,
+            position: Position::new(x, y, 0.0),
+            velocity: Vector3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type: NodeType::Concept { title: String::new(), content: String::new() },
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        })
+    }
+
+    fn edge(scene: &mut Scene, source: SceneId, target: SceneId, weight: f32) {
+        scene.add_edge(SceneEdge {
+            id: 0,
+            source,
+            target,
+            edge_type: EdgeType::DependsOn,
+            weight,
+            color: [1.0; 4],
+            visible: true,
+            animated: false,
+        });
+    }
+
+    #[test]
+    fn test_neighbour_in_each_direction() {
+        let mut scene = Scene::new();
+        let center = node(&mut scene, 0.0, 0.0);
+        let right = node(&mut scene, 3.0, 0.5);
+        let left = node(&mut scene, -3.0, -0.5);
+        let up = node(&mut scene, 0.5, 3.0);
+        let down = node(&mut scene, -0.5, -3.0);
+        let camera = Camera::new();
+
+        assert_eq!(nearest_in_direction(&scene, &camera, center, Direction::Right), Some(right));
+        assert_eq!(nearest_in_direction(&scene, &camera, center, Direction::Left), Some(left));
+        assert_eq!(nearest_in_direction(&scene, &camera, center, Direction::Up), Some(up));
+        assert_eq!(nearest_in_direction(&scene, &camera, center, Direction::Down), Some(down));
+
+        // Nothing further out at the edge of the graph
+        assert_eq!(nearest_in_direction(&scene, &camera, right, Direction::Right), None);
+        assert_eq!(nearest_in_direction(&scene, &camera, 99, Direction::Right), None);
+    }
+
+    #[test]
+    fn test_hidden_nodes_are_skipped() {
+        let mut scene = Scene::new();
+        let center = node(&mut scene, 0.0, 0.0);
+        let near = node(&mut scene, 2.0, 0.0);
+        let far = node(&mut scene, 5.0, 0.0);
+        scene.get_node_mut(near).unwrap().visible = false;
+
+        assert_eq!(nearest_in_direction(&scene, &Camera::new(), center, Direction::Right), Some(far));
+    }
+
+    #[test]
+    fn test_directions_follow_the_camera() {
+        let mut scene = Scene::new();
+        let center = node(&mut scene, 0.0, 0.0);
+        let east = node(&mut scene, 3.0, 0.0);
+        let west = node(&mut scene, -3.0, 0.0);
+
+        // Looking at the graph from behind swaps left and right on screen
+        let mut camera = Camera::new();
+        camera.position = Point3::new(0.0, 0.0, -10.0);
+        camera.look_at(Point3::origin());
+        assert_eq!(nearest_in_direction(&scene, &camera, center, Direction::Left), Some(east));
+        assert_eq!(nearest_in_direction(&scene, &camera, center, Direction::Right), Some(west));
+    }
+
+    #[test]
+    fn test_sideways_offset_counts_double() {
+        let mut scene = Scene::new();
+        let center = node(&mut scene, 0.0, 0.0);
+        // 2 + 2 * 1.5 = 5 against 4 + 2 * 0 = 4
+        let near_but_off_axis = node(&mut scene, 2.0, 1.5);
+        let farther_on_axis = node(&mut scene, 4.0, 0.0);
+        // More sideways than along is not to the right at all
+        node(&mut scene, 1.0, 1.1);
+        let camera = Camera::new();
+
+        assert_eq!(nearest_in_direction(&scene, &camera, center, Direction::Right), Some(farther_on_axis));
+        scene.get_node_mut(farther_on_axis).unwrap().visible = false;
+        assert_eq!(nearest_in_direction(&scene, &camera, center, Direction::Right), Some(near_but_off_axis));
+    }
+
+    #[test]
+    fn test_ties_go_to_the_lower_id() {
+        let mut scene = Scene::new();
+        let center = node(&mut scene, 0.0, 0.0);
+        let above = node(&mut scene, 3.0, 1.0);
+        let below = node(&mut scene, 3.0, -1.0);
+        assert!(above < below);
+
+        assert_eq!(nearest_in_direction(&scene, &Camera::new(), center, Direction::Right), Some(above));
+        scene.get_node_mut(above).unwrap().visible = false;
+        assert_eq!(nearest_in_direction(&scene, &Camera::new(), center, Direction::Right), Some(below));
+    }
+
+    #[test]
+    fn test_navigation_starts_nearest_the_view_center() {
+        let mut scene = Scene::new();
+        node(&mut scene, 4.0, 0.0);
+        let centered = node(&mut scene, 0.5, 0.0);
+        let mut camera = Camera::new();
+        assert_eq!(nearest_to_view_center(&scene, &camera), Some(centered));
+
+        // Nodes behind the camera are never picked
+        camera.position = Point3::new(0.0, 0.0, -1.0);
+        assert_eq!(nearest_to_view_center(&scene, &camera), None);
+    }
+
+    #[test]
+    fn test_tab_follows_the_strongest_relationships_first() {
+        let mut scene = Scene::new();
+        let start = node(&mut scene, 0.0, 0.0);
+        let weak = node(&mut scene, 1.0, 0.0);
+        let strong = node(&mut scene, 2.0, 0.0);
+        let second_hop = node(&mut scene, 3.0, 0.0);
+        let unrelated = node(&mut scene, 4.0, 0.0);
+        edge(&mut scene, start, weak, 0.2);
+        edge(&mut scene, strong, start, 0.9);
+        edge(&mut scene, strong, second_hop, 0.5);
+
+        let order = TabOrder::from_node(&scene, start);
+        assert_eq!(order.order(), [start, strong, weak, second_hop, unrelated]);
+        assert_eq!(order.step(&scene, start, false), Some(strong));
+        assert_eq!(order.step(&scene, unrelated, false), Some(start));
+        assert_eq!(order.step(&scene, start, true), Some(unrelated));
+
+        // Hidden nodes are skipped without changing the order
+        scene.get_node_mut(strong).unwrap().visible = false;
+        assert_eq!(order.step(&scene, start, false), Some(weak));
+        assert!(order.contains(strong));
+    }
+
+    #[test]
+    fn test_announcement_names_the_link_focus_came_along() {
+        let mut scene = Scene::new();
+        let a = node(&mut scene, 0.0, 0.0);
+        let b = node(&mut scene, 1.0, 0.0);
+        let lonely = node(&mut scene, 2.0, 0.0);
+        edge(&mut scene, a, b, 1.0);
+
+        let announcement = focus_announcement(&scene, b, Some(a)).unwrap();
+        assert!(announcement.contains("1 connection"), "{}", announcement);
+        assert!(announcement.contains("depends on link with"), "{}", announcement);
+        assert!(focus_announcement(&scene, lonely, Some(a)).unwrap().ends_with("no connections"));
+        assert!(focus_announcement(&scene, 99, None).is_none());
+    }
+}
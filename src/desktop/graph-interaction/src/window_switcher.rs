@@ -0,0 +1,488 @@
+//! Graph-aware window switcher (Alt-Tab)
+//!
+//! Candidates are application nodes, ordered by how closely they relate to the
+//! focused window in the graph: shared clusters, shared workspace, edge
+//! strength and recency. Without a focused window or any relationships the
+//! switcher falls back to most-recently-used order.
+
+use horizonos_graph_clustering::ClusterStore;
+use horizonos_graph_engine::{NodeType, Scene, SceneId};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Instant;
+
+/// How candidates are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwitcherOrdering {
+    /// Graph relevance to the focused window, MRU as tie-breaker
+    Relevance,
+    /// Most recently used first
+    Mru,
+}
+
+/// Window switcher configuration
+#[derive(Debug, Clone)]
+pub struct SwitcherConfig {
+    /// Candidate ordering
+    pub ordering: SwitcherOrdering,
+    /// Weight of sharing a cluster with the focused window
+    pub cluster_weight: f32,
+    /// Weight of sharing a workspace with the focused window
+    pub workspace_weight: f32,
+    /// Weight of edge strength to the focused window
+    pub edge_weight: f32,
+    /// Weight of focus recency
+    pub recency_weight: f32,
+    /// Number of focus changes remembered
+    pub history_size: usize,
+    /// Requested preview size in pixels
+    pub preview_size: (u32, u32),
+}
+
+impl Default for SwitcherConfig {
+    fn default() -> Self {
+        Self {
+            ordering: SwitcherOrdering::Relevance,
+            cluster_weight: 0.3,
+            workspace_weight: 0.2,
+            edge_weight: 0.3,
+            recency_weight: 0.2,
+            history_size: 64,
+            preview_size: (320, 200),
+        }
+    }
+}
+
+/// Live preview of a window
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowPreview {
+    /// Renderer texture holding the window contents
+    pub texture_id: u64,
+    /// Preview width in pixels
+    pub width: u32,
+    /// Preview height in pixels
+    pub height: u32,
+}
+
+/// Source of live window previews, implemented by the compositor
+pub trait PreviewProvider: Send + Sync {
+    /// Current preview of a window node, scaled to fit `size`
+    fn preview(&self, node_id: SceneId, size: (u32, u32)) -> Option<WindowPreview>;
+}
+
+/// Why a candidate scored as it did
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RelevanceBreakdown {
+    /// Shares a cluster with the focused window
+    pub same_cluster: bool,
+    /// Shares a workspace with the focused window
+    pub same_workspace: bool,
+    /// Summed edge weight to the focused window, clamped to 1
+    pub edge_strength: f32,
+    /// 1 for the most recent window, falling towards 0
+    pub recency: f32,
+}
+
+/// A window offered by the switcher
+#[derive(Debug, Clone)]
+pub struct SwitcherCandidate {
+    /// Application node
+    pub node_id: SceneId,
+    /// Window title, or the application name
+    pub title: String,
+    /// Application name
+    pub app_name: String,
+    /// Combined relevance score
+    pub score: f32,
+    /// Score components
+    pub relevance: RelevanceBreakdown,
+    /// Position in focus history, 0 being the focused window
+    pub mru_rank: Option<usize>,
+    /// Latest live preview
+    pub preview: Option<WindowPreview>,
+}
+
+/// Graph-aware Alt-Tab switcher
+pub struct WindowSwitcher {
+    /// Configuration
+    config: SwitcherConfig,
+    /// Focused windows, most recent first
+    focus_history: VecDeque<SceneId>,
+    /// Workspace each window belongs to
+    workspaces: HashMap<SceneId, String>,
+    /// Preview source
+    preview_provider: Option<Box<dyn PreviewProvider>>,
+    /// Candidates while open, in display order
+    candidates: Vec<SwitcherCandidate>,
+    /// Type-ahead filter
+    filter: String,
+    /// Indices into `candidates` matching the filter
+    visible: Vec<usize>,
+    /// Selected position in `visible`
+    selected: usize,
+    /// When the switcher was opened
+    opened_at: Option<Instant>,
+}
+
+impl WindowSwitcher {
+    /// Create a switcher
+    pub fn new(config: SwitcherConfig) -> Self {
+        Self {
+            config,
+            focus_history: VecDeque::new(),
+            workspaces: HashMap::new(),
+            preview_provider: None,
+            candidates: Vec::new(),
+            filter: String::new(),
+            visible: Vec::new(),
+            selected: 0,
+            opened_at: None,
+        }
+    }
+
+    /// Get configuration
+    pub fn config(&self) -> &SwitcherConfig {
+        &self.config
+    }
+
+    /// Update configuration
+    pub fn set_config(&mut self, config: SwitcherConfig) {
+        self.config = config;
+    }
+
+    /// Set the source of live previews
+    pub fn set_preview_provider(&mut self, provider: Box<dyn PreviewProvider>) {
+        self.preview_provider = Some(provider);
+    }
+
+    /// Record that a window was focused
+    pub fn record_focus(&mut self, node_id: SceneId) {
+        self.focus_history.retain(|&id| id != node_id);
+        self.focus_history.push_front(node_id);
+        self.focus_history.truncate(self.config.history_size);
+    }
+
+    /// Forget a closed window
+    pub fn forget(&mut self, node_id: SceneId) {
+        self.focus_history.retain(|&id| id != node_id);
+        self.workspaces.remove(&node_id);
+    }
+
+    /// Assign a window to a workspace
+    pub fn set_workspace(&mut self, node_id: SceneId, workspace_id: impl Into<String>) {
+        self.workspaces.insert(node_id, workspace_id.into());
+    }
+
+    /// Currently focused window
+    pub fn focused(&self) -> Option<SceneId> {
+        self.focus_history.front().copied()
+    }
+
+    /// Whether the switcher is showing
+    pub fn is_open(&self) -> bool {
+        self.opened_at.is_some()
+    }
+
+    /// Open the switcher, selecting the window after the focused one
+    pub fn open(&mut self, scene: &Scene, clusters: Option<&dyn ClusterStore>) {
+        self.candidates = self.rank_candidates(scene, clusters);
+        self.filter.clear();
+        self.apply_filter();
+        self.opened_at = Some(Instant::now());
+        self.refresh_previews();
+
+        // Alt-Tab lands on the next window, not the one already focused
+        let focused_first = self.current().map(|c| Some(c.node_id) == self.focused()).unwrap_or(false);
+        if focused_first && self.visible.len() > 1 {
+            self.selected = 1;
+        }
+    }
+
+    /// Select the next candidate, wrapping around
+    pub fn next(&mut self) {
+        if !self.visible.is_empty() {
+            self.selected = (self.selected + 1) % self.visible.len();
+        }
+    }
+
+    /// Select the previous candidate, wrapping around
+    pub fn previous(&mut self) {
+        if !self.visible.is_empty() {
+            self.selected = (self.selected + self.visible.len() - 1) % self.visible.len();
+        }
+    }
+
+    /// Select the last candidate, where Alt+Shift+Tab starts
+    pub fn select_last(&mut self) {
+        self.selected = self.visible.len().saturating_sub(1);
+    }
+
+    /// Add a character to the type-ahead filter
+    pub fn push_filter_char(&mut self, c: char) {
+        self.filter.push(c);
+        self.apply_filter();
+    }
+
+    /// Remove the last character of the type-ahead filter
+    pub fn pop_filter_char(&mut self) {
+        self.filter.pop();
+        self.apply_filter();
+    }
+
+    /// Current type-ahead filter
+    pub fn filter(&self) -> &str {
+        &self.filter
+    }
+
+    /// Candidates matching the filter, in display order
+    pub fn visible_candidates(&self) -> Vec<&SwitcherCandidate> {
+        self.visible.iter().map(|&i| &self.candidates[i]).collect()
+    }
+
+    /// Selected candidate
+    pub fn current(&self) -> Option<&SwitcherCandidate> {
+        self.visible.get(self.selected).map(|&i| &self.candidates[i])
+    }
+
+    /// Index of the selected candidate among the visible ones
+    pub fn selected_index(&self) -> usize {
+        self.selected
+    }
+
+    /// Fetch fresh previews for visible candidates
+    pub fn refresh_previews(&mut self) {
+        let Some(provider) = &self.preview_provider else {
+            return;
+        };
+        for &index in &self.visible {
+            let candidate = &mut self.candidates[index];
+            candidate.preview = provider.preview(candidate.node_id, self.config.preview_size);
+        }
+    }
+
+    /// Close the switcher and focus the selected window
+    pub fn commit(&mut self) -> Option<SceneId> {
+        let chosen = self.current().map(|c| c.node_id);
+        self.close();
+        if let Some(node_id) = chosen {
+            self.record_focus(node_id);
+        }
+        chosen
+    }
+
+    /// Close the switcher without changing focus
+    pub fn cancel(&mut self) {
+        self.close();
+    }
+
+    /// Reset open state
+    fn close(&mut self) {
+        self.candidates.clear();
+        self.visible.clear();
+        self.filter.clear();
+        self.selected = 0;
+        self.opened_at = None;
+    }
+
+    /// Recompute visible candidates for the filter
+    fn apply_filter(&mut self) {
+        let filter = self.filter.to_lowercase();
+        self.visible = self.candidates.iter()
+            .enumerate()
+            .filter(|(_, c)| {
+                filter.is_empty()
+                    || fuzzy_match(&c.title.to_lowercase(), &filter)
+                    || fuzzy_match(&c.app_name.to_lowercase(), &filter)
+            })
+            .map(|(i, _)| i)
+            .collect();
+        self.selected = 0;
+    }
+
+    /// Build and order candidates from the application nodes in the scene
+    fn rank_candidates(&self, scene: &Scene, clusters: Option<&dyn ClusterStore>) -> Vec<SwitcherCandidate> {
+        let focused = self.focused().filter(|id| scene.get_node(*id).is_some());
+        let focused_clusters: HashSet<_> = match (focused, clusters) {
+            (Some(id), Some(clusters)) => clusters.get_node_clusters(id).into_iter().collect(),
+            _ => HashSet::new(),
+        };
+        let focused_workspace = focused.and_then(|id| self.workspaces.get(&id));
+
+        let mut edge_strength: HashMap<SceneId, f32> = HashMap::new();
+        if let Some(focused) = focused {
+            for edge in scene.get_connected_edges(focused) {
+                let other = if edge.source == focused { edge.target } else { edge.source };
+                *edge_strength.entry(other).or_default() += edge.weight.max(0.0);
+            }
+        }
+
+        let history_len = self.focus_history.len().max(1) as f32;
+        let mut candidates: Vec<SwitcherCandidate> = scene.nodes()
+            .filter(|(_, node)| node.visible)
+            .filter_map(|(&id, node)| {
+                let NodeType::Application { name, .. } = &node.node_type else {
+                    return None;
+                };
+                let title = node.metadata.properties.get("title")
+                    .cloned()
+                    .unwrap_or_else(|| name.clone());
+                let mru_rank = self.focus_history.iter().position(|&h| h == id);
+
+                let relevance = RelevanceBreakdown {
+                    same_cluster: clusters
+                        .map(|c| c.get_node_clusters(id).iter().any(|cid| focused_clusters.contains(cid)))
+                        .unwrap_or(false),
+                    same_workspace: focused_workspace.is_some() && self.workspaces.get(&id) == focused_workspace,
+                    edge_strength: edge_strength.get(&id).copied().unwrap_or(0.0).min(1.0),
+                    recency: mru_rank.map(|rank| 1.0 - rank as f32 / history_len).unwrap_or(0.0),
+                };
+
+                Some(SwitcherCandidate {
+                    node_id: id,
+                    title,
+                    app_name: name.clone(),
+                    score: self.score(&relevance),
+                    relevance,
+                    mru_rank,
+                    preview: None,
+                })
+            })
+            .collect();
+
+        let has_relations = candidates.iter()
+            .filter(|c| Some(c.node_id) != focused)
+            .any(|c| c.relevance.same_cluster || c.relevance.same_workspace || c.relevance.edge_strength > 0.0);
+
+        let mru_order = |a: &SwitcherCandidate, b: &SwitcherCandidate| {
+            a.mru_rank.unwrap_or(usize::MAX).cmp(&b.mru_rank.unwrap_or(usize::MAX))
+                .then_with(|| a.title.cmp(&b.title))
+        };
+
+        if self.config.ordering == SwitcherOrdering::Mru || !has_relations {
+            candidates.sort_by(mru_order);
+        } else {
+            // The focused window stays first so Alt-Tab moves to the most relevant other one
+            candidates.sort_by(|a, b| {
+                (Some(b.node_id) == focused).cmp(&(Some(a.node_id) == focused))
+                    .then_with(|| b.score.total_cmp(&a.score))
+                    .then_with(|| mru_order(a, b))
+            });
+        }
+        candidates
+    }
+
+    /// Weighted relevance score
+    fn score(&self, relevance: &RelevanceBreakdown) -> f32 {
+        let flag = |b: bool| if b { 1.0 } else { 0.0 };
+        self.config.cluster_weight * flag(relevance.same_cluster)
+            + self.config.workspace_weight * flag(relevance.same_workspace)
+            + self.config.edge_weight * relevance.edge_strength
+            + self.config.recency_weight * relevance.recency
+    }
+}
+
+impl Default for WindowSwitcher {
+    fn default() -> Self {
+        Self::new(SwitcherConfig::default())
+    }
+}
+
+/// Whether all characters of `pattern` appear in `text` in order
+fn fuzzy_match(text: &str, pattern: &str) -> bool {
+    let mut chars = text.chars();
+    pattern.chars().all(|p| chars.any(|c| c == p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::{NodeMetadata, Position, SceneNode};
+    use nalgebra::Vector3;
+
+    /// Scene with one application node per name
+    fn windows(names: &[&str]) -> (Scene, Vec<SceneId>) {
+        let mut scene = Scene::new();
+        let ids = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                scene.add_node(SceneNode {
+                    id: 0,
+                    position: Position::new(i as f32, 0.0, 0.0),
+                    velocity: Vector3::zeros(),
+                    radius: 1.0,
+                    color: [1.0; 4],
+                    node_type: NodeType::Application { pid: i as u32, name: name.to_string() },
+                    metadata: NodeMetadata::default(),
+                    visible: true,
+                    selected: false,
+                })
+            })
+            .collect();
+        (scene, ids)
+    }
+
+    fn order(switcher: &WindowSwitcher) -> Vec<SceneId> {
+        switcher.visible_candidates().iter().map(|c| c.node_id).collect()
+    }
+
+    #[test]
+    fn test_unrelated_windows_are_in_mru_order() {
+        let (scene, ids) = windows(&["editor", "browser", "terminal"]);
+        let mut switcher = WindowSwitcher::default();
+        switcher.record_focus(ids[1]);
+        switcher.record_focus(ids[2]);
+        switcher.record_focus(ids[0]);
+
+        switcher.open(&scene, None);
+        assert!(switcher.is_open());
+        assert_eq!(order(&switcher), vec![ids[0], ids[2], ids[1]]);
+        // Alt-Tab lands on the previously focused window
+        assert_eq!(switcher.current().map(|c| c.node_id), Some(ids[2]));
+
+        assert_eq!(switcher.commit(), Some(ids[2]));
+        assert!(!switcher.is_open());
+        assert_eq!(switcher.focused(), Some(ids[2]));
+        switcher.open(&scene, None);
+        assert_eq!(order(&switcher), vec![ids[2], ids[0], ids[1]]);
+    }
+
+    #[test]
+    fn test_selection_wraps_around() {
+        let (scene, ids) = windows(&["editor", "browser", "terminal"]);
+        let mut switcher = WindowSwitcher::default();
+        for &id in ids.iter().rev() {
+            switcher.record_focus(id);
+        }
+
+        switcher.open(&scene, None);
+        assert_eq!(switcher.selected_index(), 1);
+        switcher.next();
+        assert_eq!(switcher.selected_index(), 2);
+        switcher.next();
+        assert_eq!(switcher.selected_index(), 0);
+        switcher.previous();
+        assert_eq!(switcher.selected_index(), 2);
+        switcher.select_last();
+        assert_eq!(switcher.current().map(|c| c.node_id), Some(ids[2]));
+
+        // Cancelling keeps the focus where it was
+        switcher.cancel();
+        assert!(!switcher.is_open());
+        assert_eq!(switcher.focused(), Some(ids[0]));
+    }
+
+    #[test]
+    fn test_type_ahead_filters_candidates() {
+        let (scene, ids) = windows(&["editor", "browser", "terminal"]);
+        let mut switcher = WindowSwitcher::default();
+        switcher.open(&scene, None);
+        for c in "trm".chars() {
+            switcher.push_filter_char(c);
+        }
+        assert_eq!(order(&switcher), vec![ids[2]]);
+        switcher.pop_filter_char();
+        switcher.pop_filter_char();
+        assert_eq!(switcher.filter(), "t");
+        assert_eq!(order(&switcher).len(), 2);
+    }
+}
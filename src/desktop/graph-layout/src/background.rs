@@ -0,0 +1,426 @@
+//! Force-directed layout on a worker thread
+//!
+//! Large graphs take many iterations to settle, more than fit in a frame.
+//! [`BackgroundLayout`] runs a [`ForceDirectedLayout`] on its own thread and
+//! streams intermediate positions back as [`LayoutFrame`]s, which the frame
+//! loop applies to the scene whenever one is ready. The simulation can be
+//! paused, resumed, run flat out to convergence, and fed graph changes and
+//! pinned (dragged) nodes while it runs.
+
+use crate::{ForceDirectedLayout, LayoutEdge, LayoutNode, Position, SceneId};
+use horizonos_graph_engine::Scene;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Pacing of the background simulation
+#[derive(Debug, Clone)]
+pub struct BackgroundLayoutConfig {
+    /// Iterations simulated between two position updates
+    pub iterations_per_update: usize,
+    /// Shortest time between position updates; the worker sleeps out the rest
+    pub update_interval: Duration,
+}
+
+impl Default for BackgroundLayoutConfig {
+    fn default() -> Self {
+        Self {
+            iterations_per_update: 5,
+            update_interval: Duration::from_millis(16),
+        }
+    }
+}
+
+/// What the background simulation is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackgroundLayoutState {
+    Running,
+    /// Running without pauses between updates until it converges
+    Converging,
+    Paused,
+    /// Settled; waiting for graph changes
+    Converged,
+    Stopped,
+}
+
+/// Positions after some iterations of the simulation
+#[derive(Debug, Clone)]
+pub struct LayoutFrame {
+    pub positions: HashMap<SceneId, Position>,
+    /// Iterations since the simulation last (re)started
+    pub iteration: usize,
+    pub energy: f32,
+    pub converged: bool,
+}
+
+impl LayoutFrame {
+    /// Move scene nodes to the frame's positions, returning how many moved
+    pub fn apply_to_scene(&self, scene: &mut Scene) -> usize {
+        let mut moved = 0;
+        for (id, position) in &self.positions {
+            if let Some(node) = scene.get_node_mut(*id) {
+                node.position = *position;
+                moved += 1;
+            }
+        }
+        moved
+    }
+}
+
+enum Command {
+    Pause,
+    Resume,
+    Converge,
+    SetGraph(Vec<LayoutNode>, Vec<LayoutEdge>),
+    Pin(SceneId, Position),
+    Unpin(SceneId),
+    Stop,
+}
+
+/// Handle to a force-directed layout running on a worker thread
+///
+/// Dropping the handle stops the worker.
+pub struct BackgroundLayout {
+    commands: Sender<Command>,
+    updates: Receiver<LayoutFrame>,
+    state: Arc<Mutex<BackgroundLayoutState>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl BackgroundLayout {
+    /// Start simulating `nodes` and `edges` with `layout`
+    pub fn spawn(
+        layout: ForceDirectedLayout,
+        nodes: Vec<LayoutNode>,
+        edges: Vec<LayoutEdge>,
+        config: BackgroundLayoutConfig,
+    ) -> Self {
+        let (commands, command_rx) = mpsc::channel();
+        let (update_tx, updates) = mpsc::channel();
+        let state = Arc::new(Mutex::new(BackgroundLayoutState::Running));
+
+        let mut worker = Worker {
+            layout,
+            config,
+            nodes,
+            edges,
+            iteration: 0,
+            state: state.clone(),
+            commands: command_rx,
+            updates: update_tx,
+        };
+        let worker = std::thread::Builder::new()
+            .name("graph-layout".into())
+            .spawn(move || worker.run())
+            .expect("failed to spawn layout thread");
+
+        Self {
+            commands,
+            updates,
+            state,
+            worker: Some(worker),
+        }
+    }
+
+    pub fn state(&self) -> BackgroundLayoutState {
+        *self.state.lock().unwrap()
+    }
+
+    /// Stop simulating until resumed; the last positions stay in place
+    pub fn pause(&self) {
+        self.send(Command::Pause);
+    }
+
+    /// Continue a paused or converged simulation
+    pub fn resume(&self) {
+        self.send(Command::Resume);
+    }
+
+    /// Run without pauses between updates until the layout settles
+    pub fn converge(&self) {
+        self.send(Command::Converge);
+    }
+
+    /// Replace the simulated graph, keeping the positions of nodes already in it
+    pub fn set_graph(&self, nodes: Vec<LayoutNode>, edges: Vec<LayoutEdge>) {
+        self.send(Command::SetGraph(nodes, edges));
+    }
+
+    /// Hold a node at `position`, e.g. while the user drags it
+    pub fn pin(&self, node_id: SceneId, position: Position) {
+        self.send(Command::Pin(node_id, position));
+    }
+
+    /// Let a pinned node move again
+    pub fn unpin(&self, node_id: SceneId) {
+        self.send(Command::Unpin(node_id));
+    }
+
+    /// Latest positions since the last poll, skipping older intermediate frames
+    pub fn poll(&self) -> Option<LayoutFrame> {
+        self.updates.try_iter().last()
+    }
+
+    /// Apply the latest positions to the scene, returning whether there were any
+    pub fn apply_to_scene(&self, scene: &mut Scene) -> bool {
+        match self.poll() {
+            Some(frame) => {
+                frame.apply_to_scene(scene);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Block until the layout converges, at most for `timeout`
+    ///
+    /// Returns the converged positions, or `None` on timeout.
+    pub fn wait_converged(&self, timeout: Duration) -> Option<LayoutFrame> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.checked_duration_since(Instant::now())?;
+            match self.updates.recv_timeout(remaining) {
+                Ok(frame) if frame.converged => return Some(frame),
+                Ok(_) => {}
+                Err(_) => return None,
+            }
+        }
+    }
+
+    /// Stop the worker and wait for it to exit
+    pub fn stop(&mut self) {
+        self.send(Command::Stop);
+        if let Some(worker) = self.worker.take() {
+            if worker.join().is_err() {
+                log::error!("Layout thread panicked");
+            }
+        }
+    }
+
+    fn send(&self, command: Command) {
+        // The worker only exits on stop, after which commands have no effect
+        let _ = self.commands.send(command);
+    }
+}
+
+impl Drop for BackgroundLayout {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl ForceDirectedLayout {
+    /// Run this layout incrementally on a worker thread
+    pub fn spawn_background(self, nodes: Vec<LayoutNode>, edges: Vec<LayoutEdge>, config: BackgroundLayoutConfig) -> BackgroundLayout {
+        BackgroundLayout::spawn(self, nodes, edges, config)
+    }
+}
+
+struct Worker {
+    layout: ForceDirectedLayout,
+    config: BackgroundLayoutConfig,
+    nodes: Vec<LayoutNode>,
+    edges: Vec<LayoutEdge>,
+    iteration: usize,
+    state: Arc<Mutex<BackgroundLayoutState>>,
+    commands: Receiver<Command>,
+    updates: Sender<LayoutFrame>,
+}
+
+impl Worker {
+    fn run(&mut self) {
+        self.layout.initialize_positions(&mut self.nodes);
+        let mut last_update = Instant::now();
+
+        loop {
+            // Idle states block until told to do something
+            let command = match self.state() {
+                BackgroundLayoutState::Running | BackgroundLayoutState::Converging => match self.commands.try_recv() {
+                    Ok(command) => Some(command),
+                    Err(TryRecvError::Empty) => None,
+                    Err(TryRecvError::Disconnected) => break,
+                },
+                BackgroundLayoutState::Paused | BackgroundLayoutState::Converged => match self.commands.recv() {
+                    Ok(command) => Some(command),
+                    Err(_) => break,
+                },
+                BackgroundLayoutState::Stopped => break,
+            };
+            if let Some(command) = command {
+                self.handle(command);
+                continue;
+            }
+
+            let mut energy = f32::INFINITY;
+            for _ in 0..self.config.iterations_per_update.max(1) {
+                energy = self.layout.calculate_forces(&mut self.nodes, &self.edges);
+                self.iteration += 1;
+                if self.is_converged(energy) {
+                    break;
+                }
+            }
+            let converged = self.is_converged(energy);
+            if converged {
+                log::debug!("Background layout converged after {} iterations, energy: {:.4}", self.iteration, energy);
+                self.set_state(BackgroundLayoutState::Converged);
+            }
+            if self.publish(energy, converged).is_err() {
+                break;
+            }
+
+            if self.state() == BackgroundLayoutState::Running {
+                let elapsed = last_update.elapsed();
+                if elapsed < self.config.update_interval {
+                    // Sleep out the interval, waking early for commands
+                    match self.commands.recv_timeout(self.config.update_interval - elapsed) {
+                        Ok(command) => self.handle(command),
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+            }
+            last_update = Instant::now();
+        }
+        self.set_state(BackgroundLayoutState::Stopped);
+    }
+
+    fn handle(&mut self, command: Command) {
+        match command {
+            Command::Pause => {
+                if self.state() != BackgroundLayoutState::Converged {
+                    self.set_state(BackgroundLayoutState::Paused);
+                }
+            }
+            Command::Resume => self.restart(BackgroundLayoutState::Running),
+            Command::Converge => self.restart(BackgroundLayoutState::Converging),
+            Command::SetGraph(mut nodes, edges) => {
+                let previous: HashMap<SceneId, &LayoutNode> = self.nodes.iter().map(|node| (node.id, node)).collect();
+                for node in &mut nodes {
+                    if let Some(old) = previous.get(&node.id) {
+                        node.position = old.position;
+                        node.velocity = old.velocity;
+                    }
+                }
+                self.layout.initialize_positions(&mut nodes);
+                self.nodes = nodes;
+                self.edges = edges;
+                self.reheat();
+            }
+            Command::Pin(node_id, position) => {
+                if let Some(node) = self.nodes.iter_mut().find(|node| node.id == node_id) {
+                    node.position = position;
+                    node.velocity = nalgebra::Vector3::zeros();
+                    node.fixed = true;
+                    self.reheat();
+                }
+            }
+            Command::Unpin(node_id) => {
+                if let Some(node) = self.nodes.iter_mut().find(|node| node.id == node_id) {
+                    node.fixed = false;
+                    self.reheat();
+                }
+            }
+            Command::Stop => self.set_state(BackgroundLayoutState::Stopped),
+        }
+    }
+
+    /// Restart after a change to the graph, unless paused
+    fn reheat(&mut self) {
+        self.iteration = 0;
+        if self.state() == BackgroundLayoutState::Converged {
+            self.set_state(BackgroundLayoutState::Running);
+        }
+    }
+
+    fn restart(&mut self, state: BackgroundLayoutState) {
+        if self.state() == BackgroundLayoutState::Converged {
+            self.iteration = 0;
+        }
+        self.set_state(state);
+    }
+
+    fn is_converged(&self, energy: f32) -> bool {
+        energy < self.layout.convergence_threshold || self.iteration >= self.layout.max_iterations
+    }
+
+    fn publish(&self, energy: f32, converged: bool) -> Result<(), mpsc::SendError<LayoutFrame>> {
+        self.updates.send(LayoutFrame {
+            positions: self.nodes.iter().map(|node| (node.id, node.position)).collect(),
+            iteration: self.iteration,
+            energy,
+            converged,
+        })
+    }
+
+    fn state(&self) -> BackgroundLayoutState {
+        *self.state.lock().unwrap()
+    }
+
+    fn set_state(&self, state: BackgroundLayoutState) {
+        *self.state.lock().unwrap() = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(count: u64) -> (Vec<LayoutNode>, Vec<LayoutEdge>) {
+        let nodes = (1..=count).map(|id| LayoutNode::new(id, Position::new(id as f32, 0.5 * id as f32, 0.0))).collect();
+        let edges = (1..count).map(|id| LayoutEdge::new(id, id + 1, 1.0)).collect();
+        (nodes, edges)
+    }
+
+    #[test]
+    fn test_background_layout_converges() {
+        let (nodes, edges) = chain(6);
+        let layout = ForceDirectedLayout::new().with_max_iterations(200);
+        let background = layout.spawn_background(nodes, edges, BackgroundLayoutConfig::default());
+
+        background.converge();
+        let frame = background.wait_converged(Duration::from_secs(10)).expect("layout did not converge");
+        assert!(frame.converged);
+        assert_eq!(frame.positions.len(), 6);
+        assert_eq!(background.state(), BackgroundLayoutState::Converged);
+    }
+
+    #[test]
+    fn test_pause_stops_updates_and_pin_holds_node() {
+        let (nodes, edges) = chain(4);
+        let config = BackgroundLayoutConfig { iterations_per_update: 1, update_interval: Duration::from_millis(1) };
+        let layout = ForceDirectedLayout::new().with_max_iterations(100_000);
+        let mut background = layout.spawn_background(nodes, edges, config);
+
+        let pinned = Position::new(-5.0, -5.0, 0.0);
+        background.pin(1, pinned);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let frame = loop {
+            match background.poll() {
+                Some(frame) if frame.positions[&1] == pinned => break frame,
+                _ if Instant::now() > deadline => panic!("pinned position never streamed"),
+                _ => std::thread::sleep(Duration::from_millis(1)),
+            }
+        };
+        assert!(frame.positions[&2] != Position::new(2.0, 1.0, 0.0));
+
+        background.pause();
+        while background.state() != BackgroundLayoutState::Paused && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(background.state(), BackgroundLayoutState::Paused);
+        // Drain what was sent before the pause took effect
+        background.poll();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(background.poll().is_none());
+
+        background.resume();
+        while background.poll().is_none() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_ne!(background.state(), BackgroundLayoutState::Paused);
+        background.stop();
+        assert_eq!(background.state(), BackgroundLayoutState::Stopped);
+    }
+}
@@ -66,7 +66,7 @@ impl ForceDirectedLayout {
     }
     
     /// Calculate forces between all nodes and update positions
-    fn calculate_forces(&self, nodes: &mut [LayoutNode], edges: &[LayoutEdge]) -> f32 {
+    pub(crate) fn calculate_forces(&self, nodes: &mut [LayoutNode], edges: &[LayoutEdge]) -> f32 {
         let node_count = nodes.len();
         let mut forces = vec![Vector3::zeros(); node_count];
         
@@ -80,7 +80,9 @@ impl ForceDirectedLayout {
                 let distance = diff.magnitude();
                 
                 if distance > 0.001 {
-                    let force_magnitude = self.repulsion_strength / (distance * distance);
+                    // Repel based on the gap between node footprints
+                    let gap = (distance - nodes[i].extent - nodes[j].extent).max(0.001);
+                    let force_magnitude = self.repulsion_strength / (gap * distance);
                     let force_direction = diff.normalize();
                     let force = force_direction * force_magnitude;
                     
@@ -151,7 +153,7 @@ impl ForceDirectedLayout {
     }
     
     /// Initialize node positions randomly if not set
-    fn initialize_positions(&self, nodes: &mut [LayoutNode]) {
+    pub(crate) fn initialize_positions(&self, nodes: &mut [LayoutNode]) {
         for node in nodes.iter_mut() {
             if node.position.x == 0.0 && node.position.y == 0.0 && node.position.z == 0.0 {
                 node.position = utils::random_position(&self.bounds);
@@ -174,8 +176,9 @@ impl ForceDirectedLayout {
                     
                     let diff = pos_i - pos_j;
                     let distance = diff.magnitude().max(0.001);
+                    let gap = (distance - nodes[i].extent - nodes[j].extent).max(0.001);
                     
-                    let force_magnitude = k * k / distance;
+                    let force_magnitude = k * k / gap;
                     let force = diff.normalize() * force_magnitude;
                     
                     displacements[i] += force;
@@ -56,6 +56,10 @@ impl LayoutAlgorithm for GridLayout {
         let start_time = chrono::Utc::now();
         let mut node_positions = HashMap::new();
         
+        // Cells grow to fit the largest resized node
+        let largest_extent = nodes.iter().map(|n| n.extent).fold(0.0f32, f32::max);
+        let cell_size = self.cell_size.max(largest_extent * 2.0);
+        
         let cols = self.columns.unwrap_or_else(|| (nodes.len() as f32).sqrt().ceil() as usize);
         
         match self.variant {
@@ -64,8 +68,8 @@ impl LayoutAlgorithm for GridLayout {
                     let row = i / cols;
                     let col = i % cols;
                     
-                    let x = self.center.x + (col as f32 - cols as f32 / 2.0) * self.cell_size;
-                    let y = self.center.y + (row as f32 - (nodes.len() / cols) as f32 / 2.0) * self.cell_size;
+                    let x = self.center.x + (col as f32 - cols as f32 / 2.0) * cell_size;
+                    let y = self.center.y + (row as f32 - (nodes.len() / cols) as f32 / 2.0) * cell_size;
                     
                     let mut position = Position::new(x, y, self.center.z);
                     utils::apply_bounds(&mut position, &self.bounds);
@@ -77,9 +81,9 @@ impl LayoutAlgorithm for GridLayout {
                     let row = i / cols;
                     let col = i % cols;
                     
-                    let x_offset = if row % 2 == 1 { self.cell_size * 0.5 } else { 0.0 };
-                    let x = self.center.x + (col as f32 - cols as f32 / 2.0) * self.cell_size + x_offset;
-                    let y = self.center.y + (row as f32 - (nodes.len() / cols) as f32 / 2.0) * self.cell_size * 0.866;
+                    let x_offset = if row % 2 == 1 { cell_size * 0.5 } else { 0.0 };
+                    let x = self.center.x + (col as f32 - cols as f32 / 2.0) * cell_size + x_offset;
+                    let y = self.center.y + (row as f32 - (nodes.len() / cols) as f32 / 2.0) * cell_size * 0.866;
                     
                     let mut position = Position::new(x, y, self.center.z);
                     utils::apply_bounds(&mut position, &self.bounds);
@@ -91,8 +95,8 @@ impl LayoutAlgorithm for GridLayout {
                     let row = i / cols;
                     let col = i % cols;
                     
-                    let x = self.center.x + (col as f32 - cols as f32 / 2.0) * self.cell_size;
-                    let y = self.center.y + (row as f32 - (nodes.len() / cols) as f32 / 2.0) * self.cell_size * 0.75;
+                    let x = self.center.x + (col as f32 - cols as f32 / 2.0) * cell_size;
+                    let y = self.center.y + (row as f32 - (nodes.len() / cols) as f32 / 2.0) * cell_size * 0.75;
                     
                     let mut position = Position::new(x, y, self.center.z);
                     utils::apply_bounds(&mut position, &self.bounds);
@@ -14,6 +14,10 @@ pub mod grid;
 pub mod cluster;
 pub mod temporal;
 pub mod manager;
+pub mod background;
+pub mod registry;
+pub mod routing;
+pub mod timeline;
 
 pub use force_directed::*;
 pub use hierarchical::*;
@@ -22,8 +26,13 @@ pub use grid::*;
 pub use cluster::*;
 pub use temporal::*;
 pub use manager::*;
+pub use background::{BackgroundLayout, BackgroundLayoutConfig, BackgroundLayoutState, LayoutFrame};
+pub use registry::{LayoutPlugin, LayoutRegistry};
+pub use routing::EdgeRoutingStage;
+pub use timeline::{GraphTimeline, Lifespan, EdgeLifespan, TimelineState, TimelinePlayback, TimelineScrubber, ScrubberAction};
 
 use nalgebra::{Vector3, Point3};
+use horizonos_graph_errors::{Category, Classify, Problem, Severity};
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 
@@ -57,6 +66,26 @@ pub enum LayoutType {
         time_scale: f32,
     },
     Manual, // User-positioned nodes
+    /// Layout registered by name, e.g. by a plugin
+    Custom {
+        name: String,
+    },
+}
+
+impl LayoutType {
+    /// Name of the registered algorithm computing this layout
+    pub fn algorithm_name(&self) -> Option<&str> {
+        match self {
+            LayoutType::ForceDirected { .. } => Some(registry::builtin::FORCE_DIRECTED),
+            LayoutType::Hierarchical { .. } => Some(registry::builtin::HIERARCHICAL),
+            LayoutType::Circular { .. } => Some(registry::builtin::CIRCULAR),
+            LayoutType::Grid { .. } => Some(registry::builtin::GRID),
+            LayoutType::Cluster { .. } => Some(registry::builtin::CLUSTER),
+            LayoutType::Temporal { .. } => Some(registry::builtin::TEMPORAL),
+            LayoutType::Manual => None,
+            LayoutType::Custom { name } => Some(name),
+        }
+    }
 }
 
 /// Direction for hierarchical layouts
@@ -84,6 +113,7 @@ pub struct LayoutNode {
     pub velocity: Vector3<f32>,
     pub mass: f32,
     pub fixed: bool, // Whether the node position is locked
+    pub extent: f32, // Radius of the area the node occupies
     pub cluster_id: Option<String>,
     pub timestamp: Option<chrono::DateTime<chrono::Utc>>,
 }
@@ -148,6 +178,21 @@ pub enum LayoutError {
     SystemError { message: String },
 }
 
+impl Classify for LayoutError {
+    fn classify(&self) -> Problem {
+        let problem = match self {
+            LayoutError::InvalidConfiguration { .. } => Problem::new("layout.configuration", Category::Configuration, Severity::Warning, "Layout settings are invalid"),
+            LayoutError::CalculationFailed { .. } | LayoutError::SystemError { .. } => {
+                Problem::new("layout.failed", Category::Graph, Severity::Warning, "The layout could not be applied")
+            }
+            LayoutError::NodeNotFound { id } => Problem::new("layout.node_not_found", Category::Graph, Severity::Info, "Node no longer exists").with_node(*id),
+            LayoutError::InsufficientNodes { .. } => Problem::new("layout.too_few_nodes", Category::Graph, Severity::Info, "Not enough nodes for this layout"),
+            LayoutError::Timeout => Problem::new("layout.timeout", Category::Graph, Severity::Warning, "The layout took too long").transient(),
+        };
+        problem.in_subsystem("layout").with_detail(self)
+    }
+}
+
 /// Trait for layout algorithms
 pub trait LayoutAlgorithm: Send + Sync {
     /// Calculate new positions for nodes
@@ -195,6 +240,7 @@ impl LayoutNode {
             velocity: Vector3::zeros(),
             mass: 1.0,
             fixed: false,
+            extent: 0.0,
             cluster_id: None,
             timestamp: None,
         }
@@ -205,6 +251,11 @@ impl LayoutNode {
         self
     }
     
+    pub fn with_extent(mut self, extent: f32) -> Self {
+        self.extent = extent;
+        self
+    }
+    
     pub fn with_cluster(mut self, cluster_id: String) -> Self {
         self.cluster_id = Some(cluster_id);
         self
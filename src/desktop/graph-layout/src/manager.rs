@@ -2,9 +2,9 @@
 
 use crate::{
     LayoutAlgorithm, LayoutNode, LayoutEdge, LayoutResult, LayoutError, LayoutType, 
-    LayoutAnimationSettings, LayoutBounds, ForceDirectedLayout, utils
+    LayoutAnimationSettings, LayoutBounds, LayoutPlugin, LayoutRegistry, EdgeRoutingStage, utils
 };
-use horizonos_graph_engine::{SceneId, Position};
+use horizonos_graph_engine::{EdgeRoute, EdgeRoutingConfig, SceneId, Position};
 use horizonos_graph_nodes::GraphNode;
 use horizonos_graph_edges::{GraphEdge, EdgeManager};
 use std::collections::HashMap;
@@ -15,7 +15,7 @@ use serde::{Serialize, Deserialize};
 /// Main layout manager that coordinates different layout algorithms
 pub struct LayoutManager {
     current_layout: LayoutType,
-    layout_algorithms: HashMap<String, Box<dyn LayoutAlgorithm>>,
+    registry: LayoutRegistry,
     animation_settings: LayoutAnimationSettings,
     bounds: LayoutBounds,
     node_positions: Arc<RwLock<HashMap<SceneId, Position>>>,
@@ -23,6 +23,7 @@ pub struct LayoutManager {
     layout_cache: Arc<Mutex<HashMap<String, LayoutResult>>>,
     auto_layout_enabled: bool,
     layout_stats: LayoutStatistics,
+    edge_routing: EdgeRoutingStage,
 }
 
 /// Statistics about layout operations
@@ -69,9 +70,9 @@ pub enum LayoutEventType {
 
 impl LayoutManager {
     pub fn new() -> Self {
-        let mut manager = LayoutManager {
+        LayoutManager {
             current_layout: LayoutType::default(),
-            layout_algorithms: HashMap::new(),
+            registry: LayoutRegistry::with_builtin(),
             animation_settings: LayoutAnimationSettings::default(),
             bounds: LayoutBounds::default(),
             node_positions: Arc::new(RwLock::new(HashMap::new())),
@@ -79,30 +80,58 @@ impl LayoutManager {
             layout_cache: Arc::new(Mutex::new(HashMap::new())),
             auto_layout_enabled: true,
             layout_stats: LayoutStatistics::default(),
-        };
-        
-        // Register default algorithms
-        manager.register_algorithm("force_directed", Box::new(ForceDirectedLayout::new()));
-        
-        manager
+            edge_routing: EdgeRoutingStage::new(EdgeRoutingConfig::default()),
+        }
     }
     
-    /// Register a layout algorithm
+    /// Register a layout algorithm, replacing one of the same name
     pub fn register_algorithm(&mut self, name: &str, algorithm: Box<dyn LayoutAlgorithm>) {
-        self.layout_algorithms.insert(name.to_string(), algorithm);
-        log::info!("Registered layout algorithm: {}", name);
+        self.registry.register(name, algorithm);
+    }
+    
+    /// Register the layouts of a plugin
+    pub fn load_plugin(&mut self, plugin: &dyn LayoutPlugin) {
+        self.registry.load_plugin(plugin);
+    }
+    
+    /// Layout algorithms available by name
+    pub fn registry(&self) -> &LayoutRegistry {
+        &self.registry
+    }
+    
+    /// Switch to a registered layout by name, e.g. a workspace's choice
+    ///
+    /// Built-in layouts keep their current settings when the current layout
+    /// already uses them.
+    pub fn set_algorithm(&mut self, name: &str) -> Result<(), LayoutError> {
+        if !self.registry.contains(name) {
+            return Err(LayoutError::InvalidConfiguration {
+                message: format!("Layout algorithm '{}' is not registered", name),
+            });
+        }
+        if self.current_layout.algorithm_name() == Some(name) {
+            return Ok(());
+        }
+        self.set_layout_type(LayoutType::Custom { name: name.to_string() })
+    }
+    
+    /// Name of the algorithm of the current layout, `None` for manual positioning
+    pub fn current_algorithm(&self) -> Option<&str> {
+        self.current_layout.algorithm_name()
     }
     
     /// Set the current layout type
     pub fn set_layout_type(&mut self, layout_type: LayoutType) -> Result<(), LayoutError> {
         self.current_layout = layout_type;
         
-        // Clear cache when layout type changes
-        let cache = self.layout_cache.clone();
-        tokio::spawn(async move {
-            let mut cache = cache.lock().await;
-            cache.clear();
-        });
+        // Clear cache when layout type changes, later if a layout is using it
+        let cleared = self.layout_cache.try_lock().map(|mut cache| cache.clear()).is_ok();
+        if !cleared {
+            if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+                let cache = self.layout_cache.clone();
+                runtime.spawn(async move { cache.lock().await.clear() });
+            }
+        }
         
         log::info!("Changed layout type to: {:?}", self.current_layout);
         Ok(())
@@ -123,11 +152,20 @@ impl LayoutManager {
         let layout_edges = self.convert_to_layout_edges(edge_manager, &layout_nodes);
         
         // Get appropriate algorithm
-        let algorithm = self.get_algorithm_for_layout_type(&self.current_layout)?;
+        let algorithm = self.registry.resolve(&self.current_layout)?;
         
         // Calculate layout
         let result = algorithm.calculate_layout(&layout_nodes, &layout_edges)?;
         
+        // Bend edges around the nodes at their new positions
+        let mut placed_nodes = layout_nodes.clone();
+        for node in &mut placed_nodes {
+            if let Some(position) = result.node_positions.get(&node.id) {
+                node.position = *position;
+            }
+        }
+        self.edge_routing.route_all(&placed_nodes, &layout_edges);
+        
         // Update positions
         {
             let mut positions = self.node_positions.write().unwrap();
@@ -170,7 +208,7 @@ impl LayoutManager {
             }
         }
         
-        let algorithm = self.get_algorithm_for_layout_type(&self.current_layout)?;
+        let algorithm = self.registry.resolve(&self.current_layout)?;
         
         if algorithm.supports_incremental() {
             let energy = algorithm.update_layout(&mut layout_nodes, &layout_edges, delta_time)?;
@@ -182,6 +220,7 @@ impl LayoutManager {
                     positions.insert(node.id, node.position);
                 }
             }
+            self.edge_routing.update(&layout_nodes, &layout_edges);
             
             Ok(energy)
         } else {
@@ -205,6 +244,17 @@ impl LayoutManager {
         log::debug!("Manually set position for node {}: {:?}", node_id, position);
     }
     
+    /// Route of the edge between two nodes from the last layout, bent
+    /// around nodes it would otherwise pass through
+    pub fn edge_route(&self, source: SceneId, target: SceneId) -> Option<&EdgeRoute> {
+        self.edge_routing.route(source, target)
+    }
+    
+    /// Change edge routing settings; edges are routed again on the next layout
+    pub fn set_edge_routing(&mut self, config: EdgeRoutingConfig) {
+        self.edge_routing.set_config(config);
+    }
+    
     /// Get all current node positions
     pub fn get_all_positions(&self) -> HashMap<SceneId, Position> {
         let positions = self.node_positions.read().unwrap();
@@ -220,7 +270,9 @@ impl LayoutManager {
                 .copied()
                 .unwrap_or_else(|| utils::random_position(&self.bounds));
             
+            // Resized nodes need more room
             LayoutNode::new(node.id(), position)
+                .with_extent(node.visual_data().footprint_radius())
         }).collect()
     }
     
@@ -250,59 +302,6 @@ impl LayoutManager {
         layout_edges
     }
     
-    /// Get algorithm for current layout type
-    fn get_algorithm_for_layout_type(&self, layout_type: &LayoutType) -> Result<&dyn LayoutAlgorithm, LayoutError> {
-        match layout_type {
-            LayoutType::ForceDirected { .. } => {
-                self.layout_algorithms.get("force_directed")
-                    .map(|alg| alg.as_ref())
-                    .ok_or_else(|| LayoutError::InvalidConfiguration { 
-                        message: "Force-directed algorithm not registered".to_string() 
-                    })
-            }
-            LayoutType::Hierarchical { .. } => {
-                self.layout_algorithms.get("hierarchical")
-                    .map(|alg| alg.as_ref())
-                    .ok_or_else(|| LayoutError::InvalidConfiguration { 
-                        message: "Hierarchical algorithm not registered".to_string() 
-                    })
-            }
-            LayoutType::Circular { .. } => {
-                self.layout_algorithms.get("circular")
-                    .map(|alg| alg.as_ref())
-                    .ok_or_else(|| LayoutError::InvalidConfiguration { 
-                        message: "Circular algorithm not registered".to_string() 
-                    })
-            }
-            LayoutType::Grid { .. } => {
-                self.layout_algorithms.get("grid")
-                    .map(|alg| alg.as_ref())
-                    .ok_or_else(|| LayoutError::InvalidConfiguration { 
-                        message: "Grid algorithm not registered".to_string() 
-                    })
-            }
-            LayoutType::Cluster { .. } => {
-                self.layout_algorithms.get("cluster")
-                    .map(|alg| alg.as_ref())
-                    .ok_or_else(|| LayoutError::InvalidConfiguration { 
-                        message: "Cluster algorithm not registered".to_string() 
-                    })
-            }
-            LayoutType::Temporal { .. } => {
-                self.layout_algorithms.get("temporal")
-                    .map(|alg| alg.as_ref())
-                    .ok_or_else(|| LayoutError::InvalidConfiguration { 
-                        message: "Temporal algorithm not registered".to_string() 
-                    })
-            }
-            LayoutType::Manual => {
-                Err(LayoutError::InvalidConfiguration { 
-                    message: "Manual layout does not use algorithms".to_string() 
-                })
-            }
-        }
-    }
-    
     /// Start smooth animation between current and target positions
     async fn start_position_animation(&self, target_positions: &HashMap<SceneId, Position>) {
         let mut targets = self.target_positions.write().unwrap();
@@ -327,7 +326,7 @@ impl LayoutManager {
         }
         
         // Update layout type usage
-        let layout_name = format!("{:?}", self.current_layout);
+        let layout_name = self.current_layout.algorithm_name().unwrap_or("manual").to_string();
         *self.layout_stats.layout_type_usage.entry(layout_name).or_insert(0) += 1;
         
         // Update convergence rate
@@ -432,6 +431,7 @@ impl Default for LayoutConfiguration {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ForceDirectedLayout;
     use horizonos_graph_nodes::{BaseNode, NodeVisualData, NodeAction, NodeActionResult, NodeActionType, NodeError, NodeExportData};
     use horizonos_graph_engine::{SceneNode, NodeType, Vec3};
     
@@ -479,7 +479,7 @@ mod tests {
     #[test]
     fn test_layout_manager_creation() {
         let manager = LayoutManager::new();
-        assert!(manager.layout_algorithms.contains_key("force_directed"));
+        assert!(manager.registry.contains("force-directed"));
         assert!(manager.auto_layout_enabled);
     }
     
@@ -489,7 +489,7 @@ mod tests {
         let algorithm = Box::new(ForceDirectedLayout::new());
         
         manager.register_algorithm("test_algorithm", algorithm);
-        assert!(manager.layout_algorithms.contains_key("test_algorithm"));
+        assert!(manager.registry.contains("test_algorithm"));
     }
     
     #[test]
@@ -505,6 +505,19 @@ mod tests {
         assert_eq!(manager.current_layout, layout_type);
     }
     
+    #[test]
+    fn test_algorithm_selection_by_name() {
+        let mut manager = LayoutManager::new();
+        assert_eq!(manager.current_algorithm(), Some("force-directed"));
+        
+        manager.set_algorithm("force-directed").unwrap();
+        assert_eq!(manager.current_layout, LayoutType::default());
+        
+        manager.set_algorithm("grid").unwrap();
+        assert_eq!(manager.current_algorithm(), Some("grid"));
+        assert!(manager.set_algorithm("spiral").is_err());
+    }
+    
     #[test]
     fn test_node_position_management() {
         let mut manager = LayoutManager::new();
@@ -0,0 +1,133 @@
+//! Registry of layout algorithms by name
+//!
+//! Layouts are looked up by name rather than by [`LayoutType`] variant, so
+//! layouts that are not built in can be registered while the desktop runs,
+//! either directly or through a [`LayoutPlugin`]. Names are what workspaces
+//! and `GraphConfig.layout_algorithm` store.
+
+use crate::{
+    CircularLayout, ClusterLayout, ForceDirectedLayout, GridLayout, HierarchicalLayout, LayoutAlgorithm,
+    LayoutError, LayoutType, ScalableForceDirectedLayout, TemporalLayout,
+};
+use std::collections::HashMap;
+
+/// Names of the built-in layouts
+pub mod builtin {
+    pub const FORCE_DIRECTED: &str = "force-directed";
+    pub const SCALABLE_FORCE_DIRECTED: &str = "scalable-force-directed";
+    pub const HIERARCHICAL: &str = "hierarchical";
+    pub const CIRCULAR: &str = "circular";
+    pub const GRID: &str = "grid";
+    pub const CLUSTER: &str = "cluster";
+    pub const TEMPORAL: &str = "temporal";
+}
+
+/// A set of layouts registered together, e.g. by an extension
+pub trait LayoutPlugin {
+    /// Name shown in logs
+    fn name(&self) -> &str;
+
+    /// Register the plugin's layouts
+    fn register(&self, registry: &mut LayoutRegistry);
+}
+
+/// Layout algorithms by name
+#[derive(Default)]
+pub struct LayoutRegistry {
+    algorithms: HashMap<String, Box<dyn LayoutAlgorithm>>,
+}
+
+impl LayoutRegistry {
+    /// Empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with the built-in layouts
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(builtin::FORCE_DIRECTED, Box::new(ForceDirectedLayout::new()));
+        registry.register(builtin::SCALABLE_FORCE_DIRECTED, Box::new(ScalableForceDirectedLayout::new()));
+        registry.register(builtin::HIERARCHICAL, Box::new(HierarchicalLayout::new()));
+        registry.register(builtin::CIRCULAR, Box::new(CircularLayout::new()));
+        registry.register(builtin::GRID, Box::new(GridLayout::new()));
+        registry.register(builtin::CLUSTER, Box::new(ClusterLayout::new()));
+        registry.register(builtin::TEMPORAL, Box::new(TemporalLayout::new()));
+        registry
+    }
+
+    /// Register a layout, returning the one it replaces
+    pub fn register(&mut self, name: &str, algorithm: Box<dyn LayoutAlgorithm>) -> Option<Box<dyn LayoutAlgorithm>> {
+        log::info!("Registered layout algorithm: {} ({})", name, algorithm.name());
+        self.algorithms.insert(name.to_string(), algorithm)
+    }
+
+    /// Register the layouts of a plugin
+    pub fn load_plugin(&mut self, plugin: &dyn LayoutPlugin) {
+        let before = self.algorithms.len();
+        plugin.register(self);
+        log::info!(
+            "Loaded layout plugin {} ({} new layouts)",
+            plugin.name(),
+            self.algorithms.len().saturating_sub(before)
+        );
+    }
+
+    pub fn unregister(&mut self, name: &str) -> Option<Box<dyn LayoutAlgorithm>> {
+        self.algorithms.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn LayoutAlgorithm> {
+        self.algorithms.get(name).map(|algorithm| algorithm.as_ref())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.algorithms.contains_key(name)
+    }
+
+    /// Registered names, sorted
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.algorithms.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Algorithm used for a layout type
+    pub fn resolve(&self, layout_type: &LayoutType) -> Result<&dyn LayoutAlgorithm, LayoutError> {
+        let name = layout_type.algorithm_name().ok_or_else(|| LayoutError::InvalidConfiguration {
+            message: "Manual layout does not use algorithms".to_string(),
+        })?;
+        self.get(name).ok_or_else(|| LayoutError::InvalidConfiguration {
+            message: format!("Layout algorithm '{}' is not registered", name),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct SpiralPlugin;
+
+    impl LayoutPlugin for SpiralPlugin {
+        fn name(&self) -> &str {
+            "spiral"
+        }
+
+        fn register(&self, registry: &mut LayoutRegistry) {
+            registry.register("spiral", Box::new(CircularLayout::new()));
+        }
+    }
+
+    #[test]
+    fn test_plugin_layouts_resolve_by_name() {
+        let mut registry = LayoutRegistry::with_builtin();
+        assert!(registry.resolve(&LayoutType::Custom { name: "spiral".to_string() }).is_err());
+
+        registry.load_plugin(&SpiralPlugin);
+        assert!(registry.names().contains(&"spiral"));
+        assert_eq!(registry.resolve(&LayoutType::Custom { name: "spiral".to_string() }).unwrap().name(), "Circular");
+        assert_eq!(registry.resolve(&LayoutType::default()).unwrap().name(), "Force-Directed");
+        assert!(registry.resolve(&LayoutType::Manual).is_err());
+    }
+}
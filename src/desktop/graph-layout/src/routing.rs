@@ -0,0 +1,134 @@
+//! Edge routing stage run on layout results
+//!
+//! Bends edges around the bounding spheres of unrelated nodes they would
+//! pass through, so a finished layout comes with curves that can be drawn
+//! as cubic Bézier segments. Routing is done by the engine's edge router and
+//! is incremental: only edges whose endpoints moved, or that pass near a
+//! node that moved, are recomputed.
+
+use crate::{LayoutEdge, LayoutNode};
+use horizonos_graph_engine::{EdgeRoute, EdgeRouter, EdgeRoutingConfig, RoutingStats, SceneId};
+use std::collections::{HashMap, HashSet};
+
+/// Radius of nodes whose extent is unknown
+const DEFAULT_NODE_RADIUS: f32 = 1.0;
+
+/// Routes the edges of a layout around its nodes
+#[derive(Debug, Default)]
+pub struct EdgeRoutingStage {
+    router: EdgeRouter,
+    /// Router edge ID of each edge, by endpoints
+    edge_ids: HashMap<(SceneId, SceneId), SceneId>,
+    next_edge_id: SceneId,
+}
+
+impl EdgeRoutingStage {
+    /// Create a routing stage with the given settings
+    pub fn new(config: EdgeRoutingConfig) -> Self {
+        Self {
+            router: EdgeRouter::new(config),
+            ..Self::default()
+        }
+    }
+
+    /// Current settings
+    pub fn config(&self) -> &EdgeRoutingConfig {
+        self.router.config()
+    }
+
+    /// Change settings; all edges are routed again
+    pub fn set_config(&mut self, config: EdgeRoutingConfig) {
+        self.router.set_config(config);
+    }
+
+    /// Route of the edge between two nodes, if it has been computed
+    pub fn route(&self, source: SceneId, target: SceneId) -> Option<&EdgeRoute> {
+        self.edge_ids.get(&(source, target)).and_then(|id| self.router.route(*id))
+    }
+
+    /// Counters from the last update
+    pub fn stats(&self) -> RoutingStats {
+        self.router.stats()
+    }
+
+    /// Reroute edges affected by node movement since the last update, within
+    /// the router's per-update budget; for layouts updated every frame
+    pub fn update(&mut self, nodes: &[LayoutNode], edges: &[LayoutEdge]) {
+        let edges = self.identify(edges);
+        self.router.update_from(
+            nodes.iter().map(|node| (node.id, node.position, Self::radius(node))),
+            edges,
+        );
+    }
+
+    /// Reroute every affected edge, for a layout that was just calculated
+    pub fn route_all(&mut self, nodes: &[LayoutNode], edges: &[LayoutEdge]) {
+        self.update(nodes, edges);
+        while self.router.stats().pending > 0 {
+            self.update(nodes, edges);
+        }
+    }
+
+    /// Router edges for layout edges, forgetting edges that are gone
+    fn identify(&mut self, edges: &[LayoutEdge]) -> Vec<(SceneId, SceneId, SceneId)> {
+        let mut present = HashSet::new();
+        let mut identified = Vec::with_capacity(edges.len());
+        for edge in edges {
+            let key = (edge.source, edge.target);
+            if !present.insert(key) {
+                continue;
+            }
+            let id = *self.edge_ids.entry(key).or_insert_with(|| {
+                self.next_edge_id += 1;
+                self.next_edge_id
+            });
+            identified.push((id, edge.source, edge.target));
+        }
+        self.edge_ids.retain(|key, _| present.contains(key));
+        identified
+    }
+
+    fn radius(node: &LayoutNode) -> f32 {
+        if node.extent > 0.0 {
+            node.extent
+        } else {
+            DEFAULT_NODE_RADIUS
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Position;
+
+    #[test]
+    fn test_edges_bend_around_nodes_and_follow_moves() {
+        let mut nodes = vec![
+            LayoutNode::new(1, Position::new(0.0, 0.0, 0.0)).with_extent(2.0),
+            LayoutNode::new(2, Position::new(10.0, 0.0, 0.0)).with_extent(2.0),
+            LayoutNode::new(3, Position::new(5.0, 0.0, 0.0)).with_extent(2.0),
+            LayoutNode::new(4, Position::new(5.0, 0.0, 50.0)),
+        ];
+        let edges = vec![LayoutEdge::new(1, 2, 1.0), LayoutEdge::new(3, 4, 1.0)];
+
+        let mut stage = EdgeRoutingStage::new(EdgeRoutingConfig::default());
+        stage.route_all(&nodes, &edges);
+        assert_eq!(stage.stats().pending, 0);
+
+        let route = stage.route(1, 2).unwrap();
+        assert!(route.bent);
+        assert!(route.points.iter().all(|p| (p - nodes[2].position).norm() > 2.0));
+        assert!(!route.cubic_segments().is_empty());
+        assert!(!stage.route(3, 4).unwrap().bent);
+
+        // Moving the obstacle out of the way straightens the edge again
+        nodes[2].position.y = 20.0;
+        stage.update(&nodes, &edges);
+        assert!(!stage.route(1, 2).unwrap().bent);
+
+        // Removed edges lose their routes
+        stage.update(&nodes, &edges[1..]);
+        assert!(stage.route(1, 2).is_none());
+    }
+}
@@ -0,0 +1,534 @@
+//! Exploring the graph's history over time
+//!
+//! A [`GraphTimeline`] records when nodes and edges were created and
+//! removed, seeded from node creation times and kept up to date from scene
+//! changes. [`TimelinePlayback`] moves a time cursor through the recorded
+//! range, by seeking or by playing it back at a chosen speed, and gives the
+//! [`TimelineState`] at the cursor: which elements exist and how far they
+//! have faded in or out. Play position and settings are kept in the
+//! engine's [`PlaybackClock`], and the look of the scene before playback in
+//! a [`PlaybackBase`], as for cluster timelines. With the temporal layout, nodes are placed by
+//! creation time over the whole recorded range, so they keep their place
+//! while the cursor moves and new ones appear along the time axis.
+//!
+//! [`TimelineScrubber`] is the on-screen control: a track spanning the
+//! recorded range, with a handle at the cursor that is dragged to seek.
+
+use crate::{LayoutAlgorithm, LayoutEdge, LayoutError, LayoutNode, LayoutResult, TemporalLayout};
+use chrono::{DateTime, Duration as TimeDelta, Utc};
+use horizonos_graph_engine::{Change, PlaybackBase, PlaybackClock, PlaybackConfig, Scene, SceneId};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// When an element existed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lifespan {
+    pub created: DateTime<Utc>,
+    /// `None` while the element still exists
+    pub removed: Option<DateTime<Utc>>,
+}
+
+impl Lifespan {
+    /// How far the element has appeared at `at`: 0 before creation and
+    /// after removal, 1 while it exists, ramping over `fade` in between
+    pub fn appearance(&self, at: DateTime<Utc>, fade: TimeDelta) -> f32 {
+        let ramp = |since: TimeDelta| {
+            if fade <= TimeDelta::zero() {
+                1.0
+            } else {
+                (since.num_milliseconds() as f32 / fade.num_milliseconds() as f32).clamp(0.0, 1.0)
+            }
+        };
+        if at < self.created {
+            return 0.0;
+        }
+        let appeared = ramp(at - self.created);
+        match self.removed {
+            Some(removed) if at >= removed => appeared.min(1.0 - ramp(at - removed)),
+            _ => appeared,
+        }
+    }
+}
+
+/// Lifespan of an edge and the nodes it connects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EdgeLifespan {
+    pub source: SceneId,
+    pub target: SceneId,
+    pub lifespan: Lifespan,
+}
+
+/// Creation and removal times of the graph's nodes and edges
+#[derive(Debug, Clone, Default)]
+pub struct GraphTimeline {
+    nodes: HashMap<SceneId, Lifespan>,
+    edges: HashMap<SceneId, EdgeLifespan>,
+}
+
+impl GraphTimeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Timeline of the current scene
+    ///
+    /// Nodes are dated by their creation time. Edges carry no time of their
+    /// own and are dated when the later of their two nodes was created.
+    pub fn from_scene(scene: &Scene) -> Self {
+        let mut timeline = Self::new();
+        for (id, node) in scene.nodes() {
+            timeline.record_node_created(*id, node.metadata.created_at);
+        }
+        for edge in scene.edges() {
+            let created = [edge.source, edge.target].iter()
+                .filter_map(|id| timeline.nodes.get(id))
+                .map(|lifespan| lifespan.created)
+                .max();
+            if let Some(created) = created {
+                timeline.record_edge_created(edge.id, edge.source, edge.target, created);
+            }
+        }
+        timeline
+    }
+
+    /// Record a new node, or one that came back, e.g. by undoing its removal
+    pub fn record_node_created(&mut self, id: SceneId, at: DateTime<Utc>) {
+        self.nodes.entry(id)
+            .and_modify(|lifespan| lifespan.removed = None)
+            .or_insert(Lifespan { created: at, removed: None });
+    }
+
+    pub fn record_node_removed(&mut self, id: SceneId, at: DateTime<Utc>) {
+        if let Some(lifespan) = self.nodes.get_mut(&id) {
+            lifespan.removed = Some(at);
+        }
+    }
+
+    /// Record a new edge, or one that came back
+    pub fn record_edge_created(&mut self, id: SceneId, source: SceneId, target: SceneId, at: DateTime<Utc>) {
+        self.edges.entry(id)
+            .and_modify(|edge| edge.lifespan.removed = None)
+            .or_insert(EdgeLifespan { source, target, lifespan: Lifespan { created: at, removed: None } });
+    }
+
+    pub fn record_edge_removed(&mut self, id: SceneId, at: DateTime<Utc>) {
+        if let Some(edge) = self.edges.get_mut(&id) {
+            edge.lifespan.removed = Some(at);
+        }
+    }
+
+    /// Record a scene change made at `at`, e.g. from the command history
+    pub fn record_change(&mut self, change: &Change, at: DateTime<Utc>) {
+        match change {
+            Change::NodeAdded(node) => self.record_node_created(node.id, at),
+            Change::NodeRemoved { node, edges } => {
+                self.record_node_removed(node.id, at);
+                for edge in edges {
+                    self.record_edge_removed(edge.id, at);
+                }
+            }
+            Change::NodeMoved { .. } | Change::NodeUpdated { .. } => {}
+            Change::EdgeAdded(edge) => self.record_edge_created(edge.id, edge.source, edge.target, at),
+            Change::EdgeRemoved(edge) => self.record_edge_removed(edge.id, at),
+        }
+    }
+
+    pub fn node(&self, id: SceneId) -> Option<&Lifespan> {
+        self.nodes.get(&id)
+    }
+
+    pub fn edge(&self, id: SceneId) -> Option<&EdgeLifespan> {
+        self.edges.get(&id)
+    }
+
+    /// First and last recorded time, `None` while nothing was recorded
+    pub fn range(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let times = self.nodes.values()
+            .chain(self.edges.values().map(|edge| &edge.lifespan))
+            .flat_map(|lifespan| std::iter::once(lifespan.created).chain(lifespan.removed));
+        times.fold(None, |range, at| match range {
+            None => Some((at, at)),
+            Some((start, end)) => Some((start.min(at), end.max(at))),
+        })
+    }
+
+    /// Times at which something was created or removed, in order, for scrubber ticks
+    pub fn event_times(&self) -> Vec<DateTime<Utc>> {
+        let mut times: Vec<DateTime<Utc>> = self.nodes.values()
+            .chain(self.edges.values().map(|edge| &edge.lifespan))
+            .flat_map(|lifespan| std::iter::once(lifespan.created).chain(lifespan.removed))
+            .collect();
+        times.sort();
+        times.dedup();
+        times
+    }
+
+    /// Which nodes and edges exist at `at`, and how far they have faded in or out
+    pub fn state_at(&self, at: DateTime<Utc>, fade: TimeDelta) -> TimelineState {
+        let visible = |lifespan: &Lifespan| Some(lifespan.appearance(at, fade)).filter(|appearance| *appearance > 0.0);
+        TimelineState {
+            at,
+            nodes: self.nodes.iter()
+                .filter_map(|(id, lifespan)| visible(lifespan).map(|appearance| (*id, appearance)))
+                .collect(),
+            edges: self.edges.iter()
+                .filter_map(|(id, edge)| visible(&edge.lifespan).map(|appearance| (*id, appearance)))
+                .collect(),
+        }
+    }
+}
+
+/// The graph at one point in time
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineState {
+    pub at: DateTime<Utc>,
+    /// Nodes present, with how far they have appeared (0 to 1)
+    pub nodes: HashMap<SceneId, f32>,
+    /// Edges present, with how far they have appeared (0 to 1)
+    pub edges: HashMap<SceneId, f32>,
+}
+
+impl TimelineState {
+    /// Show the scene as it was: absent elements hidden, fading ones shrunk and translucent
+    ///
+    /// Sizes and colors are taken from `base`, so states can be applied
+    /// repeatedly while scrubbing; elements the timeline does not know keep
+    /// their look.
+    pub fn apply(&self, scene: &mut Scene, base: &PlaybackBase, timeline: &GraphTimeline) {
+        for (id, look) in base.nodes() {
+            if timeline.node(id).is_none() {
+                continue;
+            }
+            if let Some(node) = scene.get_node_mut(id) {
+                let appearance = self.nodes.get(&id).copied().unwrap_or(0.0);
+                node.visible = look.visible && appearance > 0.0;
+                node.radius = look.radius * appearance;
+                node.color = look.color;
+                node.color[3] *= appearance;
+            }
+        }
+        for (id, look) in base.edges() {
+            if timeline.edge(id).is_none() {
+                continue;
+            }
+            if let Some(edge) = scene.get_edge_mut(id) {
+                let appearance = self.edges.get(&id).copied().unwrap_or(0.0);
+                edge.visible = look.visible && appearance > 0.0;
+                edge.color = look.color;
+                edge.color[3] *= appearance;
+            }
+        }
+    }
+}
+
+/// A time cursor over a graph timeline
+#[derive(Debug, Clone)]
+pub struct TimelinePlayback {
+    timeline: GraphTimeline,
+    clock: PlaybackClock,
+}
+
+impl TimelinePlayback {
+    /// Start at the end of the timeline, showing the graph as it is now
+    ///
+    /// Elements fade in and out over the config's grow time; looping
+    /// playback starts over at the end.
+    pub fn new(timeline: GraphTimeline, config: PlaybackConfig) -> Self {
+        Self { timeline, clock: PlaybackClock::new(config, 1.0) }
+    }
+
+    pub fn timeline(&self) -> &GraphTimeline {
+        &self.timeline
+    }
+
+    /// Record changes made while exploring
+    pub fn timeline_mut(&mut self) -> &mut GraphTimeline {
+        &mut self.timeline
+    }
+
+    /// Play position and settings
+    pub fn clock(&self) -> &PlaybackClock {
+        &self.clock
+    }
+
+    pub fn clock_mut(&mut self) -> &mut PlaybackClock {
+        &mut self.clock
+    }
+
+    /// Time at the cursor; now while nothing is recorded
+    pub fn cursor(&self) -> DateTime<Utc> {
+        match self.timeline.range() {
+            Some((start, end)) => self.clock.time_in(start, end),
+            None => Utc::now(),
+        }
+    }
+
+    /// Move the cursor, clamped to the recorded range
+    pub fn seek(&mut self, at: DateTime<Utc>) {
+        if let Some((start, end)) = self.timeline.range() {
+            let length = (end - start).num_milliseconds().max(1) as f64;
+            self.clock.seek_fraction((at - start).num_milliseconds() as f64 / length);
+        }
+    }
+
+    /// Move the cursor to a fraction of the recorded range
+    pub fn seek_fraction(&mut self, fraction: f64) {
+        self.clock.seek_fraction(fraction);
+    }
+
+    /// Cursor as a fraction of the recorded range
+    pub fn fraction(&self) -> f64 {
+        self.clock.position()
+    }
+
+    /// Play forward from the cursor, from the start if it is at the end
+    pub fn play(&mut self) {
+        self.clock.play();
+    }
+
+    pub fn pause(&mut self) {
+        self.clock.pause();
+    }
+
+    pub fn toggle(&mut self) {
+        self.clock.toggle();
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.clock.is_playing()
+    }
+
+    /// Playback speed multiplier; negative plays backwards
+    pub fn set_speed(&mut self, speed: f64) {
+        self.clock.set_speed(speed);
+    }
+
+    /// Move the cursor by wall-clock time while playing
+    ///
+    /// Returns whether the cursor moved.
+    pub fn advance(&mut self, elapsed: Duration) -> bool {
+        self.timeline.range().is_some() && self.clock.advance(elapsed)
+    }
+
+    /// The graph at the cursor
+    pub fn state(&self) -> TimelineState {
+        let fade = match self.timeline.range() {
+            Some((start, end)) => self.clock.timeline_time(self.clock.config().grow_time, end - start),
+            None => TimeDelta::zero(),
+        };
+        self.timeline.state_at(self.cursor(), fade)
+    }
+
+    /// Temporal layout of the nodes present at the cursor
+    ///
+    /// The layout spans the whole recorded range, so nodes do not move as
+    /// the cursor does; nodes and edges absent at the cursor are left out.
+    pub fn layout(&self, layout: &TemporalLayout, nodes: &[LayoutNode], edges: &[LayoutEdge]) -> Result<LayoutResult, LayoutError> {
+        let state = self.state();
+        let present: Vec<LayoutNode> = nodes.iter()
+            .filter(|node| state.nodes.contains_key(&node.id))
+            .map(|node| {
+                let created = self.timeline.node(node.id).map(|lifespan| lifespan.created);
+                LayoutNode { timestamp: created.or(node.timestamp), ..node.clone() }
+            })
+            .collect();
+        let edges: Vec<LayoutEdge> = edges.iter()
+            .filter(|edge| state.nodes.contains_key(&edge.source) && state.nodes.contains_key(&edge.target))
+            .cloned()
+            .collect();
+        let spanning = TemporalLayout {
+            time_axis: layout.time_axis.clone(),
+            time_scale: layout.time_scale,
+            bounds: layout.bounds.clone(),
+            variant: layout.variant.clone(),
+            time_range: self.timeline.range().or(layout.time_range),
+        };
+        spanning.calculate_layout(&present, &edges)
+    }
+}
+
+/// What the scrubber asks for after pointer input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrubberAction {
+    /// Move the cursor to a fraction of the range
+    Seek(f64),
+    TogglePlayback,
+}
+
+/// Timeline scrubber shown along the bottom of the screen
+///
+/// Positions are in screen pixels. The play button sits at the left end
+/// of the track.
+#[derive(Debug, Clone)]
+pub struct TimelineScrubber {
+    /// Top-left corner
+    pub position: (f32, f32),
+    pub size: (f32, f32),
+    /// Width of the play button
+    pub button_width: f32,
+    /// Cursor as a fraction of the range
+    pub cursor: f64,
+    pub playing: bool,
+    /// Event times as fractions of the range, drawn as ticks
+    pub ticks: Vec<f64>,
+    dragging: bool,
+}
+
+impl TimelineScrubber {
+    pub fn new(position: (f32, f32), size: (f32, f32)) -> Self {
+        Self {
+            position,
+            size,
+            button_width: size.1,
+            cursor: 1.0,
+            playing: false,
+            ticks: Vec::new(),
+            dragging: false,
+        }
+    }
+
+    /// Take the cursor, play state and ticks from the playback
+    pub fn sync(&mut self, playback: &TimelinePlayback) {
+        self.cursor = playback.fraction();
+        self.playing = playback.is_playing();
+        self.ticks = match playback.timeline().range() {
+            Some((start, end)) if end > start => {
+                let length = (end - start).num_milliseconds() as f64;
+                playback.timeline().event_times().into_iter()
+                    .map(|at| (at - start).num_milliseconds() as f64 / length)
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+    }
+
+    /// Horizontal extent of the track
+    pub fn track(&self) -> (f32, f32) {
+        (self.position.0 + self.button_width, self.position.0 + self.size.0)
+    }
+
+    /// Screen x of the handle
+    pub fn handle_x(&self) -> f32 {
+        let (left, right) = self.track();
+        left + (right - left) * self.cursor as f32
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.position.0 && x <= self.position.0 + self.size.0 && y >= self.position.1 && y <= self.position.1 + self.size.1
+    }
+
+    fn fraction_at(&self, x: f32) -> f64 {
+        let (left, right) = self.track();
+        (((x - left) / (right - left).max(1.0)) as f64).clamp(0.0, 1.0)
+    }
+
+    /// Pointer pressed; `None` if it missed the scrubber
+    pub fn press(&mut self, x: f32, y: f32) -> Option<ScrubberAction> {
+        if !self.contains(x, y) {
+            return None;
+        }
+        if x < self.track().0 {
+            return Some(ScrubberAction::TogglePlayback);
+        }
+        self.dragging = true;
+        self.cursor = self.fraction_at(x);
+        Some(ScrubberAction::Seek(self.cursor))
+    }
+
+    /// Pointer moved; seeks while the handle is dragged, even past the ends of the track
+    pub fn drag(&mut self, x: f32) -> Option<ScrubberAction> {
+        if !self.dragging {
+            return None;
+        }
+        self.cursor = self.fraction_at(x);
+        Some(ScrubberAction::Seek(self.cursor))
+    }
+
+    pub fn release(&mut self) {
+        self.dragging = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TimeAxis;
+    use chrono::TimeZone;
+    use horizonos_graph_engine::{EdgeType, NodeMetadata, NodeType, Position, SceneEdge, SceneNode};
+    use nalgebra::Vector3;
+
+    fn node(created_at: DateTime<Utc>) -> SceneNode {
+        SceneNode {
+            id: 0,
+            position: Position::origin(),
+            velocity: Vector3::zeros(),
+            radius: 2.0,
+            color: [1.0; 4],
+            node_type: NodeType::Concept { title: String::new(), content: String::new() },
+            metadata: NodeMetadata { created_at, ..NodeMetadata::default() },
+            visible: true,
+            selected: false,
+        }
+    }
+
+    #[test]
+    fn test_scrubbing_shows_the_graph_as_it_was() {
+        let start = Utc.with_ymd_and_hms(2024, 5, 1, 8, 0, 0).unwrap();
+        let mut scene = Scene::new();
+        let early = scene.add_node(node(start));
+        let late = scene.add_node(node(start + TimeDelta::hours(9)));
+        let edge = scene.add_edge(SceneEdge {
+            id: 0,
+            source: early,
+            target: late,
+            edge_type: EdgeType::RelatedTo { similarity: 1.0 },
+            weight: 1.0,
+            color: [1.0; 4],
+            visible: true,
+            animated: false,
+        });
+
+        // Playing the ten hours in 30 seconds fades elements over an hour
+        let config = PlaybackConfig { duration: Duration::from_secs(30), grow_time: Duration::from_secs(3), ..Default::default() };
+        let mut playback = TimelinePlayback::new(GraphTimeline::from_scene(&scene), config);
+        assert_eq!(playback.fraction(), 1.0);
+        playback.timeline_mut().record_node_removed(early, start + TimeDelta::hours(10));
+        let base = PlaybackBase::capture_scene(&scene);
+
+        // Half an hour after the second node appeared it is half grown
+        playback.seek(start + TimeDelta::minutes(570));
+        let state = playback.state();
+        assert_eq!(state.nodes.get(&late), Some(&0.5));
+        state.apply(&mut scene, &base, playback.timeline());
+        assert_eq!(scene.get_node(late).unwrap().radius, 1.0);
+
+        let mut scrubber = TimelineScrubber::new((0.0, 500.0), (220.0, 20.0));
+        let Some(ScrubberAction::Seek(fraction)) = scrubber.press(70.0, 510.0) else {
+            panic!("press on the track should seek");
+        };
+        playback.seek_fraction(fraction);
+        assert_eq!(playback.cursor(), start + TimeDelta::hours(2) + TimeDelta::minutes(30));
+        playback.state().apply(&mut scene, &base, playback.timeline());
+        assert!(!scene.get_node(late).unwrap().visible);
+        assert!(!scene.get_edge(edge).unwrap().visible);
+        assert_eq!(scrubber.press(5.0, 510.0), Some(ScrubberAction::TogglePlayback));
+
+        // Nodes keep the place the whole range gives them
+        let nodes: Vec<LayoutNode> = [early, late].iter().map(|id| LayoutNode::new(*id, Position::origin())).collect();
+        let layout = TemporalLayout::new().with_time_axis(TimeAxis::X);
+        let result = playback.layout(&layout, &nodes, &[LayoutEdge::new(early, late, 1.0)]).unwrap();
+        assert_eq!(result.node_positions.len(), 1);
+        assert_eq!(result.node_positions[&early].x, -layout.time_scale / 2.0);
+
+        playback.play();
+        assert_eq!(playback.fraction(), 0.25);
+        assert!(playback.advance(Duration::from_secs(30)));
+        assert!(!playback.is_playing());
+        base.restore(&mut scene);
+        assert!(scene.get_node(late).unwrap().visible);
+    }
+}
@@ -0,0 +1,54 @@
+## Node action labels
+
+action-open = Öffnen
+action-edit = Bearbeiten
+action-delete = Löschen
+action-copy = Kopieren
+action-move = Verschieben
+action-connect = Verbinden
+action-disconnect = Trennen
+
+## Custom node actions, looked up as action-custom-<name>
+
+action-custom-add-parameter = Parameter hinzufügen
+action-custom-execute = Ausführen
+action-custom-focus-mode = Fokusmodus
+action-custom-pause = Pausieren
+action-custom-properties = Eigenschaften
+action-custom-reset = Zurücksetzen
+action-custom-restart = Neu starten
+action-custom-resume = Fortsetzen
+action-custom-set-value = Wert setzen
+action-custom-show-in-folder = Im Ordner anzeigen
+action-custom-stop = Beenden
+action-custom-test = Testen
+action-custom-undo = Rückgängig
+action-custom-validate = Prüfen
+action-custom-view-logs = Protokolle anzeigen
+
+## File nodes
+
+file-opened = { $name } geöffnet
+file-delete-confirm = Datei { $name } löschen?
+directory-delete-confirm = Ordner { $name } mit allen Inhalten löschen?
+file-path-copied = Pfad kopiert: { $path }
+
+## Application nodes
+
+app-started = { $name } gestartet
+app-already-running = { $name } läuft bereits
+app-remove-confirm = { $name } beenden und entfernen?
+app-removed = { $name } entfernt
+app-stopped = { $name } beendet
+app-not-running = Anwendung läuft nicht
+app-restarted = { $name } neu gestartet
+
+## AI agent nodes
+
+agent-conversation-opened = Gespräch mit { $name }
+agent-not-bound = { $name } ist mit keinem laufenden Agenten verbunden
+
+## System nodes
+
+focus-mode-on = Fokusmodus: { $mode }
+focus-mode-off = Fokusmodus aus
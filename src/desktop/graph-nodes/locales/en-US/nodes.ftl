@@ -0,0 +1,54 @@
+## Node action labels
+
+action-open = Open
+action-edit = Edit
+action-delete = Delete
+action-copy = Copy
+action-move = Move
+action-connect = Connect
+action-disconnect = Disconnect
+
+## Custom node actions, looked up as action-custom-<name>
+
+action-custom-add-parameter = Add parameter
+action-custom-execute = Run
+action-custom-focus-mode = Focus mode
+action-custom-pause = Pause
+action-custom-properties = Properties
+action-custom-reset = Reset
+action-custom-restart = Restart
+action-custom-resume = Resume
+action-custom-set-value = Set value
+action-custom-show-in-folder = Show in folder
+action-custom-stop = Stop
+action-custom-test = Test
+action-custom-undo = Undo
+action-custom-validate = Validate
+action-custom-view-logs = View logs
+
+## File nodes
+
+file-opened = Opened { $name }
+file-delete-confirm = Delete file { $name }?
+directory-delete-confirm = Delete directory { $name } and all its contents?
+file-path-copied = Copied path: { $path }
+
+## Application nodes
+
+app-started = Started { $name }
+app-already-running = { $name } is already running
+app-remove-confirm = Stop and remove { $name }?
+app-removed = Removed { $name }
+app-stopped = Stopped { $name }
+app-not-running = Application is not running
+app-restarted = Restarted { $name }
+
+## AI agent nodes
+
+agent-conversation-opened = Talking to { $name }
+agent-not-bound = { $name } is not connected to a running agent
+
+## System nodes
+
+focus-mode-on = Focus mode: { $mode }
+focus-mode-off = Focus mode off
@@ -1,38 +1,86 @@
 //! AI Agent node implementation
+//!
+//! An agent node stands for a live agent of the AI agent system. The ID of
+//! the agent it is bound to is kept in the [`AGENT_ID_PROPERTY`] metadata
+//! property, so bindings survive saving and restoring the graph; the agent
+//! system keeps the node's status and task progress up to date.
 
+use crate::i18n::I18N;
 use crate::{GraphNode, BaseNode, NodeVisualData, NodeAction, NodeActionResult, NodeActionType, NodeError, NodeExportData};
 use horizonos_graph_engine::{SceneNode, NodeType, NodeMetadata, SceneId, Position, Vec3};
+use horizonos_graph_i18n::tr;
+
+/// Metadata property holding the ID of the bound agent
+pub const AGENT_ID_PROPERTY: &str = "agent.id";
 
 #[derive(Debug, Clone)]
 pub struct AIAgentNode {
     base: BaseNode,
+    name: String,
+    model: String,
 }
 
 impl AIAgentNode {
     pub fn new(id: SceneId) -> Self {
         AIAgentNode {
-            base: BaseNode::new(id),
+            base: BaseNode::new(id).with_color([0.6, 0.4, 0.9, 1.0]),
+            name: "AI Agent".to_string(),
+            model: "local".to_string(),
         }
     }
+
+    /// Node bound to a live agent
+    pub fn for_agent(id: SceneId, agent_id: &str, name: String, model: String) -> Self {
+        let mut node = Self::new(id);
+        node.name = name;
+        node.model = model;
+        node.base.metadata.properties.insert(AGENT_ID_PROPERTY.to_string(), agent_id.to_string());
+        node
+    }
+
+    pub fn with_metadata(mut self, metadata: NodeMetadata) -> Self {
+        self.base.metadata = metadata;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// ID of the bound agent, if any
+    pub fn agent_id(&self) -> Option<&str> {
+        self.base.metadata.properties.get(AGENT_ID_PROPERTY).map(String::as_str)
+    }
 }
 
 impl GraphNode for AIAgentNode {
     fn id(&self) -> SceneId { self.base.id }
-    fn display_name(&self) -> String { "AI Agent".to_string() }
-    fn description(&self) -> Option<String> { Some("AI Agent node".to_string()) }
+    fn display_name(&self) -> String { self.name.clone() }
+    fn description(&self) -> Option<String> { Some(format!("AI agent using {}", self.model)) }
     fn node_type(&self) -> NodeType {
         NodeType::AIAgent {
-            name: "Generic AI Agent".to_string(),
-            model: "unknown".to_string(),
+            name: self.name.clone(),
+            model: self.model.clone(),
         }
     }
     fn metadata(&self) -> NodeMetadata { self.base.metadata.clone() }
     fn visual_data(&self) -> NodeVisualData { self.base.visual_data.clone() }
     fn update(&mut self, _delta_time: f32) -> Result<(), NodeError> { Ok(()) }
     fn handle_action(&mut self, action: NodeAction) -> Result<NodeActionResult, NodeError> {
-        Err(NodeError::InvalidAction { action })
+        match action {
+            // The conversation itself is opened by the agent system, which watches node actions
+            NodeAction::Open if self.agent_id().is_some() => Ok(NodeActionResult::Success {
+                message: Some(tr!(I18N, "agent-conversation-opened", name = self.name.as_str())),
+            }),
+            NodeAction::Open => Ok(NodeActionResult::Error { error: tr!(I18N, "agent-not-bound", name = self.name.as_str()) }),
+            _ => Err(NodeError::InvalidAction { action }),
+        }
     }
-    fn available_actions(&self) -> Vec<NodeActionType> { vec![] }
+    fn available_actions(&self) -> Vec<NodeActionType> { vec![NodeActionType::Open] }
     fn export_data(&self) -> Result<NodeExportData, NodeError> {
         Ok(NodeExportData {
             node_type: "ai_agent".to_string(),
@@ -40,7 +88,7 @@ impl GraphNode for AIAgentNode {
             description: self.description(),
             visual_data: self.visual_data(),
             metadata: self.base.metadata.clone(),
-            type_specific_data: serde_json::Value::Null,
+            type_specific_data: serde_json::json!({ "model": self.model }),
         })
     }
     fn to_scene_node(&self) -> SceneNode {
@@ -49,11 +97,11 @@ impl GraphNode for AIAgentNode {
             position: Position::new(0.0, 0.0, 0.0),
             velocity: Vec3::zeros(),
             radius: 1.0,
-            color: [0.5, 0.5, 0.5, 1.0],
-            node_type: NodeType::AIAgent { name: "AI Agent".to_string(), model: "local".to_string() },
+            color: self.base.visual_data.color,
+            node_type: self.node_type(),
             metadata: self.base.metadata.clone(),
             visible: true,
             selected: false,
         }
     }
-}
\ No newline at end of file
+}
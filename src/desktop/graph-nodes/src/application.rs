@@ -1,6 +1,8 @@
 //! Application node implementation
 
 use crate::{GraphNode, BaseNode, NodeVisualData, NodeAction, NodeActionResult, NodeActionType, NodeError, NodeExportData};
+use crate::i18n::I18N;
+use horizonos_graph_i18n::tr;
 use horizonos_graph_engine::{SceneNode, NodeType, NodeMetadata, SceneId, Position, Vec3};
 use serde::{Serialize, Deserialize};
 use std::process::Command;
@@ -11,7 +13,7 @@ pub struct ApplicationNode {
     base: BaseNode,
     app_data: ApplicationData,
     process: Option<u32>, // PID
-    #[allow(dead_code)]
+    /// Compositor window shown on the node face
     window_id: Option<String>,
     is_running: bool,
 }
@@ -199,6 +201,15 @@ impl ApplicationNode {
         self.base.visual_data.color = color;
     }
     
+    /// Attach the compositor window of this application, or detach it
+    pub fn set_window(&mut self, window_id: Option<String>) {
+        self.window_id = window_id;
+    }
+    
+    pub fn window_id(&self) -> Option<&str> {
+        self.window_id.as_deref()
+    }
+    
     pub fn pid(&self) -> Option<u32> {
         self.process
     }
@@ -268,6 +279,9 @@ impl GraphNode for ApplicationNode {
             visual.glow = false;
         }
         
+        // A mapped window replaces the plain sphere with its live contents
+        visual.extensions.live_thumbnail = self.window_id.is_some();
+        
         visual
     }
     
@@ -285,22 +299,22 @@ impl GraphNode for ApplicationNode {
                 if !self.is_running {
                     self.start_process()?;
                     Ok(NodeActionResult::Success { 
-                        message: Some(format!("Started {}", self.app_data.name)) 
+                        message: Some(tr!(I18N, "app-started", name = self.app_data.name.as_str())) 
                     })
                 } else {
                     Ok(NodeActionResult::Success { 
-                        message: Some(format!("{} is already running", self.app_data.name)) 
+                        message: Some(tr!(I18N, "app-already-running", name = self.app_data.name.as_str())) 
                     })
                 }
             }
             NodeAction::Delete => {
                 if self.is_running {
                     Ok(NodeActionResult::ConfirmationRequired { 
-                        prompt: format!("Stop and remove {}?", self.app_data.name) 
+                        prompt: tr!(I18N, "app-remove-confirm", name = self.app_data.name.as_str()) 
                     })
                 } else {
                     Ok(NodeActionResult::Success { 
-                        message: Some(format!("Removed {}", self.app_data.name)) 
+                        message: Some(tr!(I18N, "app-removed", name = self.app_data.name.as_str())) 
                     })
                 }
             }
@@ -310,11 +324,11 @@ impl GraphNode for ApplicationNode {
                         if self.is_running {
                             self.stop_process()?;
                             Ok(NodeActionResult::Success { 
-                                message: Some(format!("Stopped {}", self.app_data.name)) 
+                                message: Some(tr!(I18N, "app-stopped", name = self.app_data.name.as_str())) 
                             })
                         } else {
                             Ok(NodeActionResult::Error { 
-                                error: "Application is not running".to_string() 
+                                error: tr!(I18N, "app-not-running") 
                             })
                         }
                     }
@@ -326,7 +340,7 @@ impl GraphNode for ApplicationNode {
                         }
                         self.start_process()?;
                         Ok(NodeActionResult::Success { 
-                            message: Some(format!("Restarted {}", self.app_data.name)) 
+                            message: Some(tr!(I18N, "app-restarted", name = self.app_data.name.as_str())) 
                         })
                     }
                     _ => Err(NodeError::InvalidAction { action: NodeAction::Custom { action_type, parameters: std::collections::HashMap::new() } })
@@ -10,6 +10,20 @@ use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
 
+/// Language of automation scripts run by the embedded interpreter
+pub const SCRIPT_LANGUAGE: &str = "rhai";
+
+/// Change to a script automation, for the scheduler that runs the scripts
+#[derive(Debug, Clone)]
+pub enum AutomationEvent {
+    /// A script automation was added or its script or parameters changed
+    Saved(Box<AutomationNode>),
+    /// A run was requested through `execute`; report it back with `NodeManager::record_automation_run`
+    RunQueued(SceneId),
+    /// A script automation was removed
+    Removed(SceneId),
+}
+
 /// Automation node representing scripts, workflows, and automated processes
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutomationNode {
@@ -71,6 +85,9 @@ pub struct AutomationNode {
     pub metadata: NodeMetadata,
     /// Visual data for rendering
     pub visual_data: NodeVisualData,
+    /// Script run requested through `execute`, not yet handed to the scheduler
+    #[serde(skip)]
+    run_queued: bool,
 }
 
 /// Trigger condition for automation
@@ -274,9 +291,20 @@ impl AutomationNode {
             tags: Vec::new(),
             metadata,
             visual_data,
+            run_queued: false,
         }
     }
     
+    /// Create a script automation run by the embedded interpreter
+    pub fn new_script(id: SceneId, name: String, source: String) -> Self {
+        Self::new(id, name, AutomationType::Script, source, SCRIPT_LANGUAGE.to_string())
+    }
+    
+    /// Whether the content is a script for the embedded interpreter
+    pub fn is_script(&self) -> bool {
+        self.language == SCRIPT_LANGUAGE
+    }
+    
     /// Get icon name for automation type
     fn get_icon_for_type(automation_type: &AutomationType) -> String {
         match automation_type {
@@ -301,6 +329,12 @@ impl AutomationNode {
         self.visual_data.glow = true;
         self.visual_data.color[3] = 0.8; // Slightly transparent when running
         
+        // Scripts are run by the automation scheduler, which reports back through `record_run`
+        if self.is_script() {
+            self.run_queued = true;
+            return Ok(());
+        }
+        
         // TODO: Implement actual execution logic based on automation type
         // For now, simulate execution
         let execution_time = 1000; // 1 second
@@ -321,6 +355,20 @@ impl AutomationNode {
         Ok(())
     }
     
+    /// Take the script run requested through `execute`, if any
+    pub fn take_queued_run(&mut self) -> bool {
+        std::mem::take(&mut self.run_queued)
+    }
+    
+    /// Record the result of a run made elsewhere, such as a scheduled script run
+    pub fn record_run(&mut self, result: ExecutionResult) -> Result<(), NodeError> {
+        // Scheduled runs were not started through `execute`
+        if !matches!(self.status, AutomationStatus::Running) {
+            self.run_count += 1;
+        }
+        self.finish_execution(result)
+    }
+    
     /// Finish execution with result
     fn finish_execution(&mut self, result: ExecutionResult) -> Result<(), NodeError> {
         let success = result.success;
@@ -505,8 +553,9 @@ impl GraphNode for AutomationNode {
                 match action_type.as_str() {
                     "execute" => {
                         self.execute()?;
+                        let message = if self.is_script() { "Automation script started" } else { "Automation executed" };
                         Ok(NodeActionResult::Success {
-                            message: Some("Automation executed".to_string()),
+                            message: Some(message.to_string()),
                         })
                     }
                     "pause" => {
@@ -539,6 +588,19 @@ impl GraphNode for AutomationNode {
                             })
                         }
                     }
+                    "set_script" => {
+                        if let Some(source) = parameters.get("source") {
+                            self.content = source.clone();
+                            self.metadata.updated_at = Utc::now();
+                            Ok(NodeActionResult::Success {
+                                message: Some("Automation script updated".to_string()),
+                            })
+                        } else {
+                            Ok(NodeActionResult::Error {
+                                error: "Source parameter required".to_string(),
+                            })
+                        }
+                    }
                     "view_logs" => {
                         Ok(NodeActionResult::Success {
                             message: Some("Opening automation logs".to_string()),
@@ -565,10 +627,19 @@ impl GraphNode for AutomationNode {
             NodeActionType::Custom("resume".to_string()),
             NodeActionType::Custom("stop".to_string()),
             NodeActionType::Custom("add_parameter".to_string()),
+            NodeActionType::Custom("set_script".to_string()),
             NodeActionType::Custom("view_logs".to_string()),
         ]
     }
     
+    fn as_automation(&self) -> Option<&AutomationNode> {
+        Some(self)
+    }
+    
+    fn as_automation_mut(&mut self) -> Option<&mut AutomationNode> {
+        Some(self)
+    }
+    
     fn export_data(&self) -> Result<NodeExportData, NodeError> {
         Ok(NodeExportData {
             node_type: "Automation".to_string(),
@@ -675,6 +746,32 @@ mod tests {
         assert_eq!(automation.success_rate(), 1.0);
     }
     
+    #[test]
+    fn test_script_run_is_recorded() {
+        let mut automation = AutomationNode::new_script(1, "Linker".to_string(), "notify(\"hi\")".to_string());
+        assert!(automation.is_script());
+        
+        // The scheduler runs the script; the node waits for its result
+        automation.execute().unwrap();
+        assert!(matches!(automation.status, AutomationStatus::Running));
+        assert_eq!(automation.success_count, 0);
+        
+        let now = Utc::now();
+        automation.record_run(ExecutionResult {
+            success: false,
+            exit_code: None,
+            execution_time: 4,
+            stdout: String::new(),
+            stderr: String::new(),
+            start_time: now,
+            end_time: now,
+            error: Some("Unknown node 9".to_string()),
+        }).unwrap();
+        assert_eq!(automation.run_count, 1);
+        assert_eq!(automation.failure_count, 1);
+        assert!(matches!(automation.status, AutomationStatus::Failed));
+    }
+    
     #[test]
     fn test_automation_pause_resume() {
         let mut automation = AutomationNode::new(
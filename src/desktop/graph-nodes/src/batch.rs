@@ -0,0 +1,51 @@
+//! Aggregated change events of bulk node operations
+//!
+//! Adding, updating or removing many nodes through the `NodeManager` batch
+//! methods records one history transaction and sends one event, so layout
+//! and physics catch up once instead of once per node.
+
+use horizonos_graph_engine::{SceneChange, SceneId};
+
+/// Bulk operation an event reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeBatchKind {
+    Added,
+    Updated,
+    Removed,
+}
+
+/// Nodes changed by one bulk operation
+#[derive(Debug, Clone)]
+pub struct NodeBatchEvent {
+    pub kind: NodeBatchKind,
+    /// Nodes the operation changed, in the order given
+    pub nodes: Vec<SceneId>,
+    /// Scene changes for incremental layout, e.g. `LayoutAlgorithm::apply_incremental`
+    /// or `GraphEngine::apply_scene_changes`
+    pub changes: Vec<SceneChange>,
+}
+
+impl NodeBatchEvent {
+    pub fn new(kind: NodeBatchKind) -> Self {
+        Self {
+            kind,
+            nodes: Vec::new(),
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Nodes whose position changed, e.g. to wake their physics islands
+    pub fn moved_nodes(&self) -> Vec<SceneId> {
+        self.changes
+            .iter()
+            .filter_map(|change| match change {
+                SceneChange::NodeMoved { id, .. } => Some(*id),
+                _ => None,
+            })
+            .collect()
+    }
+}
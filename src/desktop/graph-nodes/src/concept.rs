@@ -1,29 +1,58 @@
 //! Concept node implementation
 
-use crate::{GraphNode, BaseNode, NodeVisualData, NodeAction, NodeActionResult, NodeActionType, NodeError, NodeExportData};
+use crate::{GraphNode, BaseNode, NodeVisualData, NodeAction, NodeActionResult, NodeActionType, NodeError, NodeExportData, NodeTransform};
 use horizonos_graph_engine::{SceneNode, NodeType, NodeMetadata, SceneId, Position, Vec3};
 
 #[derive(Debug, Clone)]
 pub struct ConceptNode {
     base: BaseNode,
+    title: String,
+    content: String,
 }
 
 impl ConceptNode {
     pub fn new(id: SceneId) -> Self {
         ConceptNode {
             base: BaseNode::new(id),
+            title: "Concept".to_string(),
+            content: String::new(),
         }
     }
+    
+    pub fn with_content(mut self, title: String, content: String) -> Self {
+        self.title = title;
+        self.content = content;
+        self
+    }
+    
+    pub fn with_metadata(mut self, metadata: NodeMetadata) -> Self {
+        self.base.metadata = metadata;
+        self
+    }
+    
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    
+    pub fn content(&self) -> &str {
+        &self.content
+    }
 }
 
 impl GraphNode for ConceptNode {
     fn id(&self) -> SceneId { self.base.id }
-    fn display_name(&self) -> String { "Concept".to_string() }
-    fn description(&self) -> Option<String> { Some("Concept node".to_string()) }
+    fn display_name(&self) -> String { self.title.clone() }
+    fn description(&self) -> Option<String> {
+        if self.content.is_empty() {
+            Some("Concept node".to_string())
+        } else {
+            Some(self.content.clone())
+        }
+    }
     fn node_type(&self) -> NodeType {
         NodeType::Concept {
-            title: "Generic Concept".to_string(),
-            content: "Conceptual information".to_string(),
+            title: self.title.clone(),
+            content: self.content.clone(),
         }
     }
     fn metadata(&self) -> NodeMetadata { self.base.metadata.clone() }
@@ -33,6 +62,10 @@ impl GraphNode for ConceptNode {
         Err(NodeError::InvalidAction { action })
     }
     fn available_actions(&self) -> Vec<NodeActionType> { vec![] }
+    fn set_transform(&mut self, transform: NodeTransform) -> Result<(), NodeError> {
+        self.base.set_transform(transform);
+        Ok(())
+    }
     fn export_data(&self) -> Result<NodeExportData, NodeError> {
         Ok(NodeExportData {
             node_type: "concept".to_string(),
@@ -50,7 +83,7 @@ impl GraphNode for ConceptNode {
             velocity: Vec3::zeros(),
             radius: 1.0,
             color: [0.5, 0.5, 0.5, 1.0],
-            node_type: NodeType::Concept { title: "Concept".to_string(), content: "".to_string() },
+            node_type: self.node_type(),
             metadata: self.base.metadata.clone(),
             visible: true,
             selected: false,
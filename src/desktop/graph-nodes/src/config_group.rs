@@ -1,4 +1,4 @@
-use crate::{GraphNode, NodeVisualData, NodeAction, NodeActionResult, NodeActionType, NodeError, NodeExportData};
+use crate::{GraphNode, NodeVisualData, NodeAction, NodeActionResult, NodeActionType, NodeError, NodeExportData, NodeTransform};
 use horizonos_graph_engine::{SceneId, SceneNode, NodeMetadata};
 use horizonos_graph_engine::scene::{NodeType, ConfigType};
 use serde::{Deserialize, Serialize};
@@ -454,6 +454,8 @@ impl GraphNode for ConfigGroupNode {
             description: self.description.clone(),
             tags: self.tags.clone(),
             properties,
+            privacy: Default::default(),
+            persistence: Default::default(),
         }
     }
     
@@ -481,6 +483,14 @@ impl GraphNode for ConfigGroupNode {
         }
     }
     
+    fn set_transform(&mut self, transform: NodeTransform) -> Result<(), NodeError> {
+        // Groups only resize to frame their members
+        let transform = NodeTransform { rotation: 0.0, scale: 1.0, ..transform };
+        self.visual_data.extensions.transform = Some(transform.clamped());
+        self.last_modified = Some(chrono::Utc::now());
+        Ok(())
+    }
+    
     fn available_actions(&self) -> Vec<NodeActionType> {
         vec![
             NodeActionType::Open,
@@ -1,6 +1,8 @@
 //! File node implementation
 
 use crate::{GraphNode, BaseNode, NodeVisualData, NodeAction, NodeActionResult, NodeActionType, NodeError, NodeExportData};
+use crate::i18n::I18N;
+use horizonos_graph_i18n::tr;
 use horizonos_graph_engine::{SceneNode, NodeType, NodeMetadata, FileType, SceneId, Position, Vec3};
 use serde::{Serialize, Deserialize};
 use std::path::{Path, PathBuf};
@@ -346,21 +348,21 @@ impl GraphNode for FileNode {
             NodeAction::Open => {
                 self.open_with_default_app()?;
                 Ok(NodeActionResult::Success { 
-                    message: Some(format!("Opened {}", self.display_name())) 
+                    message: Some(tr!(I18N, "file-opened", name = self.display_name())) 
                 })
             }
             NodeAction::Delete => {
                 let prompt = if self.file_data.file_type == FileType::Directory {
-                    format!("Delete directory {} and all its contents?", self.display_name())
+                    tr!(I18N, "directory-delete-confirm", name = self.display_name())
                 } else {
-                    format!("Delete file {}?", self.display_name())
+                    tr!(I18N, "file-delete-confirm", name = self.display_name())
                 };
                 Ok(NodeActionResult::ConfirmationRequired { prompt })
             }
             NodeAction::Copy => {
                 // Copy file path to clipboard (if available)
                 Ok(NodeActionResult::Success { 
-                    message: Some(format!("Copied path: {}", self.file_data.path.display())) 
+                    message: Some(tr!(I18N, "file-path-copied", path = self.file_data.path.display().to_string())) 
                 })
             }
             NodeAction::Custom { ref action_type, .. } => {
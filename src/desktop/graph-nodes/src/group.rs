@@ -0,0 +1,189 @@
+//! Group node implementation
+//!
+//! A group node stands in for a collapsed cluster. It is ephemeral: the
+//! collapsed state is saved with the workspace and the group node recreated
+//! from it, so saved graphs always contain the member nodes themselves.
+
+use crate::{GraphNode, BaseNode, NodeVisualData, NodeAction, NodeActionResult, NodeActionType, NodeError, NodeExportData};
+use horizonos_graph_engine::{SceneNode, NodeType, NodeMetadata, NodePersistence, SceneId, Position, Vec3};
+
+/// Node property holding the ID of the group a group node stands for
+pub const GROUP_ID_PROPERTY: &str = "group_id";
+
+/// Custom action that expands a group node back into its members
+pub const EXPAND_ACTION: &str = "expand";
+
+/// Radius of a group node for its member count
+pub fn group_radius(member_count: usize) -> f32 {
+    1.0 + (member_count as f32).sqrt() * 0.5
+}
+
+/// ID of the group a scene node stands for, if it is a group node
+pub fn group_id_of(node: &SceneNode) -> Option<&str> {
+    match node.node_type {
+        NodeType::Group { .. } => node.metadata.properties.get(GROUP_ID_PROPERTY).map(String::as_str),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupNode {
+    base: BaseNode,
+    group_id: String,
+    name: String,
+    members: Vec<SceneId>,
+}
+
+impl GroupNode {
+    pub fn new(id: SceneId, group_id: String, name: String, members: Vec<SceneId>) -> Self {
+        let mut base = BaseNode::new(id)
+            .with_color([0.45, 0.55, 0.75, 1.0])
+            .with_radius(group_radius(members.len()));
+        base.metadata.properties.insert(GROUP_ID_PROPERTY.to_string(), group_id.clone());
+        base.metadata.persistence = NodePersistence::Ephemeral;
+        base.visual_data.badge = Some(members.len().to_string());
+        GroupNode {
+            base,
+            group_id,
+            name,
+            members,
+        }
+    }
+
+    pub fn with_position(mut self, position: [f32; 3]) -> Self {
+        self.base.visual_data.position = position;
+        self
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.base.visual_data.color = color;
+        self
+    }
+
+    /// ID of the group, such as a cluster ID
+    pub fn group_id(&self) -> &str {
+        &self.group_id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Nodes hidden behind this group
+    pub fn members(&self) -> &[SceneId] {
+        &self.members
+    }
+}
+
+impl GraphNode for GroupNode {
+    fn id(&self) -> SceneId { self.base.id }
+    fn display_name(&self) -> String { self.name.clone() }
+    fn description(&self) -> Option<String> {
+        Some(format!("Group of {} nodes", self.members.len()))
+    }
+    fn node_type(&self) -> NodeType {
+        NodeType::Group {
+            name: self.name.clone(),
+            member_count: self.members.len(),
+        }
+    }
+    fn metadata(&self) -> NodeMetadata { self.base.metadata.clone() }
+    fn visual_data(&self) -> NodeVisualData { self.base.visual_data.clone() }
+    fn update(&mut self, _delta_time: f32) -> Result<(), NodeError> { Ok(()) }
+    fn handle_action(&mut self, action: NodeAction) -> Result<NodeActionResult, NodeError> {
+        match action {
+            NodeAction::MoveTo { position } => {
+                self.base.visual_data.position = position;
+                self.base.update_timestamp();
+                Ok(NodeActionResult::Success { message: None })
+            }
+            // Expanding is done by the clustering system, which owns the members
+            NodeAction::Custom { ref action_type, .. } if action_type == EXPAND_ACTION => {
+                Ok(NodeActionResult::Success { message: Some(format!("Expanding {}", self.name)) })
+            }
+            _ => Err(NodeError::InvalidAction { action }),
+        }
+    }
+    fn available_actions(&self) -> Vec<NodeActionType> {
+        vec![NodeActionType::Custom(EXPAND_ACTION.to_string()), NodeActionType::Move]
+    }
+    fn export_data(&self) -> Result<NodeExportData, NodeError> {
+        Ok(NodeExportData {
+            node_type: "group".to_string(),
+            display_name: self.display_name(),
+            description: self.description(),
+            visual_data: self.visual_data(),
+            metadata: self.base.metadata.clone(),
+            type_specific_data: serde_json::json!({
+                "group_id": self.group_id,
+                "members": self.members,
+            }),
+        })
+    }
+    fn to_scene_node(&self) -> SceneNode {
+        let [x, y, z] = self.base.visual_data.position;
+        SceneNode {
+            id: self.base.id,
+            position: Position::new(x, y, z),
+            velocity: Vec3::zeros(),
+            radius: self.base.visual_data.radius,
+            color: self.base.visual_data.color,
+            node_type: self.node_type(),
+            metadata: self.base.metadata.clone(),
+            visible: self.base.visual_data.visible,
+            selected: self.base.visual_data.selected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn group() -> GroupNode {
+        GroupNode::new(7, "cluster-3".to_string(), "Project".to_string(), vec![1, 2, 3, 4])
+            .with_position([1.0, 2.0, 3.0])
+    }
+
+    #[test]
+    fn test_group_node_stands_for_its_cluster() {
+        let group = group();
+        assert_eq!(group.group_id(), "cluster-3");
+        assert_eq!(group.members(), &[1, 2, 3, 4]);
+        assert_eq!(group.visual_data().badge.as_deref(), Some("4"));
+        assert_eq!(group.description().as_deref(), Some("Group of 4 nodes"));
+
+        let node = group.to_scene_node();
+        assert_eq!(node.id, 7);
+        assert_eq!(group_id_of(&node), Some("cluster-3"));
+        assert_eq!(node.position, Position::new(1.0, 2.0, 3.0));
+        assert_eq!(node.radius, group_radius(4));
+        assert!(matches!(node.node_type, NodeType::Group { member_count: 4, .. }));
+        // Saved graphs keep the members, not the group
+        assert_eq!(node.metadata.persistence, NodePersistence::Ephemeral);
+    }
+
+    #[test]
+    fn test_only_group_nodes_have_a_group_id() {
+        let mut node = group().to_scene_node();
+        node.node_type = NodeType::Concept { title: String::new(), content: String::new() };
+        assert_eq!(group_id_of(&node), None);
+        assert!(group_radius(16) > group_radius(4));
+    }
+
+    #[test]
+    fn test_group_node_actions() {
+        let mut group = group();
+        assert!(group.available_actions().contains(&NodeActionType::Custom(EXPAND_ACTION.to_string())));
+        let expand = NodeAction::Custom { action_type: EXPAND_ACTION.to_string(), parameters: HashMap::new() };
+        assert!(matches!(group.handle_action(expand), Ok(NodeActionResult::Success { .. })));
+        assert!(group.handle_action(NodeAction::MoveTo { position: [5.0, 0.0, 0.0] }).is_ok());
+        assert_eq!(group.visual_data().position, [5.0, 0.0, 0.0]);
+        assert!(matches!(group.handle_action(NodeAction::Open), Err(NodeError::InvalidAction { .. })));
+
+        let export = group.export_data().unwrap();
+        assert_eq!(export.node_type, "group");
+        assert_eq!(export.type_specific_data["members"], serde_json::json!([1, 2, 3, 4]));
+    }
+}
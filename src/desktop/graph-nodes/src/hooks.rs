@@ -0,0 +1,688 @@
+//! User-defined node lifecycle hooks
+//!
+//! Hooks run an action when a node matching a filter is created, opened or
+//! deleted, e.g. "when a file node in ~/inbox is created, run the sorter
+//! workflow". Hooks are read from `$XDG_CONFIG_HOME/horizonos/hooks.toml`:
+//!
+//! ```toml
+//! [[hook]]
+//! id = "inbox-sorter"
+//! event = "on-create"
+//! filter = { node_type = "file", path_prefix = "~/inbox" }
+//! action = { type = "workflow", workflow_id = "sort-inbox" }
+//! ```
+//!
+//! Shell commands and scripts run under bubblewrap with a read-only root,
+//! no network and a cleared environment. Node details are passed as
+//! `HORIZONOS_NODE_*` environment variables, never interpolated into the
+//! command line.
+
+use crate::{GraphNode, NodeError};
+use horizonos_graph_engine::{NodeType, SceneId};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Hook outcomes kept for inspection
+const OUTCOME_HISTORY: usize = 64;
+
+/// Point in a node's life a hook runs at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LifecycleEvent {
+    #[serde(rename = "on-create")]
+    Created,
+    #[serde(rename = "on-open")]
+    Opened,
+    #[serde(rename = "on-delete")]
+    Deleted,
+}
+
+impl LifecycleEvent {
+    /// Name used in configuration and passed to hooks
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LifecycleEvent::Created => "on-create",
+            LifecycleEvent::Opened => "on-open",
+            LifecycleEvent::Deleted => "on-delete",
+        }
+    }
+}
+
+/// Nodes a hook applies to; empty fields match everything
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HookFilter {
+    /// Node type name, e.g. `file` or `application`
+    pub node_type: Option<String>,
+    /// Tag the node must carry
+    pub tag: Option<String>,
+    /// Directory file nodes must be inside; `~` is expanded
+    pub path_prefix: Option<String>,
+}
+
+impl HookFilter {
+    /// Whether a node matches
+    pub fn matches(&self, context: &HookContext) -> bool {
+        if self.node_type.as_ref().is_some_and(|node_type| node_type != context.node_type) {
+            return false;
+        }
+        if self.tag.as_ref().is_some_and(|tag| !context.tags.contains(tag)) {
+            return false;
+        }
+        if let Some(prefix) = &self.path_prefix {
+            let prefix = expand_home(prefix);
+            if !context.path.as_ref().is_some_and(|path| path.starts_with(&prefix)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// What a hook does
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HookAction {
+    /// Run a command with `sh -c`
+    Shell { command: String },
+    /// Run an executable script with arguments
+    Script {
+        path: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Start an automation workflow with the node as input
+    Workflow { workflow_id: String },
+}
+
+/// Restrictions for shell and script hooks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HookSandbox {
+    /// Run under bubblewrap; hooks fail rather than run unsandboxed when it is missing
+    pub enabled: bool,
+    /// Allow network access
+    pub allow_network: bool,
+    /// Paths the hook may write to; `~` is expanded
+    pub writable_paths: Vec<String>,
+    /// Seconds before the hook is killed
+    pub timeout_secs: u64,
+}
+
+impl Default for HookSandbox {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allow_network: false,
+            writable_paths: Vec::new(),
+            timeout_secs: 30,
+        }
+    }
+}
+
+/// A configured lifecycle hook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LifecycleHook {
+    /// Unique hook identifier
+    pub id: String,
+    /// Event the hook runs on
+    pub event: LifecycleEvent,
+    /// Nodes the hook applies to
+    #[serde(default)]
+    pub filter: HookFilter,
+    /// Action to run
+    pub action: HookAction,
+    /// Disabled hooks are kept but never run
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Sandbox for shell and script actions
+    #[serde(default)]
+    pub sandbox: HookSandbox,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Hooks file contents
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct HooksFile {
+    #[serde(default, rename = "hook")]
+    hooks: Vec<LifecycleHook>,
+}
+
+/// Node details available to hooks
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookContext {
+    pub node_id: SceneId,
+    pub node_type: &'static str,
+    pub display_name: String,
+    pub tags: Vec<String>,
+    /// Path of file nodes
+    pub path: Option<PathBuf>,
+}
+
+impl HookContext {
+    /// Collect the details of a node
+    pub fn from_node(node: &dyn GraphNode) -> Self {
+        let node_type = node.node_type();
+        let path = match &node_type {
+            NodeType::File { path, .. } => Some(PathBuf::from(path)),
+            _ => None,
+        };
+        Self {
+            node_id: node.id(),
+            node_type: node_type_name(&node_type),
+            display_name: node.display_name(),
+            tags: node.metadata().tags,
+            path,
+        }
+    }
+
+    /// Environment passed to shell and script hooks
+    fn env(&self, event: LifecycleEvent) -> Vec<(&'static str, String)> {
+        let mut env = vec![("HORIZONOS_HOOK_EVENT", event.as_str().to_string())];
+        env.extend(self.node_env());
+        env
+    }
+
+    /// `HORIZONOS_NODE_*` variables describing the node
+    pub(crate) fn node_env(&self) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("HORIZONOS_NODE_ID", self.node_id.to_string()),
+            ("HORIZONOS_NODE_TYPE", self.node_type.to_string()),
+            ("HORIZONOS_NODE_NAME", self.display_name.clone()),
+            ("HORIZONOS_NODE_TAGS", self.tags.join(",")),
+        ];
+        if let Some(path) = &self.path {
+            env.push(("HORIZONOS_NODE_PATH", path.to_string_lossy().into_owned()));
+        }
+        env
+    }
+
+    /// Workflow input
+    fn to_json(&self, event: LifecycleEvent) -> serde_json::Value {
+        serde_json::json!({
+            "event": event.as_str(),
+            "node_id": self.node_id,
+            "node_type": self.node_type,
+            "display_name": self.display_name,
+            "tags": self.tags,
+            "path": self.path,
+        })
+    }
+}
+
+/// Name of a node type as used in hook filters
+pub fn node_type_name(node_type: &NodeType) -> &'static str {
+    match node_type {
+        NodeType::Application { .. } => "application",
+        NodeType::File { .. } => "file",
+        NodeType::Person { .. } => "person",
+        NodeType::Task { .. } => "task",
+        NodeType::Device { .. } => "device",
+        NodeType::AIAgent { .. } => "ai_agent",
+        NodeType::Concept { .. } => "concept",
+        NodeType::System { .. } => "system",
+        NodeType::URL { .. } => "url",
+        NodeType::Automation { .. } => "automation",
+        NodeType::Setting { .. } => "setting",
+        NodeType::ConfigGroup { .. } => "config_group",
+        NodeType::Group { .. } => "group",
+    }
+}
+
+/// Starts automation workflows for workflow hooks
+pub trait WorkflowLauncher: Send + Sync {
+    /// Start a workflow without waiting for it to finish
+    fn launch(&self, workflow_id: &str, input: serde_json::Value) -> Result<(), String>;
+}
+
+/// Result of one hook run
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookOutcome {
+    pub hook_id: String,
+    pub event: LifecycleEvent,
+    pub node_id: SceneId,
+    pub result: Result<(), String>,
+}
+
+/// Configured hooks and their dispatch
+pub struct HookRegistry {
+    hooks: Vec<LifecycleHook>,
+    workflows: Option<Arc<dyn WorkflowLauncher>>,
+    outcomes: Arc<Mutex<VecDeque<HookOutcome>>>,
+}
+
+impl HookRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self {
+            hooks: Vec::new(),
+            workflows: None,
+            outcomes: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Default hooks file location
+    pub fn default_path() -> Option<PathBuf> {
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_dir.join("horizonos").join("hooks.toml"))
+    }
+
+    /// Replace the hooks with those in a TOML file; a missing file means no hooks
+    pub fn load(&mut self, path: &Path) -> Result<usize, NodeError> {
+        if !path.exists() {
+            self.hooks.clear();
+            return Ok(0);
+        }
+        let content = std::fs::read_to_string(path)?;
+        let hooks = parse_hooks(&content)?;
+        self.hooks.clear();
+        for hook in hooks {
+            self.add(hook)?;
+        }
+        Ok(self.hooks.len())
+    }
+
+    /// Write the hooks to a TOML file
+    pub fn save(&self, path: &Path) -> Result<(), NodeError> {
+        let file = HooksFile { hooks: self.hooks.clone() };
+        let content = toml::to_string_pretty(&file).map_err(|e| NodeError::SystemError {
+            message: format!("Failed to serialize hooks: {}", e),
+        })?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Add a hook
+    pub fn add(&mut self, hook: LifecycleHook) -> Result<(), NodeError> {
+        if self.hooks.iter().any(|existing| existing.id == hook.id) {
+            return Err(NodeError::SystemError {
+                message: format!("Duplicate hook id '{}'", hook.id),
+            });
+        }
+        self.hooks.push(hook);
+        Ok(())
+    }
+
+    /// Remove a hook
+    pub fn remove(&mut self, id: &str) -> Option<LifecycleHook> {
+        let index = self.hooks.iter().position(|hook| hook.id == id)?;
+        Some(self.hooks.remove(index))
+    }
+
+    /// Enable or disable a hook, returning whether it exists
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) -> bool {
+        match self.hooks.iter_mut().find(|hook| hook.id == id) {
+            Some(hook) => {
+                hook.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// All hooks
+    pub fn hooks(&self) -> &[LifecycleHook] {
+        &self.hooks
+    }
+
+    /// Set where workflow hooks are sent
+    pub fn set_workflow_launcher(&mut self, launcher: Arc<dyn WorkflowLauncher>) {
+        self.workflows = Some(launcher);
+    }
+
+    /// Enabled hooks that apply to an event and node
+    pub fn matching<'a>(&'a self, event: LifecycleEvent, context: &'a HookContext) -> impl Iterator<Item = &'a LifecycleHook> {
+        self.hooks
+            .iter()
+            .filter(move |hook| hook.enabled && hook.event == event && hook.filter.matches(context))
+    }
+
+    /// Run the hooks for an event in the background, returning how many were started
+    pub fn fire(&self, event: LifecycleEvent, context: &HookContext) -> usize {
+        let mut started = 0;
+        for hook in self.matching(event, context) {
+            started += 1;
+            log::debug!("Running {} hook '{}' for node {}", event.as_str(), hook.id, context.node_id);
+
+            if let HookAction::Workflow { workflow_id } = &hook.action {
+                let result = match &self.workflows {
+                    Some(launcher) => launcher.launch(workflow_id, context.to_json(event)),
+                    None => Err("no workflow launcher configured".to_string()),
+                };
+                record_outcome(&self.outcomes, hook, event, context.node_id, result);
+                continue;
+            }
+
+            let command = match build_command(hook, event, context) {
+                Ok(command) => command,
+                Err(e) => {
+                    record_outcome(&self.outcomes, hook, event, context.node_id, Err(e));
+                    continue;
+                }
+            };
+            let hook = hook.clone();
+            let node_id = context.node_id;
+            let outcomes = self.outcomes.clone();
+            std::thread::spawn(move || {
+                let timeout = Duration::from_secs(hook.sandbox.timeout_secs);
+                let result = run_command(command, timeout);
+                record_outcome(&outcomes, &hook, event, node_id, result);
+            });
+        }
+        started
+    }
+
+    /// Recent hook outcomes, oldest first
+    pub fn recent_outcomes(&self) -> Vec<HookOutcome> {
+        self.outcomes.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+    }
+}
+
+impl Default for HookRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse hooks from TOML
+pub fn parse_hooks(content: &str) -> Result<Vec<LifecycleHook>, NodeError> {
+    let file: HooksFile = toml::from_str(content).map_err(|e| NodeError::SystemError {
+        message: format!("Invalid hooks file: {}", e),
+    })?;
+    Ok(file.hooks)
+}
+
+fn record_outcome(
+    outcomes: &Mutex<VecDeque<HookOutcome>>,
+    hook: &LifecycleHook,
+    event: LifecycleEvent,
+    node_id: SceneId,
+    result: Result<(), String>,
+) {
+    if let Err(e) = &result {
+        log::warn!("Hook '{}' failed for node {}: {}", hook.id, node_id, e);
+    }
+    let mut outcomes = outcomes.lock().unwrap_or_else(|e| e.into_inner());
+    if outcomes.len() == OUTCOME_HISTORY {
+        outcomes.pop_front();
+    }
+    outcomes.push_back(HookOutcome {
+        hook_id: hook.id.clone(),
+        event,
+        node_id,
+        result,
+    });
+}
+
+/// Build the process for a shell or script hook
+fn build_command(hook: &LifecycleHook, event: LifecycleEvent, context: &HookContext) -> Result<Command, String> {
+    let program: Vec<String> = match &hook.action {
+        HookAction::Shell { command } => vec!["sh".to_string(), "-c".to_string(), command.clone()],
+        HookAction::Script { path, args } => {
+            let mut program = vec![expand_home(path).to_string_lossy().into_owned()];
+            program.extend(args.iter().cloned());
+            program
+        }
+        HookAction::Workflow { .. } => return Err("workflow hooks do not run a command".to_string()),
+    };
+    sandboxed_command(&program, &hook.sandbox, context.env(event))
+}
+
+/// Build a process running `program` in a sandbox with a cleared environment plus `env`
+///
+/// Output is discarded except for stderr, which is kept for error messages.
+pub(crate) fn sandboxed_command(
+    program: &[String],
+    sandbox: &HookSandbox,
+    env: Vec<(&'static str, String)>,
+) -> Result<Command, String> {
+    let mut command = if sandbox.enabled {
+        let bwrap = find_in_path("bwrap").ok_or("bubblewrap (bwrap) is required for sandboxed hooks")?;
+        let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+        let mut command = Command::new(bwrap);
+        command.args(sandbox_args(sandbox, runtime_dir.as_deref())?).arg("--").args(program);
+        command
+    } else {
+        let mut command = Command::new(&program[0]);
+        command.args(&program[1..]);
+        command
+    };
+
+    // No session bus, display or runtime sockets reach the hook
+    command.env_clear();
+    for key in ["PATH", "HOME", "LANG"] {
+        if let Some(value) = std::env::var_os(key) {
+            command.env(key, value);
+        }
+    }
+    command.envs(env);
+    if let Some(home) = std::env::var_os("HOME") {
+        command.current_dir(home);
+    }
+    command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::piped());
+    Ok(command)
+}
+
+/// Bubblewrap arguments for a sandbox
+///
+/// The runtime directory, with the session bus and display sockets, is
+/// replaced by an empty one. Writable paths must exist, as bubblewrap can
+/// only bind what is there.
+fn sandbox_args(sandbox: &HookSandbox, runtime_dir: Option<&Path>) -> Result<Vec<String>, String> {
+    let mut args: Vec<String> = [
+        "--ro-bind", "/", "/",
+        "--dev", "/dev",
+        "--proc", "/proc",
+        "--tmpfs", "/tmp",
+        "--unshare-pid",
+        "--die-with-parent",
+        "--new-session",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+    if let Some(runtime_dir) = runtime_dir {
+        args.extend(["--tmpfs".to_string(), runtime_dir.to_string_lossy().into_owned()]);
+    }
+    if !sandbox.allow_network {
+        args.push("--unshare-net".to_string());
+    }
+    for path in &sandbox.writable_paths {
+        let expanded = expand_home(path);
+        if !expanded.exists() {
+            return Err(format!("writable path {} does not exist", path));
+        }
+        let expanded = expanded.to_string_lossy().into_owned();
+        args.extend(["--bind".to_string(), expanded.clone(), expanded]);
+    }
+    Ok(args)
+}
+
+/// Run a command to completion, killing it after `timeout`
+fn run_command(command: Command, timeout: Duration) -> Result<(), String> {
+    let output = run_with_timeout(command, timeout)?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("exited with {}: {}", output.status, output.stderr.trim()))
+    }
+}
+
+/// What a finished command printed
+pub(crate) struct CommandOutput {
+    pub status: std::process::ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Run a command to completion, killing it after `timeout`
+///
+/// Piped output is read while the command runs, so it can't fill the pipe
+/// and stall the command.
+pub(crate) fn run_with_timeout(mut command: Command, timeout: Duration) -> Result<CommandOutput, String> {
+    let mut child = command.spawn().map_err(|e| format!("failed to start: {}", e))?;
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {}s", timeout.as_secs()));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(e.to_string()),
+        }
+    };
+    Ok(CommandOutput {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// Read a pipe to the end on another thread
+fn read_in_background<R: std::io::Read + Send + 'static>(pipe: Option<R>) -> std::thread::JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut text = String::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_string(&mut text);
+        }
+        text
+    })
+}
+
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(program))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ if path == "~" => std::env::var_os("HOME").map(PathBuf::from).unwrap_or_else(|| PathBuf::from(path)),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOOKS: &str = r#"
+[[hook]]
+id = "inbox-sorter"
+event = "on-create"
+filter = { node_type = "file", path_prefix = "/home/ada/inbox" }
+action = { type = "workflow", workflow_id = "sort-inbox" }
+
+[[hook]]
+id = "log-open"
+event = "on-open"
+enabled = false
+filter = { tag = "work" }
+action = { type = "shell", command = "echo \"$HORIZONOS_NODE_NAME\" >> ~/opened.log" }
+sandbox = { writable_paths = ["~/opened.log"], timeout_secs = 5 }
+"#;
+
+    struct Recorder(Mutex<Vec<(String, serde_json::Value)>>);
+
+    impl WorkflowLauncher for Recorder {
+        fn launch(&self, workflow_id: &str, input: serde_json::Value) -> Result<(), String> {
+            self.0.lock().unwrap().push((workflow_id.to_string(), input));
+            Ok(())
+        }
+    }
+
+    fn file_context(path: &str) -> HookContext {
+        HookContext {
+            node_id: 7,
+            node_type: "file",
+            display_name: "report.pdf".to_string(),
+            tags: vec!["work".to_string()],
+            path: Some(PathBuf::from(path)),
+        }
+    }
+
+    #[test]
+    fn test_parse_and_match_hooks() {
+        let mut registry = HookRegistry::new();
+        for hook in parse_hooks(HOOKS).unwrap() {
+            registry.add(hook).unwrap();
+        }
+        assert_eq!(registry.hooks()[1].sandbox.timeout_secs, 5);
+        assert!(registry.hooks()[1].sandbox.enabled);
+
+        let inbox = file_context("/home/ada/inbox/report.pdf");
+        let elsewhere = file_context("/home/ada/docs/report.pdf");
+        assert_eq!(registry.matching(LifecycleEvent::Created, &inbox).count(), 1);
+        assert_eq!(registry.matching(LifecycleEvent::Created, &elsewhere).count(), 0);
+
+        // Disabled until toggled on
+        assert_eq!(registry.matching(LifecycleEvent::Opened, &elsewhere).count(), 0);
+        assert!(registry.set_enabled("log-open", true));
+        assert_eq!(registry.matching(LifecycleEvent::Opened, &elsewhere).count(), 1);
+
+        let recorder = Arc::new(Recorder(Mutex::new(Vec::new())));
+        registry.set_workflow_launcher(recorder.clone());
+        assert_eq!(registry.fire(LifecycleEvent::Created, &inbox), 1);
+        let launched = recorder.0.lock().unwrap();
+        assert_eq!(launched[0].0, "sort-inbox");
+        assert_eq!(launched[0].1["path"], "/home/ada/inbox/report.pdf");
+        assert_eq!(registry.recent_outcomes()[0].result, Ok(()));
+    }
+
+    #[test]
+    fn test_sandbox_arguments() {
+        let out = tempfile::tempdir().unwrap();
+        let out = out.path().to_string_lossy().into_owned();
+        let sandbox = HookSandbox {
+            writable_paths: vec![out.clone()],
+            ..Default::default()
+        };
+        let args = sandbox_args(&sandbox, Some(Path::new("/run/user/1000"))).unwrap();
+        assert!(args.contains(&"--unshare-net".to_string()));
+        assert!(args.windows(3).any(|w| w == ["--bind", out.as_str(), out.as_str()]));
+        assert!(args.windows(2).any(|w| w == ["--tmpfs", "/run/user/1000"]));
+        for flag in ["--die-with-parent", "--new-session", "--unshare-pid"] {
+            assert!(args.contains(&flag.to_string()));
+        }
+
+        let missing = HookSandbox {
+            writable_paths: vec![format!("{}/missing", out)],
+            ..Default::default()
+        };
+        assert_eq!(
+            sandbox_args(&missing, None).unwrap_err(),
+            format!("writable path {}/missing does not exist", out)
+        );
+
+        // Only the basics of the environment are passed on
+        let unsandboxed = HookSandbox { enabled: false, ..Default::default() };
+        let command = sandboxed_command(&["true".to_string()], &unsandboxed, Vec::new()).unwrap();
+        for (key, _) in command.get_envs() {
+            assert!(["PATH", "HOME", "LANG"].contains(&key.to_str().unwrap()), "{:?} passed on", key);
+        }
+
+        let env = file_context("/tmp/a b; rm -rf ~").env(LifecycleEvent::Deleted);
+        assert!(env.contains(&("HORIZONOS_NODE_PATH", "/tmp/a b; rm -rf ~".to_string())));
+        assert!(env.contains(&("HORIZONOS_HOOK_EVENT", "on-delete".to_string())));
+    }
+}
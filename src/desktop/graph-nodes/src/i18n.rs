@@ -0,0 +1,9 @@
+//! Translations for node labels and action messages
+
+use horizonos_graph_i18n::Domain;
+
+/// Node strings, in `locales/<locale>/nodes.ftl`
+pub static I18N: Domain = Domain::new("nodes", &[
+    ("en-US", include_str!("../locales/en-US/nodes.ftl")),
+    ("de", include_str!("../locales/de/nodes.ftl")),
+]);
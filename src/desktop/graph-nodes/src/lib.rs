@@ -18,6 +18,14 @@ pub mod url;
 pub mod automation;
 pub mod setting;
 pub mod config_group;
+pub mod group;
+pub mod manipulation;
+pub mod i18n;
+pub mod hooks;
+pub mod status_badges;
+pub mod search;
+pub mod query;
+pub mod batch;
 
 pub use application::*;
 pub use file::*;
@@ -32,8 +40,14 @@ pub use url::*;
 pub use automation::*;
 pub use setting::*;
 pub use config_group::*;
+pub use group::*;
+pub use manipulation::*;
+pub use search::{NodeSearchIndex, SearchDocument, SearchHit, MatchField};
+pub use query::{GraphQuery, NodePattern, EdgePattern, EdgeDirection, Condition};
+pub use batch::{NodeBatchEvent, NodeBatchKind};
 
 use std::collections::HashMap;
+use horizonos_graph_errors::{Category, Classify, Problem, Severity};
 use serde::{Serialize, Deserialize};
 
 /// Trait that all node implementations must implement
@@ -63,6 +77,8 @@ pub trait GraphNode: Send + Sync {
             description: self.description(),
             tags: vec![],
             properties: std::collections::HashMap::new(),
+            privacy: Default::default(),
+            persistence: Default::default(),
         }
     }
     
@@ -78,6 +94,26 @@ pub trait GraphNode: Send + Sync {
     /// Get available actions for this node
     fn available_actions(&self) -> Vec<NodeActionType>;
     
+    /// Get the direct manipulations this node supports
+    fn manipulation(&self) -> ManipulationCapabilities {
+        ManipulationCapabilities::for_node_type(&self.node_type())
+    }
+    
+    /// Apply a transform set through manipulation handles
+    fn set_transform(&mut self, _transform: NodeTransform) -> Result<(), NodeError> {
+        Err(NodeError::PermissionDenied { operation: "transform".to_string() })
+    }
+    
+    /// The automation this node is, for the scheduler running automation scripts
+    fn as_automation(&self) -> Option<&AutomationNode> {
+        None
+    }
+    
+    /// Mutable access to the automation this node is
+    fn as_automation_mut(&mut self) -> Option<&mut AutomationNode> {
+        None
+    }
+    
     /// Export node data for serialization
     fn export_data(&self) -> Result<NodeExportData, NodeError>;
     
@@ -96,6 +132,17 @@ pub struct NodeVisualData {
     pub glow: bool,
     pub selected: bool,
     pub visible: bool,
+    #[serde(default)]
+    pub extensions: NodeVisualExtensions,
+}
+
+impl NodeVisualData {
+    /// Radius of the area the node occupies, including any transform
+    pub fn footprint_radius(&self) -> f32 {
+        self.extensions.transform
+            .map(|transform| transform.bounding_radius())
+            .unwrap_or(self.radius)
+    }
 }
 
 impl Default for NodeVisualData {
@@ -109,6 +156,7 @@ impl Default for NodeVisualData {
             glow: false,
             selected: false,
             visible: true,
+            extensions: NodeVisualExtensions::default(),
         }
     }
 }
@@ -147,6 +195,36 @@ pub enum NodeActionType {
     Custom(String),
 }
 
+impl NodeActionType {
+    /// Localized label for menus and tooltips
+    pub fn label(&self) -> String {
+        use horizonos_graph_i18n::tr;
+        match self {
+            NodeActionType::Open => tr!(i18n::I18N, "action-open"),
+            NodeActionType::Edit => tr!(i18n::I18N, "action-edit"),
+            NodeActionType::Delete => tr!(i18n::I18N, "action-delete"),
+            NodeActionType::Copy => tr!(i18n::I18N, "action-copy"),
+            NodeActionType::Move => tr!(i18n::I18N, "action-move"),
+            NodeActionType::Connect => tr!(i18n::I18N, "action-connect"),
+            NodeActionType::Disconnect => tr!(i18n::I18N, "action-disconnect"),
+            NodeActionType::Custom(name) => {
+                let id = format!("action-custom-{}", name.replace('_', "-"));
+                i18n::I18N.try_tr(&id, None).unwrap_or_else(|| humanize_action(name))
+            }
+        }
+    }
+}
+
+/// `show_in_folder` -> `Show in folder`, for custom actions without a translation
+fn humanize_action(name: &str) -> String {
+    let words = name.replace(['_', '-'], " ");
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
 /// Result of performing an action on a node
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NodeActionResult {
@@ -199,6 +277,20 @@ pub enum NodeError {
     SystemError { message: String },
 }
 
+impl Classify for NodeError {
+    fn classify(&self) -> Problem {
+        let problem = match self {
+            NodeError::InvalidAction { .. } => Problem::new("nodes.invalid_action", Category::Graph, Severity::Info, "This action is not available for the node"),
+            NodeError::NodeNotFound { id } => Problem::new("nodes.not_found", Category::Graph, Severity::Info, "Node no longer exists").with_node(*id),
+            NodeError::PermissionDenied { .. } => Problem::new("nodes.permission_denied", Category::Permission, Severity::Error, "Permission denied"),
+            NodeError::IoError(_) => Problem::new("nodes.io", Category::Io, Severity::Error, "The node's file could not be accessed"),
+            NodeError::SerializationError(_) => Problem::new("nodes.data_corrupt", Category::Storage, Severity::Error, "Node data is unreadable"),
+            NodeError::SystemError { .. } => Problem::new("nodes.internal", Category::Internal, Severity::Error, "Node operation failed"),
+        };
+        problem.in_subsystem("nodes").with_detail(self)
+    }
+}
+
 /// Base node implementation that provides common functionality
 #[derive(Debug, Clone)]
 pub struct BaseNode {
@@ -233,6 +325,12 @@ impl BaseNode {
         self
     }
     
+    /// Store a manipulation transform, clamping it to sane limits
+    pub fn set_transform(&mut self, transform: NodeTransform) {
+        self.visual_data.extensions.transform = Some(transform.clamped());
+        self.update_timestamp();
+    }
+    
     pub fn update_timestamp(&mut self) {
         self.last_update = std::time::Instant::now();
         self.metadata.updated_at = chrono::Utc::now();
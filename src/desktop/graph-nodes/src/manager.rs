@@ -1,14 +1,26 @@
 //! Node manager for the graph desktop
 
-use crate::{GraphNode, ApplicationNode, FileNode, PersonNode, TaskNode, NodeError, NodeAction, NodeActionResult};
-use horizonos_graph_engine::{SceneId, Scene};
-use std::collections::HashMap;
+use crate::{GraphNode, ApplicationNode, AutomationEvent, AutomationNode, ExecutionResult, ConceptNode, FileNode, PersonNode, TaskNode, NodeError, NodeAction, NodeActionResult, NodeActionType, NodeVisualData};
+use crate::batch::{NodeBatchEvent, NodeBatchKind};
+use crate::hooks::{HookContext, HookRegistry, LifecycleEvent};
+use crate::search::{NodeSearchIndex, SearchHit};
+use crate::status_badges::StatusBadgeRegistry;
+use horizonos_graph_engine::{Change, CommandHistory, NodeMetadata, NodePersistence, NodeType, SceneChange, SceneId, Scene, SceneNode, SceneSnapshot};
+use crossbeam_channel::{Receiver, Sender};
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 
 /// Manages all nodes in the graph desktop
 pub struct NodeManager {
     nodes: Arc<RwLock<HashMap<SceneId, Box<dyn GraphNode + Send + Sync>>>>,
     next_id: SceneId,
+    hooks: HookRegistry,
+    status_badges: StatusBadgeRegistry,
+    search_index: Arc<RwLock<NodeSearchIndex>>,
+    /// Receivers of bulk operation events
+    batch_subscribers: Vec<Sender<NodeBatchEvent>>,
+    /// Receivers of script automation changes and run requests
+    automation_subscribers: Vec<Sender<AutomationEvent>>,
 }
 
 impl NodeManager {
@@ -16,6 +28,11 @@ impl NodeManager {
         NodeManager {
             nodes: Arc::new(RwLock::new(HashMap::new())),
             next_id: 1,
+            hooks: HookRegistry::new(),
+            status_badges: StatusBadgeRegistry::new(),
+            search_index: Arc::new(RwLock::new(NodeSearchIndex::new())),
+            batch_subscribers: Vec::new(),
+            automation_subscribers: Vec::new(),
         }
     }
     
@@ -26,14 +43,247 @@ impl NodeManager {
         id
     }
     
+    /// Lifecycle hooks run when nodes are created, opened or deleted
+    pub fn hooks(&self) -> &HookRegistry {
+        &self.hooks
+    }
+    
+    /// Mutable access to the lifecycle hooks
+    pub fn hooks_mut(&mut self) -> &mut HookRegistry {
+        &mut self.hooks
+    }
+    
+    /// Badges driven by external commands
+    pub fn status_badges(&self) -> &StatusBadgeRegistry {
+        &self.status_badges
+    }
+    
+    /// Mutable access to the status badges
+    pub fn status_badges_mut(&mut self) -> &mut StatusBadgeRegistry {
+        &mut self.status_badges
+    }
+    
+    /// Start the status badge commands that are due, returning how many were started
+    pub fn refresh_status_badges(&self) -> usize {
+        let contexts: Vec<HookContext> = self.nodes.read().unwrap()
+            .values()
+            .map(|node| HookContext::from_node(node.as_ref()))
+            .collect();
+        self.status_badges.refresh(&contexts)
+    }
+    
+    /// Visual data of a node, with its status badge reading applied
+    pub fn visual_data(&self, id: SceneId) -> Option<NodeVisualData> {
+        let nodes = self.nodes.read().unwrap();
+        let mut visual = nodes.get(&id)?.visual_data();
+        self.status_badges.apply(id, &mut visual);
+        Some(visual)
+    }
+    
     /// Add a node to the manager
+    ///
+    /// Nodes created with a scene ID move the ID counter past it, so later
+    /// nodes don't reuse it.
     pub fn add_node(&mut self, node: Box<dyn GraphNode + Send + Sync>) -> Result<SceneId, NodeError> {
         let id = node.id();
-        let mut nodes = self.nodes.write().unwrap();
-        nodes.insert(id, node);
+        self.next_id = self.next_id.max(id + 1);
+        let context = HookContext::from_node(node.as_ref());
+        let script = script_of(node.as_ref());
+        self.search_index.write().unwrap().index_node(node.as_ref());
+        self.nodes.write().unwrap().insert(id, node);
+        self.hooks.fire(LifecycleEvent::Created, &context);
+        if let Some(script) = script {
+            self.emit_automation(AutomationEvent::Saved(Box::new(script)));
+        }
         Ok(id)
     }
     
+    /// Receive one event per bulk add, update or remove, e.g. to update layout and physics
+    pub fn subscribe_batches(&mut self) -> Receiver<NodeBatchEvent> {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        self.batch_subscribers.push(tx);
+        rx
+    }
+    
+    /// Add many nodes to the manager and the scene as one undoable transaction
+    ///
+    /// Nodes keep their IDs; the batch is rejected before anything changes if
+    /// an ID is already taken or repeated. Creation hooks still run per node.
+    pub fn add_nodes(
+        &mut self,
+        nodes: Vec<Box<dyn GraphNode + Send + Sync>>,
+        scene: &mut Scene,
+        history: &mut CommandHistory,
+    ) -> Result<Vec<SceneId>, NodeError> {
+        let mut event = NodeBatchEvent::new(NodeBatchKind::Added);
+        let mut contexts = Vec::with_capacity(nodes.len());
+        let mut scripts = Vec::new();
+        {
+            let mut managed = self.nodes.write().unwrap();
+            let mut seen = HashSet::with_capacity(nodes.len());
+            if let Some(node) = nodes.iter().find(|node| {
+                let id = node.id();
+                !seen.insert(id) || managed.contains_key(&id) || scene.get_node(id).is_some()
+            }) {
+                return Err(NodeError::SystemError { message: format!("Node {} already exists", node.id()) });
+            }
+            
+            let mut search_index = self.search_index.write().unwrap();
+            history.begin(format!("Add {} nodes", nodes.len()));
+            for node in nodes {
+                let id = node.id();
+                self.next_id = self.next_id.max(id + 1);
+                let scene_node = SceneNode { id, ..node.to_scene_node() };
+                scene.insert_node(scene_node.clone());
+                history.record(Change::NodeAdded(scene_node));
+                search_index.index_node(node.as_ref());
+                contexts.push(HookContext::from_node(node.as_ref()));
+                scripts.extend(script_of(node.as_ref()));
+                managed.insert(id, node);
+                event.nodes.push(id);
+                event.changes.push(SceneChange::NodeAdded { id });
+            }
+            history.commit();
+        }
+        
+        for context in &contexts {
+            self.hooks.fire(LifecycleEvent::Created, context);
+        }
+        for script in scripts {
+            self.emit_automation(AutomationEvent::Saved(Box::new(script)));
+        }
+        let ids = event.nodes.clone();
+        self.emit_batch(event);
+        Ok(ids)
+    }
+    
+    /// Apply new position, size, color, visibility and selection to many scene nodes as one transaction
+    ///
+    /// Nodes missing from the scene and updates that change nothing are
+    /// skipped; returns the nodes that changed.
+    pub fn update_visuals_batch(
+        &mut self,
+        updates: &[(SceneId, NodeVisualData)],
+        scene: &mut Scene,
+        history: &mut CommandHistory,
+    ) -> Vec<SceneId> {
+        let mut event = NodeBatchEvent::new(NodeBatchKind::Updated);
+        history.begin(format!("Change {} nodes", updates.len()));
+        for (id, visual) in updates {
+            let Some(from) = scene.get_node(*id).cloned() else {
+                continue;
+            };
+            let to = SceneNode {
+                position: visual.position.into(),
+                radius: visual.radius,
+                color: visual.color,
+                visible: visual.visible,
+                selected: visual.selected,
+                ..from.clone()
+            };
+            if to.position == from.position
+                && to.radius == from.radius
+                && to.color == from.color
+                && to.visible == from.visible
+                && to.selected == from.selected
+            {
+                continue;
+            }
+            if to.position != from.position {
+                event.changes.push(SceneChange::NodeMoved { id: *id, old_position: from.position, new_position: to.position });
+            }
+            scene.insert_node(to.clone());
+            history.record(Change::NodeUpdated { from: Box::new(from), to: Box::new(to) });
+            event.nodes.push(*id);
+        }
+        history.commit();
+        
+        let ids = event.nodes.clone();
+        self.emit_batch(event);
+        ids
+    }
+    
+    /// Remove many nodes and their edges from the manager and the scene as one undoable transaction
+    ///
+    /// Unknown IDs are skipped; returns the nodes that were removed.
+    /// Deletion hooks still run per node.
+    pub fn remove_nodes(&mut self, ids: &[SceneId], scene: &mut Scene, history: &mut CommandHistory) -> Vec<SceneId> {
+        let mut event = NodeBatchEvent::new(NodeBatchKind::Removed);
+        let mut contexts = Vec::with_capacity(ids.len());
+        let mut scripts = Vec::new();
+        {
+            let mut managed = self.nodes.write().unwrap();
+            let mut search_index = self.search_index.write().unwrap();
+            history.begin(format!("Delete {} nodes", ids.len()));
+            for &id in ids {
+                let edges: Vec<_> = scene.get_connected_edges(id).into_iter().cloned().collect();
+                let from_scene = scene.remove_node(id);
+                let from_manager = managed.remove(&id);
+                if from_scene.is_none() && from_manager.is_none() {
+                    continue;
+                }
+                search_index.remove(id);
+                self.status_badges.forget_node(id);
+                if let Some(node) = from_manager {
+                    contexts.push(HookContext::from_node(node.as_ref()));
+                    scripts.extend(script_of(node.as_ref()).map(|script| script.id));
+                }
+                for edge in &edges {
+                    event.changes.push(SceneChange::EdgeRemoved { id: edge.id, source: edge.source, target: edge.target });
+                }
+                if let Some(node) = from_scene {
+                    history.record(Change::NodeRemoved { node, edges });
+                }
+                event.nodes.push(id);
+                event.changes.push(SceneChange::NodeRemoved { id });
+            }
+            history.commit();
+        }
+        
+        for context in &contexts {
+            self.hooks.fire(LifecycleEvent::Deleted, context);
+        }
+        for id in scripts {
+            self.emit_automation(AutomationEvent::Removed(id));
+        }
+        let removed = event.nodes.clone();
+        self.emit_batch(event);
+        removed
+    }
+    
+    /// Send a bulk operation event to subscribers, dropping disconnected ones
+    fn emit_batch(&mut self, event: NodeBatchEvent) {
+        if event.is_empty() {
+            return;
+        }
+        self.batch_subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+    }
+    
+    /// Receive script automations as they are added, edited, run or removed, e.g. to register them with the scheduler
+    pub fn subscribe_automations(&mut self) -> Receiver<AutomationEvent> {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        self.automation_subscribers.push(tx);
+        rx
+    }
+    
+    fn emit_automation(&mut self, event: AutomationEvent) {
+        self.automation_subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+    }
+    
+    /// Copy of an automation node, e.g. to show its last run
+    pub fn automation(&self, id: SceneId) -> Option<AutomationNode> {
+        self.nodes.read().unwrap().get(&id)?.as_automation().cloned()
+    }
+    
+    /// Record the result of a script run requested through an automation's `execute`
+    pub fn record_automation_run(&mut self, id: SceneId, result: ExecutionResult) -> Result<(), NodeError> {
+        let mut nodes = self.nodes.write().unwrap();
+        let node = nodes.get_mut(&id).ok_or(NodeError::NodeNotFound { id })?;
+        node.as_automation_mut()
+            .ok_or_else(|| NodeError::SystemError { message: format!("Node {} is not an automation", id) })?
+            .record_run(result)
+    }
+    
     /// Get a node by ID
     pub fn get_node(&self, _id: SceneId) -> Option<Box<dyn GraphNode + Send + Sync>> {
         let _nodes = self.nodes.read().unwrap();
@@ -43,11 +293,50 @@ impl NodeManager {
     
     /// Remove a node
     pub fn remove_node(&mut self, id: SceneId) -> Result<(), NodeError> {
-        let mut nodes = self.nodes.write().unwrap();
-        nodes.remove(&id);
+        let removed = self.nodes.write().unwrap().remove(&id);
+        self.search_index.write().unwrap().remove(id);
+        self.status_badges.forget_node(id);
+        if let Some(node) = removed {
+            self.hooks.fire(LifecycleEvent::Deleted, &HookContext::from_node(node.as_ref()));
+            if script_of(node.as_ref()).is_some() {
+                self.emit_automation(AutomationEvent::Removed(id));
+            }
+        }
         Ok(())
     }
     
+    /// Find the node of a running application, by process ID or else by name
+    ///
+    /// Names are compared ignoring case, so both a desktop entry name and an
+    /// application ID such as `firefox` match.
+    pub fn find_application(&self, name: &str, pid: Option<u32>) -> Option<SceneId> {
+        let nodes = self.nodes.read().unwrap();
+        let applications = || nodes.iter().filter_map(|(&id, node)| match node.node_type() {
+            NodeType::Application { pid, name } => Some((id, pid, name)),
+            _ => None,
+        });
+        pid.filter(|&pid| pid != 0)
+            .and_then(|pid| applications().find(|(_, app_pid, _)| *app_pid == pid))
+            .or_else(|| applications().find(|(_, _, app_name)| app_name.eq_ignore_ascii_case(name)))
+            .map(|(id, _, _)| id)
+    }
+    
+    /// Create a concept node with the given ID, e.g. one taken from the scene
+    pub fn create_concept(&mut self, id: SceneId, title: String, content: String, metadata: NodeMetadata) -> Result<SceneId, NodeError> {
+        let concept = ConceptNode::new(id).with_content(title, content).with_metadata(metadata);
+        self.add_node(Box::new(concept))
+    }
+    
+    /// Search index of all nodes, shared with the search palette
+    pub fn search_index(&self) -> Arc<RwLock<NodeSearchIndex>> {
+        self.search_index.clone()
+    }
+    
+    /// Fuzzy search node names, descriptions and metadata
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        self.search_index.read().unwrap().search(query, limit)
+    }
+    
     /// Update all nodes
     pub fn update_all(&mut self, delta_time: f32) -> Result<(), NodeError> {
         let mut nodes = self.nodes.write().unwrap();
@@ -57,14 +346,72 @@ impl NodeManager {
         Ok(())
     }
     
+    /// Actions a node offers, e.g. for its context menu; empty for unknown nodes
+    pub fn available_actions(&self, id: SceneId) -> Vec<NodeActionType> {
+        self.nodes.read().unwrap()
+            .get(&id)
+            .map(|node| node.available_actions())
+            .unwrap_or_default()
+    }
+    
+    /// Name a node is shown with
+    pub fn display_name(&self, id: SceneId) -> Option<String> {
+        self.nodes.read().unwrap().get(&id).map(|node| node.display_name())
+    }
+    
     /// Handle action on a specific node
     pub fn handle_node_action(&mut self, id: SceneId, action: NodeAction) -> Result<NodeActionResult, NodeError> {
         let mut nodes = self.nodes.write().unwrap();
-        if let Some(node) = nodes.get_mut(&id) {
-            node.handle_action(action)
-        } else {
-            Err(NodeError::NodeNotFound { id })
+        let node = nodes.get_mut(&id).ok_or(NodeError::NodeNotFound { id })?;
+        let opening = matches!(action, NodeAction::Open);
+        let script_before = script_of(node.as_ref()).map(|script| (script.content, script.parameters));
+        let result = node.handle_action(action)?;
+        // Actions such as renaming change what the node is found by
+        self.search_index.write().unwrap().index_node(node.as_ref());
+        
+        // Edited scripts are registered again before a requested run
+        let mut automation_events = Vec::new();
+        if let Some(script) = script_of(node.as_ref()) {
+            if script_before != Some((script.content.clone(), script.parameters.clone())) {
+                automation_events.push(AutomationEvent::Saved(Box::new(script)));
+            }
+        }
+        if node.as_automation_mut().is_some_and(|automation| automation.take_queued_run()) {
+            automation_events.push(AutomationEvent::RunQueued(id));
+        }
+        
+        let opened = (opening && !matches!(result, NodeActionResult::Error { .. }))
+            .then(|| HookContext::from_node(node.as_ref()));
+        drop(nodes);
+        if let Some(context) = opened {
+            self.hooks.fire(LifecycleEvent::Opened, &context);
+        }
+        for event in automation_events {
+            self.emit_automation(event);
+        }
+        Ok(result)
+    }
+    
+    /// Snapshot of the scene for saving, with node data from the node implementations
+    ///
+    /// Positions and appearance come from the scene; type data and metadata,
+    /// which node actions such as renaming change, from the managed nodes.
+    /// The persistence class is the one declared in the scene, and nodes
+    /// that are not durable are left out.
+    pub fn snapshot(&self, scene: &Scene) -> SceneSnapshot {
+        let mut snapshot = SceneSnapshot::capture_for(scene, NodePersistence::Durable);
+        let nodes = self.nodes.read().unwrap();
+        for scene_node in &mut snapshot.nodes {
+            if let Some(node) = nodes.get(&scene_node.id) {
+                let current = node.to_scene_node();
+                scene_node.node_type = current.node_type;
+                scene_node.metadata = NodeMetadata {
+                    persistence: scene_node.metadata.persistence,
+                    ..current.metadata
+                };
+            }
         }
+        snapshot
     }
     
     /// Sync nodes to scene for rendering
@@ -105,6 +452,11 @@ impl NodeManager {
     }
 }
 
+/// Copy of a node's automation if it carries a script
+fn script_of(node: &(dyn GraphNode + Send + Sync)) -> Option<AutomationNode> {
+    node.as_automation().filter(|automation| automation.is_script()).cloned()
+}
+
 impl Default for NodeManager {
     fn default() -> Self {
         Self::new()
@@ -135,4 +487,132 @@ mod tests {
         let id = manager.create_person("Alice".to_string()).unwrap();
         assert_eq!(id, 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_nodes_are_searchable() {
+        let mut manager = NodeManager::new();
+        manager.create_person("Alice".to_string()).unwrap();
+        let task = manager.create_task("Write quarterly report".to_string()).unwrap();
+        assert_eq!(manager.search("qrep", 5)[0].node_id, task);
+        manager.remove_node(task).unwrap();
+        assert!(manager.search("qrep", 5).is_empty());
+    }
+
+    #[test]
+    fn test_find_application_and_scene_ids() {
+        let mut manager = NodeManager::new();
+        let app = manager.create_application("Firefox".to_string(), "/usr/bin/firefox".to_string()).unwrap();
+        assert_eq!(manager.find_application("firefox", None), Some(app));
+        assert_eq!(manager.find_application("thunderbird", None), None);
+
+        let concept = manager.create_concept(10, "Build failed".to_string(), String::new(), NodeMetadata::default()).unwrap();
+        assert_eq!(concept, 10);
+        assert_eq!(manager.next_id(), 11);
+        assert_eq!(manager.find_application("Build failed", None), None);
+    }
+
+    #[test]
+    fn test_available_actions_of_managed_nodes() {
+        let mut manager = NodeManager::new();
+        let task = manager.create_task("Review budget".to_string()).unwrap();
+        assert!(manager.available_actions(task).contains(&NodeActionType::Open));
+        assert_eq!(manager.display_name(task).as_deref(), Some("Review budget"));
+        assert!(manager.available_actions(999).is_empty());
+        assert_eq!(manager.display_name(999), None);
+    }
+
+    #[test]
+    fn test_batch_operations_are_one_transaction_and_one_event() {
+        let mut manager = NodeManager::new();
+        let mut scene = Scene::new();
+        let mut history = CommandHistory::new();
+        let events = manager.subscribe_batches();
+
+        let nodes: Vec<Box<dyn GraphNode + Send + Sync>> = (1..=100)
+            .map(|id| Box::new(TaskNode::new(id, format!("Task {}", id))) as Box<dyn GraphNode + Send + Sync>)
+            .collect();
+        let added = manager.add_nodes(nodes, &mut scene, &mut history).unwrap();
+        assert_eq!(added.len(), 100);
+        assert_eq!(scene.nodes().count(), 100);
+        assert_eq!(manager.next_id(), 101);
+        assert_eq!(history.undo_label(), Some("Add 100 nodes"));
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.kind, NodeBatchKind::Added);
+        assert_eq!(event.changes.len(), 100);
+        assert!(events.try_recv().is_err());
+
+        // Taken IDs reject the whole batch
+        let clash: Vec<Box<dyn GraphNode + Send + Sync>> = vec![
+            Box::new(TaskNode::new(200, "New".into())),
+            Box::new(TaskNode::new(5, "Old".into())),
+        ];
+        assert!(manager.add_nodes(clash, &mut scene, &mut history).is_err());
+        assert!(scene.get_node(200).is_none());
+
+        let mut moved = manager.visual_data(1).unwrap();
+        moved.position = [10.0, 0.0, 0.0];
+        let mut recolored = manager.visual_data(2).unwrap();
+        recolored.color = [0.0, 0.0, 1.0, 1.0];
+        let unchanged = manager.visual_data(3).unwrap();
+        let updates = vec![(1, moved), (2, recolored), (3, unchanged), (999, manager.visual_data(4).unwrap())];
+        assert_eq!(manager.update_visuals_batch(&updates, &mut scene, &mut history), vec![1, 2]);
+        assert_eq!(scene.get_node(2).unwrap().color, [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(events.try_recv().unwrap().moved_nodes(), vec![1]);
+
+        let removed = manager.remove_nodes(&[1, 2, 3, 999], &mut scene, &mut history);
+        assert_eq!(removed, vec![1, 2, 3]);
+        assert_eq!(scene.nodes().count(), 97);
+        assert!(manager.display_name(2).is_none());
+        assert_eq!(events.try_recv().unwrap().kind, NodeBatchKind::Removed);
+
+        // Each batch undoes in one step
+        history.undo(&mut scene);
+        assert_eq!(scene.nodes().count(), 100);
+        history.undo(&mut scene);
+        assert_eq!(scene.get_node(1).unwrap().position.x, 0.0);
+        assert_eq!(scene.get_node(2).unwrap().color, [0.9, 0.6, 0.2, 1.0]);
+        history.undo(&mut scene);
+        assert_eq!(scene.nodes().count(), 0);
+    }
+
+    #[test]
+    fn test_script_automations_are_announced_and_runs_queued() {
+        let mut manager = NodeManager::new();
+        let events = manager.subscribe_automations();
+        manager.add_node(Box::new(TaskNode::new(1, "Not a script".into()))).unwrap();
+        manager.add_node(Box::new(AutomationNode::new_script(2, "Linker".into(), "notify(\"hi\")".into()))).unwrap();
+        assert!(matches!(events.try_recv().unwrap(), AutomationEvent::Saved(script) if script.id == 2));
+        assert!(events.try_recv().is_err());
+
+        let set_script = NodeAction::Custom {
+            action_type: "set_script".into(),
+            parameters: HashMap::from([("source".to_string(), "notify(\"bye\")".to_string())]),
+        };
+        manager.handle_node_action(2, set_script).unwrap();
+        assert!(matches!(events.try_recv().unwrap(), AutomationEvent::Saved(script) if script.content == "notify(\"bye\")"));
+
+        // Running an unchanged script only queues the run
+        let execute = NodeAction::Custom { action_type: "execute".into(), parameters: HashMap::new() };
+        manager.handle_node_action(2, execute).unwrap();
+        assert!(matches!(events.try_recv().unwrap(), AutomationEvent::RunQueued(2)));
+        assert!(events.try_recv().is_err());
+
+        let now = chrono::Utc::now();
+        let result = ExecutionResult {
+            success: true,
+            exit_code: None,
+            execution_time: 3,
+            stdout: String::new(),
+            stderr: String::new(),
+            start_time: now,
+            end_time: now,
+            error: None,
+        };
+        manager.record_automation_run(2, result.clone()).unwrap();
+        assert_eq!(manager.automation(2).unwrap().success_count, 1);
+        assert!(manager.record_automation_run(1, result).is_err());
+
+        manager.remove_node(2).unwrap();
+        assert!(matches!(events.try_recv().unwrap(), AutomationEvent::Removed(2)));
+    }
+}
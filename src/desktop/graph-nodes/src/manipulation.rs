@@ -0,0 +1,155 @@
+//! Direct manipulation support: which node types can be resized, rotated or
+//! scaled, and the transform stored for them
+
+use horizonos_graph_engine::{FileType, NodeType};
+use serde::{Deserialize, Serialize};
+
+/// Manipulations a node type opts in to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ManipulationCapabilities {
+    /// Width and height can be changed independently
+    pub resize: bool,
+    /// Node can be rotated
+    pub rotate: bool,
+    /// Node can be scaled uniformly
+    pub scale: bool,
+    /// Resizing keeps the aspect ratio unless overridden
+    pub lock_aspect_ratio: bool,
+}
+
+impl ManipulationCapabilities {
+    /// No direct manipulation
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Resize, rotate and scale
+    pub fn all() -> Self {
+        Self {
+            resize: true,
+            rotate: true,
+            scale: true,
+            lock_aspect_ratio: false,
+        }
+    }
+
+    /// Default capabilities for a node type
+    pub fn for_node_type(node_type: &NodeType) -> Self {
+        match node_type {
+            // Image concepts and annotation regions
+            NodeType::Concept { .. } => Self::all(),
+            // Images keep their proportions by default
+            NodeType::File { file_type: FileType::Image, .. } => Self {
+                lock_aspect_ratio: true,
+                ..Self::all()
+            },
+            // Group containers are resized to fit their members, never rotated
+            NodeType::ConfigGroup { .. } => Self {
+                resize: true,
+                rotate: false,
+                scale: false,
+                lock_aspect_ratio: false,
+            },
+            _ => Self::none(),
+        }
+    }
+
+    /// Whether any manipulation is allowed
+    pub fn is_manipulable(&self) -> bool {
+        self.resize || self.rotate || self.scale
+    }
+}
+
+/// Size, rotation and scale applied to a node on top of its radius
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NodeTransform {
+    /// Width and height in world units, before scaling
+    pub size: [f32; 2],
+    /// Rotation around the view axis, in radians
+    pub rotation: f32,
+    /// Uniform scale factor
+    pub scale: f32,
+    /// Keep the aspect ratio when resizing
+    pub aspect_locked: bool,
+}
+
+impl NodeTransform {
+    /// Smallest allowed width or height
+    pub const MIN_SIZE: f32 = 0.1;
+    /// Scale limits
+    pub const MIN_SCALE: f32 = 0.1;
+    pub const MAX_SCALE: f32 = 10.0;
+
+    /// Transform matching a round node of the given radius
+    pub fn from_radius(radius: f32) -> Self {
+        Self {
+            size: [radius * 2.0, radius * 2.0],
+            rotation: 0.0,
+            scale: 1.0,
+            aspect_locked: false,
+        }
+    }
+
+    /// Transform with size and scale clamped to their limits and rotation normalized
+    pub fn clamped(mut self) -> Self {
+        self.size[0] = self.size[0].max(Self::MIN_SIZE);
+        self.size[1] = self.size[1].max(Self::MIN_SIZE);
+        self.scale = self.scale.clamp(Self::MIN_SCALE, Self::MAX_SCALE);
+        self.rotation = self.rotation.rem_euclid(std::f32::consts::TAU);
+        self
+    }
+
+    /// Width and height after scaling
+    pub fn scaled_size(&self) -> [f32; 2] {
+        [self.size[0] * self.scale, self.size[1] * self.scale]
+    }
+
+    /// Radius of the circle enclosing the node at any rotation
+    pub fn bounding_radius(&self) -> f32 {
+        let [width, height] = self.scaled_size();
+        (width * width + height * height).sqrt() * 0.5
+    }
+
+    /// Width divided by height
+    pub fn aspect_ratio(&self) -> f32 {
+        self.size[0] / self.size[1].max(f32::EPSILON)
+    }
+}
+
+/// Type-specific visual extensions stored alongside the basic visual data
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeVisualExtensions {
+    /// Transform set through manipulation handles
+    #[serde(default)]
+    pub transform: Option<NodeTransform>,
+    /// Show the node's live thumbnail, e.g. of its window, on the node face
+    #[serde(default)]
+    pub live_thumbnail: bool,
+    /// State of a command-driven status badge, drawn as the badge color
+    #[serde(default)]
+    pub badge_state: Option<crate::status_badges::BadgeState>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_by_node_type() {
+        let concept = NodeType::Concept { title: "Sketch".to_string(), content: String::new() };
+        assert!(ManipulationCapabilities::for_node_type(&concept).rotate);
+
+        let task = NodeType::Task { title: "Todo".to_string(), status: horizonos_graph_engine::TaskStatus::Todo };
+        assert!(!ManipulationCapabilities::for_node_type(&task).is_manipulable());
+    }
+
+    #[test]
+    fn test_transform_bounding_radius() {
+        let mut transform = NodeTransform::from_radius(1.0);
+        assert!((transform.bounding_radius() - 2.0f32.sqrt()).abs() < 1e-5);
+
+        transform.scale = 2.0;
+        transform.size = [3.0, 4.0];
+        assert!((transform.bounding_radius() - 5.0).abs() < 1e-5);
+    }
+}
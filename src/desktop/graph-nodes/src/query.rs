@@ -0,0 +1,603 @@
+//! Graph queries selecting nodes by type, metadata and the paths they lie on
+//!
+//! A [`GraphQuery`] starts at a node pattern and may continue through edges
+//! to further node patterns. It matches the nodes of the first pattern from
+//! which the whole path can be followed, so "all File nodes modified this
+//! week connected to Task nodes" is
+//!
+//! ```text
+//! file[modified<7d] -- task
+//! ```
+//!
+//! or, built in code,
+//! `GraphQuery::new(NodePattern::of_type("file").modified_within(7 * 86400)).connected_to(NodePattern::of_type("task"))`.
+//!
+//! Grammar:
+//!
+//! ```text
+//! query := node (edge node)*
+//! node  := (TYPE | '*') ['[' cond (',' cond)* ']']
+//! edge  := '--' | '-->' | '<--' | '-' kind '-' | '-' kind '->' | '<-' kind '-'
+//! kind  := '[' (EDGE_TYPE | '*') ']'
+//! cond  := modified ('<' | '>') AGE | created ('<' | '>') AGE
+//!        | tag '=' VALUE | name '~' VALUE | KEY '=' VALUE
+//! ```
+//!
+//! Types are the names used by hook filters (`file`, `task`, ...) and edge
+//! types those used by edge rules (`depends_on`, `related_to`, ...). Ages
+//! are written `30s`, `12h`, `7d` or `2w`; `<` means more recent than, `>`
+//! older than. Values are single words or double-quoted strings.
+
+use crate::hooks::node_type_name;
+use crate::search::SearchDocument;
+use chrono::{DateTime, Utc};
+use horizonos_graph_engine::{EdgeType, Scene, SceneId, SceneNode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// Name of an edge type as used in queries and edge rules
+pub fn edge_type_name(edge_type: &EdgeType) -> &'static str {
+    match edge_type {
+        EdgeType::Contains => "contains",
+        EdgeType::DependsOn => "depends_on",
+        EdgeType::CommunicatesWith => "communicates_with",
+        EdgeType::CreatedBy => "created_by",
+        EdgeType::RelatedTo { .. } => "related_to",
+        EdgeType::Temporal { .. } => "temporal",
+        EdgeType::TaggedAs { .. } => "tagged_as",
+        EdgeType::WorksOn => "works_on",
+    }
+}
+
+/// A condition on a node's metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    /// Modified in the last number of seconds
+    ModifiedWithin(u64),
+    /// Last modified longer ago than the number of seconds
+    ModifiedBefore(u64),
+    /// Created in the last number of seconds
+    CreatedWithin(u64),
+    /// Created longer ago than the number of seconds
+    CreatedBefore(u64),
+    /// Carries a tag
+    Tag(String),
+    /// Display name contains the text, ignoring case
+    NameContains(String),
+    /// Metadata property has a value
+    Property { key: String, value: String },
+}
+
+impl Condition {
+    fn matches(&self, node: &SceneNode, now: DateTime<Utc>) -> bool {
+        let ago = |seconds: &u64| now - chrono::Duration::seconds(*seconds as i64);
+        let metadata = &node.metadata;
+        match self {
+            Condition::ModifiedWithin(seconds) => metadata.updated_at >= ago(seconds),
+            Condition::ModifiedBefore(seconds) => metadata.updated_at < ago(seconds),
+            Condition::CreatedWithin(seconds) => metadata.created_at >= ago(seconds),
+            Condition::CreatedBefore(seconds) => metadata.created_at < ago(seconds),
+            Condition::Tag(tag) => metadata.tags.contains(tag),
+            Condition::NameContains(text) => SearchDocument::from_scene_node(node)
+                .display_name
+                .to_lowercase()
+                .contains(&text.to_lowercase()),
+            Condition::Property { key, value } => metadata.properties.get(key) == Some(value),
+        }
+    }
+}
+
+/// Nodes of a type, or any type, meeting all conditions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodePattern {
+    /// Node type name, `None` for any type
+    pub node_type: Option<String>,
+    pub conditions: Vec<Condition>,
+}
+
+impl NodePattern {
+    /// Nodes of any type
+    pub fn any() -> Self {
+        Self::default()
+    }
+
+    /// Nodes of a type, e.g. `file`
+    pub fn of_type(node_type: impl Into<String>) -> Self {
+        Self { node_type: Some(node_type.into().to_lowercase()), conditions: Vec::new() }
+    }
+
+    /// Add a condition
+    pub fn with(mut self, condition: Condition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    /// Only nodes modified in the last number of seconds
+    pub fn modified_within(self, seconds: u64) -> Self {
+        self.with(Condition::ModifiedWithin(seconds))
+    }
+
+    /// Only nodes carrying a tag
+    pub fn tagged(self, tag: impl Into<String>) -> Self {
+        self.with(Condition::Tag(tag.into()))
+    }
+
+    /// Only nodes whose name contains the text
+    pub fn named(self, text: impl Into<String>) -> Self {
+        self.with(Condition::NameContains(text.into()))
+    }
+
+    /// Only nodes with a metadata property value
+    pub fn property(self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.with(Condition::Property { key: key.into(), value: value.into() })
+    }
+
+    /// Whether a node matches
+    pub fn matches(&self, node: &SceneNode, now: DateTime<Utc>) -> bool {
+        self.node_type.as_deref().is_none_or(|name| node_type_name(&node.node_type) == name)
+            && self.conditions.iter().all(|condition| condition.matches(node, now))
+    }
+}
+
+/// Which way an edge is followed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EdgeDirection {
+    /// From source to target
+    Outgoing,
+    /// From target to source
+    Incoming,
+    /// Either way
+    #[default]
+    Either,
+}
+
+/// Edges of a type, or any type, followed in a direction
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EdgePattern {
+    /// Edge type name, `None` for any type
+    pub edge_type: Option<String>,
+    pub direction: EdgeDirection,
+}
+
+impl EdgePattern {
+    /// Edges of any type, either way
+    pub fn any() -> Self {
+        Self::default()
+    }
+
+    /// Edges of a type, e.g. `depends_on`, either way
+    pub fn of_type(edge_type: impl Into<String>) -> Self {
+        Self { edge_type: Some(edge_type.into().to_lowercase()), direction: EdgeDirection::Either }
+    }
+
+    /// Follow edges only in a direction
+    pub fn directed(mut self, direction: EdgeDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    fn matches(&self, edge_type: &EdgeType) -> bool {
+        self.edge_type.as_deref().is_none_or(|name| edge_type_name(edge_type) == name)
+    }
+}
+
+/// A node pattern followed by a path through edges to further node patterns
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphQuery {
+    /// Pattern of the nodes the query returns
+    pub start: NodePattern,
+    /// Steps the returned nodes must be able to follow
+    pub path: Vec<(EdgePattern, NodePattern)>,
+}
+
+impl GraphQuery {
+    /// Query for nodes matching a pattern
+    pub fn new(start: NodePattern) -> Self {
+        Self { start, path: Vec::new() }
+    }
+
+    /// Continue the path through an edge to a node
+    pub fn then(mut self, edge: EdgePattern, node: NodePattern) -> Self {
+        self.path.push((edge, node));
+        self
+    }
+
+    /// Continue the path through an edge of any type to a node
+    pub fn connected_to(self, node: NodePattern) -> Self {
+        self.then(EdgePattern::any(), node)
+    }
+
+    /// Nodes matching the query at a point in time
+    ///
+    /// The path is resolved from its far end: each step keeps the nodes
+    /// with an edge to a node kept by the step after it.
+    pub fn evaluate(&self, scene: &Scene, now: DateTime<Utc>) -> HashSet<SceneId> {
+        let matching = |pattern: &NodePattern| -> HashSet<SceneId> {
+            scene.nodes().filter(|(_, node)| pattern.matches(node, now)).map(|(id, _)| *id).collect()
+        };
+
+        let mut reachable: Option<HashSet<SceneId>> = None;
+        for (index, (edge, node)) in self.path.iter().enumerate().rev() {
+            let mut targets = matching(node);
+            if let Some(next) = &reachable {
+                targets.retain(|id| next.contains(id));
+            }
+            // Nodes of the step before that reach one of the targets
+            let sources = if index == 0 { &self.start } else { &self.path[index - 1].1 };
+            let mut found = HashSet::new();
+            for scene_edge in scene.edges().filter(|scene_edge| edge.matches(&scene_edge.edge_type)) {
+                let (from, to) = (scene_edge.source, scene_edge.target);
+                if edge.direction != EdgeDirection::Incoming && targets.contains(&to) {
+                    found.insert(from);
+                }
+                if edge.direction != EdgeDirection::Outgoing && targets.contains(&from) {
+                    found.insert(to);
+                }
+            }
+            found.retain(|id| scene.get_node(*id).is_some_and(|node| sources.matches(node, now)));
+            reachable = Some(found);
+        }
+        reachable.unwrap_or_else(|| matching(&self.start))
+    }
+
+    /// Nodes matching the query now, in ID order
+    pub fn run(&self, scene: &Scene) -> Vec<SceneId> {
+        let mut ids: Vec<SceneId> = self.evaluate(scene, Utc::now()).into_iter().collect();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::ModifiedWithin(seconds) => write!(f, "modified<{}", Age(*seconds)),
+            Condition::ModifiedBefore(seconds) => write!(f, "modified>{}", Age(*seconds)),
+            Condition::CreatedWithin(seconds) => write!(f, "created<{}", Age(*seconds)),
+            Condition::CreatedBefore(seconds) => write!(f, "created>{}", Age(*seconds)),
+            Condition::Tag(tag) => write!(f, "tag={}", Value(tag)),
+            Condition::NameContains(text) => write!(f, "name~{}", Value(text)),
+            Condition::Property { key, value } => write!(f, "{}={}", key, Value(value)),
+        }
+    }
+}
+
+impl fmt::Display for NodePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.node_type.as_deref().unwrap_or("*"))?;
+        if !self.conditions.is_empty() {
+            let conditions: Vec<String> = self.conditions.iter().map(ToString::to_string).collect();
+            write!(f, "[{}]", conditions.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for EdgePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = self.edge_type.as_deref().map(|name| format!("[{}]", name)).unwrap_or_default();
+        match self.direction {
+            EdgeDirection::Outgoing => write!(f, "-{}->", kind),
+            EdgeDirection::Incoming => write!(f, "<-{}-", kind),
+            EdgeDirection::Either => write!(f, "-{}-", kind),
+        }
+    }
+}
+
+impl fmt::Display for GraphQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.start)?;
+        for (edge, node) in &self.path {
+            write!(f, " {} {}", edge, node)?;
+        }
+        Ok(())
+    }
+}
+
+/// Seconds written in the largest unit that divides them
+struct Age(u64);
+
+impl fmt::Display for Age {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.0;
+        for (unit, size) in [('w', 604_800), ('d', 86_400), ('h', 3_600)] {
+            if seconds > 0 && seconds.is_multiple_of(size) {
+                return write!(f, "{}{}", seconds / size, unit);
+            }
+        }
+        write!(f, "{}s", seconds)
+    }
+}
+
+/// A value, quoted unless it is a single word
+struct Value<'a>(&'a str);
+
+impl fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.0.is_empty() && self.0.chars().all(is_word_char) {
+            write!(f, "{}", self.0)
+        } else {
+            write!(f, "\"{}\"", self.0.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '/')
+}
+
+impl FromStr for GraphQuery {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { input: s, pos: 0 };
+        let query = parser.query()?;
+        parser.skip_whitespace();
+        if parser.pos < s.len() {
+            return Err(format!("Unexpected '{}' at {}", &s[parser.pos..], parser.pos));
+        }
+        Ok(query)
+    }
+}
+
+/// Recursive descent parser for query text
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(format!("Expected '{}' at {}", token, self.pos))
+        }
+    }
+
+    fn query(&mut self) -> Result<GraphQuery, String> {
+        let mut query = GraphQuery::new(self.node()?);
+        while let Some(edge) = self.edge()? {
+            query.path.push((edge, self.node()?));
+        }
+        Ok(query)
+    }
+
+    fn node(&mut self) -> Result<NodePattern, String> {
+        let start = self.pos;
+        let mut pattern = if self.eat("*") {
+            NodePattern::any()
+        } else {
+            match self.word() {
+                "" => return Err(format!("Expected a node type at {}", start)),
+                name => NodePattern::of_type(name),
+            }
+        };
+        if self.eat("[") {
+            loop {
+                pattern.conditions.push(self.condition()?);
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            self.expect("]")?;
+        }
+        Ok(pattern)
+    }
+
+    /// An edge, or `None` at the end of the path
+    fn edge(&mut self) -> Result<Option<EdgePattern>, String> {
+        let incoming = self.eat("<-");
+        if !incoming && !self.eat("-") {
+            return Ok(None);
+        }
+        let edge_type = if self.eat("[") {
+            let name = if self.eat("*") { "*" } else { self.word() };
+            if name.is_empty() {
+                return Err(format!("Expected an edge type at {}", self.pos));
+            }
+            self.expect("]")?;
+            (name != "*").then(|| name.to_lowercase())
+        } else {
+            None
+        };
+        let outgoing = if self.eat("->") {
+            true
+        } else {
+            self.expect("-")?;
+            false
+        };
+        let direction = match (incoming, outgoing) {
+            (true, true) => return Err(format!("Edge at {} points both ways", self.pos)),
+            (true, false) => EdgeDirection::Incoming,
+            (false, true) => EdgeDirection::Outgoing,
+            (false, false) => EdgeDirection::Either,
+        };
+        Ok(Some(EdgePattern { edge_type, direction }))
+    }
+
+    fn condition(&mut self) -> Result<Condition, String> {
+        let start = self.pos;
+        let key = self.word();
+        self.skip_whitespace();
+        let operator = self.rest().chars().next().ok_or_else(|| format!("Unfinished condition at {}", start))?;
+        if !matches!(operator, '<' | '>' | '=' | '~') {
+            return Err(format!("Expected '<', '>', '=' or '~' after '{}' at {}", key, self.pos));
+        }
+        self.pos += operator.len_utf8();
+
+        let condition = match (key, operator) {
+            ("modified" | "created", '<' | '>') => {
+                let age = parse_age(self.word())?;
+                match (key, operator) {
+                    ("modified", '<') => Condition::ModifiedWithin(age),
+                    ("modified", _) => Condition::ModifiedBefore(age),
+                    (_, '<') => Condition::CreatedWithin(age),
+                    _ => Condition::CreatedBefore(age),
+                }
+            }
+            ("tag", '=') => Condition::Tag(self.value()?),
+            ("name", '~') => Condition::NameContains(self.value()?),
+            ("", _) => return Err(format!("Expected a condition at {}", start)),
+            (key, '=') => Condition::Property { key: key.to_string(), value: self.value()? },
+            (key, _) => return Err(format!("Unsupported condition '{}{}' at {}", key, operator, start)),
+        };
+        Ok(condition)
+    }
+
+    /// Read a run of identifier characters
+    fn word(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest.find(|c: char| !is_word_char(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// Read a word or a double-quoted string
+    fn value(&mut self) -> Result<String, String> {
+        if !self.eat("\"") {
+            return match self.word() {
+                "" => Err(format!("Expected a value at {}", self.pos)),
+                word => Ok(word.to_string()),
+            };
+        }
+        let mut value = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += offset + 1;
+                    return Ok(value);
+                }
+                '\\' => value.extend(chars.next().map(|(_, escaped)| escaped)),
+                c => value.push(c),
+            }
+        }
+        Err(format!("Unterminated string at {}", self.pos))
+    }
+}
+
+/// Parse `30s`, `12h`, `7d` or `2w` into seconds
+fn parse_age(age: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid age '{}', expected e.g. 12h or 7d", age);
+    let unit = age.chars().last().ok_or_else(invalid)?;
+    let multiplier = match unit {
+        's' => 1,
+        'h' => 3_600,
+        'd' => 86_400,
+        'w' => 604_800,
+        _ => return Err(invalid()),
+    };
+    let amount: u64 = age[..age.len() - 1].parse().map_err(|_| invalid())?;
+    Ok(amount * multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::{FileType, NodeMetadata, NodeType, Position, SceneEdge, TaskStatus, Vec3};
+
+    fn add(scene: &mut Scene, node_type: NodeType, age_days: i64, tags: &[&str]) -> SceneId {
+        let now = Utc::now();
+        let metadata = NodeMetadata {
+            updated_at: now - chrono::Duration::days(age_days),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Default::default()
+        };
+        scene.add_node(SceneNode {
+            id: 0,
+            position: Position::origin(),
+            velocity: Vec3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type,
+            metadata,
+            visible: true,
+            selected: false,
+        })
+    }
+
+    fn file(path: &str) -> NodeType {
+        NodeType::File { path: path.to_string(), file_type: FileType::Document }
+    }
+
+    fn link(scene: &mut Scene, source: SceneId, target: SceneId, edge_type: EdgeType) {
+        scene.add_edge(SceneEdge {
+            id: 0,
+            source,
+            target,
+            edge_type,
+            weight: 1.0,
+            color: [1.0; 4],
+            visible: true,
+            animated: false,
+        });
+    }
+
+    #[test]
+    fn test_recent_files_connected_to_tasks() {
+        let mut scene = Scene::new();
+        let task = add(&mut scene, NodeType::Task { title: "Ship release".to_string(), status: TaskStatus::Todo }, 0, &[]);
+        let recent = add(&mut scene, file("/home/me/notes.md"), 2, &["work"]);
+        let old = add(&mut scene, file("/home/me/old.md"), 30, &["work"]);
+        let unlinked = add(&mut scene, file("/home/me/draft.md"), 1, &[]);
+        link(&mut scene, recent, task, EdgeType::WorksOn);
+        link(&mut scene, old, task, EdgeType::WorksOn);
+
+        let query: GraphQuery = "file[modified<7d] -- task".parse().unwrap();
+        assert_eq!(query.run(&scene), vec![recent]);
+
+        let built = GraphQuery::new(NodePattern::of_type("file").modified_within(7 * 86_400))
+            .connected_to(NodePattern::of_type("task"));
+        assert_eq!(built, query);
+
+        let all_recent: GraphQuery = "file[modified<1w]".parse().unwrap();
+        let mut expected = vec![recent, unlinked];
+        expected.sort_unstable();
+        assert_eq!(all_recent.run(&scene), expected);
+
+        // Direction and edge type are respected
+        let incoming: GraphQuery = "task <-[works_on]- file[tag=work]".parse().unwrap();
+        assert_eq!(incoming.run(&scene), vec![task]);
+        assert!("task -[works_on]-> file".parse::<GraphQuery>().unwrap().run(&scene).is_empty());
+        assert!("file -[depends_on]- task".parse::<GraphQuery>().unwrap().run(&scene).is_empty());
+        assert_eq!("*[name~\"ship\"]".parse::<GraphQuery>().unwrap().run(&scene), vec![task]);
+    }
+
+    #[test]
+    fn test_query_text_round_trips() {
+        for text in [
+            "file[modified<7d, tag=work] -- task",
+            "* <-[contains]- group[name~\"Q3 plans\"] -[depends_on]-> task[created>2w]",
+            "person[team=infra]",
+        ] {
+            let query: GraphQuery = text.parse().unwrap();
+            assert_eq!(query.to_string().parse::<GraphQuery>().unwrap(), query, "{}", text);
+        }
+        assert!("file[modified<7x]".parse::<GraphQuery>().is_err());
+        assert!("file <-[x]-> task".parse::<GraphQuery>().is_err());
+        assert!("file --".parse::<GraphQuery>().is_err());
+    }
+}
@@ -0,0 +1,286 @@
+//! Fuzzy search over node names, descriptions and metadata
+//!
+//! [`NodeSearchIndex`] keeps one [`SearchDocument`] per node with the text a
+//! user would search for. Queries match as subsequences, so "dlrep" finds
+//! "Download report", and score higher for consecutive characters, matches at
+//! word starts and matches in the name rather than the description.
+
+use crate::hooks::node_type_name;
+use crate::GraphNode;
+use horizonos_graph_engine::{NodeType, SceneId, SceneNode};
+use std::collections::HashMap;
+
+/// Weight of a match in the display name
+const NAME_WEIGHT: f32 = 1.0;
+/// Weight of a match in a tag
+const TAG_WEIGHT: f32 = 0.7;
+/// Weight of a match in the description
+const DESCRIPTION_WEIGHT: f32 = 0.5;
+/// Weight of a match in a metadata property value
+const PROPERTY_WEIGHT: f32 = 0.4;
+
+/// Searchable text of a node
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchDocument {
+    pub node_id: SceneId,
+    pub display_name: String,
+    /// Node type name, e.g. `file`
+    pub node_type: &'static str,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    /// Metadata property values
+    pub properties: Vec<String>,
+}
+
+impl SearchDocument {
+    /// Collect the searchable text of a node implementation
+    pub fn from_node(node: &dyn GraphNode) -> Self {
+        let metadata = node.metadata();
+        Self {
+            node_id: node.id(),
+            display_name: node.display_name(),
+            node_type: node_type_name(&node.node_type()),
+            description: node.description().or(metadata.description),
+            tags: metadata.tags,
+            properties: metadata.properties.into_values().collect(),
+        }
+    }
+
+    /// Collect the searchable text of a scene node, for nodes without an implementation
+    pub fn from_scene_node(node: &SceneNode) -> Self {
+        let display_name = match &node.node_type {
+            NodeType::Application { name, .. }
+            | NodeType::Person { name, .. }
+            | NodeType::Device { name, .. }
+            | NodeType::AIAgent { name, .. }
+            | NodeType::Automation { name, .. }
+            | NodeType::ConfigGroup { name, .. }
+            | NodeType::Group { name, .. } => name.clone(),
+            NodeType::File { path, .. } => path.rsplit('/').find(|part| !part.is_empty()).unwrap_or(path).to_string(),
+            NodeType::Task { title, .. } | NodeType::Concept { title, .. } => title.clone(),
+            NodeType::System { component, .. } => component.clone(),
+            NodeType::URL { url, title, .. } => title.clone().unwrap_or_else(|| url.clone()),
+            NodeType::Setting { key, .. } => key.clone(),
+        };
+        Self {
+            node_id: node.id,
+            display_name: node.metadata.properties.get("title").cloned().unwrap_or(display_name),
+            node_type: node_type_name(&node.node_type),
+            description: node.metadata.description.clone(),
+            tags: node.metadata.tags.clone(),
+            properties: node.metadata.properties.values().cloned().collect(),
+        }
+    }
+}
+
+/// Which part of a node matched a query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchField {
+    Name,
+    Tag,
+    Description,
+    Property,
+}
+
+/// A node matching a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub node_id: SceneId,
+    pub display_name: String,
+    pub node_type: &'static str,
+    /// Higher is better
+    pub score: f32,
+    /// Best matching field
+    pub field: MatchField,
+    /// Character positions in the display name matching the query, for highlighting
+    pub highlights: Vec<usize>,
+}
+
+/// Searchable text of all nodes
+#[derive(Debug, Default)]
+pub struct NodeSearchIndex {
+    documents: HashMap<SceneId, SearchDocument>,
+}
+
+impl NodeSearchIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Index a node implementation, replacing its earlier document
+    pub fn index_node(&mut self, node: &dyn GraphNode) {
+        self.insert(SearchDocument::from_node(node));
+    }
+
+    pub fn insert(&mut self, document: SearchDocument) {
+        self.documents.insert(document.node_id, document);
+    }
+
+    pub fn remove(&mut self, node_id: SceneId) {
+        self.documents.remove(&node_id);
+    }
+
+    pub fn contains(&self, node_id: SceneId) -> bool {
+        self.documents.contains_key(&node_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Best matches for `query`, best first
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        search_documents(self.documents.values(), query, limit)
+    }
+}
+
+/// Best matches for `query` among `documents`, best first
+///
+/// An empty query matches every document, in name order.
+pub fn search_documents<'a>(
+    documents: impl IntoIterator<Item = &'a SearchDocument>,
+    query: &str,
+    limit: usize,
+) -> Vec<SearchHit> {
+    let query: Vec<char> = query.trim().to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    let mut hits: Vec<SearchHit> = documents
+        .into_iter()
+        .filter_map(|document| score_document(document, &query))
+        .collect();
+    rank_hits(&mut hits, limit);
+    hits
+}
+
+/// Order hits best first, shorter names first among equal scores, and keep the best `limit`
+pub fn rank_hits(hits: &mut Vec<SearchHit>, limit: usize) {
+    hits.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.display_name.len().cmp(&b.display_name.len()))
+            .then_with(|| a.display_name.cmp(&b.display_name))
+            .then_with(|| a.node_id.cmp(&b.node_id))
+    });
+    hits.truncate(limit);
+}
+
+fn score_document(document: &SearchDocument, query: &[char]) -> Option<SearchHit> {
+    let hit = |score: f32, field: MatchField, highlights: Vec<usize>| SearchHit {
+        node_id: document.node_id,
+        display_name: document.display_name.clone(),
+        node_type: document.node_type,
+        score,
+        field,
+        highlights,
+    };
+    if query.is_empty() {
+        return Some(hit(0.0, MatchField::Name, Vec::new()));
+    }
+
+    let mut best: Option<SearchHit> = fuzzy_score(&document.display_name, query)
+        .map(|(score, positions)| hit(score * NAME_WEIGHT, MatchField::Name, positions));
+    let others = document
+        .tags
+        .iter()
+        .map(|tag| (tag.as_str(), TAG_WEIGHT, MatchField::Tag))
+        .chain(document.description.as_deref().map(|d| (d, DESCRIPTION_WEIGHT, MatchField::Description)))
+        .chain(document.properties.iter().map(|p| (p.as_str(), PROPERTY_WEIGHT, MatchField::Property)));
+    for (text, weight, field) in others {
+        if let Some((score, _)) = fuzzy_score(text, query) {
+            let score = score * weight;
+            if best.as_ref().is_none_or(|best| score > best.score) {
+                best = Some(hit(score, field, Vec::new()));
+            }
+        }
+    }
+    best
+}
+
+/// Score `query` as a subsequence of `text`, with the matched character positions
+///
+/// `query` must be lowercase. Consecutive matches and matches at word starts
+/// score higher, gaps and long texts lower; `None` when not all of `query`
+/// occurs in order.
+pub fn fuzzy_score(text: &str, query: &[char]) -> Option<(f32, Vec<usize>)> {
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+    let mut positions = Vec::with_capacity(query.len());
+    let mut score = 0.0;
+    let mut next = 0;
+    for &q in query {
+        // Prefer a word start ahead over the first occurrence
+        let first = (next..lower.len()).find(|&i| lower[i] == q)?;
+        let at_word_start = |i: usize| i == 0 || !chars[i - 1].is_alphanumeric() || (chars[i].is_uppercase() && chars[i - 1].is_lowercase());
+        let position = if positions.last().is_some_and(|&last| last + 1 == first) {
+            first
+        } else {
+            (first..lower.len()).find(|&i| lower[i] == q && at_word_start(i)).unwrap_or(first)
+        };
+
+        score += 1.0;
+        if positions.last().is_some_and(|&last| last + 1 == position) {
+            score += 1.5;
+        } else if let Some(&last) = positions.last() {
+            score -= 0.05 * (position - last - 1) as f32;
+        }
+        if at_word_start(position) {
+            score += 1.0;
+        }
+        if position == 0 {
+            score += 1.0;
+        }
+        positions.push(position);
+        next = position + 1;
+    }
+    // Normalise so scores of different query lengths compare, and prefer tighter texts
+    let score = score / (query.len() as f32 * 4.5) - 0.002 * chars.len() as f32;
+    Some((score.max(0.001), positions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(node_id: SceneId, name: &str, tags: &[&str]) -> SearchDocument {
+        SearchDocument {
+            node_id,
+            display_name: name.to_string(),
+            node_type: "file",
+            description: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            properties: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        let query: Vec<char> = "dlrep".chars().collect();
+        let (_, positions) = fuzzy_score("Download report", &query).unwrap();
+        assert_eq!(positions, vec![0, 4, 9, 10, 11]);
+        assert!(fuzzy_score("Download", &query).is_none());
+
+        let term: Vec<char> = "term".chars().collect();
+        let prefix = fuzzy_score("Terminal", &term).unwrap().0;
+        let scattered = fuzzy_score("The ethernet room", &term).unwrap().0;
+        assert!(prefix > scattered);
+    }
+
+    #[test]
+    fn test_index_ranks_names_over_tags() {
+        let mut index = NodeSearchIndex::new();
+        index.insert(document(1, "Quarterly budget.ods", &["finance"]));
+        index.insert(document(2, "Notes", &["budget"]));
+        index.insert(document(3, "Holiday photos", &[]));
+
+        let hits = index.search("budget", 10);
+        assert_eq!(hits.iter().map(|h| h.node_id).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(hits[1].field, MatchField::Tag);
+        assert_eq!(index.search("", 2).len(), 2);
+
+        index.remove(1);
+        assert_eq!(index.search("budget", 10)[0].node_id, 2);
+    }
+}
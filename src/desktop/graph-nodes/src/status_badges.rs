@@ -0,0 +1,445 @@
+//! Node badges fed by external commands
+//!
+//! A status badge runs a command for each matching node on an interval and
+//! shows the first line it prints on the node's badge, e.g. the CI status of
+//! a repository or the ping time of a server. Badges are read from
+//! `$XDG_CONFIG_HOME/horizonos/badges.toml`:
+//!
+//! ```toml
+//! [[badge]]
+//! id = "ci"
+//! filter = { tag = "repo" }
+//! command = { type = "script", path = "~/bin/ci-status" }
+//! interval_secs = 300
+//! sandbox = { allow_network = true, timeout_secs = 20 }
+//! ```
+//!
+//! The exit code sets the badge state the way monitoring plugins do: 0 is
+//! ok, 1 a warning, 2 critical and anything else unknown. Commands run in the
+//! same sandbox as lifecycle hooks and get the same `HORIZONOS_NODE_*`
+//! variables. Results are cached until the interval has passed; a command
+//! that fails to run keeps the last reading but marks it failed, so a broken
+//! check never looks like a healthy one.
+
+use crate::hooks::{expand_home, run_with_timeout, sandboxed_command, HookContext, HookFilter, HookSandbox};
+use crate::{NodeError, NodeVisualData};
+use chrono::{DateTime, Utc};
+use horizonos_graph_engine::SceneId;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Longest badge text shown; longer output is cut
+pub const MAX_BADGE_CHARS: usize = 16;
+
+/// Command a badge runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BadgeCommand {
+    /// Run a command with `sh -c`
+    Shell { command: String },
+    /// Run an executable script with arguments
+    Script {
+        path: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+impl BadgeCommand {
+    fn program(&self) -> Vec<String> {
+        match self {
+            BadgeCommand::Shell { command } => vec!["sh".to_string(), "-c".to_string(), command.clone()],
+            BadgeCommand::Script { path, args } => {
+                let mut program = vec![expand_home(path).to_string_lossy().into_owned()];
+                program.extend(args.iter().cloned());
+                program
+            }
+        }
+    }
+}
+
+/// A configured status badge
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusBadge {
+    /// Unique badge identifier
+    pub id: String,
+    /// Nodes the badge is shown on
+    #[serde(default)]
+    pub filter: HookFilter,
+    /// Command printing the badge text
+    pub command: BadgeCommand,
+    /// Seconds between runs for a node
+    #[serde(default = "default_interval")]
+    pub interval_secs: u64,
+    /// Disabled badges are kept but never run
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Sandbox for the command
+    #[serde(default)]
+    pub sandbox: HookSandbox,
+}
+
+fn default_interval() -> u64 {
+    60
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Badges file contents
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct BadgesFile {
+    #[serde(default, rename = "badge")]
+    badges: Vec<StatusBadge>,
+}
+
+/// State a badge is drawn in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BadgeState {
+    Ok,
+    Warning,
+    Critical,
+    /// The command ran but reported an unknown state
+    Unknown,
+    /// The command could not run, timed out or was killed
+    Failed,
+}
+
+impl BadgeState {
+    /// State reported by an exit code
+    pub fn from_exit_code(code: Option<i32>) -> Self {
+        match code {
+            Some(0) => BadgeState::Ok,
+            Some(1) => BadgeState::Warning,
+            Some(2) => BadgeState::Critical,
+            _ => BadgeState::Unknown,
+        }
+    }
+
+    /// Badge background color
+    pub fn color(&self) -> [f32; 4] {
+        match self {
+            BadgeState::Ok => [0.2, 0.7, 0.3, 1.0],
+            BadgeState::Warning => [0.9, 0.6, 0.1, 1.0],
+            BadgeState::Critical => [0.85, 0.2, 0.2, 1.0],
+            BadgeState::Unknown => [0.5, 0.5, 0.5, 1.0],
+            BadgeState::Failed => [0.6, 0.2, 0.7, 1.0],
+        }
+    }
+
+    /// Text shown when the command printed nothing
+    fn symbol(&self) -> &'static str {
+        match self {
+            BadgeState::Ok => "✓",
+            BadgeState::Warning => "!",
+            BadgeState::Critical => "✗",
+            BadgeState::Unknown => "?",
+            BadgeState::Failed => "⚠",
+        }
+    }
+}
+
+/// Last result of a badge command for a node
+#[derive(Debug, Clone, PartialEq)]
+pub struct BadgeReading {
+    pub badge_id: String,
+    /// Text of the last successful run
+    pub text: String,
+    pub state: BadgeState,
+    /// Why the last run failed
+    pub error: Option<String>,
+    /// When the last successful run finished
+    pub updated_at: Option<DateTime<Utc>>,
+    checked_at: Instant,
+}
+
+impl BadgeReading {
+    /// Text shown on the node
+    ///
+    /// Failed readings keep the last text behind a warning sign.
+    pub fn label(&self) -> String {
+        match (self.state, self.text.is_empty()) {
+            (_, true) => self.state.symbol().to_string(),
+            (BadgeState::Failed, false) => format!("{} {}", BadgeState::Failed.symbol(), self.text),
+            (_, false) => self.text.clone(),
+        }
+    }
+}
+
+type ReadingKey = (String, SceneId);
+
+/// Configured status badges and their cached readings
+pub struct StatusBadgeRegistry {
+    badges: Vec<StatusBadge>,
+    readings: Arc<Mutex<HashMap<ReadingKey, BadgeReading>>>,
+    running: Arc<Mutex<HashSet<ReadingKey>>>,
+}
+
+impl StatusBadgeRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self {
+            badges: Vec::new(),
+            readings: Arc::new(Mutex::new(HashMap::new())),
+            running: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Default badges file, `$XDG_CONFIG_HOME/horizonos/badges.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_dir.join("horizonos").join("badges.toml"))
+    }
+
+    /// Replace the badges with those in a TOML file; a missing file means no badges
+    pub fn load(&mut self, path: &Path) -> Result<usize, NodeError> {
+        if !path.exists() {
+            self.clear();
+            return Ok(0);
+        }
+        let content = std::fs::read_to_string(path)?;
+        let badges = parse_badges(&content)?;
+        self.clear();
+        for badge in badges {
+            self.add(badge)?;
+        }
+        Ok(self.badges.len())
+    }
+
+    /// Add a badge
+    pub fn add(&mut self, badge: StatusBadge) -> Result<(), NodeError> {
+        if self.badges.iter().any(|existing| existing.id == badge.id) {
+            return Err(NodeError::SystemError {
+                message: format!("Duplicate badge id '{}'", badge.id),
+            });
+        }
+        self.badges.push(badge);
+        Ok(())
+    }
+
+    /// Remove a badge and its readings
+    pub fn remove(&mut self, id: &str) -> Option<StatusBadge> {
+        let index = self.badges.iter().position(|badge| badge.id == id)?;
+        lock(&self.readings).retain(|(badge_id, _), _| badge_id != id);
+        Some(self.badges.remove(index))
+    }
+
+    /// All badges
+    pub fn badges(&self) -> &[StatusBadge] {
+        &self.badges
+    }
+
+    /// Run the badge commands that are due for the given nodes in the background,
+    /// returning how many were started
+    ///
+    /// A command is due when the node has no reading yet or its interval
+    /// has passed; a command still running for a node is never started twice.
+    pub fn refresh(&self, contexts: &[HookContext]) -> usize {
+        let now = Instant::now();
+        let mut started = 0;
+        for badge in self.badges.iter().filter(|badge| badge.enabled) {
+            let interval = Duration::from_secs(badge.interval_secs);
+            for context in contexts.iter().filter(|context| badge.filter.matches(context)) {
+                let key = (badge.id.clone(), context.node_id);
+                let due = lock(&self.readings)
+                    .get(&key)
+                    .is_none_or(|reading| now.duration_since(reading.checked_at) >= interval);
+                if !due || !lock(&self.running).insert(key.clone()) {
+                    continue;
+                }
+                started += 1;
+                self.spawn(badge, context, key);
+            }
+        }
+        started
+    }
+
+    fn spawn(&self, badge: &StatusBadge, context: &HookContext, key: ReadingKey) {
+        let mut env = context.node_env();
+        env.push(("HORIZONOS_BADGE_ID", badge.id.clone()));
+        let command = sandboxed_command(&badge.command.program(), &badge.sandbox, env).map(|mut command| {
+            command.stdout(Stdio::piped());
+            command
+        });
+        let timeout = Duration::from_secs(badge.sandbox.timeout_secs);
+        let readings = self.readings.clone();
+        let running = self.running.clone();
+        std::thread::spawn(move || {
+            let result = command.and_then(|command| run_with_timeout(command, timeout)).and_then(|output| {
+                let state = BadgeState::from_exit_code(output.status.code());
+                if state == BadgeState::Unknown && output.status.code().is_none() {
+                    return Err(format!("killed by {}", output.status));
+                }
+                Ok((badge_text(&output.stdout), state))
+            });
+            record_reading(&readings, &key, result);
+            lock(&running).remove(&key);
+        });
+    }
+
+    /// Reading shown on a node; with several matching badges, the first configured wins
+    pub fn reading(&self, node_id: SceneId) -> Option<BadgeReading> {
+        let readings = lock(&self.readings);
+        self.badges
+            .iter()
+            .find_map(|badge| readings.get(&(badge.id.clone(), node_id)))
+            .cloned()
+    }
+
+    /// Show a node's reading on its visual data
+    pub fn apply(&self, node_id: SceneId, visual: &mut NodeVisualData) {
+        if let Some(reading) = self.reading(node_id) {
+            visual.badge = Some(reading.label());
+            visual.extensions.badge_state = Some(reading.state);
+        }
+    }
+
+    /// Drop the readings of a removed node
+    pub fn forget_node(&self, node_id: SceneId) {
+        lock(&self.readings).retain(|(_, id), _| *id != node_id);
+    }
+
+    fn clear(&mut self) {
+        self.badges.clear();
+        lock(&self.readings).clear();
+    }
+}
+
+impl Default for StatusBadgeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse badges from TOML
+pub fn parse_badges(content: &str) -> Result<Vec<StatusBadge>, NodeError> {
+    let file: BadgesFile = toml::from_str(content).map_err(|e| NodeError::SystemError {
+        message: format!("Invalid badges file: {}", e),
+    })?;
+    Ok(file.badges)
+}
+
+/// First non-empty line of the output, cut to [`MAX_BADGE_CHARS`]
+fn badge_text(stdout: &str) -> String {
+    let line = stdout.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or_default();
+    if line.chars().count() > MAX_BADGE_CHARS {
+        let mut text: String = line.chars().take(MAX_BADGE_CHARS - 1).collect();
+        text.push('…');
+        text
+    } else {
+        line.to_string()
+    }
+}
+
+fn record_reading(
+    readings: &Mutex<HashMap<ReadingKey, BadgeReading>>,
+    key: &ReadingKey,
+    result: Result<(String, BadgeState), String>,
+) {
+    let mut readings = lock(readings);
+    let previous = readings.remove(key);
+    let reading = match result {
+        Ok((text, state)) => BadgeReading {
+            badge_id: key.0.clone(),
+            text,
+            state,
+            error: None,
+            updated_at: Some(Utc::now()),
+            checked_at: Instant::now(),
+        },
+        Err(e) => {
+            log::warn!("Badge '{}' failed for node {}: {}", key.0, key.1, e);
+            BadgeReading {
+                badge_id: key.0.clone(),
+                text: previous.as_ref().map(|reading| reading.text.clone()).unwrap_or_default(),
+                state: BadgeState::Failed,
+                error: Some(e),
+                updated_at: previous.and_then(|reading| reading.updated_at),
+                checked_at: Instant::now(),
+            }
+        }
+    };
+    readings.insert(key.clone(), reading);
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(node_id: SceneId, tag: &str) -> HookContext {
+        HookContext {
+            node_id,
+            node_type: "concept",
+            display_name: format!("node {}", node_id),
+            tags: vec![tag.to_string()],
+            path: None,
+        }
+    }
+
+    fn wait_for_reading(registry: &StatusBadgeRegistry, node_id: SceneId) -> BadgeReading {
+        for _ in 0..100 {
+            if let Some(reading) = registry.reading(node_id) {
+                return reading;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        panic!("no reading for node {}", node_id);
+    }
+
+    #[test]
+    fn test_command_output_and_exit_code_drive_badge() {
+        let badges = parse_badges(
+            r#"
+            [[badge]]
+            id = "ci"
+            filter = { tag = "repo" }
+            command = { type = "shell", command = "echo; echo \"3 failing $HORIZONOS_NODE_ID\"; exit 2" }
+            interval_secs = 3600
+            sandbox = { enabled = false }
+            "#,
+        )
+        .unwrap();
+
+        let mut registry = StatusBadgeRegistry::new();
+        registry.add(badges[0].clone()).unwrap();
+        assert!(registry.add(badges[0].clone()).is_err());
+
+        let nodes = [context(7, "repo"), context(8, "server")];
+        assert_eq!(registry.refresh(&nodes), 1);
+        let reading = wait_for_reading(&registry, 7);
+        assert_eq!((reading.label().as_str(), reading.state), ("3 failing 7", BadgeState::Critical));
+        assert!(registry.reading(8).is_none());
+
+        // Cached until the interval passes
+        assert_eq!(registry.refresh(&nodes), 0);
+        let mut visual = NodeVisualData::default();
+        registry.apply(7, &mut visual);
+        assert_eq!(visual.extensions.badge_state, Some(BadgeState::Critical));
+    }
+
+    #[test]
+    fn test_failures_keep_last_text() {
+        let key = ("ping".to_string(), 1);
+        let readings = Mutex::new(HashMap::new());
+        record_reading(&readings, &key, Ok(("12ms".to_string(), BadgeState::Ok)));
+        record_reading(&readings, &key, Err("timed out after 10s".to_string()));
+
+        let reading = readings.lock().unwrap()[&key].clone();
+        assert_eq!(reading.state, BadgeState::Failed);
+        assert_eq!(reading.label(), "⚠ 12ms");
+        assert!(reading.updated_at.is_some());
+        assert_eq!(badge_text("a very long status line\nignored"), "a very long sta…");
+    }
+}
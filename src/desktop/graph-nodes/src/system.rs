@@ -1,8 +1,17 @@
 //! System node implementation
 
 use crate::{GraphNode, BaseNode, NodeVisualData, NodeAction, NodeActionResult, NodeActionType, NodeError, NodeExportData};
+use crate::i18n::I18N;
+use horizonos_graph_i18n::tr;
 use horizonos_graph_engine::{SceneNode, NodeType, NodeMetadata, SystemStatus, SceneId, Position, Vec3};
 
+/// Custom action that switches notification focus modes
+pub const FOCUS_MODE_ACTION: &str = "focus_mode";
+
+/// Parameter of [`FOCUS_MODE_ACTION`] naming the mode; focus modes are
+/// switched off when it is missing or empty
+pub const FOCUS_MODE_PARAMETER: &str = "mode";
+
 #[derive(Debug, Clone)]
 pub struct SystemNode {
     base: BaseNode,
@@ -30,9 +39,21 @@ impl GraphNode for SystemNode {
     fn visual_data(&self) -> NodeVisualData { self.base.visual_data.clone() }
     fn update(&mut self, _delta_time: f32) -> Result<(), NodeError> { Ok(()) }
     fn handle_action(&mut self, action: NodeAction) -> Result<NodeActionResult, NodeError> {
-        Err(NodeError::InvalidAction { action })
+        match action {
+            // Switching is done by the notification manager, which owns the focus modes
+            NodeAction::Custom { ref action_type, ref parameters } if action_type == FOCUS_MODE_ACTION => {
+                let message = match parameters.get(FOCUS_MODE_PARAMETER).map(|mode| mode.trim()) {
+                    Some(mode) if !mode.is_empty() => tr!(I18N, "focus-mode-on", mode = mode),
+                    _ => tr!(I18N, "focus-mode-off"),
+                };
+                Ok(NodeActionResult::Success { message: Some(message) })
+            }
+            _ => Err(NodeError::InvalidAction { action }),
+        }
+    }
+    fn available_actions(&self) -> Vec<NodeActionType> {
+        vec![NodeActionType::Custom(FOCUS_MODE_ACTION.to_string())]
     }
-    fn available_actions(&self) -> Vec<NodeActionType> { vec![] }
     fn export_data(&self) -> Result<NodeExportData, NodeError> {
         Ok(NodeExportData {
             node_type: "system".to_string(),
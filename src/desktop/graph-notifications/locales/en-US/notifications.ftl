@@ -0,0 +1,105 @@
+## Snooze durations
+
+snooze-fifteen-minutes = 15 minutes
+snooze-one-hour = 1 hour
+snooze-three-hours = 3 hours
+snooze-tomorrow = Tomorrow
+snooze-next-week = Next week
+snooze-hours = { $count ->
+    [one] 1 hour
+   *[other] { $count } hours
+}
+snooze-minutes = { $count ->
+    [one] 1 minute
+   *[other] { $count } minutes
+}
+
+## Reminders
+
+reminder-title = Reminder: { $node }
+reminder-source = Reminders
+reminder-jump-to-node = Show node
+
+## Notification templates
+
+template-file-operation-title = { $operation ->
+    [copy] Copy complete
+    [move] Move complete
+    [delete] Delete complete
+    [compress] Compression complete
+    [extract] Extraction complete
+    [download] Download complete
+    [upload] Upload complete
+   *[sync] Sync complete
+}
+template-file-operation-body = { $count ->
+    [one] 1 file
+   *[other] { $count } files
+}
+template-file-operation-body-size = { $count ->
+    [one] 1 file
+   *[other] { $count } files
+} ({ $size })
+template-update-title = Update available for { $app }
+template-update-body = Version { $current } → { $new }
+template-ai-title = { $kind ->
+    [relationship] Relationship discovered
+    [cluster] Cluster suggestion
+    [workflow] Workflow optimization
+    [duplicate] Possible duplicates
+    [organization] Organization suggestion
+   *[automation] Automation opportunity
+}
+template-ai-body = { $confidence }% confidence
+template-alert-title = { $alert ->
+    [low-memory] Memory is running low
+    [high-cpu] High CPU usage
+    [disk-space] Disk space is running low
+    [network] Network problem
+    [security] Security warning
+    [update] System update available
+    [backup] Time to back up
+   *[maintenance] Maintenance required
+}
+template-alert-body = { $severity ->
+    [info] For your information
+    [warning] Warning
+    [error] Error
+   *[critical] Critical
+}
+template-task-title = { $task } finished
+template-task-success = Completed in { $seconds ->
+    [one] 1 second
+   *[other] { $seconds } seconds
+}
+template-task-partial = { $completed } of { $total } completed
+template-task-failed = Failed: { $error }
+template-task-cancelled = Cancelled
+template-connection-title = { $connected ->
+    [yes] { $service } connected
+   *[no] { $service } disconnected
+}
+
+## Review mode
+
+review-entered = Notification review. { $count ->
+    [0] No notifications.
+    [one] 1 notification.
+   *[other] { $count } notifications.
+}
+review-exited = Notification review off.
+review-arrived = New notification from { $source }. { $count ->
+    [one] 1 to review.
+   *[other] { $count } to review.
+}
+review-position = { $index } of { $total }.
+review-priority = { $priority ->
+    [critical] Critical.
+   *[high] High priority.
+}
+review-private = Private content.
+review-actions = Actions: { $actions }.
+review-dismissed = Dismissed.
+review-empty = No notifications.
+review-end = End of notifications.
+review-start = First notification.
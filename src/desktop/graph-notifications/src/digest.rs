@@ -0,0 +1,300 @@
+//! Collapsing old notification groups into digest entries
+
+use crate::history::HistoryEntry;
+use crate::Notification;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Words too common to be useful as digest keywords
+const STOP_WORDS: &[&str] = &[
+    "about", "after", "from", "have", "into", "just", "more", "that", "their", "there",
+    "this", "with", "your", "been", "will", "were", "what", "when", "which",
+];
+
+/// Maximum keywords stored per digest
+const MAX_KEYWORDS: usize = 8;
+
+/// When and how old notification groups are collapsed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestPolicy {
+    /// Collapse old groups at all
+    pub enabled: bool,
+    /// Age in days after which a group's entries are collapsed
+    pub collapse_after_days: u32,
+    /// Groups with fewer old entries are left alone
+    pub min_group_size: usize,
+    /// Use the installed summarizer (e.g. a local LLM) instead of the built-in summary
+    pub summarize: bool,
+    /// Titles kept verbatim in each digest
+    pub max_titles: usize,
+    /// Retention overrides by source name or application ID
+    pub source_overrides: HashMap<String, SourceRetention>,
+}
+
+/// Per-source retention override
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SourceRetention {
+    /// Collapse after this many days
+    Days(u32),
+    /// Keep full history for this source
+    Never,
+}
+
+impl Default for DigestPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            collapse_after_days: 3,
+            min_group_size: 3,
+            summarize: false,
+            max_titles: 5,
+            source_overrides: HashMap::new(),
+        }
+    }
+}
+
+impl DigestPolicy {
+    /// Collapse age for a notification source, or `None` if it is never collapsed
+    pub fn retention_for(&self, notification: &Notification) -> Option<u32> {
+        let source = &notification.source;
+        let retention = self.source_overrides.get(&source.name).or_else(|| {
+            source.app_id.as_ref().and_then(|app_id| self.source_overrides.get(app_id))
+        });
+
+        match retention {
+            Some(SourceRetention::Days(days)) => Some(*days),
+            Some(SourceRetention::Never) => None,
+            None => Some(self.collapse_after_days),
+        }
+    }
+
+    /// Whether a history entry is old enough to collapse at `now`
+    pub fn is_collapsible(&self, entry: &HistoryEntry, now: DateTime<Utc>) -> bool {
+        if entry.snoozed_until.is_some() {
+            return false;
+        }
+        match self.retention_for(&entry.notification) {
+            Some(days) => entry.created_at <= now - ChronoDuration::days(days as i64),
+            None => false,
+        }
+    }
+}
+
+/// Old history entries that will be collapsed into one digest
+#[derive(Debug, Clone)]
+pub struct DigestCandidate {
+    /// Notification group, or source name for ungrouped notifications
+    pub group: String,
+    /// Source name
+    pub source: String,
+    /// Entries to collapse, oldest first
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl DigestCandidate {
+    /// Notifications in this candidate
+    pub fn notifications(&self) -> Vec<Notification> {
+        self.entries.iter().map(|entry| entry.notification.clone()).collect()
+    }
+
+    /// Build the digest with the given summary text
+    pub fn into_digest(self, summary: String, summarized_by: Option<String>, max_titles: usize) -> NotificationDigest {
+        let first_at = self.entries.first().map(|e| e.created_at).unwrap_or_else(Utc::now);
+        let last_at = self.entries.last().map(|e| e.created_at).unwrap_or(first_at);
+        let titles = self.entries.iter()
+            .map(|e| e.notification.title.clone())
+            .take(max_titles)
+            .collect();
+        let keywords = extract_keywords(self.entries.iter().map(|e| &e.notification));
+
+        NotificationDigest {
+            id: Uuid::new_v4(),
+            group: self.group,
+            source: self.source,
+            count: self.entries.len(),
+            first_at,
+            last_at,
+            titles,
+            keywords,
+            summary,
+            summarized_by,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Searchable record standing in for a collapsed notification group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationDigest {
+    /// Digest ID
+    pub id: Uuid,
+    /// Collapsed group
+    pub group: String,
+    /// Source the notifications came from
+    pub source: String,
+    /// Number of notifications collapsed
+    pub count: usize,
+    /// Oldest collapsed notification
+    pub first_at: DateTime<Utc>,
+    /// Newest collapsed notification
+    pub last_at: DateTime<Utc>,
+    /// Sample of titles
+    pub titles: Vec<String>,
+    /// Frequent words, for search
+    pub keywords: Vec<String>,
+    /// Summary text
+    pub summary: String,
+    /// Summarizer that wrote the summary, `None` for the built-in one
+    pub summarized_by: Option<String>,
+    /// When the digest was created
+    pub created_at: DateTime<Utc>,
+}
+
+impl NotificationDigest {
+    /// Case-insensitive match against group, source, summary, titles and keywords
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        [&self.group, &self.source, &self.summary]
+            .into_iter()
+            .chain(self.titles.iter())
+            .chain(self.keywords.iter())
+            .any(|text| text.to_lowercase().contains(&query))
+    }
+}
+
+/// Summarizer for collapsed notification groups, e.g. backed by a local LLM
+#[async_trait]
+pub trait DigestSummarizer: Send + Sync {
+    /// Summarizer name, recorded in the digest
+    fn name(&self) -> &str;
+
+    /// Summarize a group's notifications
+    async fn summarize(&self, group: &str, notifications: &[Notification]) -> Result<String>;
+}
+
+/// Group old history entries into digest candidates
+pub fn collect_candidates(policy: &DigestPolicy, entries: &[HistoryEntry], now: DateTime<Utc>) -> Vec<DigestCandidate> {
+    let mut groups: HashMap<String, DigestCandidate> = HashMap::new();
+
+    for entry in entries.iter().filter(|e| policy.is_collapsible(e, now)) {
+        let notification = &entry.notification;
+        let group = notification.group.clone().unwrap_or_else(|| notification.source.name.clone());
+        groups.entry(group.clone())
+            .or_insert_with(|| DigestCandidate {
+                group,
+                source: notification.source.name.clone(),
+                entries: Vec::new(),
+            })
+            .entries
+            .push(entry.clone());
+    }
+
+    let mut candidates: Vec<DigestCandidate> = groups.into_values()
+        .filter(|candidate| candidate.entries.len() >= policy.min_group_size.max(1))
+        .collect();
+    for candidate in &mut candidates {
+        candidate.entries.sort_by_key(|e| e.created_at);
+    }
+    candidates.sort_by(|a, b| a.group.cmp(&b.group));
+    candidates
+}
+
+/// Built-in summary listing counts, time span and the first few titles
+pub fn extractive_summary(candidate: &DigestCandidate, max_titles: usize) -> String {
+    let count = candidate.entries.len();
+    let mut summary = format!(
+        "{} notification{} from {}",
+        count,
+        if count == 1 { "" } else { "s" },
+        candidate.source
+    );
+
+    if let (Some(first), Some(last)) = (candidate.entries.first(), candidate.entries.last()) {
+        summary.push_str(&format!(
+            " between {} and {}",
+            first.created_at.format("%Y-%m-%d"),
+            last.created_at.format("%Y-%m-%d")
+        ));
+    }
+
+    let titles: Vec<&str> = candidate.entries.iter()
+        .map(|e| e.notification.title.as_str())
+        .take(max_titles)
+        .collect();
+    if !titles.is_empty() {
+        summary.push_str(": ");
+        summary.push_str(&titles.join("; "));
+        if count > titles.len() {
+            summary.push_str(&format!(" and {} more", count - titles.len()));
+        }
+    }
+    summary
+}
+
+/// Most frequent words across titles and bodies
+fn extract_keywords<'a>(notifications: impl Iterator<Item = &'a Notification>) -> Vec<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for notification in notifications {
+        let text = format!("{} {}", notification.title, notification.body);
+        for word in text.split(|c: char| !c.is_alphanumeric()) {
+            let word = word.to_lowercase();
+            if word.len() >= 4 && !STOP_WORDS.contains(&word.as_str()) {
+                *counts.entry(word).or_default() += 1;
+            }
+        }
+    }
+
+    let mut words: Vec<(String, usize)> = counts.into_iter().collect();
+    words.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    words.into_iter().take(MAX_KEYWORDS).map(|(word, _)| word).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(title: &str, source: &str, age_days: i64) -> HistoryEntry {
+        let mut notification = Notification::new(title.to_string(), "Build finished".to_string());
+        notification.source.name = source.to_string();
+        HistoryEntry {
+            notification,
+            created_at: Utc::now() - ChronoDuration::days(age_days),
+            dismissed_at: None,
+            dismissal_reason: None,
+            snoozed_until: None,
+        }
+    }
+
+    #[test]
+    fn test_collect_candidates_respects_overrides() {
+        let mut policy = DigestPolicy::default();
+        policy.source_overrides.insert("Security".to_string(), SourceRetention::Never);
+
+        let entries = vec![
+            entry("CI passed", "CI", 5),
+            entry("CI failed", "CI", 4),
+            entry("CI passed", "CI", 4),
+            entry("CI running", "CI", 0),
+            entry("Login", "Security", 10),
+            entry("Login", "Security", 10),
+            entry("Login", "Security", 10),
+        ];
+
+        let candidates = collect_candidates(&policy, &entries, Utc::now());
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].group, "CI");
+        assert_eq!(candidates[0].entries.len(), 3);
+
+        let summary = extractive_summary(&candidates[0], 2);
+        assert!(summary.starts_with("3 notifications from CI"));
+        assert!(summary.ends_with("and 1 more"));
+
+        let digest = candidates[0].clone().into_digest(summary, None, 2);
+        assert!(digest.matches("build"));
+        assert!(digest.matches("ci failed"));
+    }
+}
@@ -0,0 +1,299 @@
+//! Do not disturb schedules, exceptions and focus modes
+//!
+//! Besides the manual `do_not_disturb` switch, notifications are held back
+//! during scheduled quiet hours and while a focus mode is on. A focus mode
+//! silences some notification types and lets urgent ones through, e.g.
+//! "Deep Work" silences messages but still shows critical notifications.
+//! Focus modes are switched from the system node's `focus_mode` action, by
+//! automation scripts, or by a schedule naming one.
+//!
+//! Held back notifications are not shown or sent on, but they are kept in
+//! the history.
+
+use crate::{Notification, NotificationPriority, NotificationType};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveTime, Weekday};
+use horizonos_graph_nodes::{NodeAction, FOCUS_MODE_ACTION, FOCUS_MODE_PARAMETER};
+use serde::{Deserialize, Serialize};
+
+/// Name of the focus mode available by default
+pub const DEEP_WORK: &str = "Deep Work";
+
+/// A recurring window of quiet hours, in local time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DndSchedule {
+    /// Start of the window
+    pub start: NaiveTime,
+    /// End of the window; before `start` for windows running past midnight
+    pub end: NaiveTime,
+    /// Days the window starts on; every day when empty
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    /// Focus mode in effect during the window; plain do not disturb when unset
+    #[serde(default)]
+    pub focus_mode: Option<String>,
+}
+
+impl DndSchedule {
+    /// Window from `start` to `end` every day
+    pub fn daily(start: NaiveTime, end: NaiveTime) -> Self {
+        Self { start, end, days: Vec::new(), focus_mode: None }
+    }
+
+    /// Whether the window is open at `now`
+    pub fn is_active_at(&self, now: DateTime<Local>) -> bool {
+        let time = now.time();
+        let today = now.weekday();
+        if self.start < self.end {
+            self.starts_on(today) && time >= self.start && time < self.end
+        } else {
+            // Past midnight the window belongs to the day it started on
+            (self.starts_on(today) && time >= self.start)
+                || (self.starts_on((now - ChronoDuration::days(1)).weekday()) && time < self.end)
+        }
+    }
+
+    fn starts_on(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+}
+
+/// Notifications shown whatever else is silenced
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DndExceptions {
+    /// Application IDs or source names, e.g. `org.horizonos.Calendar`
+    pub apps: Vec<String>,
+    /// Notification types
+    pub types: Vec<NotificationType>,
+}
+
+impl DndExceptions {
+    /// Whether the notification is exempt
+    pub fn allows(&self, notification: &Notification) -> bool {
+        if self.types.contains(&notification.notification_type) {
+            return true;
+        }
+        let source = &notification.source;
+        self.apps.iter().any(|app| source.app_id.as_deref() == Some(app.as_str()) || source.name == *app)
+    }
+}
+
+/// A named set of rules for notifications to hold back
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FocusMode {
+    /// Name shown to the user and used to switch to the mode
+    pub name: String,
+    /// Types to silence; every type when empty
+    #[serde(default)]
+    pub silenced_types: Vec<NotificationType>,
+    /// Notifications at this priority or above are shown anyway
+    #[serde(default)]
+    pub breakthrough_priority: Option<NotificationPriority>,
+    /// Notifications shown while the mode is on
+    #[serde(default)]
+    pub exceptions: DndExceptions,
+}
+
+impl FocusMode {
+    /// Mode silencing messages but letting critical notifications through
+    pub fn deep_work() -> Self {
+        Self {
+            name: DEEP_WORK.to_string(),
+            silenced_types: vec![NotificationType::Message],
+            breakthrough_priority: Some(NotificationPriority::Critical),
+            exceptions: DndExceptions::default(),
+        }
+    }
+
+    /// Whether the mode holds the notification back
+    pub fn silences(&self, notification: &Notification) -> bool {
+        if self.breakthrough_priority.is_some_and(|priority| notification.priority >= priority)
+            || self.exceptions.allows(notification)
+        {
+            return false;
+        }
+        self.silenced_types.is_empty() || self.silenced_types.contains(&notification.notification_type)
+    }
+}
+
+/// Quiet hours, exceptions and focus modes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DoNotDisturbSettings {
+    /// Recurring quiet hours
+    pub schedules: Vec<DndSchedule>,
+    /// Notifications shown even during do not disturb and focus modes
+    pub exceptions: DndExceptions,
+    /// Focus modes that can be switched to
+    pub focus_modes: Vec<FocusMode>,
+    /// Focus mode switched on, by name
+    pub active_focus_mode: Option<String>,
+}
+
+impl Default for DoNotDisturbSettings {
+    fn default() -> Self {
+        Self {
+            schedules: Vec::new(),
+            exceptions: DndExceptions::default(),
+            focus_modes: vec![FocusMode::deep_work()],
+            active_focus_mode: None,
+        }
+    }
+}
+
+impl DoNotDisturbSettings {
+    /// Focus mode by name, ignoring case
+    pub fn focus_mode(&self, name: &str) -> Option<&FocusMode> {
+        self.focus_modes.iter().find(|mode| mode.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Focus modes in effect at `now`: the one switched on and those of open schedules
+    pub fn active_focus_modes(&self, now: DateTime<Local>) -> Vec<&FocusMode> {
+        let scheduled = self
+            .schedules
+            .iter()
+            .filter(|schedule| schedule.is_active_at(now))
+            .filter_map(|schedule| schedule.focus_mode.as_deref());
+        let mut modes: Vec<&FocusMode> = Vec::new();
+        for name in self.active_focus_mode.as_deref().into_iter().chain(scheduled) {
+            match self.focus_mode(name) {
+                Some(mode) if !modes.contains(&mode) => modes.push(mode),
+                Some(_) => {}
+                None => log::warn!("Unknown focus mode {}", name),
+            }
+        }
+        modes
+    }
+
+    /// Whether plain do not disturb is on at `now`, manually or by schedule
+    pub fn quiet_hours_at(&self, manual: bool, now: DateTime<Local>) -> bool {
+        manual
+            || self
+                .schedules
+                .iter()
+                .any(|schedule| schedule.focus_mode.is_none() && schedule.is_active_at(now))
+    }
+
+    /// Whether a notification is held back at `now`
+    ///
+    /// `manual` is the do not disturb switch; with `critical_bypass` critical
+    /// notifications are shown during do not disturb.
+    pub fn silences(&self, notification: &Notification, manual: bool, critical_bypass: bool, now: DateTime<Local>) -> bool {
+        if self.exceptions.allows(notification) {
+            return false;
+        }
+        let bypasses = critical_bypass && notification.priority == NotificationPriority::Critical;
+        if self.quiet_hours_at(manual, now) && !bypasses {
+            return true;
+        }
+        self.active_focus_modes(now).iter().any(|mode| mode.silences(notification))
+    }
+}
+
+/// Focus mode a system node `focus_mode` action switches to
+///
+/// `Some(None)` switches focus modes off, which is what an action without a
+/// mode, or with an empty one, asks for. `None` for other actions.
+pub fn focus_mode_request(action: &NodeAction) -> Option<Option<String>> {
+    match action {
+        NodeAction::Custom { action_type, parameters } if action_type == FOCUS_MODE_ACTION => Some(
+            parameters
+                .get(FOCUS_MODE_PARAMETER)
+                .map(|mode| mode.trim())
+                .filter(|mode| !mode.is_empty())
+                .map(str::to_string),
+        ),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NotificationSource;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        // 2024-01-01 is a Monday
+        Local.with_ymd_and_hms(2024, 1, day, hour, minute, 0).unwrap()
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn notification(notification_type: NotificationType, priority: NotificationPriority) -> Notification {
+        Notification::new("Ping".to_string(), String::new())
+            .with_type(notification_type)
+            .with_priority(priority)
+    }
+
+    #[test]
+    fn test_schedules_span_midnight_and_days() {
+        let night = DndSchedule::daily(time(22, 0), time(7, 0));
+        assert!(night.is_active_at(at(1, 23, 30)));
+        assert!(night.is_active_at(at(2, 6, 59)));
+        assert!(!night.is_active_at(at(2, 7, 0)));
+        assert!(!night.is_active_at(at(2, 12, 0)));
+
+        // Friday night runs into Saturday, but Saturday night is free
+        let friday_night = DndSchedule { days: vec![Weekday::Fri], ..night };
+        assert!(friday_night.is_active_at(at(6, 2, 0)));
+        assert!(!friday_night.is_active_at(at(6, 23, 0)));
+        assert!(!friday_night.is_active_at(at(7, 2, 0)));
+    }
+
+    #[test]
+    fn test_focus_modes_and_exceptions() {
+        let mut settings = DoNotDisturbSettings::default();
+        let message = notification(NotificationType::Message, NotificationPriority::Normal);
+        let critical_message = notification(NotificationType::Message, NotificationPriority::Critical);
+        let alert = notification(NotificationType::Alert, NotificationPriority::Normal);
+        let now = at(1, 10, 0);
+        assert!(!settings.silences(&message, false, true, now));
+
+        // Deep Work silences messages but lets critical ones through
+        settings.active_focus_mode = Some("deep work".to_string());
+        assert!(settings.silences(&message, false, true, now));
+        assert!(!settings.silences(&critical_message, false, true, now));
+        assert!(!settings.silences(&alert, false, true, now));
+
+        // Scheduled quiet hours silence everything but exceptions
+        settings.active_focus_mode = None;
+        settings.schedules.push(DndSchedule::daily(time(9, 0), time(12, 0)));
+        settings.exceptions.types.push(NotificationType::Security);
+        settings.exceptions.apps.push("org.horizonos.Calendar".to_string());
+        assert!(settings.silences(&alert, false, true, now));
+        assert!(!settings.silences(&critical_message, false, true, now));
+        assert!(settings.silences(&critical_message, false, false, now));
+        assert!(!settings.silences(&notification(NotificationType::Security, NotificationPriority::Low), false, false, now));
+        let calendar = alert.clone().with_source(NotificationSource {
+            name: "Calendar".to_string(),
+            app_id: Some("org.horizonos.Calendar".to_string()),
+            pid: None,
+            icon: None,
+        });
+        assert!(!settings.silences(&calendar, false, false, now));
+        assert!(!settings.silences(&alert, false, true, at(1, 13, 0)));
+
+        // A schedule can switch a focus mode on instead
+        settings.schedules[0].focus_mode = Some(DEEP_WORK.to_string());
+        assert!(!settings.silences(&alert, false, true, now));
+        assert!(settings.silences(&message, false, true, now));
+    }
+
+    #[test]
+    fn test_focus_mode_requests_from_node_actions() {
+        let action = |mode: Option<&str>| NodeAction::Custom {
+            action_type: FOCUS_MODE_ACTION.to_string(),
+            parameters: mode
+                .map(|mode| HashMap::from([(FOCUS_MODE_PARAMETER.to_string(), mode.to_string())]))
+                .unwrap_or_default(),
+        };
+        assert_eq!(focus_mode_request(&action(Some("Deep Work"))), Some(Some(DEEP_WORK.to_string())));
+        assert_eq!(focus_mode_request(&action(Some(" "))), Some(None));
+        assert_eq!(focus_mode_request(&action(None)), Some(None));
+        assert_eq!(focus_mode_request(&NodeAction::Open), None);
+    }
+}
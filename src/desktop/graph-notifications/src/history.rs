@@ -1,22 +1,86 @@
 //! Notification history management
+//!
+//! History is kept in a SQLite database: on disk when a storage path is
+//! configured, in memory otherwise. Entries are pruned by the retention
+//! period and entry limit of [`HistorySettings`], except snoozed ones, which
+//! stay until they resurface. [`HistoryQuery`] searches entries by
+//! application, type, tag, date range and full text over title and body.
 
-use crate::Notification;
-use anyhow::Result;
+use crate::digest::{self, DigestCandidate, DigestPolicy, NotificationDigest};
+use crate::{HistorySettings, Notification, NotificationType};
+use anyhow::{Context, Result};
+use rusqlite::types::Value;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
-use std::sync::{Arc, RwLock};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
+/// Tables, indexes and the full-text index kept in step with entries
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS entries (
+        seq INTEGER PRIMARY KEY,
+        id TEXT NOT NULL UNIQUE,
+        created_at INTEGER NOT NULL,
+        app TEXT NOT NULL,
+        app_id TEXT,
+        notification_type TEXT NOT NULL,
+        title TEXT NOT NULL,
+        body TEXT NOT NULL,
+        tags TEXT NOT NULL,
+        snoozed_until INTEGER,
+        entry TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS entries_created_at ON entries (created_at);
+    CREATE INDEX IF NOT EXISTS entries_app ON entries (app);
+    CREATE INDEX IF NOT EXISTS entries_snoozed_until ON entries (snoozed_until);
+    CREATE VIRTUAL TABLE IF NOT EXISTS entries_fts USING fts5 (title, body, content = 'entries', content_rowid = 'seq');
+    CREATE TRIGGER IF NOT EXISTS entries_fts_insert AFTER INSERT ON entries BEGIN
+        INSERT INTO entries_fts (rowid, title, body) VALUES (new.seq, new.title, new.body);
+    END;
+    CREATE TRIGGER IF NOT EXISTS entries_fts_delete AFTER DELETE ON entries BEGIN
+        INSERT INTO entries_fts (entries_fts, rowid, title, body) VALUES ('delete', old.seq, old.title, old.body);
+    END;
+    CREATE TRIGGER IF NOT EXISTS entries_fts_update AFTER UPDATE ON entries BEGIN
+        INSERT INTO entries_fts (entries_fts, rowid, title, body) VALUES ('delete', old.seq, old.title, old.body);
+        INSERT INTO entries_fts (rowid, title, body) VALUES (new.seq, new.title, new.body);
+    END;
+    CREATE TABLE IF NOT EXISTS digests (
+        seq INTEGER PRIMARY KEY,
+        digest TEXT NOT NULL
+    );
+";
+
 /// Notification history storage
 pub struct NotificationHistory {
-    /// Historical notifications
-    entries: Arc<RwLock<VecDeque<HistoryEntry>>>,
-    /// Maximum entries
+    /// Database holding entries and digests
+    db: Mutex<Connection>,
+    /// Retention applied when pruning
+    limits: RwLock<HistoryLimits>,
+}
+
+/// Retention settings taken from [`HistorySettings`]
+#[derive(Debug, Clone, Copy)]
+struct HistoryLimits {
+    /// Maximum entries, and maximum digests
     max_entries: usize,
+    /// Days entries are kept, 0 to keep them until the limit is reached
+    retention_days: u32,
+    /// Keep entries once dismissed
+    store_dismissed: bool,
+}
+
+impl Default for HistoryLimits {
+    fn default() -> Self {
+        Self { max_entries: 1000, retention_days: 0, store_dismissed: true }
+    }
 }
 
 /// History entry
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryEntry {
     /// Notification
     pub notification: Notification,
@@ -26,10 +90,21 @@ pub struct HistoryEntry {
     pub dismissed_at: Option<DateTime<Utc>>,
     /// How it was dismissed
     pub dismissal_reason: Option<DismissalReason>,
+    /// When a snoozed notification should resurface
+    #[serde(default)]
+    pub snoozed_until: Option<DateTime<Utc>>,
+}
+
+/// JSON history file written before the database, imported on first open
+#[derive(Deserialize)]
+struct HistoryFile {
+    entries: VecDeque<HistoryEntry>,
+    #[serde(default)]
+    digests: VecDeque<NotificationDigest>,
 }
 
 /// Reasons for dismissal
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DismissalReason {
     UserDismissed,
     Expired,
@@ -38,62 +113,589 @@ pub enum DismissalReason {
     SystemCleared,
 }
 
+/// Search over history entries; unset fields match everything
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryQuery {
+    /// Source name or application ID
+    pub app: Option<String>,
+    pub notification_type: Option<NotificationType>,
+    pub tag: Option<String>,
+    /// Entries created at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Entries created before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Words that must all appear in the title or body, matched as prefixes
+    pub text: Option<String>,
+    /// Maximum entries returned
+    pub limit: Option<usize>,
+}
+
+impl HistoryQuery {
+    /// Query matching every entry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only entries from a source name or application ID
+    pub fn app(mut self, app: impl Into<String>) -> Self {
+        self.app = Some(app.into());
+        self
+    }
+
+    /// Only entries of a type
+    pub fn of_type(mut self, notification_type: NotificationType) -> Self {
+        self.notification_type = Some(notification_type);
+        self
+    }
+
+    /// Only entries carrying a tag
+    pub fn tagged(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    /// Only entries created in `[since, until)`
+    pub fn between(mut self, since: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self.until = Some(until);
+        self
+    }
+
+    /// Only entries whose title or body contain the words
+    pub fn matching(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Return at most `limit` entries
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
 impl NotificationHistory {
-    /// Create new history
+    /// Create history kept in memory
     pub fn new() -> Self {
-        Self {
-            entries: Arc::new(RwLock::new(VecDeque::new())),
-            max_entries: 1000,
+        let connection = Connection::open_in_memory().expect("Failed to open in-memory notification history");
+        Self::from_connection(connection).expect("Failed to create in-memory notification history")
+    }
+
+    /// Create history persisted to a database file, loading any existing entries
+    ///
+    /// A JSON history file from earlier versions at the same path is
+    /// imported and kept next to the database with a `.json.bak` extension.
+    pub fn with_storage(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let legacy = read_legacy_file(&path)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create notification history directory {:?}", parent))?;
+        }
+        let connection = Connection::open(&path)
+            .with_context(|| format!("Failed to open notification history {:?}", path))?;
+        let history = Self::from_connection(connection)?;
+
+        if let Some(file) = legacy {
+            let mut db = history.db.lock().unwrap();
+            let tx = db.transaction()?;
+            for entry in &file.entries {
+                insert_entry(&tx, entry)?;
+            }
+            for digest in &file.digests {
+                tx.execute("INSERT INTO digests (digest) VALUES (?1)", params![serde_json::to_string(digest)?])?;
+            }
+            tx.commit()?;
+            log::info!("Imported {} notifications into history database {:?}", file.entries.len(), path);
         }
+        Ok(history)
     }
-    
-    /// Add notification to history
+
+    fn from_connection(connection: Connection) -> Result<Self> {
+        connection.execute_batch(SCHEMA).context("Failed to create notification history tables")?;
+        Ok(Self {
+            db: Mutex::new(connection),
+            limits: RwLock::new(HistoryLimits::default()),
+        })
+    }
+
+    /// Apply entry limit, retention and dismissal settings, pruning right away
+    pub fn apply_settings(&self, settings: &HistorySettings) {
+        *self.limits.write().unwrap() = HistoryLimits {
+            max_entries: settings.max_entries,
+            retention_days: settings.retention_days,
+            store_dismissed: settings.store_dismissed,
+        };
+        if let Err(e) = self.prune(Utc::now()) {
+            log::warn!("Failed to prune notification history: {}", e);
+        }
+    }
+
+    /// Add notification to history, replacing any earlier entry for it
     pub async fn add(&self, notification: Notification) -> Result<()> {
         let entry = HistoryEntry {
             notification,
             created_at: Utc::now(),
             dismissed_at: None,
             dismissal_reason: None,
+            snoozed_until: None,
         };
-        
-        let mut entries = self.entries.write().unwrap();
-        entries.push_back(entry);
-        
-        // Limit size
-        while entries.len() > self.max_entries {
-            entries.pop_front();
-        }
-        
+
+        let mut db = self.db.lock().unwrap();
+        let tx = db.transaction()?;
+        tx.execute("DELETE FROM entries WHERE id = ?1", params![entry.notification.id.to_string()])?;
+        insert_entry(&tx, &entry)?;
+        prune_entries(&tx, *self.limits.read().unwrap(), entry.created_at)?;
+        tx.commit()?;
         Ok(())
     }
-    
+
     /// Update notification in history
     pub async fn update(&self, notification: Notification) -> Result<()> {
-        let mut entries = self.entries.write().unwrap();
-        if let Some(entry) = entries.iter_mut().rev().find(|e| e.notification.id == notification.id) {
+        let db = self.db.lock().unwrap();
+        if let Some(mut entry) = find_entry(&db, notification.id)? {
             entry.notification = notification;
+            insert_entry(&db, &entry)?;
         }
         Ok(())
     }
-    
-    /// Mark notification as dismissed
+
+    /// Mark notification as dismissed, or drop it if dismissed ones are not stored
     pub async fn dismiss(&self, id: Uuid) -> Result<()> {
-        let mut entries = self.entries.write().unwrap();
-        if let Some(entry) = entries.iter_mut().rev().find(|e| e.notification.id == id) {
+        let db = self.db.lock().unwrap();
+        if !self.limits.read().unwrap().store_dismissed {
+            db.execute("DELETE FROM entries WHERE id = ?1", params![id.to_string()])?;
+            return Ok(());
+        }
+        if let Some(mut entry) = find_entry(&db, id)? {
             entry.dismissed_at = Some(Utc::now());
             entry.dismissal_reason = Some(DismissalReason::UserDismissed);
+            entry.snoozed_until = None;
+            insert_entry(&db, &entry)?;
         }
         Ok(())
     }
-    
+
+    /// Snooze a notification until the given time
+    pub async fn snooze(&self, notification: Notification, until: DateTime<Utc>) -> Result<()> {
+        let db = self.db.lock().unwrap();
+        let entry = match find_entry(&db, notification.id)? {
+            Some(entry) => HistoryEntry { notification, snoozed_until: Some(until), ..entry },
+            None => HistoryEntry {
+                notification,
+                created_at: Utc::now(),
+                dismissed_at: None,
+                dismissal_reason: None,
+                snoozed_until: Some(until),
+            },
+        };
+        insert_entry(&db, &entry)
+    }
+
+    /// Move an already snoozed notification to a new time, returning whether it was snoozed
+    pub async fn reschedule(&self, id: Uuid, until: DateTime<Utc>) -> Result<bool> {
+        let db = self.db.lock().unwrap();
+        match find_entry(&db, id)?.filter(|entry| entry.snoozed_until.is_some()) {
+            Some(mut entry) => {
+                entry.snoozed_until = Some(until);
+                insert_entry(&db, &entry)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Remove a notification from the snoozed set without showing it again
+    pub async fn take_snoozed(&self, id: Uuid) -> Result<Option<Notification>> {
+        let db = self.db.lock().unwrap();
+        let Some(mut entry) = find_entry(&db, id)?.filter(|entry| entry.snoozed_until.is_some()) else {
+            return Ok(None);
+        };
+        entry.snoozed_until = None;
+        insert_entry(&db, &entry)?;
+        Ok(Some(entry.notification))
+    }
+
+    /// Remove and return snoozed notifications due at or before `now`
+    pub async fn take_due_snoozes(&self, now: DateTime<Utc>) -> Result<Vec<Notification>> {
+        let mut db = self.db.lock().unwrap();
+        let tx = db.transaction()?;
+        let due = select_entries(
+            &tx,
+            "WHERE snoozed_until <= ?1 ORDER BY seq",
+            [Value::from(now.timestamp_millis())],
+        )?;
+        for entry in &due {
+            insert_entry(&tx, &HistoryEntry { snoozed_until: None, ..entry.clone() })?;
+        }
+        tx.commit()?;
+        Ok(due.into_iter().map(|entry| entry.notification).collect())
+    }
+
+    /// Get snoozed notifications, soonest first
+    pub fn get_snoozed(&self) -> Vec<HistoryEntry> {
+        let db = self.db.lock().unwrap();
+        or_log(select_entries(&db, "WHERE snoozed_until IS NOT NULL ORDER BY snoozed_until, seq", []))
+    }
+
     /// Get recent notifications
     pub fn get_recent(&self, count: usize) -> Vec<HistoryEntry> {
-        let entries = self.entries.read().unwrap();
-        entries.iter().rev().take(count).cloned().collect()
+        let db = self.db.lock().unwrap();
+        or_log(select_entries(&db, "ORDER BY seq DESC LIMIT ?1", [Value::from(count as i64)]))
     }
-    
-    /// Clear history
+
+    /// Entries matching a query, newest first
+    pub fn query(&self, query: &HistoryQuery) -> Result<Vec<HistoryEntry>> {
+        let mut clauses = Vec::new();
+        let mut values = Vec::new();
+        if let Some(app) = &query.app {
+            clauses.push("(app = ? OR app_id = ?)");
+            values.extend([Value::from(app.clone()), Value::from(app.clone())]);
+        }
+        if let Some(notification_type) = query.notification_type {
+            clauses.push("notification_type = ?");
+            values.push(Value::from(type_name(notification_type)));
+        }
+        if let Some(tag) = &query.tag {
+            clauses.push("EXISTS (SELECT 1 FROM json_each(entries.tags) WHERE json_each.value = ?)");
+            values.push(Value::from(tag.clone()));
+        }
+        if let Some(since) = query.since {
+            clauses.push("created_at >= ?");
+            values.push(Value::from(since.timestamp_millis()));
+        }
+        if let Some(until) = query.until {
+            clauses.push("created_at < ?");
+            values.push(Value::from(until.timestamp_millis()));
+        }
+        if let Some(text) = query.text.as_deref().and_then(fts_query) {
+            clauses.push("seq IN (SELECT rowid FROM entries_fts WHERE entries_fts MATCH ?)");
+            values.push(Value::from(text));
+        }
+
+        let filter = if clauses.is_empty() { String::new() } else { format!("WHERE {}", clauses.join(" AND ")) };
+        let limit = query.limit.map_or(-1, |limit| limit as i64);
+        values.push(Value::from(limit));
+        let db = self.db.lock().unwrap();
+        select_entries(&db, &format!("{} ORDER BY seq DESC LIMIT ?", filter), values)
+    }
+
+    /// Delete entries past the retention period or beyond the entry limit
+    ///
+    /// Snoozed entries are kept until they resurface. Returns the number of
+    /// entries deleted.
+    pub fn prune(&self, now: DateTime<Utc>) -> Result<usize> {
+        let limits = *self.limits.read().unwrap();
+        let db = self.db.lock().unwrap();
+        prune_entries(&db, limits, now)
+    }
+
+    /// Old groups that the policy would collapse at `now`
+    pub fn digest_candidates(&self, policy: &DigestPolicy, now: DateTime<Utc>) -> Vec<DigestCandidate> {
+        let db = self.db.lock().unwrap();
+        let entries = or_log(select_entries(&db, "ORDER BY seq", []));
+        digest::collect_candidates(policy, &entries, now)
+    }
+
+    /// Replace collapsed entries with their digest
+    pub async fn apply_digest(&self, digest: NotificationDigest, collapsed: &[Uuid]) -> Result<()> {
+        let max_entries = self.limits.read().unwrap().max_entries;
+        let mut db = self.db.lock().unwrap();
+        let tx = db.transaction()?;
+        for id in collapsed {
+            tx.execute("DELETE FROM entries WHERE id = ?1 AND snoozed_until IS NULL", params![id.to_string()])?;
+        }
+        tx.execute("INSERT INTO digests (digest) VALUES (?1)", params![serde_json::to_string(&digest)?])?;
+        tx.execute(
+            "DELETE FROM digests WHERE seq NOT IN (SELECT seq FROM digests ORDER BY seq DESC LIMIT ?1)",
+            params![max_entries as i64],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Get digests, newest first
+    pub fn get_digests(&self) -> Vec<NotificationDigest> {
+        let db = self.db.lock().unwrap();
+        or_log(select_digests(&db))
+    }
+
+    /// Search entries and digests by text, newest first
+    pub fn search(&self, query: &str) -> (Vec<HistoryEntry>, Vec<NotificationDigest>) {
+        let db = self.db.lock().unwrap();
+        let pattern = format!("%{}%", query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"));
+        let matching_entries = or_log(select_entries(
+            &db,
+            "WHERE title LIKE ?1 ESCAPE '\\' OR body LIKE ?1 ESCAPE '\\' OR app LIKE ?1 ESCAPE '\\' ORDER BY seq DESC",
+            [Value::from(pattern)],
+        ));
+        let matching_digests = or_log(select_digests(&db))
+            .into_iter()
+            .filter(|d| d.matches(query))
+            .collect();
+
+        (matching_entries, matching_digests)
+    }
+
+    /// Clear history and digests, keeping snoozed notifications
     pub fn clear(&self) {
-        self.entries.write().unwrap().clear();
+        let db = self.db.lock().unwrap();
+        let result = db.execute_batch("DELETE FROM entries WHERE snoozed_until IS NULL; DELETE FROM digests;");
+        if let Err(e) = result {
+            log::warn!("Failed to clear notification history: {}", e);
+        }
+    }
+}
+
+impl Default for NotificationHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Read a JSON history file left by earlier versions, moving it aside
+fn read_legacy_file(path: &Path) -> Result<Option<HistoryFile>> {
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return Ok(None);
+    };
+    let mut data = Vec::new();
+    file.by_ref().take(16).read_to_end(&mut data)?;
+    if data.is_empty() || data == b"SQLite format 3\0" {
+        return Ok(None);
+    }
+    file.read_to_end(&mut data)?;
+    // The oldest files hold only the entry list
+    let file = serde_json::from_slice::<HistoryFile>(&data)
+        .or_else(|_| serde_json::from_slice(&data).map(|entries| HistoryFile { entries, digests: VecDeque::new() }))
+        .with_context(|| format!("Failed to parse notification history {:?}", path))?;
+    let backup = path.with_extension("json.bak");
+    std::fs::rename(path, &backup)
+        .with_context(|| format!("Failed to move notification history {:?} to {:?}", path, backup))?;
+    Ok(Some(file))
+}
+
+/// Insert an entry, or update the stored one with the same notification ID in place
+fn insert_entry(db: &Connection, entry: &HistoryEntry) -> Result<()> {
+    let notification = &entry.notification;
+    db.execute(
+        "INSERT INTO entries (id, created_at, app, app_id, notification_type, title, body, tags, snoozed_until, entry)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+         ON CONFLICT (id) DO UPDATE SET
+            created_at = excluded.created_at, app = excluded.app, app_id = excluded.app_id,
+            notification_type = excluded.notification_type, title = excluded.title, body = excluded.body,
+            tags = excluded.tags, snoozed_until = excluded.snoozed_until, entry = excluded.entry",
+        params![
+            notification.id.to_string(),
+            entry.created_at.timestamp_millis(),
+            notification.source.name,
+            notification.source.app_id,
+            type_name(notification.notification_type),
+            notification.title,
+            notification.body,
+            serde_json::to_string(&notification.tags)?,
+            entry.snoozed_until.map(|until| until.timestamp_millis()),
+            serde_json::to_string(entry)?,
+        ],
+    )?;
+    Ok(())
+}
+
+fn find_entry(db: &Connection, id: Uuid) -> Result<Option<HistoryEntry>> {
+    let entry: Option<String> = db
+        .query_row("SELECT entry FROM entries WHERE id = ?1", params![id.to_string()], |row| row.get(0))
+        .optional()?;
+    Ok(entry.map(|entry| serde_json::from_str(&entry)).transpose()?)
+}
+
+/// Entries selected by the clause following `SELECT entry FROM entries`
+fn select_entries(
+    db: &Connection,
+    clause: &str,
+    values: impl IntoIterator<Item = Value>,
+) -> Result<Vec<HistoryEntry>> {
+    let mut statement = db.prepare(&format!("SELECT entry FROM entries {}", clause))?;
+    let rows = statement.query_map(rusqlite::params_from_iter(values), |row| row.get::<_, String>(0))?;
+    let mut entries = Vec::new();
+    for row in rows {
+        entries.push(serde_json::from_str(&row?)?);
     }
-}
\ No newline at end of file
+    Ok(entries)
+}
+
+fn select_digests(db: &Connection) -> Result<Vec<NotificationDigest>> {
+    let mut statement = db.prepare("SELECT digest FROM digests ORDER BY seq DESC")?;
+    let rows = statement.query_map([], |row| row.get::<_, String>(0))?;
+    let mut digests = Vec::new();
+    for row in rows {
+        digests.push(serde_json::from_str(&row?)?);
+    }
+    Ok(digests)
+}
+
+fn prune_entries(db: &Connection, limits: HistoryLimits, now: DateTime<Utc>) -> Result<usize> {
+    let mut deleted = 0;
+    if limits.retention_days > 0 {
+        let cutoff = now - chrono::Duration::days(limits.retention_days as i64);
+        deleted += db.execute(
+            "DELETE FROM entries WHERE snoozed_until IS NULL AND created_at < ?1",
+            params![cutoff.timestamp_millis()],
+        )?;
+    }
+    deleted += db.execute(
+        "DELETE FROM entries WHERE seq IN (
+            SELECT seq FROM entries WHERE snoozed_until IS NULL ORDER BY seq
+            LIMIT max(0, (SELECT COUNT(*) FROM entries) - ?1)
+        )",
+        params![limits.max_entries as i64],
+    )?;
+    Ok(deleted)
+}
+
+/// Stored name of a notification type
+fn type_name(notification_type: NotificationType) -> String {
+    format!("{:?}", notification_type).to_lowercase()
+}
+
+/// FTS5 expression requiring every word of `text` as a prefix, `None` for no words
+fn fts_query(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Result of a read, or nothing after logging the error
+fn or_log<T: Default>(result: Result<T>) -> T {
+    result.unwrap_or_else(|e| {
+        log::warn!("Failed to read notification history: {}", e);
+        T::default()
+    })
+}
+
+/// Write a file through a temporary sibling so a crash never leaves it truncated
+pub(crate) fn write_atomic(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, data)?;
+    std::fs::rename(tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_snoozed_entries_survive_restart() {
+        let path = std::env::temp_dir().join(format!("horizonos-history-{}.json", Uuid::new_v4()));
+        let notification = Notification::new("Test".to_string(), "Body".to_string());
+        let id = notification.id;
+        let until = Utc::now() + chrono::Duration::minutes(10);
+
+        let history = NotificationHistory::with_storage(&path).unwrap();
+        history.add(notification.clone()).await.unwrap();
+        history.snooze(notification, until).await.unwrap();
+        history.clear();
+        drop(history);
+
+        let history = NotificationHistory::with_storage(&path).unwrap();
+        let snoozed = history.get_snoozed();
+        assert_eq!(snoozed.len(), 1);
+        assert_eq!(snoozed[0].notification.id, id);
+
+        assert!(history.take_due_snoozes(Utc::now()).await.unwrap().is_empty());
+        let due = history.take_due_snoozes(until).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert!(history.get_snoozed().is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    #[tokio::test]
+    async fn test_digest_replaces_collapsed_entries() {
+        let path = std::env::temp_dir().join(format!("horizonos-history-{}.json", Uuid::new_v4()));
+        let history = NotificationHistory::with_storage(&path).unwrap();
+        for title in ["Backup done", "Backup done", "Backup failed"] {
+            history.add(Notification::new(title.to_string(), "Nightly job".to_string())).await.unwrap();
+        }
+
+        let later = Utc::now() + chrono::Duration::days(30);
+        let policy = DigestPolicy::default();
+        let candidate = history.digest_candidates(&policy, later).pop().unwrap();
+        let collapsed: Vec<Uuid> = candidate.entries.iter().map(|e| e.notification.id).collect();
+        let summary = digest::extractive_summary(&candidate, policy.max_titles);
+        history.apply_digest(candidate.into_digest(summary, None, policy.max_titles), &collapsed).await.unwrap();
+        drop(history);
+
+        let history = NotificationHistory::with_storage(&path).unwrap();
+        assert!(history.get_recent(10).is_empty());
+        let (entries, digests) = history.search("nightly");
+        assert!(entries.is_empty());
+        assert_eq!(digests.len(), 1);
+        assert_eq!(digests[0].count, 3);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_query_by_app_type_tag_date_and_text() {
+        let history = NotificationHistory::new();
+        let mut invoice = Notification::new("Invoice overdue".to_string(), "Pay the electricity bill".to_string())
+            .with_source(crate::NotificationSource { name: "Mail".to_string(), app_id: Some("org.mail".to_string()), pid: None, icon: None });
+        invoice.notification_type = NotificationType::Message;
+        invoice.tags.push("finance".to_string());
+        history.add(invoice.clone()).await.unwrap();
+        history.add(Notification::new("Build failed".to_string(), "Pipeline billing-service".to_string())).await.unwrap();
+
+        let ids = |query: HistoryQuery| -> Vec<Uuid> {
+            history.query(&query).unwrap().into_iter().map(|e| e.notification.id).collect()
+        };
+        assert_eq!(ids(HistoryQuery::new().app("org.mail")), vec![invoice.id]);
+        assert_eq!(ids(HistoryQuery::new().of_type(NotificationType::Message)), vec![invoice.id]);
+        assert_eq!(ids(HistoryQuery::new().tagged("finance")), vec![invoice.id]);
+        assert_eq!(ids(HistoryQuery::new().matching("electric bill")), vec![invoice.id]);
+        assert_eq!(ids(HistoryQuery::new().matching("bill")).len(), 2);
+        assert_eq!(ids(HistoryQuery::new().matching("\"unbalanced")), Vec::<Uuid>::new());
+        assert_eq!(ids(HistoryQuery::new().limit(1)).len(), 1);
+
+        let now = Utc::now();
+        assert_eq!(ids(HistoryQuery::new().between(now - chrono::Duration::hours(1), now + chrono::Duration::hours(1))).len(), 2);
+        assert!(ids(HistoryQuery::new().between(now - chrono::Duration::days(2), now - chrono::Duration::days(1))).is_empty());
+
+        // Edits reach the full-text index
+        invoice.title = "Invoice paid".to_string();
+        history.update(invoice.clone()).await.unwrap();
+        assert_eq!(ids(HistoryQuery::new().matching("paid")), vec![invoice.id]);
+        assert!(ids(HistoryQuery::new().matching("overdue")).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_retention_and_legacy_import() {
+        let path = std::env::temp_dir().join(format!("horizonos-history-{}.json", Uuid::new_v4()));
+        let old = HistoryEntry {
+            notification: Notification::new("Old".to_string(), String::new()),
+            created_at: Utc::now() - chrono::Duration::days(30),
+            dismissed_at: None,
+            dismissal_reason: None,
+            snoozed_until: None,
+        };
+        let recent = HistoryEntry { notification: Notification::new("Recent".to_string(), String::new()), created_at: Utc::now(), ..old.clone() };
+        std::fs::write(&path, serde_json::to_vec(&vec![old, recent]).unwrap()).unwrap();
+
+        let history = NotificationHistory::with_storage(&path).unwrap();
+        assert_eq!(history.get_recent(10).len(), 2);
+        history.apply_settings(&HistorySettings { retention_days: 7, ..Default::default() });
+        let titles: Vec<String> = history.get_recent(10).into_iter().map(|e| e.notification.title).collect();
+        assert_eq!(titles, vec!["Recent".to_string()]);
+        drop(history);
+
+        let backup = path.with_extension("json.bak");
+        assert!(backup.exists());
+        assert_eq!(NotificationHistory::with_storage(&path).unwrap().get_recent(10).len(), 1);
+
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&backup);
+    }
+}
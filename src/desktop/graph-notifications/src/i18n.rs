@@ -0,0 +1,8 @@
+//! Translations for notification labels and templates
+
+use horizonos_graph_i18n::Domain;
+
+/// Notification strings, in `locales/<locale>/notifications.ftl`
+pub static I18N: Domain = Domain::new("notifications", &[
+    ("en-US", include_str!("../locales/en-US/notifications.ftl")),
+]);
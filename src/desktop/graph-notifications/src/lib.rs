@@ -7,6 +7,7 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::time::Duration;
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
@@ -20,15 +21,34 @@ pub mod history;
 pub mod filters;
 pub mod actions;
 pub mod channels;
+pub mod snooze;
+pub mod reminders;
+pub mod digest;
+pub mod i18n;
+pub mod problems;
+pub mod node_actions;
+pub mod materialize;
+pub mod review;
+pub mod focus;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
 
 pub use manager::NotificationManager;
 pub use types::*;
 pub use renderer::NotificationRenderer;
 pub use handler::NotificationHandler;
-pub use history::NotificationHistory;
+pub use history::{HistoryQuery, NotificationHistory};
 pub use filters::NotificationFilter;
 pub use actions::NotificationAction;
 pub use channels::NotificationChannel;
+pub use snooze::SnoozeDuration;
+pub use reminders::{Recurrence, Reminder, ReminderRequest, ReminderStore};
+pub use digest::{DigestPolicy, DigestSummarizer, NotificationDigest, SourceRetention};
+pub use problems::{problem_notification, ProblemNotifier};
+pub use node_actions::node_action_notification;
+pub use materialize::{keep_as_node, materialize_history, materialize_notification, MaterializedNotification};
+pub use focus::{focus_mode_request, DndExceptions, DndSchedule, DoNotDisturbSettings, FocusMode};
+pub use review::{NotificationReview, ReviewCommand, ReviewEffect, ReviewSettings, ReviewStep, ReviewUtterance, ReviewVerbosity};
 
 /// Notification system configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,10 +69,16 @@ pub struct NotificationConfig {
     pub priority_settings: PrioritySettings,
     /// Do not disturb mode
     pub do_not_disturb: bool,
+    /// Quiet hours, exceptions and focus modes
+    #[serde(default)]
+    pub dnd: DoNotDisturbSettings,
     /// Notification grouping
     pub enable_grouping: bool,
     /// History settings
     pub history_settings: HistorySettings,
+    /// File pending node reminders are persisted to
+    #[serde(default)]
+    pub reminders_path: Option<PathBuf>,
 }
 
 /// Notification position on screen
@@ -110,10 +136,16 @@ pub struct HistorySettings {
     pub enabled: bool,
     /// Maximum history entries
     pub max_entries: usize,
-    /// History retention period
+    /// Days entries are kept; 0 keeps them until `max_entries` is reached
     pub retention_days: u32,
     /// Store dismissed notifications
     pub store_dismissed: bool,
+    /// SQLite database the history is persisted to, keeping it and snoozed notifications across restarts
+    #[serde(default)]
+    pub storage_path: Option<PathBuf>,
+    /// Collapsing of old groups into digests
+    #[serde(default)]
+    pub digest: DigestPolicy,
 }
 
 /// Main notification structure
@@ -166,7 +198,7 @@ pub struct Notification {
 }
 
 /// Notification types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum NotificationType {
     /// System notification
     System,
@@ -244,10 +276,21 @@ pub enum NotificationEvent {
     },
     /// Notification clicked
     Clicked(Uuid),
+    /// Notification snoozed until the given time
+    Snoozed {
+        notification_id: Uuid,
+        until: DateTime<Utc>,
+    },
+    /// Snoozed notification shown again
+    Resurfaced(Uuid),
+    /// Old notifications collapsed into a digest
+    DigestCreated(NotificationDigest),
     /// Group expanded
     GroupExpanded(String),
     /// Group collapsed
     GroupCollapsed(String),
+    /// Focus mode switched on, or off for `None`
+    FocusModeChanged(Option<String>),
 }
 
 /// Trait for notification providers
@@ -288,6 +331,40 @@ pub trait NotificationRenderTarget: Send + Sync {
     async fn animate(&mut self, id: Uuid, animation: NotificationAnimation) -> Result<()>;
 }
 
+/// Public notification operations, implemented by `NotificationManager` and
+/// by the in-memory double in `testing`
+#[async_trait]
+pub trait NotificationService: Send + Sync {
+    /// Create a new notification
+    async fn notify(&self, notification: Notification) -> Result<()>;
+    
+    /// Update an existing notification
+    async fn update(&self, notification: Notification) -> Result<()>;
+    
+    /// Dismiss a notification
+    async fn dismiss(&self, id: Uuid) -> Result<()>;
+    
+    /// Dismiss all notifications
+    async fn dismiss_all(&self) -> Result<()>;
+    
+    /// Snooze a notification until the given time
+    async fn snooze_until(&self, id: Uuid, until: DateTime<Utc>) -> Result<()>;
+    
+    /// Get active notifications
+    async fn get_active(&self) -> Vec<Notification>;
+    
+    /// Get notification by ID
+    async fn get_notification(&self, id: Uuid) -> Option<Notification>;
+    
+    /// Subscribe to notification events
+    fn subscribe(&self) -> tokio::sync::broadcast::Receiver<NotificationEvent>;
+    
+    /// Snooze a notification for a preset or custom duration
+    async fn snooze(&self, id: Uuid, duration: SnoozeDuration) -> Result<()> {
+        self.snooze_until(id, duration.resurface_at()).await
+    }
+}
+
 /// Notification animations
 #[derive(Debug, Clone)]
 pub enum NotificationAnimation {
@@ -327,8 +404,10 @@ impl Default for NotificationConfig {
             animations: AnimationConfig::default(),
             priority_settings: PrioritySettings::default(),
             do_not_disturb: false,
+            dnd: DoNotDisturbSettings::default(),
             enable_grouping: true,
             history_settings: HistorySettings::default(),
+            reminders_path: None,
         }
     }
 }
@@ -361,6 +440,8 @@ impl Default for HistorySettings {
             max_entries: 1000,
             retention_days: 7,
             store_dismissed: true,
+            storage_path: None,
+            digest: DigestPolicy::default(),
         }
     }
 }
@@ -453,6 +534,11 @@ impl Notification {
         self
     }
     
+    /// Check if notification came back from a snooze
+    pub fn was_snoozed(&self) -> bool {
+        self.tags.iter().any(|tag| tag == snooze::SNOOZED_TAG)
+    }
+    
     /// Check if notification is expired
     pub fn is_expired(&self) -> bool {
         if let Some(expires_at) = self.expires_at {
@@ -3,9 +3,17 @@
 use crate::{
     Notification, NotificationConfig, NotificationEvent, NotificationFilter, NotificationHistory,
     NotificationPosition, NotificationPriority, NotificationProvider, NotificationRenderTarget,
-    NotificationChannel, NotificationAnimation, SlideDirection
+    NotificationChannel, NotificationAnimation, NotificationService, SlideDirection, SnoozeDuration
 };
-use anyhow::{Result, Context};
+use crate::history::HistoryEntry;
+use crate::snooze;
+use crate::reminders::{Reminder, ReminderStore};
+use crate::digest::{self, DigestSummarizer, NotificationDigest};
+use crate::focus::focus_mode_request;
+use chrono::{DateTime, Local, Utc};
+use anyhow::{anyhow, Result, Context};
+use horizonos_graph_nodes::NodeAction;
+use async_trait::async_trait;
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -38,8 +46,15 @@ pub struct NotificationManager {
     command_tx: mpsc::Sender<NotificationCommand>,
     /// Grouped notifications
     groups: Arc<RwLock<HashMap<String, Vec<Uuid>>>>,
+    /// Summarizer for history digests
+    digest_summarizer: Arc<RwLock<Option<Arc<dyn DigestSummarizer>>>>,
+    /// Pending node reminders
+    reminders: Arc<ReminderStore>,
 }
 
+/// How often old history is checked for groups to collapse and entries past retention
+const DIGEST_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
 /// Internal commands for the notification manager
 enum NotificationCommand {
     Create(Notification),
@@ -47,6 +62,9 @@ enum NotificationCommand {
     Dismiss(Uuid),
     DismissAll,
     ClearGroup(String),
+    Snooze(Uuid, DateTime<Utc>),
+    Resurface(Uuid),
+    CancelSnooze(Uuid),
     ProcessQueue,
     CheckExpiry,
 }
@@ -57,11 +75,27 @@ impl NotificationManager {
         let (event_tx, _) = broadcast::channel(1024);
         let (command_tx, mut command_rx) = mpsc::channel(1024);
         
+        let history = match &config.history_settings.storage_path {
+            Some(path) => NotificationHistory::with_storage(path).unwrap_or_else(|e| {
+                warn!("Failed to load notification history, starting empty: {}", e);
+                NotificationHistory::new()
+            }),
+            None => NotificationHistory::new(),
+        };
+        history.apply_settings(&config.history_settings);
+        let reminders = match &config.reminders_path {
+            Some(path) => ReminderStore::with_storage(path).unwrap_or_else(|e| {
+                warn!("Failed to load reminders, starting empty: {}", e);
+                ReminderStore::new()
+            }),
+            None => ReminderStore::new(),
+        };
+        
         let manager = Self {
             config: Arc::new(RwLock::new(config)),
             active: Arc::new(RwLock::new(HashMap::new())),
             queue: Arc::new(RwLock::new(VecDeque::new())),
-            history: Arc::new(NotificationHistory::new()),
+            history: Arc::new(history),
             providers: Arc::new(RwLock::new(Vec::new())),
             render_targets: Arc::new(RwLock::new(Vec::new())),
             event_tx: event_tx.clone(),
@@ -69,6 +103,8 @@ impl NotificationManager {
             filters: Arc::new(RwLock::new(Vec::new())),
             command_tx,
             groups: Arc::new(RwLock::new(HashMap::new())),
+            digest_summarizer: Arc::new(RwLock::new(None)),
+            reminders: Arc::new(reminders),
         };
         
         // Spawn command processor
@@ -90,6 +126,27 @@ impl NotificationManager {
                 if let Err(e) = manager_clone.check_expired_notifications().await {
                     error!("Error checking expired notifications: {}", e);
                 }
+                if let Err(e) = manager_clone.resurface_due_notifications().await {
+                    error!("Error resurfacing snoozed notifications: {}", e);
+                }
+                if let Err(e) = manager_clone.fire_due_reminders().await {
+                    error!("Error firing reminders: {}", e);
+                }
+            }
+        });
+        
+        // Spawn history digester
+        let manager_clone = manager.clone_internals();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(DIGEST_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(e) = manager_clone.collapse_old_history(Utc::now()).await {
+                    error!("Error collapsing notification history: {}", e);
+                }
+                if let Err(e) = manager_clone.history.prune(Utc::now()) {
+                    error!("Error pruning notification history: {}", e);
+                }
             }
         });
         
@@ -109,6 +166,8 @@ impl NotificationManager {
             channels: self.channels.clone(),
             filters: self.filters.clone(),
             groups: self.groups.clone(),
+            digest_summarizer: self.digest_summarizer.clone(),
+            reminders: self.reminders.clone(),
         }
     }
     
@@ -171,6 +230,63 @@ impl NotificationManager {
         Ok(())
     }
     
+    /// Snooze a notification for a preset or custom duration
+    pub async fn snooze(&self, id: Uuid, duration: SnoozeDuration) -> Result<()> {
+        self.snooze_until(id, duration.resurface_at()).await
+    }
+    
+    /// Snooze a notification until the given time, or reschedule an already snoozed one
+    pub async fn snooze_until(&self, id: Uuid, until: DateTime<Utc>) -> Result<()> {
+        self.command_tx.send(NotificationCommand::Snooze(id, until)).await
+            .context("Failed to send snooze command")?;
+        Ok(())
+    }
+    
+    /// Show a snoozed notification again right away
+    pub async fn resurface_now(&self, id: Uuid) -> Result<()> {
+        self.command_tx.send(NotificationCommand::Resurface(id)).await
+            .context("Failed to send resurface command")?;
+        Ok(())
+    }
+    
+    /// Drop a snoozed notification without showing it again
+    pub async fn cancel_snooze(&self, id: Uuid) -> Result<()> {
+        self.command_tx.send(NotificationCommand::CancelSnooze(id)).await
+            .context("Failed to send cancel snooze command")?;
+        Ok(())
+    }
+    
+    /// Get snoozed notifications, soonest first
+    pub fn get_snoozed(&self) -> Vec<HistoryEntry> {
+        self.history.get_snoozed()
+    }
+    
+    /// Schedule a reminder, returning its id
+    pub fn remind(&self, reminder: Reminder) -> Result<Uuid> {
+        info!("Reminder on node {} set for {}", reminder.node_id, reminder.due);
+        self.reminders.add(reminder)
+    }
+    
+    /// Drop a pending reminder
+    pub fn cancel_reminder(&self, id: Uuid) -> Result<bool> {
+        self.reminders.cancel(id)
+    }
+    
+    /// Pending node reminders
+    pub fn reminders(&self) -> &ReminderStore {
+        &self.reminders
+    }
+    
+    /// Install a summarizer (e.g. a local LLM) used for digests when the policy asks for it
+    pub async fn set_digest_summarizer(&self, summarizer: Arc<dyn DigestSummarizer>) {
+        *self.digest_summarizer.write().await = Some(summarizer);
+    }
+    
+    /// Collapse old notification groups into digests now instead of waiting for the timer
+    pub async fn collapse_history_now(&self) -> Result<Vec<NotificationDigest>> {
+        self.clone_internals().collapse_old_history(Utc::now()).await
+    }
+    
     /// Get active notifications
     pub async fn get_active(&self) -> Vec<Notification> {
         self.active.read().await.values().cloned().collect()
@@ -195,6 +311,19 @@ impl NotificationManager {
             .unwrap_or_default()
     }
     
+    /// Report that the user ran one of a notification's actions, e.g. from the keyboard
+    pub fn trigger_action(&self, notification_id: Uuid, action_id: &str) {
+        let _ = self.event_tx.send(NotificationEvent::ActionTriggered {
+            notification_id,
+            action_id: action_id.to_string(),
+        });
+    }
+    
+    /// Report that the user opened a notification, as clicking it would
+    pub fn open(&self, id: Uuid) {
+        let _ = self.event_tx.send(NotificationEvent::Clicked(id));
+    }
+    
     /// Subscribe to notification events
     pub fn subscribe(&self) -> broadcast::Receiver<NotificationEvent> {
         self.event_tx.subscribe()
@@ -207,6 +336,7 @@ impl NotificationManager {
     
     /// Update configuration
     pub async fn update_config(&self, config: NotificationConfig) {
+        self.history.apply_settings(&config.history_settings);
         *self.config.write().await = config;
     }
     
@@ -214,6 +344,82 @@ impl NotificationManager {
     pub async fn config(&self) -> NotificationConfig {
         self.config.read().await.clone()
     }
+    
+    /// Switch to a focus mode by name, or switch focus modes off with `None`
+    pub async fn set_focus_mode(&self, name: Option<&str>) -> Result<()> {
+        let mut config = self.config.write().await;
+        let mode = match name {
+            Some(name) => Some(
+                config.dnd.focus_mode(name)
+                    .map(|mode| mode.name.clone())
+                    .ok_or_else(|| anyhow!("Unknown focus mode: {}", name))?,
+            ),
+            None => None,
+        };
+        if config.dnd.active_focus_mode == mode {
+            return Ok(());
+        }
+        info!("Focus mode: {}", mode.as_deref().unwrap_or("off"));
+        config.dnd.active_focus_mode = mode.clone();
+        let _ = self.event_tx.send(NotificationEvent::FocusModeChanged(mode));
+        Ok(())
+    }
+    
+    /// Focus mode switched on, by name
+    pub async fn active_focus_mode(&self) -> Option<String> {
+        self.config.read().await.dnd.active_focus_mode.clone()
+    }
+    
+    /// Names of the focus modes that can be switched to
+    pub async fn focus_modes(&self) -> Vec<String> {
+        self.config.read().await.dnd.focus_modes.iter().map(|mode| mode.name.clone()).collect()
+    }
+    
+    /// Run a system node `focus_mode` action, returning whether it was one
+    pub async fn handle_focus_action(&self, action: &NodeAction) -> Result<bool> {
+        match focus_mode_request(action) {
+            Some(mode) => {
+                self.set_focus_mode(mode.as_deref()).await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationService for NotificationManager {
+    async fn notify(&self, notification: Notification) -> Result<()> {
+        NotificationManager::notify(self, notification).await
+    }
+    
+    async fn update(&self, notification: Notification) -> Result<()> {
+        NotificationManager::update(self, notification).await
+    }
+    
+    async fn dismiss(&self, id: Uuid) -> Result<()> {
+        NotificationManager::dismiss(self, id).await
+    }
+    
+    async fn dismiss_all(&self) -> Result<()> {
+        NotificationManager::dismiss_all(self).await
+    }
+    
+    async fn snooze_until(&self, id: Uuid, until: DateTime<Utc>) -> Result<()> {
+        NotificationManager::snooze_until(self, id, until).await
+    }
+    
+    async fn get_active(&self) -> Vec<Notification> {
+        NotificationManager::get_active(self).await
+    }
+    
+    async fn get_notification(&self, id: Uuid) -> Option<Notification> {
+        NotificationManager::get_notification(self, id).await
+    }
+    
+    fn subscribe(&self) -> broadcast::Receiver<NotificationEvent> {
+        NotificationManager::subscribe(self)
+    }
 }
 
 /// Internal notification manager for spawned tasks
@@ -228,6 +434,8 @@ struct NotificationManagerInternal {
     channels: Arc<RwLock<HashMap<String, NotificationChannel>>>,
     filters: Arc<RwLock<Vec<NotificationFilter>>>,
     groups: Arc<RwLock<HashMap<String, Vec<Uuid>>>>,
+    digest_summarizer: Arc<RwLock<Option<Arc<dyn DigestSummarizer>>>>,
+    reminders: Arc<ReminderStore>,
 }
 
 impl NotificationManagerInternal {
@@ -249,6 +457,20 @@ impl NotificationManagerInternal {
             NotificationCommand::ClearGroup(group) => {
                 self.clear_notification_group(group).await?;
             }
+            NotificationCommand::Snooze(id, until) => {
+                self.snooze_notification(id, until).await?;
+            }
+            NotificationCommand::Resurface(id) => {
+                if let Some(notification) = self.history.take_snoozed(id).await? {
+                    self.resurface(notification).await?;
+                }
+            }
+            NotificationCommand::CancelSnooze(id) => {
+                if self.history.take_snoozed(id).await?.is_some() {
+                    self.history.dismiss(id).await?;
+                    let _ = self.event_tx.send(NotificationEvent::Dismissed(id));
+                }
+            }
             NotificationCommand::ProcessQueue => {
                 self.process_notification_queue().await?;
             }
@@ -263,12 +485,16 @@ impl NotificationManagerInternal {
     async fn create_notification(&self, mut notification: Notification) -> Result<()> {
         let config = self.config.read().await;
         
-        // Check do not disturb mode
-        if config.do_not_disturb && notification.priority < NotificationPriority::Critical {
-            if !config.priority_settings.critical_bypass_dnd {
-                debug!("Notification blocked by do not disturb mode");
-                return Ok(());
-            }
+        // Held back by do not disturb or a focus mode, but kept in history
+        if config.dnd.silences(
+            &notification,
+            config.do_not_disturb,
+            config.priority_settings.critical_bypass_dnd,
+            Local::now(),
+        ) {
+            debug!("Notification silenced by do not disturb: {}", notification.title);
+            self.history.add(notification).await?;
+            return Ok(());
         }
         
         // Apply filters
@@ -372,16 +598,16 @@ impl NotificationManagerInternal {
         Ok(())
     }
     
-    /// Dismiss a notification
-    async fn dismiss_notification(&self, id: Uuid) -> Result<()> {
-        if let Some(mut notification) = self.active.write().await.remove(&id) {
-            notification.dismissed = true;
-            
+    /// Remove a notification from the visible stack
+    async fn withdraw_notification(&self, id: Uuid) -> Result<Option<Notification>> {
+        let notification = self.active.write().await.remove(&id);
+        if notification.is_some() {
             // Remove from groups
             let mut groups = self.groups.write().await;
             for (_, ids) in groups.iter_mut() {
                 ids.retain(|&nid| nid != id);
             }
+            drop(groups);
             
             // Dismiss in providers
             let providers = self.providers.read().await;
@@ -392,10 +618,18 @@ impl NotificationManagerInternal {
                     }
                 }
             }
+            drop(providers);
             
             // Remove render
             self.remove_render(id).await?;
-            
+        }
+        
+        Ok(notification)
+    }
+    
+    /// Dismiss a notification
+    async fn dismiss_notification(&self, id: Uuid) -> Result<()> {
+        if self.withdraw_notification(id).await?.is_some() {
             // Update history
             self.history.dismiss(id).await?;
             
@@ -411,6 +645,111 @@ impl NotificationManagerInternal {
         Ok(())
     }
     
+    /// Snooze a visible or queued notification, or reschedule a snoozed one
+    async fn snooze_notification(&self, id: Uuid, until: DateTime<Utc>) -> Result<()> {
+        let notification = match self.withdraw_notification(id).await? {
+            Some(notification) => Some(notification),
+            None => {
+                let mut queue = self.queue.write().await;
+                let index = queue.iter().position(|n| n.id == id);
+                index.and_then(|index| queue.remove(index))
+            }
+        };
+        
+        match notification {
+            Some(notification) => {
+                info!("Notification snoozed until {}: {}", until, notification.title);
+                self.history.snooze(notification, until).await?;
+            }
+            None => {
+                if !self.history.reschedule(id, until).await? {
+                    warn!("Cannot snooze unknown notification: {}", id);
+                    return Ok(());
+                }
+            }
+        }
+        
+        let _ = self.event_tx.send(NotificationEvent::Snoozed { notification_id: id, until });
+        
+        // Let waiting notifications take the freed slot
+        if !self.queue.read().await.is_empty() {
+            let _ = self.process_notification_queue().await;
+        }
+        
+        Ok(())
+    }
+    
+    /// Show snoozed notifications whose time has come
+    async fn resurface_due_notifications(&self) -> Result<()> {
+        for notification in self.history.take_due_snoozes(Utc::now()).await? {
+            self.resurface(notification).await?;
+        }
+        Ok(())
+    }
+    
+    /// Show reminders whose time has come
+    async fn fire_due_reminders(&self) -> Result<()> {
+        for reminder in self.reminders.take_due(Utc::now())? {
+            info!("Firing reminder on node {}: {}", reminder.node_id, reminder.message);
+            self.create_notification(reminder.notification()).await?;
+        }
+        Ok(())
+    }
+    
+    /// Collapse groups older than the digest policy allows into digest entries
+    async fn collapse_old_history(&self, now: DateTime<Utc>) -> Result<Vec<NotificationDigest>> {
+        let policy = self.config.read().await.history_settings.digest.clone();
+        if !policy.enabled {
+            return Ok(Vec::new());
+        }
+        
+        let summarizer = if policy.summarize {
+            self.digest_summarizer.read().await.clone()
+        } else {
+            None
+        };
+        let active: Vec<Uuid> = self.active.read().await.keys().cloned().collect();
+        
+        let mut digests = Vec::new();
+        for mut candidate in self.history.digest_candidates(&policy, now) {
+            // Notifications still on screen stay in full history
+            candidate.entries.retain(|e| !active.contains(&e.notification.id));
+            if candidate.entries.len() < policy.min_group_size.max(1) {
+                continue;
+            }
+            
+            let (summary, summarized_by) = match &summarizer {
+                Some(summarizer) => match summarizer.summarize(&candidate.group, &candidate.notifications()).await {
+                    Ok(summary) => (summary, Some(summarizer.name().to_string())),
+                    Err(e) => {
+                        warn!("Digest summarizer {} failed, using built-in summary: {}", summarizer.name(), e);
+                        (digest::extractive_summary(&candidate, policy.max_titles), None)
+                    }
+                },
+                None => (digest::extractive_summary(&candidate, policy.max_titles), None),
+            };
+            
+            let collapsed: Vec<Uuid> = candidate.entries.iter().map(|e| e.notification.id).collect();
+            let digest = candidate.into_digest(summary, summarized_by, policy.max_titles);
+            debug!("Collapsed {} notifications from group {} into a digest", digest.count, digest.group);
+            
+            self.history.apply_digest(digest.clone(), &collapsed).await?;
+            let _ = self.event_tx.send(NotificationEvent::DigestCreated(digest.clone()));
+            digests.push(digest);
+        }
+        
+        Ok(digests)
+    }
+    
+    /// Show a snoozed notification again with the snoozed marker
+    async fn resurface(&self, notification: Notification) -> Result<()> {
+        let id = notification.id;
+        info!("Resurfacing snoozed notification: {}", notification.title);
+        self.create_notification(snooze::mark_resurfaced(notification)).await?;
+        let _ = self.event_tx.send(NotificationEvent::Resurfaced(id));
+        Ok(())
+    }
+    
     /// Dismiss all notifications
     async fn dismiss_all_notifications(&self) -> Result<()> {
         let ids: Vec<Uuid> = self.active.read().await.keys().cloned().collect();
@@ -597,6 +936,22 @@ mod tests {
         assert_eq!(active[0].title, "Test");
     }
     
+    #[tokio::test]
+    async fn test_actions_run_outside_toasts_reach_subscribers() {
+        let manager = NotificationManager::new(NotificationConfig::default());
+        let mut events = manager.subscribe();
+        let id = Uuid::new_v4();
+        
+        manager.trigger_action(id, "reply");
+        manager.open(id);
+        
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            NotificationEvent::ActionTriggered { notification_id, action_id } if notification_id == id && action_id == "reply"
+        ));
+        assert!(matches!(events.recv().await.unwrap(), NotificationEvent::Clicked(clicked) if clicked == id));
+    }
+    
     #[tokio::test]
     async fn test_notification_dismissal() {
         let config = NotificationConfig::default();
@@ -618,4 +973,115 @@ mod tests {
         
         assert_eq!(manager.get_active().await.len(), 0);
     }
+    
+    #[tokio::test]
+    async fn test_notification_snooze_and_resurface() {
+        let config = NotificationConfig::default();
+        let manager = NotificationManager::new(config);
+        
+        let notification = Notification::new(
+            "Test".to_string(),
+            "Test notification".to_string()
+        );
+        let id = notification.id;
+        
+        manager.notify(notification).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        
+        manager.snooze(id, SnoozeDuration::OneHour).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        
+        assert!(manager.get_active().await.is_empty());
+        assert_eq!(manager.get_snoozed().len(), 1);
+        
+        manager.resurface_now(id).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        
+        let active = manager.get_active().await;
+        assert_eq!(active.len(), 1);
+        assert!(active[0].was_snoozed());
+        assert!(manager.get_snoozed().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_focus_mode_silences_into_history() {
+        let manager = NotificationManager::new(NotificationConfig::default());
+        let mut events = manager.subscribe();
+        
+        assert!(manager.set_focus_mode(Some("Nap")).await.is_err());
+        manager.handle_focus_action(&NodeAction::Custom {
+            action_type: horizonos_graph_nodes::FOCUS_MODE_ACTION.to_string(),
+            parameters: HashMap::from([(horizonos_graph_nodes::FOCUS_MODE_PARAMETER.to_string(), "deep work".to_string())]),
+        }).await.unwrap();
+        assert_eq!(manager.active_focus_mode().await.as_deref(), Some(crate::focus::DEEP_WORK));
+        assert!(matches!(events.try_recv().unwrap(), NotificationEvent::FocusModeChanged(Some(_))));
+        
+        let message = Notification::new("Chat".to_string(), "Lunch?".to_string())
+            .with_type(crate::NotificationType::Message);
+        let urgent = Notification::new("Chat".to_string(), "Server down".to_string())
+            .with_type(crate::NotificationType::Message)
+            .with_priority(NotificationPriority::Critical);
+        let urgent_id = urgent.id;
+        manager.notify(message).await.unwrap();
+        manager.notify(urgent).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        
+        let active = manager.get_active().await;
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].id, urgent_id);
+        assert_eq!(manager.history().get_recent(10).len(), 2);
+        
+        manager.set_focus_mode(None).await.unwrap();
+        assert!(manager.active_focus_mode().await.is_none());
+    }
+    
+    struct CountingSummarizer;
+    
+    #[async_trait]
+    impl DigestSummarizer for CountingSummarizer {
+        fn name(&self) -> &str {
+            "counting"
+        }
+        
+        async fn summarize(&self, group: &str, notifications: &[Notification]) -> Result<String> {
+            Ok(format!("{} updates in {}", notifications.len(), group))
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_collapse_history_into_digest() {
+        let mut config = NotificationConfig::default();
+        config.history_settings.digest.collapse_after_days = 0;
+        config.history_settings.digest.summarize = true;
+        let manager = NotificationManager::new(config);
+        manager.set_digest_summarizer(Arc::new(CountingSummarizer)).await;
+        let mut events = manager.subscribe();
+        
+        for title in ["New mail", "New mail", "Mail sync failed"] {
+            let mut notification = Notification::new(title.to_string(), "Inbox".to_string());
+            notification.group = Some("mail".to_string());
+            manager.notify(notification).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        
+        // Nothing is collapsed while the notifications are still shown
+        assert!(manager.collapse_history_now().await.unwrap().is_empty());
+        
+        manager.dismiss_all().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        
+        let digests = manager.collapse_history_now().await.unwrap();
+        assert_eq!(digests.len(), 1);
+        assert_eq!(digests[0].summary, "3 updates in mail");
+        assert_eq!(digests[0].summarized_by.as_deref(), Some("counting"));
+        assert!(manager.history().get_recent(10).is_empty());
+        
+        let mut digest_events = 0;
+        while let Ok(event) = events.try_recv() {
+            if matches!(event, NotificationEvent::DigestCreated(_)) {
+                digest_events += 1;
+            }
+        }
+        assert_eq!(digest_events, 1);
+    }
 }
\ No newline at end of file
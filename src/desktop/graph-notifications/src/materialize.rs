@@ -0,0 +1,220 @@
+//! Keeping notifications as graph nodes
+//!
+//! [`materialize_notification`] turns a notification into a persistent
+//! concept node placed next to the node of the application that sent it and
+//! connected to it with a `CreatedBy` edge. The node also links to the node
+//! the notification is about, if any. [`keep_as_node`] does the same for an
+//! active notification and dismisses its toast, and [`materialize_history`]
+//! for notifications found in the history.
+
+use crate::history::HistoryQuery;
+use crate::{Notification, NotificationHistory, NotificationService};
+use anyhow::{anyhow, Result};
+use horizonos_graph_engine::{EdgeType, NodeMetadata, Position, Scene, SceneEdge, SceneId, Vec3};
+use horizonos_graph_nodes::{ConceptNode, GraphNode, NodeManager};
+use uuid::Uuid;
+
+/// Tag of every node made from a notification
+pub const NOTIFICATION_TAG: &str = "notification";
+
+/// Node property holding the ID of the notification a node was made from
+const NOTIFICATION_ID_PROPERTY: &str = "notification.id";
+
+/// Nodes and edges a materialized notification added to the graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaterializedNotification {
+    pub node_id: SceneId,
+    /// Node of the sending application, if it is in the graph
+    pub source_node: Option<SceneId>,
+    pub edges: Vec<SceneId>,
+}
+
+/// Add a notification to the graph as a node connected to its source application
+pub fn materialize_notification(
+    notification: &Notification,
+    nodes: &mut NodeManager,
+    scene: &mut Scene,
+) -> Result<MaterializedNotification> {
+    let source_node = source_node(notification, nodes, scene);
+    let related_node = notification
+        .node_id
+        .filter(|&id| Some(id) != source_node && scene.get_node(id).is_some());
+
+    let placeholder = ConceptNode::new(0)
+        .with_content(notification.title.clone(), notification.body.clone())
+        .with_metadata(notification_metadata(notification));
+    let mut scene_node = placeholder.to_scene_node();
+    scene_node.radius = 0.6;
+    scene_node.position = source_node
+        .or(related_node)
+        .and_then(|id| scene.get_node(id))
+        .map(|anchor| anchor.position + Vec3::new(anchor.radius + 1.5, -1.0, 0.0))
+        .unwrap_or_else(|| Position::new(0.0, 0.0, 0.0));
+    let node_id = scene.add_node(scene_node);
+
+    nodes
+        .create_concept(node_id, notification.title.clone(), notification.body.clone(), notification_metadata(notification))
+        .map_err(|e| anyhow!("Failed to create node for notification {}: {}", notification.id, e))?;
+
+    let mut edges = Vec::new();
+    if let Some(app_node) = source_node {
+        edges.push(scene.add_edge(edge(node_id, app_node, EdgeType::CreatedBy)));
+    }
+    if let Some(related_node) = related_node {
+        edges.push(scene.add_edge(edge(node_id, related_node, EdgeType::RelatedTo { similarity: 1.0 })));
+    }
+
+    log::info!("Kept notification \"{}\" from {} as node {}", notification.title, notification.source.name, node_id);
+    Ok(MaterializedNotification { node_id, source_node, edges })
+}
+
+/// Keep an active notification as a node and dismiss its toast
+pub async fn keep_as_node(
+    service: &dyn NotificationService,
+    id: Uuid,
+    nodes: &mut NodeManager,
+    scene: &mut Scene,
+) -> Result<MaterializedNotification> {
+    let notification = service
+        .get_notification(id)
+        .await
+        .ok_or_else(|| anyhow!("Notification {} not found", id))?;
+    let materialized = materialize_notification(&notification, nodes, scene)?;
+    service.dismiss(id).await?;
+    Ok(materialized)
+}
+
+/// Add historical notifications matching a query to the graph
+///
+/// Notifications that already have a node are skipped, so the same query
+/// can be materialized again after more notifications arrived.
+pub fn materialize_history(
+    history: &NotificationHistory,
+    query: &HistoryQuery,
+    nodes: &mut NodeManager,
+    scene: &mut Scene,
+) -> Result<Vec<MaterializedNotification>> {
+    let mut materialized = Vec::new();
+    for entry in history.query(query)?.into_iter().rev() {
+        if existing_node(&entry.notification, scene).is_none() {
+            materialized.push(materialize_notification(&entry.notification, nodes, scene)?);
+        }
+    }
+    Ok(materialized)
+}
+
+/// Node a notification was already materialized as
+fn existing_node(notification: &Notification, scene: &Scene) -> Option<SceneId> {
+    let id = notification.id.to_string();
+    scene
+        .nodes()
+        .find(|(_, node)| node.metadata.properties.get(NOTIFICATION_ID_PROPERTY) == Some(&id))
+        .map(|(node_id, _)| *node_id)
+}
+
+/// Node of the application that sent a notification
+fn source_node(notification: &Notification, nodes: &NodeManager, scene: &Scene) -> Option<SceneId> {
+    let source = &notification.source;
+    let in_scene = |id: &SceneId| scene.get_node(*id).is_some();
+    nodes
+        .find_application(&source.name, source.pid)
+        .filter(in_scene)
+        .or_else(|| {
+            source
+                .app_id
+                .as_deref()
+                .and_then(|app_id| nodes.find_application(app_id, None))
+                .filter(in_scene)
+        })
+}
+
+fn notification_metadata(notification: &Notification) -> NodeMetadata {
+    let mut metadata = NodeMetadata {
+        created_at: notification.timestamp,
+        updated_at: notification.timestamp,
+        description: Some(notification.body.clone()).filter(|body| !body.is_empty()),
+        ..NodeMetadata::default()
+    };
+    metadata.tags.push(NOTIFICATION_TAG.to_string());
+    metadata.tags.push(format!("{:?}", notification.notification_type).to_lowercase());
+    metadata.tags.extend(notification.tags.iter().cloned());
+    metadata.properties.insert(NOTIFICATION_ID_PROPERTY.to_string(), notification.id.to_string());
+    metadata.properties.insert("notification.source".to_string(), notification.source.name.clone());
+    metadata.properties.insert("notification.priority".to_string(), format!("{:?}", notification.priority).to_lowercase());
+    if let Some(app_id) = &notification.source.app_id {
+        metadata.properties.insert("notification.app_id".to_string(), app_id.clone());
+    }
+    metadata
+}
+
+fn edge(source: SceneId, target: SceneId, edge_type: EdgeType) -> SceneEdge {
+    SceneEdge {
+        id: 0,
+        source,
+        target,
+        edge_type,
+        weight: 1.0,
+        color: [0.8, 0.6, 0.2, 0.8],
+        visible: true,
+        animated: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockNotificationService;
+    use crate::NotificationSource;
+    use horizonos_graph_engine::{NodeType, SceneNode};
+
+    #[tokio::test]
+    async fn test_notification_becomes_node_next_to_its_app() {
+        let mut scene = Scene::new();
+        let mut nodes = NodeManager::new();
+        let app = ConceptNode::new(0).to_scene_node();
+        let app_node = scene.add_node(SceneNode {
+            node_type: NodeType::Application { pid: 0, name: "Mail".to_string() },
+            ..app
+        });
+        nodes
+            .add_node(Box::new(horizonos_graph_nodes::ApplicationNode::new(app_node, "Mail".to_string(), "/usr/bin/mail".to_string())))
+            .unwrap();
+
+        let service = MockNotificationService::new();
+        let notification = Notification::new("Invoice due".to_string(), "Pay by Friday".to_string()).with_source(NotificationSource {
+            name: "Other".to_string(),
+            app_id: Some("mail".to_string()),
+            pid: None,
+            icon: None,
+        });
+        let id = notification.id;
+        service.notify(notification).await.unwrap();
+
+        let kept = keep_as_node(&service, id, &mut nodes, &mut scene).await.unwrap();
+        assert_eq!(kept.source_node, Some(app_node));
+        let edge = scene.get_edge(kept.edges[0]).unwrap();
+        assert_eq!((edge.source, edge.target), (kept.node_id, app_node));
+        let node = scene.get_node(kept.node_id).unwrap();
+        assert!(matches!(&node.node_type, NodeType::Concept { title, .. } if title == "Invoice due"));
+        assert!(node.metadata.tags.iter().any(|tag| tag == NOTIFICATION_TAG));
+        assert!(service.get_active().await.is_empty());
+        assert!(keep_as_node(&service, id, &mut nodes, &mut scene).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_history_materializes_once() {
+        let mut scene = Scene::new();
+        let mut nodes = NodeManager::new();
+        let history = NotificationHistory::new();
+        history.add(Notification::new("Disk almost full".to_string(), "5% left".to_string())).await.unwrap();
+        history.add(Notification::new("Update ready".to_string(), String::new())).await.unwrap();
+
+        let query = HistoryQuery::new().matching("disk");
+        let kept = materialize_history(&history, &query, &mut nodes, &mut scene).unwrap();
+        assert_eq!(kept.len(), 1);
+        assert!(kept[0].source_node.is_none());
+        assert!(materialize_history(&history, &query, &mut nodes, &mut scene).unwrap().is_empty());
+        assert_eq!(materialize_history(&history, &HistoryQuery::new(), &mut nodes, &mut scene).unwrap().len(), 1);
+        assert_eq!(scene.nodes().count(), 2);
+    }
+}
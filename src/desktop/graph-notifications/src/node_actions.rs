@@ -0,0 +1,97 @@
+//! Toasts for node actions
+//!
+//! Actions run on a node, e.g. from its context menu, report what came of
+//! them through [`node_action_notification`]: successes become short-lived
+//! toasts, while failures and confirmation prompts stay until dismissed.
+
+use crate::{Notification, NotificationPriority, NotificationSource, NotificationType};
+use horizonos_graph_nodes::{NodeActionResult, NodeActionType};
+use std::time::Duration;
+
+/// Group all node action notifications share
+pub const NODE_ACTIONS_GROUP: &str = "node-actions";
+
+/// How long success toasts stay up
+const SUCCESS_TOAST_DURATION: Duration = Duration::from_secs(4);
+
+/// Notification reporting the result of an action on a node
+pub fn node_action_notification(
+    node_id: u64,
+    node_name: &str,
+    action: &NodeActionType,
+    result: &Result<NodeActionResult, String>,
+) -> Notification {
+    let title = format!("{}: {}", action.label(), node_name);
+    let (notification_type, body) = match result {
+        Ok(NodeActionResult::Success { message }) => (NotificationType::Success, message.clone().unwrap_or_default()),
+        Ok(NodeActionResult::NodeSpawned { node_id }) => (NotificationType::Success, format!("Created node {}", node_id)),
+        Ok(NodeActionResult::RelationshipChanged { target_id, edge_type, added }) => {
+            let body = if *added {
+                format!("Added {:?} edge to node {}", edge_type, target_id)
+            } else {
+                format!("Removed {:?} edge to node {}", edge_type, target_id)
+            };
+            (NotificationType::Success, body)
+        }
+        Ok(NodeActionResult::ConfirmationRequired { prompt }) => (NotificationType::Alert, prompt.clone()),
+        Ok(NodeActionResult::Error { error }) | Err(error) => (NotificationType::Error, error.clone()),
+    };
+
+    let mut notification = Notification::new(title, body)
+        .with_type(notification_type)
+        .with_node(node_id)
+        .with_source(NotificationSource {
+            name: "Graph nodes".to_string(),
+            app_id: None,
+            pid: None,
+            icon: None,
+        });
+    notification = match notification_type {
+        NotificationType::Success => notification
+            .with_priority(NotificationPriority::Low)
+            .expires_in(SUCCESS_TOAST_DURATION),
+        NotificationType::Error => notification.with_priority(NotificationPriority::High),
+        _ => notification,
+    };
+    if let Ok(NodeActionResult::NodeSpawned { node_id }) = result {
+        notification.metadata.insert("spawned_node_id".to_string(), node_id.to_string());
+    }
+    notification.group = Some(NODE_ACTIONS_GROUP.to_string());
+    notification.tags = vec!["node-action".to_string()];
+    notification.metadata.insert("action".to_string(), format!("{:?}", action));
+    notification
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_action_results_become_toasts() {
+        let done = node_action_notification(
+            7,
+            "report.pdf",
+            &NodeActionType::Open,
+            &Ok(NodeActionResult::Success { message: Some("Opened in viewer".to_string()) }),
+        );
+        assert_eq!(done.notification_type, NotificationType::Success);
+        assert_eq!(done.body, "Opened in viewer");
+        assert!(done.title.ends_with("report.pdf"));
+        assert_eq!(done.node_id, Some(7));
+        assert!(done.expires_at.is_some());
+        assert_eq!(done.group.as_deref(), Some(NODE_ACTIONS_GROUP));
+
+        let failed = node_action_notification(
+            7,
+            "report.pdf",
+            &NodeActionType::Custom("compress".to_string()),
+            &Err("Node 7 not found".to_string()),
+        );
+        assert_eq!(failed.notification_type, NotificationType::Error);
+        assert_eq!(failed.priority, NotificationPriority::High);
+        assert!(failed.expires_at.is_none());
+
+        let spawned = node_action_notification(7, "report.pdf", &NodeActionType::Copy, &Ok(NodeActionResult::NodeSpawned { node_id: 9 }));
+        assert_eq!(spawned.metadata["spawned_node_id"], "9");
+    }
+}
@@ -0,0 +1,234 @@
+//! Notifications for user-visible problems
+//!
+//! [`ProblemNotifier`] watches a [`ProblemsPanel`] and shows a notification
+//! for every user-visible problem at or above a severity, updating it when
+//! the problem repeats and dismissing it when the problem is resolved. The
+//! problem's recovery actions become notification buttons; pressing one is
+//! passed back to the panel as a recovery request for the subsystem that
+//! reported the problem.
+
+use crate::actions::{ActionType, NotificationAction};
+use crate::{Notification, NotificationEvent, NotificationPriority, NotificationService, NotificationSource, NotificationType};
+use horizonos_graph_errors::{Category, Problem, ProblemEvent, ProblemsPanel, RecoveryKind, Severity};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+/// Prefix of notification action ids that map to recovery actions
+const RECOVERY_PREFIX: &str = "recovery:";
+
+/// Group all problem notifications share
+pub const PROBLEMS_GROUP: &str = "problems";
+
+/// Notification describing a problem
+pub fn problem_notification(problem: &Problem) -> Notification {
+    let notification_type = match problem.category {
+        Category::Network | Category::Sync => NotificationType::Network,
+        Category::Permission => NotificationType::Security,
+        _ if problem.severity >= Severity::Error => NotificationType::Error,
+        _ => NotificationType::Alert,
+    };
+    let priority = match problem.severity {
+        Severity::Info => NotificationPriority::Low,
+        Severity::Warning => NotificationPriority::Normal,
+        Severity::Error => NotificationPriority::High,
+        Severity::Critical => NotificationPriority::Critical,
+    };
+    let title = if problem.occurrences > 1 {
+        format!("{} ({}×)", problem.message, problem.occurrences)
+    } else {
+        problem.message.clone()
+    };
+
+    let mut notification = Notification::new(title, problem.detail.clone().unwrap_or_default())
+        .with_type(notification_type)
+        .with_priority(priority)
+        .with_source(NotificationSource {
+            name: problem.subsystem.clone(),
+            app_id: None,
+            pid: None,
+            icon: None,
+        });
+    for action in &problem.recovery {
+        notification = notification.add_action(recovery_action(problem, &action.id, &action.label, &action.kind));
+    }
+    if let Some(node_id) = problem.node_id {
+        notification = notification.with_node(node_id);
+    }
+    if problem.severity == Severity::Critical {
+        notification = notification.persistent();
+    }
+    notification.group = Some(PROBLEMS_GROUP.to_string());
+    notification.tags = vec!["problem".to_string(), problem.category.to_string().to_lowercase()];
+    notification.metadata.insert("problem_id".to_string(), problem.id.to_string());
+    notification.metadata.insert("problem_code".to_string(), problem.code.clone());
+    notification
+}
+
+fn recovery_action(problem: &Problem, id: &str, label: &str, kind: &RecoveryKind) -> NotificationAction {
+    let action_type = match kind {
+        RecoveryKind::OpenFile { path } => ActionType::OpenFile { path: path.clone() },
+        RecoveryKind::ShowNode { node_id } => ActionType::NavigateToNode { node_id: *node_id },
+        RecoveryKind::Retry => ActionType::Custom { handler: "problem.retry".to_string() },
+        RecoveryKind::OpenSettings { .. } => ActionType::Custom { handler: "settings.open".to_string() },
+        RecoveryKind::Custom { handler } => ActionType::Custom { handler: handler.clone() },
+    };
+    let mut parameters = HashMap::from([("problem_id".to_string(), problem.id.to_string())]);
+    if let RecoveryKind::OpenSettings { section } = kind {
+        parameters.insert("section".to_string(), section.clone());
+    }
+    NotificationAction {
+        id: format!("{}{}", RECOVERY_PREFIX, id),
+        label: label.to_string(),
+        icon: None,
+        action_type,
+        parameters,
+        destructive: false,
+        primary: matches!(kind, RecoveryKind::Retry),
+    }
+}
+
+/// Shows user-visible problems as notifications
+pub struct ProblemNotifier {
+    panel: Arc<ProblemsPanel>,
+    service: Arc<dyn NotificationService>,
+    /// Least severe problem that is notified
+    min_severity: Severity,
+    /// Notification shown for each problem
+    shown: Mutex<HashMap<Uuid, Uuid>>,
+}
+
+impl ProblemNotifier {
+    /// Notify warnings and worse
+    pub fn new(panel: Arc<ProblemsPanel>, service: Arc<dyn NotificationService>) -> Self {
+        Self {
+            panel,
+            service,
+            min_severity: Severity::Warning,
+            shown: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set the least severe problem that is notified
+    pub fn with_min_severity(mut self, severity: Severity) -> Self {
+        self.min_severity = severity;
+        self
+    }
+
+    /// Forward panel changes and recovery button presses until either side closes
+    pub async fn run(self: Arc<Self>) {
+        let mut problems = self.panel.subscribe();
+        let mut notifications = self.service.subscribe();
+        loop {
+            tokio::select! {
+                event = problems.recv() => match event {
+                    Ok(event) => self.handle_problem_event(event).await,
+                    Err(RecvError::Lagged(missed)) => log::warn!("Problem notifier missed {} problem events", missed),
+                    Err(RecvError::Closed) => return,
+                },
+                event = notifications.recv() => match event {
+                    Ok(NotificationEvent::ActionTriggered { notification_id, action_id }) => {
+                        self.handle_action(notification_id, &action_id);
+                    }
+                    Ok(NotificationEvent::Dismissed(notification_id)) => {
+                        // The problem stays in the panel; a repeat notifies again
+                        self.shown.lock().unwrap().retain(|_, shown| *shown != notification_id);
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => return,
+                },
+            }
+        }
+    }
+
+    /// Show, update or dismiss the notification for a problem
+    pub async fn handle_problem_event(&self, event: ProblemEvent) {
+        match event {
+            ProblemEvent::Reported(problem) | ProblemEvent::Repeated(problem) => {
+                if !problem.user_visible || problem.severity < self.min_severity {
+                    return;
+                }
+                let mut notification = problem_notification(&problem);
+                let existing = self.shown.lock().unwrap().get(&problem.id).copied();
+                let result = match existing {
+                    Some(id) => {
+                        notification.id = id;
+                        self.service.update(notification).await
+                    }
+                    None => {
+                        self.shown.lock().unwrap().insert(problem.id, notification.id);
+                        self.service.notify(notification).await
+                    }
+                };
+                if let Err(e) = result {
+                    log::warn!("Could not show notification for problem {}: {}", problem.code, e);
+                }
+            }
+            ProblemEvent::Resolved(problem) => {
+                let shown = self.shown.lock().unwrap().remove(&problem.id);
+                if let Some(id) = shown {
+                    if let Err(e) = self.service.dismiss(id).await {
+                        log::warn!("Could not dismiss notification for problem {}: {}", problem.code, e);
+                    }
+                }
+            }
+            ProblemEvent::RecoveryRequested { .. } => {}
+        }
+    }
+
+    /// Turn a recovery button press into a recovery request; false for other actions
+    pub fn handle_action(&self, notification_id: Uuid, action_id: &str) -> bool {
+        let Some(recovery_id) = action_id.strip_prefix(RECOVERY_PREFIX) else {
+            return false;
+        };
+        let problem_id = self
+            .shown
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(_, shown)| **shown == notification_id)
+            .map(|(problem, _)| *problem);
+        problem_id.is_some_and(|problem_id| self.panel.request_recovery(problem_id, recovery_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockNotificationService;
+    use horizonos_graph_errors::RecoveryAction;
+
+    #[tokio::test]
+    async fn test_problems_become_notifications() {
+        let panel = Arc::new(ProblemsPanel::new());
+        let service = Arc::new(MockNotificationService::new());
+        let notifier = ProblemNotifier::new(panel.clone(), service.clone());
+        let mut events = panel.subscribe();
+
+        let problem = Problem::new("automation.execution_failed", Category::Automation, Severity::Error, "Automation backup failed")
+            .in_subsystem("automation")
+            .with_recovery(RecoveryAction::retry());
+        panel.report(problem.clone());
+        panel.report(problem);
+        panel.report(Problem::new("ai.suggestions", Category::Ai, Severity::Info, "Could not produce suggestions"));
+        for _ in 0..3 {
+            notifier.handle_problem_event(events.recv().await.unwrap()).await;
+        }
+
+        let active = service.get_active().await;
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].title, "Automation backup failed (2×)");
+        assert_eq!(active[0].priority, NotificationPriority::High);
+        assert_eq!(active[0].actions[0].id, "recovery:retry");
+
+        assert!(notifier.handle_action(active[0].id, "recovery:retry"));
+        assert!(matches!(events.recv().await.unwrap(), ProblemEvent::RecoveryRequested { .. }));
+        assert!(!notifier.handle_action(active[0].id, "dismiss"));
+
+        let problem_id = panel.problems()[0].id;
+        panel.resolve(problem_id);
+        notifier.handle_problem_event(events.recv().await.unwrap()).await;
+        assert!(service.get_active().await.is_empty());
+    }
+}
@@ -0,0 +1,573 @@
+//! Reminders anchored to graph nodes
+//!
+//! A reminder is set on a node with a natural language request such as
+//! "remind me about this file tomorrow at 9" or "every monday at 10". When
+//! it is due it fires as a notification carrying the node, with an action
+//! jumping to it. Pending reminders are persisted so they survive restarts.
+//! Only task nodes take recurring reminders; they repeat until the task is
+//! finished.
+
+use crate::actions::NotificationAction;
+use crate::history::write_atomic;
+use crate::i18n::I18N;
+use crate::snooze::MORNING_HOUR;
+use crate::{Notification, NotificationPriority, NotificationSource, NotificationType};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveTime, TimeZone, Utc, Weekday,
+};
+use horizonos_graph_engine::{NodeType, SceneId, SceneNode, TaskStatus};
+use horizonos_graph_i18n::tr;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use uuid::Uuid;
+
+/// Tag added to reminder notifications
+pub const REMINDER_TAG: &str = "reminder";
+
+/// Metadata key holding the id of the reminder a notification came from
+pub const REMINDER_ID_KEY: &str = "reminder_id";
+
+/// Hour used for "tonight" and "evening"
+const EVENING_HOUR: u32 = 20;
+
+/// Hour used for "afternoon"
+const AFTERNOON_HOUR: u32 = 15;
+
+/// Words in front of the time that are not part of the reminder message
+const FILLER_WORDS: &[&str] = &["remind", "me", "to", "about", "of"];
+
+/// How a reminder repeats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Recurrence {
+    /// A fixed interval after each firing
+    Interval { seconds: u64 },
+    /// Every day at a time
+    Daily { time: NaiveTime },
+    /// Monday to Friday at a time
+    Weekdays { time: NaiveTime },
+    /// One day a week at a time
+    Weekly { weekday: Weekday, time: NaiveTime },
+}
+
+impl Recurrence {
+    /// First occurrence strictly after `after`
+    pub fn next_after(&self, after: DateTime<Local>) -> DateTime<Local> {
+        let time = match *self {
+            Recurrence::Interval { seconds } => {
+                return after + ChronoDuration::seconds(seconds.max(60) as i64);
+            }
+            Recurrence::Daily { time } | Recurrence::Weekdays { time } | Recurrence::Weekly { time, .. } => time,
+        };
+
+        let mut date = after.date_naive();
+        loop {
+            if self.occurs_on(date.weekday()) {
+                let at = local_at(date, time);
+                if at > after {
+                    return at;
+                }
+            }
+            date += ChronoDuration::days(1);
+        }
+    }
+
+    fn occurs_on(&self, day: Weekday) -> bool {
+        match *self {
+            Recurrence::Interval { .. } | Recurrence::Daily { .. } => true,
+            Recurrence::Weekdays { .. } => !matches!(day, Weekday::Sat | Weekday::Sun),
+            Recurrence::Weekly { weekday, .. } => day == weekday,
+        }
+    }
+}
+
+/// When a reminder fires, as parsed from a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReminderRequest {
+    /// What to be reminded of, e.g. "this file"; empty when the request
+    /// named only a time
+    pub message: String,
+    pub due: DateTime<Utc>,
+    pub recurrence: Option<Recurrence>,
+}
+
+impl ReminderRequest {
+    /// Parse a request relative to the current time
+    pub fn parse(text: &str) -> Result<Self> {
+        Self::parse_from(text, Local::now())
+    }
+
+    /// Parse a request relative to `now`
+    ///
+    /// The time is the longest phrase at the end of the request that reads
+    /// as one; everything in front of it, minus "remind me about", is the
+    /// message. Understood times:
+    /// - "in 20 minutes", "in an hour", "in 3 days", "in 2 weeks"
+    /// - "today", "tonight", "tomorrow", "monday", "next friday", "next week",
+    ///   each optionally followed by a time or a part of the day
+    /// - "at 9", "at 9:30pm", "at noon", "14:00"; hours from 1 to 7 without
+    ///   am or pm are taken as afternoon, and a time that has passed today
+    ///   means tomorrow
+    /// - "every day", "every weekday", "every monday", each optionally "at"
+    ///   a time, and "every 2 hours"
+    pub fn parse_from(text: &str, now: DateTime<Local>) -> Result<Self> {
+        let words: Vec<(&str, String)> = text
+            .split_whitespace()
+            .map(|word| (word, word.trim_matches(|c: char| ",.!?;".contains(c)).to_lowercase()))
+            .filter(|(_, token)| !token.is_empty())
+            .collect();
+        let tokens: Vec<&str> = words.iter().map(|(_, token)| token.as_str()).collect();
+
+        for start in 0..tokens.len() {
+            if let Some((due, recurrence)) = parse_when(&tokens[start..], now) {
+                let skip = tokens[..start].iter().take_while(|token| FILLER_WORDS.contains(token)).count();
+                let message: Vec<&str> = words[skip..start].iter().map(|(word, _)| *word).collect();
+                return Ok(Self {
+                    message: message.join(" "),
+                    due: due.with_timezone(&Utc),
+                    recurrence,
+                });
+            }
+        }
+        Err(anyhow!("No time found in reminder \"{}\"", text))
+    }
+}
+
+/// Parse a whole token slice as a time phrase
+fn parse_when(tokens: &[&str], now: DateTime<Local>) -> Option<(DateTime<Local>, Option<Recurrence>)> {
+    match tokens {
+        [] => None,
+        ["in", rest @ ..] => parse_offset(rest).map(|offset| (now + offset, None)),
+        ["every", rest @ ..] => {
+            let recurrence = parse_recurrence(rest)?;
+            Some((recurrence.next_after(now), Some(recurrence)))
+        }
+        ["next", "week"] => {
+            let days = 7 - now.weekday().num_days_from_monday() as i64;
+            Some((local_at(now.date_naive() + ChronoDuration::days(days), morning()), None))
+        }
+        _ => {
+            let today = now.date_naive();
+            let (day, rest) = parse_day(tokens, today);
+            let time = match rest {
+                [] => None,
+                ["at", rest @ ..] | rest => Some(parse_time(rest)?),
+            };
+            let at = match (day, time) {
+                (Some((date, default_time)), time) => local_at(date, time.unwrap_or(default_time)),
+                (None, Some(time)) => {
+                    let at = local_at(today, time);
+                    if at > now { at } else { local_at(today + ChronoDuration::days(1), time) }
+                }
+                (None, None) => return None,
+            };
+            Some((at, None))
+        }
+    }
+}
+
+/// Parse "20 minutes", "an hour", "3 days"
+fn parse_offset(tokens: &[&str]) -> Option<ChronoDuration> {
+    let (count, unit) = match tokens {
+        [count, unit] => (parse_count(count)?, *unit),
+        [compact] => {
+            let split = compact.find(|c: char| !c.is_ascii_digit())?;
+            (compact[..split].parse().ok()?, &compact[split..])
+        }
+        _ => return None,
+    };
+    unit_duration(unit).map(|unit| unit * count as i32)
+}
+
+fn parse_count(token: &str) -> Option<u32> {
+    match token {
+        "a" | "an" | "one" => Some(1),
+        "two" => Some(2),
+        "three" => Some(3),
+        "four" => Some(4),
+        "five" => Some(5),
+        "ten" => Some(10),
+        "fifteen" => Some(15),
+        "thirty" => Some(30),
+        _ => token.parse().ok().filter(|&count| count > 0),
+    }
+}
+
+fn unit_duration(unit: &str) -> Option<ChronoDuration> {
+    match unit {
+        "m" | "min" | "mins" | "minute" | "minutes" => Some(ChronoDuration::minutes(1)),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Some(ChronoDuration::hours(1)),
+        "d" | "day" | "days" => Some(ChronoDuration::days(1)),
+        "w" | "week" | "weeks" => Some(ChronoDuration::weeks(1)),
+        _ => None,
+    }
+}
+
+/// Parse what follows "every"
+fn parse_recurrence(tokens: &[&str]) -> Option<Recurrence> {
+    let (day, rest) = match tokens.split_first()? {
+        (&"day", rest) => (None, rest),
+        (&"weekday", rest) => (Some(None), rest),
+        (word, rest) => match parse_weekday(word) {
+            Some(weekday) => (Some(Some(weekday)), rest),
+            None => {
+                let offset = parse_offset(tokens).or_else(|| match tokens {
+                    [unit] => unit_duration(unit),
+                    _ => None,
+                })?;
+                return Some(Recurrence::Interval { seconds: offset.num_seconds() as u64 });
+            }
+        },
+    };
+    let time = match rest {
+        [] => morning(),
+        ["at", rest @ ..] | rest => parse_time(rest)?,
+    };
+    Some(match day {
+        None => Recurrence::Daily { time },
+        Some(None) => Recurrence::Weekdays { time },
+        Some(Some(weekday)) => Recurrence::Weekly { weekday, time },
+    })
+}
+
+/// Parse a leading day, returning it with the time used when none is given,
+/// and the remaining tokens
+fn parse_day<'a, 'b>(tokens: &'a [&'b str], today: NaiveDate) -> (Option<(NaiveDate, NaiveTime)>, &'a [&'b str]) {
+    let (days, time, rest) = match tokens {
+        ["today", rest @ ..] => (0, morning(), rest),
+        ["tonight", rest @ ..] => (0, evening(), rest),
+        ["tomorrow", rest @ ..] => (1, morning(), rest),
+        ["next" | "on", day, rest @ ..] | [day, rest @ ..] => match parse_weekday(day) {
+            Some(weekday) => {
+                let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+                (if ahead == 0 { 7 } else { ahead as i64 }, morning(), rest)
+            }
+            None => return (None, tokens),
+        },
+        [] => return (None, tokens),
+    };
+    (Some((today + ChronoDuration::days(days), time)), rest)
+}
+
+fn parse_weekday(token: &str) -> Option<Weekday> {
+    let token = token.strip_suffix('s').unwrap_or(token);
+    match token {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "tuesday" | "tue" | "tues" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday" | "thu" | "thurs" => Some(Weekday::Thu),
+        "friday" | "fri" => Some(Weekday::Fri),
+        "saturday" | "sat" => Some(Weekday::Sat),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Parse "9", "9am", "9 pm", "9:30", "21:00", "noon", "midnight" and parts of the day
+fn parse_time(tokens: &[&str]) -> Option<NaiveTime> {
+    let (clock, meridiem) = match tokens {
+        ["noon" | "midday"] => return NaiveTime::from_hms_opt(12, 0, 0),
+        ["midnight"] => return NaiveTime::from_hms_opt(0, 0, 0),
+        ["morning"] | ["in", "the", "morning"] => return Some(morning()),
+        ["afternoon"] | ["in", "the", "afternoon"] => return NaiveTime::from_hms_opt(AFTERNOON_HOUR, 0, 0),
+        ["evening" | "night"] | ["in", "the", "evening"] => return Some(evening()),
+        [clock, meridiem @ ("am" | "pm")] => (*clock, Some(*meridiem)),
+        [clock] => match clock.strip_suffix("am").or_else(|| clock.strip_suffix("pm")) {
+            Some(stripped) => (stripped, Some(&clock[stripped.len()..])),
+            None => (*clock, None),
+        },
+        _ => return None,
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+        Some(_) => return None,
+        None => (clock.parse::<u32>().ok()?, 0),
+    };
+    let hour = match (meridiem, hour) {
+        (Some(_), 0) | (Some(_), 13..) => return None,
+        (Some("am"), 12) => 0,
+        (Some("pm"), 1..=11) => hour + 12,
+        (Some(_), _) => hour,
+        // Nobody sets a reminder for five in the morning by saying "at 5"
+        (None, 1..=7) if !clock.starts_with('0') => hour + 12,
+        (None, _) => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+fn morning() -> NaiveTime {
+    NaiveTime::from_hms_opt(MORNING_HOUR, 0, 0).unwrap()
+}
+
+fn evening() -> NaiveTime {
+    NaiveTime::from_hms_opt(EVENING_HOUR, 0, 0).unwrap()
+}
+
+/// A local date and time, moved past a DST gap if it falls into one
+fn local_at(date: NaiveDate, time: NaiveTime) -> DateTime<Local> {
+    let naive = date.and_time(time);
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| Local.from_local_datetime(&(naive + ChronoDuration::hours(1))).earliest())
+        .unwrap_or_else(|| Local.from_utc_datetime(&naive))
+}
+
+/// A pending reminder on a node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: Uuid,
+    pub node_id: SceneId,
+    /// Name of the node when the reminder was set, shown in the notification
+    pub node_label: String,
+    pub message: String,
+    pub due: DateTime<Utc>,
+    pub recurrence: Option<Recurrence>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Reminder {
+    /// Reminder on a node from a natural language request
+    ///
+    /// Recurring requests are refused for nodes other than tasks.
+    pub fn for_node(node: &SceneNode, node_label: impl Into<String>, request: &str) -> Result<Self> {
+        Self::from_request(node, node_label, ReminderRequest::parse(request)?)
+    }
+
+    /// Reminder on a node from an already parsed request
+    pub fn from_request(node: &SceneNode, node_label: impl Into<String>, request: ReminderRequest) -> Result<Self> {
+        if request.recurrence.is_some() && !matches!(node.node_type, NodeType::Task { .. }) {
+            bail!("Recurring reminders can only be set on task nodes");
+        }
+        Ok(Self {
+            id: Uuid::new_v4(),
+            node_id: node.id,
+            node_label: node_label.into(),
+            message: request.message,
+            due: request.due,
+            recurrence: request.recurrence,
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Notification shown when the reminder fires
+    pub fn notification(&self) -> Notification {
+        let body = if self.message.is_empty() { self.node_label.clone() } else { self.message.clone() };
+        let mut notification = Notification::new(tr!(I18N, "reminder-title", node = self.node_label.as_str()), body)
+            .with_type(NotificationType::Reminder)
+            .with_priority(NotificationPriority::High)
+            .with_source(NotificationSource {
+                name: tr!(I18N, "reminder-source"),
+                app_id: None,
+                pid: None,
+                icon: Some("alarm".to_string()),
+            })
+            .with_node(self.node_id)
+            .add_action(NotificationAction::navigate_to_node(tr!(I18N, "reminder-jump-to-node"), self.node_id))
+            .persistent();
+        notification.tags.push(REMINDER_TAG.to_string());
+        notification.metadata.insert(REMINDER_ID_KEY.to_string(), self.id.to_string());
+        notification
+    }
+}
+
+/// Pending reminders, optionally persisted to a file
+#[derive(Debug, Default)]
+pub struct ReminderStore {
+    reminders: RwLock<Vec<Reminder>>,
+    storage_path: Option<PathBuf>,
+}
+
+impl ReminderStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store persisted to a file, loading any reminders already in it
+    pub fn with_storage(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let reminders = if path.exists() {
+            let data = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read reminders {:?}", path))?;
+            serde_json::from_str(&data).with_context(|| format!("Failed to parse reminders {:?}", path))?
+        } else {
+            Vec::new()
+        };
+        Ok(Self { reminders: RwLock::new(reminders), storage_path: Some(path) })
+    }
+
+    pub fn add(&self, reminder: Reminder) -> Result<Uuid> {
+        let id = reminder.id;
+        let mut reminders = self.reminders.write().unwrap();
+        reminders.push(reminder);
+        self.persist(&reminders)?;
+        Ok(id)
+    }
+
+    /// Remove a reminder, returning whether it was pending
+    pub fn cancel(&self, id: Uuid) -> Result<bool> {
+        self.remove_where(|reminder| reminder.id == id).map(|removed| removed > 0)
+    }
+
+    /// Remove every reminder on a node, e.g. when the node is deleted
+    pub fn cancel_node(&self, node_id: SceneId) -> Result<usize> {
+        self.remove_where(|reminder| reminder.node_id == node_id)
+    }
+
+    /// Stop the recurring reminders of a task once it is completed or cancelled
+    pub fn task_updated(&self, node: &SceneNode) -> Result<usize> {
+        match node.node_type {
+            NodeType::Task { status: TaskStatus::Completed | TaskStatus::Cancelled, .. } => {
+                self.remove_where(|reminder| reminder.node_id == node.id && reminder.recurrence.is_some())
+            }
+            _ => Ok(0),
+        }
+    }
+
+    /// Pending reminders, soonest first
+    pub fn pending(&self) -> Vec<Reminder> {
+        let mut reminders = self.reminders.read().unwrap().clone();
+        reminders.sort_by_key(|reminder| reminder.due);
+        reminders
+    }
+
+    /// Pending reminders on a node, soonest first
+    pub fn for_node(&self, node_id: SceneId) -> Vec<Reminder> {
+        let mut reminders = self.pending();
+        reminders.retain(|reminder| reminder.node_id == node_id);
+        reminders
+    }
+
+    /// Take the reminders due at `now`
+    ///
+    /// One-off reminders are removed; recurring ones are moved to their next
+    /// occurrence after `now`, so a reminder missed while the session was
+    /// down fires once rather than once per missed occurrence.
+    pub fn take_due(&self, now: DateTime<Utc>) -> Result<Vec<Reminder>> {
+        let mut reminders = self.reminders.write().unwrap();
+        if !reminders.iter().any(|reminder| reminder.due <= now) {
+            return Ok(Vec::new());
+        }
+
+        let mut due = Vec::new();
+        reminders.retain_mut(|reminder| {
+            if reminder.due > now {
+                return true;
+            }
+            due.push(reminder.clone());
+            match reminder.recurrence {
+                Some(recurrence) => {
+                    reminder.due = recurrence.next_after(now.with_timezone(&Local)).with_timezone(&Utc);
+                    true
+                }
+                None => false,
+            }
+        });
+        self.persist(&reminders)?;
+        Ok(due)
+    }
+
+    fn remove_where(&self, remove: impl Fn(&Reminder) -> bool) -> Result<usize> {
+        let mut reminders = self.reminders.write().unwrap();
+        let before = reminders.len();
+        reminders.retain(|reminder| !remove(reminder));
+        let removed = before - reminders.len();
+        if removed > 0 {
+            self.persist(&reminders)?;
+        }
+        Ok(removed)
+    }
+
+    fn persist(&self, reminders: &[Reminder]) -> Result<()> {
+        let Some(path) = &self.storage_path else {
+            return Ok(());
+        };
+        write_atomic(path, &serde_json::to_vec(reminders)?)
+            .with_context(|| format!("Failed to write reminders {:?}", path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::NodeMetadata;
+    use nalgebra::{Point3, Vector3};
+
+    fn node(node_type: NodeType) -> SceneNode {
+        SceneNode {
+            id: 3,
+            position: Point3::origin(),
+            velocity: Vector3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type,
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        }
+    }
+
+    fn local(text: &str, now: DateTime<Local>) -> (String, String, Option<Recurrence>) {
+        let request = ReminderRequest::parse_from(text, now).unwrap();
+        let due = request.due.with_timezone(&Local).format("%a %d %H:%M").to_string();
+        (request.message, due, request.recurrence)
+    }
+
+    #[test]
+    fn test_parse_requests() {
+        // Wednesday evening
+        let now = Local.with_ymd_and_hms(2024, 3, 13, 18, 30, 0).unwrap();
+        let nine = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+
+        assert_eq!(local("remind me about this file tomorrow at 9", now), ("this file".into(), "Thu 14 09:00".into(), None));
+        assert_eq!(local("call back in 2 hours", now).1, "Wed 13 20:30");
+        assert_eq!(local("in an hour", now), (String::new(), "Wed 13 19:30".into(), None));
+        assert_eq!(local("review on friday at 3:30pm", now).1, "Fri 15 15:30");
+        assert_eq!(local("next wednesday", now).1, "Wed 20 09:00");
+        assert_eq!(local("at 5", now).1, "Thu 14 17:00");
+        assert_eq!(local("tonight", now).1, "Wed 13 20:00");
+        assert_eq!(
+            local("standup every weekday at 9", now),
+            ("standup".into(), "Thu 14 09:00".into(), Some(Recurrence::Weekdays { time: nine })),
+        );
+        assert_eq!(local("every monday", now).2, Some(Recurrence::Weekly { weekday: Weekday::Mon, time: nine }));
+        assert!(ReminderRequest::parse_from("remind me about this file", now).is_err());
+    }
+
+    #[test]
+    fn test_recurring_reminders_reschedule_and_persist() {
+        let path = std::env::temp_dir().join(format!("horizonos-reminders-{}.json", Uuid::new_v4()));
+        let now = Local.with_ymd_and_hms(2024, 3, 15, 8, 0, 0).unwrap();
+        let file = node(NodeType::File { path: "/tmp/a".into(), file_type: horizonos_graph_engine::FileType::RegularFile });
+        let task = node(NodeType::Task { title: "Report".into(), status: TaskStatus::Todo });
+
+        let daily = ReminderRequest::parse_from("every weekday at 9", now).unwrap();
+        assert!(Reminder::from_request(&file, "a", daily.clone()).is_err());
+
+        let store = ReminderStore::with_storage(&path).unwrap();
+        store.add(Reminder::from_request(&task, "Report", daily).unwrap()).unwrap();
+        store.add(Reminder::from_request(&file, "a", ReminderRequest::parse_from("in 2 hours", now).unwrap()).unwrap()).unwrap();
+        drop(store);
+
+        let store = ReminderStore::with_storage(&path).unwrap();
+        let fired = store.take_due((now + ChronoDuration::hours(3)).with_timezone(&Utc)).unwrap();
+        assert_eq!(fired.len(), 2);
+        let notification = fired[0].notification();
+        assert_eq!(notification.node_id, Some(3));
+        assert!(notification.tags.iter().any(|tag| tag == REMINDER_TAG));
+
+        // Friday's reminder came back for Monday, the one-off one is gone
+        let pending = store.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].due.with_timezone(&Local).format("%a %d %H:%M").to_string(), "Mon 18 09:00");
+
+        let done = node(NodeType::Task { title: "Report".into(), status: TaskStatus::Completed });
+        assert_eq!(store.task_updated(&done).unwrap(), 1);
+        assert!(ReminderStore::with_storage(&path).unwrap().pending().is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -7,9 +7,14 @@ use crate::{
 use anyhow::Result;
 use nalgebra::{Point3, Vector3};
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use uuid::Uuid;
 use horizonos_graph_nodes::NodeVisualData;
+use horizonos_graph_visual::theme::EasingFunction;
+use horizonos_graph_visual::{AnimationId, AnimationSystem, Timeline};
+
+/// Owner of the notification animations in the animation system
+const ANIMATION_OWNER: &str = "notifications";
 
 /// Graph-integrated notification renderer
 pub struct NotificationRenderer {
@@ -17,8 +22,10 @@ pub struct NotificationRenderer {
     active_visuals: Arc<RwLock<HashMap<Uuid, NotificationVisual>>>,
     /// Notification layout manager
     layout: NotificationLayout,
-    /// Animation states
-    animations: Arc<RwLock<HashMap<Uuid, AnimationState>>>,
+    /// Animation system the notification animations run in
+    animation_system: Arc<Mutex<AnimationSystem>>,
+    /// Running animations of each notification
+    animations: HashMap<Uuid, Vec<AnimationTrack>>,
 }
 
 /// Visual representation of a notification
@@ -56,37 +63,58 @@ pub struct NotificationLayout {
     stack: Vec<Uuid>,
 }
 
-/// Animation state
-#[derive(Debug, Clone)]
-struct AnimationState {
-    /// Current animation
-    animation: NotificationAnimation,
-    /// Start time
-    start_time: std::time::Instant,
-    /// Initial state
-    initial: AnimationFrame,
-    /// Target state
-    target: AnimationFrame,
+/// A running animation and the visual property it drives
+#[derive(Debug, Clone, Copy)]
+struct AnimationTrack {
+    id: AnimationId,
+    property: AnimatedProperty,
 }
 
-/// Animation frame
-#[derive(Debug, Clone, Copy)]
-struct AnimationFrame {
-    position: Point3<f32>,
-    opacity: f32,
-    scale: f32,
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AnimatedProperty {
+    /// Position and opacity, as `[x, y, z, opacity]`
+    Frame,
+    /// Opacity alone
+    Opacity,
+    /// Horizontal offset from a resting x position
+    Shake { rest_x: f32 },
+    /// Scale, glowing while enlarged
+    Pulse,
+}
+
+impl AnimatedProperty {
+    /// Whether two animations drive the same property, so the later one replaces the earlier
+    ///
+    /// A fade runs alongside a slide, the later of the two setting the opacity.
+    fn conflicts_with(&self, other: &AnimatedProperty) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
 }
 
 impl NotificationRenderer {
-    /// Create a new notification renderer
+    /// Create a new notification renderer with an animation system of its own
     pub fn new(screen_size: (f32, f32)) -> Self {
+        Self::with_animation_system(screen_size, Arc::new(Mutex::new(AnimationSystem::new())))
+    }
+    
+    /// Create a notification renderer animating through a shared animation system
+    ///
+    /// The system is advanced by its owner, usually once per frame by the
+    /// render loop; [`Self::update_animations`] then applies the values.
+    pub fn with_animation_system(screen_size: (f32, f32), animation_system: Arc<Mutex<AnimationSystem>>) -> Self {
         Self {
             active_visuals: Arc::new(RwLock::new(HashMap::new())),
             layout: NotificationLayout::new(screen_size),
-            animations: Arc::new(RwLock::new(HashMap::new())),
+            animation_system,
+            animations: HashMap::new(),
         }
     }
     
+    /// Animation system the notification animations run in
+    pub fn animation_system(&self) -> Arc<Mutex<AnimationSystem>> {
+        self.animation_system.clone()
+    }
+    
     /// Update screen size
     pub fn update_screen_size(&mut self, width: f32, height: f32) {
         self.layout.screen_size = (width, height);
@@ -154,127 +182,122 @@ impl NotificationRenderer {
     pub fn remove_notification(&mut self, id: Uuid) {
         self.active_visuals.write().unwrap().remove(&id);
         self.layout.stack.retain(|&nid| nid != id);
-        self.animations.write().unwrap().remove(&id);
+        if let Some(tracks) = self.animations.remove(&id) {
+            let mut system = self.animation_system.lock().unwrap_or_else(|e| e.into_inner());
+            for track in tracks {
+                system.cancel(track.id);
+            }
+        }
         self.relayout();
     }
     
     /// Start animation for notification
+    ///
+    /// The animation replaces any running one that drives the same property.
     pub fn start_animation(&mut self, id: Uuid, animation: NotificationAnimation) {
-        if let Some(visual) = self.active_visuals.read().unwrap().get(&id) {
-            let initial = AnimationFrame {
-                position: visual.position,
-                opacity: visual.opacity,
-                scale: visual.scale,
-            };
-            
-            let target = match &animation {
-                NotificationAnimation::SlideIn { direction, .. } => {
-                    let offset = self.get_slide_offset(*direction);
-                    AnimationFrame {
-                        position: visual.position,
-                        opacity: 1.0,
-                        scale: 1.0,
-                    }
-                }
-                NotificationAnimation::SlideOut { direction, .. } => {
-                    let offset = self.get_slide_offset(*direction);
-                    AnimationFrame {
-                        position: visual.position + offset,
-                        opacity: 0.0,
-                        scale: 1.0,
-                    }
-                }
-                NotificationAnimation::FadeIn { .. } => AnimationFrame {
-                    position: visual.position,
-                    opacity: 1.0,
-                    scale: 1.0,
-                },
-                NotificationAnimation::FadeOut { .. } => AnimationFrame {
-                    position: visual.position,
-                    opacity: 0.0,
-                    scale: 1.0,
-                },
-                NotificationAnimation::Shake { .. } => initial, // Handled differently
-                NotificationAnimation::Pulse { .. } => initial,  // Handled differently
-                NotificationAnimation::ProgressUpdate { .. } => initial,
-            };
-            
-            self.animations.write().unwrap().insert(id, AnimationState {
-                animation,
-                start_time: std::time::Instant::now(),
-                initial,
-                target,
-            });
-        }
+        let Some(visual) = self.active_visuals.read().unwrap().get(&id).cloned() else {
+            return;
+        };
+        let frame = |position: Point3<f32>, opacity: f32| [position.x, position.y, position.z, opacity];
+        let mut system = self.animation_system.lock().unwrap_or_else(|e| e.into_inner());
+        let (property, animation_id) = match animation {
+            NotificationAnimation::SlideIn { duration, direction } => {
+                let from = frame(visual.position + self.get_slide_offset(direction), 0.0);
+                let to = frame(visual.position, 1.0);
+                (AnimatedProperty::Frame, system.tween(ANIMATION_OWNER, from, to, duration.as_secs_f32(), EasingFunction::EaseInOut))
+            }
+            NotificationAnimation::SlideOut { duration, direction } => {
+                let from = frame(visual.position, visual.opacity);
+                let to = frame(visual.position + self.get_slide_offset(direction), 0.0);
+                (AnimatedProperty::Frame, system.tween(ANIMATION_OWNER, from, to, duration.as_secs_f32(), EasingFunction::EaseInOut))
+            }
+            NotificationAnimation::FadeIn { duration } => {
+                (AnimatedProperty::Opacity, system.tween(ANIMATION_OWNER, 0.0f32, 1.0, duration.as_secs_f32(), EasingFunction::EaseInOut))
+            }
+            NotificationAnimation::FadeOut { duration } => {
+                (AnimatedProperty::Opacity, system.tween(ANIMATION_OWNER, visual.opacity, 0.0f32, duration.as_secs_f32(), EasingFunction::EaseInOut))
+            }
+            NotificationAnimation::Shake { duration, intensity } => {
+                // Swings from side to side, each smaller than the last
+                const SWINGS: u32 = 8;
+                let swing = duration.as_secs_f32() / (SWINGS + 1) as f32;
+                let timeline = (0..SWINGS).fold(Timeline::new(0.0f32), |timeline, i| {
+                    let side = if i % 2 == 0 { 1.0 } else { -1.0 };
+                    let offset = side * intensity * (1.0 - i as f32 / SWINGS as f32);
+                    timeline.then(swing, offset, EasingFunction::EaseInOut)
+                });
+                let timeline = timeline.then(swing, 0.0, EasingFunction::EaseOut);
+                (AnimatedProperty::Shake { rest_x: visual.position.x }, system.timeline(ANIMATION_OWNER, timeline))
+            }
+            NotificationAnimation::Pulse { duration, count } => {
+                let count = count.max(1);
+                let half = duration.as_secs_f32() / (2 * count) as f32;
+                let timeline = (0..count).fold(Timeline::new(1.0f32), |timeline, _| {
+                    timeline.then(half, 1.2, EasingFunction::EaseInOut).then(half, 1.0, EasingFunction::EaseInOut)
+                });
+                (AnimatedProperty::Pulse, system.timeline(ANIMATION_OWNER, timeline))
+            }
+            // Progress is shown through the badge, which changes with the notification
+            NotificationAnimation::ProgressUpdate { .. } => return,
+        };
+        
+        let tracks = self.animations.entry(id).or_default();
+        tracks.retain(|track| {
+            let replaced = track.property.conflicts_with(&property);
+            if replaced {
+                system.cancel(track.id);
+            }
+            !replaced
+        });
+        tracks.push(AnimationTrack { id: animation_id, property });
     }
     
-    /// Update animations
-    pub fn update_animations(&mut self, delta_time: f32) {
-        let mut completed = Vec::new();
-        let mut animations = self.animations.write().unwrap();
+    /// Apply the current values of the running animations to the visuals
+    ///
+    /// Call after the animation system was advanced for the frame.
+    pub fn update_animations(&mut self) {
+        let system = self.animation_system.lock().unwrap_or_else(|e| e.into_inner());
         let mut visuals = self.active_visuals.write().unwrap();
         
-        for (id, state) in animations.iter() {
-            if let Some(visual) = visuals.get_mut(id) {
-                let elapsed = state.start_time.elapsed();
-                
-                match &state.animation {
-                    NotificationAnimation::SlideIn { duration, .. } |
-                    NotificationAnimation::SlideOut { duration, .. } |
-                    NotificationAnimation::FadeIn { duration } |
-                    NotificationAnimation::FadeOut { duration } => {
-                        let progress = (elapsed.as_secs_f32() / duration.as_secs_f32()).min(1.0);
-                        let t = self.ease_in_out(progress);
-                        
-                        // Interpolate
-                        visual.position = Point3::from(
-                            state.initial.position.coords.lerp(&state.target.position.coords, t)
-                        );
-                        visual.opacity = state.initial.opacity + (state.target.opacity - state.initial.opacity) * t;
-                        visual.scale = state.initial.scale + (state.target.scale - state.initial.scale) * t;
-                        
-                        if progress >= 1.0 {
-                            completed.push(*id);
-                        }
+        self.animations.retain(|id, tracks| {
+            let Some(visual) = visuals.get_mut(id) else {
+                return false;
+            };
+            tracks.retain(|track| {
+                match track.property {
+                    AnimatedProperty::Frame => {
+                        let Some([x, y, z, opacity]) = system.value::<[f32; 4]>(track.id) else {
+                            return false;
+                        };
+                        visual.position = Point3::new(x, y, z);
+                        visual.opacity = opacity;
+                    }
+                    AnimatedProperty::Opacity => {
+                        let Some(opacity) = system.value::<f32>(track.id) else {
+                            return false;
+                        };
+                        visual.opacity = opacity;
                     }
-                    NotificationAnimation::Shake { duration, intensity } => {
-                        let progress = elapsed.as_secs_f32() / duration.as_secs_f32();
-                        if progress < 1.0 {
-                            let shake = (progress * 20.0).sin() * intensity * (1.0 - progress);
-                            visual.position.x = state.initial.position.x + shake;
-                        } else {
-                            visual.position = state.initial.position;
-                            completed.push(*id);
-                        }
+                    AnimatedProperty::Shake { rest_x } => {
+                        let Some(offset) = system.value::<f32>(track.id) else {
+                            return false;
+                        };
+                        visual.position.x = rest_x + offset;
                     }
-                    NotificationAnimation::Pulse { duration, count } => {
-                        let progress = elapsed.as_secs_f32() / duration.as_secs_f32();
-                        let cycles = progress * (*count as f32);
-                        if cycles < *count as f32 {
-                            let pulse = (cycles * std::f32::consts::PI * 2.0).sin() * 0.5 + 0.5;
-                            visual.scale = 1.0 + pulse * 0.2;
-                            visual.visual_data.glow = pulse > 0.5;
-                        } else {
+                    AnimatedProperty::Pulse => {
+                        let Some(scale) = system.value::<f32>(track.id) else {
                             visual.scale = 1.0;
                             visual.visual_data.glow = false;
-                            completed.push(*id);
-                        }
-                    }
-                    NotificationAnimation::ProgressUpdate { value, duration } => {
-                        let progress = (elapsed.as_secs_f32() / duration.as_secs_f32()).min(1.0);
-                        // Update progress bar visual
-                        if progress >= 1.0 {
-                            completed.push(*id);
-                        }
+                            return false;
+                        };
+                        visual.scale = scale;
+                        visual.visual_data.glow = scale > 1.1;
                     }
                 }
-            }
-        }
-        
-        // Remove completed animations
-        for id in completed {
-            animations.remove(&id);
-        }
+                system.is_running(track.id)
+            });
+            !tracks.is_empty()
+        });
     }
     
     /// Get slide offset for direction
@@ -288,15 +311,6 @@ impl NotificationRenderer {
         }
     }
     
-    /// Ease in-out animation curve
-    fn ease_in_out(&self, t: f32) -> f32 {
-        if t < 0.5 {
-            2.0 * t * t
-        } else {
-            -1.0 + (4.0 - 2.0 * t) * t
-        }
-    }
-    
     /// Relayout all notifications
     fn relayout(&mut self) {
         let stack = self.layout.stack.clone();
@@ -427,4 +441,35 @@ mod tests {
         assert_eq!(visual.id, notification.id);
         assert_eq!(visual.size.y, 2.0); // Base size without image or actions
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_animations_run_in_the_shared_system() {
+        let system = Arc::new(Mutex::new(AnimationSystem::new().with_step(0.05)));
+        let mut renderer = NotificationRenderer::with_animation_system((1920.0, 1080.0), system.clone());
+        let notification = crate::Notification::new("Test".to_string(), "Body".to_string());
+        renderer.add_notification(&notification);
+        let rest = renderer.get_visuals()[0].position;
+        
+        let duration = std::time::Duration::from_millis(200);
+        renderer.start_animation(notification.id, NotificationAnimation::SlideIn { duration, direction: SlideDirection::Right });
+        renderer.update_animations();
+        let visual = renderer.get_visuals()[0].clone();
+        assert_eq!(visual.opacity, 0.0);
+        assert_eq!(visual.position.x, rest.x + 10.0);
+        
+        for _ in 0..4 {
+            system.lock().unwrap().step();
+        }
+        renderer.update_animations();
+        let visual = renderer.get_visuals()[0].clone();
+        assert_eq!(visual.opacity, 1.0);
+        assert_eq!(visual.position, rest);
+        assert!(renderer.animations.is_empty());
+        
+        // Removing a notification cancels its animations
+        renderer.start_animation(notification.id, NotificationAnimation::FadeOut { duration });
+        assert_eq!(system.lock().unwrap().running(), 1);
+        renderer.remove_notification(notification.id);
+        assert_eq!(system.lock().unwrap().running(), 0);
+    }
+}
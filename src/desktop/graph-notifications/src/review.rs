@@ -0,0 +1,449 @@
+//! Screen reader review mode for notifications
+//!
+//! While review mode is on, incoming notifications are queued instead of
+//! relying on toasts alone. The user steps through the queue with the
+//! keyboard, hearing one notification at a time, and acts on or dismisses
+//! the one being read. Each step produces a [`ReviewUtterance`] with the
+//! spoken text and the lines for a braille display; callers pass it to the
+//! screen reader and AT-SPI, e.g. with `AccessibilityManager::announce`.
+//!
+//! How much is read is set per notification type, so chat messages can be
+//! read in full while progress updates only give their title, or are left
+//! out of the queue entirely.
+
+use crate::actions::NotificationAction;
+use crate::i18n::I18N;
+use crate::{Notification, NotificationEvent, NotificationPriority, NotificationType};
+use chrono::Local;
+use horizonos_graph_i18n::tr;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// How much of a notification is read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ReviewVerbosity {
+    /// Not queued for review
+    Silent,
+    /// Title only
+    Brief,
+    /// Source, title and body
+    Normal,
+    /// Also priority, time and available actions
+    Detailed,
+}
+
+/// Review mode preferences
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReviewSettings {
+    /// Verbosity of types without their own setting
+    pub default_verbosity: ReviewVerbosity,
+    /// Verbosity by notification type
+    pub verbosity: HashMap<NotificationType, ReviewVerbosity>,
+    /// Cells per line of the braille display
+    pub braille_cells: usize,
+    /// Announce notifications as they arrive, not only when stepped to
+    pub announce_arrivals: bool,
+}
+
+impl Default for ReviewSettings {
+    fn default() -> Self {
+        Self {
+            default_verbosity: ReviewVerbosity::Normal,
+            verbosity: HashMap::from([
+                (NotificationType::Progress, ReviewVerbosity::Brief),
+                (NotificationType::GraphEvent, ReviewVerbosity::Brief),
+                (NotificationType::Error, ReviewVerbosity::Detailed),
+                (NotificationType::Security, ReviewVerbosity::Detailed),
+            ]),
+            braille_cells: 40,
+            announce_arrivals: true,
+        }
+    }
+}
+
+impl ReviewSettings {
+    /// Verbosity a notification type is read with
+    pub fn verbosity_for(&self, notification_type: NotificationType) -> ReviewVerbosity {
+        self.verbosity.get(&notification_type).copied().unwrap_or(self.default_verbosity)
+    }
+}
+
+/// Keyboard commands in review mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewCommand {
+    Next,
+    Previous,
+    /// Read the current notification again
+    Repeat,
+    /// Run the current notification's primary action, or open it
+    Act,
+    Dismiss,
+    /// Leave review mode
+    Exit,
+}
+
+impl ReviewCommand {
+    /// Command bound to a key, by XKB key name
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "Down" | "j" | "n" => Some(ReviewCommand::Next),
+            "Up" | "k" | "p" => Some(ReviewCommand::Previous),
+            "space" | "r" => Some(ReviewCommand::Repeat),
+            "Return" | "KP_Enter" => Some(ReviewCommand::Act),
+            "Delete" | "d" => Some(ReviewCommand::Dismiss),
+            "Escape" | "q" => Some(ReviewCommand::Exit),
+            _ => None,
+        }
+    }
+}
+
+/// Text for the screen reader and braille display
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewUtterance {
+    /// Notification the text is about
+    pub notification_id: Option<Uuid>,
+    /// Spoken text
+    pub speech: String,
+    /// Braille display lines, each at most `braille_cells` long
+    pub braille: Vec<String>,
+    /// Interrupt current speech
+    pub urgent: bool,
+}
+
+/// What a review command asks the caller to do to a notification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewEffect {
+    /// Dismiss the notification
+    Dismiss(Uuid),
+    /// Run one of the notification's actions
+    InvokeAction { notification_id: Uuid, action_id: String },
+    /// Open the notification, as clicking it would
+    Open(Uuid),
+}
+
+/// Result of a review command
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReviewStep {
+    pub utterance: Option<ReviewUtterance>,
+    pub effect: Option<ReviewEffect>,
+}
+
+/// Queue of notifications read one at a time
+#[derive(Debug, Clone, Default)]
+pub struct NotificationReview {
+    settings: ReviewSettings,
+    active: bool,
+    queue: Vec<Notification>,
+    position: usize,
+}
+
+impl NotificationReview {
+    pub fn new(settings: ReviewSettings) -> Self {
+        Self { settings, ..Self::default() }
+    }
+
+    pub fn settings(&self) -> &ReviewSettings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: ReviewSettings) {
+        self.settings = settings;
+        let settings = &self.settings;
+        self.queue.retain(|notification| settings.verbosity_for(notification.notification_type) != ReviewVerbosity::Silent);
+        self.position = self.position.min(self.queue.len().saturating_sub(1));
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Notifications waiting to be reviewed
+    pub fn queue(&self) -> &[Notification] {
+        &self.queue
+    }
+
+    /// Notification being read
+    pub fn current(&self) -> Option<&Notification> {
+        self.queue.get(self.position)
+    }
+
+    /// Turn review mode on with the notifications already showing, and read the first
+    pub fn enter(&mut self, active: Vec<Notification>) -> ReviewUtterance {
+        self.active = true;
+        self.queue.clear();
+        self.position = 0;
+        for notification in active {
+            self.enqueue(notification);
+        }
+        let count = self.queue.len() as i64;
+        let mut utterance = self.plain(tr!(I18N, "review-entered", count = count), false);
+        if let Some(current) = self.describe_current() {
+            utterance.speech = format!("{} {}", utterance.speech, current.speech);
+            utterance.braille.extend(current.braille);
+            utterance.notification_id = current.notification_id;
+        }
+        utterance
+    }
+
+    /// Turn review mode off, dropping the queue
+    pub fn exit(&mut self) -> ReviewUtterance {
+        self.active = false;
+        self.queue.clear();
+        self.position = 0;
+        self.plain(tr!(I18N, "review-exited"), false)
+    }
+
+    /// Keep the queue in step with notification events
+    ///
+    /// Returns an arrival announcement for new notifications when
+    /// `announce_arrivals` is set; critical ones are always announced.
+    pub fn handle_event(&mut self, event: &NotificationEvent) -> Option<ReviewUtterance> {
+        if !self.active {
+            return None;
+        }
+        match event {
+            NotificationEvent::Created(notification) => {
+                if !self.enqueue(notification.clone()) {
+                    return None;
+                }
+                let urgent = notification.priority == NotificationPriority::Critical;
+                if !self.settings.announce_arrivals && !urgent {
+                    return None;
+                }
+                let speech = tr!(
+                    I18N,
+                    "review-arrived",
+                    source = notification.source.name.as_str(),
+                    count = self.queue.len() as i64
+                );
+                let mut utterance = self.plain(speech, urgent);
+                utterance.notification_id = Some(notification.id);
+                Some(utterance)
+            }
+            NotificationEvent::Updated(notification) => {
+                if let Some(queued) = self.queue.iter_mut().find(|queued| queued.id == notification.id) {
+                    *queued = notification.clone();
+                }
+                None
+            }
+            NotificationEvent::Dismissed(id) | NotificationEvent::Expired(id) => {
+                self.remove(*id);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Run a keyboard command
+    pub fn command(&mut self, command: ReviewCommand) -> ReviewStep {
+        if !self.active {
+            return ReviewStep::default();
+        }
+        match command {
+            ReviewCommand::Exit => ReviewStep { utterance: Some(self.exit()), effect: None },
+            _ if self.queue.is_empty() => {
+                ReviewStep { utterance: Some(self.plain(tr!(I18N, "review-empty"), false)), effect: None }
+            }
+            ReviewCommand::Next if self.position + 1 >= self.queue.len() => {
+                ReviewStep { utterance: Some(self.plain(tr!(I18N, "review-end"), false)), effect: None }
+            }
+            ReviewCommand::Previous if self.position == 0 => {
+                ReviewStep { utterance: Some(self.plain(tr!(I18N, "review-start"), false)), effect: None }
+            }
+            ReviewCommand::Next | ReviewCommand::Previous | ReviewCommand::Repeat => {
+                match command {
+                    ReviewCommand::Next => self.position += 1,
+                    ReviewCommand::Previous => self.position -= 1,
+                    _ => {}
+                }
+                ReviewStep { utterance: self.describe_current(), effect: None }
+            }
+            ReviewCommand::Act => {
+                let current = &self.queue[self.position];
+                let effect = match primary_action(current) {
+                    Some(action) => ReviewEffect::InvokeAction { notification_id: current.id, action_id: action.id.clone() },
+                    None => ReviewEffect::Open(current.id),
+                };
+                ReviewStep { utterance: None, effect: Some(effect) }
+            }
+            ReviewCommand::Dismiss => {
+                let id = self.queue[self.position].id;
+                self.remove(id);
+                let mut utterance = self.plain(tr!(I18N, "review-dismissed"), false);
+                match self.describe_current() {
+                    Some(next) => {
+                        utterance.speech = format!("{} {}", utterance.speech, next.speech);
+                        utterance.braille = next.braille;
+                        utterance.notification_id = next.notification_id;
+                    }
+                    None => utterance.speech = format!("{} {}", utterance.speech, tr!(I18N, "review-empty")),
+                }
+                ReviewStep { utterance: Some(utterance), effect: Some(ReviewEffect::Dismiss(id)) }
+            }
+        }
+    }
+
+    /// Utterance for the notification being read
+    pub fn describe_current(&self) -> Option<ReviewUtterance> {
+        let notification = self.current()?;
+        let verbosity = self.settings.verbosity_for(notification.notification_type);
+        let position = tr!(I18N, "review-position", index = (self.position + 1) as i64, total = self.queue.len() as i64);
+        let body = if notification.private { tr!(I18N, "review-private") } else { notification.body.clone() };
+
+        let mut speech = vec![position];
+        let mut braille = vec![format!("{}/{}", self.position + 1, self.queue.len())];
+        if verbosity == ReviewVerbosity::Detailed && notification.priority >= NotificationPriority::High {
+            let priority = tr!(I18N, "review-priority", priority = format!("{:?}", notification.priority).to_lowercase());
+            speech.push(priority);
+            braille.push("!!".to_string());
+        }
+        if verbosity >= ReviewVerbosity::Normal {
+            speech.push(format!("{}:", notification.source.name));
+            braille.push(format!("{}:", notification.source.name));
+        }
+        speech.push(sentence(&notification.title));
+        braille.push(notification.title.clone());
+        if verbosity >= ReviewVerbosity::Normal && !body.is_empty() {
+            speech.push(sentence(&body));
+            braille.push(format!("- {}", body));
+        }
+        if verbosity == ReviewVerbosity::Detailed {
+            let time = notification.timestamp.with_timezone(&Local).format("%H:%M").to_string();
+            speech.push(sentence(&time));
+            braille.push(time);
+            if !notification.actions.is_empty() {
+                let labels: Vec<&str> = notification.actions.iter().map(|action| action.label.as_str()).collect();
+                speech.push(tr!(I18N, "review-actions", actions = labels.join(", ")));
+                braille.push(format!("[{}]", labels.join("|")));
+            }
+        }
+
+        Some(ReviewUtterance {
+            notification_id: Some(notification.id),
+            speech: speech.join(" "),
+            braille: braille_lines(&braille.join(" "), self.settings.braille_cells),
+            urgent: notification.priority == NotificationPriority::Critical,
+        })
+    }
+
+    /// Add a notification unless its type is silent; `false` if it was not added
+    fn enqueue(&mut self, notification: Notification) -> bool {
+        if self.settings.verbosity_for(notification.notification_type) == ReviewVerbosity::Silent {
+            return false;
+        }
+        if let Some(queued) = self.queue.iter_mut().find(|queued| queued.id == notification.id) {
+            *queued = notification;
+            return false;
+        }
+        self.queue.push(notification);
+        true
+    }
+
+    fn remove(&mut self, id: Uuid) {
+        if let Some(index) = self.queue.iter().position(|notification| notification.id == id) {
+            self.queue.remove(index);
+            if index < self.position || self.position >= self.queue.len() {
+                self.position = self.position.saturating_sub(1);
+            }
+        }
+    }
+
+    fn plain(&self, speech: String, urgent: bool) -> ReviewUtterance {
+        ReviewUtterance {
+            notification_id: None,
+            braille: braille_lines(&speech, self.settings.braille_cells),
+            speech,
+            urgent,
+        }
+    }
+}
+
+/// Action run by [`ReviewCommand::Act`]
+fn primary_action(notification: &Notification) -> Option<&NotificationAction> {
+    notification
+        .actions
+        .iter()
+        .find(|action| action.primary)
+        .or_else(|| notification.actions.first())
+}
+
+/// Text ending in punctuation, so speech pauses after it
+fn sentence(text: &str) -> String {
+    let text = text.trim();
+    if text.ends_with(['.', '!', '?', ':']) {
+        text.to_string()
+    } else {
+        format!("{}.", text)
+    }
+}
+
+/// Word-wrap text to lines of at most `cells` characters
+fn braille_lines(text: &str, cells: usize) -> Vec<String> {
+    let cells = cells.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        while word.len() > cells {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            lines.push(word.drain(..cells).collect());
+        }
+        let word: String = word.into_iter().collect();
+        if word.is_empty() {
+            continue;
+        }
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > cells {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_review_steps_through_queue() {
+        let mut review = NotificationReview::new(ReviewSettings {
+            verbosity: HashMap::from([(NotificationType::Progress, ReviewVerbosity::Silent)]),
+            braille_cells: 20,
+            ..ReviewSettings::default()
+        });
+        let first = Notification::new("Build finished".to_string(), "All tests passed".to_string());
+        let entered = review.enter(vec![first.clone()]);
+        assert!(entered.speech.contains("Build finished"));
+        assert!(entered.braille.iter().all(|line| line.chars().count() <= 20));
+
+        let progress = Notification::new("Copying".to_string(), String::new()).with_type(NotificationType::Progress);
+        assert!(review.handle_event(&NotificationEvent::Created(progress)).is_none());
+        let urgent = Notification::new("Disk full".to_string(), String::new()).with_priority(NotificationPriority::Critical);
+        assert!(review.handle_event(&NotificationEvent::Created(urgent.clone())).unwrap().urgent);
+        assert_eq!(review.queue().len(), 2);
+
+        assert_eq!(review.command(ReviewCommand::Next).utterance.unwrap().notification_id, Some(urgent.id));
+        assert!(review.command(ReviewCommand::Next).utterance.unwrap().notification_id.is_none());
+        assert_eq!(review.command(ReviewCommand::Act).effect, Some(ReviewEffect::Open(urgent.id)));
+
+        let step = review.command(ReviewCommand::Dismiss);
+        assert_eq!(step.effect, Some(ReviewEffect::Dismiss(urgent.id)));
+        assert_eq!(step.utterance.unwrap().notification_id, Some(first.id));
+
+        review.handle_event(&NotificationEvent::Dismissed(first.id));
+        assert!(review.current().is_none());
+        assert_eq!(ReviewCommand::from_key("Escape"), Some(ReviewCommand::Exit));
+        review.command(ReviewCommand::Exit);
+        assert!(!review.is_active());
+    }
+}
@@ -0,0 +1,150 @@
+//! Notification snoozing and scheduled resurfacing
+
+use crate::i18n::I18N;
+use crate::Notification;
+use horizonos_graph_i18n::tr;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Tag added to notifications that come back from a snooze
+pub const SNOOZED_TAG: &str = "snoozed";
+
+/// Metadata key counting how many times a notification has been snoozed
+pub const SNOOZE_COUNT_KEY: &str = "snooze_count";
+
+/// Hour of the day (local time) used by the "tomorrow" and "next week" presets
+pub(crate) const MORNING_HOUR: u32 = 9;
+
+/// How long to snooze a notification for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnoozeDuration {
+    /// 15 minutes
+    FifteenMinutes,
+    /// 1 hour
+    OneHour,
+    /// 3 hours
+    ThreeHours,
+    /// Tomorrow morning
+    Tomorrow,
+    /// Next Monday morning
+    NextWeek,
+    /// Custom duration
+    Custom(Duration),
+}
+
+impl SnoozeDuration {
+    /// Preset durations offered in the notification center
+    pub fn presets() -> [SnoozeDuration; 5] {
+        [
+            SnoozeDuration::FifteenMinutes,
+            SnoozeDuration::OneHour,
+            SnoozeDuration::ThreeHours,
+            SnoozeDuration::Tomorrow,
+            SnoozeDuration::NextWeek,
+        ]
+    }
+
+    /// Localized display label
+    pub fn label(&self) -> String {
+        match self {
+            SnoozeDuration::FifteenMinutes => tr!(I18N, "snooze-fifteen-minutes"),
+            SnoozeDuration::OneHour => tr!(I18N, "snooze-one-hour"),
+            SnoozeDuration::ThreeHours => tr!(I18N, "snooze-three-hours"),
+            SnoozeDuration::Tomorrow => tr!(I18N, "snooze-tomorrow"),
+            SnoozeDuration::NextWeek => tr!(I18N, "snooze-next-week"),
+            SnoozeDuration::Custom(duration) => {
+                let minutes = duration.as_secs() / 60;
+                if minutes >= 60 && minutes % 60 == 0 {
+                    tr!(I18N, "snooze-hours", count = minutes / 60)
+                } else {
+                    tr!(I18N, "snooze-minutes", count = minutes.max(1))
+                }
+            }
+        }
+    }
+
+    /// Time at which a notification snoozed now should resurface
+    pub fn resurface_at(&self) -> DateTime<Utc> {
+        self.resurface_from(Local::now())
+    }
+
+    /// Time at which a notification snoozed at `now` should resurface
+    pub fn resurface_from(&self, now: DateTime<Local>) -> DateTime<Utc> {
+        let resurface = match self {
+            SnoozeDuration::FifteenMinutes => now + ChronoDuration::minutes(15),
+            SnoozeDuration::OneHour => now + ChronoDuration::hours(1),
+            SnoozeDuration::ThreeHours => now + ChronoDuration::hours(3),
+            SnoozeDuration::Tomorrow => morning_after(now, 1),
+            SnoozeDuration::NextWeek => {
+                let days = 7 - now.weekday().num_days_from_monday() as i64;
+                morning_after(now, days)
+            }
+            SnoozeDuration::Custom(duration) => {
+                now + ChronoDuration::from_std(*duration).unwrap_or_else(|_| ChronoDuration::days(365))
+            }
+        };
+        resurface.with_timezone(&Utc)
+    }
+}
+
+/// Morning of the day `days` after `now`, falling back to a plain offset across DST gaps
+fn morning_after(now: DateTime<Local>, days: i64) -> DateTime<Local> {
+    let date = now.date_naive() + ChronoDuration::days(days);
+    let morning = date.and_time(NaiveTime::from_hms_opt(MORNING_HOUR, 0, 0).unwrap());
+    Local
+        .from_local_datetime(&morning)
+        .earliest()
+        .unwrap_or_else(|| now + ChronoDuration::days(days))
+}
+
+/// Prepare a snoozed notification for showing again
+pub fn mark_resurfaced(mut notification: Notification) -> Notification {
+    if !notification.was_snoozed() {
+        notification.tags.push(SNOOZED_TAG.to_string());
+    }
+
+    let count = notification
+        .metadata
+        .get(SNOOZE_COUNT_KEY)
+        .and_then(|count| count.parse::<u32>().ok())
+        .unwrap_or(0);
+    notification.metadata.insert(SNOOZE_COUNT_KEY.to_string(), (count + 1).to_string());
+
+    // Shown as a fresh notification with a new timeout
+    notification.timestamp = Utc::now();
+    notification.expires_at = None;
+    notification.read = false;
+    notification.dismissed = false;
+    notification
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Weekday;
+
+    #[test]
+    fn test_tomorrow_resurfaces_in_the_morning() {
+        let now = Local.with_ymd_and_hms(2024, 3, 13, 22, 30, 0).unwrap();
+        let at = SnoozeDuration::Tomorrow.resurface_from(now).with_timezone(&Local);
+        assert_eq!(at.day(), 14);
+        assert_eq!(at.format("%H:%M").to_string(), "09:00");
+
+        let at = SnoozeDuration::NextWeek.resurface_from(now).with_timezone(&Local);
+        assert_eq!(at.weekday(), Weekday::Mon);
+        assert_eq!(at.day(), 18);
+    }
+
+    #[test]
+    fn test_mark_resurfaced() {
+        let notification = Notification::new("Test".to_string(), "Body".to_string())
+            .expires_in(Duration::from_secs(5));
+
+        let resurfaced = mark_resurfaced(mark_resurfaced(notification));
+        assert!(resurfaced.was_snoozed());
+        assert_eq!(resurfaced.tags.iter().filter(|t| *t == SNOOZED_TAG).count(), 1);
+        assert_eq!(resurfaced.metadata.get(SNOOZE_COUNT_KEY).map(String::as_str), Some("2"));
+        assert!(resurfaced.expires_at.is_none());
+    }
+}
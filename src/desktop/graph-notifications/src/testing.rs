@@ -0,0 +1,164 @@
+//! In-memory notification service for unit tests
+//!
+//! Enabled with the `test-util` feature. Applies every call immediately,
+//! without the background task, providers or renderers used by
+//! `NotificationManager`, and keeps a log of what was sent.
+
+use crate::{Notification, NotificationEvent, NotificationService};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// In-memory `NotificationService`
+pub struct MockNotificationService {
+    /// Notifications currently shown
+    active: RwLock<HashMap<Uuid, Notification>>,
+    /// Snoozed notifications and when they resurface
+    snoozed: RwLock<HashMap<Uuid, (Notification, DateTime<Utc>)>>,
+    /// Every notification passed to `notify`, in order
+    sent: RwLock<Vec<Notification>>,
+    /// Notification event broadcaster
+    event_tx: broadcast::Sender<NotificationEvent>,
+}
+
+impl MockNotificationService {
+    /// Create an empty service
+    pub fn new() -> Self {
+        let (event_tx, _) = broadcast::channel(100);
+        Self {
+            active: RwLock::new(HashMap::new()),
+            snoozed: RwLock::new(HashMap::new()),
+            sent: RwLock::new(Vec::new()),
+            event_tx,
+        }
+    }
+
+    /// Every notification passed to `notify`, including dismissed ones
+    pub fn sent(&self) -> Vec<Notification> {
+        self.sent.read().unwrap().clone()
+    }
+
+    /// Snoozed notifications with their resurface time
+    pub fn snoozed(&self) -> Vec<(Notification, DateTime<Utc>)> {
+        self.snoozed.read().unwrap().values().cloned().collect()
+    }
+
+    /// Show snoozed notifications due at or before `now`, as the manager's timer would
+    pub fn resurface_due(&self, now: DateTime<Utc>) -> Vec<Uuid> {
+        let due: Vec<Notification> = {
+            let mut snoozed = self.snoozed.write().unwrap();
+            let ids: Vec<Uuid> = snoozed.iter()
+                .filter(|(_, (_, until))| *until <= now)
+                .map(|(id, _)| *id)
+                .collect();
+            ids.iter().filter_map(|id| snoozed.remove(id)).map(|(n, _)| n).collect()
+        };
+
+        due.into_iter()
+            .map(|notification| {
+                let notification = crate::snooze::mark_resurfaced(notification);
+                let id = notification.id;
+                self.active.write().unwrap().insert(id, notification);
+                let _ = self.event_tx.send(NotificationEvent::Resurfaced(id));
+                id
+            })
+            .collect()
+    }
+}
+
+impl Default for MockNotificationService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl NotificationService for MockNotificationService {
+    async fn notify(&self, notification: Notification) -> Result<()> {
+        self.sent.write().unwrap().push(notification.clone());
+        self.active.write().unwrap().insert(notification.id, notification.clone());
+        let _ = self.event_tx.send(NotificationEvent::Created(notification));
+        Ok(())
+    }
+
+    async fn update(&self, notification: Notification) -> Result<()> {
+        let mut active = self.active.write().unwrap();
+        if !active.contains_key(&notification.id) {
+            return Err(anyhow!("Notification {} not found", notification.id));
+        }
+        active.insert(notification.id, notification.clone());
+        let _ = self.event_tx.send(NotificationEvent::Updated(notification));
+        Ok(())
+    }
+
+    async fn dismiss(&self, id: Uuid) -> Result<()> {
+        if self.active.write().unwrap().remove(&id).is_some() {
+            let _ = self.event_tx.send(NotificationEvent::Dismissed(id));
+        }
+        Ok(())
+    }
+
+    async fn dismiss_all(&self) -> Result<()> {
+        let ids: Vec<Uuid> = self.active.write().unwrap().drain().map(|(id, _)| id).collect();
+        for id in ids {
+            let _ = self.event_tx.send(NotificationEvent::Dismissed(id));
+        }
+        Ok(())
+    }
+
+    async fn snooze_until(&self, id: Uuid, until: DateTime<Utc>) -> Result<()> {
+        let notification = match self.active.write().unwrap().remove(&id) {
+            Some(notification) => notification,
+            None => {
+                let mut snoozed = self.snoozed.write().unwrap();
+                let (notification, _) = snoozed.remove(&id)
+                    .ok_or_else(|| anyhow!("Notification {} not found", id))?;
+                notification
+            }
+        };
+        self.snoozed.write().unwrap().insert(id, (notification, until));
+        let _ = self.event_tx.send(NotificationEvent::Snoozed { notification_id: id, until });
+        Ok(())
+    }
+
+    async fn get_active(&self) -> Vec<Notification> {
+        self.active.read().unwrap().values().cloned().collect()
+    }
+
+    async fn get_notification(&self, id: Uuid) -> Option<Notification> {
+        self.active.read().unwrap().get(&id).cloned()
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<NotificationEvent> {
+        self.event_tx.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_service_snooze_and_resurface() {
+        let service = MockNotificationService::new();
+        let mut events = service.subscribe();
+        let notification = Notification::new("Test".to_string(), "Body".to_string());
+        let id = notification.id;
+
+        service.notify(notification).await.unwrap();
+        assert!(matches!(events.recv().await.unwrap(), NotificationEvent::Created(_)));
+
+        let until = Utc::now() + chrono::Duration::minutes(15);
+        service.snooze_until(id, until).await.unwrap();
+        assert!(service.get_active().await.is_empty());
+        assert!(service.resurface_due(Utc::now()).is_empty());
+
+        assert_eq!(service.resurface_due(until), vec![id]);
+        assert!(service.get_notification(id).await.unwrap().was_snoozed());
+        assert_eq!(service.sent().len(), 1);
+    }
+}
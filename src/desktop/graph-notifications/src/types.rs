@@ -1,5 +1,7 @@
 //! Notification type definitions and utilities
 
+use crate::i18n::I18N;
+use horizonos_graph_i18n::tr;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -148,6 +150,102 @@ pub enum NotificationTemplate {
     },
 }
 
+impl NotificationTemplate {
+    /// Localized title and body
+    pub fn render(&self) -> (String, String) {
+        match self {
+            NotificationTemplate::FileOperation { operation, file_count, total_size } => {
+                let operation = match operation {
+                    FileOperationType::Copy => "copy",
+                    FileOperationType::Move => "move",
+                    FileOperationType::Delete => "delete",
+                    FileOperationType::Compress => "compress",
+                    FileOperationType::Extract => "extract",
+                    FileOperationType::Download => "download",
+                    FileOperationType::Upload => "upload",
+                    FileOperationType::Sync => "sync",
+                };
+                let body = match total_size {
+                    Some(size) => tr!(I18N, "template-file-operation-body-size", count = *file_count, size = format_size(*size)),
+                    None => tr!(I18N, "template-file-operation-body", count = *file_count),
+                };
+                (tr!(I18N, "template-file-operation-title", operation = operation), body)
+            }
+            NotificationTemplate::UpdateAvailable { app_name, current_version, new_version } => (
+                tr!(I18N, "template-update-title", app = app_name.as_str()),
+                tr!(I18N, "template-update-body", current = current_version.as_str(), new = new_version.as_str()),
+            ),
+            NotificationTemplate::AiSuggestion { suggestion_type, confidence, .. } => {
+                let kind = match suggestion_type {
+                    AiSuggestionType::RelationshipDiscovered => "relationship",
+                    AiSuggestionType::ClusterSuggestion => "cluster",
+                    AiSuggestionType::WorkflowOptimization => "workflow",
+                    AiSuggestionType::DuplicateDetection => "duplicate",
+                    AiSuggestionType::OrganizationSuggestion => "organization",
+                    AiSuggestionType::AutomationOpportunity => "automation",
+                };
+                (
+                    tr!(I18N, "template-ai-title", kind = kind),
+                    tr!(I18N, "template-ai-body", confidence = (confidence * 100.0).round() as i64),
+                )
+            }
+            NotificationTemplate::SystemAlert { alert_type, severity } => {
+                let alert = match alert_type {
+                    SystemAlertType::LowMemory => "low-memory",
+                    SystemAlertType::HighCpu => "high-cpu",
+                    SystemAlertType::DiskSpace => "disk-space",
+                    SystemAlertType::NetworkIssue => "network",
+                    SystemAlertType::SecurityWarning => "security",
+                    SystemAlertType::SystemUpdate => "update",
+                    SystemAlertType::BackupReminder => "backup",
+                    SystemAlertType::MaintenanceRequired => "maintenance",
+                };
+                let severity = match severity {
+                    AlertSeverity::Info => "info",
+                    AlertSeverity::Warning => "warning",
+                    AlertSeverity::Error => "error",
+                    AlertSeverity::Critical => "critical",
+                };
+                (
+                    tr!(I18N, "template-alert-title", alert = alert),
+                    tr!(I18N, "template-alert-body", severity = severity),
+                )
+            }
+            NotificationTemplate::TaskComplete { task_name, duration, result } => {
+                let body = match result {
+                    TaskResult::Success => tr!(I18N, "template-task-success", seconds = duration.as_secs()),
+                    TaskResult::PartialSuccess { completed, total } => {
+                        tr!(I18N, "template-task-partial", completed = *completed, total = *total)
+                    }
+                    TaskResult::Failed { error } => tr!(I18N, "template-task-failed", error = error.as_str()),
+                    TaskResult::Cancelled => tr!(I18N, "template-task-cancelled"),
+                };
+                (tr!(I18N, "template-task-title", task = task_name.as_str()), body)
+            }
+            NotificationTemplate::ConnectionStatus { service, connected, details } => (
+                tr!(I18N, "template-connection-title", service = service.as_str(), connected = if *connected { "yes" } else { "no" }),
+                details.clone().unwrap_or_default(),
+            ),
+        }
+    }
+}
+
+/// Human-readable byte size
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
 /// File operation types
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum FileOperationType {
@@ -383,4 +481,21 @@ mod tests {
         assert!(metadata.urgency.is_none());
         assert!(metadata.properties.is_empty());
     }
+    
+    #[test]
+    fn test_template_render_plurals() {
+        let template = NotificationTemplate::FileOperation {
+            operation: FileOperationType::Copy,
+            file_count: 1,
+            total_size: None,
+        };
+        assert_eq!(template.render(), ("Copy complete".to_string(), "1 file".to_string()));
+        
+        let template = NotificationTemplate::FileOperation {
+            operation: FileOperationType::Download,
+            file_count: 3,
+            total_size: Some(3 * 1024 * 1024),
+        };
+        assert_eq!(template.render().1, "3 files (3.0 MB)");
+    }
 }
\ No newline at end of file
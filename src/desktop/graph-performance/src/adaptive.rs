@@ -13,6 +13,8 @@ pub struct AdaptiveQualitySystem {
     settings: AdaptiveSettings,
     /// Last adjustment time
     last_adjustment: Instant,
+    /// Highest quality while saving power, with effects off
+    power_cap: Option<QualityLevel>,
 }
 
 /// Render quality configuration
@@ -158,6 +160,8 @@ pub enum AdjustmentReason {
     HighGpuUsage { current_percent: f32, threshold_percent: f32 },
     /// Performance improved, can increase quality
     PerformanceImproved,
+    /// Power saving started or ended
+    PowerSaving { active: bool },
     /// Manual adjustment
     Manual,
 }
@@ -200,6 +204,7 @@ impl AdaptiveQualitySystem {
             adjustment_history: Vec::new(),
             settings: AdaptiveSettings::default(),
             last_adjustment: Instant::now(),
+            power_cap: None,
         }
     }
     
@@ -281,7 +286,8 @@ impl AdaptiveQualitySystem {
             QualityLevel::Ultra => return None, // Already at highest
         };
         
-        if new_level as u8 <= self.settings.max_quality_level as u8 {
+        let below_power_cap = self.power_cap.is_none_or(|cap| new_level as u8 >= cap as u8);
+        if new_level as u8 <= self.settings.max_quality_level as u8 && below_power_cap {
             Some(new_level)
         } else {
             None
@@ -411,6 +417,47 @@ impl AdaptiveQualitySystem {
                 texture_quality: TextureQuality::VeryLow,
             },
         };
+        if self.power_cap.is_some() {
+            // Glow and animations cost frames for little while on battery
+            self.current_quality.post_processing.bloom_intensity = 0.0;
+            self.current_quality.animation_quality = AnimationQuality::Off;
+            self.current_quality.particle_density = 0.0;
+        }
+    }
+    
+    /// Cap quality at a level with effects off while saving power, or lift the cap with `None`
+    pub fn set_power_saving(&mut self, cap: Option<QualityLevel>) {
+        if self.power_cap == cap {
+            return;
+        }
+        self.power_cap = cap;
+        let from_quality = self.current_quality.level;
+        let level = match cap {
+            Some(cap) if (from_quality as u8) < cap as u8 => cap,
+            _ => from_quality,
+        };
+        // Reapplied even at the same level, to turn effects off or back on
+        self.apply_quality_preset(level);
+        
+        self.adjustment_history.push(QualityAdjustment {
+            timestamp: Instant::now(),
+            from_quality,
+            to_quality: level,
+            reason: AdjustmentReason::PowerSaving { active: cap.is_some() },
+            metrics_snapshot: PerformanceSnapshot {
+                fps: 0.0,
+                frame_time_ms: 0.0,
+                memory_mb: 0.0,
+                gpu_usage_percent: 0.0,
+            },
+        });
+        self.last_adjustment = Instant::now();
+        log::info!("Power saving {}, quality {:?}", if cap.is_some() { "on" } else { "off" }, level);
+    }
+    
+    /// Whether quality is capped to save power
+    pub fn is_power_saving(&self) -> bool {
+        self.power_cap.is_some()
     }
     
     /// Determine reason for quality adjustment
@@ -437,6 +484,10 @@ impl AdaptiveQualitySystem {
     
     /// Set quality level manually
     pub fn set_quality_level(&mut self, level: QualityLevel) {
+        let level = match self.power_cap {
+            Some(cap) if (level as u8) < cap as u8 => cap,
+            _ => level,
+        };
         self.apply_quality_preset(level);
         
         let adjustment = QualityAdjustment {
@@ -517,4 +568,56 @@ impl Default for AdaptiveQualitySystem {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_effects_off(quality: &RenderQuality) {
+        assert_eq!(quality.post_processing.bloom_intensity, 0.0);
+        assert_eq!(quality.animation_quality, AnimationQuality::Off);
+        assert_eq!(quality.particle_density, 0.0);
+    }
+
+    #[test]
+    fn test_power_saving_caps_quality_and_turns_effects_off() {
+        let mut system = AdaptiveQualitySystem::new();
+        assert_eq!(system.current_quality().level, QualityLevel::High);
+
+        system.set_power_saving(Some(QualityLevel::Low));
+        assert!(system.is_power_saving());
+        assert_eq!(system.current_quality().level, QualityLevel::Low);
+        assert_effects_off(system.current_quality());
+        assert!(matches!(
+            system.get_adjustment_history().last().unwrap().reason,
+            AdjustmentReason::PowerSaving { active: true }
+        ));
+        // Adaptive and manual changes stay under the cap
+        assert_eq!(system.get_higher_quality_level(QualityLevel::Low), None);
+        system.set_quality_level(QualityLevel::Ultra);
+        assert_eq!(system.current_quality().level, QualityLevel::Low);
+        system.set_quality_level(QualityLevel::Performance);
+        assert_eq!(system.current_quality().level, QualityLevel::Performance);
+        assert_effects_off(system.current_quality());
+
+        // The same cap again changes nothing
+        let adjustments = system.get_adjustment_history().len();
+        system.set_power_saving(Some(QualityLevel::Low));
+        assert_eq!(system.get_adjustment_history().len(), adjustments);
+    }
+
+    #[test]
+    fn test_lifting_power_saving_restores_effects() {
+        let mut system = AdaptiveQualitySystem::new();
+        system.set_power_saving(Some(QualityLevel::Medium));
+        system.set_power_saving(None);
+
+        let mut reference = AdaptiveQualitySystem::new();
+        reference.set_quality_level(QualityLevel::Medium);
+        let (quality, expected) = (system.current_quality(), reference.current_quality());
+        assert!(!system.is_power_saving());
+        assert_eq!(quality.level, QualityLevel::Medium);
+        assert_eq!(quality.animation_quality, expected.animation_quality);
+        assert_eq!(quality.post_processing.bloom_intensity, expected.post_processing.bloom_intensity);
+    }
+}
@@ -0,0 +1,320 @@
+//! First-run hardware calibration
+//!
+//! A short benchmark of the work that limits the desktop on a given machine
+//! (building instance data, force-directed layout steps and memory copies)
+//! sorts the machine into a [`PerformanceTier`], which picks the default node
+//! budget, LOD distances, frame rate cap and AI acceleration. The result is
+//! stored so the benchmark only runs again when asked to from settings or
+//! when the benchmark itself changes.
+
+use crate::{InstanceData, InstancingSystem, LodLevel};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use horizonos_graph_engine::Position;
+use horizonos_graph_layout::{ForceDirectedLayout, LayoutAlgorithm, LayoutEdge, LayoutNode};
+use serde::{Deserialize, Serialize};
+use std::hint::black_box;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Version of the benchmark; stored calibrations of other versions are re-run
+pub const CALIBRATION_VERSION: u32 = 1;
+
+/// Raw benchmark scores
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkResults {
+    /// Node instances built and batched per second
+    pub instances_per_second: f64,
+    /// Force-directed layout steps per second on the benchmark graph
+    pub layout_steps_per_second: f64,
+    /// Memory copy bandwidth in GB/s
+    pub memory_bandwidth_gbps: f64,
+    /// Wall time the benchmark took
+    pub duration: Duration,
+}
+
+/// Coarse class of a machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum PerformanceTier {
+    Low,
+    Medium,
+    High,
+}
+
+impl PerformanceTier {
+    /// Tier of the benchmark scores; the slowest score decides, since any of
+    /// them can bottleneck a frame
+    pub fn from_results(results: &BenchmarkResults) -> Self {
+        let tier = |value: f64, medium: f64, high: f64| {
+            if value >= high {
+                PerformanceTier::High
+            } else if value >= medium {
+                PerformanceTier::Medium
+            } else {
+                PerformanceTier::Low
+            }
+        };
+        tier(results.instances_per_second, 2_000_000.0, 10_000_000.0)
+            .min(tier(results.layout_steps_per_second, 20.0, 80.0))
+            .min(tier(results.memory_bandwidth_gbps, 4.0, 12.0))
+    }
+}
+
+/// How local AI models should use the hardware
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AiAcceleration {
+    /// Detect the best option at runtime
+    #[default]
+    Auto,
+    PreferGpu,
+    CpuOnly,
+    /// Small models and few concurrent requests
+    PowerSaving,
+}
+
+/// Settings a calibration recommends
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalibratedDefaults {
+    pub max_nodes: usize,
+    pub lod_distances: [f32; 3],
+    pub max_fps: u32,
+    pub ai_acceleration: AiAcceleration,
+}
+
+impl CalibratedDefaults {
+    pub fn for_tier(tier: PerformanceTier) -> Self {
+        match tier {
+            PerformanceTier::Low => Self {
+                max_nodes: 2_000,
+                lod_distances: [50.0, 200.0, 500.0],
+                max_fps: 30,
+                ai_acceleration: AiAcceleration::PowerSaving,
+            },
+            PerformanceTier::Medium => Self {
+                max_nodes: 10_000,
+                lod_distances: [100.0, 500.0, 1000.0],
+                max_fps: 60,
+                ai_acceleration: AiAcceleration::Auto,
+            },
+            PerformanceTier::High => Self {
+                max_nodes: 50_000,
+                lod_distances: [200.0, 1000.0, 2000.0],
+                max_fps: 120,
+                ai_acceleration: AiAcceleration::Auto,
+            },
+        }
+    }
+}
+
+/// Stored outcome of a benchmark run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Calibration {
+    pub version: u32,
+    pub measured_at: DateTime<Utc>,
+    pub results: BenchmarkResults,
+    pub tier: PerformanceTier,
+    pub defaults: CalibratedDefaults,
+}
+
+impl Calibration {
+    pub fn from_results(results: BenchmarkResults) -> Self {
+        let tier = PerformanceTier::from_results(&results);
+        Self {
+            version: CALIBRATION_VERSION,
+            measured_at: Utc::now(),
+            results,
+            tier,
+            defaults: CalibratedDefaults::for_tier(tier),
+        }
+    }
+
+    /// Whether this was measured by the current benchmark
+    pub fn is_current(&self) -> bool {
+        self.version == CALIBRATION_VERSION
+    }
+
+    /// Read a stored calibration; `None` if there is none yet
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// The calibration benchmark
+#[derive(Debug, Clone)]
+pub struct HardwareBenchmark {
+    /// Time spent on each of the three measurements
+    pub budget: Duration,
+    /// Nodes in the layout benchmark graph
+    pub layout_nodes: usize,
+}
+
+impl Default for HardwareBenchmark {
+    fn default() -> Self {
+        Self {
+            budget: Duration::from_millis(300),
+            layout_nodes: 500,
+        }
+    }
+}
+
+impl HardwareBenchmark {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run the benchmark; blocks for about three times the budget
+    pub fn run(&self) -> BenchmarkResults {
+        let start = Instant::now();
+        let results = BenchmarkResults {
+            instances_per_second: self.measure_instancing(),
+            layout_steps_per_second: self.measure_layout(),
+            memory_bandwidth_gbps: self.measure_memory(),
+            duration: Duration::ZERO,
+        };
+        let results = BenchmarkResults { duration: start.elapsed(), ..results };
+        log::info!("Hardware benchmark finished: {:?}", results);
+        results
+    }
+
+    /// Run the benchmark and derive the recommended settings
+    pub fn calibrate(&self) -> Calibration {
+        Calibration::from_results(self.run())
+    }
+
+    /// Build node instances and batch them by LOD, as each frame does
+    fn measure_instancing(&self) -> f64 {
+        const BATCH: usize = 10_000;
+        let system = InstancingSystem::new();
+        let lods = [LodLevel::High, LodLevel::Medium, LodLevel::Low, LodLevel::VeryLow];
+        let mut instances: Vec<InstanceData> = Vec::with_capacity(BATCH);
+        let mut built = 0usize;
+        let start = Instant::now();
+        while start.elapsed() < self.budget {
+            instances.clear();
+            for i in 0..BATCH {
+                let position = [i as f32, (i % 100) as f32, 0.0];
+                instances.push(system.create_node_instance(i as u64, position, 1.0, [1.0; 4], lods[i % lods.len()]));
+            }
+            black_box(bytemuck::cast_slice::<InstanceData, u8>(&instances));
+            black_box(system.batch_instances(&instances));
+            built += BATCH;
+        }
+        built as f64 / start.elapsed().as_secs_f64()
+    }
+
+    /// Step a force-directed layout of a ring graph with chords
+    fn measure_layout(&self) -> f64 {
+        let count = self.layout_nodes.max(2);
+        let mut nodes: Vec<LayoutNode> = (0..count)
+            .map(|i| {
+                let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+                LayoutNode::new(i as u64, Position::new(angle.cos() * 100.0, angle.sin() * 100.0, (i % 7) as f32))
+            })
+            .collect();
+        let edges: Vec<LayoutEdge> = (0..count)
+            .flat_map(|i| {
+                [
+                    LayoutEdge::new(i as u64, ((i + 1) % count) as u64, 1.0),
+                    LayoutEdge::new(i as u64, ((i * 7 + 3) % count) as u64, 0.5),
+                ]
+            })
+            .collect();
+        let layout = ForceDirectedLayout::new();
+        let mut steps = 0u32;
+        let start = Instant::now();
+        while start.elapsed() < self.budget {
+            if layout.update_layout(&mut nodes, &edges, 1.0 / 60.0).is_err() {
+                break;
+            }
+            steps += 1;
+        }
+        steps as f64 / start.elapsed().as_secs_f64()
+    }
+
+    /// Copy a buffer larger than typical caches
+    fn measure_memory(&self) -> f64 {
+        const SIZE: usize = 64 * 1024 * 1024;
+        let source = vec![1u8; SIZE];
+        let mut target = vec![0u8; SIZE];
+        let mut copied = 0usize;
+        let start = Instant::now();
+        while start.elapsed() < self.budget {
+            target.copy_from_slice(black_box(&source));
+            black_box(&mut target);
+            copied += SIZE;
+        }
+        copied as f64 / start.elapsed().as_secs_f64() / 1e9
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn results(instances_per_second: f64, layout_steps_per_second: f64, memory_bandwidth_gbps: f64) -> BenchmarkResults {
+        BenchmarkResults {
+            instances_per_second,
+            layout_steps_per_second,
+            memory_bandwidth_gbps,
+            duration: Duration::from_millis(900),
+        }
+    }
+
+    #[test]
+    fn test_tier_thresholds() {
+        assert_eq!(PerformanceTier::from_results(&results(10_000_000.0, 80.0, 12.0)), PerformanceTier::High);
+        assert_eq!(PerformanceTier::from_results(&results(9_999_999.0, 200.0, 30.0)), PerformanceTier::Medium);
+        assert_eq!(PerformanceTier::from_results(&results(2_000_000.0, 20.0, 4.0)), PerformanceTier::Medium);
+        assert_eq!(PerformanceTier::from_results(&results(1_999_999.0, 20.0, 4.0)), PerformanceTier::Low);
+    }
+
+    #[test]
+    fn test_slowest_score_decides_the_tier() {
+        // Fast instancing and memory cannot make up for slow layout
+        assert_eq!(PerformanceTier::from_results(&results(50_000_000.0, 10.0, 40.0)), PerformanceTier::Low);
+        assert_eq!(PerformanceTier::from_results(&results(50_000_000.0, 100.0, 5.0)), PerformanceTier::Medium);
+    }
+
+    #[test]
+    fn test_calibration_recommends_the_defaults_of_its_tier() {
+        let calibration = Calibration::from_results(results(3_000_000.0, 30.0, 3.0));
+        assert!(calibration.is_current());
+        assert_eq!(calibration.tier, PerformanceTier::Low);
+        assert_eq!(calibration.defaults, CalibratedDefaults::for_tier(PerformanceTier::Low));
+        assert_eq!(calibration.defaults.ai_acceleration, AiAcceleration::PowerSaving);
+        assert!(CalibratedDefaults::for_tier(PerformanceTier::High).max_nodes > calibration.defaults.max_nodes);
+    }
+
+    #[test]
+    fn test_stored_calibration_round_trips() {
+        let path = std::env::temp_dir()
+            .join(format!("horizonos-calibration-{}", std::process::id()))
+            .join("calibration.json");
+        assert!(Calibration::load(&path).unwrap().is_none());
+
+        let mut calibration = Calibration::from_results(results(20_000_000.0, 100.0, 20.0));
+        calibration.save(&path).unwrap();
+        let loaded = Calibration::load(&path).unwrap().unwrap();
+        assert_eq!(loaded.tier, PerformanceTier::High);
+        assert_eq!(loaded.defaults, calibration.defaults);
+        assert!(loaded.is_current());
+
+        // Calibrations of an older benchmark are run again
+        calibration.version = CALIBRATION_VERSION - 1;
+        calibration.save(&path).unwrap();
+        assert!(!Calibration::load(&path).unwrap().unwrap().is_current());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -40,14 +40,22 @@ pub struct OcclusionCuller {
 
 /// Visibility cache to avoid recalculating visibility
 struct VisibilityCache {
-    /// Cached visibility results
-    cache: HashMap<(SceneId, u64), bool>, // (node_id, frame_hash)
-    /// Current frame hash for cache invalidation
-    current_frame_hash: u64,
+    /// Last result per node, valid while neither the camera nor the node moves
+    cache: HashMap<SceneId, CachedVisibility>,
+    /// Current frame, for evicting stale entries
+    current_frame: u64,
     /// Cache hit/miss statistics
     cache_stats: CacheStats,
 }
 
+/// Visibility of a node for one camera state and node position
+struct CachedVisibility {
+    camera_hash: u64,
+    position: Point3<f32>,
+    visible: bool,
+    frame: u64,
+}
+
 /// Geometric plane for frustum culling
 #[derive(Debug, Clone)]
 pub struct Plane {
@@ -140,15 +148,29 @@ impl CullingSystem {
     }
     
     /// Get all visible nodes after culling
+    ///
+    /// Frustum culling queries the scene's spatial index, so nodes outside
+    /// the frustum are skipped without being tested one by one.
     pub fn get_visible_nodes(&mut self, scene: &Scene, camera: &Camera) -> Vec<SceneId> {
         let mut visible_nodes = Vec::new();
-        let all_nodes = scene.get_all_nodes();
+        let candidates = if self.frustum_culler.enabled {
+            let frustum = &self.frustum_culler;
+            let in_frustum = scene.find_nodes_in_bounds(|bounds| frustum.is_box_visible(&padded_bounds(bounds)));
+            let total = scene.get_all_nodes().len() as u32;
+            self.stats.nodes_tested += total;
+            self.stats.frustum_culled += total.saturating_sub(in_frustum.len() as u32);
+            in_frustum
+        } else {
+            let all_nodes = scene.get_all_nodes();
+            self.stats.nodes_tested += all_nodes.len() as u32;
+            all_nodes
+        };
         
-        for &node_id in &all_nodes {
-            self.stats.nodes_tested += 1;
+        for node_id in candidates {
+            let position = scene.get_node_position(node_id).unwrap_or_else(Point3::origin);
             
             // Check visibility cache first
-            if let Some(cached_visible) = self.visibility_cache.get_cached_visibility(node_id, camera) {
+            if let Some(cached_visible) = self.visibility_cache.get_cached_visibility(node_id, camera, position) {
                 if cached_visible {
                     visible_nodes.push(node_id);
                     self.stats.visible_nodes += 1;
@@ -156,22 +178,11 @@ impl CullingSystem {
                 continue;
             }
             
-            // Check frustum culling
-            if self.frustum_culler.enabled {
-                if let Some(bounding_box) = self.get_node_bounding_box(node_id, scene) {
-                    if !self.frustum_culler.is_box_visible(&bounding_box) {
-                        self.stats.frustum_culled += 1;
-                        self.visibility_cache.cache_visibility(node_id, camera, false);
-                        continue;
-                    }
-                }
-            }
-            
             // Check occlusion culling
             if self.occlusion_culler.enabled {
                 if !self.occlusion_culler.is_node_visible(node_id, scene) {
                     self.stats.occlusion_culled += 1;
-                    self.visibility_cache.cache_visibility(node_id, camera, false);
+                    self.visibility_cache.cache_visibility(node_id, camera, position, false);
                     continue;
                 }
             }
@@ -179,7 +190,7 @@ impl CullingSystem {
             // Node is visible
             visible_nodes.push(node_id);
             self.stats.visible_nodes += 1;
-            self.visibility_cache.cache_visibility(node_id, camera, true);
+            self.visibility_cache.cache_visibility(node_id, camera, position, true);
         }
         
         // Update cache statistics
@@ -188,19 +199,9 @@ impl CullingSystem {
         visible_nodes
     }
     
-    /// Get bounding box for a node
-    fn get_node_bounding_box(&self, node_id: SceneId, scene: &Scene) -> Option<BoundingBox> {
-        if let Some(position) = scene.get_node_position(node_id) {
-            // For now, use a simple bounding box around the node position
-            // In a real implementation, this would come from the node's geometry
-            let size = 10.0; // Default node size
-            Some(BoundingBox {
-                min: Point3::new(position.x - size, position.y - size, position.z - size),
-                max: Point3::new(position.x + size, position.y + size, position.z + size),
-            })
-        } else {
-            None
-        }
+    /// Whether any culling is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.frustum_culler.enabled || self.occlusion_culler.enabled
     }
     
     /// Enable or disable frustum culling
@@ -224,6 +225,18 @@ impl CullingSystem {
     }
 }
 
+/// Margin around node bounds when testing them against the frustum
+const NODE_BOUNDS_PADDING: f32 = 10.0;
+
+/// Node bounds from the scene's spatial index, padded for frustum tests
+fn padded_bounds(bounds: &horizonos_graph_engine::BoundingBox) -> BoundingBox {
+    let padded = bounds.expanded(NODE_BOUNDS_PADDING);
+    BoundingBox {
+        min: padded.min,
+        max: padded.max,
+    }
+}
+
 impl FrustumCuller {
     /// Create a new frustum culler
     pub fn new() -> Self {
@@ -236,7 +249,7 @@ impl FrustumCuller {
     
     /// Update frustum from camera
     pub fn update_from_camera(&mut self, camera: &Camera) {
-        let view_proj = camera.view_matrix() * camera.projection_matrix();
+        let view_proj = camera.view_projection_matrix();
         self.extract_frustum_planes(&view_proj);
         self.calculate_frustum_corners(camera);
     }
@@ -442,60 +455,63 @@ impl VisibilityCache {
     pub fn new() -> Self {
         Self {
             cache: HashMap::new(),
-            current_frame_hash: 0,
+            current_frame: 0,
             cache_stats: CacheStats::default(),
         }
     }
     
-    /// Update frame hash for cache invalidation
+    /// Advance to the next frame, evicting entries of nodes not seen lately
     pub fn update_frame(&mut self) {
-        self.current_frame_hash = self.current_frame_hash.wrapping_add(1);
+        self.current_frame = self.current_frame.wrapping_add(1);
+        self.cache_stats = CacheStats::default();
         
         // Clear old cache entries periodically
-        if self.current_frame_hash % 60 == 0 {
-            self.cache.retain(|&(_, frame_hash), _| {
-                self.current_frame_hash - frame_hash < 10 // Keep last 10 frames
-            });
+        if self.current_frame.is_multiple_of(60) {
+            let current_frame = self.current_frame;
+            self.cache.retain(|_, entry| current_frame.wrapping_sub(entry.frame) < 10); // Keep last 10 frames
         }
     }
     
     /// Get cached visibility result
-    pub fn get_cached_visibility(&mut self, node_id: SceneId, camera: &Camera) -> Option<bool> {
+    pub fn get_cached_visibility(&mut self, node_id: SceneId, camera: &Camera, position: Point3<f32>) -> Option<bool> {
         let camera_hash = self.hash_camera_state(camera);
-        let key = (node_id, camera_hash);
         
-        if let Some(&visible) = self.cache.get(&key) {
-            self.cache_stats.hits += 1;
-            Some(visible)
-        } else {
-            self.cache_stats.misses += 1;
-            None
+        match self.cache.get_mut(&node_id) {
+            Some(entry) if entry.camera_hash == camera_hash && entry.position == position => {
+                entry.frame = self.current_frame;
+                self.cache_stats.hits += 1;
+                Some(entry.visible)
+            }
+            _ => {
+                self.cache_stats.misses += 1;
+                None
+            }
         }
     }
     
     /// Cache visibility result
-    pub fn cache_visibility(&mut self, node_id: SceneId, camera: &Camera, visible: bool) {
+    pub fn cache_visibility(&mut self, node_id: SceneId, camera: &Camera, position: Point3<f32>, visible: bool) {
         let camera_hash = self.hash_camera_state(camera);
-        let key = (node_id, camera_hash);
-        self.cache.insert(key, visible);
+        self.cache.insert(node_id, CachedVisibility {
+            camera_hash,
+            position,
+            visible,
+            frame: self.current_frame,
+        });
     }
     
     /// Hash camera state for cache key
     fn hash_camera_state(&self, camera: &Camera) -> u64 {
-        // Simple hash based on camera position and orientation
-        // In practice, you'd want a more sophisticated hash
-        let pos = camera.position;
-        let forward = camera.forward;
-        
-        let mut hash = 0u64;
-        hash ^= (pos.x as u64).wrapping_mul(73856093);
-        hash ^= (pos.y as u64).wrapping_mul(19349663);
-        hash ^= (pos.z as u64).wrapping_mul(83492791);
-        hash ^= (forward.x as u64).wrapping_mul(73856093);
-        hash ^= (forward.y as u64).wrapping_mul(19349663);
-        hash ^= (forward.z as u64).wrapping_mul(83492791);
-        
-        hash
+        // Exact bits, so any camera movement invalidates the cache
+        let values = [
+            camera.position.x, camera.position.y, camera.position.z,
+            camera.forward.x, camera.forward.y, camera.forward.z,
+            camera.up.x, camera.up.y, camera.up.z,
+            camera.fov, camera.aspect_ratio, camera.near, camera.far,
+        ];
+        values.iter().fold(0xcbf29ce484222325u64, |hash, value| {
+            (hash ^ value.to_bits() as u64).wrapping_mul(0x100000001b3)
+        })
     }
     
     /// Get cache hit rate
@@ -1,6 +1,6 @@
 //! GPU instancing optimization for batch rendering
 
-use horizonos_graph_engine::SceneId;
+use horizonos_graph_engine::{IconSlot, NodeBatches, Scene, SceneId, VisibleNodes};
 use crate::lod::LodLevel;
 use std::collections::HashMap;
 use wgpu::Buffer;
@@ -14,6 +14,10 @@ pub struct InstancingSystem {
     batch_configs: HashMap<InstanceType, BatchConfig>,
     /// Instance data staging area
     staging_data: HashMap<InstanceType, Vec<InstanceData>>,
+    /// Atlas slots of nodes drawn as icons; other nodes are drawn as spheres
+    icon_slots: HashMap<SceneId, IconSlot>,
+    /// Node instances and instanced draws of the last built batches
+    node_batch_counts: (u32, u32),
     /// Enable instancing optimization
     enabled: bool,
 }
@@ -167,6 +171,8 @@ impl InstancingSystem {
             instance_buffers: HashMap::new(),
             batch_configs: Self::default_configs(),
             staging_data: HashMap::new(),
+            icon_slots: HashMap::new(),
+            node_batch_counts: (0, 0),
             enabled: true,
         }
     }
@@ -227,6 +233,39 @@ impl InstancingSystem {
         self.batch_configs.insert(instance_type, config);
     }
     
+    /// Draw a node as an icon from the atlas instead of a sphere
+    pub fn set_node_icon(&mut self, node_id: SceneId, slot: IconSlot) {
+        self.icon_slots.insert(node_id, slot);
+    }
+    
+    /// Draw a node as a sphere again
+    pub fn clear_node_icon(&mut self, node_id: SceneId) {
+        self.icon_slots.remove(&node_id);
+    }
+    
+    /// Group the visible nodes into instanced batches for the renderer
+    ///
+    /// Sphere nodes share one batch and icon nodes get a batch per atlas
+    /// page, so each batch is drawn with a single instanced draw. Icons of
+    /// removed nodes are forgotten.
+    pub fn build_node_batches(&mut self, scene: &Scene, visible: &VisibleNodes) -> NodeBatches {
+        self.icon_slots.retain(|id, _| scene.get_node(*id).is_some());
+        
+        let mut batches = NodeBatches::new();
+        for (&id, node) in scene.nodes() {
+            if !node.visible || !visible.contains(id) {
+                continue;
+            }
+            match self.icon_slots.get(&id) {
+                Some(slot) => batches.push_icon(node, *slot),
+                None => batches.push_sphere(node),
+            }
+        }
+        
+        self.node_batch_counts = (batches.instance_count() as u32, batches.draw_calls() as u32);
+        batches
+    }
+    
     /// Create instance data from node information
     pub fn create_node_instance(&self, node_id: SceneId, position: [f32; 3], scale: f32, color: [f32; 4], lod: LodLevel) -> InstanceData {
         // Create transformation matrix
@@ -316,6 +355,11 @@ impl InstancingSystem {
         // Estimate draw calls saved (rough approximation)
         stats.draw_calls_saved = stats.total_instances.saturating_sub(self.instance_buffers.len() as u32);
         
+        // Node batches replace a draw per node with a draw per batch
+        let (node_instances, node_draws) = self.node_batch_counts;
+        stats.total_instances += node_instances;
+        stats.draw_calls_saved += node_instances.saturating_sub(node_draws);
+        
         stats
     }
     
@@ -7,25 +7,33 @@
 //! - GPU instancing optimization for batch rendering
 //! - Memory pooling and cache management
 //! - Performance monitoring and metrics
+//! - First-run hardware calibration of the defaults
+//! - A power-saving render path on battery or with the power saver profile
 
 pub mod lod;
+pub mod lod_appearance;
 pub mod culling;
 pub mod adaptive;
 pub mod instancing;
 pub mod memory;
 pub mod metrics;
 pub mod cache;
+pub mod calibration;
+pub mod power;
 
 pub use lod::*;
+pub use lod_appearance::*;
 pub use culling::*;
 pub use adaptive::*;
 pub use instancing::*;
 pub use memory::*;
 pub use metrics::*;
 pub use cache::*;
+pub use calibration::*;
+pub use power::*;
 
-use horizonos_graph_engine::{GraphEngine, SceneId, Camera};
-use std::time::Instant;
+use horizonos_graph_engine::{GraphEngine, SceneId, Camera, CameraState, VisibleNodes};
+use std::time::{Duration, Instant};
 
 /// Main performance manager that coordinates all optimization systems
 pub struct PerformanceManager {
@@ -47,6 +55,16 @@ pub struct PerformanceManager {
     last_update: Instant,
     /// Performance targets
     targets: PerformanceTargets,
+    /// Whether running on battery or with the power saver profile
+    power_state: PowerState,
+    /// What changes while saving power
+    power_policy: PowerSavingPolicy,
+    /// Last camera move, input or change in node count
+    last_activity: Instant,
+    /// Camera and node count of the last update, to notice activity
+    last_view: Option<(CameraState, usize)>,
+    /// Whether physics was paused for an idle scene
+    physics_paused: bool,
 }
 
 /// Performance targets and thresholds
@@ -75,6 +93,11 @@ impl PerformanceManager {
             cache_system: RenderCache::new(),
             last_update: Instant::now(),
             targets: PerformanceTargets::default(),
+            power_state: PowerState::default(),
+            power_policy: PowerSavingPolicy::default(),
+            last_activity: Instant::now(),
+            last_view: None,
+            physics_paused: false,
         }
     }
     
@@ -86,15 +109,30 @@ impl PerformanceManager {
         
         // Collect performance metrics
         self.metrics.update(delta_time);
+        let targets = self.effective_targets();
         
         // Update LOD system based on performance
-        self.lod_system.update(camera, &self.metrics, &self.targets);
+        self.lod_system.update(camera, &self.metrics, &targets);
         
-        // Update culling system
+        // Counters of the frame drawn with the previous culling results
+        self.metrics.record_draw_stats(engine.draw_stats());
+        
+        // Update culling system and hand the surviving nodes to the renderer, batched for instanced draws
         self.culling_system.update(camera, engine.scene());
+        let visible = if self.culling_system.is_enabled() {
+            VisibleNodes::only(self.culling_system.get_visible_nodes(engine.scene(), camera))
+        } else {
+            VisibleNodes::all()
+        };
+        let batches = self.instancing_system.build_node_batches(engine.scene(), &visible);
+        engine.set_node_batches(batches);
+        engine.set_visible_nodes(visible);
         
         // Update adaptive quality based on performance
-        self.adaptive_system.update(&self.metrics, &self.targets);
+        self.adaptive_system.update(&self.metrics, &targets);
+        
+        // Idle scenes need no physics while saving power
+        self.update_physics_pause(engine, camera, now);
         
         // Update memory management
         self.memory_system.update(&self.metrics);
@@ -116,6 +154,11 @@ impl PerformanceManager {
         self.lod_system.get_node_lod(node_id, camera, engine.scene())
     }
     
+    /// Instancing system, e.g. to assign nodes icons from the atlas
+    pub fn instancing(&mut self) -> &mut InstancingSystem {
+        &mut self.instancing_system
+    }
+    
     /// Get render quality settings
     pub fn get_render_quality(&self) -> &RenderQuality {
         self.adaptive_system.current_quality()
@@ -133,8 +176,77 @@ impl PerformanceManager {
     
     /// Check if performance is meeting targets
     pub fn is_performance_good(&self) -> bool {
-        self.metrics.current_fps() >= self.targets.target_fps * 0.9 &&
-        self.metrics.current_frame_time() <= self.targets.max_frame_time * 1.1
+        let targets = self.effective_targets();
+        self.metrics.current_fps() >= targets.target_fps * 0.9 &&
+        self.metrics.current_frame_time() <= targets.max_frame_time * 1.1
+    }
+    
+    /// Set the power state, switching the power-saving render path on or off
+    pub fn set_power_state(&mut self, state: PowerState) {
+        self.power_state = state;
+        let cap = state.saves_power().then_some(self.power_policy.max_quality);
+        self.adaptive_system.set_power_saving(cap);
+    }
+    
+    /// Current power state
+    pub fn power_state(&self) -> PowerState {
+        self.power_state
+    }
+    
+    /// Configure what changes while saving power
+    pub fn set_power_policy(&mut self, policy: PowerSavingPolicy) {
+        self.power_policy = policy;
+        self.set_power_state(self.power_state);
+    }
+    
+    /// Whether the power-saving render path is active
+    pub fn is_power_saving(&self) -> bool {
+        self.power_state.saves_power()
+    }
+    
+    /// Time to leave between frames for the current target frame rate
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_secs_f32(1.0 / self.effective_targets().target_fps.max(1.0))
+    }
+    
+    /// AI monitoring sampling rate for a configured rate, reduced while saving power
+    pub fn ai_sampling_rate(&self, rate: u32) -> u32 {
+        if self.is_power_saving() {
+            self.power_policy.ai_sampling_rate(rate)
+        } else {
+            rate
+        }
+    }
+    
+    /// Note user input, which resumes physics paused for an idle scene
+    pub fn note_activity(&mut self) {
+        self.last_activity = Instant::now();
+    }
+    
+    /// Targets lowered to the power-saving frame rate while saving power
+    fn effective_targets(&self) -> PerformanceTargets {
+        let mut targets = self.targets.clone();
+        if self.is_power_saving() && self.power_policy.target_fps < targets.target_fps {
+            targets.target_fps = self.power_policy.target_fps;
+            targets.max_frame_time = 1000.0 / self.power_policy.target_fps;
+        }
+        targets
+    }
+    
+    /// Pause physics once the scene was idle for a while when saving power, resume it on activity
+    fn update_physics_pause(&mut self, engine: &mut GraphEngine, camera: &Camera, now: Instant) {
+        let view = (camera.state(), engine.scene().nodes().count());
+        if self.last_view.as_ref() != Some(&view) {
+            self.last_view = Some(view);
+            self.last_activity = now;
+        }
+        
+        let pause = self.is_power_saving() && now.duration_since(self.last_activity) >= self.power_policy.physics_idle_after;
+        if pause != self.physics_paused {
+            self.physics_paused = pause;
+            engine.physics_mut().set_paused(pause);
+            log::debug!("Physics {} for idle scene", if pause { "paused" } else { "resumed" });
+        }
     }
     
     /// Get memory usage information
@@ -150,14 +262,15 @@ impl PerformanceManager {
     
     /// Check for performance warnings
     fn check_performance_warnings(&self) {
-        if self.metrics.current_fps() < self.targets.target_fps * 0.7 {
+        let targets = self.effective_targets();
+        if self.metrics.current_fps() < targets.target_fps * 0.7 {
             log::warn!("Performance warning: FPS {} below target {}", 
-                self.metrics.current_fps(), self.targets.target_fps);
+                self.metrics.current_fps(), targets.target_fps);
         }
         
-        if self.metrics.current_frame_time() > self.targets.max_frame_time * 1.5 {
+        if self.metrics.current_frame_time() > targets.max_frame_time * 1.5 {
             log::warn!("Performance warning: Frame time {:.2}ms above target {:.2}ms",
-                self.metrics.current_frame_time(), self.targets.max_frame_time);
+                self.metrics.current_frame_time(), targets.max_frame_time);
         }
         
         let memory_mb = self.memory_system.get_info().used_mb;
@@ -183,4 +296,48 @@ impl Default for PerformanceManager {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ON_BATTERY: PowerState = PowerState { on_battery: true, power_saver: false };
+
+    #[test]
+    fn test_power_saving_lowers_frame_rate_and_ai_sampling() {
+        let mut manager = PerformanceManager::new();
+        assert_eq!(manager.frame_interval(), Duration::from_secs_f32(1.0 / 60.0));
+        assert_eq!(manager.ai_sampling_rate(10), 10);
+
+        manager.set_power_state(ON_BATTERY);
+        assert!(manager.is_power_saving());
+        assert_eq!(manager.frame_interval(), Duration::from_secs_f32(1.0 / 30.0));
+        assert!((manager.effective_targets().max_frame_time - 1000.0 / 30.0).abs() < 1e-3);
+        assert_eq!(manager.ai_sampling_rate(10), 2);
+        assert_eq!(manager.adaptive_system.current_quality().level, QualityLevel::Low);
+
+        manager.set_power_state(PowerState::default());
+        assert!(!manager.is_power_saving());
+        assert_eq!(manager.frame_interval(), Duration::from_secs_f32(1.0 / 60.0));
+        assert_eq!(manager.ai_sampling_rate(10), 10);
+        assert!(!manager.adaptive_system.is_power_saving());
+    }
+
+    #[test]
+    fn test_power_policy_never_raises_targets() {
+        let mut manager = PerformanceManager::new();
+        manager.set_power_policy(PowerSavingPolicy {
+            target_fps: 90.0,
+            max_quality: QualityLevel::Medium,
+            ..PowerSavingPolicy::default()
+        });
+        manager.set_power_state(PowerState { on_battery: false, power_saver: true });
+
+        assert_eq!(manager.frame_interval(), Duration::from_secs_f32(1.0 / 60.0));
+        assert_eq!(manager.adaptive_system.current_quality().level, QualityLevel::Medium);
+        // A new policy applies to the current power state right away
+        manager.set_power_policy(PowerSavingPolicy::default());
+        assert_eq!(manager.frame_interval(), Duration::from_secs_f32(1.0 / 30.0));
+        assert_eq!(manager.adaptive_system.current_quality().level, QualityLevel::Low);
+    }
+}
@@ -1,9 +1,11 @@
 //! Level-of-detail (LOD) system for scalable graph rendering
 
 use horizonos_graph_engine::{SceneId, Scene, Camera};
-use crate::{PerformanceMetrics, PerformanceTargets};
+use crate::{LodAppearance, LodPolicy, NodeAppearance, PerformanceMetrics, PerformanceTargets};
 use nalgebra::Point3;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 
 /// Level-of-detail system
 pub struct LodSystem {
@@ -15,6 +17,8 @@ pub struct LodSystem {
     distance_cache: HashMap<SceneId, f32>,
     /// Adaptive LOD settings
     adaptive_settings: AdaptiveLodSettings,
+    /// How each node type looks at each tier
+    appearance: LodAppearance,
 }
 
 /// LOD configuration for a distance range
@@ -29,7 +33,8 @@ pub struct LodConfig {
 }
 
 /// LOD levels for rendering
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum LodLevel {
     /// Highest quality - full detail
     High,
@@ -43,6 +48,19 @@ pub enum LodLevel {
     Culled,
 }
 
+impl LodLevel {
+    /// Rank of the level, higher meaning more detail
+    pub fn detail(self) -> u8 {
+        match self {
+            LodLevel::High => 4,
+            LodLevel::Medium => 3,
+            LodLevel::Low => 2,
+            LodLevel::VeryLow => 1,
+            LodLevel::Culled => 0,
+        }
+    }
+}
+
 /// Adaptive LOD settings that adjust based on performance
 #[derive(Debug, Clone)]
 pub struct AdaptiveLodSettings {
@@ -66,6 +84,7 @@ impl LodSystem {
             node_overrides: HashMap::new(),
             distance_cache: HashMap::new(),
             adaptive_settings: AdaptiveLodSettings::default(),
+            appearance: LodAppearance::default(),
         }
     }
     
@@ -87,13 +106,25 @@ impl LodSystem {
             return override_lod;
         }
         
-        // Calculate distance to camera
-        let distance = self.calculate_distance(node_id, camera, scene);
+        // Calculate distance to camera, as seen by the node type's policy
+        let mut distance = self.calculate_distance(node_id, camera, scene);
+        if let Some(node) = scene.get_node(node_id) {
+            distance = self.appearance.policy_for(&node.node_type).scaled_distance(distance);
+        }
         
         // Determine LOD level based on distance and configuration
         self.determine_lod_level(distance)
     }
     
+    /// Get how a node is drawn, from its LOD level and its node type's policy
+    pub fn get_node_appearance(&mut self, node_id: SceneId, camera: &Camera, scene: &Scene) -> NodeAppearance {
+        let level = self.get_node_lod(node_id, camera, scene);
+        match scene.get_node(node_id) {
+            Some(node) => self.appearance.policy_for(&node.node_type).appearance(level),
+            None => self.appearance.default.appearance(level),
+        }
+    }
+    
     /// Calculate distance from node to camera
     fn calculate_distance(&mut self, node_id: SceneId, camera: &Camera, scene: &Scene) -> f32 {
         if let Some(&cached_distance) = self.distance_cache.get(&node_id) {
@@ -204,6 +235,7 @@ impl LodSystem {
                 enable_shadows: true,
                 enable_reflections: true,
                 enable_animations: true,
+                live_texture_interval: Some(Duration::from_millis(33)),
             },
             LodLevel::Medium => RenderComplexity {
                 vertex_count_multiplier: 0.7,
@@ -212,6 +244,7 @@ impl LodSystem {
                 enable_shadows: true,
                 enable_reflections: false,
                 enable_animations: true,
+                live_texture_interval: Some(Duration::from_millis(100)),
             },
             LodLevel::Low => RenderComplexity {
                 vertex_count_multiplier: 0.4,
@@ -220,6 +253,7 @@ impl LodSystem {
                 enable_shadows: false,
                 enable_reflections: false,
                 enable_animations: false,
+                live_texture_interval: Some(Duration::from_millis(500)),
             },
             LodLevel::VeryLow => RenderComplexity {
                 vertex_count_multiplier: 0.1,
@@ -228,6 +262,7 @@ impl LodSystem {
                 enable_shadows: false,
                 enable_reflections: false,
                 enable_animations: false,
+                live_texture_interval: None,
             },
             LodLevel::Culled => RenderComplexity {
                 vertex_count_multiplier: 0.0,
@@ -236,6 +271,7 @@ impl LodSystem {
                 enable_shadows: false,
                 enable_reflections: false,
                 enable_animations: false,
+                live_texture_interval: None,
             },
         }
     }
@@ -285,6 +321,31 @@ impl LodSystem {
     pub fn configure_adaptive(&mut self, settings: AdaptiveLodSettings) {
         self.adaptive_settings = settings;
     }
+    
+    /// Get the per-node-type appearance policies
+    pub fn appearance(&self) -> &LodAppearance {
+        &self.appearance
+    }
+    
+    /// Replace the per-node-type appearance policies; applies from the next lookup
+    pub fn set_appearance(&mut self, appearance: LodAppearance) {
+        self.appearance = appearance;
+    }
+    
+    /// Get the policy of a node type, by its palette name (`application`, `concept`, ...)
+    pub fn type_policy(&self, node_type: &str) -> &LodPolicy {
+        self.appearance.policy(node_type)
+    }
+    
+    /// Set the policy of a node type, by its palette name; applies from the next lookup
+    pub fn set_type_policy(&mut self, node_type: &str, policy: LodPolicy) {
+        self.appearance.node_types.insert(node_type.to_string(), policy);
+    }
+    
+    /// Let a node type fall back to the default policy, returning the policy it had
+    pub fn remove_type_policy(&mut self, node_type: &str) -> Option<LodPolicy> {
+        self.appearance.node_types.remove(node_type)
+    }
 }
 
 /// Render complexity settings for different LOD levels
@@ -302,6 +363,9 @@ pub struct RenderComplexity {
     pub enable_reflections: bool,
     /// Whether to render animations
     pub enable_animations: bool,
+    /// How often live textures such as window thumbnails are refreshed;
+    /// `None` keeps the last captured image
+    pub live_texture_interval: Option<Duration>,
 }
 
 /// Effect quality levels
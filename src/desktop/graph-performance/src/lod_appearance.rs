@@ -0,0 +1,151 @@
+//! Per-node-type appearance across LOD tiers
+//!
+//! The LOD tier of a node says how much detail it can afford; the policy of
+//! its node type decides how soon it drops to a lower tier and what each
+//! tier looks like: whether it shrinks to a dot, shows an icon or a live
+//! thumbnail, and at which tiers its label is drawn. Policies are keyed by the palette's node type names (`application`,
+//! `file`, ...), with a default for the types without one.
+
+use crate::LodLevel;
+use horizonos_graph_engine::{node_kind, NodeType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How a node is drawn, from least to most detailed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Representation {
+    /// A point in the node's color
+    Dot,
+    /// The node's icon
+    Icon,
+    /// Live thumbnail, e.g. of an application window
+    Thumbnail,
+    /// Full node with its content
+    Full,
+}
+
+/// Whether the label is drawn at each tier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LabelTiers {
+    pub high: bool,
+    pub medium: bool,
+    pub low: bool,
+    pub very_low: bool,
+}
+
+impl Default for LabelTiers {
+    fn default() -> Self {
+        Self { high: true, medium: true, low: false, very_low: false }
+    }
+}
+
+impl LabelTiers {
+    pub fn shown_at(&self, level: LodLevel) -> bool {
+        match level {
+            LodLevel::High => self.high,
+            LodLevel::Medium => self.medium,
+            LodLevel::Low => self.low,
+            LodLevel::VeryLow => self.very_low,
+            LodLevel::Culled => false,
+        }
+    }
+}
+
+/// Appearance of one node type across LOD tiers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LodPolicy {
+    /// Scale of the LOD distances for this type; above 1 keeps detail
+    /// further away, below 1 gives it up closer to the camera
+    pub distance_scale: f32,
+    /// Least detailed representation used however far the node is
+    pub min_representation: Representation,
+    /// Least detailed tier still showing a live thumbnail instead of an icon
+    pub thumbnail_from: LodLevel,
+    /// Most detailed tier already drawn as a dot
+    pub dot_from: LodLevel,
+    pub labels: LabelTiers,
+}
+
+impl Default for LodPolicy {
+    fn default() -> Self {
+        Self {
+            distance_scale: 1.0,
+            min_representation: Representation::Dot,
+            thumbnail_from: LodLevel::Medium,
+            dot_from: LodLevel::VeryLow,
+            labels: LabelTiers::default(),
+        }
+    }
+}
+
+impl LodPolicy {
+    /// Representation at a tier, `None` when the node is not drawn
+    pub fn representation(&self, level: LodLevel) -> Option<Representation> {
+        let representation = match level {
+            LodLevel::Culled => return None,
+            LodLevel::High => Representation::Full,
+            _ if level.detail() >= self.thumbnail_from.detail() => Representation::Thumbnail,
+            _ if level.detail() <= self.dot_from.detail() => Representation::Dot,
+            _ => Representation::Icon,
+        };
+        Some(representation.max(self.min_representation))
+    }
+
+    /// Distance to the camera as the LOD tiers see it for this type
+    pub fn scaled_distance(&self, distance: f32) -> f32 {
+        if self.distance_scale > 0.0 {
+            distance / self.distance_scale
+        } else {
+            distance
+        }
+    }
+
+    /// Resolved appearance at a tier
+    pub fn appearance(&self, level: LodLevel) -> NodeAppearance {
+        let representation = self.representation(level);
+        NodeAppearance {
+            level,
+            representation,
+            show_label: representation.is_some() && self.labels.shown_at(level),
+        }
+    }
+}
+
+/// How one node is drawn this frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeAppearance {
+    pub level: LodLevel,
+    /// `None` when the node is not drawn
+    pub representation: Option<Representation>,
+    pub show_label: bool,
+}
+
+impl NodeAppearance {
+    /// Whether the node shows a live texture such as a window thumbnail
+    pub fn live_texture(&self) -> bool {
+        matches!(self.representation, Some(Representation::Thumbnail | Representation::Full))
+    }
+}
+
+/// LOD policies by node type
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LodAppearance {
+    /// Policy of node types without their own
+    pub default: LodPolicy,
+    /// Policies by node type name
+    pub node_types: HashMap<String, LodPolicy>,
+}
+
+impl LodAppearance {
+    pub fn policy(&self, node_type: &str) -> &LodPolicy {
+        self.node_types.get(node_type).unwrap_or(&self.default)
+    }
+
+    pub fn policy_for(&self, node_type: &NodeType) -> &LodPolicy {
+        self.policy(node_kind(node_type))
+    }
+}
@@ -1,5 +1,6 @@
 //! Performance metrics collection and monitoring
 
+use horizonos_graph_engine::DrawStats;
 use std::collections::VecDeque;
 use std::time::{Duration, Instant};
 
@@ -17,6 +18,8 @@ pub struct PerformanceMetrics {
     window_size: usize,
     /// Performance statistics
     stats: PerformanceStats,
+    /// What the last frame drew and culled
+    render_stats: RenderStats,
 }
 
 /// Performance statistics
@@ -104,6 +107,8 @@ pub struct RenderStats {
     pub edges_rendered: u32,
     /// Nodes culled this frame
     pub nodes_culled: u32,
+    /// Edges culled this frame
+    pub edges_culled: u32,
     /// LOD level distribution
     pub lod_distribution: [u32; 5], // [High, Medium, Low, VeryLow, Culled]
 }
@@ -123,9 +128,25 @@ impl PerformanceMetrics {
             last_frame_time: Instant::now(),
             window_size,
             stats: PerformanceStats::default(),
+            render_stats: RenderStats::default(),
         }
     }
     
+    /// Record what the renderer drew and culled in the last frame
+    pub fn record_draw_stats(&mut self, draw_stats: DrawStats) {
+        self.render_stats.nodes_rendered = draw_stats.nodes_drawn;
+        self.render_stats.nodes_culled = draw_stats.nodes_culled;
+        self.render_stats.edges_rendered = draw_stats.edges_drawn;
+        self.render_stats.edges_culled = draw_stats.edges_culled;
+        // Edges go out in a single draw
+        self.render_stats.draw_calls = draw_stats.node_draw_calls + u32::from(draw_stats.edges_drawn > 0);
+    }
+    
+    /// Drawn and culled counts of the last frame
+    pub fn render_stats(&self) -> &RenderStats {
+        &self.render_stats
+    }
+    
     /// Update metrics with frame timing
     pub fn update(&mut self, delta_time: Duration) {
         let now = Instant::now();
@@ -272,19 +293,24 @@ impl PerformanceMetrics {
         self.frame_count = 0;
         self.last_frame_time = Instant::now();
         self.stats = PerformanceStats::default();
+        self.render_stats = RenderStats::default();
     }
     
     /// Get performance summary as string
     pub fn get_summary(&self) -> String {
         format!(
-            "FPS: {:.1} (avg: {:.1}, min: {:.1}, max: {:.1}) | Frame Time: {:.2}ms (avg: {:.2}ms) | Trend: {:?}",
+            "FPS: {:.1} (avg: {:.1}, min: {:.1}, max: {:.1}) | Frame Time: {:.2}ms (avg: {:.2}ms) | Trend: {:?} | Drawn: {} nodes ({} culled), {} edges ({} culled)",
             self.stats.current_fps,
             self.stats.average_fps,
             self.stats.min_fps,
             self.stats.max_fps,
             self.stats.current_frame_time,
             self.stats.average_frame_time,
-            self.stats.trend
+            self.stats.trend,
+            self.render_stats.nodes_rendered,
+            self.render_stats.nodes_culled,
+            self.render_stats.edges_rendered,
+            self.render_stats.edges_culled
         )
     }
 }
@@ -340,6 +366,7 @@ impl Default for RenderStats {
             nodes_rendered: 0,
             edges_rendered: 0,
             nodes_culled: 0,
+            edges_culled: 0,
             lod_distribution: [0; 5],
         }
     }
@@ -0,0 +1,79 @@
+//! Power-aware rendering
+//!
+//! On battery or with the power saver profile active, the performance
+//! manager switches to a power-saving render path: a lower frame rate
+//! target, render quality capped with glow and animation effects off,
+//! physics paused while the scene is idle, and a slower AI monitoring
+//! sampling rate. The power state comes from the system's power manager.
+
+use crate::QualityLevel;
+use std::time::Duration;
+
+/// Power state the render path adapts to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PowerState {
+    /// Running on battery rather than AC power
+    pub on_battery: bool,
+    /// Power saver profile selected
+    pub power_saver: bool,
+}
+
+impl PowerState {
+    /// Whether the power-saving render path applies
+    pub fn saves_power(&self) -> bool {
+        self.on_battery || self.power_saver
+    }
+}
+
+/// What the power-saving render path changes
+#[derive(Debug, Clone)]
+pub struct PowerSavingPolicy {
+    /// Frame rate targeted while saving power
+    pub target_fps: f32,
+    /// Highest render quality while saving power
+    pub max_quality: QualityLevel,
+    /// Time without activity after which physics is paused
+    pub physics_idle_after: Duration,
+    /// Factor AI monitoring sampling rates are divided by
+    pub ai_sampling_divisor: u32,
+}
+
+impl Default for PowerSavingPolicy {
+    fn default() -> Self {
+        Self {
+            target_fps: 30.0,
+            max_quality: QualityLevel::Low,
+            physics_idle_after: Duration::from_secs(2),
+            ai_sampling_divisor: 4,
+        }
+    }
+}
+
+impl PowerSavingPolicy {
+    /// AI monitoring sampling rate for a configured rate, never below 1 Hz
+    pub fn ai_sampling_rate(&self, rate: u32) -> u32 {
+        (rate / self.ai_sampling_divisor.max(1)).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_battery_or_power_saver_saves_power() {
+        assert!(!PowerState::default().saves_power());
+        assert!(PowerState { on_battery: true, power_saver: false }.saves_power());
+        assert!(PowerState { on_battery: false, power_saver: true }.saves_power());
+    }
+
+    #[test]
+    fn test_ai_sampling_rate_is_divided_but_kept_running() {
+        let policy = PowerSavingPolicy::default();
+        assert_eq!(policy.ai_sampling_rate(10), 2);
+        assert_eq!(policy.ai_sampling_rate(2), 1);
+
+        let unset = PowerSavingPolicy { ai_sampling_divisor: 0, ..PowerSavingPolicy::default() };
+        assert_eq!(unset.ai_sampling_rate(10), 10);
+    }
+}
@@ -0,0 +1,85 @@
+//! Typed event bus shared between subsystems
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+/// Events buffered per type before slow subscribers start lagging
+const DEFAULT_CAPACITY: usize = 256;
+
+/// Broadcast channel per event type
+///
+/// Any `Clone` type can be an event; publishing a type nobody subscribed to
+/// is a no-op.
+pub struct EventBus {
+    capacity: usize,
+    channels: Mutex<HashMap<TypeId, Box<dyn Any + Send + Sync>>>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            channels: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Send an event to current subscribers, returning how many there are
+    pub fn publish<E: Clone + Send + 'static>(&self, event: E) -> usize {
+        self.sender::<E>().send(event).unwrap_or(0)
+    }
+
+    /// Receive events of one type published from now on
+    pub fn subscribe<E: Clone + Send + 'static>(&self) -> broadcast::Receiver<E> {
+        self.sender::<E>().subscribe()
+    }
+
+    /// Current subscribers of an event type
+    pub fn subscriber_count<E: Clone + Send + 'static>(&self) -> usize {
+        self.sender::<E>().receiver_count()
+    }
+
+    fn sender<E: Clone + Send + 'static>(&self) -> broadcast::Sender<E> {
+        let mut channels = self.channels.lock().unwrap_or_else(|e| e.into_inner());
+        channels
+            .entry(TypeId::of::<E>())
+            .or_insert_with(|| Box::new(broadcast::channel::<E>(self.capacity).0))
+            .downcast_ref::<broadcast::Sender<E>>()
+            .expect("channel stored under its event type")
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct NodeFocused(u64);
+
+    #[test]
+    fn test_events_are_routed_by_type() {
+        let bus = EventBus::new();
+        assert_eq!(bus.publish(NodeFocused(1)), 0);
+
+        let mut focused = bus.subscribe::<NodeFocused>();
+        let mut names = bus.subscribe::<String>();
+        assert_eq!(bus.publish(NodeFocused(2)), 1);
+        assert_eq!(bus.publish("theme".to_string()), 1);
+
+        assert_eq!(focused.try_recv().unwrap(), NodeFocused(2));
+        assert!(focused.try_recv().is_err());
+        assert_eq!(names.try_recv().unwrap(), "theme");
+    }
+}
@@ -0,0 +1,37 @@
+//! Service registry for HorizonOS graph desktop
+//!
+//! Long-lived subsystems (configuration, notifications, permissions, AI, the
+//! event bus) are singletons owned by a [`ServiceRegistry`] instead of being
+//! constructed ad hoc or kept in globals. A [`ServiceRegistryBuilder`] lists
+//! them in initialization order; each initializer may look up the services
+//! listed before it. Shutdown hooks run in reverse order, and a scope over a
+//! registry replaces some services, e.g. with test doubles, while sharing the
+//! rest.
+
+pub mod events;
+pub mod registry;
+
+pub use events::EventBus;
+pub use registry::{ServiceContext, ServiceRegistry, ServiceRegistryBuilder};
+
+/// Errors of the service registry
+#[derive(Debug, thiserror::Error)]
+pub enum ServiceError {
+    #[error("Service {0} is not registered")]
+    NotRegistered(&'static str),
+
+    #[error("Service {service} is initialized after {requested_by}, which needs it")]
+    NotYetInitialized {
+        service: &'static str,
+        requested_by: &'static str,
+    },
+
+    #[error("Failed to initialize service {service}: {error:#}")]
+    Init {
+        service: &'static str,
+        error: anyhow::Error,
+    },
+
+    #[error("Failed to shut down services: {}", .failed.join(", "))]
+    Shutdown { failed: Vec<&'static str> },
+}
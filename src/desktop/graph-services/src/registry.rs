@@ -0,0 +1,308 @@
+//! Service registry and its builder
+
+use crate::ServiceError;
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+type AnyService = Arc<dyn Any + Send + Sync>;
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+type Initializer = Box<dyn FnOnce(ServiceContext) -> BoxFuture<anyhow::Result<AnyService>> + Send>;
+type ShutdownHook = Box<dyn FnOnce(AnyService) -> BoxFuture<anyhow::Result<()>> + Send>;
+
+struct Provider {
+    id: TypeId,
+    name: &'static str,
+    init: Initializer,
+}
+
+#[derive(Clone)]
+struct Registered {
+    name: &'static str,
+    service: AnyService,
+}
+
+/// Lists services in initialization order
+pub struct ServiceRegistryBuilder {
+    parent: Option<Arc<ServiceRegistry>>,
+    providers: Vec<Provider>,
+    shutdown_hooks: HashMap<TypeId, ShutdownHook>,
+}
+
+impl Default for ServiceRegistryBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ServiceRegistryBuilder {
+    pub fn new() -> Self {
+        Self {
+            parent: None,
+            providers: Vec::new(),
+            shutdown_hooks: HashMap::new(),
+        }
+    }
+
+    /// Add a service created by `init` once the services before it are up
+    ///
+    /// Providing a type again replaces its initializer but keeps its place
+    /// in the order, so a test can swap one service of a full setup.
+    pub fn provide<T, F, Fut>(mut self, init: F) -> Self
+    where
+        T: Send + Sync + 'static,
+        F: FnOnce(ServiceContext) -> Fut + Send + 'static,
+        Fut: Future<Output = anyhow::Result<T>> + Send + 'static,
+    {
+        let init: Initializer = Box::new(move |context| {
+            let service = init(context);
+            Box::pin(async move { service.await.map(|service| Arc::new(service) as AnyService) })
+        });
+        self.push::<T>(init);
+        self
+    }
+
+    /// Add an already created service
+    pub fn instance<T: Send + Sync + 'static>(mut self, service: Arc<T>) -> Self {
+        let service: AnyService = service;
+        self.push::<T>(Box::new(move |_| Box::pin(async move { Ok(service) })));
+        self
+    }
+
+    fn push<T: 'static>(&mut self, init: Initializer) {
+        let provider = Provider { id: TypeId::of::<T>(), name: type_name::<T>(), init };
+        match self.providers.iter_mut().find(|existing| existing.id == provider.id) {
+            Some(existing) => *existing = provider,
+            None => self.providers.push(provider),
+        }
+    }
+
+    /// Run `hook` when the registry shuts down, before the services it was
+    /// initialized after
+    pub fn on_shutdown<T, F, Fut>(mut self, hook: F) -> Self
+    where
+        T: Send + Sync + 'static,
+        F: FnOnce(Arc<T>) -> Fut + Send + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        let hook: ShutdownHook = Box::new(move |service| {
+            let service = service.downcast::<T>().expect("shutdown hook stored under its service type");
+            Box::pin(hook(service))
+        });
+        self.shutdown_hooks.insert(TypeId::of::<T>(), hook);
+        self
+    }
+
+    /// Initialize every service in order
+    ///
+    /// When one fails, the services already started are shut down again.
+    pub async fn build(self) -> Result<ServiceRegistry, ServiceError> {
+        let mut registry = ServiceRegistry {
+            parent: self.parent,
+            services: HashMap::new(),
+            order: Vec::new(),
+            shutdown_hooks: Mutex::new(HashMap::new()),
+        };
+        let mut hooks = self.shutdown_hooks;
+        let pending: Vec<TypeId> = self.providers.iter().map(|provider| provider.id).collect();
+
+        for (index, provider) in self.providers.into_iter().enumerate() {
+            let context = ServiceContext {
+                services: registry.services.clone(),
+                parent: registry.parent.clone(),
+                later: pending[index + 1..].to_vec(),
+                requested_by: provider.name,
+            };
+            match (provider.init)(context).await {
+                Ok(service) => {
+                    log::debug!("Service {} initialized", provider.name);
+                    registry.services.insert(provider.id, Registered { name: provider.name, service });
+                    registry.order.push(provider.id);
+                    if let Some(hook) = hooks.remove(&provider.id) {
+                        registry.shutdown_hooks.get_mut().unwrap_or_else(|e| e.into_inner()).insert(provider.id, hook);
+                    }
+                }
+                Err(error) => {
+                    if let Err(e) = registry.shutdown().await {
+                        log::warn!("{}", e);
+                    }
+                    return Err(ServiceError::Init { service: provider.name, error });
+                }
+            }
+        }
+
+        if !hooks.is_empty() {
+            log::warn!("{} shutdown hooks registered for services that were never provided", hooks.len());
+        }
+        Ok(registry)
+    }
+}
+
+/// Services initialized so far, handed to each initializer
+pub struct ServiceContext {
+    services: HashMap<TypeId, Registered>,
+    parent: Option<Arc<ServiceRegistry>>,
+    /// Services initialized after the one being created
+    later: Vec<TypeId>,
+    requested_by: &'static str,
+}
+
+impl ServiceContext {
+    /// A service initialized earlier
+    pub fn get<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, ServiceError> {
+        let id = TypeId::of::<T>();
+        if let Some(registered) = self.services.get(&id) {
+            return Ok(downcast(registered));
+        }
+        if self.later.contains(&id) {
+            return Err(ServiceError::NotYetInitialized { service: type_name::<T>(), requested_by: self.requested_by });
+        }
+        match &self.parent {
+            Some(parent) => parent.get(),
+            None => Err(ServiceError::NotRegistered(type_name::<T>())),
+        }
+    }
+
+    /// A service initialized earlier, if there is one
+    pub fn try_get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.get().ok()
+    }
+}
+
+/// Owner of the desktop's singleton services
+pub struct ServiceRegistry {
+    parent: Option<Arc<ServiceRegistry>>,
+    services: HashMap<TypeId, Registered>,
+    /// Initialization order
+    order: Vec<TypeId>,
+    /// Hooks not run yet
+    shutdown_hooks: Mutex<HashMap<TypeId, ShutdownHook>>,
+}
+
+impl ServiceRegistry {
+    pub fn builder() -> ServiceRegistryBuilder {
+        ServiceRegistryBuilder::new()
+    }
+
+    /// Builder for a registry that looks up services it does not provide
+    /// itself in this one
+    ///
+    /// Shutting a scope down runs only the hooks of its own services.
+    pub fn scope(self: &Arc<Self>) -> ServiceRegistryBuilder {
+        ServiceRegistryBuilder { parent: Some(self.clone()), ..ServiceRegistryBuilder::new() }
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, ServiceError> {
+        match (self.services.get(&TypeId::of::<T>()), &self.parent) {
+            (Some(registered), _) => Ok(downcast(registered)),
+            (None, Some(parent)) => parent.get(),
+            (None, None) => Err(ServiceError::NotRegistered(type_name::<T>())),
+        }
+    }
+
+    pub fn try_get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.get().ok()
+    }
+
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.try_get::<T>().is_some()
+    }
+
+    /// Names of this registry's own services in initialization order
+    pub fn service_names(&self) -> Vec<&'static str> {
+        self.order.iter().map(|id| self.services[id].name).collect()
+    }
+
+    /// Run the shutdown hooks in reverse initialization order
+    ///
+    /// Every hook runs once, even when an earlier one fails.
+    pub async fn shutdown(&self) -> Result<(), ServiceError> {
+        let mut failed = Vec::new();
+        for id in self.order.iter().rev() {
+            let hook = self.shutdown_hooks.lock().unwrap_or_else(|e| e.into_inner()).remove(id);
+            let Some(hook) = hook else {
+                continue;
+            };
+            let registered = &self.services[id];
+            log::debug!("Shutting down service {}", registered.name);
+            if let Err(e) = hook(registered.service.clone()).await {
+                log::warn!("Service {} failed to shut down: {:#}", registered.name, e);
+                failed.push(registered.name);
+            }
+        }
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(ServiceError::Shutdown { failed })
+        }
+    }
+}
+
+fn downcast<T: Send + Sync + 'static>(registered: &Registered) -> Arc<T> {
+    registered.service.clone().downcast::<T>().expect("service stored under its type")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Config {
+        name: String,
+    }
+
+    struct Notifier {
+        config: Arc<Config>,
+    }
+
+    #[tokio::test]
+    async fn test_initialization_and_shutdown_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let (config_log, notifier_log) = (log.clone(), log.clone());
+        let registry = ServiceRegistry::builder()
+            .provide(|_| async { Ok(Config { name: "desktop".to_string() }) })
+            .provide(|services| async move { Ok(Notifier { config: services.get::<Config>()? }) })
+            .on_shutdown::<Config, _, _>(move |_| async move { config_log.lock().unwrap().push("config"); Ok(()) })
+            .on_shutdown::<Notifier, _, _>(move |_| async move { notifier_log.lock().unwrap().push("notifier"); Ok(()) })
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(registry.get::<Notifier>().unwrap().config.name, "desktop");
+        registry.shutdown().await.unwrap();
+        registry.shutdown().await.unwrap();
+        assert_eq!(*log.lock().unwrap(), ["notifier", "config"]);
+
+        // Depending on a service listed later is an ordering mistake
+        let result = ServiceRegistry::builder()
+            .provide(|services| async move { Ok(Notifier { config: services.get::<Config>()? }) })
+            .provide(|_| async { Ok(Config { name: String::new() }) })
+            .build()
+            .await;
+        assert!(matches!(result, Err(ServiceError::Init { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_scope_overrides_services() {
+        let registry = Arc::new(
+            ServiceRegistry::builder()
+                .provide(|_| async { Ok(Config { name: "desktop".to_string() }) })
+                .provide(|services| async move { Ok(Notifier { config: services.get::<Config>()? }) })
+                .build()
+                .await
+                .unwrap(),
+        );
+
+        let scope = registry
+            .scope()
+            .instance(Arc::new(Config { name: "test".to_string() }))
+            .provide(|services| async move { Ok(Notifier { config: services.get::<Config>()? }) })
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(scope.get::<Notifier>().unwrap().config.name, "test");
+        assert_eq!(registry.get::<Notifier>().unwrap().config.name, "desktop");
+        assert!(!scope.contains::<String>());
+    }
+}
@@ -0,0 +1,268 @@
+//! Conflict center: the open sync conflicts waiting for the user
+
+use crate::{BulkResolution, MetadataDiff, Resolution, SyncConflict, SyncError, SyncProvider};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Changes to the conflict center
+#[derive(Debug, Clone)]
+pub enum ConflictEvent {
+    /// A new conflict was reported
+    Reported(SyncConflict),
+    /// An open conflict was reported again with newer versions
+    Updated(SyncConflict),
+    /// The user's resolution was applied
+    Resolved { conflict: SyncConflict, resolution: Resolution },
+    /// The provider withdrew the conflict, e.g. because the item was deleted
+    Dismissed(SyncConflict),
+}
+
+/// Result of a bulk resolution
+#[derive(Debug, Default)]
+pub struct BulkOutcome {
+    pub resolved: Vec<Uuid>,
+    /// Conflicts left open because their provider failed
+    pub failed: Vec<(Uuid, SyncError)>,
+}
+
+/// Open conflicts of all sync providers, one per item
+pub struct ConflictCenter {
+    conflicts: RwLock<HashMap<Uuid, SyncConflict>>,
+    providers: RwLock<HashMap<String, Arc<dyn SyncProvider>>>,
+    events: broadcast::Sender<ConflictEvent>,
+}
+
+impl ConflictCenter {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(100);
+        Self {
+            conflicts: RwLock::new(HashMap::new()),
+            providers: RwLock::new(HashMap::new()),
+            events,
+        }
+    }
+
+    /// Register a provider, replacing one with the same name
+    pub fn register_provider(&self, provider: Arc<dyn SyncProvider>) {
+        log::info!("Registered sync provider: {}", provider.name());
+        self.providers.write().unwrap().insert(provider.name().to_string(), provider);
+    }
+
+    /// Names of the registered providers, sorted
+    pub fn providers(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.providers.read().unwrap().keys().cloned().collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Record a conflict
+    ///
+    /// A conflict already open for the same item is updated with the new
+    /// versions and keeps its id.
+    pub fn report(&self, conflict: SyncConflict) -> Uuid {
+        log::warn!(
+            "{}: sync conflict on {} ({} vs {})",
+            conflict.provider,
+            conflict.label,
+            conflict.local.origin,
+            conflict.remote.origin
+        );
+
+        let mut conflicts = self.conflicts.write().unwrap();
+        let existing = conflicts
+            .values_mut()
+            .find(|existing| existing.provider == conflict.provider && existing.item_id == conflict.item_id);
+        let (id, event) = match existing {
+            Some(existing) => {
+                existing.label = conflict.label;
+                existing.local = conflict.local;
+                existing.remote = conflict.remote;
+                existing.detected_at = conflict.detected_at;
+                (existing.id, ConflictEvent::Updated(existing.clone()))
+            }
+            None => {
+                conflicts.insert(conflict.id, conflict.clone());
+                (conflict.id, ConflictEvent::Reported(conflict))
+            }
+        };
+        drop(conflicts);
+
+        let _ = self.events.send(event);
+        id
+    }
+
+    /// Withdraw the open conflict on an item without resolving it
+    pub fn dismiss(&self, provider: &str, item_id: &str) -> Option<SyncConflict> {
+        let mut conflicts = self.conflicts.write().unwrap();
+        let id = conflicts
+            .values()
+            .find(|c| c.provider == provider && c.item_id == item_id)
+            .map(|c| c.id)?;
+        let conflict = conflicts.remove(&id)?;
+        drop(conflicts);
+
+        let _ = self.events.send(ConflictEvent::Dismissed(conflict.clone()));
+        Some(conflict)
+    }
+
+    pub fn get(&self, id: Uuid) -> Option<SyncConflict> {
+        self.conflicts.read().unwrap().get(&id).cloned()
+    }
+
+    /// Open conflicts, oldest first
+    pub fn conflicts(&self) -> Vec<SyncConflict> {
+        let mut conflicts: Vec<SyncConflict> = self.conflicts.read().unwrap().values().cloned().collect();
+        conflicts.sort_by(|a, b| a.detected_at.cmp(&b.detected_at).then(a.label.cmp(&b.label)));
+        conflicts
+    }
+
+    /// Open conflicts of one provider, oldest first
+    pub fn conflicts_of(&self, provider: &str) -> Vec<SyncConflict> {
+        self.conflicts().into_iter().filter(|c| c.provider == provider).collect()
+    }
+
+    /// Number of open conflicts, for a status badge
+    pub fn count(&self) -> usize {
+        self.conflicts.read().unwrap().len()
+    }
+
+    /// Differences between the two versions of a conflict
+    pub fn diff(&self, id: Uuid) -> Result<MetadataDiff, SyncError> {
+        self.get(id).map(|c| c.diff()).ok_or(SyncError::ConflictNotFound(id))
+    }
+
+    /// Apply a resolution through the conflict's provider and close it
+    pub fn resolve(&self, id: Uuid, resolution: Resolution) -> Result<(), SyncError> {
+        let conflict = self.get(id).ok_or(SyncError::ConflictNotFound(id))?;
+        let provider = self
+            .providers
+            .read()
+            .unwrap()
+            .get(&conflict.provider)
+            .cloned()
+            .ok_or_else(|| SyncError::UnknownProvider(conflict.provider.clone()))?;
+
+        provider.resolve(&conflict, &resolution)?;
+        self.conflicts.write().unwrap().remove(&id);
+        log::info!("{}: resolved conflict on {} with {}", conflict.provider, conflict.label, resolution.name());
+
+        let _ = self.events.send(ConflictEvent::Resolved { conflict, resolution });
+        Ok(())
+    }
+
+    /// Resolve all open conflicts, or those of one provider, the same way
+    pub fn resolve_all(&self, provider: Option<&str>, choice: BulkResolution) -> BulkOutcome {
+        let conflicts = match provider {
+            Some(provider) => self.conflicts_of(provider),
+            None => self.conflicts(),
+        };
+        let mut outcome = BulkOutcome::default();
+        for conflict in conflicts {
+            match self.resolve(conflict.id, choice.for_conflict(&conflict)) {
+                Ok(()) => outcome.resolved.push(conflict.id),
+                Err(e) => {
+                    log::warn!("{}: could not resolve conflict on {}: {}", conflict.provider, conflict.label, e);
+                    outcome.failed.push((conflict.id, e));
+                }
+            }
+        }
+        outcome
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ConflictEvent> {
+        self.events.subscribe()
+    }
+}
+
+impl Default for ConflictCenter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ItemVersion;
+    use chrono::{Duration, Utc};
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Tasks {
+        applied: Mutex<Vec<(String, &'static str)>>,
+    }
+
+    impl SyncProvider for Tasks {
+        fn name(&self) -> &str {
+            "tasks"
+        }
+
+        fn resolve(&self, conflict: &SyncConflict, resolution: &Resolution) -> Result<(), SyncError> {
+            if let Resolution::KeepBoth = resolution {
+                return Err(SyncError::Unsupported { provider: "tasks".to_string(), resolution: resolution.name() });
+            }
+            self.applied.lock().unwrap().push((conflict.item_id.clone(), resolution.name()));
+            Ok(())
+        }
+    }
+
+    fn task_conflict(item_id: &str, remote_age: i64) -> SyncConflict {
+        let now = Utc::now();
+        SyncConflict::new(
+            "tasks",
+            item_id,
+            format!("Task {}", item_id),
+            ItemVersion::new(json!({ "title": "Local", "done": false }), now - Duration::minutes(5), "laptop"),
+            ItemVersion::new(json!({ "title": "Remote", "done": true }), now - Duration::minutes(remote_age), "phone"),
+        )
+    }
+
+    #[test]
+    fn test_conflicts_are_resolved_through_their_provider() {
+        let center = ConflictCenter::new();
+        let tasks = Arc::new(Tasks::default());
+        let mut events = center.subscribe();
+
+        let first = center.report(task_conflict("1", 1));
+        assert_eq!(center.report(task_conflict("1", 2)), first);
+        assert!(matches!(events.try_recv(), Ok(ConflictEvent::Reported(_))));
+        assert!(matches!(events.try_recv(), Ok(ConflictEvent::Updated(_))));
+        assert_eq!(center.diff(first).unwrap().changes.len(), 2);
+
+        assert!(matches!(center.resolve(first, Resolution::KeepLocal), Err(SyncError::UnknownProvider(_))));
+        center.register_provider(tasks.clone());
+        assert!(center.resolve(first, Resolution::KeepBoth).is_err());
+        assert_eq!(center.count(), 1);
+
+        center.resolve(first, Resolution::KeepRemote).unwrap();
+        assert_eq!(center.count(), 0);
+        assert!(matches!(events.try_recv(), Ok(ConflictEvent::Resolved { .. })));
+        assert_eq!(tasks.applied.lock().unwrap().as_slice(), &[("1".to_string(), "keep_remote")]);
+    }
+
+    #[test]
+    fn test_bulk_resolution_keeps_newest() {
+        let center = ConflictCenter::new();
+        let tasks = Arc::new(Tasks::default());
+        center.register_provider(tasks.clone());
+        center.report(task_conflict("newer-remote", 1));
+        center.report(task_conflict("older-remote", 10));
+
+        let outcome = center.resolve_all(Some("tasks"), BulkResolution::KeepNewest);
+        assert_eq!(outcome.resolved.len(), 2);
+        let mut applied = tasks.applied.lock().unwrap().clone();
+        applied.sort();
+        assert_eq!(
+            applied,
+            vec![("newer-remote".to_string(), "keep_remote"), ("older-remote".to_string(), "keep_local")]
+        );
+
+        center.report(task_conflict("3", 1));
+        let outcome = center.resolve_all(None, BulkResolution::KeepBoth);
+        assert_eq!(outcome.failed.len(), 1);
+        assert_eq!(center.count(), 1);
+    }
+}
@@ -0,0 +1,136 @@
+//! Field-level diff of two versions of an item's metadata
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// How a field differs in the remote version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// Only the remote version has the field
+    Added,
+    /// Only the local version has the field
+    Removed,
+    /// Both have it with different values
+    Modified,
+}
+
+/// A field whose value differs between the versions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// Dotted path of the field, e.g. `settings.grid_size`
+    pub path: String,
+    pub local: Option<Value>,
+    pub remote: Option<Value>,
+}
+
+impl FieldChange {
+    pub fn kind(&self) -> ChangeKind {
+        match (&self.local, &self.remote) {
+            (None, _) => ChangeKind::Added,
+            (_, None) => ChangeKind::Removed,
+            _ => ChangeKind::Modified,
+        }
+    }
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.local, &self.remote) {
+            (Some(local), Some(remote)) => write!(f, "~ {}: {} → {}", self.path, local, remote),
+            (None, Some(remote)) => write!(f, "+ {}: {}", self.path, remote),
+            (Some(local), None) => write!(f, "- {}: {}", self.path, local),
+            (None, None) => write!(f, "  {}", self.path),
+        }
+    }
+}
+
+/// Differences from the local to the remote version
+///
+/// Objects are compared field by field; arrays and scalars as a whole.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetadataDiff {
+    /// Changed fields, sorted by path
+    pub changes: Vec<FieldChange>,
+}
+
+impl MetadataDiff {
+    pub fn between(local: &Value, remote: &Value) -> Self {
+        let mut changes = Vec::new();
+        collect(String::new(), Some(local), Some(remote), &mut changes);
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        Self { changes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Whether a field differs, e.g. to highlight it in an editor
+    pub fn touches(&self, path: &str) -> bool {
+        self.changes.iter().any(|change| {
+            change.path == path || change.path.strip_prefix(path).is_some_and(|rest| rest.starts_with('.'))
+        })
+    }
+
+    /// One line per changed field: `~` modified, `+` only remote, `-` only local
+    pub fn render(&self) -> String {
+        self.changes.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n")
+    }
+}
+
+fn collect(path: String, local: Option<&Value>, remote: Option<&Value>, changes: &mut Vec<FieldChange>) {
+    match (local, remote) {
+        (Some(Value::Object(local)), Some(Value::Object(remote))) => {
+            for (key, value) in local {
+                collect(join(&path, key), Some(value), remote.get(key), changes);
+            }
+            for (key, value) in remote.iter().filter(|(key, _)| !local.contains_key(*key)) {
+                collect(join(&path, key), None, Some(value), changes);
+            }
+        }
+        (local, remote) if local != remote => changes.push(FieldChange {
+            path: if path.is_empty() { "$".to_string() } else { path },
+            local: local.cloned(),
+            remote: remote.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_nested_fields_are_diffed() {
+        let local = json!({ "name": "Work", "settings": { "grid_size": 20, "show_grid": true }, "tags": ["a"] });
+        let remote = json!({ "name": "Work", "settings": { "grid_size": 40 }, "tags": ["a", "b"], "color": "red" });
+        let diff = MetadataDiff::between(&local, &remote);
+
+        let kinds: Vec<(&str, ChangeKind)> = diff.changes.iter().map(|c| (c.path.as_str(), c.kind())).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("color", ChangeKind::Added),
+                ("settings.grid_size", ChangeKind::Modified),
+                ("settings.show_grid", ChangeKind::Removed),
+                ("tags", ChangeKind::Modified),
+            ]
+        );
+        assert!(diff.touches("settings"));
+        assert!(!diff.touches("name"));
+        assert_eq!(diff.render().lines().nth(1), Some("~ settings.grid_size: 20 → 40"));
+        assert!(MetadataDiff::between(&local, &local).is_empty());
+    }
+}
@@ -0,0 +1,183 @@
+//! Sync conflict center for HorizonOS graph desktop
+//!
+//! Sync features (workspaces, tasks, calendars, collaboration) never pick a
+//! side silently when the local and remote versions of an item both
+//! changed. They report a [`SyncConflict`] carrying both versions to the
+//! [`ConflictCenter`], which lists open conflicts, renders a [`MetadataDiff`]
+//! of the two versions and hands the user's [`Resolution`] back to the
+//! [`SyncProvider`] that reported it. Conflicts can be resolved one by one or
+//! in bulk with a [`BulkResolution`].
+
+pub mod center;
+pub mod diff;
+
+pub use center::{BulkOutcome, ConflictCenter, ConflictEvent};
+pub use diff::{ChangeKind, FieldChange, MetadataDiff};
+
+use chrono::{DateTime, Utc};
+use horizonos_graph_errors::{Category, Classify, Problem, RecoveryAction, RecoveryKind, Retryability, Severity};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// One side of a conflict
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemVersion {
+    /// The item's metadata, as the provider serializes it
+    pub data: serde_json::Value,
+    /// When this version was last changed
+    pub modified_at: DateTime<Utc>,
+    /// Device or user that made the change
+    pub origin: String,
+}
+
+impl ItemVersion {
+    pub fn new(data: serde_json::Value, modified_at: DateTime<Utc>, origin: impl Into<String>) -> Self {
+        Self { data, modified_at, origin: origin.into() }
+    }
+}
+
+/// An item changed both here and elsewhere since it was last in sync
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncConflict {
+    pub id: Uuid,
+    /// Name of the reporting [`SyncProvider`]
+    pub provider: String,
+    /// Provider-specific identifier of the item
+    pub item_id: String,
+    /// Title shown in the conflict list
+    pub label: String,
+    pub local: ItemVersion,
+    pub remote: ItemVersion,
+    pub detected_at: DateTime<Utc>,
+}
+
+impl SyncConflict {
+    pub fn new(
+        provider: impl Into<String>,
+        item_id: impl Into<String>,
+        label: impl Into<String>,
+        local: ItemVersion,
+        remote: ItemVersion,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            provider: provider.into(),
+            item_id: item_id.into(),
+            label: label.into(),
+            local,
+            remote,
+            detected_at: Utc::now(),
+        }
+    }
+
+    /// Field-level differences from the local to the remote version
+    pub fn diff(&self) -> MetadataDiff {
+        MetadataDiff::between(&self.local.data, &self.remote.data)
+    }
+
+    /// Whether the remote version is the more recent one
+    pub fn remote_is_newer(&self) -> bool {
+        self.remote.modified_at > self.local.modified_at
+    }
+}
+
+/// How the user settles one conflict
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "choice", content = "data")]
+pub enum Resolution {
+    /// Keep this device's version and overwrite the remote one
+    KeepLocal,
+    /// Take the remote version
+    KeepRemote,
+    /// Keep the local version and add the remote one as a copy
+    KeepBoth,
+    /// Use a version the user merged by hand
+    Merged(serde_json::Value),
+}
+
+impl Resolution {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Resolution::KeepLocal => "keep_local",
+            Resolution::KeepRemote => "keep_remote",
+            Resolution::KeepBoth => "keep_both",
+            Resolution::Merged(_) => "merged",
+        }
+    }
+}
+
+/// One choice applied to many conflicts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkResolution {
+    KeepLocal,
+    KeepRemote,
+    KeepBoth,
+    /// Whichever version changed last, local on a tie
+    KeepNewest,
+}
+
+impl BulkResolution {
+    /// Resolution of one conflict
+    pub fn for_conflict(&self, conflict: &SyncConflict) -> Resolution {
+        match self {
+            BulkResolution::KeepLocal => Resolution::KeepLocal,
+            BulkResolution::KeepRemote => Resolution::KeepRemote,
+            BulkResolution::KeepBoth => Resolution::KeepBoth,
+            BulkResolution::KeepNewest if conflict.remote_is_newer() => Resolution::KeepRemote,
+            BulkResolution::KeepNewest => Resolution::KeepLocal,
+        }
+    }
+}
+
+/// A sync feature whose conflicts go through the conflict center
+///
+/// Providers report conflicts with [`ConflictCenter::report`] and are called
+/// back once the user has chosen a resolution. The conflict stays open when
+/// `resolve` fails.
+pub trait SyncProvider: Send + Sync {
+    /// Name the provider's conflicts are reported under, e.g. `workspaces`
+    fn name(&self) -> &str;
+
+    /// Apply the user's choice
+    fn resolve(&self, conflict: &SyncConflict, resolution: &Resolution) -> Result<(), SyncError>;
+}
+
+/// Sync errors
+#[derive(Debug, thiserror::Error)]
+pub enum SyncError {
+    #[error("Conflict not found: {0}")]
+    ConflictNotFound(Uuid),
+
+    #[error("No sync provider named {0}")]
+    UnknownProvider(String),
+
+    #[error("{provider} cannot resolve conflicts with {resolution}")]
+    Unsupported { provider: String, resolution: &'static str },
+
+    #[error("Merged version is invalid: {0}")]
+    InvalidMerge(String),
+
+    #[error("Failed to apply resolution: {0}")]
+    Apply(String),
+}
+
+impl Classify for SyncError {
+    fn classify(&self) -> Problem {
+        let problem = match self {
+            SyncError::ConflictNotFound(_) => Problem::new("sync.conflict_not_found", Category::Sync, Severity::Info, "The conflict was already resolved"),
+            SyncError::UnknownProvider(name) => Problem::new("sync.unknown_provider", Category::Internal, Severity::Error, format!("Sync provider {} is not available", name)),
+            SyncError::Unsupported { .. } => Problem::new("sync.unsupported_resolution", Category::Sync, Severity::Warning, "This conflict cannot be resolved that way"),
+            SyncError::InvalidMerge(_) => Problem::new("sync.invalid_merge", Category::Sync, Severity::Warning, "The merged version is invalid")
+                .with_retry(Retryability::NeedsUser),
+            SyncError::Apply(_) => Problem::new("sync.resolve_failed", Category::Sync, Severity::Error, "The conflict could not be resolved")
+                .with_retry(Retryability::NeedsUser)
+                .with_recovery(RecoveryAction::new(
+                    "resolve",
+                    "Resolve",
+                    RecoveryKind::Custom { handler: "sync.open_conflicts".to_string() },
+                )),
+        };
+        problem.in_subsystem("sync").with_detail(self)
+    }
+}
@@ -0,0 +1,901 @@
+//! PipeWire audio device and stream nodes for the graph desktop
+//!
+//! Audio sinks and sources appear as device nodes with an anchored volume
+//! slider, application playback and capture streams appear as stream nodes
+//! connected to their application and to the device they play through.
+//! Dropping a stream node onto a device node reroutes the stream, and card
+//! profiles (for example HDMI versus headset) are offered as node actions.
+//!
+//! The PipeWire graph is read with `pw-dump` and changed through `wpctl` and
+//! `pw-metadata`, so no PipeWire client library is linked.
+
+use anyhow::{Result, Context, anyhow};
+use horizonos_graph_engine::{
+    DeviceType, EdgeType, NodeMetadata, NodePersistence, NodeType, Position, Scene, SceneEdge,
+    SceneId, SceneNode, SystemStatus, Vec3,
+};
+use horizonos_graph_nodes::{NodeAction, NodeActionResult, NodeActionType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::process::Command;
+
+/// Node action: make a device the default
+pub const ACTION_SET_DEFAULT: &str = "audio.set_default";
+/// Node action: toggle mute on a device or stream
+pub const ACTION_TOGGLE_MUTE: &str = "audio.toggle_mute";
+/// Node action prefix: switch the card profile, followed by the profile name
+pub const ACTION_SET_PROFILE_PREFIX: &str = "audio.set_profile:";
+
+/// Snapshot of the PipeWire audio graph
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AudioGraphSnapshot {
+    /// Sinks and sources
+    pub endpoints: Vec<AudioEndpoint>,
+    /// Application streams
+    pub streams: Vec<AudioStream>,
+    /// Sound cards with their profiles
+    pub cards: Vec<AudioCard>,
+}
+
+/// An audio sink or source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioEndpoint {
+    /// PipeWire node ID
+    pub id: u32,
+    /// PipeWire node name
+    pub name: String,
+    /// Human readable description
+    pub description: String,
+    /// Output (sink) or input (source)
+    pub direction: AudioDirection,
+    /// Card this endpoint belongs to
+    pub card_id: Option<u32>,
+    /// Volume (0.0 - 1.5, linear)
+    pub volume: f32,
+    /// Is muted
+    pub muted: bool,
+    /// Is the default for its direction
+    pub is_default: bool,
+}
+
+/// An application playback or capture stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioStream {
+    /// PipeWire node ID
+    pub id: u32,
+    /// Application name
+    pub app_name: String,
+    /// Application process ID
+    pub pid: Option<u32>,
+    /// Media title, if the application sets one
+    pub media_name: Option<String>,
+    /// Playback (output) or capture (input)
+    pub direction: AudioDirection,
+    /// Endpoint the stream is linked to
+    pub target: Option<u32>,
+    /// Volume (0.0 - 1.5, linear)
+    pub volume: f32,
+    /// Is muted
+    pub muted: bool,
+}
+
+/// A sound card
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioCard {
+    /// PipeWire device ID
+    pub id: u32,
+    /// Human readable description
+    pub description: String,
+    /// Available profiles
+    pub profiles: Vec<AudioProfile>,
+    /// Index of the active profile
+    pub active_profile: Option<u32>,
+}
+
+/// A card profile such as "HDMI Stereo Output" or "Headset"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioProfile {
+    /// Profile index used by `wpctl set-profile`
+    pub index: u32,
+    /// Profile name
+    pub name: String,
+    /// Human readable description
+    pub description: String,
+    /// Whether the profile can currently be used
+    pub available: bool,
+}
+
+/// Audio direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AudioDirection {
+    Output,
+    Input,
+}
+
+/// Thin wrapper around the PipeWire command line tools
+#[derive(Debug, Clone)]
+pub struct PipeWireClient {
+    /// Path to `pw-dump`
+    pub pw_dump: String,
+    /// Path to `wpctl`
+    pub wpctl: String,
+    /// Path to `pw-metadata`
+    pub pw_metadata: String,
+}
+
+/// Volume slider anchored next to an audio node
+#[derive(Debug, Clone)]
+pub struct VolumeSliderWidget {
+    /// Node the slider belongs to
+    pub node_id: SceneId,
+    /// Offset from the node center in graph space
+    pub anchor_offset: (f32, f32, f32),
+    /// Current position in graph space
+    pub position: (f32, f32, f32),
+    /// Size
+    pub size: (f32, f32),
+    /// Displayed volume (0.0 - 1.0)
+    pub volume: f32,
+    /// Displayed mute state
+    pub muted: bool,
+}
+
+/// Audio graph manager
+pub struct AudioGraphManager {
+    /// PipeWire access
+    client: PipeWireClient,
+    /// Last snapshot read from PipeWire
+    snapshot: AudioGraphSnapshot,
+    /// Endpoint ID to node mapping
+    endpoint_nodes: HashMap<u32, SceneId>,
+    /// Stream ID to node mapping
+    stream_nodes: HashMap<u32, SceneId>,
+    /// Edges created for each stream
+    stream_edges: HashMap<u32, Vec<SceneId>>,
+    /// Volume sliders keyed by node
+    sliders: HashMap<SceneId, VolumeSliderWidget>,
+}
+
+impl PipeWireClient {
+    /// Create a client using the tools found on `PATH`
+    pub fn new() -> Self {
+        Self {
+            pw_dump: "pw-dump".to_string(),
+            wpctl: "wpctl".to_string(),
+            pw_metadata: "pw-metadata".to_string(),
+        }
+    }
+
+    /// Read the current audio graph
+    pub async fn snapshot(&self) -> Result<AudioGraphSnapshot> {
+        let output = Command::new(&self.pw_dump)
+            .output()
+            .await
+            .context("Failed to run pw-dump")?;
+
+        if !output.status.success() {
+            return Err(anyhow!("pw-dump failed: {}", String::from_utf8_lossy(&output.stderr)));
+        }
+
+        let objects: serde_json::Value = serde_json::from_slice(&output.stdout)
+            .context("Failed to parse pw-dump output")?;
+
+        Ok(parse_pw_dump(&objects))
+    }
+
+    /// Set the volume of a node (linear, 1.0 = 100%)
+    pub async fn set_volume(&self, id: u32, volume: f32) -> Result<()> {
+        let volume = volume.clamp(0.0, 1.5);
+        self.run(&self.wpctl, &["set-volume", &id.to_string(), &format!("{:.3}", volume)]).await
+    }
+
+    /// Mute or unmute a node
+    pub async fn set_mute(&self, id: u32, muted: bool) -> Result<()> {
+        self.run(&self.wpctl, &["set-mute", &id.to_string(), if muted { "1" } else { "0" }]).await
+    }
+
+    /// Make an endpoint the default for its direction
+    pub async fn set_default(&self, id: u32) -> Result<()> {
+        self.run(&self.wpctl, &["set-default", &id.to_string()]).await
+    }
+
+    /// Switch a card to another profile
+    pub async fn set_profile(&self, card_id: u32, profile_index: u32) -> Result<()> {
+        self.run(&self.wpctl, &["set-profile", &card_id.to_string(), &profile_index.to_string()]).await
+    }
+
+    /// Move a stream to another endpoint
+    pub async fn move_stream(&self, stream_id: u32, target_name: &str) -> Result<()> {
+        self.run(
+            &self.pw_metadata,
+            &[&stream_id.to_string(), "target.object", target_name, "Spa:String"],
+        ).await
+    }
+
+    async fn run(&self, program: &str, args: &[&str]) -> Result<()> {
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .await
+            .with_context(|| format!("Failed to run {}", program))?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "{} {} failed: {}",
+                program,
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+}
+
+/// Parse `pw-dump` JSON into an audio graph snapshot
+pub fn parse_pw_dump(objects: &serde_json::Value) -> AudioGraphSnapshot {
+    let mut snapshot = AudioGraphSnapshot::default();
+    let Some(objects) = objects.as_array() else {
+        return snapshot;
+    };
+
+    let mut default_sink = None;
+    let mut default_source = None;
+    let mut links = Vec::new();
+
+    for object in objects {
+        let id = object["id"].as_u64().unwrap_or(0) as u32;
+        let info = &object["info"];
+        let props = &info["props"];
+
+        match object["type"].as_str().unwrap_or("") {
+            "PipeWire:Interface:Node" => {
+                let media_class = props["media.class"].as_str().unwrap_or("");
+                let (volume, muted) = parse_volume(&info["params"]["Props"]);
+
+                match media_class {
+                    "Audio/Sink" | "Audio/Source" => snapshot.endpoints.push(AudioEndpoint {
+                        id,
+                        name: prop_string(props, "node.name").unwrap_or_default(),
+                        description: prop_string(props, "node.description")
+                            .or_else(|| prop_string(props, "node.nick"))
+                            .unwrap_or_else(|| format!("Audio device {}", id)),
+                        direction: if media_class == "Audio/Sink" {
+                            AudioDirection::Output
+                        } else {
+                            AudioDirection::Input
+                        },
+                        card_id: prop_u32(props, "device.id"),
+                        volume,
+                        muted,
+                        is_default: false,
+                    }),
+                    "Stream/Output/Audio" | "Stream/Input/Audio" => snapshot.streams.push(AudioStream {
+                        id,
+                        app_name: prop_string(props, "application.name")
+                            .or_else(|| prop_string(props, "node.name"))
+                            .unwrap_or_else(|| "Unknown".to_string()),
+                        pid: prop_u32(props, "application.process.id"),
+                        media_name: prop_string(props, "media.name"),
+                        direction: if media_class == "Stream/Output/Audio" {
+                            AudioDirection::Output
+                        } else {
+                            AudioDirection::Input
+                        },
+                        target: None,
+                        volume,
+                        muted,
+                    }),
+                    _ => {}
+                }
+            }
+            "PipeWire:Interface:Device" => {
+                if props["media.class"].as_str() != Some("Audio/Device") {
+                    continue;
+                }
+
+                let profiles = info["params"]["EnumProfile"]
+                    .as_array()
+                    .map(|profiles| {
+                        profiles
+                            .iter()
+                            .map(|p| AudioProfile {
+                                index: p["index"].as_u64().unwrap_or(0) as u32,
+                                name: p["name"].as_str().unwrap_or("").to_string(),
+                                description: p["description"].as_str().unwrap_or("").to_string(),
+                                available: p["available"].as_str() != Some("no"),
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let active_profile = info["params"]["Profile"]
+                    .as_array()
+                    .and_then(|p| p.first())
+                    .and_then(|p| p["index"].as_u64())
+                    .map(|index| index as u32);
+
+                snapshot.cards.push(AudioCard {
+                    id,
+                    description: prop_string(props, "device.description")
+                        .unwrap_or_else(|| format!("Sound card {}", id)),
+                    profiles,
+                    active_profile,
+                });
+            }
+            "PipeWire:Interface:Link" => {
+                let output = info["output-node-id"].as_u64().map(|id| id as u32);
+                let input = info["input-node-id"].as_u64().map(|id| id as u32);
+                if let (Some(output), Some(input)) = (output, input) {
+                    links.push((output, input));
+                }
+            }
+            "PipeWire:Interface:Metadata" => {
+                // Metadata objects carry their props at the top level
+                if object["props"]["metadata.name"].as_str() != Some("default") {
+                    continue;
+                }
+                for entry in object["metadata"].as_array().into_iter().flatten() {
+                    let name = entry["value"]["name"].as_str().map(str::to_string);
+                    match entry["key"].as_str() {
+                        Some("default.audio.sink") => default_sink = name,
+                        Some("default.audio.source") => default_source = name,
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for endpoint in &mut snapshot.endpoints {
+        let default = match endpoint.direction {
+            AudioDirection::Output => &default_sink,
+            AudioDirection::Input => &default_source,
+        };
+        endpoint.is_default = default.as_deref() == Some(endpoint.name.as_str());
+    }
+
+    // Playback streams link out to a sink, capture streams link in from a source
+    for stream in &mut snapshot.streams {
+        stream.target = links.iter().find_map(|&(output, input)| match stream.direction {
+            AudioDirection::Output if output == stream.id => Some(input),
+            AudioDirection::Input if input == stream.id => Some(output),
+            _ => None,
+        })
+        .filter(|target| snapshot.endpoints.iter().any(|e| e.id == *target));
+    }
+
+    snapshot
+}
+
+/// Read linear volume and mute from a node's `Props` param
+fn parse_volume(props: &serde_json::Value) -> (f32, bool) {
+    let Some(props) = props.as_array().and_then(|p| p.first()) else {
+        return (1.0, false);
+    };
+
+    let muted = props["mute"].as_bool().unwrap_or(false);
+
+    // PipeWire stores cubic channel volumes; wpctl shows their cube root
+    let channels: Vec<f64> = props["channelVolumes"]
+        .as_array()
+        .map(|v| v.iter().filter_map(|c| c.as_f64()).collect())
+        .unwrap_or_default();
+
+    let volume = if channels.is_empty() {
+        props["volume"].as_f64().unwrap_or(1.0)
+    } else {
+        channels.iter().sum::<f64>() / channels.len() as f64
+    };
+
+    (volume.cbrt() as f32, muted)
+}
+
+fn prop_string(props: &serde_json::Value, key: &str) -> Option<String> {
+    props[key].as_str().map(str::to_string)
+}
+
+fn prop_u32(props: &serde_json::Value, key: &str) -> Option<u32> {
+    props[key]
+        .as_u64()
+        .or_else(|| props[key].as_str().and_then(|s| s.parse().ok()))
+        .map(|v| v as u32)
+}
+
+impl AudioGraphManager {
+    /// Create a new audio graph manager
+    pub fn new(client: PipeWireClient) -> Self {
+        Self {
+            client,
+            snapshot: AudioGraphSnapshot::default(),
+            endpoint_nodes: HashMap::new(),
+            stream_nodes: HashMap::new(),
+            stream_edges: HashMap::new(),
+            sliders: HashMap::new(),
+        }
+    }
+
+    /// Last snapshot read from PipeWire
+    pub fn snapshot(&self) -> &AudioGraphSnapshot {
+        &self.snapshot
+    }
+
+    /// Volume sliders to draw next to audio nodes
+    pub fn sliders(&self) -> impl Iterator<Item = &VolumeSliderWidget> {
+        self.sliders.values()
+    }
+
+    /// Re-read PipeWire and update the scene
+    pub async fn refresh(&mut self, scene: &mut Scene) -> Result<()> {
+        let snapshot = self.client.snapshot().await?;
+        self.apply_snapshot(scene, snapshot);
+        Ok(())
+    }
+
+    /// Bring the scene in line with a snapshot
+    pub fn apply_snapshot(&mut self, scene: &mut Scene, snapshot: AudioGraphSnapshot) {
+        // Drop nodes for endpoints and streams that went away
+        let endpoint_ids: Vec<u32> = snapshot.endpoints.iter().map(|e| e.id).collect();
+        let stream_ids: Vec<u32> = snapshot.streams.iter().map(|s| s.id).collect();
+
+        self.endpoint_nodes.retain(|id, node_id| {
+            let keep = endpoint_ids.contains(id);
+            if !keep {
+                scene.remove_node(*node_id);
+            }
+            keep
+        });
+        self.stream_nodes.retain(|id, node_id| {
+            let keep = stream_ids.contains(id);
+            if !keep {
+                scene.remove_node(*node_id);
+            }
+            keep
+        });
+        self.stream_edges.retain(|id, _| stream_ids.contains(id));
+        let live_nodes: Vec<SceneId> = self.endpoint_nodes.values()
+            .chain(self.stream_nodes.values())
+            .copied()
+            .collect();
+        self.sliders.retain(|node_id, _| live_nodes.contains(node_id));
+
+        for endpoint in &snapshot.endpoints {
+            let card = endpoint.card_id.and_then(|id| snapshot.cards.iter().find(|c| c.id == id));
+            let node_id = match self.endpoint_nodes.get(&endpoint.id) {
+                Some(&node_id) => node_id,
+                None => {
+                    // Rebuilt from PipeWire on every start, so never saved
+                    let node_id = scene.add_node_with_persistence(Self::endpoint_scene_node(endpoint), NodePersistence::Ephemeral);
+                    self.endpoint_nodes.insert(endpoint.id, node_id);
+                    node_id
+                }
+            };
+
+            if let Some(node) = scene.get_node_mut(node_id) {
+                Self::update_endpoint_node(node, endpoint, card);
+            }
+            self.update_slider(scene, node_id, endpoint.volume, endpoint.muted);
+        }
+
+        for stream in &snapshot.streams {
+            let node_id = match self.stream_nodes.get(&stream.id) {
+                Some(&node_id) => node_id,
+                None => {
+                    let node_id = scene.add_node_with_persistence(Self::stream_scene_node(stream), NodePersistence::Ephemeral);
+                    self.stream_nodes.insert(stream.id, node_id);
+                    node_id
+                }
+            };
+
+            if let Some(node) = scene.get_node_mut(node_id) {
+                node.node_type = NodeType::System {
+                    component: Self::stream_label(stream),
+                    status: if stream.muted { SystemStatus::Stopped } else { SystemStatus::Running },
+                };
+                node.metadata.properties.insert("audio.volume".to_string(), format!("{:.2}", stream.volume));
+                node.metadata.properties.insert("audio.muted".to_string(), stream.muted.to_string());
+            }
+            self.update_slider(scene, node_id, stream.volume, stream.muted);
+            self.link_stream(scene, stream, node_id);
+        }
+
+        self.snapshot = snapshot;
+    }
+
+    /// Actions offered on an audio node
+    pub fn available_actions(&self, node_id: SceneId) -> Vec<NodeActionType> {
+        let mut actions = Vec::new();
+
+        if let Some(endpoint) = self.endpoint_for_node(node_id) {
+            if !endpoint.is_default {
+                actions.push(NodeActionType::Custom(ACTION_SET_DEFAULT.to_string()));
+            }
+            actions.push(NodeActionType::Custom(ACTION_TOGGLE_MUTE.to_string()));
+
+            if let Some(card) = self.card_for_endpoint(endpoint) {
+                for profile in card.profiles.iter().filter(|p| p.available) {
+                    if Some(profile.index) != card.active_profile {
+                        actions.push(NodeActionType::Custom(format!("{}{}", ACTION_SET_PROFILE_PREFIX, profile.name)));
+                    }
+                }
+            }
+        } else if self.stream_for_node(node_id).is_some() {
+            actions.push(NodeActionType::Custom(ACTION_TOGGLE_MUTE.to_string()));
+        }
+
+        actions
+    }
+
+    /// Perform an audio node action
+    pub async fn handle_action(&mut self, node_id: SceneId, action: NodeAction) -> Result<NodeActionResult> {
+        let NodeAction::Custom { action_type, .. } = action else {
+            return Err(anyhow!("Unsupported audio action: {:?}", action));
+        };
+
+        if let Some(endpoint) = self.endpoint_for_node(node_id).cloned() {
+            if action_type == ACTION_SET_DEFAULT {
+                self.client.set_default(endpoint.id).await?;
+                return Ok(NodeActionResult::Success {
+                    message: Some(format!("{} is now the default device", endpoint.description)),
+                });
+            }
+
+            if action_type == ACTION_TOGGLE_MUTE {
+                self.client.set_mute(endpoint.id, !endpoint.muted).await?;
+                return Ok(NodeActionResult::Success { message: None });
+            }
+
+            if let Some(profile_name) = action_type.strip_prefix(ACTION_SET_PROFILE_PREFIX) {
+                let card = self.card_for_endpoint(&endpoint)
+                    .ok_or_else(|| anyhow!("{} has no card profiles", endpoint.description))?;
+                let profile = card.profiles.iter()
+                    .find(|p| p.name == profile_name)
+                    .ok_or_else(|| anyhow!("Unknown profile: {}", profile_name))?;
+
+                self.client.set_profile(card.id, profile.index).await?;
+                return Ok(NodeActionResult::Success {
+                    message: Some(format!("Switched {} to {}", card.description, profile.description)),
+                });
+            }
+        } else if let Some(stream) = self.stream_for_node(node_id).cloned() {
+            if action_type == ACTION_TOGGLE_MUTE {
+                self.client.set_mute(stream.id, !stream.muted).await?;
+                return Ok(NodeActionResult::Success { message: None });
+            }
+        } else {
+            return Err(anyhow!("Node {} is not an audio node", node_id));
+        }
+
+        Err(anyhow!("Unsupported audio action: {}", action_type))
+    }
+
+    /// Handle a node dropped onto another node
+    ///
+    /// Returns `true` when a stream was dropped onto a matching device and
+    /// rerouted there.
+    pub async fn handle_drop(&mut self, scene: &mut Scene, dragged: SceneId, target: SceneId) -> Result<bool> {
+        let (Some(stream), Some(endpoint)) = (
+            self.stream_for_node(dragged).cloned(),
+            self.endpoint_for_node(target).cloned(),
+        ) else {
+            return Ok(false);
+        };
+
+        if stream.direction != endpoint.direction {
+            return Ok(false);
+        }
+
+        self.client.move_stream(stream.id, &endpoint.name).await?;
+        log::info!("Moved audio stream {} to {}", stream.app_name, endpoint.description);
+
+        // Show the new route immediately; the next refresh confirms it
+        if let Some(s) = self.snapshot.streams.iter_mut().find(|s| s.id == stream.id) {
+            s.target = Some(endpoint.id);
+            let updated = s.clone();
+            self.link_stream(scene, &updated, dragged);
+        }
+
+        Ok(true)
+    }
+
+    /// Set volume from a slider (0.0 - 1.0)
+    pub async fn set_volume(&mut self, node_id: SceneId, volume: f32) -> Result<()> {
+        let id = self.endpoint_for_node(node_id).map(|e| e.id)
+            .or_else(|| self.stream_for_node(node_id).map(|s| s.id))
+            .ok_or_else(|| anyhow!("Node {} is not an audio node", node_id))?;
+
+        let volume = volume.clamp(0.0, 1.0);
+        self.client.set_volume(id, volume).await?;
+
+        if let Some(slider) = self.sliders.get_mut(&node_id) {
+            slider.volume = volume;
+        }
+
+        Ok(())
+    }
+
+    /// Keep sliders attached to their nodes as the layout moves them
+    pub fn update_slider_positions(&mut self, scene: &Scene) {
+        for slider in self.sliders.values_mut() {
+            if let Some(position) = scene.get_node_position(slider.node_id) {
+                slider.position = (
+                    position.x + slider.anchor_offset.0,
+                    position.y + slider.anchor_offset.1,
+                    position.z + slider.anchor_offset.2,
+                );
+            }
+        }
+    }
+
+    fn endpoint_for_node(&self, node_id: SceneId) -> Option<&AudioEndpoint> {
+        let (&id, _) = self.endpoint_nodes.iter().find(|(_, &n)| n == node_id)?;
+        self.snapshot.endpoints.iter().find(|e| e.id == id)
+    }
+
+    fn stream_for_node(&self, node_id: SceneId) -> Option<&AudioStream> {
+        let (&id, _) = self.stream_nodes.iter().find(|(_, &n)| n == node_id)?;
+        self.snapshot.streams.iter().find(|s| s.id == id)
+    }
+
+    fn card_for_endpoint(&self, endpoint: &AudioEndpoint) -> Option<&AudioCard> {
+        let card_id = endpoint.card_id?;
+        self.snapshot.cards.iter().find(|c| c.id == card_id)
+    }
+
+    /// Recreate the edges of a stream to its application and endpoint
+    fn link_stream(&mut self, scene: &mut Scene, stream: &AudioStream, node_id: SceneId) {
+        for edge_id in self.stream_edges.remove(&stream.id).unwrap_or_default() {
+            scene.remove_edge(edge_id);
+        }
+
+        let mut edges = Vec::new();
+
+        let app_node = stream.pid.and_then(|pid| {
+            scene.nodes().find_map(|(&id, node)| match &node.node_type {
+                NodeType::Application { pid: app_pid, .. } if *app_pid == pid => Some(id),
+                _ => None,
+            })
+        });
+        if let Some(app_node) = app_node {
+            edges.push(scene.add_edge(Self::edge(node_id, app_node, EdgeType::CreatedBy, false)));
+        }
+
+        if let Some(endpoint_node) = stream.target.and_then(|t| self.endpoint_nodes.get(&t)) {
+            let (source, target) = match stream.direction {
+                AudioDirection::Output => (node_id, *endpoint_node),
+                AudioDirection::Input => (*endpoint_node, node_id),
+            };
+            edges.push(scene.add_edge(Self::edge(source, target, EdgeType::CommunicatesWith, !stream.muted)));
+        }
+
+        self.stream_edges.insert(stream.id, edges);
+    }
+
+    fn update_slider(&mut self, scene: &Scene, node_id: SceneId, volume: f32, muted: bool) {
+        let radius = scene.get_node(node_id).map(|n| n.radius).unwrap_or(1.0);
+        let slider = self.sliders.entry(node_id).or_insert_with(|| VolumeSliderWidget {
+            node_id,
+            anchor_offset: (radius + 0.3, 0.0, 0.0),
+            position: (0.0, 0.0, 0.0),
+            size: (24.0, 120.0),
+            volume,
+            muted,
+        });
+        slider.volume = volume.clamp(0.0, 1.0);
+        slider.muted = muted;
+
+        if let Some(position) = scene.get_node_position(node_id) {
+            slider.position = (
+                position.x + slider.anchor_offset.0,
+                position.y + slider.anchor_offset.1,
+                position.z + slider.anchor_offset.2,
+            );
+        }
+    }
+
+    fn endpoint_scene_node(endpoint: &AudioEndpoint) -> SceneNode {
+        let mut metadata = NodeMetadata::default();
+        metadata.tags.push("audio".to_string());
+        metadata.properties.insert("pipewire.id".to_string(), endpoint.id.to_string());
+        metadata.properties.insert("pipewire.node_name".to_string(), endpoint.name.clone());
+
+        SceneNode {
+            id: 0,
+            position: Position::new(0.0, 0.0, 0.0),
+            velocity: Vec3::zeros(),
+            radius: 1.2,
+            color: match endpoint.direction {
+                AudioDirection::Output => [0.2, 0.6, 0.9, 1.0],
+                AudioDirection::Input => [0.9, 0.5, 0.2, 1.0],
+            },
+            node_type: NodeType::Device {
+                name: endpoint.description.clone(),
+                device_type: match endpoint.direction {
+                    AudioDirection::Output => DeviceType::AudioOutput,
+                    AudioDirection::Input => DeviceType::AudioInput,
+                },
+            },
+            metadata,
+            visible: true,
+            selected: false,
+        }
+    }
+
+    fn update_endpoint_node(node: &mut SceneNode, endpoint: &AudioEndpoint, card: Option<&AudioCard>) {
+        let properties = &mut node.metadata.properties;
+        properties.insert("audio.volume".to_string(), format!("{:.2}", endpoint.volume));
+        properties.insert("audio.muted".to_string(), endpoint.muted.to_string());
+        properties.insert("audio.default".to_string(), endpoint.is_default.to_string());
+
+        match card.and_then(|c| c.profiles.iter().find(|p| Some(p.index) == c.active_profile)) {
+            Some(profile) => {
+                properties.insert("audio.profile".to_string(), profile.description.clone());
+            }
+            None => {
+                properties.remove("audio.profile");
+            }
+        }
+
+        node.metadata.description = Some(if endpoint.is_default {
+            format!("{} (default)", endpoint.description)
+        } else {
+            endpoint.description.clone()
+        });
+    }
+
+    fn stream_scene_node(stream: &AudioStream) -> SceneNode {
+        let mut metadata = NodeMetadata::default();
+        metadata.tags.push("audio".to_string());
+        metadata.tags.push("audio-stream".to_string());
+        metadata.properties.insert("pipewire.id".to_string(), stream.id.to_string());
+        metadata.description = stream.media_name.clone();
+
+        SceneNode {
+            id: 0,
+            position: Position::new(0.0, 0.0, 0.0),
+            velocity: Vec3::zeros(),
+            radius: 0.6,
+            color: [0.4, 0.8, 0.5, 1.0],
+            node_type: NodeType::System {
+                component: Self::stream_label(stream),
+                status: SystemStatus::Running,
+            },
+            metadata,
+            visible: true,
+            selected: false,
+        }
+    }
+
+    fn stream_label(stream: &AudioStream) -> String {
+        match stream.direction {
+            AudioDirection::Output => format!("{} playback", stream.app_name),
+            AudioDirection::Input => format!("{} recording", stream.app_name),
+        }
+    }
+
+    fn edge(source: SceneId, target: SceneId, edge_type: EdgeType, animated: bool) -> SceneEdge {
+        SceneEdge {
+            id: 0,
+            source,
+            target,
+            edge_type,
+            weight: 1.0,
+            color: [0.4, 0.8, 0.5, 0.8],
+            visible: true,
+            animated,
+        }
+    }
+}
+
+impl Default for PipeWireClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for AudioGraphManager {
+    fn default() -> Self {
+        Self::new(PipeWireClient::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_dump() -> serde_json::Value {
+        serde_json::json!([
+            {
+                "id": 40, "type": "PipeWire:Interface:Device",
+                "info": {
+                    "props": { "media.class": "Audio/Device", "device.description": "Built-in Audio" },
+                    "params": {
+                        "EnumProfile": [
+                            { "index": 1, "name": "output:analog-stereo", "description": "Analog Stereo", "available": "yes" },
+                            { "index": 2, "name": "output:hdmi-stereo", "description": "HDMI Stereo", "available": "yes" }
+                        ],
+                        "Profile": [ { "index": 1 } ]
+                    }
+                }
+            },
+            {
+                "id": 50, "type": "PipeWire:Interface:Node",
+                "info": {
+                    "props": { "media.class": "Audio/Sink", "node.name": "alsa_output.analog", "node.description": "Speakers", "device.id": 40 },
+                    "params": { "Props": [ { "channelVolumes": [0.125, 0.125], "mute": false } ] }
+                }
+            },
+            {
+                "id": 60, "type": "PipeWire:Interface:Node",
+                "info": {
+                    "props": { "media.class": "Stream/Output/Audio", "application.name": "Firefox", "application.process.id": 1234 },
+                    "params": { "Props": [ { "channelVolumes": [1.0], "mute": false } ] }
+                }
+            },
+            { "id": 70, "type": "PipeWire:Interface:Link", "info": { "output-node-id": 60, "input-node-id": 50 } },
+            {
+                "id": 0, "type": "PipeWire:Interface:Metadata",
+                "props": { "metadata.name": "default" },
+                "metadata": [ { "subject": 0, "key": "default.audio.sink", "value": { "name": "alsa_output.analog" } } ]
+            }
+        ])
+    }
+
+    #[test]
+    fn test_parse_pw_dump() {
+        let snapshot = parse_pw_dump(&sample_dump());
+
+        assert_eq!(snapshot.endpoints.len(), 1);
+        let speakers = &snapshot.endpoints[0];
+        assert_eq!(speakers.direction, AudioDirection::Output);
+        assert!((speakers.volume - 0.5).abs() < 1e-4);
+        assert_eq!(speakers.card_id, Some(40));
+
+        assert_eq!(snapshot.streams.len(), 1);
+        assert_eq!(snapshot.streams[0].pid, Some(1234));
+        assert_eq!(snapshot.streams[0].target, Some(50));
+
+        assert_eq!(snapshot.cards[0].profiles.len(), 2);
+        assert_eq!(snapshot.cards[0].active_profile, Some(1));
+    }
+
+    #[test]
+    fn test_apply_snapshot_builds_graph() {
+        let mut scene = Scene::new();
+        let app_node = scene.add_node(SceneNode {
+            id: 0,
+            position: Position::new(0.0, 0.0, 0.0),
+            velocity: Vec3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type: NodeType::Application { pid: 1234, name: "Firefox".to_string() },
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        });
+
+        let mut manager = AudioGraphManager::default();
+        manager.apply_snapshot(&mut scene, parse_pw_dump(&sample_dump()));
+
+        // Speakers, stream and the existing app node
+        assert_eq!(scene.nodes().count(), 3);
+        assert_eq!(manager.sliders().count(), 2);
+
+        let stream_node = manager.stream_nodes[&60];
+        let speakers_node = manager.endpoint_nodes[&50];
+        let edges = scene.get_connected_edges(stream_node);
+        assert!(edges.iter().any(|e| e.target == app_node));
+        assert!(edges.iter().any(|e| e.target == speakers_node));
+
+        // Speakers are already default; HDMI is the only other profile
+        let actions = manager.available_actions(speakers_node);
+        assert!(!actions.contains(&NodeActionType::Custom(ACTION_SET_DEFAULT.to_string())));
+        assert!(actions.contains(&NodeActionType::Custom(format!("{}output:hdmi-stereo", ACTION_SET_PROFILE_PREFIX))));
+
+        // Applying again does not duplicate nodes or edges
+        manager.apply_snapshot(&mut scene, parse_pw_dump(&sample_dump()));
+        assert_eq!(scene.nodes().count(), 3);
+        assert_eq!(scene.get_connected_edges(stream_node).len(), 2);
+    }
+}
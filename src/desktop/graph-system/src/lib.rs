@@ -5,9 +5,11 @@ pub mod tray;
 pub mod monitors;
 pub mod power;
 pub mod media;
+pub mod audio;
 
 pub use dbus::{EnhancedDBusManager, DBusManager, MediaAction};
 pub use tray::{SystemTrayManager, TrayItem, GraphTrayIntegration};
-pub use monitors::{MonitorManager, Monitor, MonitorLayout, GraphViewport};
+pub use monitors::{MonitorManager, Monitor, MonitorLayout, GraphViewport, MonitorCrossing};
 pub use power::{PowerManager, PowerProfile, GraphPowerSettings, NodePowerManager};
-pub use media::{MediaManager, MediaPlayer, VolumeControl, MediaControlWidget};
\ No newline at end of file
+pub use media::{MediaManager, MediaPlayer, VolumeControl, MediaControlWidget};
+pub use audio::{AudioGraphManager, PipeWireClient, AudioGraphSnapshot, VolumeSliderWidget};
\ No newline at end of file
@@ -1,6 +1,18 @@
 //! Multi-monitor support for the graph desktop
+//!
+//! Every output has a viewport into the graph. By default it shows the
+//! active workspace through its own camera; it can be pinned to another
+//! workspace instead, so that each output shows a different workspace or a
+//! different view of the same one. Dragging a node from one output onto
+//! another yields a [`MonitorCrossing`], telling the caller where the node
+//! lands in graph space and which workspace it moves to.
+//!
+//! The layout and each output's settings are kept in
+//! [`MonitorsConfig`], by output ID, so an output gets its position,
+//! workspace and camera back when it is connected again.
 
 use anyhow::Result;
+use horizonos_graph_config::{MonitorsConfig, OutputCamera, OutputConfig};
 use nalgebra::Point3;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -16,6 +28,8 @@ pub struct MonitorManager {
     layout: Arc<RwLock<MonitorLayout>>,
     /// Graph viewport assignments
     viewports: Arc<RwLock<HashMap<String, GraphViewport>>>,
+    /// Settings of every output seen, including disconnected ones
+    config: Arc<RwLock<MonitorsConfig>>,
 }
 
 /// Individual monitor information
@@ -132,10 +146,32 @@ pub struct GraphViewport {
     pub camera_target: Point3<f32>,
     /// Zoom level
     pub zoom: f32,
-    /// Workspace assignment
+    /// Workspace shown on the monitor; `None` follows the active workspace
     pub workspace: Option<String>,
 }
 
+/// A dragged node moving from one monitor onto another
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorCrossing {
+    /// Monitor the node was dragged from
+    pub from_monitor: String,
+    /// Monitor the node was dragged onto
+    pub to_monitor: String,
+    /// Workspace shown on the monitor it was dragged from
+    pub from_workspace: Option<String>,
+    /// Workspace shown on the monitor it was dragged onto
+    pub to_workspace: Option<String>,
+    /// Graph-space position under the pointer, seen through the new monitor's camera
+    pub position: Point3<f32>,
+}
+
+impl MonitorCrossing {
+    /// Whether the node has to move to another workspace
+    pub fn changes_workspace(&self) -> bool {
+        self.from_workspace != self.to_workspace
+    }
+}
+
 /// Viewport bounds
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct ViewportBounds {
@@ -153,9 +189,17 @@ impl MonitorManager {
             primary: Arc::new(RwLock::new(None)),
             layout: Arc::new(RwLock::new(MonitorLayout::default())),
             viewports: Arc::new(RwLock::new(HashMap::new())),
+            config: Arc::new(RwLock::new(MonitorsConfig::default())),
         }
     }
     
+    /// Create a monitor manager with a saved monitor layout
+    pub fn with_config(config: MonitorsConfig) -> Self {
+        let manager = Self::new();
+        manager.apply_config(config);
+        manager
+    }
+    
     /// Detect available monitors
     pub async fn detect_monitors(&self) -> Result<Vec<Monitor>> {
         let mut detected = Vec::new();
@@ -213,6 +257,15 @@ impl MonitorManager {
         // Create default viewport for the monitor
         self.create_viewport_for_monitor(&id)?;
         
+        // A monitor seen before gets its settings back
+        let remembered = self.config.read().unwrap().outputs.get(&id).cloned();
+        if let Some(output) = remembered {
+            self.apply_output_config(&id, &output);
+        }
+        if self.config.read().unwrap().primary.as_deref() == Some(id.as_str()) {
+            self.set_primary(&id)?;
+        }
+        
         // Update layout
         self.update_layout()?;
         
@@ -221,6 +274,8 @@ impl MonitorManager {
     
     /// Remove a monitor
     pub fn remove_monitor(&self, id: &str) -> Result<()> {
+        // Keep its settings for when it is connected again
+        self.remember_output(id);
         self.monitors.write().unwrap().remove(id);
         self.viewports.write().unwrap().remove(id);
         
@@ -304,6 +359,177 @@ impl MonitorManager {
         self.viewports.read().unwrap().get(monitor_id).cloned()
     }
     
+    /// Show a workspace on a monitor; `None` makes it follow the active workspace
+    pub fn assign_workspace(&self, monitor_id: &str, workspace: Option<&str>) -> Result<()> {
+        let mut viewports = self.viewports.write().unwrap();
+        let viewport = viewports.get_mut(monitor_id)
+            .ok_or_else(|| anyhow::anyhow!("Monitor {} not found", monitor_id))?;
+        viewport.workspace = workspace.map(str::to_string);
+        Ok(())
+    }
+    
+    /// Point a monitor's camera into the graph
+    pub fn set_camera(&self, monitor_id: &str, position: Point3<f32>, target: Point3<f32>, zoom: f32) -> Result<()> {
+        if zoom <= 0.0 {
+            return Err(anyhow::anyhow!("Zoom must be positive"));
+        }
+        let mut viewports = self.viewports.write().unwrap();
+        let viewport = viewports.get_mut(monitor_id)
+            .ok_or_else(|| anyhow::anyhow!("Monitor {} not found", monitor_id))?;
+        viewport.camera_position = position;
+        viewport.camera_target = target;
+        viewport.zoom = zoom;
+        Ok(())
+    }
+    
+    /// Workspace a monitor shows, given the active workspace
+    pub fn workspace_on(&self, monitor_id: &str, active_workspace: Option<&str>) -> Option<String> {
+        let viewports = self.viewports.read().unwrap();
+        let viewport = viewports.get(monitor_id)?;
+        viewport.workspace.clone().or_else(|| active_workspace.map(str::to_string))
+    }
+    
+    /// Monitors showing a workspace, given the active workspace, sorted by ID
+    pub fn monitors_showing(&self, workspace_id: &str, active_workspace: Option<&str>) -> Vec<String> {
+        let mut monitors: Vec<String> = self.viewports.read().unwrap()
+            .values()
+            .filter(|viewport| {
+                viewport.workspace.as_deref().or(active_workspace) == Some(workspace_id)
+            })
+            .map(|viewport| viewport.monitor_id.clone())
+            .collect();
+        monitors.sort();
+        monitors
+    }
+    
+    /// Graph-space point under a global screen position, seen through its monitor's camera
+    ///
+    /// The viewport bounds are the extent visible at zoom 1, centred on the
+    /// camera target; screen y grows downwards, graph y upwards.
+    pub fn screen_to_graph(&self, global_x: i32, global_y: i32) -> Option<(String, Point3<f32>)> {
+        let monitor = self.get_monitor_at_position(global_x, global_y)?;
+        let viewport = self.get_viewport(&monitor.id)?;
+        let (mx, my) = monitor.position;
+        let (width, height) = monitor.resolution;
+        let u = (global_x - mx) as f32 / width as f32;
+        let v = (global_y - my) as f32 / height as f32;
+        
+        let half_width = (viewport.bounds.max.x - viewport.bounds.min.x) / 2.0 / viewport.zoom;
+        let half_height = (viewport.bounds.max.y - viewport.bounds.min.y) / 2.0 / viewport.zoom;
+        let target = viewport.camera_target;
+        let position = Point3::new(
+            target.x + (u * 2.0 - 1.0) * half_width,
+            target.y + (1.0 - v * 2.0) * half_height,
+            target.z,
+        );
+        Some((monitor.id, position))
+    }
+    
+    /// Follow a node dragged from one global position to another
+    ///
+    /// Returns `None` while the drag stays on one monitor, or leaves every
+    /// monitor; otherwise the node lands on the new monitor at the returned
+    /// position, and in its workspace when that differs.
+    pub fn drag_node(&self, from: (i32, i32), to: (i32, i32), active_workspace: Option<&str>) -> Option<MonitorCrossing> {
+        let from_monitor = self.get_monitor_at_position(from.0, from.1)?;
+        let (to_monitor, position) = self.screen_to_graph(to.0, to.1)?;
+        if from_monitor.id == to_monitor {
+            return None;
+        }
+        
+        Some(MonitorCrossing {
+            from_workspace: self.workspace_on(&from_monitor.id, active_workspace),
+            to_workspace: self.workspace_on(&to_monitor, active_workspace),
+            from_monitor: from_monitor.id,
+            to_monitor,
+            position,
+        })
+    }
+    
+    /// Take over a saved monitor layout, applying it to the connected monitors
+    pub fn apply_config(&self, config: MonitorsConfig) {
+        {
+            let mut layout = self.layout.write().unwrap();
+            layout.mode = LayoutMode::from_name(&config.layout_mode).unwrap_or_else(|| {
+                log::warn!("Unknown monitor layout mode {}, using automatic", config.layout_mode);
+                LayoutMode::Automatic
+            });
+            layout.alignment = LayoutAlignment::from_name(&config.alignment).unwrap_or(LayoutAlignment::Middle);
+            layout.positions = config.outputs.iter()
+                .map(|(id, output)| (id.clone(), output.position))
+                .collect();
+        }
+        
+        let connected: Vec<String> = self.monitors.read().unwrap().keys().cloned().collect();
+        for id in &connected {
+            if let Some(output) = config.outputs.get(id) {
+                self.apply_output_config(id, output);
+            }
+        }
+        if let Some(primary) = config.primary.as_deref().filter(|id| connected.iter().any(|c| c == id)) {
+            let _ = self.set_primary(primary);
+        }
+        *self.config.write().unwrap() = config;
+        
+        if let Err(e) = self.update_layout() {
+            log::warn!("Could not apply monitor layout: {}", e);
+        }
+    }
+    
+    /// Current monitor layout for saving, with the settings of disconnected monitors kept
+    pub fn to_config(&self) -> MonitorsConfig {
+        let ids: Vec<String> = self.monitors.read().unwrap().keys().cloned().collect();
+        for id in &ids {
+            self.remember_output(id);
+        }
+        
+        let layout = self.layout.read().unwrap();
+        let mut config = self.config.write().unwrap();
+        config.layout_mode = layout.mode.name().to_string();
+        config.alignment = layout.alignment.name().to_string();
+        if let Some(primary) = self.primary.read().unwrap().clone() {
+            config.primary = Some(primary);
+        }
+        config.clone()
+    }
+    
+    /// Record a connected monitor's settings in the remembered configuration
+    fn remember_output(&self, monitor_id: &str) {
+        let Some(monitor) = self.get_monitor(monitor_id) else {
+            return;
+        };
+        let viewport = self.get_viewport(monitor_id);
+        let output = OutputConfig {
+            position: monitor.position,
+            scale: monitor.scale,
+            enabled: monitor.enabled,
+            workspace: viewport.as_ref().and_then(|viewport| viewport.workspace.clone()),
+            camera: viewport.map(|viewport| OutputCamera {
+                position: viewport.camera_position.into(),
+                target: viewport.camera_target.into(),
+                zoom: viewport.zoom,
+            }),
+        };
+        self.config.write().unwrap().outputs.insert(monitor_id.to_string(), output);
+    }
+    
+    /// Apply remembered settings to a connected monitor and its viewport
+    fn apply_output_config(&self, monitor_id: &str, output: &OutputConfig) {
+        if let Some(monitor) = self.monitors.write().unwrap().get_mut(monitor_id) {
+            monitor.scale = output.scale;
+            monitor.enabled = output.enabled;
+        }
+        self.layout.write().unwrap().positions.insert(monitor_id.to_string(), output.position);
+        if let Some(viewport) = self.viewports.write().unwrap().get_mut(monitor_id) {
+            viewport.workspace = output.workspace.clone();
+            if let Some(camera) = output.camera {
+                viewport.camera_position = camera.position.into();
+                viewport.camera_target = camera.target.into();
+                viewport.zoom = camera.zoom;
+            }
+        }
+    }
+    
     /// Create viewport for monitor
     fn create_viewport_for_monitor(&self, monitor_id: &str) -> Result<()> {
         if let Some(monitor) = self.get_monitor(monitor_id) {
@@ -347,32 +573,38 @@ impl MonitorManager {
     
     /// Update monitor layout
     fn update_layout(&self) -> Result<()> {
+        // Read before locking the monitors, which `get_primary` reads too
+        let primary_height = self.get_primary().map(|primary| primary.resolution.1 as i32);
         let layout = self.layout.read().unwrap();
         let mut monitors = self.monitors.write().unwrap();
         
+        // Side by side layouts go by ID, so monitors keep their places across restarts
+        let mut ordered: Vec<&mut Monitor> = monitors.values_mut().collect();
+        ordered.sort_by(|a, b| a.id.cmp(&b.id));
+        
         match layout.mode {
             LayoutMode::Automatic => {
                 // Arrange monitors side by side
                 let mut x_offset = 0;
-                for (_, monitor) in monitors.iter_mut() {
+                for monitor in ordered {
                     monitor.position = (x_offset, 0);
                     x_offset += monitor.resolution.0 as i32;
                 }
             }
             LayoutMode::Mirror => {
                 // All monitors at same position
-                for (_, monitor) in monitors.iter_mut() {
+                for monitor in ordered {
                     monitor.position = (0, 0);
                 }
             }
             LayoutMode::Extend => {
                 // Similar to automatic but with alignment
-                self.apply_extended_layout(&mut monitors, layout.alignment);
+                Self::apply_extended_layout(ordered, layout.alignment, primary_height);
             }
             LayoutMode::Custom => {
                 // Use positions from layout
-                for (id, position) in &layout.positions {
-                    if let Some(monitor) = monitors.get_mut(id) {
+                for monitor in ordered {
+                    if let Some(position) = layout.positions.get(&monitor.id) {
                         monitor.position = *position;
                     }
                 }
@@ -383,28 +615,17 @@ impl MonitorManager {
     }
     
     /// Apply extended layout with alignment
-    fn apply_extended_layout(&self, monitors: &mut HashMap<String, Monitor>, alignment: LayoutAlignment) {
+    fn apply_extended_layout(monitors: Vec<&mut Monitor>, alignment: LayoutAlignment, primary_height: Option<i32>) {
         let mut x_offset = 0;
         
-        for (_, monitor) in monitors.iter_mut() {
-            let y_offset = match alignment {
-                LayoutAlignment::Top => 0,
-                LayoutAlignment::Middle => {
-                    // Align to middle of primary monitor
-                    if let Some(primary) = self.get_primary() {
-                        (primary.resolution.1 as i32 - monitor.resolution.1 as i32) / 2
-                    } else {
-                        0
-                    }
-                }
-                LayoutAlignment::Bottom => {
-                    // Align to bottom of primary monitor
-                    if let Some(primary) = self.get_primary() {
-                        primary.resolution.1 as i32 - monitor.resolution.1 as i32
-                    } else {
-                        0
-                    }
-                }
+        for monitor in monitors {
+            let height = monitor.resolution.1 as i32;
+            let y_offset = match (alignment, primary_height) {
+                // Align to middle of primary monitor
+                (LayoutAlignment::Middle, Some(primary_height)) => (primary_height - height) / 2,
+                // Align to bottom of primary monitor
+                (LayoutAlignment::Bottom, Some(primary_height)) => primary_height - height,
+                _ => 0,
             };
             
             monitor.position = (x_offset, y_offset);
@@ -455,6 +676,43 @@ impl Default for MonitorLayout {
     }
 }
 
+impl LayoutMode {
+    /// Name used in the configuration
+    pub fn name(&self) -> &'static str {
+        match self {
+            LayoutMode::Automatic => "automatic",
+            LayoutMode::Mirror => "mirror",
+            LayoutMode::Extend => "extend",
+            LayoutMode::Custom => "custom",
+        }
+    }
+    
+    /// Layout mode with a configuration name
+    pub fn from_name(name: &str) -> Option<Self> {
+        [LayoutMode::Automatic, LayoutMode::Mirror, LayoutMode::Extend, LayoutMode::Custom]
+            .into_iter()
+            .find(|mode| mode.name() == name)
+    }
+}
+
+impl LayoutAlignment {
+    /// Name used in the configuration
+    pub fn name(&self) -> &'static str {
+        match self {
+            LayoutAlignment::Top => "top",
+            LayoutAlignment::Middle => "middle",
+            LayoutAlignment::Bottom => "bottom",
+        }
+    }
+    
+    /// Alignment with a configuration name
+    pub fn from_name(name: &str) -> Option<Self> {
+        [LayoutAlignment::Top, LayoutAlignment::Middle, LayoutAlignment::Bottom]
+            .into_iter()
+            .find(|alignment| alignment.name() == name)
+    }
+}
+
 impl MonitorRotation {
     /// Get rotation angle in degrees
     pub fn angle(&self) -> f32 {
@@ -476,32 +734,6 @@ impl MonitorRotation {
     }
 }
 
-/// Monitor configuration for persistence
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MonitorConfig {
-    /// Monitor configurations by ID
-    pub monitors: HashMap<String, MonitorSettings>,
-    /// Layout configuration
-    pub layout: MonitorLayout,
-    /// Primary monitor ID
-    pub primary: Option<String>,
-}
-
-/// Individual monitor settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MonitorSettings {
-    /// Resolution
-    pub resolution: (u32, u32),
-    /// Position
-    pub position: (i32, i32),
-    /// Rotation
-    pub rotation: MonitorRotation,
-    /// Scale factor
-    pub scale: f32,
-    /// Enabled state
-    pub enabled: bool,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -574,6 +806,60 @@ mod tests {
         assert_eq!(local_x, 100);
         assert_eq!(local_y, 200);
     }
+    
+    fn two_monitors() -> MonitorManager {
+        let manager = MonitorManager::new();
+        for (id, is_primary) in [("left", true), ("right", false)] {
+            manager.add_monitor(Monitor {
+                id: id.to_string(),
+                name: id.to_string(),
+                resolution: (1920, 1080),
+                is_primary,
+                ..Default::default()
+            }).unwrap();
+        }
+        manager
+    }
+    
+    #[test]
+    fn test_drag_node_across_monitors() {
+        let manager = two_monitors();
+        manager.assign_workspace("right", Some("media")).unwrap();
+        manager.set_camera("right", Point3::new(50.0, 0.0, 20.0), Point3::new(50.0, 0.0, 0.0), 2.0).unwrap();
+        
+        assert_eq!(manager.workspace_on("left", Some("work")), Some("work".to_string()));
+        assert_eq!(manager.monitors_showing("media", Some("work")), vec!["right".to_string()]);
+        
+        // Staying on one monitor is not a crossing
+        assert!(manager.drag_node((100, 100), (900, 500), Some("work")).is_none());
+        
+        // The centre of the right monitor is its camera target
+        let crossing = manager.drag_node((1900, 540), (1920 + 960, 540), Some("work")).unwrap();
+        assert_eq!(crossing.from_monitor, "left");
+        assert_eq!(crossing.to_monitor, "right");
+        assert!(crossing.changes_workspace());
+        assert_eq!(crossing.to_workspace.as_deref(), Some("media"));
+        assert!((crossing.position - Point3::new(50.0, 0.0, 0.0)).norm() < 1e-4);
+    }
+    
+    #[test]
+    fn test_monitor_config_survives_reconnect() {
+        let manager = two_monitors();
+        manager.assign_workspace("right", Some("media")).unwrap();
+        manager.set_camera("right", Point3::new(1.0, 2.0, 20.0), Point3::new(1.0, 2.0, 0.0), 1.5).unwrap();
+        
+        manager.remove_monitor("right").unwrap();
+        let config = manager.to_config();
+        assert_eq!(config.outputs["right"].workspace.as_deref(), Some("media"));
+        assert_eq!(config.primary.as_deref(), Some("left"));
+        
+        // A fresh session gets the saved settings back once the output shows up
+        let restored = MonitorManager::with_config(config);
+        restored.add_monitor(Monitor { id: "right".to_string(), ..Default::default() }).unwrap();
+        let viewport = restored.get_viewport("right").unwrap();
+        assert_eq!(viewport.workspace.as_deref(), Some("media"));
+        assert_eq!(viewport.zoom, 1.5);
+    }
 }
 
 impl Default for Monitor {
@@ -6,6 +6,7 @@ use tokio::sync::mpsc;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use std::path::Path;
+use horizonos_graph_performance::PowerState as RenderPowerState;
 
 /// Power manager for system power states
 pub struct PowerManager {
@@ -332,6 +333,14 @@ impl PowerManager {
         Ok(())
     }
     
+    /// Power state the renderer's power-saving path follows
+    pub fn render_power_state(&self) -> RenderPowerState {
+        RenderPowerState {
+            on_battery: self.get_battery_status().is_some_and(|battery| !battery.is_charging),
+            power_saver: self.get_power_profile() == PowerProfile::PowerSaver,
+        }
+    }
+    
     /// Get recommended graph settings for current power state
     pub fn get_graph_power_settings(&self) -> GraphPowerSettings {
         let profile = self.get_power_profile();
@@ -0,0 +1,622 @@
+//! Shared animation system
+//!
+//! Subsystems register tweens, keyframe timelines and springs with one
+//! [`AnimationSystem`] and read the animated values back by [`AnimationId`].
+//! The system is advanced once per frame with [`AnimationSystem::update`],
+//! which runs the animations in fixed time steps, so the same sequence of
+//! frame times always gives the same values; tests can drive it one step at a
+//! time with [`AnimationSystem::step`].
+//!
+//! Timelines use the theme's [`EasingFunction`]s. Spring stiffness and damping,
+//! the speed multiplier and whether springs are used at all come from the
+//! desktop's animation configuration. With reduce motion enabled animations
+//! skip to their end state instead of moving, and looping ones hold still.
+
+use crate::theme::{AnimationConfig as ThemeAnimationConfig, EasingFunction};
+use horizonos_graph_config::GraphDesktopConfig;
+use nalgebra::{Point3, Vector3};
+use std::collections::HashMap;
+
+/// Default fixed time step, in seconds
+const DEFAULT_STEP: f32 = 1.0 / 120.0;
+/// Frame time beyond which time is dropped instead of simulated, in seconds
+const MAX_FRAME_TIME: f32 = 0.25;
+/// Distance and speed below which a spring counts as settled
+const SPRING_REST: f32 = 1e-3;
+/// Duration of the tween that replaces a spring when springs are disabled
+const SPRING_FALLBACK_DURATION: f32 = 0.3;
+
+impl EasingFunction {
+    /// Eased progress for linear progress `t` in `0..=1`
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            EasingFunction::Linear => t,
+            EasingFunction::EaseIn => t * t,
+            EasingFunction::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            EasingFunction::EaseInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - 2.0 * (1.0 - t) * (1.0 - t)
+                }
+            }
+            EasingFunction::Bounce => {
+                if t < 1.0 / 2.75 {
+                    7.5625 * t * t
+                } else if t < 2.0 / 2.75 {
+                    let t = t - 1.5 / 2.75;
+                    7.5625 * t * t + 0.75
+                } else if t < 2.5 / 2.75 {
+                    let t = t - 2.25 / 2.75;
+                    7.5625 * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / 2.75;
+                    7.5625 * t * t + 0.984375
+                }
+            }
+            EasingFunction::Elastic => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    let p = 0.3;
+                    let s = p / 4.0;
+                    2.0_f32.powf(-10.0 * t) * ((t - s) * (2.0 * std::f32::consts::PI) / p).sin() + 1.0
+                }
+            }
+        }
+    }
+}
+
+/// Values that can be animated, as up to four channels
+pub trait Animatable: Copy {
+    fn to_channels(self) -> [f32; 4];
+    fn from_channels(channels: [f32; 4]) -> Self;
+}
+
+impl Animatable for f32 {
+    fn to_channels(self) -> [f32; 4] {
+        [self, 0.0, 0.0, 0.0]
+    }
+
+    fn from_channels(channels: [f32; 4]) -> Self {
+        channels[0]
+    }
+}
+
+impl Animatable for [f32; 2] {
+    fn to_channels(self) -> [f32; 4] {
+        [self[0], self[1], 0.0, 0.0]
+    }
+
+    fn from_channels(channels: [f32; 4]) -> Self {
+        [channels[0], channels[1]]
+    }
+}
+
+impl Animatable for [f32; 3] {
+    fn to_channels(self) -> [f32; 4] {
+        [self[0], self[1], self[2], 0.0]
+    }
+
+    fn from_channels(channels: [f32; 4]) -> Self {
+        [channels[0], channels[1], channels[2]]
+    }
+}
+
+impl Animatable for [f32; 4] {
+    fn to_channels(self) -> [f32; 4] {
+        self
+    }
+
+    fn from_channels(channels: [f32; 4]) -> Self {
+        channels
+    }
+}
+
+impl Animatable for Point3<f32> {
+    fn to_channels(self) -> [f32; 4] {
+        [self.x, self.y, self.z, 0.0]
+    }
+
+    fn from_channels(channels: [f32; 4]) -> Self {
+        Point3::new(channels[0], channels[1], channels[2])
+    }
+}
+
+impl Animatable for Vector3<f32> {
+    fn to_channels(self) -> [f32; 4] {
+        [self.x, self.y, self.z, 0.0]
+    }
+
+    fn from_channels(channels: [f32; 4]) -> Self {
+        Vector3::new(channels[0], channels[1], channels[2])
+    }
+}
+
+/// How a timeline continues after its last keyframe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    /// Stop on the last keyframe
+    Once,
+    /// Start over from the first keyframe
+    Loop,
+    /// Play backwards to the first keyframe, then forwards again
+    PingPong,
+}
+
+/// A keyframe of a timeline
+#[derive(Debug, Clone, Copy)]
+pub struct Keyframe<T> {
+    /// Seconds from the start of the timeline
+    pub time: f32,
+    pub value: T,
+    /// Easing of the segment that ends at this keyframe
+    pub easing: EasingFunction,
+}
+
+/// Keyframe animation
+#[derive(Debug, Clone)]
+pub struct Timeline<T> {
+    keyframes: Vec<Keyframe<T>>,
+    /// Seconds before the first keyframe starts moving
+    delay: f32,
+    repeat: Repeat,
+}
+
+impl<T: Animatable> Timeline<T> {
+    /// Timeline starting at `start`
+    pub fn new(start: T) -> Self {
+        Self {
+            keyframes: vec![Keyframe { time: 0.0, value: start, easing: EasingFunction::Linear }],
+            delay: 0.0,
+            repeat: Repeat::Once,
+        }
+    }
+
+    /// Animation from `from` to `to` over `duration` seconds
+    pub fn tween(from: T, to: T, duration: f32, easing: EasingFunction) -> Self {
+        Self::new(from).then(duration, to, easing)
+    }
+
+    /// Move on to `value` over `duration` seconds after the last keyframe
+    pub fn then(mut self, duration: f32, value: T, easing: EasingFunction) -> Self {
+        let time = self.duration() + duration.max(0.0);
+        self.keyframes.push(Keyframe { time, value, easing });
+        self
+    }
+
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.delay = delay.max(0.0);
+        self
+    }
+
+    pub fn with_repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Seconds from the first to the last keyframe, without the delay
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    fn into_channels(self) -> Timeline<[f32; 4]> {
+        Timeline {
+            keyframes: self
+                .keyframes
+                .into_iter()
+                .map(|k| Keyframe { time: k.time, value: k.value.to_channels(), easing: k.easing })
+                .collect(),
+            delay: self.delay,
+            repeat: self.repeat,
+        }
+    }
+}
+
+impl Timeline<[f32; 4]> {
+    /// Value `elapsed` seconds after the timeline was started, and whether it is over
+    fn sample(&self, elapsed: f32) -> ([f32; 4], bool) {
+        let duration = self.duration();
+        let t = elapsed - self.delay;
+        let (t, finished) = if t <= 0.0 {
+            (0.0, false)
+        } else if duration <= 0.0 {
+            (0.0, self.repeat == Repeat::Once)
+        } else {
+            match self.repeat {
+                Repeat::Once => (t.min(duration), t >= duration),
+                Repeat::Loop => (t % duration, false),
+                Repeat::PingPong => {
+                    let t = t % (2.0 * duration);
+                    (if t > duration { 2.0 * duration - t } else { t }, false)
+                }
+            }
+        };
+        (self.value_at(t), finished)
+    }
+
+    fn value_at(&self, t: f32) -> [f32; 4] {
+        let next = self.keyframes.iter().position(|k| k.time > t);
+        let Some(next) = next.filter(|next| *next > 0) else {
+            return self.keyframes.last().map_or([0.0; 4], |k| k.value);
+        };
+        let (from, to) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let progress = to.easing.apply((t - from.time) / (to.time - from.time));
+        lerp(from.value, to.value, progress)
+    }
+
+    fn end_value(&self) -> [f32; 4] {
+        self.keyframes.last().map_or([0.0; 4], |k| k.value)
+    }
+}
+
+/// Identifier of a registered animation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AnimationId(u64);
+
+enum Motion {
+    Timeline { timeline: Timeline<[f32; 4]>, elapsed: f32 },
+    Spring { target: [f32; 4], velocity: [f32; 4], stiffness: f32, damping: f32 },
+}
+
+struct Entry {
+    /// Subsystem that registered the animation
+    owner: String,
+    motion: Motion,
+    value: [f32; 4],
+    paused: bool,
+    finished: bool,
+}
+
+impl Entry {
+    fn advance(&mut self, dt: f32) {
+        match &mut self.motion {
+            Motion::Timeline { timeline, elapsed } => {
+                *elapsed += dt;
+                let (value, finished) = timeline.sample(*elapsed);
+                self.value = value;
+                self.finished = finished;
+            }
+            Motion::Spring { target, velocity, stiffness, damping } => {
+                // Semi-implicit Euler, stable for the fixed step sizes used here
+                let mut settled = true;
+                for i in 0..4 {
+                    let acceleration = -*stiffness * (self.value[i] - target[i]) - *damping * velocity[i];
+                    velocity[i] += acceleration * dt;
+                    self.value[i] += velocity[i] * dt;
+                    settled &= (self.value[i] - target[i]).abs() < SPRING_REST && velocity[i].abs() < SPRING_REST;
+                }
+                if settled {
+                    self.value = *target;
+                    *velocity = [0.0; 4];
+                    self.finished = true;
+                }
+            }
+        }
+    }
+
+    /// Jump to the end state; looping timelines stay where they are
+    fn skip_to_end(&mut self) {
+        match &mut self.motion {
+            Motion::Timeline { timeline, .. } if timeline.repeat != Repeat::Once => {}
+            Motion::Timeline { timeline, .. } => {
+                self.value = timeline.end_value();
+                self.finished = true;
+            }
+            Motion::Spring { target, velocity, .. } => {
+                self.value = *target;
+                *velocity = [0.0; 4];
+                self.finished = true;
+            }
+        }
+    }
+}
+
+/// Runs the animations of all subsystems
+pub struct AnimationSystem {
+    entries: HashMap<AnimationId, Entry>,
+    next_id: u64,
+    /// Fixed time step, in seconds
+    step: f32,
+    /// Frame time not yet simulated
+    accumulator: f32,
+    /// Multiplier applied to frame times
+    speed: f32,
+    paused: bool,
+    reduce_motion: bool,
+    springs: bool,
+    spring_stiffness: f32,
+    spring_damping: f32,
+    /// Animations reported finished by the last update, removed on the next one
+    finished: Vec<AnimationId>,
+}
+
+impl AnimationSystem {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            next_id: 0,
+            step: DEFAULT_STEP,
+            accumulator: 0.0,
+            speed: 1.0,
+            paused: false,
+            reduce_motion: false,
+            springs: true,
+            spring_stiffness: 300.0,
+            spring_damping: 20.0,
+            finished: Vec::new(),
+        }
+    }
+
+    /// Animation system using the desktop's animation and accessibility settings
+    pub fn from_config(config: &GraphDesktopConfig) -> Self {
+        let mut system = Self::new();
+        system.configure(config);
+        system
+    }
+
+    /// Apply changed animation and accessibility settings
+    pub fn configure(&mut self, config: &GraphDesktopConfig) {
+        let animations = &config.appearance.animations;
+        self.speed = animations.speed.max(0.0);
+        self.springs = animations.spring_animations;
+        self.spring_stiffness = animations.spring_stiffness;
+        self.spring_damping = animations.spring_damping;
+        self.set_reduce_motion(config.accessibility.reduce_motion || !animations.enabled);
+    }
+
+    /// Change the fixed time step, in seconds
+    pub fn with_step(mut self, step: f32) -> Self {
+        self.step = step.max(1e-4);
+        self
+    }
+
+    /// Skip animations to their end state, e.g. when the user asks for reduced motion
+    pub fn set_reduce_motion(&mut self, reduce_motion: bool) {
+        self.reduce_motion = reduce_motion;
+        if reduce_motion {
+            for entry in self.entries.values_mut() {
+                entry.skip_to_end();
+            }
+        }
+    }
+
+    pub fn reduce_motion(&self) -> bool {
+        self.reduce_motion
+    }
+
+    /// Register a keyframe timeline
+    pub fn timeline<T: Animatable>(&mut self, owner: &str, timeline: Timeline<T>) -> AnimationId {
+        let timeline = timeline.into_channels();
+        let (value, _) = timeline.sample(0.0);
+        self.insert(owner, Motion::Timeline { timeline, elapsed: 0.0 }, value)
+    }
+
+    /// Register an animation from `from` to `to` over `duration` seconds
+    pub fn tween<T: Animatable>(&mut self, owner: &str, from: T, to: T, duration: f32, easing: EasingFunction) -> AnimationId {
+        self.timeline(owner, Timeline::tween(from, to, duration, easing))
+    }
+
+    /// Register a tween with the duration, delay and easing of a theme animation
+    pub fn tween_with<T: Animatable>(&mut self, owner: &str, from: T, to: T, config: &ThemeAnimationConfig) -> AnimationId {
+        self.timeline(owner, Timeline::tween(from, to, config.duration, config.easing).with_delay(config.delay))
+    }
+
+    /// Register a spring from `from` towards `to` with the configured stiffness and damping
+    ///
+    /// When springs are disabled this is a short ease-out tween instead.
+    pub fn spring<T: Animatable>(&mut self, owner: &str, from: T, to: T) -> AnimationId {
+        if !self.springs {
+            return self.tween(owner, from, to, SPRING_FALLBACK_DURATION, EasingFunction::EaseOut);
+        }
+        let motion = Motion::Spring {
+            target: to.to_channels(),
+            velocity: [0.0; 4],
+            stiffness: self.spring_stiffness,
+            damping: self.spring_damping,
+        };
+        self.insert(owner, motion, from.to_channels())
+    }
+
+    /// Send an animation to a new end value from wherever it is now
+    ///
+    /// Springs keep their velocity; timelines are replaced by a tween as long
+    /// as the original timeline, using its last easing.
+    pub fn retarget<T: Animatable>(&mut self, id: AnimationId, to: T) -> bool {
+        let Some(entry) = self.entries.get_mut(&id) else {
+            return false;
+        };
+        let to = to.to_channels();
+        match &mut entry.motion {
+            Motion::Spring { target, .. } => *target = to,
+            Motion::Timeline { timeline, elapsed } => {
+                let easing = timeline.keyframes.last().map_or(EasingFunction::Linear, |k| k.easing);
+                *timeline = Timeline::tween(entry.value, to, timeline.duration(), easing);
+                *elapsed = 0.0;
+            }
+        }
+        entry.finished = false;
+        self.finished.retain(|finished| *finished != id);
+        if self.reduce_motion {
+            entry.skip_to_end();
+        }
+        true
+    }
+
+    /// Current value of an animation
+    ///
+    /// Finished animations keep their final value until the next update.
+    pub fn value<T: Animatable>(&self, id: AnimationId) -> Option<T> {
+        self.entries.get(&id).map(|entry| T::from_channels(entry.value))
+    }
+
+    /// Whether an animation is still running
+    pub fn is_running(&self, id: AnimationId) -> bool {
+        self.entries.get(&id).is_some_and(|entry| !entry.finished)
+    }
+
+    /// Number of animations still running, e.g. to decide whether to keep redrawing
+    pub fn running(&self) -> usize {
+        self.entries.values().filter(|entry| !entry.finished).count()
+    }
+
+    pub fn set_paused(&mut self, id: AnimationId, paused: bool) {
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.paused = paused;
+        }
+    }
+
+    pub fn cancel(&mut self, id: AnimationId) -> bool {
+        self.entries.remove(&id).is_some()
+    }
+
+    /// Cancel every animation a subsystem registered
+    pub fn cancel_owner(&mut self, owner: &str) {
+        self.entries.retain(|_, entry| entry.owner != owner);
+    }
+
+    /// Stop or restart all animations
+    pub fn set_all_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Advance by one frame, returning the animations that finished in it
+    ///
+    /// Frame time is simulated in fixed steps; the remainder carries over to
+    /// the next frame.
+    pub fn update(&mut self, delta_time: f32) -> Vec<AnimationId> {
+        self.remove_finished();
+        if !self.paused {
+            self.accumulator = (self.accumulator + delta_time.max(0.0) * self.speed).min(MAX_FRAME_TIME);
+            while self.accumulator >= self.step {
+                self.accumulator -= self.step;
+                self.advance(self.step);
+            }
+        }
+        self.collect_finished()
+    }
+
+    /// Advance by exactly one fixed step, returning the animations that finished
+    pub fn step(&mut self) -> Vec<AnimationId> {
+        self.remove_finished();
+        self.advance(self.step);
+        self.collect_finished()
+    }
+
+    fn insert(&mut self, owner: &str, motion: Motion, value: [f32; 4]) -> AnimationId {
+        let id = AnimationId(self.next_id);
+        self.next_id += 1;
+        let mut entry = Entry { owner: owner.to_string(), motion, value, paused: false, finished: false };
+        if self.reduce_motion {
+            entry.skip_to_end();
+        }
+        self.entries.insert(id, entry);
+        id
+    }
+
+    fn advance(&mut self, dt: f32) {
+        if self.reduce_motion {
+            return;
+        }
+        for entry in self.entries.values_mut().filter(|entry| !entry.paused && !entry.finished) {
+            entry.advance(dt);
+        }
+    }
+
+    fn remove_finished(&mut self) {
+        for id in self.finished.drain(..) {
+            self.entries.remove(&id);
+        }
+    }
+
+    fn collect_finished(&mut self) -> Vec<AnimationId> {
+        let mut finished: Vec<AnimationId> =
+            self.entries.iter().filter(|(_, entry)| entry.finished).map(|(id, _)| *id).collect();
+        finished.sort();
+        self.finished = finished.clone();
+        finished
+    }
+}
+
+impl Default for AnimationSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn lerp(from: [f32; 4], to: [f32; 4], t: f32) -> [f32; 4] {
+    std::array::from_fn(|i| from[i] + (to[i] - from[i]) * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeline_keyframes() {
+        let mut system = AnimationSystem::new().with_step(0.125);
+        let timeline = Timeline::new(0.0f32)
+            .then(1.0, 10.0, EasingFunction::Linear)
+            .then(1.0, 0.0, EasingFunction::Linear)
+            .with_delay(0.5);
+        let id = system.timeline("test", timeline);
+
+        for _ in 0..12 {
+            assert!(system.step().is_empty());
+        }
+        assert_eq!(system.value::<f32>(id), Some(10.0));
+        for _ in 0..4 {
+            system.step();
+        }
+        assert_eq!(system.value::<f32>(id), Some(5.0));
+        for _ in 0..3 {
+            assert!(system.step().is_empty());
+        }
+        assert_eq!(system.step(), vec![id]);
+        assert_eq!(system.value::<f32>(id), Some(0.0));
+        system.step();
+        assert!(system.value::<f32>(id).is_none());
+    }
+
+    #[test]
+    fn test_update_is_frame_rate_independent() {
+        let run = |frame: f32, frames: usize| {
+            let mut system = AnimationSystem::new();
+            let id = system.spring("test", Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 2.0, 3.0));
+            for _ in 0..frames {
+                system.update(frame);
+            }
+            system.value::<Point3<f32>>(id).unwrap()
+        };
+        assert_eq!(run(1.0 / 60.0, 30), run(1.0 / 30.0, 15));
+
+        let mut system = AnimationSystem::new();
+        let id = system.spring("test", 0.0f32, 1.0);
+        let mut finished = Vec::new();
+        for _ in 0..600 {
+            finished.extend(system.update(1.0 / 60.0));
+        }
+        assert_eq!(finished, vec![id]);
+    }
+
+    #[test]
+    fn test_reduce_motion_skips_to_end() {
+        let mut system = AnimationSystem::new();
+        let tween = system.tween("ui", [0.0f32; 4], [1.0; 4], 1.0, EasingFunction::EaseInOut);
+        let looping = system.timeline("ui", Timeline::tween(0.0f32, 1.0, 1.0, EasingFunction::Linear).with_repeat(Repeat::Loop));
+        system.update(0.25);
+        let looping_value = system.value::<f32>(looping).unwrap();
+
+        system.set_reduce_motion(true);
+        assert_eq!(system.value::<[f32; 4]>(tween), Some([1.0; 4]));
+        assert!(!system.is_running(tween));
+        system.update(0.25);
+        assert_eq!(system.value::<f32>(looping), Some(looping_value));
+
+        let spring = system.spring("camera", 0.0f32, 4.0);
+        assert_eq!(system.value::<f32>(spring), Some(4.0));
+        system.cancel_owner("ui");
+        assert!(system.value::<f32>(looping).is_none());
+    }
+}
@@ -1,13 +1,32 @@
 //! Icon loading and management system
+//!
+//! Icons are resolved as the freedesktop.org icon theme specification
+//! describes: the configured theme is searched first, then the themes it
+//! inherits from, depth first, then `hicolor`, unthemed icons in
+//! `/usr/share/pixmaps`, and finally the icons bundled in the asset pack.
+//! Within a theme an exact size and scale match wins over the closest one.
+//!
+//! Resolving and rasterizing touch the disk, so they run on the blocking
+//! thread pool. The render thread asks with [`IconLoader::request_icon`],
+//! which only answers from the cache, and picks up finished icons from
+//! [`IconLoader::subscribe`].
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, RwLock};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
 use anyhow::{Result, Context};
 use image::DynamicImage;
 use tokio::fs;
 use log::debug;
 use dirs;
+use horizonos_graph_config::{ConfigChangeEvent, ConfigManager};
+use horizonos_graph_engine::{AssetData, AssetKind, AssetStore};
+
+/// Theme every theme implicitly inherits from
+const HICOLOR: &str = "hicolor";
+
+/// Icon file extensions in order of preference
+const ICON_EXTENSIONS: [&str; 3] = ["png", "svg", "xpm"];
 
 /// Icon sizes supported by the system
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -30,13 +49,105 @@ impl IconSize {
             IconSize::XXLarge,
         ]
     }
-    
+
     /// Get the pixel size
     pub fn pixels(&self) -> u32 {
         *self as u32
     }
 }
 
+/// Base directories icon themes are installed in, most important first
+pub fn icon_search_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        paths.push(home.join(".icons"));
+    }
+    if let Some(data) = dirs::data_dir() {
+        paths.push(data.join("icons"));
+    }
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    for dir in data_dirs.split(':').filter(|dir| !dir.is_empty()) {
+        let path = Path::new(dir).join("icons");
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+/// How the icons of a theme directory may be scaled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectoryType {
+    /// Only usable at its size
+    Fixed,
+    /// Usable between a minimum and maximum size
+    Scalable,
+    /// Usable within a threshold of its size
+    Threshold,
+}
+
+/// Subdirectory of an icon theme, as described in its `index.theme`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThemeDirectory {
+    /// Path relative to the theme directory
+    pub path: String,
+    pub size: u32,
+    pub scale: u32,
+    pub kind: DirectoryType,
+    pub min_size: u32,
+    pub max_size: u32,
+    pub threshold: u32,
+}
+
+impl ThemeDirectory {
+    fn new(path: &str, keys: &HashMap<String, String>) -> Option<Self> {
+        let number = |key: &str| keys.get(key).and_then(|value| value.trim().parse::<u32>().ok());
+        let size = number("Size")?;
+        let kind = match keys.get("Type").map(|value| value.trim()) {
+            Some("Fixed") => DirectoryType::Fixed,
+            Some("Scalable") => DirectoryType::Scalable,
+            _ => DirectoryType::Threshold,
+        };
+        Some(Self {
+            path: path.to_string(),
+            size,
+            scale: number("Scale").unwrap_or(1).max(1),
+            kind,
+            min_size: number("MinSize").unwrap_or(size),
+            max_size: number("MaxSize").unwrap_or(size),
+            threshold: number("Threshold").unwrap_or(2),
+        })
+    }
+
+    /// Whether the directory holds icons for this size and scale
+    pub fn matches(&self, size: u32, scale: u32) -> bool {
+        if self.scale != scale {
+            return false;
+        }
+        match self.kind {
+            DirectoryType::Fixed => self.size == size,
+            DirectoryType::Scalable => (self.min_size..=self.max_size).contains(&size),
+            DirectoryType::Threshold => {
+                (self.size.saturating_sub(self.threshold)..=self.size + self.threshold).contains(&size)
+            }
+        }
+    }
+
+    /// How far the directory's icons are from this size and scale, in pixels
+    pub fn distance(&self, size: u32, scale: u32) -> u32 {
+        let wanted = size * scale;
+        let (min, max) = match self.kind {
+            DirectoryType::Fixed => (self.size, self.size),
+            DirectoryType::Scalable => (self.min_size, self.max_size),
+            DirectoryType::Threshold => (self.size.saturating_sub(self.threshold), self.size + self.threshold),
+        };
+        (min * self.scale).saturating_sub(wanted) + wanted.saturating_sub(max * self.scale)
+    }
+}
+
 /// Icon theme following freedesktop.org specification
 #[derive(Debug, Clone)]
 pub struct IconTheme {
@@ -46,175 +157,442 @@ pub struct IconTheme {
     pub directories: Vec<PathBuf>,
     /// Inherits from these themes
     pub inherits: Vec<String>,
+    /// Subdirectories holding icons
+    pub subdirs: Vec<ThemeDirectory>,
 }
 
 impl IconTheme {
     /// Load system icon themes
     pub fn load_system_themes() -> Vec<Self> {
-        let mut themes = Vec::new();
-        
-        // Standard icon theme directories
-        let mut theme_dirs = vec![
-            "/usr/share/icons".to_string(),
-            "/usr/local/share/icons".to_string(),
-        ];
-        
-        if let Some(home) = dirs::home_dir() {
-            theme_dirs.push(home.join(".local/share/icons").to_string_lossy().to_string());
-        }
-        
-        for dir in theme_dirs {
-            if let Ok(entries) = std::fs::read_dir(dir) {
-                for entry in entries.flatten() {
-                    if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
-                        let theme_path = entry.path();
-                        let theme_name = theme_path.file_name()
-                            .and_then(|n| n.to_str())
-                            .unwrap_or_default()
-                            .to_string();
-                        
-                        themes.push(IconTheme {
-                            name: theme_name,
-                            directories: vec![theme_path],
-                            inherits: vec!["hicolor".to_string()],
-                        });
+        let search_paths = icon_search_paths();
+        let mut names = Vec::new();
+        for dir in &search_paths {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                if entry.path().join("index.theme").is_file() {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    if !names.contains(&name) {
+                        names.push(name);
                     }
                 }
             }
         }
-        
-        themes
+        names.iter()
+            .filter_map(|name| Self::find(name, &search_paths))
+            .collect()
+    }
+
+    /// Find a theme by name in the search paths
+    ///
+    /// The theme may be spread over several base directories; its
+    /// `index.theme` is read from the first that has one.
+    pub fn find(name: &str, search_paths: &[PathBuf]) -> Option<Self> {
+        let directories: Vec<PathBuf> = search_paths.iter()
+            .map(|base| base.join(name))
+            .filter(|dir| dir.is_dir())
+            .collect();
+        let index = directories.iter()
+            .find_map(|dir| std::fs::read_to_string(dir.join("index.theme")).ok())?;
+        Some(Self::parse(name, directories, &index))
+    }
+
+    /// Build a theme from the contents of its `index.theme`
+    pub fn parse(name: &str, directories: Vec<PathBuf>, index: &str) -> Self {
+        let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+        let mut current = None;
+        for line in index.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                current = Some(section.to_string());
+                sections.entry(section.to_string()).or_default();
+            } else if let (Some(section), Some((key, value))) = (&current, line.split_once('=')) {
+                sections.entry(section.clone()).or_default()
+                    .insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+
+        let header = sections.get("Icon Theme").cloned().unwrap_or_default();
+        let list = |key: &str| -> Vec<String> {
+            header.get(key)
+                .map(|value| value.split(',').map(str::trim).filter(|item| !item.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default()
+        };
+        let subdirs = list("Directories").into_iter()
+            .chain(list("ScaledDirectories"))
+            .filter_map(|path| ThemeDirectory::new(&path, sections.get(&path)?))
+            .collect();
+
+        Self {
+            name: name.to_string(),
+            directories,
+            inherits: list("Inherits"),
+            subdirs,
+        }
+    }
+
+    /// The theme followed by everything it inherits from, depth first,
+    /// ending with `hicolor`
+    ///
+    /// Themes that cannot be found are skipped; an unknown theme yields just
+    /// `hicolor`, if installed.
+    pub fn chain(name: &str, search_paths: &[PathBuf]) -> Vec<Self> {
+        fn visit(name: &str, search_paths: &[PathBuf], seen: &mut HashSet<String>, chain: &mut Vec<IconTheme>) {
+            if name == HICOLOR || !seen.insert(name.to_string()) {
+                return;
+            }
+            let Some(theme) = IconTheme::find(name, search_paths) else {
+                debug!("Icon theme '{}' not found", name);
+                return;
+            };
+            let parents = theme.inherits.clone();
+            chain.push(theme);
+            for parent in parents {
+                visit(&parent, search_paths, seen, chain);
+            }
+        }
+
+        let mut chain = Vec::new();
+        visit(name, search_paths, &mut HashSet::new(), &mut chain);
+        chain.extend(Self::find(HICOLOR, search_paths));
+        chain
+    }
+
+    /// Path of an icon in this theme, without looking at inherited themes
+    ///
+    /// A directory made for the size and scale is preferred; otherwise the
+    /// icon from the directory closest in size is used.
+    pub fn lookup(&self, icon_name: &str, size: u32, scale: u32) -> Option<PathBuf> {
+        let exact = self.subdirs.iter()
+            .filter(|subdir| subdir.matches(size, scale))
+            .find_map(|subdir| self.icon_in(subdir, icon_name));
+        if exact.is_some() {
+            return exact;
+        }
+
+        self.subdirs.iter()
+            .filter_map(|subdir| Some((subdir.distance(size, scale), self.icon_in(subdir, icon_name)?)))
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, path)| path)
+    }
+
+    fn icon_in(&self, subdir: &ThemeDirectory, icon_name: &str) -> Option<PathBuf> {
+        self.directories.iter()
+            .flat_map(|dir| ICON_EXTENSIONS.iter().map(move |ext| dir.join(&subdir.path).join(format!("{}.{}", icon_name, ext))))
+            .find(|path| path.is_file())
+    }
+}
+
+/// What a rasterized icon is cached under
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IconKey {
+    pub name: String,
+    /// Size in logical pixels
+    pub size: u32,
+    /// Display scale; the image is `size * scale` pixels wide
+    pub scale: u32,
+    /// Icon theme the icon was resolved in
+    pub theme: String,
+}
+
+/// An icon that finished loading in the background
+#[derive(Debug, Clone)]
+pub struct LoadedIcon {
+    pub key: IconKey,
+    pub image: Arc<DynamicImage>,
+}
+
+/// Where an icon was found
+enum IconSource {
+    File(PathBuf),
+    Asset { data: AssetData, is_svg: bool },
+}
+
+/// Everything icons are resolved from, replaced as a whole on theme changes
+#[derive(Clone)]
+struct IconSources {
+    /// Configured theme name
+    theme_name: String,
+    /// Configured theme, its ancestors and hicolor
+    themes: Vec<IconTheme>,
+    /// Base directories themes are looked up in
+    search_paths: Vec<PathBuf>,
+    /// Directories with unthemed icons
+    pixmap_dirs: Vec<PathBuf>,
+    /// Bundled icons
+    assets: Option<AssetStore>,
+    /// Icon used when nothing else is found
+    fallback_icon: Option<Arc<DynamicImage>>,
+}
+
+impl IconSources {
+    fn find(&self, icon_name: &str, size: u32, scale: u32) -> Option<IconSource> {
+        if let Some(path) = self.themes.iter().find_map(|theme| theme.lookup(icon_name, size, scale)) {
+            return Some(IconSource::File(path));
+        }
+
+        if let Some(path) = self.pixmap_dirs.iter()
+            .flat_map(|dir| ICON_EXTENSIONS.iter().map(move |ext| dir.join(format!("{}.{}", icon_name, ext))))
+            .find(|path| path.is_file())
+        {
+            return Some(IconSource::File(path));
+        }
+
+        // Bundled icons are packed as "<size>/<name>.png" or "scalable/<name>.svg"
+        let assets = self.assets.as_ref()?;
+        let candidates = [
+            (format!("{}/{}.png", size * scale, icon_name), false),
+            (format!("{}/{}.png", size, icon_name), false),
+            (format!("scalable/{}.svg", icon_name), true),
+        ];
+        candidates.into_iter().find_map(|(name, is_svg)| {
+            assets.load(AssetKind::Icon, &name).ok().map(|data| IconSource::Asset { data, is_svg })
+        })
+    }
+
+    /// Resolve and rasterize an icon; blocks on disk access
+    fn load(&self, key: &IconKey) -> Result<Arc<DynamicImage>> {
+        let pixels = key.size * key.scale;
+        let image = match self.find(&key.name, key.size, key.scale) {
+            Some(IconSource::File(path)) => {
+                let data = std::fs::read(&path)
+                    .with_context(|| format!("Failed to read icon file {}", path.display()))?;
+                let is_svg = path.extension().and_then(|e| e.to_str()) == Some("svg");
+                decode_icon(&data, is_svg, pixels)?
+            }
+            Some(IconSource::Asset { data, is_svg }) => decode_icon(&data, is_svg, pixels)?,
+            None => {
+                return self.fallback_icon.clone()
+                    .ok_or_else(|| anyhow::anyhow!("Icon '{}' not found", key.name));
+            }
+        };
+        Ok(Arc::new(image))
+    }
+}
+
+/// State shared with loads running in the background
+struct SharedIcons {
+    /// Rasterized icons
+    cache: RwLock<HashMap<IconKey, Arc<DynamicImage>>>,
+    /// Icons found in no source, so they are not searched for again
+    missing: RwLock<HashSet<IconKey>>,
+    /// Icons being loaded
+    pending: Mutex<HashSet<IconKey>>,
+    sources: RwLock<Arc<IconSources>>,
+    /// Receivers of icons loaded in the background
+    subscribers: Mutex<Vec<mpsc::Sender<LoadedIcon>>>,
+}
+
+impl SharedIcons {
+    fn sources(&self) -> Arc<IconSources> {
+        self.sources.read().unwrap().clone()
+    }
+
+    async fn load(&self, key: IconKey) -> Result<Arc<DynamicImage>> {
+        if let Some(cached) = self.cache.read().unwrap().get(&key) {
+            return Ok(cached.clone());
+        }
+
+        let sources = self.sources();
+        let load_key = key.clone();
+        let result = tokio::task::spawn_blocking(move || sources.load(&load_key))
+            .await
+            .context("Icon loading task failed")?;
+
+        // A theme switch while loading makes the result stale
+        if self.sources.read().unwrap().theme_name != key.theme {
+            return result;
+        }
+        match &result {
+            Ok(image) => {
+                self.cache.write().unwrap().insert(key, image.clone());
+            }
+            Err(_) => {
+                self.missing.write().unwrap().insert(key);
+            }
+        }
+        result
+    }
+
+    fn notify(&self, icon: LoadedIcon) {
+        self.subscribers.lock().unwrap().retain(|tx| tx.send(icon.clone()).is_ok());
     }
 }
 
 /// Icon loader with caching
+#[derive(Clone)]
 pub struct IconLoader {
-    /// Icon cache: (icon_name, size) -> image data
-    cache: Arc<RwLock<HashMap<(String, IconSize), Arc<DynamicImage>>>>,
-    /// Current icon theme
-    theme: IconTheme,
-    /// Fallback icon
-    fallback_icon: Option<Arc<DynamicImage>>,
+    shared: Arc<SharedIcons>,
 }
 
 impl IconLoader {
     /// Create new icon loader
     pub fn new(theme_name: Option<&str>) -> Self {
-        let themes = IconTheme::load_system_themes();
-        let theme = theme_name
-            .and_then(|name| themes.into_iter().find(|t| t.name == name))
-            .unwrap_or_else(|| IconTheme {
-                name: "hicolor".to_string(),
-                directories: vec![PathBuf::from("/usr/share/icons/hicolor")],
-                inherits: vec![],
-            });
-        
-        Self {
-            cache: Arc::new(RwLock::new(HashMap::new())),
-            theme,
+        Self::with_search_paths(theme_name, icon_search_paths(), vec![PathBuf::from("/usr/share/pixmaps")])
+    }
+
+    /// Create an icon loader looking for themes and unthemed icons in the
+    /// given directories only
+    pub fn with_search_paths(theme_name: Option<&str>, search_paths: Vec<PathBuf>, pixmap_dirs: Vec<PathBuf>) -> Self {
+        let theme_name = theme_name.unwrap_or(HICOLOR).to_string();
+        let sources = IconSources {
+            themes: IconTheme::chain(&theme_name, &search_paths),
+            theme_name,
+            search_paths,
+            pixmap_dirs,
+            assets: None,
             fallback_icon: None,
+        };
+        let shared = SharedIcons {
+            cache: RwLock::new(HashMap::new()),
+            missing: RwLock::new(HashSet::new()),
+            pending: Mutex::new(HashSet::new()),
+            sources: RwLock::new(Arc::new(sources)),
+            subscribers: Mutex::new(Vec::new()),
+        };
+        Self { shared: Arc::new(shared) }
+    }
+
+    /// Use icons from an asset pack when no theme has them
+    pub fn with_asset_store(self, assets: AssetStore) -> Self {
+        self.update_sources(|sources| sources.assets = Some(assets));
+        self
+    }
+
+    fn update_sources(&self, update: impl FnOnce(&mut IconSources)) {
+        let mut sources = self.shared.sources.write().unwrap();
+        update(Arc::make_mut(&mut sources));
+    }
+
+    /// Name of the configured icon theme
+    pub fn theme_name(&self) -> String {
+        self.shared.sources().theme_name.clone()
+    }
+
+    /// Names of the themes searched, in order
+    pub fn theme_chain(&self) -> Vec<String> {
+        self.shared.sources().themes.iter().map(|theme| theme.name.clone()).collect()
+    }
+
+    /// Receive icons as they finish loading in the background
+    pub fn subscribe(&self) -> mpsc::Receiver<LoadedIcon> {
+        let (tx, rx) = mpsc::channel();
+        self.shared.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    fn key(&self, icon_name: &str, size: u32, scale: u32) -> IconKey {
+        IconKey {
+            name: icon_name.to_string(),
+            size,
+            scale: scale.max(1),
+            theme: self.theme_name(),
         }
     }
-    
+
     /// Load an icon by name
     pub async fn load_icon(&self, icon_name: &str, size: IconSize) -> Result<Arc<DynamicImage>> {
-        // Check cache first
-        let cache_key = (icon_name.to_string(), size);
-        if let Some(cached) = self.cache.read().unwrap().get(&cache_key) {
-            return Ok(cached.clone());
-        }
-        
-        // Search for icon in theme directories
-        let icon = self.find_and_load_icon(icon_name, size).await?;
-        
-        // Cache the result
-        let icon_arc = Arc::new(icon);
-        self.cache.write().unwrap().insert(cache_key, icon_arc.clone());
-        
-        Ok(icon_arc)
+        self.load_icon_scaled(icon_name, size.pixels(), 1).await
     }
-    
-    /// Find and load icon from theme directories
-    async fn find_and_load_icon(&self, icon_name: &str, size: IconSize) -> Result<DynamicImage> {
-        let size_str = size.pixels().to_string();
-        let extensions = ["svg", "png", "xpm"];
-        
-        // Search in theme directories
-        for dir in &self.theme.directories {
-            // Try size-specific directories first
-            let size_dirs = vec![
-                dir.join(&size_str).join(&size_str),
-                dir.join(format!("{}x{}", size_str, size_str)),
-                dir.join("scalable"),
-            ];
-            
-            for size_dir in size_dirs {
-                for ext in &extensions {
-                    let icon_path = size_dir.join(format!("{}.{}", icon_name, ext));
-                    if icon_path.exists() {
-                        return self.load_icon_file(&icon_path, size).await;
-                    }
-                }
-            }
+
+    /// Load an icon at a logical size for a display scale
+    pub async fn load_icon_scaled(&self, icon_name: &str, size: u32, scale: u32) -> Result<Arc<DynamicImage>> {
+        self.shared.load(self.key(icon_name, size, scale)).await
+    }
+
+    /// Cached icon, or start loading it in the background
+    ///
+    /// Does not block, so it can be called from the render thread. `None`
+    /// while the icon loads; it is then sent to subscribers. Icons found
+    /// nowhere are not searched for again until the theme changes. Needs a
+    /// Tokio runtime to load on.
+    pub fn request_icon(&self, icon_name: &str, size: u32, scale: u32) -> Option<Arc<DynamicImage>> {
+        let key = self.key(icon_name, size, scale);
+        if let Some(cached) = self.shared.cache.read().unwrap().get(&key) {
+            return Some(cached.clone());
         }
-        
-        // Fallback to generic icon
-        if let Some(fallback) = &self.fallback_icon {
-            return Ok((**fallback).clone());
+        if !self.shared.missing.read().unwrap().contains(&key) {
+            self.spawn_load(key);
         }
-        
-        Err(anyhow::anyhow!("Icon '{}' not found", icon_name))
+        None
     }
-    
-    /// Load icon file and resize if needed
-    async fn load_icon_file(&self, path: &Path, target_size: IconSize) -> Result<DynamicImage> {
-        let data = fs::read(path).await
-            .context("Failed to read icon file")?;
-        
-        // Handle SVG files specially
-        if path.extension().and_then(|e| e.to_str()) == Some("svg") {
-            return self.load_svg(&data, target_size);
+
+    fn spawn_load(&self, key: IconKey) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            debug!("No runtime to load icon '{}' on", key.name);
+            return;
+        };
+        if !self.shared.pending.lock().unwrap().insert(key.clone()) {
+            return;
         }
-        
-        // Load other formats with image crate
-        let img = image::load_from_memory(&data)
-            .context("Failed to decode icon")?;
-        
-        // Resize if needed
-        let target_pixels = target_size.pixels();
-        if img.width() != target_pixels || img.height() != target_pixels {
-            Ok(img.resize_exact(
-                target_pixels,
-                target_pixels,
-                image::imageops::FilterType::Lanczos3
-            ))
-        } else {
-            Ok(img)
+        let shared = self.shared.clone();
+        runtime.spawn(async move {
+            let result = shared.load(key.clone()).await;
+            shared.pending.lock().unwrap().remove(&key);
+            match result {
+                Ok(image) => shared.notify(LoadedIcon { key, image }),
+                Err(e) => debug!("Failed to load icon '{}': {}", key.name, e),
+            }
+        });
+    }
+
+    /// Switch to another icon theme
+    ///
+    /// Icons cached under the previous theme are resolved again in the new
+    /// one in the background and sent to subscribers, so what is on screen
+    /// follows the switch. Returns whether the theme changed.
+    pub fn set_theme(&self, theme_name: &str) -> bool {
+        if self.theme_name() == theme_name {
+            return false;
         }
+        let themes = IconTheme::chain(theme_name, &self.shared.sources().search_paths);
+        self.update_sources(|sources| {
+            sources.theme_name = theme_name.to_string();
+            sources.themes = themes;
+        });
+        self.reresolve();
+        true
     }
-    
-    /// Load SVG file
-    fn load_svg(&self, data: &[u8], size: IconSize) -> Result<DynamicImage> {
-        // TODO: Implement proper SVG rendering when resvg API stabilizes
-        // For now, return a placeholder image
-        let target_size = size.pixels();
-        let img = image::RgbaImage::new(target_size, target_size);
-        Ok(DynamicImage::ImageRgba8(img))
+
+    fn reresolve(&self) {
+        let previous: Vec<IconKey> = self.shared.cache.write().unwrap().drain().map(|(key, _)| key).collect();
+        self.shared.missing.write().unwrap().clear();
+        let theme = self.theme_name();
+        for key in previous {
+            self.spawn_load(IconKey { theme: theme.clone(), ..key });
+        }
     }
-    
+
+    /// Follow icon theme changes of the appearance configuration
+    ///
+    /// Returns whether the icon theme changed.
+    pub fn apply_config_change(&self, event: &ConfigChangeEvent, config: &ConfigManager) -> bool {
+        match event {
+            ConfigChangeEvent::Initialized | ConfigChangeEvent::ConfigReloaded => {
+                self.set_theme(&config.config().appearance.icon_theme)
+            }
+            _ => false,
+        }
+    }
+
     /// Set fallback icon
     pub fn set_fallback_icon(&mut self, icon: DynamicImage) {
-        self.fallback_icon = Some(Arc::new(icon));
+        self.update_sources(|sources| sources.fallback_icon = Some(Arc::new(icon)));
+        self.shared.missing.write().unwrap().clear();
     }
-    
+
     /// Clear icon cache
     pub fn clear_cache(&self) {
-        self.cache.write().unwrap().clear();
+        self.shared.cache.write().unwrap().clear();
+        self.shared.missing.write().unwrap().clear();
     }
-    
+
     /// Get cache statistics
     pub fn cache_stats(&self) -> CacheStats {
-        let cache = self.cache.read().unwrap();
+        let cache = self.shared.cache.read().unwrap();
         CacheStats {
             total_icons: cache.len(),
             memory_usage: cache.values()
@@ -224,6 +602,48 @@ impl IconLoader {
     }
 }
 
+/// Decode icon bytes and resize to a square of `pixels`
+fn decode_icon(data: &[u8], is_svg: bool, pixels: u32) -> Result<DynamicImage> {
+    if is_svg {
+        return render_svg(data, pixels);
+    }
+
+    let img = image::load_from_memory(data)
+        .context("Failed to decode icon")?;
+
+    if img.width() != pixels || img.height() != pixels {
+        Ok(img.resize_exact(pixels, pixels, image::imageops::FilterType::Lanczos3))
+    } else {
+        Ok(img)
+    }
+}
+
+/// Rasterize an SVG icon to a square of `pixels`
+fn render_svg(data: &[u8], pixels: u32) -> Result<DynamicImage> {
+    use resvg::{tiny_skia, usvg};
+
+    let tree = usvg::Tree::from_data(data, &usvg::Options::default())
+        .context("Failed to parse SVG icon")?;
+    let mut pixmap = tiny_skia::Pixmap::new(pixels, pixels)
+        .context("Invalid icon size")?;
+    let size = tree.size();
+    let transform = tiny_skia::Transform::from_scale(
+        pixels as f32 / size.width(),
+        pixels as f32 / size.height(),
+    );
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+    let rgba: Vec<u8> = pixmap.pixels().iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+    let img = image::RgbaImage::from_raw(pixels, pixels, rgba)
+        .context("Failed to convert rendered SVG")?;
+    Ok(DynamicImage::ImageRgba8(img))
+}
+
 /// Cache statistics
 #[derive(Debug, Clone)]
 pub struct CacheStats {
@@ -386,5 +806,70 @@ mod tests {
         assert_eq!(mapper.icon_for_mime_type("application/pdf"), Some("application-pdf"));
         assert_eq!(mapper.icon_for_extension("rs"), Some("text-x-rust"));
         assert_eq!(mapper.icon_for_file(Path::new("test.py")), "text-x-python");
+    }    
+    fn write_theme(base: &Path, name: &str, inherits: &str, icons: &[(&str, u32, &str)]) {
+        let dir = base.join(name);
+        let mut dirs: Vec<String> = icons.iter().map(|(subdir, _, _)| subdir.to_string()).collect();
+        dirs.dedup();
+        let mut index = format!("[Icon Theme]\nName={}\nInherits={}\nDirectories={}\n", name, inherits, dirs.join(","));
+        for (subdir, size, icon) in icons {
+            index.push_str(&format!("\n[{}]\nSize={}\nType=Fixed\n", subdir, size));
+            std::fs::create_dir_all(dir.join(subdir)).unwrap();
+            image::RgbaImage::new(*size, *size).save(dir.join(subdir).join(format!("{}.png", icon))).unwrap();
+        }
+        std::fs::write(dir.join("index.theme"), index).unwrap();
+    }
+    
+    fn temp_icon_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("horizonos-icons-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+    
+    #[test]
+    fn test_lookup_follows_inheritance_and_prefers_exact_size() {
+        let base = temp_icon_dir();
+        write_theme(&base, "child", "parent", &[("16x16/apps", 16, "editor")]);
+        write_theme(&base, "parent", "", &[("32x32/apps", 32, "editor"), ("48x48/apps", 48, "terminal")]);
+        write_theme(&base, "hicolor", "", &[("16x16/apps", 16, "browser")]);
+        let search_paths = vec![base.clone()];
+        
+        let chain = IconTheme::chain("child", &search_paths);
+        let names: Vec<&str> = chain.iter().map(|theme| theme.name.as_str()).collect();
+        assert_eq!(names, ["child", "parent", "hicolor"]);
+        
+        let find = |icon: &str, size: u32| chain.iter().find_map(|theme| theme.lookup(icon, size, 1));
+        // The child's icon wins even at a size only the parent has exactly
+        assert_eq!(find("editor", 32), Some(base.join("child/16x16/apps/editor.png")));
+        assert_eq!(find("terminal", 16), Some(base.join("parent/48x48/apps/terminal.png")));
+        assert_eq!(find("browser", 64), Some(base.join("hicolor/16x16/apps/browser.png")));
+        assert_eq!(find("missing", 16), None);
+        
+        std::fs::remove_dir_all(base).unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_theme_switch_reloads_cached_icons() {
+        let base = temp_icon_dir();
+        write_theme(&base, "light", "", &[("16x16/apps", 16, "editor")]);
+        write_theme(&base, "dark", "", &[("32x32/apps", 32, "editor")]);
+        let loader = IconLoader::with_search_paths(Some("light"), vec![base.clone()], Vec::new());
+        let loaded = loader.subscribe();
+        
+        let icon = loader.load_icon_scaled("editor", 16, 2).await.unwrap();
+        assert_eq!(icon.width(), 32);
+        assert!(loader.request_icon("editor", 16, 2).is_some());
+        
+        assert!(loader.set_theme("dark"));
+        assert!(loader.request_icon("editor", 16, 2).is_none());
+        let reloaded = tokio::task::spawn_blocking(move || loaded.recv_timeout(std::time::Duration::from_secs(5)))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reloaded.key.theme, "dark");
+        assert_eq!(reloaded.key.scale, 2);
+        assert_eq!(loader.cache_stats().total_icons, 1);
+        
+        std::fs::remove_dir_all(base).unwrap();
     }
 }
\ No newline at end of file
@@ -3,21 +3,32 @@
 //! This module handles:
 //! - Icon loading and management (app icons, file type icons)
 //! - Thumbnail generation for files
+//! - Live thumbnails of application windows
+//! - Quick-look previews of files
 //! - Edge visual styles
 //! - Visual effects and animations
+//! - Shared animation system (tweens, timelines, springs)
 //! - Theming support
 
 pub mod icons;
 pub mod thumbnails;
+pub mod live_thumbnails;
+pub mod quick_look;
 pub mod effects;
 pub mod theme;
+pub mod animation;
 
 use anyhow::Result;
-use std::sync::Arc;
+use horizonos_graph_config::{ConfigChangeEvent, ConfigManager};
+use horizonos_graph_engine::RenderPalette;
+use std::sync::{mpsc, Arc};
 
-pub use icons::{IconLoader, IconSize, FileTypeIconMapper, AppIconExtractor};
+pub use icons::{IconLoader, IconSize, IconTheme, IconKey, LoadedIcon, FileTypeIconMapper, AppIconExtractor};
 pub use thumbnails::{ThumbnailGenerator, ThumbnailSize, ProfilePictureGenerator};
-pub use theme::{Theme as NewTheme, ThemeSystem, ThemeObserver, Color};
+pub use live_thumbnails::{LiveThumbnails, LiveThumbnail, WindowFrame, CaptureRequest};
+pub use quick_look::{PreviewLoader, PreviewContent, QuickLookConfig, MediaPlayer, MediaKind, TextSpan, TokenKind};
+pub use animation::{Animatable, AnimationId, AnimationSystem, Keyframe, Repeat, Timeline};
+pub use theme::{Theme as NewTheme, ThemeSystem, ThemeObserver, PaletteObserver, Color, WorkspaceTint};
 
 /// Visual resource manager
 pub struct VisualManager {
@@ -72,6 +83,57 @@ impl VisualManager {
     pub fn theme_system_mut(&mut self) -> &mut ThemeSystem {
         Arc::get_mut(&mut self.theme_system).unwrap()
     }
+    
+    /// Receive the renderer palette of every theme change, starting with the current theme
+    pub fn subscribe_palette(&mut self) -> mpsc::Receiver<RenderPalette> {
+        let (observer, rx) = PaletteObserver::channel();
+        observer.on_theme_changed(&self.theme_system.effective_theme());
+        self.theme_system_mut().add_observer(Box::new(observer));
+        rx
+    }
+    
+    /// Follow theme switches and theme file edits of the configuration
+    ///
+    /// Returns whether the theme changed. Themes known to the configuration
+    /// replace the built-in theme of the same name, so edits to a theme file
+    /// show up without restarting.
+    pub fn apply_config_change(&mut self, event: &ConfigChangeEvent, config: &ConfigManager) -> Result<bool> {
+        let theme_name = match event {
+            ConfigChangeEvent::ThemeChanged(name) => name.clone(),
+            // A reloaded configuration may name another theme
+            ConfigChangeEvent::ConfigReloaded => {
+                let name = config.config().appearance.theme;
+                if name == self.theme_system.current_theme().metadata.name {
+                    return Ok(false);
+                }
+                name
+            }
+            _ => return Ok(false),
+        };
+        
+        let theme_system = self.theme_system_mut();
+        if let Some(config_theme) = config.theme(&theme_name) {
+            theme_system.add_theme(NewTheme::from_config_theme(&theme_name, &config_theme)?);
+        }
+        theme_system.set_theme(&theme_name)?;
+        
+        let effective = self.theme_system.effective_theme();
+        self.theme = Arc::new(Theme::from(&effective));
+        Ok(true)
+    }
+}
+
+impl From<&NewTheme> for Theme {
+    fn from(theme: &NewTheme) -> Self {
+        Self {
+            name: theme.metadata.name.clone(),
+            is_dark: theme.metadata.is_dark,
+            primary_color: theme.colors.primary.to_array(),
+            secondary_color: theme.colors.secondary.to_array(),
+            background_color: theme.colors.background.to_array(),
+            text_color: theme.colors.text.primary.to_array(),
+        }
+    }
 }
 
 impl Default for Theme {
@@ -0,0 +1,217 @@
+//! Live thumbnails of application windows
+//!
+//! The compositor captures window surfaces and hands them in as
+//! [`WindowFrame`]s; the renderer draws the latest thumbnail on the face of
+//! the window's node. A capture costs a GPU readback, so a node is refreshed
+//! only as often as its level of detail allows, and only when its window was
+//! damaged since the last capture. Frames are scaled down to the thumbnail
+//! size times the texture resolution of the node's level of detail.
+
+use crate::thumbnails::ThumbnailSize;
+use anyhow::{anyhow, Result};
+use horizonos_graph_engine::SceneId;
+use horizonos_graph_performance::RenderComplexity;
+use image::{imageops::FilterType, DynamicImage, RgbaImage};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Refresh interval of windows until their level of detail is known
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A captured window surface
+#[derive(Debug, Clone)]
+pub struct WindowFrame {
+    pub width: u32,
+    pub height: u32,
+    /// RGBA8 pixels, rows without padding
+    pub pixels: Vec<u8>,
+}
+
+/// Latest thumbnail of a window
+#[derive(Debug, Clone)]
+pub struct LiveThumbnail {
+    pub image: Arc<DynamicImage>,
+    /// Grows with every new image, so the renderer knows when to upload it
+    pub generation: u64,
+    pub captured_at: Instant,
+}
+
+/// A window the compositor should capture now
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaptureRequest {
+    pub node_id: SceneId,
+    /// Longest side of the thumbnail in pixels, so the capture can be
+    /// rendered small to begin with
+    pub max_size: u32,
+}
+
+#[derive(Debug)]
+struct LiveEntry {
+    /// `None` keeps the last image
+    interval: Option<Duration>,
+    /// Texture resolution multiplier of the level of detail
+    resolution: f32,
+    /// Whether the window changed since the last capture
+    damaged: bool,
+    last_capture: Option<Instant>,
+    thumbnail: Option<LiveThumbnail>,
+}
+
+/// Live thumbnails of the nodes showing a window
+#[derive(Debug)]
+pub struct LiveThumbnails {
+    size: ThumbnailSize,
+    entries: HashMap<SceneId, LiveEntry>,
+    next_generation: u64,
+}
+
+impl Default for LiveThumbnails {
+    fn default() -> Self {
+        Self::new(ThumbnailSize::Large)
+    }
+}
+
+impl LiveThumbnails {
+    pub fn new(size: ThumbnailSize) -> Self {
+        Self {
+            size,
+            entries: HashMap::new(),
+            next_generation: 1,
+        }
+    }
+
+    /// Start keeping a thumbnail for a node's window
+    pub fn track(&mut self, node_id: SceneId) {
+        self.entries.entry(node_id).or_insert(LiveEntry {
+            interval: Some(DEFAULT_INTERVAL),
+            resolution: 1.0,
+            damaged: true,
+            last_capture: None,
+            thumbnail: None,
+        });
+    }
+
+    /// Drop a node's thumbnail, e.g. when its window is unmapped
+    pub fn untrack(&mut self, node_id: SceneId) {
+        self.entries.remove(&node_id);
+    }
+
+    pub fn is_tracked(&self, node_id: SceneId) -> bool {
+        self.entries.contains_key(&node_id)
+    }
+
+    /// Follow the node's level of detail
+    pub fn set_lod(&mut self, node_id: SceneId, complexity: &RenderComplexity) {
+        if let Some(entry) = self.entries.get_mut(&node_id) {
+            entry.interval = complexity.live_texture_interval;
+            entry.resolution = complexity.texture_resolution_multiplier;
+        }
+    }
+
+    /// Note that a node's window drew something new
+    pub fn damage(&mut self, node_id: SceneId) {
+        if let Some(entry) = self.entries.get_mut(&node_id) {
+            entry.damaged = true;
+        }
+    }
+
+    /// Windows to capture this frame
+    ///
+    /// A window is captured when it changed and its refresh interval passed.
+    /// Every window is captured once, even at a level of detail that does not
+    /// refresh, so its node never shows an empty face; culled nodes are not.
+    pub fn due(&self, now: Instant) -> Vec<CaptureRequest> {
+        self.entries.iter()
+            .filter(|(_, entry)| entry.damaged && entry.resolution > 0.0)
+            .filter(|(_, entry)| match (entry.last_capture, entry.interval) {
+                (None, _) => true,
+                (Some(last), Some(interval)) => now.duration_since(last) >= interval,
+                (Some(_), None) => false,
+            })
+            .map(|(&node_id, entry)| CaptureRequest { node_id, max_size: self.max_size(entry) })
+            .collect()
+    }
+
+    fn max_size(&self, entry: &LiveEntry) -> u32 {
+        ((self.size.pixels() as f32 * entry.resolution).round() as u32).max(1)
+    }
+
+    /// Store a captured frame, scaled down to the thumbnail size
+    ///
+    /// Returns the new generation; frames of untracked nodes are ignored.
+    pub fn submit(&mut self, node_id: SceneId, frame: WindowFrame, now: Instant) -> Result<Option<u64>> {
+        let Some(max_size) = self.entries.get(&node_id).map(|entry| self.max_size(entry)) else {
+            return Ok(None);
+        };
+        let image = RgbaImage::from_raw(frame.width, frame.height, frame.pixels)
+            .ok_or_else(|| anyhow!("Window frame of {}x{} has too few pixels", frame.width, frame.height))?;
+        let mut image = DynamicImage::ImageRgba8(image);
+        if image.width() > max_size || image.height() > max_size {
+            image = image.resize(max_size, max_size, FilterType::Triangle);
+        }
+
+        let generation = self.next_generation;
+        self.next_generation += 1;
+        let entry = self.entries.get_mut(&node_id).expect("entry checked above");
+        entry.damaged = false;
+        entry.last_capture = Some(now);
+        entry.thumbnail = Some(LiveThumbnail {
+            image: Arc::new(image),
+            generation,
+            captured_at: now,
+        });
+        Ok(Some(generation))
+    }
+
+    /// Latest thumbnail of a node
+    pub fn thumbnail(&self, node_id: SceneId) -> Option<&LiveThumbnail> {
+        self.entries.get(&node_id)?.thumbnail.as_ref()
+    }
+
+    /// Bytes held by thumbnails
+    pub fn memory_usage(&self) -> usize {
+        self.entries.values()
+            .filter_map(|entry| entry.thumbnail.as_ref())
+            .map(|thumbnail| (thumbnail.image.width() * thumbnail.image.height() * 4) as usize)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_performance::{LodLevel, LodSystem};
+
+    fn frame(width: u32, height: u32) -> WindowFrame {
+        WindowFrame { width, height, pixels: vec![255; (width * height * 4) as usize] }
+    }
+
+    #[test]
+    fn test_refresh_follows_damage_and_lod() {
+        let lod = LodSystem::new();
+        let mut live = LiveThumbnails::new(ThumbnailSize::Large);
+        let node = 7;
+        live.track(node);
+        let start = Instant::now();
+
+        let due = live.due(start);
+        assert_eq!(due, vec![CaptureRequest { node_id: node, max_size: 256 }]);
+        assert_eq!(live.submit(node, frame(1024, 512), start).unwrap(), Some(1));
+        let image = &live.thumbnail(node).unwrap().image;
+        assert_eq!((image.width(), image.height()), (256, 128));
+
+        // Nothing new drawn, nothing to capture
+        assert!(live.due(start + Duration::from_secs(1)).is_empty());
+
+        // Damaged, but a low level of detail refreshes only twice a second
+        live.set_lod(node, &lod.get_render_complexity(LodLevel::Low));
+        live.damage(node);
+        assert!(live.due(start + Duration::from_millis(200)).is_empty());
+        assert_eq!(live.due(start + Duration::from_millis(600))[0].max_size, 128);
+
+        // Far away the last image is kept
+        live.set_lod(node, &lod.get_render_complexity(LodLevel::VeryLow));
+        assert!(live.due(start + Duration::from_secs(10)).is_empty());
+    }
+}
@@ -0,0 +1,453 @@
+//! Quick-look file previews
+//!
+//! Loads what the quick-look overlay shows for a file: the image itself,
+//! the first pages of a PDF, syntax-highlighted text, or a poster frame for
+//! audio and video, which [`MediaPlayer`] plays through GStreamer. Previews
+//! are sized for a large overlay rather than a thumbnail, and nothing here
+//! launches the application that owns the file.
+
+use crate::thumbnails::{ThumbnailGenerator, ThumbnailSize};
+use anyhow::{anyhow, Result};
+use image::DynamicImage;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use tokio::fs;
+
+/// Limits for loading previews
+#[derive(Debug, Clone)]
+pub struct QuickLookConfig {
+    /// Longest side of image and page previews, in pixels
+    pub max_image_size: u32,
+    /// PDF pages rendered
+    pub pdf_pages: u32,
+    /// Bytes of a text file read
+    pub max_text_bytes: usize,
+    /// Lines of a text file shown
+    pub max_text_lines: usize,
+}
+
+impl Default for QuickLookConfig {
+    fn default() -> Self {
+        Self {
+            max_image_size: 2048,
+            pdf_pages: 3,
+            max_text_bytes: 256 * 1024,
+            max_text_lines: 2000,
+        }
+    }
+}
+
+/// Kind of a highlighted piece of text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Plain,
+    Keyword,
+    String,
+    Comment,
+    Number,
+}
+
+/// A run of text with one highlight
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextSpan {
+    pub text: String,
+    pub kind: TokenKind,
+}
+
+/// Audio or video
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Audio,
+    Video,
+}
+
+/// What the overlay shows for a file
+#[derive(Debug, Clone)]
+pub enum PreviewContent {
+    Image {
+        image: Arc<DynamicImage>,
+    },
+    /// First pages of a document
+    Document {
+        pages: Vec<Arc<DynamicImage>>,
+    },
+    Text {
+        /// Language used for highlighting, if recognised
+        language: Option<&'static str>,
+        lines: Vec<Vec<TextSpan>>,
+        /// Whether the file was longer than shown
+        truncated: bool,
+    },
+    /// Played with [`MediaPlayer`]
+    Media {
+        kind: MediaKind,
+        path: PathBuf,
+        /// Frame shown before playback starts
+        poster: Option<Arc<DynamicImage>>,
+    },
+    /// Nothing to preview; the overlay shows the file's details only
+    Unsupported {
+        mime: String,
+    },
+}
+
+/// Loads quick-look previews
+pub struct PreviewLoader {
+    config: QuickLookConfig,
+    /// Source of video poster frames
+    thumbnails: Option<Arc<ThumbnailGenerator>>,
+}
+
+impl PreviewLoader {
+    /// Create a loader with the given limits
+    pub fn new(config: QuickLookConfig) -> Self {
+        Self { config, thumbnails: None }
+    }
+
+    /// Use a thumbnail generator for video poster frames
+    pub fn with_thumbnails(mut self, thumbnails: Arc<ThumbnailGenerator>) -> Self {
+        self.thumbnails = Some(thumbnails);
+        self
+    }
+
+    /// Current limits
+    pub fn config(&self) -> &QuickLookConfig {
+        &self.config
+    }
+
+    /// Load the preview of a file
+    pub async fn load(&self, path: &Path) -> Result<PreviewContent> {
+        let mime = tree_magic_mini::from_filepath(path).unwrap_or("application/octet-stream");
+        let language = language_for_path(path);
+
+        match mime {
+            t if t.starts_with("image/") => self.load_image(path).await,
+            "application/pdf" => self.load_pdf(path).await,
+            t if t.starts_with("video/") => Ok(PreviewContent::Media {
+                kind: MediaKind::Video,
+                path: path.to_path_buf(),
+                poster: self.load_poster(path).await,
+            }),
+            t if t.starts_with("audio/") => Ok(PreviewContent::Media { kind: MediaKind::Audio, path: path.to_path_buf(), poster: None }),
+            t if is_text_mime(t) || language.is_some() => self.load_text(path, language).await,
+            _ => Ok(PreviewContent::Unsupported { mime: mime.to_string() }),
+        }
+    }
+
+    /// Load an image scaled down to the preview size
+    async fn load_image(&self, path: &Path) -> Result<PreviewContent> {
+        let data = fs::read(path).await?;
+        let image = image::load_from_memory(&data)?;
+        let size = self.config.max_image_size;
+        let image = if image.width() > size || image.height() > size { image.thumbnail(size, size) } else { image };
+        Ok(PreviewContent::Image { image: Arc::new(image) })
+    }
+
+    /// Render the first pages of a PDF with pdftoppm
+    async fn load_pdf(&self, path: &Path) -> Result<PreviewContent> {
+        let dir = std::env::temp_dir().join(format!("quicklook_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).await?;
+        let pages = self.render_pdf_pages(path, &dir).await;
+        let _ = fs::remove_dir_all(&dir).await;
+        Ok(PreviewContent::Document { pages: pages? })
+    }
+
+    async fn render_pdf_pages(&self, path: &Path, dir: &Path) -> Result<Vec<Arc<DynamicImage>>> {
+        let output = tokio::process::Command::new("pdftoppm")
+            .arg("-png")
+            .args(["-f", "1", "-l", &self.config.pdf_pages.to_string()])
+            .args(["-scale-to", &self.config.max_image_size.to_string()])
+            .arg(path)
+            .arg(dir.join("page"))
+            .output()
+            .await?;
+        if !output.status.success() {
+            return Err(anyhow!("pdftoppm failed: {}", String::from_utf8_lossy(&output.stderr)));
+        }
+
+        // Pages are written as page-1.png, page-2.png, ... with zero padding for long documents
+        let mut files = Vec::new();
+        let mut entries = fs::read_dir(dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            files.push(entry.path());
+        }
+        files.sort();
+        let mut pages = Vec::with_capacity(files.len());
+        for file in files {
+            pages.push(Arc::new(image::load_from_memory(&fs::read(&file).await?)?));
+        }
+        Ok(pages)
+    }
+
+    /// Poster frame of a video, if a thumbnail generator is attached
+    async fn load_poster(&self, path: &Path) -> Option<Arc<DynamicImage>> {
+        let thumbnails = self.thumbnails.as_ref()?;
+        match thumbnails.get_thumbnail(path, ThumbnailSize::XLarge).await {
+            Ok(poster) => Some(poster),
+            Err(e) => {
+                log::debug!("No poster frame for {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Read the start of a text file and highlight it
+    async fn load_text(&self, path: &Path, language: Option<&'static str>) -> Result<PreviewContent> {
+        let data = fs::read(path).await?;
+        let mut truncated = data.len() > self.config.max_text_bytes;
+        let text = String::from_utf8_lossy(&data[..data.len().min(self.config.max_text_bytes)]);
+
+        let mut lines = Vec::new();
+        for line in text.lines() {
+            if lines.len() == self.config.max_text_lines {
+                truncated = true;
+                break;
+            }
+            lines.push(highlight_line(line, language));
+        }
+        Ok(PreviewContent::Text { language, lines, truncated })
+    }
+}
+
+impl Default for PreviewLoader {
+    fn default() -> Self {
+        Self::new(QuickLookConfig::default())
+    }
+}
+
+/// MIME types of plain text that do not start with `text/`
+fn is_text_mime(mime: &str) -> bool {
+    mime.starts_with("text/")
+        || matches!(
+            mime,
+            "application/json" | "application/xml" | "application/toml" | "application/x-shellscript" | "application/javascript"
+        )
+}
+
+/// Syntax of a language, for highlighting
+struct Syntax {
+    name: &'static str,
+    extensions: &'static [&'static str],
+    keywords: &'static [&'static str],
+    line_comments: &'static [&'static str],
+}
+
+const SYNTAXES: &[Syntax] = &[
+    Syntax {
+        name: "rust",
+        extensions: &["rs"],
+        keywords: &[
+            "as", "async", "await", "break", "const", "continue", "crate", "else", "enum", "false", "fn", "for", "if", "impl", "in",
+            "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super",
+            "trait", "true", "type", "unsafe", "use", "where", "while",
+        ],
+        line_comments: &["//"],
+    },
+    Syntax {
+        name: "kotlin",
+        extensions: &["kt", "kts"],
+        keywords: &[
+            "class", "data", "else", "false", "for", "fun", "if", "import", "in", "interface", "is", "null", "object", "package",
+            "private", "return", "true", "val", "var", "when", "while",
+        ],
+        line_comments: &["//"],
+    },
+    Syntax {
+        name: "python",
+        extensions: &["py"],
+        keywords: &[
+            "and", "as", "class", "def", "elif", "else", "False", "for", "from", "if", "import", "in", "is", "lambda", "None",
+            "not", "or", "pass", "return", "self", "True", "while", "with", "yield",
+        ],
+        line_comments: &["#"],
+    },
+    Syntax {
+        name: "javascript",
+        extensions: &["js", "mjs", "ts", "tsx", "jsx"],
+        keywords: &[
+            "async", "await", "class", "const", "else", "export", "false", "for", "function", "if", "import", "let", "new",
+            "null", "return", "this", "true", "undefined", "var", "while",
+        ],
+        line_comments: &["//"],
+    },
+    Syntax {
+        name: "shell",
+        extensions: &["sh", "bash", "zsh"],
+        keywords: &["case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if", "in", "local", "then", "while"],
+        line_comments: &["#"],
+    },
+    Syntax {
+        name: "toml",
+        extensions: &["toml", "ini", "conf"],
+        keywords: &["true", "false"],
+        line_comments: &["#", ";"],
+    },
+    Syntax {
+        name: "json",
+        extensions: &["json"],
+        keywords: &["true", "false", "null"],
+        line_comments: &[],
+    },
+];
+
+/// Language of a file, by extension
+pub fn language_for_path(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    SYNTAXES.iter().find(|syntax| syntax.extensions.contains(&extension.as_str())).map(|syntax| syntax.name)
+}
+
+/// Split a line into highlighted spans; unknown languages come back as one plain span
+pub fn highlight_line(line: &str, language: Option<&str>) -> Vec<TextSpan> {
+    let Some(syntax) = language.and_then(|name| SYNTAXES.iter().find(|syntax| syntax.name == name)) else {
+        return vec![TextSpan { text: line.to_string(), kind: TokenKind::Plain }];
+    };
+
+    let mut spans: Vec<TextSpan> = Vec::new();
+    let mut push = |text: &str, kind: TokenKind| match spans.last_mut() {
+        Some(last) if last.kind == kind => last.text.push_str(text),
+        _ => spans.push(TextSpan { text: text.to_string(), kind }),
+    };
+
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if syntax.line_comments.iter().any(|marker| rest.starts_with(marker)) {
+            push(rest, TokenKind::Comment);
+            break;
+        }
+        let (token, kind) = if c == '"' || c == '\'' {
+            // Up to the closing quote, skipping escaped ones
+            let mut escaped = false;
+            let end = rest[1..]
+                .char_indices()
+                .find(|&(_, next)| {
+                    let closes = next == c && !escaped;
+                    escaped = next == '\\' && !escaped;
+                    closes
+                })
+                .map_or(rest.len(), |(i, _)| i + 2);
+            (&rest[..end], TokenKind::String)
+        } else if c.is_alphanumeric() || c == '_' {
+            let end = rest.find(|next: char| !(next.is_alphanumeric() || next == '_')).unwrap_or(rest.len());
+            let word = &rest[..end];
+            let kind = if c.is_ascii_digit() {
+                TokenKind::Number
+            } else if syntax.keywords.contains(&word) {
+                TokenKind::Keyword
+            } else {
+                TokenKind::Plain
+            };
+            (word, kind)
+        } else {
+            (&rest[..c.len_utf8()], TokenKind::Plain)
+        };
+        push(token, kind);
+        rest = &rest[token.len()..];
+    }
+    spans
+}
+
+/// Plays audio and video through a GStreamer pipeline
+///
+/// Playback stops when the player is dropped, e.g. when the overlay closes
+/// or moves to the next file.
+pub struct MediaPlayer {
+    path: PathBuf,
+    process: Child,
+}
+
+impl MediaPlayer {
+    /// Start playing a file
+    pub fn play(path: &Path) -> Result<Self> {
+        let path = path.canonicalize()?;
+        let process = Command::new("gst-launch-1.0")
+            .arg("-q")
+            .arg("playbin")
+            .arg(format!("uri={}", file_uri(&path)))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow!("Could not start GStreamer: {}", e))?;
+        Ok(Self { path, process })
+    }
+
+    /// File being played
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether playback is still running
+    pub fn is_playing(&mut self) -> bool {
+        matches!(self.process.try_wait(), Ok(None))
+    }
+
+    /// Stop playback
+    pub fn stop(&mut self) {
+        if self.is_playing() {
+            let _ = self.process.kill();
+        }
+        let _ = self.process.wait();
+    }
+}
+
+impl Drop for MediaPlayer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// `file://` URI of an absolute path, percent-encoding reserved bytes
+fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_rust_line() {
+        let spans = highlight_line(r#"let name = "a \"b\""; // note 42"#, Some("rust"));
+        let kinds: Vec<(&str, TokenKind)> = spans.iter().map(|span| (span.text.as_str(), span.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("let", TokenKind::Keyword),
+                (" name = ", TokenKind::Plain),
+                (r#""a \"b\"""#, TokenKind::String),
+                ("; ", TokenKind::Plain),
+                ("// note 42", TokenKind::Comment),
+            ]
+        );
+        assert_eq!(highlight_line("x = 1", None).len(), 1);
+        assert_eq!(language_for_path(Path::new("/tmp/build.RS")), Some("rust"));
+        assert_eq!(file_uri(Path::new("/home/me/My Song.ogg")), "file:///home/me/My%20Song.ogg");
+    }
+
+    #[tokio::test]
+    async fn test_text_preview_is_truncated() {
+        let path = std::env::temp_dir().join(format!("quicklook_{}.py", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "def f():\n    return 1\n# end\n").unwrap();
+        let loader = PreviewLoader::new(QuickLookConfig { max_text_lines: 2, ..Default::default() });
+
+        let preview = loader.load(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        match preview {
+            PreviewContent::Text { language, lines, truncated } => {
+                assert_eq!(language, Some("python"));
+                assert_eq!(lines.len(), 2);
+                assert!(truncated);
+                assert_eq!(lines[0][0], TextSpan { text: "def".to_string(), kind: TokenKind::Keyword });
+            }
+            other => panic!("expected a text preview, got {:?}", other),
+        }
+    }
+}
@@ -4,9 +4,10 @@
 //! the Kotlin DSL configuration to provide rich theming capabilities.
 
 use anyhow::Result;
+use horizonos_graph_engine::RenderPalette;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{mpsc, Arc};
 
 /// Comprehensive theme system
 pub struct ThemeSystem {
@@ -16,6 +17,23 @@ pub struct ThemeSystem {
     current_theme: String,
     /// Theme observers
     observers: Vec<Box<dyn ThemeObserver>>,
+    /// Tint of the active workspace, if any
+    workspace_tint: Option<WorkspaceTint>,
+}
+
+/// Accent tint contributed by the active workspace
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WorkspaceTint {
+    /// Workspace accent color
+    pub accent: Color,
+    /// Blend strength (0.0 to 1.0)
+    pub strength: f32,
+    /// Tint the background and surfaces
+    pub background: bool,
+    /// Tint cluster boundaries
+    pub cluster_boundaries: bool,
+    /// Tint panel highlights (borders, focus, selection)
+    pub panels: bool,
 }
 
 /// Complete theme definition
@@ -607,6 +625,28 @@ pub trait ThemeObserver: Send + Sync {
     fn on_theme_changed(&self, theme: &Theme);
 }
 
+/// Observer forwarding theme changes to the renderer as palettes
+///
+/// The renderer usually lives on another thread than the theme system, so
+/// palettes are sent over a channel and applied once per frame.
+pub struct PaletteObserver {
+    tx: mpsc::Sender<RenderPalette>,
+}
+
+impl PaletteObserver {
+    /// Create an observer and the receiver the renderer drains
+    pub fn channel() -> (Self, mpsc::Receiver<RenderPalette>) {
+        let (tx, rx) = mpsc::channel();
+        (Self { tx }, rx)
+    }
+}
+
+impl ThemeObserver for PaletteObserver {
+    fn on_theme_changed(&self, theme: &Theme) {
+        let _ = self.tx.send(theme.render_palette());
+    }
+}
+
 impl ThemeSystem {
     /// Create new theme system
     pub fn new() -> Self {
@@ -622,6 +662,7 @@ impl ThemeSystem {
             themes,
             current_theme: "horizon-dark".to_string(),
             observers: Vec::new(),
+            workspace_tint: None,
         }
     }
     
@@ -637,14 +678,37 @@ impl ThemeSystem {
         }
         
         self.current_theme = theme_name.to_string();
-        let theme = self.themes.get(theme_name).unwrap();
+        self.notify_observers();
         
-        // Notify observers
+        Ok(())
+    }
+    
+    /// Current theme with the workspace tint applied
+    pub fn effective_theme(&self) -> Theme {
+        let theme = self.current_theme();
+        match &self.workspace_tint {
+            Some(tint) => theme.with_workspace_tint(tint),
+            None => theme.clone(),
+        }
+    }
+    
+    /// Set or clear the active workspace tint
+    pub fn set_workspace_tint(&mut self, tint: Option<WorkspaceTint>) {
+        self.workspace_tint = tint;
+        self.notify_observers();
+    }
+    
+    /// Get the active workspace tint
+    pub fn workspace_tint(&self) -> Option<&WorkspaceTint> {
+        self.workspace_tint.as_ref()
+    }
+    
+    /// Notify observers with the effective theme
+    fn notify_observers(&self) {
+        let theme = self.effective_theme();
         for observer in &self.observers {
-            observer.on_theme_changed(theme);
+            observer.on_theme_changed(&theme);
         }
-        
-        Ok(())
     }
     
     /// Add theme
@@ -718,6 +782,40 @@ impl Color {
     pub fn to_array(&self) -> [f32; 4] {
         [self.r, self.g, self.b, self.a]
     }
+    
+    /// Blend RGB towards another color, keeping this color's alpha
+    pub fn mix(&self, other: &Color, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        Self {
+            r: self.r + (other.r - self.r) * t,
+            g: self.g + (other.g - self.g) * t,
+            b: self.b + (other.b - self.b) * t,
+            a: self.a,
+        }
+    }
+}
+
+impl WorkspaceTint {
+    /// Create a tint applied to every surface
+    pub fn new(accent: Color, strength: f32) -> Self {
+        Self {
+            accent,
+            strength: strength.clamp(0.0, 1.0),
+            background: true,
+            cluster_boundaries: true,
+            panels: true,
+        }
+    }
+    
+    /// Apply the tint to a cluster boundary color
+    pub fn tint_boundary(&self, color: [f32; 4]) -> [f32; 4] {
+        if !self.cluster_boundaries {
+            return color;
+        }
+        
+        let base = Color::rgba(color[0], color[1], color[2], color[3]);
+        base.mix(&self.accent, self.strength).to_array()
+    }
 }
 
 impl Default for AnimationConfig {
@@ -976,6 +1074,139 @@ impl Theme {
         theme
     }
     
+    /// Copy of this theme blended with a workspace tint
+    pub fn with_workspace_tint(&self, tint: &WorkspaceTint) -> Self {
+        let mut theme = self.clone();
+        let accent = &tint.accent;
+        
+        if tint.background {
+            // Backgrounds get a subtle wash so content stays readable
+            let wash = tint.strength * 0.25;
+            theme.colors.background = theme.colors.background.mix(accent, wash);
+            theme.colors.surface = theme.colors.surface.mix(accent, wash);
+        }
+        
+        if tint.panels {
+            theme.colors.ui.border = theme.colors.ui.border.mix(accent, tint.strength);
+            theme.colors.ui.focus = theme.colors.ui.focus.mix(accent, tint.strength);
+            theme.colors.ui.selection_bg = theme.colors.ui.selection_bg.mix(accent, tint.strength);
+            theme.colors.accent = *accent;
+        }
+        
+        theme
+    }
+    
+    /// Create theme from a theme of the configuration, e.g. a theme file
+    pub fn from_config_theme(name: &str, config_theme: &horizonos_graph_config::theme::Theme) -> Result<Self> {
+        let mut theme = Theme::horizon_dark(); // Start with default
+        
+        // Update metadata
+        let meta = &config_theme.metadata;
+        theme.metadata.name = name.to_string();
+        theme.metadata.display_name = meta.name.clone();
+        theme.metadata.author = meta.author.clone();
+        theme.metadata.version = meta.version.clone();
+        theme.metadata.description = meta.description.clone().unwrap_or_default();
+        
+        // Convert colors
+        let colors = &config_theme.colors;
+        theme.colors.background = Color::from_hex(&colors.background)?;
+        theme.colors.surface = Color::from_hex(&colors.surface)?;
+        theme.colors.primary = Color::from_hex(&colors.primary)?;
+        theme.colors.secondary = Color::from_hex(&colors.secondary)?;
+        theme.colors.accent = Color::from_hex(&colors.accent)?;
+        theme.colors.text.primary = Color::from_hex(&colors.text)?;
+        theme.colors.text.secondary = Color::from_hex(&colors.text_secondary)?;
+        theme.colors.text.disabled = Color::from_hex(&colors.text_disabled)?;
+        theme.colors.status.success = Color::from_hex(&colors.success)?;
+        theme.colors.status.warning = Color::from_hex(&colors.warning)?;
+        theme.colors.status.error = Color::from_hex(&colors.error)?;
+        theme.colors.status.info = Color::from_hex(&colors.info)?;
+        theme.colors.ui.border = Color::from_hex(&colors.border)?;
+        theme.colors.ui.focus = Color::from_hex(&colors.border_focus)?;
+        
+        let background = theme.colors.background;
+        theme.metadata.is_dark = 0.299 * background.r + 0.587 * background.g + 0.114 * background.b < 0.5;
+        
+        // Graph colors; node kinds without a slot in the palette become type styles
+        let nodes = &config_theme.graph.node_colors;
+        theme.colors.nodes.file = Color::from_hex(&nodes.file)?;
+        theme.colors.nodes.application = Color::from_hex(&nodes.application)?;
+        theme.colors.nodes.person = Color::from_hex(&nodes.person)?;
+        theme.colors.nodes.task = Color::from_hex(&nodes.task)?;
+        theme.colors.nodes.concept = Color::from_hex(&nodes.concept)?;
+        for (kind, hex) in [
+            ("device", &nodes.device),
+            ("ai_agent", &nodes.ai_agent),
+            ("system", &nodes.system),
+            ("url", &nodes.url),
+            ("automation", &nodes.automation),
+            ("setting", &nodes.setting),
+            ("config_group", &nodes.config_group),
+        ] {
+            let mut style = theme.node_styles.default.clone();
+            style.color = Color::from_hex(hex)?;
+            theme.node_styles.by_type.insert(kind.to_string(), style);
+        }
+        
+        let edges = &config_theme.graph.edge_colors;
+        theme.colors.edges.relationship = Color::from_hex(&edges.relationship)?;
+        theme.colors.edges.dependency = Color::from_hex(&edges.dependency)?;
+        theme.colors.edges.data_flow = Color::from_hex(&edges.data_flow)?;
+        theme.colors.edges.temporal = Color::from_hex(&edges.temporal)?;
+        let mut hierarchy = theme.edge_styles.default.clone();
+        hierarchy.color = Color::from_hex(&edges.hierarchy)?;
+        theme.edge_styles.by_type.insert("contains".to_string(), hierarchy);
+        
+        Ok(theme)
+    }
+    
+    /// Colors the renderer draws the graph with
+    ///
+    /// Type styles keyed by [`horizonos_graph_engine::node_kind`] or
+    /// [`horizonos_graph_engine::edge_kind`] override the palette colors.
+    pub fn render_palette(&self) -> RenderPalette {
+        let nodes = &self.colors.nodes;
+        let mut node_colors: HashMap<String, [f32; 4]> = [
+            ("file", nodes.file),
+            ("application", nodes.application),
+            ("person", nodes.person),
+            ("task", nodes.task),
+            ("concept", nodes.concept),
+        ]
+        .into_iter()
+        .map(|(kind, color)| (kind.to_string(), color.to_array()))
+        .collect();
+        for (kind, style) in &self.node_styles.by_type {
+            node_colors.insert(kind.clone(), style.color.to_array());
+        }
+        
+        let edges = &self.colors.edges;
+        let mut edge_colors: HashMap<String, [f32; 4]> = [
+            ("depends_on", edges.dependency),
+            ("communicates_with", edges.data_flow),
+            ("related_to", edges.relationship),
+            ("temporal", edges.temporal),
+        ]
+        .into_iter()
+        .map(|(kind, color)| (kind.to_string(), color.to_array()))
+        .collect();
+        for (kind, style) in &self.edge_styles.by_type {
+            edge_colors.insert(kind.clone(), style.color.to_array());
+        }
+        
+        RenderPalette {
+            background: self.colors.background.to_array(),
+            node_default: nodes.default.to_array(),
+            nodes: node_colors,
+            edge_default: edges.default.to_array(),
+            edges: edge_colors,
+            selected: nodes.selected.to_array(),
+            hovered: nodes.hovered.to_array(),
+            selection_fill: self.colors.ui.selection_bg.to_array(),
+        }
+    }
+    
     /// Create theme from Kotlin DSL configuration
     pub fn from_kotlin_dsl(kotlin_theme: &horizonos_graph_config::kotlindsl::ThemeDef) -> Result<Self> {
         let mut theme = Theme::horizon_dark(); // Start with default
@@ -1043,6 +1274,46 @@ mod tests {
         assert_eq!(color_alpha.a, 170.0 / 255.0);
     }
     
+    #[test]
+    fn test_workspace_tint() {
+        let mut theme_system = ThemeSystem::new();
+        let base = theme_system.current_theme().clone();
+        
+        let accent = Color::from_hex("#00FF00").unwrap();
+        theme_system.set_workspace_tint(Some(WorkspaceTint::new(accent, 1.0)));
+        
+        let tinted = theme_system.effective_theme();
+        assert_eq!(tinted.colors.ui.focus.g, 1.0);
+        assert!(tinted.colors.background.g > base.colors.background.g);
+        assert_eq!(tinted.colors.background.a, base.colors.background.a);
+        
+        theme_system.set_workspace_tint(None);
+        assert_eq!(theme_system.effective_theme().colors.ui.focus.g, base.colors.ui.focus.g);
+    }
+    
+    #[test]
+    fn test_config_theme_reaches_palette_observers() {
+        let mut theme_system = ThemeSystem::new();
+        let (observer, palettes) = PaletteObserver::channel();
+        theme_system.add_observer(Box::new(observer));
+        
+        let mut config_theme = horizonos_graph_config::theme::Theme::default_dark();
+        config_theme.colors.background = "#FFFFFF".to_string();
+        config_theme.graph.node_colors.device = "#00FF00".to_string();
+        config_theme.graph.edge_colors.hierarchy = "#0000FF".to_string();
+        let theme = Theme::from_config_theme("edited", &config_theme).unwrap();
+        assert!(!theme.metadata.is_dark);
+        
+        theme_system.add_theme(theme);
+        theme_system.set_theme("edited").unwrap();
+        
+        let palette = palettes.try_recv().unwrap();
+        assert_eq!(palette.background, [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(palette.nodes["device"], [0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(palette.edges["contains"], [0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(palette.nodes["file"], Color::from_hex(&config_theme.graph.node_colors.file).unwrap().to_array());
+    }
+    
     #[test]
     fn test_theme_creation() {
         let theme = Theme::horizon_dark();
@@ -0,0 +1,154 @@
+//! Shareable workspace archives
+//!
+//! An archive is a single JSON file holding a workspace (metadata, layout,
+//! settings and identity), the scene nodes it references together with
+//! whatever export data the node layer provides for them, and the edges
+//! between those nodes. Archives are meant to move between machines, so on
+//! import every node or edge ID already used by the local scene is replaced
+//! by a fresh one and all references to it are rewritten.
+
+use crate::{Workspace, WorkspaceError};
+use chrono::{DateTime, Utc};
+use horizonos_graph_engine::scene::{NodePersistence, Scene, SceneEdge, SceneId, SceneNode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Version of the archive format written by this build
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// A scene node stored in an archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedNode {
+    pub node: SceneNode,
+    /// Node layer data, e.g. `NodeExportData`, kept as is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub export_data: Option<Value>,
+}
+
+/// A workspace with everything needed to recreate it elsewhere
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceArchive {
+    /// Format version the archive was written with
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub workspace: Workspace,
+    pub nodes: Vec<ArchivedNode>,
+    /// Edges whose ends are both in the workspace
+    pub edges: Vec<SceneEdge>,
+}
+
+impl WorkspaceArchive {
+    /// Collect a workspace and its nodes from the scene
+    ///
+    /// Workspace nodes missing from the scene or not durable are dropped
+    /// from the archived workspace and its layout.
+    pub fn build(workspace: &Workspace, scene: &Scene, node_data: &HashMap<SceneId, Value>) -> Self {
+        let mut workspace = workspace.clone();
+        workspace.nodes.retain(|id| {
+            scene.get_node(*id).is_some_and(|node| node.metadata.persistence.is_saved_in(NodePersistence::Durable))
+        });
+        let included: HashSet<SceneId> = workspace.nodes.iter().copied().collect();
+        workspace.layout.node_positions.retain(|id, _| included.contains(id));
+
+        let nodes = workspace.nodes.iter()
+            .filter_map(|id| scene.get_node(*id))
+            .map(|node| ArchivedNode {
+                node: node.clone(),
+                export_data: node_data.get(&node.id).cloned(),
+            })
+            .collect();
+        let mut edges: Vec<SceneEdge> = scene.edges()
+            .filter(|edge| included.contains(&edge.source) && included.contains(&edge.target))
+            .cloned()
+            .collect();
+        edges.sort_by_key(|edge| edge.id);
+
+        Self {
+            version: ARCHIVE_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            workspace,
+            nodes,
+            edges,
+        }
+    }
+
+    pub fn encode(&self) -> Result<String, WorkspaceError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn decode(text: &str) -> Result<Self, WorkspaceError> {
+        let archive: Self = serde_json::from_str(text)?;
+        if archive.version > ARCHIVE_FORMAT_VERSION {
+            return Err(WorkspaceError::UnsupportedArchive(archive.version));
+        }
+        Ok(archive)
+    }
+
+    /// Add the archived nodes and edges to a scene as a new workspace
+    ///
+    /// IDs taken in the scene are replaced by fresh ones; the workspace gets
+    /// a new ID so importing the same archive twice yields two workspaces.
+    pub fn import_into(self, scene: &mut Scene) -> ImportedWorkspace {
+        let mut node_ids = HashMap::new();
+        let mut nodes = Vec::with_capacity(self.nodes.len());
+        for mut archived in self.nodes {
+            let original = archived.node.id;
+            let id = if id_taken(scene, original) {
+                scene.add_node(archived.node.clone())
+            } else {
+                scene.insert_node(archived.node.clone())
+            };
+            archived.node.id = id;
+            node_ids.insert(original, id);
+            nodes.push(archived);
+        }
+
+        let mut edge_ids = HashMap::new();
+        for mut edge in self.edges {
+            let (Some(&source), Some(&target)) = (node_ids.get(&edge.source), node_ids.get(&edge.target)) else {
+                continue;
+            };
+            edge.source = source;
+            edge.target = target;
+            let original = edge.id;
+            let id = if id_taken(scene, original) {
+                scene.add_edge(edge)
+            } else {
+                scene.insert_edge(edge)
+            };
+            edge_ids.insert(original, id);
+        }
+
+        let mut workspace = self.workspace;
+        workspace.id = uuid::Uuid::new_v4().to_string();
+        workspace.nodes = workspace.nodes.iter()
+            .filter_map(|id| node_ids.get(id).copied())
+            .collect();
+        workspace.layout.node_positions = workspace.layout.node_positions.into_iter()
+            .filter_map(|(id, position)| node_ids.get(&id).map(|new_id| (*new_id, position)))
+            .collect();
+        workspace.created_at = Utc::now();
+        workspace.touch();
+
+        ImportedWorkspace { workspace, nodes, node_ids, edge_ids }
+    }
+}
+
+/// Result of importing an archive
+#[derive(Debug, Clone)]
+pub struct ImportedWorkspace {
+    /// The new workspace, referring to the IDs used in the scene
+    pub workspace: Workspace,
+    /// Imported nodes with their scene IDs, for the node layer to rebuild
+    /// from their export data
+    pub nodes: Vec<ArchivedNode>,
+    /// Archived node ID to scene ID
+    pub node_ids: HashMap<SceneId, SceneId>,
+    /// Archived edge ID to scene ID
+    pub edge_ids: HashMap<SceneId, SceneId>,
+}
+
+fn id_taken(scene: &Scene, id: SceneId) -> bool {
+    scene.get_node(id).is_some() || scene.get_edge(id).is_some()
+}
@@ -0,0 +1,143 @@
+//! Named camera views saved with a workspace
+
+use chrono::{DateTime, Utc};
+use horizonos_graph_engine::CameraState;
+use serde::{Deserialize, Serialize};
+
+/// A saved camera position, orientation and zoom
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraBookmark {
+    /// Name shown to the user, unique within the workspace
+    pub name: String,
+    /// Saved view
+    pub camera: CameraState,
+    /// When the view was saved
+    pub created_at: DateTime<Utc>,
+}
+
+/// Camera bookmarks of a workspace, in the order they were saved
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CameraBookmarks {
+    bookmarks: Vec<CameraBookmark>,
+    /// Bookmark last cycled to
+    #[serde(skip)]
+    current: Option<usize>,
+}
+
+impl CameraBookmarks {
+    /// Saved bookmarks, oldest first
+    pub fn list(&self) -> &[CameraBookmark] {
+        &self.bookmarks
+    }
+
+    pub fn len(&self) -> usize {
+        self.bookmarks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty()
+    }
+
+    /// Bookmark by name
+    pub fn get(&self, name: &str) -> Option<&CameraBookmark> {
+        self.bookmarks.iter().find(|bookmark| bookmark.name == name)
+    }
+
+    /// Save a view under a name, replacing a bookmark of the same name in place
+    pub fn save(&mut self, name: &str, camera: CameraState) {
+        let bookmark = CameraBookmark {
+            name: name.to_string(),
+            camera,
+            created_at: Utc::now(),
+        };
+        match self.bookmarks.iter().position(|b| b.name == name) {
+            Some(index) => {
+                self.bookmarks[index] = bookmark;
+                self.current = Some(index);
+            }
+            None => {
+                self.bookmarks.push(bookmark);
+                self.current = Some(self.bookmarks.len() - 1);
+            }
+        }
+    }
+
+    /// Remove a bookmark, returning whether it existed
+    pub fn remove(&mut self, name: &str) -> bool {
+        let Some(index) = self.bookmarks.iter().position(|b| b.name == name) else {
+            return false;
+        };
+        self.bookmarks.remove(index);
+        self.current = match self.current {
+            Some(current) if current == index => None,
+            Some(current) if current > index => Some(current - 1),
+            current => current,
+        };
+        true
+    }
+
+    /// Name for a new bookmark that no existing one uses, such as `View 3`
+    pub fn next_name(&self) -> String {
+        (self.bookmarks.len() + 1..)
+            .map(|n| format!("View {}", n))
+            .find(|name| self.get(name).is_none())
+            .unwrap_or_default()
+    }
+
+    /// Step `offset` bookmarks from the one last visited, wrapping around
+    ///
+    /// Before any bookmark was visited, stepping forward starts at the first
+    /// and stepping back at the last.
+    pub fn cycle(&mut self, offset: isize) -> Option<&CameraBookmark> {
+        if self.bookmarks.is_empty() {
+            return None;
+        }
+        let len = self.bookmarks.len() as isize;
+        let index = match self.current {
+            Some(current) => (current as isize + offset).rem_euclid(len),
+            None if offset < 0 => (len + offset).rem_euclid(len),
+            None => (offset - 1).max(0).rem_euclid(len),
+        } as usize;
+        self.current = Some(index);
+        self.bookmarks.get(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::Camera;
+    use nalgebra::Point3;
+
+    fn view(x: f32) -> CameraState {
+        let mut camera = Camera::new();
+        camera.position = Point3::new(x, 0.0, 10.0);
+        camera.state()
+    }
+
+    #[test]
+    fn test_save_replace_remove_and_cycle() {
+        let mut bookmarks = CameraBookmarks::default();
+        assert!(bookmarks.cycle(1).is_none());
+
+        bookmarks.save("Inbox", view(1.0));
+        bookmarks.save("Sources", view(2.0));
+        bookmarks.save(&bookmarks.next_name(), view(3.0));
+        assert_eq!(bookmarks.len(), 3);
+        assert!(bookmarks.get("View 3").is_some());
+
+        // Saving under an existing name keeps its place
+        bookmarks.save("Inbox", view(4.0));
+        assert_eq!(bookmarks.list()[0].camera.position.x, 4.0);
+
+        // Cycling continues from the bookmark saved last
+        assert_eq!(bookmarks.cycle(1).unwrap().name, "Sources");
+        assert_eq!(bookmarks.cycle(1).unwrap().name, "View 3");
+        assert_eq!(bookmarks.cycle(1).unwrap().name, "Inbox");
+        assert_eq!(bookmarks.cycle(-1).unwrap().name, "View 3");
+
+        assert!(bookmarks.remove("View 3"));
+        assert!(!bookmarks.remove("View 3"));
+        assert_eq!(bookmarks.cycle(1).unwrap().name, "Inbox");
+    }
+}
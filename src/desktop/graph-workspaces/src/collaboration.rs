@@ -2,7 +2,12 @@
 //!
 //! This module provides real-time collaboration features for HorizonOS graph desktop workspaces,
 //! including shared workspace management, real-time synchronization, and multi-user support.
+//!
+//! Participants see each other's cursors in per-user colors with name labels,
+//! can attach short-lived chat bubbles to their cursor, and discuss nodes in
+//! comment threads that persist with the workspace.
 
+use crate::comments::{Comment, CommentThread, CommentThreads};
 use crate::{Workspace, WorkspaceError};
 use horizonos_graph_engine::scene::SceneId;
 use serde::{Deserialize, Serialize};
@@ -10,9 +15,26 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::sync::broadcast;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use uuid::Uuid;
 
+/// How long a cursor chat bubble stays visible
+const CURSOR_CHAT_SECONDS: i64 = 5;
+/// Longest cursor chat message, in characters
+const MAX_CURSOR_CHAT_CHARS: usize = 140;
+
+/// Cursor colors handed out to participants
+const CURSOR_PALETTE: [[f32; 3]; 8] = [
+    [0.2, 0.6, 1.0],
+    [0.95, 0.4, 0.35],
+    [0.3, 0.8, 0.45],
+    [0.95, 0.7, 0.2],
+    [0.7, 0.45, 0.95],
+    [0.2, 0.8, 0.8],
+    [0.95, 0.45, 0.75],
+    [0.6, 0.75, 0.3],
+];
+
 /// Collaboration manager for shared workspaces
 pub struct CollaborationManager {
     /// Shared workspaces
@@ -167,7 +189,7 @@ impl CollaborationManager {
         }
         
         // Apply the change
-        self.apply_change_to_workspace(&mut shared_workspace.workspace, &change)?;
+        self.apply_change_to_workspace(shared_workspace, &change, user_id)?;
         
         // Record the change
         shared_workspace.add_change(change.clone(), user_id.to_string());
@@ -201,6 +223,104 @@ impl CollaborationManager {
             .collect()
     }
     
+    /// Comment on a node, in an existing thread or a new one; returns the thread ID
+    pub async fn add_comment(
+        &self,
+        workspace_id: &str,
+        node_id: SceneId,
+        thread_id: Option<&str>,
+        user_id: &str,
+        text: &str,
+    ) -> Result<String, WorkspaceError> {
+        let thread_id = thread_id.map(str::to_string).unwrap_or_else(|| Uuid::new_v4().to_string());
+        let change = WorkspaceChange::AddComment {
+            thread_id: thread_id.clone(),
+            node_id,
+            comment: Comment::new(user_id, text),
+        };
+        self.apply_workspace_change(workspace_id, change, user_id).await?;
+        Ok(thread_id)
+    }
+    
+    /// Resolve or reopen a comment thread
+    pub async fn set_comment_resolved(
+        &self,
+        workspace_id: &str,
+        thread_id: &str,
+        resolved: bool,
+        user_id: &str,
+    ) -> Result<(), WorkspaceError> {
+        let change = WorkspaceChange::SetCommentResolved {
+            thread_id: thread_id.to_string(),
+            resolved,
+            at: Utc::now(),
+        };
+        self.apply_workspace_change(workspace_id, change, user_id).await
+    }
+    
+    /// Comment threads on a node, oldest first
+    pub async fn comment_threads(&self, workspace_id: &str, node_id: SceneId) -> Vec<CommentThread> {
+        self.shared_workspaces.read().await
+            .get(workspace_id)
+            .map(|ws| ws.comments.for_node(node_id).into_iter().cloned().collect())
+            .unwrap_or_default()
+    }
+    
+    /// Move a participant's cursor and show it to the others
+    pub async fn update_cursor(&self, session_id: &str, position: (f32, f32)) -> Result<(), WorkspaceError> {
+        let mut sessions = self.active_sessions.write().await;
+        let session = sessions.get_mut(session_id)
+            .ok_or_else(|| WorkspaceError::NotFound(session_id.to_string()))?;
+        session.update_cursor(position);
+        
+        if session.settings.show_cursor {
+            self.event_sender.send(CollaborationEvent::CursorMoved {
+                workspace_id: session.workspace_id.clone(),
+                user_id: session.user_id.clone(),
+                position,
+            }).ok();
+        }
+        Ok(())
+    }
+    
+    /// Show a short chat bubble at a participant's cursor; empty text clears it
+    pub async fn send_cursor_chat(&self, session_id: &str, text: &str) -> Result<(), WorkspaceError> {
+        let mut sessions = self.active_sessions.write().await;
+        let session = sessions.get_mut(session_id)
+            .ok_or_else(|| WorkspaceError::NotFound(session_id.to_string()))?;
+        let text: String = text.trim().chars().take(MAX_CURSOR_CHAT_CHARS).collect();
+        session.chat = (!text.is_empty()).then(|| CursorChat { text: text.clone(), sent_at: Utc::now() });
+        session.last_activity = Utc::now();
+        
+        // Chat is ephemeral: it is broadcast but not recorded as a workspace change
+        self.event_sender.send(CollaborationEvent::CursorChat {
+            workspace_id: session.workspace_id.clone(),
+            user_id: session.user_id.clone(),
+            text,
+        }).ok();
+        Ok(())
+    }
+    
+    /// Cursors of the other participants in a workspace, to draw with name labels
+    pub async fn remote_cursors(&self, workspace_id: &str, viewer_id: &str) -> Vec<RemoteCursor> {
+        let users = self.users.read().await;
+        let now = Utc::now();
+        self.active_sessions.read().await
+            .values()
+            .filter(|session| session.workspace_id == workspace_id && session.user_id != viewer_id)
+            .filter(|session| session.settings.show_cursor && session.is_active())
+            .filter_map(|session| {
+                Some(RemoteCursor {
+                    user_id: session.user_id.clone(),
+                    name: users.get(&session.user_id).map_or_else(|| session.user_id.clone(), |user| user.name.clone()),
+                    color: session.settings.cursor_color,
+                    position: session.cursor_position?,
+                    chat: session.chat.as_ref().filter(|chat| chat.is_visible(now)).map(|chat| chat.text.clone()),
+                })
+            })
+            .collect()
+    }
+    
     /// Register a user
     pub async fn register_user(&self, user: User) -> Result<(), WorkspaceError> {
         self.users.write().await.insert(user.id.clone(), user);
@@ -259,9 +379,12 @@ impl CollaborationManager {
                     WorkspaceChange::AddNode { .. } | 
                     WorkspaceChange::RemoveNode { .. } |
                     WorkspaceChange::MoveNode { .. } |
-                    WorkspaceChange::UpdateNodeProperties { .. }
+                    WorkspaceChange::UpdateNodeProperties { .. } |
+                    WorkspaceChange::AddComment { .. } |
+                    WorkspaceChange::SetCommentResolved { .. }
                 ),
-                UserRole::Viewer => false,
+                // Viewers may join the discussion but not settle it
+                UserRole::Viewer => matches!(change, WorkspaceChange::AddComment { .. }),
             }
         } else {
             false
@@ -270,9 +393,11 @@ impl CollaborationManager {
     
     fn apply_change_to_workspace(
         &self,
-        workspace: &mut Workspace,
+        shared_workspace: &mut SharedWorkspace,
         change: &WorkspaceChange,
+        user_id: &str,
     ) -> Result<(), WorkspaceError> {
+        let workspace = &mut shared_workspace.workspace;
         match change {
             WorkspaceChange::AddNode { node_id } => {
                 workspace.add_node(*node_id);
@@ -298,9 +423,15 @@ impl CollaborationManager {
             WorkspaceChange::UpdateSettings { settings } => {
                 workspace.settings = settings.clone();
             }
+            WorkspaceChange::AddComment { thread_id, node_id, comment } => {
+                shared_workspace.comments.add_comment(thread_id, *node_id, comment.clone())?;
+            }
+            WorkspaceChange::SetCommentResolved { thread_id, resolved, at } => {
+                shared_workspace.comments.set_resolved(thread_id, *resolved, user_id, *at)?;
+            }
         }
         
-        workspace.touch();
+        shared_workspace.workspace.touch();
         Ok(())
     }
     
@@ -332,6 +463,9 @@ pub struct SharedWorkspace {
     pub changes: Vec<WorkspaceChangeRecord>,
     /// Sharing metadata
     pub sharing_metadata: SharingMetadata,
+    /// Comment threads on nodes
+    #[serde(default)]
+    pub comments: CommentThreads,
 }
 
 impl SharedWorkspace {
@@ -347,6 +481,7 @@ impl SharedWorkspace {
             participants,
             changes: Vec::new(),
             sharing_metadata: SharingMetadata::new(),
+            comments: CommentThreads::new(),
         }
     }
     
@@ -514,6 +649,9 @@ pub struct CollaborationSession {
     pub last_activity: DateTime<Utc>,
     /// Current cursor position
     pub cursor_position: Option<(f32, f32)>,
+    /// Chat bubble at the cursor
+    #[serde(default)]
+    pub chat: Option<CursorChat>,
     /// Session-specific settings
     pub settings: SessionSettings,
 }
@@ -525,6 +663,10 @@ impl CollaborationSession {
         user_id: String,
         _event_sender: broadcast::Sender<CollaborationEvent>,
     ) -> Self {
+        let settings = SessionSettings {
+            cursor_color: cursor_color_for(&user_id),
+            ..SessionSettings::default()
+        };
         Self {
             id: Uuid::new_v4().to_string(),
             workspace_id,
@@ -532,7 +674,8 @@ impl CollaborationSession {
             started_at: Utc::now(),
             last_activity: Utc::now(),
             cursor_position: None,
-            settings: SessionSettings::default(),
+            chat: None,
+            settings,
         }
     }
     
@@ -550,6 +693,46 @@ impl CollaborationSession {
     }
 }
 
+/// Ephemeral chat bubble shown at a participant's cursor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CursorChat {
+    /// Message text
+    pub text: String,
+    /// When the message was sent
+    pub sent_at: DateTime<Utc>,
+}
+
+impl CursorChat {
+    /// Whether the bubble is still showing
+    pub fn is_visible(&self, now: DateTime<Utc>) -> bool {
+        now.signed_duration_since(self.sent_at) < Duration::seconds(CURSOR_CHAT_SECONDS)
+    }
+}
+
+/// Another participant's cursor as drawn locally
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteCursor {
+    /// User ID
+    pub user_id: String,
+    /// Name label
+    pub name: String,
+    /// Cursor and label color
+    pub color: [f32; 3],
+    /// Cursor position
+    pub position: (f32, f32),
+    /// Chat bubble text, while visible
+    pub chat: Option<String>,
+}
+
+/// Cursor color for a user, the same on every participant's screen
+pub fn cursor_color_for(user_id: &str) -> [f32; 3] {
+    // FNV-1a, stable across builds unlike the std hasher
+    let hash = user_id.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    CURSOR_PALETTE[(hash % CURSOR_PALETTE.len() as u64) as usize]
+}
+
 /// Session-specific settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionSettings {
@@ -586,6 +769,10 @@ pub enum WorkspaceChange {
     UpdateLayout { layout: crate::layout::WorkspaceLayout },
     /// Update workspace settings
     UpdateSettings { settings: crate::WorkspaceSettings },
+    /// Comment on a node, starting the thread if it is new
+    AddComment { thread_id: String, node_id: SceneId, comment: Comment },
+    /// Resolve or reopen a comment thread
+    SetCommentResolved { thread_id: String, resolved: bool, at: DateTime<Utc> },
 }
 
 /// Record of a workspace change
@@ -657,6 +844,8 @@ pub enum CollaborationEvent {
     WorkspaceChanged { workspace_id: String, change: WorkspaceChange, user_id: String },
     /// User cursor moved
     CursorMoved { workspace_id: String, user_id: String, position: (f32, f32) },
+    /// User sent a cursor chat message; empty text clears the bubble
+    CursorChat { workspace_id: String, user_id: String, text: String },
     /// User status changed
     UserStatusChanged { user_id: String, status: UserStatus },
 }
@@ -770,6 +959,35 @@ mod tests {
         assert!(user.preferences.show_other_cursors);
     }
     
+    #[tokio::test]
+    async fn test_comments_and_cursor_chat() {
+        let manager = CollaborationManager::new();
+        let workspace = Workspace::new("Test", "Test workspace");
+        let mut permissions = WorkspacePermissions::default();
+        permissions.allowed_users.insert("viewer".to_string());
+        permissions.user_roles.insert("viewer".to_string(), UserRole::Viewer);
+        let workspace_id = manager.create_shared_workspace(workspace, "owner".to_string(), permissions).await.unwrap();
+        
+        let thread = manager.add_comment(&workspace_id, 3, None, "viewer", "Why is this here?").await.unwrap();
+        manager.add_comment(&workspace_id, 3, Some(&thread), "owner", "Moving it").await.unwrap();
+        assert!(manager.set_comment_resolved(&workspace_id, &thread, true, "viewer").await.is_err());
+        manager.set_comment_resolved(&workspace_id, &thread, true, "owner").await.unwrap();
+        let threads = manager.comment_threads(&workspace_id, 3).await;
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].comments.len(), 2);
+        assert!(threads[0].resolved);
+        
+        let owner = manager.join_workspace(&workspace_id, "owner", None).await.unwrap();
+        manager.join_workspace(&workspace_id, "viewer", None).await.unwrap();
+        manager.update_cursor(&owner.id, (10.0, 20.0)).await.unwrap();
+        manager.send_cursor_chat(&owner.id, "over here").await.unwrap();
+        let cursors = manager.remote_cursors(&workspace_id, "viewer").await;
+        assert_eq!(cursors.len(), 1);
+        assert_eq!(cursors[0].chat.as_deref(), Some("over here"));
+        assert_eq!(cursors[0].color, cursor_color_for("owner"));
+        assert!(manager.remote_cursors(&workspace_id, "owner").await.is_empty());
+    }
+    
     #[test]
     fn test_collaboration_session() {
         let (event_sender, _) = broadcast::channel(100);
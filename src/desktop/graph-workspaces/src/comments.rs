@@ -0,0 +1,205 @@
+//! Comment threads attached to nodes of shared workspaces
+//!
+//! Threads are changed only through [`WorkspaceChange`] operations, so they
+//! sync over the same channel as every other workspace edit. The operations
+//! converge whatever order participants receive them in: adding a comment
+//! twice keeps one copy, comments are ordered by time and ID, and the latest
+//! resolve or unresolve wins.
+//!
+//! [`WorkspaceChange`]: crate::collaboration::WorkspaceChange
+
+use crate::WorkspaceError;
+use chrono::{DateTime, Utc};
+use horizonos_graph_engine::scene::SceneId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A comment in a thread
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Comment {
+    /// Comment ID
+    pub id: String,
+    /// Author user ID
+    pub author_id: String,
+    /// Comment text
+    pub text: String,
+    /// When the comment was written
+    pub created_at: DateTime<Utc>,
+}
+
+impl Comment {
+    /// Create a comment written now
+    pub fn new(author_id: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            author_id: author_id.into(),
+            text: text.into(),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// A discussion attached to a node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentThread {
+    /// Thread ID
+    pub id: String,
+    /// Node the thread is attached to
+    pub node_id: SceneId,
+    /// Comments, oldest first
+    pub comments: Vec<Comment>,
+    /// Whether the discussion is settled
+    pub resolved: bool,
+    /// User who last resolved or reopened the thread
+    pub resolved_by: Option<String>,
+    /// When the thread was last resolved or reopened
+    pub resolved_changed_at: Option<DateTime<Utc>>,
+}
+
+impl CommentThread {
+    /// When the thread was started
+    pub fn started_at(&self) -> Option<DateTime<Utc>> {
+        self.comments.first().map(|comment| comment.created_at)
+    }
+}
+
+/// All comment threads of a shared workspace
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommentThreads {
+    threads: HashMap<String, CommentThread>,
+}
+
+impl CommentThreads {
+    /// Create an empty set of threads
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a comment, starting the thread if it does not exist yet
+    ///
+    /// A comment already in the thread is ignored, so replayed operations are harmless.
+    pub fn add_comment(&mut self, thread_id: &str, node_id: SceneId, comment: Comment) -> Result<(), WorkspaceError> {
+        let thread = self.threads.entry(thread_id.to_string()).or_insert_with(|| CommentThread {
+            id: thread_id.to_string(),
+            node_id,
+            comments: Vec::new(),
+            resolved: false,
+            resolved_by: None,
+            resolved_changed_at: None,
+        });
+        if thread.node_id != node_id {
+            return Err(WorkspaceError::SyncConflict(format!(
+                "Comment thread {} belongs to node {}, not {}",
+                thread_id, thread.node_id, node_id
+            )));
+        }
+        if thread.comments.iter().any(|existing| existing.id == comment.id) {
+            return Ok(());
+        }
+        let position = thread
+            .comments
+            .partition_point(|existing| (existing.created_at, &existing.id) <= (comment.created_at, &comment.id));
+        thread.comments.insert(position, comment);
+        Ok(())
+    }
+
+    /// Resolve or reopen a thread; an older change than the last one applied is ignored
+    pub fn set_resolved(
+        &mut self,
+        thread_id: &str,
+        resolved: bool,
+        user_id: &str,
+        at: DateTime<Utc>,
+    ) -> Result<(), WorkspaceError> {
+        let thread = self
+            .threads
+            .get_mut(thread_id)
+            .ok_or_else(|| WorkspaceError::NotFound(format!("comment thread {}", thread_id)))?;
+        if thread.resolved_changed_at.is_some_and(|last| last > at) {
+            return Ok(());
+        }
+        thread.resolved = resolved;
+        thread.resolved_by = Some(user_id.to_string());
+        thread.resolved_changed_at = Some(at);
+        Ok(())
+    }
+
+    /// Get a thread
+    pub fn get(&self, thread_id: &str) -> Option<&CommentThread> {
+        self.threads.get(thread_id)
+    }
+
+    /// Threads on a node, oldest first
+    pub fn for_node(&self, node_id: SceneId) -> Vec<&CommentThread> {
+        let mut threads: Vec<&CommentThread> = self.threads.values().filter(|thread| thread.node_id == node_id).collect();
+        threads.sort_by(|a, b| a.started_at().cmp(&b.started_at()).then_with(|| a.id.cmp(&b.id)));
+        threads
+    }
+
+    /// Number of open threads on a node, for a badge
+    pub fn unresolved_count(&self, node_id: SceneId) -> usize {
+        self.threads.values().filter(|thread| thread.node_id == node_id && !thread.resolved).count()
+    }
+
+    /// Drop the threads of a removed node
+    pub fn remove_node(&mut self, node_id: SceneId) {
+        self.threads.retain(|_, thread| thread.node_id != node_id);
+    }
+
+    /// Number of threads
+    pub fn len(&self) -> usize {
+        self.threads.len()
+    }
+
+    /// Whether there are no threads
+    pub fn is_empty(&self) -> bool {
+        self.threads.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_comments_converge_in_any_order() {
+        let first = Comment::new("alice", "Is this the final draft?");
+        let mut second = Comment::new("bob", "Yes");
+        second.created_at = first.created_at + Duration::seconds(1);
+
+        let mut a = CommentThreads::new();
+        a.add_comment("t1", 7, first.clone()).unwrap();
+        a.add_comment("t1", 7, second.clone()).unwrap();
+        let mut b = CommentThreads::new();
+        b.add_comment("t1", 7, second.clone()).unwrap();
+        b.add_comment("t1", 7, first.clone()).unwrap();
+        b.add_comment("t1", 7, first.clone()).unwrap();
+
+        assert_eq!(a.get("t1").unwrap().comments, b.get("t1").unwrap().comments);
+        assert_eq!(b.get("t1").unwrap().comments.len(), 2);
+        assert!(a.add_comment("t1", 8, Comment::new("carol", "Wrong node")).is_err());
+    }
+
+    #[test]
+    fn test_latest_resolve_wins() {
+        let mut threads = CommentThreads::new();
+        threads.add_comment("t1", 7, Comment::new("alice", "Typo in the title")).unwrap();
+        assert_eq!(threads.unresolved_count(7), 1);
+
+        let now = Utc::now();
+        threads.set_resolved("t1", true, "bob", now).unwrap();
+        // A reopen made before the resolve arrives late and is ignored
+        threads.set_resolved("t1", false, "alice", now - Duration::seconds(5)).unwrap();
+        assert!(threads.get("t1").unwrap().resolved);
+        assert_eq!(threads.unresolved_count(7), 0);
+
+        threads.set_resolved("t1", false, "alice", now + Duration::seconds(5)).unwrap();
+        assert_eq!(threads.unresolved_count(7), 1);
+        assert!(threads.set_resolved("missing", true, "bob", now).is_err());
+
+        threads.remove_node(7);
+        assert!(threads.is_empty());
+    }
+}
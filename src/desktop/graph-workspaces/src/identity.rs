@@ -0,0 +1,240 @@
+//! Workspace visual identity: accent tint, emblem and switch transition
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Accent colors handed out to new workspaces
+const ACCENT_PALETTE: [[f32; 4]; 8] = [
+    [0.29, 0.56, 0.89, 1.0], // Blue
+    [0.48, 0.41, 0.93, 1.0], // Violet
+    [0.30, 0.69, 0.31, 1.0], // Green
+    [1.00, 0.60, 0.00, 1.0], // Orange
+    [0.91, 0.30, 0.40, 1.0], // Rose
+    [0.00, 0.74, 0.83, 1.0], // Cyan
+    [0.80, 0.75, 0.20, 1.0], // Olive
+    [0.60, 0.45, 0.35, 1.0], // Brown
+];
+
+/// Visual identity of a workspace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceIdentity {
+    /// Accent color (RGBA)
+    pub accent_color: [f32; 4],
+    /// How strongly the accent tints the desktop (0.0 to 1.0)
+    pub tint_strength: f32,
+    /// Emblem icon name or path
+    pub emblem: Option<String>,
+    /// Tint the background
+    pub tint_background: bool,
+    /// Tint cluster boundaries
+    pub tint_cluster_boundaries: bool,
+    /// Tint panel highlights
+    pub tint_panels: bool,
+    /// Animation played when switching to this workspace
+    pub transition: TransitionConfig,
+}
+
+/// Switch transition configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransitionConfig {
+    /// Transition style
+    pub style: TransitionStyle,
+    /// Duration in milliseconds
+    pub duration_ms: u64,
+}
+
+/// Transition styles for workspace switches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransitionStyle {
+    /// Switch instantly
+    None,
+    /// Crossfade the accent tint
+    Crossfade,
+    /// Crossfade and briefly show the emblem
+    Pulse,
+    /// Sweep the new accent across the screen
+    Sweep,
+}
+
+/// In-progress transition between two workspace identities
+#[derive(Debug, Clone)]
+pub struct IdentityTransition {
+    /// Identity being left, if any
+    from: Option<WorkspaceIdentity>,
+    /// Identity being entered
+    to: WorkspaceIdentity,
+    /// Transition start time
+    started_at: Instant,
+}
+
+/// Interpolated identity values for one frame of a transition
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransitionFrame {
+    /// Transition progress (0.0 to 1.0)
+    pub progress: f32,
+    /// Accent color to render with
+    pub accent_color: [f32; 4],
+    /// Tint strength to render with
+    pub tint_strength: f32,
+    /// Emblem overlay opacity
+    pub emblem_opacity: f32,
+    /// Emblem overlay scale
+    pub emblem_scale: f32,
+    /// Sweep edge position across the screen (0.0 to 1.0), for sweep transitions
+    pub sweep_position: Option<f32>,
+}
+
+impl WorkspaceIdentity {
+    /// Identity with an accent picked deterministically from a seed, such as the workspace ID
+    pub fn from_seed(seed: &str) -> Self {
+        let hash = seed.bytes().fold(0u32, |acc, b| acc.wrapping_mul(31).wrapping_add(b as u32));
+        Self {
+            accent_color: ACCENT_PALETTE[hash as usize % ACCENT_PALETTE.len()],
+            ..Default::default()
+        }
+    }
+
+    /// Set the accent color
+    pub fn with_accent(mut self, accent_color: [f32; 4]) -> Self {
+        self.accent_color = accent_color;
+        self
+    }
+
+    /// Set the emblem
+    pub fn with_emblem(mut self, emblem: &str) -> Self {
+        self.emblem = Some(emblem.to_string());
+        self
+    }
+}
+
+impl IdentityTransition {
+    /// Start a transition now
+    pub fn new(from: Option<WorkspaceIdentity>, to: WorkspaceIdentity) -> Self {
+        Self {
+            from,
+            to,
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Identity being entered
+    pub fn target(&self) -> &WorkspaceIdentity {
+        &self.to
+    }
+
+    /// Whether the transition has completed
+    pub fn is_finished(&self) -> bool {
+        self.started_at.elapsed() >= self.duration()
+    }
+
+    /// Sample the transition at the current time
+    pub fn sample(&self) -> TransitionFrame {
+        self.sample_at(self.started_at.elapsed())
+    }
+
+    /// Sample the transition after the given elapsed time
+    pub fn sample_at(&self, elapsed: Duration) -> TransitionFrame {
+        let duration = self.duration();
+        let progress = if duration.is_zero() {
+            1.0
+        } else {
+            (elapsed.as_secs_f32() / duration.as_secs_f32()).clamp(0.0, 1.0)
+        };
+
+        // Ease in-out so the tint settles gently
+        let eased = progress * progress * (3.0 - 2.0 * progress);
+
+        let (from_accent, from_strength) = match &self.from {
+            Some(from) => (from.accent_color, from.tint_strength),
+            None => (self.to.accent_color, 0.0),
+        };
+
+        let mut accent_color = [0.0; 4];
+        for (i, channel) in accent_color.iter_mut().enumerate() {
+            *channel = from_accent[i] + (self.to.accent_color[i] - from_accent[i]) * eased;
+        }
+
+        let (emblem_opacity, emblem_scale, sweep_position) = match self.to.transition.style {
+            TransitionStyle::None | TransitionStyle::Crossfade => (0.0, 1.0, None),
+            TransitionStyle::Pulse => {
+                // Emblem fades in and out while growing slightly
+                let pulse = (progress * std::f32::consts::PI).sin();
+                let opacity = if self.to.emblem.is_some() { pulse } else { 0.0 };
+                (opacity, 1.0 + 0.2 * pulse, None)
+            }
+            TransitionStyle::Sweep => (0.0, 1.0, Some(eased)),
+        };
+
+        TransitionFrame {
+            progress,
+            accent_color,
+            tint_strength: from_strength + (self.to.tint_strength - from_strength) * eased,
+            emblem_opacity,
+            emblem_scale,
+            sweep_position,
+        }
+    }
+
+    /// Effective duration, zero for instant switches
+    fn duration(&self) -> Duration {
+        match self.to.transition.style {
+            TransitionStyle::None => Duration::ZERO,
+            _ => Duration::from_millis(self.to.transition.duration_ms),
+        }
+    }
+}
+
+impl Default for WorkspaceIdentity {
+    fn default() -> Self {
+        Self {
+            accent_color: ACCENT_PALETTE[0],
+            tint_strength: 0.35,
+            emblem: None,
+            tint_background: true,
+            tint_cluster_boundaries: true,
+            tint_panels: true,
+            transition: TransitionConfig::default(),
+        }
+    }
+}
+
+impl Default for TransitionConfig {
+    fn default() -> Self {
+        Self {
+            style: TransitionStyle::Pulse,
+            duration_ms: 400,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_from_seed_is_stable() {
+        let a = WorkspaceIdentity::from_seed("workspace-1");
+        let b = WorkspaceIdentity::from_seed("workspace-1");
+        assert_eq!(a.accent_color, b.accent_color);
+    }
+
+    #[test]
+    fn test_transition_interpolates_accent() {
+        let from = WorkspaceIdentity::default().with_accent([0.0, 0.0, 0.0, 1.0]);
+        let to = WorkspaceIdentity::default()
+            .with_accent([1.0, 1.0, 1.0, 1.0])
+            .with_emblem("code");
+        let transition = IdentityTransition::new(Some(from), to);
+
+        let start = transition.sample_at(Duration::ZERO);
+        assert_eq!(start.accent_color, [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(start.emblem_opacity, 0.0);
+
+        let middle = transition.sample_at(Duration::from_millis(200));
+        assert!(middle.emblem_opacity > 0.9);
+
+        let end = transition.sample_at(Duration::from_millis(400));
+        assert_eq!(end.progress, 1.0);
+        assert_eq!(end.accent_color, [1.0, 1.0, 1.0, 1.0]);
+    }
+}
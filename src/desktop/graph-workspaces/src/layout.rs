@@ -10,25 +10,54 @@ use horizonos_graph_engine::scene::SceneId;
 pub struct WorkspaceLayout {
     /// Layout type
     pub layout_type: LayoutType,
+    /// Registered layout algorithm chosen for this workspace; `None` follows
+    /// `GraphConfig.layout_algorithm`
+    #[serde(default)]
+    pub algorithm: Option<String>,
     /// Node positions (if manually positioned)
     pub node_positions: HashMap<SceneId, Point3<f32>>,
     /// Layout parameters
     pub parameters: LayoutParameters,
     /// Viewport configuration
     pub viewport: ViewportConfig,
+    /// Clusters collapsed into group nodes
+    #[serde(default)]
+    pub collapsed_groups: Vec<String>,
 }
 
 impl Default for WorkspaceLayout {
     fn default() -> Self {
         Self {
             layout_type: LayoutType::ForceDirected,
+            algorithm: None,
             node_positions: HashMap::new(),
             parameters: LayoutParameters::default(),
             viewport: ViewportConfig::default(),
+            collapsed_groups: Vec::new(),
         }
     }
 }
 
+impl WorkspaceLayout {
+    /// Layout algorithm to run for this workspace, given the configured default
+    pub fn algorithm_or<'a>(&'a self, default: &'a str) -> &'a str {
+        self.algorithm.as_deref().unwrap_or(default)
+    }
+
+    /// Remember whether a cluster is collapsed into a group node
+    pub fn set_group_collapsed(&mut self, group_id: &str, collapsed: bool) {
+        self.collapsed_groups.retain(|id| id != group_id);
+        if collapsed {
+            self.collapsed_groups.push(group_id.to_string());
+        }
+    }
+
+    /// Whether a cluster was collapsed into a group node
+    pub fn is_group_collapsed(&self, group_id: &str) -> bool {
+        self.collapsed_groups.iter().any(|id| id == group_id)
+    }
+}
+
 /// Layout types
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum LayoutType {
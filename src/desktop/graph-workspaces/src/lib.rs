@@ -2,25 +2,79 @@
 //! 
 //! Provides workspace organization, switching, and persistence
 
-use horizonos_graph_engine::scene::SceneId;
+use horizonos_graph_engine::scene::{Scene, SceneId, SceneNode};
+use horizonos_graph_engine::{CameraState, EdgeFilter};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 use tokio::sync::broadcast;
 use chrono::{DateTime, Utc};
+use horizonos_graph_errors::{Category, Classify, Problem, RecoveryAction, RecoveryKind, Retryability, Severity};
+use horizonos_graph_sync::ConflictCenter;
 
 pub mod layout;
 pub mod persistence;
 pub mod rules;
 pub mod templates;
 pub mod collaboration;
+pub mod comments;
+pub mod identity;
+pub mod archive;
+pub mod bookmarks;
+pub mod publishing;
+pub mod preload;
+pub mod session;
+pub mod sync;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
 
 use layout::WorkspaceLayout;
 use persistence::WorkspacePersistence;
 use rules::WorkspaceRules;
+pub use rules::{NodeOrigin, RouteCondition, RoutingRule, SOURCE_APP_PROPERTY};
 use templates::WorkspaceTemplate;
 use collaboration::CollaborationManager;
 pub use collaboration::{CollaborationEvent, SharedWorkspace, User, UserStatus};
+pub use identity::{WorkspaceIdentity, IdentityTransition, TransitionFrame, TransitionStyle, TransitionConfig};
+pub use archive::{ArchivedNode, ImportedWorkspace, WorkspaceArchive};
+pub use bookmarks::{CameraBookmark, CameraBookmarks};
+pub use preload::{PreloadCandidate, PreloadConfig, PreloadStage, PreloadStats, PreloadWarmer, WorkspacePreloader};
+pub use publishing::{Publication, PublishChannel, PublishOptions, PublishedInfo, PublishedSnapshot, Redaction, RedactionMode};
+pub use session::{JournalOp, RecoveredSession, SessionJournal, SessionState};
+pub use sync::{MergeOutcome, WorkspaceSync};
+
+/// Default time between session checkpoints
+pub const DEFAULT_AUTO_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Public workspace operations, implemented by `WorkspaceManager` and by
+/// the in-memory double in `testing`
+pub trait WorkspaceService: Send + Sync {
+    /// Create a new workspace
+    fn create_workspace(&self, name: &str, description: &str) -> Result<String, WorkspaceError>;
+    
+    /// Switch to a different workspace
+    fn switch_workspace(&self, workspace_id: &str) -> Result<(), WorkspaceError>;
+    
+    /// Get the active workspace
+    fn get_active_workspace(&self) -> Option<Workspace>;
+    
+    /// Get a specific workspace
+    fn get_workspace(&self, workspace_id: &str) -> Option<Workspace>;
+    
+    /// List all workspaces
+    fn list_workspaces(&self) -> Vec<WorkspaceInfo>;
+    
+    /// Delete a workspace
+    fn delete_workspace(&self, workspace_id: &str) -> Result<(), WorkspaceError>;
+    
+    /// Set the visual identity of a workspace
+    fn set_workspace_identity(&self, workspace_id: &str, identity: WorkspaceIdentity) -> Result<(), WorkspaceError>;
+    
+    /// Subscribe to workspace events
+    fn subscribe(&self) -> broadcast::Receiver<WorkspaceEvent>;
+}
 
 /// Workspace manager for organizing graph desktop sessions
 pub struct WorkspaceManager {
@@ -33,9 +87,25 @@ pub struct WorkspaceManager {
     /// Persistence handler
     persistence: WorkspacePersistence,
     /// Workspace rules engine
-    rules: WorkspaceRules,
+    rules: RwLock<WorkspaceRules>,
     /// Collaboration manager
     collaboration: CollaborationManager,
+    /// Identity transition started by the last switch
+    identity_transition: Arc<RwLock<Option<IdentityTransition>>>,
+    /// Published workspaces by publication ID
+    publications: Arc<RwLock<HashMap<String, Publication>>>,
+    /// Where publications are written
+    publish_channel: PublishChannel,
+    /// Crash journal of the running session
+    session: Option<Arc<SessionJournal>>,
+    /// Time between session checkpoints
+    auto_save_interval: Duration,
+    /// When the session was last checkpointed
+    last_checkpoint: Arc<RwLock<Instant>>,
+    /// Sync with other devices, once a conflict center is attached
+    sync: Option<Arc<WorkspaceSync>>,
+    /// Warms the workspace predicted to be switched to next
+    preloader: WorkspacePreloader,
 }
 
 impl WorkspaceManager {
@@ -48,30 +118,92 @@ impl WorkspaceManager {
             active_workspace: Arc::new(RwLock::new(None)),
             event_sender,
             persistence: WorkspacePersistence::new(),
-            rules: WorkspaceRules::new(),
+            rules: RwLock::new(WorkspaceRules::new()),
             collaboration: CollaborationManager::new(),
+            identity_transition: Arc::new(RwLock::new(None)),
+            publications: Arc::new(RwLock::new(HashMap::new())),
+            publish_channel: PublishChannel::new(),
+            session: None,
+            auto_save_interval: DEFAULT_AUTO_SAVE_INTERVAL,
+            last_checkpoint: Arc::new(RwLock::new(Instant::now())),
+            sync: None,
+            preloader: WorkspacePreloader::default(),
         }
     }
     
+    /// Publish to a different channel directory
+    pub fn with_publish_channel(mut self, channel: PublishChannel) -> Self {
+        self.publish_channel = channel;
+        self
+    }
+    
+    /// Preload predicted workspaces with this configuration
+    pub fn with_preload_config(self, config: PreloadConfig) -> Self {
+        self.preloader.set_config(config);
+        self
+    }
+    
+    /// Journal workspace changes so the session survives a crash
+    pub fn with_session_journal(mut self, journal: Arc<SessionJournal>) -> Self {
+        self.session = Some(journal);
+        self
+    }
+    
+    /// Checkpoint the session this often
+    pub fn with_auto_save_interval(mut self, interval: Duration) -> Self {
+        self.auto_save_interval = interval;
+        self
+    }
+    
+    /// Sync workspaces with other devices, reporting conflicts to `center`
+    ///
+    /// Call after [`with_session_journal`](Self::with_session_journal) so
+    /// resolved conflicts are journaled.
+    pub fn with_conflict_center(mut self, center: Arc<ConflictCenter>) -> Self {
+        let sync = Arc::new(WorkspaceSync::new(
+            self.workspaces.clone(),
+            self.event_sender.clone(),
+            self.session.clone(),
+            center.clone(),
+        ));
+        center.register_provider(sync.clone());
+        self.sync = Some(sync);
+        self
+    }
+    
+    /// Time between session checkpoints
+    pub fn auto_save_interval(&self) -> Duration {
+        self.auto_save_interval
+    }
+    
+    /// Crash journal of the running session, if one is attached
+    pub fn session_journal(&self) -> Option<&Arc<SessionJournal>> {
+        self.session.as_ref()
+    }
+    
     /// Initialize the workspace manager
     pub async fn initialize(&mut self) -> Result<(), WorkspaceError> {
-        // Load saved workspaces
+        // Load saved workspaces and the rules placing new nodes in them
         let saved_workspaces = self.persistence.load_workspaces().await?;
+        let routing_rules = self.persistence.load_routing_rules().await?;
+        self.rules.write().unwrap().set_routing_rules(routing_rules);
         
-        let mut workspaces = self.workspaces.write().unwrap();
-        for workspace in saved_workspaces {
-            workspaces.insert(workspace.id.clone(), workspace);
-        }
-        
-        // Create default workspace if none exist
-        if workspaces.is_empty() {
-            let default_workspace = Workspace::new("default", "Default Workspace");
-            workspaces.insert(default_workspace.id.clone(), default_workspace);
-        }
-        
-        // Set the first workspace as active
-        if let Some(first_id) = workspaces.keys().next().cloned() {
-            *self.active_workspace.write().unwrap() = Some(first_id);
+        {
+            let mut workspaces = self.workspaces.write().unwrap();
+            for workspace in saved_workspaces {
+                workspaces.insert(workspace.id.clone(), workspace);
+            }
+            
+            // Create default workspace if none exist
+            if workspaces.is_empty() {
+                let default_workspace = Workspace::new("default", "Default Workspace");
+                workspaces.insert(default_workspace.id.clone(), default_workspace);
+            }
+            
+            // Set the first workspace as active
+            if let Some(first_id) = workspaces.keys().next().cloned() {
+                *self.active_workspace.write().unwrap() = Some(first_id);
+            }
         }
         
         // Initialize collaboration manager
@@ -87,6 +219,7 @@ impl WorkspaceManager {
         
         let workspace_id = workspace.id.clone();
         
+        self.journal(JournalOp::WorkspaceSet { workspace: Box::new(workspace.clone()) });
         self.workspaces.write().unwrap()
             .insert(workspace_id.clone(), workspace);
         
@@ -102,6 +235,7 @@ impl WorkspaceManager {
         let workspace = template.instantiate();
         let workspace_id = workspace.id.clone();
         
+        self.journal(JournalOp::WorkspaceSet { workspace: Box::new(workspace.clone()) });
         self.workspaces.write().unwrap()
             .insert(workspace_id.clone(), workspace);
         
@@ -122,6 +256,15 @@ impl WorkspaceManager {
         
         let previous = self.active_workspace.read().unwrap().clone();
         *self.active_workspace.write().unwrap() = Some(workspace_id.to_string());
+        self.journal(JournalOp::ActiveWorkspace { id: workspace_id.to_string() });
+        
+        // Animate from the previous workspace's identity to the new one
+        let from_identity = previous.as_ref()
+            .and_then(|id| workspaces.get(id))
+            .map(|w| w.identity.clone());
+        let to_identity = workspaces[workspace_id].identity.clone();
+        *self.identity_transition.write().unwrap() = Some(IdentityTransition::new(from_identity, to_identity));
+        self.preloader.record_switch(workspace_id);
         
         self.event_sender.send(WorkspaceEvent::Switched {
             from: previous,
@@ -131,6 +274,29 @@ impl WorkspaceManager {
         Ok(())
     }
     
+    /// Switch to the workspace `offset` places from the active one in
+    /// creation order, wrapping around; returns the workspace switched to
+    pub fn switch_adjacent_workspace(&self, offset: isize) -> Option<String> {
+        let target = {
+            let workspaces = self.workspaces.read().unwrap();
+            let mut ordered: Vec<&Workspace> = workspaces.values().collect();
+            if ordered.is_empty() {
+                return None;
+            }
+            ordered.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+
+            let active = self.active_workspace.read().unwrap().clone();
+            let current = active
+                .and_then(|id| ordered.iter().position(|w| w.id == id))
+                .unwrap_or(0) as isize;
+            let index = (current + offset).rem_euclid(ordered.len() as isize) as usize;
+            ordered[index].id.clone()
+        };
+
+        self.switch_workspace(&target).ok()?;
+        Some(target)
+    }
+
     /// Get the active workspace
     pub fn get_active_workspace(&self) -> Option<Workspace> {
         let active_id = self.active_workspace.read().unwrap();
@@ -142,6 +308,11 @@ impl WorkspaceManager {
         }
     }
     
+    /// ID of the active workspace, without copying it
+    pub fn active_workspace_id(&self) -> Option<String> {
+        self.active_workspace.read().unwrap().clone()
+    }
+    
     /// Get a specific workspace
     pub fn get_workspace(&self, workspace_id: &str) -> Option<Workspace> {
         self.workspaces.read().unwrap().get(workspace_id).cloned()
@@ -189,11 +360,17 @@ impl WorkspaceManager {
                 .find(|id| *id != workspace_id)
                 .cloned()
             {
+                self.journal(JournalOp::ActiveWorkspace { id: other_id.clone() });
                 *self.active_workspace.write().unwrap() = Some(other_id);
             }
         }
         
         workspaces.remove(workspace_id);
+        self.preloader.release(workspace_id);
+        self.journal(JournalOp::WorkspaceRemoved { id: workspace_id.to_string() });
+        if let Some(sync) = &self.sync {
+            sync.forget(workspace_id);
+        }
         
         self.event_sender.send(WorkspaceEvent::Deleted {
             workspace_id: workspace_id.to_string(),
@@ -202,6 +379,392 @@ impl WorkspaceManager {
         Ok(())
     }
     
+    /// Set the visual identity of a workspace
+    pub fn set_workspace_identity(&self, workspace_id: &str, identity: WorkspaceIdentity) -> Result<(), WorkspaceError> {
+        let mut workspaces = self.workspaces.write().unwrap();
+        let workspace = workspaces.get_mut(workspace_id)
+            .ok_or_else(|| WorkspaceError::NotFound(workspace_id.to_string()))?;
+        
+        workspace.identity = identity;
+        self.journal(JournalOp::WorkspaceSet { workspace: Box::new(workspace.clone()) });
+        
+        self.event_sender.send(WorkspaceEvent::Modified {
+            workspace_id: workspace_id.to_string(),
+        }).ok();
+        
+        Ok(())
+    }
+    
+    /// Choose the layout algorithm of a workspace by its registered name
+    ///
+    /// `None` returns the workspace to the configured default layout.
+    pub fn set_workspace_layout_algorithm(&self, workspace_id: &str, algorithm: Option<String>) -> Result<(), WorkspaceError> {
+        let mut workspaces = self.workspaces.write().unwrap();
+        let workspace = workspaces.get_mut(workspace_id)
+            .ok_or_else(|| WorkspaceError::NotFound(workspace_id.to_string()))?;
+        
+        workspace.layout.algorithm = algorithm;
+        self.journal(JournalOp::WorkspaceSet { workspace: Box::new(workspace.clone()) });
+        
+        self.event_sender.send(WorkspaceEvent::Modified {
+            workspace_id: workspace_id.to_string(),
+        }).ok();
+        
+        Ok(())
+    }
+    
+    /// Save which edges a workspace draws
+    pub fn set_workspace_edge_filter(&self, workspace_id: &str, filter: EdgeFilter) -> Result<(), WorkspaceError> {
+        let mut workspaces = self.workspaces.write().unwrap();
+        let workspace = workspaces.get_mut(workspace_id)
+            .ok_or_else(|| WorkspaceError::NotFound(workspace_id.to_string()))?;
+        
+        workspace.settings.edge_filter = filter;
+        self.journal(JournalOp::WorkspaceSet { workspace: Box::new(workspace.clone()) });
+        
+        self.event_sender.send(WorkspaceEvent::Modified {
+            workspace_id: workspace_id.to_string(),
+        }).ok();
+        
+        Ok(())
+    }
+    
+    /// Remember in the active workspace whether a cluster is collapsed into a group node
+    pub fn set_group_collapsed(&self, group_id: &str, collapsed: bool) -> Result<(), WorkspaceError> {
+        let workspace_id = self.active_workspace.read().unwrap().clone()
+            .ok_or_else(|| WorkspaceError::NotFound("active workspace".to_string()))?;
+        let mut workspaces = self.workspaces.write().unwrap();
+        let workspace = workspaces.get_mut(&workspace_id)
+            .ok_or_else(|| WorkspaceError::NotFound(workspace_id.clone()))?;
+        
+        if workspace.layout.is_group_collapsed(group_id) == collapsed {
+            return Ok(());
+        }
+        workspace.layout.set_group_collapsed(group_id, collapsed);
+        self.journal(JournalOp::WorkspaceSet { workspace: Box::new(workspace.clone()) });
+        
+        self.event_sender.send(WorkspaceEvent::Modified { workspace_id }).ok();
+        
+        Ok(())
+    }
+    
+    /// Save the camera view under a name in the active workspace
+    ///
+    /// Without a name the view gets the next free `View N` name. Returns
+    /// the bookmark's name.
+    pub fn save_camera_bookmark(&self, name: Option<&str>, camera: CameraState) -> Result<String, WorkspaceError> {
+        let workspace_id = self.active_workspace.read().unwrap().clone()
+            .ok_or_else(|| WorkspaceError::NotFound("active workspace".to_string()))?;
+        let mut workspaces = self.workspaces.write().unwrap();
+        let workspace = workspaces.get_mut(&workspace_id)
+            .ok_or_else(|| WorkspaceError::NotFound(workspace_id.clone()))?;
+        
+        let name = name.map_or_else(|| workspace.camera_bookmarks.next_name(), str::to_string);
+        workspace.camera_bookmarks.save(&name, camera);
+        self.journal(JournalOp::WorkspaceSet { workspace: Box::new(workspace.clone()) });
+        
+        self.event_sender.send(WorkspaceEvent::Modified { workspace_id }).ok();
+        
+        Ok(name)
+    }
+    
+    /// Remove a camera bookmark from a workspace, returning whether it existed
+    pub fn remove_camera_bookmark(&self, workspace_id: &str, name: &str) -> Result<bool, WorkspaceError> {
+        let mut workspaces = self.workspaces.write().unwrap();
+        let workspace = workspaces.get_mut(workspace_id)
+            .ok_or_else(|| WorkspaceError::NotFound(workspace_id.to_string()))?;
+        
+        if !workspace.camera_bookmarks.remove(name) {
+            return Ok(false);
+        }
+        self.journal(JournalOp::WorkspaceSet { workspace: Box::new(workspace.clone()) });
+        
+        self.event_sender.send(WorkspaceEvent::Modified {
+            workspace_id: workspace_id.to_string(),
+        }).ok();
+        
+        Ok(true)
+    }
+    
+    /// Camera bookmark of the active workspace by name
+    pub fn camera_bookmark(&self, name: &str) -> Option<CameraBookmark> {
+        let active = self.active_workspace.read().unwrap().clone()?;
+        self.workspaces.read().unwrap().get(&active)?.camera_bookmarks.get(name).cloned()
+    }
+    
+    /// Step through the camera bookmarks of the active workspace, wrapping around;
+    /// returns the bookmark to fly to
+    pub fn cycle_camera_bookmark(&self, offset: isize) -> Option<CameraBookmark> {
+        let active = self.active_workspace.read().unwrap().clone()?;
+        let mut workspaces = self.workspaces.write().unwrap();
+        workspaces.get_mut(&active)?.camera_bookmarks.cycle(offset).cloned()
+    }
+    
+    /// Register a warmer run when a workspace is preloaded
+    pub fn add_preload_warmer(&self, warmer: Arc<dyn PreloadWarmer>) {
+        self.preloader.add_warmer(warmer);
+    }
+    
+    /// Preload the workspaces predicted to be switched to next
+    ///
+    /// Call while the desktop is idle, e.g. with the AI service's
+    /// `predict_next_workspaces` for the active workspace. Returns the
+    /// number of workspaces newly preloaded.
+    pub fn preload_predicted(&self, candidates: &[PreloadCandidate]) -> usize {
+        let active = self.active_workspace.read().unwrap().clone();
+        self.preloader.preload(candidates, &self.workspaces.read().unwrap(), active.as_deref())
+    }
+    
+    /// Preload counters, including the hit rate of switches
+    pub fn preload_stats(&self) -> PreloadStats {
+        self.preloader.stats()
+    }
+    
+    /// Get the identity transition started by the last workspace switch
+    pub fn identity_transition(&self) -> Option<IdentityTransition> {
+        self.identity_transition.read().unwrap().clone()
+    }
+    
+    /// Current identity frame to render, following any running transition
+    ///
+    /// A transition is dropped once it has completed, after which the active
+    /// workspace's identity is rendered as is.
+    pub fn current_identity_frame(&self) -> Option<TransitionFrame> {
+        {
+            let mut transition = self.identity_transition.write().unwrap();
+            match transition.as_ref() {
+                Some(running) if !running.is_finished() => return Some(running.sample()),
+                Some(_) => *transition = None,
+                None => {}
+            }
+        }
+        
+        self.get_active_workspace().map(|workspace| {
+            IdentityTransition::new(None, workspace.identity).sample_at(std::time::Duration::MAX)
+        })
+    }
+    
+    /// Add a node to the active workspace, returning the workspace ID
+    pub fn add_node_to_active(&self, node_id: SceneId) -> Option<String> {
+        let workspace_id = self.active_workspace.read().unwrap().clone()?;
+        let mut workspaces = self.workspaces.write().unwrap();
+        let workspace = workspaces.get_mut(&workspace_id)?;
+        workspace.add_node(node_id);
+        self.journal(JournalOp::WorkspaceSet { workspace: Box::new(workspace.clone()) });
+        
+        self.event_sender.send(WorkspaceEvent::NodeAdded {
+            workspace_id: workspace_id.clone(),
+            node_id,
+        }).ok();
+        
+        Some(workspace_id)
+    }
+    
+    /// Add a newly created node to the workspace its routing rules pick,
+    /// or to the active workspace when none does
+    ///
+    /// Call when a node is created; returns the workspace it was added to.
+    pub fn add_new_node(&self, node: &SceneNode) -> Option<String> {
+        let origin = NodeOrigin::of(node);
+        let routed = {
+            let rules = self.rules.read().unwrap();
+            let workspaces = self.workspaces.read().unwrap();
+            rules.matching_routes(&origin).into_iter().find_map(|rule| {
+                let workspace_id = Self::resolve_workspace(&workspaces, &rule.workspace);
+                if workspace_id.is_none() {
+                    log::debug!("Skipping routing rule {}: no workspace {}", rule.name, rule.workspace);
+                }
+                workspace_id.map(|id| (id, rule.name.clone()))
+            })
+        };
+        let Some((workspace_id, rule)) = routed else {
+            return self.add_node_to_active(node.id);
+        };
+        
+        let mut workspaces = self.workspaces.write().unwrap();
+        let workspace = workspaces.get_mut(&workspace_id)?;
+        workspace.add_node(node.id);
+        self.journal(JournalOp::WorkspaceSet { workspace: Box::new(workspace.clone()) });
+        log::debug!("Routed node {} to workspace {} by rule {}", node.id, workspace.name, rule);
+        
+        self.event_sender.send(WorkspaceEvent::NodeAdded {
+            workspace_id: workspace_id.clone(),
+            node_id: node.id,
+        }).ok();
+        
+        Some(workspace_id)
+    }
+    
+    /// ID of a workspace named by ID or, failing that, by name
+    fn resolve_workspace(workspaces: &HashMap<String, Workspace>, id_or_name: &str) -> Option<String> {
+        if workspaces.contains_key(id_or_name) {
+            return Some(id_or_name.to_string());
+        }
+        workspaces.values().find(|w| w.name == id_or_name).map(|w| w.id.clone())
+    }
+    
+    /// Rules routing new nodes to workspaces
+    pub fn routing_rules(&self) -> Vec<RoutingRule> {
+        self.rules.read().unwrap().routing_rules().to_vec()
+    }
+    
+    /// Add a routing rule, replacing one with the same name; saved with the workspaces
+    pub fn add_routing_rule(&self, rule: RoutingRule) {
+        self.rules.write().unwrap().add_routing_rule(rule);
+    }
+    
+    /// Remove a routing rule by name, returning whether it existed
+    pub fn remove_routing_rule(&self, name: &str) -> bool {
+        self.rules.write().unwrap().remove_routing_rule(name)
+    }
+    
+    /// Remove a node from every workspace holding it
+    pub fn remove_node(&self, node_id: SceneId) {
+        let mut workspaces = self.workspaces.write().unwrap();
+        for workspace in workspaces.values_mut().filter(|w| w.nodes.contains(&node_id)) {
+            workspace.remove_node(node_id);
+            self.journal(JournalOp::WorkspaceSet { workspace: Box::new(workspace.clone()) });
+            
+            self.event_sender.send(WorkspaceEvent::NodeRemoved {
+                workspace_id: workspace.id.clone(),
+                node_id,
+            }).ok();
+        }
+    }
+    
+    /// Move a node into a workspace, out of every other workspace holding it
+    ///
+    /// Used when a node is dragged onto an output showing another
+    /// workspace; returns the workspaces it was taken out of.
+    pub fn move_node(&self, node_id: SceneId, workspace_id: &str) -> Result<Vec<String>, WorkspaceError> {
+        let mut workspaces = self.workspaces.write().unwrap();
+        if !workspaces.contains_key(workspace_id) {
+            return Err(WorkspaceError::NotFound(workspace_id.to_string()));
+        }
+        
+        let mut removed_from = Vec::new();
+        for workspace in workspaces.values_mut().filter(|w| w.id != workspace_id && w.nodes.contains(&node_id)) {
+            workspace.remove_node(node_id);
+            self.journal(JournalOp::WorkspaceSet { workspace: Box::new(workspace.clone()) });
+            removed_from.push(workspace.id.clone());
+            
+            self.event_sender.send(WorkspaceEvent::NodeRemoved {
+                workspace_id: workspace.id.clone(),
+                node_id,
+            }).ok();
+        }
+        
+        let workspace = workspaces.get_mut(workspace_id).unwrap();
+        if !workspace.nodes.contains(&node_id) {
+            workspace.add_node(node_id);
+            self.journal(JournalOp::WorkspaceSet { workspace: Box::new(workspace.clone()) });
+            
+            self.event_sender.send(WorkspaceEvent::NodeAdded {
+                workspace_id: workspace_id.to_string(),
+                node_id,
+            }).ok();
+        }
+        
+        Ok(removed_from)
+    }
+    
+    /// Take over the workspaces of a recovered session
+    ///
+    /// Returns the scene and camera for the caller to install. A session
+    /// without workspaces leaves the loaded ones in place.
+    pub fn restore_session(&self, session: SessionState) -> (Scene, Option<CameraState>) {
+        if !session.workspaces.is_empty() {
+            let mut workspaces = self.workspaces.write().unwrap();
+            *workspaces = session.workspaces.into_iter()
+                .map(|workspace| (workspace.id.clone(), workspace))
+                .collect();
+            
+            let active = session.active_workspace
+                .filter(|id| workspaces.contains_key(id))
+                .or_else(|| workspaces.keys().next().cloned());
+            *self.active_workspace.write().unwrap() = active;
+        }
+        
+        (session.scene, session.camera)
+    }
+    
+    /// Write a session checkpoint if the auto-save interval has passed
+    ///
+    /// Returns whether a checkpoint was written.
+    pub fn auto_save_session(&self, scene: &Scene, camera: Option<CameraState>) -> Result<bool, WorkspaceError> {
+        if self.session.is_none() || self.last_checkpoint.read().unwrap().elapsed() < self.auto_save_interval {
+            return Ok(false);
+        }
+        self.checkpoint_session(scene, camera)?;
+        Ok(true)
+    }
+    
+    /// Write a session checkpoint now
+    pub fn checkpoint_session(&self, scene: &Scene, camera: Option<CameraState>) -> Result<(), WorkspaceError> {
+        let Some(journal) = &self.session else {
+            return Ok(());
+        };
+        
+        let workspaces = session::workspace_list(&self.workspaces.read().unwrap());
+        let active = self.active_workspace.read().unwrap().clone();
+        // Count the attempt so a failing disk is not retried every frame
+        *self.last_checkpoint.write().unwrap() = Instant::now();
+        journal.checkpoint(scene, &workspaces, active.as_deref(), camera)
+    }
+    
+    /// Write a final checkpoint and mark the session as closed cleanly
+    pub fn close_session(&self, scene: &Scene, camera: Option<CameraState>) -> Result<(), WorkspaceError> {
+        let Some(journal) = &self.session else {
+            return Ok(());
+        };
+        self.checkpoint_session(scene, camera)?;
+        journal.close()
+    }
+    
+    /// Merge a workspace received from another device
+    ///
+    /// Takes the remote version only if the local one has not changed since
+    /// the last sync; when both changed, the conflict is reported to the
+    /// conflict center, or returned as an error without one.
+    pub fn merge_remote_workspace(&self, remote: Workspace, origin: &str) -> Result<MergeOutcome, WorkspaceError> {
+        let Some(sync) = &self.sync else {
+            return match self.get_workspace(&remote.id) {
+                Some(local) => Err(WorkspaceError::SyncConflict(local.name)),
+                None => {
+                    let workspace_id = remote.id.clone();
+                    self.journal(JournalOp::WorkspaceSet { workspace: Box::new(remote.clone()) });
+                    self.workspaces.write().unwrap().insert(workspace_id.clone(), remote);
+                    self.event_sender.send(WorkspaceEvent::Created { workspace_id }).ok();
+                    Ok(MergeOutcome::Added)
+                }
+            };
+        };
+        sync.merge(remote, origin)
+    }
+    
+    /// Record that the current version of a workspace is on the other devices
+    pub fn mark_synced(&self, workspace_id: &str) -> Result<(), WorkspaceError> {
+        let workspace = self.get_workspace(workspace_id)
+            .ok_or_else(|| WorkspaceError::NotFound(workspace_id.to_string()))?;
+        if let Some(sync) = &self.sync {
+            sync.mark_synced(&workspace)?;
+        }
+        Ok(())
+    }
+    
+    /// Conflict center workspace conflicts are reported to
+    pub fn conflict_center(&self) -> Option<&Arc<ConflictCenter>> {
+        self.sync.as_ref().map(|sync| sync.center())
+    }
+    
+    fn journal(&self, op: JournalOp) {
+        if let Some(journal) = &self.session {
+            if let Err(e) = journal.record(&op) {
+                log::warn!("Failed to journal workspace change: {}", e);
+            }
+        }
+    }
+    
     /// Save all workspaces
     pub async fn save_workspaces(&self) -> Result<(), WorkspaceError> {
         let workspaces: Vec<Workspace> = self.workspaces.read().unwrap()
@@ -210,9 +773,138 @@ impl WorkspaceManager {
             .collect();
         
         self.persistence.save_workspaces(&workspaces).await?;
+        let routing_rules = self.rules.read().unwrap().routing_rules().to_vec();
+        self.persistence.save_routing_rules(&routing_rules).await?;
+        Ok(())
+    }
+    
+    /// Write a workspace with its nodes, edges and layout to an archive file
+    ///
+    /// `node_data` holds export data of the node layer by node ID; nodes
+    /// without an entry are archived with their scene data only.
+    pub async fn export_workspace(
+        &self,
+        workspace_id: &str,
+        scene: &Scene,
+        node_data: &HashMap<SceneId, serde_json::Value>,
+        path: &Path,
+    ) -> Result<(), WorkspaceError> {
+        let workspace = self.get_workspace(workspace_id)
+            .ok_or_else(|| WorkspaceError::NotFound(workspace_id.to_string()))?;
+        let archive = WorkspaceArchive::build(&workspace, scene, node_data);
+        tokio::fs::write(path, archive.encode()?).await?;
         Ok(())
     }
     
+    /// Import an archive file as a new workspace
+    ///
+    /// Archived nodes and edges are added to `scene`, with IDs already in use
+    /// replaced; the result maps archived IDs to the ones used.
+    pub async fn import_workspace(&self, path: &Path, scene: &mut Scene) -> Result<ImportedWorkspace, WorkspaceError> {
+        let text = tokio::fs::read_to_string(path).await?;
+        let imported = WorkspaceArchive::decode(&text)?.import_into(scene);
+        let workspace_id = imported.workspace.id.clone();
+        
+        self.workspaces.write().unwrap()
+            .insert(workspace_id.clone(), imported.workspace.clone());
+        
+        self.event_sender.send(WorkspaceEvent::Created { workspace_id }).ok();
+        
+        Ok(imported)
+    }
+    
+    /// Publish a read-only snapshot of a workspace for other users
+    pub async fn publish_workspace(
+        &self,
+        workspace_id: &str,
+        scene: &Scene,
+        node_data: &HashMap<SceneId, serde_json::Value>,
+        options: PublishOptions,
+    ) -> Result<Publication, WorkspaceError> {
+        let workspace = self.get_workspace(workspace_id)
+            .ok_or_else(|| WorkspaceError::NotFound(workspace_id.to_string()))?;
+        let publication = Publication::new(&workspace, options);
+        self.publications.write().unwrap().insert(publication.id.clone(), publication.clone());
+        self.republish(&publication.id, scene, node_data).await
+    }
+    
+    /// Write a new revision of a publication from the current workspace
+    pub async fn republish(
+        &self,
+        publication_id: &str,
+        scene: &Scene,
+        node_data: &HashMap<SceneId, serde_json::Value>,
+    ) -> Result<Publication, WorkspaceError> {
+        let publication = self.publications.read().unwrap().get(publication_id).cloned()
+            .ok_or_else(|| WorkspaceError::NotFound(publication_id.to_string()))?;
+        let workspace = self.get_workspace(&publication.workspace_id)
+            .ok_or_else(|| WorkspaceError::NotFound(publication.workspace_id.clone()))?;
+        
+        let snapshot = publication.snapshot(&workspace, scene, node_data);
+        self.publish_channel.write(&snapshot).await?;
+        
+        let mut publications = self.publications.write().unwrap();
+        let publication = publications.get_mut(publication_id)
+            .ok_or_else(|| WorkspaceError::NotFound(publication_id.to_string()))?;
+        publication.revision = snapshot.revision;
+        publication.last_published = Some(snapshot.published_at);
+        
+        self.event_sender.send(WorkspaceEvent::Published {
+            workspace_id: publication.workspace_id.clone(),
+            publication_id: publication.id.clone(),
+            revision: publication.revision,
+        }).ok();
+        
+        Ok(publication.clone())
+    }
+    
+    /// Republish every publication whose interval has passed; meant to be
+    /// called periodically, e.g. alongside auto-save
+    ///
+    /// Returns the IDs of the publications written. A failing publication is
+    /// logged and retried on the next call.
+    pub async fn republish_due(
+        &self,
+        scene: &Scene,
+        node_data: &HashMap<SceneId, serde_json::Value>,
+    ) -> Vec<String> {
+        let now = Utc::now();
+        let due: Vec<String> = {
+            let workspaces = self.workspaces.read().unwrap();
+            self.publications.read().unwrap()
+                .values()
+                .filter(|publication| publication.is_due(now) && workspaces.contains_key(&publication.workspace_id))
+                .map(|publication| publication.id.clone())
+                .collect()
+        };
+        
+        let mut written = Vec::with_capacity(due.len());
+        for publication_id in due {
+            match self.republish(&publication_id, scene, node_data).await {
+                Ok(_) => written.push(publication_id),
+                Err(e) => log::warn!("Failed to republish {}: {}", publication_id, e),
+            }
+        }
+        written
+    }
+    
+    /// Stop publishing and take the snapshot off the channel
+    pub async fn unpublish(&self, publication_id: &str) -> Result<(), WorkspaceError> {
+        self.publications.write().unwrap().remove(publication_id)
+            .ok_or_else(|| WorkspaceError::NotFound(publication_id.to_string()))?;
+        self.publish_channel.remove(publication_id).await
+    }
+    
+    /// Publications of this manager
+    pub fn publications(&self) -> Vec<Publication> {
+        self.publications.read().unwrap().values().cloned().collect()
+    }
+    
+    /// Channel publications are written to, also used to open them
+    pub fn publish_channel(&self) -> &PublishChannel {
+        &self.publish_channel
+    }
+    
     /// Subscribe to workspace events
     pub fn subscribe(&self) -> broadcast::Receiver<WorkspaceEvent> {
         self.event_sender.subscribe()
@@ -223,7 +915,7 @@ impl WorkspaceManager {
         let mut workspaces = self.workspaces.write().unwrap();
         
         if let Some(workspace) = workspaces.get_mut(workspace_id) {
-            self.rules.apply_to_workspace(workspace);
+            self.rules.read().unwrap().apply_to_workspace(workspace);
             Ok(())
         } else {
             Err(WorkspaceError::NotFound(workspace_id.to_string()))
@@ -295,6 +987,40 @@ impl WorkspaceManager {
     }
 }
 
+impl WorkspaceService for WorkspaceManager {
+    fn create_workspace(&self, name: &str, description: &str) -> Result<String, WorkspaceError> {
+        WorkspaceManager::create_workspace(self, name, description)
+    }
+    
+    fn switch_workspace(&self, workspace_id: &str) -> Result<(), WorkspaceError> {
+        WorkspaceManager::switch_workspace(self, workspace_id)
+    }
+    
+    fn get_active_workspace(&self) -> Option<Workspace> {
+        WorkspaceManager::get_active_workspace(self)
+    }
+    
+    fn get_workspace(&self, workspace_id: &str) -> Option<Workspace> {
+        WorkspaceManager::get_workspace(self, workspace_id)
+    }
+    
+    fn list_workspaces(&self) -> Vec<WorkspaceInfo> {
+        WorkspaceManager::list_workspaces(self)
+    }
+    
+    fn delete_workspace(&self, workspace_id: &str) -> Result<(), WorkspaceError> {
+        WorkspaceManager::delete_workspace(self, workspace_id)
+    }
+    
+    fn set_workspace_identity(&self, workspace_id: &str, identity: WorkspaceIdentity) -> Result<(), WorkspaceError> {
+        WorkspaceManager::set_workspace_identity(self, workspace_id, identity)
+    }
+    
+    fn subscribe(&self) -> broadcast::Receiver<WorkspaceEvent> {
+        WorkspaceManager::subscribe(self)
+    }
+}
+
 /// Individual workspace
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Workspace {
@@ -310,6 +1036,12 @@ pub struct Workspace {
     pub layout: WorkspaceLayout,
     /// Workspace-specific settings
     pub settings: WorkspaceSettings,
+    /// Visual identity (accent tint, emblem, switch transition)
+    #[serde(default)]
+    pub identity: WorkspaceIdentity,
+    /// Saved camera views
+    #[serde(default)]
+    pub camera_bookmarks: CameraBookmarks,
     /// Creation timestamp
     pub created_at: DateTime<Utc>,
     /// Last accessed timestamp
@@ -321,13 +1053,18 @@ pub struct Workspace {
 impl Workspace {
     /// Create a new workspace
     pub fn new(name: &str, description: &str) -> Self {
+        let id = uuid::Uuid::new_v4().to_string();
+        let identity = WorkspaceIdentity::from_seed(&id);
+        
         Self {
-            id: uuid::Uuid::new_v4().to_string(),
+            id,
             name: name.to_string(),
             description: description.to_string(),
             nodes: Vec::new(),
             layout: WorkspaceLayout::default(),
             settings: WorkspaceSettings::default(),
+            identity,
+            camera_bookmarks: CameraBookmarks::default(),
             created_at: Utc::now(),
             last_accessed: Utc::now(),
             metadata: HashMap::new(),
@@ -369,6 +1106,9 @@ pub struct WorkspaceSettings {
     pub auto_arrange: bool,
     /// Default node spacing
     pub node_spacing: f32,
+    /// Edges drawn while this workspace is active
+    #[serde(default)]
+    pub edge_filter: EdgeFilter,
 }
 
 impl Default for WorkspaceSettings {
@@ -380,6 +1120,7 @@ impl Default for WorkspaceSettings {
             auto_save: true,
             auto_arrange: false,
             node_spacing: 100.0,
+            edge_filter: EdgeFilter::default(),
         }
     }
 }
@@ -405,6 +1146,7 @@ pub enum WorkspaceEvent {
     Modified { workspace_id: String },
     NodeAdded { workspace_id: String, node_id: SceneId },
     NodeRemoved { workspace_id: String, node_id: SceneId },
+    Published { workspace_id: String, publication_id: String, revision: u64 },
 }
 
 /// Workspace errors
@@ -422,11 +1164,44 @@ pub enum WorkspaceError {
     #[error("Invalid template")]
     InvalidTemplate,
     
+    #[error("Sync conflict: {0}")]
+    SyncConflict(String),
+    
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
     
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
+    
+    #[error("Unsupported archive version: {0}")]
+    UnsupportedArchive(u32),
+    
+    #[error("Session journal is corrupt: {0}")]
+    SessionCorrupt(String),
+}
+
+impl Classify for WorkspaceError {
+    fn classify(&self) -> Problem {
+        let problem = match self {
+            WorkspaceError::NotFound(id) => Problem::new("workspaces.not_found", Category::Graph, Severity::Warning, format!("Workspace {} does not exist", id)),
+            WorkspaceError::CannotDeleteLast => Problem::new("workspaces.last_workspace", Category::Graph, Severity::Info, "The last workspace cannot be deleted"),
+            WorkspaceError::AccessDenied => Problem::new("workspaces.access_denied", Category::Permission, Severity::Error, "You do not have access to this workspace"),
+            WorkspaceError::InvalidTemplate => Problem::new("workspaces.invalid_template", Category::Configuration, Severity::Warning, "Workspace template is invalid"),
+            WorkspaceError::SyncConflict(_) => Problem::new("workspaces.sync_conflict", Category::Sync, Severity::Error, "Changes from another device conflict with yours")
+                .with_retry(Retryability::NeedsUser)
+                .with_recovery(RecoveryAction::new(
+                    "resolve",
+                    "Resolve",
+                    RecoveryKind::Custom { handler: "workspaces.resolve_conflict".to_string() },
+                )),
+            WorkspaceError::IoError(_) => Problem::new("workspaces.save_failed", Category::Io, Severity::Error, "Workspaces could not be saved or loaded")
+                .transient(),
+            WorkspaceError::SerializationError(_) => Problem::new("workspaces.data_corrupt", Category::Storage, Severity::Error, "Workspace data is unreadable"),
+            WorkspaceError::UnsupportedArchive(_) => Problem::new("workspaces.archive_unsupported", Category::Storage, Severity::Warning, "This workspace was exported by a newer version"),
+            WorkspaceError::SessionCorrupt(_) => Problem::new("workspaces.session_corrupt", Category::Storage, Severity::Warning, "The previous session could not be restored"),
+        };
+        problem.in_subsystem("workspaces").with_detail(self)
+    }
 }
 
 // Re-export uuid for workspace IDs
@@ -465,5 +1240,270 @@ mod tests {
             manager.get_active_workspace().unwrap().id,
             workspace2
         );
+
+        // Adjacent switching wraps around in both directions
+        assert_eq!(manager.switch_adjacent_workspace(1), Some(workspace1.clone()));
+        assert_eq!(manager.switch_adjacent_workspace(-1), Some(workspace2.clone()));
+        assert_eq!(manager.switch_adjacent_workspace(2), Some(workspace2));
+    }
+
+    #[test]
+    fn test_identity_transition_is_cleared_when_complete() {
+        let manager = WorkspaceManager::new();
+        let work = manager.create_workspace("Work", "Work workspace").unwrap();
+        let personal = manager.create_workspace("Personal", "Personal workspace").unwrap();
+        let mut identity = WorkspaceIdentity::default().with_accent([1.0, 0.0, 0.0, 1.0]);
+        identity.transition.style = TransitionStyle::None;
+        manager.set_workspace_identity(&personal, identity).unwrap();
+
+        manager.switch_workspace(&work).unwrap();
+        manager.switch_workspace(&personal).unwrap();
+        assert!(manager.identity_transition().is_some());
+
+        // An instant switch has completed by the first frame
+        let frame = manager.current_identity_frame().unwrap();
+        assert_eq!(frame.progress, 1.0);
+        assert_eq!(frame.accent_color, [1.0, 0.0, 0.0, 1.0]);
+        assert!(manager.identity_transition().is_none());
+        assert_eq!(manager.current_identity_frame(), Some(frame));
+    }
+
+    #[test]
+    fn test_new_nodes_are_routed_by_origin() {
+        use horizonos_graph_engine::{NodeMetadata, NodeType, Position, UrlType};
+        
+        let node = |id, node_type, app: Option<&str>| {
+            let mut metadata = NodeMetadata::default();
+            if let Some(app) = app {
+                metadata.properties.insert(SOURCE_APP_PROPERTY.to_string(), app.to_string());
+            }
+            SceneNode {
+                id,
+                position: Position::origin(),
+                velocity: nalgebra::Vector3::zeros(),
+                radius: 1.0,
+                color: [1.0; 4],
+                node_type,
+                metadata,
+                visible: true,
+                selected: false,
+            }
+        };
+        let url = |url: &str| NodeType::URL { url: url.to_string(), title: None, url_type: UrlType::Website };
+        
+        let manager = WorkspaceManager::new();
+        let home = manager.create_workspace("Home", "").unwrap();
+        let work = manager.create_workspace("Work", "").unwrap();
+        let research = manager.create_workspace("Research", "").unwrap();
+        manager.switch_workspace(&home).unwrap();
+        manager.add_routing_rule(RoutingRule::new("Chat", RouteCondition::CreatedByApp("slack".to_string()), "Work"));
+        manager.add_routing_rule(
+            RoutingRule::new("Papers", RouteCondition::UrlDomain("arxiv.org".to_string()), research.clone()).with_priority(5),
+        );
+        manager.add_routing_rule(
+            RoutingRule::new("Gone", RouteCondition::NodeType("url".to_string()), "Archive").with_priority(10),
+        );
+        
+        assert_eq!(manager.add_new_node(&node(1, url("https://arxiv.org/abs/1"), Some("slack"))), Some(research.clone()));
+        assert_eq!(manager.add_new_node(&node(2, url("https://example.org"), Some("Slack"))), Some(work.clone()));
+        assert_eq!(manager.add_new_node(&node(3, url("https://example.org"), None)), Some(home.clone()));
+        assert_eq!(manager.get_workspace(&research).unwrap().nodes, vec![1]);
+        assert_eq!(manager.get_workspace(&work).unwrap().nodes, vec![2]);
+        assert_eq!(manager.get_workspace(&home).unwrap().nodes, vec![3]);
+        assert_eq!(manager.routing_rules().len(), 3);
+    }
+    
+    #[test]
+    fn test_move_node_between_workspaces() {
+        let manager = WorkspaceManager::new();
+        let work = manager.create_workspace("Work", "").unwrap();
+        let personal = manager.create_workspace("Personal", "").unwrap();
+        
+        manager.switch_workspace(&work).unwrap();
+        manager.add_node_to_active(7);
+        
+        assert_eq!(manager.move_node(7, &personal).unwrap(), vec![work.clone()]);
+        assert!(manager.get_workspace(&work).unwrap().nodes.is_empty());
+        assert_eq!(manager.get_workspace(&personal).unwrap().nodes, vec![7]);
+        
+        // Moving to where it already is changes nothing
+        assert!(manager.move_node(7, &personal).unwrap().is_empty());
+        assert!(manager.move_node(7, "missing").is_err());
+    }
+    
+    #[test]
+    fn test_camera_bookmarks_are_kept_per_workspace() {
+        let manager = WorkspaceManager::new();
+        let work = manager.create_workspace("Work", "").unwrap();
+        let personal = manager.create_workspace("Personal", "").unwrap();
+        let camera = horizonos_graph_engine::Camera::new().state();
+        
+        manager.switch_workspace(&work).unwrap();
+        assert_eq!(manager.save_camera_bookmark(None, camera).unwrap(), "View 1");
+        manager.save_camera_bookmark(Some("Overview"), camera).unwrap();
+        
+        manager.switch_workspace(&personal).unwrap();
+        assert!(manager.cycle_camera_bookmark(1).is_none());
+        assert!(manager.camera_bookmark("Overview").is_none());
+        
+        manager.switch_workspace(&work).unwrap();
+        assert_eq!(manager.cycle_camera_bookmark(1).unwrap().name, "View 1");
+        assert!(manager.remove_camera_bookmark(&work, "Overview").unwrap());
+        assert_eq!(manager.get_workspace(&work).unwrap().camera_bookmarks.len(), 1);
+    }
+    
+    #[test]
+    fn test_collapsed_groups_are_kept_per_workspace() {
+        let manager = WorkspaceManager::new();
+        let work = manager.create_workspace("Work", "").unwrap();
+        let personal = manager.create_workspace("Personal", "").unwrap();
+        
+        manager.switch_workspace(&work).unwrap();
+        manager.set_group_collapsed("cluster-a", true).unwrap();
+        manager.set_group_collapsed("cluster-a", true).unwrap();
+        manager.set_group_collapsed("cluster-b", true).unwrap();
+        manager.set_group_collapsed("cluster-b", false).unwrap();
+        
+        manager.switch_workspace(&personal).unwrap();
+        assert!(manager.get_active_workspace().unwrap().layout.collapsed_groups.is_empty());
+        assert_eq!(manager.get_workspace(&work).unwrap().layout.collapsed_groups, vec!["cluster-a"]);
+    }
+    
+    #[test]
+    fn test_in_memory_service_matches_manager() {
+        fn exercise(service: &dyn WorkspaceService) {
+            let work = service.create_workspace("Work", "Work workspace").unwrap();
+            let personal = service.create_workspace("Personal", "Personal workspace").unwrap();
+            
+            service.switch_workspace(&work).unwrap();
+            assert_eq!(service.get_active_workspace().unwrap().id, work);
+            
+            service.delete_workspace(&work).unwrap();
+            assert!(service.get_workspace(&work).is_none());
+            assert!(service.list_workspaces().iter().any(|w| w.id == personal));
+            assert!(matches!(service.switch_workspace(&work), Err(WorkspaceError::NotFound(_))));
+        }
+        
+        exercise(&WorkspaceManager::new());
+        exercise(&testing::InMemoryWorkspaceService::new());
+    }    
+    #[tokio::test]
+    async fn test_archive_round_trip_remaps_colliding_ids() {
+        use horizonos_graph_engine::scene::{EdgeType, NodeMetadata, NodeType, SceneEdge, SceneNode};
+        
+        fn node(title: &str) -> SceneNode {
+            SceneNode {
+                id: 0,
+                position: nalgebra::Point3::new(1.0, 2.0, 3.0),
+                velocity: nalgebra::Vector3::zeros(),
+                radius: 1.0,
+                color: [1.0; 4],
+                node_type: NodeType::Concept { title: title.to_string(), content: String::new() },
+                metadata: NodeMetadata::default(),
+                visible: true,
+                selected: false,
+            }
+        }
+        
+        let mut scene = Scene::new();
+        let a = scene.add_node(node("a"));
+        let b = scene.add_node(node("b"));
+        scene.add_edge(SceneEdge {
+            id: 0,
+            source: a,
+            target: b,
+            edge_type: EdgeType::RelatedTo { similarity: 0.5 },
+            weight: 1.0,
+            color: [1.0; 4],
+            visible: true,
+            animated: false,
+        });
+        
+        let manager = WorkspaceManager::new();
+        let workspace_id = manager.create_workspace("Shared", "Curated").unwrap();
+        {
+            let mut workspaces = manager.workspaces.write().unwrap();
+            let workspace = workspaces.get_mut(&workspace_id).unwrap();
+            workspace.add_node(a);
+            workspace.add_node(b);
+            workspace.layout.node_positions.insert(b, nalgebra::Point3::new(5.0, 0.0, 0.0));
+        }
+        
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shared.json");
+        let node_data = HashMap::from([(a, serde_json::json!({ "display_name": "a" }))]);
+        manager.export_workspace(&workspace_id, &scene, &node_data, &path).await.unwrap();
+        
+        // Importing into the same scene collides with every archived ID
+        let imported = manager.import_workspace(&path, &mut scene).await.unwrap();
+        let new_a = imported.node_ids[&a];
+        let new_b = imported.node_ids[&b];
+        assert!(new_a != a && new_b != b && new_a != new_b);
+        assert_ne!(imported.workspace.id, workspace_id);
+        assert_eq!(imported.workspace.nodes, vec![new_a, new_b]);
+        assert!(imported.workspace.layout.node_positions.contains_key(&new_b));
+        assert_eq!(imported.nodes[0].export_data, node_data.get(&a).cloned());
+        
+        let edge = scene.get_edge(imported.edge_ids.values().copied().next().unwrap()).unwrap();
+        assert_eq!((edge.source, edge.target), (new_a, new_b));
+        assert_eq!(scene.get_all_nodes().len(), 4);
+        assert!(manager.get_workspace(&imported.workspace.id).is_some());
+    }    
+    #[tokio::test]
+    async fn test_publish_redacts_private_nodes() {
+        use horizonos_graph_engine::scene::{NodeMetadata, NodeType, SceneNode};
+        
+        let mut scene = Scene::new();
+        let mut ids = Vec::new();
+        for (title, private) in [("roadmap", false), ("salaries", true)] {
+            let mut metadata = NodeMetadata::default();
+            if private {
+                metadata.tags.push("private".to_string());
+            }
+            ids.push(scene.add_node(SceneNode {
+                id: 0,
+                position: nalgebra::Point3::origin(),
+                velocity: nalgebra::Vector3::zeros(),
+                radius: 1.0,
+                color: [1.0; 4],
+                node_type: NodeType::Concept { title: title.to_string(), content: String::new() },
+                metadata,
+                visible: true,
+                selected: false,
+            }));
+        }
+        
+        let dir = tempfile::tempdir().unwrap();
+        let manager = WorkspaceManager::new()
+            .with_publish_channel(PublishChannel::with_dir(dir.path().to_path_buf()));
+        let workspace_id = manager.create_workspace("Team", "Dashboard").unwrap();
+        for id in &ids {
+            manager.workspaces.write().unwrap().get_mut(&workspace_id).unwrap().add_node(*id);
+        }
+        
+        let options = PublishOptions {
+            interval: Some(std::time::Duration::from_secs(3600)),
+            ..PublishOptions::default()
+        };
+        let publication = manager.publish_workspace(&workspace_id, &scene, &HashMap::new(), options).await.unwrap();
+        assert_eq!(publication.revision, 1);
+        
+        let snapshot = manager.publish_channel().open(&publication.id).await.unwrap();
+        assert_eq!(snapshot.redacted, 1);
+        let (view, workspace) = snapshot.to_scene();
+        assert_ne!(workspace.id, workspace_id);
+        let titles: Vec<String> = workspace.nodes.iter()
+            .map(|id| match &view.get_node(*id).unwrap().node_type {
+                NodeType::Concept { title, .. } => title.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(titles, vec!["roadmap".to_string(), "Private".to_string()]);
+        
+        // Not due again until the interval passes
+        assert!(manager.republish_due(&scene, &HashMap::new()).await.is_empty());
+        
+        manager.unpublish(&publication.id).await.unwrap();
+        assert!(manager.publish_channel().list().await.unwrap().is_empty());
     }
 }
\ No newline at end of file
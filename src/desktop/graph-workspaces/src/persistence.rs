@@ -1,14 +1,216 @@
 //! Workspace persistence to disk
+//!
+//! Each workspace is stored as a full snapshot (`<id>.json`) plus an
+//! append-only log of deltas (`<id>.delta.jsonl`). Saving a workspace whose
+//! last saved state is known only appends what changed since then: nodes
+//! added or removed, positions set or cleared, and the remaining
+//! workspace properties when any of them changed. Once the log grows past
+//! a number of deltas or relative to the snapshot size, it is compacted
+//! into a new snapshot, so saving stays cheap even for large graphs and
+//! auto-save can run every few seconds.
+//!
+//! Deltas record absolute values, so replaying a log onto a snapshot that
+//! already contains it yields the same workspace. A crash between writing
+//! a compacted snapshot and truncating the log is therefore harmless, and a
+//! torn last line from a crash mid-append is skipped on load.
+//!
+//! The rules routing new nodes to workspaces are kept next to them in
+//! `routing.json`.
 
+use crate::rules::RoutingRule;
 use crate::{Workspace, WorkspaceError};
+use chrono::{DateTime, Utc};
+use horizonos_graph_engine::scene::SceneId;
+use nalgebra::Point3;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use tokio::fs;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::Mutex;
+
+/// When the delta log is folded into a new snapshot
+#[derive(Debug, Clone)]
+pub struct CompactionPolicy {
+    /// Compact after this many deltas
+    pub max_deltas: usize,
+    /// Compact once the log is larger than this fraction of the snapshot
+    pub max_log_ratio: f64,
+}
+
+impl Default for CompactionPolicy {
+    fn default() -> Self {
+        Self {
+            max_deltas: 64,
+            max_log_ratio: 0.5,
+        }
+    }
+}
+
+/// Write counters since the handler was created
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PersistenceStats {
+    /// Full snapshots written, including compactions
+    pub snapshots_written: u64,
+    /// Deltas appended to logs
+    pub deltas_written: u64,
+    /// Saves skipped because nothing changed
+    pub unchanged_skipped: u64,
+    /// Bytes written for workspaces
+    pub bytes_written: u64,
+}
+
+/// Changes to a workspace since the previous save
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceDelta {
+    /// When the delta was written
+    pub timestamp: DateTime<Utc>,
+    /// Nodes added to the workspace
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_nodes: Vec<SceneId>,
+    /// Nodes removed from the workspace
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_nodes: Vec<SceneId>,
+    /// Manual positions set or changed
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub moved_nodes: HashMap<SceneId, Point3<f32>>,
+    /// Manual positions cleared
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unpositioned_nodes: Vec<SceneId>,
+    /// All other workspace fields, present when any of them changed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<Value>,
+}
+
+impl WorkspaceDelta {
+    /// Whether the delta changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.moved_nodes.is_empty()
+            && self.unpositioned_nodes.is_empty()
+            && self.properties.is_none()
+    }
+
+    /// Apply the delta to a workspace
+    pub fn apply(&self, workspace: &mut Workspace) -> Result<(), WorkspaceError> {
+        if let Some(properties) = &self.properties {
+            let nodes = std::mem::take(&mut workspace.nodes);
+            let positions = std::mem::take(&mut workspace.layout.node_positions);
+            let mut updated: Workspace = serde_json::from_value(with_graph_fields(properties.clone()))?;
+            updated.nodes = nodes;
+            updated.layout.node_positions = positions;
+            *workspace = updated;
+        }
+
+        let removed: HashSet<SceneId> = self.removed_nodes.iter().copied().collect();
+        workspace.nodes.retain(|id| !removed.contains(id));
+        for id in &self.added_nodes {
+            if !workspace.nodes.contains(id) {
+                workspace.nodes.push(*id);
+            }
+        }
+        for id in &self.unpositioned_nodes {
+            workspace.layout.node_positions.remove(id);
+        }
+        workspace.layout.node_positions.extend(self.moved_nodes.iter().map(|(id, p)| (*id, *p)));
+        Ok(())
+    }
+}
+
+/// Workspace state as last written, the base for the next delta
+struct Baseline {
+    nodes: HashSet<SceneId>,
+    positions: HashMap<SceneId, Point3<f32>>,
+    properties: Value,
+    /// Deltas in the log since the snapshot
+    deltas: usize,
+    log_bytes: u64,
+    snapshot_bytes: u64,
+}
+
+impl Baseline {
+    fn of(workspace: &Workspace, snapshot_bytes: u64) -> Result<Self, WorkspaceError> {
+        Ok(Self {
+            nodes: workspace.nodes.iter().copied().collect(),
+            positions: workspace.layout.node_positions.clone(),
+            properties: properties_of(workspace)?,
+            deltas: 0,
+            log_bytes: 0,
+            snapshot_bytes,
+        })
+    }
+
+    /// Changes from this baseline to `workspace`
+    fn diff(&self, workspace: &Workspace) -> Result<WorkspaceDelta, WorkspaceError> {
+        let nodes: HashSet<SceneId> = workspace.nodes.iter().copied().collect();
+        let positions = &workspace.layout.node_positions;
+        let properties = properties_of(workspace)?;
+
+        let mut delta = WorkspaceDelta {
+            timestamp: Utc::now(),
+            added_nodes: workspace.nodes.iter().filter(|id| !self.nodes.contains(id)).copied().collect(),
+            removed_nodes: self.nodes.difference(&nodes).copied().collect(),
+            moved_nodes: positions
+                .iter()
+                .filter(|(id, position)| self.positions.get(id) != Some(position))
+                .map(|(id, position)| (*id, *position))
+                .collect(),
+            unpositioned_nodes: self.positions.keys().filter(|id| !positions.contains_key(id)).copied().collect(),
+            properties: (properties != self.properties).then_some(properties),
+        };
+        // Stable output keeps logs diffable
+        delta.removed_nodes.sort_unstable();
+        delta.unpositioned_nodes.sort_unstable();
+        Ok(delta)
+    }
+
+    fn advance(&mut self, workspace: &Workspace, delta: &WorkspaceDelta, bytes: u64) {
+        self.nodes = workspace.nodes.iter().copied().collect();
+        self.positions = workspace.layout.node_positions.clone();
+        if let Some(properties) = &delta.properties {
+            self.properties = properties.clone();
+        }
+        self.deltas += 1;
+        self.log_bytes += bytes;
+    }
+}
+
+/// Workspace fields other than its nodes and their positions
+fn properties_of(workspace: &Workspace) -> Result<Value, WorkspaceError> {
+    let mut value = serde_json::to_value(workspace)?;
+    if let Value::Object(fields) = &mut value {
+        fields.remove("nodes");
+        if let Some(Value::Object(layout)) = fields.get_mut("layout") {
+            layout.remove("node_positions");
+        }
+    }
+    Ok(value)
+}
+
+/// Re-add empty node fields so the properties deserialize as a workspace
+fn with_graph_fields(mut properties: Value) -> Value {
+    if let Value::Object(fields) = &mut properties {
+        fields.insert("nodes".to_string(), Value::Array(Vec::new()));
+        if let Some(Value::Object(layout)) = fields.get_mut("layout") {
+            layout.insert("node_positions".to_string(), Value::Object(Default::default()));
+        }
+    }
+    properties
+}
 
 /// Workspace persistence handler
 pub struct WorkspacePersistence {
     /// Base directory for workspace storage
     base_dir: PathBuf,
+    /// When logs are compacted
+    compaction: CompactionPolicy,
+    /// Last saved state per workspace
+    baselines: Mutex<HashMap<String, Baseline>>,
+    /// Last written index
+    index: Mutex<Option<Vec<String>>>,
+    stats: Mutex<PersistenceStats>,
 }
 
 impl WorkspacePersistence {
@@ -18,15 +220,32 @@ impl WorkspacePersistence {
             .unwrap_or_else(|| PathBuf::from("."))
             .join("horizonos")
             .join("workspaces");
-        
-        Self { base_dir }
+
+        Self::with_base_dir(base_dir)
     }
-    
+
     /// Set custom base directory
     pub fn with_base_dir(base_dir: PathBuf) -> Self {
-        Self { base_dir }
+        Self {
+            base_dir,
+            compaction: CompactionPolicy::default(),
+            baselines: Mutex::new(HashMap::new()),
+            index: Mutex::new(None),
+            stats: Mutex::new(PersistenceStats::default()),
+        }
     }
-    
+
+    /// Set when delta logs are compacted
+    pub fn with_compaction(mut self, compaction: CompactionPolicy) -> Self {
+        self.compaction = compaction;
+        self
+    }
+
+    /// Write counters
+    pub async fn stats(&self) -> PersistenceStats {
+        *self.stats.lock().await
+    }
+
     /// Ensure the workspace directory exists
     async fn ensure_dir(&self) -> Result<(), WorkspaceError> {
         if !self.base_dir.exists() {
@@ -34,134 +253,276 @@ impl WorkspacePersistence {
         }
         Ok(())
     }
-    
+
     /// Get the path for a workspace file
     fn workspace_path(&self, workspace_id: &str) -> PathBuf {
         self.base_dir.join(format!("{}.json", workspace_id))
     }
-    
+
+    /// Get the path for a workspace delta log
+    fn log_path(&self, workspace_id: &str) -> PathBuf {
+        self.base_dir.join(format!("{}.delta.jsonl", workspace_id))
+    }
+
+    /// Get the path of the routing rules file
+    fn routing_path(&self) -> PathBuf {
+        self.base_dir.join("routing.json")
+    }
+
+    /// Save the rules routing new nodes to workspaces
+    pub async fn save_routing_rules(&self, rules: &[RoutingRule]) -> Result<(), WorkspaceError> {
+        self.ensure_dir().await?;
+        fs::write(self.routing_path(), serde_json::to_string_pretty(rules)?).await?;
+        Ok(())
+    }
+
+    /// Load the rules routing new nodes to workspaces, none if never saved
+    pub async fn load_routing_rules(&self) -> Result<Vec<RoutingRule>, WorkspaceError> {
+        let path = self.routing_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = fs::read_to_string(&path).await?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
     /// Save a single workspace
+    ///
+    /// Appends a delta when the last saved state is known and the log does
+    /// not need compacting; writes a full snapshot otherwise.
     pub async fn save_workspace(&self, workspace: &Workspace) -> Result<(), WorkspaceError> {
         self.ensure_dir().await?;
-        
+
+        let mut baselines = self.baselines.lock().await;
+        let Some(baseline) = baselines.get_mut(&workspace.id) else {
+            let baseline = self.write_snapshot(workspace).await?;
+            baselines.insert(workspace.id.clone(), baseline);
+            return Ok(());
+        };
+
+        let delta = baseline.diff(workspace)?;
+        if delta.is_empty() {
+            self.stats.lock().await.unchanged_skipped += 1;
+            return Ok(());
+        }
+
+        let mut line = serde_json::to_string(&delta)?;
+        line.push('\n');
+        let bytes = line.len() as u64;
+        let over_budget = baseline.deltas + 1 >= self.compaction.max_deltas
+            || (baseline.log_bytes + bytes) as f64 > baseline.snapshot_bytes as f64 * self.compaction.max_log_ratio;
+        // A log that is not as last written, e.g. torn by a crash, is folded into a snapshot
+        let log_path = self.log_path(&workspace.id);
+        let log_bytes = fs::metadata(&log_path).await.map(|metadata| metadata.len()).unwrap_or(0);
+        if over_budget || log_bytes != baseline.log_bytes {
+            *baseline = self.write_snapshot(workspace).await?;
+            return Ok(());
+        }
+
+        let mut log = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)
+            .await?;
+        log.write_all(line.as_bytes()).await?;
+        log.sync_data().await?;
+        baseline.advance(workspace, &delta, bytes);
+
+        let mut stats = self.stats.lock().await;
+        stats.deltas_written += 1;
+        stats.bytes_written += bytes;
+        Ok(())
+    }
+
+    /// Write a full snapshot and clear the delta log
+    async fn write_snapshot(&self, workspace: &Workspace) -> Result<Baseline, WorkspaceError> {
         let path = self.workspace_path(&workspace.id);
         let json = serde_json::to_string_pretty(workspace)?;
-        
-        let mut file = fs::File::create(&path).await?;
+
+        // Replace atomically so a crash never leaves a half-written snapshot
+        let temporary = path.with_extension("json.tmp");
+        let mut file = fs::File::create(&temporary).await?;
         file.write_all(json.as_bytes()).await?;
         file.sync_all().await?;
-        
+        fs::rename(&temporary, &path).await?;
+
+        let log_path = self.log_path(&workspace.id);
+        if log_path.exists() {
+            fs::remove_file(&log_path).await?;
+        }
+
+        let mut stats = self.stats.lock().await;
+        stats.snapshots_written += 1;
+        stats.bytes_written += json.len() as u64;
+        Baseline::of(workspace, json.len() as u64)
+    }
+
+    /// Fold the delta log of a workspace into a new snapshot
+    pub async fn compact_workspace(&self, workspace_id: &str) -> Result<(), WorkspaceError> {
+        let workspace = self.load_workspace(workspace_id).await?;
+        let baseline = self.write_snapshot(&workspace).await?;
+        self.baselines.lock().await.insert(workspace_id.to_string(), baseline);
         Ok(())
     }
-    
+
     /// Load a single workspace
     pub async fn load_workspace(&self, workspace_id: &str) -> Result<Workspace, WorkspaceError> {
         let path = self.workspace_path(workspace_id);
-        
+
         let mut file = fs::File::open(&path).await?;
         let mut contents = String::new();
         file.read_to_string(&mut contents).await?;
-        
-        let workspace: Workspace = serde_json::from_str(&contents)?;
+
+        let mut workspace: Workspace = serde_json::from_str(&contents)?;
+        let mut baseline = Baseline::of(&workspace, contents.len() as u64)?;
+
+        let log_path = self.log_path(workspace_id);
+        if log_path.exists() {
+            let log = fs::read_to_string(&log_path).await?;
+            // End of the last delta that was read back, where the next one is appended
+            let mut good_end = 0;
+            for (number, line) in log.split_inclusive('\n').enumerate() {
+                if line.trim().is_empty() {
+                    good_end += line.len();
+                    baseline.log_bytes += line.len() as u64;
+                    continue;
+                }
+                let delta: WorkspaceDelta = match serde_json::from_str(line) {
+                    Ok(delta) => delta,
+                    Err(e) => {
+                        // Everything after a torn write is unusable
+                        log::warn!("Ignoring workspace {} deltas from line {}: {}", workspace_id, number + 1, e);
+                        break;
+                    }
+                };
+                delta.apply(&mut workspace)?;
+                baseline.advance(&workspace, &delta, line.trim_end_matches('\n').len() as u64 + 1);
+                good_end += line.len();
+            }
+            self.repair_log(&log_path, &log, good_end).await?;
+        }
+
+        self.baselines.lock().await.insert(workspace_id.to_string(), baseline);
         Ok(workspace)
     }
-    
+
+    /// Cut a torn or corrupt tail off a delta log, so later appends are read back
+    async fn repair_log(&self, log_path: &Path, log: &str, good_end: usize) -> Result<(), WorkspaceError> {
+        let mut repaired = log[..good_end].to_string();
+        if !repaired.is_empty() && !repaired.ends_with('\n') {
+            repaired.push('\n');
+        }
+        if repaired == log {
+            return Ok(());
+        }
+
+        // Replace atomically, like snapshots
+        let temporary = log_path.with_extension("jsonl.tmp");
+        let mut file = fs::File::create(&temporary).await?;
+        file.write_all(repaired.as_bytes()).await?;
+        file.sync_all().await?;
+        fs::rename(&temporary, log_path).await?;
+        log::warn!("Truncated the delta log {} to its last complete delta", log_path.display());
+        Ok(())
+    }
+
     /// Save all workspaces
     pub async fn save_workspaces(&self, workspaces: &[Workspace]) -> Result<(), WorkspaceError> {
         self.ensure_dir().await?;
-        
-        // Save index file
-        let index_path = self.base_dir.join("index.json");
+
+        // Save index file when the set of workspaces changed
         let workspace_ids: Vec<String> = workspaces.iter()
             .map(|w| w.id.clone())
             .collect();
-        
-        let index_json = serde_json::to_string_pretty(&workspace_ids)?;
-        let mut index_file = fs::File::create(&index_path).await?;
-        index_file.write_all(index_json.as_bytes()).await?;
-        index_file.sync_all().await?;
-        
+
+        let mut index = self.index.lock().await;
+        if index.as_ref() != Some(&workspace_ids) {
+            let index_path = self.base_dir.join("index.json");
+            let index_json = serde_json::to_string_pretty(&workspace_ids)?;
+            let mut index_file = fs::File::create(&index_path).await?;
+            index_file.write_all(index_json.as_bytes()).await?;
+            index_file.sync_all().await?;
+            *index = Some(workspace_ids);
+        }
+        drop(index);
+
         // Save individual workspaces
         for workspace in workspaces {
             self.save_workspace(workspace).await?;
         }
-        
+
         Ok(())
     }
-    
+
     /// Load all workspaces
     pub async fn load_workspaces(&self) -> Result<Vec<Workspace>, WorkspaceError> {
         if !self.base_dir.exists() {
             return Ok(Vec::new());
         }
-        
+
         let index_path = self.base_dir.join("index.json");
         if !index_path.exists() {
             // No index file, scan directory
             return self.scan_workspaces().await;
         }
-        
+
         // Load from index
         let mut file = fs::File::open(&index_path).await?;
         let mut contents = String::new();
         file.read_to_string(&mut contents).await?;
-        
+
         let workspace_ids: Vec<String> = serde_json::from_str(&contents)?;
         let mut workspaces = Vec::new();
-        
-        for id in workspace_ids {
-            match self.load_workspace(&id).await {
+
+        for id in &workspace_ids {
+            match self.load_workspace(id).await {
                 Ok(workspace) => workspaces.push(workspace),
                 Err(e) => {
                     log::warn!("Failed to load workspace {}: {}", id, e);
                 }
             }
         }
-        
+        *self.index.lock().await = Some(workspace_ids);
+
         Ok(workspaces)
     }
-    
+
     /// Scan directory for workspace files
     async fn scan_workspaces(&self) -> Result<Vec<Workspace>, WorkspaceError> {
         let mut workspaces = Vec::new();
-        
+
         let mut entries = fs::read_dir(&self.base_dir).await?;
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
-            
-            if path.extension().and_then(|s| s.to_str()) == Some("json") 
-                && path.file_stem().and_then(|s| s.to_str()) != Some("index") 
-            {
-                match fs::read_to_string(&path).await {
-                    Ok(contents) => {
-                        match serde_json::from_str::<Workspace>(&contents) {
-                            Ok(workspace) => workspaces.push(workspace),
-                            Err(e) => {
-                                log::warn!("Failed to parse workspace file {:?}: {}", path, e);
-                            }
-                        }
-                    }
+
+            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                let Some(id) = path.file_stem().and_then(|s| s.to_str()).filter(|id| !matches!(*id, "index" | "routing")) else {
+                    continue;
+                };
+                match self.load_workspace(id).await {
+                    Ok(workspace) => workspaces.push(workspace),
                     Err(e) => {
-                        log::warn!("Failed to read workspace file {:?}: {}", path, e);
+                        log::warn!("Failed to load workspace file {:?}: {}", path, e);
                     }
                 }
             }
         }
-        
+
         Ok(workspaces)
     }
-    
+
     /// Delete a workspace
     pub async fn delete_workspace(&self, workspace_id: &str) -> Result<(), WorkspaceError> {
-        let path = self.workspace_path(workspace_id);
-        
-        if path.exists() {
-            fs::remove_file(&path).await?;
+        for path in [self.workspace_path(workspace_id), self.log_path(workspace_id)] {
+            if path.exists() {
+                fs::remove_file(&path).await?;
+            }
         }
-        
+        self.baselines.lock().await.remove(workspace_id);
+
         Ok(())
     }
-    
+
     /// Export workspace to a specific path
     pub async fn export_workspace(
         &self,
@@ -169,24 +530,137 @@ impl WorkspacePersistence {
         path: &Path,
     ) -> Result<(), WorkspaceError> {
         let json = serde_json::to_string_pretty(workspace)?;
-        
+
         let mut file = fs::File::create(path).await?;
         file.write_all(json.as_bytes()).await?;
         file.sync_all().await?;
-        
+
         Ok(())
     }
-    
+
     /// Import workspace from a specific path
     pub async fn import_workspace(&self, path: &Path) -> Result<Workspace, WorkspaceError> {
         let mut file = fs::File::open(path).await?;
         let mut contents = String::new();
         file.read_to_string(&mut contents).await?;
-        
+
         let workspace: Workspace = serde_json::from_str(&contents)?;
         Ok(workspace)
     }
 }
 
+impl Default for WorkspacePersistence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // Re-export dirs for finding config directories
-pub use dirs;
\ No newline at end of file
+pub use dirs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn large_workspace() -> Workspace {
+        let mut workspace = Workspace::new("Research", "Papers and notes");
+        for id in 0..2000 {
+            workspace.add_node(id);
+            workspace.layout.node_positions.insert(id, Point3::new(id as f32, 0.0, 0.0));
+        }
+        workspace
+    }
+
+    async fn reload(dir: &Path, id: &str) -> Workspace {
+        WorkspacePersistence::with_base_dir(dir.to_path_buf()).load_workspace(id).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_saves_append_small_deltas() {
+        let dir = tempfile::tempdir().unwrap();
+        let persistence = WorkspacePersistence::with_base_dir(dir.path().to_path_buf());
+        let mut workspace = large_workspace();
+        persistence.save_workspace(&workspace).await.unwrap();
+        let snapshot_bytes = persistence.stats().await.bytes_written;
+
+        workspace.layout.node_positions.insert(7, Point3::new(1.0, 2.0, 3.0));
+        workspace.remove_node(9);
+        workspace.layout.node_positions.remove(&9);
+        workspace.add_node(5000);
+        workspace.name = "Renamed".to_string();
+        persistence.save_workspace(&workspace).await.unwrap();
+        persistence.save_workspace(&workspace).await.unwrap();
+
+        let stats = persistence.stats().await;
+        assert_eq!(stats.snapshots_written, 1);
+        assert_eq!(stats.deltas_written, 1);
+        assert_eq!(stats.unchanged_skipped, 1);
+        assert!(stats.bytes_written - snapshot_bytes < snapshot_bytes / 20);
+
+        let loaded = reload(dir.path(), &workspace.id).await;
+        assert_eq!(loaded.name, "Renamed");
+        assert_eq!(loaded.layout.node_positions.get(&7), Some(&Point3::new(1.0, 2.0, 3.0)));
+        assert!(!loaded.nodes.contains(&9) && !loaded.layout.node_positions.contains_key(&9));
+        assert!(loaded.nodes.contains(&5000));
+        assert_eq!(loaded.nodes.len(), workspace.nodes.len());
+    }
+
+    #[tokio::test]
+    async fn test_log_is_compacted_into_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let persistence = WorkspacePersistence::with_base_dir(dir.path().to_path_buf())
+            .with_compaction(CompactionPolicy { max_deltas: 3, max_log_ratio: 1.0 });
+        let mut workspace = large_workspace();
+        persistence.save_workspace(&workspace).await.unwrap();
+
+        for step in 1..=3 {
+            workspace.layout.node_positions.insert(1, Point3::new(-step as f32, 0.0, 0.0));
+            persistence.save_workspace(&workspace).await.unwrap();
+        }
+
+        let stats = persistence.stats().await;
+        assert_eq!(stats.snapshots_written, 2);
+        assert_eq!(stats.deltas_written, 2);
+        assert!(!persistence.log_path(&workspace.id).exists());
+        assert_eq!(reload(dir.path(), &workspace.id).await.layout.node_positions[&1], Point3::new(-3.0, 0.0, 0.0));
+    }
+
+    #[tokio::test]
+    async fn test_torn_delta_is_truncated_and_replay_is_idempotent() {
+        let dir = tempfile::tempdir().unwrap();
+        let persistence = WorkspacePersistence::with_base_dir(dir.path().to_path_buf());
+        let mut workspace = large_workspace();
+        persistence.save_workspace(&workspace).await.unwrap();
+        workspace.add_node(4242);
+        persistence.save_workspace(&workspace).await.unwrap();
+
+        let log_path = persistence.log_path(&workspace.id);
+        let log = std::fs::read_to_string(&log_path).unwrap();
+        std::fs::write(&log_path, format!("{}{{\"timestamp\":\"20", log)).unwrap();
+        assert!(reload(dir.path(), &workspace.id).await.nodes.contains(&4242));
+
+        // Loading cuts the torn line off, so deltas saved after it are read back
+        assert_eq!(std::fs::read_to_string(&log_path).unwrap(), log);
+        workspace.add_node(4343);
+        let reloaded = WorkspacePersistence::with_base_dir(dir.path().to_path_buf());
+        reloaded.load_workspace(&workspace.id).await.unwrap();
+        reloaded.save_workspace(&workspace).await.unwrap();
+        assert_eq!(reloaded.stats().await.deltas_written, 1);
+        let loaded = reload(dir.path(), &workspace.id).await;
+        assert!(loaded.nodes.contains(&4242) && loaded.nodes.contains(&4343));
+
+        // Saving over a log torn since it was last written folds it into a snapshot
+        std::fs::write(&log_path, format!("{}{{\"timestamp\":\"20", std::fs::read_to_string(&log_path).unwrap())).unwrap();
+        workspace.add_node(4444);
+        reloaded.save_workspace(&workspace).await.unwrap();
+        assert_eq!(reloaded.stats().await.snapshots_written, 1);
+        let loaded = reload(dir.path(), &workspace.id).await;
+        assert!(loaded.nodes.contains(&4343) && loaded.nodes.contains(&4444));
+
+        // A crash after compaction but before the log was removed replays it onto the new snapshot
+        persistence.export_workspace(&workspace, &persistence.workspace_path(&workspace.id)).await.unwrap();
+        let loaded = reload(dir.path(), &workspace.id).await;
+        assert_eq!(loaded.nodes.iter().filter(|id| **id == 4242).count(), 1);
+        assert_eq!(loaded.nodes.len(), workspace.nodes.len());
+    }
+}
@@ -0,0 +1,340 @@
+//! Preloading the workspace the user is likely to switch to next
+//!
+//! Given predictions of the next workspace, e.g. from the AI service's
+//! learned switch patterns, the preloader warms that workspace's resources
+//! while the desktop is idle so the switch itself has nothing left to load.
+//! The resources live in other layers, so the work is done by registered
+//! [`PreloadWarmer`]s, run stage by stage: node data first, then
+//! thumbnails, then window snapshots. All preloaded workspaces share one
+//! memory budget; a workspace that drops out of the predictions is
+//! released. Every switch counts as a hit when its target was preloaded
+//! and as a miss otherwise.
+
+use crate::Workspace;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+/// Kind of resource a warmer prepares, in the order they are warmed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum PreloadStage {
+    /// Deserialized scene nodes and their data
+    Nodes,
+    /// Node thumbnails
+    Thumbnails,
+    /// Last captured window contents
+    Snapshots,
+}
+
+/// Prepares one kind of resource for a workspace ahead of a switch
+pub trait PreloadWarmer: Send + Sync {
+    fn stage(&self) -> PreloadStage;
+
+    /// Warm resources for the workspace's nodes, using at most `budget_bytes`
+    ///
+    /// Returns the bytes now held for the workspace.
+    fn warm(&self, workspace: &Workspace, budget_bytes: u64) -> u64;
+
+    /// Drop what was warmed for a workspace that is no longer expected
+    fn release(&self, workspace_id: &str);
+}
+
+/// Preloading configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreloadConfig {
+    /// Preload at all
+    pub enabled: bool,
+    /// Predictions below this confidence are not preloaded
+    pub min_confidence: f32,
+    /// Most workspaces kept preloaded at once
+    pub max_workspaces: usize,
+    /// Memory all preloaded workspaces may hold together
+    pub budget_bytes: u64,
+}
+
+impl Default for PreloadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_confidence: 0.3,
+            max_workspaces: 1,
+            budget_bytes: 128 * 1024 * 1024,
+        }
+    }
+}
+
+/// Workspace predicted to be switched to next
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreloadCandidate {
+    pub workspace_id: String,
+    /// Likelihood of the switch (0.0 to 1.0)
+    pub confidence: f32,
+}
+
+/// Preloading counters since the preloader was created
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PreloadStats {
+    /// Workspaces preloaded
+    pub preloads: u64,
+    /// Switches to a preloaded workspace
+    pub hits: u64,
+    /// Switches to a workspace that was not preloaded
+    pub misses: u64,
+    /// Preloaded workspaces released before being switched to
+    pub evictions: u64,
+    /// Preloads cut short because the budget ran out
+    pub budget_exhausted: u64,
+    /// Memory currently held by preloaded workspaces
+    pub bytes_in_use: u64,
+}
+
+impl PreloadStats {
+    /// Share of switches that found their workspace preloaded
+    pub fn hit_rate(&self) -> Option<f32> {
+        let switches = self.hits + self.misses;
+        (switches > 0).then(|| self.hits as f32 / switches as f32)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PreparedWorkspace {
+    bytes: u64,
+    prepared_at: Instant,
+}
+
+/// Warms predicted workspaces within a memory budget
+pub struct WorkspacePreloader {
+    config: RwLock<PreloadConfig>,
+    /// Registered warmers, sorted by stage
+    warmers: RwLock<Vec<Arc<dyn PreloadWarmer>>>,
+    prepared: RwLock<HashMap<String, PreparedWorkspace>>,
+    stats: RwLock<PreloadStats>,
+}
+
+impl WorkspacePreloader {
+    pub fn new(config: PreloadConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            warmers: RwLock::new(Vec::new()),
+            prepared: RwLock::new(HashMap::new()),
+            stats: RwLock::new(PreloadStats::default()),
+        }
+    }
+
+    pub fn config(&self) -> PreloadConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /// Replace the configuration; preloaded workspaces are kept until the next preload
+    pub fn set_config(&self, config: PreloadConfig) {
+        *self.config.write().unwrap() = config;
+    }
+
+    pub fn add_warmer(&self, warmer: Arc<dyn PreloadWarmer>) {
+        let mut warmers = self.warmers.write().unwrap();
+        warmers.push(warmer);
+        warmers.sort_by_key(|warmer| warmer.stage());
+    }
+
+    pub fn stats(&self) -> PreloadStats {
+        *self.stats.read().unwrap()
+    }
+
+    /// Whether a workspace is currently preloaded
+    pub fn is_prepared(&self, workspace_id: &str) -> bool {
+        self.prepared.read().unwrap().contains_key(workspace_id)
+    }
+
+    /// Preload the most likely of the candidates and release the rest
+    ///
+    /// Candidates below the confidence threshold, unknown workspaces and the
+    /// active workspace are skipped. Returns the number of workspaces newly
+    /// preloaded.
+    pub fn preload(
+        &self,
+        candidates: &[PreloadCandidate],
+        workspaces: &HashMap<String, Workspace>,
+        active: Option<&str>,
+    ) -> usize {
+        let config = self.config();
+        let mut chosen: Vec<&PreloadCandidate> = if config.enabled {
+            candidates.iter()
+                .filter(|candidate| candidate.confidence >= config.min_confidence)
+                .filter(|candidate| Some(candidate.workspace_id.as_str()) != active)
+                .filter(|candidate| workspaces.contains_key(&candidate.workspace_id))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        chosen.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap_or(std::cmp::Ordering::Equal));
+        chosen.truncate(config.max_workspaces);
+
+        // Free the budget of workspaces no longer expected before warming new ones
+        let stale: Vec<String> = self.prepared.read().unwrap()
+            .keys()
+            .filter(|id| !chosen.iter().any(|candidate| &candidate.workspace_id == *id))
+            .cloned()
+            .collect();
+        for workspace_id in stale {
+            if self.release(&workspace_id) {
+                self.stats.write().unwrap().evictions += 1;
+            }
+        }
+
+        let warmers = self.warmers.read().unwrap().clone();
+        let mut preloaded = 0;
+        for candidate in chosen {
+            if self.is_prepared(&candidate.workspace_id) {
+                continue;
+            }
+            let workspace = &workspaces[&candidate.workspace_id];
+            let in_use = self.stats.read().unwrap().bytes_in_use;
+            let mut remaining = config.budget_bytes.saturating_sub(in_use);
+            if remaining == 0 {
+                self.stats.write().unwrap().budget_exhausted += 1;
+                break;
+            }
+
+            let mut bytes = 0;
+            for warmer in &warmers {
+                let used = warmer.warm(workspace, remaining).min(remaining);
+                bytes += used;
+                remaining -= used;
+                if remaining == 0 {
+                    self.stats.write().unwrap().budget_exhausted += 1;
+                    break;
+                }
+            }
+
+            log::debug!(
+                "Preloaded workspace {} ({:.0}% likely, {} bytes)",
+                candidate.workspace_id, candidate.confidence * 100.0, bytes
+            );
+            self.prepared.write().unwrap().insert(candidate.workspace_id.clone(), PreparedWorkspace {
+                bytes,
+                prepared_at: Instant::now(),
+            });
+            let mut stats = self.stats.write().unwrap();
+            stats.preloads += 1;
+            stats.bytes_in_use += bytes;
+            preloaded += 1;
+        }
+        preloaded
+    }
+
+    /// Count a switch as a hit or miss, returning whether it was a hit
+    ///
+    /// A preloaded target's resources now belong to the active workspace,
+    /// so they stop counting against the budget without being released.
+    pub fn record_switch(&self, workspace_id: &str) -> bool {
+        let prepared = self.prepared.write().unwrap().remove(workspace_id);
+        let mut stats = self.stats.write().unwrap();
+        match &prepared {
+            Some(prepared) => {
+                stats.hits += 1;
+                stats.bytes_in_use = stats.bytes_in_use.saturating_sub(prepared.bytes);
+                log::debug!(
+                    "Workspace {} was preloaded {:?} before the switch",
+                    workspace_id, prepared.prepared_at.elapsed()
+                );
+            }
+            None => stats.misses += 1,
+        }
+        if let Some(rate) = stats.hit_rate() {
+            log::debug!("Workspace preload hit rate {:.0}% over {} switches", rate * 100.0, stats.hits + stats.misses);
+        }
+        prepared.is_some()
+    }
+
+    /// Release a preloaded workspace, returning whether it was preloaded
+    pub fn release(&self, workspace_id: &str) -> bool {
+        let Some(prepared) = self.prepared.write().unwrap().remove(workspace_id) else {
+            return false;
+        };
+        for warmer in self.warmers.read().unwrap().iter() {
+            warmer.release(workspace_id);
+        }
+        let mut stats = self.stats.write().unwrap();
+        stats.bytes_in_use = stats.bytes_in_use.saturating_sub(prepared.bytes);
+        true
+    }
+}
+
+impl Default for WorkspacePreloader {
+    fn default() -> Self {
+        Self::new(PreloadConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Claims a fixed size per node and logs what it did
+    struct FakeWarmer {
+        stage: PreloadStage,
+        bytes_per_node: u64,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl PreloadWarmer for FakeWarmer {
+        fn stage(&self) -> PreloadStage {
+            self.stage
+        }
+
+        fn warm(&self, workspace: &Workspace, budget_bytes: u64) -> u64 {
+            self.log.lock().unwrap().push(format!("warm {:?} {}", self.stage, workspace.name));
+            (workspace.nodes.len() as u64 * self.bytes_per_node).min(budget_bytes)
+        }
+
+        fn release(&self, workspace_id: &str) {
+            self.log.lock().unwrap().push(format!("release {:?} {}", self.stage, workspace_id));
+        }
+    }
+
+    fn workspace(name: &str, nodes: u64) -> Workspace {
+        let mut workspace = Workspace::new(name, "");
+        workspace.id = name.to_string();
+        workspace.nodes = (0..nodes).collect();
+        workspace
+    }
+
+    fn candidate(workspace_id: &str, confidence: f32) -> PreloadCandidate {
+        PreloadCandidate { workspace_id: workspace_id.to_string(), confidence }
+    }
+
+    #[test]
+    fn test_preload_within_budget_and_count_hits() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let preloader = WorkspacePreloader::new(PreloadConfig { budget_bytes: 1000, ..Default::default() });
+        for (stage, bytes_per_node) in [(PreloadStage::Snapshots, 100), (PreloadStage::Nodes, 10)] {
+            preloader.add_warmer(Arc::new(FakeWarmer { stage, bytes_per_node, log: log.clone() }));
+        }
+        let workspaces: HashMap<String, Workspace> = [workspace("mail", 2), workspace("code", 20)]
+            .into_iter()
+            .map(|workspace| (workspace.id.clone(), workspace))
+            .collect();
+
+        // Too unlikely, then the active workspace itself
+        assert_eq!(preloader.preload(&[candidate("mail", 0.1)], &workspaces, Some("code")), 0);
+        assert_eq!(preloader.preload(&[candidate("code", 0.9)], &workspaces, Some("code")), 0);
+
+        assert_eq!(preloader.preload(&[candidate("mail", 0.6), candidate("code", 0.4)], &workspaces, None), 1);
+        assert_eq!(*log.lock().unwrap(), ["warm Nodes mail", "warm Snapshots mail"]);
+        assert_eq!(preloader.stats().bytes_in_use, 220);
+
+        // The prediction changes: mail is released, code runs out of budget
+        preloader.preload(&[candidate("code", 0.7)], &workspaces, None);
+        assert!(log.lock().unwrap().contains(&"release Nodes mail".to_string()));
+        let stats = preloader.stats();
+        assert_eq!((stats.evictions, stats.budget_exhausted, stats.bytes_in_use), (1, 1, 1000));
+
+        assert!(preloader.record_switch("code"));
+        assert!(!preloader.record_switch("mail"));
+        let stats = preloader.stats();
+        assert_eq!((stats.hits, stats.misses, stats.bytes_in_use), (1, 1, 0));
+        assert_eq!(stats.hit_rate(), Some(0.5));
+    }
+}
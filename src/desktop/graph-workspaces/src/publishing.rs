@@ -0,0 +1,427 @@
+//! Read-only published workspaces
+//!
+//! Publishing writes a snapshot of a workspace to a channel directory that
+//! other users on the machine, and the remote view server, can read but not
+//! write. Viewers open the snapshot into a scene of their own, so nothing
+//! they do reaches the source workspace. Nodes marked private are masked or
+//! left out before the snapshot is written, and a publication can be
+//! republished on an interval so a dashboard stays current.
+//!
+//! The channel directory is expected to be shared like `/tmp`: writable by
+//! every user, with the sticky bit set so only a snapshot's publisher can
+//! replace or remove it. Snapshots are written to a temporary file and
+//! renamed into place, so a viewer never reads a half-written one.
+
+use crate::archive::WorkspaceArchive;
+use crate::{Workspace, WorkspaceError};
+use chrono::{DateTime, Utc};
+use horizonos_graph_engine::scene::{NodeMetadata, NodeType, Scene, SceneId, SceneNode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::fs;
+
+/// Channel directory shared by all users
+pub const DEFAULT_CHANNEL_DIR: &str = "/var/lib/horizonos/published";
+
+/// How private nodes are kept out of a snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RedactionMode {
+    /// Leave out private nodes and their edges
+    Remove,
+    /// Keep private nodes as blank placeholders, so the shape of the graph
+    /// stays visible
+    Mask,
+}
+
+/// Which nodes are private
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Redaction {
+    pub mode: RedactionMode,
+    /// Nodes carrying this tag are private
+    pub private_tag: String,
+    /// Nodes that are private regardless of tags
+    pub hidden_nodes: HashSet<SceneId>,
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Self {
+            mode: RedactionMode::Mask,
+            private_tag: "private".to_string(),
+            hidden_nodes: HashSet::new(),
+        }
+    }
+}
+
+impl Redaction {
+    pub fn is_private(&self, node: &SceneNode) -> bool {
+        self.hidden_nodes.contains(&node.id) || node.metadata.tags.iter().any(|tag| tag == &self.private_tag)
+    }
+
+    /// Redact an archive in place, returning how many nodes were private
+    pub fn apply(&self, archive: &mut WorkspaceArchive) -> usize {
+        let private: HashSet<SceneId> = archive.nodes.iter()
+            .filter(|archived| self.is_private(&archived.node))
+            .map(|archived| archived.node.id)
+            .collect();
+        if private.is_empty() {
+            return 0;
+        }
+
+        match self.mode {
+            RedactionMode::Remove => {
+                archive.nodes.retain(|archived| !private.contains(&archived.node.id));
+                archive.edges.retain(|edge| !private.contains(&edge.source) && !private.contains(&edge.target));
+                archive.workspace.nodes.retain(|id| !private.contains(id));
+                archive.workspace.layout.node_positions.retain(|id, _| !private.contains(id));
+            }
+            RedactionMode::Mask => {
+                for archived in archive.nodes.iter_mut().filter(|archived| private.contains(&archived.node.id)) {
+                    let node = &mut archived.node;
+                    node.node_type = NodeType::Concept { title: "Private".to_string(), content: String::new() };
+                    node.metadata = NodeMetadata {
+                        created_at: node.metadata.created_at,
+                        updated_at: node.metadata.created_at,
+                        privacy: node.metadata.privacy,
+                        persistence: node.metadata.persistence,
+                        ..NodeMetadata::default()
+                    };
+                    node.color = [0.5, 0.5, 0.5, 1.0];
+                    node.selected = false;
+                    archived.export_data = None;
+                }
+            }
+        }
+        private.len()
+    }
+}
+
+/// How to publish a workspace
+#[derive(Debug, Clone, Default)]
+pub struct PublishOptions {
+    /// Name shown to viewers; the workspace name if empty
+    pub name: String,
+    pub redaction: Redaction,
+    /// Republish this often; `None` publishes once
+    pub interval: Option<Duration>,
+}
+
+/// A workspace published to the channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Publication {
+    pub id: String,
+    pub workspace_id: String,
+    pub name: String,
+    pub redaction: Redaction,
+    pub interval: Option<Duration>,
+    /// Times the snapshot was written
+    pub revision: u64,
+    pub last_published: Option<DateTime<Utc>>,
+}
+
+impl Publication {
+    pub fn new(workspace: &Workspace, options: PublishOptions) -> Self {
+        let name = if options.name.is_empty() { workspace.name.clone() } else { options.name };
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            workspace_id: workspace.id.clone(),
+            name,
+            redaction: options.redaction,
+            interval: options.interval,
+            revision: 0,
+            last_published: None,
+        }
+    }
+
+    /// Whether the snapshot should be written again
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        match (self.last_published, self.interval) {
+            (None, _) => true,
+            (Some(last), Some(interval)) => chrono::Duration::from_std(interval)
+                .map(|interval| now - last >= interval)
+                .unwrap_or(false),
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Snapshot of the workspace as it is now, redacted
+    pub fn snapshot(&self, workspace: &Workspace, scene: &Scene, node_data: &HashMap<SceneId, Value>) -> PublishedSnapshot {
+        let mut archive = WorkspaceArchive::build(workspace, scene, node_data);
+        // Viewers get neither the source ID nor its custom metadata
+        archive.workspace.id = self.id.clone();
+        archive.workspace.name = self.name.clone();
+        archive.workspace.metadata.clear();
+        let redacted = self.redaction.apply(&mut archive);
+        PublishedSnapshot {
+            publication_id: self.id.clone(),
+            name: self.name.clone(),
+            revision: self.revision + 1,
+            published_at: archive.exported_at,
+            redacted,
+            archive,
+        }
+    }
+}
+
+/// Summary of a snapshot in the channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedInfo {
+    pub publication_id: String,
+    pub name: String,
+    pub revision: u64,
+    pub published_at: DateTime<Utc>,
+}
+
+/// A snapshot as viewers receive it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedSnapshot {
+    pub publication_id: String,
+    pub name: String,
+    pub revision: u64,
+    pub published_at: DateTime<Utc>,
+    /// Private nodes masked or left out
+    pub redacted: usize,
+    pub archive: WorkspaceArchive,
+}
+
+impl PublishedSnapshot {
+    pub fn info(&self) -> PublishedInfo {
+        PublishedInfo {
+            publication_id: self.publication_id.clone(),
+            name: self.name.clone(),
+            revision: self.revision,
+            published_at: self.published_at,
+        }
+    }
+
+    /// Scene and workspace to display, independent of the source
+    pub fn to_scene(&self) -> (Scene, Workspace) {
+        let mut scene = Scene::new();
+        let imported = self.archive.clone().import_into(&mut scene);
+        (scene, imported.workspace)
+    }
+}
+
+/// Directory snapshots are published to
+#[derive(Debug, Clone)]
+pub struct PublishChannel {
+    dir: PathBuf,
+}
+
+impl Default for PublishChannel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PublishChannel {
+    pub fn new() -> Self {
+        Self::with_dir(PathBuf::from(DEFAULT_CHANNEL_DIR))
+    }
+
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn snapshot_path(&self, publication_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", publication_id))
+    }
+
+    /// Write a snapshot, replacing the previous revision
+    pub async fn write(&self, snapshot: &PublishedSnapshot) -> Result<(), WorkspaceError> {
+        fs::create_dir_all(&self.dir).await?;
+        let path = self.snapshot_path(&snapshot.publication_id);
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_string(snapshot)?).await?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&temp_path, std::fs::Permissions::from_mode(0o644)).await?;
+        }
+        fs::rename(&temp_path, &path).await?;
+        Ok(())
+    }
+
+    /// Open a published snapshot
+    pub async fn open(&self, publication_id: &str) -> Result<PublishedSnapshot, WorkspaceError> {
+        let path = self.snapshot_path(publication_id);
+        let text = match fs::read_to_string(&path).await {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(WorkspaceError::NotFound(publication_id.to_string()));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// Snapshots in the channel, newest first; unreadable ones are skipped
+    pub async fn list(&self) -> Result<Vec<PublishedInfo>, WorkspaceError> {
+        let mut published = Vec::new();
+        let mut entries = match fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(published),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Ok(text) = fs::read_to_string(&path).await else {
+                continue;
+            };
+            match serde_json::from_str::<PublishedSnapshot>(&text) {
+                Ok(snapshot) => published.push(snapshot.info()),
+                Err(e) => log::debug!("Skipping unreadable snapshot {}: {}", path.display(), e),
+            }
+        }
+        published.sort_by_key(|info| std::cmp::Reverse(info.published_at));
+        Ok(published)
+    }
+
+    /// Take a snapshot off the channel
+    pub async fn remove(&self, publication_id: &str) -> Result<(), WorkspaceError> {
+        match fs::remove_file(self.snapshot_path(publication_id)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::scene::{EdgeType, SceneEdge};
+
+    fn node(scene: &mut Scene, title: &str, tags: &[&str]) -> SceneId {
+        scene.add_node(SceneNode {
+            id: 0,
+            position: nalgebra::Point3::origin(),
+            velocity: nalgebra::Vector3::zeros(),
+            radius: 1.0,
+            color: [1.0; 4],
+            node_type: NodeType::Concept { title: title.to_string(), content: String::new() },
+            metadata: NodeMetadata { tags: tags.iter().map(|tag| tag.to_string()).collect(), ..NodeMetadata::default() },
+            visible: true,
+            selected: false,
+        })
+    }
+
+    fn edge(scene: &mut Scene, source: SceneId, target: SceneId) {
+        scene.add_edge(SceneEdge {
+            id: 0,
+            source,
+            target,
+            edge_type: EdgeType::RelatedTo { similarity: 0.5 },
+            weight: 1.0,
+            color: [1.0; 4],
+            visible: true,
+            animated: false,
+        });
+    }
+
+    fn title(scene: &Scene, id: SceneId) -> String {
+        match &scene.get_node(id).unwrap().node_type {
+            NodeType::Concept { title, .. } => title.clone(),
+            other => panic!("Unexpected node type {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_published_snapshot_imports_as_a_copy() {
+        let mut scene = Scene::new();
+        let roadmap = node(&mut scene, "roadmap", &[]);
+        let notes = node(&mut scene, "notes", &["team"]);
+        let salaries = node(&mut scene, "salaries", &["private"]);
+        edge(&mut scene, roadmap, notes);
+        edge(&mut scene, notes, salaries);
+        let mut workspace = Workspace::new("Planning", "Quarterly planning");
+        for id in [roadmap, notes, salaries] {
+            workspace.add_node(id);
+        }
+        workspace.layout.node_positions.insert(notes, nalgebra::Point3::new(4.0, 2.0, 0.0));
+        workspace.metadata.insert("owner".to_string(), serde_json::json!("alice"));
+        let node_data = HashMap::from([(notes, serde_json::json!({ "display_name": "notes" }))]);
+
+        let options = PublishOptions {
+            name: "Dashboard".to_string(),
+            redaction: Redaction { mode: RedactionMode::Remove, ..Redaction::default() },
+            interval: None,
+        };
+        let publication = Publication::new(&workspace, options);
+        let dir = tempfile::tempdir().unwrap();
+        let channel = PublishChannel::with_dir(dir.path().to_path_buf());
+        channel.write(&publication.snapshot(&workspace, &scene, &node_data)).await.unwrap();
+
+        let snapshot = channel.open(&publication.id).await.unwrap();
+        assert_eq!((snapshot.name.as_str(), snapshot.revision, snapshot.redacted), ("Dashboard", 1, 1));
+        assert_eq!(snapshot.archive.nodes[1].export_data, node_data.get(&notes).cloned());
+
+        let (view, imported) = snapshot.to_scene();
+        assert_ne!(imported.id, workspace.id);
+        assert_eq!(imported.name, "Dashboard");
+        assert!(imported.metadata.is_empty());
+        let titles: Vec<String> = imported.nodes.iter().map(|id| title(&view, *id)).collect();
+        assert_eq!(titles, ["roadmap", "notes"]);
+        // Edges to removed nodes go with them
+        assert_eq!(view.get_all_edges().len(), 1);
+        let imported_notes = imported.nodes[1];
+        assert_eq!(imported.layout.node_positions[&imported_notes], nalgebra::Point3::new(4.0, 2.0, 0.0));
+        assert_eq!(view.get_node(imported_notes).unwrap().metadata.tags, ["team"]);
+
+        // The source is untouched
+        assert_eq!(scene.get_all_nodes().len(), 3);
+        assert_eq!(workspace.metadata["owner"], "alice");
+    }
+
+    #[tokio::test]
+    async fn test_channel_lists_newest_first_and_skips_unreadable() {
+        let mut scene = Scene::new();
+        let mut workspace = Workspace::new("Status", "");
+        workspace.add_node(node(&mut scene, "build", &[]));
+        let dir = tempfile::tempdir().unwrap();
+        let channel = PublishChannel::with_dir(dir.path().to_path_buf());
+
+        let older = Publication::new(&workspace, PublishOptions { name: "Older".to_string(), ..PublishOptions::default() });
+        let mut snapshot = older.snapshot(&workspace, &scene, &HashMap::new());
+        snapshot.published_at -= chrono::Duration::hours(1);
+        channel.write(&snapshot).await.unwrap();
+        let newer = Publication::new(&workspace, PublishOptions::default());
+        channel.write(&newer.snapshot(&workspace, &scene, &HashMap::new())).await.unwrap();
+        std::fs::write(dir.path().join("broken.json"), "{").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a snapshot").unwrap();
+
+        let names: Vec<String> = channel.list().await.unwrap().into_iter().map(|info| info.name).collect();
+        assert_eq!(names, ["Status", "Older"]);
+
+        channel.remove(&newer.id).await.unwrap();
+        channel.remove(&newer.id).await.unwrap();
+        assert!(matches!(channel.open(&newer.id).await, Err(WorkspaceError::NotFound(_))));
+        assert_eq!(channel.list().await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_republishing_follows_the_interval() {
+        let workspace = Workspace::new("Status", "");
+        let now = Utc::now();
+        let mut publication = Publication::new(&workspace, PublishOptions {
+            interval: Some(Duration::from_secs(3600)),
+            ..PublishOptions::default()
+        });
+        assert!(publication.is_due(now));
+
+        publication.last_published = Some(now);
+        publication.revision = 3;
+        assert!(!publication.is_due(now + chrono::Duration::minutes(30)));
+        assert!(publication.is_due(now + chrono::Duration::hours(1)));
+        assert_eq!(publication.snapshot(&workspace, &Scene::new(), &HashMap::new()).revision, 4);
+
+        // Publications without an interval are written once
+        publication.interval = None;
+        assert!(!publication.is_due(now + chrono::Duration::days(1)));
+    }
+}
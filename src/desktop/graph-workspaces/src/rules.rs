@@ -1,12 +1,33 @@
 //! Workspace organization rules
+//!
+//! Besides the organization rule sets, routing rules pick the workspace a
+//! new node goes to from where it came from, e.g. nodes created by a chat
+//! app go to "Work" and URL nodes on a paper site go to "Research":
+//!
+//! ```json
+//! [
+//!     {"name": "Chat", "workspace": "Work", "when": {"created_by_app": "slack"}},
+//!     {"name": "Papers", "workspace": "Research", "priority": 10, "when": {"url_domain": "arxiv.org"}}
+//! ]
+//! ```
+//!
+//! When several rules match, the highest priority wins, and among equal
+//! priorities the rule listed first. Rules naming a workspace that does not
+//! exist are passed over; nodes no rule places go to the active workspace.
 
 use crate::Workspace;
+use horizonos_graph_engine::{node_kind, NodeType, SceneNode};
 use serde::{Deserialize, Serialize};
 
+/// Metadata property naming the application that created a node
+pub const SOURCE_APP_PROPERTY: &str = "source.app";
+
 /// Workspace rules engine
 pub struct WorkspaceRules {
     /// Active rule sets
     rule_sets: Vec<RuleSet>,
+    /// Rules placing new nodes, in the order they were added
+    routing: Vec<RoutingRule>,
 }
 
 impl WorkspaceRules {
@@ -17,6 +38,7 @@ impl WorkspaceRules {
                 RuleSet::default_organization_rules(),
                 RuleSet::default_grouping_rules(),
             ],
+            routing: Vec::new(),
         }
     }
     
@@ -25,6 +47,41 @@ impl WorkspaceRules {
         self.rule_sets.push(rule_set);
     }
     
+    /// Routing rules, in the order they were added
+    pub fn routing_rules(&self) -> &[RoutingRule] {
+        &self.routing
+    }
+    
+    /// Replace the routing rules
+    pub fn set_routing_rules(&mut self, rules: Vec<RoutingRule>) {
+        self.routing = rules;
+    }
+    
+    /// Add a routing rule, replacing one with the same name
+    pub fn add_routing_rule(&mut self, rule: RoutingRule) {
+        match self.routing.iter_mut().find(|existing| existing.name == rule.name) {
+            Some(existing) => *existing = rule,
+            None => self.routing.push(rule),
+        }
+    }
+    
+    /// Remove a routing rule by name, returning whether it existed
+    pub fn remove_routing_rule(&mut self, name: &str) -> bool {
+        let before = self.routing.len();
+        self.routing.retain(|rule| rule.name != name);
+        self.routing.len() != before
+    }
+    
+    /// Enabled routing rules matching a node, the one that wins first
+    pub fn matching_routes(&self, origin: &NodeOrigin) -> Vec<&RoutingRule> {
+        let mut matching: Vec<&RoutingRule> = self.routing.iter()
+            .filter(|rule| rule.enabled && rule.when.matches(origin))
+            .collect();
+        // Stable, so equal priorities keep their order
+        matching.sort_by_key(|rule| std::cmp::Reverse(rule.priority));
+        matching
+    }
+    
     /// Apply all rules to a workspace
     pub fn apply_to_workspace(&self, workspace: &mut Workspace) {
         for rule_set in &self.rule_sets {
@@ -173,4 +230,194 @@ pub enum Action {
     
     /// Archive node
     Archive,
-}
\ No newline at end of file
+}
+
+/// Rule sending new nodes of some origin to a workspace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutingRule {
+    /// Rule name, unique among routing rules
+    pub name: String,
+    /// Whether the rule is evaluated
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Higher priorities win when several rules match
+    #[serde(default)]
+    pub priority: i32,
+    /// Which nodes the rule applies to
+    pub when: RouteCondition,
+    /// ID or name of the workspace the nodes go to
+    pub workspace: String,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl RoutingRule {
+    pub fn new(name: impl Into<String>, when: RouteCondition, workspace: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            enabled: true,
+            priority: 0,
+            when,
+            workspace: workspace.into(),
+        }
+    }
+    
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Which new nodes a routing rule applies to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteCondition {
+    /// Created by an application, by name or app ID, ignoring case
+    CreatedByApp(String),
+    /// URL nodes on a domain or one of its subdomains
+    UrlDomain(String),
+    /// Nodes of a type, by palette name (`file`, `url`, ...)
+    NodeType(String),
+    /// File nodes at or below a path
+    PathPrefix(String),
+    /// Every condition holds
+    All(Vec<RouteCondition>),
+    /// Any condition holds
+    Any(Vec<RouteCondition>),
+}
+
+impl RouteCondition {
+    pub fn matches(&self, origin: &NodeOrigin) -> bool {
+        match self {
+            RouteCondition::CreatedByApp(app) => {
+                origin.app.as_ref().is_some_and(|origin_app| origin_app.eq_ignore_ascii_case(app))
+            }
+            RouteCondition::UrlDomain(domain) => {
+                let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+                origin.url.as_deref().and_then(url_host).is_some_and(|host| {
+                    host == domain || host.strip_suffix(domain.as_str()).is_some_and(|sub| sub.ends_with('.'))
+                })
+            }
+            RouteCondition::NodeType(node_type) => origin.node_type == *node_type,
+            RouteCondition::PathPrefix(prefix) => {
+                origin.path.as_deref().is_some_and(|path| std::path::Path::new(path).starts_with(prefix))
+            }
+            RouteCondition::All(conditions) => conditions.iter().all(|condition| condition.matches(origin)),
+            RouteCondition::Any(conditions) => conditions.iter().any(|condition| condition.matches(origin)),
+        }
+    }
+}
+
+/// Lowercased host of a URL, without user info or port
+fn url_host(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = host.split(':').next()?;
+    (!host.is_empty()).then(|| host.to_ascii_lowercase())
+}
+
+/// Where a new node came from, as routing rules see it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeOrigin {
+    /// Palette name of the node type
+    pub node_type: String,
+    /// Application that created the node
+    pub app: Option<String>,
+    /// URL of URL nodes
+    pub url: Option<String>,
+    /// Path of file nodes
+    pub path: Option<String>,
+}
+
+impl NodeOrigin {
+    /// Origin of a scene node
+    ///
+    /// The creating app is read from [`SOURCE_APP_PROPERTY`], then from the
+    /// app ID of materialized notifications; application nodes count as
+    /// created by their own app.
+    pub fn of(node: &SceneNode) -> Self {
+        let properties = &node.metadata.properties;
+        let app = properties.get(SOURCE_APP_PROPERTY)
+            .or_else(|| properties.get("notification.app_id"))
+            .cloned()
+            .or_else(|| match &node.node_type {
+                NodeType::Application { name, .. } => Some(name.clone()),
+                _ => None,
+            });
+        Self {
+            node_type: node_kind(&node.node_type).to_string(),
+            app,
+            url: match &node.node_type {
+                NodeType::URL { url, .. } => Some(url.clone()),
+                _ => None,
+            },
+            path: match &node.node_type {
+                NodeType::File { path, .. } => Some(path.clone()),
+                _ => None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn origin(node_type: &str, app: Option<&str>, url: Option<&str>) -> NodeOrigin {
+        NodeOrigin {
+            node_type: node_type.to_string(),
+            app: app.map(str::to_string),
+            url: url.map(str::to_string),
+            path: None,
+        }
+    }
+    
+    #[test]
+    fn test_conditions_match_app_domain_and_type() {
+        let slack = RouteCondition::CreatedByApp("Slack".to_string());
+        assert!(slack.matches(&origin("concept", Some("slack"), None)));
+        assert!(!slack.matches(&origin("concept", None, None)));
+        
+        let arxiv = RouteCondition::UrlDomain("arxiv.org".to_string());
+        assert!(arxiv.matches(&origin("url", None, Some("https://arxiv.org/abs/1234"))));
+        assert!(arxiv.matches(&origin("url", None, Some("https://user@export.ARXIV.org:443/list"))));
+        assert!(!arxiv.matches(&origin("url", None, Some("https://notarxiv.org/"))));
+        
+        let both = RouteCondition::All(vec![RouteCondition::NodeType("url".to_string()), slack]);
+        assert!(both.matches(&origin("url", Some("slack"), None)));
+        assert!(!both.matches(&origin("file", Some("slack"), None)));
+        
+        let mut file = origin("file", None, None);
+        file.path = Some("/home/user/papers/a.pdf".to_string());
+        assert!(RouteCondition::PathPrefix("/home/user/papers".to_string()).matches(&file));
+        assert!(!RouteCondition::PathPrefix("/home/user/pap".to_string()).matches(&file));
+    }
+    
+    #[test]
+    fn test_routes_by_priority_then_order() {
+        let rules: Vec<RoutingRule> = serde_json::from_str(r#"[
+            {"name": "Browser", "workspace": "Web", "when": {"created_by_app": "firefox"}},
+            {"name": "Papers", "workspace": "Research", "priority": 10, "when": {"url_domain": "arxiv.org"}},
+            {"name": "Links", "workspace": "Reading", "when": {"node_type": "url"}},
+            {"name": "Off", "workspace": "Nowhere", "enabled": false, "when": {"node_type": "url"}}
+        ]"#).unwrap();
+        let mut engine = WorkspaceRules::new();
+        engine.set_routing_rules(rules);
+        
+        let paper = origin("url", Some("firefox"), Some("https://arxiv.org/abs/1"));
+        let names: Vec<&str> = engine.matching_routes(&paper).iter().map(|rule| rule.name.as_str()).collect();
+        assert_eq!(names, ["Papers", "Browser", "Links"]);
+        
+        engine.add_routing_rule(RoutingRule::new("Browser", RouteCondition::CreatedByApp("firefox".to_string()), "Web").with_priority(20));
+        assert_eq!(engine.matching_routes(&paper)[0].name, "Browser");
+        assert_eq!(engine.routing_rules().len(), 4);
+        assert!(engine.remove_routing_rule("Browser"));
+        assert!(!engine.remove_routing_rule("Browser"));
+        
+        let json = serde_json::to_string(engine.routing_rules()).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<RoutingRule>>(&json).unwrap(), engine.routing_rules());
+    }
+}
@@ -0,0 +1,407 @@
+//! Crash-resilient session journal
+//!
+//! The graph state of a session (scene nodes and edges, workspaces, the
+//! active workspace and the camera) is kept in a directory holding a full
+//! snapshot (`session.json`) and an append-only log of the operations made
+//! since (`session.log.jsonl`). Operations are appended as they happen; a
+//! checkpoint, run on the workspace manager's auto-save interval, folds the
+//! log into a new snapshot. If the compositor dies, the next launch replays
+//! the log onto the snapshot and gets the session back up to the last
+//! operation.
+//!
+//! Like the workspace delta logs, operations carry absolute values, so
+//! replaying a log onto a snapshot that already contains part of it yields
+//! the same state, and a torn last line from a crash mid-append is skipped.
+//! A `running` marker exists while a session is open; finding it at startup
+//! means the previous session did not close cleanly.
+//!
+//! Ephemeral nodes are never journaled; session-only nodes are, but are
+//! left out of regular workspace saves.
+
+use crate::{Workspace, WorkspaceError};
+use horizonos_graph_engine::{
+    CameraState, Change, NodePersistence, Position, Scene, SceneEdge, SceneFormat, SceneId, SceneNode,
+    SceneSnapshot,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const SNAPSHOT_FILE: &str = "session.json";
+const LOG_FILE: &str = "session.log.jsonl";
+const RUNNING_MARKER: &str = "running";
+
+/// One journaled change to the session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum JournalOp {
+    /// A node was added or changed
+    NodeSet { node: Box<SceneNode> },
+    NodeMoved { id: SceneId, position: Position },
+    NodeRemoved { id: SceneId },
+    /// An edge was added or changed
+    EdgeSet { edge: SceneEdge },
+    EdgeRemoved { id: SceneId },
+    /// A workspace was created or changed
+    WorkspaceSet { workspace: Box<Workspace> },
+    WorkspaceRemoved { id: String },
+    ActiveWorkspace { id: String },
+    Camera { camera: CameraState },
+}
+
+impl JournalOp {
+    /// Operations recording a scene change made through the command history
+    pub fn from_change(change: &Change) -> Vec<JournalOp> {
+        match change {
+            Change::NodeAdded(node) => vec![JournalOp::NodeSet { node: Box::new(node.clone()) }],
+            Change::NodeRemoved { node, .. } => vec![JournalOp::NodeRemoved { id: node.id }],
+            Change::NodeMoved { id, to, .. } => vec![JournalOp::NodeMoved { id: *id, position: *to }],
+            Change::NodeUpdated { to, .. } => vec![JournalOp::NodeSet { node: to.clone() }],
+            Change::EdgeAdded(edge) => vec![JournalOp::EdgeSet { edge: edge.clone() }],
+            Change::EdgeRemoved(edge) => vec![JournalOp::EdgeRemoved { id: edge.id }],
+        }
+    }
+}
+
+/// Graph state of a session
+#[derive(Debug, Default)]
+pub struct SessionState {
+    pub scene: Scene,
+    pub workspaces: Vec<Workspace>,
+    pub active_workspace: Option<String>,
+    pub camera: Option<CameraState>,
+}
+
+impl SessionState {
+    /// Apply one journaled operation
+    pub fn apply(&mut self, op: JournalOp) {
+        match op {
+            JournalOp::NodeSet { node } => {
+                self.scene.insert_node(*node);
+            }
+            JournalOp::NodeMoved { id, position } => {
+                if let Some(node) = self.scene.get_node_mut(id) {
+                    node.position = position;
+                }
+            }
+            JournalOp::NodeRemoved { id } => {
+                self.scene.remove_node(id);
+            }
+            JournalOp::EdgeSet { edge } => {
+                // Edges to ephemeral nodes are journaled, the nodes are not
+                if self.scene.get_node(edge.source).is_some() && self.scene.get_node(edge.target).is_some() {
+                    self.scene.insert_edge(edge);
+                }
+            }
+            JournalOp::EdgeRemoved { id } => {
+                self.scene.remove_edge(id);
+            }
+            JournalOp::WorkspaceSet { workspace } => {
+                match self.workspaces.iter_mut().find(|existing| existing.id == workspace.id) {
+                    Some(existing) => *existing = *workspace,
+                    None => self.workspaces.push(*workspace),
+                }
+            }
+            JournalOp::WorkspaceRemoved { id } => {
+                self.workspaces.retain(|workspace| workspace.id != id);
+                if self.active_workspace.as_deref() == Some(id.as_str()) {
+                    self.active_workspace = None;
+                }
+            }
+            JournalOp::ActiveWorkspace { id } => self.active_workspace = Some(id),
+            JournalOp::Camera { camera } => self.camera = Some(camera),
+        }
+    }
+}
+
+/// A session read back from the journal
+#[derive(Debug)]
+pub struct RecoveredSession {
+    pub state: SessionState,
+    /// Whether the previous session ended without closing the journal
+    pub crashed: bool,
+    /// Operations replayed on top of the snapshot
+    pub replayed: usize,
+}
+
+/// On-disk snapshot layout; the scene keeps its own format version
+#[derive(Serialize, Deserialize)]
+struct SnapshotFile {
+    scene: Value,
+    workspaces: Vec<Workspace>,
+    active_workspace: Option<String>,
+    camera: Option<CameraState>,
+}
+
+/// Snapshot plus operation log of the running session
+pub struct SessionJournal {
+    dir: PathBuf,
+    format: SceneFormat,
+    /// Whether the running marker was left behind by the previous session
+    crashed: bool,
+    log: Mutex<JournalLog>,
+}
+
+struct JournalLog {
+    file: Option<File>,
+    /// Operations appended since the last checkpoint
+    pending: usize,
+}
+
+impl SessionJournal {
+    /// `$XDG_STATE_HOME/horizonos/session`, or the data directory where there is no state directory
+    pub fn default_dir() -> PathBuf {
+        dirs::state_dir()
+            .or_else(dirs::data_dir)
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("horizonos")
+            .join("session")
+    }
+
+    /// Open the journal in a directory and mark the session as running
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, WorkspaceError> {
+        Self::with_format(dir, SceneFormat::new())
+    }
+
+    /// Open the journal, reading old scene versions with the given format's migrations
+    pub fn with_format(dir: impl Into<PathBuf>, format: SceneFormat) -> Result<Self, WorkspaceError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let marker = dir.join(RUNNING_MARKER);
+        let crashed = marker.exists();
+        if crashed {
+            log::warn!("Previous session did not shut down cleanly, recovering from {:?}", dir);
+        }
+        fs::write(&marker, std::process::id().to_string())?;
+
+        Ok(Self {
+            dir,
+            format,
+            crashed,
+            log: Mutex::new(JournalLog { file: None, pending: 0 }),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Whether the previous session ended without closing the journal
+    pub fn crashed(&self) -> bool {
+        self.crashed
+    }
+
+    /// Operations appended since the last checkpoint
+    pub fn pending_ops(&self) -> usize {
+        self.lock_log().pending
+    }
+
+    /// Read the saved session, or `None` when nothing was ever saved
+    pub fn recover(&self) -> Result<Option<RecoveredSession>, WorkspaceError> {
+        let snapshot_path = self.dir.join(SNAPSHOT_FILE);
+        let log_path = self.dir.join(LOG_FILE);
+        if !snapshot_path.exists() && !log_path.exists() {
+            return Ok(None);
+        }
+
+        let mut state = SessionState::default();
+        if snapshot_path.exists() {
+            let file: SnapshotFile = serde_json::from_str(&fs::read_to_string(&snapshot_path)?)?;
+            let scene = self.format.migrate(file.scene).map_err(|e| WorkspaceError::SessionCorrupt(e.to_string()))?;
+            let scene: SceneSnapshot = serde_json::from_value(scene)?;
+            state.scene = scene.to_scene();
+            state.workspaces = file.workspaces;
+            state.active_workspace = file.active_workspace;
+            state.camera = file.camera;
+        }
+
+        let mut replayed = 0;
+        if log_path.exists() {
+            let text = fs::read_to_string(&log_path)?;
+            let mut lines = text.lines().filter(|line| !line.trim().is_empty()).peekable();
+            while let Some(line) = lines.next() {
+                match serde_json::from_str::<JournalOp>(line) {
+                    Ok(op) => {
+                        state.apply(op);
+                        replayed += 1;
+                    }
+                    // Only the last line can be torn by a crash
+                    Err(e) if lines.peek().is_none() => {
+                        log::warn!("Skipping torn last line of session log: {}", e);
+                    }
+                    Err(e) => return Err(WorkspaceError::SessionCorrupt(e.to_string())),
+                }
+            }
+        }
+
+        log::info!(
+            "Recovered session with {} nodes, {} workspaces and {} replayed operations",
+            state.scene.nodes().count(),
+            state.workspaces.len(),
+            replayed
+        );
+        Ok(Some(RecoveredSession { state, crashed: self.crashed, replayed }))
+    }
+
+    /// Append an operation to the log
+    ///
+    /// The line is handed to the OS before this returns, which is enough
+    /// to survive a compositor crash; power loss may cost the last few.
+    pub fn record(&self, op: &JournalOp) -> Result<(), WorkspaceError> {
+        if let JournalOp::NodeSet { node } = op {
+            if !node.metadata.persistence.is_saved_in(NodePersistence::Session) {
+                return Ok(());
+            }
+        }
+        let mut line = serde_json::to_string(op)?;
+        line.push('\n');
+
+        let mut log = self.lock_log();
+        if log.file.is_none() {
+            log.file = Some(OpenOptions::new().create(true).append(true).open(self.dir.join(LOG_FILE))?);
+        }
+        log.file.as_mut().expect("log opened above").write_all(line.as_bytes())?;
+        log.pending += 1;
+        Ok(())
+    }
+
+    /// Append the operations of a scene change
+    pub fn record_change(&self, change: &Change) -> Result<(), WorkspaceError> {
+        JournalOp::from_change(change).iter().try_for_each(|op| self.record(op))
+    }
+
+    /// Write a new snapshot and start an empty log
+    pub fn checkpoint(
+        &self,
+        scene: &Scene,
+        workspaces: &[Workspace],
+        active_workspace: Option<&str>,
+        camera: Option<CameraState>,
+    ) -> Result<(), WorkspaceError> {
+        let snapshot = SceneSnapshot::capture_for(scene, NodePersistence::Session);
+        let mut workspaces = workspaces.to_vec();
+        let unsaved: HashSet<SceneId> = scene.nodes()
+            .filter(|(_, node)| !node.metadata.persistence.is_saved_in(NodePersistence::Session))
+            .map(|(id, _)| *id)
+            .collect();
+        if !unsaved.is_empty() {
+            for workspace in &mut workspaces {
+                workspace.nodes.retain(|id| !unsaved.contains(id));
+                workspace.layout.node_positions.retain(|id, _| !unsaved.contains(id));
+            }
+        }
+        let file = SnapshotFile {
+            scene: serde_json::to_value(snapshot)?,
+            workspaces,
+            active_workspace: active_workspace.map(str::to_string),
+            camera,
+        };
+        let json = serde_json::to_vec(&file)?;
+
+        // Hold the log so no operation lands between the snapshot and the truncation
+        let mut log = self.lock_log();
+        let path = self.dir.join(SNAPSHOT_FILE);
+        let temporary = path.with_extension("json.tmp");
+        let mut snapshot = File::create(&temporary)?;
+        snapshot.write_all(&json)?;
+        snapshot.sync_all()?;
+        fs::rename(&temporary, &path)?;
+
+        log.file = None;
+        let log_path = self.dir.join(LOG_FILE);
+        if log_path.exists() {
+            fs::remove_file(&log_path)?;
+        }
+        log.pending = 0;
+        Ok(())
+    }
+
+    /// Mark the session as closed cleanly
+    ///
+    /// Call after a final checkpoint; the saved state is kept for the next
+    /// launch.
+    pub fn close(&self) -> Result<(), WorkspaceError> {
+        self.lock_log().file = None;
+        let marker = self.dir.join(RUNNING_MARKER);
+        if marker.exists() {
+            fs::remove_file(marker)?;
+        }
+        Ok(())
+    }
+
+    fn lock_log(&self) -> std::sync::MutexGuard<'_, JournalLog> {
+        self.log.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Workspaces by ID, for building a checkpoint from the manager's map
+pub(crate) fn workspace_list(workspaces: &HashMap<String, Workspace>) -> Vec<Workspace> {
+    let mut list: Vec<Workspace> = workspaces.values().cloned().collect();
+    list.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use horizonos_graph_engine::{NodeMetadata, NodeType, SceneNode};
+    use nalgebra::{Point3, Vector3};
+
+    fn node(id: SceneId) -> SceneNode {
+        SceneNode {
+            id,
+            position: Point3::new(id as f32, 0.0, 0.0),
+            velocity: Vector3::zeros(),
+            radius: 1.0,
+            color: [1.0, 1.0, 1.0, 1.0],
+            node_type: NodeType::Concept { title: format!("node-{}", id), content: String::new() },
+            metadata: NodeMetadata::default(),
+            visible: true,
+            selected: false,
+        }
+    }
+
+    #[test]
+    fn test_crash_recovery_replays_log_onto_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = Workspace::new("Work", "");
+
+        let journal = SessionJournal::open(dir.path()).unwrap();
+        assert!(!journal.crashed());
+        assert!(journal.recover().unwrap().is_none());
+
+        let mut scene = Scene::new();
+        scene.insert_node(node(1));
+        let mut stream = node(9);
+        stream.metadata.persistence = NodePersistence::Ephemeral;
+        scene.insert_node(stream.clone());
+        journal.checkpoint(&scene, std::slice::from_ref(&workspace), Some(&workspace.id), None).unwrap();
+        journal.record(&JournalOp::NodeSet { node: Box::new(node(2)) }).unwrap();
+        journal.record(&JournalOp::NodeMoved { id: 1, position: Point3::new(5.0, 0.0, 0.0) }).unwrap();
+        journal.record(&JournalOp::Camera { camera: horizonos_graph_engine::Camera::new().state() }).unwrap();
+        journal.record(&JournalOp::NodeSet { node: Box::new(stream) }).unwrap();
+        assert_eq!(journal.pending_ops(), 3);
+        drop(journal);
+
+        // A crash while appending leaves a torn line behind
+        let mut log = OpenOptions::new().append(true).open(dir.path().join(LOG_FILE)).unwrap();
+        log.write_all(b"{\"op\":\"node_rem").unwrap();
+
+        let journal = SessionJournal::open(dir.path()).unwrap();
+        assert!(journal.crashed());
+        let recovered = journal.recover().unwrap().unwrap();
+        assert!(recovered.crashed);
+        assert_eq!(recovered.replayed, 3);
+        assert_eq!(recovered.state.scene.nodes().count(), 2);
+        assert_eq!(recovered.state.scene.get_node(1).unwrap().position.x, 5.0);
+        assert_eq!(recovered.state.active_workspace.as_deref(), Some(workspace.id.as_str()));
+        assert!(recovered.state.camera.is_some());
+
+        journal.close().unwrap();
+        assert!(!SessionJournal::open(dir.path()).unwrap().crashed());
+    }
+}
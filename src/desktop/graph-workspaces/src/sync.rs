@@ -0,0 +1,226 @@
+//! Syncing workspaces with other devices
+//!
+//! A sync backend hands each workspace it receives to
+//! [`WorkspaceManager::merge_remote_workspace`](crate::WorkspaceManager::merge_remote_workspace).
+//! The manager remembers the last version both sides agreed on and takes the
+//! remote version only when the local one has not changed since. When both
+//! changed, the workspace goes to the conflict center instead of either side
+//! winning, and [`WorkspaceSync`] applies the user's resolution.
+//!
+//! `last_accessed` is left out of the comparison, since switching to a
+//! workspace is not an edit.
+
+use crate::session::JournalOp;
+use crate::{SessionJournal, Workspace, WorkspaceError, WorkspaceEvent};
+use chrono::{DateTime, Utc};
+use horizonos_graph_sync::{ConflictCenter, ItemVersion, Resolution, SyncConflict, SyncError, SyncProvider};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Name workspace conflicts are reported under
+pub const PROVIDER_NAME: &str = "workspaces";
+
+/// Origin of the local version in conflicts
+const LOCAL_ORIGIN: &str = "this device";
+
+/// What merging a remote workspace did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeOutcome {
+    /// The workspace was new here
+    Added,
+    /// The local workspace was unchanged and took the remote version
+    Updated,
+    /// Both versions are the same
+    Unchanged,
+    /// Only the local workspace changed; the backend should upload it
+    LocalNewer,
+    /// Both changed; the conflict waits in the conflict center
+    Conflict(Uuid),
+}
+
+/// Sync state shared by the workspace manager and its conflict provider
+pub struct WorkspaceSync {
+    workspaces: Arc<RwLock<HashMap<String, Workspace>>>,
+    /// Last version of each workspace known to be on both sides
+    bases: RwLock<HashMap<String, Value>>,
+    event_sender: broadcast::Sender<WorkspaceEvent>,
+    session: Option<Arc<SessionJournal>>,
+    center: Arc<ConflictCenter>,
+}
+
+impl WorkspaceSync {
+    pub(crate) fn new(
+        workspaces: Arc<RwLock<HashMap<String, Workspace>>>,
+        event_sender: broadcast::Sender<WorkspaceEvent>,
+        session: Option<Arc<SessionJournal>>,
+        center: Arc<ConflictCenter>,
+    ) -> Self {
+        Self {
+            workspaces,
+            bases: RwLock::new(HashMap::new()),
+            event_sender,
+            session,
+            center,
+        }
+    }
+
+    pub fn center(&self) -> &Arc<ConflictCenter> {
+        &self.center
+    }
+
+    pub(crate) fn merge(&self, remote: Workspace, origin: &str) -> Result<MergeOutcome, WorkspaceError> {
+        let remote_value = sync_value(&remote)?;
+        let mut workspaces = self.workspaces.write().unwrap();
+        let mut bases = self.bases.write().unwrap();
+
+        let Some(local) = workspaces.get_mut(&remote.id) else {
+            bases.insert(remote.id.clone(), remote_value);
+            self.store(&mut workspaces, remote, true);
+            return Ok(MergeOutcome::Added);
+        };
+
+        let local_value = sync_value(local)?;
+        if local_value == remote_value {
+            bases.insert(remote.id.clone(), remote_value);
+            return Ok(MergeOutcome::Unchanged);
+        }
+        match bases.get(&remote.id) {
+            Some(base) if *base == local_value => {
+                bases.insert(remote.id.clone(), remote_value);
+                self.store(&mut workspaces, remote, false);
+                Ok(MergeOutcome::Updated)
+            }
+            Some(base) if *base == remote_value => Ok(MergeOutcome::LocalNewer),
+            _ => {
+                let conflict = SyncConflict::new(
+                    PROVIDER_NAME,
+                    remote.id.clone(),
+                    local.name.clone(),
+                    ItemVersion::new(local_value, local.last_accessed, LOCAL_ORIGIN),
+                    ItemVersion::new(remote_value, remote.last_accessed, origin),
+                );
+                Ok(MergeOutcome::Conflict(self.center.report(conflict)))
+            }
+        }
+    }
+
+    pub(crate) fn mark_synced(&self, workspace: &Workspace) -> Result<(), WorkspaceError> {
+        self.bases.write().unwrap().insert(workspace.id.clone(), sync_value(workspace)?);
+        Ok(())
+    }
+
+    pub(crate) fn forget(&self, workspace_id: &str) {
+        self.bases.write().unwrap().remove(workspace_id);
+        self.center.dismiss(PROVIDER_NAME, workspace_id);
+    }
+
+    fn store(&self, workspaces: &mut HashMap<String, Workspace>, workspace: Workspace, created: bool) {
+        let workspace_id = workspace.id.clone();
+        if let Some(journal) = &self.session {
+            if let Err(e) = journal.record(&JournalOp::WorkspaceSet { workspace: Box::new(workspace.clone()) }) {
+                log::warn!("Failed to journal workspace change: {}", e);
+            }
+        }
+        workspaces.insert(workspace_id.clone(), workspace);
+
+        let event = if created {
+            WorkspaceEvent::Created { workspace_id }
+        } else {
+            WorkspaceEvent::Modified { workspace_id }
+        };
+        self.event_sender.send(event).ok();
+    }
+}
+
+impl SyncProvider for WorkspaceSync {
+    fn name(&self) -> &str {
+        PROVIDER_NAME
+    }
+
+    fn resolve(&self, conflict: &SyncConflict, resolution: &Resolution) -> Result<(), SyncError> {
+        let remote = from_sync_value(conflict.remote.data.clone(), conflict.remote.modified_at)
+            .map_err(|e| SyncError::Apply(e.to_string()))?;
+        let mut workspaces = self.workspaces.write().unwrap();
+        let mut bases = self.bases.write().unwrap();
+
+        match resolution {
+            // The local version now counts as ahead and is uploaded next sync
+            Resolution::KeepLocal => {}
+            Resolution::KeepRemote => {
+                let created = !workspaces.contains_key(&remote.id);
+                self.store(&mut workspaces, remote, created);
+            }
+            Resolution::KeepBoth => {
+                let mut copy = remote;
+                copy.id = Uuid::new_v4().to_string();
+                copy.name = format!("{} ({})", copy.name, conflict.remote.origin);
+                self.store(&mut workspaces, copy, true);
+            }
+            Resolution::Merged(data) => {
+                let mut merged = from_sync_value(data.clone(), Utc::now())
+                    .map_err(|e| SyncError::InvalidMerge(e.to_string()))?;
+                merged.id = conflict.item_id.clone();
+                let created = !workspaces.contains_key(&merged.id);
+                self.store(&mut workspaces, merged, created);
+            }
+        }
+        bases.insert(conflict.item_id.clone(), conflict.remote.data.clone());
+        Ok(())
+    }
+}
+
+/// Version of a workspace compared between devices
+fn sync_value(workspace: &Workspace) -> Result<Value, WorkspaceError> {
+    let mut value = serde_json::to_value(workspace)?;
+    if let Value::Object(fields) = &mut value {
+        fields.remove("last_accessed");
+    }
+    Ok(value)
+}
+
+fn from_sync_value(mut value: Value, modified_at: DateTime<Utc>) -> Result<Workspace, serde_json::Error> {
+    if let Value::Object(fields) = &mut value {
+        fields.insert("last_accessed".to_string(), serde_json::to_value(modified_at)?);
+    }
+    serde_json::from_value(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WorkspaceManager;
+
+    #[test]
+    fn test_concurrent_edits_go_to_the_conflict_center() {
+        let center = Arc::new(ConflictCenter::new());
+        let manager = WorkspaceManager::new().with_conflict_center(center.clone());
+        let id = manager.create_workspace("Research", "").unwrap();
+        let synced = manager.get_workspace(&id).unwrap();
+        manager.mark_synced(&id).unwrap();
+
+        // Remote-only edits fast-forward
+        let mut remote = synced.clone();
+        remote.description = "Papers".to_string();
+        assert_eq!(manager.merge_remote_workspace(remote.clone(), "desktop").unwrap(), MergeOutcome::Updated);
+        assert_eq!(manager.get_workspace(&id).unwrap().description, "Papers");
+
+        // Both sides edit: nobody wins until the user decides
+        manager.set_workspace_layout_algorithm(&id, Some("grid".to_string())).unwrap();
+        remote.name = "Reading".to_string();
+        let MergeOutcome::Conflict(conflict_id) = manager.merge_remote_workspace(remote.clone(), "laptop").unwrap() else {
+            panic!("expected a conflict");
+        };
+        assert_eq!(manager.get_workspace(&id).unwrap().name, "Research");
+        let diff = center.diff(conflict_id).unwrap();
+        assert!(diff.touches("name") && diff.touches("layout.algorithm"));
+
+        center.resolve(conflict_id, Resolution::KeepBoth).unwrap();
+        assert_eq!(manager.list_workspaces().len(), 2);
+        assert!(manager.list_workspaces().iter().any(|w| w.name == "Reading (laptop)"));
+        assert_eq!(manager.merge_remote_workspace(remote, "laptop").unwrap(), MergeOutcome::LocalNewer);
+        assert_eq!(center.count(), 0);
+    }
+}
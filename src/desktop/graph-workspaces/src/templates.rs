@@ -1,6 +1,7 @@
 //! Workspace templates for quick setup
 
 use crate::{Workspace, WorkspaceSettings, layout::{WorkspaceLayout, LayoutType}};
+use horizonos_graph_engine::{EdgeFilter, EdgePreset};
 use serde::{Deserialize, Serialize};
 
 /// Workspace template
@@ -52,9 +53,11 @@ impl WorkspaceTemplate {
                 auto_save: true,
                 auto_arrange: true,
                 node_spacing: 120.0,
+                edge_filter: EdgePreset::named("structure").map(|preset| preset.filter).unwrap_or_default(),
             },
             layout: WorkspaceLayout {
                 layout_type: LayoutType::Hierarchical,
+                algorithm: Some("hierarchical".to_string()),
                 ..Default::default()
             },
             metadata: serde_json::json!({
@@ -77,6 +80,7 @@ impl WorkspaceTemplate {
                 auto_save: true,
                 auto_arrange: false,
                 node_spacing: 150.0,
+                edge_filter: EdgeFilter::default(),
             },
             layout: WorkspaceLayout {
                 layout_type: LayoutType::ForceDirected,
@@ -102,6 +106,7 @@ impl WorkspaceTemplate {
                 auto_save: true,
                 auto_arrange: false,
                 node_spacing: 100.0,
+                edge_filter: EdgeFilter::default(),
             },
             layout: WorkspaceLayout {
                 layout_type: LayoutType::Manual,
@@ -127,9 +132,11 @@ impl WorkspaceTemplate {
                 auto_save: true,
                 auto_arrange: true,
                 node_spacing: 80.0,
+                edge_filter: EdgePreset::named("social").map(|preset| preset.filter).unwrap_or_default(),
             },
             layout: WorkspaceLayout {
                 layout_type: LayoutType::Grid,
+                algorithm: Some("grid".to_string()),
                 ..Default::default()
             },
             metadata: serde_json::json!({
@@ -152,9 +159,11 @@ impl WorkspaceTemplate {
                 auto_save: true,
                 auto_arrange: false,
                 node_spacing: 100.0,
+                edge_filter: EdgeFilter::default(),
             },
             layout: WorkspaceLayout {
                 layout_type: LayoutType::Timeline,
+                algorithm: Some("temporal".to_string()),
                 ..Default::default()
             },
             metadata: serde_json::json!({
@@ -0,0 +1,157 @@
+//! In-memory workspace service for unit tests
+//!
+//! Enabled with the `test-util` feature. Behaves like `WorkspaceManager` for
+//! the `WorkspaceService` operations but never touches disk or the network.
+
+use crate::{
+    Workspace, WorkspaceError, WorkspaceEvent, WorkspaceIdentity, WorkspaceInfo, WorkspaceService,
+};
+use std::sync::RwLock;
+use tokio::sync::broadcast;
+
+/// In-memory `WorkspaceService`
+pub struct InMemoryWorkspaceService {
+    /// Workspaces in creation order
+    workspaces: RwLock<Vec<Workspace>>,
+    /// Currently active workspace
+    active_workspace: RwLock<Option<String>>,
+    /// Workspace event broadcaster
+    event_sender: broadcast::Sender<WorkspaceEvent>,
+}
+
+impl InMemoryWorkspaceService {
+    /// Create an empty service
+    pub fn new() -> Self {
+        let (event_sender, _) = broadcast::channel(100);
+        Self {
+            workspaces: RwLock::new(Vec::new()),
+            active_workspace: RwLock::new(None),
+            event_sender,
+        }
+    }
+
+    /// Create a service holding one active workspace, like an initialized manager
+    pub fn with_default_workspace() -> Self {
+        let service = Self::new();
+        service.insert(Workspace::new("default", "Default Workspace"));
+        service
+    }
+
+    /// Add a prepared workspace, activating it if none is active
+    pub fn insert(&self, workspace: Workspace) -> String {
+        let workspace_id = workspace.id.clone();
+        self.workspaces.write().unwrap().push(workspace);
+
+        let mut active = self.active_workspace.write().unwrap();
+        if active.is_none() {
+            *active = Some(workspace_id.clone());
+        }
+        workspace_id
+    }
+
+    /// Modify a workspace in place, e.g. to add nodes
+    pub fn update<F>(&self, workspace_id: &str, f: F) -> Result<(), WorkspaceError>
+    where
+        F: FnOnce(&mut Workspace),
+    {
+        let mut workspaces = self.workspaces.write().unwrap();
+        let workspace = workspaces.iter_mut()
+            .find(|w| w.id == workspace_id)
+            .ok_or_else(|| WorkspaceError::NotFound(workspace_id.to_string()))?;
+        f(workspace);
+
+        self.event_sender.send(WorkspaceEvent::Modified {
+            workspace_id: workspace_id.to_string(),
+        }).ok();
+        Ok(())
+    }
+}
+
+impl Default for InMemoryWorkspaceService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorkspaceService for InMemoryWorkspaceService {
+    fn create_workspace(&self, name: &str, description: &str) -> Result<String, WorkspaceError> {
+        let workspace = Workspace::new(name, description);
+        let workspace_id = workspace.id.clone();
+        self.workspaces.write().unwrap().push(workspace);
+
+        self.event_sender.send(WorkspaceEvent::Created {
+            workspace_id: workspace_id.clone(),
+        }).ok();
+        Ok(workspace_id)
+    }
+
+    fn switch_workspace(&self, workspace_id: &str) -> Result<(), WorkspaceError> {
+        if self.get_workspace(workspace_id).is_none() {
+            return Err(WorkspaceError::NotFound(workspace_id.to_string()));
+        }
+
+        let previous = self.active_workspace.write().unwrap().replace(workspace_id.to_string());
+        self.event_sender.send(WorkspaceEvent::Switched {
+            from: previous,
+            to: workspace_id.to_string(),
+        }).ok();
+        Ok(())
+    }
+
+    fn get_active_workspace(&self) -> Option<Workspace> {
+        let active = self.active_workspace.read().unwrap().clone()?;
+        self.get_workspace(&active)
+    }
+
+    fn get_workspace(&self, workspace_id: &str) -> Option<Workspace> {
+        self.workspaces.read().unwrap().iter().find(|w| w.id == workspace_id).cloned()
+    }
+
+    fn list_workspaces(&self) -> Vec<WorkspaceInfo> {
+        let active = self.active_workspace.read().unwrap().clone();
+        self.workspaces.read().unwrap()
+            .iter()
+            .map(|w| WorkspaceInfo {
+                id: w.id.clone(),
+                name: w.name.clone(),
+                description: w.description.clone(),
+                created_at: w.created_at,
+                last_accessed: w.last_accessed,
+                node_count: w.nodes.len(),
+                is_active: active.as_deref() == Some(w.id.as_str()),
+            })
+            .collect()
+    }
+
+    fn delete_workspace(&self, workspace_id: &str) -> Result<(), WorkspaceError> {
+        let mut workspaces = self.workspaces.write().unwrap();
+        let index = workspaces.iter()
+            .position(|w| w.id == workspace_id)
+            .ok_or_else(|| WorkspaceError::NotFound(workspace_id.to_string()))?;
+
+        if workspaces.len() == 1 {
+            return Err(WorkspaceError::CannotDeleteLast);
+        }
+
+        workspaces.remove(index);
+
+        let mut active = self.active_workspace.write().unwrap();
+        if active.as_deref() == Some(workspace_id) {
+            *active = workspaces.first().map(|w| w.id.clone());
+        }
+
+        self.event_sender.send(WorkspaceEvent::Deleted {
+            workspace_id: workspace_id.to_string(),
+        }).ok();
+        Ok(())
+    }
+
+    fn set_workspace_identity(&self, workspace_id: &str, identity: WorkspaceIdentity) -> Result<(), WorkspaceError> {
+        self.update(workspace_id, |workspace| workspace.identity = identity)
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<WorkspaceEvent> {
+        self.event_sender.subscribe()
+    }
+}
+
@@ -31,7 +31,7 @@ mod collaboration_manager_tests {
         assert!(!workspace_id.is_empty());
         
         // Verify workspace was created
-        let shared_workspace = manager.get_shared_workspace(&workspace_id);
+        let shared_workspace = manager.get_shared_workspace(&workspace_id).await;
         assert!(shared_workspace.is_some());
         
         let shared_workspace = shared_workspace.unwrap();
@@ -125,7 +125,7 @@ mod collaboration_manager_tests {
         assert!(result.is_ok());
         
         // Verify user is no longer in workspace
-        let shared_workspace = manager.get_shared_workspace(&workspace_id).unwrap();
+        let shared_workspace = manager.get_shared_workspace(&workspace_id).await.unwrap();
         assert!(!shared_workspace.participants.contains("user2"));
     }
 }
@@ -153,7 +153,7 @@ mod workspace_change_tests {
         assert!(result.is_ok());
         
         // Verify node was added
-        let shared_workspace = manager.get_shared_workspace(&workspace_id).unwrap();
+        let shared_workspace = manager.get_shared_workspace(&workspace_id).await.unwrap();
         assert!(shared_workspace.workspace.nodes.contains(&node_id));
     }
     
@@ -203,7 +203,7 @@ mod workspace_change_tests {
         manager.apply_workspace_change(&workspace_id, change2, "user1").await.unwrap();
         
         // Verify change history
-        let shared_workspace = manager.get_shared_workspace(&workspace_id).unwrap();
+        let shared_workspace = manager.get_shared_workspace(&workspace_id).await.unwrap();
         assert_eq!(shared_workspace.changes.len(), 2);
         assert_eq!(shared_workspace.changes[0].user_id, "user1");
         assert_eq!(shared_workspace.changes[1].user_id, "user1");
@@ -281,7 +281,7 @@ mod user_management_tests {
             preferences: UserPreferences::default(),
         };
         
-        let result = manager.register_user(user);
+        let result = manager.register_user(user).await;
         assert!(result.is_ok());
     }
 }
@@ -343,6 +343,7 @@ mod collaboration_session_tests {
             started_at: old_time,
             last_activity: old_time,
             cursor_position: None,
+            chat: None,
             settings: SessionSettings::default(),
         };
         
@@ -446,11 +447,11 @@ mod integration_tests {
             preferences: UserPreferences::default(),
         };
         
-        manager.register_user(user1).unwrap();
-        manager.register_user(user2).unwrap();
+        manager.register_user(user1).await.unwrap();
+        manager.register_user(user2).await.unwrap();
         
         // User2 joins workspace
-        let _session = manager.join_workspace(&workspace_id, "user2", None).await.unwrap();
+        let session = manager.join_workspace(&workspace_id, "user2", None).await.unwrap();
         assert_eq!(session.user_id, "user2");
         
         // User1 makes changes
@@ -466,7 +467,7 @@ mod integration_tests {
         assert!(result2.is_ok());
         
         // Verify workspace state
-        let shared_workspace = manager.get_shared_workspace(&workspace_id).unwrap();
+        let shared_workspace = manager.get_shared_workspace(&workspace_id).await.unwrap();
         assert_eq!(shared_workspace.workspace.nodes.len(), 2);
         assert!(shared_workspace.workspace.nodes.contains(&node_id));
         assert!(shared_workspace.workspace.nodes.contains(&node_id2));
@@ -478,7 +479,7 @@ mod integration_tests {
         let result = manager.leave_workspace(&workspace_id, "user2").await;
         assert!(result.is_ok());
         
-        let shared_workspace = manager.get_shared_workspace(&workspace_id).unwrap();
+        let shared_workspace = manager.get_shared_workspace(&workspace_id).await.unwrap();
         assert!(!shared_workspace.participants.contains("user2"));
     }
     
@@ -532,7 +533,7 @@ mod integration_tests {
         }
         
         // Verify final state
-        let shared_workspace = manager.get_shared_workspace(&workspace_id).unwrap();
+        let shared_workspace = manager.get_shared_workspace(&workspace_id).await.unwrap();
         assert!(shared_workspace.workspace.nodes.len() > 0);
         assert!(shared_workspace.changes.len() > 0);
         
@@ -627,7 +628,7 @@ mod performance_tests {
         assert!(duration < Duration::from_secs(10)); // Should be reasonably fast
         
         // Verify final state
-        let shared_workspace = manager.get_shared_workspace(&workspace_id).unwrap();
+        let shared_workspace = manager.get_shared_workspace(&workspace_id).await.unwrap();
         assert_eq!(shared_workspace.workspace.nodes.len(), 1000);
         assert_eq!(shared_workspace.changes.len(), 1000);
     }
@@ -654,7 +655,7 @@ mod performance_tests {
                 preferences: UserPreferences::default(),
             };
             
-            manager.register_user(user).unwrap();
+            manager.register_user(user).await.unwrap();
         }
         let registration_duration = start.elapsed();
         
@@ -687,7 +688,7 @@ mod performance_tests {
         assert!(join_duration < Duration::from_secs(10));
         
         // Verify final state
-        let shared_workspace = manager.get_shared_workspace(&workspace_id).unwrap();
+        let shared_workspace = manager.get_shared_workspace(&workspace_id).await.unwrap();
         assert_eq!(shared_workspace.participants.len(), 51); // 50 + owner
     }
 }
\ No newline at end of file